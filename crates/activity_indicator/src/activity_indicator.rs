@@ -7,11 +7,13 @@ use gpui::{
     ParentElement as _, Render, SharedString, StatefulInteractiveElement, Styled, View,
     ViewContext, VisualContext as _,
 };
-use language::{LanguageRegistry, LanguageServerBinaryStatus, LanguageServerName};
+use language::{
+    LanguageRegistry, LanguageServerBinaryStatus, LanguageServerId, LanguageServerName,
+};
 use project::{LanguageServerProgress, Project};
 use smallvec::SmallVec;
 use std::{cmp::Reverse, fmt::Write, sync::Arc};
-use ui::prelude::*;
+use ui::{popover_menu, prelude::*, ButtonLike, ContextMenu};
 use workspace::{item::ItemHandle, StatusItemView, Workspace};
 
 actions!(activity_indicator, [ShowErrorMessage]);
@@ -35,6 +37,7 @@ struct LspStatus {
 }
 
 struct PendingWork<'a> {
+    language_server_id: LanguageServerId,
     language_server_name: &'a str,
     progress_token: &'a str,
     progress: &'a LanguageServerProgress,
@@ -45,6 +48,8 @@ struct Content {
     icon: Option<&'static str>,
     message: String,
     on_click: Option<Arc<dyn Fn(&mut ActivityIndicator, &mut ViewContext<ActivityIndicator>)>>,
+    /// Whether the message describes pending language server work, which can be listed in a menu.
+    is_language_server_work: bool,
 }
 
 impl ActivityIndicator {
@@ -149,9 +154,9 @@ impl ActivityIndicator {
     ) -> impl Iterator<Item = PendingWork<'a>> {
         self.project
             .read(cx)
-            .language_server_statuses()
+            .language_server_statuses_by_id()
             .rev()
-            .filter_map(|status| {
+            .filter_map(|(language_server_id, status)| {
                 if status.pending_work.is_empty() {
                     None
                 } else {
@@ -159,6 +164,7 @@ impl ActivityIndicator {
                         .pending_work
                         .iter()
                         .map(|(token, progress)| PendingWork {
+                            language_server_id,
                             language_server_name: status.name.as_str(),
                             progress_token: token.as_str(),
                             progress,
@@ -174,24 +180,8 @@ impl ActivityIndicator {
     fn content_to_render(&mut self, cx: &mut ViewContext<Self>) -> Content {
         // Show any language server has pending activity.
         let mut pending_work = self.pending_language_server_work(cx);
-        if let Some(PendingWork {
-            language_server_name,
-            progress_token,
-            progress,
-        }) = pending_work.next()
-        {
-            let mut message = language_server_name.to_string();
-
-            message.push_str(": ");
-            if let Some(progress_message) = progress.message.as_ref() {
-                message.push_str(progress_message);
-            } else {
-                message.push_str(progress_token);
-            }
-
-            if let Some(percentage) = progress.percentage {
-                write!(&mut message, " ({}%)", percentage).unwrap();
-            }
+        if let Some(work) = pending_work.next() {
+            let mut message = work.message();
 
             let additional_work_count = pending_work.count();
             if additional_work_count > 0 {
@@ -202,6 +192,7 @@ impl ActivityIndicator {
                 icon: None,
                 message,
                 on_click: None,
+                is_language_server_work: true,
             };
         }
 
@@ -225,6 +216,7 @@ impl ActivityIndicator {
                 icon: Some(DOWNLOAD_ICON),
                 message: format!("Downloading {}...", downloading.join(", "),),
                 on_click: None,
+                is_language_server_work: false,
             };
         }
 
@@ -236,6 +228,7 @@ impl ActivityIndicator {
                     checking_for_update.join(", "),
                 ),
                 on_click: None,
+                is_language_server_work: false,
             };
         }
 
@@ -249,6 +242,7 @@ impl ActivityIndicator {
                 on_click: Some(Arc::new(|this, cx| {
                     this.show_error_message(&Default::default(), cx)
                 })),
+                is_language_server_work: false,
             };
         }
 
@@ -260,6 +254,7 @@ impl ActivityIndicator {
                 on_click: Some(Arc::new(|_, cx| {
                     cx.dispatch_action(Box::new(workspace::OpenLog));
                 })),
+                is_language_server_work: false,
             };
         }
 
@@ -270,16 +265,19 @@ impl ActivityIndicator {
                     icon: Some(DOWNLOAD_ICON),
                     message: "Checking for Zed updates…".to_string(),
                     on_click: None,
+                    is_language_server_work: false,
                 },
                 AutoUpdateStatus::Downloading => Content {
                     icon: Some(DOWNLOAD_ICON),
                     message: "Downloading Zed update…".to_string(),
                     on_click: None,
+                    is_language_server_work: false,
                 },
                 AutoUpdateStatus::Installing => Content {
                     icon: Some(DOWNLOAD_ICON),
                     message: "Installing Zed update…".to_string(),
                     on_click: None,
+                    is_language_server_work: false,
                 },
                 AutoUpdateStatus::Updated { binary_path } => Content {
                     icon: None,
@@ -290,6 +288,7 @@ impl ActivityIndicator {
                         };
                         move |_, cx| workspace::restart(&restart, cx)
                     })),
+                    is_language_server_work: false,
                 },
                 AutoUpdateStatus::Errored => Content {
                     icon: Some(WARNING_ICON),
@@ -297,6 +296,7 @@ impl ActivityIndicator {
                     on_click: Some(Arc::new(|this, cx| {
                         this.dismiss_error_message(&Default::default(), cx)
                     })),
+                    is_language_server_work: false,
                 },
                 AutoUpdateStatus::Idle => Default::default(),
             };
@@ -310,12 +310,96 @@ impl ActivityIndicator {
                     icon: Some(DOWNLOAD_ICON),
                     message: format!("Updating {extension_id} extension…"),
                     on_click: None,
+                    is_language_server_work: false,
                 };
             }
         }
 
         Default::default()
     }
+
+    /// Builds a menu listing the pending work of the language servers, where work that the
+    /// language server allows to be cancelled can be clicked to cancel it.
+    fn build_language_server_work_menu(&self, cx: &mut WindowContext) -> View<ContextMenu> {
+        let project = self.project.clone();
+        let pending_work = self
+            .pending_language_server_work(cx)
+            .map(|work| {
+                (
+                    work.language_server_id,
+                    work.progress_token.to_string(),
+                    work.message(),
+                    work.progress.is_cancellable,
+                )
+            })
+            .collect::<Vec<_>>();
+        ContextMenu::build(cx, move |mut menu, _| {
+            menu = menu.header("Language Server Work");
+            for (language_server_id, token, message, is_cancellable) in pending_work {
+                menu = menu.custom_entry(
+                    move |_| {
+                        h_flex()
+                            .w_full()
+                            .justify_between()
+                            .gap_4()
+                            .child(Label::new(message.clone()))
+                            .when(is_cancellable, |this| {
+                                this.child(
+                                    h_flex()
+                                        .gap_1()
+                                        .child(
+                                            Icon::new(IconName::XCircle)
+                                                .size(IconSize::Small)
+                                                .color(Color::Muted),
+                                        )
+                                        .child(Label::new("Cancel").color(Color::Muted)),
+                                )
+                            })
+                            .into_any_element()
+                    },
+                    {
+                        let project = project.clone();
+                        move |cx| {
+                            if is_cancellable {
+                                project.update(cx, |project, cx| {
+                                    project.cancel_language_server_work(
+                                        language_server_id,
+                                        token.clone(),
+                                        cx,
+                                    )
+                                });
+                            }
+                        }
+                    },
+                );
+            }
+            menu
+        })
+    }
+}
+
+impl PendingWork<'_> {
+    fn message(&self) -> String {
+        let mut message = self.language_server_name.to_string();
+
+        message.push_str(": ");
+        if let Some(title) = self.progress.title.as_ref() {
+            message.push_str(title);
+            if self.progress.message.is_some() {
+                message.push_str(" - ");
+            }
+        }
+        if let Some(progress_message) = self.progress.message.as_ref() {
+            message.push_str(progress_message);
+        } else if self.progress.title.is_none() {
+            message.push_str(self.progress_token);
+        }
+
+        if let Some(percentage) = self.progress.percentage {
+            write!(&mut message, " ({}%)", percentage).unwrap();
+        }
+        message
+    }
 }
 
 impl EventEmitter<Event> for ActivityIndicator {}
@@ -337,9 +421,23 @@ impl Render for ActivityIndicator {
                 }))
         }
 
+        let label = Label::new(SharedString::from(content.message)).size(LabelSize::Small);
+        if content.is_language_server_work {
+            let this = cx.view().downgrade();
+            return result.child(
+                popover_menu("language-server-work")
+                    .menu(move |cx| {
+                        this.update(cx, |this, cx| this.build_language_server_work_menu(cx))
+                            .ok()
+                    })
+                    .anchor(gpui::AnchorCorner::BottomLeft)
+                    .trigger(ButtonLike::new("language-server-work-trigger").child(label)),
+            );
+        }
+
         result
             .children(content.icon.map(|icon| svg().path(icon)))
-            .child(Label::new(SharedString::from(content.message)).size(LabelSize::Small))
+            .child(label)
     }
 }
 
@@ -0,0 +1,352 @@
+use crate::persistence::BOOKMARKS_DB;
+use gpui::{AppContext, Model, ModelContext, SharedString, Task, WeakModel};
+use language::{Buffer, Point, ToPoint};
+use std::{
+    cmp::Ordering,
+    mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use util::ResultExt;
+use workspace::WorkspaceId;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct BookmarkId(usize);
+
+impl BookmarkId {
+    pub fn to_usize(self) -> usize {
+        self.0
+    }
+}
+
+/// A bookmarked line in a file of the workspace.
+#[derive(Clone)]
+pub struct Bookmark {
+    pub id: BookmarkId,
+    pub abs_path: Arc<Path>,
+    pub label: Option<SharedString>,
+    /// The row of the bookmark when its buffer was last seen, or when it was restored.
+    row: u32,
+    /// Where the bookmark is anchored, once its buffer has been opened.
+    anchor: Option<(WeakModel<Buffer>, language::Anchor)>,
+}
+
+impl Bookmark {
+    pub fn row(&self, cx: &AppContext) -> u32 {
+        self.anchor
+            .as_ref()
+            .and_then(|(buffer, anchor)| {
+                let buffer = buffer.upgrade()?;
+                Some(anchor.to_point(&buffer.read(cx).snapshot()).row)
+            })
+            .unwrap_or(self.row)
+    }
+
+    pub fn buffer(&self) -> Option<Model<Buffer>> {
+        self.anchor.as_ref()?.0.upgrade()
+    }
+}
+
+pub enum Direction {
+    Prev,
+    Next,
+}
+
+/// The bookmarks of a workspace, kept in a single list ordered by their creation.
+pub struct BookmarkStore {
+    workspace_id: Option<WorkspaceId>,
+    bookmarks: Vec<Bookmark>,
+    next_bookmark_id: usize,
+    pending_serialization: Task<Option<()>>,
+}
+
+impl BookmarkStore {
+    pub fn new(
+        workspace_id: Option<WorkspaceId>,
+        serialized_bookmarks: Vec<(PathBuf, u32, Option<String>)>,
+    ) -> Self {
+        let mut this = Self {
+            workspace_id,
+            bookmarks: Vec::new(),
+            next_bookmark_id: 0,
+            pending_serialization: Task::ready(None),
+        };
+        for (abs_path, row, label) in serialized_bookmarks {
+            let id = this.next_id();
+            this.bookmarks.push(Bookmark {
+                id,
+                abs_path: abs_path.into(),
+                label: label.map(SharedString::from),
+                row,
+                anchor: None,
+            });
+        }
+        this
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bookmarks.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.bookmarks.len()
+    }
+
+    pub fn bookmark(&self, id: BookmarkId) -> Option<&Bookmark> {
+        self.bookmarks.iter().find(|bookmark| bookmark.id == id)
+    }
+
+    /// Returns all the bookmarks, ordered by path and row.
+    pub fn sorted_bookmarks(&self, cx: &AppContext) -> Vec<(&Bookmark, u32)> {
+        let mut bookmarks = self
+            .bookmarks
+            .iter()
+            .map(|bookmark| (bookmark, bookmark.row(cx)))
+            .collect::<Vec<_>>();
+        bookmarks
+            .sort_by(|(a, a_row), (b, b_row)| a.abs_path.cmp(&b.abs_path).then(a_row.cmp(b_row)));
+        bookmarks
+    }
+
+    /// Adds a bookmark on each of the given rows of the buffer, or removes the bookmarks
+    /// already on them. The label is only used for the added bookmarks.
+    pub fn toggle(
+        &mut self,
+        buffer: &Model<Buffer>,
+        abs_path: Arc<Path>,
+        rows: impl IntoIterator<Item = u32>,
+        label: Option<SharedString>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.resolve(buffer, &abs_path, cx);
+        for row in rows {
+            let existing = self
+                .bookmarks
+                .iter()
+                .position(|bookmark| bookmark.abs_path == abs_path && bookmark.row(cx) == row);
+            if let Some(ix) = existing {
+                self.bookmarks.remove(ix);
+            } else {
+                let id = self.next_id();
+                let anchor = buffer.read(cx).anchor_before(Point::new(row, 0));
+                self.bookmarks.push(Bookmark {
+                    id,
+                    abs_path: abs_path.clone(),
+                    label: label.clone(),
+                    row,
+                    anchor: Some((buffer.downgrade(), anchor)),
+                });
+            }
+        }
+        self.bookmarks_changed(cx);
+    }
+
+    /// Anchors the bookmarks of the given file to its buffer, so they follow the edits made to it.
+    pub fn resolve(
+        &mut self,
+        buffer: &Model<Buffer>,
+        abs_path: &Path,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let snapshot = buffer.read(cx).snapshot();
+        let max_point = snapshot.max_point();
+        let mut changed = false;
+        for bookmark in &mut self.bookmarks {
+            if bookmark.abs_path.as_ref() != abs_path || bookmark.buffer().as_ref() == Some(buffer)
+            {
+                continue;
+            }
+            bookmark.row = bookmark.row(cx).min(max_point.row);
+            let anchor = snapshot.anchor_before(Point::new(bookmark.row, 0));
+            bookmark.anchor = Some((buffer.downgrade(), anchor));
+            changed = true;
+        }
+        if changed {
+            cx.notify();
+        }
+    }
+
+    /// Returns the bookmark following (or preceding) the given position, wrapping around the
+    /// start and the end of the list.
+    pub fn adjacent_bookmark(
+        &self,
+        abs_path: Option<&Path>,
+        row: u32,
+        direction: Direction,
+        cx: &AppContext,
+    ) -> Option<BookmarkId> {
+        let bookmarks = self.sorted_bookmarks(cx);
+        let position_cmp = |bookmark: &Bookmark, bookmark_row: u32| match abs_path {
+            Some(abs_path) => bookmark
+                .abs_path
+                .as_ref()
+                .cmp(abs_path)
+                .then(bookmark_row.cmp(&row)),
+            None => Ordering::Greater,
+        };
+        let bookmark = match direction {
+            Direction::Next => bookmarks
+                .iter()
+                .find(|(bookmark, row)| position_cmp(bookmark, *row).is_gt())
+                .or_else(|| bookmarks.first()),
+            Direction::Prev => bookmarks
+                .iter()
+                .rev()
+                .find(|(bookmark, row)| position_cmp(bookmark, *row).is_lt())
+                .or_else(|| bookmarks.last()),
+        };
+        bookmark.map(|(bookmark, _)| bookmark.id)
+    }
+
+    pub fn set_label(
+        &mut self,
+        id: BookmarkId,
+        label: Option<SharedString>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if let Some(bookmark) = self.bookmarks.iter_mut().find(|bookmark| bookmark.id == id) {
+            bookmark.label = label.filter(|label| !label.trim().is_empty());
+            self.bookmarks_changed(cx);
+        }
+    }
+
+    pub fn remove(&mut self, id: BookmarkId, cx: &mut ModelContext<Self>) {
+        self.bookmarks.retain(|bookmark| bookmark.id != id);
+        self.bookmarks_changed(cx);
+    }
+
+    pub fn clear(&mut self, cx: &mut ModelContext<Self>) {
+        self.bookmarks.clear();
+        self.bookmarks_changed(cx);
+    }
+
+    /// Records the current rows of the bookmarks, and persists them for the workspace.
+    pub fn serialize(&mut self, cx: &mut ModelContext<Self>) {
+        for bookmark in &mut self.bookmarks {
+            bookmark.row = bookmark.row(cx);
+        }
+        self.merge_bookmarks_on_same_row();
+        let Some(workspace_id) = self.workspace_id else {
+            return;
+        };
+        let bookmarks = self
+            .bookmarks
+            .iter()
+            .map(|bookmark| {
+                (
+                    bookmark.abs_path.to_path_buf(),
+                    bookmark.row,
+                    bookmark.label.as_ref().map(|label| label.to_string()),
+                )
+            })
+            .collect();
+        self.pending_serialization = cx.background_executor().spawn(async move {
+            BOOKMARKS_DB
+                .save_bookmarks(workspace_id, bookmarks)
+                .await
+                .log_err()
+        });
+    }
+
+    /// A row holds at most one bookmark, but edits can move several bookmarks onto the same row.
+    /// Those are merged into the earliest created one, which takes the label of a merged bookmark
+    /// if it has none.
+    fn merge_bookmarks_on_same_row(&mut self) {
+        let mut bookmarks: Vec<Bookmark> = Vec::with_capacity(self.bookmarks.len());
+        for bookmark in mem::take(&mut self.bookmarks) {
+            let existing = bookmarks.iter_mut().find(|existing| {
+                existing.abs_path == bookmark.abs_path && existing.row == bookmark.row
+            });
+            if let Some(existing) = existing {
+                if existing.label.is_none() {
+                    existing.label = bookmark.label;
+                }
+            } else {
+                bookmarks.push(bookmark);
+            }
+        }
+        self.bookmarks = bookmarks;
+    }
+
+    fn bookmarks_changed(&mut self, cx: &mut ModelContext<Self>) {
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn next_id(&mut self) -> BookmarkId {
+        let id = BookmarkId(self.next_bookmark_id);
+        self.next_bookmark_id += 1;
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context, TestAppContext};
+
+    #[gpui::test]
+    fn test_toggle_and_navigate_bookmarks(cx: &mut TestAppContext) {
+        let buffer = cx.new_model(|cx| Buffer::local("one\ntwo\nthree\nfour\n", cx));
+        let path_a: Arc<Path> = Path::new("/root/a.rs").into();
+        let path_b: Arc<Path> = Path::new("/root/b.rs").into();
+        let store = cx.new_model(|_| {
+            BookmarkStore::new(None, vec![(path_b.to_path_buf(), 5, Some("later".into()))])
+        });
+
+        store.update(cx, |store, cx| {
+            store.toggle(&buffer, path_a.clone(), [1, 3], None, cx);
+            assert_eq!(
+                rows(store, cx),
+                ["/root/a.rs:1", "/root/a.rs:3", "/root/b.rs:5"]
+            );
+
+            store.toggle(&buffer, path_a.clone(), [3], None, cx);
+            assert_eq!(rows(store, cx), ["/root/a.rs:1", "/root/b.rs:5"]);
+        });
+
+        // Bookmarks follow the edits made to their buffer.
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+        store.update(cx, |store, cx| {
+            assert_eq!(rows(store, cx), ["/root/a.rs:2", "/root/b.rs:5"]);
+
+            let next = store.adjacent_bookmark(Some(&path_a), 2, Direction::Next, cx);
+            assert_eq!(store.bookmark(next.unwrap()).unwrap().row(cx), 5);
+            let next = store.adjacent_bookmark(Some(&path_b), 5, Direction::Next, cx);
+            assert_eq!(store.bookmark(next.unwrap()).unwrap().row(cx), 2);
+            let prev = store.adjacent_bookmark(Some(&path_a), 0, Direction::Prev, cx);
+            assert_eq!(store.bookmark(prev.unwrap()).unwrap().row(cx), 5);
+            let next = store.adjacent_bookmark(None, 0, Direction::Next, cx);
+            assert_eq!(store.bookmark(next.unwrap()).unwrap().row(cx), 2);
+        });
+    }
+
+    #[gpui::test]
+    fn test_merge_bookmarks_moved_onto_the_same_row(cx: &mut TestAppContext) {
+        let buffer = cx.new_model(|cx| Buffer::local("one\ntwo\nthree\n", cx));
+        let path: Arc<Path> = Path::new("/root/a.rs").into();
+        let store = cx.new_model(|_| BookmarkStore::new(None, Vec::new()));
+        store.update(cx, |store, cx| {
+            store.toggle(&buffer, path.clone(), [1], None, cx);
+            store.toggle(&buffer, path.clone(), [2], Some("three".into()), cx);
+        });
+
+        buffer.update(cx, |buffer, cx| buffer.edit([(4..8, "")], None, cx));
+        store.update(cx, |store, cx| {
+            assert_eq!(rows(store, cx), ["/root/a.rs:1", "/root/a.rs:1"]);
+
+            store.serialize(cx);
+            assert_eq!(rows(store, cx), ["/root/a.rs:1"]);
+            let (bookmark, _) = store.sorted_bookmarks(cx)[0];
+            assert_eq!(bookmark.label.as_deref(), Some("three"));
+        });
+    }
+
+    fn rows(store: &BookmarkStore, cx: &AppContext) -> Vec<String> {
+        store
+            .sorted_bookmarks(cx)
+            .into_iter()
+            .map(|(bookmark, row)| format!("{}:{row}", bookmark.abs_path.display()))
+            .collect()
+    }
+}
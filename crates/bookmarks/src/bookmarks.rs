@@ -0,0 +1,210 @@
+mod bookmark_store;
+mod bookmarks_panel;
+mod persistence;
+
+pub use bookmark_store::{Bookmark, BookmarkId, BookmarkStore, Direction};
+pub use bookmarks_panel::BookmarksPanel;
+
+use editor::{
+    actions::{GoToBookmark, GoToPrevBookmark, ToggleBookmark},
+    scroll::Autoscroll,
+    Editor, EditorMode,
+};
+use gpui::{actions, AppContext, Model, Pixels, View, ViewContext, WindowContext};
+use language::{Buffer, Point, ToPoint};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use std::{path::Path, sync::Arc};
+use workspace::{dock::DockPosition, Workspace};
+
+actions!(bookmarks_panel, [ToggleFocus]);
+
+#[derive(Deserialize, Debug)]
+pub struct BookmarksPanelSettings {
+    pub button: bool,
+    pub dock: DockPosition,
+    pub default_width: Pixels,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct BookmarksPanelSettingsContent {
+    /// Whether to show the bookmarks panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Where to dock the bookmarks panel.
+    ///
+    /// Default: right
+    pub dock: Option<DockPosition>,
+    /// Default width of the bookmarks panel in pixels.
+    ///
+    /// Default: 240
+    pub default_width: Option<f32>,
+}
+
+impl Settings for BookmarksPanelSettings {
+    const KEY: Option<&'static str> = Some("bookmarks_panel");
+
+    type FileContent = BookmarksPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    BookmarksPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<BookmarksPanel>(cx);
+        });
+    })
+    .detach();
+    cx.observe_new_views(register_editor_actions).detach();
+}
+
+fn register_editor_actions(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode() != EditorMode::Full {
+        return;
+    }
+
+    let handle = cx.view().downgrade();
+    editor.register_action({
+        let handle = handle.clone();
+        move |action: &ToggleBookmark, cx| {
+            if let Some(editor) = handle.upgrade() {
+                toggle_bookmark(editor, action, cx);
+            }
+        }
+    });
+    editor.register_action({
+        let handle = handle.clone();
+        move |_: &GoToBookmark, cx| {
+            if let Some(editor) = handle.upgrade() {
+                go_to_bookmark(editor, Direction::Next, cx);
+            }
+        }
+    });
+    editor.register_action(move |_: &GoToPrevBookmark, cx| {
+        if let Some(editor) = handle.upgrade() {
+            go_to_bookmark(editor, Direction::Prev, cx);
+        }
+    });
+}
+
+/// The bookmarks are kept by the bookmarks panel of the editor's workspace.
+fn bookmark_store(editor: &View<Editor>, cx: &WindowContext) -> Option<Model<BookmarkStore>> {
+    let workspace = editor.read(cx).workspace()?;
+    let panel = workspace.read(cx).panel::<BookmarksPanel>(cx)?;
+    Some(panel.read(cx).bookmark_store().clone())
+}
+
+fn buffer_abs_path(buffer: &Model<Buffer>, cx: &AppContext) -> Option<Arc<Path>> {
+    let file = buffer.read(cx).file()?.as_local()?;
+    Some(file.abs_path(cx).into())
+}
+
+fn toggle_bookmark(editor: View<Editor>, action: &ToggleBookmark, cx: &mut WindowContext) {
+    let Some(store) = bookmark_store(&editor, cx) else {
+        return;
+    };
+
+    let multi_buffer = editor.read(cx).buffer().clone();
+    let heads = editor
+        .read(cx)
+        .selections
+        .all::<usize>(cx)
+        .into_iter()
+        .map(|selection| selection.head())
+        .collect::<Vec<_>>();
+    let mut rows_by_buffer = Vec::<(Model<Buffer>, Vec<u32>)>::new();
+    for head in heads {
+        let Some((buffer, anchor)) = multi_buffer.read(cx).text_anchor_for_position(head, cx)
+        else {
+            continue;
+        };
+        let row = anchor.to_point(&buffer.read(cx).snapshot()).row;
+        match rows_by_buffer.iter_mut().find(|(b, _)| *b == buffer) {
+            Some((_, rows)) => {
+                if !rows.contains(&row) {
+                    rows.push(row);
+                }
+            }
+            None => rows_by_buffer.push((buffer, vec![row])),
+        }
+    }
+
+    let label = action.label.clone().map(Into::into);
+    store.update(cx, |store, cx| {
+        for (buffer, rows) in rows_by_buffer {
+            if let Some(abs_path) = buffer_abs_path(&buffer, cx) {
+                store.toggle(&buffer, abs_path, rows, label.clone(), cx);
+            }
+        }
+    });
+}
+
+fn go_to_bookmark(editor: View<Editor>, direction: Direction, cx: &mut WindowContext) {
+    let Some(store) = bookmark_store(&editor, cx) else {
+        return;
+    };
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+
+    let head = editor.read(cx).selections.newest::<usize>(cx).head();
+    let position = editor
+        .read(cx)
+        .buffer()
+        .read(cx)
+        .text_anchor_for_position(head, cx)
+        .map(|(buffer, anchor)| {
+            let row = anchor.to_point(&buffer.read(cx).snapshot()).row;
+            (buffer_abs_path(&buffer, cx), row)
+        });
+    let (abs_path, row) = position.unwrap_or((None, 0));
+    let Some(id) = store
+        .read(cx)
+        .adjacent_bookmark(abs_path.as_deref(), row, direction, cx)
+    else {
+        return;
+    };
+    workspace.update(cx, |workspace, cx| open_bookmark(workspace, &store, id, cx));
+}
+
+/// Opens the file of the bookmark and moves the cursor to its line.
+fn open_bookmark(
+    workspace: &mut Workspace,
+    store: &Model<BookmarkStore>,
+    id: BookmarkId,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let Some(abs_path) = store.read(cx).bookmark(id).map(|b| b.abs_path.clone()) else {
+        return;
+    };
+    let open_task = workspace.open_abs_path(abs_path.to_path_buf(), true, cx);
+    let store = store.clone();
+    cx.spawn(|_, mut cx| async move {
+        let Some(editor) = open_task.await?.downcast::<Editor>() else {
+            return Ok(());
+        };
+        editor.update(&mut cx, |editor, cx| {
+            if let Some(buffer) = editor.buffer().read(cx).as_singleton() {
+                store.update(cx, |store, cx| store.resolve(&buffer, &abs_path, cx));
+            }
+            let Some(row) = store.read(cx).bookmark(id).map(|b| b.row(cx)) else {
+                return;
+            };
+            let point = Point::new(row, 0);
+            editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                s.select_ranges([point..point])
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
@@ -0,0 +1,440 @@
+use crate::{
+    bookmark_store::{Bookmark, BookmarkId, BookmarkStore},
+    persistence::BOOKMARKS_DB,
+    BookmarksPanelSettings, ToggleFocus,
+};
+use anyhow::Result;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{actions::SelectAll, Editor, EditorEvent};
+use file_icons::FileIcons;
+use gpui::{
+    AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView, Model, ParentElement,
+    Pixels, Render, Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView,
+    WindowContext,
+};
+use language::Point;
+use project::Fs;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use ui::{prelude::*, ListItem, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace, WorkspaceId,
+};
+
+const BOOKMARKS_PANEL_KEY: &str = "BookmarksPanel";
+const PREVIEW_LEN: usize = 80;
+
+pub struct BookmarksPanel {
+    bookmark_store: Model<BookmarkStore>,
+    workspace: WeakView<Workspace>,
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    focus_handle: FocusHandle,
+    label_editor: Option<(BookmarkId, View<Editor>)>,
+    pending_serialization: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedBookmarksPanel {
+    width: Option<Pixels>,
+}
+
+impl BookmarksPanel {
+    pub fn new(
+        workspace: &mut Workspace,
+        workspace_id: Option<WorkspaceId>,
+        serialized_bookmarks: Vec<(PathBuf, u32, Option<String>)>,
+        cx: &mut ViewContext<Workspace>,
+    ) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let workspace_handle = cx.view().clone();
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            let bookmark_store =
+                cx.new_model(|_| BookmarkStore::new(workspace_id, serialized_bookmarks));
+
+            let subscriptions = vec![
+                cx.observe(&bookmark_store, |_, _, cx| cx.notify()),
+                cx.subscribe(&workspace_handle, |this, workspace, event, cx| {
+                    if let workspace::Event::ActiveItemChanged = event {
+                        this.resolve_active_editor(&workspace, cx);
+                    }
+                }),
+            ];
+
+            Self {
+                bookmark_store,
+                workspace: workspace_handle.downgrade(),
+                fs,
+                width: None,
+                focus_handle: cx.focus_handle(),
+                label_editor: None,
+                pending_serialization: Task::ready(None),
+                _subscriptions: subscriptions,
+            }
+        })
+    }
+
+    pub fn load(
+        workspace: WeakView<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<View<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let serialized_panel = if let Some(panel) = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(BOOKMARKS_PANEL_KEY) })
+                .await
+                .log_err()
+                .flatten()
+            {
+                Some(serde_json::from_str::<SerializedBookmarksPanel>(&panel)?)
+            } else {
+                None
+            };
+
+            let workspace_id = workspace.update(&mut cx, |workspace, _| workspace.database_id())?;
+            let serialized_bookmarks = if let Some(workspace_id) = workspace_id {
+                cx.background_executor()
+                    .spawn(async move { BOOKMARKS_DB.get_bookmarks(workspace_id) })
+                    .await
+                    .log_err()
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let panel = Self::new(workspace, workspace_id, serialized_bookmarks, cx);
+                if let Some(serialized_panel) = serialized_panel {
+                    panel.update(cx, |panel, cx| {
+                        panel.width = serialized_panel.width.map(|w| w.round());
+                        cx.notify();
+                    });
+                }
+                panel
+            })
+        })
+    }
+
+    pub fn bookmark_store(&self) -> &Model<BookmarkStore> {
+        &self.bookmark_store
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        BOOKMARKS_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedBookmarksPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Anchors the bookmarks of the file opened in the active editor, so they follow its edits.
+    fn resolve_active_editor(&mut self, workspace: &View<Workspace>, cx: &mut ViewContext<Self>) {
+        let Some(editor) = workspace
+            .read(cx)
+            .active_item(cx)
+            .and_then(|item| item.act_as::<Editor>(cx))
+        else {
+            return;
+        };
+        let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+            return;
+        };
+        if let Some(abs_path) = crate::buffer_abs_path(&buffer, cx) {
+            self.bookmark_store
+                .update(cx, |store, cx| store.resolve(&buffer, &abs_path, cx));
+        }
+    }
+
+    fn open_bookmark(&mut self, id: BookmarkId, cx: &mut ViewContext<Self>) {
+        let store = self.bookmark_store.clone();
+        self.workspace
+            .update(cx, |workspace, cx| {
+                crate::open_bookmark(workspace, &store, id, cx);
+            })
+            .log_err();
+    }
+
+    fn edit_label(&mut self, id: BookmarkId, cx: &mut ViewContext<Self>) {
+        let label = self
+            .bookmark_store
+            .read(cx)
+            .bookmark(id)
+            .and_then(|bookmark| bookmark.label.as_ref().map(|label| label.to_string()))
+            .unwrap_or_default();
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text("Bookmark label", cx);
+            editor.set_text(label, cx);
+            editor.select_all(&SelectAll, cx);
+            editor
+        });
+        cx.subscribe(&editor, |this, _, event, cx| {
+            if let EditorEvent::Blurred = event {
+                this.confirm_label(cx);
+            }
+        })
+        .detach();
+        editor.focus_handle(cx).focus(cx);
+        self.label_editor = Some((id, editor));
+        cx.notify();
+    }
+
+    fn confirm_label(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some((id, editor)) = self.label_editor.take() {
+            let label = editor.read(cx).text(cx);
+            self.bookmark_store.update(cx, |store, cx| {
+                store.set_label(id, Some(label.trim().to_string().into()), cx)
+            });
+            self.focus_handle.focus(cx);
+            cx.notify();
+        }
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        self.confirm_label(cx);
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        if self.label_editor.take().is_some() {
+            self.focus_handle.focus(cx);
+            cx.notify();
+        }
+    }
+
+    fn render_file_header(&self, abs_path: &Path, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let project = self
+            .workspace
+            .upgrade()
+            .map(|workspace| workspace.read(cx).project().clone());
+        let relative_path = project
+            .and_then(|project| project.read(cx).find_local_worktree(abs_path, cx))
+            .map(|(_, relative_path)| relative_path)
+            .unwrap_or_else(|| abs_path.to_path_buf());
+        let file_name = relative_path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_else(|| relative_path.to_string_lossy().to_string());
+        let directory = relative_path
+            .parent()
+            .map(|parent| parent.to_string_lossy().to_string())
+            .filter(|parent| !parent.is_empty());
+        let icon = FileIcons::get_icon(abs_path, cx);
+
+        h_flex()
+            .px_2()
+            .pt_2()
+            .pb_1()
+            .gap_1()
+            .children(icon.map(|icon| Icon::from_path(icon.to_string()).color(Color::Muted)))
+            .child(Label::new(file_name).single_line())
+            .children(directory.map(|directory| {
+                Label::new(directory)
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+                    .single_line()
+            }))
+    }
+
+    fn render_bookmark(
+        &self,
+        bookmark: &Bookmark,
+        row: u32,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let id = bookmark.id;
+        let label_editor = self
+            .label_editor
+            .as_ref()
+            .filter(|(editor_id, _)| *editor_id == id)
+            .map(|(_, editor)| editor.clone());
+        let line_text = bookmark.buffer().map(|buffer| {
+            let buffer = buffer.read(cx);
+            let line_end = Point::new(row, buffer.line_len(row));
+            let line = buffer
+                .text_for_range(Point::new(row, 0)..line_end)
+                .collect::<String>();
+            line.trim().chars().take(PREVIEW_LEN).collect::<String>()
+        });
+        let (title, detail) = match (bookmark.label.clone(), line_text) {
+            (Some(label), line_text) => (label, line_text),
+            (None, Some(line_text)) if !line_text.is_empty() => (line_text.into(), None),
+            (None, _) => (format!("Line {}", row + 1).into(), None),
+        };
+
+        ListItem::new(("bookmark", id.to_usize()))
+            .inset(true)
+            .start_slot(
+                Label::new((row + 1).to_string())
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+            .child(if let Some(editor) = label_editor {
+                h_flex().h_6().w_full().child(editor).into_any_element()
+            } else {
+                h_flex()
+                    .gap_2()
+                    .overflow_x_hidden()
+                    .child(Label::new(title).single_line())
+                    .children(detail.map(|detail| {
+                        Label::new(detail)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line()
+                    }))
+                    .into_any_element()
+            })
+            .end_hover_slot(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new(("edit-bookmark-label", id.to_usize()), IconName::Pencil)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Edit Label", cx))
+                            .on_click(cx.listener(move |this, _, cx| this.edit_label(id, cx))),
+                    )
+                    .child(
+                        IconButton::new(("remove-bookmark", id.to_usize()), IconName::Close)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Remove Bookmark", cx))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.bookmark_store
+                                    .update(cx, |store, cx| store.remove(id, cx))
+                            })),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, cx| this.open_bookmark(id, cx)))
+    }
+}
+
+impl Render for BookmarksPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let store = self.bookmark_store.read(cx);
+        let is_empty = store.is_empty();
+        let mut list = v_flex()
+            .id("bookmarks")
+            .size_full()
+            .overflow_y_scroll()
+            .pb_2();
+        if is_empty {
+            list = list.child(v_flex().p_4().child(
+                Label::new("Toggle a bookmark in an editor to list it here.").color(Color::Muted),
+            ));
+        } else {
+            let bookmarks = store
+                .sorted_bookmarks(cx)
+                .into_iter()
+                .map(|(bookmark, row)| (bookmark.clone(), row))
+                .collect::<Vec<_>>();
+            let mut current_path: Option<Arc<Path>> = None;
+            for (bookmark, row) in bookmarks {
+                if current_path.as_ref() != Some(&bookmark.abs_path) {
+                    list = list.child(self.render_file_header(&bookmark.abs_path, cx));
+                    current_path = Some(bookmark.abs_path.clone());
+                }
+                list = list.child(self.render_bookmark(&bookmark, row, cx));
+            }
+        }
+
+        v_flex()
+            .key_context("BookmarksPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .child(
+                h_flex()
+                    .h_8()
+                    .px_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Label::new("Bookmarks"))
+                    .child(
+                        IconButton::new("clear-bookmarks", IconName::Trash)
+                            .icon_size(IconSize::Small)
+                            .disabled(is_empty)
+                            .tooltip(|cx| Tooltip::text("Remove All Bookmarks", cx))
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.label_editor = None;
+                                this.bookmark_store.update(cx, |store, cx| store.clear(cx))
+                            })),
+                    ),
+            )
+            .child(list)
+    }
+}
+
+impl FocusableView for BookmarksPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for BookmarksPanel {}
+
+impl Panel for BookmarksPanel {
+    fn persistent_name() -> &'static str {
+        "BookmarksPanel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        BookmarksPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<BookmarksPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings| settings.dock = Some(position),
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| BookmarksPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        BookmarksPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Bookmark)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Bookmarks Panel")
+    }
+
+    fn icon_label(&self, cx: &WindowContext) -> Option<String> {
+        let count = self.bookmark_store.read(cx).len();
+        (count > 0).then(|| count.to_string())
+    }
+
+    fn toggle_action(&self) -> Box<dyn gpui::Action> {
+        Box::new(ToggleFocus)
+    }
+}
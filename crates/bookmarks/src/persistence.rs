@@ -0,0 +1,108 @@
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    // Current schema shape using pseudo-rust syntax:
+    // bookmarks(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   line: u32,
+    //   label: Option<String>,
+    // )
+    pub static ref BOOKMARKS_DB: BookmarksDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE bookmarks (
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                line INTEGER NOT NULL,
+                label TEXT,
+                PRIMARY KEY(workspace_id, path, line),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl BookmarksDb {
+    query! {
+        pub fn get_bookmarks(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, Option<String>)>> {
+            SELECT path, line, label
+            FROM bookmarks
+            WHERE workspace_id = ?
+            ORDER BY path, line
+        }
+    }
+
+    /// Replaces all the bookmarks stored for the given workspace. Each bookmark must be on a
+    /// different line.
+    pub async fn save_bookmarks(
+        &self,
+        workspace_id: WorkspaceId,
+        bookmarks: Vec<(PathBuf, u32, Option<String>)>,
+    ) -> anyhow::Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_bookmarks", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM bookmarks WHERE workspace_id = ?
+                ))?(workspace_id)
+                .context("clearing old bookmarks")?;
+                for (path, line, label) in bookmarks {
+                    conn.exec_bound(sql!(
+                        INSERT INTO bookmarks(workspace_id, path, line, label)
+                        VALUES (?, ?, ?, ?)
+                    ))?((workspace_id, path, line, label))
+                    .context("inserting bookmark")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use db::open_test_db;
+
+    #[gpui::test]
+    async fn test_save_and_get_bookmarks() {
+        let db = BookmarksDb(open_test_db("test_save_and_get_bookmarks").await);
+        let workspace_id = db
+            .write(|conn| {
+                conn.select_row::<WorkspaceId>(sql!(
+                    INSERT INTO workspaces DEFAULT VALUES RETURNING workspace_id
+                ))
+                .unwrap()()
+                .unwrap()
+                .unwrap()
+            })
+            .await;
+
+        let bookmarks = vec![
+            (PathBuf::from("/root/b.rs"), 0, None),
+            (PathBuf::from("/root/a.rs"), 3, None),
+            (PathBuf::from("/root/a.rs"), 1, Some("first".to_string())),
+        ];
+        db.save_bookmarks(workspace_id, bookmarks).await.unwrap();
+        assert_eq!(
+            db.get_bookmarks(workspace_id).unwrap(),
+            vec![
+                (PathBuf::from("/root/a.rs"), 1, Some("first".to_string())),
+                (PathBuf::from("/root/a.rs"), 3, None),
+                (PathBuf::from("/root/b.rs"), 0, None),
+            ]
+        );
+
+        // Saving replaces the bookmarks stored before.
+        let bookmarks = vec![(PathBuf::from("/root/a.rs"), 3, Some("third".to_string()))];
+        db.save_bookmarks(workspace_id, bookmarks.clone())
+            .await
+            .unwrap();
+        assert_eq!(db.get_bookmarks(workspace_id).unwrap(), bookmarks);
+    }
+}
@@ -1,10 +1,11 @@
 use editor::Editor;
+use file_finder::FileFinder;
 use gpui::{
-    Element, EventEmitter, IntoElement, ParentElement, Render, StyledText, Subscription,
-    ViewContext,
+    Element, EventEmitter, IntoElement, Keystroke, Modifiers, ParentElement, Render, StyledText,
+    Subscription, View, ViewContext, WindowContext,
 };
 use itertools::Itertools;
-use std::cmp;
+use std::{cmp, path::MAIN_SEPARATOR};
 use theme::ActiveTheme;
 use ui::{prelude::*, ButtonLike, ButtonStyle, Label, Tooltip};
 use workspace::{
@@ -30,6 +31,16 @@ impl Breadcrumbs {
 
 impl EventEmitter<ToolbarItemEvent> for Breadcrumbs {}
 
+/// What clicking a breadcrumb segment lists, to jump to one of its siblings.
+#[derive(Clone)]
+enum Siblings {
+    /// The files next to a path component, found by typing its directory in the file finder.
+    Files { directory: String },
+    /// The outline items next to the symbol at the given depth, listed when secondary-clicking it,
+    /// as clicking it shows the whole outline.
+    Symbols { depth: usize },
+}
+
 impl Render for Breadcrumbs {
     fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
         const MAX_SEGMENTS: usize = 12;
@@ -37,9 +48,30 @@ impl Render for Breadcrumbs {
         let Some(active_item) = self.active_item.as_ref() else {
             return element;
         };
-        let Some(mut segments) = active_item.breadcrumbs(cx.theme(), cx) else {
+        let Some(segments) = active_item.breadcrumbs(cx.theme(), cx) else {
             return element;
         };
+        let editor = active_item
+            .downcast::<Editor>()
+            .map(|editor| editor.downgrade());
+
+        let mut segments = if editor.is_some() {
+            // Editors start with the path of their file, shown one component at a time, followed
+            // by the symbols containing the cursor.
+            let mut segments = segments.into_iter();
+            let mut path_segments = segments.next().map_or(Vec::new(), split_path_segment);
+            path_segments.extend(
+                segments
+                    .enumerate()
+                    .map(|(depth, segment)| (segment, Some(Siblings::Symbols { depth }))),
+            );
+            path_segments
+        } else {
+            segments
+                .into_iter()
+                .map(|segment| (segment, None))
+                .collect::<Vec<_>>()
+        };
 
         let prefix_end_ix = cmp::min(segments.len(), MAX_SEGMENTS / 2);
         let suffix_start_ix = cmp::max(
@@ -49,53 +81,121 @@ impl Render for Breadcrumbs {
         if suffix_start_ix > prefix_end_ix {
             segments.splice(
                 prefix_end_ix..suffix_start_ix,
-                Some(BreadcrumbText {
-                    text: "⋯".into(),
-                    highlights: None,
-                    font: None,
-                }),
+                Some((
+                    BreadcrumbText {
+                        text: "⋯".into(),
+                        highlights: None,
+                        font: None,
+                    },
+                    None,
+                )),
             );
         }
 
-        let highlighted_segments = segments.into_iter().map(|segment| {
-            let mut text_style = cx.text_style();
-            if let Some(font) = segment.font {
-                text_style.font_family = font.family;
-                text_style.font_features = font.features;
-                text_style.font_style = font.style;
-                text_style.font_weight = font.weight;
-            }
-            text_style.color = Color::Muted.color(cx);
+        let highlighted_segments =
+            segments
+                .into_iter()
+                .enumerate()
+                .map(|(ix, (segment, siblings))| {
+                    let mut text_style = cx.text_style();
+                    if let Some(font) = segment.font {
+                        text_style.font_family = font.family;
+                        text_style.font_features = font.features;
+                        text_style.font_style = font.style;
+                        text_style.font_weight = font.weight;
+                    }
+                    text_style.color = Color::Muted.color(cx);
 
-            StyledText::new(segment.text.replace('\n', "␤"))
-                .with_highlights(&text_style, segment.highlights.unwrap_or_default())
-                .into_any()
-        });
+                    let text = StyledText::new(segment.text.replace('\n', "␤"))
+                        .with_highlights(&text_style, segment.highlights.unwrap_or_default())
+                        .into_any();
+                    let (Some(siblings), Some(editor)) = (siblings, editor.clone()) else {
+                        return text;
+                    };
+                    let is_symbol = matches!(siblings, Siblings::Symbols { .. });
+                    ButtonLike::new(("breadcrumb", ix))
+                        .child(text)
+                        .style(ButtonStyle::Subtle)
+                        .on_click(move |event, cx| {
+                            let Some(editor) = editor.upgrade() else {
+                                return;
+                            };
+                            // Symbols show the whole outline, unless secondary-clicked.
+                            if is_symbol && !event.down.modifiers.secondary() {
+                                outline::toggle(editor, &outline::Toggle, cx)
+                            } else {
+                                show_siblings(editor, &siblings, cx)
+                            }
+                        })
+                        .tooltip(move |cx| {
+                            if is_symbol {
+                                let secondary_modifier = Keystroke {
+                                    key: "".to_string(),
+                                    modifiers: Modifiers::secondary_key(),
+                                    ime_key: None,
+                                };
+                                Tooltip::with_meta(
+                                    "Show symbol outline",
+                                    Some(&outline::Toggle),
+                                    format!("{secondary_modifier}-click to show sibling symbols"),
+                                    cx,
+                                )
+                            } else {
+                                Tooltip::text("Show Sibling Files", cx)
+                            }
+                        })
+                        .into_any_element()
+                });
         let breadcrumbs = Itertools::intersperse_with(highlighted_segments, || {
             Label::new("›").color(Color::Muted).into_any_element()
         });
 
-        let breadcrumbs_stack = h_flex().gap_1().children(breadcrumbs);
-        match active_item
-            .downcast::<Editor>()
-            .map(|editor| editor.downgrade())
-        {
-            Some(editor) => element.child(
-                ButtonLike::new("toggle outline view")
-                    .child(breadcrumbs_stack)
-                    .style(ButtonStyle::Subtle)
-                    .on_click(move |_, cx| {
-                        if let Some(editor) = editor.upgrade() {
-                            outline::toggle(editor, &outline::Toggle, cx)
-                        }
-                    })
-                    .tooltip(|cx| Tooltip::for_action("Show symbol outline", &outline::Toggle, cx)),
-            ),
-            None => element
-                // Match the height of the `ButtonLike` in the other arm.
-                .h(rems_from_px(22.))
-                .child(breadcrumbs_stack),
+        element
+            // Match the height of the segment buttons.
+            .h(rems_from_px(22.))
+            .child(h_flex().gap_1().children(breadcrumbs))
+    }
+}
+
+/// Splits the path an editor's breadcrumbs start with into its components, each listing the
+/// files of its directory.
+fn split_path_segment(segment: BreadcrumbText) -> Vec<(BreadcrumbText, Option<Siblings>)> {
+    let mut components = Vec::new();
+    let mut component_start = 0;
+    for (ix, separator) in segment.text.match_indices(MAIN_SEPARATOR) {
+        components.push(component_start..ix);
+        component_start = ix + separator.len();
+    }
+    components.push(component_start..segment.text.len());
+
+    components
+        .into_iter()
+        .filter(|range| !range.is_empty())
+        .map(|range| {
+            let siblings = Siblings::Files {
+                directory: segment.text[..range.start].to_string(),
+            };
+            let text = BreadcrumbText {
+                text: segment.text[range].to_string(),
+                highlights: None,
+                font: segment.font.clone(),
+            };
+            (text, Some(siblings))
+        })
+        .collect()
+}
+
+fn show_siblings(editor: View<Editor>, siblings: &Siblings, cx: &mut WindowContext) {
+    match siblings {
+        Siblings::Files { directory } => {
+            if let Some(workspace) = editor.read(cx).workspace() {
+                let directory = directory.clone();
+                workspace.update(cx, |workspace, cx| {
+                    FileFinder::open_with_query(workspace, directory, cx)
+                });
+            }
         }
+        Siblings::Symbols { depth } => outline::toggle_siblings(editor, *depth, cx),
     }
 }
 
@@ -136,3 +236,44 @@ impl ToolbarItemView for Breadcrumbs {
         self.pane_focused = pane_focused;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_path_segment() {
+        let path = ["crates", "editor", "src", "editor.rs"].join(&MAIN_SEPARATOR.to_string());
+        let segments = split_path_segment(BreadcrumbText {
+            text: path,
+            highlights: None,
+            font: None,
+        });
+        let segments = segments
+            .into_iter()
+            .map(|(segment, siblings)| {
+                let Some(Siblings::Files { directory }) = siblings else {
+                    panic!("path components should list sibling files");
+                };
+                (segment.text, directory.replace(MAIN_SEPARATOR, "/"))
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            segments,
+            [
+                ("crates".to_string(), "".to_string()),
+                ("editor".to_string(), "crates/".to_string()),
+                ("src".to_string(), "crates/editor/".to_string()),
+                ("editor.rs".to_string(), "crates/editor/src/".to_string()),
+            ]
+        );
+
+        let segments = split_path_segment(BreadcrumbText {
+            text: "untitled".to_string(),
+            highlights: None,
+            font: None,
+        });
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].0.text, "untitled");
+    }
+}
@@ -0,0 +1,114 @@
+mod call_hierarchy_panel;
+
+pub use call_hierarchy_panel::CallHierarchyPanel;
+
+use editor::{
+    actions::{ShowCallHierarchy, ShowTypeHierarchy},
+    Editor, EditorMode,
+};
+use gpui::{actions, AppContext, Pixels, View, ViewContext, WindowContext};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use workspace::dock::DockPosition;
+use workspace::Workspace;
+
+actions!(call_hierarchy_panel, [ToggleFocus, ToggleDirection]);
+
+#[derive(Deserialize, Debug)]
+pub struct CallHierarchyPanelSettings {
+    pub button: bool,
+    pub dock: DockPosition,
+    pub default_width: Pixels,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct CallHierarchyPanelSettingsContent {
+    /// Whether to show the call hierarchy panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Where to dock the call hierarchy panel.
+    ///
+    /// Default: left
+    pub dock: Option<DockPosition>,
+    /// Default width of the call hierarchy panel in pixels.
+    ///
+    /// Default: 240
+    pub default_width: Option<f32>,
+}
+
+impl Settings for CallHierarchyPanelSettings {
+    const KEY: Option<&'static str> = Some("call_hierarchy_panel");
+
+    type FileContent = CallHierarchyPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    CallHierarchyPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<CallHierarchyPanel>(cx);
+        });
+    })
+    .detach();
+    cx.observe_new_views(register_editor_actions).detach();
+}
+
+fn register_editor_actions(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode() != EditorMode::Full {
+        return;
+    }
+
+    let handle = cx.view().downgrade();
+    editor.register_action({
+        let handle = handle.clone();
+        move |_: &ShowCallHierarchy, cx| {
+            if let Some(editor) = handle.upgrade() {
+                show_hierarchy(editor, false, cx);
+            }
+        }
+    });
+    editor.register_action(move |_: &ShowTypeHierarchy, cx| {
+        if let Some(editor) = handle.upgrade() {
+            show_hierarchy(editor, true, cx);
+        }
+    });
+}
+
+/// Shows the call or type hierarchy of the symbol under the newest cursor in the panel of the
+/// editor's workspace.
+fn show_hierarchy(editor: View<Editor>, types: bool, cx: &mut WindowContext) {
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+    let head = editor.read(cx).selections.newest::<usize>(cx).head();
+    let Some((buffer, position)) = editor
+        .read(cx)
+        .buffer()
+        .read(cx)
+        .text_anchor_for_position(head, cx)
+    else {
+        return;
+    };
+
+    workspace.update(cx, |workspace, cx| {
+        if let Some(panel) = workspace.focus_panel::<CallHierarchyPanel>(cx) {
+            panel.update(cx, |panel, cx| {
+                if types {
+                    panel.show_type_hierarchy(buffer, position, cx)
+                } else {
+                    panel.show_call_hierarchy(buffer, position, cx)
+                }
+            });
+        }
+    });
+}
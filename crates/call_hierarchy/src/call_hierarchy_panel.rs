@@ -0,0 +1,613 @@
+use crate::{CallHierarchyPanelSettings, ToggleDirection, ToggleFocus};
+use anyhow::Result;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{scroll::Autoscroll, Bias, Editor};
+use gpui::{
+    AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView, Model, ParentElement,
+    Pixels, Render, Styled, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+};
+use language::{point_from_lsp, Anchor, Buffer};
+use project::{CallHierarchyCall, CallHierarchyItem, Fs, Project, TypeHierarchyItem};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::sync::Arc;
+use ui::{prelude::*, ListItem, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+const CALL_HIERARCHY_PANEL_KEY: &str = "CallHierarchyPanel";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum HierarchyKind {
+    /// The tree lists the callers of each symbol.
+    IncomingCalls,
+    /// The tree lists the callees of each symbol.
+    OutgoingCalls,
+    /// The tree lists the types each type inherits from or implements.
+    Supertypes,
+    /// The tree lists the types inheriting from or implementing each type.
+    Subtypes,
+}
+
+impl HierarchyKind {
+    fn title(self) -> &'static str {
+        match self {
+            Self::IncomingCalls => "Incoming Calls",
+            Self::OutgoingCalls => "Outgoing Calls",
+            Self::Supertypes => "Supertypes",
+            Self::Subtypes => "Subtypes",
+        }
+    }
+
+    fn is_call_hierarchy(self) -> bool {
+        matches!(self, Self::IncomingCalls | Self::OutgoingCalls)
+    }
+
+    fn reversed(self) -> Self {
+        match self {
+            Self::IncomingCalls => Self::OutgoingCalls,
+            Self::OutgoingCalls => Self::IncomingCalls,
+            Self::Supertypes => Self::Subtypes,
+            Self::Subtypes => Self::Supertypes,
+        }
+    }
+}
+
+#[derive(Clone)]
+enum HierarchyItem {
+    Call(CallHierarchyItem),
+    Type(TypeHierarchyItem),
+}
+
+impl HierarchyItem {
+    fn name(&self) -> &str {
+        match self {
+            Self::Call(item) => &item.lsp_item.name,
+            Self::Type(item) => &item.lsp_item.name,
+        }
+    }
+
+    fn uri(&self) -> &lsp::Url {
+        match self {
+            Self::Call(item) => &item.lsp_item.uri,
+            Self::Type(item) => &item.lsp_item.uri,
+        }
+    }
+
+    fn selection_range(&self) -> lsp::Range {
+        match self {
+            Self::Call(item) => item.lsp_item.selection_range,
+            Self::Type(item) => item.lsp_item.selection_range,
+        }
+    }
+}
+
+struct HierarchyNode {
+    item: HierarchyItem,
+    /// Where the calls of this node to its parent are made, for callers.
+    from_ranges: Vec<lsp::Range>,
+    expanded: bool,
+    /// The children of this node in the panel's hierarchy, once they've been fetched.
+    children: Option<Vec<HierarchyNode>>,
+}
+
+impl HierarchyNode {
+    fn new(item: HierarchyItem, from_ranges: Vec<lsp::Range>) -> Self {
+        Self {
+            item,
+            from_ranges,
+            expanded: false,
+            children: None,
+        }
+    }
+
+    fn from_calls(calls: Vec<CallHierarchyCall>) -> Vec<Self> {
+        calls
+            .into_iter()
+            .map(|call| Self::new(HierarchyItem::Call(call.item), call.from_ranges))
+            .collect()
+    }
+
+    fn from_types(items: Vec<TypeHierarchyItem>) -> Vec<Self> {
+        items
+            .into_iter()
+            .map(|item| Self::new(HierarchyItem::Type(item), Vec::new()))
+            .collect()
+    }
+}
+
+/// A panel showing the call hierarchy or the type hierarchy of a symbol as a tree.
+pub struct CallHierarchyPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    focus_handle: FocusHandle,
+    kind: HierarchyKind,
+    /// The buffer the hierarchy was prepared in, whose language server is asked for calls.
+    buffer: Option<Model<Buffer>>,
+    roots: Vec<HierarchyNode>,
+    /// Incremented whenever the tree is rebuilt, so that calls fetched for a previous tree are
+    /// dropped.
+    generation: usize,
+    preparing: bool,
+    prepare_task: Task<()>,
+    pending_serialization: Task<Option<()>>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedCallHierarchyPanel {
+    width: Option<Pixels>,
+}
+
+impl CallHierarchyPanel {
+    pub fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let project = workspace.project().clone();
+        let workspace_handle = cx.view().downgrade();
+        cx.new_view(|cx: &mut ViewContext<Self>| Self {
+            workspace: workspace_handle,
+            project,
+            fs,
+            width: None,
+            focus_handle: cx.focus_handle(),
+            kind: HierarchyKind::IncomingCalls,
+            buffer: None,
+            roots: Vec::new(),
+            generation: 0,
+            preparing: false,
+            prepare_task: Task::ready(()),
+            pending_serialization: Task::ready(None),
+        })
+    }
+
+    pub fn load(
+        workspace: WeakView<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<View<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let serialized_panel = if let Some(panel) = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(CALL_HIERARCHY_PANEL_KEY) })
+                .await
+                .log_err()
+                .flatten()
+            {
+                Some(serde_json::from_str::<SerializedCallHierarchyPanel>(
+                    &panel,
+                )?)
+            } else {
+                None
+            };
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let panel = Self::new(workspace, cx);
+                if let Some(serialized_panel) = serialized_panel {
+                    panel.update(cx, |panel, cx| {
+                        panel.width = serialized_panel.width.map(|w| w.round());
+                        cx.notify();
+                    });
+                }
+                panel
+            })
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        CALL_HIERARCHY_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedCallHierarchyPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Replaces the tree with the call hierarchy of the symbol at the given position, expanding
+    /// its calls.
+    pub fn show_call_hierarchy(
+        &mut self,
+        buffer: Model<Buffer>,
+        position: Anchor,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let prepare = self.project.update(cx, |project, cx| {
+            project.prepare_call_hierarchy(&buffer, position, cx)
+        });
+        let prepare = cx.background_executor().spawn(async move {
+            let items = prepare.await?;
+            anyhow::Ok(items.into_iter().map(HierarchyItem::Call).collect())
+        });
+        if !self.kind.is_call_hierarchy() {
+            self.kind = HierarchyKind::IncomingCalls;
+        }
+        self.show_hierarchy(buffer, prepare, cx);
+    }
+
+    /// Replaces the tree with the type hierarchy of the type at the given position, expanding
+    /// its supertypes or subtypes.
+    pub fn show_type_hierarchy(
+        &mut self,
+        buffer: Model<Buffer>,
+        position: Anchor,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let prepare = self.project.update(cx, |project, cx| {
+            project.prepare_type_hierarchy(&buffer, position, cx)
+        });
+        let prepare = cx.background_executor().spawn(async move {
+            let items = prepare.await?;
+            anyhow::Ok(items.into_iter().map(HierarchyItem::Type).collect())
+        });
+        if self.kind.is_call_hierarchy() {
+            self.kind = HierarchyKind::Supertypes;
+        }
+        self.show_hierarchy(buffer, prepare, cx);
+    }
+
+    fn show_hierarchy(
+        &mut self,
+        buffer: Model<Buffer>,
+        prepare: Task<Result<Vec<HierarchyItem>>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.generation += 1;
+        self.buffer = Some(buffer);
+        self.roots.clear();
+        self.preparing = true;
+        cx.notify();
+
+        self.prepare_task = cx.spawn(|this, mut cx| async move {
+            let items = prepare.await.log_err().unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                this.preparing = false;
+                this.roots = items
+                    .into_iter()
+                    .map(|item| HierarchyNode::new(item, Vec::new()))
+                    .collect();
+                this.expand_roots(cx);
+                cx.notify();
+            })
+            .ok();
+        });
+    }
+
+    fn expand_roots(&mut self, cx: &mut ViewContext<Self>) {
+        for ix in 0..self.roots.len() {
+            self.roots[ix].expanded = true;
+            self.fetch_children(vec![ix], cx);
+        }
+    }
+
+    fn toggle_direction(&mut self, _: &ToggleDirection, cx: &mut ViewContext<Self>) {
+        self.kind = self.kind.reversed();
+        self.generation += 1;
+        for root in &mut self.roots {
+            root.children = None;
+        }
+        self.expand_roots(cx);
+        cx.notify();
+    }
+
+    fn node(&self, path: &[usize]) -> Option<&HierarchyNode> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.roots.get(*first)?;
+        for ix in rest {
+            node = node.children.as_ref()?.get(*ix)?;
+        }
+        Some(node)
+    }
+
+    fn node_mut(&mut self, path: &[usize]) -> Option<&mut HierarchyNode> {
+        let (first, rest) = path.split_first()?;
+        let mut node = self.roots.get_mut(*first)?;
+        for ix in rest {
+            node = node.children.as_mut()?.get_mut(*ix)?;
+        }
+        Some(node)
+    }
+
+    fn toggle_node(&mut self, path: Vec<usize>, cx: &mut ViewContext<Self>) {
+        let Some(node) = self.node_mut(&path) else {
+            return;
+        };
+        node.expanded = !node.expanded;
+        if node.expanded && node.children.is_none() {
+            self.fetch_children(path, cx);
+        }
+        cx.notify();
+    }
+
+    fn fetch_children(&mut self, path: Vec<usize>, cx: &mut ViewContext<Self>) {
+        let Some(buffer) = self.buffer.clone() else {
+            return;
+        };
+        let Some(item) = self.node(&path).map(|node| node.item.clone()) else {
+            return;
+        };
+        let kind = self.kind;
+        let children = self.project.update(cx, |project, cx| {
+            Some(match (kind, item) {
+                (HierarchyKind::IncomingCalls, HierarchyItem::Call(item)) => {
+                    let calls = project.incoming_calls(&buffer, item, cx);
+                    cx.background_executor()
+                        .spawn(async move { anyhow::Ok(HierarchyNode::from_calls(calls.await?)) })
+                }
+                (HierarchyKind::OutgoingCalls, HierarchyItem::Call(item)) => {
+                    let calls = project.outgoing_calls(&buffer, item, cx);
+                    cx.background_executor()
+                        .spawn(async move { anyhow::Ok(HierarchyNode::from_calls(calls.await?)) })
+                }
+                (HierarchyKind::Supertypes, HierarchyItem::Type(item)) => {
+                    let types = project.supertypes(&buffer, item, cx);
+                    cx.background_executor()
+                        .spawn(async move { anyhow::Ok(HierarchyNode::from_types(types.await?)) })
+                }
+                (HierarchyKind::Subtypes, HierarchyItem::Type(item)) => {
+                    let types = project.subtypes(&buffer, item, cx);
+                    cx.background_executor()
+                        .spawn(async move { anyhow::Ok(HierarchyNode::from_types(types.await?)) })
+                }
+                _ => return None,
+            })
+        });
+        let Some(children) = children else {
+            return;
+        };
+        let generation = self.generation;
+        cx.spawn(|this, mut cx| async move {
+            let children = children.await.log_err().unwrap_or_default();
+            this.update(&mut cx, |this, cx| {
+                if this.generation != generation {
+                    return;
+                }
+                if let Some(node) = this.node_mut(&path) {
+                    node.children = Some(children);
+                    cx.notify();
+                }
+            })
+            .ok();
+        })
+        .detach();
+    }
+
+    /// Opens the file of the node's symbol, at the first call for callers and at the symbol's
+    /// name otherwise.
+    fn open_node(&mut self, path: &[usize], cx: &mut ViewContext<Self>) {
+        let Some(node) = self.node(path) else {
+            return;
+        };
+        let Ok(abs_path) = node.item.uri().to_file_path() else {
+            return;
+        };
+        let position = match (self.kind, node.from_ranges.first()) {
+            (HierarchyKind::IncomingCalls, Some(range)) => range.start,
+            _ => node.item.selection_range().start,
+        };
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+
+        let open_task = workspace.update(cx, |workspace, cx| {
+            workspace.open_abs_path(abs_path, true, cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let Some(editor) = open_task.await?.downcast::<Editor>() else {
+                return Ok(());
+            };
+            editor.update(&mut cx, |editor, cx| {
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let point = snapshot.clip_point_utf16(point_from_lsp(position), Bias::Left);
+                editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                    s.select_ranges([point..point])
+                });
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// The paths of the nodes shown in the tree, in order.
+    fn visible_nodes(&self) -> Vec<Vec<usize>> {
+        fn push_nodes(nodes: &[HierarchyNode], path: &mut Vec<usize>, paths: &mut Vec<Vec<usize>>) {
+            for (ix, node) in nodes.iter().enumerate() {
+                path.push(ix);
+                paths.push(path.clone());
+                if let Some(children) = node.children.as_ref().filter(|_| node.expanded) {
+                    push_nodes(children, path, paths);
+                }
+                path.pop();
+            }
+        }
+
+        let mut paths = Vec::new();
+        push_nodes(&self.roots, &mut Vec::new(), &mut paths);
+        paths
+    }
+
+    fn render_node(
+        &self,
+        ix: usize,
+        path: Vec<usize>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<impl IntoElement> {
+        let node = self.node(&path)?;
+        let file_name = node
+            .item
+            .uri()
+            .to_file_path()
+            .ok()
+            .and_then(|path| Some(path.file_name()?.to_string_lossy().to_string()));
+        let line = match (self.kind, node.from_ranges.first()) {
+            (HierarchyKind::IncomingCalls, Some(range)) => range.start.line,
+            _ => node.item.selection_range().start.line,
+        };
+        let location = match file_name {
+            Some(file_name) => format!("{}:{}", file_name, line + 1),
+            None => format!("{}", line + 1),
+        };
+        let call_count =
+            (node.from_ranges.len() > 1).then(|| format!("×{}", node.from_ranges.len()));
+        let is_loading = node.expanded && node.children.is_none();
+        let has_children = node
+            .children
+            .as_ref()
+            .map_or(true, |children| !children.is_empty());
+
+        Some(
+            ListItem::new(("hierarchy-node", ix))
+                .indent_level(path.len() - 1)
+                .indent_step_size(px(12.))
+                .toggle(has_children.then_some(node.expanded))
+                .on_toggle(cx.listener({
+                    let path = path.clone();
+                    move |this, _, cx| this.toggle_node(path.clone(), cx)
+                }))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .overflow_x_hidden()
+                        .child(Label::new(node.item.name().to_string()).single_line())
+                        .children(call_count.map(|count| {
+                            Label::new(count)
+                                .size(LabelSize::Small)
+                                .color(Color::Accent)
+                        }))
+                        .child(
+                            Label::new(location)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line(),
+                        )
+                        .when(is_loading, |this| {
+                            this.child(Label::new("…").size(LabelSize::Small).color(Color::Muted))
+                        }),
+                )
+                .on_click(cx.listener(move |this, _, cx| this.open_node(&path, cx))),
+        )
+    }
+}
+
+impl Render for CallHierarchyPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let mut list = v_flex()
+            .id("hierarchy")
+            .size_full()
+            .overflow_y_scroll()
+            .pb_2();
+        if self.roots.is_empty() {
+            let message = if self.preparing {
+                "Loading hierarchy…"
+            } else if self.buffer.is_some() {
+                "No hierarchy for the symbol under the cursor."
+            } else {
+                "Show the call or type hierarchy of a symbol in an editor to browse it here."
+            };
+            list = list.child(
+                v_flex()
+                    .p_4()
+                    .child(Label::new(message).color(Color::Muted)),
+            );
+        } else {
+            for (ix, path) in self.visible_nodes().into_iter().enumerate() {
+                list = list.children(self.render_node(ix, path, cx));
+            }
+        }
+
+        let title = self.kind.title();
+        let direction_icon = match self.kind {
+            HierarchyKind::IncomingCalls | HierarchyKind::Subtypes => IconName::ArrowDown,
+            HierarchyKind::OutgoingCalls | HierarchyKind::Supertypes => IconName::ArrowUp,
+        };
+        let direction_tooltip = format!("Show {}", self.kind.reversed().title());
+
+        v_flex()
+            .key_context("CallHierarchyPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .on_action(cx.listener(Self::toggle_direction))
+            .child(
+                h_flex()
+                    .h_8()
+                    .px_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Label::new(title))
+                    .child(
+                        IconButton::new("toggle-hierarchy-direction", direction_icon)
+                            .icon_size(IconSize::Small)
+                            .tooltip(move |cx| {
+                                Tooltip::for_action(direction_tooltip.clone(), &ToggleDirection, cx)
+                            })
+                            .on_click(cx.listener(|this, _, cx| {
+                                this.toggle_direction(&ToggleDirection, cx)
+                            })),
+                    ),
+            )
+            .child(list)
+    }
+}
+
+impl FocusableView for CallHierarchyPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for CallHierarchyPanel {}
+
+impl Panel for CallHierarchyPanel {
+    fn persistent_name() -> &'static str {
+        "CallHierarchyPanel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        CallHierarchyPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<CallHierarchyPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings| settings.dock = Some(position),
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| CallHierarchyPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        CallHierarchyPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Code)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Call Hierarchy Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn gpui::Action> {
+        Box::new(ToggleFocus)
+    }
+}
@@ -0,0 +1,237 @@
+use editor::{actions::PasteFromHistory, Editor, EditorMode};
+use gpui::{
+    rems, AppContext, ClipboardItem, DismissEvent, EventEmitter, FocusHandle, FocusableView,
+    Render, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+};
+use picker::{Picker, PickerDelegate};
+use settings::Settings;
+use std::sync::Arc;
+use theme::ThemeSettings;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{ClipboardHistory, ModalView};
+
+const LABEL_LEN: usize = 64;
+const PREVIEW_LINES: usize = 8;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(ClipboardHistoryView::register)
+        .detach();
+}
+
+pub fn toggle(editor: View<Editor>, _: &PasteFromHistory, cx: &mut WindowContext) {
+    let items = ClipboardHistory::entries(cx)
+        .map(|entry| entry.item.clone())
+        .collect::<Vec<_>>();
+    if let Some(workspace) = editor.read(cx).workspace() {
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, |cx| ClipboardHistoryView::new(items, editor, cx));
+        })
+    }
+}
+
+pub struct ClipboardHistoryView {
+    picker: View<Picker<ClipboardHistoryDelegate>>,
+}
+
+impl FocusableView for ClipboardHistoryView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for ClipboardHistoryView {}
+impl ModalView for ClipboardHistoryView {}
+
+impl Render for ClipboardHistoryView {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let preview = self.picker.read(cx).delegate.selected_preview();
+        v_flex()
+            .w(rems(34.))
+            .child(self.picker.clone())
+            .when_some(preview, |this, preview| {
+                this.child(
+                    div()
+                        .mt_1()
+                        .p_2()
+                        .elevation_2(cx)
+                        .font_family(ThemeSettings::get_global(cx).buffer_font.family.clone())
+                        .text_ui_sm(cx)
+                        .text_color(cx.theme().colors().text_muted)
+                        .child(preview),
+                )
+            })
+    }
+}
+
+impl ClipboardHistoryView {
+    fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        if editor.mode() == EditorMode::Full {
+            let handle = cx.view().downgrade();
+            editor.register_action(move |action, cx| {
+                if let Some(editor) = handle.upgrade() {
+                    toggle(editor, action, cx);
+                }
+            });
+        }
+    }
+
+    fn new(items: Vec<ClipboardItem>, editor: View<Editor>, cx: &mut ViewContext<Self>) -> Self {
+        let delegate = ClipboardHistoryDelegate::new(cx.view().downgrade(), items, editor);
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+        Self { picker }
+    }
+}
+
+struct ClipboardHistoryDelegate {
+    clipboard_history_view: WeakView<ClipboardHistoryView>,
+    active_editor: View<Editor>,
+    items: Vec<ClipboardItem>,
+    matches: Vec<usize>,
+    selected_match_index: usize,
+}
+
+impl ClipboardHistoryDelegate {
+    fn new(
+        clipboard_history_view: WeakView<ClipboardHistoryView>,
+        items: Vec<ClipboardItem>,
+        editor: View<Editor>,
+    ) -> Self {
+        Self {
+            clipboard_history_view,
+            active_editor: editor,
+            matches: (0..items.len()).collect(),
+            items,
+            selected_match_index: 0,
+        }
+    }
+
+    fn selected_item(&self) -> Option<&ClipboardItem> {
+        let ix = *self.matches.get(self.selected_match_index)?;
+        self.items.get(ix)
+    }
+
+    fn selected_preview(&self) -> Option<SharedString> {
+        let text = self.selected_item()?.text();
+        let mut preview = text
+            .lines()
+            .take(PREVIEW_LINES)
+            .collect::<Vec<_>>()
+            .join("\n");
+        if text.lines().nth(PREVIEW_LINES).is_some() {
+            preview.push_str("\n…");
+        }
+        Some(preview.into())
+    }
+}
+
+impl PickerDelegate for ClipboardHistoryDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search clipboard history...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_match_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_match_index = ix;
+        self.clipboard_history_view
+            .update(cx, |_, cx| cx.notify())
+            .ok();
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let query = query.to_lowercase();
+        self.matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| query.is_empty() || item.text().to_lowercase().contains(&query))
+            .map(|(ix, _)| ix)
+            .collect();
+        self.selected_match_index = 0;
+        self.clipboard_history_view
+            .update(cx, |_, cx| cx.notify())
+            .ok();
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(item) = self.selected_item().cloned() {
+            // Like with a kill ring, the pasted item becomes the most recent one.
+            ClipboardHistory::record(item.clone(), cx);
+            cx.write_to_clipboard(item.clone());
+            self.active_editor.update(cx, |editor, cx| {
+                editor.paste_item(&item, cx);
+                editor.focus(cx);
+            });
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.clipboard_history_view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let text = self.items[*self.matches.get(ix)?].text();
+        let line_count = text.lines().count();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    h_flex()
+                        .w_full()
+                        .justify_between()
+                        .gap_2()
+                        .child(Label::new(item_label(text)).single_line())
+                        .when(line_count > 1, |this| {
+                            this.child(
+                                Label::new(format!("{line_count} lines"))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                            )
+                        }),
+                ),
+        )
+    }
+}
+
+/// The first non-blank line of the text, truncated to fit in the picker.
+fn item_label(text: &str) -> String {
+    let line = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or_default();
+    util::truncate_and_trailoff(line, LABEL_LEN)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_item_label() {
+        assert_eq!(item_label("\n    fn main() {\n    }\n"), "fn main() {");
+        assert_eq!(item_label("   "), "");
+        assert_eq!(item_label(&"a".repeat(100)).chars().count(), LABEL_LEN + 1);
+    }
+}
@@ -15,7 +15,7 @@ use editor::{
         editor_test_context::{AssertionContextManager, EditorTestContext},
         expanded_hunks, expanded_hunks_background_highlights,
     },
-    Editor,
+    Editor, RenamePreview,
 };
 use futures::StreamExt;
 use git::diff::DiffHunkStatus;
@@ -939,6 +939,13 @@ async fn test_collaborating_with_renames(cx_a: &mut TestAppContext, cx_b: &mut T
         .unwrap();
     confirm_rename.await.unwrap();
 
+    // Renames spanning several files are previewed before they're applied.
+    let rename_preview = workspace_b.update(cx_b, |workspace, cx| {
+        workspace.active_item_as::<RenamePreview>(cx).unwrap()
+    });
+    rename_preview.update(cx_b, |preview, cx| preview.apply(cx));
+    cx_b.run_until_parked();
+
     let rename_editor = workspace_b.update(cx_b, |workspace, cx| {
         workspace.active_item_as::<Editor>(cx).unwrap()
     });
@@ -7,7 +7,7 @@ use gpui::{
     InteractiveElement, IntoElement, Model, ParentElement, Path, Render,
     StatefulInteractiveElement, Styled, Subscription, ViewContext, VisualContext, WeakView,
 };
-use project::{Project, RepositoryEntry};
+use project::Project;
 use recent_projects::RecentProjects;
 use rpc::proto::{self, DevServerStatus};
 use std::sync::Arc;
@@ -488,20 +488,37 @@ impl CollabTitlebarItem {
     }
 
     pub fn render_project_branch(&self, cx: &mut ViewContext<Self>) -> Option<impl IntoElement> {
-        let entry = {
-            let mut names_and_branches =
-                self.project.read(cx).visible_worktrees(cx).map(|worktree| {
+        // Show the branch of the repository containing the active file, which may be nested in
+        // the worktree, like a submodule.
+        let (work_directory, entry) = {
+            let project = self.project.read(cx);
+            let active_repository =
+                project
+                    .active_git_repository(cx)
+                    .and_then(|(work_directory, _)| {
+                        let entry = project
+                            .worktree_for_id(work_directory.worktree_id, cx)?
+                            .read(cx)
+                            .repository_for_work_directory(&work_directory.path)?;
+                        Some((work_directory.path, entry))
+                    });
+            active_repository.or_else(|| {
+                let mut names_and_branches = project.visible_worktrees(cx).map(|worktree| {
                     let worktree = worktree.read(cx);
                     worktree.root_git_entry()
                 });
 
-            names_and_branches.next().flatten()
+                let entry = names_and_branches.next().flatten()?;
+                Some((std::path::Path::new("").into(), entry))
+            })?
         };
         let workspace = self.workspace.upgrade()?;
-        let branch_name = entry
-            .as_ref()
-            .and_then(RepositoryEntry::branch)
+        let mut branch_name = entry
+            .branch()
             .map(|branch| util::truncate_and_trailoff(&branch, MAX_BRANCH_NAME_LENGTH))?;
+        if let Some(repository_name) = work_directory.file_name() {
+            branch_name = format!("{} / {branch_name}", repository_name.to_string_lossy());
+        }
         Some(
             Button::new("project_branch_trigger", branch_name)
                 .color(Color::Muted)
@@ -511,7 +528,7 @@ impl CollabTitlebarItem {
                     Tooltip::with_meta(
                         "Recent Branches",
                         Some(&ToggleVcsMenu),
-                        "Local branches only",
+                        "Search to list remote branches",
                         cx,
                     )
                 })
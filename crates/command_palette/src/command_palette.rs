@@ -7,26 +7,35 @@ use std::{
 use client::{parse_zed_link, telemetry::Telemetry};
 use collections::HashMap;
 use command_palette_hooks::{
-    CommandInterceptResult, CommandPaletteFilter, CommandPaletteInterceptor,
+    CommandInterceptResult, CommandPaletteCompleter, CommandPaletteFilter,
+    CommandPaletteInterceptor,
 };
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    actions, Action, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Global,
-    ParentElement, Render, Styled, Task, UpdateGlobal, View, ViewContext, VisualContext, WeakView,
+    actions, Action, AppContext, DismissEvent, EntityId, EventEmitter, FocusHandle, FocusableView,
+    Global, ParentElement, Render, Styled, Task, UpdateGlobal, View, ViewContext, VisualContext,
+    WeakView,
 };
+use persistence::COMMAND_PALETTE_DB;
 use picker::{Picker, PickerDelegate};
 
 use postage::{sink::Sink, stream::Stream};
+use project::search_history::{QueryInsertionBehavior, SearchHistory, SearchHistoryCursor};
 use ui::{h_flex, prelude::*, v_flex, HighlightedLabel, KeyBinding, ListItem, ListItemSpacing};
 use util::ResultExt;
-use workspace::{ModalView, Workspace};
+use workspace::{searchable::Direction, ModalView, Workspace, WorkspaceId};
 use zed_actions::OpenZedUrl;
 
-actions!(command_palette, [Toggle]);
+mod persistence;
+
+actions!(command_palette, [Toggle, CompleteQuery]);
+
+const MAX_HISTORY_LEN: usize = 100;
 
 pub fn init(cx: &mut AppContext) {
     client::init_settings(cx);
     cx.set_global(HitCounts::default());
+    cx.set_global(CommandHistories::default());
     command_palette_hooks::init(cx);
     cx.observe_new_views(CommandPalette::register).detach();
 }
@@ -56,21 +65,46 @@ fn trim_consecutive_whitespaces(input: &str) -> String {
 }
 
 impl CommandPalette {
-    fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
         workspace.register_action(|workspace, _: &Toggle, cx| {
             let Some(previous_focus_handle) = cx.focused() else {
                 return;
             };
             let telemetry = workspace.client().telemetry().clone();
+            let history = CommandHistoryId {
+                workspace: cx.view().entity_id(),
+                database_id: workspace.database_id(),
+            };
             workspace.toggle_modal(cx, move |cx| {
-                CommandPalette::new(previous_focus_handle, telemetry, cx)
+                CommandPalette::new(previous_focus_handle, telemetry, history, cx)
             });
         });
+
+        if let Some(workspace_id) = workspace.database_id() {
+            let workspace = cx.view().entity_id();
+            cx.spawn(|_, mut cx| async move {
+                let queries = cx
+                    .background_executor()
+                    .spawn(async move { COMMAND_PALETTE_DB.get_command_history(workspace_id) })
+                    .await?;
+                cx.update(|cx| {
+                    CommandHistories::update_global(cx, |histories, _| {
+                        let history = histories.history_mut(workspace);
+                        let mut cursor = SearchHistoryCursor::default();
+                        for query in queries {
+                            history.add(&mut cursor, query);
+                        }
+                    })
+                })
+            })
+            .detach_and_log_err(cx);
+        }
     }
 
     fn new(
         previous_focus_handle: FocusHandle,
         telemetry: Arc<Telemetry>,
+        history: CommandHistoryId,
         cx: &mut ViewContext<Self>,
     ) -> Self {
         let filter = CommandPaletteFilter::try_global(cx);
@@ -95,11 +129,21 @@ impl CommandPalette {
             commands,
             telemetry,
             previous_focus_handle,
+            history,
         );
 
         let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
         Self { picker }
     }
+
+    fn complete_query(&mut self, _: &CompleteQuery, cx: &mut ViewContext<Self>) {
+        self.picker.update(cx, |picker, cx| {
+            let query = picker.query(cx);
+            if let Some(completion) = picker.delegate.complete(&query, cx) {
+                picker.set_query(completion, cx);
+            }
+        });
+    }
 }
 
 impl EventEmitter<DismissEvent> for CommandPalette {}
@@ -111,8 +155,12 @@ impl FocusableView for CommandPalette {
 }
 
 impl Render for CommandPalette {
-    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
-        v_flex().w(rems(34.)).child(self.picker.clone())
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("CommandPalette")
+            .on_action(cx.listener(Self::complete_query))
+            .w(rems(34.))
+            .child(self.picker.clone())
     }
 }
 
@@ -124,6 +172,11 @@ pub struct CommandPaletteDelegate {
     selected_ix: usize,
     telemetry: Arc<Telemetry>,
     previous_focus_handle: FocusHandle,
+    history: CommandHistoryId,
+    history_cursor: SearchHistoryCursor,
+    latest_query: String,
+    completions: Vec<String>,
+    completion_ix: usize,
     updating_matches: Option<(
         Task<()>,
         postage::dispatch::Receiver<(Vec<Command>, Vec<StringMatch>)>,
@@ -152,12 +205,35 @@ struct HitCounts(HashMap<String, usize>);
 
 impl Global for HitCounts {}
 
+/// The queries confirmed in the palette, for each workspace.
+#[derive(Default)]
+struct CommandHistories(HashMap<EntityId, SearchHistory>);
+
+impl Global for CommandHistories {}
+
+impl CommandHistories {
+    fn history_mut(&mut self, workspace: EntityId) -> &mut SearchHistory {
+        self.0.entry(workspace).or_insert_with(|| {
+            SearchHistory::new(Some(MAX_HISTORY_LEN), QueryInsertionBehavior::AlwaysInsert)
+        })
+    }
+}
+
+/// Identifies the workspace whose command history the palette uses.
+#[derive(Clone, Copy)]
+struct CommandHistoryId {
+    workspace: EntityId,
+    /// The workspace's id in the database, if the history should be persisted.
+    database_id: Option<WorkspaceId>,
+}
+
 impl CommandPaletteDelegate {
     fn new(
         command_palette: WeakView<CommandPalette>,
         commands: Vec<Command>,
         telemetry: Arc<Telemetry>,
         previous_focus_handle: FocusHandle,
+        history: CommandHistoryId,
     ) -> Self {
         Self {
             command_palette,
@@ -167,10 +243,59 @@ impl CommandPaletteDelegate {
             selected_ix: 0,
             telemetry,
             previous_focus_handle,
+            history,
+            history_cursor: SearchHistoryCursor::default(),
+            latest_query: String::new(),
+            completions: Vec::new(),
+            completion_ix: 0,
             updating_matches: None,
         }
     }
 
+    /// Returns the query to replace the given one with, cycling through the
+    /// completions when it is completed repeatedly.
+    fn complete(&mut self, query: &str, cx: &mut ViewContext<Picker<Self>>) -> Option<String> {
+        if self.completions.get(self.completion_ix).map(String::as_str) == Some(query) {
+            self.completion_ix = (self.completion_ix + 1) % self.completions.len();
+        } else {
+            self.completions = CommandPaletteCompleter::try_global(cx)
+                .map(|completer| completer.complete(query, cx))
+                .unwrap_or_default();
+            if self.completions.is_empty() {
+                let selected_command = self
+                    .matches
+                    .get(self.selected_ix)
+                    .and_then(|m| self.commands.get(m.candidate_id));
+                self.completions
+                    .extend(selected_command.map(|command| command.name.clone()));
+            }
+            self.completion_ix = 0;
+        }
+        self.completions.get(self.completion_ix).cloned()
+    }
+
+    fn add_to_history(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) {
+        if query.is_empty() {
+            return;
+        }
+        let queries = CommandHistories::update_global(cx, |histories, _| {
+            let history = histories.history_mut(self.history.workspace);
+            history.add(&mut self.history_cursor, query);
+            history.queries().to_vec()
+        });
+        let Some(workspace_id) = self.history.database_id else {
+            return;
+        };
+        cx.background_executor()
+            .spawn(async move {
+                COMMAND_PALETTE_DB
+                    .save_command_history(workspace_id, queries)
+                    .await
+                    .log_err()
+            })
+            .detach();
+    }
+
     fn matches_updated(
         &mut self,
         query: String,
@@ -251,6 +376,15 @@ impl PickerDelegate for CommandPaletteDelegate {
         query: String,
         cx: &mut ViewContext<Picker<Self>>,
     ) -> gpui::Task<()> {
+        let history = cx
+            .global::<CommandHistories>()
+            .0
+            .get(&self.history.workspace);
+        if history.and_then(|history| history.current(&self.history_cursor)) != Some(query.as_str())
+        {
+            self.history_cursor.reset();
+        }
+        self.latest_query = query.clone();
         let (mut tx, mut rx) = postage::dispatch::channel(1);
         let task = cx.background_executor().spawn({
             let mut commands = self.all_commands.clone();
@@ -356,6 +490,7 @@ impl PickerDelegate for CommandPaletteDelegate {
         }
         let action_ix = self.matches[self.selected_ix].candidate_id;
         let command = self.commands.swap_remove(action_ix);
+        self.add_to_history(self.latest_query.trim().to_string(), cx);
 
         self.telemetry
             .report_action_event("command palette", command.name.clone());
@@ -371,6 +506,35 @@ impl PickerDelegate for CommandPaletteDelegate {
         cx.dispatch_action(action);
     }
 
+    fn select_history(
+        &mut self,
+        direction: Direction,
+        query: &str,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<String> {
+        CommandHistories::update_global(cx, |histories, _| {
+            let history = histories.0.get_mut(&self.history.workspace)?;
+            if !query.is_empty() && history.current(&self.history_cursor) != Some(query) {
+                return None;
+            }
+            match direction {
+                Direction::Prev => history
+                    .previous(&mut self.history_cursor)
+                    .map(str::to_string),
+                Direction::Next => {
+                    if history.current(&self.history_cursor).is_none() {
+                        return None;
+                    }
+                    let next = history.next(&mut self.history_cursor).map(str::to_string);
+                    if next.is_none() {
+                        self.history_cursor.reset();
+                    }
+                    Some(next.unwrap_or_default())
+                }
+            }
+        })
+    }
+
     fn render_match(
         &self,
         ix: usize,
@@ -535,6 +699,50 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_command_palette_history_and_completion(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_text("abc", cx);
+            editor
+        });
+
+        workspace.update(cx, |workspace, cx| {
+            workspace.add_item_to_active_pane(Box::new(editor.clone()), None, cx);
+            editor.update(cx, |editor, cx| editor.focus(cx))
+        });
+
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("enter");
+
+        cx.simulate_keystrokes("cmd-shift-p");
+        cx.simulate_keystrokes("up");
+
+        let palette = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<CommandPalette>(cx)
+                .unwrap()
+                .read(cx)
+                .picker
+                .clone()
+        });
+        palette.update(cx, |palette, cx| assert_eq!(palette.query(cx), "bcksp"));
+
+        cx.simulate_keystrokes("down");
+        palette.update(cx, |palette, cx| assert_eq!(palette.query(cx), ""));
+
+        cx.simulate_input("bcksp");
+        cx.simulate_keystrokes("tab");
+        palette.update(cx, |palette, cx| {
+            assert_eq!(palette.query(cx), "editor: backspace")
+        });
+    }
+
     #[gpui::test]
     async fn test_go_to_line(cx: &mut TestAppContext) {
         let app_state = init_test(cx);
@@ -584,8 +792,16 @@ mod tests {
                         "bindings": {
                             "cmd-n": "workspace::NewFile",
                             "enter": "menu::Confirm",
+                            "up": "menu::SelectPrev",
+                            "down": "menu::SelectNext",
                             "cmd-shift-p": "command_palette::Toggle"
                         }
+                    },
+                    {
+                        "context": "CommandPalette > Picker > Editor",
+                        "bindings": {
+                            "tab": "command_palette::CompleteQuery"
+                        }
                     }
                 ]"#,
             )
@@ -0,0 +1,60 @@
+use anyhow::Context as _;
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    // Current schema shape using pseudo-rust syntax:
+    // command_history(
+    //   workspace_id: usize,
+    //   ix: usize,
+    //   query: String,
+    // )
+    pub static ref COMMAND_PALETTE_DB: CommandPaletteDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE command_history (
+                workspace_id INTEGER NOT NULL,
+                ix INTEGER NOT NULL,
+                query TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, ix),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl CommandPaletteDb {
+    query! {
+        pub fn get_command_history(workspace_id: WorkspaceId) -> Result<Vec<String>> {
+            SELECT query
+            FROM command_history
+            WHERE workspace_id = ?
+            ORDER BY ix
+        }
+    }
+
+    /// Replaces the command history stored for the given workspace.
+    pub async fn save_command_history(
+        &self,
+        workspace_id: WorkspaceId,
+        queries: Vec<String>,
+    ) -> anyhow::Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_command_history", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM command_history WHERE workspace_id = ?
+                ))?(workspace_id)
+                .context("clearing old command history")?;
+                for (ix, query) in queries.into_iter().enumerate() {
+                    conn.exec_bound(sql!(
+                        INSERT INTO command_history(workspace_id, ix, query)
+                        VALUES (?, ?, ?)
+                    ))?((workspace_id, ix, query))
+                    .context("inserting command history")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
@@ -12,6 +12,7 @@ use gpui::{Action, AppContext, BorrowAppContext, Global};
 pub fn init(cx: &mut AppContext) {
     cx.set_global(GlobalCommandPaletteFilter::default());
     cx.set_global(GlobalCommandPaletteInterceptor::default());
+    cx.set_global(GlobalCommandPaletteCompleter::default());
 }
 
 /// A filter for the command palette.
@@ -140,3 +141,49 @@ impl CommandPaletteInterceptor {
         self.0 = Some(handler);
     }
 }
+
+/// A completer for the command palette.
+#[derive(Default)]
+pub struct CommandPaletteCompleter(Option<Box<dyn Fn(&str, &AppContext) -> Vec<String>>>);
+
+#[derive(Default)]
+struct GlobalCommandPaletteCompleter(CommandPaletteCompleter);
+
+impl Global for GlobalCommandPaletteCompleter {}
+
+impl CommandPaletteCompleter {
+    /// Returns the global [`CommandPaletteCompleter`], if one is set.
+    pub fn try_global(cx: &AppContext) -> Option<&CommandPaletteCompleter> {
+        cx.try_global::<GlobalCommandPaletteCompleter>()
+            .map(|completer| &completer.0)
+    }
+
+    /// Updates the global [`CommandPaletteCompleter`] using the given closure.
+    pub fn update_global<F, R>(cx: &mut AppContext, update: F) -> R
+    where
+        F: FnOnce(&mut Self, &mut AppContext) -> R,
+    {
+        cx.update_global(|this: &mut GlobalCommandPaletteCompleter, cx| update(&mut this.0, cx))
+    }
+
+    /// Returns the completed queries for the given query from the command palette.
+    pub fn complete(&self, query: &str, cx: &AppContext) -> Vec<String> {
+        let Some(handler) = self.0.as_ref() else {
+            return Vec::new();
+        };
+
+        (handler)(query, cx)
+    }
+
+    /// Clears the global completer.
+    pub fn clear(&mut self) {
+        self.0 = None;
+    }
+
+    /// Sets the global completer.
+    ///
+    /// This will override the previous completer, if it exists.
+    pub fn set(&mut self, handler: Box<dyn Fn(&str, &AppContext) -> Vec<String>>) {
+        self.0 = Some(handler);
+    }
+}
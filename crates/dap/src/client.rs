@@ -0,0 +1,549 @@
+use anyhow::{anyhow, Context, Result};
+use collections::HashMap;
+use futures::{channel::oneshot, io::BufWriter, AsyncRead, AsyncWrite, FutureExt};
+use gpui::{AsyncAppContext, BackgroundExecutor, Task};
+use parking_lot::Mutex;
+use serde_json::json;
+use smol::{
+    channel,
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    process::{self, Child},
+};
+use std::{
+    path::PathBuf,
+    process::Stdio,
+    sync::{
+        atomic::{AtomicU64, Ordering::SeqCst},
+        Arc,
+    },
+    time::Duration,
+};
+use util::ResultExt;
+
+use crate::{
+    requests::{Disconnect, Initialize, Request},
+    types::{
+        Capabilities, DisconnectArguments, EventMessage, Events, InitializeRequestArguments,
+        Message, RequestMessage, ResponseMessage,
+    },
+};
+
+const CONTENT_LEN_HEADER: &str = "Content-Length: ";
+const HEADER_DELIMITER: &[u8; 4] = b"\r\n\r\n";
+
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(2);
+
+type ResponseHandlers = Arc<Mutex<Option<HashMap<u64, oneshot::Sender<ResponseMessage>>>>>;
+
+/// Identifies a running debug adapter.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DebugAdapterClientId(pub usize);
+
+/// The command that spawns a debug adapter, which then talks the debug adapter protocol over
+/// its stdio.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DebugAdapterBinary {
+    pub command: String,
+    pub args: Vec<String>,
+    pub env: HashMap<String, String>,
+    pub cwd: Option<PathBuf>,
+}
+
+/// A running debug adapter process.
+pub struct DebugAdapterClient {
+    id: DebugAdapterClientId,
+    name: String,
+    next_sequence_id: Arc<AtomicU64>,
+    outbound_tx: channel::Sender<String>,
+    response_handlers: ResponseHandlers,
+    capabilities: Mutex<Capabilities>,
+    executor: BackgroundExecutor,
+    #[allow(dead_code)]
+    io_tasks: (Task<Option<()>>, Task<Option<()>>),
+    process: Mutex<Option<Child>>,
+}
+
+impl DebugAdapterClient {
+    /// Starts a debug adapter process. The events it sends are passed to `on_event`.
+    pub fn start<F>(
+        id: DebugAdapterClientId,
+        name: String,
+        binary: DebugAdapterBinary,
+        on_event: F,
+        cx: &AsyncAppContext,
+    ) -> Result<Self>
+    where
+        F: FnMut(Events) + Send + 'static,
+    {
+        log::info!(
+            "starting debug adapter. command: {:?}, working directory: {:?}, args: {:?}",
+            binary.command,
+            binary.cwd,
+            binary.args
+        );
+
+        let mut command = process::Command::new(&binary.command);
+        command
+            .args(&binary.args)
+            .envs(binary.env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(cwd) = &binary.cwd {
+            command.current_dir(cwd);
+        }
+        let mut process = command.spawn().with_context(|| {
+            format!(
+                "failed to spawn debug adapter. command: {:?}, args: {:?}",
+                binary.command, binary.args
+            )
+        })?;
+
+        let stdin = process.stdin.take().unwrap();
+        let stdout = process.stdout.take().unwrap();
+        let stderr = process.stderr.take().unwrap();
+        cx.background_executor()
+            .spawn(Self::handle_stderr(stderr).log_err())
+            .detach();
+
+        Ok(Self::new_internal(
+            id,
+            name,
+            stdin,
+            stdout,
+            Some(process),
+            on_event,
+            cx.background_executor(),
+        ))
+    }
+
+    fn new_internal<Stdin, Stdout, F>(
+        id: DebugAdapterClientId,
+        name: String,
+        stdin: Stdin,
+        stdout: Stdout,
+        process: Option<Child>,
+        on_event: F,
+        executor: &BackgroundExecutor,
+    ) -> Self
+    where
+        Stdin: AsyncWrite + Unpin + Send + 'static,
+        Stdout: AsyncRead + Unpin + Send + 'static,
+        F: FnMut(Events) + Send + 'static,
+    {
+        let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
+        let response_handlers = Arc::new(Mutex::new(Some(HashMap::default())));
+        let next_sequence_id = Arc::new(AtomicU64::new(1));
+
+        let input_task = executor.spawn(
+            Self::handle_input(
+                stdout,
+                on_event,
+                response_handlers.clone(),
+                outbound_tx.clone(),
+                next_sequence_id.clone(),
+            )
+            .log_err(),
+        );
+        let output_task = executor.spawn(write_messages(stdin, outbound_rx).log_err());
+
+        Self {
+            id,
+            name,
+            next_sequence_id,
+            outbound_tx,
+            response_handlers,
+            capabilities: Default::default(),
+            executor: executor.clone(),
+            io_tasks: (input_task, output_task),
+            process: Mutex::new(process),
+        }
+    }
+
+    async fn handle_input<Stdout, F>(
+        stdout: Stdout,
+        mut on_event: F,
+        response_handlers: ResponseHandlers,
+        outbound_tx: channel::Sender<String>,
+        next_sequence_id: Arc<AtomicU64>,
+    ) -> Result<()>
+    where
+        Stdout: AsyncRead + Unpin + Send + 'static,
+        F: FnMut(Events) + Send + 'static,
+    {
+        let _clear_response_handlers = util::defer({
+            let response_handlers = response_handlers.clone();
+            move || {
+                response_handlers.lock().take();
+            }
+        });
+        let mut stdout = BufReader::new(stdout);
+        let mut buffer = Vec::new();
+        loop {
+            match read_message(&mut stdout, &mut buffer).await? {
+                Message::Response(response) => {
+                    let handler = response_handlers
+                        .lock()
+                        .as_mut()
+                        .and_then(|handlers| handlers.remove(&response.request_seq));
+                    if let Some(handler) = handler {
+                        handler.send(response).ok();
+                    } else {
+                        log::warn!("unhandled debug adapter response {}", response.request_seq);
+                    }
+                }
+                Message::Event(event) => on_event(Events::from_message(event)),
+                Message::Request(request) => {
+                    // Reverse requests, like `runInTerminal`, aren't supported yet.
+                    let response = Message::Response(ResponseMessage {
+                        seq: next_sequence_id.fetch_add(1, SeqCst),
+                        request_seq: request.seq,
+                        success: false,
+                        command: request.command.clone(),
+                        message: Some(format!("unsupported request {}", request.command)),
+                        body: None,
+                    });
+                    outbound_tx.send(serde_json::to_string(&response)?).await?;
+                }
+            }
+
+            // Don't starve the main thread when receiving lots of messages at once.
+            smol::future::yield_now().await;
+        }
+    }
+
+    async fn handle_stderr<Stderr>(stderr: Stderr) -> Result<()>
+    where
+        Stderr: AsyncRead + Unpin + Send + 'static,
+    {
+        let mut stderr = BufReader::new(stderr);
+        let mut buffer = String::new();
+        loop {
+            buffer.clear();
+            if stderr.read_line(&mut buffer).await? == 0 {
+                return Ok(());
+            }
+            log::debug!("debug adapter stderr: {}", buffer.trim_end());
+        }
+    }
+
+    pub fn id(&self) -> DebugAdapterClientId {
+        self.id
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The features the debug adapter reported supporting when it was initialized.
+    pub fn capabilities(&self) -> Capabilities {
+        self.capabilities.lock().clone()
+    }
+
+    /// Sends a request to the debug adapter, and waits for its response.
+    pub async fn request<R: Request>(&self, arguments: R::Arguments) -> Result<R::Response> {
+        let seq = self.next_sequence_id.fetch_add(1, SeqCst);
+        let message = serde_json::to_string(&Message::Request(RequestMessage {
+            seq,
+            command: R::COMMAND.to_string(),
+            arguments: Some(serde_json::to_value(arguments)?)
+                .filter(|arguments| !arguments.is_null()),
+        }))?;
+
+        let (tx, rx) = oneshot::channel();
+        self.response_handlers
+            .lock()
+            .as_mut()
+            .context("debug adapter has shut down")?
+            .insert(seq, tx);
+        self.outbound_tx.send(message).await?;
+
+        let response = rx
+            .await
+            .with_context(|| format!("debug adapter shut down before answering {}", R::COMMAND))?;
+        if response.success {
+            let body = response.body.unwrap_or_else(|| json!({}));
+            serde_json::from_value(body)
+                .with_context(|| format!("invalid response to {}", R::COMMAND))
+        } else {
+            Err(anyhow!(
+                "{} failed: {}",
+                R::COMMAND,
+                response.message.as_deref().unwrap_or("unknown error")
+            ))
+        }
+    }
+
+    /// Sends the `initialize` request, and records the capabilities of the debug adapter.
+    pub async fn initialize(&self) -> Result<Capabilities> {
+        let capabilities = self
+            .request::<Initialize>(InitializeRequestArguments {
+                client_id: Some("zed".to_string()),
+                client_name: Some("Zed".to_string()),
+                adapter_id: self.name.clone(),
+                lines_start_at1: Some(true),
+                columns_start_at1: Some(true),
+                path_format: Some("path".to_string()),
+                supports_variable_type: Some(true),
+            })
+            .await?;
+        *self.capabilities.lock() = capabilities.clone();
+        Ok(capabilities)
+    }
+
+    /// Asks the debug adapter to end the debug session and terminate the debuggee, then kills
+    /// the debug adapter process.
+    pub async fn shutdown(&self) -> Result<()> {
+        let disconnect = self.request::<Disconnect>(DisconnectArguments {
+            restart: Some(false),
+            terminate_debuggee: Some(true),
+        });
+        let timeout = self.executor.timer(SHUTDOWN_TIMEOUT);
+        let result = futures::select! {
+            result = disconnect.fuse() => result.map(|_| ()),
+            _ = timeout.fuse() => Err(anyhow!("timed out disconnecting from debug adapter {}", self.name)),
+        };
+        self.response_handlers.lock().take();
+        self.process.lock().take();
+        result
+    }
+}
+
+async fn read_message<Stdout>(
+    reader: &mut BufReader<Stdout>,
+    buffer: &mut Vec<u8>,
+) -> Result<Message>
+where
+    Stdout: AsyncRead + Unpin + Send + 'static,
+{
+    buffer.clear();
+    loop {
+        if buffer.len() >= HEADER_DELIMITER.len()
+            && buffer[(buffer.len() - HEADER_DELIMITER.len())..] == HEADER_DELIMITER[..]
+        {
+            break;
+        }
+        if reader.read_until(b'\n', buffer).await? == 0 {
+            return Err(anyhow!("cannot read debug adapter message headers"));
+        }
+    }
+
+    let headers = std::str::from_utf8(buffer)?;
+    let message_len = headers
+        .split('\n')
+        .find_map(|line| line.strip_prefix(CONTENT_LEN_HEADER))
+        .ok_or_else(|| anyhow!("invalid debug adapter message header {headers:?}"))?
+        .trim_end()
+        .parse()?;
+
+    buffer.resize(message_len, 0);
+    reader.read_exact(buffer).await?;
+    log::trace!(
+        "incoming debug adapter message: {}",
+        String::from_utf8_lossy(buffer)
+    );
+    serde_json::from_slice(buffer).context("invalid debug adapter message")
+}
+
+async fn write_messages<Stdin>(stdin: Stdin, outbound_rx: channel::Receiver<String>) -> Result<()>
+where
+    Stdin: AsyncWrite + Unpin + Send + 'static,
+{
+    let mut stdin = BufWriter::new(stdin);
+    while let Ok(message) = outbound_rx.recv().await {
+        log::trace!("outgoing debug adapter message: {}", message);
+        stdin
+            .write_all(format!("{CONTENT_LEN_HEADER}{}\r\n\r\n", message.len()).as_bytes())
+            .await?;
+        stdin.write_all(message.as_bytes()).await?;
+        stdin.flush().await?;
+    }
+    Ok(())
+}
+
+#[cfg(any(test, feature = "test-support"))]
+type FakeRequestHandler = Box<dyn Send + FnMut(serde_json::Value) -> Result<serde_json::Value>>;
+
+/// Mock debug adapter for use in tests.
+#[cfg(any(test, feature = "test-support"))]
+pub struct FakeDebugAdapter {
+    request_handlers: Arc<Mutex<HashMap<&'static str, FakeRequestHandler>>>,
+    outbound_tx: channel::Sender<String>,
+    next_sequence_id: AtomicU64,
+    #[allow(dead_code)]
+    io_tasks: (Task<Option<()>>, Task<Option<()>>),
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl DebugAdapterClient {
+    /// Creates a client talking to a fake debug adapter, which answers the requests it has
+    /// handlers for.
+    pub fn fake<F>(
+        id: DebugAdapterClientId,
+        name: String,
+        on_event: F,
+        executor: &BackgroundExecutor,
+    ) -> (Self, FakeDebugAdapter)
+    where
+        F: FnMut(Events) + Send + 'static,
+    {
+        let (stdin_writer, stdin_reader) = async_pipe::pipe();
+        let (stdout_writer, stdout_reader) = async_pipe::pipe();
+        let client = Self::new_internal(
+            id,
+            name,
+            stdin_writer,
+            stdout_reader,
+            None,
+            on_event,
+            executor,
+        );
+
+        let (outbound_tx, outbound_rx) = channel::unbounded::<String>();
+        let request_handlers = Arc::new(Mutex::new(
+            HashMap::<&'static str, FakeRequestHandler>::default(),
+        ));
+        let input_task = executor.spawn({
+            let request_handlers = request_handlers.clone();
+            let outbound_tx = outbound_tx.clone();
+            async move {
+                let mut stdin = BufReader::new(stdin_reader);
+                let mut buffer = Vec::new();
+                let mut seq = 0;
+                loop {
+                    let Message::Request(request) = read_message(&mut stdin, &mut buffer).await?
+                    else {
+                        continue;
+                    };
+                    let handler = request_handlers
+                        .lock()
+                        .get_mut(request.command.as_str())
+                        .map(|handler| handler(request.arguments.unwrap_or_default()));
+                    let result = handler
+                        .unwrap_or_else(|| Err(anyhow!("unhandled request {}", request.command)));
+                    seq += 1;
+                    let response = Message::Response(ResponseMessage {
+                        seq,
+                        request_seq: request.seq,
+                        success: result.is_ok(),
+                        command: request.command,
+                        message: result.as_ref().err().map(|error| error.to_string()),
+                        body: result.ok(),
+                    });
+                    outbound_tx.send(serde_json::to_string(&response)?).await?;
+                }
+            }
+            .log_err()
+        });
+        let output_task = executor.spawn(write_messages(stdout_writer, outbound_rx).log_err());
+
+        let fake = FakeDebugAdapter {
+            request_handlers,
+            outbound_tx,
+            next_sequence_id: AtomicU64::new(1_000_000),
+            io_tasks: (input_task, output_task),
+        };
+        fake.handle_request::<Initialize, _>(|_| Ok(Capabilities::default()));
+        fake.handle_request::<Disconnect, _>(|_| Ok(serde_json::Value::Null));
+        (client, fake)
+    }
+
+    /// Overrides the capabilities recorded for the debug adapter, as if it had been initialized.
+    pub fn set_capabilities(&self, capabilities: Capabilities) {
+        *self.capabilities.lock() = capabilities;
+    }
+}
+
+#[cfg(any(test, feature = "test-support"))]
+impl FakeDebugAdapter {
+    /// Registers a handler answering the requests of the given type.
+    pub fn handle_request<R, F>(&self, mut handler: F)
+    where
+        R: Request,
+        R::Arguments: serde::de::DeserializeOwned,
+        R::Response: serde::Serialize,
+        F: 'static + Send + FnMut(R::Arguments) -> Result<R::Response>,
+    {
+        self.request_handlers.lock().insert(
+            R::COMMAND,
+            Box::new(move |arguments| {
+                let arguments = serde_json::from_value(arguments)?;
+                Ok(serde_json::to_value(handler(arguments)?)?)
+            }),
+        );
+    }
+
+    /// Sends an event to the client.
+    pub fn send_event(&self, event: &str, body: Option<serde_json::Value>) {
+        let message = Message::Event(EventMessage {
+            seq: self.next_sequence_id.fetch_add(1, SeqCst),
+            event: event.to_string(),
+            body,
+        });
+        self.outbound_tx
+            .try_send(serde_json::to_string(&message).unwrap())
+            .ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        requests::{Continue, Threads},
+        types::{ContinueResponse, Thread, ThreadArguments, ThreadsResponse},
+    };
+    use gpui::TestAppContext;
+
+    #[gpui::test]
+    async fn test_fake_debug_adapter(cx: &mut TestAppContext) {
+        let (events_tx, events_rx) = channel::unbounded();
+        let (client, fake) = DebugAdapterClient::fake(
+            DebugAdapterClientId(0),
+            "fake".to_string(),
+            move |event| {
+                events_tx.try_send(event).ok();
+            },
+            &cx.executor(),
+        );
+        fake.handle_request::<Threads, _>(|_| {
+            Ok(ThreadsResponse {
+                threads: vec![Thread {
+                    id: 1,
+                    name: "main".to_string(),
+                }],
+            })
+        });
+
+        client.initialize().await.unwrap();
+        let threads = client.request::<Threads>(json!({})).await.unwrap();
+        assert_eq!(threads.threads[0].name, "main");
+
+        let error = client
+            .request::<Continue>(ThreadArguments { thread_id: 1 })
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "continue failed: unhandled request continue"
+        );
+        fake.handle_request::<Continue, _>(|_| Ok(ContinueResponse::default()));
+        client
+            .request::<Continue>(ThreadArguments { thread_id: 1 })
+            .await
+            .unwrap();
+
+        fake.send_event(
+            "stopped",
+            Some(json!({ "reason": "breakpoint", "threadId": 1 })),
+        );
+        match events_rx.recv().await.unwrap() {
+            Events::Stopped(event) => {
+                assert_eq!(event.reason, "breakpoint");
+                assert_eq!(event.thread_id, Some(1));
+            }
+            event => panic!("unexpected event {event:?}"),
+        }
+    }
+}
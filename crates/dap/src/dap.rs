@@ -0,0 +1,10 @@
+//! A client for the [Debug Adapter Protocol](https://microsoft.github.io/debug-adapter-protocol/),
+//! which Zed uses to drive debuggers.
+
+pub mod client;
+pub mod requests;
+pub mod types;
+
+#[cfg(any(test, feature = "test-support"))]
+pub use client::FakeDebugAdapter;
+pub use client::{DebugAdapterBinary, DebugAdapterClient, DebugAdapterClientId};
@@ -0,0 +1,50 @@
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::types::*;
+
+/// A request sent to a debug adapter, with the types of its arguments and of its response body.
+///
+/// [DAP Specification](https://microsoft.github.io/debug-adapter-protocol/specification#Requests)
+pub trait Request {
+    type Arguments: Serialize;
+    type Response: DeserializeOwned;
+    const COMMAND: &'static str;
+}
+
+macro_rules! requests {
+    ($($name:ident($command:literal, $arguments:ty) -> $response:ty;)*) => {
+        $(
+            pub enum $name {}
+
+            impl Request for $name {
+                type Arguments = $arguments;
+                type Response = $response;
+                const COMMAND: &'static str = $command;
+            }
+        )*
+    };
+}
+
+requests! {
+    Initialize("initialize", InitializeRequestArguments) -> Capabilities;
+    Launch("launch", Value) -> Value;
+    Attach("attach", Value) -> Value;
+    ConfigurationDone("configurationDone", Value) -> Value;
+    Disconnect("disconnect", DisconnectArguments) -> Value;
+    Terminate("terminate", TerminateArguments) -> Value;
+    SetBreakpoints("setBreakpoints", SetBreakpointsArguments) -> SetBreakpointsResponse;
+    SetExceptionBreakpoints("setExceptionBreakpoints", SetExceptionBreakpointsArguments) -> Value;
+    Threads("threads", Value) -> ThreadsResponse;
+    StackTrace("stackTrace", StackTraceArguments) -> StackTraceResponse;
+    Continue("continue", ThreadArguments) -> ContinueResponse;
+    Pause("pause", ThreadArguments) -> Value;
+    Next("next", ThreadArguments) -> Value;
+    StepIn("stepIn", ThreadArguments) -> Value;
+    StepOut("stepOut", ThreadArguments) -> Value;
+    Scopes("scopes", ScopesArguments) -> ScopesResponse;
+    Variables("variables", VariablesArguments) -> VariablesResponse;
+    Evaluate("evaluate", EvaluateArguments) -> EvaluateResponse;
+    SetVariable("setVariable", SetVariableArguments) -> SetVariableResponse;
+    Completions("completions", CompletionsArguments) -> CompletionsResponse;
+}
@@ -0,0 +1,185 @@
+use editor::{
+    actions::{EditBreakpointCondition, EditBreakpointHitCount, EditBreakpointLogMessage},
+    Editor, EditorEvent,
+};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    Subscription, View, ViewContext, VisualContext, WeakView,
+};
+use language::Buffer;
+use project::dap_store::{BreakpointProperty, DapStore};
+use ui::prelude::*;
+use workspace::ModalView;
+
+/// A modal editing the condition, hit count or log message of the breakpoint on a line.
+pub struct BreakpointModal {
+    property_editor: View<Editor>,
+    dap_store: Model<DapStore>,
+    buffer: Model<Buffer>,
+    row: u32,
+    property: BreakpointProperty,
+    _subscription: Subscription,
+}
+
+impl ModalView for BreakpointModal {}
+
+impl FocusableView for BreakpointModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.property_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for BreakpointModal {}
+
+impl BreakpointModal {
+    /// Registers the actions editing the breakpoint under the cursor of the editor.
+    pub(crate) fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        let handle = cx.view().downgrade();
+        editor
+            .register_action({
+                let handle = handle.clone();
+                move |_: &EditBreakpointCondition, cx| {
+                    Self::toggle(&handle, BreakpointProperty::Condition, cx)
+                }
+            })
+            .register_action({
+                let handle = handle.clone();
+                move |_: &EditBreakpointHitCount, cx| {
+                    Self::toggle(&handle, BreakpointProperty::HitCondition, cx)
+                }
+            })
+            .register_action(move |_: &EditBreakpointLogMessage, cx| {
+                Self::toggle(&handle, BreakpointProperty::LogMessage, cx)
+            });
+    }
+
+    fn toggle(editor: &WeakView<Editor>, property: BreakpointProperty, cx: &mut WindowContext) {
+        let Some(editor) = editor.upgrade() else {
+            return;
+        };
+        let Some(workspace) = editor.read(cx).workspace() else {
+            return;
+        };
+        let Some((buffer, row)) = editor.read(cx).breakpoint_location(cx) else {
+            return;
+        };
+        let dap_store = workspace.read(cx).project().read(cx).dap_store().clone();
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, move |cx| {
+                Self::new(dap_store, buffer, row, property, cx)
+            });
+        });
+    }
+
+    fn new(
+        dap_store: Model<DapStore>,
+        buffer: Model<Buffer>,
+        row: u32,
+        property: BreakpointProperty,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let abs_path = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| file.as_local())
+            .map(|file| file.abs_path(cx));
+        let value = abs_path
+            .and_then(|abs_path| {
+                dap_store
+                    .read(cx)
+                    .breakpoint_at(&abs_path, row, cx)?
+                    .property(property)
+                    .map(ToString::to_string)
+            })
+            .unwrap_or_default();
+
+        let property_editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_placeholder_text(
+                match property {
+                    BreakpointProperty::Condition => "Expression, like `x > 3`",
+                    BreakpointProperty::HitCondition => "Hit count, like `>= 5`",
+                    BreakpointProperty::LogMessage => "Message, like `x is {x}`",
+                },
+                cx,
+            );
+            editor.set_text(value, cx);
+            editor.select_all(&editor::actions::SelectAll, cx);
+            editor
+        });
+        let subscription = cx.subscribe(&property_editor, |_, _, event: &EditorEvent, cx| {
+            if let EditorEvent::Blurred = event {
+                cx.emit(DismissEvent);
+            }
+        });
+
+        Self {
+            property_editor,
+            dap_store,
+            buffer,
+            row,
+            property,
+            _subscription: subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let value = self.property_editor.read(cx).text(cx);
+        self.dap_store.update(cx, |dap_store, cx| {
+            dap_store.set_breakpoint_property(
+                &self.buffer,
+                self.row,
+                self.property,
+                Some(value),
+                cx,
+            )
+        });
+        cx.emit(DismissEvent);
+    }
+}
+
+impl Render for BreakpointModal {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let title = match self.property {
+            BreakpointProperty::Condition => "Condition",
+            BreakpointProperty::HitCondition => "Hit Count",
+            BreakpointProperty::LogMessage => "Log Message",
+        };
+        let help_text = match self.property {
+            BreakpointProperty::Condition => "Stop only when the expression is true",
+            BreakpointProperty::HitCondition => "Stop only when the hit count matches",
+            BreakpointProperty::LogMessage => "Log the message instead of stopping",
+        };
+
+        v_flex()
+            .elevation_2(cx)
+            .key_context("BreakpointModal")
+            .on_action(cx.listener(Self::cancel))
+            .on_action(cx.listener(Self::confirm))
+            .w_96()
+            .p_2()
+            .gap_1()
+            .child(Label::new(format!(
+                "{title} of the breakpoint on line {}",
+                self.row + 1
+            )))
+            .child(
+                div()
+                    .px_1()
+                    .py_0p5()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .rounded_md()
+                    .child(self.property_editor.clone()),
+            )
+            .child(
+                Label::new(format!("{help_text}. Leave it empty to clear it."))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            )
+    }
+}
@@ -0,0 +1,405 @@
+use crate::DebugConsoleSettings;
+use anyhow::Result;
+use dap::DebugAdapterClientId;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{CompletionProvider, Editor};
+use gpui::{
+    actions, Action, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView,
+    Model, Pixels, Render, ScrollHandle, Subscription, Task, View, ViewContext, WeakView,
+    WindowContext,
+};
+use language::{
+    Buffer, CodeLabel, Documentation, LanguageServerId, PointUtf16, ToOffset, ToPointUtf16,
+};
+use parking_lot::RwLock;
+use project::{
+    dap_store::{ConsoleEntryKind, DapStore, DapStoreEvent},
+    Completion, Fs,
+};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::sync::Arc;
+use theme::ThemeSettings;
+use ui::{prelude::*, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    Workspace,
+};
+
+actions!(debug_console, [ToggleFocus, Clear]);
+
+const DEBUG_CONSOLE_KEY: &str = "DebugConsole";
+
+/// A panel streaming the output of the active debug session, where expressions can be evaluated
+/// in its selected stack frame.
+pub struct DebugConsole {
+    dap_store: Model<DapStore>,
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+    focus_handle: FocusHandle,
+    query_editor: View<Editor>,
+    scroll_handle: ScrollHandle,
+    /// How many entries the console of the active session had when last rendered, to scroll to
+    /// the new ones.
+    rendered_entry_count: Option<(DebugAdapterClientId, usize)>,
+    pending_serialization: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedDebugConsole {
+    width: Option<Pixels>,
+    height: Option<Pixels>,
+}
+
+impl DebugConsole {
+    pub fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let dap_store = workspace.project().read(cx).dap_store().clone();
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            let query_editor = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Evaluate an expression", cx);
+                editor.set_completion_provider(Box::new(ConsoleCompletionProvider {
+                    dap_store: dap_store.clone(),
+                }));
+                editor
+            });
+            let subscriptions = vec![
+                cx.observe(&dap_store, |_, _, cx| cx.notify()),
+                cx.subscribe(&dap_store, |_, _, event, cx| {
+                    if let DapStoreEvent::SessionStarted(_) = event {
+                        cx.emit(PanelEvent::Activate);
+                    }
+                }),
+            ];
+
+            Self {
+                dap_store,
+                fs,
+                width: None,
+                height: None,
+                focus_handle: cx.focus_handle(),
+                query_editor,
+                scroll_handle: ScrollHandle::new(),
+                rendered_entry_count: None,
+                pending_serialization: Task::ready(None),
+                _subscriptions: subscriptions,
+            }
+        })
+    }
+
+    pub fn load(
+        workspace: WeakView<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<View<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let serialized_console = if let Some(console) = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(DEBUG_CONSOLE_KEY) })
+                .await
+                .log_err()
+                .flatten()
+            {
+                Some(serde_json::from_str::<SerializedDebugConsole>(&console)?)
+            } else {
+                None
+            };
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let console = Self::new(workspace, cx);
+                if let Some(serialized_console) = serialized_console {
+                    console.update(cx, |console, cx| {
+                        console.width = serialized_console.width.map(|w| w.round());
+                        console.height = serialized_console.height.map(|h| h.round());
+                        cx.notify();
+                    });
+                }
+                console
+            })
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        let height = self.height;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        DEBUG_CONSOLE_KEY.into(),
+                        serde_json::to_string(&SerializedDebugConsole { width, height })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        let expression = self.query_editor.read(cx).text(cx);
+        if expression.trim().is_empty() {
+            return;
+        }
+        let dap_store = self.dap_store.clone();
+        let Some(id) = dap_store.read(cx).active_session().map(|(id, _)| id) else {
+            return;
+        };
+        dap_store
+            .update(cx, |dap_store, cx| {
+                dap_store.evaluate_in_console(id, expression, cx)
+            })
+            .detach_and_log_err(cx);
+        self.query_editor
+            .update(cx, |editor, cx| editor.set_text("", cx));
+    }
+
+    fn clear(&mut self, _: &Clear, cx: &mut ViewContext<Self>) {
+        let dap_store = self.dap_store.clone();
+        if let Some(id) = dap_store.read(cx).active_session().map(|(id, _)| id) {
+            dap_store.update(cx, |dap_store, cx| dap_store.clear_console(id, cx));
+        }
+    }
+}
+
+impl Render for DebugConsole {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let dap_store = self.dap_store.read(cx);
+        let active_session = dap_store.active_session();
+        let entries = active_session
+            .map(|(_, session)| session.console().to_vec())
+            .unwrap_or_default();
+
+        let entry_count = active_session.map(|(id, _)| (id, entries.len()));
+        if entry_count != self.rendered_entry_count {
+            self.rendered_entry_count = entry_count;
+            if !entries.is_empty() {
+                self.scroll_handle.scroll_to_item(entries.len() - 1);
+            }
+        }
+
+        let has_session = active_session.is_some();
+        let lines = v_flex()
+            .id("debug-console-entries")
+            .flex_1()
+            .min_h_0()
+            .px_2()
+            .py_1()
+            .overflow_y_scroll()
+            .track_scroll(&self.scroll_handle)
+            .font_family(ThemeSettings::get_global(cx).buffer_font.family.clone())
+            .children(entries.into_iter().map(|entry| {
+                let text = entry.text.trim_end_matches('\n');
+                let (prefix, color) = match entry.kind {
+                    ConsoleEntryKind::Output => ("", Color::Default),
+                    ConsoleEntryKind::Stderr => ("", Color::Error),
+                    ConsoleEntryKind::Input => ("> ", Color::Muted),
+                    ConsoleEntryKind::Result => ("", Color::Accent),
+                    ConsoleEntryKind::Error => ("", Color::Error),
+                };
+                v_flex().children(text.lines().enumerate().map(|(ix, line)| {
+                    let prefix = if ix == 0 { prefix } else { "" };
+                    Label::new(format!("{prefix}{line}"))
+                        .size(LabelSize::Small)
+                        .color(color)
+                }))
+            }))
+            .when(!has_session, |this| {
+                this.child(
+                    Label::new("Start a debug session to see its output here.").color(Color::Muted),
+                )
+            });
+
+        v_flex()
+            .key_context("DebugConsole")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::clear))
+            .size_full()
+            .child(lines)
+            .child(
+                h_flex()
+                    .px_2()
+                    .py_1()
+                    .gap_2()
+                    .border_t_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Label::new(">").color(Color::Muted))
+                    .child(div().flex_1().child(self.query_editor.clone()))
+                    .child(
+                        IconButton::new("clear-debug-console", IconName::Delete)
+                            .icon_size(IconSize::Small)
+                            .disabled(!has_session)
+                            .tooltip(|cx| Tooltip::for_action("Clear Console", &Clear, cx))
+                            .on_click(|_, cx| cx.dispatch_action(Clear.boxed_clone())),
+                    ),
+            )
+    }
+}
+
+impl FocusableView for DebugConsole {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.query_editor.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<PanelEvent> for DebugConsole {}
+
+impl Panel for DebugConsole {
+    fn persistent_name() -> &'static str {
+        "DebugConsole"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        DebugConsoleSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, _: DockPosition) -> bool {
+        true
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<DebugConsoleSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings| settings.dock = Some(position),
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        let settings = DebugConsoleSettings::get_global(cx);
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => {
+                self.width.unwrap_or_else(|| settings.default_width)
+            }
+            DockPosition::Bottom => self.height.unwrap_or_else(|| settings.default_height),
+        }
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        match self.position(cx) {
+            DockPosition::Left | DockPosition::Right => self.width = size,
+            DockPosition::Bottom => self.height = size,
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        DebugConsoleSettings::get_global(cx)
+            .button
+            .then_some(IconName::Code)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Debug Console")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+/// Completes the expressions of the console with the completions of the debug adapter of the
+/// active session.
+struct ConsoleCompletionProvider {
+    dap_store: Model<DapStore>,
+}
+
+impl CompletionProvider for ConsoleCompletionProvider {
+    fn completions(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: language::Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Vec<Completion>>> {
+        let dap_store = self.dap_store.read(cx);
+        let Some((id, _)) = dap_store.active_session() else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let offset = buffer_position.to_offset(&snapshot);
+        let column = buffer_position.to_point_utf16(&snapshot).column as u64 + 1;
+        let completions = dap_store.console_completions(id, snapshot.text(), column, cx);
+
+        cx.background_executor().spawn(async move {
+            let items = completions.await?;
+            Ok(items
+                .into_iter()
+                .map(|item| {
+                    // Adapters that don't say which text a completion replaces expect it to
+                    // replace the word before the cursor.
+                    let start = match item.start {
+                        Some(start) => snapshot.point_utf16_to_offset(PointUtf16::new(
+                            0,
+                            start.saturating_sub(1) as u32,
+                        )),
+                        None => {
+                            let prefix_len = snapshot
+                                .reversed_chars_at(offset)
+                                .take_while(|c| c.is_alphanumeric() || *c == '_')
+                                .map(char::len_utf8)
+                                .sum::<usize>();
+                            offset - prefix_len
+                        }
+                    };
+                    let end = match item.length {
+                        Some(length) => {
+                            let start = snapshot.offset_to_point_utf16(start);
+                            snapshot.point_utf16_to_offset(PointUtf16::new(
+                                0,
+                                start.column + length as u32,
+                            ))
+                        }
+                        None => offset,
+                    };
+                    let new_text = item.text.clone().unwrap_or_else(|| item.label.clone());
+                    Completion {
+                        old_range: snapshot.anchor_before(start)..snapshot.anchor_after(end),
+                        new_text,
+                        label: CodeLabel::plain(item.label, None),
+                        documentation: item.type_.map(Documentation::SingleLine),
+                        server_id: LanguageServerId(0),
+                        lsp_completion: Default::default(),
+                        confirm: None,
+                        show_new_completions_on_confirm: false,
+                    }
+                })
+                .collect())
+        })
+    }
+
+    fn resolve_completions(
+        &self,
+        _buffer: Model<Buffer>,
+        _completion_indices: Vec<usize>,
+        _completions: Arc<RwLock<Box<[Completion]>>>,
+        _cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<bool>> {
+        Task::ready(Ok(false))
+    }
+
+    fn apply_additional_edits_for_completion(
+        &self,
+        _buffer: Model<Buffer>,
+        _completion: Completion,
+        _push_to_history: bool,
+        _cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Option<language::Transaction>>> {
+        Task::ready(Ok(None))
+    }
+
+    fn is_completion_trigger(
+        &self,
+        _buffer: &Model<Buffer>,
+        _position: language::Anchor,
+        text: &str,
+        trigger_in_words: bool,
+        _cx: &mut ViewContext<Editor>,
+    ) -> bool {
+        text == "." || (trigger_in_words && text.chars().all(|c| c.is_alphanumeric() || c == '_'))
+    }
+}
@@ -0,0 +1,986 @@
+use crate::{
+    persistence::DEBUGGER_DB, process_picker::ProcessPicker, Continue, DebugPanelSettings, Pause,
+    StepInto, StepOut, StepOver, Stop, ToggleFocus,
+};
+use anyhow::Result;
+use collections::{HashMap, HashSet};
+use dap::{
+    types::{ExceptionBreakpointsFilter, StackFrame, Variable},
+    DebugAdapterClientId,
+};
+use db::kvp::KEY_VALUE_STORE;
+use editor::{actions::SelectAll, scroll::Autoscroll, Editor, EditorEvent};
+use gpui::{
+    Action, AnyElement, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView,
+    Model, ParentElement, Pixels, Render, Styled, Subscription, Task, View, ViewContext, WeakView,
+    WindowContext,
+};
+use language::{Bias, Point};
+use project::{
+    dap_store::{stack_frame_abs_path, DapStore, DapStoreEvent, DebugSession, DebugSessionStatus},
+    Fs,
+};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::sync::Arc;
+use ui::{prelude::*, Checkbox, ListItem, Selection, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    notifications::DetachAndPromptErr,
+    Workspace, WorkspaceId,
+};
+
+const DEBUG_PANEL_KEY: &str = "DebugPanel";
+
+/// The row highlight of the line that the active stack frame is executing.
+enum DebugStackFrameLine {}
+
+pub struct DebugPanel {
+    dap_store: Model<DapStore>,
+    workspace: WeakView<Workspace>,
+    workspace_id: Option<WorkspaceId>,
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    focus_handle: FocusHandle,
+    /// The editor that highlights the line of the active stack frame.
+    highlighted_editor: Option<WeakView<Editor>>,
+    /// The scopes and variables, by reference, that were expanded or collapsed. Scopes are
+    /// expanded by default unless they are expensive to fetch, and variables are collapsed.
+    toggled_variables: HashSet<u64>,
+    /// The editor of the value of a variable, along with the variable it sets.
+    variable_editor: Option<(EditedVariable, View<Editor>)>,
+    watch_editor: View<Editor>,
+    pending_serialization: Task<Option<()>>,
+    pending_exception_filters_serialization: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+/// A variable whose value is being edited.
+#[derive(Clone, PartialEq, Eq)]
+struct EditedVariable {
+    /// The reference of the scope or variable containing the variable.
+    container_reference: u64,
+    name: String,
+}
+
+/// A variable of the variables or watch trees, flattened with the variables of the expanded
+/// variables.
+struct VariableEntry {
+    depth: usize,
+    container_reference: u64,
+    variable: Variable,
+    is_expanded: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedDebugPanel {
+    width: Option<Pixels>,
+}
+
+impl DebugPanel {
+    pub fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let dap_store = workspace.project().read(cx).dap_store().clone();
+        let workspace_handle = cx.view().downgrade();
+        let workspace_id = workspace.database_id();
+        let workspace_view = cx.view().clone();
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            let watch_editor = cx.new_view(|cx| {
+                let mut editor = Editor::single_line(cx);
+                editor.set_placeholder_text("Add a watch expression", cx);
+                editor
+            });
+            let subscriptions = vec![
+                cx.observe(&dap_store, |_, _, cx| cx.notify()),
+                cx.subscribe(&dap_store, Self::handle_dap_store_event),
+                cx.subscribe(&workspace_view, Self::handle_workspace_event),
+            ];
+
+            Self {
+                dap_store,
+                workspace: workspace_handle,
+                workspace_id,
+                fs,
+                width: None,
+                focus_handle: cx.focus_handle(),
+                highlighted_editor: None,
+                toggled_variables: HashSet::default(),
+                variable_editor: None,
+                watch_editor,
+                pending_serialization: Task::ready(None),
+                pending_exception_filters_serialization: Task::ready(None),
+                _subscriptions: subscriptions,
+            }
+        })
+    }
+
+    pub fn load(
+        workspace: WeakView<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<View<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let serialized_panel = if let Some(panel) = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(DEBUG_PANEL_KEY) })
+                .await
+                .log_err()
+                .flatten()
+            {
+                Some(serde_json::from_str::<SerializedDebugPanel>(&panel)?)
+            } else {
+                None
+            };
+
+            let workspace_id = workspace.update(&mut cx, |workspace, _| workspace.database_id())?;
+            let exception_breakpoint_filters = if let Some(workspace_id) = workspace_id {
+                cx.background_executor()
+                    .spawn(
+                        async move { DEBUGGER_DB.get_exception_breakpoint_filters(workspace_id) },
+                    )
+                    .await
+                    .log_err()
+                    .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+
+            workspace.update(&mut cx, |workspace, cx| {
+                if !exception_breakpoint_filters.is_empty() {
+                    workspace
+                        .project()
+                        .read(cx)
+                        .dap_store()
+                        .update(cx, |dap_store, cx| {
+                            dap_store.set_exception_breakpoint_filters(
+                                exception_breakpoint_filters.into_iter().collect(),
+                                cx,
+                            )
+                        });
+                }
+                let panel = Self::new(workspace, cx);
+                if let Some(serialized_panel) = serialized_panel {
+                    panel.update(cx, |panel, cx| {
+                        panel.width = serialized_panel.width.map(|w| w.round());
+                        cx.notify();
+                    });
+                }
+                panel
+            })
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let width = self.width;
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        DEBUG_PANEL_KEY.into(),
+                        serde_json::to_string(&SerializedDebugPanel { width })?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    fn toggle_exception_breakpoint_filter(
+        &mut self,
+        filter: &ExceptionBreakpointsFilter,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let filters = self.dap_store.update(cx, |dap_store, cx| {
+            dap_store.toggle_exception_breakpoint_filter(filter, cx);
+            dap_store.toggled_exception_breakpoint_filters().clone()
+        });
+        self.serialize_exception_breakpoint_filters(filters, cx);
+    }
+
+    fn serialize_exception_breakpoint_filters(
+        &mut self,
+        filters: HashMap<String, bool>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace_id) = self.workspace_id else {
+            return;
+        };
+        self.pending_exception_filters_serialization = cx.background_executor().spawn(async move {
+            DEBUGGER_DB
+                .save_exception_breakpoint_filters(workspace_id, filters.into_iter().collect())
+                .await
+                .log_err()
+        });
+    }
+
+    fn handle_workspace_event(
+        &mut self,
+        workspace: View<Workspace>,
+        event: &workspace::Event,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let workspace::Event::PickProcessToDebug(config) = event {
+            let dap_store = self.dap_store.clone();
+            let config = config.clone();
+            workspace.update(cx, |workspace, cx| {
+                workspace.toggle_modal(cx, |cx| ProcessPicker::new(dap_store, config, cx));
+            });
+        }
+    }
+
+    fn handle_dap_store_event(
+        &mut self,
+        _: Model<DapStore>,
+        event: &DapStoreEvent,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match event {
+            DapStoreEvent::SessionStarted(_) => {
+                if let Some(workspace) = self.workspace.upgrade() {
+                    workspace.update(cx, |workspace, cx| {
+                        workspace.open_panel::<Self>(cx);
+                    });
+                }
+            }
+            DapStoreEvent::Stopped(id) | DapStoreEvent::ActiveStackFrameChanged(id) => {
+                if self.dap_store.read(cx).active_session().map(|(id, _)| id) == Some(*id) {
+                    self.reset_variables();
+                    self.go_to_active_stack_frame(cx);
+                }
+            }
+            DapStoreEvent::Continued(_) | DapStoreEvent::SessionEnded(_) => {
+                self.reset_variables();
+                self.clear_highlighted_line(cx);
+            }
+            DapStoreEvent::SessionFailed { label, error } => {
+                let message = format!("Debug session \"{label}\" failed: {error}");
+                self.workspace
+                    .update(cx, |workspace, cx| workspace.show_error(&message, cx))
+                    .ok();
+            }
+        }
+    }
+
+    /// Opens the file of the active stack frame, and highlights the line it is executing.
+    fn go_to_active_stack_frame(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((_, session)) = self.dap_store.read(cx).active_session() else {
+            return;
+        };
+        let Some(stack_frame) = session.active_stack_frame() else {
+            return;
+        };
+        let Some(abs_path) = stack_frame_abs_path(stack_frame) else {
+            return;
+        };
+        let row = stack_frame.line.saturating_sub(1) as u32;
+        let Some(open_task) = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path(abs_path, true, cx)
+            })
+            .log_err()
+        else {
+            return;
+        };
+
+        cx.spawn(|this, mut cx| async move {
+            let Some(editor) = open_task.await?.downcast::<Editor>() else {
+                return Ok(());
+            };
+            this.update(&mut cx, |this, cx| {
+                this.clear_highlighted_line(cx);
+                editor.update(cx, |editor, cx| {
+                    let snapshot = editor.snapshot(cx).display_snapshot;
+                    let point = snapshot
+                        .buffer_snapshot
+                        .clip_point(Point::new(row, 0), Bias::Left);
+                    let anchor = snapshot.buffer_snapshot.anchor_before(point);
+                    editor.highlight_rows::<DebugStackFrameLine>(
+                        anchor..=anchor,
+                        Some(cx.theme().status().warning_background),
+                        false,
+                        cx,
+                    );
+                    editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                        selections.select_ranges([point..point])
+                    });
+                });
+                this.highlighted_editor = Some(editor.downgrade());
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn clear_highlighted_line(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(editor) = self.highlighted_editor.take() {
+            editor
+                .update(cx, |editor, cx| {
+                    editor.clear_row_highlights::<DebugStackFrameLine>();
+                    cx.notify();
+                })
+                .ok();
+        }
+    }
+
+    /// Forgets the expanded variables and the edited one, as variable references are only valid
+    /// until the debuggee resumes.
+    fn reset_variables(&mut self) {
+        self.toggled_variables.clear();
+        self.variable_editor = None;
+    }
+
+    fn toggle_variable(
+        &mut self,
+        id: DebugAdapterClientId,
+        variables_reference: u64,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if !self.toggled_variables.remove(&variables_reference) {
+            self.toggled_variables.insert(variables_reference);
+        }
+        let is_fetched = self.dap_store.read(cx).session(id).map_or(true, |session| {
+            session.variables(variables_reference).is_some()
+        });
+        if !is_fetched {
+            self.dap_store
+                .update(cx, |dap_store, cx| {
+                    dap_store.fetch_variables(id, variables_reference, cx)
+                })
+                .detach_and_log_err(cx);
+        }
+        cx.notify();
+    }
+
+    fn edit_variable(
+        &mut self,
+        variable: EditedVariable,
+        value: String,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::single_line(cx);
+            editor.set_text(value, cx);
+            editor.select_all(&SelectAll, cx);
+            editor
+        });
+        cx.subscribe(&editor, |this, _, event, cx| {
+            if let EditorEvent::Blurred = event {
+                this.variable_editor = None;
+                cx.notify();
+            }
+        })
+        .detach();
+        editor.focus_handle(cx).focus(cx);
+        self.variable_editor = Some((variable, editor));
+        cx.notify();
+    }
+
+    fn confirm(&mut self, _: &menu::Confirm, cx: &mut ViewContext<Self>) {
+        if let Some((variable, editor)) = self.variable_editor.take() {
+            let value = editor.read(cx).text(cx);
+            let active_session = self.dap_store.read(cx).active_session().map(|(id, _)| id);
+            if let Some(id) = active_session {
+                self.dap_store
+                    .update(cx, |dap_store, cx| {
+                        dap_store.set_variable(
+                            id,
+                            variable.container_reference,
+                            variable.name,
+                            value,
+                            cx,
+                        )
+                    })
+                    .detach_and_prompt_err("Failed to set the variable", cx, |_, _| None);
+            }
+            self.focus_handle.focus(cx);
+            cx.notify();
+        } else if self.watch_editor.focus_handle(cx).is_focused(cx) {
+            let expression = self.watch_editor.read(cx).text(cx);
+            self.dap_store.update(cx, |dap_store, cx| {
+                dap_store.add_watch_expression(expression, cx)
+            });
+            self.watch_editor
+                .update(cx, |editor, cx| editor.set_text("", cx));
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        if self.variable_editor.take().is_some() {
+            self.focus_handle.focus(cx);
+            cx.notify();
+        }
+    }
+
+    /// Flattens the variables of a scope or variable, along with the variables of the expanded
+    /// ones.
+    fn variable_entries(
+        &self,
+        session: &DebugSession,
+        variables_reference: u64,
+        depth: usize,
+        entries: &mut Vec<VariableEntry>,
+    ) {
+        for variable in session.variables(variables_reference).unwrap_or_default() {
+            let is_expanded = variable.variables_reference != 0
+                && self
+                    .toggled_variables
+                    .contains(&variable.variables_reference);
+            entries.push(VariableEntry {
+                depth,
+                container_reference: variables_reference,
+                variable: variable.clone(),
+                is_expanded,
+            });
+            if is_expanded {
+                self.variable_entries(session, variable.variables_reference, depth + 1, entries);
+            }
+        }
+    }
+
+    fn render_variable(
+        &self,
+        id: DebugAdapterClientId,
+        ix: usize,
+        entry: VariableEntry,
+        can_set_variables: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let variable = entry.variable;
+        let edited_variable = EditedVariable {
+            container_reference: entry.container_reference,
+            name: variable.name.clone(),
+        };
+        let value_editor = self
+            .variable_editor
+            .as_ref()
+            .filter(|(edited, _)| *edited == edited_variable)
+            .map(|(_, editor)| editor.clone());
+        let variables_reference = variable.variables_reference;
+
+        ListItem::new(("variable", ix))
+            .inset(true)
+            .indent_level(entry.depth)
+            .toggle((variables_reference != 0).then_some(entry.is_expanded))
+            .on_toggle(
+                cx.listener(move |this, _, cx| this.toggle_variable(id, variables_reference, cx)),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .overflow_x_hidden()
+                    .child(Label::new(format!("{}:", variable.name)).single_line())
+                    .child(if let Some(editor) = value_editor {
+                        h_flex().h_6().w_full().child(editor).into_any_element()
+                    } else {
+                        Label::new(variable.value.clone())
+                            .color(Color::Muted)
+                            .single_line()
+                            .into_any_element()
+                    }),
+            )
+            .when_some(variable.type_.clone(), |item, type_| {
+                item.tooltip(move |cx| Tooltip::text(type_.clone(), cx))
+            })
+            .on_click(cx.listener(move |this, event: &gpui::ClickEvent, cx| {
+                if event.up.click_count > 1 && can_set_variables {
+                    this.edit_variable(edited_variable.clone(), variable.value.clone(), cx);
+                } else if variables_reference != 0 {
+                    this.toggle_variable(id, variables_reference, cx);
+                }
+            }))
+    }
+
+    fn render_variables(
+        &self,
+        id: DebugAdapterClientId,
+        cx: &mut ViewContext<Self>,
+    ) -> Vec<AnyElement> {
+        let Some(session) = self.dap_store.read(cx).session(id) else {
+            return Vec::new();
+        };
+        let can_set_variables = session
+            .client()
+            .capabilities()
+            .supports_set_variable
+            .unwrap_or(false);
+        let scopes = session
+            .scopes()
+            .iter()
+            .map(|scope| {
+                let is_expanded =
+                    scope.expensive == self.toggled_variables.contains(&scope.variables_reference);
+                let mut entries = Vec::new();
+                if is_expanded {
+                    self.variable_entries(session, scope.variables_reference, 1, &mut entries);
+                }
+                (scope.clone(), is_expanded, entries)
+            })
+            .collect::<Vec<_>>();
+
+        let mut items = Vec::new();
+        let mut ix = 0;
+        for (scope, is_expanded, entries) in scopes {
+            let variables_reference = scope.variables_reference;
+            items.push(
+                ListItem::new(("scope", variables_reference as usize))
+                    .inset(true)
+                    .toggle(is_expanded)
+                    .on_toggle(cx.listener(move |this, _, cx| {
+                        this.toggle_variable(id, variables_reference, cx)
+                    }))
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.toggle_variable(id, variables_reference, cx)
+                    }))
+                    .child(Label::new(scope.name).single_line())
+                    .into_any_element(),
+            );
+            for entry in entries {
+                items.push(
+                    self.render_variable(id, ix, entry, can_set_variables, cx)
+                        .into_any_element(),
+                );
+                ix += 1;
+            }
+        }
+        items
+    }
+
+    fn render_watches(&self, cx: &mut ViewContext<Self>) -> Vec<AnyElement> {
+        let dap_store = self.dap_store.read(cx);
+        let session = dap_store
+            .active_session()
+            .filter(|(_, session)| session.status() == DebugSessionStatus::Stopped);
+        let session_id = session.map(|(id, _)| id);
+        let watches = dap_store
+            .watch_expressions()
+            .iter()
+            .map(|expression| {
+                let value = session
+                    .and_then(|(_, session)| session.watch_value(expression))
+                    .cloned();
+                let variables_reference = match &value {
+                    Some(Ok(response)) => response.variables_reference,
+                    _ => 0,
+                };
+                let is_expanded = variables_reference != 0
+                    && self.toggled_variables.contains(&variables_reference);
+                let mut entries = Vec::new();
+                if let (true, Some((_, session))) = (is_expanded, session) {
+                    self.variable_entries(session, variables_reference, 1, &mut entries);
+                }
+                (expression.clone(), value, is_expanded, entries)
+            })
+            .collect::<Vec<_>>();
+
+        let mut items = Vec::new();
+        let mut ix = 0;
+        for (expression_ix, (expression, value, is_expanded, entries)) in
+            watches.into_iter().enumerate()
+        {
+            let (value_label, variables_reference) = match value {
+                Some(Ok(response)) => (
+                    Label::new(response.result).color(Color::Muted),
+                    response.variables_reference,
+                ),
+                Some(Err(error)) => (Label::new(error).color(Color::Error), 0),
+                None => (Label::new("not available").color(Color::Disabled), 0),
+            };
+
+            items.push(
+                ListItem::new(("watch", expression_ix))
+                    .inset(true)
+                    .toggle((variables_reference != 0).then_some(is_expanded))
+                    .on_toggle(cx.listener(move |this, _, cx| {
+                        if let Some(id) = session_id {
+                            this.toggle_variable(id, variables_reference, cx)
+                        }
+                    }))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .overflow_x_hidden()
+                            .child(Label::new(format!("{expression}:")).single_line())
+                            .child(value_label.single_line()),
+                    )
+                    .end_hover_slot(
+                        IconButton::new(("remove-watch", expression_ix), IconName::Close)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Remove Expression", cx))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.dap_store.update(cx, |dap_store, cx| {
+                                    dap_store.remove_watch_expression(expression_ix, cx)
+                                });
+                            })),
+                    )
+                    .into_any_element(),
+            );
+            if let Some(id) = session_id {
+                for entry in entries {
+                    items.push(
+                        self.render_variable(id, ix, entry, false, cx)
+                            .into_any_element(),
+                    );
+                    ix += 1;
+                }
+            }
+        }
+        items
+    }
+
+    fn render_exception_breakpoint_filters(&self, cx: &mut ViewContext<Self>) -> Vec<AnyElement> {
+        let dap_store = self.dap_store.read(cx);
+        let Some((_, session)) = dap_store.active_session() else {
+            return Vec::new();
+        };
+        session
+            .exception_breakpoint_filters()
+            .into_iter()
+            .enumerate()
+            .map(|(ix, filter)| {
+                let selection = if dap_store.is_exception_breakpoint_filter_enabled(&filter) {
+                    Selection::Selected
+                } else {
+                    Selection::Unselected
+                };
+                let description = filter.description.clone();
+                ListItem::new(("exception-filter", ix))
+                    .inset(true)
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            // The whole item toggles the filter when clicked.
+                            .child(Checkbox::new(("exception-filter-checkbox", ix), selection))
+                            .child(Label::new(filter.label.clone()).single_line()),
+                    )
+                    .when_some(description, |item, description| {
+                        item.tooltip(move |cx| Tooltip::text(description.clone(), cx))
+                    })
+                    .on_click(cx.listener(move |this, _, cx| {
+                        this.toggle_exception_breakpoint_filter(&filter, cx)
+                    }))
+                    .into_any_element()
+            })
+            .collect()
+    }
+
+    fn render_controls(&self, status: Option<DebugSessionStatus>) -> impl IntoElement {
+        let is_stopped = status == Some(DebugSessionStatus::Stopped);
+
+        let control = |id: &'static str,
+                       icon: IconName,
+                       label: &'static str,
+                       action: Box<dyn Action>,
+                       enabled: bool| {
+            IconButton::new(id, icon)
+                .icon_size(IconSize::Small)
+                .disabled(!enabled)
+                .tooltip({
+                    let action = action.boxed_clone();
+                    move |cx| Tooltip::for_action(label, action.as_ref(), cx)
+                })
+                .on_click(move |_, cx| cx.dispatch_action(action.boxed_clone()))
+        };
+
+        h_flex()
+            .gap_1()
+            .child(if is_stopped || status.is_none() {
+                control(
+                    "debug-continue",
+                    IconName::DebugContinue,
+                    "Continue",
+                    Box::new(Continue),
+                    is_stopped,
+                )
+            } else {
+                control(
+                    "debug-pause",
+                    IconName::DebugPause,
+                    "Pause",
+                    Box::new(Pause),
+                    status == Some(DebugSessionStatus::Running),
+                )
+            })
+            .child(control(
+                "debug-step-over",
+                IconName::DebugStepOver,
+                "Step Over",
+                Box::new(StepOver),
+                is_stopped,
+            ))
+            .child(control(
+                "debug-step-into",
+                IconName::DebugStepInto,
+                "Step Into",
+                Box::new(StepInto),
+                is_stopped,
+            ))
+            .child(control(
+                "debug-step-out",
+                IconName::DebugStepOut,
+                "Step Out",
+                Box::new(StepOut),
+                is_stopped,
+            ))
+            .child(control(
+                "debug-stop",
+                IconName::DebugStop,
+                "Stop",
+                Box::new(Stop),
+                status.is_some(),
+            ))
+    }
+
+    fn render_stack_frame(
+        &self,
+        id: DebugAdapterClientId,
+        ix: usize,
+        stack_frame: &StackFrame,
+        is_active: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let location = stack_frame
+            .source
+            .as_ref()
+            .and_then(|source| source.name.clone().or_else(|| source.path.clone()))
+            .map(|file_name| format!("{file_name}:{}", stack_frame.line));
+        let is_subtle = stack_frame.presentation_hint.as_deref() == Some("subtle");
+
+        ListItem::new(("stack-frame", ix))
+            .inset(true)
+            .selected(is_active)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .overflow_x_hidden()
+                    .child(
+                        Label::new(stack_frame.name.clone())
+                            .color(if is_subtle {
+                                Color::Muted
+                            } else {
+                                Color::Default
+                            })
+                            .single_line(),
+                    )
+                    .children(location.map(|location| {
+                        Label::new(location)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line()
+                    })),
+            )
+            .on_click(cx.listener(move |this, _, cx| {
+                if is_active {
+                    this.go_to_active_stack_frame(cx);
+                } else {
+                    this.dap_store
+                        .update(cx, |dap_store, cx| dap_store.select_stack_frame(id, ix, cx));
+                }
+            }))
+    }
+}
+
+impl Render for DebugPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let dap_store = self.dap_store.read(cx);
+        let active_session = dap_store.active_session().map(|(id, _)| id);
+        let sessions = dap_store
+            .sessions()
+            .map(|(id, session)| (id, session.label.clone()))
+            .collect::<Vec<_>>();
+
+        let mut content = v_flex()
+            .id("debug-panel-content")
+            .size_full()
+            .overflow_y_scroll()
+            .pb_2();
+        if sessions.is_empty() {
+            content = content.child(
+                v_flex().p_4().child(
+                    Label::new("Run a task with \"type\": \"debug\" to start debugging.")
+                        .color(Color::Muted),
+                ),
+            );
+        } else {
+            if sessions.len() > 1 {
+                content = content.child(section_header("Sessions"));
+                for (id, label) in sessions {
+                    content = content.child(
+                        ListItem::new(("debug-session", id.0))
+                            .inset(true)
+                            .selected(Some(id) == active_session)
+                            .child(Label::new(label).single_line())
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.dap_store.update(cx, |dap_store, cx| {
+                                    dap_store.set_active_session(id, cx)
+                                });
+                                this.go_to_active_stack_frame(cx);
+                            })),
+                    );
+                }
+            }
+
+            if let Some((id, session)) = self.dap_store.read(cx).active_session() {
+                let status = match session.status() {
+                    DebugSessionStatus::Starting => "Starting…".to_string(),
+                    DebugSessionStatus::Running => "Running".to_string(),
+                    DebugSessionStatus::Stopped => match session.stop_reason() {
+                        Some(reason) => format!("Paused on {reason}"),
+                        None => "Paused".to_string(),
+                    },
+                };
+                let thread_name = session
+                    .stopped_thread_id()
+                    .and_then(|thread_id| {
+                        session
+                            .threads()
+                            .iter()
+                            .find(|thread| thread.id == thread_id)
+                    })
+                    .map(|thread| thread.name.clone());
+                let active_stack_frame = session.active_stack_frame().map(|frame| frame.id);
+                let stack_frames = session.stack_frames().to_vec();
+
+                content = content.child(section_header("Call Stack")).child(
+                    h_flex()
+                        .px_2()
+                        .gap_2()
+                        .child(Label::new(status).size(LabelSize::Small))
+                        .children(thread_name.map(|name| {
+                            Label::new(name)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line()
+                        })),
+                );
+                for (ix, stack_frame) in stack_frames.iter().enumerate() {
+                    let is_active = Some(stack_frame.id) == active_stack_frame;
+                    content =
+                        content.child(self.render_stack_frame(id, ix, stack_frame, is_active, cx));
+                }
+            }
+
+            let stopped_session = self
+                .dap_store
+                .read(cx)
+                .active_session()
+                .filter(|(_, session)| session.status() == DebugSessionStatus::Stopped)
+                .map(|(id, _)| id);
+            if let Some(id) = stopped_session {
+                content = content
+                    .child(section_header("Variables"))
+                    .children(self.render_variables(id, cx));
+            }
+            content = content
+                .child(section_header("Watch"))
+                .children(self.render_watches(cx))
+                .child(
+                    div()
+                        .mx_2()
+                        .mt_1()
+                        .px_1()
+                        .py_0p5()
+                        .border_1()
+                        .border_color(cx.theme().colors().border)
+                        .rounded_md()
+                        .child(self.watch_editor.clone()),
+                );
+
+            let exception_breakpoint_filters = self.render_exception_breakpoint_filters(cx);
+            if !exception_breakpoint_filters.is_empty() {
+                content = content
+                    .child(section_header("Exceptions"))
+                    .children(exception_breakpoint_filters);
+            }
+        }
+
+        let active_session = self.dap_store.read(cx).active_session();
+        let controls = self.render_controls(active_session.map(|(_, session)| session.status()));
+        let title = active_session
+            .map(|(_, session)| session.label.clone())
+            .unwrap_or_else(|| "Debug".into());
+
+        v_flex()
+            .key_context("DebugPanel")
+            .track_focus(&self.focus_handle)
+            .on_action(cx.listener(Self::confirm))
+            .on_action(cx.listener(Self::cancel))
+            .size_full()
+            .child(
+                h_flex()
+                    .h_8()
+                    .px_2()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Label::new(title).single_line())
+                    .child(controls),
+            )
+            .child(content)
+    }
+}
+
+fn section_header(title: &'static str) -> impl IntoElement {
+    h_flex()
+        .px_2()
+        .pt_2()
+        .pb_1()
+        .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+}
+
+impl FocusableView for DebugPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for DebugPanel {}
+
+impl Panel for DebugPanel {
+    fn persistent_name() -> &'static str {
+        "DebugPanel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        DebugPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<DebugPanelSettings>(
+            self.fs.clone(),
+            cx,
+            move |settings| settings.dock = Some(position),
+        );
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| DebugPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        DebugPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::Debug)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Debug Panel")
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
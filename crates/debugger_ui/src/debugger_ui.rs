@@ -0,0 +1,158 @@
+mod breakpoint_modal;
+mod debug_console;
+mod debug_panel;
+mod persistence;
+mod process_picker;
+
+pub use debug_console::DebugConsole;
+pub use debug_panel::DebugPanel;
+
+use breakpoint_modal::BreakpointModal;
+
+use gpui::{actions, AppContext, Pixels, Task, ViewContext};
+use project::dap_store::DapStore;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use workspace::{dock::DockPosition, Workspace};
+
+actions!(debug_panel, [ToggleFocus]);
+actions!(
+    debugger,
+    [Continue, Pause, StepOver, StepInto, StepOut, Stop]
+);
+
+#[derive(Deserialize, Debug)]
+pub struct DebugPanelSettings {
+    pub button: bool,
+    pub dock: DockPosition,
+    pub default_width: Pixels,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct DebugPanelSettingsContent {
+    /// Whether to show the debug panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Where to dock the debug panel.
+    ///
+    /// Default: left
+    pub dock: Option<DockPosition>,
+    /// Default width of the debug panel in pixels.
+    ///
+    /// Default: 300
+    pub default_width: Option<f32>,
+}
+
+impl Settings for DebugPanelSettings {
+    const KEY: Option<&'static str> = Some("debug_panel");
+
+    type FileContent = DebugPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
+
+#[derive(Deserialize, Debug)]
+pub struct DebugConsoleSettings {
+    pub button: bool,
+    pub dock: DockPosition,
+    pub default_width: Pixels,
+    pub default_height: Pixels,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct DebugConsoleSettingsContent {
+    /// Whether to show the debug console button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Where to dock the debug console.
+    ///
+    /// Default: bottom
+    pub dock: Option<DockPosition>,
+    /// Default width of the debug console in pixels, when docked to the left or right.
+    ///
+    /// Default: 400
+    pub default_width: Option<f32>,
+    /// Default height of the debug console in pixels, when docked to the bottom.
+    ///
+    /// Default: 240
+    pub default_height: Option<f32>,
+}
+
+impl Settings for DebugConsoleSettings {
+    const KEY: Option<&'static str> = Some("debug_console");
+
+    type FileContent = DebugConsoleSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    DebugPanelSettings::register(cx);
+    DebugConsoleSettings::register(cx);
+    cx.observe_new_views(BreakpointModal::register).detach();
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &ToggleFocus, cx| {
+                workspace.toggle_panel_focus::<DebugPanel>(cx);
+            })
+            .register_action(|workspace, _: &debug_console::ToggleFocus, cx| {
+                workspace.toggle_panel_focus::<DebugConsole>(cx);
+            })
+            .register_action(|workspace, _: &Continue, cx| {
+                with_active_session(workspace, cx, DapStore::continue_thread);
+            })
+            .register_action(|workspace, _: &Pause, cx| {
+                with_active_session(workspace, cx, DapStore::pause);
+            })
+            .register_action(|workspace, _: &StepOver, cx| {
+                with_active_session(workspace, cx, DapStore::step_over);
+            })
+            .register_action(|workspace, _: &StepInto, cx| {
+                with_active_session(workspace, cx, DapStore::step_in);
+            })
+            .register_action(|workspace, _: &StepOut, cx| {
+                with_active_session(workspace, cx, DapStore::step_out);
+            })
+            .register_action(|workspace, _: &Stop, cx| {
+                let dap_store = workspace.project().read(cx).dap_store().clone();
+                dap_store.update(cx, |dap_store, cx| {
+                    if let Some((id, _)) = dap_store.active_session() {
+                        dap_store.stop_session(id, cx).detach();
+                    }
+                });
+            });
+    })
+    .detach();
+}
+
+/// Sends a request to the debug session that was last started or that last stopped.
+fn with_active_session(
+    workspace: &mut Workspace,
+    cx: &mut ViewContext<Workspace>,
+    request: fn(
+        &mut DapStore,
+        dap::DebugAdapterClientId,
+        &mut gpui::ModelContext<DapStore>,
+    ) -> Task<anyhow::Result<()>>,
+) {
+    let dap_store = workspace.project().read(cx).dap_store().clone();
+    dap_store.update(cx, |dap_store, cx| {
+        if let Some((id, _)) = dap_store.active_session() {
+            request(dap_store, id, cx).detach_and_log_err(cx);
+        }
+    });
+}
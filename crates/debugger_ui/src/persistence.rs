@@ -0,0 +1,59 @@
+use anyhow::Context as _;
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    // Current schema shape using pseudo-rust syntax:
+    // exception_breakpoint_filters(
+    //   workspace_id: usize,
+    //   filter: String,
+    //   enabled: bool,
+    // )
+    pub static ref DEBUGGER_DB: DebuggerDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE exception_breakpoint_filters (
+                workspace_id INTEGER NOT NULL,
+                filter TEXT NOT NULL,
+                enabled INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, filter),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl DebuggerDb {
+    query! {
+        pub fn get_exception_breakpoint_filters(workspace_id: WorkspaceId) -> Result<Vec<(String, bool)>> {
+            SELECT filter, enabled
+            FROM exception_breakpoint_filters
+            WHERE workspace_id = ?
+        }
+    }
+
+    /// Replaces all the exception filters toggled in the given workspace.
+    pub async fn save_exception_breakpoint_filters(
+        &self,
+        workspace_id: WorkspaceId,
+        filters: Vec<(String, bool)>,
+    ) -> anyhow::Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_exception_breakpoint_filters", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM exception_breakpoint_filters WHERE workspace_id = ?
+                ))?(workspace_id)
+                .context("clearing old exception breakpoint filters")?;
+                for (filter, enabled) in filters {
+                    conn.exec_bound(sql!(
+                        INSERT OR REPLACE INTO exception_breakpoint_filters(workspace_id, filter, enabled)
+                        VALUES (?, ?, ?)
+                    ))?((workspace_id, filter, enabled))
+                    .context("inserting exception breakpoint filter")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
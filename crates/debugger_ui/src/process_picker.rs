@@ -0,0 +1,242 @@
+use std::sync::Arc;
+
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render, Task, View,
+    ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::dap_store::DapStore;
+use sysinfo::{ProcessRefreshKind, RefreshKind, System, UpdateKind};
+use task::DebugAdapterConfig;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::ModalView;
+
+/// A modal picking the local process that a debug task attaches to.
+pub struct ProcessPicker {
+    picker: View<Picker<ProcessPickerDelegate>>,
+}
+
+impl ProcessPicker {
+    pub(crate) fn new(
+        dap_store: Model<DapStore>,
+        config: DebugAdapterConfig,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let delegate = ProcessPickerDelegate {
+            process_picker: cx.view().downgrade(),
+            dap_store,
+            config,
+            processes: Arc::default(),
+            matches: Vec::new(),
+            selected_index: 0,
+        };
+        let picker = cx.new_view(|cx| Picker::uniform_list(delegate, cx));
+
+        let load_processes = cx
+            .background_executor()
+            .spawn(async move { local_processes() });
+        cx.spawn(|this, mut cx| async move {
+            let processes = load_processes.await;
+            this.update(&mut cx, |this, cx| {
+                this.picker.update(cx, |picker, cx| {
+                    picker.delegate.processes = Arc::new(processes);
+                    picker.refresh(cx);
+                })
+            })
+            .log_err();
+        })
+        .detach();
+
+        Self { picker }
+    }
+}
+
+impl Render for ProcessPicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(40.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for ProcessPicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for ProcessPicker {}
+impl ModalView for ProcessPicker {}
+
+#[derive(Debug)]
+struct Process {
+    pid: u32,
+    name: String,
+    command_line: String,
+}
+
+/// Lists the processes of the current user, most recently started first.
+fn local_processes() -> Vec<Process> {
+    let system = System::new_with_specifics(
+        RefreshKind::new().with_processes(
+            ProcessRefreshKind::new()
+                .with_cmd(UpdateKind::Always)
+                .with_user(UpdateKind::Always),
+        ),
+    );
+    let current_user = sysinfo::get_current_pid()
+        .ok()
+        .and_then(|pid| system.process(pid)?.user_id().cloned());
+    let mut processes = system
+        .processes()
+        .values()
+        .filter(|process| current_user.is_none() || process.user_id() == current_user.as_ref())
+        .map(|process| (process.start_time(), process))
+        .collect::<Vec<_>>();
+    processes.sort_by(|(a, _), (b, _)| b.cmp(a));
+    processes
+        .into_iter()
+        .map(|(_, process)| Process {
+            pid: process.pid().as_u32(),
+            name: process.name().to_string(),
+            command_line: process.cmd().join(" "),
+        })
+        .collect()
+}
+
+struct ProcessPickerDelegate {
+    process_picker: WeakView<ProcessPicker>,
+    dap_store: Model<DapStore>,
+    config: DebugAdapterConfig,
+    processes: Arc<Vec<Process>>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl PickerDelegate for ProcessPickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        format!("Pick a process to attach {} to...", self.config.label).into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let processes = self.processes.clone();
+        cx.spawn(|this, mut cx| async move {
+            // Processes are matched by name, id and command line, in that order.
+            let candidates = processes
+                .iter()
+                .enumerate()
+                .map(|(ix, process)| {
+                    StringMatchCandidate::new(
+                        ix,
+                        format!("{} {} {}", process.name, process.pid, process.command_line),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(mat) = self.matches.get(self.selected_index) else {
+            return;
+        };
+        let pid = self.processes[mat.candidate_id].pid;
+        let config = self.config.clone().with_process_id(pid);
+        self.dap_store.update(cx, |dap_store, cx| {
+            dap_store.start_session(config, cx).detach_and_log_err(cx);
+        });
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.process_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let process = &self.processes[mat.candidate_id];
+        let name_positions = mat
+            .positions
+            .iter()
+            .copied()
+            .filter(|position| *position < process.name.len())
+            .collect();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    v_flex()
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(HighlightedLabel::new(process.name.clone(), name_positions))
+                                .child(
+                                    Label::new(process.pid.to_string())
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                ),
+                        )
+                        .child(
+                            Label::new(util::truncate_and_trailoff(&process.command_line, 120))
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
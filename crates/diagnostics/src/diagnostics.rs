@@ -6,7 +6,7 @@ mod toolbar_controls;
 mod diagnostics_tests;
 
 use anyhow::Result;
-use collections::{BTreeSet, HashSet};
+use collections::{BTreeSet, HashMap, HashSet};
 use editor::{
     diagnostic_block_renderer,
     display_map::{BlockDisposition, BlockId, BlockProperties, BlockStyle, RenderBlock},
@@ -16,16 +16,18 @@ use editor::{
 };
 use futures::{
     channel::mpsc::{self, UnboundedSender},
+    future::join_all,
     StreamExt as _,
 };
 use gpui::{
-    actions, div, svg, AnyElement, AnyView, AppContext, Context, EventEmitter, FocusHandle,
-    FocusableView, HighlightStyle, InteractiveElement, IntoElement, Model, ParentElement, Render,
-    SharedString, Styled, StyledText, Subscription, Task, View, ViewContext, VisualContext,
-    WeakView, WindowContext,
+    actions, div, svg, AnyElement, AnyView, AppContext, AsyncAppContext, Context, EventEmitter,
+    FocusHandle, FocusableView, HighlightStyle, InteractiveElement, IntoElement, Model,
+    ParentElement, Render, SharedString, Styled, StyledText, Subscription, Task, View, ViewContext,
+    VisualContext, WeakView, WindowContext,
 };
 use language::{
     Bias, Buffer, Diagnostic, DiagnosticEntry, DiagnosticSeverity, Point, Selection, SelectionGoal,
+    ToPoint as _,
 };
 use lsp::LanguageServerId;
 use project::{DiagnosticSummary, Project, ProjectPath};
@@ -36,6 +38,8 @@ use std::{
     cmp::Ordering,
     mem,
     ops::Range,
+    path::Path,
+    sync::Arc,
 };
 use theme::ActiveTheme;
 pub use toolbar_controls::ToolbarControls;
@@ -80,6 +84,8 @@ struct DiagnosticGroupState {
     primary_diagnostic: DiagnosticEntry<language::Anchor>,
     primary_excerpt_ix: usize,
     excerpts: Vec<ExcerptId>,
+    /// How many of the last excerpts show related locations in other files.
+    related_excerpt_count: usize,
     blocks: HashSet<BlockId>,
     block_count: usize,
 }
@@ -201,8 +207,19 @@ impl ProjectDiagnosticsEditor {
                         .await
                         .log_err()
                     {
+                        let related_buffers =
+                            Self::open_related_buffers(&project_handle, &buffer, &mut cx)
+                                .await
+                                .log_err()
+                                .unwrap_or_default();
                         this.update(&mut cx, |this, cx| {
-                            this.update_excerpts(path, language_server_id, buffer, cx);
+                            this.update_excerpts(
+                                path,
+                                language_server_id,
+                                buffer,
+                                related_buffers,
+                                cx,
+                            );
                         })?;
                     }
                 }
@@ -286,11 +303,42 @@ impl ProjectDiagnosticsEditor {
         }
     }
 
+    /// Opens the buffers of the locations in other files that the diagnostics of a buffer relate
+    /// to, so that they can be shown as excerpts below the diagnostics.
+    async fn open_related_buffers(
+        project: &Model<Project>,
+        buffer: &Model<Buffer>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<HashMap<Arc<Path>, Model<Buffer>>> {
+        let open_buffers = project.update(cx, |project, cx| {
+            let mut abs_paths = HashSet::default();
+            for (_, group) in buffer.read(cx).snapshot().diagnostic_groups(None) {
+                let primary = &group.entries[group.primary_ix].diagnostic;
+                abs_paths.extend(
+                    primary
+                        .related_information
+                        .iter()
+                        .map(|info| info.abs_path.clone()),
+                );
+            }
+            abs_paths
+                .into_iter()
+                .filter_map(|abs_path| {
+                    let project_path = project.project_path_for_absolute_path(&abs_path, cx)?;
+                    let open_buffer = project.open_buffer(project_path, cx);
+                    Some(async move { Some((abs_path, open_buffer.await.log_err()?)) })
+                })
+                .collect::<Vec<_>>()
+        })?;
+        Ok(join_all(open_buffers).await.into_iter().flatten().collect())
+    }
+
     fn update_excerpts(
         &mut self,
         path_to_update: ProjectPath,
         server_to_update: Option<LanguageServerId>,
         buffer: Model<Buffer>,
+        related_buffers: HashMap<Arc<Path>, Model<Buffer>>,
         cx: &mut ViewContext<Self>,
     ) {
         self.paths_to_update.retain(|(path, server_id)| {
@@ -393,6 +441,7 @@ impl ProjectDiagnosticsEditor {
                         primary_diagnostic: group.entries[group.primary_ix].clone(),
                         primary_excerpt_ix: 0,
                         excerpts: Default::default(),
+                        related_excerpt_count: 0,
                         blocks: Default::default(),
                         block_count: 0,
                     };
@@ -477,6 +526,57 @@ impl ProjectDiagnosticsEditor {
                         }
                     }
 
+                    // Show the locations in other files that the diagnostic relates to below it.
+                    let primary = &group.entries[group.primary_ix].diagnostic;
+                    for info in &primary.related_information {
+                        let Some(related_buffer) = related_buffers.get(&info.abs_path) else {
+                            continue;
+                        };
+                        let related_snapshot = related_buffer.read(cx).snapshot();
+                        let range = info.range.start.to_point(&related_snapshot)
+                            ..info.range.end.to_point(&related_snapshot);
+                        let excerpt_start =
+                            Point::new(range.start.row.saturating_sub(self.context), 0);
+                        let excerpt_end = related_snapshot.clip_point(
+                            Point::new(range.end.row + self.context, u32::MAX),
+                            Bias::Left,
+                        );
+                        let excerpt_id = excerpts
+                            .insert_excerpts_after(
+                                prev_excerpt_id,
+                                related_buffer.clone(),
+                                [ExcerptRange {
+                                    context: excerpt_start..excerpt_end,
+                                    primary: Some(range.clone()),
+                                }],
+                                cx,
+                            )
+                            .pop()
+                            .unwrap();
+
+                        prev_excerpt_id = excerpt_id;
+                        group_state.excerpts.push(excerpt_id);
+                        group_state.related_excerpt_count += 1;
+                        if !info.message.is_empty() {
+                            group_state.block_count += 1;
+                            blocks_to_add.push(BlockProperties {
+                                position: (excerpt_id, related_snapshot.anchor_before(range.start)),
+                                height: info.message.matches('\n').count() as u8 + 1,
+                                style: BlockStyle::Fixed,
+                                render: diagnostic_block_renderer(
+                                    Diagnostic {
+                                        severity: DiagnosticSeverity::INFORMATION,
+                                        message: info.message.clone(),
+                                        group_id: primary.group_id,
+                                        ..Default::default()
+                                    },
+                                    true,
+                                ),
+                                disposition: BlockDisposition::Below,
+                            });
+                        }
+                    }
+
                     new_group_ixs.push(path_state.diagnostic_groups.len());
                     path_state.diagnostic_groups.push(group_state);
                 } else if let Some((_, group_state)) = to_remove {
@@ -591,8 +691,20 @@ impl ProjectDiagnosticsEditor {
 
     #[cfg(test)]
     fn check_invariants(&self, cx: &mut ViewContext<Self>) {
+        let related_excerpts = self
+            .path_states
+            .iter()
+            .flat_map(|state| &state.diagnostic_groups)
+            .flat_map(|group| {
+                group.excerpts[group.excerpts.len() - group.related_excerpt_count..].iter()
+            })
+            .collect::<HashSet<_>>();
+
         let mut excerpts = Vec::new();
         for (id, buffer, _) in self.excerpts.read(cx).snapshot(cx).excerpts() {
+            if related_excerpts.contains(&id) {
+                continue;
+            }
             if let Some(file) = buffer.file() {
                 excerpts.push((id, file.path().clone()));
             }
@@ -632,6 +744,11 @@ impl Item for ProjectDiagnosticsEditor {
             .update(cx, |editor, cx| editor.navigate(data, cx))
     }
 
+    fn handle_drop(&mut self, dropped: &dyn Any, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.handle_drop(dropped, cx))
+    }
+
     fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
         Some("Project Diagnostics".into())
     }
@@ -6,7 +6,8 @@ use editor::{
 };
 use gpui::{px, AvailableSpace, Stateful, TestAppContext, VisualTestContext};
 use language::{
-    Diagnostic, DiagnosticEntry, DiagnosticSeverity, OffsetRangeExt, PointUtf16, Rope, Unclipped,
+    Diagnostic, DiagnosticEntry, DiagnosticRelatedInformation, DiagnosticSeverity, OffsetRangeExt,
+    PointUtf16, Rope, Unclipped,
 };
 use pretty_assertions::assert_eq;
 use project::FakeFs;
@@ -689,6 +690,93 @@ async fn test_diagnostics_multiple_servers(cx: &mut TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_diagnostics_with_related_locations_in_other_files(cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/test",
+        json!({
+            "lib.rs": "pub fn consume(v: Vec<u8>) {}\n",
+            "main.rs": "
+                fn main() {
+                    let x = vec![];
+                    consume(x);
+                    drop(x);
+                }
+            "
+            .unindent(),
+        }),
+    )
+    .await;
+
+    let language_server_id = LanguageServerId(0);
+    let project = Project::test(fs.clone(), ["/test".as_ref()], cx).await;
+    let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+    let cx = &mut VisualTestContext::from_window(*window, cx);
+    let workspace = window.root(cx).unwrap();
+
+    project.update(cx, |project, cx| {
+        project
+            .update_diagnostic_entries(
+                language_server_id,
+                PathBuf::from("/test/main.rs"),
+                None,
+                vec![DiagnosticEntry {
+                    range: Unclipped(PointUtf16::new(3, 9))..Unclipped(PointUtf16::new(3, 10)),
+                    diagnostic: Diagnostic {
+                        message: "use of moved value: `x`".to_string(),
+                        severity: DiagnosticSeverity::ERROR,
+                        is_primary: true,
+                        group_id: 0,
+                        related_information: vec![DiagnosticRelatedInformation {
+                            abs_path: Path::new("/test/lib.rs").into(),
+                            range: Unclipped(PointUtf16::new(0, 15))
+                                ..Unclipped(PointUtf16::new(0, 16)),
+                            message: "value moved into this parameter".to_string(),
+                        }],
+                        ..Default::default()
+                    },
+                }],
+                cx,
+            )
+            .unwrap();
+    });
+
+    let view = window.build_view(cx, |cx| {
+        ProjectDiagnosticsEditor::new_with_context(1, project.clone(), workspace.downgrade(), cx)
+    });
+    cx.executor().run_until_parked();
+
+    // The related location is shown in an excerpt of its own file, below the diagnostic.
+    assert_eq!(
+        get_diagnostics_excerpts(&view, cx),
+        [
+            ExcerptInfo {
+                path: "main.rs".into(),
+                range: ExcerptRange {
+                    context: Point::new(2, 0)..Point::new(4, 1),
+                    primary: Some(Point::new(3, 9)..Point::new(3, 10)),
+                },
+                group_id: 0,
+                primary: true,
+                language_server: language_server_id,
+            },
+            ExcerptInfo {
+                path: "lib.rs".into(),
+                range: ExcerptRange {
+                    context: Point::new(0, 0)..Point::new(1, 0),
+                    primary: Some(Point::new(0, 15)..Point::new(0, 16)),
+                },
+                group_id: 0,
+                primary: false,
+                language_server: language_server_id,
+            },
+        ]
+    );
+}
+
 #[gpui::test(iterations = 20)]
 async fn test_random_diagnostics(cx: &mut TestAppContext, mut rng: StdRng) {
     init_test(cx);
@@ -954,6 +1042,7 @@ fn random_diagnostic(
             is_primary,
             is_disk_based: false,
             is_unnecessary: false,
+            related_information: Vec::new(),
         },
     }
 }
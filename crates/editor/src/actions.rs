@@ -80,6 +80,11 @@ pub struct FoldAt {
     pub buffer_row: MultiBufferRow,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct FoldAtLevel {
+    pub level: u32,
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct UnfoldAt {
     pub buffer_row: MultiBufferRow,
@@ -108,6 +113,20 @@ pub struct SelectDownByLines {
     pub(super) lines: u32,
 }
 
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct ToggleBookmark {
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+#[derive(PartialEq, Clone, Deserialize, Default)]
+pub struct AlignSelections {
+    /// When set, the occurrences of this delimiter in the selected lines are aligned instead of
+    /// the selections themselves.
+    #[serde(default)]
+    pub delimiter: Option<String>,
+}
+
 #[derive(PartialEq, Clone, Deserialize, Default)]
 pub struct ExpandExcerpts {
     #[serde(default)]
@@ -129,12 +148,14 @@ pub struct ExpandExcerptsDown {
 impl_actions!(
     editor,
     [
+        AlignSelections,
         ConfirmCodeAction,
         ConfirmCompletion,
         ExpandExcerpts,
         ExpandExcerptsUp,
         ExpandExcerptsDown,
         FoldAt,
+        FoldAtLevel,
         MoveDownByLines,
         MovePageDown,
         MovePageUp,
@@ -147,6 +168,7 @@ impl_actions!(
         SelectToBeginningOfLine,
         SelectToEndOfLine,
         SelectUpByLines,
+        ToggleBookmark,
         ToggleCodeActions,
         ToggleComments,
         UnfoldAt,
@@ -156,6 +178,9 @@ impl_actions!(
 gpui::actions!(
     editor,
     [
+        AcceptConflictBoth,
+        AcceptConflictOurs,
+        AcceptConflictTheirs,
         AcceptPartialCopilotSuggestion,
         AcceptInlineCompletion,
         AcceptPartialInlineCompletion,
@@ -180,6 +205,7 @@ gpui::actions!(
         CopyHighlightJson,
         CopyPath,
         CopyPermalinkToLine,
+        CopyPermalinkToSelection,
         CopyRelativePath,
         Cut,
         CutToEndOfLine,
@@ -194,20 +220,31 @@ gpui::actions!(
         DisplayCursorNames,
         DuplicateLineDown,
         DuplicateLineUp,
+        EditBreakpointCondition,
+        EditBreakpointHitCount,
+        EditBreakpointLogMessage,
+        EnableSyntaxHighlighting,
         ExpandAllHunkDiffs,
+        ExpandExcerptsToFunction,
         ExpandMacroRecursively,
         FindAllReferences,
         Fold,
+        FoldAllImports,
         FoldSelectedRanges,
         Format,
+        GoToBookmark,
+        GoToConflict,
         GoToDefinition,
         GoToDefinitionSplit,
         GoToDiagnostic,
         GoToHunk,
         GoToImplementation,
         GoToImplementationSplit,
+        GoToPrevBookmark,
+        GoToPrevConflict,
         GoToPrevDiagnostic,
         GoToPrevHunk,
+        GoToRelatedFile,
         GoToTypeDefinition,
         GoToTypeDefinitionSplit,
         HalfPageDown,
@@ -240,14 +277,23 @@ gpui::actions!(
         OpenExcerpts,
         OpenExcerptsSplit,
         OpenPermalinkToLine,
+        OpenUndoHistory,
         OpenUrl,
         Outdent,
         PageDown,
         PageUp,
         Paste,
+        PasteFromHistory,
         PreviousInlineCompletion,
+        RebaseDrop,
+        RebaseEdit,
+        RebaseFixup,
+        RebasePick,
+        RebaseReword,
+        RebaseSquash,
         Redo,
         RedoSelection,
+        Reflow,
         Rename,
         RestartLanguageServer,
         RevealInFinder,
@@ -273,15 +319,23 @@ gpui::actions!(
         SelectToPreviousWordStart,
         SelectToStartOfParagraph,
         SelectUp,
+        ShowCallHierarchy,
         ShowCharacterPalette,
         ShowCompletions,
         ShowInlineCompletion,
+        ShowTypeHierarchy,
         ShuffleLines,
         SortLinesCaseInsensitive,
         SortLinesCaseSensitive,
+        SortLinesDescending,
+        SortLinesNumerically,
         SplitSelectionIntoLines,
+        StageSelectedHunks,
         Tab,
         TabPrev,
+        ToggleBlockSelection,
+        ToggleBreakpoint,
+        ToggleDiagnostics,
         ToggleGitBlame,
         ToggleGitBlameInline,
         ToggleHunkDiff,
@@ -293,8 +347,10 @@ gpui::actions!(
         Transpose,
         Undo,
         UndoSelection,
+        UnfoldAll,
         UnfoldLines,
         UniqueLinesCaseInsensitive,
         UniqueLinesCaseSensitive,
+        UnstageSelectedHunks,
     ]
 );
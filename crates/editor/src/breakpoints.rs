@@ -0,0 +1,214 @@
+use gpui::{AppContext, Model, Pixels, Point, ViewContext};
+use language::{Buffer, ToPoint as _};
+use multi_buffer::{MultiBufferRow, ToPoint as _};
+use project::dap_store::BreakpointKind;
+use ui::{prelude::*, ButtonSize, ContextMenu, IconButton, IconName, IconSize};
+
+use crate::{
+    actions::{
+        EditBreakpointCondition, EditBreakpointHitCount, EditBreakpointLogMessage, ToggleBreakpoint,
+    },
+    display_map::ToDisplayPoint,
+    mouse_context_menu::MouseContextMenu,
+    DisplayPoint, DisplayRow, Editor, EditorMode, EditorSnapshot, SelectMode,
+};
+
+impl Editor {
+    /// Returns the buffer and row of the newest selection's head, where the breakpoint actions
+    /// apply.
+    pub fn breakpoint_location(&self, cx: &AppContext) -> Option<(Model<Buffer>, u32)> {
+        if self.mode != EditorMode::Full || self.project.is_none() {
+            return None;
+        }
+        let head = self.selections.newest::<usize>(cx).head();
+        let (buffer, anchor) = self.buffer.read(cx).text_anchor_for_position(head, cx)?;
+        let row = anchor.to_point(&buffer.read(cx).snapshot()).row;
+        Some((buffer, row))
+    }
+}
+
+/// Toggles a breakpoint on the lines of the selections.
+pub(super) fn toggle_breakpoint(
+    editor: &mut Editor,
+    _: &ToggleBreakpoint,
+    cx: &mut ViewContext<Editor>,
+) {
+    let heads = editor
+        .selections
+        .all::<usize>(cx)
+        .into_iter()
+        .map(|selection| selection.head())
+        .collect::<Vec<_>>();
+    toggle_breakpoints_at(editor, heads, cx);
+}
+
+/// Toggles a breakpoint on the line displayed on the given row, like when clicking the gutter.
+pub(super) fn toggle_breakpoint_at_display_row(
+    editor: &mut Editor,
+    row: DisplayRow,
+    cx: &mut ViewContext<Editor>,
+) {
+    let snapshot = editor.snapshot(cx);
+    let offset = DisplayPoint::new(row, 0).to_offset(&snapshot.display_snapshot, text::Bias::Left);
+    toggle_breakpoints_at(editor, vec![offset], cx);
+}
+
+fn toggle_breakpoints_at(editor: &mut Editor, offsets: Vec<usize>, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let mut rows_by_buffer = Vec::<(Model<Buffer>, Vec<u32>)>::new();
+    for offset in offsets {
+        let Some((buffer, anchor)) = editor.buffer.read(cx).text_anchor_for_position(offset, cx)
+        else {
+            continue;
+        };
+        let row = anchor.to_point(&buffer.read(cx).snapshot()).row;
+        match rows_by_buffer.iter_mut().find(|(b, _)| *b == buffer) {
+            Some((_, rows)) => {
+                if !rows.contains(&row) {
+                    rows.push(row);
+                }
+            }
+            None => rows_by_buffer.push((buffer, vec![row])),
+        }
+    }
+
+    let dap_store = project.read(cx).dap_store().clone();
+    dap_store.update(cx, |dap_store, cx| {
+        for (buffer, rows) in rows_by_buffer {
+            dap_store.toggle_breakpoints(&buffer, rows, cx);
+        }
+    });
+}
+
+/// Deploys the context menu of the breakpoint displayed on the given row, returning whether
+/// there is one.
+pub(super) fn deploy_breakpoint_context_menu(
+    editor: &mut Editor,
+    position: Point<Pixels>,
+    row: DisplayRow,
+    cx: &mut ViewContext<Editor>,
+) -> bool {
+    let snapshot = editor.snapshot(cx);
+    let has_breakpoint = breakpoint_display_rows(editor, &snapshot, cx)
+        .binary_search_by_key(&row, |(row, _)| *row)
+        .is_ok();
+    if !has_breakpoint {
+        return false;
+    }
+
+    if !editor.is_focused(cx) {
+        editor.focus(cx);
+    }
+    // Move the cursor to the breakpoint's line, where the menu's actions apply.
+    let point = DisplayPoint::new(row, 0);
+    editor.change_selections(None, cx, |s| {
+        s.clear_disjoint();
+        s.set_pending_display_range(point..point, SelectMode::Character);
+    });
+
+    let focus = cx.focused();
+    let context_menu = ContextMenu::build(cx, |menu, _| {
+        let menu = menu
+            .action("Edit Condition…", Box::new(EditBreakpointCondition))
+            .action("Edit Hit Count…", Box::new(EditBreakpointHitCount))
+            .action("Edit Log Message…", Box::new(EditBreakpointLogMessage))
+            .separator()
+            .action("Remove Breakpoint", Box::new(ToggleBreakpoint));
+        match focus {
+            Some(focus) => menu.context(focus),
+            None => menu,
+        }
+    });
+    editor.mouse_context_menu = Some(MouseContextMenu::new(position, context_menu, cx));
+    cx.notify();
+    true
+}
+
+/// Returns the display rows of the breakpoints set in the buffers of the editor along with their
+/// kinds, in ascending order, skipping the ones that are folded away.
+pub(super) fn breakpoint_display_rows(
+    editor: &Editor,
+    snapshot: &EditorSnapshot,
+    cx: &AppContext,
+) -> Vec<(DisplayRow, BreakpointKind)> {
+    if editor.mode != EditorMode::Full {
+        return Vec::new();
+    }
+    let Some(project) = editor.project.as_ref() else {
+        return Vec::new();
+    };
+
+    let dap_store = project.read(cx).dap_store().read(cx);
+    let multibuffer = editor.buffer.read(cx);
+    let mut rows = Vec::new();
+    for buffer in multibuffer.all_buffers() {
+        let breakpoints = dap_store.breakpoints_for_buffer(&buffer, cx);
+        if breakpoints.is_empty() {
+            continue;
+        }
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let excerpts = multibuffer.excerpts_for_buffer(&buffer, cx);
+        for (breakpoint, kind) in breakpoints {
+            let anchor = excerpts.iter().find_map(|(excerpt_id, excerpt_range)| {
+                let contains_breakpoint = excerpt_range
+                    .context
+                    .start
+                    .cmp(&breakpoint, &buffer_snapshot)
+                    .is_le()
+                    && excerpt_range
+                        .context
+                        .end
+                        .cmp(&breakpoint, &buffer_snapshot)
+                        .is_ge();
+                if !contains_breakpoint {
+                    return None;
+                }
+                snapshot
+                    .buffer_snapshot
+                    .anchor_in_excerpt(*excerpt_id, breakpoint)
+            });
+            let Some(anchor) = anchor else {
+                continue;
+            };
+            let point = anchor.to_point(&snapshot.buffer_snapshot);
+            if !snapshot.is_line_folded(MultiBufferRow(point.row)) {
+                rows.push((point.to_display_point(snapshot).row(), kind));
+            }
+        }
+    }
+    rows.sort_by_key(|(row, _)| *row);
+    rows.dedup_by_key(|(row, _)| *row);
+    rows
+}
+
+/// Renders the gutter indicator of a breakpoint of the given kind, or of a breakpoint that
+/// clicking the hovered line would add when there is no kind.
+pub(super) fn render_breakpoint_indicator(
+    row: DisplayRow,
+    kind: Option<BreakpointKind>,
+    cx: &mut ViewContext<Editor>,
+) -> IconButton {
+    let icon = match kind {
+        Some(BreakpointKind::Conditional) => IconName::DebugBreakpointConditional,
+        Some(BreakpointKind::Log) => IconName::DebugLogBreakpoint,
+        Some(BreakpointKind::Standard) | None => IconName::DebugBreakpoint,
+    };
+    IconButton::new(("breakpoint_indicator", row.0 as usize), icon)
+        .icon_size(IconSize::XSmall)
+        .size(ButtonSize::None)
+        .icon_color(if kind.is_some() {
+            Color::Error
+        } else {
+            Color::Hint
+        })
+        .on_click(cx.listener(move |editor, _, cx| {
+            editor.focus(cx);
+            toggle_breakpoint_at_display_row(editor, row, cx);
+        }))
+}
@@ -0,0 +1,293 @@
+use std::mem;
+
+use collections::HashSet;
+use futures::future::join_all;
+use gpui::{Model, SharedString, Task, ViewContext};
+use language::{Buffer, ToPoint as _};
+use multi_buffer::{ToOffset as _, ToPoint as _};
+use project::CodeLens;
+use settings::Settings;
+use ui::{h_flex, prelude::*};
+use util::ResultExt;
+
+use crate::{
+    display_map::{
+        BlockContext, BlockDisposition, BlockId, BlockProperties, BlockStyle, ToDisplayPoint,
+    },
+    scroll::Autoscroll,
+    Anchor, Editor, EditorMode, EditorSettings, FindAllReferences, ToggleCodeActions,
+};
+
+/// The client-side command that language servers use for lenses showing references.
+const SHOW_REFERENCES_COMMAND: &str = "editor.action.showReferences";
+
+/// The code lenses of the buffers in an editor, rendered as blocks above the lines they're about.
+#[derive(Default)]
+pub(crate) struct CodeLensState {
+    lenses: Vec<CodeLensEntry>,
+    blocks: HashSet<BlockId>,
+    fetch_task: Option<Task<()>>,
+}
+
+struct CodeLensEntry {
+    buffer: Model<Buffer>,
+    lens: CodeLens,
+    /// Where the lens is shown in the multibuffer.
+    position: Anchor,
+    /// Whether the lens has been sent to its language server to be resolved.
+    resolving: bool,
+    /// Whether the language server has answered, even if it didn't fill in the lens's command.
+    resolved: bool,
+}
+
+/// Fetches the code lenses of all the buffers in the editor, replacing the ones shown so far.
+/// Lenses are refetched when the editor is first shown, when excerpts are added or removed,
+/// when a buffer is saved, when a language server is added and when the settings change. They
+/// keep their position through edits in between.
+pub(super) fn refresh_code_lenses(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    if !EditorSettings::get_global(cx).code_lens {
+        editor.code_lens.fetch_task = None;
+        if !editor.code_lens.lenses.is_empty() {
+            editor.code_lens.lenses.clear();
+            update_code_lens_blocks(editor, cx);
+        }
+        return;
+    }
+
+    let fetches = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .map(|buffer| {
+            let lenses = project.update(cx, |project, cx| project.code_lens(&buffer, cx));
+            async move { (buffer, lenses.await) }
+        })
+        .collect::<Vec<_>>();
+    editor.code_lens.fetch_task = Some(cx.spawn(|editor, mut cx| async move {
+        let lenses_by_buffer = join_all(fetches).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                let multibuffer = editor.buffer.read(cx);
+                let snapshot = multibuffer.snapshot(cx);
+                let mut entries = Vec::new();
+                for (buffer, lenses) in lenses_by_buffer {
+                    let excerpts = multibuffer.excerpts_for_buffer(&buffer, cx);
+                    let buffer_snapshot = buffer.read(cx).snapshot();
+                    for lens in lenses {
+                        let start = lens.range.start;
+                        let position = excerpts.iter().find_map(|(excerpt_id, range)| {
+                            let contains_start =
+                                range.context.start.cmp(&start, &buffer_snapshot).is_le()
+                                    && range.context.end.cmp(&start, &buffer_snapshot).is_ge();
+                            contains_start
+                                .then(|| snapshot.anchor_in_excerpt(*excerpt_id, start))
+                                .flatten()
+                        });
+                        if let Some(position) = position {
+                            entries.push(CodeLensEntry {
+                                buffer: buffer.clone(),
+                                lens,
+                                position,
+                                resolving: false,
+                                resolved: false,
+                            });
+                        }
+                    }
+                }
+                entries.sort_by(|a, b| a.position.cmp(&b.position, &snapshot));
+
+                editor.code_lens.lenses = entries;
+                resolve_visible_code_lenses(editor, cx);
+                update_code_lens_blocks(editor, cx);
+            })
+            .ok();
+    }));
+}
+
+/// Resolves the visible code lenses that were returned without a command.
+pub(super) fn resolve_visible_code_lenses(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let needs_resolve =
+        |entry: &CodeLensEntry| !entry.resolving && entry.lens.lsp_lens.command.is_none();
+    if !editor.code_lens.lenses.iter().any(needs_resolve) {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let display_snapshot = editor
+        .display_map
+        .update(cx, |display_map, cx| display_map.snapshot(cx));
+    let visible_range = editor.visible_buffer_range(&display_snapshot);
+    let buffer_snapshot = &display_snapshot.buffer_snapshot;
+    for (ix, entry) in editor.code_lens.lenses.iter_mut().enumerate() {
+        if !needs_resolve(entry)
+            || !visible_range.contains(&entry.position.to_offset(buffer_snapshot))
+        {
+            continue;
+        }
+        entry.resolving = true;
+        let lens_range = entry.lens.range.clone();
+        let resolve = project.update(cx, |project, cx| {
+            project.resolve_code_lens(entry.buffer.clone(), entry.lens.clone(), cx)
+        });
+        cx.spawn(|editor, mut cx| async move {
+            let resolved_lens = resolve.await.log_err();
+            editor
+                .update(&mut cx, |editor, cx| {
+                    // The lenses may have been refetched while this one was being resolved.
+                    let Some(entry) = editor.code_lens.lenses.get_mut(ix).filter(|entry| {
+                        entry.resolving && !entry.resolved && entry.lens.range == lens_range
+                    }) else {
+                        return;
+                    };
+                    entry.resolved = true;
+                    if let Some(resolved_lens) = resolved_lens {
+                        entry.lens = resolved_lens;
+                    }
+                    update_code_lens_blocks(editor, cx);
+                })
+                .ok();
+        })
+        .detach();
+    }
+}
+
+fn update_code_lens_blocks(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let old_blocks = mem::take(&mut editor.code_lens.blocks);
+    if !old_blocks.is_empty() {
+        editor.remove_blocks(old_blocks, None, cx);
+    }
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let mut lines = Vec::<(u32, Anchor, Vec<(usize, Option<SharedString>)>)>::new();
+    for (ix, entry) in editor.code_lens.lenses.iter().enumerate() {
+        let title = match &entry.lens.lsp_lens.command {
+            Some(command) if command.title.is_empty() => continue,
+            Some(command) => Some(SharedString::from(command.title.clone())),
+            None if entry.resolved => continue,
+            None => None,
+        };
+        let row = entry.position.to_point(&snapshot).row;
+        match lines.last_mut() {
+            Some((last_row, _, titles)) if *last_row == row => titles.push((ix, title)),
+            _ => lines.push((row, entry.position, vec![(ix, title)])),
+        }
+    }
+
+    let editor_handle = cx.view().downgrade();
+    let blocks = lines
+        .into_iter()
+        .map(|(_, position, titles)| {
+            let editor_handle = editor_handle.clone();
+            BlockProperties {
+                position,
+                height: 1,
+                style: BlockStyle::Flex,
+                disposition: BlockDisposition::Above,
+                render: Box::new(move |cx: &mut BlockContext| {
+                    h_flex()
+                        .pl(cx.anchor_x)
+                        .gap_1()
+                        .children(titles.iter().enumerate().map(|(i, (ix, title))| {
+                            let ix = *ix;
+                            let lens = match title {
+                                Some(title) => Button::new(("code-lens", ix), title.clone())
+                                    .label_size(LabelSize::XSmall)
+                                    .color(Color::Muted)
+                                    .style(ButtonStyle::Transparent)
+                                    .on_click({
+                                        let editor_handle = editor_handle.clone();
+                                        move |_, cx| {
+                                            editor_handle
+                                                .update(cx, |editor, cx| {
+                                                    editor.run_code_lens(ix, cx)
+                                                })
+                                                .ok();
+                                        }
+                                    })
+                                    .into_any_element(),
+                                None => Label::new("…")
+                                    .size(LabelSize::XSmall)
+                                    .color(Color::Muted)
+                                    .into_any_element(),
+                            };
+                            h_flex()
+                                .gap_1()
+                                .when(i > 0, |this| {
+                                    this.child(
+                                        Label::new("|")
+                                            .size(LabelSize::XSmall)
+                                            .color(Color::Disabled),
+                                    )
+                                })
+                                .child(lens)
+                        }))
+                        .into_any_element()
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+    if !blocks.is_empty() {
+        editor.code_lens.blocks = editor.insert_blocks(blocks, None, cx).into_iter().collect();
+    }
+    cx.notify();
+}
+
+impl Editor {
+    /// Runs the command of the code lens at the given index. Reference lenses show the
+    /// references of their symbol, lenses running tests open the runnables at their line,
+    /// and all other commands are executed by the language server.
+    fn run_code_lens(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.code_lens.lenses.get(ix) else {
+            return;
+        };
+        let Some(command) = entry.lens.lsp_lens.command.as_ref() else {
+            return;
+        };
+        let position = entry.position;
+
+        if command.command == SHOW_REFERENCES_COMMAND {
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_anchor_ranges([position..position])
+            });
+            if let Some(task) = self.find_all_references(&FindAllReferences, cx) {
+                task.detach_and_log_err(cx);
+            }
+            return;
+        }
+
+        let command_name = command.command.to_lowercase();
+        let buffer = entry.buffer.read(cx);
+        let buffer_row = entry.lens.range.start.to_point(buffer).row;
+        if (command_name.contains("run") || command_name.contains("test"))
+            && self.tasks.contains_key(&(buffer.remote_id(), buffer_row))
+        {
+            let snapshot = self.snapshot(cx);
+            let row = position.to_display_point(&snapshot).row();
+            self.toggle_code_actions(
+                &ToggleCodeActions {
+                    deployed_from_indicator: Some(row),
+                },
+                cx,
+            );
+            return;
+        }
+
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let buffer = entry.buffer.clone();
+        let lens = entry.lens.clone();
+        project
+            .update(cx, |project, cx| project.run_code_lens(buffer, lens, cx))
+            .detach_and_log_err(cx);
+    }
+}
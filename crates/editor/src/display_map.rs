@@ -66,6 +66,12 @@ pub enum FoldStatus {
     Foldable,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum FoldRegionMarker {
+    Start,
+    End,
+}
+
 pub type RenderFoldToggle = Arc<dyn Fn(FoldStatus, &mut WindowContext) -> AnyElement>;
 
 const UNNECESSARY_CODE_FADE: f32 = 0.3;
@@ -101,8 +107,13 @@ pub struct DisplayMap {
     inlay_highlights: InlayHighlights,
     /// A container for explicitly foldable ranges, which supersede indentation based fold range suggestions.
     flap_map: FlapMap,
+    /// Foldable ranges reported by language servers, sorted by their start. They take precedence
+    /// over comment regions and indentation, but not over flaps.
+    folding_ranges: Arc<[Range<Anchor>]>,
     fold_placeholder: FoldPlaceholder,
     pub clip_at_line_ends: bool,
+    /// Whether the diagnostics of the buffer are underlined.
+    pub show_diagnostics: bool,
 }
 
 impl DisplayMap {
@@ -146,10 +157,12 @@ impl DisplayMap {
             wrap_map,
             block_map,
             flap_map,
+            folding_ranges: Arc::default(),
             fold_placeholder,
             text_highlights: Default::default(),
             inlay_highlights: Default::default(),
             clip_at_line_ends: false,
+            show_diagnostics: true,
         }
     }
 
@@ -173,9 +186,11 @@ impl DisplayMap {
             wrap_snapshot,
             block_snapshot,
             flap_snapshot: self.flap_map.snapshot(),
+            folding_ranges: self.folding_ranges.clone(),
             text_highlights: self.text_highlights.clone(),
             inlay_highlights: self.inlay_highlights.clone(),
             clip_at_line_ends: self.clip_at_line_ends,
+            show_diagnostics: self.show_diagnostics,
             fold_placeholder: self.fold_placeholder.clone(),
         }
     }
@@ -342,6 +357,12 @@ impl DisplayMap {
         block_map.remove(ids);
     }
 
+    /// Replaces the foldable ranges reported by language servers, which must be sorted by
+    /// their start.
+    pub fn set_folding_ranges(&mut self, ranges: Vec<Range<Anchor>>) {
+        self.folding_ranges = ranges.into();
+    }
+
     pub fn highlight_text(
         &mut self,
         type_id: TypeId,
@@ -467,6 +488,7 @@ pub struct DisplaySnapshot {
     pub buffer_snapshot: MultiBufferSnapshot,
     pub fold_snapshot: FoldSnapshot,
     pub flap_snapshot: FlapSnapshot,
+    folding_ranges: Arc<[Range<Anchor>]>,
     inlay_snapshot: InlaySnapshot,
     tab_snapshot: TabSnapshot,
     wrap_snapshot: WrapSnapshot,
@@ -474,6 +496,7 @@ pub struct DisplaySnapshot {
     text_highlights: TextHighlights,
     inlay_highlights: InlayHighlights,
     clip_at_line_ends: bool,
+    show_diagnostics: bool,
     pub(crate) fold_placeholder: FoldPlaceholder,
 }
 
@@ -688,11 +711,11 @@ impl DisplaySnapshot {
 
             let mut diagnostic_highlight = HighlightStyle::default();
 
-            if chunk.is_unnecessary {
+            if chunk.is_unnecessary && self.show_diagnostics {
                 diagnostic_highlight.fade_out = Some(UNNECESSARY_CODE_FADE);
             }
 
-            if let Some(severity) = chunk.diagnostic_severity {
+            if let Some(severity) = chunk.diagnostic_severity.filter(|_| self.show_diagnostics) {
                 // Omit underlines for HINT/INFO diagnostics on 'unnecessary' code.
                 if severity <= DiagnosticSeverity::WARNING || !chunk.is_unnecessary {
                     let diagnostic_color =
@@ -944,6 +967,87 @@ impl DisplaySnapshot {
         false
     }
 
+    /// Returns whether the buffer row starts a foldable range reported by a language server.
+    pub fn starts_folding_range(&self, buffer_row: MultiBufferRow) -> bool {
+        self.folding_range_end(buffer_row).is_some()
+    }
+
+    /// Returns the end of the outermost language server folding range starting on the given
+    /// buffer row.
+    fn folding_range_end(&self, buffer_row: MultiBufferRow) -> Option<Point> {
+        let start_ix = self.folding_ranges.partition_point(|range| {
+            range.start.to_point(&self.buffer_snapshot).row < buffer_row.0
+        });
+        self.folding_ranges[start_ix..]
+            .iter()
+            .take_while(|range| range.start.to_point(&self.buffer_snapshot).row == buffer_row.0)
+            .map(|range| range.end.to_point(&self.buffer_snapshot))
+            .filter(|end| end.row > buffer_row.0)
+            .max()
+    }
+
+    /// Returns whether the buffer row starts a region delimited by comments, like `// #region`.
+    pub fn starts_fold_region(&self, buffer_row: MultiBufferRow) -> bool {
+        self.fold_region_end(buffer_row).is_some()
+    }
+
+    /// Returns the end of the line closing the region started on the given buffer row,
+    /// taking nested regions into account.
+    fn fold_region_end(&self, buffer_row: MultiBufferRow) -> Option<Point> {
+        if self.fold_region_marker(buffer_row)? != FoldRegionMarker::Start {
+            return None;
+        }
+
+        let mut depth = 0;
+        for row in (buffer_row.0 + 1)..=self.buffer_snapshot.max_buffer_row().0 {
+            let row = MultiBufferRow(row);
+            match self.fold_region_marker(row) {
+                Some(FoldRegionMarker::Start) => depth += 1,
+                Some(FoldRegionMarker::End) if depth == 0 => {
+                    return Some(Point::new(row.0, self.buffer_snapshot.line_len(row)));
+                }
+                Some(FoldRegionMarker::End) => depth -= 1,
+                None => {}
+            }
+        }
+        None
+    }
+
+    fn fold_region_marker(&self, buffer_row: MultiBufferRow) -> Option<FoldRegionMarker> {
+        let line_indent = self.line_indent_for_buffer_row(buffer_row);
+        if line_indent.is_line_blank() {
+            return None;
+        }
+
+        let line_start = Point::new(buffer_row.0, line_indent.raw_len());
+        let scope = self.buffer_snapshot.language_scope_at(line_start)?;
+        let (start_marker, end_marker) = scope.fold_region_markers()?;
+        let line_end = Point::new(buffer_row.0, self.buffer_snapshot.line_len(buffer_row));
+        let line = self
+            .buffer_snapshot
+            .text_for_range(line_start..line_end)
+            .collect::<String>();
+        let comment = scope
+            .line_comment_prefixes()
+            .iter()
+            .chain(scope.block_comment_delimiters().map(|(start, _)| start))
+            .find_map(|prefix| line.strip_prefix(prefix.trim_end()))?
+            .trim_start();
+
+        let is_marker = |marker: &str| {
+            comment.strip_prefix(marker).map_or(false, |rest| {
+                !rest.starts_with(|c: char| c.is_alphanumeric() || c == '_')
+            })
+        };
+        if is_marker(end_marker) {
+            Some(FoldRegionMarker::End)
+        } else if is_marker(start_marker) {
+            Some(FoldRegionMarker::Start)
+        } else {
+            None
+        }
+    }
+
     pub fn foldable_range(
         &self,
         buffer_row: MultiBufferRow,
@@ -957,6 +1061,16 @@ impl DisplaySnapshot {
                 flap.range.to_point(&self.buffer_snapshot),
                 flap.placeholder.clone(),
             ))
+        } else if let Some(end) = self
+            .folding_range_end(buffer_row)
+            .filter(|_| !self.is_line_folded(buffer_row))
+        {
+            Some((start..end, self.fold_placeholder.clone()))
+        } else if let Some(end) = self
+            .fold_region_end(buffer_row)
+            .filter(|_| !self.is_line_folded(buffer_row))
+        {
+            Some((start..end, self.fold_placeholder.clone()))
         } else if self.starts_indent(MultiBufferRow(start.row))
             && !self.is_line_folded(MultiBufferRow(start.row))
         {
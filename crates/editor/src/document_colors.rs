@@ -0,0 +1,600 @@
+use std::{ops::Range, time::Duration};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{
+    DismissEvent, EventEmitter, FocusHandle, FocusableView, HighlightStyle, Hsla, Model, Pixels,
+    Point, Rgba, Subscription, Task, View, ViewContext,
+};
+use language::{Bias, Buffer, BufferSnapshot};
+use multi_buffer::ToOffset as _;
+use project::DocumentColor;
+use settings::Settings;
+use text::BufferId;
+use ui::{h_flex, prelude::*, v_flex};
+use util::post_inc;
+
+use crate::{
+    display_map::Inlay, element::PointForPosition, hover_links::InlayHighlight, Anchor, Editor,
+    EditorMode, EditorSettings, EditorSnapshot, InlayId,
+};
+
+/// The text of the inlay rendered before color literals, whose first character is colored.
+const SWATCH: &str = "■ ";
+
+/// How long to wait after an edit before refetching the colors of the buffers.
+const FETCH_DEBOUNCE: Duration = Duration::from_millis(150);
+
+/// The number of hues in each row of the color picker.
+const PICKER_HUE_COUNT: usize = 12;
+
+/// The lightness of each colored row of the color picker, followed by a row of grays.
+const PICKER_LIGHTNESSES: [f32; 5] = [0.85, 0.7, 0.5, 0.35, 0.2];
+
+/// The color literals of the buffers in an editor, rendered with a swatch that opens a color
+/// picker when clicked.
+#[derive(Default)]
+pub(crate) struct DocumentColorState {
+    /// The colors reported by language servers for each buffer. Buffers without any are
+    /// scanned for hex color literals instead.
+    lsp_colors: HashMap<BufferId, (Model<Buffer>, Vec<DocumentColor>)>,
+    swatches: Vec<ColorSwatch>,
+    fetch_task: Option<Task<()>>,
+    pub(crate) picker: Option<ColorPickerPopover>,
+}
+
+#[derive(Clone)]
+struct ColorSwatch {
+    inlay: InlayId,
+    /// The range of the color literal in the multibuffer.
+    range: Range<Anchor>,
+    color: Hsla,
+    source: ColorSource,
+}
+
+#[derive(Clone)]
+enum ColorSource {
+    /// A color reported by a language server, which knows how to write other colors.
+    Lsp {
+        buffer: Model<Buffer>,
+        color: DocumentColor,
+    },
+    /// A hex color literal found in the text, rewritten as hex.
+    HexLiteral,
+}
+
+pub(crate) struct ColorPickerPopover {
+    pub(crate) position: Point<Pixels>,
+    pub(crate) picker: View<ColorPicker>,
+    _subscriptions: Vec<Subscription>,
+}
+
+impl DocumentColorState {
+    /// Returns the index of the swatch rendered at the given position, if any.
+    pub(crate) fn swatch_at(
+        &self,
+        point_for_position: &PointForPosition,
+        snapshot: &EditorSnapshot,
+    ) -> Option<usize> {
+        // Clicking on an inlay is the only way for the valid points around a position to differ.
+        if point_for_position.column_overshoot_after_line_end > 0
+            || point_for_position.previous_valid == point_for_position.next_valid
+        {
+            return None;
+        }
+        let buffer = &snapshot.buffer_snapshot;
+        let start = buffer.anchor_at(
+            point_for_position.previous_valid.to_point(snapshot),
+            Bias::Left,
+        );
+        let end = buffer.anchor_at(
+            point_for_position.next_valid.to_point(snapshot),
+            Bias::Right,
+        );
+        self.swatches.iter().position(|swatch| {
+            swatch.range.start.cmp(&start, buffer).is_ge()
+                && swatch.range.start.cmp(&end, buffer).is_le()
+        })
+    }
+}
+
+/// Fetches the colors of all the buffers in the editor from their language servers, then
+/// updates the visible swatches.
+pub(super) fn refresh_document_colors(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    if !EditorSettings::get_global(cx).document_colors {
+        editor.document_colors.fetch_task = None;
+        editor.document_colors.lsp_colors.clear();
+        update_color_swatches(editor, cx);
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        update_color_swatches(editor, cx);
+        return;
+    };
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    editor.document_colors.fetch_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(FETCH_DEBOUNCE).await;
+        let Ok(fetches) = editor.update(&mut cx, |_, cx| {
+            buffers
+                .into_iter()
+                .map(|buffer| {
+                    let colors =
+                        project.update(cx, |project, cx| project.document_colors(&buffer, cx));
+                    async move { (buffer, colors.await) }
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return;
+        };
+        let colors_by_buffer = join_all(fetches).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.document_colors.lsp_colors = colors_by_buffer
+                    .into_iter()
+                    .map(|(buffer, colors)| (buffer.read(cx).remote_id(), (buffer, colors)))
+                    .collect();
+                update_color_swatches(editor, cx);
+            })
+            .ok();
+    }));
+}
+
+/// Renders a swatch before each visible color literal, using the colors reported by language
+/// servers, or the hex literals of the buffers they don't report any colors for.
+pub(super) fn update_color_swatches(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+
+    let display_snapshot = editor
+        .display_map
+        .update(cx, |display_map, cx| display_map.snapshot(cx));
+    let snapshot = &display_snapshot.buffer_snapshot;
+    let mut swatches = Vec::new();
+    if EditorSettings::get_global(cx).document_colors {
+        let visible_range = editor.visible_buffer_range(&display_snapshot);
+        let multibuffer = editor.buffer.read(cx);
+        for (buffer, colors) in editor.document_colors.lsp_colors.values() {
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(buffer, cx) {
+                for color in colors {
+                    let in_excerpt = excerpt_range
+                        .context
+                        .start
+                        .cmp(&color.range.start, &buffer_snapshot)
+                        .is_le()
+                        && excerpt_range
+                            .context
+                            .end
+                            .cmp(&color.range.end, &buffer_snapshot)
+                            .is_ge();
+                    if !in_excerpt {
+                        continue;
+                    }
+                    let (Some(start), Some(end)) = (
+                        snapshot.anchor_in_excerpt(excerpt_id, color.range.start),
+                        snapshot.anchor_in_excerpt(excerpt_id, color.range.end),
+                    ) else {
+                        continue;
+                    };
+                    if visible_range.contains(&start.to_offset(snapshot)) {
+                        swatches.push(ColorSwatch {
+                            inlay: InlayId::Color(0),
+                            range: start..end,
+                            color: color_from_lsp(color.color),
+                            source: ColorSource::Lsp {
+                                buffer: buffer.clone(),
+                                color: color.clone(),
+                            },
+                        });
+                    }
+                }
+            }
+        }
+
+        let text = snapshot
+            .text_for_range(visible_range.clone())
+            .collect::<String>();
+        for (range, color) in find_hex_colors(&text) {
+            let range = visible_range.start + range.start..visible_range.start + range.end;
+            let Some((buffer_snapshot, buffer_offset)) =
+                snapshot.point_to_buffer_offset(range.start)
+            else {
+                continue;
+            };
+            let has_lsp_colors = editor
+                .document_colors
+                .lsp_colors
+                .get(&buffer_snapshot.remote_id())
+                .map_or(false, |(_, colors)| !colors.is_empty());
+            if has_lsp_colors
+                || !is_color_literal(buffer_snapshot, buffer_offset..buffer_offset + range.len())
+            {
+                continue;
+            }
+            swatches.push(ColorSwatch {
+                inlay: InlayId::Color(0),
+                range: snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+                color,
+                source: ColorSource::HexLiteral,
+            });
+        }
+        swatches.sort_by(|a, b| a.range.start.cmp(&b.range.start, snapshot));
+    }
+
+    let old_swatches = &editor.document_colors.swatches;
+    let swatches_changed = old_swatches.len() != swatches.len()
+        || old_swatches.iter().zip(&swatches).any(|(old, new)| {
+            old.range.start.to_offset(snapshot) != new.range.start.to_offset(snapshot)
+                || old.color != new.color
+        });
+    if !swatches_changed {
+        for (old, new) in old_swatches.iter().zip(&mut swatches) {
+            new.inlay = old.inlay;
+        }
+        editor.document_colors.swatches = swatches;
+        return;
+    }
+
+    let to_remove = old_swatches.iter().map(|swatch| swatch.inlay).collect();
+    let mut to_insert = Vec::new();
+    for swatch in &mut swatches {
+        let inlay = Inlay::color(
+            post_inc(&mut editor.next_inlay_id),
+            swatch.range.start,
+            SWATCH,
+        );
+        swatch.inlay = inlay.id;
+        to_insert.push(inlay);
+    }
+    editor.splice_inlays(to_remove, to_insert, cx);
+    editor.clear_highlights::<DocumentColorState>(cx);
+    for swatch in &swatches {
+        editor.highlight_inlays::<DocumentColorState>(
+            vec![InlayHighlight {
+                inlay: swatch.inlay,
+                inlay_position: swatch.range.start,
+                range: 0..SWATCH.chars().next().map_or(0, char::len_utf8),
+            }],
+            HighlightStyle {
+                color: Some(swatch.color),
+                ..Default::default()
+            },
+            cx,
+        );
+    }
+    editor.document_colors.swatches = swatches;
+}
+
+/// Returns the ranges and colors of the hex color literals in the given text: a `#` followed by
+/// 3, 4, 6 or 8 hex digits, not surrounded by other word characters.
+fn find_hex_colors(text: &str) -> Vec<(Range<usize>, Hsla)> {
+    let is_word_char = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let mut colors = Vec::new();
+    for (start, _) in text.match_indices('#') {
+        if text[..start]
+            .chars()
+            .next_back()
+            .map_or(false, is_word_char)
+        {
+            continue;
+        }
+        let digits = text[start + 1..]
+            .chars()
+            .take_while(|c| c.is_ascii_hexdigit())
+            .count();
+        if !matches!(digits, 3 | 4 | 6 | 8) {
+            continue;
+        }
+        let end = start + 1 + digits;
+        if text[end..].chars().next().map_or(false, is_word_char) {
+            continue;
+        }
+        if let Ok(color) = Rgba::try_from(&text[start..end]) {
+            colors.push((start..end, color.into()));
+        }
+    }
+    colors
+}
+
+/// Whether the syntax tree considers the given hex literal a value rather than, say, a comment
+/// or a CSS id selector. Text without syntax is always considered a value.
+fn is_color_literal(buffer: &BufferSnapshot, range: Range<usize>) -> bool {
+    let Some(layer) = buffer.syntax_layer_at(range.start) else {
+        return true;
+    };
+    layer
+        .node()
+        .descendant_for_byte_range(range.start, range.end)
+        .map_or(false, |node| {
+            let kind = node.kind();
+            kind.contains("color") || kind.contains("string")
+        })
+}
+
+fn color_from_lsp(color: lsp::Color) -> Hsla {
+    Rgba {
+        r: color.red,
+        g: color.green,
+        b: color.blue,
+        a: color.alpha,
+    }
+    .into()
+}
+
+fn color_to_lsp(color: Hsla) -> lsp::Color {
+    let rgba = Rgba::from(color);
+    lsp::Color {
+        red: rgba.r,
+        green: rgba.g,
+        blue: rgba.b,
+        alpha: rgba.a,
+    }
+}
+
+/// Formats a color as hex, keeping the alpha channel and the case of the literal it replaces.
+fn format_hex_color(color: Hsla, replaced: &str) -> String {
+    let rgba = Rgba::from(color);
+    let component = |value: f32| (value.clamp(0., 1.) * 255.).round() as u8;
+    let mut hex = format!(
+        "#{:02x}{:02x}{:02x}",
+        component(rgba.r),
+        component(rgba.g),
+        component(rgba.b)
+    );
+    let digits = replaced.len().saturating_sub(1);
+    if digits == 4 || digits == 8 || rgba.a < 1. {
+        hex.push_str(&format!("{:02x}", component(rgba.a)));
+    }
+    if replaced.chars().any(|c| c.is_ascii_uppercase()) {
+        hex = hex.to_uppercase();
+    }
+    hex
+}
+
+impl Editor {
+    /// Opens a color picker for the swatch at the given index, writing the picked color over
+    /// its literal.
+    pub(crate) fn deploy_color_picker(
+        &mut self,
+        swatch_ix: usize,
+        position: Point<Pixels>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(swatch) = self.document_colors.swatches.get(swatch_ix).cloned() else {
+            return;
+        };
+        let picker = cx.new_view(|cx| ColorPicker::new(swatch.color, cx));
+        cx.focus(&picker.focus_handle(cx));
+        let _subscriptions = vec![
+            cx.subscribe(&picker, move |editor, _, event: &ColorPicked, cx| {
+                editor.apply_picked_color(swatch.clone(), event.0, cx);
+                editor.dismiss_color_picker(cx);
+            }),
+            cx.subscribe(&picker, |editor, _, _: &DismissEvent, cx| {
+                editor.dismiss_color_picker(cx);
+            }),
+        ];
+        self.document_colors.picker = Some(ColorPickerPopover {
+            position,
+            picker,
+            _subscriptions,
+        });
+        cx.notify();
+    }
+
+    fn dismiss_color_picker(&mut self, cx: &mut ViewContext<Self>) {
+        if let Some(popover) = self.document_colors.picker.take() {
+            if popover.picker.focus_handle(cx).contains_focused(cx) {
+                self.focus(cx);
+            }
+            cx.notify();
+        }
+    }
+
+    fn apply_picked_color(&mut self, swatch: ColorSwatch, color: Hsla, cx: &mut ViewContext<Self>) {
+        match swatch.source {
+            ColorSource::Lsp {
+                buffer,
+                color: document_color,
+            } => {
+                let Some(project) = self.project.clone() else {
+                    return;
+                };
+                let presentations = project.update(cx, |project, cx| {
+                    project.color_presentations(
+                        buffer.clone(),
+                        document_color,
+                        color_to_lsp(color),
+                        cx,
+                    )
+                });
+                cx.spawn(|_, mut cx| async move {
+                    let Some(presentation) = presentations.await?.into_iter().next() else {
+                        return Ok(());
+                    };
+                    buffer.update(&mut cx, |buffer, cx| {
+                        buffer.edit(presentation.edits, None, cx);
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
+            ColorSource::HexLiteral => {
+                let snapshot = self.buffer.read(cx).snapshot(cx);
+                let replaced = snapshot
+                    .text_for_range(swatch.range.clone())
+                    .collect::<String>();
+                let new_text = format_hex_color(color, &replaced);
+                self.transact(cx, |editor, cx| {
+                    editor.buffer.update(cx, |buffer, cx| {
+                        buffer.edit([(swatch.range, new_text)], None, cx)
+                    });
+                });
+            }
+        }
+    }
+}
+
+/// Emitted by the [`ColorPicker`] when a color is clicked.
+pub(crate) struct ColorPicked(Hsla);
+
+/// A palette of hues and lightnesses to pick a color from, keeping the alpha of the color
+/// being replaced.
+pub(crate) struct ColorPicker {
+    color: Hsla,
+    focus_handle: FocusHandle,
+    _on_blur_subscription: Subscription,
+}
+
+impl ColorPicker {
+    fn new(color: Hsla, cx: &mut ViewContext<Self>) -> Self {
+        let focus_handle = cx.focus_handle();
+        let _on_blur_subscription = cx.on_blur(&focus_handle, |_, cx| cx.emit(DismissEvent));
+        Self {
+            color,
+            focus_handle,
+            _on_blur_subscription,
+        }
+    }
+
+    fn cancel(&mut self, _: &menu::Cancel, cx: &mut ViewContext<Self>) {
+        cx.emit(DismissEvent);
+    }
+
+    fn palette_rows(&self) -> Vec<Vec<Hsla>> {
+        let alpha = self.color.a;
+        let mut rows = PICKER_LIGHTNESSES
+            .iter()
+            .map(|lightness| {
+                (0..PICKER_HUE_COUNT)
+                    .map(|ix| {
+                        gpui::hsla(ix as f32 / PICKER_HUE_COUNT as f32, 0.8, *lightness, alpha)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        rows.push(
+            (0..PICKER_HUE_COUNT)
+                .map(|ix| gpui::hsla(0., 0., ix as f32 / (PICKER_HUE_COUNT - 1) as f32, alpha))
+                .collect(),
+        );
+        rows
+    }
+}
+
+impl EventEmitter<ColorPicked> for ColorPicker {}
+
+impl EventEmitter<DismissEvent> for ColorPicker {}
+
+impl FocusableView for ColorPicker {
+    fn focus_handle(&self, _: &gpui::AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ColorPicker {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let border = cx.theme().colors().border;
+        let selected_border = cx.theme().colors().border_focused;
+        let current_color = self.color;
+        let hex = format_hex_color(current_color, "#rrggbb");
+
+        v_flex()
+            .occlude()
+            .elevation_2(cx)
+            .p_1()
+            .gap_1()
+            .track_focus(&self.focus_handle)
+            .key_context("menu")
+            .on_action(cx.listener(Self::cancel))
+            .on_mouse_down_out(cx.listener(|_, _, cx| cx.emit(DismissEvent)))
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        div()
+                            .size_4()
+                            .rounded_sm()
+                            .border_1()
+                            .border_color(border)
+                            .bg(current_color),
+                    )
+                    .child(Label::new(hex).size(LabelSize::Small).color(Color::Muted)),
+            )
+            .children(
+                self.palette_rows()
+                    .into_iter()
+                    .enumerate()
+                    .map(|(row_ix, row)| {
+                        h_flex().gap_0p5().children(row.into_iter().enumerate().map(
+                            |(column_ix, color)| {
+                                div()
+                                    .id((
+                                        "color-picker-swatch",
+                                        row_ix * PICKER_HUE_COUNT + column_ix,
+                                    ))
+                                    .size_4()
+                                    .rounded_sm()
+                                    .border_1()
+                                    .border_color(if color == current_color {
+                                        selected_border
+                                    } else {
+                                        border
+                                    })
+                                    .bg(color)
+                                    .cursor_pointer()
+                                    .hover(|style| style.border_color(selected_border))
+                                    .on_click(
+                                        cx.listener(move |_, _, cx| cx.emit(ColorPicked(color))),
+                                    )
+                            },
+                        ))
+                    }),
+            )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_hex_colors() {
+        let found = |text: &str| {
+            find_hex_colors(text)
+                .into_iter()
+                .map(|(range, _)| &text[range])
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            found("color: #fff; background: #12345678; border: #abcd"),
+            ["#fff", "#12345678", "#abcd"]
+        );
+        assert_eq!(found("let c = \"#1a2B3c\";"), ["#1a2B3c"]);
+        assert_eq!(
+            found("#12345 #fffff #ggg a#fff #fff-1 #fffz"),
+            Vec::<&str>::new()
+        );
+    }
+
+    #[test]
+    fn test_format_hex_color() {
+        let red = color_from_lsp(lsp::Color {
+            red: 1.,
+            green: 0.,
+            blue: 0.,
+            alpha: 1.,
+        });
+        assert_eq!(format_hex_color(red, "#00f"), "#ff0000");
+        assert_eq!(format_hex_color(red, "#00FF00"), "#FF0000");
+        assert_eq!(format_hex_color(red, "#00f8"), "#ff0000ff");
+
+        let translucent = Hsla { a: 0.5, ..red };
+        assert_eq!(format_hex_color(translucent, "#0000ff"), "#ff000080");
+    }
+}
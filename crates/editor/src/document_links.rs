@@ -0,0 +1,429 @@
+use std::{
+    ops::Range,
+    path::{Component, Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{px, HighlightStyle, Model, Task, UnderlineStyle, ViewContext};
+use language::{Buffer, Point};
+use linkify::{LinkFinder, LinkKind};
+use multi_buffer::{MultiBufferSnapshot, ToOffset as _};
+use project::{DocumentLink, Project, ProjectPath, WorktreeId};
+use settings::Settings;
+use text::BufferId;
+use theme::ActiveTheme as _;
+use util::{paths::PathLikeWithPosition, ResultExt};
+
+use crate::{hover_links::HoverLink, Anchor, Editor, EditorMode, EditorSettings};
+
+/// How long to wait after an edit before refetching the links of the buffers.
+const FETCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// The links of the buffers in an editor, underlined and opened with a cmd-click.
+#[derive(Default)]
+pub(crate) struct DocumentLinkState {
+    /// The links reported by language servers for each buffer. Buffers without any are
+    /// scanned for URLs and paths of project files instead.
+    lsp_links: HashMap<BufferId, (Model<Buffer>, Vec<DocumentLink>)>,
+    /// The visible links, sorted by their start.
+    links: Vec<EditorLink>,
+    fetch_task: Option<Task<()>>,
+}
+
+#[derive(Clone)]
+struct EditorLink {
+    /// The range of the link in the multibuffer.
+    range: Range<Anchor>,
+    target: LinkTarget,
+}
+
+#[derive(Clone)]
+enum LinkTarget {
+    /// A link reported by a language server, which may need resolving to know its target.
+    Lsp {
+        buffer: Model<Buffer>,
+        link: DocumentLink,
+    },
+    Url(String),
+    File(ProjectPath, Option<Point>),
+}
+
+impl DocumentLinkState {
+    fn link_at(&self, position: &Anchor, snapshot: &MultiBufferSnapshot) -> Option<&EditorLink> {
+        self.links.iter().find(|link| {
+            link.range.start.cmp(position, snapshot).is_le()
+                && link.range.end.cmp(position, snapshot).is_ge()
+        })
+    }
+}
+
+/// Fetches the links of all the buffers in the editor from their language servers, then
+/// updates the visible links.
+pub(super) fn refresh_document_links(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    if !EditorSettings::get_global(cx).document_links {
+        editor.document_links.fetch_task = None;
+        editor.document_links.lsp_links.clear();
+        update_document_links(editor, cx);
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        update_document_links(editor, cx);
+        return;
+    };
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    editor.document_links.fetch_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(FETCH_DEBOUNCE).await;
+        let Ok(fetches) = editor.update(&mut cx, |_, cx| {
+            buffers
+                .into_iter()
+                .map(|buffer| {
+                    let links =
+                        project.update(cx, |project, cx| project.document_links(&buffer, cx));
+                    async move { (buffer, links.await) }
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return;
+        };
+        let links_by_buffer = join_all(fetches).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.document_links.lsp_links = links_by_buffer
+                    .into_iter()
+                    .map(|(buffer, links)| (buffer.read(cx).remote_id(), (buffer, links)))
+                    .collect();
+                update_document_links(editor, cx);
+            })
+            .ok();
+    }));
+}
+
+/// Underlines the visible links, using the links reported by language servers, or the URLs
+/// and project file paths in the text of the buffers they don't report any links for.
+pub(super) fn update_document_links(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+
+    let display_snapshot = editor
+        .display_map
+        .update(cx, |display_map, cx| display_map.snapshot(cx));
+    let snapshot = &display_snapshot.buffer_snapshot;
+    let mut links = Vec::new();
+    if EditorSettings::get_global(cx).document_links {
+        let visible_range = editor.visible_buffer_range(&display_snapshot);
+        let multibuffer = editor.buffer.read(cx);
+        for (buffer, buffer_links) in editor.document_links.lsp_links.values() {
+            let buffer_snapshot = buffer.read(cx).snapshot();
+            for (excerpt_id, excerpt_range) in multibuffer.excerpts_for_buffer(buffer, cx) {
+                for link in buffer_links {
+                    let in_excerpt = excerpt_range
+                        .context
+                        .start
+                        .cmp(&link.range.start, &buffer_snapshot)
+                        .is_le()
+                        && excerpt_range
+                            .context
+                            .end
+                            .cmp(&link.range.end, &buffer_snapshot)
+                            .is_ge();
+                    if !in_excerpt {
+                        continue;
+                    }
+                    let (Some(start), Some(end)) = (
+                        snapshot.anchor_in_excerpt(excerpt_id, link.range.start),
+                        snapshot.anchor_in_excerpt(excerpt_id, link.range.end),
+                    ) else {
+                        continue;
+                    };
+                    let offset_range = start.to_offset(snapshot)..end.to_offset(snapshot);
+                    if offset_range.start < visible_range.end
+                        && offset_range.end > visible_range.start
+                    {
+                        links.push(EditorLink {
+                            range: start..end,
+                            target: LinkTarget::Lsp {
+                                buffer: buffer.clone(),
+                                link: link.clone(),
+                            },
+                        });
+                    }
+                }
+            }
+        }
+
+        let text = snapshot
+            .text_for_range(visible_range.clone())
+            .collect::<String>();
+        for (range, target) in find_links(&text) {
+            let range = visible_range.start + range.start..visible_range.start + range.end;
+            let Some((buffer_snapshot, _)) = snapshot.point_to_buffer_offset(range.start) else {
+                continue;
+            };
+            let has_lsp_links = editor
+                .document_links
+                .lsp_links
+                .get(&buffer_snapshot.remote_id())
+                .map_or(false, |(_, links)| !links.is_empty());
+            if has_lsp_links {
+                continue;
+            }
+            let target = match target {
+                DetectedLink::Url(url) => LinkTarget::Url(url),
+                DetectedLink::Path(path, position) => {
+                    let Some(project) = editor.project.as_ref() else {
+                        continue;
+                    };
+                    let Some(project_path) =
+                        project_file_path(project.read(cx), buffer_snapshot.file(), &path, cx)
+                    else {
+                        continue;
+                    };
+                    LinkTarget::File(project_path, position)
+                }
+            };
+            links.push(EditorLink {
+                range: snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end),
+                target,
+            });
+        }
+        links.sort_by(|a, b| a.range.start.cmp(&b.range.start, snapshot));
+    }
+
+    let style = HighlightStyle {
+        underline: Some(UnderlineStyle {
+            thickness: px(1.),
+            color: Some(cx.theme().colors().link_text_hover),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    if links.is_empty() {
+        editor.clear_highlights::<DocumentLinkState>(cx);
+    } else {
+        let ranges = links.iter().map(|link| link.range.clone()).collect();
+        editor.highlight_text::<DocumentLinkState>(ranges, style, cx);
+    }
+    editor.document_links.links = links;
+}
+
+/// Returns the range and target of the link at the given position, if any, resolving it first
+/// when its language server returned it without a target.
+pub(crate) fn hover_link_at(
+    editor: &Editor,
+    position: &Anchor,
+    cx: &mut ViewContext<Editor>,
+) -> Option<Task<Option<(Range<Anchor>, HoverLink)>>> {
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let link = editor.document_links.link_at(position, &snapshot)?.clone();
+    let range = link.range;
+    match link.target {
+        LinkTarget::Url(url) => Some(Task::ready(Some((range, HoverLink::Url(url))))),
+        LinkTarget::File(path, position) => {
+            Some(Task::ready(Some((range, HoverLink::File(path, position)))))
+        }
+        LinkTarget::Lsp { buffer, link } => {
+            let project = editor.project.clone()?;
+            let resolve = project.update(cx, |project, cx| {
+                project.resolve_document_link(buffer, link, cx)
+            });
+            Some(cx.spawn(|_, mut cx| async move {
+                let target = resolve.await.log_err()?.lsp_link.target?;
+                let hover_link = project
+                    .update(&mut cx, |project, cx| {
+                        hover_link_for_url(project, target, cx)
+                    })
+                    .ok()?;
+                Some((range, hover_link))
+            }))
+        }
+    }
+}
+
+/// Opens links to files of the project in the editor, and other links in the browser. Links to
+/// files may end with a `#L<line>` or `#L<line>,<column>` fragment.
+fn hover_link_for_url(project: &Project, url: lsp::Url, cx: &gpui::AppContext) -> HoverLink {
+    if url.scheme() == "file" {
+        let project_path = url
+            .to_file_path()
+            .ok()
+            .and_then(|abs_path| project.project_path_for_absolute_path(&abs_path, cx));
+        if let Some(project_path) = project_path {
+            let position = url
+                .fragment()
+                .and_then(|fragment| fragment.strip_prefix('L'))
+                .and_then(|position| {
+                    let (row, column) = position.split_once(',').unwrap_or((position, "1"));
+                    Some(Point::new(
+                        row.parse::<u32>().ok()?.saturating_sub(1),
+                        column.parse::<u32>().ok()?.saturating_sub(1),
+                    ))
+                });
+            return HoverLink::File(project_path, position);
+        }
+    }
+    HoverLink::Url(url.to_string())
+}
+
+/// Returns the project file a path found in a buffer refers to, if it exists. Relative paths
+/// are looked up from the directory of the buffer's file, then from the root of its worktree.
+fn project_file_path(
+    project: &Project,
+    file: Option<&Arc<dyn language::File>>,
+    path: &Path,
+    cx: &gpui::AppContext,
+) -> Option<ProjectPath> {
+    let is_file = |project_path: &ProjectPath| {
+        project
+            .entry_for_path(project_path, cx)
+            .map_or(false, |entry| entry.is_file())
+    };
+    if path.is_absolute() {
+        return project
+            .project_path_for_absolute_path(path, cx)
+            .filter(is_file);
+    }
+
+    let file = file?;
+    let worktree_id = WorktreeId::from_usize(file.worktree_id());
+    let mut candidates = Vec::new();
+    if let Some(directory) = file.path().parent() {
+        candidates.push(directory.join(path));
+    }
+    if !path.starts_with(".") && !path.starts_with("..") {
+        candidates.push(path.to_path_buf());
+    }
+    candidates.into_iter().find_map(|candidate| {
+        let project_path = ProjectPath {
+            worktree_id,
+            path: normalize_relative_path(&candidate)?.into(),
+        };
+        is_file(&project_path).then_some(project_path)
+    })
+}
+
+/// Resolves the `.` and `..` components of a relative path, or returns `None` when the path
+/// leaves its root.
+fn normalize_relative_path(path: &Path) -> Option<PathBuf> {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    return None;
+                }
+            }
+            Component::Normal(name) => normalized.push(name),
+            Component::RootDir | Component::Prefix(_) => return None,
+        }
+    }
+    Some(normalized)
+}
+
+#[derive(Debug, PartialEq)]
+enum DetectedLink {
+    Url(String),
+    /// A path containing at least one separator, with the position following it, if any.
+    Path(PathBuf, Option<Point>),
+}
+
+/// Returns the ranges of the URLs and the file paths, such as `src/main.rs:12:5`, in the
+/// given text.
+fn find_links(text: &str) -> Vec<(Range<usize>, DetectedLink)> {
+    let mut finder = LinkFinder::new();
+    finder.kinds(&[LinkKind::Url]);
+    let mut links = finder
+        .links(text)
+        .map(|link| {
+            (
+                link.start()..link.end(),
+                DetectedLink::Url(link.as_str().to_string()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let is_path_char = |c: char| c.is_alphanumeric() || "/\\._-~:@+".contains(c);
+    let mut token_start = None;
+    let mut tokens = Vec::new();
+    for (ix, c) in text.char_indices().chain([(text.len(), ' ')]) {
+        if is_path_char(c) {
+            token_start.get_or_insert(ix);
+        } else if let Some(start) = token_start.take() {
+            tokens.push((start, &text[start..ix]));
+        }
+    }
+    for (start, token) in tokens {
+        let token = token.trim_end_matches(|c| c == '.' || c == ':');
+        if !token.contains('/') || token.contains("://") || token.chars().all(|c| c == '/') {
+            continue;
+        }
+        let range = start..start + token.len();
+        if links
+            .iter()
+            .any(|(link_range, _)| link_range.start < range.end && range.start < link_range.end)
+        {
+            continue;
+        }
+        let Ok(path) =
+            PathLikeWithPosition::parse_str(token, |path| anyhow::Ok(PathBuf::from(path)))
+        else {
+            continue;
+        };
+        let position = path.row.map(|row| {
+            Point::new(
+                row.saturating_sub(1),
+                path.column.unwrap_or(1).saturating_sub(1),
+            )
+        });
+        links.push((range, DetectedLink::Path(path.path_like, position)));
+    }
+    links.sort_by_key(|(range, _)| range.start);
+    links
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_links() {
+        let text = "see https://zed.dev/docs, src/main.rs:12:5 and ../lib.rs. a/b or 1/2 // c";
+        let links = find_links(text)
+            .into_iter()
+            .map(|(range, link)| (&text[range], link))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            links,
+            [
+                (
+                    "https://zed.dev/docs",
+                    DetectedLink::Url("https://zed.dev/docs".into())
+                ),
+                (
+                    "src/main.rs:12:5",
+                    DetectedLink::Path("src/main.rs".into(), Some(Point::new(11, 4)))
+                ),
+                ("../lib.rs", DetectedLink::Path("../lib.rs".into(), None)),
+                ("a/b", DetectedLink::Path("a/b".into(), None)),
+                ("1/2", DetectedLink::Path("1/2".into(), None)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_normalize_relative_path() {
+        assert_eq!(
+            normalize_relative_path(Path::new("src/./a/../b.rs")),
+            Some(PathBuf::from("src/b.rs"))
+        );
+        assert_eq!(normalize_relative_path(Path::new("../b.rs")), None);
+    }
+}
@@ -15,10 +15,15 @@
 pub mod actions;
 mod blame_entry_tooltip;
 mod blink_manager;
+mod breakpoints;
+mod code_lens;
 mod debounced_delay;
 pub mod display_map;
+mod document_colors;
+mod document_links;
 mod editor_settings;
 mod element;
+mod folding_ranges;
 mod git;
 mod highlight_matching_bracket;
 mod hover_links;
@@ -27,14 +32,28 @@ mod hunk_diff;
 mod indent_guides;
 mod inlay_hint_cache;
 mod inline_completion_provider;
+mod inline_values;
 pub mod items;
+mod linked_editing_indicator;
+mod linked_editing_ranges;
+mod merge_conflicts;
 mod mouse_context_menu;
 pub mod movement;
 mod persistence;
+mod rainbow_brackets;
+mod rebase_todo;
+mod reflow;
+mod related_files;
+mod rename_preview;
 mod rust_analyzer_ext;
 pub mod scroll;
+mod selection_drag;
 mod selections_collection;
+mod semantic_tokens;
+mod snippet_variables;
+mod sticky_scroll;
 pub mod tasks;
+mod unicode_highlights;
 
 #[cfg(test)]
 mod editor_tests;
@@ -88,10 +107,27 @@ use language::{
     Point, Selection, SelectionGoal, TransactionId,
 };
 use language::{BufferRow, Runnable, RunnableRange};
+pub use linked_editing_indicator::LinkedEditingIndicator;
+use linked_editing_ranges::refresh_linked_ranges;
+use rainbow_brackets::refresh_rainbow_brackets;
+use rebase_todo::{refresh_rebase_todo, RebaseTodoState};
+use reflow::LinePrefixes;
+pub use rename_preview::RenamePreview;
+use semantic_tokens::{
+    refresh_semantic_tokens, update_semantic_token_highlights, SemanticTokensState,
+};
+pub use sticky_scroll::StickyHeader;
 use task::{ResolvedTask, TaskTemplate, TaskVariables};
+use unicode_highlights::{refresh_unicode_highlights, UnicodeHighlightState};
 
+use code_lens::{refresh_code_lenses, CodeLensState};
+use document_colors::{refresh_document_colors, update_color_swatches, DocumentColorState};
+use document_links::{refresh_document_links, DocumentLinkState};
+use folding_ranges::{folding_ranges_in_excerpts, refresh_folding_ranges, FoldingRangesState};
 use hover_links::{HoverLink, HoveredLinkState, InlayHighlight};
+use inline_values::{refresh_inline_values, InlineValuesState};
 use lsp::{DiagnosticSeverity, LanguageServerId};
+use merge_conflicts::{refresh_merge_conflicts, MergeConflictState};
 use mouse_context_menu::MouseContextMenu;
 use movement::TextLayoutDetails;
 pub use multi_buffer::{
@@ -113,7 +149,8 @@ use selections_collection::{resolve_multiple, MutableSelectionsCollection, Selec
 use serde::{Deserialize, Serialize};
 use settings::{update_settings_file, Settings, SettingsStore};
 use smallvec::SmallVec;
-use snippet::Snippet;
+use snippet::{Snippet, Transform};
+use snippet_variables::snippet_variable;
 use std::ops::Not as _;
 use std::{
     any::TypeId,
@@ -141,11 +178,12 @@ use util::{defer, maybe, post_inc, RangeExt, ResultExt, TryFutureExt};
 use workspace::item::{ItemHandle, PreviewTabsSettings};
 use workspace::notifications::{DetachAndPromptErr, NotificationId};
 use workspace::{
-    searchable::SearchEvent, ItemNavHistory, SplitDirection, ViewId, Workspace, WorkspaceId,
+    searchable::SearchEvent, ItemId, ItemNavHistory, SplitDirection, ViewId, Workspace, WorkspaceId,
 };
-use workspace::{OpenInTerminal, OpenTerminal, TabBarSettings, Toast};
+use workspace::{ClipboardHistory, OpenInTerminal, OpenTerminal, TabBarSettings, Toast};
 
 use crate::hover_links::find_url;
+use crate::persistence::DB;
 
 pub const DEFAULT_MULTIBUFFER_CONTEXT: u32 = 2;
 const CURSOR_BLINK_INTERVAL: Duration = Duration::from_millis(500);
@@ -227,6 +265,8 @@ pub fn render_parsed_markdown(
 pub(crate) enum InlayId {
     Suggestion(usize),
     Hint(usize),
+    Invisible(usize),
+    Color(usize),
 }
 
 impl InlayId {
@@ -234,6 +274,8 @@ impl InlayId {
         match self {
             Self::Suggestion(id) => *id,
             Self::Hint(id) => *id,
+            Self::Invisible(id) => *id,
+            Self::Color(id) => *id,
         }
     }
 }
@@ -435,6 +477,7 @@ pub struct Editor {
     pub selections: SelectionsCollection,
     pub scroll_manager: ScrollManager,
     columnar_selection_tail: Option<Anchor>,
+    block_selection: Option<BlockSelection>,
     add_selections_state: Option<AddSelectionsState>,
     select_next_state: Option<SelectNextState>,
     select_prev_state: Option<SelectNextState>,
@@ -491,6 +534,8 @@ pub struct Editor {
     remote_id: Option<ViewId>,
     hover_state: HoverState,
     gutter_hovered: bool,
+    /// The row of the gutter under the mouse, where a breakpoint can be added by clicking.
+    gutter_hovered_row: Option<DisplayRow>,
     hovered_link_state: Option<HoveredLinkState>,
     inline_completion_provider: Option<RegisteredInlineCompletionProvider>,
     active_inline_completion: Option<Inlay>,
@@ -523,6 +568,18 @@ pub struct Editor {
     tasks: BTreeMap<(BufferId, BufferRow), RunnableTasks>,
     tasks_update_task: Option<Task<()>>,
     previous_search_ranges: Option<Arc<[Range<Anchor>]>>,
+    linked_edit_ranges: linked_editing_ranges::LinkedEditingRanges,
+    linked_editing_range_task: Option<Task<Option<()>>>,
+    unicode_highlights: UnicodeHighlightState,
+    code_lens: CodeLensState,
+    document_colors: DocumentColorState,
+    document_links: DocumentLinkState,
+    folding_ranges: FoldingRangesState,
+    semantic_tokens: SemanticTokensState,
+    inline_values: InlineValuesState,
+    merge_conflicts: MergeConflictState,
+    rebase_todo: RebaseTodoState,
+    selection_drop_cursor: Option<DisplayPoint>,
 }
 
 #[derive(Clone)]
@@ -699,6 +756,36 @@ struct RowHighlight {
     should_autoscroll: bool,
 }
 
+/// A rectangular selection, made of a selection on each of its lines. Its columns may extend
+/// past the end of the shorter lines, where the selections are clipped to the line end.
+#[derive(Clone, Debug)]
+pub(crate) struct BlockSelection {
+    /// The first and last lines of the block.
+    rows: RangeInclusive<Anchor>,
+    /// The display columns spanned by the block.
+    columns: Range<u32>,
+    reversed: bool,
+}
+
+impl BlockSelection {
+    pub(crate) fn display_rows(&self, snapshot: &DisplaySnapshot) -> RangeInclusive<DisplayRow> {
+        self.rows.start().to_display_point(snapshot).row()
+            ..=self.rows.end().to_display_point(snapshot).row()
+    }
+
+    pub(crate) fn columns(&self) -> Range<u32> {
+        self.columns.clone()
+    }
+
+    pub(crate) fn head_column(&self) -> u32 {
+        if self.reversed {
+            self.columns.start
+        } else {
+            self.columns.end
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 struct AddSelectionsState {
     above: bool,
@@ -731,6 +818,10 @@ struct AutocloseRegion {
 #[derive(Debug)]
 struct SnippetState {
     ranges: Vec<Vec<Range<Anchor>>>,
+    /// The transforms applied to each of the ranges once their tabstop is left.
+    transforms: Vec<Vec<Option<Transform>>>,
+    /// The values offered as completions for each tabstop.
+    choices: Vec<Vec<String>>,
     active_index: usize,
 }
 
@@ -862,6 +953,30 @@ struct CompletionsMenu {
     selected_completion_documentation_resolve_debounce: Arc<Mutex<DebouncedDelay>>,
 }
 
+/// The text shown at the end of a completion: the imports it adds when confirmed, or else its
+/// single-line documentation, or else the description the language server gave for it.
+fn completion_end_slot_text(
+    completion: &Completion,
+    documentation: &Option<Documentation>,
+) -> Option<String> {
+    if let Some(text) = completion.additional_edits_text() {
+        return Some(text);
+    }
+    if let Some(Documentation::SingleLine(text)) = documentation {
+        if !text.trim().is_empty() {
+            return Some(text.clone());
+        }
+    }
+    let description = completion
+        .lsp_completion
+        .label_details
+        .as_ref()?
+        .description
+        .as_ref()?
+        .trim();
+    (!description.is_empty()).then(|| description.to_string())
+}
+
 impl CompletionsMenu {
     fn select_first(&mut self, project: Option<&Model<Project>>, cx: &mut ViewContext<Editor>) {
         self.selected_item = 0;
@@ -992,10 +1107,16 @@ impl CompletionsMenu {
                 let documentation = &completion.documentation;
 
                 let mut len = completion.label.text.chars().count();
-                if let Some(Documentation::SingleLine(text)) = documentation {
-                    if show_completion_documentation {
-                        len += text.chars().count();
-                    }
+                if let Some(detail) = completion.label_detail() {
+                    len += detail.chars().count();
+                }
+                let documentation = if show_completion_documentation {
+                    documentation
+                } else {
+                    &None
+                };
+                if let Some(text) = completion_end_slot_text(completion, documentation) {
+                    len += text.chars().count();
                 }
 
                 len
@@ -1084,22 +1205,17 @@ impl CompletionsMenu {
                         );
                         let completion_label = StyledText::new(completion.label.text.clone())
                             .with_highlights(&style.text, highlights);
+                        let label_detail = completion.label_detail().map(|detail| {
+                            Label::new(detail.to_string())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                        });
                         let documentation_label =
-                            if let Some(Documentation::SingleLine(text)) = documentation {
-                                if text.trim().is_empty() {
-                                    None
-                                } else {
-                                    Some(
-                                        h_flex().ml_4().child(
-                                            Label::new(text.clone())
-                                                .size(LabelSize::Small)
-                                                .color(Color::Muted),
-                                        ),
-                                    )
-                                }
-                            } else {
-                                None
-                            };
+                            completion_end_slot_text(completion, documentation).map(|text| {
+                                h_flex().ml_4().child(
+                                    Label::new(text).size(LabelSize::Small).color(Color::Muted),
+                                )
+                            });
 
                         div().min_w(px(220.)).max_w(px(540.)).child(
                             ListItem::new(mat.candidate_id)
@@ -1116,7 +1232,13 @@ impl CompletionsMenu {
                                         task.detach_and_log_err(cx)
                                     }
                                 }))
-                                .child(h_flex().overflow_hidden().child(completion_label))
+                                .child(
+                                    h_flex()
+                                        .overflow_hidden()
+                                        .gap_1()
+                                        .child(completion_label)
+                                        .children(label_detail),
+                                )
                                 .end_slot::<Div>(documentation_label),
                         )
                     })
@@ -1666,8 +1788,15 @@ impl Editor {
 
         let blink_manager = cx.new_model(|cx| BlinkManager::new(CURSOR_BLINK_INTERVAL, cx));
 
-        let soft_wrap_mode_override =
-            (mode == EditorMode::SingleLine).then(|| language_settings::SoftWrap::PreferLine);
+        let is_large_file = mode == EditorMode::Full
+            && buffer.read(cx).as_singleton().map_or(false, |buffer| {
+                ProjectSettings::get_global(cx).is_large_file(buffer.read(cx).len())
+            });
+        let soft_wrap_mode_override = if is_large_file {
+            Some(language_settings::SoftWrap::None)
+        } else {
+            (mode == EditorMode::SingleLine).then(|| language_settings::SoftWrap::PreferLine)
+        };
 
         let mut project_subscriptions = Vec::new();
         if mode == EditorMode::Full {
@@ -1680,6 +1809,12 @@ impl Editor {
                 project_subscriptions.push(cx.subscribe(project, |editor, _, event, cx| {
                     if let project::Event::RefreshInlayHints = event {
                         editor.refresh_inlay_hints(InlayHintRefreshReason::RefreshRequested, cx);
+                    } else if let project::Event::LanguageServerAdded(_) = event {
+                        refresh_code_lenses(editor, cx);
+                        refresh_document_colors(editor, cx);
+                        refresh_document_links(editor, cx);
+                        refresh_folding_ranges(editor, cx);
+                        refresh_semantic_tokens(editor, cx);
                     } else if let project::Event::SnippetEdit(id, snippet_edits) = event {
                         if let Some(buffer) = editor.buffer.read(cx).buffer(*id) {
                             let focus_handle = editor.focus_handle(cx);
@@ -1700,6 +1835,11 @@ impl Editor {
                 project_subscriptions.push(cx.observe(&task_inventory, |editor, _, cx| {
                     editor.tasks_update_task = Some(editor.refresh_runnables(cx));
                 }));
+                let dap_store = project.read(cx).dap_store().clone();
+                project_subscriptions.push(cx.observe(&dap_store, |editor, _, cx| {
+                    refresh_inline_values(editor, cx);
+                    cx.notify();
+                }));
             }
         }
 
@@ -1725,6 +1865,7 @@ impl Editor {
             selections,
             scroll_manager: ScrollManager::new(cx),
             columnar_selection_tail: None,
+            block_selection: None,
             add_selections_state: None,
             select_next_state: None,
             select_prev_state: None,
@@ -1787,6 +1928,7 @@ impl Editor {
             inlay_hint_cache: InlayHintCache::new(inlay_hint_settings),
             expanded_hunks: ExpandedHunks::default(),
             gutter_hovered: false,
+            gutter_hovered_row: None,
             pixel_position_of_newest_cursor: None,
             last_bounds: None,
             expect_bounds_change: None,
@@ -1825,10 +1967,25 @@ impl Editor {
                 }),
             ],
             tasks_update_task: None,
+            linked_edit_ranges: Default::default(),
+            linked_editing_range_task: None,
             previous_search_ranges: None,
+            unicode_highlights: UnicodeHighlightState::default(),
+            code_lens: CodeLensState::default(),
+            document_colors: DocumentColorState::default(),
+            document_links: DocumentLinkState::default(),
+            folding_ranges: FoldingRangesState::default(),
+            semantic_tokens: SemanticTokensState::default(),
+            inline_values: InlineValuesState::default(),
+            merge_conflicts: MergeConflictState::default(),
+            rebase_todo: RebaseTodoState::default(),
+            selection_drop_cursor: None,
         };
         this.tasks_update_task = Some(this.refresh_runnables(cx));
         this._subscriptions.extend(project_subscriptions);
+        refresh_inline_values(&mut this, cx);
+        refresh_merge_conflicts(&mut this, cx);
+        refresh_rebase_todo(&mut this, cx);
 
         this.end_selection(cx);
         this.scroll_manager.show_scrollbar(cx);
@@ -1837,6 +1994,14 @@ impl Editor {
             let should_auto_hide_scrollbars = cx.should_auto_hide_scrollbars();
             cx.set_global(ScrollbarAutoHide(should_auto_hide_scrollbars));
 
+            // Large files are opened without the features that would make them slow to display,
+            // each of which can be enabled again on demand.
+            if is_large_file {
+                this.inlay_hint_cache.enabled = false;
+                this.display_map
+                    .update(cx, |display_map, _| display_map.show_diagnostics = false);
+            }
+
             if this.git_blame_inline_enabled {
                 this.git_blame_inline_enabled = true;
                 this.start_git_blame_inline(false, cx);
@@ -1892,6 +2057,10 @@ impl Editor {
             key_context.set("extension", extension.to_string());
         }
 
+        if self.rebase_todo.is_active() {
+            key_context.add("rebase_todo");
+        }
+
         if self.has_active_inline_completion(cx) {
             key_context.add("copilot_suggestion");
             key_context.add("inline_completion");
@@ -2211,6 +2380,7 @@ impl Editor {
             .update(cx, |display_map, cx| display_map.snapshot(cx));
         let buffer = &display_map.buffer_snapshot;
         self.add_selections_state = None;
+        self.block_selection = None;
         self.select_next_state = None;
         self.select_prev_state = None;
         self.select_larger_syntax_node_stack.clear();
@@ -2292,6 +2462,7 @@ impl Editor {
             self.refresh_code_actions(cx);
             self.refresh_document_highlights(cx);
             refresh_matching_bracket_highlights(self, cx);
+            refresh_linked_ranges(self, cx);
             self.discard_inline_completion(false, cx);
             if self.git_blame_inline_enabled {
                 self.start_inline_blame_timer(cx);
@@ -2685,10 +2856,12 @@ impl Editor {
         let end_column = cmp::max(tail.column(), goal_column);
         let reversed = start_column < tail.column();
 
+        // Lines ending before the block get a cursor at their end, which is moved into the
+        // virtual space of the block when text is inserted.
         let selection_ranges = (start_row.0..=end_row.0)
             .map(DisplayRow)
             .filter_map(|row| {
-                if start_column <= display_map.line_len(row) && !display_map.is_block_line(row) {
+                if !display_map.is_block_line(row) {
                     let start = display_map
                         .clip_point(DisplayPoint::new(row, start_column), Bias::Left)
                         .to_point(display_map);
@@ -2709,9 +2882,79 @@ impl Editor {
         self.change_selections(None, cx, |s| {
             s.select_ranges(selection_ranges);
         });
+        self.block_selection = Some(BlockSelection {
+            rows: display_map.display_point_to_anchor(DisplayPoint::new(start_row, 0), Bias::Left)
+                ..=display_map.display_point_to_anchor(DisplayPoint::new(end_row, 0), Bias::Left),
+            columns: start_column..end_column,
+            reversed,
+        });
         cx.notify();
     }
 
+    /// Turns the newest selection into a block selection spanning the same lines and columns,
+    /// or a block selection back into a regular selection between its corners.
+    pub fn toggle_block_selection(&mut self, _: &ToggleBlockSelection, cx: &mut ViewContext<Self>) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        if let Some(block) = self.block_selection.take() {
+            let rows = block.display_rows(&display_map);
+            let tail_column = if block.reversed {
+                block.columns.end
+            } else {
+                block.columns.start
+            };
+            let tail =
+                display_map.clip_point(DisplayPoint::new(*rows.start(), tail_column), Bias::Left);
+            let head = display_map.clip_point(
+                DisplayPoint::new(*rows.end(), block.head_column()),
+                Bias::Left,
+            );
+            self.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                s.select_display_ranges([tail..head]);
+            });
+        } else {
+            let newest = self.selections.newest_display(cx);
+            let (tail, head) = (newest.tail(), newest.head());
+            self.select_columns(tail, head, head.column(), &display_map, cx);
+        }
+    }
+
+    pub(crate) fn block_selection(&self) -> Option<&BlockSelection> {
+        self.block_selection.as_ref()
+    }
+
+    /// Pads the lines ending before the block selection with spaces, moving their cursors into
+    /// the block, so that the text inserted next lines up on every row.
+    fn fill_block_selection_virtual_space(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(block) = self.block_selection.take() else {
+            return;
+        };
+
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let mut selections = self.selections.all::<Point>(cx);
+        let mut edits = Vec::new();
+        for selection in &mut selections {
+            let row = selection.head().to_display_point(&display_map).row();
+            let line_len = display_map.line_len(row);
+            if selection.is_empty() && line_len < block.columns.start {
+                let padding = block.columns.start - line_len;
+                let line_end = selection.head();
+                edits.push((line_end..line_end, " ".repeat(padding as usize)));
+                let new_head = Point::new(line_end.row, line_end.column + padding);
+                selection.start = new_head;
+                selection.end = new_head;
+            }
+        }
+        if edits.is_empty() {
+            return;
+        }
+
+        self.transact(cx, |this, cx| {
+            this.buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+            this.change_selections(None, cx, |s| s.select(selections));
+        });
+    }
+
     pub fn has_pending_nonempty_selection(&self) -> bool {
         let pending_nonempty_selection = match self.selections.pending_anchor() {
             Some(Selection { start, end, .. }) => start != end,
@@ -2781,16 +3024,40 @@ impl Editor {
             return;
         }
 
+        if self.block_selection.is_some() {
+            self.transact(cx, |this, cx| {
+                this.fill_block_selection_virtual_space(cx);
+                this.handle_input(&text, cx);
+            });
+            return;
+        }
+
         let selections = self.selections.all_adjusted(cx);
         let mut brace_inserted = false;
         let mut edits = Vec::new();
         let mut new_selections = Vec::with_capacity(selections.len());
         let mut new_autoclose_regions = Vec::new();
+        let mut linked_edits = HashMap::<_, Vec<_>>::default();
         let snapshot = self.buffer.read(cx).read(cx);
 
         for (selection, autoclose_region) in
             self.selections_with_autoclose_regions(selections, &snapshot)
         {
+            if !self.linked_edit_ranges.is_empty() {
+                let start_anchor = snapshot.anchor_before(selection.start);
+                let end_anchor = snapshot.anchor_after(selection.end);
+                if let Some(ranges) = self
+                    .linked_editing_ranges_for(start_anchor.text_anchor..end_anchor.text_anchor, cx)
+                {
+                    for (buffer, edits) in ranges {
+                        linked_edits
+                            .entry(buffer.clone())
+                            .or_default()
+                            .extend(edits.into_iter().map(|range| (range, text.clone())));
+                    }
+                }
+            }
+
             if let Some(scope) = snapshot.language_scope_at(selection.head()) {
                 // Determine if the inserted text matches the opening or closing
                 // bracket of any of this language's bracket pairs.
@@ -2973,6 +3240,7 @@ impl Editor {
             this.buffer.update(cx, |buffer, cx| {
                 buffer.edit(edits, this.autoindent_mode.clone(), cx);
             });
+            this.apply_linked_edits(linked_edits, cx);
 
             let new_anchor_selections = new_selections.iter().map(|e| &e.0);
             let new_selection_deltas = new_selections.iter().map(|e| e.1);
@@ -3020,7 +3288,7 @@ impl Editor {
                 s.select(new_selections)
             });
 
-            if !brace_inserted && EditorSettings::get_global(cx).use_on_type_format {
+            if !brace_inserted {
                 if let Some(on_type_format_task) =
                     this.trigger_on_type_formatting(text.to_string(), cx)
                 {
@@ -3216,6 +3484,9 @@ impl Editor {
                 .collect();
 
             this.change_selections(Some(Autoscroll::fit()), cx, |s| s.select(new_selections));
+            if let Some(on_type_format_task) = this.trigger_on_type_formatting("\n".into(), cx) {
+                on_type_format_task.detach_and_log_err(cx);
+            }
             this.refresh_inline_completion(true, cx);
         });
     }
@@ -3571,6 +3842,41 @@ impl Editor {
         self.inlay_hint_cache.enabled
     }
 
+    pub fn toggle_diagnostics(&mut self, _: &ToggleDiagnostics, cx: &mut ViewContext<Self>) {
+        self.display_map.update(cx, |display_map, _| {
+            display_map.show_diagnostics = !display_map.show_diagnostics
+        });
+        cx.notify();
+    }
+
+    pub fn diagnostics_enabled(&self, cx: &AppContext) -> bool {
+        self.display_map.read(cx).show_diagnostics
+    }
+
+    /// Detects the language of the buffers without one, such as large files, which are opened
+    /// as plain text.
+    pub fn enable_syntax_highlighting(
+        &mut self,
+        _: &EnableSyntaxHighlighting,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let buffers = self
+            .buffer
+            .read(cx)
+            .all_buffers()
+            .into_iter()
+            .filter(|buffer| buffer.read(cx).language().is_none())
+            .collect::<Vec<_>>();
+        project.update(cx, |project, cx| {
+            for buffer in &buffers {
+                project.assign_detected_language(buffer, cx);
+            }
+        });
+    }
+
     fn refresh_inlay_hints(&mut self, reason: InlayHintRefreshReason, cx: &mut ViewContext<Self>) {
         if self.project.is_none() || self.mode != EditorMode::Full {
             return;
@@ -3754,6 +4060,18 @@ impl Editor {
             .buffer
             .read(cx)
             .text_anchor_for_position(position, cx)?;
+        let use_on_type_format = {
+            let buffer = buffer.read(cx);
+            language_settings::language_settings(
+                buffer.language_at(buffer_position).as_ref(),
+                buffer.file(),
+                cx,
+            )
+            .use_on_type_format
+        };
+        if !use_on_type_format {
+            return None;
+        }
 
         // OnTypeFormatting returns a list of edits, no need to pass them between Zed instances,
         // hence we do LSP request & edit on host side only — add formats to host's history.
@@ -3934,7 +4252,12 @@ impl Editor {
         let text;
 
         if completion.is_snippet() {
-            snippet = Some(Snippet::parse(&completion.new_text).log_err()?);
+            snippet = Some(
+                Snippet::parse_with_variables(&completion.new_text, |name| {
+                    snippet_variable(self, name, cx)
+                })
+                .log_err()?,
+            );
             text = snippet.as_ref().unwrap().text.clone();
         } else {
             snippet = None;
@@ -3998,20 +4321,29 @@ impl Editor {
             }
         }
         let text = &text[common_prefix_len..];
-
-        cx.emit(EditorEvent::InputHandled {
-            utf16_range_to_replace: range_to_replace,
-            text: text.into(),
+        let snippet = snippet.map(|mut snippet| {
+            snippet.text = text.to_string();
+            for tabstop in snippet.tabstops.iter_mut().flatten() {
+                tabstop.start -= common_prefix_len as isize;
+                tabstop.end -= common_prefix_len as isize;
+            }
+            snippet
         });
 
-        self.transact(cx, |this, cx| {
-            if let Some(mut snippet) = snippet {
-                snippet.text = text.to_string();
-                for tabstop in snippet.tabstops.iter_mut().flatten() {
-                    tabstop.start -= common_prefix_len as isize;
-                    tabstop.end -= common_prefix_len as isize;
-                }
+        if let Some(snippet) = &snippet {
+            cx.emit(EditorEvent::SnippetInputHandled {
+                utf16_range_to_replace: range_to_replace,
+                snippet: snippet.clone(),
+            });
+        } else {
+            cx.emit(EditorEvent::InputHandled {
+                utf16_range_to_replace: range_to_replace,
+                text: text.into(),
+            });
+        }
 
+        self.transact(cx, |this, cx| {
+            if let Some(snippet) = snippet {
                 this.insert_snippet(&ranges, snippet, cx).log_err();
             } else {
                 this.buffer.update(cx, |buffer, cx| {
@@ -4847,6 +5179,8 @@ impl Editor {
         struct Tabstop<T> {
             is_end_tabstop: bool,
             ranges: Vec<Range<T>>,
+            transforms: Vec<Option<Transform>>,
+            choices: Vec<String>,
         }
 
         let tabstops = self.buffer.update(cx, |buffer, cx| {
@@ -4865,13 +5199,19 @@ impl Editor {
             snippet
                 .tabstops
                 .iter()
-                .map(|tabstop| {
+                .enumerate()
+                .map(|(tabstop_ix, tabstop)| {
                     let is_end_tabstop = tabstop.first().map_or(false, |tabstop| {
                         tabstop.is_empty() && tabstop.start == snippet.text.len() as isize
                     });
+                    let transforms = snippet.transforms.get(tabstop_ix);
                     let mut tabstop_ranges = tabstop
                         .iter()
-                        .flat_map(|tabstop_range| {
+                        .enumerate()
+                        .flat_map(|(range_ix, tabstop_range)| {
+                            let transform = transforms
+                                .and_then(|transforms| transforms.get(range_ix).cloned())
+                                .flatten();
                             let mut delta = 0_isize;
                             insertion_ranges.iter().map(move |insertion_range| {
                                 let insertion_start = insertion_range.start as isize + delta;
@@ -4882,15 +5222,22 @@ impl Editor {
                                     .min(snapshot.len());
                                 let end = ((insertion_start + tabstop_range.end) as usize)
                                     .min(snapshot.len());
-                                snapshot.anchor_before(start)..snapshot.anchor_after(end)
+                                (
+                                    snapshot.anchor_before(start)..snapshot.anchor_after(end),
+                                    transform.clone(),
+                                )
                             })
                         })
                         .collect::<Vec<_>>();
-                    tabstop_ranges.sort_unstable_by(|a, b| a.start.cmp(&b.start, snapshot));
+                    tabstop_ranges
+                        .sort_unstable_by(|(a, _), (b, _)| a.start.cmp(&b.start, snapshot));
+                    let (ranges, transforms) = tabstop_ranges.into_iter().unzip();
 
                     Tabstop {
                         is_end_tabstop,
-                        ranges: tabstop_ranges,
+                        ranges,
+                        transforms,
+                        choices: snippet.choices.get(tabstop_ix).cloned().unwrap_or_default(),
                     }
                 })
                 .collect::<Vec<_>>()
@@ -4900,18 +5247,27 @@ impl Editor {
             self.change_selections(Some(Autoscroll::fit()), cx, |s| {
                 s.select_ranges(tabstop.ranges.iter().cloned());
             });
+            let first_choices = tabstop
+                .ranges
+                .first()
+                .filter(|_| !tabstop.choices.is_empty())
+                .map(|range| (tabstop.choices.clone(), range.clone()));
 
             // If we're already at the last tabstop and it's at the end of the snippet,
             // we're done, we don't need to keep the state around.
             if !tabstop.is_end_tabstop {
-                let ranges = tabstops
-                    .into_iter()
-                    .map(|tabstop| tabstop.ranges)
-                    .collect::<Vec<_>>();
-                self.snippet_stack.push(SnippetState {
+                let mut snippet = SnippetState {
                     active_index: 0,
-                    ranges,
-                });
+                    ranges: Vec::new(),
+                    transforms: Vec::new(),
+                    choices: Vec::new(),
+                };
+                for tabstop in tabstops {
+                    snippet.ranges.push(tabstop.ranges);
+                    snippet.transforms.push(tabstop.transforms);
+                    snippet.choices.push(tabstop.choices);
+                }
+                self.snippet_stack.push(snippet);
             }
 
             // Check whether the just-entered snippet ends with an auto-closable bracket.
@@ -4949,10 +5305,102 @@ impl Editor {
                     }
                 }
             }
+
+            if let Some((choices, range)) = first_choices {
+                self.show_snippet_choices(&choices, range, cx);
+            }
         }
         Ok(())
     }
 
+    /// Shows the values of a choice tabstop, like `${1|one,two|}`, in the completions menu.
+    fn show_snippet_choices(
+        &mut self,
+        choices: &[String],
+        range: Range<Anchor>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(buffer) = range
+            .start
+            .buffer_id
+            .and_then(|buffer_id| self.buffer.read(cx).buffer(buffer_id))
+        else {
+            return;
+        };
+
+        let old_range = range.start.text_anchor..range.end.text_anchor;
+        let completions = choices
+            .iter()
+            .map(|choice| Completion {
+                old_range: old_range.clone(),
+                new_text: choice.clone(),
+                label: CodeLabel::plain(choice.clone(), None),
+                // Choices don't come from a language server, so there is nothing to resolve.
+                server_id: LanguageServerId(usize::MAX),
+                documentation: Some(Documentation::Undocumented),
+                lsp_completion: Default::default(),
+                confirm: None,
+                show_new_completions_on_confirm: false,
+            })
+            .collect::<Vec<_>>();
+        let match_candidates = choices
+            .iter()
+            .enumerate()
+            .map(|(id, choice)| StringMatchCandidate::new(id, choice.clone()))
+            .collect();
+        let matches = choices
+            .iter()
+            .enumerate()
+            .map(|(candidate_id, choice)| StringMatch {
+                candidate_id,
+                score: Default::default(),
+                positions: Default::default(),
+                string: choice.clone(),
+            })
+            .collect();
+
+        *self.context_menu.write() = Some(ContextMenu::Completions(CompletionsMenu {
+            id: post_inc(&mut self.next_completion_id),
+            initial_position: range.start,
+            buffer,
+            completions: Arc::new(RwLock::new(completions.into())),
+            match_candidates,
+            matches,
+            selected_item: 0,
+            scroll_handle: UniformListScrollHandle::new(),
+            selected_completion_documentation_resolve_debounce: Arc::new(Mutex::new(
+                DebouncedDelay::new(),
+            )),
+        }));
+        cx.notify();
+    }
+
+    /// Replaces the text of the ranges of the active tabstop that have a transform, like
+    /// `${1/(.*)/${1:/upcase}/}`, with its result.
+    fn apply_snippet_transforms(&mut self, snippet: &SnippetState, cx: &mut ViewContext<Self>) {
+        let (Some(ranges), Some(transforms)) = (
+            snippet.ranges.get(snippet.active_index),
+            snippet.transforms.get(snippet.active_index),
+        ) else {
+            return;
+        };
+
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let edits = ranges
+            .iter()
+            .zip(transforms)
+            .filter_map(|(range, transform)| {
+                let text = buffer.text_for_range(range.clone()).collect::<String>();
+                let new_text = transform.as_ref()?.apply(&text);
+                (new_text != text).then(|| (range.clone(), new_text))
+            })
+            .collect::<Vec<_>>();
+        if !edits.is_empty() {
+            self.buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+        }
+    }
+
     pub fn move_to_next_snippet_tabstop(&mut self, cx: &mut ViewContext<Self>) -> bool {
         self.move_to_snippet_tabstop(Bias::Right, cx)
     }
@@ -4963,6 +5411,13 @@ impl Editor {
 
     pub fn move_to_snippet_tabstop(&mut self, bias: Bias, cx: &mut ViewContext<Self>) -> bool {
         if let Some(mut snippet) = self.snippet_stack.pop() {
+            let can_move = match bias {
+                Bias::Left => snippet.active_index > 0,
+                Bias::Right => snippet.active_index + 1 < snippet.ranges.len(),
+            };
+            if can_move {
+                self.apply_snippet_transforms(&snippet, cx);
+            }
             match bias {
                 Bias::Left => {
                     if snippet.active_index > 0 {
@@ -4985,6 +5440,15 @@ impl Editor {
                 self.change_selections(Some(Autoscroll::fit()), cx, |s| {
                     s.select_anchor_ranges(current_ranges.iter().cloned())
                 });
+                if let (Some(choices), Some(range)) = (
+                    snippet.choices.get(snippet.active_index),
+                    current_ranges.first(),
+                ) {
+                    if !choices.is_empty() {
+                        let choices = choices.clone();
+                        self.show_snippet_choices(&choices, range.clone(), cx);
+                    }
+                }
                 // If snippet state is not at the last tabstop, push it back on the stack
                 if snippet.active_index + 1 < snippet.ranges.len() {
                     self.snippet_stack.push(snippet);
@@ -5044,8 +5508,27 @@ impl Editor {
                 }
             }
 
+            let mut linked_edits = HashMap::<_, Vec<_>>::default();
+            if !this.linked_edit_ranges.is_empty() {
+                let snapshot = this.buffer.read(cx).snapshot(cx);
+                let empty_str: Arc<str> = Arc::from("");
+                for selection in &selections {
+                    let start = snapshot.anchor_before(selection.start).text_anchor;
+                    let end = snapshot.anchor_after(selection.end).text_anchor;
+                    if let Some(ranges) = this.linked_editing_ranges_for(start..end, cx) {
+                        for (buffer, ranges) in ranges {
+                            linked_edits
+                                .entry(buffer)
+                                .or_default()
+                                .extend(ranges.into_iter().map(|range| (range, empty_str.clone())));
+                        }
+                    }
+                }
+            }
+
             this.change_selections(Some(Autoscroll::fit()), cx, |s| s.select(selections));
             this.insert("", cx);
+            this.apply_linked_edits(linked_edits, cx);
             this.refresh_inline_completion(true, cx);
         });
     }
@@ -5484,6 +5967,21 @@ impl Editor {
         self.manipulate_lines(cx, |lines| lines.sort_by_key(|line| line.to_lowercase()))
     }
 
+    pub fn sort_lines_descending(&mut self, _: &SortLinesDescending, cx: &mut ViewContext<Self>) {
+        self.manipulate_lines(cx, |lines| lines.sort_by(|a, b| b.cmp(a)))
+    }
+
+    /// Sorts the lines by the number they start with, placing the lines that don't start with a
+    /// number first.
+    pub fn sort_lines_numerically(&mut self, _: &SortLinesNumerically, cx: &mut ViewContext<Self>) {
+        self.manipulate_lines(cx, |lines| {
+            lines.sort_by(|a, b| match (leading_number(a), leading_number(b)) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            })
+        })
+    }
+
     pub fn unique_lines_case_insensitive(
         &mut self,
         _: &UniqueLinesCaseInsensitive,
@@ -5606,7 +6104,14 @@ impl Editor {
 
         let mut edits = Vec::new();
 
-        let selections = self.selections.all::<Point>(cx);
+        let mut selections = self.selections.all::<Point>(cx);
+        // Without any text selected, a single cursor manipulates all the lines of the buffer.
+        if let [selection] = selections.as_mut_slice() {
+            if selection.is_empty() && self.buffer.read(cx).is_singleton() {
+                selection.start = Point::zero();
+                selection.end = buffer.max_point();
+            }
+        }
         let mut selections = selections.iter().peekable();
         let mut contiguous_row_selections = Vec::new();
         let mut new_selections = Vec::new();
@@ -5687,6 +6192,111 @@ impl Editor {
         });
     }
 
+    /// Pads the selections with spaces so that they start at the same display column, or aligns
+    /// the occurrences of the action's delimiter in the selected lines instead. Rows with several
+    /// selections or occurrences are aligned one column at a time, from left to right.
+    pub fn align_selections(&mut self, action: &AlignSelections, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<Point>(cx);
+        let delimiter = action
+            .delimiter
+            .as_deref()
+            .filter(|delimiter| !delimiter.is_empty());
+
+        let mut positions_by_row = BTreeMap::<u32, Vec<Point>>::new();
+        for selection in &selections {
+            if let Some(delimiter) = delimiter {
+                let mut end_row = selection.end.row;
+                if selection.end.column == 0 && end_row > selection.start.row {
+                    end_row -= 1;
+                }
+                for row in selection.start.row..=end_row {
+                    positions_by_row.entry(row).or_insert_with(|| {
+                        let line_end = Point::new(row, buffer.line_len(MultiBufferRow(row)));
+                        buffer
+                            .text_for_range(Point::new(row, 0)..line_end)
+                            .collect::<String>()
+                            .match_indices(delimiter)
+                            .map(|(ix, _)| Point::new(row, ix as u32))
+                            .collect()
+                    });
+                }
+            } else {
+                positions_by_row
+                    .entry(selection.start.row)
+                    .or_default()
+                    .push(selection.start);
+            }
+        }
+        // Anchors biased to the right follow the padding inserted before them.
+        let rows = positions_by_row
+            .into_values()
+            .filter(|positions| !positions.is_empty())
+            .map(|positions| {
+                positions
+                    .into_iter()
+                    .map(|position| buffer.anchor_after(position))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        if rows.len() < 2 {
+            return;
+        }
+        let column_count = rows.iter().map(Vec::len).max().unwrap_or(0);
+        let new_selections = delimiter.is_none().then(|| {
+            selections
+                .iter()
+                .map(|selection| Selection {
+                    id: selection.id,
+                    start: buffer.anchor_after(selection.start),
+                    end: if selection.is_empty() {
+                        buffer.anchor_after(selection.end)
+                    } else {
+                        buffer.anchor_before(selection.end)
+                    },
+                    reversed: selection.reversed,
+                    goal: SelectionGoal::None,
+                })
+                .collect::<Vec<_>>()
+        });
+
+        self.transact(cx, |this, cx| {
+            for column_ix in 0..column_count {
+                // Measure the columns after padding the previous ones, as the width of the tabs
+                // following the padding may have changed.
+                let display_map = this.display_map.update(cx, |map, cx| map.snapshot(cx));
+                let columns = rows
+                    .iter()
+                    .filter_map(|anchors| {
+                        let anchor = *anchors.get(column_ix)?;
+                        Some((anchor, anchor.to_display_point(&display_map).column()))
+                    })
+                    .collect::<Vec<_>>();
+                let Some(target_column) = columns.iter().map(|(_, column)| *column).max() else {
+                    continue;
+                };
+                let edits = columns
+                    .into_iter()
+                    .filter(|(_, column)| *column < target_column)
+                    .map(|(anchor, column)| {
+                        (
+                            anchor..anchor,
+                            " ".repeat((target_column - column) as usize),
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                this.buffer
+                    .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+            }
+
+            if let Some(new_selections) = new_selections {
+                this.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchors(new_selections)
+                });
+            }
+        });
+    }
+
     pub fn convert_to_upper_case(&mut self, _: &ConvertToUpperCase, cx: &mut ViewContext<Self>) {
         self.manipulate_text(cx, |text| text.to_uppercase())
     }
@@ -6159,7 +6769,9 @@ impl Editor {
                 s.select(selections);
             });
             this.insert("", cx);
-            cx.write_to_clipboard(ClipboardItem::new(text).with_metadata(clipboard_selections));
+            let item = ClipboardItem::new(text).with_metadata(clipboard_selections);
+            ClipboardHistory::record_cut(item.clone(), cx);
+            cx.write_to_clipboard(item);
         });
     }
 
@@ -6198,81 +6810,89 @@ impl Editor {
             }
         }
 
-        cx.write_to_clipboard(ClipboardItem::new(text).with_metadata(clipboard_selections));
+        let item = ClipboardItem::new(text).with_metadata(clipboard_selections);
+        ClipboardHistory::record(item.clone(), cx);
+        cx.write_to_clipboard(item);
     }
 
     pub fn paste(&mut self, _: &Paste, cx: &mut ViewContext<Self>) {
+        if let Some(item) = cx.read_from_clipboard() {
+            self.paste_item(&item, cx);
+        }
+    }
+
+    /// Pastes the given clipboard item, splitting it between the selections in the same
+    /// way as [`Editor::paste`] does with the clipboard's.
+    pub fn paste_item(&mut self, item: &ClipboardItem, cx: &mut ViewContext<Self>) {
         if self.read_only(cx) {
             return;
         }
 
         self.transact(cx, |this, cx| {
-            if let Some(item) = cx.read_from_clipboard() {
-                let clipboard_text = Cow::Borrowed(item.text());
-                if let Some(mut clipboard_selections) = item.metadata::<Vec<ClipboardSelection>>() {
-                    let old_selections = this.selections.all::<usize>(cx);
-                    let all_selections_were_entire_line =
-                        clipboard_selections.iter().all(|s| s.is_entire_line);
-                    let first_selection_indent_column =
-                        clipboard_selections.first().map(|s| s.first_line_indent);
-                    if clipboard_selections.len() != old_selections.len() {
-                        clipboard_selections.drain(..);
-                    }
+            this.fill_block_selection_virtual_space(cx);
+            let clipboard_text = Cow::Borrowed(item.text());
+            if let Some(mut clipboard_selections) = item.metadata::<Vec<ClipboardSelection>>() {
+                let old_selections = this.selections.all::<usize>(cx);
+                let all_selections_were_entire_line =
+                    clipboard_selections.iter().all(|s| s.is_entire_line);
+                let first_selection_indent_column =
+                    clipboard_selections.first().map(|s| s.first_line_indent);
+                if clipboard_selections.len() != old_selections.len() {
+                    clipboard_selections.drain(..);
+                }
 
-                    this.buffer.update(cx, |buffer, cx| {
-                        let snapshot = buffer.read(cx);
-                        let mut start_offset = 0;
-                        let mut edits = Vec::new();
-                        let mut original_indent_columns = Vec::new();
-                        let line_mode = this.selections.line_mode;
-                        for (ix, selection) in old_selections.iter().enumerate() {
-                            let to_insert;
-                            let entire_line;
-                            let original_indent_column;
-                            if let Some(clipboard_selection) = clipboard_selections.get(ix) {
-                                let end_offset = start_offset + clipboard_selection.len;
-                                to_insert = &clipboard_text[start_offset..end_offset];
-                                entire_line = clipboard_selection.is_entire_line;
-                                start_offset = end_offset + 1;
-                                original_indent_column =
-                                    Some(clipboard_selection.first_line_indent);
-                            } else {
-                                to_insert = clipboard_text.as_str();
-                                entire_line = all_selections_were_entire_line;
-                                original_indent_column = first_selection_indent_column
-                            }
+                this.buffer.update(cx, |buffer, cx| {
+                    let snapshot = buffer.read(cx);
+                    let mut start_offset = 0;
+                    let mut edits = Vec::new();
+                    let mut original_indent_columns = Vec::new();
+                    let line_mode = this.selections.line_mode;
+                    for (ix, selection) in old_selections.iter().enumerate() {
+                        let to_insert;
+                        let entire_line;
+                        let original_indent_column;
+                        if let Some(clipboard_selection) = clipboard_selections.get(ix) {
+                            let end_offset = start_offset + clipboard_selection.len;
+                            to_insert = &clipboard_text[start_offset..end_offset];
+                            entire_line = clipboard_selection.is_entire_line;
+                            start_offset = end_offset + 1;
+                            original_indent_column = Some(clipboard_selection.first_line_indent);
+                        } else {
+                            to_insert = clipboard_text.as_str();
+                            entire_line = all_selections_were_entire_line;
+                            original_indent_column = first_selection_indent_column
+                        }
 
-                            // If the corresponding selection was empty when this slice of the
-                            // clipboard text was written, then the entire line containing the
-                            // selection was copied. If this selection is also currently empty,
-                            // then paste the line before the current line of the buffer.
-                            let range = if selection.is_empty() && !line_mode && entire_line {
-                                let column = selection.start.to_point(&snapshot).column as usize;
-                                let line_start = selection.start - column;
-                                line_start..line_start
-                            } else {
-                                selection.range()
-                            };
+                        // If the corresponding selection was empty when this slice of the
+                        // clipboard text was written, then the entire line containing the
+                        // selection was copied. If this selection is also currently empty,
+                        // then paste the line before the current line of the buffer.
+                        let range = if selection.is_empty() && !line_mode && entire_line {
+                            let column = selection.start.to_point(&snapshot).column as usize;
+                            let line_start = selection.start - column;
+                            line_start..line_start
+                        } else {
+                            selection.range()
+                        };
 
-                            edits.push((range, to_insert));
-                            original_indent_columns.extend(original_indent_column);
-                        }
-                        drop(snapshot);
+                        edits.push((range, to_insert));
+                        original_indent_columns.extend(original_indent_column);
+                    }
+                    drop(snapshot);
 
-                        buffer.edit(
-                            edits,
-                            Some(AutoindentMode::Block {
-                                original_indent_columns,
-                            }),
-                            cx,
-                        );
-                    });
+                    buffer.edit(
+                        edits,
+                        Some(AutoindentMode::Block {
+                            original_indent_columns,
+                        }),
+                        cx,
+                    );
+                });
 
-                    let selections = this.selections.all::<usize>(cx);
-                    this.change_selections(Some(Autoscroll::fit()), cx, |s| s.select(selections));
-                } else {
-                    this.insert(&clipboard_text, cx);
-                }
+                let selections = this.selections.all::<usize>(cx);
+                this.change_selections(Some(Autoscroll::fit()), cx, |s| s.select(selections));
+            } else {
+                this.insert(&clipboard_text, cx);
             }
         });
     }
@@ -6317,6 +6937,40 @@ impl Editor {
         }
     }
 
+    /// Brings a singleton buffer back to the state right after the given transaction, or to its
+    /// initial state for `None`, even if that state was undone and then replaced by other edits.
+    pub fn restore_undo_history(
+        &mut self,
+        transaction_id: Option<TransactionId>,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        if self.read_only(cx) {
+            return false;
+        }
+        let Some(buffer) = self.buffer.read(cx).as_singleton() else {
+            return false;
+        };
+        if !buffer.update(cx, |buffer, cx| {
+            buffer.restore_undo_history(transaction_id, cx)
+        }) {
+            return false;
+        }
+
+        if let Some((_, Some(selections))) = transaction_id
+            .and_then(|tx_id| self.selection_history.transaction(tx_id))
+            .cloned()
+        {
+            self.change_selections(None, cx, |s| {
+                s.select_anchors(selections.to_vec());
+            });
+        }
+        self.request_autoscroll(Autoscroll::fit(), cx);
+        self.unmark_text(cx);
+        self.refresh_inline_completion(true, cx);
+        cx.emit(EditorEvent::Edited);
+        true
+    }
+
     pub fn finalize_last_transaction(&mut self, cx: &mut ViewContext<Self>) {
         self.buffer
             .update(cx, |buffer, cx| buffer.finalize_last_transaction(cx));
@@ -7065,6 +7719,10 @@ impl Editor {
                 }),
                 cx,
             );
+            cx.emit(EditorEvent::PushedToNavHistory {
+                anchor: cursor_anchor,
+                is_deactivate: new_position.is_none(),
+            });
         }
     }
 
@@ -7899,6 +8557,73 @@ impl Editor {
         });
     }
 
+    pub fn reflow(&mut self, _: &Reflow, cx: &mut ViewContext<Self>) {
+        let selections = self.selections.all::<Point>(cx);
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let line_text = |row: u32| {
+            buffer
+                .text_for_range(
+                    Point::new(row, 0)..Point::new(row, buffer.line_len(MultiBufferRow(row))),
+                )
+                .collect::<String>()
+        };
+
+        let mut edits = Vec::new();
+        let mut last_reflowed_row = None;
+        for selection in selections {
+            let mut start_row = selection.start.row;
+            let mut end_row = selection.end.row;
+            if selection.end.column == 0 && end_row > start_row {
+                end_row -= 1;
+            }
+            if last_reflowed_row.map_or(false, |row| start_row <= row) {
+                continue;
+            }
+
+            let line_start = Point::new(start_row, 0);
+            let prefixes = LinePrefixes::new(buffer.language_scope_at(line_start).as_ref());
+            let settings = buffer.settings_at(line_start, cx);
+            let line_length = settings.preferred_line_length as usize;
+            let tab_size = settings.tab_size.get() as usize;
+
+            // Without a selection, reflow the paragraph around the cursor.
+            if selection.is_empty() {
+                let cursor_line = line_text(start_row);
+                while start_row > 0
+                    && last_reflowed_row.map_or(true, |row| start_row - 1 > row)
+                    && reflow::same_paragraph(&prefixes, &line_text(start_row - 1), &cursor_line)
+                {
+                    start_row -= 1;
+                }
+                while end_row < buffer.max_buffer_row().0
+                    && reflow::same_paragraph(&prefixes, &line_text(end_row + 1), &cursor_line)
+                {
+                    end_row += 1;
+                }
+            }
+
+            let lines = (start_row..=end_row).map(line_text).collect::<Vec<_>>();
+            let old_text = lines.join("\n");
+            let lines = lines.iter().map(String::as_str).collect::<Vec<_>>();
+            let new_text = reflow::reflow_lines(&lines, &prefixes, line_length, tab_size);
+            if new_text != old_text {
+                let end = Point::new(end_row, buffer.line_len(MultiBufferRow(end_row)));
+                edits.push((Point::new(start_row, 0)..end, new_text));
+            }
+            last_reflowed_row = Some(end_row);
+        }
+
+        if edits.is_empty() {
+            return;
+        }
+        self.transact(cx, |this, cx| {
+            this.buffer
+                .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+            let selections = this.selections.all::<Point>(cx);
+            this.change_selections(Some(Autoscroll::fit()), cx, |s| s.select(selections));
+        });
+    }
+
     pub fn select_larger_syntax_node(
         &mut self,
         _: &SelectLargerSyntaxNode,
@@ -8224,6 +8949,47 @@ impl Editor {
         })
     }
 
+    pub fn expand_excerpts_to_function(
+        &mut self,
+        _: &ExpandExcerptsToFunction,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let mut ranges = HashMap::default();
+        for selection in self.selections.disjoint_anchors().iter() {
+            let head = selection.head();
+            let Some(buffer) = snapshot.buffer_for_excerpt(head.excerpt_id) else {
+                continue;
+            };
+            let Some(innermost_symbol) = buffer
+                .symbols_containing(head.text_anchor, None)
+                .and_then(|mut symbols| symbols.pop())
+            else {
+                continue;
+            };
+            ranges
+                .entry(head.excerpt_id)
+                .and_modify(|range: &mut Range<text::Anchor>| {
+                    if innermost_symbol
+                        .range
+                        .start
+                        .cmp(&range.start, buffer)
+                        .is_lt()
+                    {
+                        range.start = innermost_symbol.range.start;
+                    }
+                    if innermost_symbol.range.end.cmp(&range.end, buffer).is_gt() {
+                        range.end = innermost_symbol.range.end;
+                    }
+                })
+                .or_insert(innermost_symbol.range);
+        }
+
+        self.buffer.update(cx, |buffer, cx| {
+            buffer.expand_excerpts_to_ranges(ranges, cx)
+        })
+    }
+
     pub fn expand_excerpt(
         &mut self,
         excerpt: ExcerptId,
@@ -8236,6 +9002,30 @@ impl Editor {
         })
     }
 
+    /// Adds an excerpt spanning the whole of each buffer that isn't already in the multibuffer.
+    pub fn push_buffer_excerpts(
+        &mut self,
+        buffers: impl IntoIterator<Item = Model<Buffer>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.buffer.update(cx, |multibuffer, cx| {
+            for buffer in buffers {
+                if !multibuffer.excerpts_for_buffer(&buffer, cx).is_empty() {
+                    continue;
+                }
+                let end = buffer.read(cx).max_point();
+                multibuffer.push_excerpts(
+                    buffer,
+                    [ExcerptRange {
+                        context: Point::zero()..end,
+                        primary: None,
+                    }],
+                    cx,
+                );
+            }
+        });
+    }
+
     fn go_to_diagnostic(&mut self, _: &GoToDiagnostic, cx: &mut ViewContext<Self>) {
         self.go_to_diagnostic_impl(Direction::Next, cx)
     }
@@ -8567,6 +9357,9 @@ impl Editor {
                     cx.open_url(&url);
                     Task::ready(Ok(None))
                 }
+                HoverLink::File(project_path, position) => {
+                    self.compute_target_file_location(project_path, position, cx)
+                }
             };
             cx.spawn(|editor, mut cx| async move {
                 let target = target_task.await.context("target resolution task")?;
@@ -8660,6 +9453,7 @@ impl Editor {
                                 }),
                                 HoverLink::InlayHint(_, _) => None,
                                 HoverLink::Url(_) => None,
+                                HoverLink::File(_, _) => None,
                             })
                             .unwrap_or(tab_kind.to_string());
                         let location_tasks = definitions
@@ -8670,6 +9464,9 @@ impl Editor {
                                     editor.compute_target_location(lsp_location, server_id, cx)
                                 }
                                 HoverLink::Url(_) => Task::ready(Ok(None)),
+                                HoverLink::File(project_path, position) => {
+                                    editor.compute_target_file_location(project_path, position, cx)
+                                }
                             })
                             .collect::<Vec<_>>();
                         (title, location_tasks, editor.workspace().clone())
@@ -8701,6 +9498,27 @@ impl Editor {
         }
     }
 
+    fn compute_target_file_location(
+        &self,
+        project_path: ProjectPath,
+        position: Option<Point>,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<anyhow::Result<Option<Location>>> {
+        let Some(project) = self.project.clone() else {
+            return Task::Ready(Some(Ok(None)));
+        };
+        let open_buffer = project.update(cx, |project, cx| project.open_buffer(project_path, cx));
+        cx.spawn(move |_, mut cx| async move {
+            let buffer = open_buffer.await?;
+            let range = buffer.update(&mut cx, |buffer, _| {
+                let position = buffer.clip_point(position.unwrap_or_default(), Bias::Left);
+                let anchor = buffer.anchor_before(position);
+                anchor..anchor
+            })?;
+            Ok(Some(Location { buffer, range }))
+        })
+    }
+
     fn compute_target_location(
         &self,
         lsp_location: lsp::Location,
@@ -9114,22 +9932,44 @@ impl Editor {
         let old_name = rename.old_name;
         let new_name = rename.editor.read(cx).text(cx);
 
-        let rename = workspace
-            .read(cx)
-            .project()
-            .clone()
-            .update(cx, |project, cx| {
-                project.perform_rename(buffer.clone(), range.start, new_name.clone(), true, cx)
-            });
+        let project = workspace.read(cx).project().clone();
+        let rename_edits = project.update(cx, |project, cx| {
+            project.rename_edits(buffer.clone(), range.start, new_name.clone(), cx)
+        });
         let workspace = workspace.downgrade();
 
         Some(cx.spawn(|editor, mut cx| async move {
-            let project_transaction = rename.await?;
+            let title = format!("Rename: {} → {}", old_name, new_name);
+            let project_transaction = match rename_edits.await {
+                // Renames spanning several files are previewed before they're applied.
+                Ok(edits) if edits.0.len() > 1 => {
+                    workspace.update(&mut cx, |workspace, cx| {
+                        let workspace_handle = cx.view().downgrade();
+                        let preview = cx.new_view(|cx| {
+                            RenamePreview::new(edits, title, project, workspace_handle, cx)
+                        });
+                        workspace.add_item_to_active_pane(Box::new(preview), None, cx);
+                    })?;
+                    return Ok(());
+                }
+                Ok(edits) => {
+                    project.update(&mut cx, |project, cx| project.apply_rename_edits(edits, cx))?
+                }
+                // Renames that create, rename or delete files can't be previewed.
+                Err(error) => {
+                    log::debug!("applying rename without a preview: {error:#}");
+                    project
+                        .update(&mut cx, |project, cx| {
+                            project.perform_rename(buffer, range.start, new_name, true, cx)
+                        })?
+                        .await?
+                }
+            };
             Self::open_project_transaction(
                 &editor,
                 workspace,
                 project_transaction,
-                format!("Rename: {} → {}", old_name, new_name),
+                title,
                 cx.clone(),
             )
             .await?;
@@ -9468,6 +10308,55 @@ impl Editor {
         }
     }
 
+    /// Folds the foldable ranges nested at the given level, the outermost ones being at level 1.
+    pub fn fold_at_level(&mut self, action: &FoldAtLevel, cx: &mut ViewContext<Self>) {
+        if action.level == 0 {
+            return;
+        }
+
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let max_row = display_map.buffer_snapshot.max_buffer_row().0;
+        let mut fold_ranges = Vec::new();
+        // The last rows of the foldable ranges containing the current row.
+        let mut enclosing_ends = Vec::<u32>::new();
+        let mut row = 0;
+        while row <= max_row {
+            while enclosing_ends.last().map_or(false, |end| *end < row) {
+                enclosing_ends.pop();
+            }
+            if let Some((range, placeholder)) = display_map.foldable_range(MultiBufferRow(row)) {
+                let end_row = range.end.row;
+                if enclosing_ends.len() + 1 == action.level as usize {
+                    fold_ranges.push((range, placeholder));
+                    // The ranges nested inside are hidden by this one.
+                    row = end_row + 1;
+                    continue;
+                }
+                enclosing_ends.push(end_row);
+            }
+            row += 1;
+        }
+
+        self.fold_ranges(fold_ranges, true, cx);
+    }
+
+    /// Folds the blocks of imports reported by the language servers of the buffers.
+    pub fn fold_all_imports(&mut self, _: &FoldAllImports, cx: &mut ViewContext<Self>) {
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let fold_placeholder = display_map.fold_placeholder.clone();
+        let fold_ranges = folding_ranges_in_excerpts(self, cx)
+            .into_iter()
+            .filter(|(_, kind)| matches!(kind, Some(lsp::FoldingRangeKind::Imports)))
+            .map(|(range, _)| (range, fold_placeholder.clone()))
+            .collect::<Vec<_>>();
+        self.fold_ranges(fold_ranges, true, cx);
+    }
+
+    pub fn unfold_all(&mut self, _: &UnfoldAll, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        self.unfold_ranges([0..buffer.len()], true, true, cx);
+    }
+
     pub fn unfold_lines(&mut self, _: &UnfoldLines, cx: &mut ViewContext<Self>) {
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
         let buffer = &display_map.buffer_snapshot;
@@ -9505,6 +10394,13 @@ impl Editor {
         self.unfold_ranges(std::iter::once(intersection_range), true, autoscroll, cx)
     }
 
+    /// The placeholder shown for folds made by the editor itself.
+    pub fn default_fold_placeholder(&self, cx: &mut ViewContext<Self>) -> FoldPlaceholder {
+        self.display_map
+            .update(cx, |map, cx| map.snapshot(cx))
+            .fold_placeholder
+    }
+
     pub fn fold_selected_ranges(&mut self, _: &FoldSelectedRanges, cx: &mut ViewContext<Self>) {
         let selections = self.selections.all::<Point>(cx);
         let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
@@ -9571,6 +10467,7 @@ impl Editor {
             }
 
             self.scrollbar_marker_state.dirty = true;
+            self.serialize_folds(cx);
         }
     }
 
@@ -9606,7 +10503,55 @@ impl Editor {
             cx.notify();
             self.scrollbar_marker_state.dirty = true;
             self.active_indent_guides_state.dirty = true;
+            self.serialize_folds(cx);
+        }
+    }
+
+    /// Stores the folds of the editor's buffer, so they are restored along with the workspace.
+    pub(crate) fn serialize_folds(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((_, Some(workspace_id))) = self.workspace.as_ref() else {
+            return;
+        };
+        if self.buffer.read(cx).as_singleton().is_none() {
+            return;
         }
+
+        let workspace_id = *workspace_id;
+        let item_id = cx.view().item_id().as_u64() as ItemId;
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        let buffer = &display_map.buffer_snapshot;
+        let folds = display_map
+            .folds_in_range(0..buffer.len())
+            .map(|fold| {
+                (
+                    fold.range.start.to_offset(buffer),
+                    fold.range.end.to_offset(buffer),
+                )
+            })
+            .collect::<Vec<_>>();
+        cx.background_executor()
+            .spawn(async move { DB.save_folds(item_id, workspace_id, folds).await.log_err() })
+            .detach();
+    }
+
+    pub fn read_folds_from_db(
+        &mut self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(folds) = DB.get_folds(item_id, workspace_id).log_err() else {
+            return;
+        };
+        let display_map = self.display_map.update(cx, |map, cx| map.snapshot(cx));
+        // The file may have changed since the folds were stored.
+        let len = display_map.buffer_snapshot.len();
+        let ranges = folds
+            .into_iter()
+            .filter(|(start, end)| start < end && *end <= len)
+            .map(|(start, end)| (start..end, display_map.fold_placeholder.clone()))
+            .collect::<Vec<_>>();
+        self.fold_ranges(ranges, false, cx);
     }
 
     pub fn set_gutter_hovered(&mut self, hovered: bool, cx: &mut ViewContext<Self>) {
@@ -9616,6 +10561,13 @@ impl Editor {
         }
     }
 
+    fn set_gutter_hovered_row(&mut self, row: Option<DisplayRow>, cx: &mut ViewContext<Self>) {
+        if row != self.gutter_hovered_row {
+            self.gutter_hovered_row = row;
+            cx.notify();
+        }
+    }
+
     pub fn insert_blocks(
         &mut self,
         blocks: impl IntoIterator<Item = BlockProperties<Anchor>>,
@@ -9998,13 +10950,39 @@ impl Editor {
     }
 
     fn get_permalink_to_line(&mut self, cx: &mut ViewContext<Self>) -> Result<url::Url> {
+        let selection = self.selections.newest::<Point>(cx);
+        self.get_permalink(selection.range(), selection.reversed, cx)
+    }
+
+    /// Returns a permalink to each selection, leaving out the line after a selection of whole
+    /// lines, where it ends.
+    fn get_permalinks_to_selections(
+        &mut self,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<Vec<url::Url>> {
+        let mut permalinks = Vec::new();
+        for selection in self.selections.all::<Point>(cx) {
+            let mut selection_range = selection.range();
+            if selection_range.end.column == 0
+                && selection_range.end.row > selection_range.start.row
+            {
+                selection_range.end = Point::new(selection_range.end.row - 1, 0);
+            }
+            permalinks.push(self.get_permalink(selection_range, selection.reversed, cx)?);
+        }
+        Ok(permalinks)
+    }
+
+    fn get_permalink(
+        &self,
+        selection_range: Range<Point>,
+        reversed: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Result<url::Url> {
         let (path, selection, repo) = maybe!({
             let project_handle = self.project.as_ref()?.clone();
             let project = project_handle.read(cx);
 
-            let selection = self.selections.newest::<Point>(cx);
-            let selection_range = selection.range();
-
             let (buffer, selection) = if let Some(buffer) = self.buffer().read(cx).as_singleton() {
                 (buffer, selection_range.start.row..selection_range.end.row)
             } else {
@@ -10013,7 +10991,7 @@ impl Editor {
                     .read(cx)
                     .range_to_buffer_ranges(selection_range, cx);
 
-                let (buffer, range, _) = if selection.reversed {
+                let (buffer, range, _) = if reversed {
                     buffer_ranges.first()
                 } else {
                     buffer_ranges.last()
@@ -10085,6 +11063,44 @@ impl Editor {
         }
     }
 
+    pub fn copy_permalink_to_selection(
+        &mut self,
+        _: &CopyPermalinkToSelection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let permalinks = self.get_permalinks_to_selections(cx);
+
+        match permalinks {
+            Ok(permalinks) => {
+                let permalinks = permalinks
+                    .iter()
+                    .map(|permalink| permalink.as_str())
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                cx.write_to_clipboard(ClipboardItem::new(permalinks));
+            }
+            Err(err) => {
+                let message = format!("Failed to copy permalink: {err}");
+
+                Err::<(), anyhow::Error>(err).log_err();
+
+                if let Some(workspace) = self.workspace() {
+                    workspace.update(cx, |workspace, cx| {
+                        struct CopyPermalinkToSelection;
+
+                        workspace.show_toast(
+                            Toast::new(
+                                NotificationId::unique::<CopyPermalinkToSelection>(),
+                                message,
+                            ),
+                            cx,
+                        )
+                    })
+                }
+            }
+        }
+    }
+
     pub fn open_permalink_to_line(&mut self, _: &OpenPermalinkToLine, cx: &mut ViewContext<Self>) {
         let permalink = self.get_permalink_to_line(cx);
 
@@ -10539,6 +11555,13 @@ impl Editor {
                 self.active_indent_guides_state.dirty = true;
                 self.refresh_active_diagnostics(cx);
                 self.refresh_code_actions(cx);
+                refresh_unicode_highlights(self, cx);
+                refresh_document_colors(self, cx);
+                refresh_document_links(self, cx);
+                refresh_folding_ranges(self, cx);
+                refresh_semantic_tokens(self, cx);
+                refresh_merge_conflicts(self, cx);
+                refresh_rebase_todo(self, cx);
                 if self.has_active_inline_completion(cx) {
                     self.update_visible_inline_completion(cx);
                 }
@@ -10590,23 +11613,52 @@ impl Editor {
                     excerpts: excerpts.clone(),
                 });
                 self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                refresh_rainbow_brackets(self, cx);
+                refresh_unicode_highlights(self, cx);
+                refresh_code_lenses(self, cx);
+                refresh_document_colors(self, cx);
+                refresh_document_links(self, cx);
+                refresh_folding_ranges(self, cx);
+                refresh_semantic_tokens(self, cx);
+                refresh_inline_values(self, cx);
+                refresh_merge_conflicts(self, cx);
             }
             multi_buffer::Event::ExcerptsRemoved { ids } => {
                 self.refresh_inlay_hints(InlayHintRefreshReason::ExcerptsRemoved(ids.clone()), cx);
+                refresh_code_lenses(self, cx);
+                refresh_document_colors(self, cx);
+                refresh_document_links(self, cx);
+                refresh_folding_ranges(self, cx);
+                update_semantic_token_highlights(self, cx);
+                refresh_merge_conflicts(self, cx);
                 cx.emit(EditorEvent::ExcerptsRemoved { ids: ids.clone() })
             }
             multi_buffer::Event::Reparsed => {
                 self.tasks_update_task = Some(self.refresh_runnables(cx));
+                refresh_rainbow_brackets(self, cx);
+                refresh_unicode_highlights(self, cx);
+                update_color_swatches(self, cx);
+                refresh_inline_values(self, cx);
 
                 cx.emit(EditorEvent::Reparsed);
             }
             multi_buffer::Event::LanguageChanged => {
+                refresh_rainbow_brackets(self, cx);
+                refresh_unicode_highlights(self, cx);
+                refresh_document_colors(self, cx);
+                refresh_document_links(self, cx);
+                refresh_folding_ranges(self, cx);
+                refresh_semantic_tokens(self, cx);
                 cx.emit(EditorEvent::Reparsed);
                 cx.notify();
             }
             multi_buffer::Event::DirtyChanged => cx.emit(EditorEvent::DirtyChanged),
-            multi_buffer::Event::Saved => cx.emit(EditorEvent::Saved),
+            multi_buffer::Event::Saved => {
+                refresh_code_lenses(self, cx);
+                cx.emit(EditorEvent::Saved);
+            }
             multi_buffer::Event::FileHandleChanged | multi_buffer::Event::Reloaded => {
+                refresh_rebase_todo(self, cx);
                 cx.emit(EditorEvent::TitleChanged)
             }
             multi_buffer::Event::DiffBaseChanged => {
@@ -10652,6 +11704,13 @@ impl Editor {
                 self.toggle_git_blame_inline_internal(false, cx);
             }
         }
+        refresh_rainbow_brackets(self, cx);
+        refresh_unicode_highlights(self, cx);
+        refresh_code_lenses(self, cx);
+        refresh_document_colors(self, cx);
+        refresh_document_links(self, cx);
+        refresh_semantic_tokens(self, cx);
+        refresh_inline_values(self, cx);
 
         cx.notify();
     }
@@ -10974,6 +12033,35 @@ impl Editor {
         self.handle_input(text, cx);
     }
 
+    /// Inserts a snippet the way a completion did, so its tabstops can be visited again.
+    pub fn replay_snippet_event(
+        &mut self,
+        snippet: Snippet,
+        relative_utf16_range: Option<Range<isize>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let ranges = self
+            .selections
+            .all::<OffsetUtf16>(cx)
+            .into_iter()
+            .map(|selection| {
+                let range = match &relative_utf16_range {
+                    Some(relative_utf16_range) => {
+                        let head = selection.head().0;
+                        OffsetUtf16(head.saturating_add_signed(relative_utf16_range.start))
+                            ..OffsetUtf16(head.saturating_add_signed(relative_utf16_range.end))
+                    }
+                    None => selection.start..selection.end,
+                };
+                range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot)
+            })
+            .collect::<Vec<_>>();
+        self.transact(cx, |this, cx| {
+            this.insert_snippet(&ranges, snippet, cx).log_err();
+        });
+    }
+
     pub fn supports_inlay_hints(&self, cx: &AppContext) -> bool {
         let Some(project) = self.project.as_ref() else {
             return false;
@@ -11266,6 +12354,27 @@ fn inlay_hint_settings(
         .inlay_hints
 }
 
+/// Parses the number a line starts with, ignoring its indentation.
+fn leading_number(line: &str) -> Option<f64> {
+    let line = line.trim_start();
+    let mut end = 0;
+    let mut seen_digit = false;
+    let mut seen_point = false;
+    for (ix, c) in line.char_indices() {
+        match c {
+            '-' | '+' if ix == 0 => {}
+            '.' if !seen_point => seen_point = true,
+            c if c.is_ascii_digit() => seen_digit = true,
+            _ => break,
+        }
+        end = ix + c.len_utf8();
+    }
+    if !seen_digit {
+        return None;
+    }
+    line[..end].trim_end_matches('.').parse().ok()
+}
+
 fn consume_contiguous_rows(
     contiguous_row_selections: &mut Vec<Selection<Point>>,
     selection: &Selection<Point>,
@@ -11444,7 +12553,10 @@ impl EditorSnapshot {
                 cx,
             ))
         } else if folded
-            || (self.starts_indent(buffer_row) && (row_contains_cursor || self.gutter_hovered))
+            || ((self.starts_indent(buffer_row)
+                || self.starts_folding_range(buffer_row)
+                || self.starts_fold_region(buffer_row))
+                && (row_contains_cursor || self.gutter_hovered))
         {
             Some(
                 IconButton::new(
@@ -11500,6 +12612,11 @@ pub enum EditorEvent {
         utf16_range_to_replace: Option<Range<isize>>,
         text: Arc<str>,
     },
+    /// A completion inserted a snippet, whose tabstops can be visited.
+    SnippetInputHandled {
+        utf16_range_to_replace: Option<Range<isize>>,
+        snippet: Snippet,
+    },
     ExcerptsAdded {
         buffer: Model<Buffer>,
         predecessor: ExcerptId,
@@ -11531,6 +12648,12 @@ pub enum EditorEvent {
     TransactionBegun {
         transaction_id: clock::Lamport,
     },
+    /// The cursor position was recorded in the navigation history, either because the cursor
+    /// moved far away from it or because the editor was deactivated.
+    PushedToNavHistory {
+        anchor: Anchor,
+        is_deactivate: bool,
+    },
 }
 
 impl EventEmitter<EditorEvent> for Editor {}
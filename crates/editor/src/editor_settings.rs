@@ -1,4 +1,5 @@
 use gpui::AppContext;
+use lsp::DiagnosticSeverity;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use settings::{Settings, SettingsSources};
@@ -11,10 +12,11 @@ pub struct EditorSettings {
     pub show_completions_on_input: bool,
     pub show_completion_documentation: bool,
     pub completion_documentation_secondary_query_debounce: u64,
-    pub use_on_type_format: bool,
     pub toolbar: Toolbar,
     pub scrollbar: Scrollbar,
+    pub minimap: Minimap,
     pub gutter: Gutter,
+    pub diagnostics: Diagnostics,
     pub vertical_scroll_margin: f32,
     pub scroll_sensitivity: f32,
     pub relative_line_numbers: bool,
@@ -24,6 +26,12 @@ pub struct EditorSettings {
     pub expand_excerpt_lines: u32,
     #[serde(default)]
     pub double_click_in_multibuffer: DoubleClickInMultibuffer,
+    pub unicode_highlights: UnicodeHighlights,
+    pub code_lens: bool,
+    pub document_colors: bool,
+    pub document_links: bool,
+    pub drag_and_drop_selection: bool,
+    pub inline_values: bool,
 }
 
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
@@ -63,6 +71,19 @@ pub enum DoubleClickInMultibuffer {
     Open,
 }
 
+/// Where to highlight invisible and confusable Unicode characters.
+#[derive(Default, Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum UnicodeHighlights {
+    /// Highlight them everywhere.
+    #[default]
+    All,
+    /// Highlight them everywhere but in comments.
+    Code,
+    /// Never highlight them.
+    None,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Toolbar {
     pub breadcrumbs: bool,
@@ -79,6 +100,12 @@ pub struct Scrollbar {
     pub cursors: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct Minimap {
+    pub show: bool,
+    pub width: f32,
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct Gutter {
     pub line_numbers: bool,
@@ -86,6 +113,46 @@ pub struct Gutter {
     pub folds: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct Diagnostics {
+    pub inline: InlineDiagnostics,
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct InlineDiagnostics {
+    pub enabled: bool,
+    pub min_severity: DiagnosticSeverityFilter,
+    pub max_length: usize,
+}
+
+/// The least severe diagnostics to show.
+///
+/// Default: hint
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticSeverityFilter {
+    /// Only show errors.
+    Error,
+    /// Show errors and warnings.
+    Warning,
+    /// Show errors, warnings and information.
+    Info,
+    /// Show all the diagnostics.
+    Hint,
+}
+
+impl DiagnosticSeverityFilter {
+    pub fn includes(&self, severity: DiagnosticSeverity) -> bool {
+        let min_severity = match self {
+            DiagnosticSeverityFilter::Error => DiagnosticSeverity::ERROR,
+            DiagnosticSeverityFilter::Warning => DiagnosticSeverity::WARNING,
+            DiagnosticSeverityFilter::Info => DiagnosticSeverity::INFORMATION,
+            DiagnosticSeverityFilter::Hint => DiagnosticSeverity::HINT,
+        };
+        severity <= min_severity
+    }
+}
+
 /// When to show the scrollbar in the editor.
 ///
 /// Default: auto
@@ -144,17 +211,16 @@ pub struct EditorSettingsContent {
     ///
     /// Default: 300 ms
     pub completion_documentation_secondary_query_debounce: Option<u64>,
-    /// Whether to use additional LSP queries to format (and amend) the code after
-    /// every "trigger" symbol input, defined by LSP server capabilities.
-    ///
-    /// Default: true
-    pub use_on_type_format: Option<bool>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
     /// Scrollbar related settings
     pub scrollbar: Option<ScrollbarContent>,
+    /// Minimap related settings
+    pub minimap: Option<MinimapContent>,
     /// Gutter related settings
     pub gutter: Option<GutterContent>,
+    /// Diagnostics related settings
+    pub diagnostics: Option<DiagnosticsContent>,
     /// The number of lines to keep above/below the cursor when auto-scrolling.
     ///
     /// Default: 3.
@@ -193,6 +259,44 @@ pub struct EditorSettingsContent {
     ///
     /// Default: select
     pub double_click_in_multibuffer: Option<DoubleClickInMultibuffer>,
+
+    /// Where to render invisible Unicode characters, such as zero-width spaces
+    /// and bidirectional text controls, with visible placeholders, and to
+    /// highlight characters that look like others.
+    ///
+    /// Default: all
+    pub unicode_highlights: Option<UnicodeHighlights>,
+
+    /// Whether to show the code lenses provided by language servers, such as
+    /// reference counts, above the symbols they're about.
+    ///
+    /// Default: true
+    pub code_lens: Option<bool>,
+
+    /// Whether to show a swatch next to color literals, which opens a color
+    /// picker when clicked.
+    ///
+    /// Default: true
+    pub document_colors: Option<bool>,
+
+    /// Whether to underline the links provided by language servers, or the
+    /// URLs and project file paths found in buffers they don't provide links
+    /// for, opening them when clicked with cmd (ctrl on Linux and Windows).
+    ///
+    /// Default: true
+    pub document_links: Option<bool>,
+
+    /// Whether to move selections by dragging them with the mouse, or to copy
+    /// them when holding alt (ctrl on Linux and Windows) while dropping them.
+    ///
+    /// Default: true
+    pub drag_and_drop_selection: Option<bool>,
+
+    /// Whether to show the values of variables after the lines they occur on,
+    /// in the function a debug session is stopped in.
+    ///
+    /// Default: true
+    pub inline_values: Option<bool>,
 }
 
 // Toolbar related settings
@@ -237,6 +341,19 @@ pub struct ScrollbarContent {
     pub cursors: Option<bool>,
 }
 
+/// Minimap related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq)]
+pub struct MinimapContent {
+    /// Whether to show a scaled-down overview of the buffer next to the scrollbar.
+    ///
+    /// Default: false
+    pub show: Option<bool>,
+    /// The width of the minimap in pixels.
+    ///
+    /// Default: 80.0
+    pub width: Option<f32>,
+}
+
 /// Gutter related settings
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct GutterContent {
@@ -254,6 +371,31 @@ pub struct GutterContent {
     pub folds: Option<bool>,
 }
 
+/// Diagnostics related settings
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct DiagnosticsContent {
+    /// Settings for showing diagnostics as text at the end of the lines they start on.
+    pub inline: Option<InlineDiagnosticsContent>,
+}
+
+/// Settings for showing diagnostics as text at the end of the lines they start on.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct InlineDiagnosticsContent {
+    /// Whether to show diagnostics inline.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The least severe diagnostics to show inline.
+    ///
+    /// Default: hint
+    pub min_severity: Option<DiagnosticSeverityFilter>,
+    /// The maximum number of characters of a diagnostic message to show,
+    /// longer messages being truncated.
+    ///
+    /// Default: 128
+    pub max_length: Option<usize>,
+}
+
 impl Settings for EditorSettings {
     const KEY: Option<&'static str> = None;
 
@@ -9,11 +9,14 @@ use crate::{
     JoinLines,
 };
 use futures::StreamExt;
-use gpui::{div, TestAppContext, UpdateGlobal, VisualTestContext, WindowBounds, WindowOptions};
+use gpui::{
+    div, KeyBinding, TestAppContext, UpdateGlobal, VisualTestContext, WindowBounds, WindowOptions,
+};
 use indoc::indoc;
 use language::{
     language_settings::{
         AllLanguageSettings, AllLanguageSettingsContent, LanguageSettingsContent, PrettierSettings,
+        StickyScrollSettings,
     },
     BracketPairConfig,
     Capability::ReadWrite,
@@ -36,7 +39,7 @@ use util::{
 };
 use workspace::{
     item::{FollowEvent, FollowableItem, Item, ItemHandle},
-    NavigationEntry, ViewId,
+    NavigationEntry, ReplayMacro, StartRecordingMacro, StopRecordingMacro, ViewId,
 };
 
 #[gpui::test]
@@ -473,6 +476,225 @@ fn test_canceling_pending_selection(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_block_selection_virtual_space(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc! {"
+        ˇabcdef
+        ab
+        abcdef"
+    });
+
+    cx.update_editor(|editor, cx| {
+        editor.begin_columnar_selection(DisplayPoint::new(DisplayRow(0), 4), 4, true, cx);
+        editor.update_selection(
+            DisplayPoint::new(DisplayRow(2), 4),
+            4,
+            gpui::Point::<f32>::default(),
+            cx,
+        );
+        editor.end_selection(cx);
+    });
+    cx.assert_editor_state(indoc! {"
+        abcdˇef
+        abˇ
+        abcdˇef"
+    });
+
+    cx.simulate_input("|");
+    cx.assert_editor_state(indoc! {"
+        abcd|ˇef
+        ab  |ˇ
+        abcd|ˇef"
+    });
+
+    cx.update_editor(|editor, cx| editor.undo(&Undo, cx));
+    cx.assert_editor_state(indoc! {"
+        abcdˇef
+        abˇ
+        abcdˇef"
+    });
+}
+
+#[gpui::test]
+async fn test_toggle_block_selection(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc! {"
+        ab«cdef
+        a
+        abcdˇ»ef"
+    });
+
+    cx.update_editor(|editor, cx| editor.toggle_block_selection(&ToggleBlockSelection, cx));
+    cx.assert_editor_state(indoc! {"
+        ab«cdˇ»ef
+        aˇ
+        ab«cdˇ»ef"
+    });
+
+    cx.update_editor(|editor, cx| editor.toggle_block_selection(&ToggleBlockSelection, cx));
+    cx.assert_editor_state(indoc! {"
+        ab«cdef
+        a
+        abcdˇ»ef"
+    });
+
+    cx.update_editor(|editor, cx| editor.toggle_block_selection(&ToggleBlockSelection, cx));
+    cx.simulate_input("X");
+    cx.assert_editor_state(indoc! {"
+        abXˇef
+        a Xˇ
+        abXˇef"
+    });
+}
+
+#[gpui::test]
+async fn test_resolve_merge_conflicts(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.set_state(indoc! {"
+        ˇfn main() {
+        <<<<<<< HEAD
+            ours();
+        =======
+            theirs();
+        >>>>>>> feature
+        <<<<<<< HEAD
+            a();
+        =======
+            b();
+        >>>>>>> feature
+        }
+    "});
+    cx.executor()
+        .advance_clock(super::merge_conflicts::PARSE_DEBOUNCE);
+    cx.executor().run_until_parked();
+
+    cx.update_editor(|editor, cx| {
+        editor.go_to_conflict(&GoToConflict, cx);
+        editor.go_to_conflict(&GoToConflict, cx);
+        editor.accept_conflict_theirs(&AcceptConflictTheirs, cx);
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                fn main() {
+                <<<<<<< HEAD
+                    ours();
+                =======
+                    theirs();
+                >>>>>>> feature
+                    b();
+                }
+            "}
+        );
+    });
+    cx.executor()
+        .advance_clock(super::merge_conflicts::PARSE_DEBOUNCE);
+    cx.executor().run_until_parked();
+
+    cx.update_editor(|editor, cx| {
+        editor.go_to_prev_conflict(&GoToPrevConflict, cx);
+        editor.accept_conflict_both(&AcceptConflictBoth, cx);
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                fn main() {
+                    ours();
+                    theirs();
+                    b();
+                }
+            "}
+        );
+
+        // Without conflicts left, nothing is resolved.
+        editor.accept_conflict_ours(&AcceptConflictOurs, cx);
+        assert_eq!(editor.text(cx).matches("ours").count(), 1);
+    });
+}
+
+#[gpui::test]
+async fn test_rebase_todo(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/repo",
+        json!({
+            "git-rebase-todo": indoc! {"
+                pick 1234567 Add the parser
+                pick 89abcde Fix the parser
+                pick fedcba9 Document the parser
+                # Rebase 7654321..fedcba9 onto 7654321 (3 commands)
+            "},
+        }),
+    )
+    .await;
+    let project = Project::test(fs, ["/repo".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/repo/git-rebase-todo", cx)
+        })
+        .await
+        .unwrap();
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+
+    editor.update(cx, |editor, cx| {
+        assert!(editor.rebase_todo.is_active());
+        editor.change_selections(None, cx, |s| {
+            s.select_ranges([Point::new(1, 1)..Point::new(1, 1)])
+        });
+        editor.rebase_squash(&RebaseSquash, cx);
+        editor.rebase_drop(&RebaseDrop, cx);
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                pick 1234567 Add the parser
+                squash 89abcde Fix the parser
+                drop fedcba9 Document the parser
+                # Rebase 7654321..fedcba9 onto 7654321 (3 commands)
+            "}
+        );
+        assert_eq!(
+            editor.selections.newest::<Point>(cx).head(),
+            Point::new(3, 1)
+        );
+
+        editor.cycle_rebase_command(2, cx);
+        assert_eq!(
+            editor.text(cx).lines().nth(2),
+            Some("pick fedcba9 Document the parser")
+        );
+    });
+
+    // Pressing the mouse on a command drags its whole line.
+    editor.update(cx, |editor, cx| {
+        let from = DisplayPoint::new(DisplayRow(2), 1);
+        let to = DisplayPoint::new(DisplayRow(0), 5);
+        assert!(editor.begin_selection_drag(from, gpui::point(px(0.), px(0.)), cx));
+        assert!(editor.update_selection_drag(Some(to), gpui::point(px(20.), px(0.)), cx));
+        assert_eq!(
+            editor.selection_drop_cursor,
+            Some(DisplayPoint::new(DisplayRow(0), 0))
+        );
+        assert!(editor.end_selection_drag(Some(to), gpui::Modifiers::default(), cx));
+        assert_eq!(
+            editor.text(cx),
+            indoc! {"
+                pick fedcba9 Document the parser
+                pick 1234567 Add the parser
+                squash 89abcde Fix the parser
+                # Rebase 7654321..fedcba9 onto 7654321 (3 commands)
+            "}
+        );
+    });
+}
+
 #[gpui::test]
 fn test_clone(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -813,6 +1035,104 @@ fn test_fold_action(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+fn test_fold_at_level(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let view = cx.add_window(|cx| {
+        let buffer = MultiBuffer::build_simple(
+            &"
+                impl Foo {
+                    fn a() {
+                        1
+                    }
+
+                    fn b() {
+                        2
+                    }
+                }
+            "
+            .unindent(),
+            cx,
+        );
+        build_editor(buffer.clone(), cx)
+    });
+
+    _ = view.update(cx, |view, cx| {
+        view.fold_at_level(&FoldAtLevel { level: 2 }, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                impl Foo {
+                    fn a() {⋯
+                    }
+
+                    fn b() {⋯
+                    }
+                }
+            "
+            .unindent(),
+        );
+
+        view.fold_at_level(&FoldAtLevel { level: 1 }, cx);
+        assert_eq!(
+            view.display_text(cx),
+            "
+                impl Foo {⋯
+                }
+            "
+            .unindent(),
+        );
+
+        view.unfold_all(&UnfoldAll, cx);
+        assert_eq!(view.display_text(cx), view.buffer.read(cx).read(cx).text());
+    });
+}
+
+#[gpui::test]
+async fn test_fold_comment_regions(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(Language::new(
+        LanguageConfig {
+            line_comments: vec!["// ".into()],
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    ));
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(language), cx));
+    cx.set_state(indoc! {"
+        ˇ// #region imports
+        use a;
+        // #region nested
+        use b;
+        // #endregion
+        // #endregion
+        // #regional
+        fn main() {}
+    "});
+
+    cx.update_editor(|editor, cx| {
+        let snapshot = editor.snapshot(cx);
+        assert!(snapshot.starts_fold_region(MultiBufferRow(0)));
+        assert!(snapshot.starts_fold_region(MultiBufferRow(2)));
+        assert!(!snapshot.starts_fold_region(MultiBufferRow(4)));
+        assert!(!snapshot.starts_fold_region(MultiBufferRow(6)));
+
+        editor.fold(&Fold, cx);
+    });
+    assert_eq!(
+        cx.display_text(),
+        indoc! {"
+            // #region imports⋯
+            // #regional
+            fn main() {}
+        "}
+    );
+}
+
 #[gpui::test]
 fn test_move_cursor(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -2899,6 +3219,36 @@ async fn test_manipulate_lines_with_single_selection(cx: &mut TestAppContext) {
         Zˇ»
     "});
 
+    // Test sort_lines_descending()
+    cx.set_state(indoc! {"
+        «b
+        c
+        aˇ»
+    "});
+    cx.update_editor(|e, cx| e.sort_lines_descending(&SortLinesDescending, cx));
+    cx.assert_editor_state(indoc! {"
+        «c
+        b
+        aˇ»
+    "});
+
+    // Test sort_lines_numerically()
+    cx.set_state(indoc! {"
+        «10. ten
+        -2 minus two
+        none
+        1.5 one and a half
+        9 nineˇ»
+    "});
+    cx.update_editor(|e, cx| e.sort_lines_numerically(&SortLinesNumerically, cx));
+    cx.assert_editor_state(indoc! {"
+        «none
+        -2 minus two
+        1.5 one and a half
+        9 nine
+        10. tenˇ»
+    "});
+
     // Test reverse_lines()
     cx.set_state(indoc! {"
         «5
@@ -2921,7 +3271,7 @@ async fn test_manipulate_lines_with_single_selection(cx: &mut TestAppContext) {
     // From here on out, test more complex cases of manipulate_lines() with a single driver method: sort_lines_case_sensitive()
     // Since all methods calling manipulate_lines() are doing the exact same general thing (reordering lines)
 
-    // Don't manipulate when cursor is on single line, but expand the selection
+    // Manipulate all the lines of the buffer when nothing is selected
     cx.set_state(indoc! {"
         ddˇdd
         ccc
@@ -2929,10 +3279,25 @@ async fn test_manipulate_lines_with_single_selection(cx: &mut TestAppContext) {
         a
     "});
     cx.update_editor(|e, cx| e.sort_lines_case_sensitive(&SortLinesCaseSensitive, cx));
+    cx.assert_editor_state(indoc! {"
+        «a
+        bb
+        ccc
+        ddddˇ»
+    "});
+
+    // Don't manipulate when each of several cursors is on a single line, but expand the selections
+    cx.set_state(indoc! {"
+        ddˇdd
+        ccc
+        bˇb
+        a
+    "});
+    cx.update_editor(|e, cx| e.sort_lines_case_sensitive(&SortLinesCaseSensitive, cx));
     cx.assert_editor_state(indoc! {"
         «ddddˇ»
         ccc
-        bb
+        «bbˇ»
         a
     "});
 
@@ -3182,6 +3547,60 @@ async fn test_manipulate_lines_with_multi_selection(cx: &mut TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_align_selections(cx: &mut TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorTestContext::new(cx).await;
+
+    // Align the cursors
+    cx.set_state(indoc! {"
+        a ˇ= 1
+        long ˇ= 2
+        mid ˇ= 3
+    "});
+    cx.update_editor(|e, cx| e.align_selections(&AlignSelections::default(), cx));
+    cx.assert_editor_state(indoc! {"
+        a    ˇ= 1
+        long ˇ= 2
+        mid  ˇ= 3
+    "});
+
+    // Tabs are measured by their width on screen
+    cx.set_state("a\tˇx\nbbbbbˇy\n");
+    cx.update_editor(|e, cx| e.align_selections(&AlignSelections::default(), cx));
+    cx.assert_editor_state("a\t ˇx\nbbbbbˇy\n");
+
+    // Align a delimiter in the selected lines, one occurrence at a time
+    cx.set_state(indoc! {"
+        «a, bb, c
+        aaa, b, cc
+        ˇ»
+    "});
+    cx.update_editor(|e, cx| {
+        e.align_selections(
+            &AlignSelections {
+                delimiter: Some(",".to_string()),
+            },
+            cx,
+        )
+    });
+    cx.assert_editor_state(indoc! {"
+        «a  , bb, c
+        aaa, b , cc
+        ˇ»
+    "});
+
+    // A single line has nothing to be aligned with
+    cx.set_state(indoc! {"
+        a ˇ= 1
+    "});
+    cx.update_editor(|e, cx| e.align_selections(&AlignSelections::default(), cx));
+    cx.assert_editor_state(indoc! {"
+        a ˇ= 1
+    "});
+}
+
 #[gpui::test]
 async fn test_manipulate_text(cx: &mut TestAppContext) {
     init_test(cx, |_| {});
@@ -5773,16 +6192,54 @@ async fn test_snippets(cx: &mut gpui::TestAppContext) {
 }
 
 #[gpui::test]
-async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
+async fn test_snippet_choices_and_transforms(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
 
-    let fs = FakeFs::new(cx.executor());
-    fs.insert_file("/file.rs", Default::default()).await;
-
-    let project = Project::test(fs, ["/file.rs".as_ref()], cx).await;
+    let (text, insertion_ranges) = marked_text_ranges("ˇ", false);
+    let buffer = cx.update(|cx| MultiBuffer::build_simple(&text, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
 
-    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(rust_lang());
+    _ = editor.update(cx, |editor, cx| {
+        let snippet =
+            Snippet::parse("${1|one,two|}(${2:foo_bar}) ${2/(.*)/${1:/upcase}/};$0").unwrap();
+        editor
+            .insert_snippet(&insertion_ranges, snippet, cx)
+            .unwrap();
+
+        fn assert(editor: &mut Editor, cx: &mut ViewContext<Editor>, marked_text: &str) {
+            let (expected_text, selection_ranges) = marked_text_ranges(marked_text, false);
+            assert_eq!(editor.text(cx), expected_text);
+            assert_eq!(editor.selections.ranges::<usize>(cx), selection_ranges);
+        }
+
+        // The choices of the first tabstop are offered as completions.
+        assert(editor, cx, "«one»(foo_bar) ;");
+        assert!(editor.context_menu_visible());
+        editor.confirm_completion(&ConfirmCompletion { item_ix: Some(1) }, cx);
+        assert_eq!(editor.text(cx), "two(foo_bar) ;");
+
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert(editor, cx, "two(«foo_bar») ˇ;");
+        editor.handle_input("a_b", cx);
+        assert(editor, cx, "two(a_bˇ) a_bˇ;");
+
+        // Leaving the tabstop transforms its mirror.
+        assert!(editor.move_to_next_snippet_tabstop(cx));
+        assert(editor, cx, "two(a_b) A_B;ˇ");
+    });
+}
+
+#[gpui::test]
+async fn test_document_format_during_save(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_file("/file.rs", Default::default()).await;
+
+    let project = Project::test(fs, ["/file.rs".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
     let mut fake_servers = language_registry.register_fake_lsp_adapter(
         "Rust",
         FakeLspAdapter {
@@ -6937,6 +7394,91 @@ async fn test_toggle_comment(cx: &mut gpui::TestAppContext) {
     "});
 }
 
+#[gpui::test]
+async fn test_reflow(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.preferred_line_length = Some(30);
+    });
+    let mut cx = EditorTestContext::new(cx).await;
+    let rust_language = Arc::new(Language::new(
+        LanguageConfig {
+            line_comments: vec!["// ".into(), "/// ".into()],
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    ));
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(rust_language), cx));
+
+    // Without a selection, the comment around the cursor is reflowed, keeping its prefix.
+    cx.set_state(indoc! {"
+        fn a() {}
+        /// Lorem ipsum dolor sit amet, consectetur adipiscing
+        /// elit. Sed doˇ eiusmod.
+        fn b() {}
+    "});
+    cx.update_editor(|e, cx| e.reflow(&Reflow, cx));
+    cx.update_editor(|e, cx| {
+        assert_eq!(
+            e.text(cx),
+            indoc! {"
+                fn a() {}
+                /// Lorem ipsum dolor sit
+                /// amet, consectetur
+                /// adipiscing elit. Sed do
+                /// eiusmod.
+                fn b() {}
+            "}
+        )
+    });
+
+    // Code within the selection is left untouched.
+    cx.set_state(indoc! {"
+        «fn a() {
+            // Short
+            // comment.
+        }ˇ»
+    "});
+    cx.update_editor(|e, cx| e.reflow(&Reflow, cx));
+    cx.update_editor(|e, cx| {
+        assert_eq!(
+            e.text(cx),
+            indoc! {"
+                fn a() {
+                    // Short comment.
+                }
+            "}
+        )
+    });
+
+    // In languages without line comments, paragraphs and list items are reflowed.
+    let prose_language = Arc::new(Language::new(LanguageConfig::default(), None));
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(prose_language), cx));
+    cx.set_state(indoc! {"
+        «# Heading
+
+        A paragraph that is
+        short.
+
+        - first item that wraps around the end
+        - second itemˇ»
+    "});
+    cx.update_editor(|e, cx| e.reflow(&Reflow, cx));
+    cx.update_editor(|e, cx| {
+        assert_eq!(
+            e.text(cx),
+            indoc! {"
+                # Heading
+
+                A paragraph that is short.
+
+                - first item that wraps around
+                  the end
+                - second item
+            "}
+        )
+    });
+}
+
 #[gpui::test]
 async fn test_advance_downward_on_toggle_comment(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -8384,6 +8926,89 @@ async fn test_on_type_formatting_not_triggered(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_on_type_formatting_language_opt_out(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.languages.insert(
+            "Rust".into(),
+            LanguageSettingsContent {
+                use_on_type_format: Some(false),
+                ..Default::default()
+            },
+        );
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/a", json!({ "main.rs": "fn main() { let a = 5 }" }))
+        .await;
+    let project = Project::test(fs, ["/a".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                document_on_type_formatting_provider: Some(lsp::DocumentOnTypeFormattingOptions {
+                    first_trigger_character: ";".to_string(),
+                    more_trigger_character: None,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/a/main.rs", cx)
+        })
+        .await
+        .unwrap();
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (editor, cx) = cx.add_window_view(|cx| build_editor_with_project(project, buffer, cx));
+    cx.executor().start_waiting();
+    let fake_server = fake_servers.next().await.unwrap();
+
+    let requests = Arc::new(AtomicUsize::new(0));
+    fake_server.handle_request::<lsp::request::OnTypeFormatting, _, _>({
+        let requests = requests.clone();
+        move |_, _| {
+            requests.fetch_add(1, atomic::Ordering::Release);
+            async move {
+                Ok(Some(vec![lsp::TextEdit {
+                    new_text: " ".to_string(),
+                    range: lsp::Range::new(lsp::Position::new(0, 23), lsp::Position::new(0, 23)),
+                }]))
+            }
+        }
+    });
+
+    editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| s.select_ranges([22..22]));
+        editor.handle_input(";", cx);
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(requests.load(atomic::Ordering::Acquire), 0);
+    editor.update(cx, |editor, cx| {
+        assert_eq!(editor.text(cx), "fn main() { let a = 5; }");
+    });
+
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.languages.remove("Rust");
+            });
+        });
+    });
+    editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| s.select_ranges([0..0]));
+        editor.handle_input(";", cx);
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(requests.load(atomic::Ordering::Acquire), 1);
+}
+
 #[gpui::test]
 async fn test_language_server_restart_due_to_settings_change(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -8464,10 +9089,12 @@ async fn test_language_server_restart_due_to_settings_change(cx: &mut gpui::Test
             "Some other server name".into(),
             LspSettings {
                 binary: None,
+                languages: None,
                 settings: None,
                 initialization_options: Some(json!({
                     "some other init value": false
                 })),
+                diagnostics: None,
             },
         );
     });
@@ -8483,10 +9110,12 @@ async fn test_language_server_restart_due_to_settings_change(cx: &mut gpui::Test
             language_server_name.into(),
             LspSettings {
                 binary: None,
+                languages: None,
                 settings: None,
                 initialization_options: Some(json!({
                     "anotherInitValue": false
                 })),
+                diagnostics: None,
             },
         );
     });
@@ -8502,10 +9131,12 @@ async fn test_language_server_restart_due_to_settings_change(cx: &mut gpui::Test
             language_server_name.into(),
             LspSettings {
                 binary: None,
+                languages: None,
                 settings: None,
                 initialization_options: Some(json!({
                     "anotherInitValue": false
                 })),
+                diagnostics: None,
             },
         );
     });
@@ -8521,8 +9152,10 @@ async fn test_language_server_restart_due_to_settings_change(cx: &mut gpui::Test
             language_server_name.into(),
             LspSettings {
                 binary: None,
+                languages: None,
                 settings: None,
                 initialization_options: None,
+                diagnostics: None,
             },
         );
     });
@@ -8626,6 +9259,95 @@ async fn test_completions_with_additional_edits(cx: &mut gpui::TestAppContext) {
     cx.assert_editor_state(indoc! {"fn main() { let a = Some(2)ˇ; }"});
 }
 
+#[gpui::test]
+async fn test_completions_with_resolved_auto_imports(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let mut cx = EditorLspTestContext::new_rust(
+        lsp::ServerCapabilities {
+            completion_provider: Some(lsp::CompletionOptions {
+                resolve_provider: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        },
+        cx,
+    )
+    .await;
+
+    cx.set_state(indoc! {"
+        fn main() {
+            let map = HashMˇ
+        }
+    "});
+    let completion_item = lsp::CompletionItem {
+        label: "HashMap".into(),
+        kind: Some(lsp::CompletionItemKind::STRUCT),
+        ..Default::default()
+    };
+    let closure_completion_item = completion_item.clone();
+    cx.handle_request::<lsp::request::Completion, _, _>(move |_, _, _| {
+        let completion_item = closure_completion_item.clone();
+        async move { Ok(Some(lsp::CompletionResponse::Array(vec![completion_item]))) }
+    });
+    let import = lsp::TextEdit::new(
+        lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 0)),
+        "use std::collections::HashMap;\n\n".to_string(),
+    );
+    let resolved_item = lsp::CompletionItem {
+        additional_text_edits: Some(vec![import]),
+        label_details: Some(lsp::CompletionItemLabelDetails {
+            detail: Some("<K, V>".to_string()),
+            description: Some("std::collections".to_string()),
+        }),
+        ..completion_item
+    };
+    let mut resolve_requests =
+        cx.handle_request::<lsp::request::ResolveCompletionItem, _, _>(move |_, _, _| {
+            let resolved_item = resolved_item.clone();
+            async move { Ok(resolved_item) }
+        });
+
+    cx.update_editor(|editor, cx| editor.show_completions(&ShowCompletions, cx));
+    cx.executor().run_until_parked();
+    cx.executor().advance_clock(Duration::from_secs(1));
+    resolve_requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    // The resolved auto-import and label details are shown in the menu.
+    cx.update_editor(|editor, _| {
+        if let Some(ContextMenu::Completions(menu)) = editor.context_menu.read().as_ref() {
+            let completions = menu.completions.read();
+            assert_eq!(completions[0].label_detail(), Some("<K, V>"));
+            assert_eq!(
+                completion_end_slot_text(&completions[0], &completions[0].documentation),
+                Some("use std::collections::HashMap;".to_string())
+            );
+        } else {
+            panic!("expected completion menu to be open");
+        }
+    });
+
+    // The server can no longer resolve the completion once its text has been inserted, so the
+    // edits stored when it was resolved for the menu are applied instead.
+    cx.handle_request::<lsp::request::ResolveCompletionItem, _, _>(move |_, _, _| async move {
+        Err(anyhow!("completion item is stale"))
+    });
+    let apply_additional_edits = cx.update_editor(|editor, cx| {
+        editor
+            .confirm_completion(&ConfirmCompletion::default(), cx)
+            .unwrap()
+    });
+    apply_additional_edits.await.unwrap();
+    cx.assert_editor_state(indoc! {"
+        use std::collections::HashMap;
+
+        fn main() {
+            let map = HashMapˇ
+        }
+    "});
+}
+
 #[gpui::test]
 async fn test_completions_in_languages_with_extra_word_characters(cx: &mut gpui::TestAppContext) {
     init_test(cx, |_| {});
@@ -12062,6 +12784,331 @@ fn test_flap_insertion_and_rendering(cx: &mut TestAppContext) {
     assert!(!snapshot.is_line_folded(MultiBufferRow(1)));
 }
 
+#[gpui::test]
+async fn test_linked_edits_on_html_tags(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.linked_edits_debounce_ms = Some(0);
+    });
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(html_lang_with_linked_edits()), cx));
+
+    cx.set_state("<div><spˇan></span></div>");
+    cx.run_until_parked();
+    cx.set_selections_state("<div><spaˇn></span></div>");
+    cx.run_until_parked();
+    cx.editor(|editor, _| assert!(editor.has_active_linked_edits()));
+
+    // Typing inside the opening tag name also edits the closing tag name.
+    cx.update_editor(|editor, cx| editor.handle_input("x", cx));
+    cx.assert_editor_state("<div><spaxˇn></spaxn></div>");
+
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| editor.backspace(&Backspace, cx));
+    cx.assert_editor_state("<div><spaˇn></span></div>");
+
+    // Ranges outside of tag names are not linked.
+    cx.set_selections_state("<div>ˇ<span></span></div>");
+    cx.run_until_parked();
+    cx.editor(|editor, _| assert!(!editor.has_active_linked_edits()));
+    cx.update_editor(|editor, cx| editor.handle_input("x", cx));
+    cx.assert_editor_state("<div>xˇ<span></span></div>");
+
+    // Linked edits can be turned off per language.
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |store, cx| {
+            store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.defaults.linked_edits = Some(false);
+            });
+        });
+    });
+    cx.set_selections_state("<div>x<spaˇn></span></div>");
+    cx.run_until_parked();
+    cx.update_editor(|editor, cx| editor.handle_input("x", cx));
+    cx.assert_editor_state("<div>x<spaxˇn></span></div>");
+}
+
+#[gpui::test]
+async fn test_linked_edit_highlights(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.linked_edits_debounce_ms = Some(0);
+    });
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(html_lang_with_linked_edits()), cx));
+    let linked_edit_highlights = |cx: &mut EditorTestContext| {
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            let buffer = &snapshot.buffer_snapshot;
+            editor.linked_edit_highlights_in_range(
+                buffer.anchor_before(0)..buffer.anchor_after(buffer.len()),
+                &snapshot,
+                cx.theme().colors(),
+            )
+        })
+    };
+
+    // Both the opening and the closing tag names of the innermost element are highlighted.
+    cx.set_state("<div><spˇan></span></div>");
+    cx.run_until_parked();
+    let color = cx.update(|cx| cx.theme().colors().editor_linked_edit_background);
+    assert_eq!(
+        linked_edit_highlights(&mut cx),
+        &[
+            (
+                DisplayPoint::new(DisplayRow(0), 6)..DisplayPoint::new(DisplayRow(0), 10),
+                color,
+            ),
+            (
+                DisplayPoint::new(DisplayRow(0), 13)..DisplayPoint::new(DisplayRow(0), 17),
+                color,
+            ),
+        ]
+    );
+
+    // The highlights follow the mirrored edits.
+    cx.update_editor(|editor, cx| editor.handle_input("x", cx));
+    cx.assert_editor_state("<div><spxˇan></spxan></div>");
+    cx.run_until_parked();
+    assert_eq!(
+        linked_edit_highlights(&mut cx),
+        &[
+            (
+                DisplayPoint::new(DisplayRow(0), 6)..DisplayPoint::new(DisplayRow(0), 11),
+                color,
+            ),
+            (
+                DisplayPoint::new(DisplayRow(0), 14)..DisplayPoint::new(DisplayRow(0), 19),
+                color,
+            ),
+        ]
+    );
+
+    // Nothing is highlighted once the selection leaves the tag names.
+    cx.set_selections_state("<div>ˇ<spxan></spxan></div>");
+    cx.run_until_parked();
+    assert!(linked_edit_highlights(&mut cx).is_empty());
+}
+
+#[gpui::test]
+async fn test_linked_edits_refresh_is_debounced(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.linked_edits_debounce_ms = Some(100);
+    });
+
+    let mut cx = EditorTestContext::new(cx).await;
+    cx.update_buffer(|buffer, cx| buffer.set_language(Some(html_lang_with_linked_edits()), cx));
+
+    cx.set_state("<diˇv></div>");
+    cx.run_until_parked();
+    cx.set_selections_state("<dˇiv></div>");
+    cx.run_until_parked();
+    cx.editor(|editor, _| assert!(!editor.has_active_linked_edits()));
+
+    cx.executor().advance_clock(Duration::from_millis(50));
+    cx.run_until_parked();
+    cx.editor(|editor, _| assert!(!editor.has_active_linked_edits()));
+
+    // Moving the cursor again restarts the debounce timer.
+    cx.set_selections_state("<diˇv></div>");
+    cx.executor().advance_clock(Duration::from_millis(60));
+    cx.run_until_parked();
+    cx.editor(|editor, _| assert!(!editor.has_active_linked_edits()));
+
+    cx.executor().advance_clock(Duration::from_millis(50));
+    cx.run_until_parked();
+    cx.editor(|editor, _| assert!(editor.has_active_linked_edits()));
+}
+
+#[gpui::test]
+async fn test_expand_excerpts_to_function(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+        .with_outline_query(indoc! {r#"
+            (function_item "fn" @context name: (_) @name) @item
+        "#})
+        .unwrap(),
+    );
+    let text = r#"
+        fn a() {
+            one();
+        }
+
+        const C: u32 = 0;
+
+        fn b() {
+            two();
+            three();
+            four();
+        }
+    "#
+    .unindent();
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(language, cx));
+    let multibuffer = cx.new_model(|cx| {
+        let mut multibuffer = MultiBuffer::new(0, ReadWrite);
+        multibuffer.push_excerpts(
+            buffer.clone(),
+            [
+                ExcerptRange {
+                    context: Point::new(4, 0)..Point::new(4, 17),
+                    primary: None,
+                },
+                ExcerptRange {
+                    context: Point::new(8, 0)..Point::new(8, 12),
+                    primary: None,
+                },
+            ],
+            cx,
+        );
+        multibuffer
+    });
+    let (view, cx) = cx.add_window_view(|cx| build_editor(multibuffer, cx));
+    cx.run_until_parked();
+    assert_eq!(
+        view.update(cx, |view, cx| view.text(cx)),
+        "const C: u32 = 0;\n    three();"
+    );
+
+    // Only the excerpts containing a cursor within a function are expanded, to the whole of the
+    // innermost function.
+    view.update(cx, |view, cx| {
+        view.change_selections(None, cx, |s| {
+            s.select_ranges([
+                Point::new(0, 0)..Point::new(0, 0),
+                Point::new(1, 6)..Point::new(1, 6),
+            ])
+        });
+        view.expand_excerpts_to_function(&ExpandExcerptsToFunction, cx);
+    });
+    assert_eq!(
+        view.update(cx, |view, cx| view.text(cx)),
+        "const C: u32 = 0;\nfn b() {\n    two();\n    three();\n    four();\n}"
+    );
+}
+
+#[gpui::test]
+async fn test_sticky_headers(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |settings| {
+        settings.defaults.sticky_scroll = Some(StickyScrollSettings {
+            enabled: true,
+            max_lines: 5,
+        });
+    });
+
+    let language = Arc::new(
+        Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+        .with_outline_query(indoc! {r#"
+            (impl_item "impl" @context type: (_) @name) @item
+            (function_item "fn" @context name: (_) @name) @item
+        "#})
+        .unwrap(),
+    );
+
+    let text = r#"
+        impl Foo {
+            fn bar() {
+                a();
+                b();
+            }
+        }
+    "#
+    .unindent();
+
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(language, cx));
+    let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+    let (view, cx) = cx.add_window_view(|cx| build_editor(buffer, cx));
+
+    view.condition::<crate::EditorEvent>(&cx, |view, cx| !view.buffer.read(cx).is_parsing(cx))
+        .await;
+
+    let sticky_header_texts = |row: u32, cx: &mut VisualTestContext| {
+        view.update(cx, |view, cx| {
+            view.snapshot(cx)
+                .sticky_headers(DisplayRow(row), None, cx)
+                .into_iter()
+                .map(|header| header.text)
+                .collect::<Vec<_>>()
+        })
+    };
+
+    assert_eq!(sticky_header_texts(0, cx), Vec::<String>::new());
+    assert_eq!(sticky_header_texts(1, cx), vec!["impl Foo", "fn bar"]);
+    assert_eq!(sticky_header_texts(2, cx), vec!["impl Foo", "fn bar"]);
+    assert_eq!(sticky_header_texts(3, cx), vec!["impl Foo"]);
+    assert_eq!(sticky_header_texts(5, cx), Vec::<String>::new());
+
+    _ = view.update(cx, |view, cx| {
+        let anchor = view.snapshot(cx).sticky_headers(DisplayRow(2), None, cx)[1].anchor;
+        view.jump_to_sticky_header(anchor, cx);
+        assert_eq!(
+            view.selections.display_ranges(cx),
+            &[DisplayPoint::new(DisplayRow(1), 4)..DisplayPoint::new(DisplayRow(1), 4)]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_record_and_replay_keyboard_macro(cx: &mut gpui::TestAppContext) {
+    init_test(cx, |_| {});
+    let mut cx = EditorLspTestContext::new_rust(Default::default(), cx).await;
+    cx.update(|cx| {
+        cx.bind_keys([
+            KeyBinding::new("ctrl-r", StartRecordingMacro::default(), None),
+            KeyBinding::new("ctrl-s", StopRecordingMacro, None),
+            KeyBinding::new(
+                "ctrl-p",
+                ReplayMacro {
+                    slot: None,
+                    times: 2,
+                },
+                None,
+            ),
+            KeyBinding::new(
+                "end",
+                MoveToEndOfLine {
+                    stop_at_soft_wraps: true,
+                },
+                None,
+            ),
+            KeyBinding::new("down", MoveDown, None),
+        ])
+    });
+
+    cx.set_state("ˇone\ntwo\nthree\nfour\nfive");
+    cx.simulate_keystrokes("ctrl-r end ! down ctrl-s");
+    cx.assert_editor_state("one!\ntwoˇ\nthree\nfour\nfive");
+
+    cx.simulate_keystrokes("ctrl-p");
+    cx.run_until_parked();
+    cx.assert_editor_state("one!\ntwo!\nthree!\nfourˇ\nfive");
+}
+
+pub(crate) fn html_lang_with_linked_edits() -> Arc<Language> {
+    Arc::new(
+        Language::new(
+            LanguageConfig {
+                name: "HTML".into(),
+                ..Default::default()
+            },
+            Some(tree_sitter_html::language()),
+        )
+        .with_linked_edit_query(indoc! {r#"
+            (element
+                (start_tag (tag_name) @linked)
+                (end_tag (tag_name) @linked))
+        "#})
+        .unwrap(),
+    )
+}
+
 fn empty_range(row: usize, column: usize) -> Range<DisplayPoint> {
     let point = DisplayPoint::new(DisplayRow(row as u32), column as u32);
     point..point
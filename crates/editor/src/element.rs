@@ -1,10 +1,12 @@
 use crate::{
     blame_entry_tooltip::{blame_entry_relative_timestamp, BlameEntryTooltip},
+    breakpoints, diagnostic_style,
     display_map::{
         BlockContext, BlockStyle, DisplaySnapshot, HighlightedChunk, ToDisplayPoint, TransformBlock,
     },
     editor_settings::{
-        CurrentLineHighlight, DoubleClickInMultibuffer, MultiCursorModifier, ShowScrollbar,
+        CurrentLineHighlight, DiagnosticSeverityFilter, DoubleClickInMultibuffer,
+        MultiCursorModifier, ShowScrollbar,
     },
     git::{
         blame::{CommitDetails, GitBlame},
@@ -13,9 +15,10 @@ use crate::{
     hover_popover::{
         self, hover_at, HOVER_POPOVER_GAP, MIN_POPOVER_CHARACTER_WIDTH, MIN_POPOVER_LINE_HEIGHT,
     },
-    hunk_status,
+    hunk_status, inline_values,
     items::BufferSearchHighlights,
     mouse_context_menu::{self, MouseContextMenu},
+    rebase_todo,
     scroll::scroll_amount::ScrollAmount,
     CodeActionsMenu, CursorShape, DisplayPoint, DisplayRow, DocumentHighlightRead,
     DocumentHighlightWrite, Editor, EditorMode, EditorSettings, EditorSnapshot, EditorStyle,
@@ -24,7 +27,7 @@ use crate::{
     Selection, SoftWrap, ToPoint, CURSORS_VISIBLE_FOR, MAX_LINE_LEN,
 };
 use client::ParticipantIndex;
-use collections::{BTreeMap, HashMap};
+use collections::{hash_map, BTreeMap, HashMap};
 use git::{blame::BlameEntry, diff::DiffHunkStatus, Oid};
 use gpui::{
     anchored, deferred, div, fill, outline, point, px, quad, relative, size, svg,
@@ -33,16 +36,21 @@ use gpui::{
     FontId, GlobalElementId, Hitbox, Hsla, InteractiveElement, IntoElement, Length,
     ModifiersChangedEvent, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent, PaintQuad,
     ParentElement, Pixels, ScrollDelta, ScrollWheelEvent, ShapedLine, SharedString, Size,
-    StatefulInteractiveElement, Style, Styled, TextRun, TextStyle, TextStyleRefinement, View,
-    ViewContext, WeakView, WindowContext,
+    StatefulInteractiveElement, Style, Styled, StyledText, TextRun, TextStyle, TextStyleRefinement,
+    View, ViewContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
-use language::language_settings::{
-    IndentGuideBackgroundColoring, IndentGuideColoring, IndentGuideSettings, ShowWhitespaceSetting,
+use language::{
+    language_settings::{
+        IndentGuideBackgroundColoring, IndentGuideColoring, IndentGuideSettings,
+        ShowWhitespaceSetting,
+    },
+    Diagnostic,
 };
 use lsp::DiagnosticSeverity;
 use multi_buffer::{Anchor, MultiBufferPoint, MultiBufferRow};
 use project::{
+    dap_store::BreakpointKind,
     project_settings::{GitGutterSetting, ProjectSettings},
     ProjectPath,
 };
@@ -181,6 +189,9 @@ impl EditorElement {
         register_action(view, cx, Editor::join_lines);
         register_action(view, cx, Editor::sort_lines_case_sensitive);
         register_action(view, cx, Editor::sort_lines_case_insensitive);
+        register_action(view, cx, Editor::sort_lines_descending);
+        register_action(view, cx, Editor::sort_lines_numerically);
+        register_action(view, cx, Editor::align_selections);
         register_action(view, cx, Editor::reverse_lines);
         register_action(view, cx, Editor::shuffle_lines);
         register_action(view, cx, Editor::convert_to_upper_case);
@@ -271,6 +282,8 @@ impl EditorElement {
             editor.select_previous(action, cx).log_err();
         });
         register_action(view, cx, Editor::toggle_comments);
+        register_action(view, cx, Editor::reflow);
+        register_action(view, cx, Editor::toggle_block_selection);
         register_action(view, cx, Editor::select_larger_syntax_node);
         register_action(view, cx, Editor::select_smaller_syntax_node);
         register_action(view, cx, Editor::move_to_enclosing_bracket);
@@ -280,11 +293,24 @@ impl EditorElement {
             register_action(view, cx, Editor::expand_excerpts);
             register_action(view, cx, Editor::expand_excerpts_up);
             register_action(view, cx, Editor::expand_excerpts_down);
+            register_action(view, cx, Editor::expand_excerpts_to_function);
         }
         register_action(view, cx, Editor::go_to_diagnostic);
         register_action(view, cx, Editor::go_to_prev_diagnostic);
         register_action(view, cx, Editor::go_to_hunk);
         register_action(view, cx, Editor::go_to_prev_hunk);
+        register_action(view, cx, Editor::go_to_related_file);
+        register_action(view, cx, Editor::go_to_conflict);
+        register_action(view, cx, Editor::go_to_prev_conflict);
+        register_action(view, cx, Editor::accept_conflict_ours);
+        register_action(view, cx, Editor::accept_conflict_theirs);
+        register_action(view, cx, Editor::accept_conflict_both);
+        register_action(view, cx, Editor::rebase_pick);
+        register_action(view, cx, Editor::rebase_reword);
+        register_action(view, cx, Editor::rebase_edit);
+        register_action(view, cx, Editor::rebase_squash);
+        register_action(view, cx, Editor::rebase_fixup);
+        register_action(view, cx, Editor::rebase_drop);
         register_action(view, cx, |editor, a, cx| {
             editor.go_to_definition(a, cx).detach_and_log_err(cx);
         });
@@ -310,7 +336,10 @@ impl EditorElement {
         register_action(view, cx, Editor::open_url);
         register_action(view, cx, Editor::fold);
         register_action(view, cx, Editor::fold_at);
+        register_action(view, cx, Editor::fold_at_level);
+        register_action(view, cx, Editor::fold_all_imports);
         register_action(view, cx, Editor::unfold_lines);
+        register_action(view, cx, Editor::unfold_all);
         register_action(view, cx, Editor::unfold_at);
         register_action(view, cx, Editor::fold_selected_ranges);
         register_action(view, cx, Editor::show_completions);
@@ -322,14 +351,18 @@ impl EditorElement {
         register_action(view, cx, Editor::toggle_line_numbers);
         register_action(view, cx, Editor::toggle_indent_guides);
         register_action(view, cx, Editor::toggle_inlay_hints);
+        register_action(view, cx, Editor::toggle_diagnostics);
+        register_action(view, cx, Editor::enable_syntax_highlighting);
         register_action(view, cx, hover_popover::hover);
         register_action(view, cx, Editor::reveal_in_finder);
         register_action(view, cx, Editor::copy_path);
         register_action(view, cx, Editor::copy_relative_path);
         register_action(view, cx, Editor::copy_highlight_json);
         register_action(view, cx, Editor::copy_permalink_to_line);
+        register_action(view, cx, Editor::copy_permalink_to_selection);
         register_action(view, cx, Editor::open_permalink_to_line);
         register_action(view, cx, Editor::toggle_git_blame);
+        register_action(view, cx, breakpoints::toggle_breakpoint);
         register_action(view, cx, Editor::toggle_git_blame_inline);
         register_action(view, cx, Editor::toggle_hunk_diff);
         register_action(view, cx, Editor::expand_all_hunk_diffs);
@@ -390,6 +423,8 @@ impl EditorElement {
         register_action(view, cx, Editor::accept_partial_inline_completion);
         register_action(view, cx, Editor::accept_inline_completion);
         register_action(view, cx, Editor::revert_selected_hunks);
+        register_action(view, cx, Editor::stage_selected_hunks);
+        register_action(view, cx, Editor::unstage_selected_hunks);
         register_action(view, cx, Editor::open_active_item_in_terminal)
     }
 
@@ -477,8 +512,37 @@ impl EditorElement {
 
         let point_for_position =
             position_map.point_for_position(text_hitbox.bounds, event.position);
+        if click_count == 1 && !modifiers.modified() {
+            if let Some(swatch_ix) = editor
+                .document_colors
+                .swatch_at(&point_for_position, &position_map.snapshot)
+            {
+                editor.deploy_color_picker(swatch_ix, event.position, cx);
+                cx.stop_propagation();
+                return;
+            }
+            if editor.begin_selection_drag(point_for_position.previous_valid, event.position, cx) {
+                cx.stop_propagation();
+                return;
+            }
+        }
         let position = point_for_position.previous_valid;
-        if modifiers.shift && modifiers.alt {
+        let multi_cursor_setting = EditorSettings::get_global(cx).multi_cursor_modifier;
+        // Alt is free to start a block selection when it doesn't add cursors.
+        if click_count == 1
+            && modifiers.alt
+            && !modifiers.shift
+            && multi_cursor_setting == MultiCursorModifier::CmdOrCtrl
+        {
+            editor.select(
+                SelectPhase::BeginColumnar {
+                    position,
+                    reset: true,
+                    goal_column: point_for_position.exact_unclipped.column(),
+                },
+                cx,
+            );
+        } else if modifiers.shift && modifiers.alt {
             editor.select(
                 SelectPhase::BeginColumnar {
                     position,
@@ -497,7 +561,6 @@ impl EditorElement {
                 cx,
             );
         } else {
-            let multi_cursor_setting = EditorSettings::get_global(cx).multi_cursor_modifier;
             let multi_cursor_modifier = match multi_cursor_setting {
                 MultiCursorModifier::Alt => modifiers.alt,
                 MultiCursorModifier::CmdOrCtrl => modifiers.secondary(),
@@ -520,8 +583,20 @@ impl EditorElement {
         event: &MouseDownEvent,
         position_map: &PositionMap,
         text_hitbox: &Hitbox,
+        gutter_hitbox: &Hitbox,
         cx: &mut ViewContext<Editor>,
     ) {
+        if gutter_hitbox.is_hovered(cx) {
+            let point_for_position =
+                position_map.point_for_position(text_hitbox.bounds, event.position);
+            let row = point_for_position.previous_valid.row();
+            if breakpoints::deploy_breakpoint_context_menu(editor, event.position, row, cx)
+                || editor.deploy_hunk_context_menu(event.position, row, cx)
+            {
+                cx.stop_propagation();
+            }
+            return;
+        }
         if !text_hitbox.is_hovered(cx) {
             return;
         }
@@ -568,6 +643,19 @@ impl EditorElement {
         text_hitbox: &Hitbox,
         cx: &mut ViewContext<Editor>,
     ) {
+        if event.button == MouseButton::Left {
+            let drop_position = text_hitbox.is_hovered(cx).then(|| {
+                position_map
+                    .point_for_position(text_hitbox.bounds, event.position)
+                    .previous_valid
+            });
+            // Other editors must see the mouse being released too, so that the one under the
+            // mouse can accept the drop.
+            if editor.end_selection_drag(drop_position, event.modifiers, cx) {
+                return;
+            }
+        }
+
         let end_selection = editor.has_pending_selection();
         let pending_nonempty_selections = editor.has_pending_nonempty_selection();
 
@@ -613,6 +701,21 @@ impl EditorElement {
         }
     }
 
+    fn selection_dragged(
+        editor: &mut Editor,
+        event: &MouseMoveEvent,
+        position_map: &PositionMap,
+        text_hitbox: &Hitbox,
+        cx: &mut ViewContext<Editor>,
+    ) -> bool {
+        let drop_position = text_hitbox.is_hovered(cx).then(|| {
+            position_map
+                .point_for_position(text_hitbox.bounds, event.position)
+                .previous_valid
+        });
+        editor.update_selection_drag(drop_position, event.position, cx)
+    }
+
     fn mouse_dragged(
         editor: &mut Editor,
         event: &MouseMoveEvent,
@@ -667,6 +770,13 @@ impl EditorElement {
         let modifiers = event.modifiers;
         let gutter_hovered = gutter_hitbox.is_hovered(cx);
         editor.set_gutter_hovered(gutter_hovered, cx);
+        let gutter_hovered_row = gutter_hovered.then(|| {
+            position_map
+                .point_for_position(text_hitbox.bounds, event.position)
+                .previous_valid
+                .row()
+        });
+        editor.set_gutter_hovered_row(gutter_hovered_row, cx);
 
         // Don't trigger hover popover if mouse is hovering over context menu
         if text_hitbox.is_hovered(cx) {
@@ -859,6 +969,37 @@ impl EditorElement {
         (selections, active_rows, newest_selection_head)
     }
 
+    /// Returns the columns of the block selection that lie past the end of the visible lines,
+    /// counted from the end of each line.
+    fn layout_virtual_selections(
+        &self,
+        snapshot: &EditorSnapshot,
+        visible_rows: Range<DisplayRow>,
+        cx: &WindowContext,
+    ) -> Vec<(DisplayRow, Range<u32>)> {
+        let Some(block) = self.editor.read(cx).block_selection() else {
+            return Vec::new();
+        };
+
+        let block_rows = block.display_rows(&snapshot.display_snapshot);
+        let columns = block.columns();
+        let start_row = cmp::max(*block_rows.start(), visible_rows.start);
+        let end_row = cmp::min(block_rows.end().next_row(), visible_rows.end);
+        (start_row.0..end_row.0)
+            .map(DisplayRow)
+            .filter(|row| !snapshot.is_block_line(*row))
+            .filter_map(|row| {
+                let line_len = snapshot.line_len(row);
+                (line_len < columns.end).then(|| {
+                    (
+                        row,
+                        columns.start.saturating_sub(line_len)..columns.end - line_len,
+                    )
+                })
+            })
+            .collect()
+    }
+
     fn collect_cursors(
         &self,
         snapshot: &EditorSnapshot,
@@ -915,6 +1056,12 @@ impl EditorElement {
     ) -> Vec<CursorLayout> {
         let mut autoscroll_bounds = None;
         let cursor_layouts = self.editor.update(cx, |editor, cx| {
+            let block_selection = editor.block_selection().map(|block| {
+                (
+                    block.display_rows(&snapshot.display_snapshot),
+                    block.head_column(),
+                )
+            });
             let mut cursors = Vec::new();
             for (player_color, selections) in selections {
                 for selection in selections {
@@ -929,7 +1076,19 @@ impl EditorElement {
                         [cursor_position.row().minus(visible_display_row_range.start) as usize];
                     let cursor_column = cursor_position.column() as usize;
 
-                    let cursor_character_x = cursor_row_layout.x_for_index(cursor_column);
+                    let mut cursor_character_x = cursor_row_layout.x_for_index(cursor_column);
+                    // Cursors of a block selection ending past their line are shown in the
+                    // block's virtual space.
+                    if let Some((block_rows, head_column)) = block_selection.as_ref() {
+                        let line_len = snapshot.line_len(cursor_position.row());
+                        if selection.is_local
+                            && block_rows.contains(&cursor_position.row())
+                            && cursor_position.column() == line_len
+                            && line_len < *head_column
+                        {
+                            cursor_character_x += (*head_column - line_len) as f32 * em_width;
+                        }
+                    }
                     let mut block_width =
                         cursor_row_layout.x_for_index(cursor_column + 1) - cursor_character_x;
                     if block_width == Pixels::ZERO {
@@ -1021,6 +1180,30 @@ impl EditorElement {
                     cursors.push(cursor);
                 }
             }
+
+            // Show where a selection dragged with the mouse would be dropped.
+            if let Some(drop_position) = editor
+                .selection_drop_cursor
+                .filter(|position| visible_display_row_range.contains(&position.row()))
+            {
+                let row_layout = &line_layouts
+                    [drop_position.row().minus(visible_display_row_range.start) as usize];
+                let x = row_layout.x_for_index(drop_position.column() as usize)
+                    - scroll_pixel_position.x;
+                let y = (drop_position.row().as_f32() - scroll_pixel_position.y / line_height)
+                    * line_height;
+                let mut cursor = CursorLayout {
+                    color: cx.theme().players().local().cursor,
+                    block_width: em_width,
+                    origin: point(x, y),
+                    line_height,
+                    shape: CursorShape::Bar,
+                    block_text: None,
+                    cursor_name: None,
+                };
+                cursor.layout(content_origin, None, cx);
+                cursors.push(cursor);
+            }
             cursors
         });
 
@@ -1101,6 +1284,206 @@ impl EditorElement {
         })
     }
 
+    fn minimap_width(&self, cx: &WindowContext) -> Pixels {
+        let minimap_settings = EditorSettings::get_global(cx).minimap;
+        if minimap_settings.show {
+            px(minimap_settings.width)
+        } else {
+            Pixels::ZERO
+        }
+    }
+
+    fn layout_minimap(
+        &self,
+        snapshot: &EditorSnapshot,
+        bounds: Bounds<Pixels>,
+        scroll_position: gpui::Point<f32>,
+        rows_per_page: f32,
+        cx: &mut WindowContext,
+    ) -> Option<MinimapLayout> {
+        let minimap_width = self.minimap_width(cx);
+        if snapshot.mode != EditorMode::Full || minimap_width == Pixels::ZERO {
+            return None;
+        }
+
+        // If a drag took place after we started dragging the minimap,
+        // cancel the minimap drag.
+        if cx.has_active_drag() {
+            self.editor.update(cx, |editor, cx| {
+                editor.scroll_manager.set_is_dragging_minimap(false, cx);
+            });
+        }
+
+        let minimap_right = self.scrollbar_left(&bounds);
+        let minimap_bounds = Bounds::from_corners(
+            point(minimap_right - minimap_width, bounds.origin.y),
+            point(minimap_right, bounds.lower_left().y),
+        );
+
+        let total_rows = snapshot.max_point().row().next_row().as_f32();
+        let minimap_rows = (bounds.size.height / MinimapLayout::ROW_HEIGHT).floor();
+        let first_row = MinimapLayout::first_visible_row(
+            scroll_position.y,
+            rows_per_page,
+            total_rows,
+            minimap_rows,
+        );
+
+        let mut minimap = MinimapLayout {
+            hitbox: cx.insert_hitbox(minimap_bounds, false),
+            first_row,
+            visible_row_range: scroll_position.y..scroll_position.y + rows_per_page,
+            quads: Vec::new(),
+        };
+        // Only the rows that fit into the minimap are laid out, so that its cost does not depend
+        // on the size of the buffer.
+        let start_row = DisplayRow(first_row as u32);
+        let end_row = DisplayRow(cmp::min(
+            (first_row + minimap_rows).ceil() as u32,
+            total_rows as u32,
+        ));
+        minimap.quads = self.layout_minimap_quads(&minimap, snapshot, start_row..end_row, cx);
+        Some(minimap)
+    }
+
+    fn layout_minimap_quads(
+        &self,
+        minimap: &MinimapLayout,
+        snapshot: &EditorSnapshot,
+        rows: Range<DisplayRow>,
+        cx: &WindowContext,
+    ) -> Vec<PaintQuad> {
+        let mut quads = Vec::new();
+        if rows.is_empty() {
+            return quads;
+        }
+
+        let theme = cx.theme();
+        let max_columns = minimap.max_columns();
+        let display_snapshot = &snapshot.display_snapshot;
+        let last_row = rows.end.previous_row();
+        let start_point = DisplayPoint::new(rows.start, 0).to_point(display_snapshot);
+        let end_point =
+            DisplayPoint::new(last_row, snapshot.line_len(last_row)).to_point(display_snapshot);
+        let mut push_range = |range: Range<DisplayPoint>, color: Hsla| {
+            let start_row = range.start.row().max(rows.start);
+            let end_row = range.end.row().min(last_row);
+            for row in start_row.0..=end_row.0 {
+                let row = DisplayRow(row);
+                let start_column = if row == range.start.row() {
+                    range.start.column()
+                } else {
+                    0
+                };
+                let end_column = if row == range.end.row() {
+                    range.end.column()
+                } else {
+                    max_columns
+                };
+                quads.push(minimap.quad_for_columns(
+                    row,
+                    start_column..end_column.max(start_column + 1),
+                    color,
+                ));
+            }
+        };
+
+        let start_anchor = snapshot.buffer_snapshot.anchor_before(start_point);
+        let end_anchor = snapshot.buffer_snapshot.anchor_after(end_point);
+        for (range, color) in self.editor.read(cx).background_highlights_in_range(
+            start_anchor..end_anchor,
+            display_snapshot,
+            theme.colors(),
+        ) {
+            push_range(range, color);
+        }
+
+        let diagnostics = snapshot
+            .buffer_snapshot
+            .diagnostics_in_range::<_, Point>(start_point..end_point, false)
+            // Paint the most severe diagnostics last.
+            .sorted_by_key(|diagnostic| cmp::Reverse(diagnostic.diagnostic.severity));
+        for diagnostic in diagnostics {
+            let mut color = match diagnostic.diagnostic.severity {
+                DiagnosticSeverity::ERROR => theme.status().error,
+                DiagnosticSeverity::WARNING => theme.status().warning,
+                DiagnosticSeverity::INFORMATION => theme.status().info,
+                _ => theme.status().hint,
+            };
+            color.fade_out(0.5);
+            push_range(
+                diagnostic.range.start.to_display_point(display_snapshot)
+                    ..diagnostic.range.end.to_display_point(display_snapshot),
+                color,
+            );
+        }
+
+        let text_color = self.style.text.color;
+        let mut row = rows.start;
+        let mut column = 0;
+        for chunk in snapshot.highlighted_chunks(rows.clone(), true, &self.style) {
+            let mut color = chunk
+                .style
+                .and_then(|style| style.color)
+                .unwrap_or(text_color);
+            color.fade_out(0.3);
+            for (ix, line) in chunk.text.split('\n').enumerate() {
+                if ix > 0 {
+                    row.0 += 1;
+                    column = 0;
+                }
+                let mut run_start = None;
+                for ch in line.chars() {
+                    if column >= max_columns {
+                        break;
+                    }
+                    if ch.is_whitespace() {
+                        if let Some(start) = run_start.take() {
+                            quads.push(minimap.quad_for_columns(row, start..column, color));
+                        }
+                    } else if run_start.is_none() {
+                        run_start = Some(column);
+                    }
+                    column += 1;
+                }
+                if let Some(start) = run_start {
+                    quads.push(minimap.quad_for_columns(row, start..column, color));
+                }
+            }
+        }
+
+        let start_buffer_row = MultiBufferRow(start_point.row);
+        let end_buffer_row = MultiBufferRow(end_point.row + 1);
+        for hunk in snapshot
+            .buffer_snapshot
+            .git_diff_hunks_in_range(start_buffer_row..end_buffer_row)
+        {
+            let start_display_row = MultiBufferPoint::new(hunk.associated_range.start.0, 0)
+                .to_display_point(display_snapshot)
+                .row();
+            let mut end_display_row = MultiBufferPoint::new(hunk.associated_range.end.0, 0)
+                .to_display_point(display_snapshot)
+                .row();
+            if end_display_row != start_display_row {
+                end_display_row.0 -= 1;
+            }
+            let color = match hunk_status(&hunk) {
+                DiffHunkStatus::Added => theme.status().created,
+                DiffHunkStatus::Modified => theme.status().modified,
+                DiffHunkStatus::Removed => theme.status().deleted,
+            };
+            quads.push(fill(
+                minimap.bounds_for_rows(
+                    start_display_row.as_f32()..end_display_row.next_row().as_f32(),
+                    Pixels::ZERO..MinimapLayout::HUNK_MARKER_WIDTH,
+                ),
+                color,
+            ));
+        }
+
+        quads
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn prepaint_gutter_fold_toggles(
         &self,
@@ -1260,6 +1643,136 @@ impl EditorElement {
             .collect()
     }
 
+    /// Lays out the most severe primary diagnostic starting on each visible row, at the end
+    /// of the row's line and after its inlay hints.
+    #[allow(clippy::too_many_arguments)]
+    fn layout_inline_diagnostics(
+        &self,
+        snapshot: &EditorSnapshot,
+        rows: Range<DisplayRow>,
+        buffer_range: Range<Anchor>,
+        line_layouts: &[LineWithInvisibles],
+        flap_trailers: &[Option<FlapTrailerLayout>],
+        em_width: Pixels,
+        content_origin: gpui::Point<Pixels>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        line_height: Pixels,
+        cx: &mut WindowContext,
+    ) -> HashMap<DisplayRow, InlineLayout> {
+        const INLINE_DIAGNOSTIC_PADDING_EM_WIDTHS: f32 = 3.;
+
+        let settings = EditorSettings::get_global(cx).diagnostics.inline;
+        if !settings.enabled || snapshot.mode != EditorMode::Full {
+            return HashMap::default();
+        }
+
+        inline_diagnostics_by_row(snapshot, rows.clone(), buffer_range, settings.min_severity)
+            .into_iter()
+            .map(|(display_row, diagnostic)| {
+                let line_ix = display_row.minus(rows.start) as usize;
+                let line_end = if let Some(flap_trailer) = flap_trailers[line_ix].as_ref() {
+                    flap_trailer.bounds.right()
+                } else {
+                    content_origin.x - scroll_pixel_position.x + line_layouts[line_ix].width
+                };
+                let origin = point(
+                    line_end + em_width * INLINE_DIAGNOSTIC_PADDING_EM_WIDTHS,
+                    content_origin.y
+                        + line_height
+                            * (display_row.as_f32() - scroll_pixel_position.y / line_height),
+                );
+
+                let message = diagnostic.message.lines().next().unwrap_or_default();
+                let message = util::truncate_and_trailoff(message, settings.max_length);
+                let mut color = diagnostic_style(diagnostic.severity, true, cx.theme().status());
+                color.fade_out(0.4);
+
+                let mut element = h_flex()
+                    .font_family(self.style.text.font().family)
+                    .text_color(color)
+                    .line_height(self.style.text.line_height)
+                    .child(message)
+                    .into_any();
+                let available_space = size(AvailableSpace::MinContent, AvailableSpace::MinContent);
+                let element_size = element.layout_as_root(available_space, cx);
+                element.prepaint_as_root(origin, available_space, cx);
+
+                (
+                    display_row,
+                    InlineLayout {
+                        element,
+                        bounds: Bounds {
+                            origin,
+                            size: element_size,
+                        },
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn layout_inline_values(
+        &self,
+        snapshot: &EditorSnapshot,
+        rows: Range<DisplayRow>,
+        line_layouts: &[LineWithInvisibles],
+        flap_trailers: &[Option<FlapTrailerLayout>],
+        inline_diagnostics: &HashMap<DisplayRow, InlineLayout>,
+        em_width: Pixels,
+        content_origin: gpui::Point<Pixels>,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        line_height: Pixels,
+        cx: &mut WindowContext,
+    ) -> HashMap<DisplayRow, InlineLayout> {
+        const INLINE_VALUES_PADDING_EM_WIDTHS: f32 = 3.;
+
+        let mut color = cx.theme().colors().text_muted;
+        color.fade_out(0.2);
+        inline_values::inline_value_display_rows(self.editor.read(cx), snapshot, cx)
+            .into_iter()
+            .filter(|(display_row, _)| rows.contains(display_row))
+            .map(|(display_row, text)| {
+                let line_ix = display_row.minus(rows.start) as usize;
+                let line_end = if let Some(inline_diagnostic) = inline_diagnostics.get(&display_row)
+                {
+                    inline_diagnostic.bounds.right()
+                } else if let Some(flap_trailer) = flap_trailers[line_ix].as_ref() {
+                    flap_trailer.bounds.right()
+                } else {
+                    content_origin.x - scroll_pixel_position.x + line_layouts[line_ix].width
+                };
+                let origin = point(
+                    line_end + em_width * INLINE_VALUES_PADDING_EM_WIDTHS,
+                    content_origin.y
+                        + line_height
+                            * (display_row.as_f32() - scroll_pixel_position.y / line_height),
+                );
+
+                let mut element = h_flex()
+                    .font_family(self.style.text.font().family)
+                    .text_color(color)
+                    .line_height(self.style.text.line_height)
+                    .child(text)
+                    .into_any();
+                let available_space = size(AvailableSpace::MinContent, AvailableSpace::MinContent);
+                let element_size = element.layout_as_root(available_space, cx);
+                element.prepaint_as_root(origin, available_space, cx);
+
+                (
+                    display_row,
+                    InlineLayout {
+                        element,
+                        bounds: Bounds {
+                            origin,
+                            size: element_size,
+                        },
+                    },
+                )
+            })
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_inline_blame(
         &self,
@@ -1267,6 +1780,7 @@ impl EditorElement {
         display_snapshot: &DisplaySnapshot,
         line_layout: &LineWithInvisibles,
         flap_trailer: Option<&FlapTrailerLayout>,
+        inline_layout: Option<&InlineLayout>,
         em_width: Pixels,
         content_origin: gpui::Point<Pixels>,
         scroll_pixel_position: gpui::Point<Pixels>,
@@ -1306,7 +1820,9 @@ impl EditorElement {
         let start_x = {
             const INLINE_BLAME_PADDING_EM_WIDTHS: f32 = 6.;
 
-            let line_end = if let Some(flap_trailer) = flap_trailer {
+            let line_end = if let Some(inline_layout) = inline_layout {
+                inline_layout.bounds.right()
+            } else if let Some(flap_trailer) = flap_trailer {
                 flap_trailer.bounds.right()
             } else {
                 content_origin.x - scroll_pixel_position.x + line_layout.width
@@ -1332,6 +1848,63 @@ impl EditorElement {
         Some(element)
     }
 
+    fn layout_sticky_headers(
+        &self,
+        snapshot: &EditorSnapshot,
+        start_row: DisplayRow,
+        text_hitbox: &Hitbox,
+        content_origin: gpui::Point<Pixels>,
+        line_height: Pixels,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        if snapshot.mode != EditorMode::Full {
+            return Vec::new();
+        }
+
+        let headers = snapshot.sticky_headers(start_row, Some(&self.style.syntax), cx);
+        let header_count = headers.len();
+        headers
+            .into_iter()
+            .enumerate()
+            .map(|(ix, header)| {
+                let editor = self.editor.clone();
+                let anchor = header.anchor;
+                let highlights = header.highlight_ranges;
+                let mut element = div()
+                    .id(("sticky-header", ix))
+                    .w(text_hitbox.size.width)
+                    .h(line_height)
+                    .pl(content_origin.x - text_hitbox.origin.x)
+                    .bg(self.style.background)
+                    .when(ix + 1 == header_count, |div| {
+                        div.border_b_1()
+                            .border_color(cx.theme().colors().border_variant)
+                    })
+                    .occlude()
+                    .cursor_pointer()
+                    .child(
+                        StyledText::new(header.text).with_highlights(&self.style.text, highlights),
+                    )
+                    .on_mouse_down(MouseButton::Left, move |_, cx| {
+                        cx.stop_propagation();
+                        editor.update(cx, |editor, cx| editor.jump_to_sticky_header(anchor, cx));
+                    })
+                    .into_any_element();
+
+                let origin = point(
+                    text_hitbox.origin.x,
+                    text_hitbox.origin.y + line_height * ix,
+                );
+                let available_space = size(
+                    AvailableSpace::Definite(text_hitbox.size.width),
+                    AvailableSpace::Definite(line_height),
+                );
+                element.prepaint_as_root(origin, available_space, cx);
+                element
+            })
+            .collect()
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_blame_entries(
         &self,
@@ -1523,6 +2096,7 @@ impl EditorElement {
         gutter_dimensions: &GutterDimensions,
         gutter_hitbox: &Hitbox,
         snapshot: &EditorSnapshot,
+        breakpoint_rows: &[DisplayRow],
         cx: &mut WindowContext,
     ) -> Vec<AnyElement> {
         self.editor.update(cx, |editor, cx| {
@@ -1551,6 +2125,9 @@ impl EditorElement {
                         return None;
                     }
                     let display_row = multibuffer_point.to_display_point(snapshot).row();
+                    if breakpoint_rows.binary_search(&display_row).is_ok() {
+                        return None;
+                    }
                     let button = editor.render_run_indicator(
                         &self.style,
                         Some(display_row) == active_task_indicator_row,
@@ -1573,6 +2150,78 @@ impl EditorElement {
         })
     }
 
+    /// Lays out the breakpoints of the visible rows, along with the breakpoint that clicking the
+    /// hovered row of the gutter would add.
+    fn layout_breakpoint_indicators(
+        &self,
+        line_height: Pixels,
+        rows: Range<DisplayRow>,
+        display_breakpoints: &[(DisplayRow, BreakpointKind)],
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            let hovered_row = editor
+                .gutter_hovered_row
+                .filter(|_| editor.mode == EditorMode::Full && editor.project.is_some())
+                .filter(|row| {
+                    display_breakpoints
+                        .binary_search_by_key(row, |(row, _)| *row)
+                        .is_err()
+                });
+            display_breakpoints
+                .iter()
+                .map(|(row, kind)| (*row, Some(*kind)))
+                .chain(hovered_row.map(|row| (row, None)))
+                .filter(|(row, _)| rows.contains(row))
+                .map(|(row, kind)| {
+                    let button = breakpoints::render_breakpoint_indicator(row, kind, cx);
+                    prepaint_gutter_button(
+                        button,
+                        row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        cx,
+                    )
+                })
+                .collect_vec()
+        })
+    }
+
+    /// Lays out the commands of the visible lines of a rebase's todo list.
+    fn layout_rebase_todo_indicators(
+        &self,
+        line_height: Pixels,
+        rows: Range<DisplayRow>,
+        snapshot: &EditorSnapshot,
+        scroll_pixel_position: gpui::Point<Pixels>,
+        gutter_dimensions: &GutterDimensions,
+        gutter_hitbox: &Hitbox,
+        cx: &mut WindowContext,
+    ) -> Vec<AnyElement> {
+        self.editor.update(cx, |editor, cx| {
+            rebase_todo::rebase_todo_display_rows(editor, rows, snapshot)
+                .into_iter()
+                .map(|(row, ix, command)| {
+                    let button = rebase_todo::render_rebase_todo_indicator(row, ix, command, cx);
+                    prepaint_gutter_button(
+                        button,
+                        row,
+                        line_height,
+                        gutter_dimensions,
+                        scroll_pixel_position,
+                        gutter_hitbox,
+                        cx,
+                    )
+                })
+                .collect_vec()
+        })
+    }
+
     fn layout_code_actions_indicator(
         &self,
         line_height: Pixels,
@@ -2477,6 +3126,22 @@ impl EditorElement {
         Some(element)
     }
 
+    fn layout_color_picker(&self, cx: &mut WindowContext) -> Option<AnyElement> {
+        let popover = self.editor.read(cx).document_colors.picker.as_ref()?;
+        let mut element = deferred(
+            anchored()
+                .position(popover.position)
+                .child(popover.picker.clone())
+                .anchor(AnchorCorner::TopLeft)
+                .snap_to_window(),
+        )
+        .with_priority(1)
+        .into_any();
+
+        element.prepaint_as_root(gpui::Point::default(), AvailableSpace::min_size(), cx);
+        Some(element)
+    }
+
     #[allow(clippy::too_many_arguments)]
     fn layout_hover_popovers(
         &self,
@@ -2862,6 +3527,16 @@ impl EditorElement {
                 }
             });
 
+            // The breakpoint that clicking the hovered row would add is covered by the other
+            // indicators of the row.
+            for breakpoint_indicator in layout.breakpoint_indicators.iter_mut() {
+                breakpoint_indicator.paint(cx);
+            }
+
+            for rebase_todo_indicator in layout.rebase_todo_indicators.iter_mut() {
+                rebase_todo_indicator.paint(cx);
+            }
+
             for test_indicators in layout.test_indicators.iter_mut() {
                 test_indicators.paint(cx);
             }
@@ -3041,6 +3716,8 @@ impl EditorElement {
                 self.paint_lines(&invisible_display_ranges, layout, cx);
                 self.paint_redactions(layout, cx);
                 self.paint_cursors(layout, cx);
+                self.paint_inline_diagnostics(layout, cx);
+                self.paint_inline_values(layout, cx);
                 self.paint_inline_blame(layout, cx);
                 cx.with_element_namespace("flap_trailers", |cx| {
                     for trailer in layout.flap_trailers.iter_mut().flatten() {
@@ -3088,6 +3765,34 @@ impl EditorElement {
                     }
                 }
             }
+
+            let start_row = layout.visible_display_row_range.start;
+            let scroll_position = layout.position_map.scroll_pixel_position;
+            for (row, columns) in &layout.virtual_selections {
+                if columns.is_empty() {
+                    continue;
+                }
+                let line_layout = &layout.position_map.line_layouts[row.minus(start_row) as usize];
+                let x_for_column = |column: u32| {
+                    layout.content_origin.x
+                        + line_layout.width
+                        + column as f32 * layout.position_map.em_advance
+                        - scroll_position.x
+                };
+                HighlightedRange {
+                    color: self.style.local_player.selection,
+                    line_height: layout.position_map.line_height,
+                    corner_radius,
+                    start_y: layout.content_origin.y
+                        + row.as_f32() * layout.position_map.line_height
+                        - scroll_position.y,
+                    lines: vec![HighlightedRangeLine {
+                        start_x: x_for_column(columns.start),
+                        end_x: x_for_column(columns.end),
+                    }],
+                }
+                .paint(layout.text_hitbox.bounds, cx);
+            }
             invisible_display_ranges
         })
     }
@@ -3287,6 +3992,100 @@ impl EditorElement {
         }
     }
 
+    fn paint_minimap(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        let Some(minimap) = layout.minimap_layout.as_ref() else {
+            return;
+        };
+
+        cx.paint_layer(minimap.hitbox.bounds, |cx| {
+            cx.paint_quad(quad(
+                minimap.hitbox.bounds,
+                Corners::default(),
+                self.style.background,
+                Edges {
+                    top: Pixels::ZERO,
+                    right: Pixels::ZERO,
+                    bottom: Pixels::ZERO,
+                    left: ScrollbarLayout::BORDER_WIDTH,
+                },
+                cx.theme().colors().scrollbar_track_border,
+            ));
+            cx.paint_quad(fill(
+                minimap.thumb_bounds(),
+                cx.theme().colors().scrollbar_thumb_background,
+            ));
+            for quad in &minimap.quads {
+                cx.paint_quad(quad.clone());
+            }
+        });
+
+        cx.set_cursor_style(CursorStyle::Arrow, &minimap.hitbox);
+
+        let top = minimap.hitbox.top();
+        let first_row = minimap.first_row;
+        let rows_per_page = minimap.visible_row_range.end - minimap.visible_row_range.start;
+        // Centers the row under the mouse in the editor.
+        let scroll_to_y = move |y: Pixels, editor: &mut Editor, cx: &mut ViewContext<Editor>| {
+            let row = first_row + (y - top) / MinimapLayout::ROW_HEIGHT;
+            let mut position = editor.scroll_position(cx);
+            position.y = (row - rows_per_page / 2.).max(0.);
+            editor.set_scroll_position(position, cx);
+        };
+
+        cx.on_mouse_event({
+            let editor = self.editor.clone();
+            let hitbox = minimap.hitbox.clone();
+            move |event: &MouseDownEvent, phase, cx| {
+                if phase == DispatchPhase::Capture
+                    || event.button != MouseButton::Left
+                    || !hitbox.is_hovered(cx)
+                {
+                    return;
+                }
+
+                editor.update(cx, |editor, cx| {
+                    editor.scroll_manager.set_is_dragging_minimap(true, cx);
+                    scroll_to_y(event.position.y, editor, cx);
+                    cx.stop_propagation();
+                });
+            }
+        });
+
+        if self.editor.read(cx).scroll_manager.is_dragging_minimap() {
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                move |event: &MouseMoveEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        if event.pressed_button == Some(MouseButton::Left) {
+                            scroll_to_y(event.position.y, editor, cx);
+                            cx.stop_propagation();
+                        } else {
+                            editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        }
+                    });
+                }
+            });
+
+            cx.on_mouse_event({
+                let editor = self.editor.clone();
+                move |_: &MouseUpEvent, phase, cx| {
+                    if phase == DispatchPhase::Capture {
+                        return;
+                    }
+
+                    editor.update(cx, |editor, cx| {
+                        editor.scroll_manager.set_is_dragging_minimap(false, cx);
+                        cx.stop_propagation();
+                    });
+                }
+            });
+        }
+    }
+
     fn collect_fast_scrollbar_markers(
         &self,
         layout: &EditorLayout,
@@ -3525,6 +4324,32 @@ impl EditorElement {
         }
     }
 
+    fn paint_inline_diagnostics(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        if layout.inline_diagnostics.is_empty() {
+            return;
+        }
+        cx.paint_layer(layout.text_hitbox.bounds, |cx| {
+            cx.with_element_namespace("inline_diagnostics", |cx| {
+                for inline_diagnostic in layout.inline_diagnostics.values_mut() {
+                    inline_diagnostic.element.paint(cx);
+                }
+            })
+        })
+    }
+
+    fn paint_inline_values(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        if layout.inline_values.is_empty() {
+            return;
+        }
+        cx.paint_layer(layout.text_hitbox.bounds, |cx| {
+            cx.with_element_namespace("inline_values", |cx| {
+                for inline_value in layout.inline_values.values_mut() {
+                    inline_value.element.paint(cx);
+                }
+            })
+        })
+    }
+
     fn paint_inline_blame(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
         if let Some(mut inline_blame) = layout.inline_blame.take() {
             cx.paint_layer(layout.text_hitbox.bounds, |cx| {
@@ -3533,6 +4358,17 @@ impl EditorElement {
         }
     }
 
+    fn paint_sticky_headers(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        if layout.sticky_headers.is_empty() {
+            return;
+        }
+        cx.paint_layer(layout.text_hitbox.bounds, |cx| {
+            for header in &mut layout.sticky_headers {
+                header.paint(cx);
+            }
+        })
+    }
+
     fn paint_blocks(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
         for mut block in layout.blocks.drain(..) {
             block.element.paint(cx);
@@ -3545,6 +4381,12 @@ impl EditorElement {
         }
     }
 
+    fn paint_color_picker(&mut self, layout: &mut EditorLayout, cx: &mut WindowContext) {
+        if let Some(color_picker) = layout.color_picker.as_mut() {
+            color_picker.paint(cx);
+        }
+    }
+
     fn paint_scroll_wheel_listener(&mut self, layout: &EditorLayout, cx: &mut WindowContext) {
         cx.on_mouse_event({
             let position_map = layout.position_map.clone();
@@ -3632,7 +4474,14 @@ impl EditorElement {
                             );
                         }),
                         MouseButton::Right => editor.update(cx, |editor, cx| {
-                            Self::mouse_right_down(editor, event, &position_map, &text_hitbox, cx);
+                            Self::mouse_right_down(
+                                editor,
+                                event,
+                                &position_map,
+                                &text_hitbox,
+                                &gutter_hitbox,
+                                cx,
+                            );
                         }),
                         MouseButton::Middle => editor.update(cx, |editor, cx| {
                             Self::mouse_middle_down(editor, event, &position_map, &text_hitbox, cx);
@@ -3665,8 +4514,17 @@ impl EditorElement {
             move |event: &MouseMoveEvent, phase, cx| {
                 if phase == DispatchPhase::Bubble {
                     editor.update(cx, |editor, cx| {
-                        if event.pressed_button == Some(MouseButton::Left)
-                            || event.pressed_button == Some(MouseButton::Middle)
+                        let selection_dragged = event.pressed_button == Some(MouseButton::Left)
+                            && Self::selection_dragged(
+                                editor,
+                                event,
+                                &position_map,
+                                &text_hitbox,
+                                cx,
+                            );
+                        if !selection_dragged
+                            && (event.pressed_button == Some(MouseButton::Left)
+                                || event.pressed_button == Some(MouseButton::Middle))
                         {
                             Self::mouse_dragged(
                                 editor,
@@ -3762,6 +4620,45 @@ fn prepaint_gutter_button(
     button
 }
 
+/// The most severe primary diagnostic starting on each of the given rows, among the diagnostics
+/// at least as severe as the given minimum.
+fn inline_diagnostics_by_row(
+    snapshot: &EditorSnapshot,
+    rows: Range<DisplayRow>,
+    buffer_range: Range<Anchor>,
+    min_severity: DiagnosticSeverityFilter,
+) -> HashMap<DisplayRow, Diagnostic> {
+    let mut diagnostics_by_row = HashMap::<DisplayRow, Diagnostic>::default();
+    for entry in snapshot
+        .buffer_snapshot
+        .diagnostics_in_range::<_, Point>(buffer_range, false)
+    {
+        let diagnostic = entry.diagnostic;
+        if !diagnostic.is_primary || !min_severity.includes(diagnostic.severity) {
+            continue;
+        }
+        let display_row = entry
+            .range
+            .start
+            .to_display_point(&snapshot.display_snapshot)
+            .row();
+        if !rows.contains(&display_row) {
+            continue;
+        }
+        match diagnostics_by_row.entry(display_row) {
+            hash_map::Entry::Occupied(mut existing) => {
+                if diagnostic.severity < existing.get().severity {
+                    existing.insert(diagnostic);
+                }
+            }
+            hash_map::Entry::Vacant(entry) => {
+                entry.insert(diagnostic);
+            }
+        }
+    }
+    diagnostics_by_row
+}
+
 fn render_inline_blame_entry(
     blame: &gpui::Model<GitBlame>,
     blame_entry: BlameEntry,
@@ -4524,7 +5421,7 @@ impl Element for EditorElement {
                     let text_width = bounds.size.width - gutter_dimensions.width;
 
                     let right_margin = if snapshot.mode == EditorMode::Full {
-                        EditorElement::SCROLLBAR_WIDTH
+                        EditorElement::SCROLLBAR_WIDTH + self.minimap_width(cx)
                     } else {
                         px(0.)
                     };
@@ -4626,10 +5523,18 @@ impl Element for EditorElement {
                     let highlighted_rows = self
                         .editor
                         .update(cx, |editor, cx| editor.highlighted_display_rows(cx));
-                    let highlighted_ranges = self.editor.read(cx).background_highlights_in_range(
-                        start_anchor..end_anchor,
-                        &snapshot.display_snapshot,
-                        cx.theme().colors(),
+                    let mut highlighted_ranges =
+                        self.editor.read(cx).background_highlights_in_range(
+                            start_anchor..end_anchor,
+                            &snapshot.display_snapshot,
+                            cx.theme().colors(),
+                        );
+                    highlighted_ranges.extend(
+                        self.editor.read(cx).linked_edit_highlights_in_range(
+                            start_anchor..end_anchor,
+                            &snapshot.display_snapshot,
+                            cx.theme().colors(),
+                        ),
                     );
 
                     let redacted_ranges = self.editor.read(cx).redacted_ranges(
@@ -4646,6 +5551,8 @@ impl Element for EditorElement {
                         end_row,
                         cx,
                     );
+                    let virtual_selections =
+                        self.layout_virtual_selections(&snapshot, start_row..end_row, cx);
 
                     let line_numbers = self.layout_line_numbers(
                         start_row..end_row,
@@ -4740,6 +5647,37 @@ impl Element for EditorElement {
                         )
                     });
 
+                    let inline_diagnostics =
+                        cx.with_element_namespace("inline_diagnostics", |cx| {
+                            self.layout_inline_diagnostics(
+                                &snapshot,
+                                start_row..end_row,
+                                start_anchor..end_anchor,
+                                &line_layouts,
+                                &flap_trailers,
+                                em_width,
+                                content_origin,
+                                scroll_pixel_position,
+                                line_height,
+                                cx,
+                            )
+                        });
+
+                    let inline_values = cx.with_element_namespace("inline_values", |cx| {
+                        self.layout_inline_values(
+                            &snapshot,
+                            start_row..end_row,
+                            &line_layouts,
+                            &flap_trailers,
+                            &inline_diagnostics,
+                            em_width,
+                            content_origin,
+                            scroll_pixel_position,
+                            line_height,
+                            cx,
+                        )
+                    });
+
                     let mut inline_blame = None;
                     if let Some(newest_selection_head) = newest_selection_head {
                         let display_row = newest_selection_head.row();
@@ -4752,6 +5690,9 @@ impl Element for EditorElement {
                                 &snapshot.display_snapshot,
                                 line_layout,
                                 flap_trailer_layout,
+                                inline_values
+                                    .get(&display_row)
+                                    .or_else(|| inline_diagnostics.get(&display_row)),
                                 em_width,
                                 content_origin,
                                 scroll_pixel_position,
@@ -4761,6 +5702,17 @@ impl Element for EditorElement {
                         }
                     }
 
+                    let sticky_headers = cx.with_element_namespace("sticky_headers", |cx| {
+                        self.layout_sticky_headers(
+                            &snapshot,
+                            start_row,
+                            &text_hitbox,
+                            content_origin,
+                            line_height,
+                            cx,
+                        )
+                    });
+
                     let blamed_display_rows = self.layout_blame_entries(
                         buffer_rows.into_iter(),
                         em_width,
@@ -4847,8 +5799,19 @@ impl Element for EditorElement {
                         cx,
                     );
 
+                    let minimap_layout = cx.with_element_namespace("minimap", |cx| {
+                        self.layout_minimap(&snapshot, bounds, scroll_position, height_in_lines, cx)
+                    });
+
                     let gutter_settings = EditorSettings::get_global(cx).gutter;
 
+                    let display_breakpoints =
+                        breakpoints::breakpoint_display_rows(self.editor.read(cx), &snapshot, cx);
+                    let breakpoint_rows = display_breakpoints
+                        .iter()
+                        .map(|(row, _)| *row)
+                        .collect_vec();
+
                     let mut _context_menu_visible = false;
                     let mut code_actions_indicator = None;
                     if let Some(newest_selection_head) = newest_selection_head {
@@ -4880,8 +5843,11 @@ impl Element for EditorElement {
                                     let row = range.start.row;
                                     let has_test_indicator =
                                         self.editor.read(cx).tasks.contains_key(&(buffer_id, row));
+                                    let has_breakpoint = breakpoint_rows
+                                        .binary_search(&newest_selection_head.row())
+                                        .is_ok();
 
-                                    if !has_test_indicator {
+                                    if !has_test_indicator && !has_breakpoint {
                                         code_actions_indicator = self
                                             .layout_code_actions_indicator(
                                                 line_height,
@@ -4903,6 +5869,27 @@ impl Element for EditorElement {
                         &gutter_dimensions,
                         &gutter_hitbox,
                         &snapshot,
+                        &breakpoint_rows,
+                        cx,
+                    );
+
+                    let breakpoint_indicators = self.layout_breakpoint_indicators(
+                        line_height,
+                        start_row..end_row,
+                        &display_breakpoints,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
+                        cx,
+                    );
+
+                    let rebase_todo_indicators = self.layout_rebase_todo_indicators(
+                        line_height,
+                        start_row..end_row,
+                        &snapshot,
+                        scroll_pixel_position,
+                        &gutter_dimensions,
+                        &gutter_hitbox,
                         cx,
                     );
 
@@ -4922,6 +5909,7 @@ impl Element for EditorElement {
                     }
 
                     let mouse_context_menu = self.layout_mouse_context_menu(cx);
+                    let color_picker = self.layout_color_picker(cx);
 
                     cx.with_element_namespace("gutter_fold_toggles", |cx| {
                         self.prepaint_gutter_fold_toggles(
@@ -4988,6 +5976,7 @@ impl Element for EditorElement {
                         gutter_dimensions,
                         content_origin,
                         scrollbar_layout,
+                        minimap_layout,
                         active_rows,
                         highlighted_rows,
                         highlighted_ranges,
@@ -4996,13 +5985,20 @@ impl Element for EditorElement {
                         line_numbers,
                         display_hunks,
                         blamed_display_rows,
+                        inline_diagnostics,
+                        inline_values,
                         inline_blame,
+                        sticky_headers,
                         blocks,
                         cursors,
                         visible_cursors,
                         selections,
+                        virtual_selections,
                         mouse_context_menu,
+                        color_picker,
                         test_indicators,
+                        breakpoint_indicators,
+                        rebase_todo_indicators,
                         code_actions_indicator,
                         gutter_fold_toggles,
                         flap_trailers,
@@ -5084,8 +6080,14 @@ impl Element for EditorElement {
                         });
                     }
 
+                    cx.with_element_namespace("sticky_headers", |cx| {
+                        self.paint_sticky_headers(layout, cx);
+                    });
+
+                    self.paint_minimap(layout, cx);
                     self.paint_scrollbar(layout, cx);
                     self.paint_mouse_context_menu(layout, cx);
+                    self.paint_color_picker(layout, cx);
                 });
             })
         })
@@ -5108,6 +6110,7 @@ pub struct EditorLayout {
     gutter_dimensions: GutterDimensions,
     content_origin: gpui::Point<Pixels>,
     scrollbar_layout: Option<ScrollbarLayout>,
+    minimap_layout: Option<MinimapLayout>,
     mode: EditorMode,
     wrap_guides: SmallVec<[(Pixels, bool); 2]>,
     indent_guides: Option<Vec<IndentGuideLayout>>,
@@ -5118,18 +6121,25 @@ pub struct EditorLayout {
     line_numbers: Vec<Option<ShapedLine>>,
     display_hunks: Vec<(DisplayDiffHunk, Option<Hitbox>)>,
     blamed_display_rows: Option<Vec<AnyElement>>,
+    inline_diagnostics: HashMap<DisplayRow, InlineLayout>,
+    inline_values: HashMap<DisplayRow, InlineLayout>,
     inline_blame: Option<AnyElement>,
+    sticky_headers: Vec<AnyElement>,
     blocks: Vec<BlockLayout>,
     highlighted_ranges: Vec<(Range<DisplayPoint>, Hsla)>,
     redacted_ranges: Vec<Range<DisplayPoint>>,
     cursors: Vec<(DisplayPoint, Hsla)>,
     visible_cursors: Vec<CursorLayout>,
     selections: Vec<(PlayerColor, Vec<SelectionLayout>)>,
+    virtual_selections: Vec<(DisplayRow, Range<u32>)>,
     code_actions_indicator: Option<AnyElement>,
     test_indicators: Vec<AnyElement>,
+    breakpoint_indicators: Vec<AnyElement>,
+    rebase_todo_indicators: Vec<AnyElement>,
     gutter_fold_toggles: Vec<Option<AnyElement>>,
     flap_trailers: Vec<Option<FlapTrailerLayout>>,
     mouse_context_menu: Option<AnyElement>,
+    color_picker: Option<AnyElement>,
     tab_invisible: ShapedLine,
     space_invisible: ShapedLine,
 }
@@ -5140,6 +6150,73 @@ impl EditorLayout {
     }
 }
 
+struct MinimapLayout {
+    hitbox: Hitbox,
+    /// The (fractional) display row shown at the top of the minimap.
+    first_row: f32,
+    visible_row_range: Range<f32>,
+    quads: Vec<PaintQuad>,
+}
+
+impl MinimapLayout {
+    const ROW_HEIGHT: Pixels = px(2.0);
+    const COLUMN_WIDTH: Pixels = px(1.0);
+    const HUNK_MARKER_WIDTH: Pixels = px(2.0);
+    const TEXT_INSET: Pixels = px(4.0);
+
+    /// Scrolls the minimap proportionally to the editor, so that both reach the end of the buffer
+    /// at the same time when the buffer does not fit into the minimap.
+    fn first_visible_row(
+        scroll_top: f32,
+        rows_per_page: f32,
+        total_rows: f32,
+        minimap_rows: f32,
+    ) -> f32 {
+        let overflow = total_rows - minimap_rows;
+        if overflow <= 0. {
+            return 0.;
+        }
+        let max_scroll_top = (total_rows - rows_per_page).max(1.);
+        (scroll_top / max_scroll_top).clamp(0., 1.) * overflow
+    }
+
+    fn max_columns(&self) -> u32 {
+        ((self.hitbox.size.width - Self::TEXT_INSET) / Self::COLUMN_WIDTH).max(0.) as u32
+    }
+
+    fn y_for_row(&self, row: f32) -> Pixels {
+        self.hitbox.top() + (row - self.first_row) * Self::ROW_HEIGHT
+    }
+
+    fn bounds_for_rows(&self, rows: Range<f32>, x: Range<Pixels>) -> Bounds<Pixels> {
+        Bounds::from_corners(
+            point(self.hitbox.left() + x.start, self.y_for_row(rows.start)),
+            point(
+                (self.hitbox.left() + x.end).min(self.hitbox.right()),
+                self.y_for_row(rows.end),
+            ),
+        )
+    }
+
+    fn quad_for_columns(&self, row: DisplayRow, columns: Range<u32>, color: Hsla) -> PaintQuad {
+        fill(
+            self.bounds_for_rows(
+                row.as_f32()..row.next_row().as_f32(),
+                Self::TEXT_INSET + Self::COLUMN_WIDTH * columns.start as f32
+                    ..Self::TEXT_INSET + Self::COLUMN_WIDTH * columns.end as f32,
+            ),
+            color,
+        )
+    }
+
+    fn thumb_bounds(&self) -> Bounds<Pixels> {
+        self.bounds_for_rows(
+            self.visible_row_range.clone(),
+            Pixels::ZERO..self.hitbox.size.width,
+        )
+    }
+}
+
 struct ColoredRange<T> {
     start: T,
     end: T,
@@ -5257,6 +6334,12 @@ struct FlapTrailerLayout {
     bounds: Bounds<Pixels>,
 }
 
+/// Text laid out after the end of a line, like an inline diagnostic or inline values.
+struct InlineLayout {
+    element: AnyElement,
+    bounds: Bounds<Pixels>,
+}
+
 struct PositionMap {
     size: Size<Pixels>,
     line_height: Pixels,
@@ -5633,7 +6716,7 @@ mod tests {
         Editor, MultiBuffer,
     };
     use gpui::{TestAppContext, VisualTestContext};
-    use language::language_settings;
+    use language::{language_settings, DiagnosticEntry, DiagnosticSet, LanguageServerId};
     use log::info;
     use std::num::NonZeroU32;
     use ui::Context;
@@ -5912,6 +6995,101 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_inline_diagnostics_by_row(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+        let buffer = cx.new_model(|cx| language::Buffer::local("one\ntwo\nthree\n", cx));
+        buffer.update(cx, |buffer, cx| {
+            let snapshot = buffer.text_snapshot();
+            let entries = [
+                (4..7, DiagnosticSeverity::WARNING, "unused", true),
+                (5..6, DiagnosticSeverity::ERROR, "mismatched types", true),
+                (5..6, DiagnosticSeverity::ERROR, "expected here", false),
+                (8..13, DiagnosticSeverity::HINT, "rename", true),
+            ]
+            .into_iter()
+            .enumerate()
+            .map(
+                |(group_id, (range, severity, message, is_primary))| DiagnosticEntry {
+                    range: snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+                    diagnostic: Diagnostic {
+                        severity,
+                        message: message.to_string(),
+                        group_id,
+                        is_primary,
+                        ..Default::default()
+                    },
+                },
+            );
+            let set = DiagnosticSet::from_sorted_entries(entries, &snapshot);
+            buffer.update_diagnostics(LanguageServerId(0), set, cx);
+        });
+        let window = cx.add_window(|cx| {
+            let buffer = cx.new_model(|cx| MultiBuffer::singleton(buffer, cx));
+            Editor::new(EditorMode::Full, buffer, None, true, cx)
+        });
+        let snapshot = window.update(cx, |editor, cx| editor.snapshot(cx)).unwrap();
+
+        let messages = |min_severity| {
+            let mut messages = inline_diagnostics_by_row(
+                &snapshot,
+                DisplayRow(0)..DisplayRow(4),
+                Anchor::min()..Anchor::max(),
+                min_severity,
+            )
+            .into_iter()
+            .map(|(row, diagnostic)| (row.0, diagnostic.message))
+            .collect::<Vec<_>>();
+            messages.sort();
+            messages
+        };
+        // Each row shows its most severe primary diagnostic.
+        assert_eq!(
+            messages(DiagnosticSeverityFilter::Hint),
+            [
+                (1, "mismatched types".to_string()),
+                (2, "rename".to_string())
+            ]
+        );
+        assert_eq!(
+            messages(DiagnosticSeverityFilter::Warning),
+            [(1, "mismatched types".to_string())]
+        );
+        assert_eq!(
+            inline_diagnostics_by_row(
+                &snapshot,
+                DisplayRow(2)..DisplayRow(4),
+                Anchor::min()..Anchor::max(),
+                DiagnosticSeverityFilter::Hint,
+            )
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_minimap_first_visible_row() {
+        // Buffers that fit into the minimap never scroll it.
+        assert_eq!(MinimapLayout::first_visible_row(0., 50., 100., 400.), 0.);
+        assert_eq!(MinimapLayout::first_visible_row(40., 50., 100., 400.), 0.);
+
+        // Otherwise the minimap scrolls proportionally to the editor.
+        assert_eq!(MinimapLayout::first_visible_row(0., 50., 1050., 400.), 0.);
+        assert_eq!(
+            MinimapLayout::first_visible_row(500., 50., 1050., 400.),
+            325.
+        );
+        assert_eq!(
+            MinimapLayout::first_visible_row(1000., 50., 1050., 400.),
+            650.
+        );
+        // Overscrolling past the end keeps the last rows of the buffer in view.
+        assert_eq!(
+            MinimapLayout::first_visible_row(1040., 50., 1050., 400.),
+            650.
+        );
+    }
+
     #[gpui::test]
     fn test_all_invisibles_drawing(cx: &mut TestAppContext) {
         const TAB_SIZE: u32 = 4;
@@ -0,0 +1,107 @@
+use std::{ops::Range, time::Duration};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{Model, Task, ViewContext};
+use language::Buffer;
+use project::FoldingRange;
+use text::BufferId;
+use util::ResultExt;
+
+use crate::{Anchor, Editor, EditorMode};
+
+/// How long to wait after an edit before refetching the folding ranges of the buffers.
+const FETCH_DEBOUNCE: Duration = Duration::from_millis(250);
+
+/// The foldable ranges language servers reported for the buffers in an editor, which augment
+/// the ranges inferred from comment regions and indentation.
+#[derive(Default)]
+pub(crate) struct FoldingRangesState {
+    ranges: HashMap<BufferId, (Model<Buffer>, Vec<FoldingRange>)>,
+    fetch_task: Option<Task<()>>,
+}
+
+/// Fetches the folding ranges of the buffers in the editor from their language servers, then
+/// hands them to the display map.
+pub(super) fn refresh_folding_ranges(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let buffers = editor.buffer.read(cx).all_buffers();
+    editor.folding_ranges.fetch_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(FETCH_DEBOUNCE).await;
+        let Ok(fetches) = editor.update(&mut cx, |_, cx| {
+            buffers
+                .into_iter()
+                .map(|buffer| {
+                    let ranges =
+                        project.update(cx, |project, cx| project.folding_ranges(&buffer, cx));
+                    async move { (buffer, ranges.await.log_err().unwrap_or_default()) }
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return;
+        };
+        let ranges_by_buffer = join_all(fetches).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.folding_ranges.ranges = ranges_by_buffer
+                    .into_iter()
+                    .map(|(buffer, ranges)| (buffer.read(cx).remote_id(), (buffer, ranges)))
+                    .collect();
+                let ranges = folding_ranges_in_excerpts(editor, cx)
+                    .into_iter()
+                    .map(|(range, _)| range)
+                    .collect();
+                editor
+                    .display_map
+                    .update(cx, |display_map, _| display_map.set_folding_ranges(ranges));
+                cx.notify();
+            })
+            .ok();
+    }));
+}
+
+/// Returns the multi-buffer ranges of the fetched folding ranges that lie within an excerpt,
+/// sorted by their start.
+pub(super) fn folding_ranges_in_excerpts(
+    editor: &Editor,
+    cx: &ViewContext<Editor>,
+) -> Vec<(Range<Anchor>, Option<lsp::FoldingRangeKind>)> {
+    let multibuffer = editor.buffer.read(cx);
+    let snapshot = multibuffer.snapshot(cx);
+    let mut result = Vec::new();
+    for (buffer, ranges) in editor.folding_ranges.ranges.values() {
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let excerpts = multibuffer.excerpts_for_buffer(buffer, cx);
+        for folding_range in ranges {
+            let range = excerpts.iter().find_map(|(excerpt_id, excerpt_range)| {
+                let contains_range = excerpt_range
+                    .context
+                    .start
+                    .cmp(&folding_range.range.start, &buffer_snapshot)
+                    .is_le()
+                    && excerpt_range
+                        .context
+                        .end
+                        .cmp(&folding_range.range.end, &buffer_snapshot)
+                        .is_ge();
+                if !contains_range {
+                    return None;
+                }
+                let start = snapshot.anchor_in_excerpt(*excerpt_id, folding_range.range.start)?;
+                let end = snapshot.anchor_in_excerpt(*excerpt_id, folding_range.range.end)?;
+                Some(start..end)
+            });
+            if let Some(range) = range {
+                result.push((range, folding_range.kind.clone()));
+            }
+        }
+    }
+    result.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &snapshot));
+    result
+}
@@ -1,16 +1,17 @@
 use crate::{
+    document_links,
     hover_popover::{self, InlayHover},
     scroll::ScrollAmount,
     Anchor, Editor, EditorSnapshot, FindAllReferences, GoToDefinition, GoToTypeDefinition, InlayId,
     PointForPosition, SelectPhase,
 };
 use gpui::{px, AppContext, AsyncWindowContext, Model, Modifiers, Task, ViewContext};
-use language::{Bias, ToOffset};
+use language::{Bias, Point, ToOffset};
 use linkify::{LinkFinder, LinkKind};
 use lsp::LanguageServerId;
 use project::{
     HoverBlock, HoverBlockKind, InlayHintLabelPartTooltip, InlayHintTooltip, LocationLink,
-    ResolveState,
+    ProjectPath, ResolveState,
 };
 use std::ops::Range;
 use theme::ActiveTheme as _;
@@ -63,6 +64,7 @@ impl RangeInEditor {
 #[derive(Debug, Clone)]
 pub enum HoverLink {
     Url(String),
+    File(ProjectPath, Option<Point>),
     Text(LocationLink),
     InlayHint(lsp::Location, LanguageServerId),
 }
@@ -416,6 +418,13 @@ pub fn update_inlay_link_and_hover_points(
                     ResolveState::Resolving => {}
                 }
             }
+        } else if let Some((range, tooltip)) = editor.unicode_highlights.placeholder_hover(
+            &previous_valid_anchor,
+            &next_valid_anchor,
+            &buffer_snapshot,
+        ) {
+            hover_popover::hover_at_inlay(editor, InlayHover { range, tooltip }, cx);
+            hover_updated = true;
         }
     }
 
@@ -486,7 +495,7 @@ pub fn show_link_definition(
         || hovered_link_state
             .links
             .first()
-            .is_some_and(|d| matches!(d, HoverLink::Url(_)));
+            .is_some_and(|d| matches!(d, HoverLink::Url(_) | HoverLink::File(_, _)));
 
     if same_kind {
         if is_cached && (&hovered_link_state.last_trigger_point == &trigger_point)
@@ -504,13 +513,25 @@ pub fn show_link_definition(
         editor.hide_hovered_link(cx)
     }
     let project = editor.project.clone();
+    let document_link = match &trigger_point {
+        TriggerPoint::Text(anchor) => document_links::hover_link_at(editor, anchor, cx),
+        TriggerPoint::InlayHint(..) => None,
+    };
 
     let snapshot = snapshot.buffer_snapshot.clone();
     hovered_link_state.task = Some(cx.spawn(|this, mut cx| {
         async move {
+            let document_link = match document_link {
+                Some(document_link) => document_link.await,
+                None => None,
+            };
             let result = match &trigger_point {
                 TriggerPoint::Text(_) => {
-                    if let Some((url_range, url)) = find_url(&buffer, buffer_position, cx.clone()) {
+                    if let Some((link_range, link)) = document_link {
+                        Some((Some(RangeInEditor::Text(link_range)), vec![link]))
+                    } else if let Some((url_range, url)) =
+                        find_url(&buffer, buffer_position, cx.clone())
+                    {
                         this.update(&mut cx, |_, _| {
                             let range = maybe!({
                                 let start =
@@ -1,24 +1,29 @@
 use crate::{
     display_map::{InlayOffset, ToDisplayPoint},
-    hover_links::{InlayHighlight, RangeInEditor},
+    hover_links::{HoverLink, InlayHighlight, RangeInEditor},
     scroll::ScrollAmount,
     Anchor, AnchorRangeExt, DisplayPoint, DisplayRow, Editor, EditorSettings, EditorSnapshot,
     EditorStyle, Hover, RangeToAnchorExt,
 };
 use futures::{stream::FuturesUnordered, FutureExt};
 use gpui::{
-    div, px, AnyElement, CursorStyle, Hsla, InteractiveElement, IntoElement, MouseButton,
-    ParentElement, Pixels, ScrollHandle, SharedString, Size, StatefulInteractiveElement, Styled,
-    Task, ViewContext, WeakView,
+    div, px, AnyElement, AppContext, CursorStyle, Hsla, InteractiveElement, IntoElement, Model,
+    MouseButton, ParentElement, Pixels, ScrollHandle, SharedString, Size,
+    StatefulInteractiveElement, Styled, Task, ViewContext, WeakView,
+};
+use language::{
+    markdown, Buffer, Diagnostic, DiagnosticEntry, Language, LanguageRegistry, ParsedMarkdown,
+    Point, ToPoint as _,
 };
-use language::{markdown, DiagnosticEntry, Language, LanguageRegistry, ParsedMarkdown};
 
 use lsp::DiagnosticSeverity;
 use multi_buffer::ToOffset;
-use project::{HoverBlock, HoverBlockKind, InlayHintLabelPart};
+use project::{
+    HoverBlock, HoverBlockKind, InlayHintLabelPart, Location, LocationLink, Project, ProjectPath,
+};
 use settings::Settings;
 use smol::stream::StreamExt;
-use std::{ops::Range, sync::Arc, time::Duration};
+use std::{ops::Range, path::Path, sync::Arc, time::Duration};
 use ui::{prelude::*, Tooltip};
 use util::TryFutureExt;
 use workspace::Workspace;
@@ -226,6 +231,10 @@ fn show_hover(
         }
     }
 
+    let unicode_hover = editor
+        .unicode_highlights
+        .confusable_hover(&anchor, &snapshot.buffer_snapshot);
+
     let task = cx.spawn(|this, mut cx| {
         async move {
             // If we need to delay, delay a set amount initially before making the lsp request
@@ -278,11 +287,25 @@ fn show_hover(
                     })
             });
 
+            let related_locations = match &local_diagnostic {
+                Some(local_diagnostic) => cx.update(|cx| {
+                    related_diagnostic_locations(
+                        &buffer,
+                        project.read(cx),
+                        local_diagnostic.diagnostic.group_id,
+                        primary_diagnostic.as_ref().map(|entry| &entry.diagnostic),
+                        cx,
+                    )
+                })?,
+                None => Vec::new(),
+            };
+
             this.update(&mut cx, |this, _| {
                 this.hover_state.diagnostic_popover =
                     local_diagnostic.map(|local_diagnostic| DiagnosticPopover {
                         local_diagnostic,
                         primary_diagnostic,
+                        related_locations,
                     });
             })?;
 
@@ -327,6 +350,16 @@ fn show_hover(
                 hover_highlights.push(highlight_range);
                 info_popovers.push(info_popover);
             }
+            // Explain the unusual character being hovered, if any
+            if let Some((range, block)) = unicode_hover {
+                let parsed_content = parse_blocks(&[block], &language_registry, None).await;
+                hover_highlights.push(range.clone());
+                info_popovers.push(InfoPopover {
+                    symbol_range: RangeInEditor::Text(range),
+                    parsed_content,
+                    scroll_handle: ScrollHandle::new(),
+                });
+            }
 
             this.update(&mut cx, |editor, cx| {
                 if hover_highlights.is_empty() {
@@ -540,6 +573,80 @@ impl InfoPopover {
 pub struct DiagnosticPopover {
     local_diagnostic: DiagnosticEntry<Anchor>,
     primary_diagnostic: Option<DiagnosticEntry<Anchor>>,
+    related_locations: Vec<RelatedDiagnosticLocation>,
+}
+
+/// A location related to a hovered diagnostic, which the diagnostic popover links to.
+#[derive(Debug, Clone)]
+struct RelatedDiagnosticLocation {
+    message: SharedString,
+    /// The path and position of the location, like `src/main.rs:3:5`.
+    label: SharedString,
+    link: Option<HoverLink>,
+}
+
+/// Collects the other members of a diagnostic's group in the buffer, followed by the locations in
+/// other files that its primary diagnostic relates to.
+fn related_diagnostic_locations(
+    buffer: &Model<Buffer>,
+    project: &Project,
+    group_id: usize,
+    primary_diagnostic: Option<&Diagnostic>,
+    cx: &AppContext,
+) -> Vec<RelatedDiagnosticLocation> {
+    let snapshot = buffer.read(cx).snapshot();
+    let buffer_path = snapshot.file().map(|file| file.path().clone());
+    let mut locations = snapshot
+        .diagnostic_group::<text::Anchor>(group_id)
+        .filter(|entry| !entry.diagnostic.is_primary && !entry.diagnostic.message.is_empty())
+        .map(|entry| {
+            let position = entry.range.start.to_point(&snapshot);
+            RelatedDiagnosticLocation {
+                message: entry.diagnostic.message.into(),
+                label: buffer_path
+                    .as_ref()
+                    .map(|path| location_label(path, position))
+                    .unwrap_or_else(|| {
+                        format!("{}:{}", position.row + 1, position.column + 1).into()
+                    }),
+                link: Some(HoverLink::Text(LocationLink {
+                    origin: None,
+                    target: Location {
+                        buffer: buffer.clone(),
+                        range: entry.range,
+                    },
+                })),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    for info in primary_diagnostic
+        .into_iter()
+        .flat_map(|diagnostic| &diagnostic.related_information)
+    {
+        let position = Point::new(info.range.start.0.row, info.range.start.0.column);
+        let project_path = project.project_path_for_absolute_path(&info.abs_path, cx);
+        locations.push(RelatedDiagnosticLocation {
+            message: info.message.clone().into(),
+            label: match &project_path {
+                Some(ProjectPath { path, .. }) => location_label(path, position),
+                None => location_label(&info.abs_path, position),
+            },
+            link: project_path.map(|project_path| HoverLink::File(project_path, Some(position))),
+        });
+    }
+
+    locations
+}
+
+fn location_label(path: &Path, position: Point) -> SharedString {
+    format!(
+        "{}:{}:{}",
+        path.display(),
+        position.row + 1,
+        position.column + 1
+    )
+    .into()
 }
 
 impl DiagnosticPopover {
@@ -608,6 +715,38 @@ impl DiagnosticPopover {
             .on_mouse_down(MouseButton::Left, |_, cx| cx.stop_propagation())
             .on_click(cx.listener(|editor, _, cx| editor.go_to_diagnostic(&Default::default(), cx)))
             .child(SharedString::from(text))
+            .children(
+                self.related_locations
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, location)| {
+                        let link = location.link.clone();
+                        h_flex()
+                            .id(("related_diagnostic_location", ix))
+                            .gap_1()
+                            .child(Label::new(location.label.clone()).color(if link.is_some() {
+                                Color::Accent
+                            } else {
+                                Color::Muted
+                            }))
+                            .when(!location.message.is_empty(), |row| {
+                                row.child(Label::new(location.message.clone()))
+                            })
+                            .when_some(link, |row, link| {
+                                row.on_click(cx.listener(move |editor, _, cx| {
+                                    cx.stop_propagation();
+                                    editor
+                                        .navigate_to_hover_links(
+                                            None,
+                                            vec![link.clone()],
+                                            false,
+                                            cx,
+                                        )
+                                        .detach_and_log_err(cx);
+                                }))
+                            })
+                    }),
+            )
             .into_any_element()
     }
 
@@ -4,8 +4,9 @@ use std::{
 };
 
 use collections::{hash_map, HashMap, HashSet};
+use futures::future;
 use git::diff::{DiffHunk, DiffHunkStatus};
-use gpui::{AppContext, Hsla, Model, Task, View};
+use gpui::{AppContext, Hsla, Model, Pixels, Task, View};
 use language::Buffer;
 use multi_buffer::{
     Anchor, ExcerptRange, MultiBuffer, MultiBufferRow, MultiBufferSnapshot, ToPoint,
@@ -13,16 +14,21 @@ use multi_buffer::{
 use settings::SettingsStore;
 use text::{BufferId, Point};
 use ui::{
-    div, ActiveTheme, Context as _, IntoElement, ParentElement, Styled, ViewContext, VisualContext,
+    div, ActiveTheme, Context as _, ContextMenu, IntoElement, ParentElement, Styled, ViewContext,
+    VisualContext,
 };
 use util::{debug_panic, RangeExt};
+use workspace::notifications::DetachAndPromptErr;
 
 use crate::{
+    actions::{StageSelectedHunks, UnstageSelectedHunks},
     editor_settings::CurrentLineHighlight,
     git::{diff_hunk_to_display, DisplayDiffHunk},
-    hunk_status, hunks_for_selections, BlockDisposition, BlockId, BlockProperties, BlockStyle,
-    DiffRowHighlight, Editor, EditorSnapshot, ExpandAllHunkDiffs, RangeToAnchorExt,
-    RevertSelectedHunks, ToDisplayPoint, ToggleHunkDiff,
+    hunk_status, hunks_for_selections,
+    mouse_context_menu::MouseContextMenu,
+    BlockDisposition, BlockId, BlockProperties, BlockStyle, DiffRowHighlight, DisplayPoint,
+    DisplayRow, Editor, EditorSnapshot, ExpandAllHunkDiffs, RangeToAnchorExt, RevertSelectedHunks,
+    SelectMode, ToDisplayPoint, ToggleHunkDiff,
 };
 
 #[derive(Debug, Clone)]
@@ -107,6 +113,127 @@ impl Editor {
         self.toggle_hunks_expanded(hunks.collect(), cx);
     }
 
+    /// Writes the changes of the hunks of the selections to the git index.
+    pub fn stage_selected_hunks(&mut self, _: &StageSelectedHunks, cx: &mut ViewContext<Self>) {
+        let multi_buffer_snapshot = self.buffer().read(cx).snapshot(cx);
+        let selections = self.selections.disjoint_anchors();
+        let mut ranges_by_buffer = HashMap::<BufferId, Vec<Range<text::Anchor>>>::default();
+        for hunk in hunks_for_selections(&multi_buffer_snapshot, &selections) {
+            ranges_by_buffer
+                .entry(hunk.buffer_id)
+                .or_default()
+                .push(hunk.buffer_range);
+        }
+        self.update_git_index(ranges_by_buffer, true, cx);
+    }
+
+    /// Restores the HEAD contents of the staged changes on the lines of the selections in the git
+    /// index.
+    pub fn unstage_selected_hunks(&mut self, _: &UnstageSelectedHunks, cx: &mut ViewContext<Self>) {
+        let mut ranges_by_buffer = HashMap::<BufferId, Vec<Range<text::Anchor>>>::default();
+        for selection in self.selections.all::<usize>(cx) {
+            let buffer_ranges = self
+                .buffer()
+                .read(cx)
+                .range_to_buffer_ranges(selection.range(), cx);
+            for (buffer, range, _) in buffer_ranges {
+                let buffer = buffer.read(cx);
+                ranges_by_buffer
+                    .entry(buffer.remote_id())
+                    .or_default()
+                    .push(buffer.anchor_before(range.start)..buffer.anchor_after(range.end));
+            }
+        }
+        self.update_git_index(ranges_by_buffer, false, cx);
+    }
+
+    fn update_git_index(
+        &mut self,
+        ranges_by_buffer: HashMap<BufferId, Vec<Range<text::Anchor>>>,
+        stage: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let mut tasks = Vec::new();
+        for (buffer_id, ranges) in ranges_by_buffer {
+            let Some(buffer) = self.buffer().read(cx).buffer(buffer_id) else {
+                continue;
+            };
+            tasks.push(project.update(cx, |project, cx| {
+                if stage {
+                    project.stage_hunks(&buffer, ranges, cx)
+                } else {
+                    project.unstage_hunks(&buffer, ranges, cx)
+                }
+            }));
+        }
+        if tasks.is_empty() {
+            return;
+        }
+
+        let message = if stage {
+            "Failed to stage the changes"
+        } else {
+            "Failed to unstage the changes"
+        };
+        cx.spawn(|_, _| async move {
+            future::try_join_all(tasks).await?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err(message, cx, |_, _| None);
+    }
+
+    /// Deploys the menu of the diff hunk on the given row when its gutter indicator is
+    /// right-clicked, if there is one.
+    pub(super) fn deploy_hunk_context_menu(
+        &mut self,
+        position: gpui::Point<Pixels>,
+        row: DisplayRow,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        let snapshot = self.snapshot(cx);
+        let buffer_row = DisplayPoint::new(row, 0)
+            .to_point(&snapshot.display_snapshot)
+            .row;
+        let has_hunk = snapshot
+            .buffer_snapshot
+            .git_diff_hunks_in_range(MultiBufferRow(buffer_row)..MultiBufferRow(buffer_row + 1))
+            .next()
+            .is_some();
+        if !has_hunk {
+            return false;
+        }
+
+        if !self.is_focused(cx) {
+            self.focus(cx);
+        }
+        // Move the cursor to the hunk's line, where the menu's actions apply.
+        let point = DisplayPoint::new(row, 0);
+        self.change_selections(None, cx, |s| {
+            s.clear_disjoint();
+            s.set_pending_display_range(point..point, SelectMode::Character);
+        });
+
+        let focus = cx.focused();
+        let context_menu = ContextMenu::build(cx, |menu, _| {
+            let menu = menu
+                .action("Stage Hunk", Box::new(StageSelectedHunks))
+                .action("Unstage Hunk", Box::new(UnstageSelectedHunks))
+                .action("Revert Hunk", Box::new(RevertSelectedHunks))
+                .separator()
+                .action("Toggle Hunk Diff", Box::new(ToggleHunkDiff));
+            match focus {
+                Some(focus) => menu.context(focus),
+                None => menu,
+            }
+        });
+        self.mouse_context_menu = Some(MouseContextMenu::new(position, context_menu, cx));
+        cx.notify();
+        true
+    }
+
     fn toggle_hunks_expanded(
         &mut self,
         hunks_to_toggle: Vec<DiffHunk<MultiBufferRow>>,
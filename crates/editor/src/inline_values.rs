@@ -0,0 +1,398 @@
+use std::{ops::Range, path::Path, time::Duration};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{AppContext, Model, Task, ViewContext};
+use language::{Buffer, BufferSnapshot, Point, ToPoint as _};
+use multi_buffer::{MultiBufferRow, ToPoint as _};
+use project::{dap_store::DebugSessionStatus, InlineValueKind};
+use settings::Settings;
+use text::BufferId;
+use util::ResultExt;
+
+use crate::{
+    display_map::ToDisplayPoint, DisplayRow, Editor, EditorMode, EditorSettings, EditorSnapshot,
+};
+
+/// How long to wait before fetching the values, as the debug session reports the scopes and
+/// variables of the stopped frame one request at a time.
+const FETCH_DEBOUNCE: Duration = Duration::from_millis(50);
+
+/// How many lines above the stopped one show values when it isn't in a function.
+const MAX_ROWS_OUTSIDE_FUNCTION: u32 = 50;
+
+/// The most characters of a value shown inline.
+const MAX_VALUE_LENGTH: usize = 50;
+
+/// The values of the variables of the function a debug session is stopped in, shown after the
+/// lines they occur on.
+#[derive(Default)]
+pub(crate) struct InlineValuesState {
+    /// The text shown after each line of the stopped buffer, by the start of the line.
+    lines: Vec<(Model<Buffer>, language::Anchor, String)>,
+    /// The stop the lines were computed for, to skip recomputing them when the debug session
+    /// changes in other ways, like when its program prints output.
+    stop: Option<Stop>,
+    fetch_task: Option<Task<()>>,
+}
+
+#[derive(PartialEq)]
+struct Stop {
+    buffer_id: BufferId,
+    parse_count: usize,
+    frame_id: u64,
+    variables: HashMap<String, String>,
+}
+
+/// Computes the values to show inline when the active debug session is stopped in one of the
+/// buffers of the editor, asking its language server for them and falling back to the values of
+/// the variables named by the identifiers of the stopped function.
+pub(super) fn refresh_inline_values(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+    let dap_store = project.read(cx).dap_store().clone();
+    let stopped_frame = (editor.mode == EditorMode::Full
+        && EditorSettings::get_global(cx).inline_values)
+        .then(|| {
+            let (session_id, session) = dap_store.read(cx).active_session()?;
+            if session.status() != DebugSessionStatus::Stopped {
+                return None;
+            }
+            let frame = session.active_stack_frame()?;
+            let path = Path::new(frame.source.as_ref()?.path.as_ref()?);
+            let buffer = editor
+                .buffer
+                .read(cx)
+                .all_buffers()
+                .into_iter()
+                .find(|buffer| {
+                    buffer
+                        .read(cx)
+                        .file()
+                        .and_then(|file| file.as_local())
+                        .map_or(false, |file| file.abs_path(cx) == path)
+                })?;
+            let mut variables = HashMap::default();
+            for scope in session.scopes() {
+                for variable in session
+                    .variables(scope.variables_reference)
+                    .unwrap_or_default()
+                {
+                    variables
+                        .entry(variable.name.clone())
+                        .or_insert_with(|| variable.value.clone());
+                }
+            }
+            let position = Point::new(
+                frame.line.saturating_sub(1) as u32,
+                frame.column.saturating_sub(1) as u32,
+            );
+            let stop = Stop {
+                buffer_id: buffer.read(cx).remote_id(),
+                parse_count: buffer.read(cx).parse_count(),
+                frame_id: frame.id,
+                variables,
+            };
+            Some((session_id, buffer, position, stop))
+        })
+        .flatten();
+    let Some((session_id, buffer, position, stop)) = stopped_frame else {
+        if editor.inline_values.stop.is_some() || !editor.inline_values.lines.is_empty() {
+            editor.inline_values = InlineValuesState::default();
+            cx.notify();
+        }
+        return;
+    };
+    if editor.inline_values.stop.as_ref() == Some(&stop) {
+        return;
+    }
+
+    let frame_id = stop.frame_id;
+    let variables = stop.variables.clone();
+    editor.inline_values.stop = Some(stop);
+
+    let snapshot = buffer.read(cx).snapshot();
+    let position = snapshot.clip_point(position, text::Bias::Left);
+    let range = stopped_function_range(&snapshot, position);
+    editor.inline_values.fetch_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(FETCH_DEBOUNCE).await;
+        let Ok(lsp_values) = project.update(&mut cx, |project, cx| {
+            let stopped_at = snapshot.anchor_before(position);
+            project.inline_values(
+                &buffer,
+                snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end),
+                frame_id,
+                stopped_at..stopped_at,
+                cx,
+            )
+        }) else {
+            return;
+        };
+        let values = match lsp_values.await.log_err().flatten() {
+            Some(lsp_values) => {
+                let mut values = Vec::new();
+                let mut evaluations = Vec::new();
+                for value in lsp_values {
+                    let start = value.range.start.to_point(&snapshot);
+                    if start.row > position.row {
+                        continue;
+                    }
+                    let text = || {
+                        snapshot
+                            .text_for_range(value.range.clone())
+                            .collect::<String>()
+                    };
+                    match value.kind {
+                        InlineValueKind::Text(text) => values.push((start, text)),
+                        InlineValueKind::VariableLookup {
+                            name,
+                            case_sensitive,
+                        } => {
+                            let name = name.unwrap_or_else(text);
+                            let value = variables.get(&name).or_else(|| {
+                                variables
+                                    .iter()
+                                    .find(|(variable, _)| {
+                                        !case_sensitive && variable.eq_ignore_ascii_case(&name)
+                                    })
+                                    .map(|(_, value)| value)
+                            });
+                            if let Some(value) = value {
+                                values.push((start, format_value(&name, value)));
+                            }
+                        }
+                        InlineValueKind::EvaluatableExpression(expression) => {
+                            let expression = expression.unwrap_or_else(text);
+                            if let Some(value) = variables.get(&expression) {
+                                values.push((start, format_value(&expression, value)));
+                                continue;
+                            }
+                            let Ok(evaluation) = dap_store.update(&mut cx, |dap_store, cx| {
+                                dap_store.evaluate(session_id, expression.clone(), cx)
+                            }) else {
+                                return;
+                            };
+                            evaluations.push(async move {
+                                let response = evaluation.await.log_err()?;
+                                Some((start, format_value(&expression, &response.result)))
+                            });
+                        }
+                    }
+                }
+                values.extend(join_all(evaluations).await.into_iter().flatten());
+                values
+            }
+            None => identifier_values(&snapshot, range, &variables),
+        };
+
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.inline_values.lines = lines_by_row(values)
+                    .into_iter()
+                    .map(|(row, text)| {
+                        let anchor = snapshot.anchor_before(Point::new(row, 0));
+                        (buffer.clone(), anchor, text)
+                    })
+                    .collect();
+                cx.notify();
+            })
+            .ok();
+    }));
+}
+
+/// Returns the display rows of the lines with inline values along with the text shown after
+/// them, skipping the ones that are folded away.
+pub(super) fn inline_value_display_rows(
+    editor: &Editor,
+    snapshot: &EditorSnapshot,
+    cx: &AppContext,
+) -> Vec<(DisplayRow, String)> {
+    let multibuffer = editor.buffer.read(cx);
+    let mut rows = Vec::new();
+    for (buffer, line_start, text) in &editor.inline_values.lines {
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let anchor = multibuffer
+            .excerpts_for_buffer(buffer, cx)
+            .into_iter()
+            .find_map(|(excerpt_id, excerpt_range)| {
+                let contains_line = excerpt_range
+                    .context
+                    .start
+                    .cmp(line_start, &buffer_snapshot)
+                    .is_le()
+                    && excerpt_range
+                        .context
+                        .end
+                        .cmp(line_start, &buffer_snapshot)
+                        .is_ge();
+                if !contains_line {
+                    return None;
+                }
+                snapshot
+                    .buffer_snapshot
+                    .anchor_in_excerpt(excerpt_id, *line_start)
+            });
+        let Some(anchor) = anchor else {
+            continue;
+        };
+        let point = anchor.to_point(&snapshot.buffer_snapshot);
+        if !snapshot.is_line_folded(MultiBufferRow(point.row)) {
+            rows.push((point.to_display_point(snapshot).row(), text.clone()));
+        }
+    }
+    rows
+}
+
+/// Returns the range from the start of the function containing the position to the end of its
+/// line, or from a few lines above it when it isn't in a function.
+fn stopped_function_range(snapshot: &BufferSnapshot, position: Point) -> Range<usize> {
+    let offset = snapshot.point_to_offset(position);
+    let function_start = snapshot.syntax_layer_at(offset).and_then(|layer| {
+        let mut node = layer.node().descendant_for_byte_range(offset, offset)?;
+        loop {
+            let kind = node.kind();
+            let is_function = !kind.contains("call")
+                && ["function", "method", "closure", "lambda"]
+                    .iter()
+                    .any(|function_kind| kind.contains(function_kind));
+            if is_function {
+                return Some(node.start_byte());
+            }
+            node = node.parent()?;
+        }
+    });
+    let start = function_start.unwrap_or_else(|| {
+        snapshot.point_to_offset(Point::new(
+            position.row.saturating_sub(MAX_ROWS_OUTSIDE_FUNCTION),
+            0,
+        ))
+    });
+    let end = snapshot.point_to_offset(Point::new(position.row, snapshot.line_len(position.row)));
+    start..end
+}
+
+/// Returns the values of the variables named by the identifiers in the range, along with where
+/// the identifiers start.
+fn identifier_values(
+    snapshot: &BufferSnapshot,
+    range: Range<usize>,
+    variables: &HashMap<String, String>,
+) -> Vec<(Point, String)> {
+    let mut values = Vec::new();
+    let Some(layer) = snapshot.syntax_layer_at(range.end) else {
+        return values;
+    };
+    let mut cursor = layer.node().walk();
+    loop {
+        let node = cursor.node();
+        let overlaps_range = node.start_byte() < range.end && node.end_byte() > range.start;
+        if overlaps_range && node.kind() == "identifier" {
+            let name = snapshot
+                .text_for_range(node.byte_range())
+                .collect::<String>();
+            if let Some(value) = variables.get(&name) {
+                let start = snapshot.offset_to_point(node.start_byte());
+                values.push((start, format_value(&name, value)));
+            }
+        }
+        if overlaps_range && cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                return values;
+            }
+        }
+    }
+}
+
+/// Joins the values starting on each row, in the order they appear and without duplicates.
+fn lines_by_row(mut values: Vec<(Point, String)>) -> Vec<(u32, String)> {
+    values.sort_by_key(|(start, _)| *start);
+    let mut lines = Vec::<(u32, Vec<String>)>::new();
+    for (start, value) in values {
+        match lines.last_mut() {
+            Some((row, row_values)) if *row == start.row => {
+                if !row_values.contains(&value) {
+                    row_values.push(value);
+                }
+            }
+            _ => lines.push((start.row, vec![value])),
+        }
+    }
+    lines
+        .into_iter()
+        .map(|(row, row_values)| (row, row_values.join(", ")))
+        .collect()
+}
+
+fn format_value(name: &str, value: &str) -> String {
+    let value = value.lines().next().unwrap_or_default();
+    format!(
+        "{name} = {}",
+        util::truncate_and_trailoff(value, MAX_VALUE_LENGTH)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{Context as _, TestAppContext};
+    use language::{Language, LanguageConfig};
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_identifier_values(cx: &mut TestAppContext) {
+        let language = Arc::new(Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        ));
+        let text = "
+fn before(a: u32) {}
+
+fn stopped(a: u32) -> u32 {
+    let b = a + 1;
+    let c = b * a;
+    let d = c;
+    d
+}
+";
+        let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(language, cx));
+        cx.executor().run_until_parked();
+
+        let snapshot = buffer.read_with(cx, |buffer, _| buffer.snapshot());
+        let position = Point::new(5, 4);
+        let range = stopped_function_range(&snapshot, position);
+        assert_eq!(
+            snapshot.offset_to_point(range.start)..snapshot.offset_to_point(range.end),
+            Point::new(3, 0)..Point::new(5, 18)
+        );
+
+        let variables = [
+            ("a", "1"),
+            ("b", "2"),
+            ("c", "a very long value\nwith lines"),
+        ]
+        .into_iter()
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .collect();
+        assert_eq!(
+            lines_by_row(identifier_values(&snapshot, range, &variables)),
+            [
+                (3, "a = 1".to_string()),
+                (4, "b = 2, a = 1".to_string()),
+                (5, "c = a very long value, b = 2, a = 1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value("x", "3"), "x = 3");
+        assert_eq!(
+            format_value("s", &"a".repeat(MAX_VALUE_LENGTH + 10)),
+            format!("s = {}…", "a".repeat(MAX_VALUE_LENGTH))
+        );
+    }
+}
@@ -9,8 +9,8 @@ use futures::future::try_join_all;
 use git::repository::GitFileStatus;
 use gpui::{
     point, AnyElement, AppContext, AsyncWindowContext, Context, Entity, EntityId, EventEmitter,
-    IntoElement, Model, ParentElement, Pixels, SharedString, Styled, Task, View, ViewContext,
-    VisualContext, WeakView, WindowContext,
+    ExternalPaths, IntoElement, Model, ParentElement, Pixels, SharedString, Styled, Task, View,
+    ViewContext, VisualContext, WeakView, WindowContext,
 };
 use language::{
     proto::serialize_anchor as serialize_text_anchor, Bias, Buffer, CharKind, Point, SelectionGoal,
@@ -19,10 +19,10 @@ use multi_buffer::AnchorRangeExt;
 use project::{search::SearchQuery, FormatTrigger, Item as _, Project, ProjectPath};
 use rpc::proto::{self, update_view, PeerId};
 use settings::Settings;
-use workspace::item::{ItemSettings, TabContentParams};
+use workspace::item::{ItemSettings, NavigationLocation, TabContentParams};
 
 use std::{
-    any::TypeId,
+    any::{Any, TypeId},
     borrow::Cow,
     cmp::{self, Ordering},
     iter,
@@ -38,7 +38,8 @@ use workspace::item::{BreadcrumbText, FollowEvent, FollowableItemHandle};
 use workspace::{
     item::{FollowableItem, Item, ItemEvent, ItemHandle, ProjectItem},
     searchable::{Direction, SearchEvent, SearchableItem, SearchableItemHandle},
-    ItemId, ItemNavHistory, Pane, ToolbarItemLocation, ViewId, Workspace, WorkspaceId,
+    DraggedSelection, ItemId, ItemNavHistory, Pane, ToolbarItemLocation, ViewId, Workspace,
+    WorkspaceId,
 };
 
 pub const MAX_TAB_TITLE_LEN: usize = 24;
@@ -572,6 +573,26 @@ impl Item for Editor {
         }
     }
 
+    fn navigation_location(&self, data: &dyn Any, cx: &AppContext) -> Option<NavigationLocation> {
+        let data = data.downcast_ref::<NavigationData>()?;
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let point = if snapshot.can_resolve(&data.cursor_anchor) {
+            data.cursor_anchor.to_point(&snapshot)
+        } else {
+            snapshot.clip_point(data.cursor_position, Bias::Left)
+        };
+        // Locations in multi-buffers are described by their row in the excerpt's buffer.
+        let (buffer, offset) = snapshot.point_to_buffer_offset(point)?;
+        let row = buffer.offset_to_point(offset).row;
+        let line = buffer
+            .text_for_range(Point::new(row, 0)..Point::new(row, buffer.line_len(row)))
+            .collect::<String>();
+        Some(NavigationLocation {
+            row,
+            preview: line.trim().to_string().into(),
+        })
+    }
+
     fn tab_tooltip_text(&self, cx: &AppContext) -> Option<SharedString> {
         let file_path = self
             .buffer()
@@ -800,6 +821,52 @@ impl Item for Editor {
         self.pixel_position_of_newest_cursor
     }
 
+    fn handle_drop(&mut self, dropped: &dyn Any, cx: &mut ViewContext<Self>) -> bool {
+        if self.buffer.read(cx).is_singleton() || self.buffer.read(cx).read_only() {
+            return false;
+        }
+        let Some(project) = self.project.clone() else {
+            return false;
+        };
+
+        let open_buffers = if let Some(selection) = dropped.downcast_ref::<DraggedSelection>() {
+            project.update(cx, |project, cx| {
+                selection
+                    .items()
+                    .filter_map(|entry| project.path_for_entry(entry.entry_id, cx))
+                    .map(|path| project.open_buffer(path, cx))
+                    .collect::<Vec<_>>()
+            })
+        } else if let Some(paths) = dropped.downcast_ref::<ExternalPaths>() {
+            if !project.read(cx).is_local() {
+                return false;
+            }
+            project.update(cx, |project, cx| {
+                paths
+                    .paths()
+                    .iter()
+                    .map(|path| project.open_local_buffer(path, cx))
+                    .collect::<Vec<_>>()
+            })
+        } else {
+            return false;
+        };
+
+        cx.spawn(|editor, mut cx| async move {
+            let mut buffers = Vec::new();
+            for open_buffer in open_buffers {
+                if let Some(buffer) = open_buffer.await.log_err() {
+                    buffers.push(buffer);
+                }
+            }
+            editor.update(&mut cx, |editor, cx| {
+                editor.push_buffer_excerpts(buffers, cx)
+            })
+        })
+        .detach_and_log_err(cx);
+        true
+    }
+
     fn breadcrumb_location(&self) -> ToolbarItemLocation {
         if self.show_breadcrumbs {
             ToolbarItemLocation::PrimaryLeft
@@ -873,6 +940,8 @@ impl Item for Editor {
 
         if let Some(buffer) = self.buffer().read(cx).as_singleton() {
             serialize(buffer.clone(), workspace_id, item_id, cx);
+            // Folds restored before the editor was added are stored under its new item id.
+            self.serialize_folds(cx);
 
             cx.subscribe(&buffer, |this, buffer, event, cx| {
                 if let Some((_, Some(workspace_id))) = this.workspace.as_ref() {
@@ -964,6 +1033,7 @@ impl Item for Editor {
                             let mut editor = Editor::for_buffer(buffer, Some(project), cx);
 
                             editor.read_scroll_position_from_db(item_id, workspace_id, cx);
+                            editor.read_folds_from_db(item_id, workspace_id, cx);
                             editor
                         })
                     })
@@ -1078,15 +1148,63 @@ impl SearchableItem for Editor {
         }
         self.change_selections(None, cx, |s| s.select_ranges(ranges));
     }
+
+    fn select_match_captures(
+        &mut self,
+        matches: &[Range<Anchor>],
+        query: &SearchQuery,
+        group: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let ranges = matches
+            .iter()
+            .filter_map(|m| {
+                let range = m.to_offset(&snapshot);
+                let text = snapshot.text_for_range(range.clone()).collect::<String>();
+                let capture = query.capture_range(&text, group)?;
+                Some(
+                    self.range_for_match(&(range.start + capture.start..range.start + capture.end)),
+                )
+            })
+            .collect::<Vec<_>>();
+        if ranges.is_empty() {
+            return;
+        }
+
+        self.unfold_ranges(ranges.clone(), false, false, cx);
+        self.change_selections(None, cx, |s| s.select_ranges(ranges));
+    }
+
     fn replace(
         &mut self,
         identifier: &Self::Match,
         query: &SearchQuery,
         cx: &mut ViewContext<Self>,
     ) {
-        let text = self.buffer.read(cx);
-        let text = text.snapshot(cx);
-        let text = text.text_for_range(identifier.clone()).collect::<Vec<_>>();
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        if query.is_structural() {
+            // Structural replacements refer to the captures of the match, so they need the syntax tree of its buffer.
+            let range = identifier.to_offset(&snapshot);
+            let replacement = snapshot
+                .excerpt_containing(range.clone())
+                .and_then(|excerpt| {
+                    query.structural_replacement_for(
+                        excerpt.buffer(),
+                        excerpt.map_range_to_buffer(range),
+                    )
+                });
+            if let Some(replacement) = replacement {
+                self.transact(cx, |this, cx| {
+                    this.edit([(identifier.clone(), Arc::from(replacement))], cx);
+                });
+            }
+            return;
+        }
+
+        let text = snapshot
+            .text_for_range(identifier.clone())
+            .collect::<Vec<_>>();
         let text: Cow<_> = if text.len() == 1 {
             text.first().cloned().unwrap().into()
         } else {
@@ -1335,9 +1453,13 @@ fn path_for_file<'a>(
 #[cfg(test)]
 mod tests {
     use super::*;
-    use gpui::AppContext;
-    use language::TestFile;
-    use std::path::Path;
+    use crate::editor_tests::init_test;
+    use gpui::{AppContext, TestAppContext, VisualTestContext};
+    use language::{Capability, TestFile};
+    use project::FakeFs;
+    use serde_json::json;
+    use std::{collections::BTreeSet, path::Path};
+    use workspace::SelectedEntry;
 
     #[gpui::test]
     fn test_path_for_file(cx: &mut AppContext) {
@@ -1347,4 +1469,66 @@ mod tests {
         };
         assert_eq!(path_for_file(&file, 0, false, cx), None);
     }
+
+    #[gpui::test]
+    async fn test_handle_drop(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/dir", json!({ "a.txt": "a", "b.txt": "b", "c.txt": "c" }))
+            .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let a = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/a.txt", cx)
+            })
+            .await
+            .unwrap();
+        let multibuffer = cx.new_model(|cx| {
+            let mut multibuffer = MultiBuffer::new(0, Capability::ReadWrite);
+            multibuffer.push_excerpts(
+                a.clone(),
+                [ExcerptRange {
+                    context: Point::zero()..Point::new(0, 1),
+                    primary: None,
+                }],
+                cx,
+            );
+            multibuffer
+        });
+        let (editor, cx) = cx.add_window_view(|cx| {
+            Editor::for_multibuffer(multibuffer, Some(project.clone()), true, cx)
+        });
+        let selected_entry = |path: &str, cx: &mut VisualTestContext| {
+            project.update(cx, |project, cx| {
+                let worktree = project.worktrees().next().unwrap().read(cx);
+                SelectedEntry {
+                    worktree_id: worktree.id(),
+                    entry_id: worktree.entry_for_path(path).unwrap().id,
+                }
+            })
+        };
+        let dropped = DraggedSelection {
+            active_selection: selected_entry("b.txt", cx),
+            marked_selections: Arc::new(BTreeSet::from_iter([
+                selected_entry("a.txt", cx),
+                selected_entry("b.txt", cx),
+                selected_entry("c.txt", cx),
+            ])),
+        };
+
+        // Dropped files get an excerpt each, unless they're already in the multibuffer.
+        editor.update(cx, |editor, cx| assert!(editor.handle_drop(&dropped, cx)));
+        cx.run_until_parked();
+        assert_eq!(editor.update(cx, |editor, cx| editor.text(cx)), "a\nb\nc");
+
+        // Anything else is left to the pane.
+        editor.update(cx, |editor, cx| assert!(!editor.handle_drop(&"a.txt", cx)));
+
+        // Files dropped onto a single file editor are opened as usual.
+        let singleton = cx.new_view(|cx| Editor::for_buffer(a, Some(project.clone()), cx));
+        singleton.update(cx, |editor, cx| assert!(!editor.handle_drop(&dropped, cx)));
+        cx.run_until_parked();
+        assert_eq!(singleton.update(cx, |editor, cx| editor.text(cx)), "a");
+    }
 }
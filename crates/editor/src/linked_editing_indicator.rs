@@ -0,0 +1,120 @@
+use gpui::{div, IntoElement, Render, Subscription, View, ViewContext};
+use ui::{prelude::*, Tooltip};
+use workspace::{item::ItemHandle, StatusItemView};
+
+use crate::Editor;
+
+/// Shows in the status bar while edits in the active editor are mirrored into linked ranges.
+pub struct LinkedEditingIndicator {
+    active: bool,
+    _observe_active_editor: Option<Subscription>,
+}
+
+impl LinkedEditingIndicator {
+    pub fn new() -> Self {
+        Self {
+            active: false,
+            _observe_active_editor: None,
+        }
+    }
+
+    fn update_state(&mut self, editor: View<Editor>, cx: &mut ViewContext<Self>) {
+        let active = editor.read(cx).has_active_linked_edits();
+        if self.active != active {
+            self.active = active;
+            cx.notify();
+        }
+    }
+}
+
+impl Default for LinkedEditingIndicator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Render for LinkedEditingIndicator {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        if !self.active {
+            return div().into_any_element();
+        }
+
+        div()
+            .id("linked-editing-indicator")
+            .child(
+                Label::new("Linked Edit")
+                    .size(LabelSize::Small)
+                    .color(Color::Accent),
+            )
+            .tooltip(|cx| Tooltip::text("Edits are mirrored to the linked ranges", cx))
+            .into_any_element()
+    }
+}
+
+impl StatusItemView for LinkedEditingIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(editor) = active_pane_item.and_then(|item| item.act_as::<Editor>(cx)) {
+            self._observe_active_editor = Some(cx.observe(&editor, Self::update_state));
+            self.update_state(editor, cx);
+        } else {
+            self.active = false;
+            self._observe_active_editor = None;
+        }
+
+        cx.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::{html_lang_with_linked_edits, init_test},
+        test::editor_test_context::EditorTestContext,
+    };
+    use gpui::{TestAppContext, VisualContext};
+
+    #[gpui::test]
+    async fn test_indicator_follows_linked_edits(cx: &mut TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.linked_edits_debounce_ms = Some(0);
+        });
+
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.update_buffer(|buffer, cx| buffer.set_language(Some(html_lang_with_linked_edits()), cx));
+        cx.set_state("<div>ˇ<span></span></div>");
+        cx.run_until_parked();
+
+        let editor = cx.editor.clone();
+        let indicator = cx.new_view(|_| LinkedEditingIndicator::new());
+        indicator.update(&mut cx.cx, |indicator, cx| {
+            indicator.set_active_pane_item(Some(&editor), cx)
+        });
+        let is_active = |cx: &mut EditorTestContext| {
+            indicator.update(&mut cx.cx, |indicator, _| indicator.active)
+        };
+        assert!(!is_active(&mut cx));
+
+        // The indicator turns on when a selection enters linked ranges, and off when it leaves.
+        cx.set_selections_state("<div><spaˇn></span></div>");
+        cx.run_until_parked();
+        assert!(is_active(&mut cx));
+
+        cx.set_selections_state("<div><span>ˇ</span></div>");
+        cx.run_until_parked();
+        assert!(!is_active(&mut cx));
+
+        // It is reset when the active item is not an editor.
+        cx.set_selections_state("<div><spaˇn></span></div>");
+        cx.run_until_parked();
+        assert!(is_active(&mut cx));
+        indicator.update(&mut cx.cx, |indicator, cx| {
+            indicator.set_active_pane_item(None, cx)
+        });
+        assert!(!is_active(&mut cx));
+    }
+}
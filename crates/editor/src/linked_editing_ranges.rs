@@ -0,0 +1,329 @@
+use std::{ops::Range, time::Duration};
+
+use collections::HashMap;
+use gpui::{AppContext, Hsla, Model, ViewContext};
+use itertools::Itertools;
+use language::{Buffer, BufferSnapshot};
+use multi_buffer::Anchor;
+use text::{BufferId, ToOffset as _};
+use theme::ThemeColors;
+
+use crate::{display_map::ToDisplayPoint, DisplayPoint, DisplaySnapshot, Editor};
+
+#[derive(Clone, Default)]
+pub(super) struct LinkedEditingRanges(
+    /// Ranges are non-overlapping and sorted by .0 (thus, [x + 1].start > [x].end must hold)
+    pub HashMap<BufferId, Vec<(Range<text::Anchor>, Vec<Range<text::Anchor>>)>>,
+);
+
+impl LinkedEditingRanges {
+    pub(super) fn get(
+        &self,
+        id: BufferId,
+        anchor: Range<text::Anchor>,
+        snapshot: &text::BufferSnapshot,
+    ) -> Option<&(Range<text::Anchor>, Vec<Range<text::Anchor>>)> {
+        let ranges_for_buffer = self.0.get(&id)?;
+        let lower_bound = ranges_for_buffer
+            .partition_point(|(range, _)| range.start.cmp(&anchor.start, snapshot).is_le());
+        if lower_bound == 0 {
+            // None of the linked ranges contains `anchor`.
+            return None;
+        }
+        ranges_for_buffer
+            .get(lower_bound - 1)
+            .filter(|(range, _)| range.end.cmp(&anchor.end, snapshot).is_ge())
+    }
+
+    pub(super) fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.0.clear();
+    }
+}
+
+/// Queries the `linked_edit` tree-sitter query of every buffer under a selection, remembering the
+/// ranges that contain a selection together with their siblings.
+///
+/// The query is debounced according to the `linked_edits_debounce_ms` language setting, and is
+/// skipped entirely while every selection stays within a previously returned range.
+pub(super) fn refresh_linked_ranges(this: &mut Editor, cx: &mut ViewContext<Editor>) -> Option<()> {
+    if this.pending_rename.is_some() {
+        return None;
+    }
+    let buffer = this.buffer.read(cx);
+    let snapshot = buffer.snapshot(cx);
+    let mut applicable_selections = Vec::new();
+    let mut debounce_ms = 0;
+    for selection in this.selections.all::<usize>(cx) {
+        let start_position = snapshot.anchor_before(selection.start);
+        let end_position = snapshot.anchor_after(selection.end);
+        if start_position.buffer_id != end_position.buffer_id || end_position.buffer_id.is_none() {
+            // Throw away selections spanning multiple buffers.
+            continue;
+        }
+        let Some(buffer) = end_position.buffer_id.and_then(|id| buffer.buffer(id)) else {
+            continue;
+        };
+        let buffer = buffer.read(cx);
+        let settings = language::language_settings::language_settings(
+            buffer.language_at(start_position.text_anchor).as_ref(),
+            buffer.file(),
+            cx,
+        );
+        if !settings.linked_edits {
+            continue;
+        }
+        debounce_ms = debounce_ms.max(settings.linked_edits_debounce_ms);
+        applicable_selections.push((
+            buffer.snapshot(),
+            start_position.text_anchor,
+            end_position.text_anchor,
+        ));
+    }
+    if applicable_selections.is_empty() {
+        this.linked_editing_range_task.take();
+        if !this.linked_edit_ranges.is_empty() {
+            this.linked_edit_ranges.clear();
+            cx.notify();
+        }
+        return None;
+    }
+
+    let cursors_within_known_ranges = applicable_selections.iter().all(|(snapshot, start, end)| {
+        this.linked_edit_ranges
+            .get(snapshot.remote_id(), *start..*end, snapshot)
+            .is_some()
+    });
+    if cursors_within_known_ranges {
+        return None;
+    }
+
+    // Replacing the task drops, and thus cancels, any refresh that is still in flight.
+    this.linked_editing_range_task = Some(cx.spawn(|this, mut cx| async move {
+        if debounce_ms > 0 {
+            cx.background_executor()
+                .timer(Duration::from_millis(debounce_ms))
+                .await;
+        }
+
+        let ranges = cx
+            .background_executor()
+            .spawn(async move {
+                applicable_selections
+                    .into_iter()
+                    .filter_map(|(snapshot, start, end)| {
+                        let siblings = linked_siblings(&snapshot, start..end)?;
+                        Some((snapshot.remote_id(), siblings))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await;
+
+        this.update(&mut cx, |this, cx| {
+            this.linked_edit_ranges.clear();
+            if this.pending_rename.is_some() {
+                return;
+            }
+            for (buffer_id, ranges) in ranges {
+                this.linked_edit_ranges
+                    .0
+                    .entry(buffer_id)
+                    .or_default()
+                    .extend(ranges);
+            }
+            for (buffer_id, values) in this.linked_edit_ranges.0.iter_mut() {
+                let Some(snapshot) = this
+                    .buffer
+                    .read(cx)
+                    .buffer(*buffer_id)
+                    .map(|buffer| buffer.read(cx).snapshot())
+                else {
+                    continue;
+                };
+                values.sort_by(|lhs, rhs| lhs.0.start.cmp(&rhs.0.start, &snapshot));
+                values.dedup_by(|lhs, rhs| lhs.0 == rhs.0);
+            }
+
+            cx.notify();
+        })
+        .ok()
+    }));
+    None
+}
+
+/// Finds the linked ranges group containing `selection` and maps each range of that group to its siblings.
+fn linked_siblings(
+    snapshot: &BufferSnapshot,
+    selection: Range<text::Anchor>,
+) -> Option<Vec<(Range<text::Anchor>, Vec<Range<text::Anchor>>)>> {
+    let selection = selection.start.to_offset(snapshot)..selection.end.to_offset(snapshot);
+    // Tags may be nested, so prefer the innermost group that contains the selection.
+    let group = snapshot
+        .linked_edit_ranges(selection.clone())
+        .filter(|group| {
+            group
+                .iter()
+                .any(|range| range.start <= selection.start && range.end >= selection.end)
+        })
+        .min_by_key(|group| group.iter().map(|range| range.len()).sum::<usize>())?;
+
+    let group = group
+        .into_iter()
+        .map(|range| snapshot.anchor_before(range.start)..snapshot.anchor_after(range.end))
+        .collect::<Vec<_>>();
+    let mut siblings = group
+        .iter()
+        .map(|range| {
+            let others = group
+                .iter()
+                .filter(|other| *other != range)
+                .cloned()
+                .collect::<Vec<_>>();
+            (range.clone(), others)
+        })
+        .collect::<Vec<_>>();
+    siblings.sort_by(|lhs, rhs| lhs.0.start.cmp(&rhs.0.start, snapshot));
+    Some(siblings)
+}
+
+impl Editor {
+    /// Returns the edits that should be mirrored into linked ranges when `selection` gets replaced.
+    pub(crate) fn linked_editing_ranges_for(
+        &self,
+        selection: Range<text::Anchor>,
+        cx: &AppContext,
+    ) -> Option<HashMap<Model<Buffer>, Vec<Range<text::Anchor>>>> {
+        if self.linked_edit_ranges.is_empty() {
+            return None;
+        }
+        let ((base_range, linked_ranges), buffer_snapshot, buffer) =
+            selection.end.buffer_id.and_then(|end_buffer_id| {
+                if selection.start.buffer_id != Some(end_buffer_id) {
+                    return None;
+                }
+                let buffer = self.buffer.read(cx).buffer(end_buffer_id)?;
+                let snapshot = buffer.read(cx).snapshot();
+                self.linked_edit_ranges
+                    .get(end_buffer_id, selection.start..selection.end, &snapshot)
+                    .map(|ranges| (ranges, snapshot, buffer))
+            })?;
+
+        // Find the offset from the start of the current range to the current cursor position.
+        let start_byte_offset = base_range.start.to_offset(&buffer_snapshot);
+        let start_difference = selection.start.to_offset(&buffer_snapshot) - start_byte_offset;
+        let end_difference = selection.end.to_offset(&buffer_snapshot) - start_byte_offset;
+
+        let mut linked_edits = HashMap::<_, Vec<_>>::default();
+        for range in linked_ranges.iter() {
+            let range_start = range.start.to_offset(&buffer_snapshot);
+            let end_offset = range_start + end_difference;
+            let start_offset = range_start + start_difference;
+            if start_offset > buffer_snapshot.len() || end_offset > buffer_snapshot.len() {
+                continue;
+            }
+            // Skip linked ranges that are already being edited by another selection.
+            if self.selections.disjoint_anchor_ranges().iter().any(|s| {
+                if s.start.buffer_id != selection.start.buffer_id
+                    || s.end.buffer_id != selection.end.buffer_id
+                {
+                    return false;
+                }
+                s.start.text_anchor.to_offset(&buffer_snapshot) <= end_offset
+                    && s.end.text_anchor.to_offset(&buffer_snapshot) >= start_offset
+            }) {
+                continue;
+            }
+            let start = buffer_snapshot.anchor_after(start_offset);
+            let end = buffer_snapshot.anchor_after(end_offset);
+            linked_edits
+                .entry(buffer.clone())
+                .or_insert_with(Vec::new)
+                .push(start..end);
+        }
+        Some(linked_edits)
+    }
+
+    /// Whether typing at any of the current selections gets mirrored into linked ranges.
+    pub fn has_active_linked_edits(&self) -> bool {
+        !self.linked_edit_ranges.is_empty()
+    }
+
+    /// Returns the display ranges of all linked ranges intersecting `search_range`, so that
+    /// the user can see where their edits are going to be mirrored to.
+    pub(crate) fn linked_edit_highlights_in_range(
+        &self,
+        search_range: Range<Anchor>,
+        display_snapshot: &DisplaySnapshot,
+        theme: &ThemeColors,
+    ) -> Vec<(Range<DisplayPoint>, Hsla)> {
+        let mut results = Vec::new();
+        if self.linked_edit_ranges.is_empty() {
+            return results;
+        }
+
+        let color = theme.editor_linked_edit_background;
+        let buffer_snapshot = &display_snapshot.buffer_snapshot;
+        for (excerpt_id, excerpt_buffer, excerpt_range) in buffer_snapshot.excerpts() {
+            let Some(ranges) = self.linked_edit_ranges.0.get(&excerpt_buffer.remote_id()) else {
+                continue;
+            };
+            for (range, _) in ranges {
+                if range
+                    .start
+                    .cmp(&excerpt_range.context.start, excerpt_buffer)
+                    .is_lt()
+                    || range
+                        .end
+                        .cmp(&excerpt_range.context.end, excerpt_buffer)
+                        .is_gt()
+                {
+                    continue;
+                }
+                let Some((start, end)) = buffer_snapshot
+                    .anchor_in_excerpt(excerpt_id, range.start)
+                    .zip(buffer_snapshot.anchor_in_excerpt(excerpt_id, range.end))
+                else {
+                    continue;
+                };
+                if end.cmp(&search_range.start, buffer_snapshot).is_lt()
+                    || start.cmp(&search_range.end, buffer_snapshot).is_gt()
+                {
+                    continue;
+                }
+                results.push((
+                    start.to_display_point(display_snapshot)
+                        ..end.to_display_point(display_snapshot),
+                    color,
+                ));
+            }
+        }
+        results
+    }
+
+    /// Applies the collected linked edits, one transaction per linked buffer.
+    pub(crate) fn apply_linked_edits(
+        &mut self,
+        linked_edits: HashMap<Model<Buffer>, Vec<(Range<text::Anchor>, std::sync::Arc<str>)>>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        for (buffer, edits) in linked_edits {
+            buffer.update(cx, |buffer, cx| {
+                let snapshot = buffer.snapshot();
+                let edits = edits
+                    .into_iter()
+                    .map(|(range, text)| {
+                        (
+                            range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot),
+                            text,
+                        )
+                    })
+                    .sorted_by_key(|(range, _)| range.start)
+                    .collect::<Vec<_>>();
+                buffer.edit(edits, None, cx);
+            })
+        }
+    }
+}
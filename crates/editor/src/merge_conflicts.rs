@@ -0,0 +1,367 @@
+use std::{mem, ops::Range, time::Duration};
+
+use collections::HashSet;
+use git::conflict::parse_conflicts;
+use gpui::{Hsla, SharedString, Task, ViewContext};
+use multi_buffer::ToOffset as _;
+use ui::{h_flex, prelude::*};
+
+use crate::{
+    display_map::{BlockContext, BlockDisposition, BlockId, BlockProperties, BlockStyle},
+    scroll::Autoscroll,
+    AcceptConflictBoth, AcceptConflictOurs, AcceptConflictTheirs, Anchor, Editor, EditorMode,
+    GoToConflict, GoToPrevConflict,
+};
+
+/// How long to wait after an edit before looking for conflicts again.
+pub(crate) const PARSE_DEBOUNCE: Duration = Duration::from_millis(100);
+
+/// The merge conflicts in the buffers of an editor, highlighted and resolved with the buttons
+/// of a block above each of them.
+#[derive(Default)]
+pub(crate) struct MergeConflictState {
+    /// The conflicts, sorted by their start.
+    conflicts: Vec<MergeConflict>,
+    blocks: HashSet<BlockId>,
+    parse_task: Option<Task<()>>,
+}
+
+struct MergeConflict {
+    /// The whole conflict in the multibuffer, including its markers.
+    range: Range<Anchor>,
+    ours: Range<Anchor>,
+    theirs: Range<Anchor>,
+    /// The start of the line separating the two sides.
+    separator_start: Anchor,
+    ours_label: SharedString,
+    theirs_label: SharedString,
+}
+
+#[derive(Clone, Copy)]
+enum Resolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+struct OursConflictHighlight;
+struct TheirsConflictHighlight;
+
+/// Looks for the conflict markers left by git in the buffers of the editor.
+pub(super) fn refresh_merge_conflicts(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+
+    let buffers = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .map(|buffer| {
+            let snapshot = buffer.read(cx).snapshot();
+            (buffer, snapshot)
+        })
+        .collect::<Vec<_>>();
+    editor.merge_conflicts.parse_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(PARSE_DEBOUNCE).await;
+        let conflicts_by_buffer = cx
+            .background_executor()
+            .spawn(async move {
+                buffers
+                    .into_iter()
+                    .filter_map(|(buffer, snapshot)| {
+                        let conflicts = parse_conflicts(&snapshot.text());
+                        (!conflicts.is_empty()).then_some((buffer, snapshot, conflicts))
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                let multibuffer = editor.buffer.read(cx);
+                let snapshot = multibuffer.snapshot(cx);
+                let mut conflicts = Vec::new();
+                for (buffer, buffer_snapshot, buffer_conflicts) in conflicts_by_buffer {
+                    let excerpts = multibuffer.excerpts_for_buffer(&buffer, cx);
+                    for conflict in buffer_conflicts {
+                        let start = buffer_snapshot.anchor_before(conflict.range.start);
+                        let end = buffer_snapshot.anchor_after(conflict.range.end);
+                        // Conflicts that aren't shown whole can't be resolved.
+                        let Some((excerpt_id, _)) = excerpts.iter().find(|(_, range)| {
+                            range.context.start.cmp(&start, &buffer_snapshot).is_le()
+                                && range.context.end.cmp(&end, &buffer_snapshot).is_ge()
+                        }) else {
+                            continue;
+                        };
+                        let anchor = |offset: usize| {
+                            let anchor = buffer_snapshot.anchor_before(offset);
+                            snapshot.anchor_in_excerpt(*excerpt_id, anchor)
+                        };
+                        let separator_start = conflict
+                            .base
+                            .as_ref()
+                            .map_or(conflict.ours.end, |base| base.end);
+                        let (
+                            Some(range_start),
+                            Some(range_end),
+                            Some(ours_start),
+                            Some(ours_end),
+                            Some(theirs_start),
+                            Some(theirs_end),
+                            Some(separator_start),
+                        ) = (
+                            anchor(conflict.range.start),
+                            anchor(conflict.range.end),
+                            anchor(conflict.ours.start),
+                            anchor(conflict.ours.end),
+                            anchor(conflict.theirs.start),
+                            anchor(conflict.theirs.end),
+                            anchor(separator_start),
+                        )
+                        else {
+                            continue;
+                        };
+                        conflicts.push(MergeConflict {
+                            range: range_start..range_end,
+                            ours: ours_start..ours_end,
+                            theirs: theirs_start..theirs_end,
+                            separator_start,
+                            ours_label: conflict.ours_label.into(),
+                            theirs_label: conflict.theirs_label.into(),
+                        });
+                    }
+                }
+                conflicts.sort_by(|a, b| a.range.start.cmp(&b.range.start, &snapshot));
+
+                editor.merge_conflicts.conflicts = conflicts;
+                update_merge_conflict_blocks(editor, cx);
+            })
+            .ok();
+    }));
+}
+
+fn conflict_color(mut color: Hsla) -> Hsla {
+    color.fade_out(0.8);
+    color
+}
+
+fn update_merge_conflict_blocks(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let old_blocks = mem::take(&mut editor.merge_conflicts.blocks);
+    if !old_blocks.is_empty() {
+        editor.remove_blocks(old_blocks, None, cx);
+    }
+    editor.clear_row_highlights::<OursConflictHighlight>();
+    editor.clear_row_highlights::<TheirsConflictHighlight>();
+    if editor.merge_conflicts.conflicts.is_empty() {
+        cx.notify();
+        return;
+    }
+
+    // Each side is highlighted along with the marker introducing it, the common ancestor's text
+    // being highlighted with ours.
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let last_line = |end: &Anchor| {
+        let offset = end.to_offset(&snapshot).saturating_sub(1);
+        snapshot.anchor_before(offset)
+    };
+    let ours_color = conflict_color(cx.theme().status().created);
+    let theirs_color = conflict_color(cx.theme().status().info);
+    let highlights = editor
+        .merge_conflicts
+        .conflicts
+        .iter()
+        .map(|conflict| {
+            (
+                conflict.range.start..=last_line(&conflict.separator_start),
+                conflict.separator_start..=last_line(&conflict.range.end),
+            )
+        })
+        .collect::<Vec<_>>();
+    for (ours_rows, theirs_rows) in highlights {
+        editor.highlight_rows::<OursConflictHighlight>(ours_rows, Some(ours_color), false, cx);
+        editor.highlight_rows::<TheirsConflictHighlight>(
+            theirs_rows,
+            Some(theirs_color),
+            false,
+            cx,
+        );
+    }
+
+    let count = editor.merge_conflicts.conflicts.len();
+    let editor_handle = cx.view().downgrade();
+    let blocks = editor
+        .merge_conflicts
+        .conflicts
+        .iter()
+        .enumerate()
+        .map(|(ix, conflict)| {
+            let editor_handle = editor_handle.clone();
+            let ours_label = conflict.ours_label.clone();
+            let theirs_label = conflict.theirs_label.clone();
+            BlockProperties {
+                position: conflict.range.start,
+                height: 1,
+                style: BlockStyle::Flex,
+                disposition: BlockDisposition::Above,
+                render: Box::new(move |cx: &mut BlockContext| {
+                    let button = |id: &'static str, label: String, resolution: Resolution| {
+                        let editor_handle = editor_handle.clone();
+                        Button::new((id, ix), label)
+                            .label_size(LabelSize::XSmall)
+                            .color(Color::Muted)
+                            .style(ButtonStyle::Transparent)
+                            .on_click(move |_, cx| {
+                                editor_handle
+                                    .update(cx, |editor, cx| {
+                                        editor.resolve_merge_conflict(ix, resolution, cx)
+                                    })
+                                    .ok();
+                            })
+                    };
+                    let separator = || {
+                        Label::new("|")
+                            .size(LabelSize::XSmall)
+                            .color(Color::Disabled)
+                    };
+                    h_flex()
+                        .pl(cx.anchor_x)
+                        .gap_1()
+                        .child(
+                            Label::new(format!("Conflict {} of {count}", ix + 1))
+                                .size(LabelSize::XSmall)
+                                .color(Color::Conflict),
+                        )
+                        .child(button(
+                            "accept-ours",
+                            side_label("Accept Ours", &ours_label),
+                            Resolution::Ours,
+                        ))
+                        .child(separator())
+                        .child(button(
+                            "accept-theirs",
+                            side_label("Accept Theirs", &theirs_label),
+                            Resolution::Theirs,
+                        ))
+                        .child(separator())
+                        .child(button(
+                            "accept-both",
+                            "Accept Both".into(),
+                            Resolution::Both,
+                        ))
+                        .into_any_element()
+                }),
+            }
+        })
+        .collect::<Vec<_>>();
+    editor.merge_conflicts.blocks = editor.insert_blocks(blocks, None, cx).into_iter().collect();
+    cx.notify();
+}
+
+fn side_label(action: &str, label: &str) -> String {
+    if label.is_empty() {
+        action.to_string()
+    } else {
+        format!("{action} ({label})")
+    }
+}
+
+impl Editor {
+    pub(crate) fn accept_conflict_ours(
+        &mut self,
+        _: &AcceptConflictOurs,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.resolve_merge_conflict_at_cursor(Resolution::Ours, cx);
+    }
+
+    pub(crate) fn accept_conflict_theirs(
+        &mut self,
+        _: &AcceptConflictTheirs,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.resolve_merge_conflict_at_cursor(Resolution::Theirs, cx);
+    }
+
+    pub(crate) fn accept_conflict_both(
+        &mut self,
+        _: &AcceptConflictBoth,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.resolve_merge_conflict_at_cursor(Resolution::Both, cx);
+    }
+
+    /// Moves to the start of the next conflict, wrapping around to the first one.
+    pub(crate) fn go_to_conflict(&mut self, _: &GoToConflict, cx: &mut ViewContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let conflicts = &self.merge_conflicts.conflicts;
+        let target = conflicts
+            .iter()
+            .find(|conflict| conflict.range.start.cmp(&head, &snapshot).is_gt())
+            .or_else(|| conflicts.first())
+            .map(|conflict| conflict.range.start);
+        if let Some(target) = target {
+            self.change_selections(Some(Autoscroll::center()), cx, |s| {
+                s.select_anchor_ranges([target..target])
+            });
+        }
+    }
+
+    /// Moves to the start of the previous conflict, wrapping around to the last one.
+    pub(crate) fn go_to_prev_conflict(&mut self, _: &GoToPrevConflict, cx: &mut ViewContext<Self>) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let conflicts = &self.merge_conflicts.conflicts;
+        let target = conflicts
+            .iter()
+            .rev()
+            .find(|conflict| conflict.range.start.cmp(&head, &snapshot).is_lt())
+            .or_else(|| conflicts.last())
+            .map(|conflict| conflict.range.start);
+        if let Some(target) = target {
+            self.change_selections(Some(Autoscroll::center()), cx, |s| {
+                s.select_anchor_ranges([target..target])
+            });
+        }
+    }
+
+    fn resolve_merge_conflict_at_cursor(
+        &mut self,
+        resolution: Resolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let head = self.selections.newest_anchor().head();
+        let ix = self.merge_conflicts.conflicts.iter().position(|conflict| {
+            conflict.range.start.cmp(&head, &snapshot).is_le()
+                && conflict.range.end.cmp(&head, &snapshot).is_gt()
+        });
+        if let Some(ix) = ix {
+            self.resolve_merge_conflict(ix, resolution, cx);
+        }
+    }
+
+    /// Replaces the conflict with the given side of it, or both one after the other.
+    fn resolve_merge_conflict(
+        &mut self,
+        ix: usize,
+        resolution: Resolution,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if ix >= self.merge_conflicts.conflicts.len() {
+            return;
+        }
+        let conflict = self.merge_conflicts.conflicts.remove(ix);
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let ours = snapshot.text_for_range(conflict.ours).collect::<String>();
+        let theirs = snapshot.text_for_range(conflict.theirs).collect::<String>();
+        let text = match resolution {
+            Resolution::Ours => ours,
+            Resolution::Theirs => theirs,
+            Resolution::Both => ours + &theirs,
+        };
+        self.transact(cx, |editor, cx| editor.edit([(conflict.range, text)], cx));
+        update_merge_conflict_blocks(self, cx);
+    }
+}
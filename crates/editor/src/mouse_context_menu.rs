@@ -1,7 +1,7 @@
 use crate::{
     Copy, Cut, DisplayPoint, Editor, EditorMode, FindAllReferences, GoToDefinition,
     GoToImplementation, GoToTypeDefinition, Paste, Rename, RevealInFinder, SelectMode,
-    ToggleCodeActions,
+    ShowCallHierarchy, ShowTypeHierarchy, ToggleCodeActions,
 };
 use gpui::{DismissEvent, Pixels, Point, Subscription, View, ViewContext};
 use workspace::OpenInTerminal;
@@ -79,6 +79,8 @@ pub fn deploy_context_menu(
                 .action("Go to Type Definition", Box::new(GoToTypeDefinition))
                 .action("Go to Implementation", Box::new(GoToImplementation))
                 .action("Find All References", Box::new(FindAllReferences))
+                .action("Show Call Hierarchy", Box::new(ShowCallHierarchy))
+                .action("Show Type Hierarchy", Box::new(ShowTypeHierarchy))
                 .action(
                     "Code Actions",
                     Box::new(ToggleCodeActions {
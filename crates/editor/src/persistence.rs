@@ -1,5 +1,6 @@
 use std::path::PathBuf;
 
+use anyhow::Context as _;
 use db::sqlez_macros::sql;
 use db::{define_connection, query};
 
@@ -15,6 +16,12 @@ define_connection!(
     //   scroll_vertical_offset: f32,
     //   scroll_horizontal_offset: f32,
     // )
+    // editor_folds(
+    //   item_id: usize,
+    //   workspace_id: usize,
+    //   start_offset: usize,
+    //   end_offset: usize,
+    // )
     pub static ref DB: EditorDb<WorkspaceDb> =
         &[sql! (
             CREATE TABLE editors(
@@ -31,6 +38,18 @@ define_connection!(
             ALTER TABLE editors ADD COLUMN scroll_top_row INTEGER NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_horizontal_offset REAL NOT NULL DEFAULT 0;
             ALTER TABLE editors ADD COLUMN scroll_vertical_offset REAL NOT NULL DEFAULT 0;
+        ),
+        sql! (
+            CREATE TABLE editor_folds(
+                item_id INTEGER NOT NULL,
+                workspace_id INTEGER NOT NULL,
+                start_offset INTEGER NOT NULL,
+                end_offset INTEGER NOT NULL,
+                PRIMARY KEY(item_id, workspace_id, start_offset),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
         )];
 );
 
@@ -80,4 +99,39 @@ impl EditorDb {
             WHERE item_id = ?1 AND workspace_id = ?2
         }
     }
+
+    query! {
+        pub fn get_folds(item_id: ItemId, workspace_id: WorkspaceId) -> Result<Vec<(usize, usize)>> {
+            SELECT start_offset, end_offset
+            FROM editor_folds
+            WHERE item_id = ? AND workspace_id = ?
+            ORDER BY start_offset
+        }
+    }
+
+    /// Replaces the folds stored for the given editor.
+    pub async fn save_folds(
+        &self,
+        item_id: ItemId,
+        workspace_id: WorkspaceId,
+        folds: Vec<(usize, usize)>,
+    ) -> anyhow::Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_folds", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM editor_folds WHERE item_id = ? AND workspace_id = ?
+                ))?((item_id, workspace_id))
+                .context("clearing old folds")?;
+                for (start, end) in folds {
+                    conn.exec_bound(sql!(
+                        INSERT OR REPLACE INTO editor_folds(item_id, workspace_id, start_offset, end_offset)
+                        VALUES (?, ?, ?, ?)
+                    ))?((item_id, workspace_id, start, end))
+                    .context("inserting fold")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
 }
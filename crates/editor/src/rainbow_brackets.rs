@@ -0,0 +1,160 @@
+use std::any::TypeId;
+
+use gpui::{HighlightStyle, ViewContext};
+use language::language_settings::language_settings;
+use theme::ActiveTheme;
+
+use crate::{Editor, EditorMode, RangeToAnchorExt};
+
+/// The number of colors brackets cycle through, at most. Text highlights are keyed by type, so
+/// every color is highlighted with its own key.
+const MAX_RAINBOW_BRACKET_COLORS: usize = 8;
+
+enum RainbowBracketHighlight<const COLOR: usize> {}
+
+fn highlight_keys() -> [TypeId; MAX_RAINBOW_BRACKET_COLORS] {
+    [
+        TypeId::of::<RainbowBracketHighlight<0>>(),
+        TypeId::of::<RainbowBracketHighlight<1>>(),
+        TypeId::of::<RainbowBracketHighlight<2>>(),
+        TypeId::of::<RainbowBracketHighlight<3>>(),
+        TypeId::of::<RainbowBracketHighlight<4>>(),
+        TypeId::of::<RainbowBracketHighlight<5>>(),
+        TypeId::of::<RainbowBracketHighlight<6>>(),
+        TypeId::of::<RainbowBracketHighlight<7>>(),
+    ]
+}
+
+/// Colors the visible brackets according to their nesting depth, cycling through the accent
+/// colors of the theme, in the buffers whose language has `rainbow_brackets` enabled.
+pub(super) fn refresh_rainbow_brackets(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+
+    let display_snapshot = editor
+        .display_map
+        .update(cx, |display_map, cx| display_map.snapshot(cx));
+    let visible_range = editor.visible_buffer_range(&display_snapshot);
+
+    let accents = cx.theme().accents().clone();
+    let color_count = accents.0.len().min(MAX_RAINBOW_BRACKET_COLORS);
+    let mut ranges_by_color = vec![Vec::new(); MAX_RAINBOW_BRACKET_COLORS];
+    if color_count > 0 {
+        let buffer_snapshot = &display_snapshot.buffer_snapshot;
+        let brackets = buffer_snapshot.bracket_depths(visible_range, |buffer| {
+            language_settings(buffer.language(), buffer.file(), cx).rainbow_brackets
+        });
+        for (range, depth) in brackets {
+            ranges_by_color[depth % color_count].push(range.to_anchors(buffer_snapshot));
+        }
+    }
+
+    let changed = editor.display_map.update(cx, |display_map, _| {
+        let mut changed = false;
+        for (color_ix, (key, ranges)) in highlight_keys()
+            .into_iter()
+            .zip(ranges_by_color)
+            .enumerate()
+        {
+            if ranges.is_empty() {
+                changed |= display_map.clear_highlights(key);
+            } else {
+                let style = HighlightStyle {
+                    color: Some(accents.color_for_index(color_ix as u32)),
+                    ..Default::default()
+                };
+                display_map.highlight_text(key, ranges, style);
+                changed = true;
+            }
+        }
+        changed
+    });
+    if changed {
+        cx.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{editor_tests::init_test, test::editor_test_context::EditorTestContext};
+    use gpui::UpdateGlobal;
+    use indoc::indoc;
+    use language::{language_settings::AllLanguageSettings, Language, LanguageConfig};
+    use settings::SettingsStore;
+    use std::sync::Arc;
+
+    #[gpui::test]
+    async fn test_rainbow_brackets(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |settings| {
+            settings.defaults.rainbow_brackets = Some(true);
+        });
+
+        let language = Language::new(
+            LanguageConfig::default(),
+            Some(tree_sitter_rust::language()),
+        )
+        .with_brackets_query(indoc! {r#"
+                ("(" @open ")" @close)
+                ("{" @open "}" @close)
+                ("\"" @open "\"" @close)
+            "#})
+        .unwrap();
+        let mut cx = EditorTestContext::new(cx).await;
+        cx.update_buffer(|buffer, cx| buffer.set_language(Some(Arc::new(language)), cx));
+        cx.set_state(indoc! {r#"
+            fn main() {
+                let s = "x";
+                if (a) { f(b); }
+            }ˇ
+        "#});
+        cx.run_until_parked();
+        cx.update_editor(|editor, cx| {
+            editor.set_visible_line_count(10., cx);
+            refresh_rainbow_brackets(editor, cx);
+        });
+
+        let keys = highlight_keys();
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let highlighted_text = |color_ix: usize| {
+                editor
+                    .display_map
+                    .read(cx)
+                    .text_highlights(keys[color_ix])
+                    .map(|(_, ranges)| {
+                        ranges
+                            .iter()
+                            .map(|range| {
+                                snapshot
+                                    .text_for_range(range.start..range.end)
+                                    .collect::<String>()
+                            })
+                            .collect::<String>()
+                    })
+                    .unwrap_or_default()
+            };
+            assert_eq!(highlighted_text(0), "(){}");
+            assert_eq!(highlighted_text(1), "(){}");
+            assert_eq!(highlighted_text(2), "()");
+            assert_eq!(highlighted_text(3), "");
+        });
+
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |store, cx| {
+                store.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                    settings.defaults.rainbow_brackets = Some(false)
+                });
+            });
+        });
+        cx.update_editor(|editor, cx| {
+            refresh_rainbow_brackets(editor, cx);
+            assert!(editor
+                .display_map
+                .read(cx)
+                .text_highlights(keys[0])
+                .is_none());
+        });
+    }
+}
@@ -0,0 +1,464 @@
+use std::{ffi::OsStr, ops::Range};
+
+use git::rebase_todo::{parse_rebase_todo, RebaseCommand, REBASE_TODO_FILE_NAME};
+use gpui::{HighlightStyle, Hsla, SharedString, ViewContext};
+use language::Point;
+use multi_buffer::{MultiBufferRow, ToOffset as _, ToPoint as _};
+use ui::{prelude::*, ButtonSize, IconButton, Tooltip};
+
+use crate::{
+    display_map::{
+        BlockContext, BlockDisposition, BlockId, BlockProperties, BlockStyle, ToDisplayPoint,
+    },
+    Anchor, DisplayRow, Editor, EditorMode, EditorSnapshot, MoveDown, RebaseDrop, RebaseEdit,
+    RebaseFixup, RebasePick, RebaseReword, RebaseSquash,
+};
+
+/// The commands that the gutter indicators cycle through when clicked.
+const COMMIT_COMMANDS: [RebaseCommand; 6] = [
+    RebaseCommand::Pick,
+    RebaseCommand::Reword,
+    RebaseCommand::Edit,
+    RebaseCommand::Squash,
+    RebaseCommand::Fixup,
+    RebaseCommand::Drop,
+];
+
+/// The todo list of an interactive rebase, when the editor shows one. Its commands are
+/// highlighted, explained in the gutter and summarized in a block above the list.
+#[derive(Default)]
+pub(crate) struct RebaseTodoState {
+    active: bool,
+    entries: Vec<RebaseTodoEntry>,
+    summary: Option<SharedString>,
+    summary_block: Option<BlockId>,
+}
+
+struct RebaseTodoEntry {
+    command: RebaseCommand,
+    command_range: Range<Anchor>,
+}
+
+struct PickCommandHighlight;
+struct ChangeCommandHighlight;
+struct MeldCommandHighlight;
+struct DropCommandHighlight;
+struct OtherCommandHighlight;
+struct RebaseCommitHighlight;
+struct RebaseCommentHighlight;
+
+impl RebaseTodoState {
+    pub(crate) fn is_active(&self) -> bool {
+        self.active
+    }
+}
+
+/// Parses the todo list again, if the editor shows the `git-rebase-todo` file of a rebase.
+pub(super) fn refresh_rebase_todo(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let buffer = editor.buffer.read(cx).as_singleton();
+    let active = editor.mode == EditorMode::Full
+        && buffer.as_ref().map_or(false, |buffer| {
+            buffer.read(cx).file().map_or(false, |file| {
+                file.path().file_name() == Some(OsStr::new(REBASE_TODO_FILE_NAME))
+            })
+        });
+    if !active {
+        if editor.rebase_todo.active {
+            editor.rebase_todo.active = false;
+            editor.rebase_todo.entries.clear();
+            update_rebase_todo_highlights(editor, Vec::new(), Vec::new(), cx);
+            update_rebase_todo_summary(editor, cx);
+        }
+        return;
+    }
+
+    let snapshot = editor.buffer.read(cx).snapshot(cx);
+    let todo = parse_rebase_todo(&snapshot.text());
+    let anchor_range =
+        |range: Range<usize>| snapshot.anchor_after(range.start)..snapshot.anchor_before(range.end);
+    editor.rebase_todo.active = true;
+    editor.rebase_todo.entries = todo
+        .lines
+        .iter()
+        .map(|line| RebaseTodoEntry {
+            command: line.command,
+            command_range: anchor_range(line.command_range.clone()),
+        })
+        .collect();
+    let commits = todo
+        .lines
+        .into_iter()
+        .filter_map(|line| line.commit_range)
+        .map(anchor_range)
+        .collect();
+    let comments = todo.comments.into_iter().map(anchor_range).collect();
+    update_rebase_todo_highlights(editor, commits, comments, cx);
+    update_rebase_todo_summary(editor, cx);
+}
+
+fn command_color(command: RebaseCommand) -> Color {
+    match command {
+        RebaseCommand::Pick => Color::Created,
+        RebaseCommand::Reword | RebaseCommand::Edit => Color::Modified,
+        RebaseCommand::Squash | RebaseCommand::Fixup => Color::Info,
+        RebaseCommand::Drop => Color::Deleted,
+        _ => Color::Accent,
+    }
+}
+
+fn command_icon(command: RebaseCommand) -> IconName {
+    match command {
+        RebaseCommand::Pick => IconName::Check,
+        RebaseCommand::Reword => IconName::Pencil,
+        RebaseCommand::Edit | RebaseCommand::Break => IconName::DebugPause,
+        RebaseCommand::Squash => IconName::ArrowUp,
+        RebaseCommand::Fixup => IconName::ArrowUpFromLine,
+        RebaseCommand::Drop => IconName::Trash,
+        RebaseCommand::Exec => IconName::Terminal,
+        RebaseCommand::Label
+        | RebaseCommand::Reset
+        | RebaseCommand::Merge
+        | RebaseCommand::UpdateRef => IconName::GitBranch,
+    }
+}
+
+/// Describes the command the way git's own help in the todo list does.
+fn command_description(command: RebaseCommand) -> &'static str {
+    match command {
+        RebaseCommand::Pick => "Use the commit",
+        RebaseCommand::Reword => "Use the commit, but edit its message",
+        RebaseCommand::Edit => "Use the commit, but stop for amending",
+        RebaseCommand::Squash => "Meld into the previous commit",
+        RebaseCommand::Fixup => "Meld into the previous commit, discarding its message",
+        RebaseCommand::Exec => "Run the command",
+        RebaseCommand::Break => "Stop here, until `git rebase --continue`",
+        RebaseCommand::Drop => "Remove the commit",
+        RebaseCommand::Label => "Label the current HEAD",
+        RebaseCommand::Reset => "Reset HEAD to a label",
+        RebaseCommand::Merge => "Create a merge commit",
+        RebaseCommand::UpdateRef => "Point the branch at the current HEAD",
+    }
+}
+
+fn set_text_highlights<T: 'static>(
+    editor: &mut Editor,
+    ranges: Vec<Range<Anchor>>,
+    color: Hsla,
+    cx: &mut ViewContext<Editor>,
+) {
+    if ranges.is_empty() {
+        editor.clear_highlights::<T>(cx);
+    } else {
+        let style = HighlightStyle {
+            color: Some(color),
+            ..HighlightStyle::default()
+        };
+        editor.highlight_text::<T>(ranges, style, cx);
+    }
+}
+
+fn update_rebase_todo_highlights(
+    editor: &mut Editor,
+    commits: Vec<Range<Anchor>>,
+    comments: Vec<Range<Anchor>>,
+    cx: &mut ViewContext<Editor>,
+) {
+    let mut picks = Vec::new();
+    let mut changes = Vec::new();
+    let mut melds = Vec::new();
+    let mut drops = Vec::new();
+    let mut others = Vec::new();
+    for entry in &editor.rebase_todo.entries {
+        let ranges = match command_color(entry.command) {
+            Color::Created => &mut picks,
+            Color::Modified => &mut changes,
+            Color::Info => &mut melds,
+            Color::Deleted => &mut drops,
+            _ => &mut others,
+        };
+        ranges.push(entry.command_range.clone());
+    }
+
+    let hsla = |color: Color| color.color(cx);
+    let (pick, change, meld, drop, other) = (
+        hsla(Color::Created),
+        hsla(Color::Modified),
+        hsla(Color::Info),
+        hsla(Color::Deleted),
+        hsla(Color::Accent),
+    );
+    let commit = cx.theme().syntax().color("constant");
+    let comment = cx.theme().syntax().color("comment");
+    set_text_highlights::<PickCommandHighlight>(editor, picks, pick, cx);
+    set_text_highlights::<ChangeCommandHighlight>(editor, changes, change, cx);
+    set_text_highlights::<MeldCommandHighlight>(editor, melds, meld, cx);
+    set_text_highlights::<DropCommandHighlight>(editor, drops, drop, cx);
+    set_text_highlights::<OtherCommandHighlight>(editor, others, other, cx);
+    set_text_highlights::<RebaseCommitHighlight>(editor, commits, commit, cx);
+    set_text_highlights::<RebaseCommentHighlight>(editor, comments, comment, cx);
+}
+
+/// Summarizes what the rebase does to the commits, e.g. "Rebasing 4 commits into 2: 2 picked,
+/// 1 squashed, 1 dropped".
+fn summarize(entries: &[RebaseTodoEntry]) -> Option<SharedString> {
+    let count = |commands: &[RebaseCommand]| {
+        entries
+            .iter()
+            .filter(|entry| commands.contains(&entry.command))
+            .count()
+    };
+    let commits = count(&COMMIT_COMMANDS);
+    if commits == 0 {
+        return None;
+    }
+    let results = count(&[
+        RebaseCommand::Pick,
+        RebaseCommand::Reword,
+        RebaseCommand::Edit,
+    ]);
+
+    let parts = [
+        (RebaseCommand::Pick, "picked"),
+        (RebaseCommand::Reword, "reworded"),
+        (RebaseCommand::Edit, "edited"),
+        (RebaseCommand::Squash, "squashed"),
+        (RebaseCommand::Fixup, "fixed up"),
+        (RebaseCommand::Drop, "dropped"),
+    ]
+    .into_iter()
+    .filter_map(|(command, verb)| {
+        let count = count(&[command]);
+        (count > 0).then(|| format!("{count} {verb}"))
+    })
+    .collect::<Vec<_>>()
+    .join(", ");
+    let noun = |count: usize| if count == 1 { "commit" } else { "commits" };
+    Some(
+        format!(
+            "Rebasing {commits} {} into {results} {}: {parts}",
+            noun(commits),
+            noun(results)
+        )
+        .into(),
+    )
+}
+
+fn update_rebase_todo_summary(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let summary = summarize(&editor.rebase_todo.entries);
+    if summary == editor.rebase_todo.summary {
+        return;
+    }
+    if let Some(block) = editor.rebase_todo.summary_block.take() {
+        editor.remove_blocks([block].into_iter().collect(), None, cx);
+    }
+    editor.rebase_todo.summary = summary.clone();
+    let Some(summary) = summary else {
+        return;
+    };
+
+    let position = editor.buffer.read(cx).snapshot(cx).anchor_before(0);
+    let block = BlockProperties {
+        position,
+        height: 1,
+        style: BlockStyle::Flex,
+        disposition: BlockDisposition::Above,
+        render: Box::new(move |cx: &mut BlockContext| {
+            h_flex()
+                .pl(cx.anchor_x)
+                .child(
+                    Label::new(summary.clone())
+                        .size(LabelSize::XSmall)
+                        .color(Color::Muted),
+                )
+                .into_any_element()
+        }),
+    };
+    editor.rebase_todo.summary_block = editor.insert_blocks([block], None, cx).into_iter().next();
+}
+
+/// Returns the commands of the todo list on the given rows, to be shown in the gutter.
+pub(super) fn rebase_todo_display_rows(
+    editor: &Editor,
+    rows: Range<DisplayRow>,
+    snapshot: &EditorSnapshot,
+) -> Vec<(DisplayRow, usize, RebaseCommand)> {
+    editor
+        .rebase_todo
+        .entries
+        .iter()
+        .enumerate()
+        .filter_map(|(ix, entry)| {
+            let point = entry
+                .command_range
+                .start
+                .to_point(&snapshot.buffer_snapshot);
+            if snapshot.is_line_folded(MultiBufferRow(point.row)) {
+                return None;
+            }
+            let row = point.to_display_point(snapshot).row();
+            rows.contains(&row).then_some((row, ix, entry.command))
+        })
+        .collect()
+}
+
+pub(super) fn render_rebase_todo_indicator(
+    row: DisplayRow,
+    ix: usize,
+    command: RebaseCommand,
+    cx: &mut ViewContext<Editor>,
+) -> IconButton {
+    IconButton::new(
+        ("rebase_todo_indicator", row.0 as usize),
+        command_icon(command),
+    )
+    .icon_size(IconSize::XSmall)
+    .size(ButtonSize::None)
+    .icon_color(command_color(command))
+    .tooltip(move |cx| {
+        let meta = if command.applies_to_commit() {
+            "Click to change the command"
+        } else {
+            ""
+        };
+        Tooltip::with_meta(command_description(command), None, meta, cx)
+    })
+    .on_click(cx.listener(move |editor, _, cx| {
+        editor.focus(cx);
+        editor.cycle_rebase_command(ix, cx);
+    }))
+}
+
+impl Editor {
+    pub(crate) fn rebase_pick(&mut self, _: &RebasePick, cx: &mut ViewContext<Self>) {
+        self.set_rebase_command(RebaseCommand::Pick, cx);
+    }
+
+    pub(crate) fn rebase_reword(&mut self, _: &RebaseReword, cx: &mut ViewContext<Self>) {
+        self.set_rebase_command(RebaseCommand::Reword, cx);
+    }
+
+    pub(crate) fn rebase_edit(&mut self, _: &RebaseEdit, cx: &mut ViewContext<Self>) {
+        self.set_rebase_command(RebaseCommand::Edit, cx);
+    }
+
+    pub(crate) fn rebase_squash(&mut self, _: &RebaseSquash, cx: &mut ViewContext<Self>) {
+        self.set_rebase_command(RebaseCommand::Squash, cx);
+    }
+
+    pub(crate) fn rebase_fixup(&mut self, _: &RebaseFixup, cx: &mut ViewContext<Self>) {
+        self.set_rebase_command(RebaseCommand::Fixup, cx);
+    }
+
+    pub(crate) fn rebase_drop(&mut self, _: &RebaseDrop, cx: &mut ViewContext<Self>) {
+        self.set_rebase_command(RebaseCommand::Drop, cx);
+    }
+
+    /// Rewrites the commands of the selected lines, then moves the cursor to the next line
+    /// when nothing is selected, so that the list can be gone through one line at a time.
+    fn set_rebase_command(&mut self, command: RebaseCommand, cx: &mut ViewContext<Self>) {
+        if !self.rebase_todo.active {
+            return;
+        }
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        let selections = self.selections.all::<Point>(cx);
+        let edits = self
+            .rebase_todo
+            .entries
+            .iter()
+            .filter(|entry| entry.command.applies_to_commit())
+            .filter(|entry| {
+                let row = entry.command_range.start.to_point(&snapshot).row;
+                selections.iter().any(|selection| {
+                    let mut end_row = selection.end.row;
+                    if selection.end.column == 0 && end_row > selection.start.row {
+                        end_row -= 1;
+                    }
+                    (selection.start.row..=end_row).contains(&row)
+                })
+            })
+            .map(|entry| (entry.command_range.clone(), command.name()))
+            .collect::<Vec<_>>();
+        if edits.is_empty() {
+            return;
+        }
+
+        let move_down = selections.iter().all(|selection| selection.is_empty());
+        self.transact(cx, |editor, cx| {
+            editor.edit(edits, cx);
+            if move_down {
+                editor.move_down(&MoveDown, cx);
+            }
+        });
+    }
+
+    /// Replaces the command of a line with the next one that applies to a commit.
+    fn cycle_rebase_command(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(entry) = self.rebase_todo.entries.get(ix) else {
+            return;
+        };
+        let Some(position) = COMMIT_COMMANDS
+            .iter()
+            .position(|command| *command == entry.command)
+        else {
+            return;
+        };
+        let command = COMMIT_COMMANDS[(position + 1) % COMMIT_COMMANDS.len()];
+        let range = entry.command_range.clone();
+        self.transact(cx, |editor, cx| editor.edit([(range, command.name())], cx));
+    }
+
+    /// Returns the whole lines dragged when the mouse is pressed on the command of a todo
+    /// line: the selected lines if the command is selected, or else its own line. The text
+    /// always ends with a newline, so that it can be dropped at the start of any line, even
+    /// when dragging the last line of a list without a trailing newline.
+    pub(crate) fn rebase_todo_lines_to_drag(
+        &self,
+        click_offset: usize,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<(Range<usize>, String)> {
+        if !self.rebase_todo.active {
+            return None;
+        }
+        let snapshot = self.buffer.read(cx).snapshot(cx);
+        self.rebase_todo.entries.iter().find(|entry| {
+            entry.command_range.start.to_offset(&snapshot) <= click_offset
+                && click_offset <= entry.command_range.end.to_offset(&snapshot)
+        })?;
+
+        let click_row = click_offset.to_point(&snapshot).row;
+        let (start_row, end_row) = self
+            .selections
+            .all::<Point>(cx)
+            .into_iter()
+            .find(|selection| {
+                let range = selection.range();
+                range.start.to_offset(&snapshot) <= click_offset
+                    && click_offset <= range.end.to_offset(&snapshot)
+            })
+            .map_or((click_row, click_row), |selection| {
+                let mut end_row = selection.end.row;
+                if selection.end.column == 0 && end_row > selection.start.row {
+                    end_row -= 1;
+                }
+                (selection.start.row, end_row)
+            });
+
+        let max_point = snapshot.max_point();
+        let range = if end_row < max_point.row {
+            Point::new(start_row, 0)..Point::new(end_row + 1, 0)
+        } else if start_row > 0 {
+            let previous_row = MultiBufferRow(start_row - 1);
+            Point::new(previous_row.0, snapshot.line_len(previous_row))..max_point
+        } else {
+            return None;
+        };
+        let mut text = snapshot.text_for_range(range.clone()).collect::<String>();
+        if end_row == max_point.row {
+            text = text.trim_start_matches('\n').to_string() + "\n";
+        }
+        Some((
+            range.start.to_offset(&snapshot)..range.end.to_offset(&snapshot),
+            text,
+        ))
+    }
+}
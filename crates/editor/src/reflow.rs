@@ -0,0 +1,188 @@
+use std::cmp::Reverse;
+
+use language::LanguageScope;
+
+/// How the lines of a language's comments and prose start, so that their prefixes can be kept
+/// when reflowing them.
+pub(crate) struct LinePrefixes {
+    /// The line comment prefixes without their trailing whitespace, longest first.
+    comment_prefixes: Vec<String>,
+    /// Whether the lines of block comments are continued with a `*`, like in `/* ... */`.
+    block_comment_stars: bool,
+    /// Whether the lines outside of comments are prose, as in languages without line comments.
+    prose: bool,
+}
+
+impl LinePrefixes {
+    pub(crate) fn new(scope: Option<&LanguageScope>) -> Self {
+        let Some(scope) = scope else {
+            return Self {
+                comment_prefixes: Vec::new(),
+                block_comment_stars: false,
+                prose: true,
+            };
+        };
+        let mut comment_prefixes = scope
+            .line_comment_prefixes()
+            .iter()
+            .map(|prefix| prefix.trim_end().to_string())
+            .filter(|prefix| !prefix.is_empty())
+            .collect::<Vec<_>>();
+        comment_prefixes.sort_by_key(|prefix| Reverse(prefix.len()));
+        comment_prefixes.dedup();
+        let block_comment_stars = scope
+            .block_comment_delimiters()
+            .map_or(false, |(start, _)| start.trim_end().ends_with('*'));
+        Self {
+            prose: comment_prefixes.is_empty(),
+            comment_prefixes,
+            block_comment_stars,
+        }
+    }
+
+    /// Splits a line into its prefix and content, or returns `None` if it can't be reflowed.
+    fn parse<'a>(&self, line: &'a str) -> Option<ParsedLine<'a>> {
+        let indent_len = line.len() - line.trim_start().len();
+        let rest = &line[indent_len..];
+        let comment_len = self
+            .comment_prefixes
+            .iter()
+            .find(|prefix| rest.starts_with(prefix.as_str()))
+            .map(|prefix| prefix.len())
+            .or_else(|| {
+                let is_star = self.block_comment_stars
+                    && rest.starts_with('*')
+                    && !rest.starts_with("*/")
+                    && rest[1..].chars().next().map_or(true, char::is_whitespace);
+                is_star.then_some(1)
+            });
+
+        let comment = match comment_len {
+            Some(comment_len) => &line[..indent_len + comment_len],
+            None if self.prose && !is_verbatim_prose(rest) => &line[..indent_len],
+            None => return None,
+        };
+        let after_comment = &line[comment.len()..];
+        let content = after_comment.trim_start();
+        let prefix = &line[..line.len() - content.len()];
+        let list_marker = list_marker(content);
+        Some(ParsedLine {
+            comment,
+            prefix,
+            content: &content[list_marker.map_or(0, str::len)..],
+            list_marker,
+        })
+    }
+}
+
+struct ParsedLine<'a> {
+    /// The indentation and comment prefix, without the whitespace following it.
+    comment: &'a str,
+    /// The comment prefix, along with the whitespace separating it from the content.
+    prefix: &'a str,
+    /// The marker of the list item started by the line, with its trailing whitespace.
+    list_marker: Option<&'a str>,
+    content: &'a str,
+}
+
+/// Lines of prose that aren't paragraphs, such as markdown headings, fences and tables.
+fn is_verbatim_prose(line: &str) -> bool {
+    line.starts_with('#') || line.starts_with("```") || line.starts_with('|')
+}
+
+fn list_marker(content: &str) -> Option<&str> {
+    let marker_len = if content.starts_with(['-', '*', '+']) {
+        1
+    } else {
+        let digits = content.chars().take_while(char::is_ascii_digit).count();
+        if digits == 0 || !content[digits..].starts_with(['.', ')']) {
+            return None;
+        }
+        digits + 1
+    };
+    let whitespace_len = content[marker_len..].len() - content[marker_len..].trim_start().len();
+    (whitespace_len > 0 && whitespace_len < content.len() - marker_len)
+        .then(|| &content[..marker_len + whitespace_len])
+}
+
+/// Whether the given lines can be reflowed together.
+pub(crate) fn same_paragraph(prefixes: &LinePrefixes, a: &str, b: &str) -> bool {
+    match (prefixes.parse(a), prefixes.parse(b)) {
+        (Some(a), Some(b)) => {
+            !a.content.is_empty() && !b.content.is_empty() && a.comment == b.comment
+        }
+        _ => false,
+    }
+}
+
+/// Rewraps the paragraphs of the given lines so that they fit in `line_length` columns, keeping
+/// their comment prefixes and the hanging indentation of list items. Blank lines and the lines
+/// that can't be reflowed, such as code, are kept as they are.
+pub(crate) fn reflow_lines(
+    lines: &[&str],
+    prefixes: &LinePrefixes,
+    line_length: usize,
+    tab_size: usize,
+) -> String {
+    let mut reflowed = Vec::new();
+    let mut lines = lines.iter().copied().peekable();
+    while let Some(line) = lines.next() {
+        let Some(parsed) = prefixes
+            .parse(line)
+            .filter(|parsed| !parsed.content.is_empty())
+        else {
+            reflowed.push(line.to_string());
+            continue;
+        };
+
+        let first_prefix = format!("{}{}", parsed.prefix, parsed.list_marker.unwrap_or(""));
+        let continuation_prefix = match parsed.list_marker {
+            Some(marker) => format!("{}{}", parsed.prefix, " ".repeat(marker.len())),
+            None => parsed.prefix.to_string(),
+        };
+        let mut words = parsed.content.split_whitespace().collect::<Vec<_>>();
+        while let Some(next) = lines.peek().and_then(|line| prefixes.parse(line)) {
+            let continues_paragraph = next.list_marker.is_none()
+                && !next.content.is_empty()
+                && (next.prefix == continuation_prefix
+                    || next.prefix.trim_end() == continuation_prefix.trim_end());
+            if !continues_paragraph {
+                break;
+            }
+            words.extend(next.content.split_whitespace());
+            lines.next();
+        }
+
+        let mut current_line = first_prefix.clone();
+        let mut current_width = display_width(&first_prefix, tab_size);
+        let mut line_has_words = false;
+        for word in words {
+            let word_width = word.chars().count();
+            if line_has_words && current_width + 1 + word_width > line_length {
+                reflowed.push(current_line.trim_end().to_string());
+                current_line = continuation_prefix.clone();
+                current_width = display_width(&continuation_prefix, tab_size);
+                line_has_words = false;
+            }
+            if line_has_words {
+                current_line.push(' ');
+                current_width += 1;
+            }
+            current_line.push_str(word);
+            current_width += word_width;
+            line_has_words = true;
+        }
+        reflowed.push(current_line);
+    }
+    reflowed.join("\n")
+}
+
+fn display_width(text: &str, tab_size: usize) -> usize {
+    text.chars().fold(0, |width, c| {
+        if c == '\t' {
+            width + tab_size - width % tab_size
+        } else {
+            width + 1
+        }
+    })
+}
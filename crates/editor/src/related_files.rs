@@ -0,0 +1,150 @@
+use std::path::Path;
+
+use gpui::{AppContext, PromptLevel, ViewContext};
+use language::language_settings::language_settings;
+use project::ProjectPath;
+use workspace::notifications::DetachAndPromptErr;
+
+use crate::{Editor, GoToRelatedFile};
+
+impl Editor {
+    /// Opens the file related to the editor's file by the `related_files` patterns of its
+    /// language, such as its header or tests, offering to create it when none exists yet.
+    pub fn go_to_related_file(&mut self, _: &GoToRelatedFile, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace() else {
+            return;
+        };
+        let Some(project) = self.project.clone() else {
+            return;
+        };
+        let Some((file_name, candidates)) = self.related_file_candidates(cx) else {
+            return;
+        };
+
+        let existing = candidates
+            .iter()
+            .find(|path| project.read(cx).entry_for_path(path, cx).is_some())
+            .cloned();
+        if let Some(path) = existing {
+            workspace.update(cx, |workspace, cx| {
+                workspace
+                    .open_path(path, None, true, cx)
+                    .detach_and_prompt_err("Failed to open the related file", cx, |_, _| None);
+            });
+            return;
+        }
+
+        let answers = candidates
+            .iter()
+            .map(|path| {
+                let name = path.path.file_name().unwrap_or_default();
+                format!("Create {}", name.to_string_lossy())
+            })
+            .chain(Some("Cancel".to_string()))
+            .collect::<Vec<_>>();
+        let answers = answers.iter().map(String::as_str).collect::<Vec<_>>();
+        let answer = cx.prompt(
+            PromptLevel::Info,
+            &format!("No file related to {file_name} exists yet"),
+            None,
+            &answers,
+        );
+        cx.spawn(|_, mut cx| async move {
+            let Some(path) = candidates.get(answer.await?).cloned() else {
+                return Ok(());
+            };
+            project
+                .update(&mut cx, |project, cx| {
+                    project.create_entry(path.clone(), false, cx)
+                })?
+                .await?;
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    workspace.open_path(path, None, true, cx)
+                })?
+                .await?;
+            anyhow::Ok(())
+        })
+        .detach_and_prompt_err("Failed to create the related file", cx, |_, _| None);
+    }
+
+    /// The name of the editor's file, along with the paths of its related files in the order
+    /// they're looked up in.
+    fn related_file_candidates(&self, cx: &AppContext) -> Option<(String, Vec<ProjectPath>)> {
+        let buffer = self.buffer.read(cx).as_singleton()?.read(cx);
+        let file = project::File::from_dyn(buffer.file())?;
+        let file_name = file.path.file_name()?.to_str()?.to_string();
+        let groups = &language_settings(buffer.language(), buffer.file(), cx).related_files;
+        let directory = file.path.parent().unwrap_or(Path::new(""));
+        let worktree_id = file.worktree.read(cx).id();
+        let candidates = related_file_names(&file_name, groups)
+            .into_iter()
+            .map(|name| ProjectPath {
+                worktree_id,
+                path: directory.join(name).into(),
+            })
+            .collect::<Vec<_>>();
+        (!candidates.is_empty()).then_some((file_name, candidates))
+    }
+}
+
+/// The names of the files related to the given one: its longest ending found in the groups is
+/// swapped for each of the other endings of the groups containing it.
+fn related_file_names(file_name: &str, groups: &[Vec<String>]) -> Vec<String> {
+    let Some(ending) = groups
+        .iter()
+        .flatten()
+        .filter(|ending| file_name.len() > ending.len() && file_name.ends_with(ending.as_str()))
+        .max_by_key(|ending| ending.len())
+    else {
+        return Vec::new();
+    };
+    let stem = &file_name[..file_name.len() - ending.len()];
+
+    let mut names = Vec::new();
+    for group in groups.iter().filter(|group| group.contains(ending)) {
+        for other_ending in group.iter().filter(|other_ending| *other_ending != ending) {
+            let name = format!("{stem}{other_ending}");
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn groups(groups: &[&[&str]]) -> Vec<Vec<String>> {
+        groups
+            .iter()
+            .map(|group| group.iter().map(|ending| ending.to_string()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_related_file_names() {
+        let c = groups(&[&[".c", ".h"]]);
+        assert_eq!(related_file_names("main.c", &c), ["main.h"]);
+        assert_eq!(related_file_names("main.h", &c), ["main.c"]);
+        assert!(related_file_names("main.rs", &c).is_empty());
+        assert!(related_file_names(".c", &c).is_empty());
+
+        let rust = groups(&[&[".rs", "_test.rs"]]);
+        assert_eq!(related_file_names("parser.rs", &rust), ["parser_test.rs"]);
+        assert_eq!(related_file_names("parser_test.rs", &rust), ["parser.rs"]);
+
+        let tsx = groups(&[&[".tsx", ".css", ".module.css"], &[".tsx", ".test.tsx"]]);
+        assert_eq!(
+            related_file_names("Button.tsx", &tsx),
+            ["Button.css", "Button.module.css", "Button.test.tsx"]
+        );
+        assert_eq!(related_file_names("Button.test.tsx", &tsx), ["Button.tsx"]);
+        assert_eq!(
+            related_file_names("Button.module.css", &tsx),
+            ["Button.tsx", "Button.css"]
+        );
+    }
+}
@@ -0,0 +1,487 @@
+use std::{mem, ops::Range};
+
+use gpui::{
+    px, AnyElement, AppContext, EventEmitter, FocusHandle, FocusableView, HighlightStyle, Model,
+    Render, SharedString, StrikethroughStyle, View, ViewContext, WeakView, WindowContext,
+};
+use language::{Buffer, Capability, ToPoint as _};
+use multi_buffer::MultiBuffer;
+use project::{Project, RenameEdits};
+use ui::{prelude::*, Checkbox, Selection};
+use util::{post_inc, ResultExt};
+use workspace::{
+    item::{Item, ItemEvent, TabContentParams},
+    Workspace,
+};
+
+use crate::{
+    display_map::Inlay, scroll::Autoscroll, Anchor, Editor, InlayId, DEFAULT_MULTIBUFFER_CONTEXT,
+};
+
+pub enum RenamePreviewEvent {
+    Close,
+}
+
+/// The edits of a rename spanning several files, shown before they're applied so that some of
+/// them can be left out. Each replaced range is struck through, followed by its new text.
+pub struct RenamePreview {
+    title: SharedString,
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    editor: View<Editor>,
+    files: Vec<PreviewFile>,
+    inlay_ids: Vec<InlayId>,
+}
+
+struct PreviewFile {
+    buffer: Model<Buffer>,
+    path: SharedString,
+    edits: Vec<PreviewEdit>,
+}
+
+struct PreviewEdit {
+    range: Range<text::Anchor>,
+    new_text: String,
+    /// The replaced range in the preview's multibuffer.
+    excerpt_range: Range<Anchor>,
+    row: u32,
+    accepted: bool,
+}
+
+impl RenamePreview {
+    pub fn new(
+        edits: RenameEdits,
+        title: String,
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let mut edits_by_buffer = edits.0;
+        edits_by_buffer.sort_by_key(|(buffer, _)| buffer.read(cx).file().map(|f| f.path().clone()));
+
+        let replica_id = project.read(cx).replica_id();
+        let mut files = Vec::new();
+        let multibuffer = cx.new_model(|cx| {
+            let mut multibuffer =
+                MultiBuffer::new(replica_id, Capability::ReadOnly).with_title(title.clone());
+            for (buffer, mut edits) in edits_by_buffer {
+                let snapshot = buffer.read(cx).snapshot();
+                edits.sort_by(|(a, _), (b, _)| a.start.cmp(&b.start, &snapshot));
+                let excerpt_ranges = multibuffer.push_excerpts_with_context_lines(
+                    buffer.clone(),
+                    edits.iter().map(|(range, _)| range.clone()).collect(),
+                    DEFAULT_MULTIBUFFER_CONTEXT,
+                    cx,
+                );
+                let path = buffer
+                    .read(cx)
+                    .file()
+                    .map(|file| file.path().to_string_lossy().to_string().into())
+                    .unwrap_or_else(|| "untitled".into());
+                let edits = edits
+                    .into_iter()
+                    .zip(excerpt_ranges)
+                    .map(|((range, new_text), excerpt_range)| PreviewEdit {
+                        row: range.start.to_point(&snapshot).row,
+                        range,
+                        new_text,
+                        excerpt_range,
+                        accepted: true,
+                    })
+                    .collect();
+                files.push(PreviewFile {
+                    buffer,
+                    path,
+                    edits,
+                });
+            }
+            multibuffer
+        });
+        let editor = cx.new_view(|cx| {
+            let mut editor = Editor::for_multibuffer(multibuffer, Some(project.clone()), true, cx);
+            editor.set_read_only(true);
+            editor
+        });
+
+        let mut this = Self {
+            title: title.into(),
+            project,
+            workspace,
+            editor,
+            files,
+            inlay_ids: Vec::new(),
+        };
+        this.update_preview(cx);
+        this
+    }
+
+    fn update_preview(&mut self, cx: &mut ViewContext<Self>) {
+        let style = HighlightStyle {
+            background_color: Some(cx.theme().status().deleted_background),
+            strikethrough: Some(StrikethroughStyle {
+                thickness: px(1.),
+                color: None,
+            }),
+            ..Default::default()
+        };
+        let accepted_edits = self
+            .files
+            .iter()
+            .flat_map(|file| &file.edits)
+            .filter(|edit| edit.accepted);
+        let to_remove = mem::take(&mut self.inlay_ids);
+        self.editor.update(cx, |editor, cx| {
+            let mut ranges = Vec::new();
+            let mut to_insert = Vec::new();
+            for edit in accepted_edits {
+                ranges.push(edit.excerpt_range.clone());
+                if !edit.new_text.is_empty() {
+                    to_insert.push(Inlay::suggestion(
+                        post_inc(&mut editor.next_inlay_id),
+                        edit.excerpt_range.end,
+                        edit.new_text.clone(),
+                    ));
+                }
+            }
+            self.inlay_ids = to_insert.iter().map(|inlay| inlay.id).collect();
+            editor.highlight_text::<Self>(ranges, style, cx);
+            editor.splice_inlays(to_remove, to_insert, cx);
+        });
+        cx.notify();
+    }
+
+    /// Includes or leaves out all the edits of the file at the given index, files being sorted
+    /// by path.
+    pub fn set_file_accepted(
+        &mut self,
+        file_ix: usize,
+        accepted: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(file) = self.files.get_mut(file_ix) {
+            for edit in &mut file.edits {
+                edit.accepted = accepted;
+            }
+            self.update_preview(cx);
+        }
+    }
+
+    /// Includes or leaves out one edit of the file at the given index, edits being sorted by
+    /// their position in the file.
+    pub fn set_edit_accepted(
+        &mut self,
+        file_ix: usize,
+        edit_ix: usize,
+        accepted: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(edit) = self
+            .files
+            .get_mut(file_ix)
+            .and_then(|file| file.edits.get_mut(edit_ix))
+        {
+            edit.accepted = accepted;
+            self.update_preview(cx);
+        }
+    }
+
+    fn reveal_edit(&mut self, file_ix: usize, edit_ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(edit) = self
+            .files
+            .get(file_ix)
+            .and_then(|file| file.edits.get(edit_ix))
+        else {
+            return;
+        };
+        let position = edit.excerpt_range.start;
+        self.editor.update(cx, |editor, cx| {
+            editor.change_selections(Some(Autoscroll::center()), cx, |selections| {
+                selections.select_anchor_ranges([position..position])
+            });
+            editor.focus(cx);
+        });
+    }
+
+    fn accepted_edit_count(&self) -> usize {
+        self.files
+            .iter()
+            .flat_map(|file| &file.edits)
+            .filter(|edit| edit.accepted)
+            .count()
+    }
+
+    /// Applies the accepted edits as one project transaction, shows them in a multibuffer
+    /// where they can be undone together, and closes the preview.
+    pub fn apply(&mut self, cx: &mut ViewContext<Self>) {
+        let edits = RenameEdits(
+            self.files
+                .iter()
+                .map(|file| {
+                    let edits = file
+                        .edits
+                        .iter()
+                        .filter(|edit| edit.accepted)
+                        .map(|edit| (edit.range.clone(), edit.new_text.clone()))
+                        .collect::<Vec<_>>();
+                    (file.buffer.clone(), edits)
+                })
+                .filter(|(_, edits)| !edits.is_empty())
+                .collect(),
+        );
+        let transaction = self
+            .project
+            .update(cx, |project, cx| project.apply_rename_edits(edits, cx));
+        let title = self.title.to_string();
+        let workspace = self.workspace.clone();
+        let editor = self.editor.downgrade();
+        cx.spawn(|this, mut cx| async move {
+            Editor::open_project_transaction(&editor, workspace, transaction, title, cx.clone())
+                .await
+                .log_err();
+            this.update(&mut cx, |_, cx| cx.emit(RenamePreviewEvent::Close))
+                .ok();
+        })
+        .detach();
+    }
+
+    fn render_file(&self, file_ix: usize, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let file = &self.files[file_ix];
+        let accepted_count = file.edits.iter().filter(|edit| edit.accepted).count();
+        let selection = if accepted_count == file.edits.len() {
+            Selection::Selected
+        } else if accepted_count == 0 {
+            Selection::Unselected
+        } else {
+            Selection::Indeterminate
+        };
+
+        v_flex()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(Checkbox::new(("rename-file", file_ix), selection).on_click(
+                        cx.listener(move |this, selection: &Selection, cx| {
+                            this.set_file_accepted(
+                                file_ix,
+                                matches!(selection, Selection::Selected),
+                                cx,
+                            )
+                        }),
+                    ))
+                    .child(Label::new(file.path.clone()).size(LabelSize::Small)),
+            )
+            .children(file.edits.iter().enumerate().map(|(edit_ix, edit)| {
+                let selection = if edit.accepted {
+                    Selection::Selected
+                } else {
+                    Selection::Unselected
+                };
+                h_flex()
+                    .pl_4()
+                    .gap_1()
+                    .child(
+                        Checkbox::new(
+                            ElementId::Name(format!("rename-edit-{file_ix}-{edit_ix}").into()),
+                            selection,
+                        )
+                        .on_click(cx.listener(
+                            move |this, selection: &Selection, cx| {
+                                this.set_edit_accepted(
+                                    file_ix,
+                                    edit_ix,
+                                    matches!(selection, Selection::Selected),
+                                    cx,
+                                )
+                            },
+                        )),
+                    )
+                    .child(
+                        Button::new(
+                            ElementId::Name(format!("rename-edit-row-{file_ix}-{edit_ix}").into()),
+                            format!("Line {}", edit.row + 1),
+                        )
+                        .label_size(LabelSize::Small)
+                        .on_click(
+                            cx.listener(move |this, _, cx| this.reveal_edit(file_ix, edit_ix, cx)),
+                        ),
+                    )
+            }))
+    }
+}
+
+impl EventEmitter<RenamePreviewEvent> for RenamePreview {}
+
+impl FocusableView for RenamePreview {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.editor.focus_handle(cx)
+    }
+}
+
+impl Render for RenamePreview {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let files = (0..self.files.len())
+            .map(|file_ix| self.render_file(file_ix, cx))
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .size_full()
+            .bg(cx.theme().colors().editor_background)
+            .child(
+                h_flex()
+                    .p_2()
+                    .gap_2()
+                    .justify_between()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Label::new(self.title.clone()))
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(Button::new("cancel-rename", "Cancel").on_click(
+                                cx.listener(|_, _, cx| cx.emit(RenamePreviewEvent::Close)),
+                            ))
+                            .child(
+                                Button::new("apply-rename", "Apply")
+                                    .style(ButtonStyle::Filled)
+                                    .disabled(self.accepted_edit_count() == 0)
+                                    .on_click(cx.listener(|this, _, cx| this.apply(cx))),
+                            ),
+                    ),
+            )
+            .child(
+                h_flex()
+                    .flex_1()
+                    .size_full()
+                    .child(
+                        v_flex()
+                            .id("rename-preview-edits")
+                            .w(px(240.))
+                            .h_full()
+                            .p_2()
+                            .gap_1()
+                            .overflow_y_scroll()
+                            .border_r_1()
+                            .border_color(cx.theme().colors().border)
+                            .children(files),
+                    )
+                    .child(div().flex_1().h_full().child(self.editor.clone())),
+            )
+    }
+}
+
+impl Item for RenamePreview {
+    type Event = RenamePreviewEvent;
+
+    fn tab_content(&self, params: TabContentParams, _: &WindowContext) -> AnyElement {
+        Label::new(self.title.clone())
+            .color(if params.selected {
+                Color::Default
+            } else {
+                Color::Muted
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        None
+    }
+
+    fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
+        match event {
+            RenamePreviewEvent::Close => f(ItemEvent::CloseItem),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor_tests::init_test;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::FakeFs;
+    use serde_json::json;
+
+    #[gpui::test]
+    async fn test_rename_preview(cx: &mut TestAppContext) {
+        init_test(cx, |_| {});
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = ONE + ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace = window.root(cx).unwrap();
+        let cx = &mut VisualTestContext::from_window(*window, cx);
+
+        let one = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/one.rs", cx)
+            })
+            .await
+            .unwrap();
+        let two = project
+            .update(cx, |project, cx| {
+                project.open_local_buffer("/dir/two.rs", cx)
+            })
+            .await
+            .unwrap();
+        let edits = cx.update(|cx| {
+            let edit = |buffer: &Model<Buffer>, range: Range<usize>| {
+                let buffer = buffer.read(cx);
+                (
+                    buffer.anchor_after(range.start)..buffer.anchor_before(range.end),
+                    "THREE".to_string(),
+                )
+            };
+            RenameEdits(vec![
+                (two.clone(), vec![edit(&two, 19..22), edit(&two, 25..28)]),
+                (one.clone(), vec![edit(&one, 6..9)]),
+            ])
+        });
+
+        let preview = cx.new_view(|cx| {
+            RenamePreview::new(
+                edits,
+                "Rename: ONE → THREE".into(),
+                project.clone(),
+                workspace.downgrade(),
+                cx,
+            )
+        });
+        preview.update(cx, |preview, cx| {
+            assert_eq!(
+                preview
+                    .files
+                    .iter()
+                    .map(|file| (file.path.as_ref(), file.edits.len()))
+                    .collect::<Vec<_>>(),
+                [("one.rs", 1), ("two.rs", 2)]
+            );
+            // The preview doesn't change the buffers.
+            assert_eq!(
+                preview.editor.read(cx).text(cx),
+                "const ONE: usize = 1;\nconst TWO: usize = ONE + ONE;"
+            );
+
+            preview.set_edit_accepted(1, 1, false, cx);
+            assert_eq!(preview.accepted_edit_count(), 2);
+            preview.apply(cx);
+        });
+        cx.run_until_parked();
+
+        assert_eq!(
+            one.read_with(cx, |buffer, _| buffer.text()),
+            "const THREE: usize = 1;"
+        );
+        assert_eq!(
+            two.read_with(cx, |buffer, _| buffer.text()),
+            "const TWO: usize = THREE + ONE;"
+        );
+    }
+}
@@ -3,9 +3,15 @@ pub(crate) mod autoscroll;
 pub(crate) mod scroll_amount;
 
 use crate::{
+    code_lens::{refresh_code_lenses, resolve_visible_code_lenses},
     display_map::{DisplaySnapshot, ToDisplayPoint},
+    document_colors::{refresh_document_colors, update_color_swatches},
+    document_links::{refresh_document_links, update_document_links},
+    folding_ranges::refresh_folding_ranges,
     hover_popover::hide_hover,
     persistence::DB,
+    rainbow_brackets::refresh_rainbow_brackets,
+    unicode_highlights::refresh_unicode_highlights,
     Anchor, DisplayPoint, DisplayRow, Editor, EditorEvent, EditorMode, EditorSettings,
     InlayHintRefreshReason, MultiBufferSnapshot, RowExt, ToPoint,
 };
@@ -16,6 +22,7 @@ pub use scroll_amount::ScrollAmount;
 use settings::Settings;
 use std::{
     cmp::Ordering,
+    ops::Range,
     time::{Duration, Instant},
 };
 use util::ResultExt;
@@ -136,6 +143,7 @@ pub struct ScrollManager {
     show_scrollbars: bool,
     hide_scrollbar_task: Option<Task<()>>,
     dragging_scrollbar: bool,
+    dragging_minimap: bool,
     visible_line_count: Option<f32>,
     forbid_vertical_scroll: bool,
 }
@@ -150,6 +158,7 @@ impl ScrollManager {
             show_scrollbars: true,
             hide_scrollbar_task: None,
             dragging_scrollbar: false,
+            dragging_minimap: false,
             last_autoscroll: None,
             visible_line_count: None,
             forbid_vertical_scroll: false,
@@ -298,6 +307,17 @@ impl ScrollManager {
         }
     }
 
+    pub fn is_dragging_minimap(&self) -> bool {
+        self.dragging_minimap
+    }
+
+    pub fn set_is_dragging_minimap(&mut self, dragging: bool, cx: &mut ViewContext<Editor>) {
+        if dragging != self.dragging_minimap {
+            self.dragging_minimap = dragging;
+            cx.notify();
+        }
+    }
+
     pub fn clamp_scroll_left(&mut self, max: f32) -> bool {
         if max < self.anchor.offset.x {
             self.anchor.offset.x = max;
@@ -330,6 +350,27 @@ impl Editor {
         self.scroll_manager.visible_line_count
     }
 
+    /// Returns the range of buffer offsets displayed in the visible rows, along with the row
+    /// following them.
+    pub(crate) fn visible_buffer_range(&self, display_snapshot: &DisplaySnapshot) -> Range<usize> {
+        let scroll_top = self
+            .scroll_manager
+            .anchor()
+            .scroll_position(display_snapshot)
+            .y;
+        let visible_line_count = self.visible_line_count().unwrap_or(0.);
+        let max_row = display_snapshot.max_point().row();
+        let start = DisplayPoint::new(DisplayRow(scroll_top.floor() as u32).min(max_row), 0)
+            .to_offset(display_snapshot, Bias::Left);
+        let end_row = DisplayRow((scroll_top + visible_line_count).ceil() as u32 + 1);
+        let end = if end_row > max_row {
+            display_snapshot.buffer_snapshot.len()
+        } else {
+            DisplayPoint::new(end_row, 0).to_offset(display_snapshot, Bias::Right)
+        };
+        start..end
+    }
+
     pub(crate) fn set_visible_line_count(&mut self, lines: f32, cx: &mut ViewContext<Self>) {
         let opened_first_time = self.scroll_manager.visible_line_count.is_none();
         self.scroll_manager.visible_line_count = Some(lines);
@@ -337,7 +378,13 @@ impl Editor {
             cx.spawn(|editor, mut cx| async move {
                 editor
                     .update(&mut cx, |editor, cx| {
-                        editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx)
+                        editor.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+                        refresh_rainbow_brackets(editor, cx);
+                        refresh_unicode_highlights(editor, cx);
+                        refresh_code_lenses(editor, cx);
+                        refresh_document_colors(editor, cx);
+                        refresh_document_links(editor, cx);
+                        refresh_folding_ranges(editor, cx);
                     })
                     .ok()
             })
@@ -401,6 +448,11 @@ impl Editor {
         );
 
         self.refresh_inlay_hints(InlayHintRefreshReason::NewLinesShown, cx);
+        refresh_rainbow_brackets(self, cx);
+        refresh_unicode_highlights(self, cx);
+        resolve_visible_code_lenses(self, cx);
+        update_color_swatches(self, cx);
+        update_document_links(self, cx);
     }
 
     pub fn scroll_position(&self, cx: &mut ViewContext<Self>) -> gpui::Point<f32> {
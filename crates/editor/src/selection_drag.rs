@@ -0,0 +1,329 @@
+use std::ops::Range;
+
+use gpui::{Global, Modifiers, Pixels, Point, ViewContext, WeakView};
+use language::Bias;
+use multi_buffer::ToOffset as _;
+use settings::Settings;
+
+use crate::{
+    display_map::DisplayPoint, scroll::Autoscroll, Anchor, Editor, EditorSettings, SelectPhase,
+};
+
+/// How far the mouse must move while pressed on a selection before it's dragged rather than
+/// clicked.
+const DRAG_THRESHOLD: f64 = 4.;
+
+/// A selection dragged with the mouse, which is moved or copied to where it's dropped, in the
+/// editor it comes from or in another one.
+struct SelectionDrag {
+    source: WeakView<Editor>,
+    range: Range<Anchor>,
+    text: String,
+    mouse_down_position: Point<Pixels>,
+    /// Where the source editor places its cursor if the mouse is released without dragging.
+    click_position: DisplayPoint,
+    /// Whether whole lines are dragged, to be dropped at the start of a line.
+    lines: bool,
+    started: bool,
+}
+
+/// The selection being dragged, shared by all editors so that any of them can accept the drop.
+#[derive(Default)]
+struct ActiveSelectionDrag(Option<SelectionDrag>);
+
+impl Global for ActiveSelectionDrag {}
+
+/// Whether the selection is copied rather than moved when dropped with these modifiers.
+fn is_copy_modifier(modifiers: &Modifiers) -> bool {
+    if cfg!(target_os = "macos") {
+        modifiers.alt
+    } else {
+        modifiers.control
+    }
+}
+
+impl Editor {
+    /// Starts dragging the selection at the clicked position, if the click lands in a non-empty
+    /// selection and dragging selections is enabled, or dragging the lines of a rebase's todo
+    /// list when the click lands on one of their commands. Returns whether a drag was started.
+    pub(crate) fn begin_selection_drag(
+        &mut self,
+        click_position: DisplayPoint,
+        mouse_position: Point<Pixels>,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        let display_snapshot = self
+            .display_map
+            .update(cx, |display_map, cx| display_map.snapshot(cx));
+        let click_offset = click_position.to_offset(&display_snapshot, Bias::Left);
+        let buffer = &display_snapshot.buffer_snapshot;
+        let (range, text, lines) =
+            if let Some((range, text)) = self.rebase_todo_lines_to_drag(click_offset, cx) {
+                (range, text, true)
+            } else {
+                if !EditorSettings::get_global(cx).drag_and_drop_selection {
+                    return false;
+                }
+                let selections = self.selections.all::<usize>(cx);
+                let Some(selection) = selections.into_iter().find(|selection| {
+                    !selection.is_empty() && selection.range().contains(&click_offset)
+                }) else {
+                    return false;
+                };
+                let text = buffer
+                    .text_for_range(selection.start..selection.end)
+                    .collect();
+                (selection.range(), text, false)
+            };
+
+        let range = buffer.anchor_after(range.start)..buffer.anchor_before(range.end);
+        cx.set_global(ActiveSelectionDrag(Some(SelectionDrag {
+            source: cx.view().downgrade(),
+            range,
+            text,
+            mouse_down_position: mouse_position,
+            click_position,
+            lines,
+            started: false,
+        })));
+        true
+    }
+
+    /// Moves the drop cursor of this editor to where the dragged selection would be dropped,
+    /// or hides it when the mouse isn't over the editor's text. Returns whether a selection is
+    /// being dragged, in which case the mouse shouldn't select text.
+    pub(crate) fn update_selection_drag(
+        &mut self,
+        drop_position: Option<DisplayPoint>,
+        mouse_position: Point<Pixels>,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        let Some(drag) = cx
+            .try_global::<ActiveSelectionDrag>()
+            .and_then(|drag| drag.0.as_ref())
+        else {
+            return false;
+        };
+        let lines = drag.lines;
+        if !drag.started {
+            let is_source = drag.source.entity_id() == cx.entity_id();
+            let moved = (mouse_position - drag.mouse_down_position).magnitude() > DRAG_THRESHOLD;
+            if !(is_source && moved) {
+                return true;
+            }
+            if let Some(drag) = cx.default_global::<ActiveSelectionDrag>().0.as_mut() {
+                drag.started = true;
+            }
+        }
+
+        let drop_position = drop_position
+            .filter(|_| !self.read_only(cx))
+            .map(|position| self.drop_position(position, lines, cx));
+        if self.selection_drop_cursor != drop_position {
+            self.selection_drop_cursor = drop_position;
+            cx.notify();
+        }
+        true
+    }
+
+    /// Drops the dragged selection at the given position if the mouse was released over this
+    /// editor's text, or places the cursor in the source editor if the mouse never moved.
+    /// Returns whether a selection was being dragged.
+    pub(crate) fn end_selection_drag(
+        &mut self,
+        drop_position: Option<DisplayPoint>,
+        modifiers: Modifiers,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        let Some(drag) = cx
+            .try_global::<ActiveSelectionDrag>()
+            .and_then(|drag| drag.0.as_ref())
+        else {
+            return false;
+        };
+        let is_source = drag.source.entity_id() == cx.entity_id();
+        let started = drag.started;
+        let click_position = drag.click_position;
+        let lines = drag.lines;
+
+        if self.selection_drop_cursor.take().is_some() {
+            cx.notify();
+        }
+        // Every editor sees the mouse being released, so the drag is only forgotten once they
+        // all have, unless one of them accepts the drop first.
+        cx.defer(|_, cx| {
+            cx.default_global::<ActiveSelectionDrag>().0.take();
+        });
+
+        if !started {
+            if is_source {
+                cx.default_global::<ActiveSelectionDrag>().0.take();
+                self.select(
+                    SelectPhase::Begin {
+                        position: click_position,
+                        add: false,
+                        click_count: 1,
+                    },
+                    cx,
+                );
+                self.select(SelectPhase::End, cx);
+            }
+            return true;
+        }
+
+        if let Some(position) = drop_position.filter(|_| !self.read_only(cx)) {
+            let position = self.drop_position(position, lines, cx);
+            if let Some(drag) = cx.default_global::<ActiveSelectionDrag>().0.take() {
+                self.drop_selection(drag, position, is_copy_modifier(&modifiers), cx);
+            }
+        }
+        true
+    }
+
+    /// Returns where a drag would be dropped with the mouse over the given position: the start
+    /// of its line when dragging whole lines.
+    fn drop_position(
+        &mut self,
+        position: DisplayPoint,
+        lines: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> DisplayPoint {
+        if !lines {
+            return position;
+        }
+        let display_snapshot = self
+            .display_map
+            .update(cx, |display_map, cx| display_map.snapshot(cx));
+        let point = position.to_point(&display_snapshot);
+        display_snapshot.prev_line_boundary(point).1
+    }
+
+    fn drop_selection(
+        &mut self,
+        drag: SelectionDrag,
+        position: DisplayPoint,
+        copy: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(source) = drag.source.upgrade() else {
+            return;
+        };
+        let display_snapshot = self
+            .display_map
+            .update(cx, |display_map, cx| display_map.snapshot(cx));
+        let drop_offset = position.to_offset(&display_snapshot, Bias::Left);
+        let buffer = &display_snapshot.buffer_snapshot;
+        let inserted_range = buffer.anchor_before(drop_offset)..buffer.anchor_after(drop_offset);
+        let remove_from_source = !copy && !source.read(cx).read_only(cx);
+
+        if source.entity_id() == cx.entity_id() {
+            let dragged_range =
+                drag.range.start.to_offset(buffer)..drag.range.end.to_offset(buffer);
+            if dragged_range.start <= drop_offset && drop_offset <= dragged_range.end {
+                self.change_selections(None, cx, |s| s.select_ranges([drop_offset..drop_offset]));
+                return;
+            }
+            self.transact(cx, |editor, cx| {
+                let mut edits = vec![(drop_offset..drop_offset, drag.text)];
+                if remove_from_source {
+                    edits.push((dragged_range, String::new()));
+                }
+                edits.sort_by_key(|(range, _)| range.start);
+                editor
+                    .buffer
+                    .update(cx, |buffer, cx| buffer.edit(edits, None, cx));
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchor_ranges([inserted_range])
+                });
+            });
+        } else {
+            self.transact(cx, |editor, cx| {
+                editor.buffer.update(cx, |buffer, cx| {
+                    buffer.edit([(drop_offset..drop_offset, drag.text)], None, cx)
+                });
+                editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                    s.select_anchor_ranges([inserted_range])
+                });
+            });
+            if remove_from_source {
+                source.update(cx, |source, cx| {
+                    source.transact(cx, |source, cx| {
+                        source
+                            .buffer
+                            .update(cx, |buffer, cx| buffer.edit([(drag.range, "")], None, cx));
+                    });
+                });
+            }
+        }
+        self.focus(cx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        editor_tests::init_test, test::editor_test_context::EditorTestContext, DisplayRow,
+    };
+    use gpui::{point, px};
+    use indoc::indoc;
+
+    #[gpui::test]
+    async fn test_drag_and_drop_selection(cx: &mut gpui::TestAppContext) {
+        init_test(cx, |_| {});
+        let mut cx = EditorTestContext::new(cx).await;
+
+        let drag_and_drop = |cx: &mut EditorTestContext, from: DisplayPoint, to, copy| {
+            cx.update_editor(|editor, cx| {
+                let modifiers = Modifiers {
+                    alt: copy,
+                    control: copy,
+                    ..Modifiers::default()
+                };
+                assert!(editor.begin_selection_drag(from, point(px(0.), px(0.)), cx));
+                assert!(editor.update_selection_drag(Some(to), point(px(20.), px(0.)), cx));
+                assert_eq!(editor.selection_drop_cursor, Some(to));
+                assert!(editor.end_selection_drag(Some(to), modifiers, cx));
+                assert_eq!(editor.selection_drop_cursor, None);
+            });
+        };
+
+        cx.set_state(indoc! {"
+            one «twoˇ» three
+            four
+        "});
+        drag_and_drop(
+            &mut cx,
+            DisplayPoint::new(DisplayRow(0), 5),
+            DisplayPoint::new(DisplayRow(1), 4),
+            false,
+        );
+        cx.assert_editor_state(indoc! {"
+            one  three
+            four«twoˇ»
+        "});
+
+        drag_and_drop(
+            &mut cx,
+            DisplayPoint::new(DisplayRow(1), 5),
+            DisplayPoint::new(DisplayRow(0), 0),
+            true,
+        );
+        cx.assert_editor_state(indoc! {"
+            «twoˇ»one  three
+            fourtwo
+        "});
+
+        // Releasing the mouse without moving it places the cursor where it was pressed.
+        cx.update_editor(|editor, cx| {
+            let from = DisplayPoint::new(DisplayRow(0), 1);
+            assert!(editor.begin_selection_drag(from, point(px(0.), px(0.)), cx));
+            assert!(editor.update_selection_drag(Some(from), point(px(1.), px(0.)), cx));
+            assert!(editor.end_selection_drag(Some(from), Modifiers::default(), cx));
+        });
+        cx.assert_editor_state(indoc! {"
+            tˇwoone  three
+            fourtwo
+        "});
+    }
+}
@@ -0,0 +1,176 @@
+use std::{any::TypeId, ops::Range, time::Duration};
+
+use collections::HashMap;
+use futures::future::join_all;
+use gpui::{HighlightStyle, Model, Task, ViewContext};
+use language::{
+    language_settings::{language_settings, SemanticTokens},
+    semantic_token_style, Buffer, SemanticToken,
+};
+use text::BufferId;
+use theme::ActiveTheme;
+use util::ResultExt;
+
+use crate::{Anchor, Editor, EditorMode};
+
+/// How long to wait after an edit before refetching the semantic tokens of the buffers.
+const FETCH_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// The number of distinct styles semantic tokens are highlighted with, at most. Text
+/// highlights are keyed by type, so every style is highlighted with its own key.
+const MAX_SEMANTIC_TOKEN_STYLES: usize = 16;
+
+enum SemanticTokenHighlight<const STYLE: usize> {}
+
+fn highlight_keys() -> [TypeId; MAX_SEMANTIC_TOKEN_STYLES] {
+    [
+        TypeId::of::<SemanticTokenHighlight<0>>(),
+        TypeId::of::<SemanticTokenHighlight<1>>(),
+        TypeId::of::<SemanticTokenHighlight<2>>(),
+        TypeId::of::<SemanticTokenHighlight<3>>(),
+        TypeId::of::<SemanticTokenHighlight<4>>(),
+        TypeId::of::<SemanticTokenHighlight<5>>(),
+        TypeId::of::<SemanticTokenHighlight<6>>(),
+        TypeId::of::<SemanticTokenHighlight<7>>(),
+        TypeId::of::<SemanticTokenHighlight<8>>(),
+        TypeId::of::<SemanticTokenHighlight<9>>(),
+        TypeId::of::<SemanticTokenHighlight<10>>(),
+        TypeId::of::<SemanticTokenHighlight<11>>(),
+        TypeId::of::<SemanticTokenHighlight<12>>(),
+        TypeId::of::<SemanticTokenHighlight<13>>(),
+        TypeId::of::<SemanticTokenHighlight<14>>(),
+        TypeId::of::<SemanticTokenHighlight<15>>(),
+    ]
+}
+
+/// The semantic tokens of the buffers in an editor, highlighted over their tree-sitter
+/// highlights.
+#[derive(Default)]
+pub(crate) struct SemanticTokensState {
+    tokens: HashMap<BufferId, (Model<Buffer>, Vec<SemanticToken>)>,
+    fetch_task: Option<Task<()>>,
+}
+
+/// Fetches the semantic tokens of the buffers in the editor whose language has
+/// `semantic_tokens` enabled, then updates their highlights.
+pub(super) fn refresh_semantic_tokens(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+    let Some(project) = editor.project.clone() else {
+        return;
+    };
+
+    let buffers = editor
+        .buffer
+        .read(cx)
+        .all_buffers()
+        .into_iter()
+        .filter(|buffer| {
+            let buffer = buffer.read(cx);
+            language_settings(buffer.language(), buffer.file(), cx).semantic_tokens
+                != SemanticTokens::Off
+        })
+        .collect::<Vec<_>>();
+    if buffers.is_empty() {
+        editor.semantic_tokens.fetch_task = None;
+        editor.semantic_tokens.tokens.clear();
+        update_semantic_token_highlights(editor, cx);
+        return;
+    }
+
+    editor.semantic_tokens.fetch_task = Some(cx.spawn(|editor, mut cx| async move {
+        cx.background_executor().timer(FETCH_DEBOUNCE).await;
+        let Ok(fetches) = editor.update(&mut cx, |_, cx| {
+            buffers
+                .into_iter()
+                .map(|buffer| {
+                    let tokens =
+                        project.update(cx, |project, cx| project.semantic_tokens(&buffer, cx));
+                    async move { (buffer, tokens.await.log_err().unwrap_or_default()) }
+                })
+                .collect::<Vec<_>>()
+        }) else {
+            return;
+        };
+        let tokens_by_buffer = join_all(fetches).await;
+        editor
+            .update(&mut cx, |editor, cx| {
+                editor.semantic_tokens.tokens = tokens_by_buffer
+                    .into_iter()
+                    .map(|(buffer, tokens)| (buffer.read(cx).remote_id(), (buffer, tokens)))
+                    .collect();
+                update_semantic_token_highlights(editor, cx);
+            })
+            .ok();
+    }));
+}
+
+/// Highlights the fetched semantic tokens in every excerpt of their buffers, styled according
+/// to the current theme and the `semantic_tokens` setting of each buffer's language.
+pub(super) fn update_semantic_token_highlights(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    let syntax_theme = cx.theme().syntax().clone();
+    let multibuffer = editor.buffer.read(cx);
+    let snapshot = multibuffer.snapshot(cx);
+    let mut ranges_by_style = Vec::<(HighlightStyle, Vec<Range<Anchor>>)>::new();
+    for (buffer, tokens) in editor.semantic_tokens.tokens.values() {
+        let buffer_snapshot = buffer.read(cx).snapshot();
+        let setting = language_settings(buffer_snapshot.language(), buffer_snapshot.file(), cx)
+            .semantic_tokens;
+        let excerpts = multibuffer.excerpts_for_buffer(buffer, cx);
+        for token in tokens {
+            let Some(style) = semantic_token_style(token, setting, &syntax_theme) else {
+                continue;
+            };
+            let range = excerpts.iter().find_map(|(excerpt_id, range)| {
+                let contains_token = range
+                    .context
+                    .start
+                    .cmp(&token.range.start, &buffer_snapshot)
+                    .is_le()
+                    && range
+                        .context
+                        .end
+                        .cmp(&token.range.end, &buffer_snapshot)
+                        .is_ge();
+                if !contains_token {
+                    return None;
+                }
+                let start = snapshot.anchor_in_excerpt(*excerpt_id, token.range.start)?;
+                let end = snapshot.anchor_in_excerpt(*excerpt_id, token.range.end)?;
+                Some(start..end)
+            });
+            let Some(range) = range else {
+                continue;
+            };
+            match ranges_by_style.iter_mut().find(|(s, _)| *s == style) {
+                Some((_, ranges)) => ranges.push(range),
+                None if ranges_by_style.len() < MAX_SEMANTIC_TOKEN_STYLES => {
+                    ranges_by_style.push((style, vec![range]))
+                }
+                None => {}
+            }
+        }
+    }
+    for (_, ranges) in &mut ranges_by_style {
+        ranges.sort_by(|a, b| a.start.cmp(&b.start, &snapshot));
+    }
+
+    let mut ranges_by_style = ranges_by_style.into_iter();
+    let changed = editor.display_map.update(cx, |display_map, _| {
+        let mut changed = false;
+        for key in highlight_keys() {
+            match ranges_by_style.next() {
+                Some((style, ranges)) => {
+                    display_map.highlight_text(key, ranges, style);
+                    changed = true;
+                }
+                None => changed |= display_map.clear_highlights(key),
+            }
+        }
+        changed
+    });
+    if changed {
+        cx.notify();
+    }
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use chrono::Local;
+use gpui::AppContext;
+use language::{CharKind, Point, ToOffset, ToPoint};
+
+use crate::Editor;
+
+/// Returns the value of a snippet variable, like `TM_FILENAME` or `CURRENT_YEAR`, at the newest
+/// selection of the editor, or `None` when the variable is unknown or has no value there.
+pub(crate) fn snippet_variable(editor: &Editor, name: &str, cx: &AppContext) -> Option<String> {
+    let now = Local::now();
+    let date = |format: &str| Some(now.format(format).to_string());
+    match name {
+        "CLIPBOARD" => return cx.read_from_clipboard().map(|item| item.text().clone()),
+        "CURRENT_YEAR" => return date("%Y"),
+        "CURRENT_YEAR_SHORT" => return date("%y"),
+        "CURRENT_MONTH" => return date("%m"),
+        "CURRENT_MONTH_NAME" => return date("%B"),
+        "CURRENT_MONTH_NAME_SHORT" => return date("%b"),
+        "CURRENT_DATE" => return date("%d"),
+        "CURRENT_DAY_NAME" => return date("%A"),
+        "CURRENT_DAY_NAME_SHORT" => return date("%a"),
+        "CURRENT_HOUR" => return date("%H"),
+        "CURRENT_MINUTE" => return date("%M"),
+        "CURRENT_SECOND" => return date("%S"),
+        "CURRENT_SECONDS_UNIX" => return Some(now.timestamp().to_string()),
+        _ => {}
+    }
+
+    let selection = editor.selections.newest_anchor();
+    let (buffer, head) = editor
+        .buffer
+        .read(cx)
+        .text_anchor_for_position(selection.head(), cx)?;
+    let buffer = buffer.read(cx);
+    let head = head.to_point(buffer);
+    let file = buffer.file();
+    let abs_path = || file?.as_local().map(|file| file.abs_path(cx));
+    match name {
+        "TM_SELECTED_TEXT" => {
+            let range = selection.start.text_anchor..selection.end.text_anchor;
+            let text = buffer.text_for_range(range).collect::<String>();
+            (!text.is_empty()).then_some(text)
+        }
+        "TM_CURRENT_LINE" => Some(
+            buffer
+                .text_for_range(
+                    Point::new(head.row, 0)..Point::new(head.row, buffer.line_len(head.row)),
+                )
+                .collect(),
+        ),
+        "TM_CURRENT_WORD" => {
+            let (range, kind) = buffer.surrounding_word(head.to_offset(buffer));
+            (kind == Some(CharKind::Word)).then(|| buffer.text_for_range(range).collect())
+        }
+        "TM_LINE_INDEX" => Some(head.row.to_string()),
+        "TM_LINE_NUMBER" => Some((head.row + 1).to_string()),
+        "TM_FILENAME" => Some(file?.file_name(cx).to_string_lossy().into_owned()),
+        "TM_FILENAME_BASE" => {
+            let file_name = Path::new(file?.file_name(cx));
+            Some(file_name.file_stem()?.to_string_lossy().into_owned())
+        }
+        "TM_DIRECTORY" => Some(abs_path()?.parent()?.to_string_lossy().into_owned()),
+        "TM_FILEPATH" => Some(abs_path()?.to_string_lossy().into_owned()),
+        "RELATIVE_FILEPATH" => Some(file?.path().to_string_lossy().into_owned()),
+        "LINE_COMMENT" => {
+            let scope = buffer.language_scope_at(head)?;
+            Some(
+                scope
+                    .line_comment_prefixes()
+                    .first()?
+                    .trim_end()
+                    .to_string(),
+            )
+        }
+        "BLOCK_COMMENT_START" | "BLOCK_COMMENT_END" => {
+            let scope = buffer.language_scope_at(head)?;
+            let (start, end) = scope.block_comment_delimiters()?;
+            let delimiter = if name == "BLOCK_COMMENT_START" {
+                start
+            } else {
+                end
+            };
+            Some(delimiter.trim().to_string())
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,97 @@
+use std::ops::Range;
+
+use gpui::{AppContext, HighlightStyle, ViewContext};
+use language::Point;
+use multi_buffer::{Anchor, MultiBufferRow, ToPoint};
+use sum_tree::Bias;
+use theme::SyntaxTheme;
+
+use crate::{
+    display_map::ToDisplayPoint, scroll::Autoscroll, DisplayPoint, DisplayRow, Editor,
+    EditorSnapshot,
+};
+
+/// The header line of a scope enclosing the first visible line, pinned to the top of the editor.
+#[derive(Clone, Debug)]
+pub struct StickyHeader {
+    /// Where the scope starts, used to jump to it when the header is clicked.
+    pub anchor: Anchor,
+    pub text: String,
+    pub highlight_ranges: Vec<(Range<usize>, HighlightStyle)>,
+}
+
+impl EditorSnapshot {
+    /// Returns the outline items whose headers have scrolled out of view while their bodies are still
+    /// visible, outermost first. Each header covers one more line at the top of the editor, so
+    /// the item under the `n`-th header is checked against the `n`-th visible row.
+    pub fn sticky_headers(
+        &self,
+        start_row: DisplayRow,
+        theme: Option<&SyntaxTheme>,
+        cx: &AppContext,
+    ) -> Vec<StickyHeader> {
+        let mut headers = Vec::new();
+        if start_row == DisplayRow(0) {
+            return headers;
+        }
+
+        let start_point = DisplayPoint::new(start_row, 0).to_point(&self.display_snapshot);
+        let settings = self
+            .buffer_snapshot
+            .settings_at(Point::new(start_point.row, 0), cx)
+            .sticky_scroll;
+        if !settings.enabled || settings.max_lines == 0 {
+            return headers;
+        }
+
+        let offset = DisplayPoint::new(start_row, 0).to_offset(&self.display_snapshot, Bias::Left);
+        // Symbols are looked up in the excerpt containing the offset, so multibuffers use the
+        // syntax tree of each excerpt's own buffer.
+        let Some((_, items)) = self.buffer_snapshot.symbols_containing(offset, theme) else {
+            return headers;
+        };
+
+        for item in items {
+            let covered_row = start_row.0 + headers.len() as u32;
+            let item_start = item
+                .range
+                .start
+                .to_display_point(&self.display_snapshot)
+                .row();
+            let item_end = item
+                .range
+                .end
+                .to_display_point(&self.display_snapshot)
+                .row();
+            if item_start.0 >= covered_row {
+                // This header is still visible, and so are those of all nested items.
+                break;
+            }
+            if item_end.0 <= covered_row {
+                continue;
+            }
+
+            headers.push(StickyHeader {
+                anchor: item.range.start,
+                text: item.text,
+                highlight_ranges: item.highlight_ranges,
+            });
+            if headers.len() >= settings.max_lines as usize {
+                break;
+            }
+        }
+
+        headers
+    }
+}
+
+impl Editor {
+    pub(crate) fn jump_to_sticky_header(&mut self, anchor: Anchor, cx: &mut ViewContext<Self>) {
+        let buffer = self.buffer.read(cx).snapshot(cx);
+        let row = MultiBufferRow(anchor.to_point(&buffer).row);
+        let start = buffer.anchor_before(Point::new(row.0, buffer.indent_size_for_line(row).len));
+        self.change_selections(Some(Autoscroll::center()), cx, |s| {
+            s.select_anchor_ranges([start..start]);
+        });
+    }
+}
@@ -149,6 +149,14 @@ impl EditorLspTestContext {
                 ("<" @open ">" @close)
                 ("\"" @open "\"" @close)
                 (closure_parameters "|" @open "|" @close)"#})),
+            text_objects: Some(Cow::from(indoc! {r#"
+                (function_item
+                    body: (_ "{" (_)* @function.inside "}")) @function.around
+
+                (struct_item
+                    body: (_ "{" (_)* @class.inside "}")) @class.around
+
+                (line_comment)+ @comment.inside @comment.around"#})),
             ..Default::default()
         })
         .expect("Could not parse queries");
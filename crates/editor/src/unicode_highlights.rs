@@ -0,0 +1,394 @@
+use std::{any::TypeId, ops::Range};
+
+use gpui::{px, HighlightStyle, UnderlineStyle, ViewContext};
+use multi_buffer::{MultiBufferSnapshot, ToOffset};
+use project::{HoverBlock, HoverBlockKind};
+use settings::Settings;
+use theme::ActiveTheme;
+use util::post_inc;
+
+use crate::{
+    display_map::Inlay, editor_settings::UnicodeHighlights, hover_links::InlayHighlight, Anchor,
+    Editor, EditorMode, EditorSettings, InlayId,
+};
+
+/// A character that can't be seen, or that is easily mistaken for another one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum UnicodeHighlight {
+    /// A character without a glyph, rendered with a placeholder showing its abbreviation.
+    Invisible {
+        abbreviation: &'static str,
+        name: &'static str,
+    },
+    /// A character that looks like a more common one.
+    Confusable {
+        name: &'static str,
+        looks_like: char,
+    },
+}
+
+impl UnicodeHighlight {
+    pub(crate) fn for_char(c: char) -> Option<Self> {
+        if let Some((abbreviation, name)) = invisible_character(c) {
+            Some(Self::Invisible { abbreviation, name })
+        } else if let Some((looks_like, name)) = confusable_character(c) {
+            Some(Self::Confusable { name, looks_like })
+        } else {
+            None
+        }
+    }
+
+    fn placeholder(&self) -> Option<String> {
+        match self {
+            Self::Invisible { abbreviation, .. } => Some(format!("[{abbreviation}]")),
+            Self::Confusable { .. } => None,
+        }
+    }
+
+    fn explanation(&self, c: char) -> String {
+        let code_point = format!("U+{:04X}", c as u32);
+        match self {
+            Self::Invisible { name, .. } if is_bidi_control(c) => format!(
+                "**{name}** ({code_point})\n\nThis invisible character changes the direction in which the text following it is displayed, so that it may read differently than it is interpreted."
+            ),
+            Self::Invisible { name, .. } => format!(
+                "**{name}** ({code_point})\n\nThis character is invisible, so the text containing it may look identical to text without it."
+            ),
+            Self::Confusable { name, looks_like } => format!(
+                "**{name}** ({code_point})\n\nThis character looks like `{looks_like}` (U+{:04X}), but is a different one.",
+                *looks_like as u32
+            ),
+        }
+    }
+}
+
+fn invisible_character(c: char) -> Option<(&'static str, &'static str)> {
+    Some(match c {
+        '\u{00AD}' => ("SHY", "Soft Hyphen"),
+        '\u{061C}' => ("ALM", "Arabic Letter Mark"),
+        '\u{180E}' => ("MVS", "Mongolian Vowel Separator"),
+        '\u{200B}' => ("ZWSP", "Zero Width Space"),
+        '\u{200C}' => ("ZWNJ", "Zero Width Non-Joiner"),
+        '\u{200D}' => ("ZWJ", "Zero Width Joiner"),
+        '\u{200E}' => ("LRM", "Left-to-Right Mark"),
+        '\u{200F}' => ("RLM", "Right-to-Left Mark"),
+        '\u{202A}' => ("LRE", "Left-to-Right Embedding"),
+        '\u{202B}' => ("RLE", "Right-to-Left Embedding"),
+        '\u{202C}' => ("PDF", "Pop Directional Formatting"),
+        '\u{202D}' => ("LRO", "Left-to-Right Override"),
+        '\u{202E}' => ("RLO", "Right-to-Left Override"),
+        '\u{2060}' => ("WJ", "Word Joiner"),
+        '\u{2061}' => ("FA", "Function Application"),
+        '\u{2062}' => ("IT", "Invisible Times"),
+        '\u{2063}' => ("IS", "Invisible Separator"),
+        '\u{2064}' => ("IP", "Invisible Plus"),
+        '\u{2066}' => ("LRI", "Left-to-Right Isolate"),
+        '\u{2067}' => ("RLI", "Right-to-Left Isolate"),
+        '\u{2068}' => ("FSI", "First Strong Isolate"),
+        '\u{2069}' => ("PDI", "Pop Directional Isolate"),
+        '\u{3164}' => ("HF", "Hangul Filler"),
+        '\u{FEFF}' => ("ZWNBSP", "Zero Width No-Break Space"),
+        _ => return None,
+    })
+}
+
+fn is_bidi_control(c: char) -> bool {
+    matches!(
+        c,
+        '\u{061C}' | '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}'
+    )
+}
+
+fn confusable_character(c: char) -> Option<(char, &'static str)> {
+    Some(match c {
+        '\u{0391}' => ('A', "Greek Capital Letter Alpha"),
+        '\u{0392}' => ('B', "Greek Capital Letter Beta"),
+        '\u{0395}' => ('E', "Greek Capital Letter Epsilon"),
+        '\u{0396}' => ('Z', "Greek Capital Letter Zeta"),
+        '\u{0397}' => ('H', "Greek Capital Letter Eta"),
+        '\u{0399}' => ('I', "Greek Capital Letter Iota"),
+        '\u{039A}' => ('K', "Greek Capital Letter Kappa"),
+        '\u{039C}' => ('M', "Greek Capital Letter Mu"),
+        '\u{039D}' => ('N', "Greek Capital Letter Nu"),
+        '\u{039F}' => ('O', "Greek Capital Letter Omicron"),
+        '\u{03A1}' => ('P', "Greek Capital Letter Rho"),
+        '\u{03A4}' => ('T', "Greek Capital Letter Tau"),
+        '\u{03A5}' => ('Y', "Greek Capital Letter Upsilon"),
+        '\u{03A7}' => ('X', "Greek Capital Letter Chi"),
+        '\u{03BD}' => ('v', "Greek Small Letter Nu"),
+        '\u{03BF}' => ('o', "Greek Small Letter Omicron"),
+        '\u{0405}' => ('S', "Cyrillic Capital Letter Dze"),
+        '\u{0406}' => ('I', "Cyrillic Capital Letter Byelorussian-Ukrainian I"),
+        '\u{0408}' => ('J', "Cyrillic Capital Letter Je"),
+        '\u{0410}' => ('A', "Cyrillic Capital Letter A"),
+        '\u{0412}' => ('B', "Cyrillic Capital Letter Ve"),
+        '\u{0415}' => ('E', "Cyrillic Capital Letter Ie"),
+        '\u{041A}' => ('K', "Cyrillic Capital Letter Ka"),
+        '\u{041C}' => ('M', "Cyrillic Capital Letter Em"),
+        '\u{041D}' => ('H', "Cyrillic Capital Letter En"),
+        '\u{041E}' => ('O', "Cyrillic Capital Letter O"),
+        '\u{0420}' => ('P', "Cyrillic Capital Letter Er"),
+        '\u{0421}' => ('C', "Cyrillic Capital Letter Es"),
+        '\u{0422}' => ('T', "Cyrillic Capital Letter Te"),
+        '\u{0425}' => ('X', "Cyrillic Capital Letter Ha"),
+        '\u{0430}' => ('a', "Cyrillic Small Letter A"),
+        '\u{0435}' => ('e', "Cyrillic Small Letter Ie"),
+        '\u{043E}' => ('o', "Cyrillic Small Letter O"),
+        '\u{0440}' => ('p', "Cyrillic Small Letter Er"),
+        '\u{0441}' => ('c', "Cyrillic Small Letter Es"),
+        '\u{0443}' => ('y', "Cyrillic Small Letter U"),
+        '\u{0445}' => ('x', "Cyrillic Small Letter Ha"),
+        '\u{0455}' => ('s', "Cyrillic Small Letter Dze"),
+        '\u{0456}' => ('i', "Cyrillic Small Letter Byelorussian-Ukrainian I"),
+        '\u{0458}' => ('j', "Cyrillic Small Letter Je"),
+        '\u{04BB}' => ('h', "Cyrillic Small Letter Shha"),
+        '\u{0501}' => ('d', "Cyrillic Small Letter Komi De"),
+        '\u{01C3}' => ('!', "Latin Letter Retroflex Click"),
+        '\u{037E}' => (';', "Greek Question Mark"),
+        '\u{2044}' => ('/', "Fraction Slash"),
+        '\u{2212}' => ('-', "Minus Sign"),
+        '\u{2215}' => ('/', "Division Slash"),
+        '\u{FF1A}' => (':', "Fullwidth Colon"),
+        '\u{FF1B}' => (';', "Fullwidth Semicolon"),
+        _ => return None,
+    })
+}
+
+/// Finds the characters of the text to highlight, along with their byte offsets.
+///
+/// Letters that look like Latin ones are only highlighted within words containing ASCII
+/// characters, so that text written in their own script isn't highlighted. Zero width joiners
+/// following emoji are part of emoji sequences, and aren't highlighted either.
+pub(crate) fn find_unicode_highlights(text: &str) -> Vec<(usize, char, UnicodeHighlight)> {
+    let mut highlights = Vec::new();
+    let mut previous_char = None;
+    for (offset, c) in text.char_indices() {
+        let prev = previous_char.replace(c);
+        let Some(highlight) = UnicodeHighlight::for_char(c) else {
+            continue;
+        };
+        match highlight {
+            UnicodeHighlight::Invisible { .. } if c == '\u{200D}' => {
+                if prev.map_or(false, |prev| prev as u32 >= 0x1F000 || prev == '\u{FE0F}') {
+                    continue;
+                }
+            }
+            UnicodeHighlight::Confusable { .. } if c.is_alphanumeric() => {
+                let word_start = text[..offset]
+                    .char_indices()
+                    .rev()
+                    .find(|(_, c)| !c.is_alphanumeric())
+                    .map_or(0, |(ix, c)| ix + c.len_utf8());
+                let word_end = text[offset..]
+                    .char_indices()
+                    .find(|(_, c)| !c.is_alphanumeric())
+                    .map_or(text.len(), |(ix, _)| offset + ix);
+                if !text[word_start..word_end]
+                    .chars()
+                    .any(|c| c.is_ascii_alphanumeric())
+                {
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        highlights.push((offset, c, highlight));
+    }
+    highlights
+}
+
+/// The placeholders and highlights of the unusual characters in the visible part of an editor.
+#[derive(Default)]
+pub(crate) struct UnicodeHighlightState {
+    placeholders: Vec<(InlayId, Anchor, char, UnicodeHighlight)>,
+    confusables: Vec<(Range<Anchor>, char, UnicodeHighlight)>,
+}
+
+impl UnicodeHighlightState {
+    /// Returns the placeholder inlay rendered between the given anchors, with its explanation.
+    pub(crate) fn placeholder_hover(
+        &self,
+        start: &Anchor,
+        end: &Anchor,
+        buffer: &MultiBufferSnapshot,
+    ) -> Option<(InlayHighlight, HoverBlock)> {
+        self.placeholders
+            .iter()
+            .find(|(_, position, _, _)| {
+                position.cmp(start, buffer).is_ge() && position.cmp(end, buffer).is_le()
+            })
+            .and_then(|(inlay, position, c, highlight)| {
+                Some((
+                    InlayHighlight {
+                        inlay: *inlay,
+                        inlay_position: *position,
+                        range: 0..highlight.placeholder()?.len(),
+                    },
+                    HoverBlock {
+                        text: highlight.explanation(*c),
+                        kind: HoverBlockKind::Markdown,
+                    },
+                ))
+            })
+    }
+
+    /// Returns the highlighted confusable character at the given anchor, with its explanation.
+    pub(crate) fn confusable_hover(
+        &self,
+        anchor: &Anchor,
+        buffer: &MultiBufferSnapshot,
+    ) -> Option<(Range<Anchor>, HoverBlock)> {
+        let offset = anchor.to_offset(buffer);
+        self.confusables
+            .iter()
+            .find(|(range, _, _)| {
+                (range.start.to_offset(buffer)..range.end.to_offset(buffer)).contains(&offset)
+            })
+            .map(|(range, c, highlight)| {
+                (
+                    range.clone(),
+                    HoverBlock {
+                        text: highlight.explanation(*c),
+                        kind: HoverBlockKind::Markdown,
+                    },
+                )
+            })
+    }
+}
+
+/// Renders the visible invisible characters with placeholders and underlines the confusable
+/// ones, according to the `unicode_highlights` setting.
+pub(super) fn refresh_unicode_highlights(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    if editor.mode != EditorMode::Full {
+        return;
+    }
+
+    let setting = EditorSettings::get_global(cx).unicode_highlights;
+    let display_snapshot = editor
+        .display_map
+        .update(cx, |display_map, cx| display_map.snapshot(cx));
+    let buffer = &display_snapshot.buffer_snapshot;
+    let mut invisibles = Vec::new();
+    let mut confusables = Vec::new();
+    if setting != UnicodeHighlights::None {
+        let visible_range = editor.visible_buffer_range(&display_snapshot);
+        let text = buffer
+            .text_for_range(visible_range.clone())
+            .collect::<String>();
+        for (offset, c, highlight) in find_unicode_highlights(&text) {
+            let offset = visible_range.start + offset;
+            if setting == UnicodeHighlights::Code
+                && buffer
+                    .language_scope_at(offset)
+                    .map_or(false, |scope| scope.override_name() == Some("comment"))
+            {
+                continue;
+            }
+            match highlight {
+                UnicodeHighlight::Invisible { .. } => invisibles.push((offset, c, highlight)),
+                UnicodeHighlight::Confusable { .. } => {
+                    let range =
+                        buffer.anchor_before(offset)..buffer.anchor_after(offset + c.len_utf8());
+                    confusables.push((range, c, highlight));
+                }
+            }
+        }
+    }
+
+    let state = &editor.unicode_highlights;
+    let placeholders_changed = state.placeholders.len() != invisibles.len()
+        || state.placeholders.iter().zip(&invisibles).any(
+            |((_, position, old_c, _), (offset, new_c, _))| {
+                position.to_offset(buffer) != *offset || old_c != new_c
+            },
+        );
+    if placeholders_changed {
+        let to_remove = state
+            .placeholders
+            .iter()
+            .map(|(inlay, _, _, _)| *inlay)
+            .collect();
+        let mut placeholders = Vec::new();
+        let mut to_insert = Vec::new();
+        for (offset, c, highlight) in invisibles {
+            let Some(placeholder) = highlight.placeholder() else {
+                continue;
+            };
+            let position = buffer.anchor_before(offset);
+            let inlay =
+                Inlay::invisible(post_inc(&mut editor.next_inlay_id), position, placeholder);
+            placeholders.push((inlay.id, position, c, highlight));
+            to_insert.push(inlay);
+        }
+        editor.unicode_highlights.placeholders = placeholders;
+        editor.splice_inlays(to_remove, to_insert, cx);
+    }
+
+    let key = TypeId::of::<UnicodeHighlightState>();
+    let ranges = confusables
+        .iter()
+        .map(|(range, _, _)| range.clone())
+        .collect::<Vec<_>>();
+    let had_confusables = !editor.unicode_highlights.confusables.is_empty();
+    editor.unicode_highlights.confusables = confusables;
+    if ranges.is_empty() {
+        if had_confusables {
+            editor
+                .display_map
+                .update(cx, |display_map, _| display_map.clear_highlights(key));
+            cx.notify();
+        }
+    } else {
+        let warning = cx.theme().status().warning;
+        let style = HighlightStyle {
+            underline: Some(UnderlineStyle {
+                thickness: px(1.),
+                color: Some(warning),
+                wavy: true,
+            }),
+            ..Default::default()
+        };
+        editor.display_map.update(cx, |display_map, _| {
+            display_map.highlight_text(key, ranges, style)
+        });
+        cx.notify();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_unicode_highlights() {
+        let highlighted = |text: &str| {
+            find_unicode_highlights(text)
+                .into_iter()
+                .map(|(offset, c, _)| (offset, c))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(highlighted("let a = 1;"), []);
+        assert_eq!(
+            highlighted("if access != \"user\u{202E} \u{2066}// admin\u{2069} \u{2066}\""),
+            [
+                (18, '\u{202E}'),
+                (22, '\u{2066}'),
+                (33, '\u{2069}'),
+                (37, '\u{2066}')
+            ]
+        );
+        assert_eq!(highlighted("zero\u{200B}width"), [(4, '\u{200B}')]);
+
+        // Cyrillic letters are only highlighted within words mixing them with Latin ones.
+        assert_eq!(highlighted("p\u{0430}ypal"), [(1, '\u{0430}')]);
+        assert_eq!(
+            highlighted("\u{043F}\u{0440}\u{0438}\u{0432}\u{0435}\u{0442}"),
+            []
+        );
+
+        // Zero width joiners within emoji sequences aren't highlighted.
+        assert_eq!(highlighted("\u{1F468}\u{200D}\u{1F469}"), []);
+        assert_eq!(highlighted("x \u{2212} y"), [(2, '\u{2212}')]);
+    }
+}
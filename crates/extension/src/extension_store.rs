@@ -1096,6 +1096,14 @@ impl ExtensionStore {
             .collect::<Vec<_>>();
 
         self.extension_index = new_index;
+        terminal::link_patterns::set_extension_link_patterns(
+            self.extension_index
+                .extensions
+                .values()
+                .flat_map(|extension| extension.manifest.terminal_link_patterns.iter().cloned())
+                .collect(),
+            cx,
+        );
         cx.notify();
         cx.emit(Event::ExtensionsUpdated);
 
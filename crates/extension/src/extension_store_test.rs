@@ -158,6 +158,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         .collect(),
                         language_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
+                        terminal_link_patterns: Vec::new(),
                     }),
                     dev: false,
                 },
@@ -182,6 +183,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                         grammars: BTreeMap::default(),
                         language_servers: BTreeMap::default(),
                         slash_commands: BTreeMap::default(),
+                        terminal_link_patterns: Vec::new(),
                     }),
                     dev: false,
                 },
@@ -339,6 +341,7 @@ async fn test_extension_store(cx: &mut TestAppContext) {
                 grammars: BTreeMap::default(),
                 language_servers: BTreeMap::default(),
                 slash_commands: BTreeMap::default(),
+                terminal_link_patterns: Vec::new(),
             }),
             dev: false,
         },
@@ -26,7 +26,10 @@ use std::{
 };
 use text::Point;
 use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
-use util::{paths::PathLikeWithPosition, post_inc, ResultExt};
+use util::{
+    paths::{PathLikeWithPosition, FILE_ROW_COLUMN_DELIMITER},
+    post_inc, ResultExt,
+};
 use workspace::{item::PreviewTabsSettings, ModalView, Workspace};
 
 actions!(file_finder, [SelectPrev]);
@@ -107,6 +110,24 @@ impl FileFinder {
         });
     }
 
+    /// Opens the file finder with the given query typed in, e.g. a directory to list the files of.
+    pub fn open_with_query(
+        workspace: &mut Workspace,
+        query: String,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        if workspace.active_modal::<Self>(cx).is_none() {
+            Self::open(workspace, false, cx);
+        }
+        if let Some(file_finder) = workspace.active_modal::<Self>(cx) {
+            file_finder.update(cx, |file_finder, cx| {
+                file_finder
+                    .picker
+                    .update(cx, |picker, cx| picker.set_query(query, cx))
+            });
+        }
+    }
+
     fn new(delegate: FileFinderDelegate, cx: &mut ViewContext<Self>) -> Self {
         Self {
             picker: cx.new_view(|cx| Picker::uniform_list(delegate, cx)),
@@ -415,6 +436,24 @@ impl FileSearchQuery {
     }
 }
 
+/// Parses a `:row` or `:row:column` query, which goes to that position in the currently opened
+/// file, like `go to line` does.
+fn position_query(raw_query: &str) -> Option<PathLikeWithPosition<FileSearchQuery>> {
+    let position = raw_query.strip_prefix(FILE_ROW_COLUMN_DELIMITER)?;
+    let (row, column) = match position.split_once(FILE_ROW_COLUMN_DELIMITER) {
+        Some((row, column)) => (row, Some(column)),
+        None => (position, None),
+    };
+    Some(PathLikeWithPosition {
+        path_like: FileSearchQuery {
+            raw_query: raw_query.to_owned(),
+            file_query_end: Some(0),
+        },
+        row: Some(row.parse().ok()?),
+        column: column.and_then(|column| column.parse().ok()),
+    })
+}
+
 impl FileFinderDelegate {
     fn new(
         file_finder: WeakView<FileFinder>,
@@ -776,6 +815,8 @@ impl PickerDelegate for FileFinderDelegate {
     ) -> Task<()> {
         let raw_query = raw_query.replace(' ', "");
         let raw_query = raw_query.trim();
+        // Locations pasted from compiler output, such as rustc's `--> src/main.rs:120:8`.
+        let raw_query = raw_query.strip_prefix("-->").unwrap_or(raw_query);
         if raw_query.is_empty() {
             let project = self.project.read(cx);
             self.latest_search_id = post_inc(&mut self.search_count);
@@ -796,6 +837,20 @@ impl PickerDelegate for FileFinderDelegate {
                 false,
             );
 
+            self.selected_index = 0;
+            cx.notify();
+            Task::ready(())
+        } else if let Some(query) = position_query(raw_query) {
+            self.latest_search_id = post_inc(&mut self.search_count);
+            self.latest_search_query = Some(query);
+            self.matches = Matches {
+                separate_history: self.separate_history,
+                matches: self
+                    .currently_opened_path
+                    .iter()
+                    .map(|found_path| Match::History(found_path.clone(), None))
+                    .collect(),
+            };
             self.selected_index = 0;
             cx.notify();
             Task::ready(())
@@ -333,6 +333,49 @@ async fn test_row_column_numbers_query_outside_file(cx: &mut TestAppContext) {
         });
 }
 
+#[gpui::test]
+async fn test_row_column_numbers_query_in_opened_file(cx: &mut TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/src",
+            json!({
+                "test": {
+                    "first.rs": "// First Rust file\nfn main() {}\n",
+                    "second.rs": "// Second Rust file",
+                }
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/src".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+    open_queried_buffer("fir", 1, "first.rs", &workspace, cx).await;
+
+    let picker = open_file_picker(&workspace, cx);
+    cx.simulate_input(":2:4");
+    picker.update(cx, |finder, _| {
+        assert_match_selection(finder, 0, "first.rs");
+        let latest_search_query = finder.delegate.latest_search_query.as_ref().unwrap();
+        assert_eq!(latest_search_query.row, Some(2));
+        assert_eq!(latest_search_query.column, Some(4));
+    });
+
+    cx.dispatch_action(Confirm);
+    cx.executor().advance_clock(Duration::from_secs(2));
+
+    let editor = cx.update(|cx| workspace.read(cx).active_item_as::<Editor>(cx).unwrap());
+    editor.update(cx, |editor, cx| {
+        assert_eq!(editor.title(cx), "first.rs");
+        let selections = editor.selections.all_adjusted(cx);
+        assert_eq!(selections.len(), 1);
+        assert_eq!(selections[0].start, selections[0].end);
+        assert_eq!(selections[0].start, Point::new(1, 3));
+    });
+}
+
 #[gpui::test]
 async fn test_matching_cancellation(cx: &mut TestAppContext) {
     let app_state = init_test(cx);
@@ -1,8 +1,9 @@
 use crate::Oid;
 use anyhow::{anyhow, Result};
 use collections::HashMap;
+use std::io::Write;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
@@ -47,3 +48,142 @@ pub fn get_messages(working_directory: &Path, shas: &[Oid]) -> Result<HashMap<Oi
         )
         .collect::<HashMap<Oid, String>>())
 }
+
+/// A commit listed in the history of a file.
+#[derive(Clone, Debug)]
+pub struct CommitSummary {
+    pub sha: Oid,
+    pub subject: String,
+    pub author_name: String,
+    /// Timestamp of the commit, in seconds since the Unix Epoch.
+    pub unix_timestamp: i64,
+}
+
+/// Lists the most recent commits changing a file, following its renames.
+pub fn file_history(
+    git_binary: &Path,
+    working_directory: &Path,
+    path: &Path,
+    max_commits: usize,
+) -> Result<Vec<CommitSummary>> {
+    let mut command = Command::new(git_binary);
+
+    command
+        .current_dir(working_directory)
+        .arg("log")
+        .arg("--follow")
+        .arg("-z")
+        .arg(format!("--max-count={max_commits}"))
+        .arg("--format=%H%x1f%an%x1f%at%x1f%s")
+        .arg("--")
+        .arg(path);
+
+    #[cfg(windows)]
+    command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+
+    let output = command
+        .output()
+        .map_err(|e| anyhow!("Failed to start git log process: {}", e))?;
+
+    anyhow::ensure!(
+        output.status.success(),
+        "'git log' failed with error {:?}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    parse_file_history(&String::from_utf8_lossy(&output.stdout))
+}
+
+fn parse_file_history(output: &str) -> Result<Vec<CommitSummary>> {
+    const FIELD_SEPARATOR: char = '\x1f';
+
+    output
+        .split_terminator('\0')
+        .map(|record| {
+            let mut fields = record.trim_start_matches('\n').split(FIELD_SEPARATOR);
+            let mut next_field = || {
+                fields
+                    .next()
+                    .ok_or_else(|| anyhow!("unexpected git log output: {:?}", record))
+            };
+            Ok(CommitSummary {
+                sha: next_field()?.parse()?,
+                author_name: next_field()?.to_string(),
+                unix_timestamp: next_field()?.parse()?,
+                subject: next_field()?.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Commits the staged changes with the git binary, so that the repository's hooks run.
+///
+/// Returns the output of the command, including the output of the hooks.
+pub fn commit(
+    git_binary: &Path,
+    working_directory: &Path,
+    message: &str,
+    amend: bool,
+) -> Result<String> {
+    let mut command = Command::new(git_binary);
+
+    command
+        .current_dir(working_directory)
+        .args(["commit", "--file=-"])
+        .args(amend.then_some("--amend"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+
+    #[cfg(windows)]
+    command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+
+    let mut child = command
+        .spawn()
+        .map_err(|e| anyhow!("Failed to start git commit process: {}", e))?;
+    child
+        .stdin
+        .take()
+        .ok_or_else(|| anyhow!("Failed to open git commit stdin"))?
+        .write_all(message.as_bytes())?;
+    let output = child
+        .wait_with_output()
+        .map_err(|e| anyhow!("Failed to read git commit output: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let output_text = format!("{}{}", stderr, stdout).trim().to_string();
+    anyhow::ensure!(
+        output.status.success(),
+        "git commit failed:\n{}",
+        output_text
+    );
+    Ok(output_text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_file_history() {
+        let output = "\
+            1111111111111111111111111111111111111111\x1fJane Doe\x1f1700000000\x1fRename the file\0\
+            \n2222222222222222222222222222222222222222\x1fJohn Doe\x1f1600000000\x1fAdd the file\0";
+        let history = parse_file_history(output).unwrap();
+        assert_eq!(history.len(), 2);
+        assert_eq!(
+            history[0].sha.to_string(),
+            "1111111111111111111111111111111111111111"
+        );
+        assert_eq!(history[0].author_name, "Jane Doe");
+        assert_eq!(history[0].unix_timestamp, 1700000000);
+        assert_eq!(history[0].subject, "Rename the file");
+        assert_eq!(history[1].author_name, "John Doe");
+        assert_eq!(history[1].subject, "Add the file");
+
+        assert!(parse_file_history("").unwrap().is_empty());
+        assert!(parse_file_history("1111111111111111111111111111111111111111\0").is_err());
+    }
+}
@@ -0,0 +1,167 @@
+use std::ops::Range;
+
+/// A region of a file with conflicting changes, delimited by the markers git writes when it can't
+/// combine the changes of a merge, rebase or cherry-pick:
+///
+/// ```text
+/// <<<<<<< HEAD
+/// our changes
+/// ||||||| base
+/// the common ancestor's text, only written with the `diff3` conflict style
+/// =======
+/// their changes
+/// >>>>>>> feature
+/// ```
+///
+/// All ranges are byte offsets in the file's text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    /// The whole region, including the markers and the line ending of the last one.
+    pub range: Range<usize>,
+    pub ours: Range<usize>,
+    pub base: Option<Range<usize>>,
+    pub theirs: Range<usize>,
+    /// The label of our side, usually `HEAD`.
+    pub ours_label: String,
+    /// The label of their side, usually the branch or commit being merged.
+    pub theirs_label: String,
+}
+
+const OURS_MARKER: &str = "<<<<<<<";
+const BASE_MARKER: &str = "|||||||";
+const SEPARATOR_MARKER: &str = "=======";
+const THEIRS_MARKER: &str = ">>>>>>>";
+
+/// Finds the conflicts in the text of a file. Regions whose markers are incomplete or out of order
+/// are skipped.
+pub fn parse_conflicts(text: &str) -> Vec<Conflict> {
+    struct PendingConflict {
+        start: usize,
+        ours_label: String,
+        ours_start: usize,
+        ours_end: Option<usize>,
+        base_start: Option<usize>,
+        base_end: Option<usize>,
+        theirs_start: Option<usize>,
+    }
+
+    let mut conflicts = Vec::new();
+    let mut pending: Option<PendingConflict> = None;
+    let mut offset = 0;
+    for line in text.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if let Some(label) = marker_label(line, OURS_MARKER) {
+            pending = Some(PendingConflict {
+                start: line_start,
+                ours_label: label.to_string(),
+                ours_start: offset,
+                ours_end: None,
+                base_start: None,
+                base_end: None,
+                theirs_start: None,
+            });
+        } else if let Some(conflict) = pending.as_mut() {
+            if marker_label(line, BASE_MARKER).is_some() && conflict.ours_end.is_none() {
+                conflict.ours_end = Some(line_start);
+                conflict.base_start = Some(offset);
+            } else if line == SEPARATOR_MARKER && conflict.theirs_start.is_none() {
+                if conflict.base_start.is_some() {
+                    conflict.base_end = Some(line_start);
+                } else {
+                    conflict.ours_end = Some(line_start);
+                }
+                conflict.theirs_start = Some(offset);
+            } else if let Some(label) = marker_label(line, THEIRS_MARKER) {
+                if let (Some(ours_end), Some(theirs_start)) =
+                    (conflict.ours_end, conflict.theirs_start)
+                {
+                    conflicts.push(Conflict {
+                        range: conflict.start..offset,
+                        ours: conflict.ours_start..ours_end,
+                        base: conflict
+                            .base_start
+                            .zip(conflict.base_end)
+                            .map(|(start, end)| start..end),
+                        theirs: theirs_start..line_start,
+                        ours_label: std::mem::take(&mut conflict.ours_label),
+                        theirs_label: label.to_string(),
+                    });
+                }
+                pending = None;
+            }
+        }
+    }
+    conflicts
+}
+
+/// Returns the label following a conflict marker at the start of the line.
+fn marker_label<'a>(line: &'a str, marker: &str) -> Option<&'a str> {
+    let rest = line.strip_prefix(marker)?;
+    if rest.is_empty() {
+        Some(rest)
+    } else {
+        rest.strip_prefix(' ').map(str::trim)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unindent::Unindent as _;
+
+    #[test]
+    fn test_parse_conflicts() {
+        let text = "
+            fn main() {
+            <<<<<<< HEAD
+                println!(\"ours\");
+            =======
+                println!(\"theirs\");
+            >>>>>>> feature
+            }
+            <<<<<<< HEAD
+            a
+            ||||||| base
+            b
+            =======
+            >>>>>>> 1234567 (Change b)
+        "
+        .unindent();
+        let conflicts = parse_conflicts(&text);
+        assert_eq!(conflicts.len(), 2);
+
+        let conflict = &conflicts[0];
+        assert!(text[conflict.range.clone()].starts_with("<<<<<<< HEAD\n"));
+        assert!(text[conflict.range.clone()].ends_with(">>>>>>> feature\n"));
+        assert_eq!(&text[conflict.ours.clone()], "    println!(\"ours\");\n");
+        assert_eq!(
+            &text[conflict.theirs.clone()],
+            "    println!(\"theirs\");\n"
+        );
+        assert_eq!(conflict.base, None);
+        assert_eq!(conflict.ours_label, "HEAD");
+        assert_eq!(conflict.theirs_label, "feature");
+
+        let conflict = &conflicts[1];
+        assert_eq!(&text[conflict.ours.clone()], "a\n");
+        assert_eq!(&text[conflict.base.clone().unwrap()], "b\n");
+        assert_eq!(&text[conflict.theirs.clone()], "");
+        assert_eq!(conflict.theirs_label, "1234567 (Change b)");
+        assert_eq!(conflict.range.end, text.len());
+    }
+
+    #[test]
+    fn test_parse_incomplete_conflicts() {
+        assert!(parse_conflicts("<<<<<<< HEAD\na\n>>>>>>> feature\n").is_empty());
+        assert!(parse_conflicts("<<<<<<< HEAD\na\n=======\nb\n").is_empty());
+        assert!(parse_conflicts("<<<<<<<<< HEAD\na\n=======\nb\n>>>>>>> feature\n").is_empty());
+
+        let conflicts = parse_conflicts("<<<<<<< HEAD\r\na\r\n=======\r\nb\r\n>>>>>>> feature");
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].ours, 14..17);
+        assert_eq!(conflicts[0].theirs_label, "feature");
+    }
+}
@@ -285,6 +285,29 @@ impl BufferDiff {
     }
 }
 
+/// Returns the rows of the old text that differ from the new one, paired with the rows of the new
+/// text that replaced them.
+pub fn changed_rows(old_text: &str, new_text: &str) -> Vec<(Range<u32>, Range<u32>)> {
+    let Some(patch) = BufferDiff::diff(old_text, new_text) else {
+        return Vec::new();
+    };
+
+    // Line numbers are one-based, except for the line that an empty side of a hunk follows.
+    let rows = |start: u32, count: u32| {
+        let start = if count == 0 { start } else { start - 1 };
+        start..start + count
+    };
+    (0..patch.num_hunks())
+        .filter_map(|hunk_index| {
+            let (hunk, _) = patch.hunk(hunk_index).ok()?;
+            Some((
+                rows(hunk.old_start(), hunk.old_lines()),
+                rows(hunk.new_start(), hunk.new_lines()),
+            ))
+        })
+        .collect()
+}
+
 /// Range (crossing new lines), old, new
 #[cfg(any(test, feature = "test-support"))]
 #[track_caller]
@@ -327,6 +350,30 @@ mod tests {
     use text::{Buffer, BufferId};
     use unindent::Unindent as _;
 
+    #[test]
+    fn test_changed_rows() {
+        let old_text = "
+            one
+            two
+            three
+            four
+        "
+        .unindent();
+        let new_text = "
+            zero
+            one
+            three
+            FOUR
+        "
+        .unindent();
+
+        assert_eq!(
+            changed_rows(&old_text, &new_text),
+            [(0..0, 0..1), (1..2, 2..2), (3..4, 3..4)]
+        );
+        assert!(changed_rows(&old_text, &old_text).is_empty());
+    }
+
     #[test]
     fn test_buffer_diff_simple() {
         let diff_base = "
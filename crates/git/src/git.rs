@@ -13,9 +13,12 @@ pub use crate::hosting_provider::*;
 
 pub mod blame;
 pub mod commit;
+pub mod conflict;
 pub mod diff;
+pub mod rebase_todo;
 pub mod repository;
 pub mod status;
+pub mod worktree;
 
 lazy_static! {
     pub static ref DOT_GIT: &'static OsStr = OsStr::new(".git");
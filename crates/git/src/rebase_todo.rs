@@ -0,0 +1,200 @@
+use std::ops::Range;
+
+/// The name of the file git asks the editor to edit during `git rebase --interactive`.
+pub const REBASE_TODO_FILE_NAME: &str = "git-rebase-todo";
+
+/// A command of an interactive rebase's todo list.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RebaseCommand {
+    Pick,
+    Reword,
+    Edit,
+    Squash,
+    Fixup,
+    Exec,
+    Break,
+    Drop,
+    Label,
+    Reset,
+    Merge,
+    UpdateRef,
+}
+
+impl RebaseCommand {
+    /// Parses a command, written out or abbreviated.
+    pub fn parse(word: &str) -> Option<Self> {
+        Some(match word {
+            "pick" | "p" => Self::Pick,
+            "reword" | "r" => Self::Reword,
+            "edit" | "e" => Self::Edit,
+            "squash" | "s" => Self::Squash,
+            "fixup" | "f" => Self::Fixup,
+            "exec" | "x" => Self::Exec,
+            "break" | "b" => Self::Break,
+            "drop" | "d" => Self::Drop,
+            "label" | "l" => Self::Label,
+            "reset" | "t" => Self::Reset,
+            "merge" | "m" => Self::Merge,
+            "update-ref" | "u" => Self::UpdateRef,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Pick => "pick",
+            Self::Reword => "reword",
+            Self::Edit => "edit",
+            Self::Squash => "squash",
+            Self::Fixup => "fixup",
+            Self::Exec => "exec",
+            Self::Break => "break",
+            Self::Drop => "drop",
+            Self::Label => "label",
+            Self::Reset => "reset",
+            Self::Merge => "merge",
+            Self::UpdateRef => "update-ref",
+        }
+    }
+
+    /// Whether the command applies to a single commit, and can be replaced by another such
+    /// command.
+    pub fn applies_to_commit(self) -> bool {
+        matches!(
+            self,
+            Self::Pick | Self::Reword | Self::Edit | Self::Squash | Self::Fixup | Self::Drop
+        )
+    }
+
+    /// Whether the command melds its commit into the one before it.
+    pub fn is_meld(self) -> bool {
+        matches!(self, Self::Squash | Self::Fixup)
+    }
+}
+
+/// A line of an interactive rebase's todo list. All ranges are byte offsets in the list's text.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RebaseTodoLine {
+    pub row: u32,
+    pub command: RebaseCommand,
+    /// The command as written, which may be abbreviated.
+    pub command_range: Range<usize>,
+    /// The commit the command applies to, or whose message `merge -C` reuses.
+    pub commit_range: Option<Range<usize>>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RebaseTodo {
+    pub lines: Vec<RebaseTodoLine>,
+    /// The comment lines, without their line endings.
+    pub comments: Vec<Range<usize>>,
+}
+
+/// Parses the todo list of an interactive rebase. Lines with unknown commands are skipped, as
+/// git reports them itself once the list is saved.
+pub fn parse_rebase_todo(text: &str) -> RebaseTodo {
+    let mut todo = RebaseTodo::default();
+    let mut offset = 0;
+    for (row, line) in text.split_inclusive('\n').enumerate() {
+        let line_start = offset;
+        offset += line.len();
+        let line = line.trim_end_matches(['\n', '\r']);
+        let trimmed = line.trim_start();
+        let indent = line.len() - trimmed.len();
+
+        if trimmed.starts_with('#') {
+            todo.comments
+                .push(line_start + indent..line_start + line.len());
+            continue;
+        }
+
+        let mut words = words_with_offsets(trimmed).map(|(start, word)| {
+            let start = line_start + indent + start;
+            (start..start + word.len(), word)
+        });
+        let Some((command_range, command)) = words
+            .next()
+            .and_then(|(range, word)| Some((range, RebaseCommand::parse(word)?)))
+        else {
+            continue;
+        };
+
+        // `fixup -C` and `merge -C` take an option before the commit.
+        let commit_range = match words.next() {
+            Some((_, word))
+                if word.starts_with('-')
+                    && matches!(command, RebaseCommand::Fixup | RebaseCommand::Merge) =>
+            {
+                words.next().map(|(range, _)| range)
+            }
+            Some((range, _)) if command.applies_to_commit() => Some(range),
+            _ => None,
+        };
+
+        todo.lines.push(RebaseTodoLine {
+            row: row as u32,
+            command,
+            command_range,
+            commit_range,
+        });
+    }
+    todo
+}
+
+/// Returns the whitespace-separated words of the text along with their offsets in it.
+fn words_with_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_whitespace()
+        .map(move |word| (word.as_ptr() as usize - text.as_ptr() as usize, word))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use unindent::Unindent as _;
+
+    #[test]
+    fn test_parse_rebase_todo() {
+        let text = "
+            pick 1234567 Add the parser
+            s 89abcde Fix the parser
+            fixup -C fedcba9 Fix the parser again
+            exec cargo test
+            frobnicate 7654321 Unknown
+            merge -C 0011223 feature # Merge branch 'feature'
+
+            # Rebase 1234567..fedcba9 onto 7654321 (3 commands)
+        "
+        .unindent();
+        let todo = parse_rebase_todo(&text);
+
+        let commands = todo
+            .lines
+            .iter()
+            .map(|line| {
+                (
+                    line.row,
+                    line.command,
+                    &text[line.command_range.clone()],
+                    line.commit_range.clone().map(|range| &text[range]),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            commands,
+            [
+                (0, RebaseCommand::Pick, "pick", Some("1234567")),
+                (1, RebaseCommand::Squash, "s", Some("89abcde")),
+                (2, RebaseCommand::Fixup, "fixup", Some("fedcba9")),
+                (3, RebaseCommand::Exec, "exec", None),
+                (5, RebaseCommand::Merge, "merge", Some("0011223")),
+            ]
+        );
+        assert_eq!(
+            todo.comments
+                .iter()
+                .map(|range| &text[range.clone()])
+                .collect::<Vec<_>>(),
+            ["# Rebase 1234567..fedcba9 onto 7654321 (3 commands)"]
+        );
+    }
+}
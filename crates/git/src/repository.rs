@@ -1,5 +1,10 @@
 use crate::GitHostingProviderRegistry;
-use crate::{blame::Blame, status::GitStatus};
+use crate::{
+    blame::Blame,
+    commit::CommitSummary,
+    status::{FileChange, FileChangeKind, GitStatus},
+    worktree::GitWorktree,
+};
 use anyhow::{Context, Result};
 use collections::HashMap;
 use git2::BranchType;
@@ -18,17 +23,94 @@ use util::ResultExt;
 pub struct Branch {
     pub is_head: bool,
     pub name: Box<str>,
+    /// Whether this is a remote-tracking branch, like `origin/main`.
+    pub is_remote: bool,
     /// Timestamp of most recent commit, normalized to Unix Epoch format.
     pub unix_timestamp: Option<i64>,
+    /// The remote-tracking branch a local branch tracks, if any.
+    pub upstream: Option<Box<str>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stash {
+    /// The position of the stash in the stack, the most recent one being `stash@{0}`.
+    pub index: usize,
+    pub message: String,
+}
+
+/// The revision that the working tree contents of a repository's files are compared with, both
+/// in the editor gutter and in diff views.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum DiffBase {
+    /// The staged contents, so that only the unstaged changes are shown.
+    #[default]
+    Index,
+    /// The contents as of the HEAD commit, showing both staged and unstaged changes.
+    Head,
+    /// A branch, a tag or any other revision.
+    Revision(String),
+    /// The common ancestor of HEAD and the given revision, showing the changes made since
+    /// branching off it.
+    MergeBase(String),
+}
+
+impl DiffBase {
+    const MERGE_BASE_PREFIX: &'static str = "merge-base:";
+
+    /// Parses the value of the `zed.diffBase` entry of a repository's git config.
+    pub fn from_config_value(value: &str) -> Self {
+        let value = value.trim();
+        if value.is_empty() || value.eq_ignore_ascii_case("index") {
+            DiffBase::Index
+        } else if value.eq_ignore_ascii_case("head") {
+            DiffBase::Head
+        } else if let Some(revision) = value.strip_prefix(Self::MERGE_BASE_PREFIX) {
+            DiffBase::MergeBase(revision.to_string())
+        } else {
+            DiffBase::Revision(value.to_string())
+        }
+    }
+
+    pub fn to_config_value(&self) -> String {
+        match self {
+            DiffBase::Index => "index".to_string(),
+            DiffBase::Head => "HEAD".to_string(),
+            DiffBase::Revision(revision) => revision.clone(),
+            DiffBase::MergeBase(revision) => format!("{}{revision}", Self::MERGE_BASE_PREFIX),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self {
+            DiffBase::Index => "Index".to_string(),
+            DiffBase::Head => "HEAD".to_string(),
+            DiffBase::Revision(revision) => revision.clone(),
+            DiffBase::MergeBase(revision) => format!("Merge base with {revision}"),
+        }
+    }
 }
 
 pub trait GitRepository: Send + Sync {
     fn reload_index(&self);
 
+    /// Returns the path of the repository's git directory. For submodules and linked worktrees,
+    /// whose `.git` is a file, this is the directory that file points to.
+    fn path(&self) -> PathBuf;
+
+    /// Whether the repository is a submodule of another one, rather than merely nested in its
+    /// working directory.
+    fn is_submodule(&self) -> bool;
+
     /// Loads a git repository entry's contents.
     /// Note that for symlink entries, this will return the contents of the symlink, not the target.
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String>;
 
+    /// Loads a git repository entry's contents as of the HEAD commit.
+    fn load_head_text(&self, relative_file_path: &Path) -> Option<String>;
+
+    /// Replaces the contents of an entry of the index, like staging part of a file's changes does.
+    fn set_index_text(&self, relative_file_path: &Path, content: &str) -> Result<()>;
+
     /// Returns the URL of the remote with the given name.
     fn remote_url(&self, name: &str) -> Option<String>;
     fn branch_name(&self) -> Option<String>;
@@ -42,11 +124,120 @@ pub trait GitRepository: Send + Sync {
         Some(self.statuses(path).ok()?.entries.first()?.1)
     }
 
+    /// Lists the local branches, followed by the remote-tracking ones.
     fn branches(&self) -> Result<Vec<Branch>>;
     fn change_branch(&self, _: &str) -> Result<()>;
-    fn create_branch(&self, _: &str) -> Result<()>;
+    /// Creates a branch pointing at the given revision, or at HEAD when there's none.
+    fn create_branch(&self, name: &str, start_point: Option<&str>) -> Result<()>;
+    fn delete_branch(&self, name: &str) -> Result<()>;
+    fn rename_branch(&self, name: &str, new_name: &str) -> Result<()>;
+    /// Checks out a local branch tracking the given remote-tracking branch, creating it if
+    /// needed, and returns its name.
+    fn checkout_remote_branch(&self, remote_branch: &str) -> Result<String>;
+    /// Returns the number of commits of the given local branch that aren't in its upstream, and
+    /// the number of commits of the upstream that aren't in the branch, if it has an upstream.
+    fn upstream_ahead_behind(&self, branch: &str) -> Result<Option<(usize, usize)>>;
 
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame>;
+
+    /// Lists the files with staged or unstaged changes, including untracked files.
+    fn changes(&self) -> Result<Vec<FileChange>>;
+
+    /// Stages the whole contents of the given files, or their removal when they were deleted.
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Resets the index entries of the given files to their contents in HEAD.
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()>;
+
+    /// Commits the staged changes, returning the output of the commit and its hooks.
+    fn commit(&self, message: &str, amend: bool) -> Result<String>;
+
+    /// Returns the message of the HEAD commit.
+    fn head_commit_message(&self) -> Option<String>;
+
+    /// Returns the distinct authors of the most recent commits, as `Name <email>`.
+    fn commit_authors(&self, max_commits: usize) -> Result<Vec<String>>;
+
+    /// Lists the most recent commits changing the given file, most recent first.
+    fn file_history(&self, path: &Path, max_commits: usize) -> Result<Vec<CommitSummary>>;
+
+    /// Loads a file's contents as of the given revision, or `None` if it didn't exist then.
+    fn load_revision_text(&self, revision: &str, path: &Path) -> Result<Option<String>>;
+
+    /// Returns the revision that file changes are shown against, as configured by the
+    /// `zed.diffBase` entry of the repository's git config.
+    fn diff_base(&self) -> DiffBase;
+
+    /// Stores the revision that file changes are shown against in the repository's git config.
+    fn set_diff_base(&self, diff_base: &DiffBase) -> Result<()>;
+
+    /// Returns the SHA of the best common ancestor of the two revisions.
+    fn merge_base(&self, revision: &str, other_revision: &str) -> Result<String>;
+
+    /// Lists the files whose working copy differs from the given revision, including untracked
+    /// files.
+    fn changed_paths(&self, revision: &str) -> Result<Vec<RepoPath>>;
+
+    fn tags(&self) -> Result<Vec<String>>;
+
+    /// Lists the stashes, most recent first.
+    fn stashes(&self) -> Result<Vec<Stash>>;
+
+    /// Stashes the staged and unstaged changes, including untracked files, with git's default
+    /// message when none is given.
+    fn stash_save(&self, message: Option<&str>) -> Result<()>;
+
+    fn stash_apply(&self, index: usize) -> Result<()>;
+
+    /// Applies the stash and removes it once applied without conflicts.
+    fn stash_pop(&self, index: usize) -> Result<()>;
+
+    fn stash_drop(&self, index: usize) -> Result<()>;
+
+    /// Returns the changes of the stash as a patch, untracked files included.
+    fn stash_diff(&self, index: usize) -> Result<String>;
+
+    /// Lists the repository's main working tree and the ones linked to it with
+    /// `git worktree add`.
+    fn worktrees(&self) -> Result<Vec<GitWorktree>>;
+
+    /// Checks out a branch in a new working tree linked to the repository, creating the branch
+    /// from HEAD when there's no such local branch.
+    fn create_worktree(&self, path: &Path, branch: &str) -> Result<()>;
+
+    /// Returns the repository's [`DiffBase`], with a merge base replaced by the revision it
+    /// currently resolves to, so that it can be used to load the base of many files.
+    fn resolve_diff_base(&self) -> Result<DiffBase> {
+        Ok(match self.diff_base() {
+            DiffBase::MergeBase(revision) => {
+                DiffBase::Revision(self.merge_base("HEAD", &revision)?)
+            }
+            diff_base => diff_base,
+        })
+    }
+
+    /// Loads the contents of a file that its changes are shown against, according to a
+    /// [`DiffBase`] returned by [`GitRepository::resolve_diff_base`].
+    fn load_diff_base_text(
+        &self,
+        diff_base: &DiffBase,
+        relative_file_path: &Path,
+    ) -> Option<String> {
+        match diff_base {
+            DiffBase::Index => self.load_index_text(relative_file_path),
+            DiffBase::Head => self.load_head_text(relative_file_path),
+            DiffBase::Revision(revision) => self
+                .load_revision_text(revision, relative_file_path)
+                .log_err()
+                .flatten(),
+            DiffBase::MergeBase(revision) => {
+                let merge_base = self.merge_base("HEAD", revision).log_err()?;
+                self.load_revision_text(&merge_base, relative_file_path)
+                    .log_err()
+                    .flatten()
+            }
+        }
+    }
 }
 
 impl std::fmt::Debug for dyn GitRepository {
@@ -82,6 +273,19 @@ impl GitRepository for RealGitRepository {
         }
     }
 
+    fn path(&self) -> PathBuf {
+        self.repository.lock().path().to_path_buf()
+    }
+
+    fn is_submodule(&self) -> bool {
+        // The git directories of submodules live in the `modules` directory of their
+        // superproject's git directory.
+        self.path()
+            .ancestors()
+            .skip(1)
+            .any(|ancestor| ancestor.file_name() == Some("modules".as_ref()))
+    }
+
     fn load_index_text(&self, relative_file_path: &Path) -> Option<String> {
         fn logic(repo: &git2::Repository, relative_file_path: &Path) -> Result<Option<String>> {
             const STAGE_NORMAL: i32 = 0;
@@ -106,6 +310,44 @@ impl GitRepository for RealGitRepository {
         None
     }
 
+    fn load_head_text(&self, relative_file_path: &Path) -> Option<String> {
+        fn logic(repo: &git2::Repository, relative_file_path: &Path) -> Result<Option<String>> {
+            check_path_to_repo_path_errors(relative_file_path)?;
+
+            let tree = repo.head()?.peel_to_tree()?;
+            let oid = match tree.get_path(relative_file_path) {
+                Ok(entry) => entry.id(),
+                Err(err) if err.code() == git2::ErrorCode::NotFound => return Ok(None),
+                Err(err) => return Err(err.into()),
+            };
+
+            let content = repo.find_blob(oid)?.content().to_owned();
+            Ok(Some(String::from_utf8(content)?))
+        }
+
+        match logic(&self.repository.lock(), relative_file_path) {
+            Ok(value) => return value,
+            Err(err) => log::error!("Error loading HEAD text: {:?}", err),
+        }
+        None
+    }
+
+    fn set_index_text(&self, relative_file_path: &Path, content: &str) -> Result<()> {
+        const STAGE_NORMAL: i32 = 0;
+        check_path_to_repo_path_errors(relative_file_path)?;
+
+        let repo = self.repository.lock();
+        let mut index = repo.index()?;
+        // The entry keeps its mode, so only the files already in the index can be updated.
+        let mut entry = index
+            .get_path(relative_file_path, STAGE_NORMAL)
+            .with_context(|| format!("{relative_file_path:?} is not in the index"))?;
+        entry.file_size = content.len() as u32;
+        index.add_frombuffer(&entry, content.as_bytes())?;
+        index.write()?;
+        Ok(())
+    }
+
     fn remote_url(&self, name: &str) -> Option<String> {
         let repo = self.repository.lock();
         let remote = repo.find_remote(name).ok()?;
@@ -135,28 +377,36 @@ impl GitRepository for RealGitRepository {
 
     fn branches(&self) -> Result<Vec<Branch>> {
         let repo = self.repository.lock();
-        let local_branches = repo.branches(Some(BranchType::Local))?;
-        let valid_branches = local_branches
-            .filter_map(|branch| {
-                branch.ok().and_then(|(branch, _)| {
-                    let is_head = branch.is_head();
-                    let name = branch.name().ok().flatten().map(Box::from)?;
-                    let timestamp = branch.get().peel_to_commit().ok()?.time();
-                    let unix_timestamp = timestamp.seconds();
-                    let timezone_offset = timestamp.offset_minutes();
-                    let utc_offset =
-                        time::UtcOffset::from_whole_seconds(timezone_offset * 60).ok()?;
-                    let unix_timestamp =
-                        time::OffsetDateTime::from_unix_timestamp(unix_timestamp).ok()?;
-                    Some(Branch {
-                        is_head,
-                        name,
-                        unix_timestamp: Some(unix_timestamp.to_offset(utc_offset).unix_timestamp()),
-                    })
+        let mut branches = Vec::new();
+        for branch_type in [BranchType::Local, BranchType::Remote] {
+            branches.extend(repo.branches(Some(branch_type))?.filter_map(|branch| {
+                let (branch, branch_type) = branch.ok()?;
+                let name = branch.name().ok().flatten().map(Box::from)?;
+                // Skip the symbolic `origin/HEAD` references.
+                if branch.get().symbolic_target().is_some() {
+                    return None;
+                }
+                let commit = branch.get().peel_to_commit().ok()?;
+                let timestamp = commit.time();
+                let unix_timestamp = timestamp.seconds();
+                let timezone_offset = timestamp.offset_minutes();
+                let utc_offset = time::UtcOffset::from_whole_seconds(timezone_offset * 60).ok()?;
+                let unix_timestamp =
+                    time::OffsetDateTime::from_unix_timestamp(unix_timestamp).ok()?;
+                let upstream = branch
+                    .upstream()
+                    .ok()
+                    .and_then(|upstream| upstream.name().ok().flatten().map(Box::from));
+                Some(Branch {
+                    is_head: branch.is_head(),
+                    name,
+                    is_remote: branch_type == BranchType::Remote,
+                    unix_timestamp: Some(unix_timestamp.to_offset(utc_offset).unix_timestamp()),
+                    upstream,
                 })
-            })
-            .collect();
-        Ok(valid_branches)
+            }));
+        }
+        Ok(branches)
     }
 
     fn change_branch(&self, name: &str) -> Result<()> {
@@ -173,13 +423,76 @@ impl GitRepository for RealGitRepository {
         Ok(())
     }
 
-    fn create_branch(&self, name: &str) -> Result<()> {
+    fn create_branch(&self, name: &str, start_point: Option<&str>) -> Result<()> {
+        let repo = self.repository.lock();
+        let start_commit = match start_point {
+            Some(start_point) => repo.revparse_single(start_point)?.peel_to_commit()?,
+            None => repo.head()?.peel_to_commit()?,
+        };
+        repo.branch(name, &start_commit, false)?;
+        Ok(())
+    }
+
+    fn delete_branch(&self, name: &str) -> Result<()> {
         let repo = self.repository.lock();
-        let current_commit = repo.head()?.peel_to_commit()?;
-        repo.branch(name, &current_commit, false)?;
+        let mut branch = repo.find_branch(name, BranchType::Local)?;
+        anyhow::ensure!(
+            !branch.is_head(),
+            "cannot delete the checked out branch '{name}'"
+        );
+        branch.delete()?;
         Ok(())
     }
 
+    fn rename_branch(&self, name: &str, new_name: &str) -> Result<()> {
+        let repo = self.repository.lock();
+        let mut branch = repo.find_branch(name, BranchType::Local)?;
+        branch.rename(new_name, false)?;
+        Ok(())
+    }
+
+    fn checkout_remote_branch(&self, remote_branch: &str) -> Result<String> {
+        let local_name = {
+            let repo = self.repository.lock();
+            let branch = repo.find_branch(remote_branch, BranchType::Remote)?;
+            let reference_name = branch
+                .get()
+                .name()
+                .context("remote branch name is not valid UTF-8")?;
+            let remote_name = repo.branch_remote_name(reference_name)?;
+            let remote_name = remote_name
+                .as_str()
+                .context("remote name is not valid UTF-8")?;
+            let local_name = remote_branch
+                .strip_prefix(remote_name)
+                .and_then(|name| name.strip_prefix('/'))
+                .with_context(|| format!("'{remote_branch}' is not a branch of '{remote_name}'"))?
+                .to_string();
+
+            if repo.find_branch(&local_name, BranchType::Local).is_err() {
+                let commit = branch.get().peel_to_commit()?;
+                let mut local_branch = repo.branch(&local_name, &commit, false)?;
+                local_branch.set_upstream(Some(remote_branch))?;
+            }
+            local_name
+        };
+        self.change_branch(&local_name)?;
+        Ok(local_name)
+    }
+
+    fn upstream_ahead_behind(&self, branch: &str) -> Result<Option<(usize, usize)>> {
+        let repo = self.repository.lock();
+        let branch = repo.find_branch(branch, BranchType::Local)?;
+        let Ok(upstream) = branch.upstream() else {
+            return Ok(None);
+        };
+        let commit = branch.get().peel_to_commit()?;
+        let upstream_commit = upstream.get().peel_to_commit()?;
+        Ok(Some(
+            repo.graph_ahead_behind(commit.id(), upstream_commit.id())?,
+        ))
+    }
+
     fn blame(&self, path: &Path, content: Rope) -> Result<crate::blame::Blame> {
         let working_directory = self
             .repository
@@ -200,6 +513,336 @@ impl GitRepository for RealGitRepository {
             self.hosting_provider_registry.clone(),
         )
     }
+
+    fn changes(&self) -> Result<Vec<FileChange>> {
+        let repo = self.repository.lock();
+        let mut options = git2::StatusOptions::new();
+        options
+            .include_untracked(true)
+            .recurse_untracked_dirs(true)
+            .include_ignored(false);
+        let statuses = repo.statuses(Some(&mut options))?;
+
+        let mut changes = statuses
+            .iter()
+            .filter_map(|entry| {
+                let path = RepoPath::new(PathBuf::from(entry.path()?));
+                let status = entry.status();
+                let (staged, unstaged) = if status.is_conflicted() {
+                    (None, Some(FileChangeKind::Conflict))
+                } else {
+                    (
+                        file_change_kind(
+                            status,
+                            git2::Status::INDEX_NEW,
+                            git2::Status::INDEX_DELETED,
+                            git2::Status::INDEX_MODIFIED
+                                | git2::Status::INDEX_RENAMED
+                                | git2::Status::INDEX_TYPECHANGE,
+                        ),
+                        file_change_kind(
+                            status,
+                            git2::Status::WT_NEW,
+                            git2::Status::WT_DELETED,
+                            git2::Status::WT_MODIFIED
+                                | git2::Status::WT_RENAMED
+                                | git2::Status::WT_TYPECHANGE,
+                        ),
+                    )
+                };
+                (staged.is_some() || unstaged.is_some()).then_some(FileChange {
+                    path,
+                    staged,
+                    unstaged,
+                })
+            })
+            .collect::<Vec<_>>();
+        changes.sort_unstable_by(|a, b| a.path.cmp(&b.path));
+        Ok(changes)
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        let workdir = repo
+            .workdir()
+            .context("failed to read git work directory")?;
+        let mut index = repo.index()?;
+        for path in paths {
+            check_path_to_repo_path_errors(path)?;
+            if workdir.join(path).exists() {
+                index.add_path(path)?;
+            } else {
+                index.remove_path(path)?;
+            }
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let repo = self.repository.lock();
+        match repo.head().and_then(|head| head.peel_to_commit()) {
+            Ok(head) => {
+                repo.reset_default(
+                    Some(head.as_object()),
+                    paths.iter().map(|path| path.as_path()),
+                )?;
+            }
+            // Without a HEAD commit, unstaging a file removes it from the index.
+            Err(_) => {
+                let mut index = repo.index()?;
+                for path in paths {
+                    index.remove_path(path)?;
+                }
+                index.write()?;
+            }
+        }
+        Ok(())
+    }
+
+    fn commit(&self, message: &str, amend: bool) -> Result<String> {
+        let working_directory = self
+            .repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_owned();
+        crate::commit::commit(&self.git_binary_path, &working_directory, message, amend)
+    }
+
+    fn head_commit_message(&self) -> Option<String> {
+        let repo = self.repository.lock();
+        let head = repo.head().ok()?.peel_to_commit().ok()?;
+        head.message().map(|message| message.to_string())
+    }
+
+    fn commit_authors(&self, max_commits: usize) -> Result<Vec<String>> {
+        let repo = self.repository.lock();
+        let mut revwalk = repo.revwalk()?;
+        if revwalk.push_head().is_err() {
+            return Ok(Vec::new());
+        }
+
+        let mut authors = Vec::new();
+        for oid in revwalk.take(max_commits) {
+            let commit = repo.find_commit(oid?)?;
+            let author = commit.author();
+            let (Some(name), Some(email)) = (author.name(), author.email()) else {
+                continue;
+            };
+            let author = format!("{name} <{email}>");
+            if !authors.contains(&author) {
+                authors.push(author);
+            }
+        }
+        Ok(authors)
+    }
+
+    fn file_history(&self, path: &Path, max_commits: usize) -> Result<Vec<CommitSummary>> {
+        let working_directory = self
+            .repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        crate::commit::file_history(&self.git_binary_path, &working_directory, path, max_commits)
+    }
+
+    fn load_revision_text(&self, revision: &str, path: &Path) -> Result<Option<String>> {
+        check_path_to_repo_path_errors(path)?;
+        let repo = self.repository.lock();
+        let tree = repo.revparse_single(revision)?.peel_to_tree()?;
+        let Ok(entry) = tree.get_path(path) else {
+            return Ok(None);
+        };
+        let content = repo.find_blob(entry.id())?.content().to_owned();
+        Ok(Some(String::from_utf8(content)?))
+    }
+
+    fn diff_base(&self) -> DiffBase {
+        let repo = self.repository.lock();
+        repo.config()
+            .and_then(|mut config| config.snapshot()?.get_string(DIFF_BASE_CONFIG_KEY))
+            .map(|value| DiffBase::from_config_value(&value))
+            .unwrap_or_default()
+    }
+
+    fn set_diff_base(&self, diff_base: &DiffBase) -> Result<()> {
+        let mut config = self.repository.lock().config()?;
+        if *diff_base == DiffBase::Index {
+            match config.remove(DIFF_BASE_CONFIG_KEY) {
+                Err(error) if error.code() != git2::ErrorCode::NotFound => return Err(error.into()),
+                _ => {}
+            }
+        } else {
+            config.set_str(DIFF_BASE_CONFIG_KEY, &diff_base.to_config_value())?;
+        }
+        Ok(())
+    }
+
+    fn merge_base(&self, revision: &str, other_revision: &str) -> Result<String> {
+        let repo = self.repository.lock();
+        let commit = repo.revparse_single(revision)?.peel_to_commit()?;
+        let other_commit = repo.revparse_single(other_revision)?.peel_to_commit()?;
+        let merge_base = repo
+            .merge_base(commit.id(), other_commit.id())
+            .with_context(|| format!("{revision} and {other_revision} have no common ancestor"))?;
+        Ok(merge_base.to_string())
+    }
+
+    fn changed_paths(&self, revision: &str) -> Result<Vec<RepoPath>> {
+        let repo = self.repository.lock();
+        let tree = repo.revparse_single(revision)?.peel_to_tree()?;
+        let mut options = git2::DiffOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let diff = repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut options))?;
+
+        let mut paths = diff
+            .deltas()
+            .filter_map(|delta| {
+                let path = delta
+                    .new_file()
+                    .path()
+                    .or_else(|| delta.old_file().path())?;
+                Some(RepoPath::new(path.to_path_buf()))
+            })
+            .collect::<Vec<_>>();
+        paths.sort();
+        paths.dedup();
+        Ok(paths)
+    }
+
+    fn tags(&self) -> Result<Vec<String>> {
+        let repo = self.repository.lock();
+        let tags = repo.tag_names(None)?;
+        Ok(tags.iter().flatten().map(str::to_string).collect())
+    }
+
+    fn stashes(&self) -> Result<Vec<Stash>> {
+        let mut repo = self.repository.lock();
+        let mut stashes = Vec::new();
+        repo.stash_foreach(|index, message, _| {
+            stashes.push(Stash {
+                index,
+                message: message.to_string(),
+            });
+            true
+        })?;
+        Ok(stashes)
+    }
+
+    fn stash_save(&self, message: Option<&str>) -> Result<()> {
+        let mut repo = self.repository.lock();
+        let signature = repo.signature()?;
+        repo.stash_save2(
+            &signature,
+            message,
+            Some(git2::StashFlags::INCLUDE_UNTRACKED),
+        )
+        .context("failed to stash the changes")?;
+        Ok(())
+    }
+
+    fn stash_apply(&self, index: usize) -> Result<()> {
+        let mut repo = self.repository.lock();
+        repo.stash_apply(index, None)
+            .with_context(|| format!("failed to apply stash@{{{index}}}"))?;
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize) -> Result<()> {
+        let mut repo = self.repository.lock();
+        repo.stash_pop(index, None)
+            .with_context(|| format!("failed to pop stash@{{{index}}}"))?;
+        Ok(())
+    }
+
+    fn stash_drop(&self, index: usize) -> Result<()> {
+        let mut repo = self.repository.lock();
+        repo.stash_drop(index)
+            .with_context(|| format!("failed to drop stash@{{{index}}}"))?;
+        Ok(())
+    }
+
+    fn stash_diff(&self, index: usize) -> Result<String> {
+        let mut repo = self.repository.lock();
+        let mut stash_oid = None;
+        repo.stash_foreach(|stash_index, _, oid| {
+            if stash_index == index {
+                stash_oid = Some(*oid);
+            }
+            stash_oid.is_none()
+        })?;
+        let stash_oid = stash_oid.with_context(|| format!("no stash at index {index}"))?;
+
+        // A stash is a merge commit of HEAD, with the index as its second parent and the
+        // untracked files as its third.
+        let stash = repo.find_commit(stash_oid)?;
+        let head_tree = stash.parent(0)?.tree()?;
+        let mut diff = repo.diff_tree_to_tree(Some(&head_tree), Some(&stash.tree()?), None)?;
+        if let Ok(untracked) = stash.parent(2) {
+            let untracked_diff = repo.diff_tree_to_tree(None, Some(&untracked.tree()?), None)?;
+            diff.merge(&untracked_diff)?;
+        }
+
+        let mut patch = String::new();
+        diff.print(git2::DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                patch.push(line.origin());
+            }
+            patch.push_str(&String::from_utf8_lossy(line.content()));
+            true
+        })?;
+        Ok(patch)
+    }
+
+    fn worktrees(&self) -> Result<Vec<GitWorktree>> {
+        let working_directory = self
+            .repository
+            .lock()
+            .workdir()
+            .context("failed to read git work directory")?
+            .to_path_buf();
+        crate::worktree::list_worktrees(&self.git_binary_path, &working_directory)
+    }
+
+    fn create_worktree(&self, path: &Path, branch: &str) -> Result<()> {
+        let (working_directory, branch_exists) = {
+            let repo = self.repository.lock();
+            let working_directory = repo
+                .workdir()
+                .context("failed to read git work directory")?
+                .to_path_buf();
+            let branch_exists = repo.find_branch(branch, BranchType::Local).is_ok();
+            (working_directory, branch_exists)
+        };
+        crate::worktree::create_worktree(
+            &self.git_binary_path,
+            &working_directory,
+            path,
+            branch,
+            !branch_exists,
+        )
+    }
+}
+
+const DIFF_BASE_CONFIG_KEY: &str = "zed.diffBase";
+
+fn file_change_kind(
+    status: git2::Status,
+    added: git2::Status,
+    deleted: git2::Status,
+    modified: git2::Status,
+) -> Option<FileChangeKind> {
+    if status.intersects(added) {
+        Some(FileChangeKind::Added)
+    } else if status.intersects(deleted) {
+        Some(FileChangeKind::Deleted)
+    } else if status.intersects(modified) {
+        Some(FileChangeKind::Modified)
+    } else {
+        None
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -209,10 +852,42 @@ pub struct FakeGitRepository {
 
 #[derive(Debug, Clone, Default)]
 pub struct FakeGitRepositoryState {
+    pub path: PathBuf,
+    pub is_submodule: bool,
     pub index_contents: HashMap<PathBuf, String>,
+    pub head_contents: HashMap<PathBuf, String>,
     pub blames: HashMap<PathBuf, Blame>,
     pub worktree_statuses: HashMap<RepoPath, GitFileStatus>,
     pub branch_name: Option<String>,
+    /// The local and remote-tracking branches, the one named `branch_name` being checked out.
+    pub branches: Vec<Branch>,
+    /// The commits ahead and behind of local branches compared to their upstream, keyed by
+    /// branch name.
+    pub upstream_ahead_behind: HashMap<String, (usize, usize)>,
+    pub file_changes: Vec<FileChange>,
+    pub commits: Vec<String>,
+    pub commit_authors: Vec<String>,
+    pub file_histories: HashMap<PathBuf, Vec<CommitSummary>>,
+    /// The contents of files keyed by revision and path.
+    pub revision_contents: HashMap<(String, PathBuf), String>,
+    pub diff_base: DiffBase,
+    pub tags: Vec<String>,
+    /// The merge bases of pairs of revisions.
+    pub merge_bases: HashMap<(String, String), String>,
+    /// The files differing from the working copy, keyed by revision.
+    pub changed_paths: HashMap<String, Vec<RepoPath>>,
+    pub stashes: Vec<Stash>,
+    /// The patches of the stashes, keyed by their message.
+    pub stash_diffs: HashMap<String, String>,
+    pub worktrees: Vec<GitWorktree>,
+}
+
+impl FakeGitRepositoryState {
+    fn local_branch_index(&self, name: &str) -> Option<usize> {
+        self.branches
+            .iter()
+            .position(|branch| !branch.is_remote && *branch.name == *name)
+    }
 }
 
 impl FakeGitRepository {
@@ -224,11 +899,34 @@ impl FakeGitRepository {
 impl GitRepository for FakeGitRepository {
     fn reload_index(&self) {}
 
+    fn path(&self) -> PathBuf {
+        self.state.lock().path.clone()
+    }
+
+    fn is_submodule(&self) -> bool {
+        self.state.lock().is_submodule
+    }
+
     fn load_index_text(&self, path: &Path) -> Option<String> {
         let state = self.state.lock();
         state.index_contents.get(path).cloned()
     }
 
+    fn load_head_text(&self, path: &Path) -> Option<String> {
+        let state = self.state.lock();
+        state.head_contents.get(path).cloned()
+    }
+
+    fn set_index_text(&self, path: &Path, content: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        let index_text = state
+            .index_contents
+            .get_mut(path)
+            .with_context(|| format!("{path:?} is not in the index"))?;
+        *index_text = content.to_string();
+        Ok(())
+    }
+
     fn remote_url(&self, _name: &str) -> Option<String> {
         None
     }
@@ -262,7 +960,15 @@ impl GitRepository for FakeGitRepository {
     }
 
     fn branches(&self) -> Result<Vec<Branch>> {
-        Ok(vec![])
+        let state = self.state.lock();
+        Ok(state
+            .branches
+            .iter()
+            .map(|branch| Branch {
+                is_head: !branch.is_remote && state.branch_name.as_deref() == Some(&branch.name),
+                ..branch.clone()
+            })
+            .collect())
     }
 
     fn change_branch(&self, name: &str) -> Result<()> {
@@ -271,12 +977,81 @@ impl GitRepository for FakeGitRepository {
         Ok(())
     }
 
-    fn create_branch(&self, name: &str) -> Result<()> {
+    fn create_branch(&self, name: &str, _start_point: Option<&str>) -> Result<()> {
         let mut state = self.state.lock();
-        state.branch_name = Some(name.to_owned());
+        anyhow::ensure!(
+            state.local_branch_index(name).is_none(),
+            "a branch named '{name}' already exists"
+        );
+        state.branches.push(Branch {
+            is_head: false,
+            name: name.into(),
+            is_remote: false,
+            unix_timestamp: None,
+            upstream: None,
+        });
+        Ok(())
+    }
+
+    fn delete_branch(&self, name: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        anyhow::ensure!(
+            state.branch_name.as_deref() != Some(name),
+            "cannot delete the checked out branch '{name}'"
+        );
+        let index = state
+            .local_branch_index(name)
+            .with_context(|| format!("no branch named '{name}'"))?;
+        state.branches.remove(index);
         Ok(())
     }
 
+    fn rename_branch(&self, name: &str, new_name: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        anyhow::ensure!(
+            state.local_branch_index(new_name).is_none(),
+            "a branch named '{new_name}' already exists"
+        );
+        let index = state
+            .local_branch_index(name)
+            .with_context(|| format!("no branch named '{name}'"))?;
+        state.branches[index].name = new_name.into();
+        if state.branch_name.as_deref() == Some(name) {
+            state.branch_name = Some(new_name.to_owned());
+        }
+        Ok(())
+    }
+
+    fn checkout_remote_branch(&self, remote_branch: &str) -> Result<String> {
+        let mut state = self.state.lock();
+        anyhow::ensure!(
+            state
+                .branches
+                .iter()
+                .any(|branch| branch.is_remote && *branch.name == *remote_branch),
+            "no remote branch named '{remote_branch}'"
+        );
+        let (_, local_name) = remote_branch
+            .split_once('/')
+            .with_context(|| format!("'{remote_branch}' is not a remote branch"))?;
+        if state.local_branch_index(local_name).is_none() {
+            state.branches.push(Branch {
+                is_head: false,
+                name: local_name.into(),
+                is_remote: false,
+                unix_timestamp: None,
+                upstream: Some(remote_branch.into()),
+            });
+        }
+        state.branch_name = Some(local_name.to_owned());
+        Ok(local_name.to_owned())
+    }
+
+    fn upstream_ahead_behind(&self, branch: &str) -> Result<Option<(usize, usize)>> {
+        let state = self.state.lock();
+        Ok(state.upstream_ahead_behind.get(branch).copied())
+    }
+
     fn blame(&self, path: &Path, _content: Rope) -> Result<crate::blame::Blame> {
         let state = self.state.lock();
         state
@@ -285,6 +1060,181 @@ impl GitRepository for FakeGitRepository {
             .with_context(|| format!("failed to get blame for {:?}", path))
             .cloned()
     }
+
+    fn changes(&self) -> Result<Vec<FileChange>> {
+        let state = self.state.lock();
+        Ok(state.file_changes.clone())
+    }
+
+    fn stage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for change in &mut state.file_changes {
+            if paths.contains(&change.path) {
+                change.staged = change.staged.or(change.unstaged.take());
+            }
+        }
+        Ok(())
+    }
+
+    fn unstage_paths(&self, paths: &[RepoPath]) -> Result<()> {
+        let mut state = self.state.lock();
+        for change in &mut state.file_changes {
+            if paths.contains(&change.path) {
+                change.unstaged = change.unstaged.or(change.staged.take());
+            }
+        }
+        Ok(())
+    }
+
+    fn commit(&self, message: &str, amend: bool) -> Result<String> {
+        let mut state = self.state.lock();
+        anyhow::ensure!(
+            amend
+                || state
+                    .file_changes
+                    .iter()
+                    .any(|change| change.staged.is_some()),
+            "nothing to commit"
+        );
+        state.file_changes.retain_mut(|change| {
+            change.staged = None;
+            change.unstaged.is_some()
+        });
+        if amend {
+            state.commits.pop();
+        }
+        state.commits.push(message.to_string());
+        Ok(String::new())
+    }
+
+    fn head_commit_message(&self) -> Option<String> {
+        let state = self.state.lock();
+        state.commits.last().cloned()
+    }
+
+    fn commit_authors(&self, max_commits: usize) -> Result<Vec<String>> {
+        let state = self.state.lock();
+        Ok(state
+            .commit_authors
+            .iter()
+            .take(max_commits)
+            .cloned()
+            .collect())
+    }
+
+    fn file_history(&self, path: &Path, max_commits: usize) -> Result<Vec<CommitSummary>> {
+        let state = self.state.lock();
+        let history = state.file_histories.get(path).into_iter().flatten();
+        Ok(history.take(max_commits).cloned().collect())
+    }
+
+    fn load_revision_text(&self, revision: &str, path: &Path) -> Result<Option<String>> {
+        let state = self.state.lock();
+        let key = (revision.to_string(), path.to_path_buf());
+        Ok(state.revision_contents.get(&key).cloned())
+    }
+
+    fn diff_base(&self) -> DiffBase {
+        self.state.lock().diff_base.clone()
+    }
+
+    fn set_diff_base(&self, diff_base: &DiffBase) -> Result<()> {
+        self.state.lock().diff_base = diff_base.clone();
+        Ok(())
+    }
+
+    fn merge_base(&self, revision: &str, other_revision: &str) -> Result<String> {
+        let state = self.state.lock();
+        let key = (revision.to_string(), other_revision.to_string());
+        state
+            .merge_bases
+            .get(&key)
+            .cloned()
+            .with_context(|| format!("{revision} and {other_revision} have no common ancestor"))
+    }
+
+    fn changed_paths(&self, revision: &str) -> Result<Vec<RepoPath>> {
+        let state = self.state.lock();
+        state
+            .changed_paths
+            .get(revision)
+            .cloned()
+            .with_context(|| format!("revision {revision} not found"))
+    }
+
+    fn tags(&self) -> Result<Vec<String>> {
+        Ok(self.state.lock().tags.clone())
+    }
+
+    fn stashes(&self) -> Result<Vec<Stash>> {
+        Ok(self.state.lock().stashes.clone())
+    }
+
+    fn stash_save(&self, message: Option<&str>) -> Result<()> {
+        let mut state = self.state.lock();
+        let message = message.map_or_else(
+            || format!("WIP on {}", state.branch_name.as_deref().unwrap_or("HEAD")),
+            str::to_string,
+        );
+        state.stashes.insert(0, Stash { index: 0, message });
+        for (index, stash) in state.stashes.iter_mut().enumerate() {
+            stash.index = index;
+        }
+        Ok(())
+    }
+
+    fn stash_apply(&self, index: usize) -> Result<()> {
+        let state = self.state.lock();
+        anyhow::ensure!(index < state.stashes.len(), "no stash at index {index}");
+        Ok(())
+    }
+
+    fn stash_pop(&self, index: usize) -> Result<()> {
+        self.stash_apply(index)?;
+        self.stash_drop(index)
+    }
+
+    fn stash_drop(&self, index: usize) -> Result<()> {
+        let mut state = self.state.lock();
+        anyhow::ensure!(index < state.stashes.len(), "no stash at index {index}");
+        state.stashes.remove(index);
+        for (index, stash) in state.stashes.iter_mut().enumerate() {
+            stash.index = index;
+        }
+        Ok(())
+    }
+
+    fn stash_diff(&self, index: usize) -> Result<String> {
+        let state = self.state.lock();
+        let stash = state
+            .stashes
+            .get(index)
+            .with_context(|| format!("no stash at index {index}"))?;
+        Ok(state
+            .stash_diffs
+            .get(&stash.message)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn worktrees(&self) -> Result<Vec<GitWorktree>> {
+        Ok(self.state.lock().worktrees.clone())
+    }
+
+    fn create_worktree(&self, path: &Path, branch: &str) -> Result<()> {
+        let mut state = self.state.lock();
+        if state.worktrees.iter().any(|worktree| worktree.path == path) {
+            anyhow::bail!("{path:?} already exists");
+        }
+        state.worktrees.push(GitWorktree {
+            path: path.to_path_buf(),
+            branch: Some(branch.to_string()),
+            head_sha: None,
+            is_main: false,
+            is_locked: false,
+        });
+        Ok(())
+    }
 }
 
 fn check_path_to_repo_path_errors(relative_file_path: &Path) -> Result<()> {
@@ -403,3 +1353,179 @@ impl<'a> MapSeekTarget<RepoPath> for RepoPathDescendants<'a> {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff_base_config_value() {
+        for diff_base in [
+            DiffBase::Index,
+            DiffBase::Head,
+            DiffBase::Revision("v1.0".to_string()),
+            DiffBase::MergeBase("main".to_string()),
+        ] {
+            assert_eq!(
+                DiffBase::from_config_value(&diff_base.to_config_value()),
+                diff_base
+            );
+        }
+
+        assert_eq!(DiffBase::from_config_value(""), DiffBase::Index);
+        assert_eq!(DiffBase::from_config_value(" head\n"), DiffBase::Head);
+        assert_eq!(
+            DiffBase::from_config_value("merge-base:origin/main"),
+            DiffBase::MergeBase("origin/main".to_string())
+        );
+        assert_eq!(
+            DiffBase::from_config_value("a1b2c3"),
+            DiffBase::Revision("a1b2c3".to_string())
+        );
+    }
+
+    #[test]
+    fn test_resolve_diff_base() {
+        let path = Path::new("a.txt");
+        let state = Arc::new(Mutex::new(FakeGitRepositoryState {
+            diff_base: DiffBase::MergeBase("main".to_string()),
+            ..Default::default()
+        }));
+        let repository = FakeGitRepository::open(state.clone());
+        assert!(repository.resolve_diff_base().is_err());
+
+        {
+            let mut state = state.lock();
+            state.merge_bases.insert(
+                ("HEAD".to_string(), "main".to_string()),
+                "abc123".to_string(),
+            );
+            state.revision_contents.insert(
+                ("abc123".to_string(), path.to_path_buf()),
+                "one\n".to_string(),
+            );
+        }
+        let diff_base = repository.resolve_diff_base().unwrap();
+        assert_eq!(diff_base, DiffBase::Revision("abc123".to_string()));
+        assert_eq!(
+            repository.load_diff_base_text(&diff_base, path).as_deref(),
+            Some("one\n")
+        );
+
+        state.lock().diff_base = DiffBase::Head;
+        assert_eq!(repository.resolve_diff_base().unwrap(), DiffBase::Head);
+    }
+
+    #[test]
+    fn test_real_repository_staging_and_committing() {
+        let dir = util::test::temp_tree(serde_json::json!({
+            "a.txt": "one\n",
+            "b.txt": "two\n",
+        }));
+        let repo = git2::Repository::init(dir.path()).unwrap();
+        {
+            let mut config = repo.config().unwrap();
+            config.set_str("user.name", "Test").unwrap();
+            config.set_str("user.email", "test@example.com").unwrap();
+            config.set_bool("commit.gpgsign", false).unwrap();
+            config.set_str("core.hooksPath", ".git/hooks").unwrap();
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt as _;
+            let hook_path = dir.path().join(".git/hooks/post-commit");
+            std::fs::create_dir_all(hook_path.parent().unwrap()).unwrap();
+            std::fs::write(&hook_path, "#!/bin/sh\necho 'post-commit hook ran'\n").unwrap();
+            std::fs::set_permissions(&hook_path, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let repository =
+            RealGitRepository::new(repo, None, Arc::new(GitHostingProviderRegistry::new()));
+        let a = RepoPath::from(Path::new("a.txt"));
+        let b = RepoPath::from(Path::new("b.txt"));
+        let change = |path: &RepoPath, staged, unstaged| FileChange {
+            path: path.clone(),
+            staged,
+            unstaged,
+        };
+
+        // Without a HEAD commit, unstaging a new file removes it from the index.
+        assert_eq!(
+            repository.changes().unwrap(),
+            [
+                change(&a, None, Some(FileChangeKind::Added)),
+                change(&b, None, Some(FileChangeKind::Added)),
+            ]
+        );
+        repository.stage_paths(&[a.clone()]).unwrap();
+        assert_eq!(
+            repository.changes().unwrap(),
+            [
+                change(&a, Some(FileChangeKind::Added), None),
+                change(&b, None, Some(FileChangeKind::Added)),
+            ]
+        );
+        repository.unstage_paths(&[a.clone()]).unwrap();
+        assert_eq!(
+            repository.changes().unwrap(),
+            [
+                change(&a, None, Some(FileChangeKind::Added)),
+                change(&b, None, Some(FileChangeKind::Added)),
+            ]
+        );
+
+        // Committing runs the repository's hooks and returns their output.
+        repository.stage_paths(&[a.clone(), b.clone()]).unwrap();
+        let output = repository.commit("Initial commit", false).unwrap();
+        assert!(output.contains("Initial commit"), "{output}");
+        #[cfg(unix)]
+        assert!(output.contains("post-commit hook ran"), "{output}");
+        assert!(repository.changes().unwrap().is_empty());
+        assert_eq!(
+            repository.head_commit_message().as_deref(),
+            Some("Initial commit\n")
+        );
+
+        // Deleted files are staged as removals, and unstaging resets a file to HEAD.
+        std::fs::write(dir.path().join("a.txt"), "three\n").unwrap();
+        std::fs::remove_file(dir.path().join("b.txt")).unwrap();
+        repository.stage_paths(&[a.clone(), b.clone()]).unwrap();
+        assert_eq!(
+            repository.changes().unwrap(),
+            [
+                change(&a, Some(FileChangeKind::Modified), None),
+                change(&b, Some(FileChangeKind::Deleted), None),
+            ]
+        );
+        repository.unstage_paths(&[a.clone()]).unwrap();
+        assert_eq!(
+            repository.changes().unwrap(),
+            [
+                change(&a, None, Some(FileChangeKind::Modified)),
+                change(&b, Some(FileChangeKind::Deleted), None),
+            ]
+        );
+
+        // Amending replaces the HEAD commit rather than adding one.
+        repository.commit("Remove b", true).unwrap();
+        assert_eq!(
+            repository.head_commit_message().as_deref(),
+            Some("Remove b\n")
+        );
+        {
+            let repo = repository.repository.lock();
+            let head = repo.head().unwrap().peel_to_commit().unwrap();
+            assert_eq!(head.parent_count(), 0);
+        }
+        assert_eq!(
+            repository.changes().unwrap(),
+            [change(&a, None, Some(FileChangeKind::Modified))]
+        );
+
+        // Committing without staged changes fails with git's output.
+        let error = repository.commit("Nothing", false).unwrap_err();
+        assert!(
+            error.to_string().starts_with("git commit failed"),
+            "{error}"
+        );
+    }
+}
@@ -97,3 +97,22 @@ impl Default for GitStatus {
         }
     }
 }
+
+/// The kind of change made to a file, on either side of the index.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileChangeKind {
+    Added,
+    Modified,
+    Deleted,
+    Conflict,
+}
+
+/// A file with changes staged in the index, changes in the working tree, or both.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub path: RepoPath,
+    /// The change between HEAD and the index.
+    pub staged: Option<FileChangeKind>,
+    /// The change between the index and the working tree.
+    pub unstaged: Option<FileChangeKind>,
+}
@@ -0,0 +1,178 @@
+use anyhow::{anyhow, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+
+/// A working tree of a repository, either its main one or one linked to it with
+/// `git worktree add`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitWorktree {
+    pub path: PathBuf,
+    /// The branch checked out in the working tree, or `None` when its HEAD is detached.
+    pub branch: Option<String>,
+    /// The SHA of the working tree's HEAD, or `None` when there are no commits yet.
+    pub head_sha: Option<String>,
+    pub is_main: bool,
+    pub is_locked: bool,
+}
+
+/// Lists the working trees of the repository, the main one first.
+pub fn list_worktrees(git_binary: &Path, working_directory: &Path) -> Result<Vec<GitWorktree>> {
+    let mut command = Command::new(git_binary);
+
+    command
+        .current_dir(working_directory)
+        .args(["worktree", "list", "--porcelain"]);
+
+    #[cfg(windows)]
+    command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+
+    let output = command
+        .output()
+        .map_err(|e| anyhow!("Failed to start git worktree process: {}", e))?;
+
+    anyhow::ensure!(
+        output.status.success(),
+        "'git worktree list' failed with error {:?}: {}",
+        output.status,
+        String::from_utf8_lossy(&output.stderr)
+    );
+
+    Ok(parse_worktree_list(&String::from_utf8_lossy(
+        &output.stdout,
+    )))
+}
+
+/// Parses the output of `git worktree list --porcelain`. Bare repositories and working trees
+/// whose directory is gone are left out, as there is nothing to open.
+fn parse_worktree_list(output: &str) -> Vec<GitWorktree> {
+    let mut worktrees = Vec::new();
+    for (ix, record) in output.split("\n\n").enumerate() {
+        let mut worktree = GitWorktree {
+            path: PathBuf::new(),
+            branch: None,
+            head_sha: None,
+            is_main: ix == 0,
+            is_locked: false,
+        };
+        let mut is_openable = true;
+        for line in record.lines() {
+            let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+            match key {
+                "worktree" => worktree.path = PathBuf::from(value),
+                "HEAD" => {
+                    worktree.head_sha = value
+                        .bytes()
+                        .any(|byte| byte != b'0')
+                        .then(|| value.to_string())
+                }
+                "branch" => {
+                    let branch = value.strip_prefix("refs/heads/").unwrap_or(value);
+                    worktree.branch = Some(branch.to_string());
+                }
+                "locked" => worktree.is_locked = true,
+                "bare" | "prunable" => is_openable = false,
+                _ => {}
+            }
+        }
+        if is_openable && !worktree.path.as_os_str().is_empty() {
+            worktrees.push(worktree);
+        }
+    }
+    worktrees
+}
+
+/// Checks out a branch in a new working tree linked to the repository. When `create_branch` is
+/// true, the branch is created from HEAD first.
+pub fn create_worktree(
+    git_binary: &Path,
+    working_directory: &Path,
+    path: &Path,
+    branch: &str,
+    create_branch: bool,
+) -> Result<()> {
+    let mut command = Command::new(git_binary);
+
+    command
+        .current_dir(working_directory)
+        .args(["worktree", "add"]);
+    if create_branch {
+        command.arg("-b").arg(branch).arg(path);
+    } else {
+        command.arg(path).arg(branch);
+    }
+
+    #[cfg(windows)]
+    command.creation_flags(windows::Win32::System::Threading::CREATE_NO_WINDOW.0);
+
+    let output = command
+        .output()
+        .map_err(|e| anyhow!("Failed to start git worktree process: {}", e))?;
+
+    anyhow::ensure!(
+        output.status.success(),
+        "git worktree add failed:\n{}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_worktree_list() {
+        let output = "\
+            worktree /code/zed\n\
+            HEAD 1111111111111111111111111111111111111111\n\
+            branch refs/heads/main\n\
+            \n\
+            worktree /code/zed-feature\n\
+            HEAD 2222222222222222222222222222222222222222\n\
+            branch refs/heads/feature/worktrees\n\
+            locked\n\
+            \n\
+            worktree /code/zed-bisect\n\
+            HEAD 3333333333333333333333333333333333333333\n\
+            detached\n\
+            \n\
+            worktree /tmp/zed-gone\n\
+            HEAD 4444444444444444444444444444444444444444\n\
+            detached\n\
+            prunable gitdir file points to non-existent location\n\
+            \n";
+
+        assert_eq!(
+            parse_worktree_list(output),
+            [
+                GitWorktree {
+                    path: PathBuf::from("/code/zed"),
+                    branch: Some("main".to_string()),
+                    head_sha: Some("1111111111111111111111111111111111111111".to_string()),
+                    is_main: true,
+                    is_locked: false,
+                },
+                GitWorktree {
+                    path: PathBuf::from("/code/zed-feature"),
+                    branch: Some("feature/worktrees".to_string()),
+                    head_sha: Some("2222222222222222222222222222222222222222".to_string()),
+                    is_main: false,
+                    is_locked: true,
+                },
+                GitWorktree {
+                    path: PathBuf::from("/code/zed-bisect"),
+                    branch: None,
+                    head_sha: Some("3333333333333333333333333333333333333333".to_string()),
+                    is_main: false,
+                    is_locked: false,
+                },
+            ]
+        );
+
+        let bare_output = "worktree /code/zed.git\nbare\n\n";
+        assert!(parse_worktree_list(bare_output).is_empty());
+    }
+}
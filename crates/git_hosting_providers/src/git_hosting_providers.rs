@@ -1,14 +1,21 @@
+mod hosting_provider_settings;
 mod providers;
 
 use std::sync::Arc;
 
+use anyhow::Context as _;
 use git::GitHostingProviderRegistry;
 use gpui::AppContext;
+use settings::{Settings, SettingsStore};
+use util::ResultExt;
 
+pub use crate::hosting_provider_settings::*;
 pub use crate::providers::*;
 
 /// Initializes the Git hosting providers.
 pub fn init(cx: &mut AppContext) {
+    GitHostingProviderSettings::register(cx);
+
     let provider_registry = GitHostingProviderRegistry::global(cx);
 
     // The providers are stored in a `BTreeMap`, so insertion order matters.
@@ -23,4 +30,36 @@ pub fn init(cx: &mut AppContext) {
     provider_registry.register_hosting_provider(Arc::new(Bitbucket));
     provider_registry.register_hosting_provider(Arc::new(Sourcehut));
     provider_registry.register_hosting_provider(Arc::new(Codeberg));
+
+    // Then the self-hosted providers from the settings, which are replaced when they change.
+    let mut configs = Vec::new();
+    register_self_hosted_providers(&provider_registry, &mut configs, cx);
+    cx.observe_global::<SettingsStore>(move |cx| {
+        register_self_hosted_providers(&provider_registry, &mut configs, cx);
+    })
+    .detach();
+}
+
+fn register_self_hosted_providers(
+    provider_registry: &GitHostingProviderRegistry,
+    configs: &mut Vec<GitHostingProviderConfig>,
+    cx: &AppContext,
+) {
+    let new_configs = &GitHostingProviderSettings::get_global(cx).git_hosting_providers;
+    if new_configs == configs {
+        return;
+    }
+
+    for config in configs.iter() {
+        provider_registry.unregister_hosting_provider(config.name.as_ref().unwrap_or(&config.host));
+    }
+    for config in new_configs {
+        if let Some(provider) = SelfHosted::new(config)
+            .with_context(|| format!("invalid Git hosting provider for {}", config.host))
+            .log_err()
+        {
+            provider_registry.register_hosting_provider(Arc::new(provider));
+        }
+    }
+    configs.clone_from(new_configs);
 }
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct GitHostingProviderSettings {
+    /// Self-hosted Git hosting providers, used to link commits, pull requests and
+    /// permalinks of repositories whose remote is on one of their hosts.
+    ///
+    /// Default: []
+    #[serde(default)]
+    pub git_hosting_providers: Vec<GitHostingProviderConfig>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct GitHostingProviderConfig {
+    /// The software the provider runs, which determines the default URL templates
+    /// and how pull requests are found in commit messages.
+    pub provider: GitHostingProviderKind,
+    /// The host of the remote URLs, e.g. `gitlab.example.com`.
+    pub host: String,
+    /// The name of the provider shown in the UI.
+    ///
+    /// Default: the host
+    pub name: Option<String>,
+    /// The URL the templates' `{base_url}` is replaced with.
+    ///
+    /// Default: `https://{host}`
+    pub base_url: Option<String>,
+    /// The URL template of commits, with `{base_url}`, `{owner}`, `{repo}` and `{sha}`
+    /// placeholders.
+    pub commit_url: Option<String>,
+    /// The URL template of files at a commit, with `{base_url}`, `{owner}`, `{repo}`,
+    /// `{sha}` and `{path}` placeholders. The selected lines are added as the fragment.
+    pub file_url: Option<String>,
+    /// The URL template of pull requests, with `{base_url}`, `{owner}`, `{repo}` and
+    /// `{number}` placeholders.
+    pub pull_request_url: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum GitHostingProviderKind {
+    Github,
+    Gitlab,
+    Bitbucket,
+}
+
+impl Settings for GitHostingProviderSettings {
+    const KEY: Option<&'static str> = None;
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -3,6 +3,7 @@ mod codeberg;
 mod gitee;
 mod github;
 mod gitlab;
+mod self_hosted;
 mod sourcehut;
 
 pub use bitbucket::*;
@@ -10,4 +11,5 @@ pub use codeberg::*;
 pub use gitee::*;
 pub use github::*;
 pub use gitlab::*;
+pub use self_hosted::*;
 pub use sourcehut::*;
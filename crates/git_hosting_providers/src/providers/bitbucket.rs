@@ -1,6 +1,20 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
 use url::Url;
 
-use git::{BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote};
+use git::{
+    BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote,
+    PullRequest,
+};
+
+/// Matches the first line of the commits Bitbucket creates when merging pull requests, e.g.
+/// `Merged in fix-permalinks (pull request #123)`.
+fn pull_request_number_regex() -> &'static Regex {
+    static PULL_REQUEST_NUMBER_REGEX: OnceLock<Regex> = OnceLock::new();
+
+    PULL_REQUEST_NUMBER_REGEX.get_or_init(|| Regex::new(r"\(pull request #(\d+)\)$").unwrap())
+}
 
 pub struct Bitbucket;
 
@@ -71,6 +85,20 @@ impl GitHostingProvider for Bitbucket {
         );
         permalink
     }
+
+    fn extract_pull_request(&self, remote: &ParsedGitRemote, message: &str) -> Option<PullRequest> {
+        let line = message.lines().next()?;
+        let capture = pull_request_number_regex().captures(line)?;
+        let number = capture.get(1)?.as_str().parse::<u32>().ok()?;
+
+        let ParsedGitRemote { owner, repo } = remote;
+        let url = self
+            .base_url()
+            .join(&format!("{owner}/{repo}/pull-requests/{number}"))
+            .ok()?;
+
+        Some(PullRequest { number, url })
+    }
 }
 
 #[cfg(test)]
@@ -172,4 +200,25 @@ mod tests {
             "https://bitbucket.org/thorstenzed/testingrepo/src/f00b4r/main.rs#lines-24:48";
         assert_eq!(permalink.to_string(), expected_url.to_string())
     }
+
+    #[test]
+    fn test_bitbucket_extract_pull_request() {
+        let remote = ParsedGitRemote {
+            owner: "thorstenzed",
+            repo: "testingrepo",
+        };
+
+        let message = "Fix the permalinks (#123)";
+        assert!(Bitbucket.extract_pull_request(&remote, message).is_none());
+
+        let message = "Merged in fix-permalinks (pull request #12)\n\nFix the permalinks";
+        assert_eq!(
+            Bitbucket
+                .extract_pull_request(&remote, message)
+                .unwrap()
+                .url
+                .as_str(),
+            "https://bitbucket.org/thorstenzed/testingrepo/pull-requests/12"
+        );
+    }
 }
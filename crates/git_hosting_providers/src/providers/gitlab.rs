@@ -1,6 +1,21 @@
+use std::sync::OnceLock;
+
+use regex::Regex;
 use url::Url;
 
-use git::{BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote};
+use git::{
+    BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote,
+    PullRequest,
+};
+
+/// Matches the trailer GitLab adds to the commit messages of merge requests, e.g.
+/// `See merge request zed-industries/zed!123`.
+fn merge_request_number_regex() -> &'static Regex {
+    static MERGE_REQUEST_NUMBER_REGEX: OnceLock<Regex> = OnceLock::new();
+
+    MERGE_REQUEST_NUMBER_REGEX
+        .get_or_init(|| Regex::new(r"(?m)^See merge request \S+!(\d+)$").unwrap())
+}
 
 pub struct Gitlab;
 
@@ -72,10 +87,25 @@ impl GitHostingProvider for Gitlab {
         );
         permalink
     }
+
+    fn extract_pull_request(&self, remote: &ParsedGitRemote, message: &str) -> Option<PullRequest> {
+        let capture = merge_request_number_regex().captures(message)?;
+        let number = capture.get(1)?.as_str().parse::<u32>().ok()?;
+
+        let ParsedGitRemote { owner, repo } = remote;
+        let url = self
+            .base_url()
+            .join(&format!("{owner}/{repo}/-/merge_requests/{number}"))
+            .ok()?;
+
+        Some(PullRequest { number, url })
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use unindent::Unindent;
+
     use super::*;
 
     #[test]
@@ -191,4 +221,33 @@ mod tests {
         let expected_url = "https://gitlab.com/zed-industries/zed/-/blob/b2efec9824c45fcc90c9a7eb107a50d1772a60aa/crates/zed/src/main.rs#L24-48";
         assert_eq!(permalink.to_string(), expected_url.to_string())
     }
+
+    #[test]
+    fn test_gitlab_extract_merge_request() {
+        let remote = ParsedGitRemote {
+            owner: "zed-industries",
+            repo: "zed",
+        };
+
+        let message = "Fix the permalinks of nested groups";
+        assert!(Gitlab.extract_pull_request(&remote, message).is_none());
+
+        let message = r#"
+            Merge branch 'fix-permalinks' into 'main'
+
+            Fix the permalinks of nested groups
+
+            See merge request zed-industries/zed!1234
+            "#
+        .unindent();
+
+        assert_eq!(
+            Gitlab
+                .extract_pull_request(&remote, &message)
+                .unwrap()
+                .url
+                .as_str(),
+            "https://gitlab.com/zed-industries/zed/-/merge_requests/1234"
+        );
+    }
 }
@@ -0,0 +1,311 @@
+use anyhow::{Context, Result};
+use url::Url;
+
+use git::{
+    BuildCommitPermalinkParams, BuildPermalinkParams, GitHostingProvider, ParsedGitRemote,
+    PullRequest,
+};
+
+use crate::hosting_provider_settings::{GitHostingProviderConfig, GitHostingProviderKind};
+use crate::{Bitbucket, Github, Gitlab};
+
+/// A Git hosting provider on a custom host, building its URLs from templates.
+pub struct SelfHosted {
+    name: String,
+    host: String,
+    kind: GitHostingProviderKind,
+    base_url: Url,
+    commit_url: String,
+    file_url: String,
+    pull_request_url: String,
+}
+
+impl SelfHosted {
+    pub fn new(config: &GitHostingProviderConfig) -> Result<Self> {
+        let base_url = match &config.base_url {
+            Some(base_url) => base_url.trim_end_matches('/').to_string(),
+            None => format!("https://{}", config.host),
+        };
+        let (commit_url, file_url, pull_request_url) = match config.provider {
+            GitHostingProviderKind::Github => (
+                "{base_url}/{owner}/{repo}/commit/{sha}",
+                "{base_url}/{owner}/{repo}/blob/{sha}/{path}",
+                "{base_url}/{owner}/{repo}/pull/{number}",
+            ),
+            GitHostingProviderKind::Gitlab => (
+                "{base_url}/{owner}/{repo}/-/commit/{sha}",
+                "{base_url}/{owner}/{repo}/-/blob/{sha}/{path}",
+                "{base_url}/{owner}/{repo}/-/merge_requests/{number}",
+            ),
+            GitHostingProviderKind::Bitbucket => (
+                "{base_url}/{owner}/{repo}/commits/{sha}",
+                "{base_url}/{owner}/{repo}/src/{sha}/{path}",
+                "{base_url}/{owner}/{repo}/pull-requests/{number}",
+            ),
+        };
+
+        let this = Self {
+            name: config.name.clone().unwrap_or_else(|| config.host.clone()),
+            host: config.host.clone(),
+            kind: config.provider,
+            base_url: Url::parse(&base_url)
+                .with_context(|| format!("invalid base URL {base_url:?}"))?,
+            commit_url: config
+                .commit_url
+                .as_deref()
+                .unwrap_or(commit_url)
+                .replace("{base_url}", &base_url),
+            file_url: config
+                .file_url
+                .as_deref()
+                .unwrap_or(file_url)
+                .replace("{base_url}", &base_url),
+            pull_request_url: config
+                .pull_request_url
+                .as_deref()
+                .unwrap_or(pull_request_url)
+                .replace("{base_url}", &base_url),
+        };
+
+        // Check the templates up front, so that building URLs can't fail later on.
+        let remote = ParsedGitRemote {
+            owner: "owner",
+            repo: "repo",
+        };
+        for template in [&this.commit_url, &this.file_url, &this.pull_request_url] {
+            let url = this.expand(
+                template,
+                &remote,
+                &[("sha", "0"), ("path", "a"), ("number", "1")],
+            );
+            Url::parse(&url).with_context(|| format!("invalid URL template {template:?}"))?;
+        }
+
+        Ok(this)
+    }
+
+    fn expand(&self, template: &str, remote: &ParsedGitRemote, values: &[(&str, &str)]) -> String {
+        let mut url = template
+            .replace("{owner}", remote.owner)
+            .replace("{repo}", remote.repo);
+        for (placeholder, value) in values {
+            url = url.replace(&format!("{{{placeholder}}}"), value);
+        }
+        url
+    }
+
+    fn build_url(&self, template: &str, remote: &ParsedGitRemote, values: &[(&str, &str)]) -> Url {
+        Url::parse(&self.expand(template, remote, values)).unwrap_or_else(|_| self.base_url())
+    }
+}
+
+impl GitHostingProvider for SelfHosted {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn base_url(&self) -> Url {
+        self.base_url.clone()
+    }
+
+    fn supports_avatars(&self) -> bool {
+        false
+    }
+
+    fn format_line_number(&self, line: u32) -> String {
+        match self.kind {
+            GitHostingProviderKind::Github => Github.format_line_number(line),
+            GitHostingProviderKind::Gitlab => Gitlab.format_line_number(line),
+            GitHostingProviderKind::Bitbucket => Bitbucket.format_line_number(line),
+        }
+    }
+
+    fn format_line_numbers(&self, start_line: u32, end_line: u32) -> String {
+        match self.kind {
+            GitHostingProviderKind::Github => Github.format_line_numbers(start_line, end_line),
+            GitHostingProviderKind::Gitlab => Gitlab.format_line_numbers(start_line, end_line),
+            GitHostingProviderKind::Bitbucket => {
+                Bitbucket.format_line_numbers(start_line, end_line)
+            }
+        }
+    }
+
+    fn parse_remote_url<'a>(&self, url: &'a str) -> Option<ParsedGitRemote<'a>> {
+        let url = url.trim_end_matches(".git");
+        let (scheme_and_user, rest) = url.split_once(self.host.as_str())?;
+        // Only match the whole host, not one that merely ends with it.
+        if !(scheme_and_user.is_empty()
+            || scheme_and_user.ends_with('@')
+            || scheme_and_user.ends_with("://"))
+        {
+            return None;
+        }
+
+        let path = if let Some(path) = rest.strip_prefix('/') {
+            path
+        } else {
+            let path = rest.strip_prefix(':')?;
+            // `ssh://git@host:2222/owner/repo` has a port where the SCP-like
+            // `git@host:owner/repo` has the path.
+            if scheme_and_user.contains("://") {
+                path.split_once('/')?.1
+            } else {
+                path
+            }
+        };
+
+        // GitLab groups can be nested, so everything but the last segment is the owner.
+        let (owner, repo) = path.rsplit_once('/')?;
+        if owner.is_empty() || repo.is_empty() {
+            return None;
+        }
+
+        Some(ParsedGitRemote { owner, repo })
+    }
+
+    fn build_commit_permalink(
+        &self,
+        remote: &ParsedGitRemote,
+        params: BuildCommitPermalinkParams,
+    ) -> Url {
+        let BuildCommitPermalinkParams { sha } = params;
+
+        self.build_url(&self.commit_url, remote, &[("sha", sha)])
+    }
+
+    fn build_permalink(&self, remote: ParsedGitRemote, params: BuildPermalinkParams) -> Url {
+        let BuildPermalinkParams {
+            sha,
+            path,
+            selection,
+        } = params;
+
+        let mut permalink =
+            self.build_url(&self.file_url, &remote, &[("sha", sha), ("path", path)]);
+        permalink.set_fragment(
+            selection
+                .map(|selection| self.line_fragment(&selection))
+                .as_deref(),
+        );
+        permalink
+    }
+
+    fn extract_pull_request(&self, remote: &ParsedGitRemote, message: &str) -> Option<PullRequest> {
+        let number = match self.kind {
+            GitHostingProviderKind::Github => Github.extract_pull_request(remote, message),
+            GitHostingProviderKind::Gitlab => Gitlab.extract_pull_request(remote, message),
+            GitHostingProviderKind::Bitbucket => Bitbucket.extract_pull_request(remote, message),
+        }?
+        .number;
+
+        let url = self.build_url(
+            &self.pull_request_url,
+            remote,
+            &[("number", &number.to_string())],
+        );
+
+        Some(PullRequest { number, url })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(provider: GitHostingProviderKind) -> GitHostingProviderConfig {
+        GitHostingProviderConfig {
+            provider,
+            host: "git.example.com".to_string(),
+            name: None,
+            base_url: None,
+            commit_url: None,
+            file_url: None,
+            pull_request_url: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_self_hosted_remote_url() {
+        let provider = SelfHosted::new(&config(GitHostingProviderKind::Gitlab)).unwrap();
+
+        for url in [
+            "git@git.example.com:zed-industries/zed.git",
+            "https://git.example.com/zed-industries/zed",
+            "ssh://git@git.example.com:2222/zed-industries/zed.git",
+        ] {
+            let remote = provider.parse_remote_url(url).unwrap();
+            assert_eq!(
+                (remote.owner, remote.repo),
+                ("zed-industries", "zed"),
+                "{url}"
+            );
+        }
+
+        let remote = provider
+            .parse_remote_url("git@git.example.com:zed-industries/editors/zed.git")
+            .unwrap();
+        assert_eq!(
+            (remote.owner, remote.repo),
+            ("zed-industries/editors", "zed")
+        );
+
+        assert!(provider
+            .parse_remote_url("git@notgit.example.com:zed-industries/zed.git")
+            .is_none());
+        assert!(provider
+            .parse_remote_url("https://git.example.com.evil/zed-industries/zed")
+            .is_none());
+    }
+
+    #[test]
+    fn test_build_self_hosted_urls() {
+        let provider = SelfHosted::new(&config(GitHostingProviderKind::Gitlab)).unwrap();
+        let remote = ParsedGitRemote {
+            owner: "zed-industries",
+            repo: "zed",
+        };
+
+        let permalink = provider.build_permalink(
+            ParsedGitRemote {
+                owner: "zed-industries",
+                repo: "zed",
+            },
+            BuildPermalinkParams {
+                sha: "f00b4r",
+                path: "crates/zed/src/main.rs",
+                selection: Some(23..47),
+            },
+        );
+        assert_eq!(
+            permalink.as_str(),
+            "https://git.example.com/zed-industries/zed/-/blob/f00b4r/crates/zed/src/main.rs#L24-48"
+        );
+
+        let pull_request = provider
+            .extract_pull_request(&remote, "Fix\n\nSee merge request zed-industries/zed!12")
+            .unwrap();
+        assert_eq!(
+            pull_request.url.as_str(),
+            "https://git.example.com/zed-industries/zed/-/merge_requests/12"
+        );
+
+        let provider = SelfHosted::new(&GitHostingProviderConfig {
+            base_url: Some("https://git.example.com/code/".to_string()),
+            commit_url: Some("{base_url}/{repo}/revision/{sha}".to_string()),
+            ..config(GitHostingProviderKind::Github)
+        })
+        .unwrap();
+        let permalink =
+            provider.build_commit_permalink(&remote, BuildCommitPermalinkParams { sha: "f00b4r" });
+        assert_eq!(
+            permalink.as_str(),
+            "https://git.example.com/code/zed/revision/f00b4r"
+        );
+
+        assert!(SelfHosted::new(&GitHostingProviderConfig {
+            commit_url: Some("{owner}/{repo}/commit/{sha}".to_string()),
+            ..config(GitHostingProviderKind::Github)
+        })
+        .is_err());
+    }
+}
@@ -0,0 +1,510 @@
+use editor::Editor;
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use git::repository::{DiffBase, GitRepository};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    Subscription, Task, View, ViewContext, WeakView,
+};
+use language::Buffer;
+use picker::{Picker, PickerDelegate};
+use project::Project;
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{
+    item::ItemHandle, notifications::DetachAndPromptErr, ModalView, StatusItemView, Workspace,
+};
+
+use crate::SelectDiffBase;
+
+/// The branches offered to show the changes made since branching off.
+const MAIN_BRANCH_NAMES: [&str; 2] = ["main", "master"];
+
+/// Returns the repository containing the file of the buffer, or the repository at the root of
+/// the project when there's no such buffer.
+fn repository_for_buffer(
+    project: &Model<Project>,
+    buffer: Option<&Model<Buffer>>,
+    cx: &AppContext,
+) -> Option<Arc<dyn GitRepository>> {
+    let project = project.read(cx);
+    buffer
+        .and_then(|buffer| project.git_repository_for_buffer(buffer, cx).ok())
+        .map(|(repository, _)| repository)
+        .or_else(|| project.get_first_worktree_root_repo(cx))
+}
+
+/// A status bar item showing the current branch and the revision changes are shown against.
+pub struct DiffBaseIndicator {
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    active_buffer: Option<Model<Buffer>>,
+    status: Option<(Option<String>, DiffBase)>,
+    update_status_task: Task<()>,
+    _project_subscription: Subscription,
+}
+
+impl DiffBaseIndicator {
+    pub fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
+        let project = workspace.project().clone();
+        let project_subscription = cx.subscribe(&project, |this, _, event, cx| {
+            // Changing the diff base writes to the repository's config, which is reported as a
+            // repository update too.
+            if let project::Event::WorktreeUpdatedGitRepositories = event {
+                this.update_status(cx);
+            }
+        });
+        let mut this = Self {
+            project,
+            workspace: workspace.weak_handle(),
+            active_buffer: None,
+            status: None,
+            update_status_task: Task::ready(()),
+            _project_subscription: project_subscription,
+        };
+        this.update_status(cx);
+        this
+    }
+
+    /// Reads the branch and the diff base of the repository in the background, as they're read
+    /// from disk.
+    fn update_status(&mut self, cx: &mut ViewContext<Self>) {
+        let status = repository_for_buffer(&self.project, self.active_buffer.as_ref(), cx).map(
+            |repository| {
+                cx.background_executor()
+                    .spawn(async move { (repository.branch_name(), repository.diff_base()) })
+            },
+        );
+        self.update_status_task = cx.spawn(|this, mut cx| async move {
+            let status = match status {
+                Some(status) => Some(status.await),
+                None => None,
+            };
+            this.update(&mut cx, |this, cx| {
+                this.status = status;
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
+}
+
+impl Render for DiffBaseIndicator {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        div().when_some(self.status.as_ref(), |el, (branch, diff_base)| {
+            let label = match branch {
+                Some(branch) => format!("{branch} ↔ {}", diff_base.label()),
+                None => diff_base.label(),
+            };
+
+            el.child(
+                Button::new("select-diff-base", label)
+                    .icon(IconName::GitBranch)
+                    .icon_position(IconPosition::Start)
+                    .icon_size(IconSize::Small)
+                    .icon_color(Color::Muted)
+                    .label_size(LabelSize::Small)
+                    .on_click(cx.listener(|this, _, cx| {
+                        if let Some(workspace) = this.workspace.upgrade() {
+                            workspace.update(cx, |workspace, cx| {
+                                DiffBasePicker::toggle(workspace, &SelectDiffBase, cx)
+                            });
+                        }
+                    }))
+                    .tooltip(|cx| Tooltip::for_action("Select Diff Base", &SelectDiffBase, cx)),
+            )
+        })
+    }
+}
+
+impl StatusItemView for DiffBaseIndicator {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.active_buffer = active_pane_item
+            .and_then(|item| item.act_as::<Editor>(cx))
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton());
+        self.update_status(cx);
+    }
+}
+
+/// A modal choosing the revision that the changes of a repository are shown against.
+pub struct DiffBasePicker {
+    picker: View<Picker<DiffBasePickerDelegate>>,
+}
+
+impl DiffBasePicker {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        _: &SelectDiffBase,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let buffer = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton());
+        let Some(repository) = repository_for_buffer(workspace.project(), buffer.as_ref(), cx)
+        else {
+            return;
+        };
+
+        cx.spawn(|workspace, mut cx| async move {
+            let (branches, tags) = cx
+                .background_executor()
+                .spawn({
+                    let repository = repository.clone();
+                    async move { anyhow::Ok((repository.branches()?, repository.tags()?)) }
+                })
+                .await?;
+
+            let mut candidates = vec![
+                DiffBaseCandidate::new(DiffBase::Index, "Unstaged changes"),
+                DiffBaseCandidate::new(DiffBase::Head, "Staged and unstaged changes"),
+            ];
+            candidates.extend(
+                branches
+                    .iter()
+                    .filter(|branch| !branch.is_remote)
+                    .find(|branch| MAIN_BRANCH_NAMES.contains(&branch.name.as_ref()))
+                    .map(|branch| {
+                        DiffBaseCandidate::new(
+                            DiffBase::MergeBase(branch.name.to_string()),
+                            "Changes since branching off",
+                        )
+                    }),
+            );
+            candidates.extend(branches.iter().map(|branch| {
+                let kind = if branch.is_remote {
+                    "Remote branch"
+                } else {
+                    "Branch"
+                };
+                DiffBaseCandidate::new(DiffBase::Revision(branch.name.to_string()), kind)
+            }));
+            candidates.extend(
+                tags.into_iter()
+                    .map(|tag| DiffBaseCandidate::new(DiffBase::Revision(tag), "Tag")),
+            );
+
+            workspace.update(&mut cx, |workspace, cx| {
+                workspace.toggle_modal(cx, move |cx| {
+                    let delegate = DiffBasePickerDelegate {
+                        diff_base_picker: cx.view().downgrade(),
+                        current_diff_base: repository.diff_base(),
+                        repository,
+                        candidates: Arc::new(candidates),
+                        matches: Vec::new(),
+                        selected_index: 0,
+                        last_query: String::new(),
+                    };
+                    Self {
+                        picker: cx.new_view(|cx| Picker::uniform_list(delegate, cx)),
+                    }
+                });
+            })
+        })
+        .detach_and_prompt_err("Failed to list the revisions", cx, |_, _| None);
+    }
+}
+
+impl Render for DiffBasePicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for DiffBasePicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for DiffBasePicker {}
+impl ModalView for DiffBasePicker {}
+
+struct DiffBaseCandidate {
+    diff_base: DiffBase,
+    label: String,
+    kind: &'static str,
+}
+
+impl DiffBaseCandidate {
+    fn new(diff_base: DiffBase, kind: &'static str) -> Self {
+        Self {
+            label: diff_base.label(),
+            diff_base,
+            kind,
+        }
+    }
+}
+
+struct DiffBasePickerDelegate {
+    diff_base_picker: WeakView<DiffBasePicker>,
+    repository: Arc<dyn GitRepository>,
+    current_diff_base: DiffBase,
+    candidates: Arc<Vec<DiffBaseCandidate>>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    last_query: String,
+}
+
+impl PickerDelegate for DiffBasePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Show changes against a branch, tag or revision...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self.candidates.clone();
+        cx.spawn(|this, mut cx| async move {
+            let string_candidates = candidates
+                .iter()
+                .enumerate()
+                .map(|(ix, candidate)| StringMatchCandidate::new(ix, candidate.label.clone()))
+                .collect::<Vec<_>>();
+            let matches = if query.is_empty() {
+                string_candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &string_candidates,
+                    &query,
+                    true,
+                    candidates.len(),
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.last_query = query;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        // A query matching none of the branches and tags is used as a revision, like a SHA.
+        let diff_base = match self.matches.get(self.selected_index) {
+            Some(mat) => self.candidates[mat.candidate_id].diff_base.clone(),
+            None if !self.last_query.trim().is_empty() => {
+                DiffBase::Revision(self.last_query.trim().to_string())
+            }
+            None => return,
+        };
+        let repository = self.repository.clone();
+        cx.background_executor()
+            .spawn(async move { repository.set_diff_base(&diff_base) })
+            .detach_and_prompt_err("Failed to change the diff base", cx, |_, _| None);
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.diff_base_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let candidate = &self.candidates[mat.candidate_id];
+        let is_current = candidate.diff_base == self.current_diff_base;
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(
+                    Icon::new(IconName::Check)
+                        .size(IconSize::Small)
+                        .color(if is_current {
+                            Color::Accent
+                        } else {
+                            Color::Hidden
+                        }),
+                )
+                .child(
+                    h_flex()
+                        .w_full()
+                        .justify_between()
+                        .child(HighlightedLabel::new(
+                            candidate.label.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(candidate.kind)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                ),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::repository::Branch;
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+    use std::path::Path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_select_diff_base(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let fs = app_state.fs.as_fake();
+        let dot_git = Path::new("/root/.git");
+        fs.insert_tree("/root", json!({ ".git": {}, "a.txt": "a" }))
+            .await;
+        fs.with_git_state(dot_git, false, |state| {
+            state.branch_name = Some("feature".into());
+            state.branches = vec![branch("feature", false), branch("main", false)];
+            state.branches.push(branch("origin/main", true));
+            state.tags = vec!["v1.0".into()];
+        });
+        let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let indicator = workspace.update(cx, |workspace, cx| {
+            cx.new_view(|cx| DiffBaseIndicator::new(workspace, cx))
+        });
+        cx.run_until_parked();
+        indicator.update(cx, |indicator, _| {
+            assert_eq!(
+                indicator.status,
+                Some((Some("feature".into()), DiffBase::Index))
+            );
+        });
+
+        // The merge base with the main branch is offered, along with every branch and tag.
+        let picker = open_diff_base_picker(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            assert_eq!(
+                match_labels(picker),
+                [
+                    "Index",
+                    "HEAD",
+                    "Merge base with main",
+                    "feature",
+                    "main",
+                    "origin/main",
+                    "v1.0",
+                ]
+            );
+        });
+
+        picker.update(cx, |picker, cx| picker.set_query("merge", cx));
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| {
+            assert_eq!(match_labels(picker).first(), Some(&"Merge base with main"));
+            picker.delegate.confirm(false, cx);
+        });
+        cx.run_until_parked();
+        fs.with_git_state(dot_git, true, |state| {
+            assert_eq!(state.diff_base, DiffBase::MergeBase("main".into()));
+        });
+        cx.run_until_parked();
+        indicator.update(cx, |indicator, _| {
+            assert_eq!(
+                indicator.status,
+                Some((Some("feature".into()), DiffBase::MergeBase("main".into())))
+            );
+        });
+
+        // A query matching no branch nor tag is used as a revision.
+        let picker = open_diff_base_picker(&workspace, cx);
+        picker.update(cx, |picker, cx| picker.set_query("a1b2c3", cx));
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| {
+            assert!(picker.delegate.matches.is_empty());
+            picker.delegate.confirm(false, cx);
+        });
+        cx.run_until_parked();
+        fs.with_git_state(dot_git, false, |state| {
+            assert_eq!(state.diff_base, DiffBase::Revision("a1b2c3".into()));
+        });
+    }
+
+    fn branch(name: &str, is_remote: bool) -> Branch {
+        Branch {
+            is_head: false,
+            name: name.into(),
+            is_remote,
+            unix_timestamp: None,
+            upstream: None,
+        }
+    }
+
+    fn open_diff_base_picker(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<Picker<DiffBasePickerDelegate>> {
+        cx.dispatch_action(SelectDiffBase);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<DiffBasePicker>(cx)
+                .expect("diff base picker is not open")
+                .read(cx)
+                .picker
+                .clone()
+        })
+    }
+
+    fn match_labels(picker: &Picker<DiffBasePickerDelegate>) -> Vec<&str> {
+        picker
+            .delegate
+            .matches
+            .iter()
+            .map(|mat| mat.string.as_str())
+            .collect()
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            state
+        })
+    }
+}
@@ -0,0 +1,646 @@
+use anyhow::Result;
+use editor::{actions::ExpandAllHunkDiffs, Editor, MultiBuffer, DEFAULT_MULTIBUFFER_CONTEXT};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use git::{
+    commit::CommitSummary,
+    repository::{GitRepository, RepoPath},
+};
+use gpui::{
+    AppContext, AsyncWindowContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Model,
+    Render, Task, View, ViewContext, VisualContext, WeakView,
+};
+use language::{Buffer, Capability};
+use picker::{Picker, PickerDelegate};
+use std::{path::Path, sync::Arc};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+const MAX_COMMITS: usize = 1000;
+
+/// A modal listing the commits that changed the file of the active editor.
+pub struct FileHistory {
+    picker: View<Picker<FileHistoryDelegate>>,
+}
+
+/// How a commit chosen in the file history is shown.
+#[derive(Clone, Copy, PartialEq)]
+enum OpenKind {
+    /// The changes the commit made to the file.
+    Diff,
+    /// The contents of the file as of the commit.
+    File,
+    /// The changes made to the file since the commit, up to its current contents.
+    WorkingCopyDiff,
+}
+
+impl FileHistory {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        _: &crate::FileHistory,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::show(workspace, OpenKind::Diff, cx);
+    }
+
+    pub(crate) fn toggle_open_at_revision(
+        workspace: &mut Workspace,
+        _: &crate::OpenAtRevision,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        Self::show(workspace, OpenKind::File, cx);
+    }
+
+    /// Shows the commits changing the file of the active editor, confirming with the given kind
+    /// of view, and secondary confirming with the other one.
+    fn show(workspace: &mut Workspace, confirm_kind: OpenKind, cx: &mut ViewContext<Workspace>) {
+        let Some(buffer) = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        else {
+            return;
+        };
+        let repository = workspace
+            .project()
+            .read(cx)
+            .git_repository_for_buffer(&buffer, cx);
+
+        cx.spawn(|workspace, mut cx| async move {
+            let (repo, path) = repository?;
+            let commits = cx
+                .background_executor()
+                .spawn({
+                    let repo = repo.clone();
+                    let path = path.clone();
+                    async move { repo.file_history(&path, MAX_COMMITS) }
+                })
+                .await?;
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let workspace_handle = cx.view().downgrade();
+                workspace.toggle_modal(cx, move |cx| {
+                    let delegate = FileHistoryDelegate {
+                        file_history: cx.view().downgrade(),
+                        workspace: workspace_handle,
+                        repo,
+                        path,
+                        buffer,
+                        confirm_kind,
+                        commits: Arc::new(commits),
+                        matches: Vec::new(),
+                        selected_index: 0,
+                    };
+                    Self {
+                        picker: cx.new_view(|cx| Picker::uniform_list(delegate, cx)),
+                    }
+                });
+            })
+        })
+        .detach_and_prompt_err("Failed to load the file history", cx, |_, _| None);
+    }
+}
+
+impl Render for FileHistory {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(40.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for FileHistory {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for FileHistory {}
+impl ModalView for FileHistory {}
+
+struct FileHistoryDelegate {
+    file_history: WeakView<FileHistory>,
+    workspace: WeakView<Workspace>,
+    repo: Arc<dyn GitRepository>,
+    path: RepoPath,
+    buffer: Model<Buffer>,
+    confirm_kind: OpenKind,
+    commits: Arc<Vec<CommitSummary>>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl FileHistoryDelegate {
+    fn open_commit(&mut self, ix: usize, kind: OpenKind, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(mat) = self.matches.get(ix) else {
+            return;
+        };
+        let commit = self.commits[mat.candidate_id].clone();
+        let workspace = self.workspace.clone();
+        let repo = self.repo.clone();
+        let path = self.path.clone();
+        let open = match kind {
+            OpenKind::Diff => open_revision_diff(workspace, repo, path, commit, cx),
+            OpenKind::File => open_file_at_revision(workspace, repo, path, commit, cx),
+            OpenKind::WorkingCopyDiff => {
+                let text = self.buffer.read(cx).text();
+                open_working_copy_diff(workspace, repo, path, commit, text, cx)
+            }
+        };
+        open.detach_and_prompt_err("Failed to open the revision", cx, |_, _| None);
+        self.dismissed(cx);
+    }
+}
+
+impl PickerDelegate for FileHistoryDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match self.confirm_kind {
+            OpenKind::File => format!("Open {} at revision...", file_name(&self.path)).into(),
+            _ => format!("Search the history of {}...", file_name(&self.path)).into(),
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let commits = self.commits.clone();
+        cx.spawn(|this, mut cx| async move {
+            // Commits are matched by subject, author and SHA, in that order.
+            let candidates = commits
+                .iter()
+                .enumerate()
+                .map(|(ix, commit)| {
+                    StringMatchCandidate::new(
+                        ix,
+                        format!("{} {} {}", commit.subject, commit.author_name, commit.sha),
+                    )
+                })
+                .collect::<Vec<_>>();
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    MAX_COMMITS,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let kind = match (self.confirm_kind, secondary) {
+            (OpenKind::Diff, true) => OpenKind::File,
+            (OpenKind::File, true) => OpenKind::Diff,
+            (kind, _) => kind,
+        };
+        self.open_commit(self.selected_index, kind, cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.file_history
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let commit = &self.commits[mat.candidate_id];
+        let subject_positions = mat
+            .positions
+            .iter()
+            .copied()
+            .filter(|position| *position < commit.subject.len())
+            .collect();
+        let timestamp = time::OffsetDateTime::from_unix_timestamp(commit.unix_timestamp)
+            .map(|timestamp| {
+                time_format::format_localized_timestamp(
+                    timestamp,
+                    time::OffsetDateTime::now_utc(),
+                    cx.local_timezone(),
+                    time_format::TimestampFormat::Relative,
+                )
+            })
+            .unwrap_or_default();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    v_flex()
+                        .child(HighlightedLabel::new(
+                            commit.subject.clone(),
+                            subject_positions,
+                        ))
+                        .child(
+                            Label::new(format!(
+                                "{} · {} · {}",
+                                commit.sha.display_short(),
+                                commit.author_name,
+                                timestamp
+                            ))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                        ),
+                )
+                .end_hover_slot(
+                    h_flex()
+                        .gap_1()
+                        .child(match self.confirm_kind {
+                            OpenKind::File => {
+                                IconButton::new(("open-revision-diff", ix), IconName::FileGit)
+                                    .icon_size(IconSize::Small)
+                                    .tooltip(|cx| {
+                                        Tooltip::for_action(
+                                            "Open the Changes of This Commit",
+                                            &menu::SecondaryConfirm,
+                                            cx,
+                                        )
+                                    })
+                                    .on_click(cx.listener(move |picker, _, cx| {
+                                        cx.stop_propagation();
+                                        cx.prevent_default();
+                                        picker.delegate.open_commit(ix, OpenKind::Diff, cx);
+                                    }))
+                            }
+                            _ => IconButton::new(("open-file-at-revision", ix), IconName::File)
+                                .icon_size(IconSize::Small)
+                                .tooltip(|cx| {
+                                    Tooltip::for_action(
+                                        "Open File at This Revision",
+                                        &menu::SecondaryConfirm,
+                                        cx,
+                                    )
+                                })
+                                .on_click(cx.listener(move |picker, _, cx| {
+                                    cx.stop_propagation();
+                                    cx.prevent_default();
+                                    picker.delegate.open_commit(ix, OpenKind::File, cx);
+                                })),
+                        })
+                        .child(
+                            IconButton::new(("open-working-copy-diff", ix), IconName::Split)
+                                .icon_size(IconSize::Small)
+                                .tooltip(|cx| Tooltip::text("Compare with Working Copy", cx))
+                                .on_click(cx.listener(move |picker, _, cx| {
+                                    cx.stop_propagation();
+                                    cx.prevent_default();
+                                    picker
+                                        .delegate
+                                        .open_commit(ix, OpenKind::WorkingCopyDiff, cx);
+                                })),
+                        ),
+                ),
+        )
+    }
+}
+
+/// Opens the changes a commit made to a file, compared with its parent, in a read-only diff.
+fn open_revision_diff(
+    workspace: WeakView<Workspace>,
+    repo: Arc<dyn GitRepository>,
+    path: RepoPath,
+    commit: CommitSummary,
+    cx: &mut WindowContext,
+) -> Task<Result<()>> {
+    let sha = commit.sha.to_string();
+    let texts = cx.background_executor().spawn({
+        let path = path.clone();
+        async move {
+            let text = repo.load_revision_text(&sha, &path)?;
+            // The commit adding a file, or the first commit, has no parent text to compare with.
+            let parent_text = repo
+                .load_revision_text(&format!("{sha}^"), &path)
+                .unwrap_or(None);
+            anyhow::Ok((text, parent_text))
+        }
+    });
+
+    cx.spawn(|mut cx| async move {
+        let (text, parent_text) = texts.await?;
+        let buffer = revision_buffer(&workspace, &path, text.unwrap_or_default(), &mut cx).await?;
+        let recalc = buffer.update(&mut cx, |buffer, cx| {
+            buffer.set_diff_base(Some(parent_text.unwrap_or_default()), cx);
+            buffer.git_diff_recalc(cx)
+        })?;
+        if let Some(recalc) = recalc {
+            recalc.await;
+        }
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let snapshot = buffer.read(cx).snapshot();
+            let mut hunk_ranges = snapshot
+                .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                .map(|hunk| hunk.buffer_range)
+                .collect::<Vec<_>>();
+            if hunk_ranges.is_empty() {
+                hunk_ranges.push(snapshot.anchor_before(0)..snapshot.anchor_after(snapshot.len()));
+            }
+
+            let title = format!(
+                "{} @ {} ({})",
+                file_name(&path),
+                commit.sha.display_short(),
+                commit.subject
+            );
+            let multibuffer = cx.new_model(|cx| {
+                let mut multibuffer = MultiBuffer::new(0, Capability::ReadOnly).with_title(title);
+                multibuffer.push_excerpts_with_context_lines(
+                    buffer,
+                    hunk_ranges,
+                    DEFAULT_MULTIBUFFER_CONTEXT,
+                    cx,
+                );
+                multibuffer
+            });
+            let project = workspace.project().clone();
+            let editor = cx.new_view(|cx| {
+                let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+                editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx);
+                editor
+            });
+            workspace.add_item_to_active_pane(Box::new(editor), None, cx);
+        })
+    })
+}
+
+/// Opens the changes made to a file since a commit in a read-only diff, comparing the file as of
+/// the commit with the given working copy text.
+fn open_working_copy_diff(
+    workspace: WeakView<Workspace>,
+    repo: Arc<dyn GitRepository>,
+    path: RepoPath,
+    commit: CommitSummary,
+    working_copy_text: String,
+    cx: &mut WindowContext,
+) -> Task<Result<()>> {
+    let sha = commit.sha.to_string();
+    let revision_text = cx.background_executor().spawn({
+        let path = path.clone();
+        async move { repo.load_revision_text(&sha, &path) }
+    });
+
+    cx.spawn(|mut cx| async move {
+        let revision_text = revision_text.await?.unwrap_or_default();
+        let buffer = revision_buffer(&workspace, &path, working_copy_text, &mut cx).await?;
+        let recalc = buffer.update(&mut cx, |buffer, cx| {
+            buffer.set_diff_base(Some(revision_text), cx);
+            buffer.git_diff_recalc(cx)
+        })?;
+        if let Some(recalc) = recalc {
+            recalc.await;
+        }
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let snapshot = buffer.read(cx).snapshot();
+            let mut hunk_ranges = snapshot
+                .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                .map(|hunk| hunk.buffer_range)
+                .collect::<Vec<_>>();
+            if hunk_ranges.is_empty() {
+                hunk_ranges.push(snapshot.anchor_before(0)..snapshot.anchor_after(snapshot.len()));
+            }
+
+            let title = format!(
+                "{} @ {} ↔ working copy",
+                file_name(&path),
+                commit.sha.display_short()
+            );
+            let multibuffer = cx.new_model(|cx| {
+                let mut multibuffer = MultiBuffer::new(0, Capability::ReadOnly).with_title(title);
+                multibuffer.push_excerpts_with_context_lines(
+                    buffer,
+                    hunk_ranges,
+                    DEFAULT_MULTIBUFFER_CONTEXT,
+                    cx,
+                );
+                multibuffer
+            });
+            let project = workspace.project().clone();
+            let editor = cx.new_view(|cx| {
+                let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+                editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx);
+                editor
+            });
+            workspace.add_item_to_active_pane(Box::new(editor), None, cx);
+        })
+    })
+}
+
+/// Opens the contents of a file as of a commit in a read-only editor.
+fn open_file_at_revision(
+    workspace: WeakView<Workspace>,
+    repo: Arc<dyn GitRepository>,
+    path: RepoPath,
+    commit: CommitSummary,
+    cx: &mut WindowContext,
+) -> Task<Result<()>> {
+    let sha = commit.sha.to_string();
+    let text = cx.background_executor().spawn({
+        let path = path.clone();
+        async move { repo.load_revision_text(&sha, &path) }
+    });
+
+    cx.spawn(|mut cx| async move {
+        let text = text.await?.unwrap_or_default();
+        let buffer = revision_buffer(&workspace, &path, text, &mut cx).await?;
+        workspace.update(&mut cx, |workspace, cx| {
+            let title = format!("{} @ {}", file_name(&path), commit.sha.display_short());
+            let multibuffer =
+                cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+            let project = workspace.project().clone();
+            let editor =
+                cx.new_view(|cx| Editor::for_multibuffer(multibuffer, Some(project), true, cx));
+            workspace.add_item_to_active_pane(Box::new(editor), None, cx);
+        })
+    })
+}
+
+/// Creates a read-only buffer holding the contents of a file at some revision.
+async fn revision_buffer(
+    workspace: &WeakView<Workspace>,
+    path: &Path,
+    text: String,
+    cx: &mut AsyncWindowContext,
+) -> Result<Model<Buffer>> {
+    let languages = workspace.update(cx, |workspace, cx| {
+        workspace.project().read(cx).languages().clone()
+    })?;
+    let language = languages.language_for_file_path(path).await.ok();
+    cx.new_model(|cx| {
+        let mut buffer = Buffer::local(text, cx);
+        buffer.set_language_registry(languages);
+        buffer.set_language(language, cx);
+        buffer.set_capability(Capability::ReadOnly, cx);
+        buffer
+    })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::Project;
+    use serde_json::json;
+    use workspace::AppState;
+
+    const SHA: &str = "1111111111111111111111111111111111111111";
+
+    #[gpui::test]
+    async fn test_opening_commits(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let fs = app_state.fs.as_fake();
+        fs.insert_tree("/root", json!({ ".git": {}, "a.txt": "one\ntwo\nthree\n" }))
+            .await;
+        fs.with_git_state(Path::new("/root/.git"), false, |state| {
+            state.file_histories.insert(
+                "a.txt".into(),
+                vec![CommitSummary {
+                    sha: SHA.parse().unwrap(),
+                    subject: "Add two".into(),
+                    author_name: "Jane".into(),
+                    unix_timestamp: 0,
+                }],
+            );
+            state
+                .revision_contents
+                .insert((SHA.into(), "a.txt".into()), "one\ntwo\n".into());
+            state
+                .revision_contents
+                .insert((format!("{SHA}^"), "a.txt".into()), "one\n".into());
+        });
+        let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_abs_path("/root/a.txt".into(), true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        // The working copy diff compares with the unsaved contents of the file.
+        editor.update(cx, |editor, cx| editor.insert("zero\n", cx));
+
+        // Each kind opens an editor titled after the commit, holding the text of the file as of
+        // the commit, or of its working copy, compared with its base text.
+        for (kind, title, text, base_text) in [
+            (
+                OpenKind::Diff,
+                "a.txt @ 1111111 (Add two)",
+                "one\ntwo\n",
+                "one\n",
+            ),
+            (OpenKind::File, "a.txt @ 1111111", "one\ntwo\n", ""),
+            (
+                OpenKind::WorkingCopyDiff,
+                "a.txt @ 1111111 ↔ working copy",
+                "zero\none\ntwo\nthree\n",
+                "one\ntwo\n",
+            ),
+        ] {
+            workspace.update(cx, |workspace, cx| {
+                workspace.activate_item(&editor, cx);
+                FileHistory::show(workspace, kind, cx);
+            });
+            cx.run_until_parked();
+            let file_history = workspace
+                .update(cx, |workspace, cx| {
+                    workspace.active_modal::<FileHistory>(cx)
+                })
+                .unwrap();
+            file_history.update(cx, |file_history, cx| {
+                file_history
+                    .picker
+                    .update(cx, |picker, cx| picker.delegate.open_commit(0, kind, cx));
+            });
+            cx.run_until_parked();
+            assert_opened_revision(&workspace, cx, title, text, base_text);
+        }
+    }
+
+    #[track_caller]
+    fn assert_opened_revision(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+        title: &str,
+        text: &str,
+        base_text: &str,
+    ) {
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<FileHistory>(cx).is_none());
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            let multibuffer = editor.read(cx).buffer().read(cx);
+            assert_eq!(multibuffer.title(cx), title);
+            assert_eq!(editor.read(cx).text(cx), text);
+            let buffers = multibuffer.all_buffers();
+            assert_eq!(buffers.len(), 1);
+            let buffer = buffers.into_iter().next().unwrap().read(cx);
+            assert!(buffer.read_only());
+            if base_text.is_empty() {
+                assert!(buffer.diff_base().is_none());
+            } else {
+                let diff_base = buffer.diff_base().map(|diff_base| diff_base.to_string());
+                assert_eq!(diff_base.as_deref(), Some(base_text));
+            }
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            state
+        })
+    }
+}
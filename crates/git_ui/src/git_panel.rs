@@ -0,0 +1,1346 @@
+use crate::{
+    ApplyStash, Commit, DropStash, GitPanelSettings, NextCommitMessage, PopStash,
+    PreviousCommitMessage, StageAll, StashChanges, ToggleAmend, ToggleFocus, UnstageAll,
+};
+use anyhow::Result;
+use db::kvp::KEY_VALUE_STORE;
+use editor::{
+    actions::ExpandAllHunkDiffs, CompletionProvider, Editor, MultiBuffer,
+    DEFAULT_MULTIBUFFER_CONTEXT,
+};
+use git::{
+    repository::{GitRepository, RepoPath, Stash},
+    status::{FileChange, FileChangeKind},
+};
+use gpui::{
+    Action, AppContext, AsyncWindowContext, EventEmitter, FocusHandle, FocusableView, Model,
+    Pixels, PromptLevel, Render, Subscription, Task, View, ViewContext, VisualContext, WeakView,
+    WindowContext,
+};
+use language::{Buffer, Capability, CodeLabel, LanguageServerId, Point, ToPoint};
+use parking_lot::RwLock;
+use project::{Completion, Fs, Project, ProjectPath};
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use std::sync::Arc;
+use ui::{popover_menu, prelude::*, Checkbox, ContextMenu, ListItem, Selection, Tooltip};
+use util::ResultExt;
+use workspace::{
+    dock::{DockPosition, Panel, PanelEvent},
+    notifications::DetachAndPromptErr,
+    Workspace,
+};
+
+const GIT_PANEL_KEY: &str = "GitPanel";
+const CO_AUTHOR_PREFIX: &str = "Co-authored-by: ";
+const MAX_COMMIT_HISTORY: usize = 50;
+const MAX_AUTHOR_COMMITS: usize = 1000;
+
+/// A panel listing the changes of the project's repository, staging them and committing them.
+pub struct GitPanel {
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    fs: Arc<dyn Fs>,
+    width: Option<Pixels>,
+    focus_handle: FocusHandle,
+    commit_editor: View<Editor>,
+    /// The working directory of the repository picked in the panel, if any. Otherwise, the
+    /// panel follows the repository of the active file.
+    selected_repository: Option<ProjectPath>,
+    changes: Vec<FileChange>,
+    stashes: Vec<Stash>,
+    amend: bool,
+    /// The committed messages, most recent first.
+    commit_history: Vec<String>,
+    history_ix: Option<usize>,
+    /// The message being written before navigating the history.
+    draft_message: String,
+    commit_output: Option<CommitOutput>,
+    pending_commit: Option<Task<()>>,
+    pending_refresh: Task<()>,
+    pending_serialization: Task<Option<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct CommitOutput {
+    text: SharedString,
+    is_error: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SerializedGitPanel {
+    width: Option<Pixels>,
+    #[serde(default)]
+    commit_history: Vec<String>,
+}
+
+impl GitPanel {
+    pub fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
+        let fs = workspace.app_state().fs.clone();
+        let project = workspace.project().clone();
+        let workspace_handle = cx.view().downgrade();
+        cx.new_view(|cx: &mut ViewContext<Self>| {
+            let commit_editor = cx.new_view(|cx| {
+                let mut editor = Editor::auto_height(10, cx);
+                editor.set_placeholder_text("Commit message", cx);
+                editor.set_completion_provider(Box::new(CoAuthorCompletionProvider {
+                    project: project.clone(),
+                }));
+                editor
+            });
+
+            let subscriptions = vec![cx.subscribe(&project, |this, _, event, cx| match event {
+                project::Event::WorktreeUpdatedGitRepositories
+                | project::Event::WorktreeUpdatedEntries(..)
+                | project::Event::WorktreeAdded
+                | project::Event::WorktreeRemoved(_) => this.refresh(cx),
+                project::Event::ActiveEntryChanged(_) if this.selected_repository.is_none() => {
+                    this.refresh(cx)
+                }
+                _ => {}
+            })];
+
+            let mut this = Self {
+                workspace: workspace_handle,
+                project,
+                fs,
+                width: None,
+                focus_handle: cx.focus_handle(),
+                commit_editor,
+                selected_repository: None,
+                changes: Vec::new(),
+                stashes: Vec::new(),
+                amend: false,
+                commit_history: Vec::new(),
+                history_ix: None,
+                draft_message: String::new(),
+                commit_output: None,
+                pending_commit: None,
+                pending_refresh: Task::ready(()),
+                pending_serialization: Task::ready(None),
+                _subscriptions: subscriptions,
+            };
+            this.refresh(cx);
+            this
+        })
+    }
+
+    pub fn load(
+        workspace: WeakView<Workspace>,
+        cx: AsyncWindowContext,
+    ) -> Task<Result<View<Self>>> {
+        cx.spawn(|mut cx| async move {
+            let serialized_panel = if let Some(panel) = cx
+                .background_executor()
+                .spawn(async move { KEY_VALUE_STORE.read_kvp(GIT_PANEL_KEY) })
+                .await
+                .log_err()
+                .flatten()
+            {
+                Some(serde_json::from_str::<SerializedGitPanel>(&panel)?)
+            } else {
+                None
+            };
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let panel = Self::new(workspace, cx);
+                if let Some(serialized_panel) = serialized_panel {
+                    panel.update(cx, |panel, cx| {
+                        panel.width = serialized_panel.width.map(|w| w.round());
+                        panel.commit_history = serialized_panel.commit_history;
+                        cx.notify();
+                    });
+                }
+                panel
+            })
+        })
+    }
+
+    fn serialize(&mut self, cx: &mut ViewContext<Self>) {
+        let serialized_panel = SerializedGitPanel {
+            width: self.width,
+            commit_history: self.commit_history.clone(),
+        };
+        self.pending_serialization = cx.background_executor().spawn(
+            async move {
+                KEY_VALUE_STORE
+                    .write_kvp(
+                        GIT_PANEL_KEY.into(),
+                        serde_json::to_string(&serialized_panel)?,
+                    )
+                    .await?;
+                anyhow::Ok(())
+            }
+            .log_err(),
+        );
+    }
+
+    /// Returns the repository picked in the panel, or the one containing the active file, along
+    /// with its working directory.
+    fn repository(&self, cx: &AppContext) -> Option<(ProjectPath, Arc<dyn GitRepository>)> {
+        let project = self.project.read(cx);
+        self.selected_repository
+            .as_ref()
+            .and_then(|selected_repository| {
+                project
+                    .git_repositories(cx)
+                    .into_iter()
+                    .find(|(work_directory, _)| work_directory == selected_repository)
+            })
+            .or_else(|| project.active_git_repository(cx))
+    }
+
+    fn select_repository(&mut self, work_directory: ProjectPath, cx: &mut ViewContext<Self>) {
+        self.selected_repository = Some(work_directory);
+        self.refresh(cx);
+    }
+
+    /// Names a repository after its working directory, prefixed with its worktree's name.
+    fn repository_name(&self, work_directory: &ProjectPath, cx: &AppContext) -> SharedString {
+        let Some(worktree) = self
+            .project
+            .read(cx)
+            .worktree_for_id(work_directory.worktree_id, cx)
+        else {
+            return work_directory.path.to_string_lossy().into_owned().into();
+        };
+        let root_name = worktree.read(cx).root_name();
+        if work_directory.path.as_os_str().is_empty() {
+            root_name.to_string().into()
+        } else {
+            format!("{root_name}/{}", work_directory.path.to_string_lossy()).into()
+        }
+    }
+
+    fn refresh(&mut self, cx: &mut ViewContext<Self>) {
+        let Some((_, repo)) = self.repository(cx) else {
+            self.changes.clear();
+            self.stashes.clear();
+            cx.notify();
+            return;
+        };
+        let changes = cx
+            .background_executor()
+            .spawn(async move { (repo.changes(), repo.stashes()) });
+        self.pending_refresh = cx.spawn(|this, mut cx| async move {
+            let (changes, stashes) = changes.await;
+            this.update(&mut cx, |this, cx| {
+                this.changes = changes.log_err().unwrap_or_default();
+                this.stashes = stashes.log_err().unwrap_or_default();
+                cx.notify();
+            })
+            .log_err();
+        });
+    }
+
+    fn update_index(&mut self, paths: Vec<RepoPath>, stage: bool, cx: &mut ViewContext<Self>) {
+        let Some((_, repo)) = self.repository(cx) else {
+            return;
+        };
+        let update = cx.background_executor().spawn(async move {
+            if stage {
+                repo.stage_paths(&paths)
+            } else {
+                repo.unstage_paths(&paths)
+            }
+        });
+        cx.spawn(|this, mut cx| async move {
+            update.await?;
+            this.update(&mut cx, |this, cx| this.refresh(cx))
+        })
+        .detach_and_prompt_err("Failed to update the git index", cx, |_, _| None);
+    }
+
+    fn stage_all(&mut self, _: &StageAll, cx: &mut ViewContext<Self>) {
+        let paths = self
+            .changes
+            .iter()
+            .filter(|change| change.unstaged.is_some())
+            .map(|change| change.path.clone())
+            .collect::<Vec<_>>();
+        if !paths.is_empty() {
+            self.update_index(paths, true, cx);
+        }
+    }
+
+    fn unstage_all(&mut self, _: &UnstageAll, cx: &mut ViewContext<Self>) {
+        let paths = self
+            .changes
+            .iter()
+            .filter(|change| change.staged.is_some())
+            .map(|change| change.path.clone())
+            .collect::<Vec<_>>();
+        if !paths.is_empty() {
+            self.update_index(paths, false, cx);
+        }
+    }
+
+    /// Runs a stash operation in the background, refreshing the panel once it's done.
+    fn update_stashes(
+        &mut self,
+        error_message: &'static str,
+        operation: impl FnOnce(&dyn GitRepository) -> Result<()> + Send + 'static,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some((_, repo)) = self.repository(cx) else {
+            return;
+        };
+        let update = cx
+            .background_executor()
+            .spawn(async move { operation(repo.as_ref()) });
+        cx.spawn(|this, mut cx| async move {
+            update.await?;
+            this.update(&mut cx, |this, cx| this.refresh(cx))
+        })
+        .detach_and_prompt_err(error_message, cx, |_, _| None);
+    }
+
+    pub(crate) fn stash_changes(&mut self, _: &StashChanges, cx: &mut ViewContext<Self>) {
+        if self.changes.is_empty() {
+            return;
+        }
+        self.update_stashes(
+            "Failed to stash the changes",
+            |repo| repo.stash_save(None),
+            cx,
+        );
+    }
+
+    pub(crate) fn apply_stash(&mut self, _: &ApplyStash, cx: &mut ViewContext<Self>) {
+        self.apply_stash_at(0, false, cx);
+    }
+
+    pub(crate) fn pop_stash(&mut self, _: &PopStash, cx: &mut ViewContext<Self>) {
+        self.apply_stash_at(0, true, cx);
+    }
+
+    pub(crate) fn drop_stash(&mut self, _: &DropStash, cx: &mut ViewContext<Self>) {
+        self.drop_stash_at(0, cx);
+    }
+
+    fn apply_stash_at(&mut self, index: usize, pop: bool, cx: &mut ViewContext<Self>) {
+        if index >= self.stashes.len() {
+            return;
+        }
+        if pop {
+            self.update_stashes(
+                "Failed to pop the stash",
+                move |repo| repo.stash_pop(index),
+                cx,
+            );
+        } else {
+            self.update_stashes(
+                "Failed to apply the stash",
+                move |repo| repo.stash_apply(index),
+                cx,
+            );
+        }
+    }
+
+    fn drop_stash_at(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        let Some(stash) = self.stashes.get(index) else {
+            return;
+        };
+        let answer = cx.prompt(
+            PromptLevel::Warning,
+            &format!("Drop stash@{{{index}}}?"),
+            Some(&stash.message),
+            &["Drop", "Cancel"],
+        );
+        cx.spawn(|this, mut cx| async move {
+            if answer.await? == 0 {
+                this.update(&mut cx, |this, cx| {
+                    this.update_stashes(
+                        "Failed to drop the stash",
+                        move |repo| repo.stash_drop(index),
+                        cx,
+                    )
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Opens the changes of a stash as a patch in a read-only editor.
+    fn open_stash_diff(&mut self, index: usize, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let Some((_, repo)) = self.repository(cx) else {
+            return;
+        };
+        let Some(stash) = self.stashes.get(index) else {
+            return;
+        };
+        let title = format!("stash@{{{index}}}: {}", stash.message);
+        let languages = self.project.read(cx).languages().clone();
+        let patch = cx
+            .background_executor()
+            .spawn(async move { repo.stash_diff(index) });
+        cx.spawn(|_, mut cx| async move {
+            let patch = patch.await?;
+            let language = languages.language_for_name("Diff").await.ok();
+            workspace.update(&mut cx, |workspace, cx| {
+                let buffer = cx.new_model(|cx| {
+                    let mut buffer = Buffer::local(patch, cx);
+                    buffer.set_language_registry(languages);
+                    buffer.set_language(language, cx);
+                    buffer.set_capability(Capability::ReadOnly, cx);
+                    buffer
+                });
+                let multibuffer =
+                    cx.new_model(|cx| MultiBuffer::singleton(buffer, cx).with_title(title));
+                let project = workspace.project().clone();
+                let editor =
+                    cx.new_view(|cx| Editor::for_multibuffer(multibuffer, Some(project), true, cx));
+                workspace.add_item_to_active_pane(Box::new(editor), None, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to open the stash", cx, |_, _| None);
+    }
+
+    fn has_staged_changes(&self) -> bool {
+        self.changes.iter().any(|change| change.staged.is_some())
+    }
+
+    fn can_commit(&self, cx: &AppContext) -> bool {
+        self.pending_commit.is_none()
+            && (self.amend || self.has_staged_changes())
+            && !self.commit_editor.read(cx).text(cx).trim().is_empty()
+    }
+
+    fn commit(&mut self, _: &Commit, cx: &mut ViewContext<Self>) {
+        if !self.can_commit(cx) {
+            return;
+        }
+        let Some((_, repo)) = self.repository(cx) else {
+            return;
+        };
+        let message = self.commit_editor.read(cx).text(cx).trim().to_string();
+        let amend = self.amend;
+        let commit = cx.background_executor().spawn({
+            let message = message.clone();
+            async move { repo.commit(&message, amend) }
+        });
+        self.commit_output = None;
+        self.pending_commit = Some(cx.spawn(|this, mut cx| async move {
+            let result = commit.await;
+            this.update(&mut cx, |this, cx| {
+                this.pending_commit = None;
+                match result {
+                    Ok(output) => {
+                        this.commit_output = Some(CommitOutput {
+                            text: if output.is_empty() {
+                                "Committed".into()
+                            } else {
+                                output.into()
+                            },
+                            is_error: false,
+                        });
+                        this.commit_history.retain(|entry| *entry != message);
+                        this.commit_history.insert(0, message);
+                        this.commit_history.truncate(MAX_COMMIT_HISTORY);
+                        this.history_ix = None;
+                        this.amend = false;
+                        this.commit_editor
+                            .update(cx, |editor, cx| editor.set_text("", cx));
+                        this.serialize(cx);
+                    }
+                    Err(error) => {
+                        this.commit_output = Some(CommitOutput {
+                            text: error.to_string().into(),
+                            is_error: true,
+                        });
+                    }
+                }
+                this.refresh(cx);
+            })
+            .log_err();
+        }));
+        cx.notify();
+    }
+
+    fn toggle_amend(&mut self, _: &ToggleAmend, cx: &mut ViewContext<Self>) {
+        self.amend = !self.amend;
+        // Amending starts from the message of the commit being amended.
+        if self.amend && self.commit_editor.read(cx).text(cx).trim().is_empty() {
+            if let Some(message) = self
+                .repository(cx)
+                .and_then(|(_, repo)| repo.head_commit_message())
+            {
+                self.commit_editor
+                    .update(cx, |editor, cx| editor.set_text(message.trim(), cx));
+            }
+        }
+        cx.notify();
+    }
+
+    fn previous_commit_message(&mut self, _: &PreviousCommitMessage, cx: &mut ViewContext<Self>) {
+        if self.commit_history.is_empty() {
+            return;
+        }
+        let ix = match self.history_ix {
+            Some(ix) => (ix + 1).min(self.commit_history.len() - 1),
+            None => {
+                self.draft_message = self.commit_editor.read(cx).text(cx);
+                0
+            }
+        };
+        self.show_history_entry(Some(ix), cx);
+    }
+
+    fn next_commit_message(&mut self, _: &NextCommitMessage, cx: &mut ViewContext<Self>) {
+        match self.history_ix {
+            Some(0) => self.show_history_entry(None, cx),
+            Some(ix) => self.show_history_entry(Some(ix - 1), cx),
+            None => {}
+        }
+    }
+
+    fn show_history_entry(&mut self, ix: Option<usize>, cx: &mut ViewContext<Self>) {
+        self.history_ix = ix;
+        let message = match ix {
+            Some(ix) => self.commit_history[ix].clone(),
+            None => std::mem::take(&mut self.draft_message),
+        };
+        self.commit_editor.update(cx, |editor, cx| {
+            editor.set_text(message, cx);
+            editor.move_to_end(&editor::actions::MoveToEnd, cx);
+        });
+    }
+
+    /// Opens the working tree changes of a file against the repository's diff base (by default,
+    /// the changes that aren't staged yet) in a diff multibuffer, or the file itself when there
+    /// are none.
+    fn open_diff(&mut self, path: RepoPath, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let Some((work_directory, repo)) = self.repository(cx) else {
+            return;
+        };
+        let diff_base = repo.diff_base();
+        let project = self.project.clone();
+        let project_path = ProjectPath {
+            worktree_id: work_directory.worktree_id,
+            path: work_directory.path.join(&path.0).into(),
+        };
+        let open_buffer = project.update(cx, |project, cx| {
+            project.open_buffer(project_path.clone(), cx)
+        });
+        cx.spawn(|_, mut cx| async move {
+            let buffer = open_buffer.await?;
+            let recalc = buffer.update(&mut cx, |buffer, cx| buffer.git_diff_recalc(cx))?;
+            if let Some(recalc) = recalc {
+                recalc.await;
+            }
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let snapshot = buffer.read(cx).snapshot();
+                let hunk_ranges = snapshot
+                    .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                    .map(|hunk| hunk.buffer_range)
+                    .collect::<Vec<_>>();
+                if hunk_ranges.is_empty() {
+                    workspace
+                        .open_path(project_path, None, true, cx)
+                        .detach_and_log_err(cx);
+                    return;
+                }
+
+                let title = format!("{} ({} ↔ working tree)", path.display(), diff_base.label());
+                let multibuffer = cx.new_model(|cx| {
+                    let mut multibuffer =
+                        MultiBuffer::new(0, Capability::ReadWrite).with_title(title);
+                    multibuffer.push_excerpts_with_context_lines(
+                        buffer,
+                        hunk_ranges,
+                        DEFAULT_MULTIBUFFER_CONTEXT,
+                        cx,
+                    );
+                    multibuffer
+                });
+                let editor = cx.new_view(|cx| {
+                    let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+                    editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx);
+                    editor
+                });
+                workspace.add_item_to_active_pane(Box::new(editor), None, cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to open the diff", cx, |_, _| None);
+    }
+
+    fn render_section_header(
+        &self,
+        title: &'static str,
+        count: usize,
+        action: Box<dyn Action>,
+        tooltip: &'static str,
+        icon: IconName,
+        enabled: bool,
+    ) -> impl IntoElement {
+        h_flex()
+            .px_2()
+            .pt_2()
+            .pb_1()
+            .justify_between()
+            .child(
+                h_flex()
+                    .gap_1()
+                    .child(Label::new(title).size(LabelSize::Small).color(Color::Muted))
+                    .child(
+                        Label::new(count.to_string())
+                            .size(LabelSize::Small)
+                            .color(Color::Muted),
+                    ),
+            )
+            .child(
+                IconButton::new(title, icon)
+                    .icon_size(IconSize::Small)
+                    .disabled(!enabled)
+                    .tooltip({
+                        let action = action.boxed_clone();
+                        move |cx| Tooltip::for_action(tooltip, action.as_ref(), cx)
+                    })
+                    .on_click(move |_, cx| cx.dispatch_action(action.boxed_clone())),
+            )
+    }
+
+    fn render_change(
+        &self,
+        ix: usize,
+        change: &FileChange,
+        staged: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<impl IntoElement> {
+        let kind = if staged {
+            change.staged?
+        } else {
+            change.unstaged?
+        };
+        let (status, color) = match kind {
+            FileChangeKind::Added => ("A", Color::Created),
+            FileChangeKind::Modified => ("M", Color::Modified),
+            FileChangeKind::Deleted => ("D", Color::Deleted),
+            FileChangeKind::Conflict => ("!", Color::Conflict),
+        };
+        let file_name = change
+            .path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_else(|| change.path.to_string_lossy().to_string());
+        let directory = change
+            .path
+            .parent()
+            .map(|parent| parent.to_string_lossy().to_string())
+            .filter(|parent| !parent.is_empty());
+        let path = change.path.clone();
+        let (id, button_id) = if staged {
+            (("staged-change", ix), ("unstage-file", ix))
+        } else {
+            (("unstaged-change", ix), ("stage-file", ix))
+        };
+
+        Some(
+            ListItem::new(id)
+                .inset(true)
+                .start_slot(Label::new(status).size(LabelSize::Small).color(color))
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .overflow_x_hidden()
+                        .child(Label::new(file_name).single_line())
+                        .children(directory.map(|directory| {
+                            Label::new(directory)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line()
+                        })),
+                )
+                .end_hover_slot(
+                    IconButton::new(
+                        button_id,
+                        if staged {
+                            IconName::Dash
+                        } else {
+                            IconName::Plus
+                        },
+                    )
+                    .icon_size(IconSize::Small)
+                    .tooltip(move |cx| {
+                        Tooltip::text(if staged { "Unstage File" } else { "Stage File" }, cx)
+                    })
+                    .on_click(cx.listener({
+                        let path = path.clone();
+                        move |this, _, cx| this.update_index(vec![path.clone()], !staged, cx)
+                    })),
+                )
+                .when(kind != FileChangeKind::Deleted, |item| {
+                    item.on_click(cx.listener(move |this, _, cx| this.open_diff(path.clone(), cx)))
+                }),
+        )
+    }
+
+    fn render_stash(
+        &self,
+        ix: usize,
+        stash: &Stash,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let index = stash.index;
+        ListItem::new(("stash", ix))
+            .inset(true)
+            .child(
+                h_flex()
+                    .gap_2()
+                    .overflow_x_hidden()
+                    .child(Label::new(stash.message.clone()).single_line())
+                    .child(
+                        Label::new(format!("stash@{{{index}}}"))
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line(),
+                    ),
+            )
+            .end_hover_slot(
+                h_flex()
+                    .gap_1()
+                    .child(
+                        IconButton::new(("apply-stash", ix), IconName::Check)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Apply Stash", cx))
+                            .on_click(cx.listener(move |this, _, cx| {
+                                this.apply_stash_at(index, false, cx)
+                            })),
+                    )
+                    .child(
+                        IconButton::new(("pop-stash", ix), IconName::ArrowUpFromLine)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Pop Stash", cx))
+                            .on_click(
+                                cx.listener(move |this, _, cx| {
+                                    this.apply_stash_at(index, true, cx)
+                                }),
+                            ),
+                    )
+                    .child(
+                        IconButton::new(("drop-stash", ix), IconName::Trash)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Drop Stash", cx))
+                            .on_click(
+                                cx.listener(move |this, _, cx| this.drop_stash_at(index, cx)),
+                            ),
+                    ),
+            )
+            .on_click(cx.listener(move |this, _, cx| this.open_stash_diff(index, cx)))
+    }
+
+    fn render_commit_editor(&self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let can_commit = self.can_commit(cx);
+        let commit_label = if self.pending_commit.is_some() {
+            "Committing..."
+        } else if self.amend {
+            "Amend"
+        } else {
+            "Commit"
+        };
+
+        v_flex()
+            .p_2()
+            .gap_2()
+            .border_t_1()
+            .border_color(cx.theme().colors().border)
+            .child(
+                div()
+                    .key_context("GitCommitEditor")
+                    .px_1()
+                    .py_0p5()
+                    .border_1()
+                    .border_color(cx.theme().colors().border)
+                    .rounded_md()
+                    .child(self.commit_editor.clone()),
+            )
+            .child(
+                h_flex()
+                    .justify_between()
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Checkbox::new(
+                                    "amend",
+                                    if self.amend {
+                                        Selection::Selected
+                                    } else {
+                                        Selection::Unselected
+                                    },
+                                )
+                                .on_click(
+                                    cx.listener(|this, _, cx| this.toggle_amend(&ToggleAmend, cx)),
+                                ),
+                            )
+                            .child(Label::new("Amend").size(LabelSize::Small)),
+                    )
+                    .child(
+                        Button::new("commit", commit_label)
+                            .style(ButtonStyle::Filled)
+                            .disabled(!can_commit)
+                            .tooltip(|cx| Tooltip::for_action("Commit Staged Changes", &Commit, cx))
+                            .on_click(cx.listener(|this, _, cx| this.commit(&Commit, cx))),
+                    ),
+            )
+            .children(self.commit_output.as_ref().map(|output| {
+                Label::new(output.text.clone())
+                    .size(LabelSize::Small)
+                    .color(if output.is_error {
+                        Color::Error
+                    } else {
+                        Color::Muted
+                    })
+            }))
+    }
+}
+
+impl Render for GitPanel {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let repository = self.repository(cx);
+        let branch_name = repository.as_ref().and_then(|(_, repo)| repo.branch_name());
+        let has_repository = repository.is_some();
+        // Nested repositories and submodules are listed separately, to pick from.
+        let repositories = self.project.read(cx).git_repositories(cx);
+        let repository_picker = (repositories.len() > 1)
+            .then(|| {
+                let (selected_work_directory, _) = repository.as_ref()?;
+                let selected_name = self.repository_name(selected_work_directory, cx);
+                let entries = repositories
+                    .into_iter()
+                    .map(|(work_directory, repo)| {
+                        let name = self.repository_name(&work_directory, cx);
+                        let name = if repo.is_submodule() {
+                            format!("{name} (submodule)").into()
+                        } else {
+                            name
+                        };
+                        let is_selected = work_directory == *selected_work_directory;
+                        (work_directory, name, is_selected)
+                    })
+                    .collect::<Vec<_>>();
+                let panel = cx.view().downgrade();
+                Some(
+                    popover_menu("git-repository-picker")
+                        .menu(move |cx| {
+                            let entries = entries.clone();
+                            let panel = panel.clone();
+                            Some(ContextMenu::build(cx, move |mut menu, _| {
+                                for (work_directory, name, is_selected) in entries {
+                                    let panel = panel.clone();
+                                    menu =
+                                        menu.toggleable_entry(name, is_selected, None, move |cx| {
+                                            let work_directory = work_directory.clone();
+                                            panel
+                                                .update(cx, |panel, cx| {
+                                                    panel.select_repository(work_directory, cx)
+                                                })
+                                                .ok();
+                                        });
+                                }
+                                menu
+                            }))
+                        })
+                        .trigger(
+                            Button::new("git-repository-picker-trigger", selected_name)
+                                .label_size(LabelSize::Small)
+                                .style(ButtonStyle::Subtle)
+                                .tooltip(|cx| Tooltip::text("Select Repository", cx)),
+                        ),
+                )
+            })
+            .flatten();
+
+        let mut list = v_flex()
+            .id("git-changes")
+            .flex_1()
+            .overflow_y_scroll()
+            .pb_2();
+        if !has_repository {
+            list = list.child(
+                v_flex()
+                    .p_4()
+                    .child(Label::new("No git repository in this project.").color(Color::Muted)),
+            );
+        } else if self.changes.is_empty() {
+            list = list.child(
+                v_flex()
+                    .p_4()
+                    .child(Label::new("No changes.").color(Color::Muted)),
+            );
+        } else {
+            let staged_count = self
+                .changes
+                .iter()
+                .filter(|change| change.staged.is_some())
+                .count();
+            let unstaged_count = self
+                .changes
+                .iter()
+                .filter(|change| change.unstaged.is_some())
+                .count();
+
+            list = list.child(self.render_section_header(
+                "Staged Changes",
+                staged_count,
+                Box::new(UnstageAll),
+                "Unstage All",
+                IconName::Dash,
+                staged_count > 0,
+            ));
+            let staged = self
+                .changes
+                .iter()
+                .enumerate()
+                .filter_map(|(ix, change)| self.render_change(ix, change, true, cx))
+                .collect::<Vec<_>>();
+            list = list.children(staged);
+
+            list = list.child(self.render_section_header(
+                "Changes",
+                unstaged_count,
+                Box::new(StageAll),
+                "Stage All",
+                IconName::Plus,
+                unstaged_count > 0,
+            ));
+            let unstaged = self
+                .changes
+                .iter()
+                .enumerate()
+                .filter_map(|(ix, change)| self.render_change(ix, change, false, cx))
+                .collect::<Vec<_>>();
+            list = list.children(unstaged);
+        }
+        if has_repository {
+            list = list.child(self.render_section_header(
+                "Stashes",
+                self.stashes.len(),
+                Box::new(StashChanges),
+                "Stash All Changes",
+                IconName::ArrowDownFromLine,
+                !self.changes.is_empty(),
+            ));
+            let stashes = self
+                .stashes
+                .iter()
+                .enumerate()
+                .map(|(ix, stash)| self.render_stash(ix, stash, cx))
+                .collect::<Vec<_>>();
+            list = list.children(stashes);
+        }
+
+        v_flex()
+            .key_context("GitPanel")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .on_action(cx.listener(Self::commit))
+            .on_action(cx.listener(Self::toggle_amend))
+            .on_action(cx.listener(Self::stage_all))
+            .on_action(cx.listener(Self::unstage_all))
+            .on_action(cx.listener(Self::previous_commit_message))
+            .on_action(cx.listener(Self::next_commit_message))
+            .child(
+                h_flex()
+                    .h_8()
+                    .px_2()
+                    .gap_1()
+                    .border_b_1()
+                    .border_color(cx.theme().colors().border)
+                    .child(Label::new("Git"))
+                    .children(repository_picker)
+                    .children(branch_name.map(|branch_name| {
+                        Label::new(branch_name)
+                            .size(LabelSize::Small)
+                            .color(Color::Muted)
+                            .single_line()
+                    })),
+            )
+            .child(list)
+            .when(has_repository, |panel| {
+                panel.child(self.render_commit_editor(cx))
+            })
+    }
+}
+
+impl FocusableView for GitPanel {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl EventEmitter<PanelEvent> for GitPanel {}
+
+impl Panel for GitPanel {
+    fn persistent_name() -> &'static str {
+        "GitPanel"
+    }
+
+    fn position(&self, cx: &WindowContext) -> DockPosition {
+        GitPanelSettings::get_global(cx).dock
+    }
+
+    fn position_is_valid(&self, position: DockPosition) -> bool {
+        matches!(position, DockPosition::Left | DockPosition::Right)
+    }
+
+    fn set_position(&mut self, position: DockPosition, cx: &mut ViewContext<Self>) {
+        settings::update_settings_file::<GitPanelSettings>(self.fs.clone(), cx, move |settings| {
+            settings.dock = Some(position)
+        });
+    }
+
+    fn size(&self, cx: &WindowContext) -> Pixels {
+        self.width
+            .unwrap_or_else(|| GitPanelSettings::get_global(cx).default_width)
+    }
+
+    fn set_size(&mut self, size: Option<Pixels>, cx: &mut ViewContext<Self>) {
+        self.width = size;
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn icon(&self, cx: &WindowContext) -> Option<IconName> {
+        GitPanelSettings::get_global(cx)
+            .button
+            .then_some(IconName::GitBranch)
+    }
+
+    fn icon_tooltip(&self, _: &WindowContext) -> Option<&'static str> {
+        Some("Git Panel")
+    }
+
+    fn icon_label(&self, _: &WindowContext) -> Option<String> {
+        let count = self.changes.len();
+        (count > 0).then(|| count.to_string())
+    }
+
+    fn toggle_action(&self) -> Box<dyn Action> {
+        Box::new(ToggleFocus)
+    }
+}
+
+/// Completes the `Co-authored-by:` trailers of the commit message with the authors of the
+/// repository's recent commits.
+struct CoAuthorCompletionProvider {
+    project: Model<Project>,
+}
+
+impl CompletionProvider for CoAuthorCompletionProvider {
+    fn completions(
+        &self,
+        buffer: &Model<Buffer>,
+        buffer_position: language::Anchor,
+        cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Vec<Completion>>> {
+        let Some(repo) = self.project.read(cx).get_first_worktree_root_repo(cx) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let position = buffer_position.to_point(&snapshot);
+        let line = snapshot
+            .text_for_range(Point::new(position.row, 0)..position)
+            .collect::<String>();
+        let Some(query) = co_author_query(&line) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let query_start = Point::new(position.row, (line.len() - query.len()) as u32);
+        let old_range = snapshot.anchor_before(query_start)..snapshot.anchor_after(position);
+        let query = query.to_lowercase();
+
+        cx.background_executor().spawn(async move {
+            let authors = repo.commit_authors(MAX_AUTHOR_COMMITS)?;
+            Ok(authors
+                .into_iter()
+                .filter(|author| author.to_lowercase().contains(&query))
+                .map(|author| Completion {
+                    old_range: old_range.clone(),
+                    new_text: author.clone(),
+                    label: CodeLabel::plain(author, None),
+                    documentation: None,
+                    server_id: LanguageServerId(0),
+                    lsp_completion: Default::default(),
+                    confirm: None,
+                    show_new_completions_on_confirm: false,
+                })
+                .collect())
+        })
+    }
+
+    fn resolve_completions(
+        &self,
+        _buffer: Model<Buffer>,
+        _completion_indices: Vec<usize>,
+        _completions: Arc<RwLock<Box<[Completion]>>>,
+        _cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<bool>> {
+        Task::ready(Ok(false))
+    }
+
+    fn apply_additional_edits_for_completion(
+        &self,
+        _buffer: Model<Buffer>,
+        _completion: Completion,
+        _push_to_history: bool,
+        _cx: &mut ViewContext<Editor>,
+    ) -> Task<Result<Option<language::Transaction>>> {
+        Task::ready(Ok(None))
+    }
+
+    fn is_completion_trigger(
+        &self,
+        buffer: &Model<Buffer>,
+        position: language::Anchor,
+        _text: &str,
+        _trigger_in_words: bool,
+        cx: &mut ViewContext<Editor>,
+    ) -> bool {
+        let buffer = buffer.read(cx);
+        let position = position.to_point(buffer);
+        let line = buffer
+            .text_for_range(Point::new(position.row, 0)..position)
+            .collect::<String>();
+        co_author_query(&line).is_some()
+    }
+}
+
+/// Returns the part of a `Co-authored-by:` trailer typed after its key.
+fn co_author_query(line: &str) -> Option<&str> {
+    let key = line.get(..CO_AUTHOR_PREFIX.len())?;
+    key.eq_ignore_ascii_case(CO_AUTHOR_PREFIX)
+        .then(|| &line[CO_AUTHOR_PREFIX.len()..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+    use std::path::Path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_stashes(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let fs = app_state.fs.as_fake();
+        let dot_git = Path::new("/root/.git");
+        fs.insert_tree("/root", json!({ ".git": {}, "a.txt": "a" }))
+            .await;
+        fs.with_git_state(dot_git, false, |state| {
+            state.branch_name = Some("main".into());
+            state.file_changes = vec![FileChange {
+                path: Path::new("a.txt").into(),
+                staged: None,
+                unstaged: Some(FileChangeKind::Modified),
+            }];
+            state.stashes = vec![Stash {
+                index: 0,
+                message: "Older changes".into(),
+            }];
+            state
+                .stash_diffs
+                .insert("WIP on main".into(), "diff --git a/a.txt b/a.txt\n".into());
+        });
+        let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let panel = workspace.update(cx, |workspace, cx| GitPanel::new(workspace, cx));
+        cx.run_until_parked();
+
+        let stash_messages = |cx: &mut VisualTestContext| {
+            panel.update(cx, |panel, _| {
+                panel
+                    .stashes
+                    .iter()
+                    .map(|stash| (stash.index, stash.message.clone()))
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(stash_messages(cx), [(0, "Older changes".to_string())]);
+
+        // New stashes are listed first, with git's default message.
+        panel.update(cx, |panel, cx| panel.stash_changes(&StashChanges, cx));
+        cx.run_until_parked();
+        assert_eq!(
+            stash_messages(cx),
+            [(0, "WIP on main".into()), (1, "Older changes".to_string())]
+        );
+
+        panel.update(cx, |panel, cx| panel.open_stash_diff(0, cx));
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            let editor = workspace.active_item_as::<Editor>(cx).unwrap();
+            assert_eq!(editor.read(cx).text(cx), "diff --git a/a.txt b/a.txt\n");
+        });
+
+        // Applying a stash keeps it, unlike popping it.
+        panel.update(cx, |panel, cx| panel.apply_stash(&ApplyStash, cx));
+        cx.run_until_parked();
+        assert_eq!(stash_messages(cx).len(), 2);
+        panel.update(cx, |panel, cx| panel.pop_stash(&PopStash, cx));
+        cx.run_until_parked();
+        assert_eq!(stash_messages(cx), [(0, "Older changes".to_string())]);
+
+        // Dropping a stash is confirmed first.
+        panel.update(cx, |panel, cx| panel.drop_stash(&DropStash, cx));
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(1);
+        cx.run_until_parked();
+        assert_eq!(stash_messages(cx).len(), 1);
+        panel.update(cx, |panel, cx| panel.drop_stash(&DropStash, cx));
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        assert!(stash_messages(cx).is_empty());
+        fs.with_git_state(dot_git, false, |state| assert!(state.stashes.is_empty()));
+    }
+
+    #[gpui::test]
+    async fn test_staging_and_committing(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let fs = app_state.fs.as_fake();
+        let dot_git = Path::new("/root/.git");
+        fs.insert_tree("/root", json!({ ".git": {}, "a.txt": "a", "b.txt": "b" }))
+            .await;
+        fs.with_git_state(dot_git, false, |state| {
+            state.commits = vec!["Initial commit".into()];
+            state.file_changes = vec![
+                FileChange {
+                    path: Path::new("a.txt").into(),
+                    staged: None,
+                    unstaged: Some(FileChangeKind::Modified),
+                },
+                FileChange {
+                    path: Path::new("b.txt").into(),
+                    staged: None,
+                    unstaged: Some(FileChangeKind::Added),
+                },
+            ];
+        });
+        let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let panel = workspace.update(cx, |workspace, cx| GitPanel::new(workspace, cx));
+        cx.run_until_parked();
+
+        let changes = |cx: &mut VisualTestContext| {
+            panel.update(cx, |panel, _| {
+                panel
+                    .changes
+                    .iter()
+                    .map(|change| {
+                        (
+                            change.path.to_string_lossy().into_owned(),
+                            change.staged.is_some(),
+                            change.unstaged.is_some(),
+                        )
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+        let commits = || {
+            let mut commits = Vec::new();
+            fs.with_git_state(dot_git, false, |state| commits = state.commits.clone());
+            commits
+        };
+        assert_eq!(
+            changes(cx),
+            [
+                ("a.txt".to_string(), false, true),
+                ("b.txt".to_string(), false, true)
+            ]
+        );
+
+        panel.update(cx, |panel, cx| panel.stage_all(&StageAll, cx));
+        cx.run_until_parked();
+        assert_eq!(
+            changes(cx),
+            [
+                ("a.txt".to_string(), true, false),
+                ("b.txt".to_string(), true, false)
+            ]
+        );
+        panel.update(cx, |panel, cx| {
+            panel.update_index(vec![Path::new("b.txt").into()], false, cx)
+        });
+        cx.run_until_parked();
+        assert_eq!(
+            changes(cx),
+            [
+                ("a.txt".to_string(), true, false),
+                ("b.txt".to_string(), false, true)
+            ]
+        );
+
+        // Committing needs a message, and commits only the staged changes.
+        panel.update(cx, |panel, cx| panel.commit(&Commit, cx));
+        cx.run_until_parked();
+        assert_eq!(commits(), ["Initial commit"]);
+        panel.update(cx, |panel, cx| {
+            panel
+                .commit_editor
+                .update(cx, |editor, cx| editor.set_text("Update a", cx));
+            panel.commit(&Commit, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(commits(), ["Initial commit", "Update a"]);
+        assert_eq!(changes(cx), [("b.txt".to_string(), false, true)]);
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.commit_editor.read(cx).text(cx), "");
+            assert_eq!(panel.commit_history, ["Update a"]);
+            let output = panel.commit_output.as_ref().unwrap();
+            assert_eq!(
+                (output.text.as_ref(), output.is_error),
+                ("Committed", false)
+            );
+        });
+
+        // Amending starts from the message of the amended commit and replaces it.
+        panel.update(cx, |panel, cx| {
+            panel.toggle_amend(&ToggleAmend, cx);
+            assert_eq!(panel.commit_editor.read(cx).text(cx), "Update a");
+            panel
+                .commit_editor
+                .update(cx, |editor, cx| editor.set_text("Update a and b", cx));
+            panel.commit(&Commit, cx);
+        });
+        cx.run_until_parked();
+        assert_eq!(commits(), ["Initial commit", "Update a and b"]);
+        panel.update(cx, |panel, _| assert!(!panel.amend));
+
+        // Changes made outside of the panel are picked up when the repository changes.
+        fs.with_git_state(dot_git, true, |state| {
+            state.file_changes[0].staged = state.file_changes[0].unstaged.take();
+        });
+        cx.run_until_parked();
+        assert_eq!(changes(cx), [("b.txt".to_string(), true, false)]);
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            state
+        })
+    }
+
+    #[test]
+    fn test_co_author_query() {
+        assert_eq!(co_author_query("Co-authored-by: "), Some(""));
+        assert_eq!(co_author_query("co-authored-by: Jane"), Some("Jane"));
+        assert_eq!(co_author_query("Co-authored-by:"), None);
+        assert_eq!(co_author_query("Fix the co-authored-by: trailer"), None);
+    }
+}
@@ -0,0 +1,118 @@
+mod diff_base;
+mod file_history;
+mod git_panel;
+mod review;
+mod worktree_picker;
+
+pub use diff_base::{DiffBaseIndicator, DiffBasePicker};
+pub use git_panel::GitPanel;
+pub use worktree_picker::WorktreePicker;
+
+use gpui::{actions, AppContext, Pixels};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+use workspace::{dock::DockPosition, Workspace};
+
+actions!(
+    git_panel,
+    [
+        ToggleFocus,
+        Commit,
+        ToggleAmend,
+        StageAll,
+        UnstageAll,
+        PreviousCommitMessage,
+        NextCommitMessage,
+        StashChanges,
+        ApplyStash,
+        PopStash,
+        DropStash
+    ]
+);
+actions!(
+    git,
+    [
+        FileHistory,
+        OpenAtRevision,
+        OpenWorktree,
+        SelectDiffBase,
+        ReviewChanges,
+        NextReviewFile,
+        PreviousReviewFile,
+        ToggleFileViewed,
+        UnmarkLastViewedFile
+    ]
+);
+
+#[derive(Deserialize, Debug)]
+pub struct GitPanelSettings {
+    pub button: bool,
+    pub dock: DockPosition,
+    pub default_width: Pixels,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct GitPanelSettingsContent {
+    /// Whether to show the git panel button in the status bar.
+    ///
+    /// Default: true
+    pub button: Option<bool>,
+    /// Where to dock the git panel.
+    ///
+    /// Default: left
+    pub dock: Option<DockPosition>,
+    /// Default width of the git panel in pixels.
+    ///
+    /// Default: 320
+    pub default_width: Option<f32>,
+}
+
+impl Settings for GitPanelSettings {
+    const KEY: Option<&'static str> = Some("git_panel");
+
+    type FileContent = GitPanelSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
+
+pub fn init(cx: &mut AppContext) {
+    GitPanelSettings::register(cx);
+
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(|workspace, _: &ToggleFocus, cx| {
+            workspace.toggle_panel_focus::<GitPanel>(cx);
+        });
+        workspace.register_action(|workspace, action: &StashChanges, cx| {
+            if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                panel.update(cx, |panel, cx| panel.stash_changes(action, cx));
+            }
+        });
+        workspace.register_action(|workspace, action: &ApplyStash, cx| {
+            if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                panel.update(cx, |panel, cx| panel.apply_stash(action, cx));
+            }
+        });
+        workspace.register_action(|workspace, action: &PopStash, cx| {
+            if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                panel.update(cx, |panel, cx| panel.pop_stash(action, cx));
+            }
+        });
+        workspace.register_action(|workspace, action: &DropStash, cx| {
+            if let Some(panel) = workspace.panel::<GitPanel>(cx) {
+                panel.update(cx, |panel, cx| panel.drop_stash(action, cx));
+            }
+        });
+        workspace.register_action(file_history::FileHistory::toggle);
+        workspace.register_action(file_history::FileHistory::toggle_open_at_revision);
+        workspace.register_action(diff_base::DiffBasePicker::toggle);
+        workspace.register_action(worktree_picker::WorktreePicker::toggle);
+        workspace.register_action(review::ReviewChanges::deploy);
+    })
+    .detach();
+}
@@ -0,0 +1,790 @@
+use crate::{
+    NextReviewFile, PreviousReviewFile, ReviewChanges as Deploy, ToggleFileViewed,
+    UnmarkLastViewedFile,
+};
+use anyhow::Result;
+use editor::{
+    actions::ExpandAllHunkDiffs, scroll::Autoscroll, Anchor, Editor, EditorEvent, MultiBuffer,
+    DEFAULT_MULTIBUFFER_CONTEXT,
+};
+use git::repository::{DiffBase, GitRepository, RepoPath};
+use gpui::{
+    AnyElement, AnyView, AppContext, EventEmitter, FocusHandle, FocusableView, Model, Render,
+    Subscription, Task, View, ViewContext, VisualContext, WeakView,
+};
+use language::Buffer;
+use project::{Project, ProjectPath};
+use std::{
+    any::{Any, TypeId},
+    collections::hash_map::DefaultHasher,
+    hash::{Hash, Hasher},
+    sync::Arc,
+};
+use ui::{prelude::*, Checkbox, ListItem, Selection, Tooltip};
+use util::ResultExt;
+use workspace::{
+    item::{Item, ItemEvent, TabContentParams},
+    ItemNavHistory, Workspace,
+};
+
+/// A multibuffer of all the changes of a repository against its diff base, to review them file
+/// by file before pushing. Files marked as viewed are hidden until their changes change again, or
+/// until the view is closed, as they aren't persisted.
+pub struct ReviewChanges {
+    project: Model<Project>,
+    workspace: WeakView<Workspace>,
+    /// The working directory of the reviewed repository.
+    work_directory: ProjectPath,
+    focus_handle: FocusHandle,
+    editor: View<Editor>,
+    multibuffer: Model<MultiBuffer>,
+    files: Vec<ReviewedFile>,
+    /// The fingerprints of the changes of the files marked as viewed, as of when they were, in
+    /// the order they were.
+    viewed: Vec<(RepoPath, u64)>,
+    /// Whether the repository changed while the view was focused, and it should be refreshed
+    /// once it isn't.
+    is_stale: bool,
+    _refresh_task: Task<Result<()>>,
+    _subscriptions: Vec<Subscription>,
+}
+
+struct ReviewedFile {
+    path: RepoPath,
+    buffer: Model<Buffer>,
+    hunk_count: usize,
+    added_lines: u32,
+    removed_lines: u32,
+    fingerprint: u64,
+    /// The start of the file's excerpts, or `None` when it's viewed and left out.
+    start: Option<Anchor>,
+}
+
+impl ReviewedFile {
+    fn is_viewed(&self) -> bool {
+        self.start.is_none()
+    }
+}
+
+impl ReviewChanges {
+    pub(crate) fn deploy(workspace: &mut Workspace, _: &Deploy, cx: &mut ViewContext<Workspace>) {
+        if let Some(existing) = workspace.item_of_type::<ReviewChanges>(cx) {
+            workspace.activate_item(&existing, cx);
+            return;
+        }
+        let project = workspace.project().clone();
+        let Some((work_directory, _)) = project.read(cx).active_git_repository(cx) else {
+            return;
+        };
+        let workspace_handle = cx.view().downgrade();
+        let review =
+            cx.new_view(|cx| ReviewChanges::new(project, workspace_handle, work_directory, cx));
+        workspace.add_item_to_active_pane(Box::new(review), None, cx);
+    }
+
+    fn new(
+        project: Model<Project>,
+        workspace: WeakView<Workspace>,
+        work_directory: ProjectPath,
+        cx: &mut ViewContext<Self>,
+    ) -> Self {
+        let multibuffer = cx.new_model(|cx| {
+            MultiBuffer::new(project.read(cx).replica_id(), project.read(cx).capability())
+        });
+        let editor = cx.new_view(|cx| {
+            let mut editor =
+                Editor::for_multibuffer(multibuffer.clone(), Some(project.clone()), true, cx);
+            editor.set_vertical_scroll_margin(5, cx);
+            editor
+        });
+
+        let focus_handle = cx.focus_handle();
+        let subscriptions = vec![
+            cx.subscribe(&project, |this, _, event, cx| {
+                if let project::Event::WorktreeUpdatedGitRepositories = event {
+                    // Rebuilding the excerpts would move them from under the reviewer.
+                    if this.is_focused(cx) {
+                        this.is_stale = true;
+                    } else {
+                        this.refresh(cx);
+                    }
+                }
+            }),
+            cx.subscribe(&editor, |_, _, event: &EditorEvent, cx| {
+                cx.emit(event.clone())
+            }),
+            cx.on_focus_in(&focus_handle, |this, cx| {
+                if this.focus_handle.is_focused(cx) && !this.all_files_viewed() {
+                    this.editor.focus_handle(cx).focus(cx);
+                }
+            }),
+            cx.on_focus_out(&focus_handle, |this, cx| {
+                if this.is_stale && !this.is_focused(cx) {
+                    this.refresh(cx);
+                }
+            }),
+        ];
+
+        let mut this = Self {
+            project,
+            workspace,
+            work_directory,
+            focus_handle,
+            editor,
+            multibuffer,
+            files: Vec::new(),
+            viewed: Vec::new(),
+            is_stale: false,
+            _refresh_task: Task::ready(Ok(())),
+            _subscriptions: subscriptions,
+        };
+        this.refresh(cx);
+        this
+    }
+
+    fn is_focused(&self, cx: &WindowContext) -> bool {
+        self.focus_handle.contains_focused(cx)
+    }
+
+    fn repository(&self, cx: &AppContext) -> Option<Arc<dyn GitRepository>> {
+        self.project
+            .read(cx)
+            .git_repositories(cx)
+            .into_iter()
+            .find(|(work_directory, _)| *work_directory == self.work_directory)
+            .map(|(_, repository)| repository)
+    }
+
+    /// Reloads the changed files of the repository, and shows the changes of those that aren't
+    /// viewed.
+    fn refresh(&mut self, cx: &mut ViewContext<Self>) {
+        self.is_stale = false;
+        let Some(repository) = self.repository(cx) else {
+            self.update_excerpts(Vec::new(), cx);
+            return;
+        };
+        let paths = cx
+            .background_executor()
+            .spawn(async move { changed_paths(repository.as_ref()) });
+        self._refresh_task = cx.spawn(|this, mut cx| async move {
+            let paths = paths.await?;
+            let open_buffers = this.update(&mut cx, |this, cx| {
+                this.project.update(cx, |project, cx| {
+                    paths
+                        .into_iter()
+                        .map(|path| {
+                            let project_path = ProjectPath {
+                                worktree_id: this.work_directory.worktree_id,
+                                path: this.work_directory.path.join(&path.0).into(),
+                            };
+                            (path, project.open_buffer(project_path, cx))
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })?;
+
+            let mut files = Vec::new();
+            for (path, open_buffer) in open_buffers {
+                let Some(buffer) = open_buffer.await.log_err() else {
+                    continue;
+                };
+                let recalc = buffer.update(&mut cx, |buffer, cx| buffer.git_diff_recalc(cx))?;
+                if let Some(recalc) = recalc {
+                    recalc.await;
+                }
+                files.push((path, buffer));
+            }
+
+            this.update(&mut cx, |this, cx| this.update_excerpts(files, cx))
+        });
+    }
+
+    /// Rebuilds the excerpts from the hunks of the given files, leaving out the viewed ones.
+    fn update_excerpts(
+        &mut self,
+        files: Vec<(RepoPath, Model<Buffer>)>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.multibuffer
+            .update(cx, |multibuffer, cx| multibuffer.clear(cx));
+        self.files.clear();
+
+        for (path, buffer) in files {
+            let snapshot = buffer.read(cx).snapshot();
+            let hunks = snapshot
+                .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                .collect::<Vec<_>>();
+            if hunks.is_empty() {
+                continue;
+            }
+
+            let diff_base = buffer.read(cx).diff_base();
+            let mut hasher = DefaultHasher::new();
+            let mut added_lines = 0;
+            let mut removed_lines = 0;
+            for hunk in &hunks {
+                added_lines += hunk.associated_range.end - hunk.associated_range.start;
+                for chunk in snapshot.text_for_range(hunk.buffer_range.clone()) {
+                    chunk.hash(&mut hasher);
+                }
+                if let Some(diff_base) = diff_base {
+                    for chunk in diff_base.chunks_in_range(hunk.diff_base_byte_range.clone()) {
+                        removed_lines += chunk.matches('\n').count() as u32;
+                        chunk.hash(&mut hasher);
+                    }
+                }
+            }
+            let fingerprint = hasher.finish();
+
+            let start = if self.viewed.contains(&(path.clone(), fingerprint)) {
+                None
+            } else {
+                let ranges = hunks
+                    .iter()
+                    .map(|hunk| hunk.buffer_range.clone())
+                    .collect::<Vec<_>>();
+                self.multibuffer.update(cx, |multibuffer, cx| {
+                    multibuffer
+                        .push_excerpts_with_context_lines(
+                            buffer.clone(),
+                            ranges,
+                            DEFAULT_MULTIBUFFER_CONTEXT,
+                            cx,
+                        )
+                        .first()
+                        .map(|range| range.start)
+                })
+            };
+
+            self.files.push(ReviewedFile {
+                path,
+                buffer,
+                hunk_count: hunks.len(),
+                added_lines,
+                removed_lines,
+                fingerprint,
+                start,
+            });
+        }
+
+        self.editor.update(cx, |editor, cx| {
+            editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx)
+        });
+        cx.emit(EditorEvent::TitleChanged);
+        cx.notify();
+    }
+
+    /// Returns the index of the file under the newest cursor.
+    fn active_file_ix(&self, cx: &AppContext) -> Option<usize> {
+        let head = self.editor.read(cx).selections.newest_anchor().head();
+        let (_, buffer, _) = self.multibuffer.read(cx).excerpt_containing(head, cx)?;
+        self.files.iter().position(|file| file.buffer == buffer)
+    }
+
+    fn set_viewed(&mut self, ix: usize, viewed: bool, cx: &mut ViewContext<Self>) {
+        let Some(file) = self.files.get(ix) else {
+            return;
+        };
+        let (path, fingerprint) = (file.path.clone(), file.fingerprint);
+        self.viewed.retain(|(viewed_path, _)| *viewed_path != path);
+        if viewed {
+            self.viewed.push((path, fingerprint));
+        }
+        let files = self
+            .files
+            .iter()
+            .map(|file| (file.path.clone(), file.buffer.clone()))
+            .collect();
+        self.update_excerpts(files, cx);
+
+        // Keep the reviewer's place by moving on to the file after the one just viewed.
+        let ix = if viewed {
+            self.files[ix..]
+                .iter()
+                .position(|file| !file.is_viewed())
+                .map(|offset| ix + offset)
+        } else {
+            Some(ix)
+        };
+        if let Some(ix) = ix {
+            self.scroll_to_file(ix, cx);
+        }
+        // The editor isn't shown once every file is viewed.
+        if self.all_files_viewed() && self.editor.focus_handle(cx).is_focused(cx) {
+            self.focus_handle.focus(cx);
+        }
+    }
+
+    fn toggle_file_viewed(&mut self, _: &ToggleFileViewed, cx: &mut ViewContext<Self>) {
+        if let Some(ix) = self.active_file_ix(cx) {
+            let viewed = self.files[ix].is_viewed();
+            self.set_viewed(ix, !viewed, cx);
+        }
+    }
+
+    /// Shows the file most recently marked as viewed again, as viewed files have no excerpts to
+    /// toggle them from.
+    fn unmark_last_viewed_file(&mut self, _: &UnmarkLastViewedFile, cx: &mut ViewContext<Self>) {
+        let ix = self.viewed.iter().rev().find_map(|(path, _)| {
+            self.files
+                .iter()
+                .position(|file| file.is_viewed() && file.path == *path)
+        });
+        if let Some(ix) = ix {
+            self.set_viewed(ix, false, cx);
+            self.editor.focus_handle(cx).focus(cx);
+        }
+    }
+
+    fn scroll_to_file(&mut self, ix: usize, cx: &mut ViewContext<Self>) {
+        let Some(start) = self.files.get(ix).and_then(|file| file.start) else {
+            return;
+        };
+        self.editor.update(cx, |editor, cx| {
+            editor.change_selections(Some(Autoscroll::top_relative(0)), cx, |selections| {
+                selections.select_anchor_ranges([start..start])
+            });
+        });
+    }
+
+    fn next_file(&mut self, _: &NextReviewFile, cx: &mut ViewContext<Self>) {
+        let next_ix = match self.active_file_ix(cx) {
+            Some(ix) => ix + 1,
+            None => 0,
+        };
+        if let Some(offset) = self.files[next_ix.min(self.files.len())..]
+            .iter()
+            .position(|file| !file.is_viewed())
+        {
+            self.scroll_to_file(next_ix + offset, cx);
+        }
+    }
+
+    fn previous_file(&mut self, _: &PreviousReviewFile, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.active_file_ix(cx) else {
+            return;
+        };
+        if let Some(previous_ix) = self.files[..ix].iter().rposition(|file| !file.is_viewed()) {
+            self.scroll_to_file(previous_ix, cx);
+        }
+    }
+
+    fn viewed_count(&self) -> usize {
+        self.files.iter().filter(|file| file.is_viewed()).count()
+    }
+
+    fn all_files_viewed(&self) -> bool {
+        self.files.iter().all(ReviewedFile::is_viewed)
+    }
+
+    fn render_file(&self, ix: usize, file: &ReviewedFile, cx: &mut ViewContext<Self>) -> ListItem {
+        let file_name = file
+            .path
+            .file_name()
+            .map(|file_name| file_name.to_string_lossy().to_string())
+            .unwrap_or_else(|| file.path.to_string_lossy().to_string());
+        let is_viewed = file.is_viewed();
+
+        ListItem::new(("review-file", ix))
+            .inset(true)
+            .start_slot(
+                Checkbox::new(
+                    ("viewed", ix),
+                    if is_viewed {
+                        Selection::Selected
+                    } else {
+                        Selection::Unselected
+                    },
+                )
+                .on_click(cx.listener(move |this, _, cx| this.set_viewed(ix, !is_viewed, cx))),
+            )
+            .child(
+                h_flex()
+                    .w_full()
+                    .gap_2()
+                    .justify_between()
+                    .child(Label::new(file_name).single_line().color(if is_viewed {
+                        Color::Muted
+                    } else {
+                        Color::Default
+                    }))
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(
+                                Label::new(format!("+{}", file.added_lines))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Created),
+                            )
+                            .child(
+                                Label::new(format!("-{}", file.removed_lines))
+                                    .size(LabelSize::Small)
+                                    .color(Color::Deleted),
+                            ),
+                    ),
+            )
+            .tooltip({
+                let path = file.path.to_string_lossy().to_string();
+                let hunk_count = file.hunk_count;
+                move |cx| {
+                    let hunks = if hunk_count == 1 { "hunk" } else { "hunks" };
+                    Tooltip::with_meta(path.clone(), None, format!("{hunk_count} {hunks}"), cx)
+                }
+            })
+            .on_click(cx.listener(move |this, _, cx| {
+                this.scroll_to_file(ix, cx);
+                this.editor.focus_handle(cx).focus(cx);
+            }))
+    }
+}
+
+/// Lists the files whose changes are shown against the repository's diff base.
+fn changed_paths(repository: &dyn GitRepository) -> Result<Vec<RepoPath>> {
+    match repository.diff_base() {
+        DiffBase::Index => Ok(repository
+            .changes()?
+            .into_iter()
+            .filter(|change| change.unstaged.is_some())
+            .map(|change| change.path)
+            .collect()),
+        DiffBase::Head => repository.changed_paths("HEAD"),
+        DiffBase::Revision(revision) => repository.changed_paths(&revision),
+        DiffBase::MergeBase(revision) => {
+            repository.changed_paths(&repository.merge_base("HEAD", &revision)?)
+        }
+    }
+}
+
+impl EventEmitter<EditorEvent> for ReviewChanges {}
+
+impl FocusableView for ReviewChanges {
+    fn focus_handle(&self, _: &AppContext) -> FocusHandle {
+        self.focus_handle.clone()
+    }
+}
+
+impl Render for ReviewChanges {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let file_list = v_flex()
+            .id("review-files")
+            .w(rems(20.))
+            .h_full()
+            .flex_none()
+            .py_1()
+            .overflow_y_scroll()
+            .border_r_1()
+            .border_color(cx.theme().colors().border)
+            .bg(cx.theme().colors().panel_background)
+            .children(
+                self.files
+                    .iter()
+                    .enumerate()
+                    .map(|(ix, file)| self.render_file(ix, file, cx)),
+            );
+
+        let content = if self.all_files_viewed() {
+            let message = if self.files.is_empty() {
+                "No changes to review"
+            } else {
+                "All files viewed"
+            };
+            div()
+                .size_full()
+                .bg(cx.theme().colors().editor_background)
+                .flex()
+                .items_center()
+                .justify_center()
+                .child(Label::new(message))
+        } else {
+            div().size_full().child(self.editor.clone())
+        };
+
+        h_flex()
+            .key_context("ReviewChanges")
+            .track_focus(&self.focus_handle)
+            .size_full()
+            .on_action(cx.listener(Self::next_file))
+            .on_action(cx.listener(Self::previous_file))
+            .on_action(cx.listener(Self::toggle_file_viewed))
+            .on_action(cx.listener(Self::unmark_last_viewed_file))
+            .when(!self.files.is_empty(), |this| this.child(file_list))
+            .child(content)
+    }
+}
+
+impl Item for ReviewChanges {
+    type Event = EditorEvent;
+
+    fn to_item_events(event: &EditorEvent, f: impl FnMut(ItemEvent)) {
+        Editor::to_item_events(event, f)
+    }
+
+    fn deactivated(&mut self, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, cx| editor.deactivated(cx));
+    }
+
+    fn navigate(&mut self, data: Box<dyn Any>, cx: &mut ViewContext<Self>) -> bool {
+        self.editor
+            .update(cx, |editor, cx| editor.navigate(data, cx))
+    }
+
+    fn tab_tooltip_text(&self, _: &AppContext) -> Option<SharedString> {
+        Some("Review Changes".into())
+    }
+
+    fn tab_content(&self, params: TabContentParams, _: &WindowContext) -> AnyElement {
+        let color = if params.selected {
+            Color::Default
+        } else {
+            Color::Muted
+        };
+        let (added_lines, removed_lines) =
+            self.files.iter().fold((0, 0), |(added, removed), file| {
+                (added + file.added_lines, removed + file.removed_lines)
+            });
+
+        h_flex()
+            .gap_2()
+            .child(Label::new("Review Changes").color(color))
+            .when(!self.files.is_empty(), |this| {
+                this.child(
+                    Label::new(format!(
+                        "{}/{} viewed",
+                        self.viewed_count(),
+                        self.files.len()
+                    ))
+                    .color(Color::Muted),
+                )
+                .child(Label::new(format!("+{added_lines}")).color(Color::Created))
+                .child(Label::new(format!("-{removed_lines}")).color(Color::Deleted))
+            })
+            .into_any_element()
+    }
+
+    fn telemetry_event_text(&self) -> Option<&'static str> {
+        Some("review changes")
+    }
+
+    fn for_each_project_item(
+        &self,
+        cx: &AppContext,
+        f: &mut dyn FnMut(gpui::EntityId, &dyn project::Item),
+    ) {
+        self.editor.for_each_project_item(cx, f)
+    }
+
+    fn is_singleton(&self, _: &AppContext) -> bool {
+        false
+    }
+
+    fn set_nav_history(&mut self, nav_history: ItemNavHistory, cx: &mut ViewContext<Self>) {
+        self.editor.update(cx, |editor, _| {
+            editor.set_nav_history(Some(nav_history));
+        });
+    }
+
+    fn clone_on_split(
+        &self,
+        _workspace_id: Option<workspace::WorkspaceId>,
+        cx: &mut ViewContext<Self>,
+    ) -> Option<View<Self>>
+    where
+        Self: Sized,
+    {
+        let (project, workspace) = (self.project.clone(), self.workspace.clone());
+        let work_directory = self.work_directory.clone();
+        let viewed = self.viewed.clone();
+        Some(cx.new_view(|cx| {
+            let mut review = Self::new(project, workspace, work_directory, cx);
+            review.viewed = viewed;
+            review
+        }))
+    }
+
+    fn is_dirty(&self, cx: &AppContext) -> bool {
+        self.multibuffer.read(cx).is_dirty(cx)
+    }
+
+    fn has_conflict(&self, cx: &AppContext) -> bool {
+        self.multibuffer.read(cx).has_conflict(cx)
+    }
+
+    fn can_save(&self, _: &AppContext) -> bool {
+        true
+    }
+
+    fn save(
+        &mut self,
+        format: bool,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        self.editor.save(format, project, cx)
+    }
+
+    fn save_as(
+        &mut self,
+        _: Model<Project>,
+        _: ProjectPath,
+        _: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        unreachable!()
+    }
+
+    fn reload(&mut self, project: Model<Project>, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        self.editor.reload(project, cx)
+    }
+
+    fn act_as_type<'a>(
+        &'a self,
+        type_id: TypeId,
+        self_handle: &'a View<Self>,
+        _: &'a AppContext,
+    ) -> Option<AnyView> {
+        if type_id == TypeId::of::<Self>() {
+            Some(self_handle.to_any())
+        } else if type_id == TypeId::of::<Editor>() {
+            Some(self.editor.to_any())
+        } else {
+            None
+        }
+    }
+
+    fn added_to_workspace(&mut self, workspace: &mut Workspace, cx: &mut ViewContext<Self>) {
+        self.editor
+            .update(cx, |editor, cx| editor.added_to_workspace(workspace, cx));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git::status::{FileChange, FileChangeKind};
+    use gpui::{TestAppContext, VisualTestContext};
+    use serde_json::json;
+    use std::path::{Path, PathBuf};
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_review_changes(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let fs = app_state.fs.as_fake();
+        fs.insert_tree(
+            "/root",
+            json!({
+                ".git": {},
+                "a.txt": "one\nTWO\n",
+                "b.txt": "three\nFOUR\n",
+                "c.txt": "five\n",
+            }),
+        )
+        .await;
+        fs.with_git_state(Path::new("/root/.git"), false, |state| {
+            for (path, text) in [("a.txt", "one\ntwo\n"), ("b.txt", "three\nfour\n")] {
+                state
+                    .index_contents
+                    .insert(PathBuf::from(path), text.to_string());
+                state.file_changes.push(FileChange {
+                    path: Path::new(path).into(),
+                    staged: None,
+                    unstaged: Some(FileChangeKind::Modified),
+                });
+            }
+            state
+                .index_contents
+                .insert(PathBuf::from("c.txt"), "five\n".to_string());
+        });
+        let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        cx.run_until_parked();
+
+        cx.dispatch_action(Deploy);
+        cx.run_until_parked();
+        let review = workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_item_as::<ReviewChanges>(cx)
+                .expect("review is not open")
+        });
+        review.update(cx, |review, cx| {
+            assert_eq!(file_paths(review), ["a.txt", "b.txt"]);
+            assert_eq!(review.active_file_ix(cx), Some(0));
+        });
+
+        review.update(cx, |review, cx| review.next_file(&NextReviewFile, cx));
+        review.update(cx, |review, cx| {
+            assert_eq!(review.active_file_ix(cx), Some(1))
+        });
+        review.update(cx, |review, cx| review.next_file(&NextReviewFile, cx));
+        review.update(cx, |review, cx| {
+            assert_eq!(review.active_file_ix(cx), Some(1))
+        });
+        review.update(cx, |review, cx| {
+            review.previous_file(&PreviousReviewFile, cx)
+        });
+        review.update(cx, |review, cx| {
+            assert_eq!(review.active_file_ix(cx), Some(0))
+        });
+
+        // Viewed files are left out, moving on to the next file.
+        review.update(cx, |review, cx| {
+            review.toggle_file_viewed(&ToggleFileViewed, cx)
+        });
+        review.update(cx, |review, cx| {
+            assert_eq!(review.viewed_count(), 1);
+            assert_eq!(review.active_file_ix(cx), Some(1));
+            assert_eq!(review.editor.read(cx).text(cx), "three\nFOUR\n");
+        });
+        review.update(cx, |review, cx| {
+            review.toggle_file_viewed(&ToggleFileViewed, cx)
+        });
+        review.update(cx, |review, _| assert!(review.all_files_viewed()));
+
+        // They're shown again in the reverse order they were viewed in.
+        review.update(cx, |review, cx| {
+            review.unmark_last_viewed_file(&UnmarkLastViewedFile, cx)
+        });
+        review.update(cx, |review, cx| {
+            assert_eq!(review.viewed_count(), 1);
+            assert_eq!(review.active_file_ix(cx), Some(1));
+        });
+        review.update(cx, |review, cx| {
+            review.unmark_last_viewed_file(&UnmarkLastViewedFile, cx)
+        });
+        review.update(cx, |review, cx| {
+            assert_eq!(review.viewed_count(), 0);
+            assert_eq!(review.active_file_ix(cx), Some(0));
+        });
+
+        // A viewed file is shown again once its changes change.
+        review.update(cx, |review, cx| review.set_viewed(0, true, cx));
+        let buffer = review.update(cx, |review, _| review.files[0].buffer.clone());
+        buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "zero\n")], None, cx));
+        review.update(cx, |review, cx| review.refresh(cx));
+        cx.run_until_parked();
+        review.update(cx, |review, _| {
+            assert_eq!(file_paths(review), ["a.txt", "b.txt"]);
+            assert_eq!(review.viewed_count(), 0);
+        });
+    }
+
+    fn file_paths(review: &ReviewChanges) -> Vec<&str> {
+        review
+            .files
+            .iter()
+            .map(|file| file.path.to_str().unwrap())
+            .collect()
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            crate::init(cx);
+            state
+        })
+    }
+}
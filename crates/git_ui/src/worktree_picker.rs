@@ -0,0 +1,289 @@
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use git::{repository::GitRepository, worktree::GitWorktree};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Task, View,
+    ViewContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use std::{path::PathBuf, sync::Arc};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing};
+use util::{paths::PathExt, ResultExt};
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+use crate::OpenWorktree;
+
+/// A modal listing the working trees of the active repository, opening one as a project, or
+/// creating one for a branch.
+pub struct WorktreePicker {
+    picker: View<Picker<WorktreePickerDelegate>>,
+}
+
+impl WorktreePicker {
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        _: &OpenWorktree,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some((_, repository)) = workspace.project().read(cx).active_git_repository(cx) else {
+            return;
+        };
+
+        cx.spawn(|workspace, mut cx| async move {
+            let worktrees = cx
+                .background_executor()
+                .spawn({
+                    let repository = repository.clone();
+                    async move { repository.worktrees() }
+                })
+                .await?;
+
+            workspace.update(&mut cx, |workspace, cx| {
+                let workspace_handle = cx.view().downgrade();
+                workspace.toggle_modal(cx, move |cx| {
+                    let delegate = WorktreePickerDelegate {
+                        worktree_picker: cx.view().downgrade(),
+                        workspace: workspace_handle,
+                        repository,
+                        worktrees: Arc::new(worktrees),
+                        matches: Vec::new(),
+                        selected_index: 0,
+                        last_query: String::new(),
+                    };
+                    Self {
+                        picker: cx.new_view(|cx| Picker::uniform_list(delegate, cx)),
+                    }
+                });
+            })
+        })
+        .detach_and_prompt_err("Failed to list the worktrees", cx, |_, _| None);
+    }
+}
+
+impl Render for WorktreePicker {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for WorktreePicker {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for WorktreePicker {}
+impl ModalView for WorktreePicker {}
+
+/// Names a working tree after its branch, or after its HEAD when it's detached.
+fn worktree_label(worktree: &GitWorktree) -> String {
+    match (&worktree.branch, &worktree.head_sha) {
+        (Some(branch), _) => branch.clone(),
+        (None, Some(sha)) => format!("{} (detached)", &sha[..sha.len().min(7)]),
+        (None, None) => worktree.path.compact().to_string_lossy().into_owned(),
+    }
+}
+
+struct WorktreePickerDelegate {
+    worktree_picker: WeakView<WorktreePicker>,
+    workspace: WeakView<Workspace>,
+    repository: Arc<dyn GitRepository>,
+    worktrees: Arc<Vec<GitWorktree>>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    last_query: String,
+}
+
+impl WorktreePickerDelegate {
+    fn open_worktree(&mut self, path: PathBuf, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(workspace) = self.workspace.upgrade() {
+            workspace.update(cx, |workspace, cx| {
+                workspace
+                    .open_workspace_for_paths(false, vec![path], cx)
+                    .detach_and_prompt_err("Failed to open the worktree", cx, |_, _| None);
+            });
+        }
+        self.dismissed(cx);
+    }
+
+    /// Checks out the branch named after the query in a new working tree next to the main one,
+    /// and opens it.
+    fn create_worktree(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        let branch = self.last_query.trim().to_string();
+        if branch.is_empty() {
+            return;
+        }
+        let Some(main_worktree) = self.worktrees.iter().find(|worktree| worktree.is_main) else {
+            return;
+        };
+        let (Some(parent), Some(name)) =
+            (main_worktree.path.parent(), main_worktree.path.file_name())
+        else {
+            return;
+        };
+        let path = parent.join(format!(
+            "{}-{}",
+            name.to_string_lossy(),
+            branch.replace(['/', '\\'], "-")
+        ));
+
+        let repository = self.repository.clone();
+        let workspace = self.workspace.clone();
+        cx.spawn(|_, mut cx| async move {
+            cx.background_executor()
+                .spawn({
+                    let path = path.clone();
+                    async move { repository.create_worktree(&path, &branch) }
+                })
+                .await?;
+            workspace
+                .update(&mut cx, |workspace, cx| {
+                    workspace.open_workspace_for_paths(false, vec![path], cx)
+                })?
+                .await
+        })
+        .detach_and_prompt_err("Failed to create the worktree", cx, |_, _| None);
+        self.dismissed(cx);
+    }
+}
+
+impl PickerDelegate for WorktreePickerDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Open a worktree, or create one for a branch...".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        if self.last_query.trim().is_empty() {
+            "No worktrees".into()
+        } else {
+            format!(
+                "Press enter to create a worktree for '{}'",
+                self.last_query.trim()
+            )
+            .into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let worktrees = self.worktrees.clone();
+        cx.spawn(|this, mut cx| async move {
+            let candidates = worktrees
+                .iter()
+                .enumerate()
+                .map(|(ix, worktree)| StringMatchCandidate::new(ix, worktree_label(worktree)))
+                .collect::<Vec<_>>();
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    true,
+                    worktrees.len(),
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.last_query = query;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        // A query matching none of the worktrees is the branch to create one for.
+        match self.matches.get(self.selected_index) {
+            Some(mat) => {
+                let path = self.worktrees[mat.candidate_id].path.clone();
+                self.open_worktree(path, cx);
+            }
+            None => self.create_worktree(cx),
+        }
+    }
+
+    fn confirm_input(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        self.create_worktree(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.worktree_picker
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let worktree = &self.worktrees[mat.candidate_id];
+        let mut details = worktree.path.compact().to_string_lossy().into_owned();
+        if worktree.is_main {
+            details.push_str(" (main)");
+        }
+        if worktree.is_locked {
+            details.push_str(" (locked)");
+        }
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(
+                    Icon::new(IconName::GitBranch)
+                        .size(IconSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(
+                    h_flex()
+                        .w_full()
+                        .gap_2()
+                        .justify_between()
+                        .child(HighlightedLabel::new(
+                            mat.string.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(details)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted)
+                                .single_line(),
+                        ),
+                ),
+        )
+    }
+}
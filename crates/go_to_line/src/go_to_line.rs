@@ -141,6 +141,10 @@ impl GoToLine {
 
     fn line_column_from_query(&self, cx: &ViewContext<Self>) -> (Option<u32>, Option<u32>) {
         let input = self.line_editor.read(cx).text(cx);
+        let input = input
+            .trim_start()
+            .strip_prefix(FILE_ROW_COLUMN_DELIMITER)
+            .unwrap_or(&input);
         let mut components = input
             .splitn(2, FILE_ROW_COLUMN_DELIMITER)
             .map(str::trim)
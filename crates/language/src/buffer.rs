@@ -10,14 +10,15 @@ use crate::{
     markdown::parse_markdown,
     outline::OutlineItem,
     syntax_map::{
-        SyntaxLayer, SyntaxMap, SyntaxMapCapture, SyntaxMapCaptures, SyntaxMapMatches,
-        SyntaxSnapshot, ToTreeSitterPoint,
+        QueryCursorHandle, SyntaxLayer, SyntaxMap, SyntaxMapCapture, SyntaxMapCaptures,
+        SyntaxMapMatches, SyntaxSnapshot, TextProvider, ToTreeSitterPoint,
     },
     task_context::RunnableRange,
-    LanguageScope, Outline, RunnableCapture, RunnableTag,
+    LanguageScope, Outline, RunnableCapture, RunnableTag, TextObject,
 };
 use anyhow::{anyhow, Context, Result};
 pub use clock::ReplicaId;
+use collections::HashMap;
 use futures::channel::oneshot;
 use gpui::{
     AnyElement, AppContext, EventEmitter, HighlightStyle, ModelContext, Task, TaskLabel,
@@ -52,7 +53,7 @@ pub use text::{
     Anchor, Bias, Buffer as TextBuffer, BufferId, BufferSnapshot as TextBufferSnapshot, Edit,
     OffsetRangeExt, OffsetUtf16, Patch, Point, PointUtf16, Rope, Selection, SelectionGoal,
     Subscription, TextDimension, TextSummary, ToOffset, ToOffsetUtf16, ToPoint, ToPointUtf16,
-    Transaction, TransactionId, Unclipped,
+    Transaction, TransactionId, Unclipped, UndoHistoryNode,
 };
 use theme::SyntaxTheme;
 #[cfg(any(test, feature = "test-support"))]
@@ -70,6 +71,16 @@ lazy_static! {
     pub static ref BUFFER_DIFF_TASK: TaskLabel = TaskLabel::new();
 }
 
+/// A match of a tree-sitter query provided by the user, see [`BufferSnapshot::structural_matches`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StructuralMatch {
+    /// The range of the `@match` capture, or the range spanning all captures if the query
+    /// doesn't have one.
+    pub range: Range<usize>,
+    /// The name and range of every capture in the match.
+    pub captures: Vec<(Arc<str>, Range<usize>)>,
+}
+
 /// Indicate whether a [Buffer] has permissions to edit.
 #[derive(PartialEq, Clone, Copy, Debug)]
 pub enum Capability {
@@ -209,6 +220,21 @@ pub struct Diagnostic {
     pub is_disk_based: bool,
     /// Whether this diagnostic marks unnecessary code.
     pub is_unnecessary: bool,
+    /// Locations in other files that are relevant to this diagnostic, like the place where a
+    /// moved value is later used. Related locations in the same file are part of the
+    /// diagnostic's group instead. Only set on primary diagnostics.
+    pub related_information: Vec<DiagnosticRelatedInformation>,
+}
+
+/// A location related to a [`Diagnostic`], possibly in another file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiagnosticRelatedInformation {
+    /// The absolute path of the file containing the location.
+    pub abs_path: Arc<Path>,
+    /// The range of the location within its file.
+    pub range: Range<Unclipped<PointUtf16>>,
+    /// A message describing how the location relates to the diagnostic.
+    pub message: String,
 }
 
 /// TODO - move this into the `project` crate and make it private.
@@ -2067,6 +2093,29 @@ impl Buffer {
         undone
     }
 
+    /// Brings the buffer back to the state right after the given transaction, or to its initial
+    /// state for `None`, including states on abandoned branches of the undo history.
+    pub fn restore_undo_history(
+        &mut self,
+        transaction_id: Option<TransactionId>,
+        cx: &mut ModelContext<Self>,
+    ) -> bool {
+        let was_dirty = self.is_dirty();
+        let old_version = self.version.clone();
+
+        let Some(operations) = self.text.restore_undo_history(transaction_id) else {
+            return false;
+        };
+        let restored = !operations.is_empty();
+        for operation in operations {
+            self.send_operation(Operation::Buffer(operation), cx);
+        }
+        if restored {
+            self.did_edit(&old_version, was_dirty, cx)
+        }
+        restored
+    }
+
     /// Manually redoes a specific transaction in the buffer's redo history.
     pub fn redo(&mut self, cx: &mut ModelContext<Self>) -> Option<TransactionId> {
         let was_dirty = self.is_dirty();
@@ -2972,6 +3021,40 @@ impl BufferSnapshot {
         result
     }
 
+    /// Returns the brackets overlapping `range` in the order they appear, along with their nesting
+    /// depth: the number of bracket pairs enclosing them. Pairs of identical brackets, such as
+    /// quotes, aren't nested and are skipped.
+    pub fn bracket_depths<T: ToOffset>(&self, range: Range<T>) -> Vec<(Range<usize>, usize)> {
+        let mut pairs = self
+            .bracket_ranges(range)
+            .filter(|(open, close)| {
+                !self
+                    .chars_for_range(open.clone())
+                    .eq(self.chars_for_range(close.clone()))
+            })
+            .collect::<Vec<_>>();
+        // Enclosing pairs are returned too, so outer pairs must come before the pairs they contain.
+        pairs.sort_by_key(|(open, close)| (open.start, cmp::Reverse(close.end)));
+        pairs.dedup();
+
+        let mut enclosing_ends = Vec::<usize>::new();
+        let mut brackets = Vec::with_capacity(pairs.len() * 2);
+        for (open, close) in pairs {
+            while enclosing_ends
+                .last()
+                .map_or(false, |end| *end <= open.start)
+            {
+                enclosing_ends.pop();
+            }
+            let depth = enclosing_ends.len();
+            enclosing_ends.push(close.end);
+            brackets.push((open, depth));
+            brackets.push((close, depth));
+        }
+        brackets.sort_by_key(|(range, _)| range.start);
+        brackets
+    }
+
     /// Returns anchor ranges for any matches of the redaction query.
     /// The buffer can be associated with multiple languages, and the redaction query associated with each
     /// will be run on the relevant section of the buffer.
@@ -3009,6 +3092,178 @@ impl BufferSnapshot {
         })
     }
 
+    /// Returns groups of ranges that should be edited in lockstep, such as the names of an
+    /// opening and closing HTML tag. Each group corresponds to one match of the `linked_edit`
+    /// query, and only groups with a range touching the given range are returned.
+    pub fn linked_edit_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = Vec<Range<usize>>> + '_ {
+        let range = range.start.to_offset(self).saturating_sub(1)
+            ..self.len().min(range.end.to_offset(self) + 1);
+
+        let mut matches = self.syntax.matches(range.clone(), &self.text, |grammar| {
+            grammar.linked_edit_config.as_ref().map(|c| &c.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.linked_edit_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+
+        iter::from_fn(move || {
+            while let Some(mat) = matches.peek() {
+                let config = &configs[mat.grammar_index];
+                let ranges = mat
+                    .captures
+                    .iter()
+                    .filter(|capture| capture.index == config.linked_capture_ix)
+                    .map(|capture| capture.node.byte_range())
+                    .collect::<Vec<_>>();
+
+                matches.advance();
+
+                if ranges.len() < 2
+                    || !ranges
+                        .iter()
+                        .any(|linked_range| linked_range.to_inclusive().overlaps(&range))
+                {
+                    continue;
+                }
+
+                return Some(ranges);
+            }
+            None
+        })
+    }
+
+    /// Returns the ranges captured by the `textobjects` query touching the given range, with
+    /// the text object each one is. Captures of the same text object in one match are merged,
+    /// so that a quantified capture like `(block (_)* @function.inside)` yields one range.
+    pub fn text_object_ranges<T: ToOffset>(
+        &self,
+        range: Range<T>,
+    ) -> impl Iterator<Item = (Range<usize>, TextObject)> + '_ {
+        let range = range.start.to_offset(self).saturating_sub(1)
+            ..self.len().min(range.end.to_offset(self) + 1);
+
+        let mut matches = self.syntax.matches(range, &self.text, |grammar| {
+            grammar.text_object_config.as_ref().map(|c| &c.query)
+        });
+        let configs = matches
+            .grammars()
+            .iter()
+            .map(|grammar| grammar.text_object_config.as_ref().unwrap())
+            .collect::<Vec<_>>();
+
+        let mut captures = Vec::<(Range<usize>, TextObject)>::new();
+        iter::from_fn(move || loop {
+            if let Some(capture) = captures.pop() {
+                return Some(capture);
+            }
+
+            let mat = matches.peek()?;
+            let config = &configs[mat.grammar_index];
+            for capture in mat.captures {
+                let Some(text_object) = config
+                    .text_objects
+                    .get(capture.index as usize)
+                    .copied()
+                    .flatten()
+                else {
+                    continue;
+                };
+                let byte_range = capture.node.byte_range();
+                if let Some((range, _)) = captures
+                    .iter_mut()
+                    .find(|(_, existing)| *existing == text_object)
+                {
+                    range.start = range.start.min(byte_range.start);
+                    range.end = range.end.max(byte_range.end);
+                } else {
+                    captures.push((byte_range, text_object));
+                }
+            }
+            matches.advance();
+        })
+    }
+
+    /// Runs a tree-sitter query, given as source text, against the syntax layers intersecting
+    /// `range`. The query is compiled for the language of each layer, and layers whose grammar
+    /// cannot compile it are skipped, so that one pattern can be used across buffers of
+    /// different languages.
+    ///
+    /// If the buffer has not been parsed yet, its text is parsed with the buffer's language.
+    pub fn structural_matches(&self, source: &str, range: Range<usize>) -> Vec<StructuralMatch> {
+        let mut queries = HashMap::default();
+        let mut matches = Vec::new();
+        let mut collect_matches = |language: &Arc<Language>, node: tree_sitter::Node| {
+            let Some(grammar) = language.grammar() else {
+                return;
+            };
+            let query = queries
+                .entry(grammar.id())
+                .or_insert_with(|| tree_sitter::Query::new(&grammar.ts_language, source).ok());
+            let Some(query) = query.as_ref() else {
+                return;
+            };
+            let match_capture_ix = query.capture_index_for_name("match");
+
+            let mut cursor = QueryCursorHandle::new();
+            cursor.set_byte_range(range.clone());
+            for mat in cursor.matches(query, node, TextProvider(self.as_rope())) {
+                let captures = mat
+                    .captures
+                    .iter()
+                    .map(|capture| {
+                        let name = query.capture_names()[capture.index as usize].to_string();
+                        (Arc::from(name), capture.node.byte_range())
+                    })
+                    .collect::<Vec<_>>();
+                let match_capture = match_capture_ix
+                    .and_then(|ix| mat.captures.iter().find(|capture| capture.index == ix));
+                let match_range = if let Some(capture) = match_capture {
+                    capture.node.byte_range()
+                } else {
+                    let Some(start) = captures.iter().map(|(_, range)| range.start).min() else {
+                        continue;
+                    };
+                    let end = captures.iter().map(|(_, range)| range.end).max().unwrap();
+                    start..end
+                };
+                if match_range.start < range.start || match_range.end > range.end {
+                    continue;
+                }
+                matches.push(StructuralMatch {
+                    range: match_range,
+                    captures,
+                });
+            }
+        };
+
+        let mut layers = self
+            .syntax
+            .layers_for_range(range.clone(), &self.text)
+            .peekable();
+        if layers.peek().is_none() {
+            if let Some(language) = self.language() {
+                let tree = language
+                    .grammar()
+                    .map(|grammar| grammar.parse_text(self.as_rope(), None));
+                if let Some(tree) = tree {
+                    collect_matches(language, tree.root_node());
+                }
+            }
+        }
+        for layer in layers {
+            collect_matches(layer.language, layer.node());
+        }
+
+        matches.sort_by_key(|mat| (mat.range.start, cmp::Reverse(mat.range.end)));
+        matches.dedup_by(|a, b| a.range == b.range);
+        matches
+    }
+
     pub fn runnable_ranges(
         &self,
         range: Range<Anchor>,
@@ -3819,6 +4074,7 @@ impl Default for Diagnostic {
             is_primary: false,
             is_disk_based: false,
             is_unnecessary: false,
+            related_information: Vec::new(),
         }
     }
 }
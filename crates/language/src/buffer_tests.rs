@@ -862,6 +862,44 @@ async fn test_symbols_containing(cx: &mut gpui::TestAppContext) {
     }
 }
 
+#[gpui::test]
+fn test_text_object_ranges(cx: &mut AppContext) {
+    let text = "fn a(b: u8, c: u8) {\n    let d = b;\n    c\n}\n";
+    let language = rust_lang()
+        .with_text_object_query(
+            r#"
+            (function_item
+                body: (_ "{" (_)* @function.inside "}")) @function.around
+            (parameters (_) @argument.inside)
+            "#,
+        )
+        .unwrap();
+    let buffer = cx.new_model(|cx| Buffer::local(text, cx).with_language(Arc::new(language), cx));
+    let snapshot = buffer.read(cx).snapshot();
+
+    let offset = text.find("let").unwrap();
+    let mut objects = snapshot
+        .text_object_ranges(offset..offset)
+        .map(|(range, object)| (&text[range], object))
+        .collect::<Vec<_>>();
+    objects.sort_by_key(|(text, _)| text.len());
+    assert_eq!(
+        objects,
+        [
+            ("let d = b;\n    c", TextObject::InsideFunction),
+            (text.trim_end(), TextObject::AroundFunction),
+        ]
+    );
+
+    let offset = text.find("c: u8").unwrap();
+    let objects = snapshot
+        .text_object_ranges(offset..offset)
+        .filter(|(_, object)| *object == TextObject::InsideArgument)
+        .map(|(range, _)| &text[range])
+        .collect::<Vec<_>>();
+    assert_eq!(objects, ["c: u8"]);
+}
+
 #[gpui::test]
 fn test_enclosing_bracket_ranges(cx: &mut AppContext) {
     let mut assert = |selection_text, range_markers| {
@@ -1010,6 +1048,59 @@ fn test_enclosing_bracket_ranges_where_brackets_are_not_outermost_children(cx: &
     );
 }
 
+#[gpui::test]
+fn test_bracket_depths(cx: &mut AppContext) {
+    cx.new_model(|cx| {
+        let text = "if (a) { f(b, () => { g(c) }); } h(d);";
+        let buffer = Buffer::local(text, cx).with_language(Arc::new(javascript_lang()), cx);
+        let snapshot = buffer.snapshot();
+
+        let depths = |range: Range<usize>| {
+            snapshot
+                .bracket_depths(range)
+                .into_iter()
+                .map(|(range, depth)| (&text[range], depth))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            depths(0..text.len()),
+            [
+                ("(", 0),
+                (")", 0),
+                ("{", 0),
+                ("(", 1),
+                ("(", 2),
+                (")", 2),
+                ("{", 2),
+                ("(", 3),
+                (")", 3),
+                ("}", 2),
+                (")", 1),
+                ("}", 0),
+                ("(", 0),
+                (")", 0),
+            ]
+        );
+
+        // The pairs enclosing the range are counted, even though they don't overlap it.
+        let start = text.find("g(").unwrap();
+        assert_eq!(
+            depths(start..start + 1),
+            [
+                ("{", 0),
+                ("(", 1),
+                ("{", 2),
+                ("(", 3),
+                (")", 3),
+                ("}", 2),
+                (")", 1),
+                ("}", 0),
+            ]
+        );
+        buffer
+    });
+}
+
 #[gpui::test]
 fn test_range_for_syntax_ancestor(cx: &mut AppContext) {
     cx.new_model(|cx| {
@@ -13,6 +13,7 @@ mod language_registry;
 pub mod language_settings;
 mod outline;
 pub mod proto;
+mod semantic_tokens;
 mod syntax_map;
 mod task_context;
 
@@ -71,6 +72,7 @@ pub use language_registry::{
 };
 pub use lsp::LanguageServerId;
 pub use outline::{Outline, OutlineItem};
+pub use semantic_tokens::{semantic_token_style, SemanticToken};
 pub use syntax_map::{OwnedSyntaxLayer, SyntaxLayer};
 pub use text::{AnchorRangeExt, LineEnding};
 pub use tree_sitter::{Node, Parser, Tree, TreeCursor};
@@ -590,6 +592,10 @@ pub struct LanguageConfig {
     /// Starting and closing characters of a block comment.
     #[serde(default)]
     pub block_comment: Option<(Arc<str>, Arc<str>)>,
+    /// Markers that start and end a foldable region when they follow a comment delimiter,
+    /// like `// #region` and `// #endregion`.
+    #[serde(default = "fold_region_markers_default")]
+    pub fold_region_markers: Option<(Arc<str>, Arc<str>)>,
     /// A list of language servers that are allowed to run on subranges of a given language.
     #[serde(default)]
     pub scope_opt_in_language_servers: Vec<String>,
@@ -692,6 +698,7 @@ impl Default for LanguageConfig {
             autoclose_before: Default::default(),
             line_comments: Default::default(),
             block_comment: Default::default(),
+            fold_region_markers: fold_region_markers_default(),
             scope_opt_in_language_servers: Default::default(),
             overrides: Default::default(),
             word_characters: Default::default(),
@@ -708,6 +715,10 @@ fn auto_indent_using_last_non_empty_line_default() -> bool {
     true
 }
 
+fn fold_region_markers_default() -> Option<(Arc<str>, Arc<str>)> {
+    Some(("#region".into(), "#endregion".into()))
+}
+
 fn deserialize_regex<'de, D: Deserializer<'de>>(d: D) -> Result<Option<Regex>, D::Error> {
     let source = Option::<String>::deserialize(d)?;
     if let Some(source) = source {
@@ -841,6 +852,8 @@ pub struct Grammar {
     pub(crate) brackets_config: Option<BracketConfig>,
     pub(crate) redactions_config: Option<RedactionConfig>,
     pub(crate) runnable_config: Option<RunnableConfig>,
+    pub(crate) linked_edit_config: Option<LinkedEditConfig>,
+    pub(crate) text_object_config: Option<TextObjectConfig>,
     pub(crate) indents_config: Option<IndentConfig>,
     pub outline_config: Option<OutlineConfig>,
     pub embedding_config: Option<EmbeddingConfig>,
@@ -899,6 +912,58 @@ struct RunnableConfig {
     pub extra_captures: Vec<RunnableCapture>,
 }
 
+struct LinkedEditConfig {
+    pub query: Query,
+    pub linked_capture_ix: u32,
+}
+
+/// A structural region of the code, captured by a language's `textobjects` query
+/// and selected by vim's text objects like `af` and `ic`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum TextObject {
+    InsideFunction,
+    AroundFunction,
+    InsideClass,
+    AroundClass,
+    InsideComment,
+    AroundComment,
+    InsideArgument,
+    AroundArgument,
+}
+
+impl TextObject {
+    pub fn from_capture_name(name: &str) -> Option<TextObject> {
+        match name {
+            "function.inside" => Some(TextObject::InsideFunction),
+            "function.around" => Some(TextObject::AroundFunction),
+            "class.inside" => Some(TextObject::InsideClass),
+            "class.around" => Some(TextObject::AroundClass),
+            "comment.inside" => Some(TextObject::InsideComment),
+            "comment.around" => Some(TextObject::AroundComment),
+            "argument.inside" => Some(TextObject::InsideArgument),
+            "argument.around" => Some(TextObject::AroundArgument),
+            _ => None,
+        }
+    }
+
+    /// The text object surrounding this one, if this one is the inside of another.
+    pub fn around(&self) -> Option<TextObject> {
+        match self {
+            TextObject::InsideFunction => Some(TextObject::AroundFunction),
+            TextObject::InsideClass => Some(TextObject::AroundClass),
+            TextObject::InsideComment => Some(TextObject::AroundComment),
+            TextObject::InsideArgument => Some(TextObject::AroundArgument),
+            _ => None,
+        }
+    }
+}
+
+struct TextObjectConfig {
+    pub query: Query,
+    /// The text object of each capture of the query, if it names one.
+    pub text_objects: Vec<Option<TextObject>>,
+}
+
 struct OverrideConfig {
     query: Query,
     values: HashMap<u32, (String, LanguageConfigOverride)>,
@@ -941,6 +1006,8 @@ impl Language {
                     override_config: None,
                     redactions_config: None,
                     runnable_config: None,
+                    linked_edit_config: None,
+                    text_object_config: None,
                     error_query: Query::new(&ts_language, "(ERROR) @error").unwrap(),
                     ts_language,
                     highlight_map: Default::default(),
@@ -1001,6 +1068,16 @@ impl Language {
                 .with_runnable_query(query.as_ref())
                 .context("Error loading tests query")?;
         }
+        if let Some(query) = queries.linked_edit {
+            self = self
+                .with_linked_edit_query(query.as_ref())
+                .context("Error loading linked edit query")?;
+        }
+        if let Some(query) = queries.text_objects {
+            self = self
+                .with_text_object_query(query.as_ref())
+                .context("Error loading text objects query")?;
+        }
         Ok(self)
     }
 
@@ -1297,6 +1374,45 @@ impl Language {
         Ok(self)
     }
 
+    pub fn with_linked_edit_query(mut self, source: &str) -> anyhow::Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let mut linked_capture_ix = None;
+        get_capture_indices(&query, &mut [("linked", &mut linked_capture_ix)]);
+
+        if let Some(linked_capture_ix) = linked_capture_ix {
+            grammar.linked_edit_config = Some(LinkedEditConfig {
+                query,
+                linked_capture_ix,
+            });
+        }
+
+        Ok(self)
+    }
+
+    pub fn with_text_object_query(mut self, source: &str) -> anyhow::Result<Self> {
+        let grammar = self
+            .grammar_mut()
+            .ok_or_else(|| anyhow!("cannot mutate grammar"))?;
+
+        let query = Query::new(&grammar.ts_language, source)?;
+        let text_objects = query
+            .capture_names()
+            .iter()
+            .map(|name| TextObject::from_capture_name(name))
+            .collect();
+
+        grammar.text_object_config = Some(TextObjectConfig {
+            query,
+            text_objects,
+        });
+
+        Ok(self)
+    }
+
     fn grammar_mut(&mut self) -> Option<&mut Grammar> {
         Arc::get_mut(self.grammar.as_mut()?)
     }
@@ -1406,6 +1522,15 @@ impl LanguageScope {
         .map(|e| (&e.0, &e.1))
     }
 
+    /// Returns the markers starting and ending the foldable regions delimited by comments.
+    pub fn fold_region_markers(&self) -> Option<(&Arc<str>, &Arc<str>)> {
+        self.language
+            .config
+            .fold_region_markers
+            .as_ref()
+            .map(|(start, end)| (start, end))
+    }
+
     /// Returns a list of language-specific word characters.
     ///
     /// By default, Zed treats alphanumeric characters (and '_') as word characters for
@@ -1460,6 +1585,14 @@ impl LanguageScope {
         }
     }
 
+    /// Returns the name of the override query capture this scope is in, such as `comment` or `string`.
+    pub fn override_name(&self) -> Option<&str> {
+        let id = self.override_id?;
+        let grammar = self.language.grammar.as_ref()?;
+        let override_config = grammar.override_config.as_ref()?;
+        override_config.values.get(&id).map(|e| e.0.as_str())
+    }
+
     fn config_override(&self) -> Option<&LanguageConfigOverride> {
         let id = self.override_id?;
         let grammar = self.language.grammar.as_ref()?;
@@ -80,6 +80,8 @@ pub struct LanguageSettings {
     pub wrap_guides: Vec<usize>,
     /// Indent guide related settings.
     pub indent_guides: IndentGuideSettings,
+    /// Sticky scroll related settings.
+    pub sticky_scroll: StickyScrollSettings,
     /// Whether or not to perform a buffer format before saving.
     pub format_on_save: FormatOnSave,
     /// Whether or not to remove any trailing whitespace from lines of a buffer
@@ -101,6 +103,9 @@ pub struct LanguageSettings {
     /// - `"!<language_server_id>"` - A language server ID prefixed with a `!` will be disabled.
     /// - `"..."` - A placeholder to refer to the **rest** of the registered language servers for this language.
     pub language_servers: Vec<Arc<str>>,
+    /// Which of the language servers running for this language to use for
+    /// each capability.
+    pub language_server_routing: LanguageServerRouting,
     /// Controls whether inline completions are shown immediately (true)
     /// or manually by triggering `editor::ShowInlineCompletion` (false).
     pub show_inline_completions: bool,
@@ -110,10 +115,24 @@ pub struct LanguageSettings {
     pub extend_comment_on_newline: bool,
     /// Inlay hint related settings.
     pub inlay_hints: InlayHintSettings,
+    /// Whether to format the code with the language server after typing one of
+    /// its on-type formatting trigger characters.
+    pub use_on_type_format: bool,
     /// Whether to automatically close brackets.
     pub use_autoclose: bool,
     // Controls how the editor handles the autoclosed characters.
     pub always_treat_brackets_as_autoclosed: bool,
+    /// Whether to keep linked ranges, such as matching HTML tag names, in sync while editing.
+    pub linked_edits: bool,
+    /// How long to wait after the cursor moves before looking up linked ranges again.
+    pub linked_edits_debounce_ms: u64,
+    /// Whether to color brackets according to their nesting depth.
+    pub rainbow_brackets: bool,
+    /// How to layer the semantic tokens of language servers over tree-sitter highlights.
+    pub semantic_tokens: SemanticTokens,
+    /// Groups of file name endings that tell which files are related to each other, for
+    /// `editor::GoToRelatedFile`.
+    pub related_files: Vec<Vec<String>>,
     /// Which code actions to run on save
     pub code_actions_on_format: HashMap<String, bool>,
 }
@@ -165,6 +184,106 @@ impl LanguageSettings {
     }
 }
 
+/// Which of the language servers running for a language to use for each
+/// capability.
+///
+/// Each list uses the same syntax as the `language_servers` setting.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct LanguageServerRouting {
+    /// The language servers to request completions from, in the order their
+    /// completions are listed.
+    ///
+    /// Default: ["..."]
+    #[serde(default = "rest_of_language_servers")]
+    pub completions: Vec<Arc<str>>,
+    /// How to combine the completions of several language servers.
+    ///
+    /// Default: all
+    #[serde(default)]
+    pub completion_merge: CompletionMerge,
+    /// The language servers to format with. The first one that is running
+    /// is used, starting with the primary language server for `"..."`.
+    ///
+    /// Default: ["..."]
+    #[serde(default = "rest_of_language_servers")]
+    pub formatting: Vec<Arc<str>>,
+    /// The language servers whose diagnostics are shown.
+    ///
+    /// Default: ["..."]
+    #[serde(default = "rest_of_language_servers")]
+    pub diagnostics: Vec<Arc<str>>,
+}
+
+impl Default for LanguageServerRouting {
+    fn default() -> Self {
+        Self {
+            completions: rest_of_language_servers(),
+            completion_merge: CompletionMerge::default(),
+            formatting: rest_of_language_servers(),
+            diagnostics: rest_of_language_servers(),
+        }
+    }
+}
+
+impl LanguageServerRouting {
+    /// Returns the available language servers to request completions from, in order.
+    pub fn completion_servers(
+        &self,
+        available_language_servers: &[LanguageServerName],
+    ) -> Vec<LanguageServerName> {
+        Self::routed_servers(&self.completions, available_language_servers)
+    }
+
+    /// Returns the available language servers to format with, in order of preference.
+    pub fn formatting_servers(
+        &self,
+        available_language_servers: &[LanguageServerName],
+    ) -> Vec<LanguageServerName> {
+        Self::routed_servers(&self.formatting, available_language_servers)
+    }
+
+    /// Returns whether the diagnostics of the given language server should be shown.
+    pub fn shows_diagnostics_from(
+        &self,
+        language_server: &LanguageServerName,
+        available_language_servers: &[LanguageServerName],
+    ) -> bool {
+        Self::routed_servers(&self.diagnostics, available_language_servers)
+            .contains(language_server)
+    }
+
+    fn routed_servers(
+        configured_language_servers: &[Arc<str>],
+        available_language_servers: &[LanguageServerName],
+    ) -> Vec<LanguageServerName> {
+        LanguageSettings::resolve_language_servers(
+            configured_language_servers,
+            available_language_servers,
+        )
+        .into_iter()
+        .filter(|language_server| available_language_servers.contains(language_server))
+        .collect()
+    }
+}
+
+fn rest_of_language_servers() -> Vec<Arc<str>> {
+    vec![LanguageSettings::REST_OF_LANGUAGE_SERVERS.into()]
+}
+
+/// How to combine the completions of several language servers.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CompletionMerge {
+    /// List the completions of every language server.
+    #[default]
+    All,
+    /// Only list the completions of the first language server that returns any.
+    First,
+    /// List the completions of every language server, dropping those with the
+    /// same label and text as a completion of an earlier language server.
+    Deduplicate,
+}
+
 /// The provider that supplies inline completions.
 #[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -247,6 +366,9 @@ pub struct LanguageSettingsContent {
     /// Indent guide related settings.
     #[serde(default)]
     pub indent_guides: Option<IndentGuideSettings>,
+    /// Sticky scroll related settings.
+    #[serde(default)]
+    pub sticky_scroll: Option<StickyScrollSettings>,
     /// Whether or not to perform a buffer format before saving.
     ///
     /// Default: on
@@ -291,6 +413,10 @@ pub struct LanguageSettingsContent {
     /// Default: ["..."]
     #[serde(default)]
     pub language_servers: Option<Vec<Arc<str>>>,
+    /// Which of the language servers running for this language to use for
+    /// each capability, when several of them are.
+    #[serde(default)]
+    pub language_server_routing: Option<LanguageServerRouting>,
     /// Controls whether inline completions are shown immediately (true)
     /// or manually by triggering `editor::ShowInlineCompletion` (false).
     ///
@@ -308,6 +434,11 @@ pub struct LanguageSettingsContent {
     /// Inlay hint related settings.
     #[serde(default)]
     pub inlay_hints: Option<InlayHintSettings>,
+    /// Whether to use additional LSP queries to format (and amend) the code after
+    /// every "trigger" symbol input, defined by LSP server capabilities.
+    ///
+    /// Default: true
+    pub use_on_type_format: Option<bool>,
     /// Whether to automatically type closing characters for you. For example,
     /// when you type (, Zed will automatically add a closing ) at the correct position.
     ///
@@ -321,6 +452,31 @@ pub struct LanguageSettingsContent {
     ///
     /// Default: false
     pub always_treat_brackets_as_autoclosed: Option<bool>,
+    /// Whether to edit linked ranges, such as the names of matching HTML tags, together.
+    ///
+    /// Default: true
+    pub linked_edits: Option<bool>,
+    /// How long to wait, in milliseconds, after the cursor moves before looking up
+    /// linked ranges again.
+    ///
+    /// Default: 50
+    pub linked_edits_debounce_ms: Option<u64>,
+    /// Whether to color brackets according to their nesting depth, using the
+    /// accent colors of the theme.
+    ///
+    /// Default: false
+    pub rainbow_brackets: Option<bool>,
+    /// How to layer the semantic tokens of language servers over tree-sitter
+    /// highlights.
+    ///
+    /// Default: combined
+    pub semantic_tokens: Option<SemanticTokens>,
+    /// Groups of file name endings that tell which files are related to each other.
+    /// `editor::GoToRelatedFile` swaps the longest ending of the file's name for the
+    /// other endings of its groups, to find the related file in the same directory.
+    ///
+    /// Default: [] (or e.g. [[".c", ".h"]] for C).
+    pub related_files: Option<Vec<Vec<String>>>,
     /// Which code actions to run on save after the formatter.
     /// These are not run if formatting is off.
     ///
@@ -381,6 +537,22 @@ pub enum FormatOnSave {
     CodeActions(HashMap<String, bool>),
 }
 
+/// Which source of highlights to prefer, between tree-sitter and the semantic
+/// tokens of language servers.
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum SemanticTokens {
+    /// Only highlight with tree-sitter.
+    Off,
+    /// Highlight with tree-sitter, and style the modifiers of semantic tokens,
+    /// such as mutable or deprecated, on top.
+    #[default]
+    Combined,
+    /// Also color semantic tokens by their type, preferring the classification
+    /// of language servers over tree-sitter's.
+    Full,
+}
+
 /// Controls how whitespace should be displayedin the editor.
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -452,6 +624,26 @@ fn line_width() -> u32 {
     1
 }
 
+/// The settings for sticky scroll.
+#[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct StickyScrollSettings {
+    /// Whether to pin the headers of the scopes enclosing the first visible line
+    /// to the top of the editor.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub enabled: bool,
+    /// The maximum number of headers to pin at once.
+    ///
+    /// Default: 5
+    #[serde(default = "sticky_scroll_max_lines")]
+    pub max_lines: u32,
+}
+
+fn sticky_scroll_max_lines() -> u32 {
+    5
+}
+
 /// Determines how indent guides are colored.
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "snake_case")]
@@ -773,14 +965,24 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
     merge(&mut settings.tab_size, src.tab_size);
     merge(&mut settings.hard_tabs, src.hard_tabs);
     merge(&mut settings.soft_wrap, src.soft_wrap);
+    merge(&mut settings.use_on_type_format, src.use_on_type_format);
     merge(&mut settings.use_autoclose, src.use_autoclose);
     merge(
         &mut settings.always_treat_brackets_as_autoclosed,
         src.always_treat_brackets_as_autoclosed,
     );
+    merge(&mut settings.linked_edits, src.linked_edits);
+    merge(
+        &mut settings.linked_edits_debounce_ms,
+        src.linked_edits_debounce_ms,
+    );
+    merge(&mut settings.rainbow_brackets, src.rainbow_brackets);
+    merge(&mut settings.semantic_tokens, src.semantic_tokens);
+    merge(&mut settings.related_files, src.related_files.clone());
     merge(&mut settings.show_wrap_guides, src.show_wrap_guides);
     merge(&mut settings.wrap_guides, src.wrap_guides.clone());
     merge(&mut settings.indent_guides, src.indent_guides);
+    merge(&mut settings.sticky_scroll, src.sticky_scroll);
     merge(
         &mut settings.code_actions_on_format,
         src.code_actions_on_format.clone(),
@@ -806,6 +1008,10 @@ fn merge_settings(settings: &mut LanguageSettings, src: &LanguageSettingsContent
         src.enable_language_server,
     );
     merge(&mut settings.language_servers, src.language_servers.clone());
+    merge(
+        &mut settings.language_server_routing,
+        src.language_server_routing.clone(),
+    );
     merge(
         &mut settings.show_inline_completions,
         src.show_inline_completions,
@@ -846,16 +1052,16 @@ pub struct PrettierSettings {
 mod tests {
     use super::*;
 
+    fn language_server_names(names: &[&str]) -> Vec<LanguageServerName> {
+        names
+            .into_iter()
+            .copied()
+            .map(|name| LanguageServerName(name.into()))
+            .collect::<Vec<_>>()
+    }
+
     #[test]
     pub fn test_resolve_language_servers() {
-        fn language_server_names(names: &[&str]) -> Vec<LanguageServerName> {
-            names
-                .into_iter()
-                .copied()
-                .map(|name| LanguageServerName(name.into()))
-                .collect::<Vec<_>>()
-        }
-
         let available_language_servers = language_server_names(&[
             "typescript-language-server",
             "biome",
@@ -925,4 +1131,45 @@ mod tests {
             ])
         );
     }
+
+    #[test]
+    pub fn test_language_server_routing() {
+        let available_language_servers =
+            language_server_names(&["typescript-language-server", "eslint", "tailwind"]);
+        let routing = LanguageServerRouting {
+            completions: vec!["tailwind".into(), "...".into(), "!eslint".into()],
+            completion_merge: CompletionMerge::Deduplicate,
+            formatting: vec!["prettierd".into(), "eslint".into()],
+            diagnostics: vec!["!tailwind".into(), "...".into()],
+        };
+
+        assert_eq!(
+            routing.completion_servers(&available_language_servers),
+            language_server_names(&["tailwind", "typescript-language-server"])
+        );
+        // Language servers that aren't running are skipped.
+        assert_eq!(
+            routing.formatting_servers(&available_language_servers),
+            language_server_names(&["eslint"])
+        );
+        assert!(routing.shows_diagnostics_from(
+            &LanguageServerName("eslint".into()),
+            &available_language_servers
+        ));
+        assert!(!routing.shows_diagnostics_from(
+            &LanguageServerName("tailwind".into()),
+            &available_language_servers
+        ));
+
+        // By default, every language server is used for everything.
+        let routing = LanguageServerRouting::default();
+        assert_eq!(
+            routing.completion_servers(&available_language_servers),
+            available_language_servers
+        );
+        assert_eq!(
+            routing.formatting_servers(&available_language_servers),
+            available_language_servers
+        );
+    }
 }
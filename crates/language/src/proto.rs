@@ -1,11 +1,13 @@
 //! Handles conversions of `language` items to and from the [`rpc`] protocol.
 
-use crate::{diagnostic_set::DiagnosticEntry, CursorShape, Diagnostic};
+use crate::{
+    diagnostic_set::DiagnosticEntry, CursorShape, Diagnostic, DiagnosticRelatedInformation,
+};
 use anyhow::{anyhow, Result};
 use clock::ReplicaId;
 use lsp::{DiagnosticSeverity, LanguageServerId};
 use rpc::proto;
-use std::{ops::Range, sync::Arc};
+use std::{ops::Range, path::Path, sync::Arc};
 use text::*;
 
 pub use proto::{BufferState, Operation};
@@ -213,10 +215,32 @@ pub fn serialize_diagnostics<'a>(
             code: entry.diagnostic.code.clone(),
             is_disk_based: entry.diagnostic.is_disk_based,
             is_unnecessary: entry.diagnostic.is_unnecessary,
+            related_information: entry
+                .diagnostic
+                .related_information
+                .iter()
+                .map(|info| proto::DiagnosticRelatedInformation {
+                    abs_path: info.abs_path.to_string_lossy().to_string(),
+                    start: Some(serialize_point_utf16(info.range.start.0)),
+                    end: Some(serialize_point_utf16(info.range.end.0)),
+                    message: info.message.clone(),
+                })
+                .collect(),
         })
         .collect()
 }
 
+fn serialize_point_utf16(point: PointUtf16) -> proto::PointUtf16 {
+    proto::PointUtf16 {
+        row: point.row,
+        column: point.column,
+    }
+}
+
+fn deserialize_point_utf16(point: proto::PointUtf16) -> Unclipped<PointUtf16> {
+    Unclipped(PointUtf16::new(point.row, point.column))
+}
+
 /// Serializes an [`Anchor`] to be sent over RPC.
 pub fn serialize_anchor(anchor: &Anchor) -> proto::Anchor {
     proto::Anchor {
@@ -398,6 +422,18 @@ pub fn deserialize_diagnostics(
                     is_primary: diagnostic.is_primary,
                     is_disk_based: diagnostic.is_disk_based,
                     is_unnecessary: diagnostic.is_unnecessary,
+                    related_information: diagnostic
+                        .related_information
+                        .into_iter()
+                        .filter_map(|info| {
+                            Some(DiagnosticRelatedInformation {
+                                abs_path: Path::new(&info.abs_path).into(),
+                                range: deserialize_point_utf16(info.start?)
+                                    ..deserialize_point_utf16(info.end?),
+                                message: info.message,
+                            })
+                        })
+                        .collect(),
                 },
             })
         })
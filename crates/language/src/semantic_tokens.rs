@@ -0,0 +1,132 @@
+use crate::{language_settings::SemanticTokens, Anchor};
+use gpui::{px, FontStyle, FontWeight, HighlightStyle, StrikethroughStyle, UnderlineStyle};
+use std::{ops::Range, sync::Arc};
+use theme::SyntaxTheme;
+
+/// A range of a buffer classified by a language server, such as a mutable variable or a
+/// deprecated function.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SemanticToken {
+    pub range: Range<Anchor>,
+    /// The type of the token, as named by the legend of its language server.
+    pub token_type: Arc<str>,
+    /// The modifiers of the token, as named by the legend of its language server.
+    pub modifiers: Vec<Arc<str>>,
+}
+
+/// Syntax theme names to fall back to for the token types that tree-sitter highlight queries
+/// don't name the same way.
+const TOKEN_TYPE_FALLBACKS: &[(&str, &str)] = &[
+    ("class", "type"),
+    ("decorator", "attribute"),
+    ("enum", "type"),
+    ("enumMember", "variant"),
+    ("interface", "type"),
+    ("macro", "function"),
+    ("method", "function"),
+    ("parameter", "variable"),
+    ("regexp", "string.regex"),
+    ("struct", "type"),
+    ("typeParameter", "type"),
+];
+
+/// The style layered over the tree-sitter highlights of a semantic token's text.
+///
+/// The token's modifiers are always styled. With [`SemanticTokens::Full`], the token is also
+/// colored like the syntax theme colors its type, so the language server's classification wins
+/// over tree-sitter's.
+pub fn semantic_token_style(
+    token: &SemanticToken,
+    setting: SemanticTokens,
+    theme: &SyntaxTheme,
+) -> Option<HighlightStyle> {
+    let mut style = HighlightStyle::default();
+    match setting {
+        SemanticTokens::Off => return None,
+        SemanticTokens::Combined => {}
+        SemanticTokens::Full => {
+            let token_type = token.token_type.as_ref();
+            let name = if theme.highlight_id(token_type).is_some() {
+                Some(token_type)
+            } else {
+                TOKEN_TYPE_FALLBACKS
+                    .iter()
+                    .find(|(fallback_type, _)| *fallback_type == token_type)
+                    .map(|(_, name)| *name)
+            };
+            if let Some(name) = name {
+                style.color = theme.get(name).color;
+            }
+        }
+    }
+
+    for modifier in &token.modifiers {
+        match modifier.as_ref() {
+            "deprecated" => {
+                style.strikethrough = Some(StrikethroughStyle {
+                    thickness: px(1.),
+                    color: None,
+                })
+            }
+            "mutable" => {
+                style.underline = Some(UnderlineStyle {
+                    thickness: px(1.),
+                    color: None,
+                    wavy: false,
+                })
+            }
+            "async" => style.font_style = Some(FontStyle::Italic),
+            "unsafe" => style.font_weight = Some(FontWeight::BOLD),
+            _ => {}
+        }
+    }
+
+    (style != HighlightStyle::default()).then_some(style)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::hsla;
+
+    #[test]
+    fn test_semantic_token_style() {
+        let theme = SyntaxTheme::new_test([("type", hsla(0.5, 1., 0.5, 1.))]);
+        let token = |token_type: &str, modifiers: &[&str]| SemanticToken {
+            range: Anchor::MIN..Anchor::MAX,
+            token_type: token_type.into(),
+            modifiers: modifiers
+                .iter()
+                .map(|modifier| (*modifier).into())
+                .collect(),
+        };
+
+        // Tokens without styled modifiers leave the tree-sitter highlights alone.
+        assert_eq!(
+            semantic_token_style(&token("class", &[]), SemanticTokens::Combined, &theme),
+            None
+        );
+        assert_eq!(
+            semantic_token_style(&token("class", &["mutable"]), SemanticTokens::Off, &theme),
+            None
+        );
+
+        let style = semantic_token_style(
+            &token("variable", &["mutable"]),
+            SemanticTokens::Combined,
+            &theme,
+        )
+        .unwrap();
+        assert!(style.underline.is_some());
+        assert_eq!(style.color, None);
+
+        let style = semantic_token_style(
+            &token("class", &["deprecated"]),
+            SemanticTokens::Full,
+            &theme,
+        )
+        .unwrap();
+        assert!(style.strikethrough.is_some());
+        assert_eq!(style.color, Some(hsla(0.5, 1., 0.5, 1.)));
+    }
+}
@@ -1,4 +1,5 @@
 mod lsp_log;
+mod lsp_request_trace;
 mod syntax_tree_view;
 
 #[cfg(test)]
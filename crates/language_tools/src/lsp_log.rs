@@ -7,10 +7,13 @@ use gpui::{
     FocusableView, IntoElement, Model, ModelContext, ParentElement, Render, Styled, Subscription,
     View, ViewContext, VisualContext, WeakModel, WindowContext,
 };
-use language::{LanguageServerId, LanguageServerName};
+use language::{LanguageServerId, LanguageServerName, Point};
 use lsp::{IoKind, LanguageServer};
 use project::{search::SearchQuery, Project};
-use std::{borrow::Cow, sync::Arc};
+use std::{
+    borrow::Cow,
+    sync::{Arc, Weak},
+};
 use ui::{popover_menu, prelude::*, Button, Checkbox, ContextMenu, Label, Selection};
 use workspace::{
     item::{Item, ItemHandle, TabContentParams},
@@ -18,6 +21,8 @@ use workspace::{
     ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView, Workspace,
 };
 
+use crate::lsp_request_trace::{RequestTrace, RequestTraceEntry};
+
 const SEND_LINE: &str = "// Send:";
 const RECEIVE_LINE: &str = "// Receive:";
 const MAX_STORED_LOG_ENTRIES: usize = 2000;
@@ -36,6 +41,7 @@ struct ProjectState {
 
 struct LanguageServerState {
     kind: LanguageServerKind,
+    server: Weak<LanguageServer>,
     log_messages: VecDeque<String>,
     rpc_state: Option<LanguageServerRpcState>,
     _io_logs_subscription: Option<lsp::Subscription>,
@@ -59,6 +65,7 @@ impl LanguageServerKind {
 struct LanguageServerRpcState {
     rpc_messages: VecDeque<String>,
     last_message_kind: Option<MessageKind>,
+    request_trace: RequestTrace,
 }
 
 pub struct LspLogView {
@@ -66,7 +73,11 @@ pub struct LspLogView {
     editor_subscriptions: Vec<Subscription>,
     log_store: Model<LogStore>,
     current_server_id: Option<LanguageServerId>,
-    is_showing_rpc_trace: bool,
+    log_kind: LogKind,
+    /// The method to show the requests and responses of in the request trace, or `None` for all.
+    request_trace_method: Option<String>,
+    /// The request trace entries shown in the editor, along with the row each starts at.
+    request_trace_rows: Vec<(u32, RequestTraceEntry)>,
     project: Model<Project>,
     focus_handle: FocusHandle,
     _log_store_subscriptions: Vec<Subscription>,
@@ -77,12 +88,19 @@ pub struct LspLogToolbarItemView {
     _log_view_subscription: Option<Subscription>,
 }
 
-#[derive(Copy, Clone, PartialEq, Eq)]
-enum MessageKind {
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum MessageKind {
     Send,
     Receive,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum LogKind {
+    Logs,
+    Rpc,
+    RequestTrace,
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub(crate) struct LogMenuItem {
     pub server_id: LanguageServerId,
@@ -90,10 +108,11 @@ pub(crate) struct LogMenuItem {
     pub worktree_root_name: String,
     pub rpc_trace_enabled: bool,
     pub rpc_trace_selected: bool,
+    pub request_trace_selected: bool,
     pub logs_selected: bool,
 }
 
-actions!(debug, [OpenLanguageServerLogs]);
+actions!(debug, [OpenLanguageServerLogs, ReplayLanguageServerRequest]);
 
 pub fn init(cx: &mut AppContext) {
     let log_store = cx.new_model(|cx| LogStore::new(cx));
@@ -119,6 +138,14 @@ pub fn init(cx: &mut AppContext) {
                 );
             }
         });
+        workspace.register_action(|workspace, action: &ReplayLanguageServerRequest, cx| {
+            if let Some(log_view) = workspace
+                .active_item(cx)
+                .and_then(|item| item.downcast::<LspLogView>())
+            {
+                log_view.update(cx, |log_view, cx| log_view.replay_request(action, cx));
+            }
+        });
     })
     .detach();
 }
@@ -241,6 +268,7 @@ impl LogStore {
                 cx.notify();
                 LanguageServerState {
                     kind,
+                    server: Arc::downgrade(&server),
                     rpc_state: None,
                     log_messages: VecDeque::with_capacity(MAX_STORED_LOG_ENTRIES),
                     _io_logs_subscription: None,
@@ -336,6 +364,7 @@ impl LogStore {
             .get_or_insert_with(|| LanguageServerRpcState {
                 rpc_messages: VecDeque::with_capacity(MAX_STORED_LOG_ENTRIES),
                 last_message_kind: None,
+                request_trace: RequestTrace::default(),
             });
         Some(rpc_state)
     }
@@ -375,6 +404,13 @@ impl LogStore {
             MessageKind::Send
         };
 
+        if let Some(entry) = state.request_trace.record(kind, message) {
+            cx.emit(Event::NewRequestTraceEntry {
+                id: language_server_id,
+                entry,
+            });
+        }
+
         let rpc_log_lines = &mut state.rpc_messages;
         if state.last_message_kind != Some(kind) {
             let line_before_message = match kind {
@@ -424,11 +460,7 @@ impl LspLogView {
             if let Some(current_lsp) = this.current_server_id {
                 if !store.read(cx).language_servers.contains_key(&current_lsp) {
                     if let Some(server_id) = first_server_id_for_project {
-                        if this.is_showing_rpc_trace {
-                            this.show_rpc_trace_for_server(server_id, cx)
-                        } else {
-                            this.show_logs_for_server(server_id, cx)
-                        }
+                        this.show_log_kind_for_server(this.log_kind, server_id, cx)
                     } else {
                         this.current_server_id = None;
                         this.editor.update(cx, |editor, cx| {
@@ -440,11 +472,7 @@ impl LspLogView {
                     }
                 }
             } else if let Some(server_id) = first_server_id_for_project {
-                if this.is_showing_rpc_trace {
-                    this.show_rpc_trace_for_server(server_id, cx)
-                } else {
-                    this.show_logs_for_server(server_id, cx)
-                }
+                this.show_log_kind_for_server(this.log_kind, server_id, cx)
             }
 
             cx.notify();
@@ -452,8 +480,8 @@ impl LspLogView {
         let events_subscriptions = cx.subscribe(&log_store, |log_view, _, e, cx| match e {
             Event::NewServerLogEntry { id, entry, is_rpc } => {
                 if log_view.current_server_id == Some(*id) {
-                    if (*is_rpc && log_view.is_showing_rpc_trace)
-                        || (!*is_rpc && !log_view.is_showing_rpc_trace)
+                    if (*is_rpc && log_view.log_kind == LogKind::Rpc)
+                        || (!*is_rpc && log_view.log_kind == LogKind::Logs)
                     {
                         log_view.editor.update(cx, |editor, cx| {
                             editor.set_read_only(false);
@@ -470,6 +498,21 @@ impl LspLogView {
                     }
                 }
             }
+            Event::NewRequestTraceEntry { id, entry } => {
+                if log_view.current_server_id == Some(*id)
+                    && log_view.log_kind == LogKind::RequestTrace
+                    && log_view.shows_request_trace_entry(entry)
+                {
+                    log_view.editor.update(cx, |editor, cx| {
+                        editor.set_read_only(false);
+                        let last_point = editor.buffer().read(cx).len(cx);
+                        let row = editor.buffer().read(cx).snapshot(cx).max_point().row;
+                        log_view.request_trace_rows.push((row, entry.clone()));
+                        editor.edit(vec![(last_point..last_point, entry.text())], cx);
+                        editor.set_read_only(true);
+                    });
+                }
+            }
         });
         let (editor, editor_subscriptions) = Self::editor_for_logs(String::new(), cx);
 
@@ -485,7 +528,9 @@ impl LspLogView {
             project,
             log_store,
             current_server_id: None,
-            is_showing_rpc_trace: false,
+            log_kind: LogKind::Logs,
+            request_trace_method: None,
+            request_trace_rows: Vec::new(),
             _log_store_subscriptions: vec![
                 model_changes_subscription,
                 events_subscriptions,
@@ -540,10 +585,9 @@ impl LspLogView {
                     server_name: language_server_name,
                     worktree_root_name: worktree.read(cx).root_name().to_string(),
                     rpc_trace_enabled: state.rpc_state.is_some(),
-                    rpc_trace_selected: self.is_showing_rpc_trace
-                        && self.current_server_id == Some(server_id),
-                    logs_selected: !self.is_showing_rpc_trace
-                        && self.current_server_id == Some(server_id),
+                    rpc_trace_selected: self.is_showing(LogKind::Rpc, server_id),
+                    request_trace_selected: self.is_showing(LogKind::RequestTrace, server_id),
+                    logs_selected: self.is_showing(LogKind::Logs, server_id),
                 })
             })
             .chain(
@@ -557,10 +601,10 @@ impl LspLogView {
                             server_name: name.clone(),
                             worktree_root_name: "supplementary".to_string(),
                             rpc_trace_enabled: state.rpc_state.is_some(),
-                            rpc_trace_selected: self.is_showing_rpc_trace
-                                && self.current_server_id == Some(server_id),
-                            logs_selected: !self.is_showing_rpc_trace
-                                && self.current_server_id == Some(server_id),
+                            rpc_trace_selected: self.is_showing(LogKind::Rpc, server_id),
+                            request_trace_selected: self
+                                .is_showing(LogKind::RequestTrace, server_id),
+                            logs_selected: self.is_showing(LogKind::Logs, server_id),
                         })
                     }),
             )
@@ -574,10 +618,10 @@ impl LspLogView {
                             server_name: name.clone(),
                             worktree_root_name: "supplementary".to_string(),
                             rpc_trace_enabled: state.rpc_state.is_some(),
-                            rpc_trace_selected: self.is_showing_rpc_trace
-                                && self.current_server_id == Some(*server_id),
-                            logs_selected: !self.is_showing_rpc_trace
-                                && self.current_server_id == Some(*server_id),
+                            rpc_trace_selected: self.is_showing(LogKind::Rpc, *server_id),
+                            request_trace_selected: self
+                                .is_showing(LogKind::RequestTrace, *server_id),
+                            logs_selected: self.is_showing(LogKind::Logs, *server_id),
                         }),
                         _ => None,
                     }),
@@ -588,6 +632,23 @@ impl LspLogView {
         Some(rows)
     }
 
+    fn is_showing(&self, log_kind: LogKind, server_id: LanguageServerId) -> bool {
+        self.log_kind == log_kind && self.current_server_id == Some(server_id)
+    }
+
+    fn show_log_kind_for_server(
+        &mut self,
+        log_kind: LogKind,
+        server_id: LanguageServerId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        match log_kind {
+            LogKind::Logs => self.show_logs_for_server(server_id, cx),
+            LogKind::Rpc => self.show_rpc_trace_for_server(server_id, cx),
+            LogKind::RequestTrace => self.show_request_trace_for_server(server_id, cx),
+        }
+    }
+
     fn show_logs_for_server(&mut self, server_id: LanguageServerId, cx: &mut ViewContext<Self>) {
         let log_contents = self
             .log_store
@@ -596,7 +657,7 @@ impl LspLogView {
             .map(log_contents);
         if let Some(log_contents) = log_contents {
             self.current_server_id = Some(server_id);
-            self.is_showing_rpc_trace = false;
+            self.log_kind = LogKind::Logs;
             let (editor, editor_subscriptions) = Self::editor_for_logs(log_contents, cx);
             self.editor = editor;
             self.editor_subscriptions = editor_subscriptions;
@@ -617,28 +678,44 @@ impl LspLogView {
         });
         if let Some(rpc_log) = rpc_log {
             self.current_server_id = Some(server_id);
-            self.is_showing_rpc_trace = true;
+            self.log_kind = LogKind::Rpc;
             let (editor, editor_subscriptions) = Self::editor_for_logs(rpc_log, cx);
-            let language = self.project.read(cx).languages().language_for_name("JSON");
-            editor
-                .read(cx)
-                .buffer()
-                .read(cx)
-                .as_singleton()
-                .expect("log buffer should be a singleton")
-                .update(cx, |_, cx| {
-                    cx.spawn({
-                        let buffer = cx.handle();
-                        |_, mut cx| async move {
-                            let language = language.await.ok();
-                            buffer.update(&mut cx, |buffer, cx| {
-                                buffer.set_language(language, cx);
-                            })
-                        }
-                    })
-                    .detach_and_log_err(cx);
-                });
+            self.set_json_language(&editor, cx);
+            self.editor = editor;
+            self.editor_subscriptions = editor_subscriptions;
+            cx.notify();
+        }
 
+        cx.focus(&self.focus_handle);
+    }
+
+    pub(crate) fn show_request_trace_for_server(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let entries = self.log_store.update(cx, |log_store, _| {
+            log_store
+                .enable_rpc_trace_for_language_server(server_id)
+                .map(|state| state.request_trace.entries().cloned().collect::<Vec<_>>())
+        });
+        if let Some(entries) = entries {
+            self.current_server_id = Some(server_id);
+            self.log_kind = LogKind::RequestTrace;
+            self.request_trace_rows.clear();
+            let mut contents = String::new();
+            let mut row = 0;
+            for entry in entries {
+                if !self.shows_request_trace_entry(&entry) {
+                    continue;
+                }
+                let text = entry.text();
+                contents.push_str(&text);
+                self.request_trace_rows.push((row, entry));
+                row += text.matches('\n').count() as u32;
+            }
+            let (editor, editor_subscriptions) = Self::editor_for_logs(contents, cx);
+            self.set_json_language(&editor, cx);
             self.editor = editor;
             self.editor_subscriptions = editor_subscriptions;
             cx.notify();
@@ -647,6 +724,98 @@ impl LspLogView {
         cx.focus(&self.focus_handle);
     }
 
+    fn shows_request_trace_entry(&self, entry: &RequestTraceEntry) -> bool {
+        self.request_trace_method
+            .as_ref()
+            .map_or(true, |method| *method == entry.method)
+    }
+
+    pub(crate) fn filter_request_trace(
+        &mut self,
+        method: Option<String>,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.request_trace_method = method;
+        if let Some(server_id) = self.current_server_id {
+            self.show_request_trace_for_server(server_id, cx);
+        }
+    }
+
+    fn request_trace_methods(&self, cx: &AppContext) -> Vec<String> {
+        self.current_server_id
+            .and_then(|server_id| {
+                let state = self.log_store.read(cx).language_servers.get(&server_id)?;
+                Some(state.rpc_state.as_ref()?.request_trace.methods())
+            })
+            .unwrap_or_default()
+    }
+
+    /// Sends the request under the cursor in the request trace to the language server again.
+    pub(crate) fn replay_request(
+        &mut self,
+        _: &ReplayLanguageServerRequest,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if self.log_kind != LogKind::RequestTrace {
+            return;
+        }
+        let Some(server) = self.current_server_id.and_then(|server_id| {
+            let state = self.log_store.read(cx).language_servers.get(&server_id)?;
+            state.server.upgrade()
+        }) else {
+            return;
+        };
+
+        let cursor_row = self.editor.update(cx, |editor, cx| {
+            editor.selections.newest::<Point>(cx).head().row
+        });
+        let Some((_, entry)) = self
+            .request_trace_rows
+            .iter()
+            .rev()
+            .find(|(row, _)| *row <= cursor_row)
+        else {
+            return;
+        };
+        let request = if entry.is_replayable() {
+            Some(entry)
+        } else {
+            self.request_trace_rows
+                .iter()
+                .map(|(_, request)| request)
+                .find(|request| request.is_replayable() && request.id == entry.id)
+        };
+        let Some(request) = request else {
+            return;
+        };
+
+        let response = server.request_raw(&request.method, request.payload.clone());
+        cx.spawn(|_, _| async move { response.await.map(drop) })
+            .detach_and_log_err(cx);
+    }
+
+    fn set_json_language(&self, editor: &View<Editor>, cx: &mut ViewContext<Self>) {
+        let language = self.project.read(cx).languages().language_for_name("JSON");
+        editor
+            .read(cx)
+            .buffer()
+            .read(cx)
+            .as_singleton()
+            .expect("log buffer should be a singleton")
+            .update(cx, |_, cx| {
+                cx.spawn({
+                    let buffer = cx.handle();
+                    |_, mut cx| async move {
+                        let language = language.await.ok();
+                        buffer.update(&mut cx, |buffer, cx| {
+                            buffer.set_language(language, cx);
+                        })
+                    }
+                })
+                .detach_and_log_err(cx);
+            });
+    }
+
     fn toggle_rpc_trace_for_server(
         &mut self,
         server_id: LanguageServerId,
@@ -806,11 +975,16 @@ impl Render for LspLogToolbarItemView {
         let Some(log_view) = self.log_view.clone() else {
             return div();
         };
-        let (menu_rows, current_server_id) = log_view.update(cx, |log_view, cx| {
-            let menu_rows = log_view.menu_items(cx).unwrap_or_default();
-            let current_server_id = log_view.current_server_id;
-            (menu_rows, current_server_id)
-        });
+        let (menu_rows, current_server_id, log_kind, request_trace_method) =
+            log_view.update(cx, |log_view, cx| {
+                let menu_rows = log_view.menu_items(cx).unwrap_or_default();
+                (
+                    menu_rows,
+                    log_view.current_server_id,
+                    log_view.log_kind,
+                    log_view.request_trace_method.clone(),
+                )
+            });
 
         let current_server = current_server_id.and_then(|current_server_id| {
             if let Ok(ix) = menu_rows.binary_search_by_key(&current_server_id, |e| e.server_id) {
@@ -833,6 +1007,8 @@ impl Render for LspLogToolbarItemView {
                             row.worktree_root_name,
                             if row.rpc_trace_selected {
                                 RPC_MESSAGES
+                            } else if row.request_trace_selected {
+                                REQUEST_TRACE
                             } else {
                                 SERVER_LOGS
                             },
@@ -862,7 +1038,7 @@ impl Render for LspLogToolbarItemView {
                         if server_selected && row.logs_selected {
                             let selected_ix = menu.select_last();
                             debug_assert_eq!(
-                                Some(ix * 3 + 1),
+                                Some(ix * 4 + 1),
                                 selected_ix,
                                 "Could not scroll to a just added LSP menu item"
                             );
@@ -913,7 +1089,23 @@ impl Render for LspLogToolbarItemView {
                         if server_selected && row.rpc_trace_selected {
                             let selected_ix = menu.select_last();
                             debug_assert_eq!(
-                                Some(ix * 3 + 2),
+                                Some(ix * 4 + 2),
+                                selected_ix,
+                                "Could not scroll to a just added LSP menu item"
+                            );
+                        }
+
+                        menu = menu.entry(
+                            REQUEST_TRACE,
+                            None,
+                            cx.handler_for(&log_view, move |view, cx| {
+                                view.show_request_trace_for_server(row.server_id, cx);
+                            }),
+                        );
+                        if server_selected && row.request_trace_selected {
+                            let selected_ix = menu.select_last();
+                            debug_assert_eq!(
+                                Some(ix * 4 + 3),
                                 selected_ix,
                                 "Could not scroll to a just added LSP menu item"
                             );
@@ -924,30 +1116,89 @@ impl Render for LspLogToolbarItemView {
                 .into()
             });
 
-        h_flex().size_full().child(lsp_menu).child(
-            div()
-                .child(
-                    Button::new("clear_log_button", "Clear").on_click(cx.listener(
-                        |this, _, cx| {
-                            if let Some(log_view) = this.log_view.as_ref() {
-                                log_view.update(cx, |log_view, cx| {
-                                    log_view.editor.update(cx, |editor, cx| {
-                                        editor.set_read_only(false);
-                                        editor.clear(cx);
-                                        editor.set_read_only(true);
-                                    });
-                                })
-                            }
-                        },
-                    )),
-                )
-                .ml_2(),
-        )
+        let request_trace_controls = (log_kind == LogKind::RequestTrace).then(|| {
+            let methods = self
+                .log_view
+                .as_ref()
+                .map(|log_view| log_view.read(cx).request_trace_methods(cx))
+                .unwrap_or_default();
+            let log_view = self.log_view.clone();
+            let method_menu = popover_menu("LspRequestTraceMethod")
+                .anchor(AnchorCorner::TopLeft)
+                .trigger(Button::new(
+                    "request_trace_method_header",
+                    request_trace_method
+                        .clone()
+                        .unwrap_or_else(|| ALL_METHODS.to_string()),
+                ))
+                .menu(move |cx| {
+                    let methods = methods.clone();
+                    let log_view = log_view.clone()?;
+                    Some(ContextMenu::build(cx, move |mut menu, cx| {
+                        menu = menu.entry(
+                            ALL_METHODS,
+                            None,
+                            cx.handler_for(&log_view, |view, cx| {
+                                view.filter_request_trace(None, cx);
+                            }),
+                        );
+                        for method in methods {
+                            menu = menu.entry(
+                                method.clone(),
+                                None,
+                                cx.handler_for(&log_view, move |view, cx| {
+                                    view.filter_request_trace(Some(method.clone()), cx);
+                                }),
+                            );
+                        }
+                        menu
+                    }))
+                });
+
+            h_flex().ml_2().gap_2().child(method_menu).child(
+                Button::new("replay_request_button", "Replay Request").on_click(cx.listener(
+                    |this, _, cx| {
+                        if let Some(log_view) = this.log_view.as_ref() {
+                            log_view.update(cx, |log_view, cx| {
+                                log_view.replay_request(&ReplayLanguageServerRequest, cx)
+                            })
+                        }
+                    },
+                )),
+            )
+        });
+
+        h_flex()
+            .size_full()
+            .child(lsp_menu)
+            .children(request_trace_controls)
+            .child(
+                div()
+                    .child(
+                        Button::new("clear_log_button", "Clear").on_click(cx.listener(
+                            |this, _, cx| {
+                                if let Some(log_view) = this.log_view.as_ref() {
+                                    log_view.update(cx, |log_view, cx| {
+                                        log_view.request_trace_rows.clear();
+                                        log_view.editor.update(cx, |editor, cx| {
+                                            editor.set_read_only(false);
+                                            editor.clear(cx);
+                                            editor.set_read_only(true);
+                                        });
+                                    })
+                                }
+                            },
+                        )),
+                    )
+                    .ml_2(),
+            )
     }
 }
 
 const RPC_MESSAGES: &str = "RPC Messages";
+const REQUEST_TRACE: &str = "Request Trace";
 const SERVER_LOGS: &str = "Server Logs";
+const ALL_METHODS: &str = "All Methods";
 
 impl LspLogToolbarItemView {
     pub fn new() -> Self {
@@ -986,6 +1237,10 @@ pub enum Event {
         entry: String,
         is_rpc: bool,
     },
+    NewRequestTraceEntry {
+        id: LanguageServerId,
+        entry: RequestTraceEntry,
+    },
 }
 
 impl EventEmitter<Event> for LogStore {}
@@ -1,10 +1,10 @@
 use std::sync::Arc;
 
-use crate::lsp_log::LogMenuItem;
+use crate::lsp_log::{LogMenuItem, ReplayLanguageServerRequest};
 
 use super::*;
 use futures::StreamExt;
-use gpui::{Context, TestAppContext, VisualTestContext};
+use gpui::{AppContext, Context, TestAppContext, VisualTestContext};
 use language::{
     tree_sitter_rust, FakeLspAdapter, Language, LanguageConfig, LanguageMatcher, LanguageServerName,
 };
@@ -93,6 +93,7 @@ async fn test_lsp_logs(cx: &mut TestAppContext) {
                     .to_string(),
                 rpc_trace_enabled: false,
                 rpc_trace_selected: false,
+                request_trace_selected: false,
                 logs_selected: true,
             }]
         );
@@ -100,6 +101,104 @@ async fn test_lsp_logs(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_lsp_request_trace(cx: &mut TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.background_executor.clone());
+    fs.insert_tree("/the-root", json!({ "test.rs": "" })).await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(Arc::new(Language::new(
+        LanguageConfig {
+            name: "Rust".into(),
+            matcher: LanguageMatcher {
+                path_suffixes: vec!["rs".to_string()],
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+        Some(tree_sitter_rust::language()),
+    )));
+    let mut fake_rust_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            name: "the-rust-language-server",
+            ..Default::default()
+        },
+    );
+
+    let log_store = cx.new_model(|cx| LogStore::new(cx));
+    log_store.update(cx, |store, cx| store.add_project(&project, cx));
+
+    let _rust_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/the-root/test.rs", cx)
+        })
+        .await
+        .unwrap();
+    let fake_server = fake_rust_servers.next().await.unwrap();
+    let server_id = fake_server.server.server_id();
+    fake_server.handle_request::<lsp::request::HoverRequest, _, _>(|_, _| async move {
+        Ok(Some(lsp::Hover {
+            contents: lsp::HoverContents::Scalar(lsp::MarkedString::String("hi".into())),
+            range: None,
+        }))
+    });
+
+    let window = cx.add_window(|cx| LspLogView::new(project.clone(), log_store.clone(), cx));
+    let log_view = window.root(cx).unwrap();
+    let mut cx = VisualTestContext::from_window(*window, cx);
+    log_view.update(&mut cx, |view, cx| {
+        view.show_request_trace_for_server(server_id, cx);
+    });
+
+    let server = project
+        .read_with(&cx, |project, _| project.language_server_for_id(server_id))
+        .unwrap();
+    server
+        .request::<lsp::request::HoverRequest>(lsp::HoverParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier::new(
+                    lsp::Url::from_file_path("/the-root/test.rs").unwrap(),
+                ),
+                position: lsp::Position::new(0, 0),
+            },
+            work_done_progress_params: Default::default(),
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let request_count = |view: &LspLogView, cx: &AppContext| {
+        view.editor
+            .read(cx)
+            .text(cx)
+            .matches("// Send request textDocument/hover")
+            .count()
+    };
+    log_view.update(&mut cx, |view, cx| {
+        let text = view.editor.read(cx).text(cx);
+        assert_eq!(request_count(view, cx), 1);
+        assert!(text.contains("// Receive response to textDocument/hover"));
+        assert!(text.contains("\"contents\": \"hi\""));
+
+        // Filtering by another method hides the hover request.
+        view.filter_request_trace(Some("initialize".into()), cx);
+        assert_eq!(request_count(view, cx), 0);
+        view.filter_request_trace(None, cx);
+        assert_eq!(request_count(view, cx), 1);
+
+        // Replaying the response at the cursor sends its request again.
+        view.replay_request(&ReplayLanguageServerRequest, cx);
+    });
+    cx.executor().run_until_parked();
+    log_view.update(&mut cx, |view, cx| {
+        assert_eq!(request_count(view, cx), 2);
+    });
+}
+
 fn init_test(cx: &mut gpui::TestAppContext) {
     cx.update(|cx| {
         let settings_store = SettingsStore::test(cx);
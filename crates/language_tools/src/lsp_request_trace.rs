@@ -0,0 +1,160 @@
+use std::time::{Duration, Instant};
+
+use collections::{HashMap, VecDeque};
+use serde_json::Value;
+
+use crate::lsp_log::MessageKind;
+
+const MAX_STORED_REQUEST_TRACE_ENTRIES: usize = 1000;
+
+/// A request or a response exchanged with a language server.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequestTraceEntry {
+    pub(crate) kind: MessageKind,
+    pub(crate) method: String,
+    pub(crate) id: String,
+    pub(crate) response: Option<TracedResponse>,
+    /// The size of the message, in bytes.
+    pub(crate) size: usize,
+    /// The params of a request, or the result or error of a response.
+    pub(crate) payload: Value,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) struct TracedResponse {
+    /// How long after its request the response arrived, when the request was traced.
+    pub duration: Option<Duration>,
+    pub is_error: bool,
+}
+
+/// The requests and responses exchanged with a language server, matched by their ids
+/// to time how long each response took.
+#[derive(Default)]
+pub(crate) struct RequestTrace {
+    entries: VecDeque<RequestTraceEntry>,
+    pending_requests: HashMap<(MessageKind, String), (String, Instant)>,
+}
+
+impl RequestTrace {
+    /// Records a message sent or received over the JSON-RPC connection, returning its entry
+    /// when it is a request or a response rather than a notification.
+    pub(crate) fn record(&mut self, kind: MessageKind, message: &str) -> Option<RequestTraceEntry> {
+        let mut message_json = serde_json::from_str::<Value>(message).ok()?;
+        let message_json = message_json.as_object_mut()?;
+        let id = message_json.get("id")?.to_string();
+
+        let entry = if let Some(method) = message_json.get("method").and_then(Value::as_str) {
+            let method = method.to_string();
+            self.pending_requests
+                .insert((kind, id.clone()), (method.clone(), Instant::now()));
+            RequestTraceEntry {
+                kind,
+                method,
+                id,
+                response: None,
+                size: message.len(),
+                payload: message_json.remove("params").unwrap_or_default(),
+            }
+        } else {
+            let request_kind = match kind {
+                MessageKind::Send => MessageKind::Receive,
+                MessageKind::Receive => MessageKind::Send,
+            };
+            let request = self.pending_requests.remove(&(request_kind, id.clone()));
+            let (payload, is_error) = match message_json.remove("error") {
+                Some(error) => (error, true),
+                None => (message_json.remove("result").unwrap_or_default(), false),
+            };
+            RequestTraceEntry {
+                kind,
+                method: request
+                    .as_ref()
+                    .map_or_else(|| "unknown".to_string(), |(method, _)| method.clone()),
+                id,
+                response: Some(TracedResponse {
+                    duration: request.map(|(_, sent_at)| sent_at.elapsed()),
+                    is_error,
+                }),
+                size: message.len(),
+                payload,
+            }
+        };
+
+        while self.entries.len() >= MAX_STORED_REQUEST_TRACE_ENTRIES {
+            self.entries.pop_front();
+        }
+        if self.pending_requests.len() > MAX_STORED_REQUEST_TRACE_ENTRIES {
+            self.pending_requests.clear();
+        }
+        self.entries.push_back(entry.clone());
+        Some(entry)
+    }
+
+    pub(crate) fn entries(&self) -> impl Iterator<Item = &RequestTraceEntry> {
+        self.entries.iter()
+    }
+
+    /// Returns the methods of the traced requests, sorted and without duplicates.
+    pub(crate) fn methods(&self) -> Vec<String> {
+        let mut methods = self
+            .entries
+            .iter()
+            .map(|entry| entry.method.clone())
+            .collect::<Vec<_>>();
+        methods.sort();
+        methods.dedup();
+        methods
+    }
+}
+
+impl RequestTraceEntry {
+    /// Whether this is a request sent to the language server, which can be replayed.
+    pub(crate) fn is_replayable(&self) -> bool {
+        self.kind == MessageKind::Send && self.response.is_none()
+    }
+
+    /// Formats the entry as a comment describing it, followed by its pretty-printed payload.
+    pub(crate) fn text(&self) -> String {
+        let direction = match self.kind {
+            MessageKind::Send => "Send",
+            MessageKind::Receive => "Receive",
+        };
+        let header = match &self.response {
+            None => format!(
+                "// {direction} request {} (id {}), {}",
+                self.method,
+                self.id,
+                format_size(self.size)
+            ),
+            Some(response) => {
+                let duration = response
+                    .duration
+                    .map(|duration| format!(" after {} ms", duration.as_millis()))
+                    .unwrap_or_default();
+                format!(
+                    "// {direction} {} to {} (id {}){duration}, {}",
+                    if response.is_error {
+                        "error response"
+                    } else {
+                        "response"
+                    },
+                    self.method,
+                    self.id,
+                    format_size(self.size)
+                )
+            }
+        };
+        let payload = serde_json::to_string_pretty(&self.payload).unwrap_or_default();
+        format!("{header}\n{payload}\n")
+    }
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024. * 1024.))
+    }
+}
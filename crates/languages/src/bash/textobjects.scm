@@ -0,0 +1,7 @@
+(function_definition
+    (compound_statement
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(comment)+ @comment.inside @comment.around
@@ -0,0 +1,23 @@
+(function_definition
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(struct_specifier
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(enum_specifier
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(comment)+ @comment.inside @comment.around
+
+(parameter_list (_) @argument.inside)
+
+(argument_list (_) @argument.inside)
@@ -0,0 +1,38 @@
+(function_declaration
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(method_declaration
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(func_literal
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(type_declaration
+    (type_spec
+        type: (struct_type
+            (field_declaration_list
+                "{"
+                (_)* @class.inside
+                "}")))) @class.around
+
+(type_declaration
+    (type_spec
+        type: (interface_type
+            "{"
+            (_)* @class.inside
+            "}"))) @class.around
+
+(comment)+ @comment.inside @comment.around
+
+(parameter_list (_) @argument.inside)
+
+(argument_list (_) @argument.inside)
@@ -0,0 +1,6 @@
+; Keep the names of a JSX element's opening and closing tags in sync
+(jsx_element
+    open_tag: (jsx_opening_element
+        name: (_) @linked)
+    close_tag: (jsx_closing_element
+        name: (_) @linked))
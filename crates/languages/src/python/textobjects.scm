@@ -0,0 +1,11 @@
+(function_definition
+    body: (block) @function.inside) @function.around
+
+(class_definition
+    body: (block) @class.inside) @class.around
+
+(comment)+ @comment.inside @comment.around
+
+(parameters (_) @argument.inside)
+
+(argument_list (_) @argument.inside)
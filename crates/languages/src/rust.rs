@@ -50,6 +50,7 @@ impl LspAdapter for RustLspAdapter {
                 path,
                 arguments,
                 path_lookup,
+                ..
             })) => {
                 let (path, env) = match (path, path_lookup) {
                     (Some(path), lookup) => {
@@ -0,0 +1,53 @@
+(function_signature_item) @function.around
+
+(function_item
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(closure_expression
+    body: (_
+        "{"
+        (_)* @function.inside
+        "}")) @function.around
+
+(struct_item
+    body: (_
+        ["{" "("]
+        (_)* @class.inside
+        ["}" ")"])) @class.around
+
+(enum_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(trait_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(impl_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(mod_item
+    body: (_
+        "{"
+        (_)* @class.inside
+        "}")) @class.around
+
+(line_comment)+ @comment.inside @comment.around
+
+(block_comment) @comment.inside @comment.around
+
+(parameters (_) @argument.inside)
+
+(closure_parameters (_) @argument.inside)
+
+(arguments (_) @argument.inside)
@@ -0,0 +1,275 @@
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Task,
+    View, ViewContext, VisualContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+actions!(layout_selector, [SaveLayout, RestoreLayout]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace
+            .register_action(|workspace, _: &SaveLayout, cx| {
+                LayoutSelector::toggle(workspace, Mode::Save, cx);
+            })
+            .register_action(|workspace, _: &RestoreLayout, cx| {
+                LayoutSelector::toggle(workspace, Mode::Restore, cx);
+            });
+    })
+    .detach();
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Save,
+    Restore,
+}
+
+/// A modal listing the layouts saved for the workspace, to restore one of them, or to save the
+/// current layout under a new or an existing name.
+pub struct LayoutSelector {
+    picker: View<Picker<LayoutSelectorDelegate>>,
+}
+
+impl LayoutSelector {
+    fn toggle(workspace: &mut Workspace, mode: Mode, cx: &mut ViewContext<Workspace>) {
+        let names = workspace.layout_names();
+        let workspace_handle = cx.view().downgrade();
+        workspace.toggle_modal(cx, move |cx| {
+            let delegate = LayoutSelectorDelegate {
+                selector: cx.view().downgrade(),
+                workspace: workspace_handle,
+                mode,
+                names,
+                query: String::new(),
+                matches: Vec::new(),
+                selected_index: 0,
+            };
+            Self {
+                picker: cx.new_view(|cx| Picker::uniform_list(delegate, cx)),
+            }
+        });
+    }
+}
+
+impl Render for LayoutSelector {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for LayoutSelector {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for LayoutSelector {}
+impl ModalView for LayoutSelector {}
+
+struct LayoutSelectorDelegate {
+    selector: WeakView<LayoutSelector>,
+    workspace: WeakView<Workspace>,
+    mode: Mode,
+    names: Vec<String>,
+    query: String,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl LayoutSelectorDelegate {
+    /// The name typed in the query when saving, if no layout was saved with it yet. It's listed
+    /// before the existing layouts.
+    fn new_name(&self) -> Option<&str> {
+        let name = self.query.trim();
+        (self.mode == Mode::Save && !name.is_empty() && !self.names.iter().any(|n| n == name))
+            .then_some(name)
+    }
+
+    /// The existing layout shown at the given index, if it isn't the new name.
+    fn layout_match(&self, ix: usize) -> Option<&StringMatch> {
+        let offset = self.new_name().is_some() as usize;
+        self.matches.get(ix.checked_sub(offset)?)
+    }
+
+    fn delete(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(name) = self.layout_match(ix).map(|mat| mat.string.clone()) else {
+            return;
+        };
+        let Some(task) = self
+            .workspace
+            .update(cx, |workspace, cx| {
+                workspace.delete_layout(name.clone(), cx)
+            })
+            .log_err()
+        else {
+            return;
+        };
+        cx.spawn(|picker, mut cx| async move {
+            task.await?;
+            picker.update(&mut cx, |picker, cx| {
+                picker.delegate.names.retain(|n| *n != name);
+                picker.refresh(cx);
+            })
+        })
+        .detach_and_prompt_err("Failed to delete the layout", cx, |_, _| None);
+    }
+}
+
+impl PickerDelegate for LayoutSelectorDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        match self.mode {
+            Mode::Save => "Name the layout, or pick one to replace...".into(),
+            Mode::Restore => "Restore a layout...".into(),
+        }
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        if self.names.is_empty() {
+            match self.mode {
+                Mode::Save => "Type a name to save the layout with".into(),
+                Mode::Restore => "No saved layouts".into(),
+            }
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len() + self.new_name().is_some() as usize
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let candidates = self
+            .names
+            .iter()
+            .enumerate()
+            .map(|(ix, name)| StringMatchCandidate::new(ix, name.clone()))
+            .collect::<Vec<_>>();
+        cx.spawn(|this, mut cx| async move {
+            let matches = if query.trim().is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    query.trim(),
+                    false,
+                    candidates.len(),
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.query = query;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.match_count().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if secondary && self.mode == Mode::Restore {
+            self.delete(self.selected_index, cx);
+            return;
+        }
+
+        let name = match self.layout_match(self.selected_index) {
+            Some(mat) => mat.string.clone(),
+            None => match self.new_name() {
+                Some(name) => name.to_string(),
+                None => return,
+            },
+        };
+        let mode = self.mode;
+        let task = self.workspace.update(cx, |workspace, cx| match mode {
+            Mode::Save => workspace.save_layout(name, cx),
+            Mode::Restore => workspace.restore_layout(name, cx),
+        });
+        if let Some(task) = task.log_err() {
+            let message = match mode {
+                Mode::Save => "Failed to save the layout",
+                Mode::Restore => "Failed to restore the layout",
+            };
+            task.detach_and_prompt_err(message, cx, |_, _| None);
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.selector
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let item = ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(selected);
+
+        let Some(mat) = self.layout_match(ix) else {
+            let name = self.new_name()?;
+            return Some(item.child(Label::new(format!("Save New Layout \"{name}\""))));
+        };
+        let item = item.child(HighlightedLabel::new(
+            mat.string.clone(),
+            mat.positions.clone(),
+        ));
+
+        Some(match self.mode {
+            Mode::Save => item.end_slot(
+                Label::new("Replace")
+                    .size(LabelSize::Small)
+                    .color(Color::Muted),
+            ),
+            Mode::Restore => item.end_hover_slot(
+                IconButton::new(("delete-layout", ix), IconName::Trash)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| {
+                        Tooltip::for_action("Delete This Layout", &menu::SecondaryConfirm, cx)
+                    })
+                    .on_click(cx.listener(move |picker, _, cx| {
+                        cx.stop_propagation();
+                        cx.prevent_default();
+                        picker.delegate.delete(ix, cx);
+                    })),
+            ),
+        })
+    }
+}
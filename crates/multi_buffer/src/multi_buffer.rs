@@ -1664,6 +1664,53 @@ impl MultiBuffer {
         if line_count == 0 {
             return;
         }
+
+        self.update_excerpt_contexts(ids, cx, |_, buffer, context| {
+            let up_line_count = if direction.should_expand_up() {
+                line_count
+            } else {
+                0
+            };
+            let start_row = context.start.row.saturating_sub(up_line_count);
+
+            let down_line_count = if direction.should_expand_down() {
+                line_count
+            } else {
+                0
+            };
+            let mut end_point =
+                buffer.clip_point(context.end + Point::new(down_line_count, 0), Bias::Left);
+            end_point.column = buffer.line_len(end_point.row);
+
+            Point::new(start_row, 0)..end_point
+        });
+    }
+
+    /// Expands the context of the given excerpts so that they contain the given ranges of their
+    /// buffers, along with the rest of the lines these ranges start and end on.
+    pub fn expand_excerpts_to_ranges(
+        &mut self,
+        ranges: impl IntoIterator<Item = (ExcerptId, Range<text::Anchor>)>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let ranges = ranges.into_iter().collect::<HashMap<_, _>>();
+        self.update_excerpt_contexts(ranges.keys().copied(), cx, |id, buffer, context| {
+            let Some(range) = ranges.get(&id) else {
+                return context;
+            };
+            let range = range.to_point(buffer);
+            let start = context.start.min(range.start);
+            let end = context.end.max(range.end);
+            Point::new(start.row, 0)..Point::new(end.row, buffer.line_len(end.row))
+        });
+    }
+
+    fn update_excerpt_contexts(
+        &mut self,
+        ids: impl IntoIterator<Item = ExcerptId>,
+        cx: &mut ModelContext<Self>,
+        mut new_context: impl FnMut(ExcerptId, &BufferSnapshot, Range<Point>) -> Range<Point>,
+    ) {
         self.sync(cx);
 
         let snapshot = self.snapshot(cx);
@@ -1679,36 +1726,11 @@ impl MultiBuffer {
             let mut excerpt = cursor.item().unwrap().clone();
             let old_text_len = excerpt.text_summary.len;
 
-            let up_line_count = if direction.should_expand_up() {
-                line_count
-            } else {
-                0
-            };
-
-            let start_row = excerpt
-                .range
-                .context
-                .start
-                .to_point(&excerpt.buffer)
-                .row
-                .saturating_sub(up_line_count);
-            let start_point = Point::new(start_row, 0);
-            excerpt.range.context.start = excerpt.buffer.anchor_before(start_point);
-
-            let down_line_count = if direction.should_expand_down() {
-                line_count
-            } else {
-                0
-            };
-
-            let mut end_point = excerpt.buffer.clip_point(
-                excerpt.range.context.end.to_point(&excerpt.buffer)
-                    + Point::new(down_line_count, 0),
-                Bias::Left,
-            );
-            end_point.column = excerpt.buffer.line_len(end_point.row);
-            excerpt.range.context.end = excerpt.buffer.anchor_after(end_point);
-            excerpt.max_buffer_row = end_point.row;
+            let context = excerpt.range.context.to_point(&excerpt.buffer);
+            let new_context = new_context(excerpt.id, &excerpt.buffer, context);
+            excerpt.range.context.start = excerpt.buffer.anchor_before(new_context.start);
+            excerpt.range.context.end = excerpt.buffer.anchor_after(new_context.end);
+            excerpt.max_buffer_row = new_context.end.row;
 
             excerpt.text_summary = excerpt
                 .buffer
@@ -3250,6 +3272,46 @@ impl MultiBufferSnapshot {
             .flatten()
     }
 
+    /// Returns the brackets overlapping `range` along with their nesting depth, see
+    /// [`BufferSnapshot::bracket_depths`]. The depth is counted separately within each excerpt.
+    pub fn bracket_depths<'a, T: ToOffset>(
+        &'a self,
+        range: Range<T>,
+        enabled: impl Fn(&BufferSnapshot) -> bool + 'a,
+    ) -> impl Iterator<Item = (Range<usize>, usize)> + 'a {
+        let range = range.start.to_offset(self)..range.end.to_offset(self);
+        self.excerpts_for_range(range.clone())
+            .filter_map(move |(excerpt, excerpt_offset)| {
+                enabled(&excerpt.buffer).then(move || {
+                    let excerpt_buffer_start =
+                        excerpt.range.context.start.to_offset(&excerpt.buffer);
+                    let excerpt_buffer_end = excerpt.range.context.end.to_offset(&excerpt.buffer);
+                    let buffer_start = (excerpt_buffer_start
+                        + range.start.saturating_sub(excerpt_offset))
+                    .min(excerpt_buffer_end);
+                    let buffer_end = (excerpt_buffer_start
+                        + range.end.saturating_sub(excerpt_offset))
+                    .min(excerpt_buffer_end);
+
+                    excerpt
+                        .buffer
+                        .bracket_depths(buffer_start..buffer_end)
+                        .into_iter()
+                        .filter(move |(bracket, _)| {
+                            excerpt_buffer_start <= bracket.start
+                                && bracket.end <= excerpt_buffer_end
+                        })
+                        .map(move |(bracket, depth)| {
+                            // Re-base onto the excerpts coordinates in the multibuffer
+                            let start = excerpt_offset + (bracket.start - excerpt_buffer_start);
+                            let end = excerpt_offset + (bracket.end - excerpt_buffer_start);
+                            (start..end, depth)
+                        })
+                })
+            })
+            .flatten()
+    }
+
     pub fn runnable_ranges(
         &self,
         range: Range<Anchor>,
@@ -5211,6 +5273,50 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_expand_excerpts_to_ranges(cx: &mut AppContext) {
+        let buffer = cx.new_model(|cx| Buffer::local(sample_text(20, 3, 'a'), cx));
+        let multibuffer = cx.new_model(|_| MultiBuffer::new(0, Capability::ReadWrite));
+
+        let excerpt_ids = multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.push_excerpts(
+                buffer.clone(),
+                [
+                    ExcerptRange {
+                        context: Point::new(3, 0)..Point::new(4, 3),
+                        primary: None,
+                    },
+                    ExcerptRange {
+                        context: Point::new(10, 0)..Point::new(10, 3),
+                        primary: None,
+                    },
+                ],
+                cx,
+            )
+        });
+        assert_eq!(multibuffer.read(cx).snapshot(cx).text(), "ddd\neee\nkkk");
+
+        let (first_range, second_range) = buffer.update(cx, |buffer, _| {
+            (
+                buffer.anchor_before(Point::new(1, 1))..buffer.anchor_after(Point::new(3, 2)),
+                buffer.anchor_before(Point::new(10, 1))..buffer.anchor_after(Point::new(12, 1)),
+            )
+        });
+        multibuffer.update(cx, |multibuffer, cx| {
+            multibuffer.expand_excerpts_to_ranges(
+                [
+                    (excerpt_ids[0], first_range),
+                    (excerpt_ids[1], second_range),
+                ],
+                cx,
+            )
+        });
+        assert_eq!(
+            multibuffer.read(cx).snapshot(cx).text(),
+            "bbb\nccc\nddd\neee\nkkk\nlll\nmmm"
+        );
+    }
+
     #[gpui::test]
     fn test_push_excerpts_with_context_lines(cx: &mut AppContext) {
         let buffer = cx.new_model(|cx| Buffer::local(sample_text(20, 3, 'a'), cx));
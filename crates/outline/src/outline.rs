@@ -5,7 +5,7 @@ use gpui::{
     FontStyle, FontWeight, HighlightStyle, ParentElement, Point, Render, Styled, StyledText, Task,
     TextStyle, View, ViewContext, VisualContext, WeakView, WhiteSpace, WindowContext,
 };
-use language::Outline;
+use language::{Outline, OutlineItem};
 use ordered_float::OrderedFloat;
 use picker::{Picker, PickerDelegate};
 use settings::Settings;
@@ -40,6 +40,48 @@ pub fn toggle(editor: View<Editor>, _: &Toggle, cx: &mut WindowContext) {
     }
 }
 
+/// Shows the outline items sharing a parent with the symbol that contains the cursor at the given
+/// depth, to jump between that symbol and its siblings.
+pub fn toggle_siblings(editor: View<Editor>, depth: usize, cx: &mut WindowContext) {
+    let buffer = editor.read(cx).buffer().read(cx).snapshot(cx);
+    let Some(outline) = buffer.outline(Some(&cx.theme().syntax())) else {
+        return;
+    };
+    let cursor_offset = editor.read(cx).selections.newest::<usize>(cx).head();
+
+    let parent_range = match depth.checked_sub(1) {
+        Some(parent_depth) => {
+            let Some(parent) = outline.items.iter().find(|item| {
+                item.depth == parent_depth && item.range.to_offset(&buffer).contains(&cursor_offset)
+            }) else {
+                return;
+            };
+            Some(parent.range.to_offset(&buffer))
+        }
+        None => None,
+    };
+    let siblings = outline
+        .items
+        .into_iter()
+        .filter(|item| {
+            let range = item.range.to_offset(&buffer);
+            item.depth == depth
+                && parent_range.as_ref().map_or(true, |parent_range| {
+                    parent_range.start <= range.start && range.end <= parent_range.end
+                })
+        })
+        .map(|item| OutlineItem { depth: 0, ..item })
+        .collect();
+
+    if let Some(workspace) = editor.read(cx).workspace() {
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, |cx| {
+                OutlineView::new(Outline::new(siblings), editor, cx)
+            });
+        })
+    }
+}
+
 pub struct OutlineView {
     picker: View<Picker<OutlineViewDelegate>>,
 }
@@ -444,6 +486,74 @@ mod tests {
         assert_single_caret_at_row(&editor, expected_first_highlighted_row, cx);
     }
 
+    #[gpui::test]
+    async fn test_toggle_siblings(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a.rs": indoc!{"
+                    struct First {
+                        field_1: i32,
+                        field_2: i32,
+                    }
+                    struct Second {
+                        field_3: i32,
+                    }
+                "}
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+        project.read_with(cx, |project, _| project.languages().add(rust_lang()));
+
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let worktree_id = workspace.update(cx, |workspace, cx| {
+            workspace.project().update(cx, |project, cx| {
+                project.worktrees().next().unwrap().read(cx).id()
+            })
+        });
+        let editor = workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path((worktree_id, "a.rs"), None, true, cx)
+            })
+            .await
+            .unwrap()
+            .downcast::<Editor>()
+            .unwrap();
+        cx.run_until_parked();
+        editor.update(cx, |editor, cx| {
+            editor.change_selections(None, cx, |selections| {
+                selections.select_ranges([language::Point::new(2, 4)..language::Point::new(2, 4)])
+            })
+        });
+
+        let open_siblings = |depth: usize, cx: &mut VisualTestContext| {
+            cx.update(|cx| toggle_siblings(editor.clone(), depth, cx));
+            workspace.update(cx, |workspace, cx| {
+                workspace
+                    .active_modal::<OutlineView>(cx)
+                    .unwrap()
+                    .read(cx)
+                    .picker
+                    .clone()
+            })
+        };
+
+        let outline_view = open_siblings(1, cx);
+        assert_eq!(outline_names(&outline_view, cx), ["field_1", "field_2"]);
+        cx.dispatch_action(menu::Cancel);
+
+        let outline_view = open_siblings(0, cx);
+        assert_eq!(
+            outline_names(&outline_view, cx),
+            ["struct First", "struct Second"]
+        );
+        cx.dispatch_action(menu::Cancel);
+    }
+
     fn open_outline_view(
         workspace: &View<Workspace>,
         cx: &mut VisualTestContext,
@@ -10,7 +10,7 @@ use head::Head;
 use serde::Deserialize;
 use std::{sync::Arc, time::Duration};
 use ui::{prelude::*, v_flex, Color, Divider, Label, ListItem, ListItemSpacing};
-use workspace::ModalView;
+use workspace::{searchable::Direction, ModalView};
 
 mod head;
 pub mod highlighted_match_with_paths;
@@ -102,6 +102,16 @@ pub trait PickerDelegate: Sized + 'static {
     fn selected_as_query(&self) -> Option<String> {
         None
     }
+    /// Returns the query to replace the current one with when moving up or down
+    /// from it, or `None` to move the selection instead.
+    fn select_history(
+        &mut self,
+        _direction: Direction,
+        _query: &str,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<String> {
+        None
+    }
 
     fn render_editor(&self, editor: &View<Editor>, _cx: &mut ViewContext<Picker<Self>>) -> Div {
         v_flex()
@@ -270,6 +280,9 @@ impl<D: PickerDelegate> Picker<D> {
     }
 
     pub fn select_next(&mut self, _: &menu::SelectNext, cx: &mut ViewContext<Self>) {
+        if self.select_history(Direction::Next, cx) {
+            return;
+        }
         let count = self.delegate.match_count();
         if count > 0 {
             let index = self.delegate.selected_index();
@@ -280,6 +293,9 @@ impl<D: PickerDelegate> Picker<D> {
     }
 
     fn select_prev(&mut self, _: &menu::SelectPrev, cx: &mut ViewContext<Self>) {
+        if self.select_history(Direction::Prev, cx) {
+            return;
+        }
         let count = self.delegate.match_count();
         if count > 0 {
             let index = self.delegate.selected_index();
@@ -289,6 +305,16 @@ impl<D: PickerDelegate> Picker<D> {
         }
     }
 
+    fn select_history(&mut self, direction: Direction, cx: &mut ViewContext<Self>) -> bool {
+        let query = self.query(cx);
+        if let Some(query) = self.delegate.select_history(direction, &query, cx) {
+            self.set_query(query, cx);
+            true
+        } else {
+            false
+        }
+    }
+
     fn select_first(&mut self, _: &menu::SelectFirst, cx: &mut ViewContext<Self>) {
         let count = self.delegate.match_count();
         if count > 0 {
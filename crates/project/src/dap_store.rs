@@ -0,0 +1,1365 @@
+use anyhow::{anyhow, Context as _, Result};
+use collections::HashMap;
+use dap::{
+    requests::{
+        Attach, Completions, ConfigurationDone, Continue, Evaluate, Launch, Next, Pause, Request,
+        Scopes, SetBreakpoints, SetExceptionBreakpoints, SetVariable, StackTrace, StepIn, StepOut,
+        Threads, Variables,
+    },
+    types::{
+        CompletionItem, CompletionsArguments, EvaluateArguments, EvaluateResponse, Events,
+        ExceptionBreakpointsFilter, Scope, ScopesArguments, SetBreakpointsArguments,
+        SetExceptionBreakpointsArguments, SetVariableArguments, Source, SourceBreakpoint,
+        StackFrame, StackTraceArguments, Thread, ThreadArguments, Variable, VariablesArguments,
+    },
+    DebugAdapterBinary, DebugAdapterClient, DebugAdapterClientId,
+};
+use gpui::{AppContext, EventEmitter, Model, ModelContext, SharedString, Task, WeakModel};
+use language::{Buffer, Point, ToPoint};
+use serde_json::json;
+use smol::channel;
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use task::{DebugAdapterConfig, DebugRequestType};
+use util::ResultExt;
+
+/// A breakpoint on a line of a file of the project.
+#[derive(Clone)]
+pub struct Breakpoint {
+    /// The row of the breakpoint when its buffer was last seen.
+    row: u32,
+    /// Where the breakpoint is anchored, while its buffer is open.
+    anchor: Option<(WeakModel<Buffer>, language::Anchor)>,
+    /// An expression that must be true for the breakpoint to stop.
+    pub condition: Option<String>,
+    /// An expression, like `>= 3`, controlling how many hits of the breakpoint are ignored.
+    pub hit_condition: Option<String>,
+    /// A message that the breakpoint logs instead of stopping.
+    pub log_message: Option<String>,
+}
+
+/// How a breakpoint is shown in the gutter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakpointKind {
+    Standard,
+    Conditional,
+    Log,
+}
+
+/// A property of a breakpoint that can be edited after the breakpoint is set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakpointProperty {
+    Condition,
+    HitCondition,
+    LogMessage,
+}
+
+impl Breakpoint {
+    fn new(row: u32, buffer: &Model<Buffer>, anchor: language::Anchor) -> Self {
+        Self {
+            row,
+            anchor: Some((buffer.downgrade(), anchor)),
+            condition: None,
+            hit_condition: None,
+            log_message: None,
+        }
+    }
+
+    pub fn kind(&self) -> BreakpointKind {
+        if self.log_message.is_some() {
+            BreakpointKind::Log
+        } else if self.condition.is_some() || self.hit_condition.is_some() {
+            BreakpointKind::Conditional
+        } else {
+            BreakpointKind::Standard
+        }
+    }
+
+    pub fn property(&self, property: BreakpointProperty) -> Option<&str> {
+        match property {
+            BreakpointProperty::Condition => self.condition.as_deref(),
+            BreakpointProperty::HitCondition => self.hit_condition.as_deref(),
+            BreakpointProperty::LogMessage => self.log_message.as_deref(),
+        }
+    }
+
+    fn property_mut(&mut self, property: BreakpointProperty) -> &mut Option<String> {
+        match property {
+            BreakpointProperty::Condition => &mut self.condition,
+            BreakpointProperty::HitCondition => &mut self.hit_condition,
+            BreakpointProperty::LogMessage => &mut self.log_message,
+        }
+    }
+
+    pub fn row(&self, cx: &AppContext) -> u32 {
+        self.anchor
+            .as_ref()
+            .and_then(|(buffer, anchor)| {
+                let buffer = buffer.upgrade()?;
+                Some(anchor.to_point(&buffer.read(cx).snapshot()).row)
+            })
+            .unwrap_or(self.row)
+    }
+
+    fn buffer(&self) -> Option<Model<Buffer>> {
+        self.anchor.as_ref()?.0.upgrade()
+    }
+}
+
+/// The most entries kept in the console of a debug session, after which the oldest are dropped.
+const MAX_CONSOLE_ENTRIES: usize = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConsoleEntryKind {
+    /// Output of the debuggee or of the debug adapter.
+    Output,
+    /// Output that the debuggee wrote to its standard error.
+    Stderr,
+    /// An expression evaluated in the console.
+    Input,
+    /// The value of an expression evaluated in the console.
+    Result,
+    /// Why an expression couldn't be evaluated.
+    Error,
+}
+
+/// An entry of the console of a debug session, which may span several lines.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConsoleEntry {
+    pub kind: ConsoleEntryKind,
+    pub text: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebugSessionStatus {
+    Starting,
+    Running,
+    Stopped,
+}
+
+/// A debug adapter started for a debug task, and the state of the program it debugs.
+pub struct DebugSession {
+    pub label: SharedString,
+    client: Arc<DebugAdapterClient>,
+    status: DebugSessionStatus,
+    /// Whether the debug adapter was sent the breakpoints and the `configurationDone` request.
+    is_configured: bool,
+    threads: Vec<Thread>,
+    /// The thread that the debug adapter last reported stopping.
+    stopped_thread_id: Option<u64>,
+    stop_reason: Option<String>,
+    /// The stack frames of the stopped thread, innermost first.
+    stack_frames: Vec<StackFrame>,
+    active_stack_frame: Option<usize>,
+    /// The scopes of the active stack frame.
+    scopes: Vec<Scope>,
+    /// The variables fetched while the session is stopped, by the reference of their scope or
+    /// parent variable.
+    variables: HashMap<u64, Vec<Variable>>,
+    /// The values of the watch expressions in the active stack frame, or why they couldn't be
+    /// evaluated.
+    watch_values: HashMap<String, Result<EvaluateResponse, String>>,
+    console: Vec<ConsoleEntry>,
+    _event_loop: Task<()>,
+}
+
+impl DebugSession {
+    pub fn client(&self) -> &Arc<DebugAdapterClient> {
+        &self.client
+    }
+
+    pub fn status(&self) -> DebugSessionStatus {
+        self.status
+    }
+
+    pub fn threads(&self) -> &[Thread] {
+        &self.threads
+    }
+
+    pub fn stopped_thread_id(&self) -> Option<u64> {
+        self.stopped_thread_id
+    }
+
+    pub fn stop_reason(&self) -> Option<&str> {
+        self.stop_reason.as_deref()
+    }
+
+    pub fn stack_frames(&self) -> &[StackFrame] {
+        &self.stack_frames
+    }
+
+    pub fn active_stack_frame(&self) -> Option<&StackFrame> {
+        self.stack_frames.get(self.active_stack_frame?)
+    }
+
+    pub fn scopes(&self) -> &[Scope] {
+        &self.scopes
+    }
+
+    /// Returns the variables of a scope or of a parent variable, if they were fetched.
+    pub fn variables(&self, variables_reference: u64) -> Option<&[Variable]> {
+        self.variables.get(&variables_reference).map(Vec::as_slice)
+    }
+
+    pub fn watch_value(&self, expression: &str) -> Option<&Result<EvaluateResponse, String>> {
+        self.watch_values.get(expression)
+    }
+
+    pub fn console(&self) -> &[ConsoleEntry] {
+        &self.console
+    }
+
+    /// The kinds of exceptions that the debug adapter can stop on.
+    pub fn exception_breakpoint_filters(&self) -> Vec<ExceptionBreakpointsFilter> {
+        self.client
+            .capabilities()
+            .exception_breakpoint_filters
+            .unwrap_or_default()
+    }
+
+    fn push_console_entry(&mut self, kind: ConsoleEntryKind, text: String) {
+        // Output events don't necessarily end with a newline, in which case the next output
+        // continues the same line.
+        if let Some(last) = self.console.last_mut() {
+            if last.kind == kind
+                && matches!(kind, ConsoleEntryKind::Output | ConsoleEntryKind::Stderr)
+                && !last.text.ends_with('\n')
+            {
+                last.text.push_str(&text);
+                return;
+            }
+        }
+        if self.console.len() == MAX_CONSOLE_ENTRIES {
+            self.console.remove(0);
+        }
+        self.console.push(ConsoleEntry { kind, text });
+    }
+
+    /// The thread that the stepping and continue requests act on.
+    fn current_thread_id(&self) -> Option<u64> {
+        self.stopped_thread_id
+            .or_else(|| self.threads.first().map(|thread| thread.id))
+    }
+}
+
+pub enum DapStoreEvent {
+    SessionStarted(DebugAdapterClientId),
+    SessionEnded(DebugAdapterClientId),
+    /// A thread of the session stopped, and the stack frames of the session were updated.
+    Stopped(DebugAdapterClientId),
+    Continued(DebugAdapterClientId),
+    ActiveStackFrameChanged(DebugAdapterClientId),
+    SessionFailed {
+        label: SharedString,
+        error: SharedString,
+    },
+}
+
+/// The breakpoints of a project, and the debug sessions started for it.
+pub struct DapStore {
+    breakpoints: BTreeMap<Arc<Path>, Vec<Breakpoint>>,
+    sessions: BTreeMap<DebugAdapterClientId, DebugSession>,
+    active_session: Option<DebugAdapterClientId>,
+    /// The expressions evaluated in the active stack frame of the sessions every time they stop.
+    watch_expressions: Vec<String>,
+    /// Whether the debug adapters should stop on the kinds of exceptions that were toggled, by
+    /// filter id. The other kinds are enabled according to the adapters' defaults.
+    exception_breakpoint_filters: HashMap<String, bool>,
+    next_client_id: usize,
+}
+
+impl EventEmitter<DapStoreEvent> for DapStore {}
+
+impl DapStore {
+    pub fn new() -> Self {
+        Self {
+            breakpoints: BTreeMap::default(),
+            sessions: BTreeMap::default(),
+            active_session: None,
+            watch_expressions: Vec::new(),
+            exception_breakpoint_filters: HashMap::default(),
+            next_client_id: 0,
+        }
+    }
+
+    /// Returns the rows of the breakpoints of the given file, in ascending order.
+    pub fn breakpoint_rows(&self, abs_path: &Path, cx: &AppContext) -> Vec<u32> {
+        let mut rows = self
+            .breakpoints
+            .get(abs_path)
+            .into_iter()
+            .flatten()
+            .map(|breakpoint| breakpoint.row(cx))
+            .collect::<Vec<_>>();
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+
+    /// Returns the files with breakpoints, along with the rows of their breakpoints.
+    pub fn all_breakpoints(&self, cx: &AppContext) -> Vec<(Arc<Path>, Vec<u32>)> {
+        self.breakpoints
+            .keys()
+            .map(|abs_path| (abs_path.clone(), self.breakpoint_rows(abs_path, cx)))
+            .collect()
+    }
+
+    /// Returns the breakpoint on the given row of a file, if any.
+    pub fn breakpoint_at(&self, abs_path: &Path, row: u32, cx: &AppContext) -> Option<&Breakpoint> {
+        self.breakpoints
+            .get(abs_path)?
+            .iter()
+            .find(|breakpoint| breakpoint.row(cx) == row)
+    }
+
+    /// Returns where the breakpoints of the buffer's file are anchored in the buffer, along with
+    /// their kinds.
+    pub fn breakpoints_for_buffer(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Vec<(language::Anchor, BreakpointKind)> {
+        let Some(abs_path) = buffer_abs_path(buffer, cx) else {
+            return Vec::new();
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        self.breakpoints
+            .get(abs_path.as_ref())
+            .into_iter()
+            .flatten()
+            .map(|breakpoint| {
+                let anchor = match &breakpoint.anchor {
+                    Some((anchor_buffer, anchor)) if anchor_buffer == &buffer.downgrade() => {
+                        *anchor
+                    }
+                    _ => {
+                        let row = breakpoint.row.min(snapshot.max_point().row);
+                        snapshot.anchor_before(Point::new(row, 0))
+                    }
+                };
+                (anchor, breakpoint.kind())
+            })
+            .collect()
+    }
+
+    /// Adds a breakpoint on each of the given rows of the buffer, or removes the breakpoints
+    /// already on them, then sends the new breakpoints of the file to the running sessions.
+    pub fn toggle_breakpoints(
+        &mut self,
+        buffer: &Model<Buffer>,
+        rows: impl IntoIterator<Item = u32>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(abs_path) = buffer_abs_path(buffer, cx) else {
+            return;
+        };
+        self.resolve_breakpoints(buffer, &abs_path, cx);
+
+        let snapshot = buffer.read(cx).snapshot();
+        let breakpoints = self.breakpoints.entry(abs_path.clone()).or_default();
+        for row in rows {
+            let existing = breakpoints
+                .iter()
+                .position(|breakpoint| breakpoint.row(cx) == row);
+            if let Some(ix) = existing {
+                breakpoints.remove(ix);
+            } else {
+                let anchor = snapshot.anchor_before(Point::new(row, 0));
+                breakpoints.push(Breakpoint::new(row, buffer, anchor));
+            }
+        }
+        if breakpoints.is_empty() {
+            self.breakpoints.remove(&abs_path);
+        }
+
+        self.breakpoints_changed(&abs_path, cx);
+    }
+
+    /// Sets a property of the breakpoint on the given row of the buffer, adding a breakpoint there
+    /// if there is none, then sends the new breakpoints of the file to the running sessions.
+    /// An empty value clears the property.
+    pub fn set_breakpoint_property(
+        &mut self,
+        buffer: &Model<Buffer>,
+        row: u32,
+        property: BreakpointProperty,
+        value: Option<String>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(abs_path) = buffer_abs_path(buffer, cx) else {
+            return;
+        };
+        self.resolve_breakpoints(buffer, &abs_path, cx);
+
+        let value = value
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty());
+        let breakpoints = self.breakpoints.entry(abs_path.clone()).or_default();
+        let ix = match breakpoints
+            .iter()
+            .position(|breakpoint| breakpoint.row(cx) == row)
+        {
+            Some(ix) => ix,
+            None => {
+                let anchor = buffer.read(cx).anchor_before(Point::new(row, 0));
+                breakpoints.push(Breakpoint::new(row, buffer, anchor));
+                breakpoints.len() - 1
+            }
+        };
+        *breakpoints[ix].property_mut(property) = value;
+
+        self.breakpoints_changed(&abs_path, cx);
+    }
+
+    /// Returns the exception filters that were toggled, by filter id.
+    pub fn toggled_exception_breakpoint_filters(&self) -> &HashMap<String, bool> {
+        &self.exception_breakpoint_filters
+    }
+
+    pub fn is_exception_breakpoint_filter_enabled(
+        &self,
+        filter: &ExceptionBreakpointsFilter,
+    ) -> bool {
+        self.exception_breakpoint_filters
+            .get(&filter.filter)
+            .copied()
+            .unwrap_or(filter.default.unwrap_or(false))
+    }
+
+    /// Replaces the exception filters that were toggled, like when restoring them for a project.
+    pub fn set_exception_breakpoint_filters(
+        &mut self,
+        filters: HashMap<String, bool>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        self.exception_breakpoint_filters = filters;
+        self.exception_breakpoint_filters_changed(cx);
+    }
+
+    pub fn toggle_exception_breakpoint_filter(
+        &mut self,
+        filter: &ExceptionBreakpointsFilter,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let enabled = !self.is_exception_breakpoint_filter_enabled(filter);
+        self.exception_breakpoint_filters
+            .insert(filter.filter.clone(), enabled);
+        self.exception_breakpoint_filters_changed(cx);
+    }
+
+    /// Sends the exception filters to the sessions that were already configured.
+    fn exception_breakpoint_filters_changed(&mut self, cx: &mut ModelContext<Self>) {
+        for session in self.sessions.values() {
+            if session.is_configured {
+                self.send_exception_breakpoints(session.client.clone(), cx)
+                    .detach_and_log_err(cx);
+            }
+        }
+        cx.notify();
+    }
+
+    fn breakpoints_changed(&mut self, abs_path: &Path, cx: &mut ModelContext<Self>) {
+        for session in self.sessions.values() {
+            if session.is_configured {
+                self.send_breakpoints(session.client.clone(), abs_path, cx)
+                    .detach_and_log_err(cx);
+            }
+        }
+        cx.notify();
+    }
+
+    /// Anchors the breakpoints of the given file to its buffer, so they follow the edits made to it.
+    pub fn resolve_breakpoints(
+        &mut self,
+        buffer: &Model<Buffer>,
+        abs_path: &Path,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(breakpoints) = self.breakpoints.get_mut(abs_path) else {
+            return;
+        };
+        let snapshot = buffer.read(cx).snapshot();
+        let max_row = snapshot.max_point().row;
+        let mut changed = false;
+        for breakpoint in breakpoints {
+            if breakpoint.buffer().as_ref() == Some(buffer) {
+                continue;
+            }
+            breakpoint.row = breakpoint.row(cx).min(max_row);
+            let anchor = snapshot.anchor_before(Point::new(breakpoint.row, 0));
+            breakpoint.anchor = Some((buffer.downgrade(), anchor));
+            changed = true;
+        }
+        if changed {
+            cx.notify();
+        }
+    }
+
+    /// Records the rows of the breakpoints of a buffer that is being released, as their anchors
+    /// can't be resolved without it.
+    pub fn buffer_released(&mut self, buffer: &Buffer, abs_path: &Path) {
+        let Some(breakpoints) = self.breakpoints.get_mut(abs_path) else {
+            return;
+        };
+        for breakpoint in breakpoints {
+            if let Some((_, anchor)) = &breakpoint.anchor {
+                if anchor.buffer_id == Some(buffer.remote_id()) {
+                    breakpoint.row = anchor.to_point(buffer).row;
+                    breakpoint.anchor = None;
+                }
+            }
+        }
+    }
+
+    pub fn sessions(&self) -> impl Iterator<Item = (DebugAdapterClientId, &DebugSession)> {
+        self.sessions.iter().map(|(id, session)| (*id, session))
+    }
+
+    pub fn session(&self, id: DebugAdapterClientId) -> Option<&DebugSession> {
+        self.sessions.get(&id)
+    }
+
+    /// The session that was last started or that last stopped.
+    pub fn active_session(&self) -> Option<(DebugAdapterClientId, &DebugSession)> {
+        let id = self.active_session?;
+        Some((id, self.sessions.get(&id)?))
+    }
+
+    pub fn set_active_session(&mut self, id: DebugAdapterClientId, cx: &mut ModelContext<Self>) {
+        if self.sessions.contains_key(&id) && self.active_session != Some(id) {
+            self.active_session = Some(id);
+            cx.notify();
+        }
+    }
+
+    /// Spawns the debug adapter of a debug task, and starts debugging with it.
+    pub fn start_session(
+        &mut self,
+        config: DebugAdapterConfig,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<DebugAdapterClientId>> {
+        let id = self.next_client_id();
+        let binary = DebugAdapterBinary {
+            command: config.command.clone(),
+            args: config.args.clone(),
+            env: config.env.clone(),
+            cwd: config.cwd.clone(),
+        };
+        let (events_tx, events_rx) = channel::unbounded();
+        cx.spawn(|this, mut cx| async move {
+            let result = async {
+                let client = DebugAdapterClient::start(
+                    id,
+                    config.label.clone(),
+                    binary,
+                    move |event| {
+                        events_tx.try_send(event).ok();
+                    },
+                    &cx,
+                )?;
+                client.initialize().await?;
+                anyhow::Ok(client)
+            }
+            .await;
+            match result {
+                Ok(client) => {
+                    this.update(&mut cx, |this, cx| {
+                        this.add_session(Arc::new(client), config, events_rx, cx)
+                    })?;
+                    Ok(id)
+                }
+                Err(error) => {
+                    this.update(&mut cx, |_, cx| {
+                        cx.emit(DapStoreEvent::SessionFailed {
+                            label: config.label.into(),
+                            error: error.to_string().into(),
+                        })
+                    })?;
+                    Err(error)
+                }
+            }
+        })
+    }
+
+    /// Starts a debug session with a fake debug adapter, which handles no requests until
+    /// handlers are registered on it.
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn start_fake_session(
+        &mut self,
+        config: DebugAdapterConfig,
+        cx: &mut ModelContext<Self>,
+    ) -> (DebugAdapterClientId, dap::FakeDebugAdapter) {
+        let id = self.next_client_id();
+        let (events_tx, events_rx) = channel::unbounded();
+        let (client, fake) = DebugAdapterClient::fake(
+            id,
+            config.label.clone(),
+            move |event| {
+                events_tx.try_send(event).ok();
+            },
+            cx.background_executor(),
+        );
+        self.add_session(Arc::new(client), config, events_rx, cx);
+        (id, fake)
+    }
+
+    fn add_session(
+        &mut self,
+        client: Arc<DebugAdapterClient>,
+        config: DebugAdapterConfig,
+        events_rx: channel::Receiver<Events>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let id = client.id();
+        let event_loop = cx.spawn(|this, mut cx| async move {
+            while let Ok(event) = events_rx.recv().await {
+                if this
+                    .update(&mut cx, |this, cx| this.handle_event(id, event, cx))
+                    .is_err()
+                {
+                    break;
+                }
+            }
+        });
+        self.sessions.insert(
+            id,
+            DebugSession {
+                label: config.label.clone().into(),
+                client: client.clone(),
+                status: DebugSessionStatus::Starting,
+                is_configured: false,
+                threads: Vec::new(),
+                stopped_thread_id: None,
+                stop_reason: None,
+                stack_frames: Vec::new(),
+                active_stack_frame: None,
+                scopes: Vec::new(),
+                variables: HashMap::default(),
+                watch_values: HashMap::default(),
+                console: Vec::new(),
+                _event_loop: event_loop,
+            },
+        );
+        self.active_session = Some(id);
+        cx.emit(DapStoreEvent::SessionStarted(id));
+        cx.notify();
+
+        // Adapters may only answer the `launch` request once they're configured, so the
+        // events are handled while waiting for it.
+        cx.spawn(|this, mut cx| async move {
+            let result = match config.request {
+                DebugRequestType::Launch => client
+                    .request::<Launch>(config.request_args)
+                    .await
+                    .map(drop),
+                DebugRequestType::Attach => client
+                    .request::<Attach>(config.request_args)
+                    .await
+                    .map(drop),
+            };
+            this.update(&mut cx, |this, cx| match result {
+                Ok(()) => {
+                    if let Some(session) = this.sessions.get_mut(&id) {
+                        if session.status == DebugSessionStatus::Starting {
+                            session.status = DebugSessionStatus::Running;
+                            cx.notify();
+                        }
+                    }
+                }
+                Err(error) => {
+                    cx.emit(DapStoreEvent::SessionFailed {
+                        label: config.label.into(),
+                        error: error.to_string().into(),
+                    });
+                    this.stop_session(id, cx).detach();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    fn handle_event(
+        &mut self,
+        id: DebugAdapterClientId,
+        event: Events,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(session) = self.sessions.get_mut(&id) else {
+            return;
+        };
+        match event {
+            Events::Initialized => {
+                let client = session.client.clone();
+                let mut send_breakpoints = self
+                    .breakpoints
+                    .keys()
+                    .map(|abs_path| self.send_breakpoints(client.clone(), abs_path, cx))
+                    .collect::<Vec<_>>();
+                send_breakpoints.push(self.send_exception_breakpoints(client.clone(), cx));
+                cx.spawn(|this, mut cx| async move {
+                    for result in futures::future::join_all(send_breakpoints).await {
+                        result.log_err();
+                    }
+                    if client
+                        .capabilities()
+                        .supports_configuration_done_request
+                        .unwrap_or(false)
+                    {
+                        client.request::<ConfigurationDone>(json!({})).await?;
+                    }
+                    this.update(&mut cx, |this, _| {
+                        if let Some(session) = this.sessions.get_mut(&id) {
+                            session.is_configured = true;
+                        }
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
+            Events::Stopped(event) => {
+                session.status = DebugSessionStatus::Stopped;
+                session.stop_reason = Some(event.reason);
+                if event.thread_id.is_some() {
+                    session.stopped_thread_id = event.thread_id;
+                }
+                self.active_session = Some(id);
+                self.fetch_stack_frames(id, cx).detach_and_log_err(cx);
+            }
+            Events::Continued(_) => {
+                Self::mark_running(session);
+                cx.emit(DapStoreEvent::Continued(id));
+                cx.notify();
+            }
+            Events::Exited(_) | Events::Terminated => {
+                self.stop_session(id, cx).detach();
+            }
+            Events::Thread(_) => {
+                let client = session.client.clone();
+                cx.spawn(|this, mut cx| async move {
+                    let threads = client.request::<Threads>(json!({})).await?.threads;
+                    this.update(&mut cx, |this, cx| {
+                        if let Some(session) = this.sessions.get_mut(&id) {
+                            session.threads = threads;
+                            cx.notify();
+                        }
+                    })
+                })
+                .detach_and_log_err(cx);
+            }
+            Events::Output(event) => {
+                let kind = match event.category.as_deref() {
+                    Some("telemetry") => return,
+                    Some("stderr") => ConsoleEntryKind::Stderr,
+                    _ => ConsoleEntryKind::Output,
+                };
+                session.push_console_entry(kind, event.output);
+                cx.notify();
+            }
+            Events::Other(event) => {
+                log::debug!("unhandled debug adapter event {}", event.event);
+            }
+        }
+    }
+
+    /// Fetches the threads of a stopped session, and the stack frames of its stopped thread.
+    fn fetch_stack_frames(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(session) = self.sessions.get(&id) else {
+            return Task::ready(Ok(()));
+        };
+        let client = session.client.clone();
+        let stopped_thread_id = session.stopped_thread_id;
+        cx.spawn(|this, mut cx| async move {
+            let threads = client.request::<Threads>(json!({})).await?.threads;
+            let thread_id = stopped_thread_id
+                .or_else(|| threads.first().map(|thread| thread.id))
+                .context("no stopped thread")?;
+            let stack_frames = client
+                .request::<StackTrace>(StackTraceArguments {
+                    thread_id,
+                    start_frame: None,
+                    levels: None,
+                })
+                .await?
+                .stack_frames;
+            this.update(&mut cx, |this, cx| {
+                let Some(session) = this.sessions.get_mut(&id) else {
+                    return;
+                };
+                session.threads = threads;
+                session.stopped_thread_id = Some(thread_id);
+                session.active_stack_frame = (!stack_frames.is_empty()).then_some(0);
+                session.stack_frames = stack_frames;
+                this.refresh_active_stack_frame(id, cx);
+                cx.emit(DapStoreEvent::Stopped(id));
+                cx.notify();
+            })
+        })
+    }
+
+    /// Fetches the scopes of the active stack frame of a session along with the variables of its
+    /// inexpensive scopes, and evaluates the watch expressions in it.
+    fn refresh_active_stack_frame(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(session) = self.sessions.get_mut(&id) else {
+            return;
+        };
+        session.scopes.clear();
+        session.variables.clear();
+        session.watch_values.clear();
+        let Some(frame_id) = session.active_stack_frame().map(|frame| frame.id) else {
+            return;
+        };
+
+        let client = session.client.clone();
+        cx.spawn(|this, mut cx| async move {
+            let scopes = client
+                .request::<Scopes>(ScopesArguments { frame_id })
+                .await?
+                .scopes;
+            this.update(&mut cx, |this, cx| {
+                let Some(session) = this.sessions.get_mut(&id) else {
+                    return;
+                };
+                if session.active_stack_frame().map(|frame| frame.id) != Some(frame_id) {
+                    return;
+                }
+                let references = scopes
+                    .iter()
+                    .filter(|scope| !scope.expensive)
+                    .map(|scope| scope.variables_reference)
+                    .collect::<Vec<_>>();
+                session.scopes = scopes;
+                cx.notify();
+                for variables_reference in references {
+                    this.fetch_variables(id, variables_reference, cx)
+                        .detach_and_log_err(cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+
+        self.evaluate_watch_expressions(id, cx);
+    }
+
+    /// Fetches the variables of a scope or of a parent variable of a stopped session.
+    pub fn fetch_variables(
+        &mut self,
+        id: DebugAdapterClientId,
+        variables_reference: u64,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(session) = self.sessions.get(&id) else {
+            return Task::ready(Err(anyhow!("no debug session {}", id.0)));
+        };
+        if session.status != DebugSessionStatus::Stopped || variables_reference == 0 {
+            return Task::ready(Ok(()));
+        }
+        let client = session.client.clone();
+        cx.spawn(|this, mut cx| async move {
+            let variables = client
+                .request::<Variables>(VariablesArguments {
+                    variables_reference,
+                })
+                .await?
+                .variables;
+            this.update(&mut cx, |this, cx| {
+                if let Some(session) = this.sessions.get_mut(&id) {
+                    if session.status == DebugSessionStatus::Stopped {
+                        session.variables.insert(variables_reference, variables);
+                        cx.notify();
+                    }
+                }
+            })
+        })
+    }
+
+    /// Changes the value of a variable of a stopped session, then re-evaluates the watch
+    /// expressions, which may depend on it.
+    pub fn set_variable(
+        &mut self,
+        id: DebugAdapterClientId,
+        container_reference: u64,
+        name: String,
+        value: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(session) = self.sessions.get(&id) else {
+            return Task::ready(Err(anyhow!("no debug session {}", id.0)));
+        };
+        if !session
+            .client
+            .capabilities()
+            .supports_set_variable
+            .unwrap_or(false)
+        {
+            return Task::ready(Err(anyhow!("the debug adapter can't set variables")));
+        }
+        let client = session.client.clone();
+        cx.spawn(|this, mut cx| async move {
+            let response = client
+                .request::<SetVariable>(SetVariableArguments {
+                    variables_reference: container_reference,
+                    name: name.clone(),
+                    value,
+                })
+                .await?;
+            this.update(&mut cx, |this, cx| {
+                let Some(session) = this.sessions.get_mut(&id) else {
+                    return;
+                };
+                let variable =
+                    session
+                        .variables
+                        .get_mut(&container_reference)
+                        .and_then(|variables| {
+                            variables.iter_mut().find(|variable| variable.name == name)
+                        });
+                if let Some(variable) = variable {
+                    let old_reference = variable.variables_reference;
+                    variable.value = response.value;
+                    if response.type_.is_some() {
+                        variable.type_ = response.type_;
+                    }
+                    if let Some(variables_reference) = response.variables_reference {
+                        variable.variables_reference = variables_reference;
+                    }
+                    // The children of the variable may have changed along with its value.
+                    session.variables.remove(&old_reference);
+                }
+                this.evaluate_watch_expressions(id, cx);
+                cx.notify();
+            })
+        })
+    }
+
+    /// Evaluates an expression entered in the console of a session, in its active stack frame
+    /// if it is stopped, and adds the expression and its value to the console.
+    pub fn evaluate_in_console(
+        &mut self,
+        id: DebugAdapterClientId,
+        expression: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(session) = self.sessions.get_mut(&id) else {
+            return Task::ready(Err(anyhow!("no debug session {}", id.0)));
+        };
+        let frame_id = session.active_stack_frame().map(|frame| frame.id);
+        let client = session.client.clone();
+        session.push_console_entry(ConsoleEntryKind::Input, expression.clone());
+        cx.notify();
+        cx.spawn(|this, mut cx| async move {
+            let result = client
+                .request::<Evaluate>(EvaluateArguments {
+                    expression,
+                    frame_id,
+                    context: Some("repl".to_string()),
+                })
+                .await;
+            this.update(&mut cx, |this, cx| {
+                if let Some(session) = this.sessions.get_mut(&id) {
+                    match result {
+                        Ok(response) => {
+                            session.push_console_entry(ConsoleEntryKind::Result, response.result)
+                        }
+                        Err(error) => {
+                            session.push_console_entry(ConsoleEntryKind::Error, error.to_string())
+                        }
+                    }
+                    cx.notify();
+                }
+            })
+        })
+    }
+
+    /// Asks the debug adapter of a session for the completions of the text of the console at
+    /// the given 1-based column, or returns none when it doesn't support completions.
+    pub fn console_completions(
+        &self,
+        id: DebugAdapterClientId,
+        text: String,
+        column: u64,
+        cx: &AppContext,
+    ) -> Task<Result<Vec<CompletionItem>>> {
+        let Some(session) = self.sessions.get(&id) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        if !session
+            .client
+            .capabilities()
+            .supports_completions_request
+            .unwrap_or(false)
+        {
+            return Task::ready(Ok(Vec::new()));
+        }
+        let frame_id = session.active_stack_frame().map(|frame| frame.id);
+        let client = session.client.clone();
+        cx.background_executor().spawn(async move {
+            Ok(client
+                .request::<Completions>(CompletionsArguments {
+                    frame_id,
+                    text,
+                    column,
+                    line: None,
+                })
+                .await?
+                .targets)
+        })
+    }
+
+    /// Evaluates an expression in the active stack frame of a session, like the expressions
+    /// whose values are shown inline in the stopped function.
+    pub fn evaluate(
+        &self,
+        id: DebugAdapterClientId,
+        expression: String,
+        cx: &AppContext,
+    ) -> Task<Result<EvaluateResponse>> {
+        let Some(session) = self.sessions.get(&id) else {
+            return Task::ready(Err(anyhow!("no debug session {}", id.0)));
+        };
+        let frame_id = session.active_stack_frame().map(|frame| frame.id);
+        let client = session.client.clone();
+        cx.background_executor().spawn(async move {
+            client
+                .request::<Evaluate>(EvaluateArguments {
+                    expression,
+                    frame_id,
+                    context: Some("hover".to_string()),
+                })
+                .await
+        })
+    }
+
+    pub fn clear_console(&mut self, id: DebugAdapterClientId, cx: &mut ModelContext<Self>) {
+        if let Some(session) = self.sessions.get_mut(&id) {
+            session.console.clear();
+            cx.notify();
+        }
+    }
+
+    pub fn watch_expressions(&self) -> &[String] {
+        &self.watch_expressions
+    }
+
+    /// Adds an expression to watch, and evaluates it in the stopped sessions.
+    pub fn add_watch_expression(&mut self, expression: String, cx: &mut ModelContext<Self>) {
+        let expression = expression.trim().to_string();
+        if expression.is_empty() || self.watch_expressions.contains(&expression) {
+            return;
+        }
+        self.watch_expressions.push(expression.clone());
+        let ids = self.sessions.keys().copied().collect::<Vec<_>>();
+        for id in ids {
+            self.evaluate_watch_expression(id, expression.clone(), cx);
+        }
+        cx.notify();
+    }
+
+    pub fn remove_watch_expression(&mut self, ix: usize, cx: &mut ModelContext<Self>) {
+        if ix < self.watch_expressions.len() {
+            let expression = self.watch_expressions.remove(ix);
+            for session in self.sessions.values_mut() {
+                session.watch_values.remove(&expression);
+            }
+            cx.notify();
+        }
+    }
+
+    fn evaluate_watch_expressions(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        for expression in self.watch_expressions.clone() {
+            self.evaluate_watch_expression(id, expression, cx);
+        }
+    }
+
+    /// Evaluates a watch expression in the active stack frame of a stopped session.
+    fn evaluate_watch_expression(
+        &mut self,
+        id: DebugAdapterClientId,
+        expression: String,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(session) = self.sessions.get(&id) else {
+            return;
+        };
+        let Some(frame_id) = session.active_stack_frame().map(|frame| frame.id) else {
+            return;
+        };
+        let client = session.client.clone();
+        cx.spawn(|this, mut cx| async move {
+            let result = client
+                .request::<Evaluate>(EvaluateArguments {
+                    expression: expression.clone(),
+                    frame_id: Some(frame_id),
+                    context: Some("watch".to_string()),
+                })
+                .await
+                .map_err(|error| error.to_string());
+            this.update(&mut cx, |this, cx| {
+                let Some(session) = this.sessions.get_mut(&id) else {
+                    return;
+                };
+                if session.active_stack_frame().map(|frame| frame.id) == Some(frame_id) {
+                    session.watch_values.insert(expression, result);
+                    cx.notify();
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Sends all the breakpoints of a file to a debug adapter, replacing the ones it had.
+    fn send_breakpoints(
+        &self,
+        client: Arc<DebugAdapterClient>,
+        abs_path: &Path,
+        cx: &ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        // Only send the options that the adapter supports, and skip the logpoints of adapters
+        // that can't log, as they would stop the program instead.
+        let capabilities = client.capabilities();
+        let supports_conditions = capabilities
+            .supports_conditional_breakpoints
+            .unwrap_or(false);
+        let supports_hit_conditions = capabilities
+            .supports_hit_conditional_breakpoints
+            .unwrap_or(false);
+        let supports_log_points = capabilities.supports_log_points.unwrap_or(false);
+        let mut breakpoints = self
+            .breakpoints
+            .get(abs_path)
+            .into_iter()
+            .flatten()
+            .filter(|breakpoint| supports_log_points || breakpoint.log_message.is_none())
+            .map(|breakpoint| SourceBreakpoint {
+                line: breakpoint.row(cx) as u64 + 1,
+                column: None,
+                condition: breakpoint.condition.clone().filter(|_| supports_conditions),
+                hit_condition: breakpoint
+                    .hit_condition
+                    .clone()
+                    .filter(|_| supports_hit_conditions),
+                log_message: breakpoint.log_message.clone(),
+            })
+            .collect::<Vec<_>>();
+        breakpoints.sort_by_key(|breakpoint| breakpoint.line);
+        breakpoints.dedup_by_key(|breakpoint| breakpoint.line);
+        let source = Source {
+            name: abs_path
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned()),
+            path: Some(abs_path.to_string_lossy().into_owned()),
+            source_reference: None,
+        };
+        cx.background_executor().spawn(async move {
+            client
+                .request::<SetBreakpoints>(SetBreakpointsArguments {
+                    source,
+                    breakpoints: Some(breakpoints),
+                    source_modified: Some(false),
+                })
+                .await?;
+            Ok(())
+        })
+    }
+
+    /// Sends the enabled exception filters to a debug adapter, when it has any.
+    fn send_exception_breakpoints(
+        &self,
+        client: Arc<DebugAdapterClient>,
+        cx: &ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let filters = client
+            .capabilities()
+            .exception_breakpoint_filters
+            .unwrap_or_default();
+        if filters.is_empty() {
+            return Task::ready(Ok(()));
+        }
+        let filters = filters
+            .iter()
+            .filter(|filter| self.is_exception_breakpoint_filter_enabled(filter))
+            .map(|filter| filter.filter.clone())
+            .collect();
+        cx.background_executor().spawn(async move {
+            client
+                .request::<SetExceptionBreakpoints>(SetExceptionBreakpointsArguments { filters })
+                .await?;
+            Ok(())
+        })
+    }
+
+    pub fn continue_thread(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        self.resume::<Continue>(id, cx)
+    }
+
+    pub fn step_over(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        self.resume::<Next>(id, cx)
+    }
+
+    pub fn step_in(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        self.resume::<StepIn>(id, cx)
+    }
+
+    pub fn step_out(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        self.resume::<StepOut>(id, cx)
+    }
+
+    /// Asks the debug adapter to pause the current thread, which is reported with a `stopped` event.
+    pub fn pause(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let Some(session) = self.sessions.get(&id) else {
+            return Task::ready(Err(anyhow!("no debug session {}", id.0)));
+        };
+        let client = session.client.clone();
+        let Some(thread_id) = session.current_thread_id() else {
+            return Task::ready(Err(anyhow!("no thread to pause")));
+        };
+        cx.background_executor().spawn(async move {
+            client
+                .request::<Pause>(ThreadArguments { thread_id })
+                .await?;
+            Ok(())
+        })
+    }
+
+    /// Sends a request resuming the current thread of a stopped session.
+    ///
+    /// Adapters don't send `continued` events for the requests of the client, so the session is
+    /// considered running as soon as the request is sent.
+    fn resume<R>(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>>
+    where
+        R: Request<Arguments = ThreadArguments> + 'static,
+        R::Response: Send,
+    {
+        let Some(session) = self.sessions.get_mut(&id) else {
+            return Task::ready(Err(anyhow!("no debug session {}", id.0)));
+        };
+        if session.status != DebugSessionStatus::Stopped {
+            return Task::ready(Ok(()));
+        }
+        let Some(thread_id) = session.current_thread_id() else {
+            return Task::ready(Err(anyhow!("no thread to resume")));
+        };
+        let client = session.client.clone();
+        Self::mark_running(session);
+        cx.emit(DapStoreEvent::Continued(id));
+        cx.notify();
+        cx.background_executor().spawn(async move {
+            client.request::<R>(ThreadArguments { thread_id }).await?;
+            Ok(())
+        })
+    }
+
+    fn mark_running(session: &mut DebugSession) {
+        session.status = DebugSessionStatus::Running;
+        session.stop_reason = None;
+        session.stack_frames.clear();
+        session.active_stack_frame = None;
+        session.scopes.clear();
+        session.variables.clear();
+        session.watch_values.clear();
+    }
+
+    pub fn select_stack_frame(
+        &mut self,
+        id: DebugAdapterClientId,
+        ix: usize,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some(session) = self.sessions.get_mut(&id) else {
+            return;
+        };
+        if ix < session.stack_frames.len() && session.active_stack_frame != Some(ix) {
+            session.active_stack_frame = Some(ix);
+            self.refresh_active_stack_frame(id, cx);
+            cx.emit(DapStoreEvent::ActiveStackFrameChanged(id));
+            cx.notify();
+        }
+    }
+
+    /// Ends a debug session, terminating the debuggee and the debug adapter.
+    pub fn stop_session(
+        &mut self,
+        id: DebugAdapterClientId,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<()> {
+        let Some(session) = self.sessions.remove(&id) else {
+            return Task::ready(());
+        };
+        if self.active_session == Some(id) {
+            self.active_session = self.sessions.keys().next_back().copied();
+        }
+        cx.emit(DapStoreEvent::SessionEnded(id));
+        cx.notify();
+        cx.background_executor().spawn(async move {
+            session.client.shutdown().await.log_err();
+        })
+    }
+
+    pub fn shutdown_sessions(&mut self, cx: &mut ModelContext<Self>) -> Task<()> {
+        let shutdowns = self
+            .sessions
+            .keys()
+            .copied()
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|id| self.stop_session(id, cx))
+            .collect::<Vec<_>>();
+        cx.background_executor().spawn(async move {
+            futures::future::join_all(shutdowns).await;
+        })
+    }
+
+    fn next_client_id(&mut self) -> DebugAdapterClientId {
+        let id = DebugAdapterClientId(self.next_client_id);
+        self.next_client_id += 1;
+        id
+    }
+}
+
+fn buffer_abs_path(buffer: &Model<Buffer>, cx: &AppContext) -> Option<Arc<Path>> {
+    let file = buffer.read(cx).file()?.as_local()?;
+    Some(file.abs_path(cx).into())
+}
+
+/// The stack frame's file on disk, if it has one.
+pub fn stack_frame_abs_path(stack_frame: &StackFrame) -> Option<PathBuf> {
+    let path = stack_frame.source.as_ref()?.path.as_ref()?;
+    Some(PathBuf::from(path)).filter(|path| path.is_absolute())
+}
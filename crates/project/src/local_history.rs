@@ -0,0 +1,116 @@
+use anyhow::Result;
+use fs::{Fs, RemoveOptions};
+use futures::StreamExt;
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// Keeps the contents files were saved with on disk, independently of version control, so that
+/// earlier versions of a file can be restored.
+///
+/// Each file's versions are kept in a directory named after a hash of its absolute path, in files
+/// named after the time they were saved at, in milliseconds since the Unix epoch.
+pub struct LocalHistory {
+    fs: Arc<dyn Fs>,
+    dir: PathBuf,
+}
+
+/// A version of a file, as it was saved at some point.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LocalHistoryEntry {
+    /// When the file was saved with this version's contents.
+    pub saved_at: SystemTime,
+    path: PathBuf,
+}
+
+impl LocalHistory {
+    pub fn new(fs: Arc<dyn Fs>, dir: PathBuf) -> Self {
+        Self { fs, dir }
+    }
+
+    /// Records the contents a file was saved with, unless they're the same as the most recent
+    /// version's, deleting the oldest versions so that at most `max_entries` are kept. Nothing
+    /// is recorded when `max_entries` is zero.
+    pub async fn record(&self, abs_path: &Path, text: String, max_entries: usize) -> Result<()> {
+        if max_entries == 0 {
+            return Ok(());
+        }
+        let entries = self.entries(abs_path).await?;
+        if let Some(latest_entry) = entries.first() {
+            if self.load(latest_entry).await.ok().as_ref() == Some(&text) {
+                return Ok(());
+            }
+        }
+
+        // Make room for the new version first, so that no more versions than allowed are ever
+        // kept, even if writing it fails.
+        for entry in entries.iter().skip(max_entries - 1) {
+            self.fs
+                .remove_file(
+                    &entry.path,
+                    RemoveOptions {
+                        recursive: false,
+                        ignore_if_not_exists: true,
+                    },
+                )
+                .await?;
+        }
+
+        let dir = self.dir_for_file(abs_path);
+        self.fs.create_dir(&dir).await?;
+        let mut saved_at = SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis();
+        // Saves within the same millisecond still get versions of their own.
+        if let Some(latest_entry) = entries.first() {
+            saved_at = saved_at.max(
+                latest_entry
+                    .saved_at
+                    .duration_since(UNIX_EPOCH)?
+                    .as_millis()
+                    + 1,
+            );
+        }
+        self.fs
+            .atomic_write(dir.join(saved_at.to_string()), text)
+            .await?;
+        Ok(())
+    }
+
+    /// The recorded versions of a file, most recent first.
+    pub async fn entries(&self, abs_path: &Path) -> Result<Vec<LocalHistoryEntry>> {
+        let dir = self.dir_for_file(abs_path);
+        if !self.fs.is_dir(&dir).await {
+            return Ok(Vec::new());
+        }
+
+        let mut paths = self.fs.read_dir(&dir).await?;
+        let mut entries = Vec::new();
+        while let Some(path) = paths.next().await {
+            let path = path?;
+            let Some(saved_at) = path
+                .file_name()
+                .and_then(|file_name| file_name.to_str()?.parse::<u64>().ok())
+            else {
+                continue;
+            };
+            entries.push(LocalHistoryEntry {
+                saved_at: UNIX_EPOCH + Duration::from_millis(saved_at),
+                path,
+            });
+        }
+        entries.sort_by(|a, b| b.saved_at.cmp(&a.saved_at));
+        Ok(entries)
+    }
+
+    /// Loads the contents of a recorded version of a file.
+    pub async fn load(&self, entry: &LocalHistoryEntry) -> Result<String> {
+        self.fs.load(&entry.path).await
+    }
+
+    fn dir_for_file(&self, abs_path: &Path) -> PathBuf {
+        let digest = Sha256::digest(abs_path.to_string_lossy().as_bytes());
+        self.dir.join(format!("{digest:x}"))
+    }
+}
@@ -1,7 +1,8 @@
 use crate::{
-    CodeAction, CoreCompletion, DocumentHighlight, Hover, HoverBlock, HoverBlockKind, InlayHint,
+    CallHierarchyCall, CallHierarchyItem, CodeAction, CodeLens, CoreCompletion, DocumentColor,
+    DocumentHighlight, DocumentLink, FoldingRange, Hover, HoverBlock, HoverBlockKind, InlayHint,
     InlayHintLabel, InlayHintLabelPart, InlayHintLabelPartTooltip, InlayHintTooltip, Location,
-    LocationLink, MarkupContent, Project, ProjectTransaction, ResolveState,
+    LocationLink, MarkupContent, Project, ProjectTransaction, RenameEdits, ResolveState,
 };
 use anyhow::{anyhow, Context, Result};
 use async_trait::async_trait;
@@ -13,12 +14,13 @@ use language::{
     point_from_lsp, point_to_lsp,
     proto::{deserialize_anchor, deserialize_version, serialize_anchor, serialize_version},
     range_from_lsp, range_to_lsp, Anchor, Bias, Buffer, BufferSnapshot, CachedLspAdapter, CharKind,
-    OffsetRangeExt, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
+    OffsetRangeExt, Point, PointUtf16, ToOffset, ToPointUtf16, Transaction, Unclipped,
 };
 use lsp::{
     CompletionListItemDefaultsEditRange, DocumentHighlightKind, LanguageServer, LanguageServerId,
     OneOf, ServerCapabilities,
 };
+use snippet::Snippet;
 use std::{cmp::Reverse, ops::Range, path::Path, sync::Arc};
 use text::{BufferId, LineEnding};
 
@@ -100,6 +102,11 @@ pub(crate) struct PerformRename {
     pub push_to_history: bool,
 }
 
+pub(crate) struct GetRenameEdits {
+    pub position: PointUtf16,
+    pub new_name: String,
+}
+
 pub struct GetDefinition {
     pub position: PointUtf16,
 }
@@ -135,6 +142,41 @@ pub(crate) struct GetCodeActions {
     pub kinds: Option<Vec<lsp::CodeActionKind>>,
 }
 
+#[derive(Clone)]
+pub(crate) struct GetCodeLens;
+
+#[derive(Clone)]
+pub(crate) struct GetDocumentColors;
+
+#[derive(Clone)]
+pub(crate) struct GetDocumentLinks;
+
+pub(crate) struct GetFoldingRanges;
+
+pub(crate) struct PrepareCallHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct GetIncomingCalls {
+    pub item: CallHierarchyItem,
+}
+
+pub(crate) struct GetOutgoingCalls {
+    pub item: CallHierarchyItem,
+}
+
+pub(crate) struct PrepareTypeHierarchy {
+    pub position: PointUtf16,
+}
+
+pub(crate) struct GetSupertypes {
+    pub item: TypeHierarchyItem,
+}
+
+pub(crate) struct GetSubtypes {
+    pub item: TypeHierarchyItem,
+}
+
 pub(crate) struct OnTypeFormatting {
     pub position: PointUtf16,
     pub trigger: String,
@@ -408,6 +450,218 @@ impl LspCommand for PerformRename {
     }
 }
 
+#[async_trait(?Send)]
+impl LspCommand for GetRenameEdits {
+    type Response = RenameEdits;
+    type LspRequest = lsp::request::Rename;
+    type ProtoRequest = proto::GetRenameEdits;
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::RenameParams {
+        lsp::RenameParams {
+            text_document_position: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            new_name: self.new_name.clone(),
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        message: Option<lsp::WorkspaceEdit>,
+        project: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<RenameEdits> {
+        let Some(edit) = message else {
+            return Ok(RenameEdits::default());
+        };
+        let (lsp_adapter, _) = language_server_for_buffer(&project, &buffer, server_id, &mut cx)?;
+        let document_edits = match (edit.document_changes, edit.changes) {
+            (Some(lsp::DocumentChanges::Edits(edits)), _) => edits,
+            (Some(lsp::DocumentChanges::Operations(operations)), _) => operations
+                .into_iter()
+                .map(|operation| match operation {
+                    lsp::DocumentChangeOperation::Edit(edit) => Ok(edit),
+                    lsp::DocumentChangeOperation::Op(_) => Err(anyhow!(
+                        "renames that create, rename or delete files can't be previewed"
+                    )),
+                })
+                .collect::<Result<_>>()?,
+            (None, Some(changes)) => changes
+                .into_iter()
+                .map(|(uri, edits)| lsp::TextDocumentEdit {
+                    text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                        uri,
+                        version: None,
+                    },
+                    edits: edits.into_iter().map(lsp::Edit::Plain).collect(),
+                })
+                .collect(),
+            (None, None) => Vec::new(),
+        };
+
+        let mut edits_by_buffer = Vec::<(Model<Buffer>, Vec<(Range<Anchor>, String)>)>::new();
+        for document_edit in document_edits {
+            let buffer_to_edit = project
+                .update(&mut cx, |project, cx| {
+                    project.open_local_buffer_via_lsp(
+                        document_edit.text_document.uri,
+                        server_id,
+                        lsp_adapter.name.clone(),
+                        cx,
+                    )
+                })?
+                .await?;
+            let lsp_edits = document_edit
+                .edits
+                .into_iter()
+                .map(|edit| match edit {
+                    lsp::Edit::Plain(edit) => edit,
+                    lsp::Edit::Annotated(edit) => edit.text_edit,
+                    lsp::Edit::Snippet(edit) => {
+                        let text = Snippet::parse(&edit.snippet.value).map(|snippet| snippet.text);
+                        lsp::TextEdit {
+                            range: edit.range,
+                            new_text: text.unwrap_or(edit.snippet.value),
+                        }
+                    }
+                })
+                .collect::<Vec<_>>();
+            let edits = project
+                .update(&mut cx, |project, cx| {
+                    project.edits_from_lsp(
+                        &buffer_to_edit,
+                        lsp_edits,
+                        server_id,
+                        document_edit.text_document.version,
+                        cx,
+                    )
+                })?
+                .await?;
+            match edits_by_buffer
+                .iter_mut()
+                .find(|(buffer, _)| *buffer == buffer_to_edit)
+            {
+                Some((_, buffer_edits)) => buffer_edits.extend(edits),
+                None => edits_by_buffer.push((buffer_to_edit, edits)),
+            }
+        }
+        Ok(RenameEdits(edits_by_buffer))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetRenameEdits {
+        proto::GetRenameEdits {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(language::proto::serialize_anchor(
+                &buffer.anchor_before(self.position),
+            )),
+            new_name: self.new_name.clone(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetRenameEdits,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+            new_name: message.new_name,
+        })
+    }
+
+    fn response_to_proto(
+        response: RenameEdits,
+        project: &mut Project,
+        peer_id: PeerId,
+        _: &clock::Global,
+        cx: &mut AppContext,
+    ) -> proto::GetRenameEditsResponse {
+        let buffers = response
+            .0
+            .into_iter()
+            .map(|(buffer, edits)| proto::BufferRenameEdits {
+                buffer_id: project.create_buffer_for_peer(&buffer, peer_id, cx).into(),
+                edits: edits
+                    .into_iter()
+                    .map(|(range, new_text)| proto::RenameEdit {
+                        start: Some(serialize_anchor(&range.start)),
+                        end: Some(serialize_anchor(&range.end)),
+                        new_text,
+                    })
+                    .collect(),
+            })
+            .collect();
+        proto::GetRenameEditsResponse { buffers }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetRenameEditsResponse,
+        project: Model<Project>,
+        _: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<RenameEdits> {
+        let mut edits_by_buffer = Vec::new();
+        for buffer_edits in message.buffers {
+            let buffer_id = BufferId::new(buffer_edits.buffer_id)?;
+            let buffer = project
+                .update(&mut cx, |project, cx| {
+                    project.wait_for_remote_buffer(buffer_id, cx)
+                })?
+                .await?;
+            let mut edits = Vec::new();
+            for edit in buffer_edits.edits {
+                let start = edit
+                    .start
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("missing edit start"))?;
+                let end = edit
+                    .end
+                    .and_then(deserialize_anchor)
+                    .ok_or_else(|| anyhow!("missing edit end"))?;
+                edits.push((start..end, edit.new_text));
+            }
+            buffer
+                .update(&mut cx, |buffer, _| {
+                    buffer.wait_for_anchors(
+                        edits.iter().flat_map(|(range, _)| [range.start, range.end]),
+                    )
+                })?
+                .await?;
+            edits_by_buffer.push((buffer, edits));
+        }
+        Ok(RenameEdits(edits_by_buffer))
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetRenameEdits) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
 #[async_trait(?Send)]
 impl LspCommand for GetDefinition {
     type Response = Vec<LocationLink>;
@@ -1897,6 +2151,1101 @@ impl GetCodeActions {
     }
 }
 
+#[async_trait(?Send)]
+impl LspCommand for GetCodeLens {
+    type Response = Vec<CodeLens>;
+    type LspRequest = lsp::request::CodeLensRequest;
+    type ProtoRequest = proto::GetCodeLens;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        capabilities.code_lens_provider.is_some()
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CodeLensParams {
+        lsp::CodeLensParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_lenses: Option<Vec<lsp::CodeLens>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_lenses
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_lens| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_lens.range.start), Bias::Left);
+                    let end =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_lens.range.end), Bias::Left);
+                    CodeLens {
+                        server_id,
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        lsp_lens,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetCodeLens {
+        proto::GetCodeLens {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetCodeLens,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        lenses: Vec<CodeLens>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCodeLensResponse {
+        proto::GetCodeLensResponse {
+            lenses: lenses.iter().map(Project::serialize_code_lens).collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCodeLensResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<CodeLens>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .lenses
+            .into_iter()
+            .map(Project::deserialize_code_lens)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetCodeLens) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetCodeLens {
+    pub fn supports_code_lens(capabilities: &ServerCapabilities) -> bool {
+        capabilities.code_lens_provider.is_some()
+    }
+
+    pub fn can_resolve_lenses(capabilities: &ServerCapabilities) -> bool {
+        capabilities
+            .code_lens_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentColors {
+    type Response = Vec<DocumentColor>;
+    type LspRequest = lsp::request::DocumentColor;
+    type ProtoRequest = proto::GetDocumentColors;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        Self::supports_document_colors(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentColorParams {
+        lsp::DocumentColorParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_colors: Vec<lsp::ColorInformation>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentColor>> {
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_colors
+                .into_iter()
+                .map(|lsp_color| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_color.range.start), Bias::Left);
+                    let end =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_color.range.end), Bias::Left);
+                    DocumentColor {
+                        server_id,
+                        range: buffer.anchor_before(start)..buffer.anchor_after(end),
+                        color: lsp_color.color,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentColors {
+        proto::GetDocumentColors {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentColors,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        colors: Vec<DocumentColor>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentColorsResponse {
+        proto::GetDocumentColorsResponse {
+            colors: colors
+                .iter()
+                .map(Project::serialize_document_color)
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentColorsResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentColor>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .colors
+            .into_iter()
+            .map(Project::deserialize_document_color)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentColors) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetDocumentColors {
+    pub fn supports_document_colors(capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.color_provider {
+            Some(lsp::ColorProviderCapability::Simple(supported)) => *supported,
+            Some(_) => true,
+            None => false,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetDocumentLinks {
+    type Response = Vec<DocumentLink>;
+    type LspRequest = lsp::request::DocumentLinkRequest;
+    type ProtoRequest = proto::GetDocumentLinks;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        Self::supports_document_links(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::DocumentLinkParams {
+        lsp::DocumentLinkParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_links: Option<Vec<lsp::DocumentLink>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        server_id: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer.update(&mut cx, |buffer, _| {
+            lsp_links
+                .unwrap_or_default()
+                .into_iter()
+                .map(|lsp_link| {
+                    let start =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_link.range.start), Bias::Left);
+                    let end =
+                        buffer.clip_point_utf16(point_from_lsp(lsp_link.range.end), Bias::Left);
+                    DocumentLink {
+                        server_id,
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        lsp_link,
+                    }
+                })
+                .collect()
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetDocumentLinks {
+        proto::GetDocumentLinks {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetDocumentLinks,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        links: Vec<DocumentLink>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetDocumentLinksResponse {
+        proto::GetDocumentLinksResponse {
+            links: links.iter().map(Project::serialize_document_link).collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetDocumentLinksResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<DocumentLink>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .links
+            .into_iter()
+            .map(Project::deserialize_document_link)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetDocumentLinks) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl GetDocumentLinks {
+    pub fn supports_document_links(capabilities: &ServerCapabilities) -> bool {
+        capabilities.document_link_provider.is_some()
+    }
+
+    pub fn can_resolve_links(capabilities: &ServerCapabilities) -> bool {
+        capabilities
+            .document_link_provider
+            .as_ref()
+            .and_then(|options| options.resolve_provider)
+            .unwrap_or(false)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetFoldingRanges {
+    type Response = Vec<FoldingRange>;
+    type LspRequest = lsp::request::FoldingRangeRequest;
+    type ProtoRequest = proto::GetFoldingRanges;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.folding_range_provider {
+            Some(lsp::FoldingRangeProviderCapability::Simple(supported)) => *supported,
+            Some(_) => true,
+            None => false,
+        }
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::FoldingRangeParams {
+        lsp::FoldingRangeParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(path).unwrap(),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_ranges: Option<Vec<lsp::FoldingRange>>,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        _: LanguageServerId,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange>> {
+        buffer.update(&mut cx, |buffer, _| {
+            let max_row = buffer.max_point().row;
+            let mut ranges = lsp_ranges
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|lsp_range| {
+                    lsp_range.start_line < lsp_range.end_line && lsp_range.end_line <= max_row
+                })
+                .map(|lsp_range| {
+                    // Only whole lines are folded, so that the first line of the range stays
+                    // visible and the fold ends where its last line does.
+                    let start_row = lsp_range.start_line;
+                    let end_row = lsp_range.end_line;
+                    let start = Point::new(start_row, buffer.line_len(start_row));
+                    let end = Point::new(end_row, buffer.line_len(end_row));
+                    FoldingRange {
+                        range: buffer.anchor_after(start)..buffer.anchor_before(end),
+                        kind: lsp_range.kind,
+                    }
+                })
+                .collect::<Vec<_>>();
+            ranges.sort_by(|a, b| a.range.start.cmp(&b.range.start, buffer));
+            ranges
+        })
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetFoldingRanges {
+        proto::GetFoldingRanges {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetFoldingRanges,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self)
+    }
+
+    fn response_to_proto(
+        ranges: Vec<FoldingRange>,
+        _: &mut Project,
+        _: PeerId,
+        buffer_version: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetFoldingRangesResponse {
+        proto::GetFoldingRangesResponse {
+            ranges: ranges
+                .iter()
+                .map(Project::serialize_folding_range)
+                .collect(),
+            version: serialize_version(buffer_version),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetFoldingRangesResponse,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Vec<FoldingRange>> {
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        message
+            .ranges
+            .into_iter()
+            .map(Project::deserialize_folding_range)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetFoldingRanges) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for PrepareCallHierarchy {
+    type Response = Vec<CallHierarchyItem>;
+    type LspRequest = lsp::request::CallHierarchyPrepare;
+    type ProtoRequest = proto::PrepareCallHierarchy;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        Self::supports_call_hierarchy(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyPrepareParams {
+        lsp::CallHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_items: Option<Vec<lsp::CallHierarchyItem>>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        Ok(lsp_items
+            .unwrap_or_default()
+            .into_iter()
+            .map(|lsp_item| CallHierarchyItem {
+                server_id,
+                lsp_item,
+            })
+            .collect())
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareCallHierarchy {
+        proto::PrepareCallHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareCallHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        items: Vec<CallHierarchyItem>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::PrepareCallHierarchyResponse {
+        proto::PrepareCallHierarchyResponse {
+            items: items
+                .iter()
+                .map(Project::serialize_call_hierarchy_item)
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::PrepareCallHierarchyResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyItem>> {
+        message
+            .items
+            .into_iter()
+            .map(Project::deserialize_call_hierarchy_item)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareCallHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+impl PrepareCallHierarchy {
+    pub fn supports_call_hierarchy(capabilities: &ServerCapabilities) -> bool {
+        match &capabilities.call_hierarchy_provider {
+            Some(lsp::CallHierarchyServerCapability::Simple(supported)) => *supported,
+            Some(_) => true,
+            None => false,
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetIncomingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyIncomingCalls;
+    type ProtoRequest = proto::GetIncomingCalls;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        PrepareCallHierarchy::supports_call_hierarchy(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyIncomingCallsParams {
+        lsp::CallHierarchyIncomingCallsParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_calls: Option<Vec<lsp::CallHierarchyIncomingCall>>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        Ok(lsp_calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| CallHierarchyCall {
+                item: CallHierarchyItem {
+                    server_id,
+                    lsp_item: call.from,
+                },
+                from_ranges: call.from_ranges,
+            })
+            .collect())
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetIncomingCalls {
+        proto::GetIncomingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(Project::serialize_call_hierarchy_item(&self.item)),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetIncomingCalls,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: Project::deserialize_call_hierarchy_item(item)?,
+        })
+    }
+
+    fn response_to_proto(
+        calls: Vec<CallHierarchyCall>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCallHierarchyCallsResponse {
+        proto::GetCallHierarchyCallsResponse {
+            calls: calls
+                .iter()
+                .map(Project::serialize_call_hierarchy_call)
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCallHierarchyCallsResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        message
+            .calls
+            .into_iter()
+            .map(Project::deserialize_call_hierarchy_call)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetIncomingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetOutgoingCalls {
+    type Response = Vec<CallHierarchyCall>;
+    type LspRequest = lsp::request::CallHierarchyOutgoingCalls;
+    type ProtoRequest = proto::GetOutgoingCalls;
+
+    fn check_capabilities(&self, capabilities: &ServerCapabilities) -> bool {
+        PrepareCallHierarchy::supports_call_hierarchy(capabilities)
+    }
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::CallHierarchyOutgoingCallsParams {
+        lsp::CallHierarchyOutgoingCallsParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_calls: Option<Vec<lsp::CallHierarchyOutgoingCall>>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        Ok(lsp_calls
+            .unwrap_or_default()
+            .into_iter()
+            .map(|call| CallHierarchyCall {
+                item: CallHierarchyItem {
+                    server_id,
+                    lsp_item: call.to,
+                },
+                from_ranges: call.from_ranges,
+            })
+            .collect())
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetOutgoingCalls {
+        proto::GetOutgoingCalls {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(Project::serialize_call_hierarchy_item(&self.item)),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetOutgoingCalls,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: Project::deserialize_call_hierarchy_item(item)?,
+        })
+    }
+
+    fn response_to_proto(
+        calls: Vec<CallHierarchyCall>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::GetCallHierarchyCallsResponse {
+        proto::GetCallHierarchyCallsResponse {
+            calls: calls
+                .iter()
+                .map(Project::serialize_call_hierarchy_call)
+                .collect(),
+        }
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::GetCallHierarchyCallsResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Vec<CallHierarchyCall>> {
+        message
+            .calls
+            .into_iter()
+            .map(Project::deserialize_call_hierarchy_call)
+            .collect()
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetOutgoingCalls) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+// The type hierarchy capability of servers isn't exposed by the LSP types, so these requests are
+// sent to any server, which fail for the servers that don't support them.
+#[async_trait(?Send)]
+impl LspCommand for PrepareTypeHierarchy {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchyPrepare;
+    type ProtoRequest = proto::PrepareTypeHierarchy;
+
+    fn to_lsp(
+        &self,
+        path: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchyPrepareParams {
+        lsp::TypeHierarchyPrepareParams {
+            text_document_position_params: lsp::TextDocumentPositionParams {
+                text_document: lsp::TextDocumentIdentifier {
+                    uri: lsp::Url::from_file_path(path).unwrap(),
+                },
+                position: point_to_lsp(self.position),
+            },
+            work_done_progress_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        Ok(type_hierarchy_items_from_lsp(lsp_items, server_id))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::PrepareTypeHierarchy {
+        proto::PrepareTypeHierarchy {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            position: Some(serialize_anchor(&buffer.anchor_before(self.position))),
+            version: serialize_version(&buffer.version()),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::PrepareTypeHierarchy,
+        _: Model<Project>,
+        buffer: Model<Buffer>,
+        mut cx: AsyncAppContext,
+    ) -> Result<Self> {
+        let position = message
+            .position
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid position"))?;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&message.version))
+            })?
+            .await?;
+        Ok(Self {
+            position: buffer.update(&mut cx, |buffer, _| position.to_point_utf16(buffer))?,
+        })
+    }
+
+    fn response_to_proto(
+        items: Vec<TypeHierarchyItem>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::TypeHierarchyItemsResponse {
+        type_hierarchy_items_to_proto(&items)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::TypeHierarchyItemsResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_proto(message)
+    }
+
+    fn buffer_id_from_proto(message: &proto::PrepareTypeHierarchy) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSupertypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySupertypes;
+    type ProtoRequest = proto::GetSupertypes;
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySupertypesParams {
+        lsp::TypeHierarchySupertypesParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        Ok(type_hierarchy_items_from_lsp(lsp_items, server_id))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSupertypes {
+        proto::GetSupertypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(Project::serialize_type_hierarchy_item(&self.item)),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSupertypes,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: Project::deserialize_type_hierarchy_item(item)?,
+        })
+    }
+
+    fn response_to_proto(
+        items: Vec<TypeHierarchyItem>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::TypeHierarchyItemsResponse {
+        type_hierarchy_items_to_proto(&items)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::TypeHierarchyItemsResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_proto(message)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSupertypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+#[async_trait(?Send)]
+impl LspCommand for GetSubtypes {
+    type Response = Vec<TypeHierarchyItem>;
+    type LspRequest = lsp::request::TypeHierarchySubtypes;
+    type ProtoRequest = proto::GetSubtypes;
+
+    fn to_lsp(
+        &self,
+        _: &Path,
+        _: &Buffer,
+        _: &Arc<LanguageServer>,
+        _: &AppContext,
+    ) -> lsp::TypeHierarchySubtypesParams {
+        lsp::TypeHierarchySubtypesParams {
+            item: self.item.lsp_item.clone(),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        }
+    }
+
+    async fn response_from_lsp(
+        self,
+        lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        server_id: LanguageServerId,
+        _: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        Ok(type_hierarchy_items_from_lsp(lsp_items, server_id))
+    }
+
+    fn to_proto(&self, project_id: u64, buffer: &Buffer) -> proto::GetSubtypes {
+        proto::GetSubtypes {
+            project_id,
+            buffer_id: buffer.remote_id().into(),
+            item: Some(Project::serialize_type_hierarchy_item(&self.item)),
+        }
+    }
+
+    async fn from_proto(
+        message: proto::GetSubtypes,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Self> {
+        let item = message.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(Self {
+            item: Project::deserialize_type_hierarchy_item(item)?,
+        })
+    }
+
+    fn response_to_proto(
+        items: Vec<TypeHierarchyItem>,
+        _: &mut Project,
+        _: PeerId,
+        _: &clock::Global,
+        _: &mut AppContext,
+    ) -> proto::TypeHierarchyItemsResponse {
+        type_hierarchy_items_to_proto(&items)
+    }
+
+    async fn response_from_proto(
+        self,
+        message: proto::TypeHierarchyItemsResponse,
+        _: Model<Project>,
+        _: Model<Buffer>,
+        _: AsyncAppContext,
+    ) -> Result<Vec<TypeHierarchyItem>> {
+        type_hierarchy_items_from_proto(message)
+    }
+
+    fn buffer_id_from_proto(message: &proto::GetSubtypes) -> Result<BufferId> {
+        BufferId::new(message.buffer_id)
+    }
+}
+
+fn type_hierarchy_items_from_lsp(
+    lsp_items: Option<Vec<lsp::TypeHierarchyItem>>,
+    server_id: LanguageServerId,
+) -> Vec<TypeHierarchyItem> {
+    lsp_items
+        .unwrap_or_default()
+        .into_iter()
+        .map(|lsp_item| TypeHierarchyItem {
+            server_id,
+            lsp_item,
+        })
+        .collect()
+}
+
+fn type_hierarchy_items_to_proto(items: &[TypeHierarchyItem]) -> proto::TypeHierarchyItemsResponse {
+    proto::TypeHierarchyItemsResponse {
+        items: items
+            .iter()
+            .map(Project::serialize_type_hierarchy_item)
+            .collect(),
+    }
+}
+
+fn type_hierarchy_items_from_proto(
+    message: proto::TypeHierarchyItemsResponse,
+) -> Result<Vec<TypeHierarchyItem>> {
+    message
+        .items
+        .into_iter()
+        .map(Project::deserialize_type_hierarchy_item)
+        .collect()
+}
+
 #[async_trait(?Send)]
 impl LspCommand for OnTypeFormatting {
     type Response = Option<Transaction>;
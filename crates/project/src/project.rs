@@ -1,5 +1,7 @@
 pub mod connection_manager;
+pub mod dap_store;
 pub mod debounced_delay;
+pub mod local_history;
 pub mod lsp_command;
 pub mod lsp_ext_command;
 mod prettier_support;
@@ -11,6 +13,8 @@ pub mod terminals;
 #[cfg(test)]
 mod project_tests;
 pub mod search_history;
+pub mod search_index;
+mod settings_lsp_adapter;
 
 use anyhow::{anyhow, bail, Context as _, Result};
 use async_trait::async_trait;
@@ -20,6 +24,7 @@ use client::{
 };
 use clock::ReplicaId;
 use collections::{hash_map, BTreeMap, HashMap, HashSet, VecDeque};
+use dap_store::DapStore;
 use debounced_delay::DebouncedDelay;
 use futures::{
     channel::{
@@ -32,7 +37,10 @@ use futures::{
     AsyncWriteExt, Future, FutureExt, StreamExt, TryFutureExt,
 };
 use fuzzy::CharBag;
-use git::{blame::Blame, repository::GitRepository};
+use git::{
+    blame::Blame,
+    repository::{DiffBase, GitRepository},
+};
 use globset::{Glob, GlobSet, GlobSetBuilder};
 use gpui::{
     AnyModel, AppContext, AsyncAppContext, BackgroundExecutor, BorrowAppContext, Context, Entity,
@@ -40,18 +48,22 @@ use gpui::{
 };
 use itertools::Itertools;
 use language::{
-    language_settings::{language_settings, FormatOnSave, Formatter, InlayHintKind},
+    language_settings::{
+        language_settings, CompletionMerge, FormatOnSave, Formatter, InlayHintKind,
+    },
     markdown, point_to_lsp, prepare_completion_documentation,
     proto::{
         deserialize_anchor, deserialize_line_ending, deserialize_version, serialize_anchor,
         serialize_line_ending, serialize_version, split_operations,
     },
-    range_from_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, Capability, CodeLabel,
-    ContextProvider, Diagnostic, DiagnosticEntry, DiagnosticSet, Diff, Documentation,
-    Event as BufferEvent, File as _, Language, LanguageRegistry, LanguageServerName, LocalFile,
-    LspAdapterDelegate, Operation, Patch, PendingLanguageServer, PointUtf16, TextBufferSnapshot,
-    ToOffset, ToPointUtf16, Transaction, Unclipped,
+    range_from_lsp, range_to_lsp, Bias, Buffer, BufferSnapshot, CachedLspAdapter, Capability,
+    CodeLabel, ContextProvider, Diagnostic, DiagnosticEntry, DiagnosticRelatedInformation,
+    DiagnosticSet, Diff, Documentation, Event as BufferEvent, File as _, Language,
+    LanguageRegistry, LanguageServerName, LocalFile, LspAdapterDelegate, Operation, Patch,
+    PendingLanguageServer, PointUtf16, SemanticToken, TextBufferSnapshot, ToOffset, ToPoint,
+    ToPointUtf16, Transaction, Unclipped,
 };
+use local_history::LocalHistory;
 use log::error;
 use lsp::{
     DiagnosticSeverity, DiagnosticTag, DidChangeWatchedFilesRegistrationOptions,
@@ -64,9 +76,11 @@ use node_runtime::NodeRuntime;
 use parking_lot::{Mutex, RwLock};
 use postage::watch;
 use prettier_support::{DefaultPrettier, PrettierInstance};
-use project_settings::{LspSettings, ProjectSettings};
+use project_settings::{DiagnosticsModel, LspSettings, ProjectSettings};
 use rand::prelude::*;
 use search_history::SearchHistory;
+use search_index::{SearchIndexFilter, WorktreeSearchIndex};
+use settings_lsp_adapter::SettingsLspAdapter;
 use snippet::Snippet;
 use worktree::{CreatedEntry, LocalSnapshot};
 
@@ -100,14 +114,15 @@ use std::{
 };
 use task::{
     static_source::{StaticSource, TrackedFile},
-    RevealStrategy, TaskContext, TaskTemplate, TaskVariables, VariableName,
+    RevealStrategy, TaskContext, TaskTemplate, TaskType, TaskVariables, VariableName,
 };
 use terminals::Terminals;
-use text::{Anchor, BufferId, LineEnding};
+use text::{Anchor, BufferId, LineEnding, Point, Rope};
 use util::{
     debug_panic, defer, maybe, merge_json_value_into, parse_env_output,
     paths::{
-        LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH, LOCAL_VSCODE_TASKS_RELATIVE_PATH,
+        LOCAL_HISTORY_DIR, LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH,
+        LOCAL_VSCODE_TASKS_RELATIVE_PATH,
     },
     post_inc, ResultExt, TryFutureExt as _,
 };
@@ -130,6 +145,7 @@ const MAX_SERVER_REINSTALL_ATTEMPT_COUNT: u64 = 4;
 const SERVER_REINSTALL_DEBOUNCE_TIMEOUT: Duration = Duration::from_secs(1);
 const SERVER_LAUNCHING_BEFORE_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
 pub const SERVER_PROGRESS_DEBOUNCE_TIMEOUT: Duration = Duration::from_millis(100);
+const DIAGNOSTIC_PULL_DEBOUNCE: Duration = Duration::from_millis(50);
 
 const MAX_PROJECT_SEARCH_HISTORY_SIZE: usize = 500;
 
@@ -194,6 +210,11 @@ pub struct Project {
     >,
     user_store: Model<UserStore>,
     fs: Arc<dyn Fs>,
+    /// The history of the contents local files are saved with, absent for remote projects.
+    local_history: Option<Arc<LocalHistory>>,
+    /// Whether the project's worktrees haven't been trusted yet, in which case nothing from them
+    /// is executed: no language servers are started and no external formatters are run.
+    is_restricted: bool,
     client_state: ProjectClientState,
     collaborators: HashMap<proto::PeerId, Collaborator>,
     client_subscriptions: Vec<client::Subscription>,
@@ -213,6 +234,10 @@ pub struct Project {
     local_buffer_ids_by_path: HashMap<ProjectPath, BufferId>,
     local_buffer_ids_by_entry_id: HashMap<ProjectEntryId, BufferId>,
     buffer_snapshots: HashMap<BufferId, HashMap<LanguageServerId, Vec<LspBufferSnapshot>>>, // buffer_id -> server_id -> vec of snapshots
+    semantic_tokens: HashMap<BufferId, HashMap<LanguageServerId, LspSemanticTokens>>,
+    /// The result id of the last diagnostics pulled from each language server for each document.
+    pulled_diagnostics: HashMap<LanguageServerId, HashMap<lsp::Url, Option<String>>>,
+    diagnostic_pulls: HashMap<BufferId, Task<()>>,
     buffers_being_formatted: HashSet<BufferId>,
     buffers_needing_diff: HashSet<WeakModel<Buffer>>,
     git_diff_debouncer: DebouncedDelay,
@@ -221,14 +246,20 @@ pub struct Project {
     _maintain_workspace_config: Task<Result<()>>,
     terminals: Terminals,
     current_lsp_settings: HashMap<Arc<str>, LspSettings>,
+    /// The languages and names of the language servers defined in the settings that were
+    /// registered with the language registry.
+    settings_lsp_adapters: HashSet<(Arc<str>, LanguageServerName)>,
     node: Option<Arc<dyn NodeRuntime>>,
     default_prettier: DefaultPrettier,
     prettiers_per_worktree: HashMap<WorktreeId, HashSet<Option<PathBuf>>>,
     prettier_instances: HashMap<PathBuf, PrettierInstance>,
     tasks: Model<Inventory>,
+    dap_store: Model<DapStore>,
     hosted_project_id: Option<ProjectId>,
     dev_server_project_id: Option<client::DevServerProjectId>,
     search_history: SearchHistory,
+    /// The search indices of the visible local worktrees, when they're enabled.
+    search_indices: HashMap<WorktreeId, WorktreeSearchIndex>,
 }
 
 pub enum LanguageServerToQuery {
@@ -241,6 +272,13 @@ struct LspBufferSnapshot {
     snapshot: TextBufferSnapshot,
 }
 
+/// The last semantic tokens a language server reported for a buffer, which later requests ask
+/// the server to send the changes to.
+struct LspSemanticTokens {
+    result_id: Option<String>,
+    data: Vec<lsp::SemanticToken>,
+}
+
 /// Message ordered with respect to buffer operations
 #[derive(Debug)]
 enum BufferOrderedMessage {
@@ -379,8 +417,11 @@ pub struct LanguageServerStatus {
 
 #[derive(Clone, Debug, Serialize)]
 pub struct LanguageServerProgress {
+    pub title: Option<String>,
     pub message: Option<String>,
     pub percentage: Option<usize>,
+    /// Whether the language server allows this work to be cancelled.
+    pub is_cancellable: bool,
     #[serde(skip_serializing)]
     pub last_update_at: Instant,
 }
@@ -472,6 +513,114 @@ pub struct CodeAction {
     pub lsp_action: lsp::CodeAction,
 }
 
+/// A code lens provided by a language server.
+#[derive(Clone, Debug)]
+pub struct CodeLens {
+    /// The id of the language server that produced this code lens.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the code lens is shown for.
+    pub range: Range<Anchor>,
+    /// The raw code lens provided by the language server, whose command is only filled in once
+    /// it's resolved.
+    pub lsp_lens: lsp::CodeLens,
+}
+
+/// A link in a buffer to a URL or a file, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct DocumentLink {
+    /// The id of the language server that reported this link.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer the link is shown for.
+    pub range: Range<Anchor>,
+    /// The raw link provided by the language server, whose target is only filled in once it's
+    /// resolved.
+    pub lsp_link: lsp::DocumentLink,
+}
+
+/// A range of lines in a buffer that can be folded, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct FoldingRange {
+    /// The range that gets folded, from the end of its first line to the end of its last one.
+    pub range: Range<Anchor>,
+    /// The kind of the range, such as a block of imports or of comments.
+    pub kind: Option<lsp::FoldingRangeKind>,
+}
+
+/// A symbol of a call hierarchy, such as a function or a method, as reported by a language
+/// server.
+#[derive(Clone, Debug)]
+pub struct CallHierarchyItem {
+    /// The id of the language server that reported this symbol, which is the one asked for its
+    /// calls.
+    pub server_id: LanguageServerId,
+    /// The raw item, which language servers expect back unchanged when asked for its calls.
+    pub lsp_item: lsp::CallHierarchyItem,
+}
+
+/// A call between two symbols of a call hierarchy.
+#[derive(Clone, Debug)]
+pub struct CallHierarchyCall {
+    /// The caller for incoming calls, or the callee for outgoing calls.
+    pub item: CallHierarchyItem,
+    /// The ranges of the calls, in the file of the caller.
+    pub from_ranges: Vec<lsp::Range>,
+}
+
+/// A type of a type hierarchy, such as a class or a trait, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct TypeHierarchyItem {
+    /// The id of the language server that reported this type, which is the one asked for its
+    /// supertypes and subtypes.
+    pub server_id: LanguageServerId,
+    /// The raw item, which language servers expect back unchanged when asked for its supertypes
+    /// and subtypes.
+    pub lsp_item: lsp::TypeHierarchyItem,
+}
+
+/// A color literal of a buffer, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct DocumentColor {
+    /// The id of the language server that reported this color.
+    pub server_id: LanguageServerId,
+    /// The range of the buffer containing the color literal.
+    pub range: Range<Anchor>,
+    /// The color, with components in the range 0.0 to 1.0.
+    pub color: lsp::Color,
+}
+
+/// A way to write a color in the buffer, as suggested by a language server.
+#[derive(Clone, Debug)]
+pub struct ColorPresentation {
+    /// The text of the color, such as `#ff0000` or `rgb(255, 0, 0)`.
+    pub label: String,
+    /// The edits writing the color over its literal, including any additional edits the
+    /// language server requires, such as imports.
+    pub edits: Vec<(Range<Anchor>, String)>,
+}
+
+/// A value to show inline while debugging, as reported by a language server.
+#[derive(Clone, Debug)]
+pub struct InlineValue {
+    /// The range of the buffer the value is about.
+    pub range: Range<Anchor>,
+    pub kind: InlineValueKind,
+}
+
+#[derive(Clone, Debug)]
+pub enum InlineValueKind {
+    /// Text to show as is.
+    Text(String),
+    /// A variable to look up in the stopped frame, named by the text of the range when there's
+    /// no name.
+    VariableLookup {
+        name: Option<String>,
+        case_sensitive: bool,
+    },
+    /// An expression to evaluate in the stopped frame, which is the text of the range when
+    /// there's none.
+    EvaluatableExpression(Option<String>),
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResolveState {
     Resolved,
@@ -583,6 +732,11 @@ impl Hover {
 #[derive(Default)]
 pub struct ProjectTransaction(pub HashMap<Model<Buffer>, language::Transaction>);
 
+/// The edits a language server makes to rename a symbol, grouped by buffer, before they're
+/// applied.
+#[derive(Clone, Debug, Default)]
+pub struct RenameEdits(pub Vec<(Model<Buffer>, Vec<(Range<Anchor>, String)>)>);
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FormatTrigger {
     Save,
@@ -685,12 +839,20 @@ impl Project {
         client.add_model_request_handler(Self::handle_on_type_formatting);
         client.add_model_request_handler(Self::handle_inlay_hints);
         client.add_model_request_handler(Self::handle_resolve_inlay_hint);
+        client.add_model_request_handler(Self::handle_resolve_code_lens);
+        client.add_model_request_handler(Self::handle_get_color_presentations);
+        client.add_model_request_handler(Self::handle_resolve_document_link);
+        client.add_model_request_handler(Self::handle_get_semantic_tokens);
         client.add_model_request_handler(Self::handle_refresh_inlay_hints);
         client.add_model_request_handler(Self::handle_reload_buffers);
         client.add_model_request_handler(Self::handle_synchronize_buffers);
         client.add_model_request_handler(Self::handle_format_buffers);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCodeActions>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetCodeLens>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetCompletions>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentColors>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetDocumentLinks>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetFoldingRanges>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetHover>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetDefinition>);
         client.add_model_request_handler(Self::handle_lsp_command::<GetTypeDefinition>);
@@ -698,6 +860,13 @@ impl Project {
         client.add_model_request_handler(Self::handle_lsp_command::<GetReferences>);
         client.add_model_request_handler(Self::handle_lsp_command::<PrepareRename>);
         client.add_model_request_handler(Self::handle_lsp_command::<PerformRename>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetRenameEdits>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareCallHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetIncomingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetOutgoingCalls>);
+        client.add_model_request_handler(Self::handle_lsp_command::<PrepareTypeHierarchy>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSupertypes>);
+        client.add_model_request_handler(Self::handle_lsp_command::<GetSubtypes>);
         client.add_model_request_handler(Self::handle_search_project);
         client.add_model_request_handler(Self::handle_get_project_symbols);
         client.add_model_request_handler(Self::handle_open_buffer_for_symbol);
@@ -710,6 +879,7 @@ impl Project {
         client.add_model_request_handler(Self::handle_blame_buffer);
         client.add_model_request_handler(Self::handle_multi_lsp_query);
         client.add_model_request_handler(Self::handle_restart_language_servers);
+        client.add_model_request_handler(Self::handle_cancel_language_server_work);
         client.add_model_request_handler(Self::handle_task_context_for_location);
         client.add_model_request_handler(Self::handle_task_templates);
     }
@@ -727,6 +897,7 @@ impl Project {
             cx.spawn(move |this, cx| Self::send_buffer_ordered_messages(this, rx, cx))
                 .detach();
             let tasks = Inventory::new(cx);
+            let dap_store = cx.new_model(|_| DapStore::new());
 
             Self {
                 worktrees: Vec::new(),
@@ -742,6 +913,9 @@ impl Project {
                 local_buffer_ids_by_path: Default::default(),
                 local_buffer_ids_by_entry_id: Default::default(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
+                pulled_diagnostics: Default::default(),
+                diagnostic_pulls: Default::default(),
                 join_project_response_message_id: 0,
                 client_state: ProjectClientState::Local,
                 loading_buffers: HashMap::default(),
@@ -758,6 +932,11 @@ impl Project {
                 languages,
                 client,
                 user_store,
+                local_history: Some(Arc::new(LocalHistory::new(
+                    fs.clone(),
+                    LOCAL_HISTORY_DIR.clone(),
+                ))),
+                is_restricted: false,
                 fs,
                 next_entry_id: Default::default(),
                 next_diagnostic_group_id: Default::default(),
@@ -779,14 +958,17 @@ impl Project {
                     local_handles: Vec::new(),
                 },
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+                settings_lsp_adapters: HashSet::default(),
                 node: Some(node),
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
                 prettier_instances: HashMap::default(),
                 tasks,
+                dap_store,
                 hosted_project_id: None,
                 dev_server_project_id: None,
                 search_history: Self::new_search_history(),
+                search_indices: HashMap::default(),
             }
         })
     }
@@ -851,6 +1033,7 @@ impl Project {
         let this = cx.new_model(|cx| {
             let replica_id = response.payload.replica_id as ReplicaId;
             let tasks = Inventory::new(cx);
+            let dap_store = cx.new_model(|_| DapStore::new());
             // BIG CAUTION NOTE: The order in which we initialize fields here matters and it should match what's done in Self::local.
             // Otherwise, you might run into issues where worktree id on remote is different than what's on local host.
             // That's because Worktree's identifier is entity id, which should probably be changed.
@@ -884,6 +1067,8 @@ impl Project {
                 languages,
                 user_store: user_store.clone(),
                 fs,
+                local_history: None,
+                is_restricted: false,
                 next_entry_id: Default::default(),
                 next_diagnostic_group_id: Default::default(),
                 diagnostic_summaries: Default::default(),
@@ -929,22 +1114,28 @@ impl Project {
                 buffers_needing_diff: Default::default(),
                 git_diff_debouncer: DebouncedDelay::new(),
                 buffer_snapshots: Default::default(),
+                semantic_tokens: Default::default(),
+                pulled_diagnostics: Default::default(),
+                diagnostic_pulls: Default::default(),
                 nonce: StdRng::from_entropy().gen(),
                 terminals: Terminals {
                     local_handles: Vec::new(),
                 },
                 current_lsp_settings: ProjectSettings::get_global(cx).lsp.clone(),
+                settings_lsp_adapters: HashSet::default(),
                 node: None,
                 default_prettier: DefaultPrettier::default(),
                 prettiers_per_worktree: HashMap::default(),
                 prettier_instances: HashMap::default(),
                 tasks,
+                dap_store,
                 hosted_project_id: None,
                 dev_server_project_id: response
                     .payload
                     .dev_server_project_id
                     .map(|dev_server_project_id| DevServerProjectId(dev_server_project_id)),
                 search_history: Self::new_search_history(),
+                search_indices: HashMap::default(),
             };
             this.set_role(role, cx);
             for worktree in worktrees {
@@ -1225,9 +1416,38 @@ impl Project {
             self.restart_language_servers(worktree, language, cx);
         }
 
+        self.maintain_search_indices(cx);
+
         cx.notify();
     }
 
+    /// Starts indexing the visible local worktrees for project search when the search index is
+    /// enabled, and drops their indices when it isn't.
+    fn maintain_search_indices(&mut self, cx: &mut ModelContext<Self>) {
+        let settings = ProjectSettings::get_global(cx).search_index;
+        if !settings.enabled {
+            self.search_indices.clear();
+            return;
+        }
+
+        let worktrees = self
+            .visible_worktrees(cx)
+            .filter(|worktree| worktree.read(cx).is_local())
+            .collect::<Vec<_>>();
+        for worktree in worktrees {
+            let worktree = worktree.read(cx);
+            if !self.search_indices.contains_key(&worktree.id()) {
+                let index = WorktreeSearchIndex::new(
+                    &worktree.snapshot(),
+                    self.fs.clone(),
+                    settings.max_file_size,
+                    cx,
+                );
+                self.search_indices.insert(worktree.id(), index);
+            }
+        }
+    }
+
     pub fn buffer_for_id(&self, remote_id: BufferId) -> Option<Model<Buffer>> {
         self.opened_buffers
             .get(&remote_id)
@@ -1280,6 +1500,27 @@ impl Project {
         &self.fs
     }
 
+    pub fn local_history(&self) -> Option<&Arc<LocalHistory>> {
+        self.local_history.as_ref()
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.is_restricted
+    }
+
+    /// Restricts the project from starting language servers and running external formatters,
+    /// starting the ones its open buffers need once the restriction is lifted.
+    pub fn set_restricted(&mut self, restricted: bool, cx: &mut ModelContext<Self>) {
+        if self.is_restricted == restricted {
+            return;
+        }
+        self.is_restricted = restricted;
+        if !restricted {
+            self.on_settings_changed(cx);
+        }
+        cx.notify();
+    }
+
     pub fn remote_id(&self) -> Option<u64> {
         match self.client_state {
             ProjectClientState::Local => None,
@@ -1342,6 +1583,11 @@ impl Project {
         &self.tasks
     }
 
+    /// The breakpoints set in the project's files, and its debug sessions.
+    pub fn dap_store(&self) -> &Model<DapStore> {
+        &self.dap_store
+    }
+
     pub fn search_history(&self) -> &SearchHistory {
         &self.search_history
     }
@@ -2458,6 +2704,14 @@ impl Project {
 
         let text = buffer.as_rope().clone();
         let version = buffer.version();
+        let history_settings = ProjectSettings::get_global(cx).local_history;
+        let local_history = self.local_history.clone().filter(|_| {
+            history_settings.enabled
+                && history_settings.max_entries > 0
+                && !worktree.is_path_private(&path)
+                && text.len() <= history_settings.max_file_size
+        });
+        let history_text = local_history.as_ref().map(|_| text.clone());
         let save = worktree.write_file(path.as_ref(), text, buffer.line_ending(), cx);
         let fs = Arc::clone(&self.fs);
         let abs_path = worktree.absolutize(&path);
@@ -2526,6 +2780,13 @@ impl Project {
                 buffer.did_save(version.clone(), mtime, cx);
             })?;
 
+            if let Some((local_history, text)) = local_history.zip(history_text) {
+                local_history
+                    .record(&abs_path, text.to_string(), history_settings.max_entries)
+                    .await
+                    .log_err();
+            }
+
             Ok(())
         })
     }
@@ -2633,6 +2894,11 @@ impl Project {
                     self.local_buffer_ids_by_entry_id
                         .insert(entry_id, remote_id);
                 }
+
+                let abs_path = file.abs_path(cx);
+                self.dap_store.update(cx, |dap_store, cx| {
+                    dap_store.resolve_breakpoints(buffer, &abs_path, cx)
+                });
             }
         }
 
@@ -2642,7 +2908,11 @@ impl Project {
         cx.observe_release(buffer, |this, buffer, cx| {
             if let Some(file) = File::from_dyn(buffer.file()) {
                 if file.is_local() {
-                    let uri = lsp::Url::from_file_path(file.abs_path(cx)).unwrap();
+                    let abs_path = file.abs_path(cx);
+                    this.dap_store.update(cx, |dap_store, _| {
+                        dap_store.buffer_released(buffer, &abs_path)
+                    });
+                    let uri = lsp::Url::from_file_path(abs_path).unwrap();
                     for server in this.language_servers_for_buffer(buffer, cx) {
                         server
                             .1
@@ -2673,6 +2943,9 @@ impl Project {
     ) {
         let buffer = buffer_handle.read(cx);
         let buffer_id = buffer.remote_id();
+        if ProjectSettings::get_global(cx).is_large_file(buffer.len()) {
+            return;
+        }
 
         if let Some(file) = File::from_dyn(buffer.file()) {
             if !file.is_local() {
@@ -2748,6 +3021,7 @@ impl Project {
                         .insert(server.server_id(), vec![snapshot]);
                 }
             }
+            self.pull_document_diagnostics(buffer_handle, cx);
         }
     }
 
@@ -2775,7 +3049,12 @@ impl Project {
             }
 
             self.buffer_snapshots.remove(&buffer.remote_id());
+            self.semantic_tokens.remove(&buffer.remote_id());
+            self.diagnostic_pulls.remove(&buffer.remote_id());
             let file_url = lsp::Url::from_file_path(old_path).unwrap();
+            for pulled_diagnostics in self.pulled_diagnostics.values_mut() {
+                pulled_diagnostics.remove(&file_url);
+            }
             for (_, language_server) in self.language_servers_for_buffer(buffer, cx) {
                 language_server
                     .notify::<lsp::notification::DidCloseTextDocument>(
@@ -2918,6 +3197,9 @@ impl Project {
         ) {
             self.request_buffer_diff_recalculation(&buffer, cx);
         }
+        if matches!(event, BufferEvent::Edited { .. }) {
+            self.pull_document_diagnostics(&buffer, cx);
+        }
 
         match event {
             BufferEvent::Operation(operation) => {
@@ -3062,6 +3344,7 @@ impl Project {
 
                 for language_server_id in self.language_server_ids_for_buffer(buffer.read(cx), cx) {
                     self.simulate_disk_based_diagnostics_events_if_needed(language_server_id, cx);
+                    self.pull_workspace_diagnostics(language_server_id, cx);
                 }
             }
             BufferEvent::FileHandleChanged => {
@@ -3353,6 +3636,20 @@ impl Project {
         &mut self,
         buffer_handle: &Model<Buffer>,
         cx: &mut ModelContext<Self>,
+    ) {
+        // Large files are left as plain text, until their language is requested explicitly.
+        if ProjectSettings::get_global(cx).is_large_file(buffer_handle.read(cx).len()) {
+            return;
+        }
+        self.assign_detected_language(buffer_handle, cx);
+    }
+
+    /// Detects the language of the buffer from its file and contents, and sets it, even if the
+    /// buffer is a large file.
+    pub fn assign_detected_language(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
     ) {
         // If the buffer has a language, set it and start the language server if we haven't already.
         let buffer = buffer_handle.read(cx);
@@ -3405,6 +3702,9 @@ impl Project {
                 cx,
             );
         };
+        if ProjectSettings::get_global(cx).is_large_file(buffer.read(cx).len()) {
+            return;
+        }
         if let Some(file) = buffer_file {
             let worktree = file.worktree.clone();
             if worktree.read(cx).is_local() {
@@ -3419,12 +3719,18 @@ impl Project {
         language: Arc<Language>,
         cx: &mut ModelContext<Self>,
     ) {
+        if self.is_restricted {
+            return;
+        }
         let root_file = worktree.update(cx, |tree, cx| tree.root_file(cx));
         let settings = language_settings(Some(&language), root_file.map(|f| f as _).as_ref(), cx);
         if !settings.enable_language_server {
             return;
         }
 
+        let lsp_settings = ProjectSettings::get_global(cx).lsp.clone();
+        self.register_settings_lsp_adapters(&language, &lsp_settings);
+
         let available_lsp_adapters = self.languages.clone().lsp_adapters(&language);
         let available_language_servers = available_lsp_adapters
             .iter()
@@ -3474,6 +3780,51 @@ impl Project {
         }
     }
 
+    /// Registers the language servers defined in the settings for the language, and unregisters
+    /// the previously registered ones whose definition no longer applies to it. Only the user's
+    /// settings can define servers, as the language registry is shared by every project.
+    fn register_settings_lsp_adapters(
+        &mut self,
+        language: &Arc<Language>,
+        lsp_settings: &HashMap<Arc<str>, LspSettings>,
+    ) {
+        let language_name = language.name();
+        let languages = &self.languages;
+        self.settings_lsp_adapters
+            .retain(|(registered_language, server_name)| {
+                if *registered_language != language_name {
+                    return true;
+                }
+                let is_defined = lsp_settings.get(&server_name.0).map_or(false, |settings| {
+                    SettingsLspAdapter::is_defined_for(settings, &language_name)
+                });
+                if !is_defined {
+                    languages.remove_lsp_adapter(&language_name, server_name);
+                }
+                is_defined
+            });
+
+        let registered_adapters = self.languages.lsp_adapters(language);
+        for (server_name, settings) in lsp_settings {
+            if !SettingsLspAdapter::is_defined_for(settings, &language_name) {
+                continue;
+            }
+            let server_name = LanguageServerName(server_name.clone());
+            if registered_adapters
+                .iter()
+                .any(|adapter| adapter.name == server_name)
+            {
+                continue;
+            }
+            self.languages.register_lsp_adapter(
+                language_name.clone(),
+                Arc::new(SettingsLspAdapter::new(server_name.clone())),
+            );
+            self.settings_lsp_adapters
+                .insert((language_name.clone(), server_name));
+        }
+    }
+
     fn start_language_server(
         &mut self,
         worktree_handle: &Model<Worktree>,
@@ -3717,6 +4068,9 @@ impl Project {
                     if let Some(this) = this.upgrade() {
                         adapter.process_diagnostics(&mut params);
                         this.update(&mut cx, |this, cx| {
+                            if !this.accepts_pushed_diagnostics(server_id, &params.uri, cx) {
+                                return;
+                            }
                             this.update_diagnostics(
                                 server_id,
                                 params,
@@ -3864,6 +4218,21 @@ impl Project {
             })
             .detach();
 
+        language_server
+            .on_request::<lsp::request::WorkspaceDiagnosticRefresh, _, _>({
+                let this = this.clone();
+                move |(), mut cx| {
+                    let this = this.clone();
+                    async move {
+                        this.update(&mut cx, |project, cx| {
+                            project.refresh_pulled_diagnostics(server_id, cx);
+                        })?;
+                        Ok(())
+                    }
+                }
+            })
+            .detach();
+
         language_server
             .on_request::<lsp::request::ShowMessageRequest, _, _>({
                 let this = this.clone();
@@ -4046,6 +4415,9 @@ impl Project {
         for buffer in self.opened_buffers.values() {
             if let Some(buffer_handle) = buffer.upgrade() {
                 let buffer = buffer_handle.read(cx);
+                if ProjectSettings::get_global(cx).is_large_file(buffer.len()) {
+                    continue;
+                }
                 let file = match File::from_dyn(buffer.file()) {
                     Some(file) => file,
                     None => continue,
@@ -4110,6 +4482,7 @@ impl Project {
                 });
             }
         }
+        self.refresh_pulled_diagnostics(server_id, cx);
 
         cx.notify();
         Ok(())
@@ -4186,6 +4559,7 @@ impl Project {
 
             self.language_server_watched_paths.remove(&server_id);
             self.language_server_statuses.remove(&server_id);
+            self.pulled_diagnostics.remove(&server_id);
             cx.notify();
 
             let server_state = self.language_servers.remove(&server_id);
@@ -4292,6 +4666,61 @@ impl Project {
         }
     }
 
+    async fn handle_cancel_language_server_work(
+        project: Model<Self>,
+        envelope: TypedEnvelope<proto::CancelLanguageServerWork>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::Ack> {
+        project.update(&mut cx, |project, cx| {
+            project.cancel_language_server_work(
+                LanguageServerId(envelope.payload.language_server_id as usize),
+                envelope.payload.token,
+                cx,
+            )
+        })?;
+
+        Ok(proto::Ack {})
+    }
+
+    /// Asks a language server to cancel the work it reported progress for under the given token.
+    pub fn cancel_language_server_work(
+        &mut self,
+        server_id: LanguageServerId,
+        token: String,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if self.is_remote() {
+            let request = self.client.request(proto::CancelLanguageServerWork {
+                project_id: self.remote_id().unwrap(),
+                language_server_id: server_id.0 as u64,
+                token,
+            });
+            cx.background_executor()
+                .spawn(request)
+                .detach_and_log_err(cx);
+            return;
+        }
+
+        let is_cancellable = self
+            .language_server_statuses
+            .get(&server_id)
+            .and_then(|status| status.pending_work.get(&token))
+            .map_or(false, |work| work.is_cancellable);
+        if !is_cancellable {
+            return;
+        }
+        if let Some(server) = self.language_server_for_id(server_id) {
+            server
+                .notify::<lsp::notification::WorkDoneProgressCancel>(
+                    lsp::WorkDoneProgressCancelParams {
+                        token: lsp::NumberOrString::String(token),
+                    },
+                )
+                .log_err();
+        }
+    }
+
     fn restart_language_servers(
         &mut self,
         worktree: Model<Worktree>,
@@ -4489,8 +4918,10 @@ impl Project {
                         language_server_id,
                         token.clone(),
                         LanguageServerProgress {
+                            title: Some(report.title),
                             message: report.message.clone(),
                             percentage: report.percentage.map(|p| p as usize),
+                            is_cancellable: report.cancellable.unwrap_or(false),
                             last_update_at: Instant::now(),
                         },
                         cx,
@@ -4499,12 +4930,20 @@ impl Project {
             }
             lsp::WorkDoneProgress::Report(report) => {
                 if !is_disk_based_diagnostics_progress {
+                    let is_cancellable = report.cancellable.unwrap_or_else(|| {
+                        language_server_status
+                            .pending_work
+                            .get(&token)
+                            .map_or(false, |work| work.is_cancellable)
+                    });
                     self.on_lsp_work_progress(
                         language_server_id,
                         token.clone(),
                         LanguageServerProgress {
+                            title: None,
                             message: report.message.clone(),
                             percentage: report.percentage.map(|p| p as usize),
+                            is_cancellable,
                             last_update_at: Instant::now(),
                         },
                         cx,
@@ -4517,6 +4956,7 @@ impl Project {
                                     token,
                                     message: report.message,
                                     percentage: report.percentage,
+                                    is_cancellable: Some(is_cancellable),
                                 },
                             ),
                         },
@@ -4555,6 +4995,8 @@ impl Project {
                     token,
                     message: progress.message,
                     percentage: progress.percentage.map(|p| p as u32),
+                    title: progress.title,
+                    is_cancellable: progress.is_cancellable,
                 }),
             })
             .ok();
@@ -4573,16 +5015,22 @@ impl Project {
                 .pending_work
                 .entry(token)
                 .or_insert(LanguageServerProgress {
+                    title: Default::default(),
                     message: Default::default(),
                     percentage: Default::default(),
+                    is_cancellable: Default::default(),
                     last_update_at: progress.last_update_at,
                 });
+            if progress.title.is_some() {
+                entry.title = progress.title;
+            }
             if progress.message.is_some() {
                 entry.message = progress.message;
             }
             if progress.percentage.is_some() {
                 entry.percentage = progress.percentage;
             }
+            entry.is_cancellable = progress.is_cancellable;
             entry.last_update_at = progress.last_update_at;
             cx.notify();
         }
@@ -4772,78 +5220,423 @@ impl Project {
         self.language_server_statuses.values()
     }
 
+    /// Returns the statuses of the language servers along with their ids, which are needed to
+    /// act on their pending work.
+    pub fn language_server_statuses_by_id(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = (LanguageServerId, &LanguageServerStatus)> {
+        self.language_server_statuses
+            .iter()
+            .map(|(id, status)| (*id, status))
+    }
+
     pub fn last_formatting_failure(&self) -> Option<&str> {
         self.last_formatting_failure.as_deref()
     }
 
-    pub fn update_diagnostics(
-        &mut self,
-        language_server_id: LanguageServerId,
-        mut params: lsp::PublishDiagnosticsParams,
-        disk_based_sources: &[String],
-        cx: &mut ModelContext<Self>,
-    ) -> Result<()> {
-        let abs_path = params
-            .uri
-            .to_file_path()
-            .map_err(|_| anyhow!("URI is not a file"))?;
-        let mut diagnostics = Vec::default();
-        let mut primary_diagnostic_group_ids = HashMap::default();
-        let mut sources_by_group_id = HashMap::default();
-        let mut supporting_diagnostics = HashMap::default();
-
-        // Ensure that primary diagnostics are always the most severe
-        params.diagnostics.sort_by_key(|item| item.severity);
-
-        for diagnostic in &params.diagnostics {
-            let source = diagnostic.source.as_ref();
-            let code = diagnostic.code.as_ref().map(|code| match code {
-                lsp::NumberOrString::Number(code) => code.to_string(),
-                lsp::NumberOrString::String(code) => code.clone(),
-            });
-            let range = range_from_lsp(diagnostic.range);
-            let is_supporting = diagnostic
-                .related_information
-                .as_ref()
-                .map_or(false, |infos| {
-                    infos.iter().any(|info| {
-                        primary_diagnostic_group_ids.contains_key(&(
-                            source,
-                            code.clone(),
-                            range_from_lsp(info.location.range),
-                        ))
-                    })
-                });
+    /// The options for pulling diagnostics from a running language server, unless it doesn't
+    /// support pulling them or is configured to push them.
+    fn pull_diagnostics_options(
+        &self,
+        server_id: LanguageServerId,
+        cx: &AppContext,
+    ) -> Option<(
+        Arc<CachedLspAdapter>,
+        Arc<LanguageServer>,
+        lsp::DiagnosticOptions,
+    )> {
+        let LanguageServerState::Running {
+            adapter, server, ..
+        } = self.language_servers.get(&server_id)?
+        else {
+            return None;
+        };
+        if Self::diagnostics_model(adapter, cx) == DiagnosticsModel::Push {
+            return None;
+        }
+        let options = match server.capabilities().diagnostic_provider.clone()? {
+            lsp::DiagnosticServerCapabilities::Options(options) => options,
+            lsp::DiagnosticServerCapabilities::RegistrationOptions(options) => {
+                options.diagnostic_options
+            }
+        };
+        Some((adapter.clone(), server.clone(), options))
+    }
 
-            let is_unnecessary = diagnostic.tags.as_ref().map_or(false, |tags| {
-                tags.iter().any(|tag| *tag == DiagnosticTag::UNNECESSARY)
-            });
+    fn diagnostics_model(adapter: &CachedLspAdapter, cx: &AppContext) -> DiagnosticsModel {
+        ProjectSettings::get_global(cx)
+            .lsp
+            .get(&adapter.name.0)
+            .and_then(|settings| settings.diagnostics)
+            .unwrap_or_default()
+    }
 
-            if is_supporting {
-                supporting_diagnostics.insert(
-                    (source, code.clone(), range),
-                    (diagnostic.severity, is_unnecessary),
-                );
-            } else {
-                let group_id = post_inc(&mut self.next_diagnostic_group_id);
-                let is_disk_based =
-                    source.map_or(false, |source| disk_based_sources.contains(source));
+    /// Whether diagnostics published by a language server should replace the current ones of a
+    /// document. Servers that are configured to only be pulled from are ignored, as are the
+    /// documents whose diagnostics were pulled, which are more up to date.
+    fn accepts_pushed_diagnostics(
+        &self,
+        server_id: LanguageServerId,
+        uri: &lsp::Url,
+        cx: &AppContext,
+    ) -> bool {
+        let Some((adapter, _, _)) = self.pull_diagnostics_options(server_id, cx) else {
+            return true;
+        };
+        match Self::diagnostics_model(&adapter, cx) {
+            DiagnosticsModel::Pull => false,
+            DiagnosticsModel::Auto | DiagnosticsModel::Push => !self
+                .pulled_diagnostics
+                .get(&server_id)
+                .map_or(false, |pulled| pulled.contains_key(uri)),
+        }
+    }
 
-                sources_by_group_id.insert(group_id, source);
-                primary_diagnostic_group_ids
-                    .insert((source, code.clone(), range.clone()), group_id);
+    /// Pulls the diagnostics of a buffer from those of its language servers that support it,
+    /// shortly after the buffer stops changing.
+    fn pull_document_diagnostics(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let buffer_id = buffer_handle.read(cx).remote_id();
+        if self
+            .document_diagnostic_requests(buffer_handle.read(cx), cx)
+            .map_or(true, |(_, requests)| requests.is_empty())
+        {
+            return;
+        }
 
-                diagnostics.push(DiagnosticEntry {
-                    range,
-                    diagnostic: Diagnostic {
-                        source: diagnostic.source.clone(),
-                        code: code.clone(),
+        let buffer_handle = buffer_handle.downgrade();
+        let task = cx.spawn(move |this, mut cx| async move {
+            cx.background_executor()
+                .timer(DIAGNOSTIC_PULL_DEBOUNCE)
+                .await;
+            // The version is read when the request is sent, after the server was told of any
+            // edits made since the pull was scheduled.
+            let Some((uri, requests)) = this
+                .update(&mut cx, |this, cx| {
+                    this.document_diagnostic_requests(buffer_handle.upgrade()?.read(cx), cx)
+                })
+                .ok()
+                .flatten()
+            else {
+                return;
+            };
+            let responses = join_all(requests.into_iter().map(
+                |(adapter, server, params, version)| async move {
+                    let response = server
+                        .request::<lsp::request::DocumentDiagnosticRequest>(params)
+                        .await
+                        .context("document diagnostics LSP request")
+                        .log_err();
+                    (adapter, server.server_id(), version, response)
+                },
+            ))
+            .await;
+            this.update(&mut cx, |this, cx| {
+                for (adapter, server_id, version, response) in responses {
+                    let report = match response {
+                        Some(lsp::DocumentDiagnosticReportResult::Report(report)) => report,
+                        Some(lsp::DocumentDiagnosticReportResult::Partial(partial)) => {
+                            for (uri, report) in partial.related_documents.into_iter().flatten() {
+                                this.apply_pulled_diagnostics(
+                                    &adapter, server_id, uri, None, report, cx,
+                                );
+                            }
+                            continue;
+                        }
+                        None => continue,
+                    };
+                    let (report, related_documents) = match report {
+                        lsp::DocumentDiagnosticReport::Full(report) => (
+                            lsp::DocumentDiagnosticReportKind::Full(
+                                report.full_document_diagnostic_report,
+                            ),
+                            report.related_documents,
+                        ),
+                        lsp::DocumentDiagnosticReport::Unchanged(report) => (
+                            lsp::DocumentDiagnosticReportKind::Unchanged(
+                                report.unchanged_document_diagnostic_report,
+                            ),
+                            report.related_documents,
+                        ),
+                    };
+                    this.apply_pulled_diagnostics(
+                        &adapter,
+                        server_id,
+                        uri.clone(),
+                        Some(version),
+                        report,
+                        cx,
+                    );
+                    for (uri, report) in related_documents.into_iter().flatten() {
+                        this.apply_pulled_diagnostics(&adapter, server_id, uri, None, report, cx);
+                    }
+                }
+            })
+            .ok();
+        });
+        self.diagnostic_pulls.insert(buffer_id, task);
+    }
+
+    /// The requests for the diagnostics of the buffer at its latest version, as the language
+    /// servers that support pulling them know it.
+    #[allow(clippy::type_complexity)]
+    fn document_diagnostic_requests(
+        &self,
+        buffer: &Buffer,
+        cx: &AppContext,
+    ) -> Option<(
+        lsp::Url,
+        Vec<(
+            Arc<CachedLspAdapter>,
+            Arc<LanguageServer>,
+            lsp::DocumentDiagnosticParams,
+            i32,
+        )>,
+    )> {
+        let file = File::from_dyn(buffer.file()).and_then(File::as_local)?;
+        let uri = lsp::Url::from_file_path(file.abs_path(cx)).unwrap();
+        let requests = self
+            .language_server_ids_for_buffer(buffer, cx)
+            .into_iter()
+            .filter_map(|server_id| {
+                let (adapter, server, options) = self.pull_diagnostics_options(server_id, cx)?;
+                let version = self
+                    .buffer_snapshots
+                    .get(&buffer.remote_id())?
+                    .get(&server_id)?
+                    .last()?
+                    .version;
+                let previous_result_id = self
+                    .pulled_diagnostics
+                    .get(&server_id)
+                    .and_then(|pulled| pulled.get(&uri).cloned())
+                    .flatten();
+                let params = lsp::DocumentDiagnosticParams {
+                    text_document: lsp::TextDocumentIdentifier::new(uri.clone()),
+                    identifier: options.identifier,
+                    previous_result_id,
+                    work_done_progress_params: Default::default(),
+                    partial_result_params: Default::default(),
+                };
+                Some((adapter, server, params, version))
+            })
+            .collect();
+        Some((uri, requests))
+    }
+
+    /// Pulls the diagnostics of the whole workspace from a language server, if it supports it.
+    fn pull_workspace_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        let Some((adapter, server, options)) = self.pull_diagnostics_options(server_id, cx) else {
+            return;
+        };
+        if !options.workspace_diagnostics {
+            return;
+        }
+        let previous_result_ids = self
+            .pulled_diagnostics
+            .get(&server_id)
+            .into_iter()
+            .flatten()
+            .filter_map(|(uri, result_id)| {
+                Some(lsp::PreviousResultId {
+                    uri: uri.clone(),
+                    value: result_id.clone()?,
+                })
+            })
+            .collect();
+        let params = lsp::WorkspaceDiagnosticParams {
+            identifier: options.identifier,
+            previous_result_ids,
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        cx.spawn(move |this, mut cx| async move {
+            let items = match server
+                .request::<lsp::request::WorkspaceDiagnosticRequest>(params)
+                .await
+                .context("workspace diagnostics LSP request")?
+            {
+                lsp::WorkspaceDiagnosticReportResult::Report(report) => report.items,
+                lsp::WorkspaceDiagnosticReportResult::Partial(partial) => partial.items,
+            };
+            this.update(&mut cx, |this, cx| {
+                for item in items {
+                    let (uri, version, report) = match item {
+                        lsp::WorkspaceDocumentDiagnosticReport::Full(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Full(
+                                report.full_document_diagnostic_report,
+                            ),
+                        ),
+                        lsp::WorkspaceDocumentDiagnosticReport::Unchanged(report) => (
+                            report.uri,
+                            report.version,
+                            lsp::DocumentDiagnosticReportKind::Unchanged(
+                                report.unchanged_document_diagnostic_report,
+                            ),
+                        ),
+                    };
+                    let version = version.and_then(|version| i32::try_from(version).ok());
+                    this.apply_pulled_diagnostics(&adapter, server_id, uri, version, report, cx);
+                }
+            })
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Pulls the diagnostics of every open buffer a language server is running for, and of
+    /// the whole workspace.
+    fn refresh_pulled_diagnostics(
+        &mut self,
+        server_id: LanguageServerId,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if self.pull_diagnostics_options(server_id, cx).is_none() {
+            return;
+        }
+        let buffers = self
+            .opened_buffers
+            .values()
+            .filter_map(|buffer| buffer.upgrade())
+            .filter(|buffer| {
+                self.language_server_ids_for_buffer(buffer.read(cx), cx)
+                    .contains(&server_id)
+            })
+            .collect::<Vec<_>>();
+        for buffer in buffers {
+            self.pull_document_diagnostics(&buffer, cx);
+        }
+        self.pull_workspace_diagnostics(server_id, cx);
+    }
+
+    fn apply_pulled_diagnostics(
+        &mut self,
+        adapter: &CachedLspAdapter,
+        server_id: LanguageServerId,
+        uri: lsp::Url,
+        version: Option<i32>,
+        report: lsp::DocumentDiagnosticReportKind,
+        cx: &mut ModelContext<Self>,
+    ) {
+        if !self.language_servers.contains_key(&server_id) {
+            return;
+        }
+        let pulled = self.pulled_diagnostics.entry(server_id).or_default();
+        match report {
+            lsp::DocumentDiagnosticReportKind::Full(report) => {
+                pulled.insert(uri.clone(), report.result_id);
+                let mut params = lsp::PublishDiagnosticsParams {
+                    uri,
+                    diagnostics: report.items,
+                    version,
+                };
+                adapter.process_diagnostics(&mut params);
+                self.update_diagnostics(
+                    server_id,
+                    params,
+                    &adapter.disk_based_diagnostic_sources,
+                    cx,
+                )
+                .log_err();
+            }
+            lsp::DocumentDiagnosticReportKind::Unchanged(report) => {
+                pulled.insert(uri, Some(report.result_id));
+            }
+        }
+    }
+
+    pub fn update_diagnostics(
+        &mut self,
+        language_server_id: LanguageServerId,
+        mut params: lsp::PublishDiagnosticsParams,
+        disk_based_sources: &[String],
+        cx: &mut ModelContext<Self>,
+    ) -> Result<()> {
+        let abs_path = params
+            .uri
+            .to_file_path()
+            .map_err(|_| anyhow!("URI is not a file"))?;
+        let mut diagnostics = Vec::default();
+        let mut primary_diagnostic_group_ids = HashMap::default();
+        let mut sources_by_group_id = HashMap::default();
+        let mut supporting_diagnostics = HashMap::default();
+
+        // Ensure that primary diagnostics are always the most severe
+        params.diagnostics.sort_by_key(|item| item.severity);
+
+        for diagnostic in &params.diagnostics {
+            let source = diagnostic.source.as_ref();
+            let code = diagnostic.code.as_ref().map(|code| match code {
+                lsp::NumberOrString::Number(code) => code.to_string(),
+                lsp::NumberOrString::String(code) => code.clone(),
+            });
+            let range = range_from_lsp(diagnostic.range);
+            let is_supporting = diagnostic
+                .related_information
+                .as_ref()
+                .map_or(false, |infos| {
+                    infos.iter().any(|info| {
+                        primary_diagnostic_group_ids.contains_key(&(
+                            source,
+                            code.clone(),
+                            range_from_lsp(info.location.range),
+                        ))
+                    })
+                });
+
+            let is_unnecessary = diagnostic.tags.as_ref().map_or(false, |tags| {
+                tags.iter().any(|tag| *tag == DiagnosticTag::UNNECESSARY)
+            });
+
+            if is_supporting {
+                supporting_diagnostics.insert(
+                    (source, code.clone(), range),
+                    (diagnostic.severity, is_unnecessary),
+                );
+            } else {
+                let group_id = post_inc(&mut self.next_diagnostic_group_id);
+                let is_disk_based =
+                    source.map_or(false, |source| disk_based_sources.contains(source));
+
+                sources_by_group_id.insert(group_id, source);
+                primary_diagnostic_group_ids
+                    .insert((source, code.clone(), range.clone()), group_id);
+
+                // Related locations in this file become supporting diagnostics of the group below,
+                // while those in other files are kept on the primary diagnostic.
+                let related_information = diagnostic
+                    .related_information
+                    .iter()
+                    .flatten()
+                    .filter(|info| info.location.uri != params.uri)
+                    .filter_map(|info| {
+                        Some(DiagnosticRelatedInformation {
+                            abs_path: info.location.uri.to_file_path().ok()?.into(),
+                            range: range_from_lsp(info.location.range),
+                            message: info.message.trim().to_string(),
+                        })
+                    })
+                    .collect();
+                diagnostics.push(DiagnosticEntry {
+                    range,
+                    diagnostic: Diagnostic {
+                        source: diagnostic.source.clone(),
+                        code: code.clone(),
                         severity: diagnostic.severity.unwrap_or(DiagnosticSeverity::ERROR),
                         message: diagnostic.message.trim().to_string(),
                         group_id,
                         is_primary: true,
                         is_disk_based,
                         is_unnecessary,
+                        related_information,
                     },
                 });
                 if let Some(infos) = &diagnostic.related_information {
@@ -4861,6 +5654,7 @@ impl Project {
                                     is_primary: false,
                                     is_disk_based,
                                     is_unnecessary: false,
+                                    related_information: Vec::new(),
                                 },
                             });
                         }
@@ -4913,7 +5707,14 @@ impl Project {
             path: relative_path.into(),
         };
 
-        if let Some(buffer) = self.get_open_buffer(&project_path, cx) {
+        let buffer = self.get_open_buffer(&project_path, cx);
+        let diagnostics = if self.shows_diagnostics_from(server_id, buffer.as_ref(), cx) {
+            diagnostics
+        } else {
+            Vec::new()
+        };
+
+        if let Some(buffer) = buffer {
             self.update_buffer_diagnostics(&buffer, server_id, version, diagnostics.clone(), cx)?;
         }
 
@@ -4935,6 +5736,41 @@ impl Project {
         Ok(())
     }
 
+    /// Whether to show the diagnostics of the language server, according to the
+    /// `language_server_routing` settings of the language of the given buffer, or
+    /// else of the language the server was started for.
+    fn shows_diagnostics_from(
+        &self,
+        server_id: LanguageServerId,
+        buffer: Option<&Model<Buffer>>,
+        cx: &AppContext,
+    ) -> bool {
+        let Some(LanguageServerState::Running {
+            language, adapter, ..
+        }) = self.language_servers.get(&server_id)
+        else {
+            return true;
+        };
+        let buffer = buffer.map(|buffer| buffer.read(cx));
+        let language = buffer
+            .and_then(|buffer| buffer.language())
+            .unwrap_or(language);
+        let file = buffer.and_then(|buffer| buffer.file());
+
+        let mut available_servers = self
+            .languages
+            .lsp_adapters(language)
+            .iter()
+            .map(|adapter| adapter.name.clone())
+            .collect::<Vec<_>>();
+        if !available_servers.contains(&adapter.name) {
+            available_servers.push(adapter.name.clone());
+        }
+        language_settings(Some(language), file, cx)
+            .language_server_routing
+            .shows_diagnostics_from(&adapter.name, &available_servers)
+    }
+
     pub fn update_worktree_diagnostics(
         &mut self,
         worktree_id: WorktreeId,
@@ -5238,7 +6074,7 @@ impl Project {
 
         let mut project_transaction = ProjectTransaction::default();
         for (buffer, buffer_abs_path) in &buffers_with_paths {
-            let (primary_adapter_and_server, adapters_and_servers) =
+            let (formatting_adapter_and_server, adapters_and_servers) =
                 project.update(&mut cx, |project, cx| {
                     let buffer = buffer.read(cx);
 
@@ -5247,11 +6083,11 @@ impl Project {
                         .map(|(adapter, lsp)| (adapter.clone(), lsp.clone()))
                         .collect::<Vec<_>>();
 
-                    let primary_adapter = project
-                        .primary_language_server_for_buffer(buffer, cx)
+                    let formatting_adapter = project
+                        .formatting_language_server_for_buffer(buffer, cx)
                         .map(|(adapter, lsp)| (adapter.clone(), lsp.clone()));
 
-                    (primary_adapter, adapters_and_servers)
+                    (formatting_adapter, adapters_and_servers)
                 })?;
 
             let settings = buffer.update(&mut cx, |buffer, cx| {
@@ -5302,12 +6138,12 @@ impl Project {
                 .await?;
             }
 
-            // Apply language-specific formatting using either the primary language server
+            // Apply language-specific formatting using either the formatting language server
             // or external command.
             // Except for code actions, which are applied with all connected language servers.
-            let primary_language_server =
-                primary_adapter_and_server.map(|(_adapter, server)| server.clone());
-            let server_and_buffer = primary_language_server
+            let formatting_language_server =
+                formatting_adapter_and_server.map(|(_adapter, server)| server.clone());
+            let server_and_buffer = formatting_language_server
                 .as_ref()
                 .zip(buffer_abs_path.as_ref());
 
@@ -5317,6 +6153,8 @@ impl Project {
                     .prettier
                     .clone()
             })?;
+            // Formatting with external commands and Prettier runs code from the project.
+            let is_restricted = project.update(&mut cx, |project, _| project.is_restricted)?;
             match (&settings.formatter, &settings.format_on_save) {
                 (_, FormatOnSave::Off) if trigger == FormatTrigger::Save => {}
 
@@ -5354,6 +6192,9 @@ impl Project {
                     }
                 }
 
+                (Formatter::External { .. }, FormatOnSave::On | FormatOnSave::Off)
+                | (_, FormatOnSave::External { .. })
+                    if is_restricted => {}
                 (
                     Formatter::External { command, arguments },
                     FormatOnSave::On | FormatOnSave::Off,
@@ -5375,7 +6216,7 @@ impl Project {
                     .map(FormatOperation::External);
                 }
                 (Formatter::Auto, FormatOnSave::On | FormatOnSave::Off) => {
-                    let prettier = if prettier_settings.allowed {
+                    let prettier = if prettier_settings.allowed && !is_restricted {
                         prettier_support::format_with_prettier(&project, buffer, &mut cx)
                             .await
                             .transpose()
@@ -5403,7 +6244,7 @@ impl Project {
                     }
                 }
                 (Formatter::Prettier, FormatOnSave::On | FormatOnSave::Off) => {
-                    if prettier_settings.allowed {
+                    if prettier_settings.allowed && !is_restricted {
                         if let Some(operation) =
                             prettier_support::format_with_prettier(&project, buffer, &mut cx).await
                         {
@@ -5645,6 +6486,104 @@ impl Project {
         self.implementation_impl(buffer, position, cx)
     }
 
+    /// Resolves the symbols of a call hierarchy at the given position, which are then asked for
+    /// their calls with [`Project::incoming_calls`] and [`Project::outgoing_calls`].
+    pub fn prepare_call_hierarchy<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareCallHierarchy { position },
+            cx,
+        )
+    }
+
+    /// Returns the calls made to a symbol of a call hierarchy, asking the language server of
+    /// the buffer the hierarchy was prepared in.
+    pub fn incoming_calls(
+        &self,
+        buffer: &Model<Buffer>,
+        item: CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetIncomingCalls { item },
+            cx,
+        )
+    }
+
+    /// Returns the calls made by a symbol of a call hierarchy, asking the language server of
+    /// the buffer the hierarchy was prepared in.
+    pub fn outgoing_calls(
+        &self,
+        buffer: &Model<Buffer>,
+        item: CallHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<CallHierarchyCall>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetOutgoingCalls { item },
+            cx,
+        )
+    }
+
+    /// Resolves the types of a type hierarchy at the given position, which are then asked for
+    /// their supertypes and subtypes with [`Project::supertypes`] and [`Project::subtypes`].
+    pub fn prepare_type_hierarchy<T: ToPointUtf16>(
+        &self,
+        buffer: &Model<Buffer>,
+        position: T,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            PrepareTypeHierarchy { position },
+            cx,
+        )
+    }
+
+    /// Returns the direct supertypes of a type, asking the language server of the buffer the
+    /// hierarchy was prepared in.
+    pub fn supertypes(
+        &self,
+        buffer: &Model<Buffer>,
+        item: TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSupertypes { item },
+            cx,
+        )
+    }
+
+    /// Returns the direct subtypes of a type, asking the language server of the buffer the
+    /// hierarchy was prepared in.
+    pub fn subtypes(
+        &self,
+        buffer: &Model<Buffer>,
+        item: TypeHierarchyItem,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<TypeHierarchyItem>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Other(item.server_id),
+            GetSubtypes { item },
+            cx,
+        )
+    }
+
     fn references_impl(
         &self,
         buffer: &Model<Buffer>,
@@ -6020,7 +6959,7 @@ impl Project {
             let scope = snapshot.language_scope_at(offset);
             let language = snapshot.language().cloned();
 
-            let server_ids: Vec<_> = self
+            let servers: Vec<_> = self
                 .language_servers_for_buffer(buffer.read(cx), cx)
                 .filter(|(_, server)| server.capabilities().completion_provider.is_some())
                 .filter(|(adapter, _)| {
@@ -6029,12 +6968,29 @@ impl Project {
                         .map(|scope| scope.language_allowed(&adapter.name))
                         .unwrap_or(true)
                 })
-                .map(|(_, server)| server.server_id())
                 .collect();
-
-            let buffer = buffer.clone();
-            cx.spawn(move |this, mut cx| async move {
-                let mut tasks = Vec::with_capacity(server_ids.len());
+            let available_servers = servers
+                .iter()
+                .map(|(adapter, _)| adapter.name.clone())
+                .collect::<Vec<_>>();
+            let routing = language_settings(language.as_ref(), buffer.read(cx).file(), cx)
+                .language_server_routing
+                .clone();
+            let completion_merge = routing.completion_merge;
+            let server_ids: Vec<_> = routing
+                .completion_servers(&available_servers)
+                .into_iter()
+                .filter_map(|server_name| {
+                    let (_, server) = servers
+                        .iter()
+                        .find(|(adapter, _)| adapter.name == server_name)?;
+                    Some(server.server_id())
+                })
+                .collect();
+
+            let buffer = buffer.clone();
+            cx.spawn(move |this, mut cx| async move {
+                let mut tasks = Vec::with_capacity(server_ids.len());
                 this.update(&mut cx, |this, cx| {
                     for server_id in server_ids {
                         let lsp_adapter = this.language_server_adapter_for_id(server_id);
@@ -6062,6 +7018,16 @@ impl Project {
                         )
                         .await;
                     }
+                    if completion_merge == CompletionMerge::First && !completions.is_empty() {
+                        break;
+                    }
+                }
+
+                if completion_merge == CompletionMerge::Deduplicate {
+                    let mut seen = HashSet::default();
+                    completions.retain(|completion| {
+                        seen.insert((completion.label.text.clone(), completion.new_text.clone()))
+                    });
                 }
 
                 Ok(completions)
@@ -6224,6 +7190,11 @@ impl Project {
             return;
         };
 
+        merge_resolved_completion_item(
+            &mut completions.write()[completion_index].lsp_completion,
+            &completion_item,
+        );
+
         if let Some(lsp_documentation) = completion_item.documentation.as_ref() {
             let documentation = language::prepare_completion_documentation(
                 lsp_documentation,
@@ -6303,6 +7274,13 @@ impl Project {
         let completion = &mut completions[completion_index];
         completion.documentation = Some(documentation);
 
+        if !response.lsp_completion.is_empty() {
+            if let Some(resolved_item) = serde_json::from_slice(&response.lsp_completion).log_err()
+            {
+                merge_resolved_completion_item(&mut completion.lsp_completion, &resolved_item);
+            }
+        }
+
         let old_range = response
             .old_start
             .and_then(deserialize_anchor)
@@ -6339,13 +7317,19 @@ impl Project {
                     .as_ref()
                     .and_then(|options| options.resolve_provider)
                     .unwrap_or(false);
+                // The completion's text has already been inserted by now, so some servers can no
+                // longer resolve it. Fall back to the edits that were stored when the completion
+                // was resolved for the menu.
+                let stored_edits = completion.lsp_completion.additional_text_edits.clone();
                 let additional_text_edits = if can_resolve {
                     lang_server
                         .request::<lsp::request::ResolveCompletionItem>(completion.lsp_completion)
-                        .await?
-                        .additional_text_edits
+                        .await
+                        .log_err()
+                        .and_then(|resolved| resolved.additional_text_edits)
+                        .or(stored_edits)
                 } else {
-                    completion.lsp_completion.additional_text_edits
+                    stored_edits
                 };
                 if let Some(edits) = additional_text_edits {
                     let edits = this
@@ -6607,6 +7591,718 @@ impl Project {
         }
     }
 
+    /// Fetches the code lenses of the whole buffer from all of its language servers.
+    pub fn code_lens(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Vec<CodeLens>> {
+        if self.is_local() {
+            let all_lenses_task = self.request_multiple_lsp_locally(
+                buffer_handle,
+                None::<usize>,
+                GetCodeLens::supports_code_lens,
+                GetCodeLens,
+                cx,
+            );
+            cx.spawn(|_, _| async move { all_lenses_task.await.into_iter().flatten().collect() })
+        } else if let Some(project_id) = self.remote_id() {
+            let request_task = self.client().request(proto::MultiLspQuery {
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                version: serialize_version(&buffer_handle.read(cx).version()),
+                project_id,
+                strategy: Some(proto::multi_lsp_query::Strategy::All(
+                    proto::AllLanguageServers {},
+                )),
+                request: Some(proto::multi_lsp_query::Request::GetCodeLens(
+                    GetCodeLens.to_proto(project_id, buffer_handle.read(cx)),
+                )),
+            });
+            let buffer = buffer_handle.clone();
+            cx.spawn(|weak_project, cx| async move {
+                let Some(project) = weak_project.upgrade() else {
+                    return Vec::new();
+                };
+                join_all(
+                    request_task
+                        .await
+                        .log_err()
+                        .map(|response| response.responses)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|lsp_response| match lsp_response.response? {
+                            proto::lsp_response::Response::GetCodeLensResponse(response) => {
+                                Some(response)
+                            }
+                            unexpected => {
+                                debug_panic!("Unexpected response: {unexpected:?}");
+                                None
+                            }
+                        })
+                        .map(|code_lens_response| {
+                            let response = GetCodeLens.response_from_proto(
+                                code_lens_response,
+                                project.clone(),
+                                buffer.clone(),
+                                cx.clone(),
+                            );
+                            async move { response.await.log_err().unwrap_or_default() }
+                        }),
+                )
+                .await
+                .into_iter()
+                .flatten()
+                .collect()
+            })
+        } else {
+            log::error!("cannot fetch code lenses: project does not have a remote id");
+            Task::ready(Vec::new())
+        }
+    }
+
+    /// Fills in the command of a code lens that was returned without one, if its language server
+    /// supports resolving code lenses.
+    pub fn resolve_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<CodeLens>> {
+        if lens.lsp_lens.command.is_some() {
+            return Task::ready(Ok(lens));
+        }
+        if self.is_local() {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_buffer(buffer, lens.server_id, cx)
+            else {
+                return Task::ready(Ok(lens));
+            };
+            if !GetCodeLens::can_resolve_lenses(lang_server.capabilities()) {
+                return Task::ready(Ok(lens));
+            }
+            let lang_server = lang_server.clone();
+            cx.spawn(move |_, _| async move {
+                let lsp_lens = lang_server
+                    .request::<lsp::request::CodeLensResolve>(lens.lsp_lens.clone())
+                    .await
+                    .context("code lens resolve LSP request")?;
+                Ok(CodeLens { lsp_lens, ..lens })
+            })
+        } else if let Some(project_id) = self.remote_id() {
+            let client = self.client.clone();
+            let request = proto::ResolveCodeLens {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                lens: Some(Self::serialize_code_lens(&lens)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("code lens proto request")?;
+                match response.lens {
+                    Some(resolved_lens) => Self::deserialize_code_lens(resolved_lens),
+                    None => Ok(lens),
+                }
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    /// Runs the command of a resolved code lens on its language server, applying the edits the
+    /// server makes while running it.
+    pub fn run_code_lens(
+        &self,
+        buffer_handle: Model<Buffer>,
+        lens: CodeLens,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<ProjectTransaction>> {
+        let Some(command) = lens.lsp_lens.command else {
+            return Task::ready(Err(anyhow!("code lens has no command")));
+        };
+        let action = CodeAction {
+            server_id: lens.server_id,
+            range: lens.range,
+            lsp_action: lsp::CodeAction {
+                title: command.title.clone(),
+                command: Some(command),
+                ..lsp::CodeAction::default()
+            },
+        };
+        self.apply_code_action(buffer_handle, action, true, cx)
+    }
+
+    /// Fetches the color literals of the whole buffer from all of its language servers.
+    pub fn document_colors(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Vec<DocumentColor>> {
+        if self.is_local() {
+            let all_colors_task = self.request_multiple_lsp_locally(
+                buffer_handle,
+                None::<usize>,
+                GetDocumentColors::supports_document_colors,
+                GetDocumentColors,
+                cx,
+            );
+            cx.spawn(|_, _| async move { all_colors_task.await.into_iter().flatten().collect() })
+        } else if let Some(project_id) = self.remote_id() {
+            let request_task = self.client().request(proto::MultiLspQuery {
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                version: serialize_version(&buffer_handle.read(cx).version()),
+                project_id,
+                strategy: Some(proto::multi_lsp_query::Strategy::All(
+                    proto::AllLanguageServers {},
+                )),
+                request: Some(proto::multi_lsp_query::Request::GetDocumentColors(
+                    GetDocumentColors.to_proto(project_id, buffer_handle.read(cx)),
+                )),
+            });
+            let buffer = buffer_handle.clone();
+            cx.spawn(|weak_project, cx| async move {
+                let Some(project) = weak_project.upgrade() else {
+                    return Vec::new();
+                };
+                join_all(
+                    request_task
+                        .await
+                        .log_err()
+                        .map(|response| response.responses)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|lsp_response| match lsp_response.response? {
+                            proto::lsp_response::Response::GetDocumentColorsResponse(response) => {
+                                Some(response)
+                            }
+                            unexpected => {
+                                debug_panic!("Unexpected response: {unexpected:?}");
+                                None
+                            }
+                        })
+                        .map(|document_colors_response| {
+                            let response = GetDocumentColors.response_from_proto(
+                                document_colors_response,
+                                project.clone(),
+                                buffer.clone(),
+                                cx.clone(),
+                            );
+                            async move { response.await.log_err().unwrap_or_default() }
+                        }),
+                )
+                .await
+                .into_iter()
+                .flatten()
+                .collect()
+            })
+        } else {
+            log::error!("cannot fetch document colors: project does not have a remote id");
+            Task::ready(Vec::new())
+        }
+    }
+
+    /// Fetches the foldable ranges of the whole buffer from its primary language server.
+    pub fn folding_ranges(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<FoldingRange>>> {
+        self.request_lsp(
+            buffer.clone(),
+            LanguageServerToQuery::Primary,
+            GetFoldingRanges,
+            cx,
+        )
+    }
+
+    /// Fetches the links of the whole buffer from all of its language servers.
+    pub fn document_links(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Vec<DocumentLink>> {
+        if self.is_local() {
+            let all_links_task = self.request_multiple_lsp_locally(
+                buffer_handle,
+                None::<usize>,
+                GetDocumentLinks::supports_document_links,
+                GetDocumentLinks,
+                cx,
+            );
+            cx.spawn(|_, _| async move { all_links_task.await.into_iter().flatten().collect() })
+        } else if let Some(project_id) = self.remote_id() {
+            let request_task = self.client().request(proto::MultiLspQuery {
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                version: serialize_version(&buffer_handle.read(cx).version()),
+                project_id,
+                strategy: Some(proto::multi_lsp_query::Strategy::All(
+                    proto::AllLanguageServers {},
+                )),
+                request: Some(proto::multi_lsp_query::Request::GetDocumentLinks(
+                    GetDocumentLinks.to_proto(project_id, buffer_handle.read(cx)),
+                )),
+            });
+            let buffer = buffer_handle.clone();
+            cx.spawn(|weak_project, cx| async move {
+                let Some(project) = weak_project.upgrade() else {
+                    return Vec::new();
+                };
+                join_all(
+                    request_task
+                        .await
+                        .log_err()
+                        .map(|response| response.responses)
+                        .unwrap_or_default()
+                        .into_iter()
+                        .filter_map(|lsp_response| match lsp_response.response? {
+                            proto::lsp_response::Response::GetDocumentLinksResponse(response) => {
+                                Some(response)
+                            }
+                            unexpected => {
+                                debug_panic!("Unexpected response: {unexpected:?}");
+                                None
+                            }
+                        })
+                        .map(|document_links_response| {
+                            let response = GetDocumentLinks.response_from_proto(
+                                document_links_response,
+                                project.clone(),
+                                buffer.clone(),
+                                cx.clone(),
+                            );
+                            async move { response.await.log_err().unwrap_or_default() }
+                        }),
+                )
+                .await
+                .into_iter()
+                .flatten()
+                .collect()
+            })
+        } else {
+            log::error!("cannot fetch document links: project does not have a remote id");
+            Task::ready(Vec::new())
+        }
+    }
+
+    /// Fills in the target of a link that was returned without one, if its language server
+    /// supports resolving links.
+    pub fn resolve_document_link(
+        &self,
+        buffer_handle: Model<Buffer>,
+        link: DocumentLink,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<DocumentLink>> {
+        if link.lsp_link.target.is_some() {
+            return Task::ready(Ok(link));
+        }
+        if self.is_local() {
+            let buffer = buffer_handle.read(cx);
+            let Some((_, lang_server)) =
+                self.language_server_for_buffer(buffer, link.server_id, cx)
+            else {
+                return Task::ready(Ok(link));
+            };
+            if !GetDocumentLinks::can_resolve_links(lang_server.capabilities()) {
+                return Task::ready(Ok(link));
+            }
+            let lang_server = lang_server.clone();
+            cx.spawn(move |_, _| async move {
+                let lsp_link = lang_server
+                    .request::<lsp::request::DocumentLinkResolve>(link.lsp_link.clone())
+                    .await
+                    .context("document link resolve LSP request")?;
+                Ok(DocumentLink { lsp_link, ..link })
+            })
+        } else if let Some(project_id) = self.remote_id() {
+            let client = self.client.clone();
+            let request = proto::ResolveDocumentLink {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                link: Some(Self::serialize_document_link(&link)),
+            };
+            cx.spawn(move |_, _| async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("document link proto request")?;
+                match response.link {
+                    Some(resolved_link) => Self::deserialize_document_link(resolved_link),
+                    None => Ok(link),
+                }
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    /// Asks the language server that reported a color literal how to write the given color
+    /// over it.
+    pub fn color_presentations(
+        &self,
+        buffer_handle: Model<Buffer>,
+        color: DocumentColor,
+        new_color: lsp::Color,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<ColorPresentation>>> {
+        let lsp_presentations = if self.is_local() {
+            self.lsp_color_presentations(&buffer_handle, &color, new_color, cx)
+        } else if let Some(project_id) = self.remote_id() {
+            let client = self.client.clone();
+            let request = proto::GetColorPresentations {
+                project_id,
+                buffer_id: buffer_handle.read(cx).remote_id().into(),
+                color: Some(Self::serialize_document_color(&DocumentColor {
+                    color: new_color,
+                    ..color.clone()
+                })),
+            };
+            cx.background_executor().spawn(async move {
+                let response = client
+                    .request(request)
+                    .await
+                    .context("color presentations proto request")?;
+                response
+                    .lsp_presentations
+                    .iter()
+                    .map(|presentation| Ok(serde_json::from_slice(presentation)?))
+                    .collect()
+            })
+        } else {
+            return Task::ready(Err(anyhow!("project does not have a remote id")));
+        };
+
+        cx.spawn(move |_, mut cx| async move {
+            let lsp_presentations = lsp_presentations.await?;
+            buffer_handle.update(&mut cx, |buffer, _| {
+                let anchor_range = |range: lsp::Range| {
+                    let range = range_from_lsp(range);
+                    let start = buffer.clip_point_utf16(range.start, Bias::Left);
+                    let end = buffer.clip_point_utf16(range.end, Bias::Left);
+                    buffer.anchor_before(start)..buffer.anchor_after(end)
+                };
+                lsp_presentations
+                    .into_iter()
+                    .map(|presentation| {
+                        let mut edits = Vec::new();
+                        match presentation.text_edit {
+                            Some(edit) => edits.push((anchor_range(edit.range), edit.new_text)),
+                            None => edits.push((color.range.clone(), presentation.label.clone())),
+                        }
+                        edits.extend(
+                            presentation
+                                .additional_text_edits
+                                .into_iter()
+                                .flatten()
+                                .map(|edit| (anchor_range(edit.range), edit.new_text)),
+                        );
+                        ColorPresentation {
+                            label: presentation.label,
+                            edits,
+                        }
+                    })
+                    .collect()
+            })
+        })
+    }
+
+    fn lsp_color_presentations(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        color: &DocumentColor,
+        new_color: lsp::Color,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<lsp::ColorPresentation>>> {
+        let buffer = buffer_handle.read(cx);
+        let Some(file) = File::from_dyn(buffer.file()).and_then(File::as_local) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let Some((_, lang_server)) = self.language_server_for_buffer(buffer, color.server_id, cx)
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let params = lsp::ColorPresentationParams {
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(file.abs_path(cx)).unwrap(),
+            ),
+            color: new_color,
+            range: range_to_lsp(
+                color.range.start.to_point_utf16(buffer)..color.range.end.to_point_utf16(buffer),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        };
+        let lang_server = lang_server.clone();
+        cx.background_executor().spawn(async move {
+            lang_server
+                .request::<lsp::request::ColorPresentationRequest>(params)
+                .await
+                .context("color presentation LSP request")
+        })
+    }
+
+    /// Asks the first language server of a local buffer that provides inline values for the
+    /// ones to show in a range while a debug session is stopped in the given frame, returning
+    /// `None` when no server provides them.
+    pub fn inline_values(
+        &self,
+        buffer_handle: &Model<Buffer>,
+        range: Range<Anchor>,
+        frame_id: u64,
+        stopped_location: Range<Anchor>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Option<Vec<InlineValue>>>> {
+        let buffer = buffer_handle.read(cx);
+        let Some(file) = File::from_dyn(buffer.file()).and_then(File::as_local) else {
+            return Task::ready(Ok(None));
+        };
+        let Some(lang_server) =
+            self.language_servers_for_buffer(buffer, cx)
+                .find_map(|(_, server)| {
+                    match server.capabilities().inline_value_provider.as_ref()? {
+                        OneOf::Left(false) => None,
+                        _ => Some(server.clone()),
+                    }
+                })
+        else {
+            return Task::ready(Ok(None));
+        };
+        let to_lsp = |range: &Range<Anchor>| {
+            range_to_lsp(range.start.to_point_utf16(buffer)..range.end.to_point_utf16(buffer))
+        };
+        let params = lsp::InlineValueParams {
+            work_done_progress_params: Default::default(),
+            text_document: lsp::TextDocumentIdentifier::new(
+                lsp::Url::from_file_path(file.abs_path(cx)).unwrap(),
+            ),
+            range: to_lsp(&range),
+            context: lsp::InlineValueContext {
+                frame_id: frame_id as i32,
+                stopped_location: to_lsp(&stopped_location),
+            },
+        };
+        let buffer_handle = buffer_handle.clone();
+        cx.spawn(move |_, mut cx| async move {
+            let values = lang_server
+                .request::<lsp::request::InlineValueRequest>(params)
+                .await
+                .context("inline value LSP request")?
+                .unwrap_or_default();
+            buffer_handle.update(&mut cx, |buffer, _| {
+                let anchor_range = |range: lsp::Range| {
+                    let range = range_from_lsp(range);
+                    let start = buffer.clip_point_utf16(range.start, Bias::Left);
+                    let end = buffer.clip_point_utf16(range.end, Bias::Left);
+                    buffer.anchor_before(start)..buffer.anchor_after(end)
+                };
+                Some(
+                    values
+                        .into_iter()
+                        .map(|value| match value {
+                            lsp::InlineValue::Text(text) => InlineValue {
+                                range: anchor_range(text.range),
+                                kind: InlineValueKind::Text(text.text),
+                            },
+                            lsp::InlineValue::VariableLookup(lookup) => InlineValue {
+                                range: anchor_range(lookup.range),
+                                kind: InlineValueKind::VariableLookup {
+                                    name: lookup.variable_name,
+                                    case_sensitive: lookup.case_sensitive_lookup,
+                                },
+                            },
+                            lsp::InlineValue::EvaluatableExpression(expression) => InlineValue {
+                                range: anchor_range(expression.range),
+                                kind: InlineValueKind::EvaluatableExpression(expression.expression),
+                            },
+                        })
+                        .collect(),
+                )
+            })
+        })
+    }
+
+    /// Fetches the semantic tokens of the whole buffer from the first of its language servers
+    /// that provides them.
+    pub fn semantic_tokens(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<SemanticToken>>> {
+        if self.is_local() {
+            self.lsp_semantic_tokens(buffer_handle, cx)
+        } else if let Some(project_id) = self.remote_id() {
+            let buffer = buffer_handle.read(cx);
+            let request = self.client.request(proto::GetSemanticTokens {
+                project_id,
+                buffer_id: buffer.remote_id().into(),
+                version: serialize_version(&buffer.version()),
+            });
+            let buffer_handle = buffer_handle.clone();
+            cx.spawn(move |_, mut cx| async move {
+                let response = request.await.context("semantic tokens proto request")?;
+                buffer_handle
+                    .update(&mut cx, |buffer, _| {
+                        buffer.wait_for_version(deserialize_version(&response.version))
+                    })?
+                    .await?;
+                response
+                    .tokens
+                    .into_iter()
+                    .map(Self::deserialize_semantic_token)
+                    .collect()
+            })
+        } else {
+            Task::ready(Err(anyhow!("project does not have a remote id")))
+        }
+    }
+
+    fn lsp_semantic_tokens(
+        &mut self,
+        buffer_handle: &Model<Buffer>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<Vec<SemanticToken>>> {
+        let buffer = buffer_handle.read(cx);
+        let buffer_id = buffer.remote_id();
+        let Some(file) = File::from_dyn(buffer.file()).and_then(File::as_local) else {
+            return Task::ready(Ok(Vec::new()));
+        };
+        let Some((server, legend, supports_delta)) = self
+            .language_servers_for_buffer(buffer, cx)
+            .find_map(|(_, server)| {
+                let options = match server.capabilities().semantic_tokens_provider.clone()? {
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(options) => {
+                        options
+                    }
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensRegistrationOptions(
+                        options,
+                    ) => options.semantic_tokens_options,
+                };
+                let supports_delta = match options.full? {
+                    lsp::SemanticTokensFullOptions::Bool(full) => {
+                        if !full {
+                            return None;
+                        }
+                        false
+                    }
+                    lsp::SemanticTokensFullOptions::Delta { delta } => delta.unwrap_or(false),
+                };
+                Some((server.clone(), options.legend, supports_delta))
+            })
+        else {
+            return Task::ready(Ok(Vec::new()));
+        };
+
+        let server_id = server.server_id();
+        let snapshot = buffer.text_snapshot();
+        let text_document =
+            lsp::TextDocumentIdentifier::new(lsp::Url::from_file_path(file.abs_path(cx)).unwrap());
+        let previous_result_id = self
+            .semantic_tokens
+            .get(&buffer_id)
+            .and_then(|tokens| tokens.get(&server_id))
+            .and_then(|tokens| tokens.result_id.clone())
+            .filter(|_| supports_delta);
+        cx.spawn(move |this, mut cx| async move {
+            let mut tokens = None;
+            if let Some(previous_result_id) = previous_result_id {
+                let response = server
+                    .request::<lsp::request::SemanticTokensFullDeltaRequest>(
+                        lsp::SemanticTokensDeltaParams {
+                            text_document: text_document.clone(),
+                            previous_result_id,
+                            work_done_progress_params: Default::default(),
+                            partial_result_params: Default::default(),
+                        },
+                    )
+                    .await
+                    .context("semantic tokens delta LSP request")
+                    .log_err()
+                    .flatten();
+                let (result_id, edits) = match response {
+                    Some(lsp::SemanticTokensFullDeltaResult::Tokens(full)) => {
+                        tokens = Some(LspSemanticTokens {
+                            result_id: full.result_id,
+                            data: full.data,
+                        });
+                        (None, Vec::new())
+                    }
+                    Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(delta)) => {
+                        (delta.result_id, delta.edits)
+                    }
+                    Some(lsp::SemanticTokensFullDeltaResult::PartialTokensDelta { edits }) => {
+                        (None, edits)
+                    }
+                    None => (None, Vec::new()),
+                };
+                if tokens.is_none() && (result_id.is_some() || !edits.is_empty()) {
+                    tokens = this.update(&mut cx, |this, _| {
+                        let mut data = this
+                            .semantic_tokens
+                            .get(&buffer_id)?
+                            .get(&server_id)?
+                            .data
+                            .clone();
+                        let mut edits = edits;
+                        // Edits index the flattened token data, where each token is five
+                        // integers, and apply to the previous data from the last edit backwards.
+                        edits.sort_by_key(|edit| cmp::Reverse(edit.start));
+                        for edit in edits {
+                            let start = (edit.start / 5) as usize;
+                            let end = start + (edit.delete_count / 5) as usize;
+                            if end > data.len() {
+                                return None;
+                            }
+                            data.splice(start..end, edit.data.unwrap_or_default());
+                        }
+                        Some(LspSemanticTokens { result_id, data })
+                    })?;
+                }
+            }
+
+            let tokens = match tokens {
+                Some(tokens) => tokens,
+                None => {
+                    let response = server
+                        .request::<lsp::request::SemanticTokensFullRequest>(
+                            lsp::SemanticTokensParams {
+                                text_document,
+                                work_done_progress_params: Default::default(),
+                                partial_result_params: Default::default(),
+                            },
+                        )
+                        .await
+                        .context("semantic tokens LSP request")?;
+                    match response {
+                        Some(lsp::SemanticTokensResult::Tokens(full)) => LspSemanticTokens {
+                            result_id: full.result_id,
+                            data: full.data,
+                        },
+                        Some(lsp::SemanticTokensResult::Partial(partial)) => LspSemanticTokens {
+                            result_id: None,
+                            data: partial.data,
+                        },
+                        None => LspSemanticTokens {
+                            result_id: None,
+                            data: Vec::new(),
+                        },
+                    }
+                }
+            };
+
+            let semantic_tokens = decode_semantic_tokens(&tokens.data, &legend, &snapshot);
+            this.update(&mut cx, |this, _| {
+                if this.opened_buffers.contains_key(&buffer_id) {
+                    this.semantic_tokens
+                        .entry(buffer_id)
+                        .or_default()
+                        .insert(server_id, tokens);
+                }
+            })?;
+            Ok(semantic_tokens)
+        })
+    }
+
     fn apply_on_type_formatting(
         &self,
         buffer: Model<Buffer>,
@@ -6766,6 +8462,10 @@ impl Project {
                         )
                         .await?;
                     }
+                    this.update(cx, |this, cx| {
+                        this.refresh_local_worktree_entries(&[abs_path.as_path()], cx)
+                    })?
+                    .await;
                 }
 
                 lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Rename(op)) => {
@@ -6788,6 +8488,16 @@ impl Project {
                             .unwrap_or_default(),
                     )
                     .await?;
+                    // Both paths are rescanned together so that the worktree keeps the entries'
+                    // ids, letting the buffers opened for them follow the rename, so that later
+                    // edits to the new path apply to those buffers.
+                    this.update(cx, |this, cx| {
+                        this.refresh_local_worktree_entries(
+                            &[source_abs_path.as_path(), target_abs_path.as_path()],
+                            cx,
+                        )
+                    })?
+                    .await;
                 }
 
                 lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Delete(op)) => {
@@ -6807,6 +8517,10 @@ impl Project {
                     } else {
                         fs.remove_file(&abs_path, options).await?;
                     }
+                    this.update(cx, |this, cx| {
+                        this.refresh_local_worktree_entries(&[abs_path.as_path()], cx)
+                    })?
+                    .await;
                 }
 
                 lsp::DocumentChangeOperation::Edit(op) => {
@@ -6914,7 +8628,16 @@ impl Project {
                         transaction
                     })?;
                     if let Some(transaction) = transaction {
-                        project_transaction.0.insert(buffer_to_edit, transaction);
+                        // A buffer edited by several operations gets a single transaction, so
+                        // that the whole workspace edit is undone at once.
+                        if let Some(previous) = project_transaction.0.get_mut(&buffer_to_edit) {
+                            buffer_to_edit.update(cx, |buffer, _| {
+                                buffer.merge_transactions(transaction.id, previous.id)
+                            })?;
+                            previous.edit_ids.extend(transaction.edit_ids);
+                        } else {
+                            project_transaction.0.insert(buffer_to_edit, transaction);
+                        }
                     }
                 }
             }
@@ -6923,6 +8646,34 @@ impl Project {
         Ok(project_transaction)
     }
 
+    /// Rescans the given paths in the local worktrees containing them, so that their entries
+    /// and the buffers opened for them reflect the files created, renamed or deleted on disk.
+    fn refresh_local_worktree_entries(
+        &self,
+        abs_paths: &[&Path],
+        cx: &AppContext,
+    ) -> impl Future<Output = ()> {
+        let mut refreshes = Vec::new();
+        for worktree in self.worktrees() {
+            let Some(worktree) = worktree.read(cx).as_local() else {
+                continue;
+            };
+            let paths = abs_paths
+                .iter()
+                .filter_map(|abs_path| abs_path.strip_prefix(worktree.abs_path()).ok())
+                .map(Arc::from)
+                .collect::<Vec<Arc<Path>>>();
+            if !paths.is_empty() {
+                refreshes.push(worktree.refresh_entries_for_paths(paths));
+            }
+        }
+        async move {
+            for mut refresh in refreshes {
+                refresh.next().await;
+            }
+        }
+    }
+
     fn prepare_rename_impl(
         &mut self,
         buffer: Model<Buffer>,
@@ -6978,6 +8729,50 @@ impl Project {
         self.perform_rename_impl(buffer, position, new_name, push_to_history, cx)
     }
 
+    /// Asks the primary language server of a buffer how to rename the symbol at the given
+    /// position, without applying its edits.
+    pub fn rename_edits<T: ToPointUtf16>(
+        &mut self,
+        buffer: Model<Buffer>,
+        position: T,
+        new_name: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<RenameEdits>> {
+        let position = position.to_point_utf16(buffer.read(cx));
+        self.request_lsp(
+            buffer,
+            LanguageServerToQuery::Primary,
+            GetRenameEdits { position, new_name },
+            cx,
+        )
+    }
+
+    /// Applies the edits of a rename with one transaction per buffer, which are undone together
+    /// when the returned project transaction is.
+    pub fn apply_rename_edits(
+        &mut self,
+        edits: RenameEdits,
+        cx: &mut ModelContext<Self>,
+    ) -> ProjectTransaction {
+        let mut project_transaction = ProjectTransaction::default();
+        for (buffer_handle, edits) in edits.0 {
+            if edits.is_empty() {
+                continue;
+            }
+            let transaction = buffer_handle.update(cx, |buffer, cx| {
+                buffer.finalize_last_transaction();
+                buffer.start_transaction();
+                buffer.edit(edits, None, cx);
+                buffer.end_transaction(cx)?;
+                buffer.finalize_last_transaction().cloned()
+            });
+            if let Some(transaction) = transaction {
+                project_transaction.0.insert(buffer_handle, transaction);
+            }
+        }
+        project_transaction
+    }
+
     pub fn on_type_format_impl(
         &mut self,
         buffer: Model<Buffer>,
@@ -7287,6 +9082,7 @@ impl Project {
                 }
             })
             .collect();
+        let index_filter = SearchIndexFilter::new(&query, &self.search_indices);
         cx.background_executor()
             .spawn(Self::background_search(
                 unnamed_files,
@@ -7298,6 +9094,7 @@ impl Project {
                 include_root,
                 path_count,
                 snapshots,
+                index_filter,
                 matching_paths_tx,
             ))
             .detach();
@@ -7366,6 +9163,11 @@ impl Project {
                 let chunk_results = futures::future::join_all(chunk_results).await;
                 for result in chunk_results {
                     if let Some((buffer, ranges)) = result.log_err() {
+                        // Structural queries can't rule out files without parsing them, so
+                        // every file is a candidate, and those without a match are skipped.
+                        if ranges.is_empty() && matches!(*query, SearchQuery::Structural { .. }) {
+                            continue;
+                        }
                         range_count += ranges.len();
                         result_tx
                             .send(SearchResult::Buffer { buffer, ranges })
@@ -7401,12 +9203,14 @@ impl Project {
         include_root: bool,
         path_count: usize,
         snapshots: Vec<LocalSnapshot>,
+        index_filter: Option<SearchIndexFilter>,
         matching_paths_tx: Sender<SearchMatchCandidate>,
     ) {
         let fs = &fs;
         let query = &query;
         let matching_paths_tx = &matching_paths_tx;
         let snapshots = &snapshots;
+        let index_filter = index_filter.as_ref();
         for buffer in unnamed_buffers {
             matching_paths_tx
                 .send(SearchMatchCandidate::OpenBuffer {
@@ -7448,6 +9252,7 @@ impl Project {
                                 matching_paths_tx,
                                 &opened_buffers,
                                 include_root,
+                                index_filter,
                                 fs,
                             )
                             .await;
@@ -7520,8 +9325,10 @@ impl Project {
                                     language_server.server_id(),
                                     id.to_string(),
                                     LanguageServerProgress {
+                                        title: None,
                                         message: status.clone(),
                                         percentage: None,
+                                        is_cancellable: false,
                                         last_update_at: Instant::now(),
                                     },
                                     cx,
@@ -7862,6 +9669,7 @@ impl Project {
         self.task_inventory().update(cx, |inventory, _| {
             inventory.remove_worktree_sources(id_to_remove);
         });
+        self.search_indices.remove(&id_to_remove);
 
         self.worktrees.retain(|worktree| {
             if let Some(worktree) = worktree.upgrade() {
@@ -7891,6 +9699,10 @@ impl Project {
                         this.update_local_worktree_language_servers(&worktree, changes, cx);
                         this.update_local_worktree_settings(&worktree, changes, cx);
                         this.update_prettier_settings(&worktree, changes, cx);
+                        let worktree = worktree.read(cx);
+                        if let Some(index) = this.search_indices.get(&worktree.id()) {
+                            index.update(&worktree.snapshot(), changes);
+                        }
                     }
 
                     cx.emit(Event::WorktreeUpdatedEntries(
@@ -7945,6 +9757,8 @@ impl Project {
         })
         .detach();
 
+        self.maintain_search_indices(cx);
+
         cx.emit(Event::WorktreeAdded);
         self.metadata_changed(cx);
     }
@@ -8200,15 +10014,22 @@ impl Project {
             let diff_bases_by_buffer = cx
                 .background_executor()
                 .spawn(async move {
+                    // Resolve the diff base of each repository once, rather than for every file.
+                    let mut diff_bases_by_repo = HashMap::default();
                     let mut diff_base_tasks = future_buffers
                         .into_iter()
                         .flatten()
                         .chain(current_buffers)
                         .filter_map(|(buffer, path, abs_path)| {
                             let (repo_entry, local_repo_entry) = snapshot.repo_for_path(&path)?;
-                            Some((buffer, path, abs_path, repo_entry, local_repo_entry))
+                            let repository = local_repo_entry.repo().clone();
+                            let diff_base = diff_bases_by_repo
+                                .entry(repo_entry.work_directory_id())
+                                .or_insert_with(|| repository.resolve_diff_base().log_err())
+                                .clone();
+                            Some((buffer, path, abs_path, repo_entry, repository, diff_base))
                         })
-                        .map(|(buffer, path, abs_path, repo, local_repo_entry)| {
+                        .map(|(buffer, path, abs_path, repo, repository, diff_base)| {
                             let fs = fs.clone();
                             let snapshot = snapshot.clone();
                             async move {
@@ -8226,7 +10047,9 @@ impl Project {
                                     None
                                 } else {
                                     let relative_path = repo.relativize(&snapshot, &path).ok()?;
-                                    local_repo_entry.repo().load_index_text(&relative_path)
+                                    diff_base.and_then(|diff_base| {
+                                        repository.load_diff_base_text(&diff_base, &relative_path)
+                                    })
                                 };
                                 Some((buffer, base_text))
                             }
@@ -8548,11 +10371,14 @@ impl Project {
         abs_path: &Path,
         cx: &AppContext,
     ) -> Option<ProjectPath> {
-        self.find_local_worktree(abs_path, cx)
-            .map(|(worktree, relative_path)| ProjectPath {
-                worktree_id: worktree.read(cx).id(),
+        self.worktrees().find_map(|worktree| {
+            let worktree = worktree.read(cx);
+            let relative_path = abs_path.strip_prefix(worktree.abs_path()).ok()?;
+            Some(ProjectPath {
+                worktree_id: worktree.id(),
                 path: relative_path.into(),
             })
+        })
     }
 
     pub fn get_workspace_root(
@@ -8587,6 +10413,59 @@ impl Project {
         worktree.get_local_repo(&root_entry)?.repo().clone().into()
     }
 
+    /// Returns the git repositories of the visible local worktrees along with their working
+    /// directories, each nested repository or submodule coming after the one it's nested in.
+    pub fn git_repositories(&self, cx: &AppContext) -> Vec<(ProjectPath, Arc<dyn GitRepository>)> {
+        self.visible_worktrees(cx)
+            .filter_map(|worktree| worktree.read(cx).as_local())
+            .flat_map(|worktree| {
+                let worktree_id = worktree.id();
+                worktree
+                    .repositories()
+                    .filter_map(move |(work_directory, repository_entry)| {
+                        let project_path = ProjectPath {
+                            worktree_id,
+                            path: work_directory.clone(),
+                        };
+                        let repo = worktree.get_local_repo(repository_entry)?.repo().clone();
+                        Some((project_path, repo))
+                    })
+            })
+            .collect()
+    }
+
+    /// Returns the innermost git repository containing the active entry, or the repository at
+    /// the root of the first worktree, along with its working directory.
+    pub fn active_git_repository(
+        &self,
+        cx: &AppContext,
+    ) -> Option<(ProjectPath, Arc<dyn GitRepository>)> {
+        let active_path = self
+            .active_entry()
+            .and_then(|entry_id| self.path_for_entry(entry_id, cx));
+        let active_repository = active_path.and_then(|active_path| {
+            let worktree = self
+                .worktree_for_id(active_path.worktree_id, cx)?
+                .read(cx)
+                .as_local()?;
+            let (work_directory, _) =
+                worktree.repository_and_work_directory_for_path(&active_path.path)?;
+            let project_path = ProjectPath {
+                worktree_id: active_path.worktree_id,
+                path: work_directory.as_ref().into(),
+            };
+            Some((project_path, worktree.local_git_repo(&active_path.path)?))
+        });
+        active_repository.or_else(|| {
+            let worktree = self.visible_worktrees(cx).next()?.read(cx);
+            let project_path = ProjectPath {
+                worktree_id: worktree.id(),
+                path: Path::new("").into(),
+            };
+            Some((project_path, self.get_first_worktree_root_repo(cx)?))
+        })
+    }
+
     pub fn blame_buffer(
         &self,
         buffer: &Model<Buffer>,
@@ -8654,6 +10533,224 @@ impl Project {
         }
     }
 
+    /// Writes the changes of the diff hunks intersecting the given ranges of the buffer to the git
+    /// index, leaving the buffer's other changes unstaged.
+    pub fn stage_hunks(
+        &self,
+        buffer: &Model<Buffer>,
+        ranges: Vec<Range<Anchor>>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        if let Err(error) = self.check_index_diff_base(buffer, cx) {
+            return Task::ready(Err(error));
+        }
+        let snapshot = buffer.read(cx).snapshot();
+        let Some(index_text) = buffer.read(cx).diff_base().cloned() else {
+            return Task::ready(Ok(()));
+        };
+
+        let mut changes = diff_hunk_base_rows(&snapshot, &index_text)
+            .into_iter()
+            .filter(|(hunk, _)| {
+                ranges.iter().any(|range| {
+                    range.start.cmp(&hunk.buffer_range.end, &snapshot).is_le()
+                        && range.end.cmp(&hunk.buffer_range.start, &snapshot).is_ge()
+                })
+            })
+            .map(|(hunk, base_rows)| {
+                let buffer_rows = hunk.associated_range;
+                (
+                    row_offset(&index_text, base_rows.start)
+                        ..row_offset(&index_text, base_rows.end),
+                    row_offset(snapshot.as_rope(), buffer_rows.start)
+                        ..row_offset(snapshot.as_rope(), buffer_rows.end),
+                )
+            })
+            .peekable();
+        if changes.peek().is_none() {
+            return Task::ready(Ok(()));
+        }
+
+        let mut new_index_text = String::new();
+        let mut last_base_offset = 0;
+        for (base_range, buffer_range) in changes {
+            new_index_text.extend(index_text.chunks_in_range(last_base_offset..base_range.start));
+            new_index_text.extend(snapshot.text_for_range(buffer_range));
+            last_base_offset = base_range.end;
+        }
+        new_index_text.extend(index_text.chunks_in_range(last_base_offset..index_text.len()));
+        self.write_index_text(buffer, new_index_text, cx)
+    }
+
+    /// Restores the HEAD contents of the staged changes intersecting the given ranges of the
+    /// buffer in the git index.
+    pub fn unstage_hunks(
+        &self,
+        buffer: &Model<Buffer>,
+        ranges: Vec<Range<Anchor>>,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        if let Err(error) = self.check_index_diff_base(buffer, cx) {
+            return Task::ready(Err(error));
+        }
+        let snapshot = buffer.read(cx).snapshot();
+        let Some(index_text) = buffer.read(cx).diff_base().cloned() else {
+            return Task::ready(Ok(()));
+        };
+        let repository = match self.git_repository_for_buffer(buffer, cx) {
+            Ok(repository) => repository,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        // The staged changes are found in the index, so the ranges are mapped to the rows of the
+        // index that the buffer rows correspond to.
+        let hunks = diff_hunk_base_rows(&snapshot, &index_text);
+        let base_row = |row: u32| {
+            let mut delta = 0;
+            for (hunk, base_rows) in &hunks {
+                if hunk.associated_range.start > row {
+                    break;
+                } else if hunk.associated_range.end > row {
+                    return (base_rows.start + row - hunk.associated_range.start)
+                        .min(base_rows.end);
+                }
+                delta = base_rows.end as i64 - hunk.associated_range.end as i64;
+            }
+            (row as i64 + delta) as u32
+        };
+        let index_row_ranges = ranges
+            .iter()
+            .map(|range| {
+                let start_row = range.start.to_point(&snapshot).row;
+                let end_row = range.end.to_point(&snapshot).row;
+                base_row(start_row)..base_row(end_row) + 1
+            })
+            .collect::<Vec<_>>();
+
+        let buffer = buffer.clone();
+        cx.spawn(|this, mut cx| async move {
+            let (repository, relative_path) = repository;
+            let Some(head_text) = cx
+                .background_executor()
+                .spawn(async move { repository.load_head_text(&relative_path) })
+                .await
+            else {
+                return Ok(());
+            };
+
+            let mut changes = git::diff::changed_rows(&head_text, &index_text.to_string())
+                .into_iter()
+                .filter(|(_, index_rows)| {
+                    index_row_ranges.iter().any(|range| {
+                        // Staged deletions are unstaged from the rows around them.
+                        if index_rows.is_empty() {
+                            range.start <= index_rows.start && index_rows.start <= range.end
+                        } else {
+                            range.start < index_rows.end && index_rows.start < range.end
+                        }
+                    })
+                })
+                .peekable();
+            if changes.peek().is_none() {
+                return Ok(());
+            }
+
+            let head_text = Rope::from(head_text.as_str());
+            let mut new_index_text = String::new();
+            let mut last_index_offset = 0;
+            for (head_rows, index_rows) in changes {
+                let index_range = row_offset(&index_text, index_rows.start)
+                    ..row_offset(&index_text, index_rows.end);
+                let head_range =
+                    row_offset(&head_text, head_rows.start)..row_offset(&head_text, head_rows.end);
+                new_index_text
+                    .extend(index_text.chunks_in_range(last_index_offset..index_range.start));
+                new_index_text.extend(head_text.chunks_in_range(head_range));
+                last_index_offset = index_range.end;
+            }
+            new_index_text.extend(index_text.chunks_in_range(last_index_offset..index_text.len()));
+
+            this.update(&mut cx, |this, cx| {
+                this.write_index_text(&buffer, new_index_text, cx)
+            })?
+            .await
+        })
+    }
+
+    /// Hunks are staged and unstaged by editing the buffer's diff base, which is only the index
+    /// when the repository's changes are shown against it.
+    fn check_index_diff_base(&self, buffer: &Model<Buffer>, cx: &AppContext) -> Result<()> {
+        let (repository, _) = self.git_repository_for_buffer(buffer, cx)?;
+        let diff_base = repository.diff_base();
+        if diff_base != DiffBase::Index {
+            bail!(
+                "hunks can only be staged when changes are shown against the index, not {}",
+                diff_base.label()
+            );
+        }
+        Ok(())
+    }
+
+    fn write_index_text(
+        &self,
+        buffer: &Model<Buffer>,
+        index_text: String,
+        cx: &mut ModelContext<Self>,
+    ) -> Task<Result<()>> {
+        let (repository, relative_path) = match self.git_repository_for_buffer(buffer, cx) {
+            Ok(repository) => repository,
+            Err(error) => return Task::ready(Err(error)),
+        };
+        let buffer = buffer.clone();
+        cx.spawn(|_, mut cx| async move {
+            let index_text = cx
+                .background_executor()
+                .spawn(async move {
+                    repository
+                        .set_index_text(&relative_path, &index_text)
+                        .with_context(|| {
+                            format!("Failed to update the index of {:?}", relative_path.0)
+                        })?;
+                    anyhow::Ok(index_text)
+                })
+                .await?;
+            // Update the diff right away rather than waiting for the index change to be detected.
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.set_diff_base(Some(index_text), cx)
+            })?;
+            Ok(())
+        })
+    }
+
+    /// Returns the local repository containing the file of the buffer, with the file's path in it.
+    pub fn git_repository_for_buffer(
+        &self,
+        buffer: &Model<Buffer>,
+        cx: &AppContext,
+    ) -> Result<(Arc<dyn GitRepository>, git::repository::RepoPath)> {
+        if !self.is_local() {
+            bail!("git operations are only supported on the host of a shared project");
+        }
+        let buffer_project_path = buffer
+            .read(cx)
+            .project_path(cx)
+            .context("failed to get buffer project path")?;
+        let worktree = self
+            .worktree_for_id(buffer_project_path.worktree_id, cx)
+            .context("failed to get worktree")?
+            .read(cx)
+            .as_local()
+            .context("worktree was not local")?
+            .snapshot();
+        let (repo_entry, local_repo_entry) = worktree
+            .repo_for_path(&buffer_project_path.path)
+            .ok_or(NoRepositoryError {})?;
+        let relative_path = repo_entry
+            .relativize(&worktree, &buffer_project_path.path)
+            .context("failed to relativize buffer path")?;
+        Ok((local_repo_entry.repo().clone(), relative_path))
+    }
+
     // RPC message handlers
 
     async fn handle_blame_buffer(
@@ -8752,27 +10849,143 @@ impl Project {
                                     &buffer_version,
                                     cx,
                                 ),
-                            )),
+                            )),
+                        })
+                        .collect(),
+                })
+            }
+            Some(proto::multi_lsp_query::Request::GetCodeActions(get_code_actions)) => {
+                let get_code_actions = GetCodeActions::from_proto(
+                    get_code_actions,
+                    project.clone(),
+                    buffer.clone(),
+                    cx.clone(),
+                )
+                .await?;
+
+                let all_actions = project
+                    .update(&mut cx, |project, cx| {
+                        project.request_multiple_lsp_locally(
+                            &buffer,
+                            Some(get_code_actions.range.start),
+                            GetCodeActions::supports_code_actions,
+                            get_code_actions,
+                            cx,
+                        )
+                    })?
+                    .await
+                    .into_iter();
+
+                project.update(&mut cx, |project, cx| proto::MultiLspQueryResponse {
+                    responses: all_actions
+                        .map(|code_actions| proto::LspResponse {
+                            response: Some(proto::lsp_response::Response::GetCodeActionsResponse(
+                                GetCodeActions::response_to_proto(
+                                    code_actions,
+                                    project,
+                                    sender_id,
+                                    &buffer_version,
+                                    cx,
+                                ),
+                            )),
+                        })
+                        .collect(),
+                })
+            }
+            Some(proto::multi_lsp_query::Request::GetCodeLens(get_code_lens)) => {
+                let get_code_lens = GetCodeLens::from_proto(
+                    get_code_lens,
+                    project.clone(),
+                    buffer.clone(),
+                    cx.clone(),
+                )
+                .await?;
+
+                let all_lenses = project
+                    .update(&mut cx, |project, cx| {
+                        project.request_multiple_lsp_locally(
+                            &buffer,
+                            None::<usize>,
+                            GetCodeLens::supports_code_lens,
+                            get_code_lens,
+                            cx,
+                        )
+                    })?
+                    .await
+                    .into_iter();
+
+                project.update(&mut cx, |project, cx| proto::MultiLspQueryResponse {
+                    responses: all_lenses
+                        .map(|code_lenses| proto::LspResponse {
+                            response: Some(proto::lsp_response::Response::GetCodeLensResponse(
+                                GetCodeLens::response_to_proto(
+                                    code_lenses,
+                                    project,
+                                    sender_id,
+                                    &buffer_version,
+                                    cx,
+                                ),
+                            )),
+                        })
+                        .collect(),
+                })
+            }
+            Some(proto::multi_lsp_query::Request::GetDocumentColors(get_document_colors)) => {
+                let get_document_colors = GetDocumentColors::from_proto(
+                    get_document_colors,
+                    project.clone(),
+                    buffer.clone(),
+                    cx.clone(),
+                )
+                .await?;
+
+                let all_colors = project
+                    .update(&mut cx, |project, cx| {
+                        project.request_multiple_lsp_locally(
+                            &buffer,
+                            None::<usize>,
+                            GetDocumentColors::supports_document_colors,
+                            get_document_colors,
+                            cx,
+                        )
+                    })?
+                    .await
+                    .into_iter();
+
+                project.update(&mut cx, |project, cx| proto::MultiLspQueryResponse {
+                    responses: all_colors
+                        .map(|document_colors| proto::LspResponse {
+                            response: Some(
+                                proto::lsp_response::Response::GetDocumentColorsResponse(
+                                    GetDocumentColors::response_to_proto(
+                                        document_colors,
+                                        project,
+                                        sender_id,
+                                        &buffer_version,
+                                        cx,
+                                    ),
+                                ),
+                            ),
                         })
                         .collect(),
                 })
             }
-            Some(proto::multi_lsp_query::Request::GetCodeActions(get_code_actions)) => {
-                let get_code_actions = GetCodeActions::from_proto(
-                    get_code_actions,
+            Some(proto::multi_lsp_query::Request::GetDocumentLinks(get_document_links)) => {
+                let get_document_links = GetDocumentLinks::from_proto(
+                    get_document_links,
                     project.clone(),
                     buffer.clone(),
                     cx.clone(),
                 )
                 .await?;
 
-                let all_actions = project
+                let all_links = project
                     .update(&mut cx, |project, cx| {
                         project.request_multiple_lsp_locally(
                             &buffer,
-                            Some(get_code_actions.range.start),
-                            GetCodeActions::supports_code_actions,
-                            get_code_actions,
+                            None::<usize>,
+                            GetDocumentLinks::supports_document_links,
+                            get_document_links,
                             cx,
                         )
                     })?
@@ -8780,17 +10993,19 @@ impl Project {
                     .into_iter();
 
                 project.update(&mut cx, |project, cx| proto::MultiLspQueryResponse {
-                    responses: all_actions
-                        .map(|code_actions| proto::LspResponse {
-                            response: Some(proto::lsp_response::Response::GetCodeActionsResponse(
-                                GetCodeActions::response_to_proto(
-                                    code_actions,
-                                    project,
-                                    sender_id,
-                                    &buffer_version,
-                                    cx,
+                    responses: all_links
+                        .map(|document_links| proto::LspResponse {
+                            response: Some(
+                                proto::lsp_response::Response::GetDocumentLinksResponse(
+                                    GetDocumentLinks::response_to_proto(
+                                        document_links,
+                                        project,
+                                        sender_id,
+                                        &buffer_version,
+                                        cx,
+                                    ),
                                 ),
-                            )),
+                            ),
                         })
                         .collect(),
                 })
@@ -9205,8 +11420,10 @@ impl Project {
                         language_server_id,
                         payload.token,
                         LanguageServerProgress {
+                            title: payload.title,
                             message: payload.message,
                             percentage: payload.percentage.map(|p| p as usize),
+                            is_cancellable: payload.is_cancellable,
                             last_update_at: Instant::now(),
                         },
                         cx,
@@ -9214,12 +11431,20 @@ impl Project {
                 }
 
                 proto::update_language_server::Variant::WorkProgress(payload) => {
+                    let is_cancellable = payload.is_cancellable.unwrap_or_else(|| {
+                        this.language_server_statuses
+                            .get(&language_server_id)
+                            .and_then(|status| status.pending_work.get(&payload.token))
+                            .map_or(false, |work| work.is_cancellable)
+                    });
                     this.on_lsp_work_progress(
                         language_server_id,
                         payload.token,
                         LanguageServerProgress {
+                            title: None,
                             message: payload.message,
                             percentage: payload.percentage.map(|p| p as usize),
+                            is_cancellable,
                             last_update_at: Instant::now(),
                         },
                         cx,
@@ -9726,12 +11951,20 @@ impl Project {
             }
         }
 
+        let lsp_completion = serde_json::to_vec(&lsp::CompletionItem {
+            additional_text_edits: completion.additional_text_edits,
+            label_details: completion.label_details,
+            detail: completion.detail,
+            ..Default::default()
+        })?;
+
         Ok(proto::ResolveCompletionDocumentationResponse {
             documentation,
             documentation_is_markdown,
             old_start,
             old_end,
             new_text,
+            lsp_completion,
         })
     }
 
@@ -9884,6 +12117,123 @@ impl Project {
         })
     }
 
+    async fn handle_resolve_code_lens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveCodeLens>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveCodeLensResponse> {
+        let lens = envelope
+            .payload
+            .lens
+            .ok_or_else(|| anyhow!("missing code lens"))?;
+        let lens = Self::deserialize_code_lens(lens)?;
+        let buffer = this.update(&mut cx, |this, _cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))
+        })??;
+        let resolved_lens = this
+            .update(&mut cx, |project, cx| {
+                project.resolve_code_lens(buffer, lens, cx)
+            })?
+            .await
+            .context("code lens resolve")?;
+        Ok(proto::ResolveCodeLensResponse {
+            lens: Some(Self::serialize_code_lens(&resolved_lens)),
+        })
+    }
+
+    async fn handle_resolve_document_link(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::ResolveDocumentLink>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::ResolveDocumentLinkResponse> {
+        let link = envelope
+            .payload
+            .link
+            .ok_or_else(|| anyhow!("missing document link"))?;
+        let link = Self::deserialize_document_link(link)?;
+        let buffer = this.update(&mut cx, |this, _cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))
+        })??;
+        let resolved_link = this
+            .update(&mut cx, |project, cx| {
+                project.resolve_document_link(buffer, link, cx)
+            })?
+            .await
+            .context("document link resolve")?;
+        Ok(proto::ResolveDocumentLinkResponse {
+            link: Some(Self::serialize_document_link(&resolved_link)),
+        })
+    }
+
+    async fn handle_get_color_presentations(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetColorPresentations>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetColorPresentationsResponse> {
+        let color = envelope
+            .payload
+            .color
+            .ok_or_else(|| anyhow!("missing color"))?;
+        let color = Self::deserialize_document_color(color)?;
+        let buffer = this.update(&mut cx, |this, _cx| {
+            let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+            this.opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))
+        })??;
+        let lsp_presentations = this
+            .update(&mut cx, |project, cx| {
+                project.lsp_color_presentations(&buffer, &color, color.color, cx)
+            })?
+            .await?;
+        Ok(proto::GetColorPresentationsResponse {
+            lsp_presentations: lsp_presentations
+                .iter()
+                .map(|presentation| serde_json::to_vec(presentation))
+                .collect::<serde_json::Result<_>>()?,
+        })
+    }
+
+    async fn handle_get_semantic_tokens(
+        this: Model<Self>,
+        envelope: TypedEnvelope<proto::GetSemanticTokens>,
+        _: Arc<Client>,
+        mut cx: AsyncAppContext,
+    ) -> Result<proto::GetSemanticTokensResponse> {
+        let buffer_id = BufferId::new(envelope.payload.buffer_id)?;
+        let buffer = this.update(&mut cx, |this, _| {
+            this.opened_buffers
+                .get(&buffer_id)
+                .and_then(|buffer| buffer.upgrade())
+                .ok_or_else(|| anyhow!("unknown buffer id {}", buffer_id))
+        })??;
+        buffer
+            .update(&mut cx, |buffer, _| {
+                buffer.wait_for_version(deserialize_version(&envelope.payload.version))
+            })?
+            .await
+            .with_context(|| format!("waiting for version for buffer {}", buffer.entity_id()))?;
+        let tokens = this
+            .update(&mut cx, |project, cx| project.semantic_tokens(&buffer, cx))?
+            .await?;
+        Ok(proto::GetSemanticTokensResponse {
+            tokens: tokens.iter().map(Self::serialize_semantic_token).collect(),
+            version: serialize_version(&buffer.update(&mut cx, |buffer, _| buffer.version())?),
+        })
+    }
+
     async fn handle_task_context_for_location(
         project: Model<Self>,
         envelope: TypedEnvelope<proto::TaskContextForLocation>,
@@ -9950,6 +12300,8 @@ impl Project {
             .await
             .context("receiving task templates")?
             .into_iter()
+            // Debug sessions can only be started on the host.
+            .filter(|(_, template)| template.task_type == TaskType::Script)
             .map(|(kind, template)| {
                 let kind = Some(match kind {
                     TaskSourceKind::UserInput => proto::task_source_kind::Kind::UserInput(
@@ -9979,20 +12331,7 @@ impl Project {
                     }
                 });
                 let kind = Some(proto::TaskSourceKind { kind });
-                let template = Some(proto::TaskTemplate {
-                    label: template.label,
-                    command: template.command,
-                    args: template.args,
-                    env: template.env.into_iter().collect(),
-                    cwd: template.cwd,
-                    use_new_terminal: template.use_new_terminal,
-                    allow_concurrent_runs: template.allow_concurrent_runs,
-                    reveal: match template.reveal {
-                        RevealStrategy::Always => proto::RevealStrategy::Always as i32,
-                        RevealStrategy::Never => proto::RevealStrategy::Never as i32,
-                    },
-                    tags: template.tags,
-                });
+                let template = Some(serialize_task_template(template));
                 proto::TemplatePair { kind, template }
             })
             .collect();
@@ -10696,6 +13035,205 @@ impl Project {
         })
     }
 
+    fn serialize_code_lens(lens: &CodeLens) -> proto::CodeLens {
+        proto::CodeLens {
+            server_id: lens.server_id.0 as u64,
+            start: Some(serialize_anchor(&lens.range.start)),
+            end: Some(serialize_anchor(&lens.range.end)),
+            lsp_lens: serde_json::to_vec(&lens.lsp_lens).unwrap(),
+        }
+    }
+
+    fn deserialize_code_lens(lens: proto::CodeLens) -> Result<CodeLens> {
+        let start = lens
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = lens
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_lens = serde_json::from_slice(&lens.lsp_lens)?;
+        Ok(CodeLens {
+            server_id: LanguageServerId(lens.server_id as usize),
+            range: start..end,
+            lsp_lens,
+        })
+    }
+
+    fn serialize_document_link(link: &DocumentLink) -> proto::DocumentLink {
+        proto::DocumentLink {
+            server_id: link.server_id.0 as u64,
+            start: Some(serialize_anchor(&link.range.start)),
+            end: Some(serialize_anchor(&link.range.end)),
+            lsp_link: serde_json::to_vec(&link.lsp_link).unwrap(),
+        }
+    }
+
+    fn deserialize_document_link(link: proto::DocumentLink) -> Result<DocumentLink> {
+        let start = link
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = link
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let lsp_link = serde_json::from_slice(&link.lsp_link)?;
+        Ok(DocumentLink {
+            server_id: LanguageServerId(link.server_id as usize),
+            range: start..end,
+            lsp_link,
+        })
+    }
+
+    fn serialize_folding_range(range: &FoldingRange) -> proto::FoldingRange {
+        proto::FoldingRange {
+            start: Some(serialize_anchor(&range.range.start)),
+            end: Some(serialize_anchor(&range.range.end)),
+            kind: range.kind.as_ref().map(|kind| {
+                match kind {
+                    lsp::FoldingRangeKind::Comment => "comment",
+                    lsp::FoldingRangeKind::Imports => "imports",
+                    lsp::FoldingRangeKind::Region => "region",
+                }
+                .to_string()
+            }),
+        }
+    }
+
+    fn deserialize_folding_range(range: proto::FoldingRange) -> Result<FoldingRange> {
+        let start = range
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = range
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        let kind = range.kind.and_then(|kind| match kind.as_str() {
+            "comment" => Some(lsp::FoldingRangeKind::Comment),
+            "imports" => Some(lsp::FoldingRangeKind::Imports),
+            "region" => Some(lsp::FoldingRangeKind::Region),
+            _ => None,
+        });
+        Ok(FoldingRange {
+            range: start..end,
+            kind,
+        })
+    }
+
+    fn serialize_call_hierarchy_item(item: &CallHierarchyItem) -> proto::CallHierarchyItem {
+        proto::CallHierarchyItem {
+            server_id: item.server_id.0 as u64,
+            lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+        }
+    }
+
+    fn deserialize_call_hierarchy_item(
+        item: proto::CallHierarchyItem,
+    ) -> Result<CallHierarchyItem> {
+        Ok(CallHierarchyItem {
+            server_id: LanguageServerId(item.server_id as usize),
+            lsp_item: serde_json::from_slice(&item.lsp_item)?,
+        })
+    }
+
+    fn serialize_call_hierarchy_call(call: &CallHierarchyCall) -> proto::CallHierarchyCall {
+        proto::CallHierarchyCall {
+            item: Some(Self::serialize_call_hierarchy_item(&call.item)),
+            lsp_ranges: serde_json::to_vec(&call.from_ranges).unwrap(),
+        }
+    }
+
+    fn deserialize_call_hierarchy_call(
+        call: proto::CallHierarchyCall,
+    ) -> Result<CallHierarchyCall> {
+        let item = call.item.ok_or_else(|| anyhow!("missing item"))?;
+        Ok(CallHierarchyCall {
+            item: Self::deserialize_call_hierarchy_item(item)?,
+            from_ranges: serde_json::from_slice(&call.lsp_ranges)?,
+        })
+    }
+
+    fn serialize_type_hierarchy_item(item: &TypeHierarchyItem) -> proto::TypeHierarchyItem {
+        proto::TypeHierarchyItem {
+            server_id: item.server_id.0 as u64,
+            lsp_item: serde_json::to_vec(&item.lsp_item).unwrap(),
+        }
+    }
+
+    fn deserialize_type_hierarchy_item(
+        item: proto::TypeHierarchyItem,
+    ) -> Result<TypeHierarchyItem> {
+        Ok(TypeHierarchyItem {
+            server_id: LanguageServerId(item.server_id as usize),
+            lsp_item: serde_json::from_slice(&item.lsp_item)?,
+        })
+    }
+
+    fn serialize_document_color(color: &DocumentColor) -> proto::DocumentColor {
+        proto::DocumentColor {
+            server_id: color.server_id.0 as u64,
+            start: Some(serialize_anchor(&color.range.start)),
+            end: Some(serialize_anchor(&color.range.end)),
+            red: color.color.red,
+            green: color.color.green,
+            blue: color.color.blue,
+            alpha: color.color.alpha,
+        }
+    }
+
+    fn deserialize_document_color(color: proto::DocumentColor) -> Result<DocumentColor> {
+        let start = color
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = color
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        Ok(DocumentColor {
+            server_id: LanguageServerId(color.server_id as usize),
+            range: start..end,
+            color: lsp::Color {
+                red: color.red,
+                green: color.green,
+                blue: color.blue,
+                alpha: color.alpha,
+            },
+        })
+    }
+
+    fn serialize_semantic_token(token: &SemanticToken) -> proto::SemanticToken {
+        proto::SemanticToken {
+            start: Some(serialize_anchor(&token.range.start)),
+            end: Some(serialize_anchor(&token.range.end)),
+            token_type: token.token_type.to_string(),
+            modifiers: token
+                .modifiers
+                .iter()
+                .map(|modifier| modifier.to_string())
+                .collect(),
+        }
+    }
+
+    fn deserialize_semantic_token(token: proto::SemanticToken) -> Result<SemanticToken> {
+        let start = token
+            .start
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid start"))?;
+        let end = token
+            .end
+            .and_then(deserialize_anchor)
+            .ok_or_else(|| anyhow!("invalid end"))?;
+        Ok(SemanticToken {
+            range: start..end,
+            token_type: token.token_type.into(),
+            modifiers: token.modifiers.into_iter().map(Into::into).collect(),
+        })
+    }
+
     async fn handle_buffer_saved(
         this: Model<Self>,
         envelope: TypedEnvelope<proto::BufferSaved>,
@@ -10955,6 +13493,31 @@ impl Project {
             .find(|s| s.0.is_primary)
     }
 
+    /// Returns the language server to format the buffer with, according to the
+    /// `language_server_routing` settings of its language.
+    fn formatting_language_server_for_buffer(
+        &self,
+        buffer: &Buffer,
+        cx: &AppContext,
+    ) -> Option<(&Arc<CachedLspAdapter>, &Arc<LanguageServer>)> {
+        let mut servers = self
+            .language_servers_for_buffer(buffer, cx)
+            .collect::<Vec<_>>();
+        servers.sort_by_key(|(adapter, _)| !adapter.is_primary);
+        let available_servers = servers
+            .iter()
+            .map(|(adapter, _)| adapter.name.clone())
+            .collect::<Vec<_>>();
+        let server_name = language_settings(buffer.language(), buffer.file(), cx)
+            .language_server_routing
+            .formatting_servers(&available_servers)
+            .into_iter()
+            .next()?;
+        servers
+            .into_iter()
+            .find(|(adapter, _)| adapter.name == server_name)
+    }
+
     pub fn language_server_for_buffer(
         &self,
         buffer: &Buffer,
@@ -11119,24 +13682,7 @@ impl Project {
                         }
                     };
 
-                    let proto_template = template_pair.template?;
-                    let reveal = match proto::RevealStrategy::from_i32(proto_template.reveal)
-                        .unwrap_or(proto::RevealStrategy::Always)
-                    {
-                        proto::RevealStrategy::Always => RevealStrategy::Always,
-                        proto::RevealStrategy::Never => RevealStrategy::Never,
-                    };
-                    let task_template = TaskTemplate {
-                        label: proto_template.label,
-                        command: proto_template.command,
-                        args: proto_template.args,
-                        env: proto_template.env.into_iter().collect(),
-                        cwd: proto_template.cwd,
-                        use_new_terminal: proto_template.use_new_terminal,
-                        allow_concurrent_runs: proto_template.allow_concurrent_runs,
-                        reveal,
-                        tags: proto_template.tags,
-                    };
+                    let task_template = deserialize_task_template(template_pair.template?);
                     Some((task_source_kind, task_template))
                 })
                 .collect())
@@ -11350,6 +13896,7 @@ async fn search_snapshots(
     results_tx: &Sender<SearchMatchCandidate>,
     opened_buffers: &HashMap<Arc<Path>, (Model<Buffer>, BufferSnapshot)>,
     include_root: bool,
+    index_filter: Option<&SearchIndexFilter>,
     fs: &Arc<dyn Fs>,
 ) {
     let mut snapshot_start_ix = 0;
@@ -11390,7 +13937,10 @@ async fn search_snapshots(
                     query.file_matches(Some(&entry.path))
                 };
 
-                let matches = if matched_path {
+                // Files that the search index rules out aren't read at all.
+                let may_match =
+                    index_filter.map_or(true, |filter| filter.may_match(snapshot.id(), entry));
+                let matches = if matched_path && may_match {
                     abs_path.clear();
                     abs_path.push(&snapshot.abs_path());
                     abs_path.push(&entry.path);
@@ -11623,6 +14173,9 @@ pub struct ProjectLspAdapterDelegate {
     http_client: Arc<dyn HttpClient>,
     language_registry: Arc<LanguageRegistry>,
     shell_env: Mutex<Option<HashMap<String, String>>>,
+    /// Whether the project is in restricted mode, where hooks like direnv must not be run by
+    /// loading the shell environment of the worktree.
+    is_restricted: bool,
 }
 
 impl ProjectLspAdapterDelegate {
@@ -11638,10 +14191,15 @@ impl ProjectLspAdapterDelegate {
             http_client: project.client.http_client(),
             language_registry: project.languages.clone(),
             shell_env: Default::default(),
+            is_restricted: project.is_restricted,
         })
     }
 
     async fn load_shell_env(&self) {
+        if self.is_restricted {
+            *self.shell_env.lock() = Some(HashMap::default());
+            return;
+        }
         let worktree_abs_path = self.worktree.abs_path();
         let shell_env = load_shell_environment(&worktree_abs_path)
             .await
@@ -11718,6 +14276,60 @@ impl LspAdapterDelegate for ProjectLspAdapterDelegate {
     }
 }
 
+/// Decodes the relative positions of semantic tokens into anchors in the snapshot of the buffer
+/// they were requested for, naming their types and modifiers with the server's legend.
+fn decode_semantic_tokens(
+    data: &[lsp::SemanticToken],
+    legend: &lsp::SemanticTokensLegend,
+    snapshot: &TextBufferSnapshot,
+) -> Vec<SemanticToken> {
+    let token_types = legend
+        .token_types
+        .iter()
+        .map(|token_type| Arc::<str>::from(token_type.as_str()))
+        .collect::<Vec<_>>();
+    let token_modifiers = legend
+        .token_modifiers
+        .iter()
+        .map(|modifier| Arc::<str>::from(modifier.as_str()))
+        .collect::<Vec<_>>();
+
+    let mut tokens = Vec::with_capacity(data.len());
+    let mut row = 0;
+    let mut column = 0;
+    for token in data {
+        if token.delta_line > 0 {
+            row += token.delta_line;
+            column = token.delta_start;
+        } else {
+            column += token.delta_start;
+        }
+        let Some(token_type) = token_types.get(token.token_type as usize) else {
+            continue;
+        };
+        let modifiers = token_modifiers
+            .iter()
+            .enumerate()
+            .filter(|(ix, _)| *ix < 32 && token.token_modifiers_bitset & (1 << ix) != 0)
+            .map(|(_, modifier)| modifier.clone())
+            .collect();
+        let start = snapshot.clip_point_utf16(Unclipped(PointUtf16::new(row, column)), Bias::Left);
+        let end = snapshot.clip_point_utf16(
+            Unclipped(PointUtf16::new(row, column + token.length)),
+            Bias::Left,
+        );
+        if start == end {
+            continue;
+        }
+        tokens.push(SemanticToken {
+            range: snapshot.anchor_after(start)..snapshot.anchor_before(end),
+            token_type: token_type.clone(),
+            modifiers,
+        });
+    }
+    tokens
+}
+
 fn serialize_symbol(symbol: &Symbol) -> proto::Symbol {
     proto::Symbol {
         language_server_name: symbol.language_server_name.0.to_string(),
@@ -11738,6 +14350,46 @@ fn serialize_symbol(symbol: &Symbol) -> proto::Symbol {
     }
 }
 
+fn serialize_task_template(template: TaskTemplate) -> proto::TaskTemplate {
+    proto::TaskTemplate {
+        label: template.label,
+        command: template.command,
+        args: template.args,
+        env: template.env.into_iter().collect(),
+        cwd: template.cwd,
+        use_new_terminal: template.use_new_terminal,
+        allow_concurrent_runs: template.allow_concurrent_runs,
+        dedicated_terminal: template.dedicated_terminal,
+        reveal: match template.reveal {
+            RevealStrategy::Always => proto::RevealStrategy::Always as i32,
+            RevealStrategy::Never => proto::RevealStrategy::Never as i32,
+        },
+        tags: template.tags,
+    }
+}
+
+fn deserialize_task_template(template: proto::TaskTemplate) -> TaskTemplate {
+    let reveal = match proto::RevealStrategy::from_i32(template.reveal)
+        .unwrap_or(proto::RevealStrategy::Always)
+    {
+        proto::RevealStrategy::Always => RevealStrategy::Always,
+        proto::RevealStrategy::Never => RevealStrategy::Never,
+    };
+    TaskTemplate {
+        label: template.label,
+        command: template.command,
+        args: template.args,
+        env: template.env.into_iter().collect(),
+        cwd: template.cwd,
+        use_new_terminal: template.use_new_terminal,
+        allow_concurrent_runs: template.allow_concurrent_runs,
+        dedicated_terminal: template.dedicated_terminal,
+        reveal,
+        tags: template.tags,
+        ..TaskTemplate::default()
+    }
+}
+
 fn relativize_path(base: &Path, path: &Path) -> PathBuf {
     let mut path_components = path.components();
     let mut base_components = base.components();
@@ -11818,6 +14470,50 @@ impl Completion {
     pub fn is_snippet(&self) -> bool {
         self.lsp_completion.insert_text_format == Some(lsp::InsertTextFormat::SNIPPET)
     }
+
+    /// The text that confirming this completion inserts elsewhere in the buffer, such as an
+    /// auto-import like `use std::collections::HashMap;`, collapsed onto a single line.
+    pub fn additional_edits_text(&self) -> Option<String> {
+        let edits = self.lsp_completion.additional_text_edits.as_ref()?;
+        let text = edits
+            .iter()
+            .flat_map(|edit| edit.new_text.lines())
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>()
+            .join(" ");
+        (!text.is_empty()).then_some(text)
+    }
+
+    /// The detail the language server shows right after the label, such as a function's
+    /// signature, unless the label already includes it.
+    pub fn label_detail(&self) -> Option<&str> {
+        let detail = self
+            .lsp_completion
+            .label_details
+            .as_ref()?
+            .detail
+            .as_deref()?;
+        let detail = detail.trim();
+        (!detail.is_empty() && !self.label.text.contains(detail)).then_some(detail)
+    }
+}
+
+/// Copies the fields that language servers commonly fill in lazily, when resolving a completion
+/// item, onto the item that was originally completed.
+fn merge_resolved_completion_item(
+    completion: &mut lsp::CompletionItem,
+    resolved: &lsp::CompletionItem,
+) {
+    if resolved.additional_text_edits.is_some() {
+        completion.additional_text_edits = resolved.additional_text_edits.clone();
+    }
+    if resolved.label_details.is_some() {
+        completion.label_details = resolved.label_details.clone();
+    }
+    if resolved.detail.is_some() {
+        completion.detail = resolved.detail.clone();
+    }
 }
 
 async fn wait_for_loading_buffer(
@@ -12032,6 +14728,36 @@ fn remove_empty_hover_blocks(mut hover: Hover) -> Option<Hover> {
     }
 }
 
+/// Returns the diff hunks of the buffer, along with the rows of the diff base that they replace.
+fn diff_hunk_base_rows(
+    snapshot: &BufferSnapshot,
+    diff_base: &Rope,
+) -> Vec<(git::diff::DiffHunk<u32>, Range<u32>)> {
+    // The diff base ranges of the hunks only adding lines are empty, but not positioned, so the
+    // rows are counted from the start of the buffer instead.
+    let mut delta = 0;
+    snapshot
+        .git_diff_hunks_in_row_range(0..u32::MAX)
+        .map(|hunk| {
+            let base_start = diff_base.offset_to_point(hunk.diff_base_byte_range.start);
+            let base_end = diff_base.offset_to_point(hunk.diff_base_byte_range.end);
+            // The last line of the diff base may not end with a newline.
+            let base_row_count = base_end.row - base_start.row + (base_end.column > 0) as u32;
+            let start = (hunk.associated_range.start as i64 + delta) as u32;
+            delta += base_row_count as i64 - hunk.associated_range.len() as i64;
+            (hunk, start..start + base_row_count)
+        })
+        .collect()
+}
+
+fn row_offset(text: &Rope, row: u32) -> usize {
+    if row > text.max_point().row {
+        text.len()
+    } else {
+        text.point_to_offset(Point::new(row, 0))
+    }
+}
+
 #[derive(Debug)]
 pub struct NoRepositoryError {}
 
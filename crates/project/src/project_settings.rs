@@ -13,6 +13,8 @@ pub struct ProjectSettings {
     /// - initialization_options
     /// To override settings for a language, add an entry for that language server's
     /// name to the lsp value.
+    /// To define a language server that isn't provided by a language or an
+    /// extension, add an entry with its `binary` and `languages`.
     /// Default: null
     #[serde(default)]
     pub lsp: HashMap<Arc<str>, LspSettings>,
@@ -20,6 +22,100 @@ pub struct ProjectSettings {
     /// Configuration for Git-related features
     #[serde(default)]
     pub git: GitSettings,
+
+    /// The size, in bytes, above which files are opened in large file mode:
+    /// without language detection, syntax highlighting and language servers.
+    ///
+    /// Default: 20000000
+    #[serde(default = "default_large_file_threshold")]
+    pub large_file_threshold: u64,
+
+    /// Configuration for the history of the contents files are saved with,
+    /// kept independently of version control.
+    #[serde(default)]
+    pub local_history: LocalHistorySettings,
+
+    /// Configuration for the index of the trigrams contained in the files of
+    /// local projects, which speeds up literal project searches.
+    #[serde(default)]
+    pub search_index: SearchIndexSettings,
+}
+
+impl ProjectSettings {
+    /// Whether a buffer of the given length, in bytes, should be opened in large file mode.
+    pub fn is_large_file(&self, len: usize) -> bool {
+        len as u64 > self.large_file_threshold
+    }
+}
+
+const fn default_large_file_threshold() -> u64 {
+    20_000_000
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct LocalHistorySettings {
+    /// Whether to keep the contents files are saved with.
+    ///
+    /// Default: true
+    #[serde(default = "true_value")]
+    pub enabled: bool,
+    /// The number of versions to keep for each file. The oldest ones are
+    /// deleted once a file has more, and none are kept when it's zero.
+    ///
+    /// Default: 50
+    #[serde(default = "default_local_history_max_entries")]
+    pub max_entries: usize,
+    /// The size, in bytes, above which saved files aren't kept.
+    ///
+    /// Default: 1000000
+    #[serde(default = "default_local_history_max_file_size")]
+    pub max_file_size: usize,
+}
+
+impl Default for LocalHistorySettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_entries: default_local_history_max_entries(),
+            max_file_size: default_local_history_max_file_size(),
+        }
+    }
+}
+
+const fn default_local_history_max_entries() -> usize {
+    50
+}
+
+const fn default_local_history_max_file_size() -> usize {
+    1_000_000
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema)]
+pub struct SearchIndexSettings {
+    /// Whether to index the files of local projects.
+    ///
+    /// Default: false
+    #[serde(default)]
+    pub enabled: bool,
+    /// The size, in bytes, above which files aren't indexed and are always
+    /// read when searching.
+    ///
+    /// Default: 1000000
+    #[serde(default = "default_search_index_max_file_size")]
+    pub max_file_size: usize,
+}
+
+impl Default for SearchIndexSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_file_size: default_search_index_max_file_size(),
+        }
+    }
+}
+
+const fn default_search_index_max_file_size() -> usize {
+    1_000_000
 }
 
 #[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
@@ -95,14 +191,39 @@ pub struct BinarySettings {
     pub path: Option<String>,
     pub arguments: Option<Vec<String>>,
     pub path_lookup: Option<bool>,
+    /// Environment variables to set for the language server process.
+    pub env: Option<HashMap<String, String>>,
 }
 
 #[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub struct LspSettings {
     pub binary: Option<BinarySettings>,
+    /// The languages to run this language server for, when it isn't provided
+    /// by a language or an extension. Requires `binary.path` to be set, and
+    /// only applies in the user settings.
+    ///
+    /// Default: null
+    pub languages: Option<Vec<String>>,
     pub initialization_options: Option<serde_json::Value>,
     pub settings: Option<serde_json::Value>,
+    /// How diagnostics are received from the language server.
+    ///
+    /// Default: auto
+    pub diagnostics: Option<DiagnosticsModel>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticsModel {
+    /// Pull diagnostics when the language server supports it, while still accepting the
+    /// diagnostics it publishes for documents that haven't been pulled.
+    #[default]
+    Auto,
+    /// Only accept the diagnostics published by the language server.
+    Push,
+    /// Only pull diagnostics, ignoring those published by the language server.
+    Pull,
 }
 
 impl Settings for ProjectSettings {
@@ -3,7 +3,9 @@ use fs::FakeFs;
 use futures::{future, StreamExt};
 use gpui::{AppContext, UpdateGlobal};
 use language::{
-    language_settings::{AllLanguageSettings, LanguageSettingsContent},
+    language_settings::{
+        AllLanguageSettings, CompletionMerge, LanguageServerRouting, LanguageSettingsContent,
+    },
     tree_sitter_rust, tree_sitter_typescript, Diagnostic, FakeLspAdapter, LanguageConfig,
     LanguageMatcher, LineEnding, OffsetRangeExt, Point, ToPoint,
 };
@@ -666,6 +668,92 @@ async fn test_managing_language_servers(cx: &mut gpui::TestAppContext) {
     );
 }
 
+#[gpui::test]
+async fn test_large_files_without_language_servers(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.large_file_threshold = 100;
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/the-root",
+        json!({
+            "large.rs": "const A: i32 = 1;\n".repeat(10),
+            "small.rs": "const B: i32 = 2;",
+            "other.rs": "const C: i32 = 3;",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/the-root".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_rust_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            name: "the-rust-language-server",
+            ..Default::default()
+        },
+    );
+
+    // Large files are opened as plain text, without notifying language servers.
+    let large_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/the-root/large.rs", cx)
+        })
+        .await
+        .unwrap();
+    let _small_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/the-root/small.rs", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    large_buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.language().map(|l| l.name()), None);
+    });
+
+    let mut fake_rust_server = fake_rust_servers.next().await.unwrap();
+    assert_eq!(
+        fake_rust_server
+            .receive_notification::<lsp::notification::DidOpenTextDocument>()
+            .await
+            .text_document
+            .uri
+            .as_str(),
+        "file:///the-root/small.rs"
+    );
+
+    // Their language can be detected on demand, which still doesn't notify language servers.
+    project.update(cx, |project, cx| {
+        project.assign_detected_language(&large_buffer, cx)
+    });
+    large_buffer.update(cx, |buffer, _| {
+        assert_eq!(buffer.language().map(|l| l.name()), Some("Rust".into()));
+    });
+    let _other_buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/the-root/other.rs", cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        fake_rust_server
+            .receive_notification::<lsp::notification::DidOpenTextDocument>()
+            .await
+            .text_document
+            .uri
+            .as_str(),
+        "file:///the-root/other.rs"
+    );
+}
+
 #[gpui::test]
 async fn test_reporting_fs_changes_to_language_servers(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -1301,387 +1389,846 @@ async fn test_restarting_server_with_diagnostics_running(cx: &mut gpui::TestAppC
 }
 
 #[gpui::test]
-async fn test_restarting_server_with_diagnostics_published(cx: &mut gpui::TestAppContext) {
+async fn test_cancelling_language_server_work(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
+    let progress_token = "the-progress-token";
+
     let fs = FakeFs::new(cx.executor());
-    fs.insert_tree("/dir", json!({ "a.rs": "x" })).await;
+    fs.insert_tree("/dir", json!({ "a.rs": "" })).await;
 
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
     language_registry.add(rust_lang());
-    let mut fake_servers =
-        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            name: "the-language-server",
+            ..Default::default()
+        },
+    );
 
-    let buffer = project
+    let _buffer = project
         .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
         .await
         .unwrap();
 
-    // Publish diagnostics
-    let fake_server = fake_servers.next().await.unwrap();
-    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
-        uri: Url::from_file_path("/dir/a.rs").unwrap(),
-        version: None,
-        diagnostics: vec![lsp::Diagnostic {
-            range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 0)),
-            severity: Some(lsp::DiagnosticSeverity::ERROR),
-            message: "the message".to_string(),
-            ..Default::default()
-        }],
+    let mut fake_server = fake_servers.next().await.unwrap();
+    fake_server
+        .request::<lsp::request::WorkDoneProgressCreate>(lsp::WorkDoneProgressCreateParams {
+            token: lsp::NumberOrString::String(progress_token.into()),
+        })
+        .await
+        .unwrap();
+    fake_server.notify::<lsp::notification::Progress>(lsp::ProgressParams {
+        token: lsp::NumberOrString::String(progress_token.into()),
+        value: lsp::ProgressParamsValue::WorkDone(lsp::WorkDoneProgress::Begin(
+            lsp::WorkDoneProgressBegin {
+                title: "Indexing".into(),
+                cancellable: Some(true),
+                ..Default::default()
+            },
+        )),
     });
-
     cx.executor().run_until_parked();
-    buffer.update(cx, |buffer, _| {
-        assert_eq!(
-            buffer
-                .snapshot()
-                .diagnostics_in_range::<_, usize>(0..1, false)
-                .map(|entry| entry.diagnostic.message.clone())
-                .collect::<Vec<_>>(),
-            ["the message".to_string()]
-        );
-    });
-    project.update(cx, |project, cx| {
-        assert_eq!(
-            project.diagnostic_summary(false, cx),
-            DiagnosticSummary {
-                error_count: 1,
-                warning_count: 0,
-            }
-        );
-    });
 
-    project.update(cx, |project, cx| {
-        project.restart_language_servers_for_buffers([buffer.clone()], cx);
+    let server_id = project.update(cx, |project, _| {
+        let (server_id, status) = project.language_server_statuses_by_id().next().unwrap();
+        let work = &status.pending_work[progress_token];
+        assert_eq!(work.title.as_deref(), Some("Indexing"));
+        assert!(work.is_cancellable);
+        server_id
     });
 
-    // The diagnostics are cleared.
-    cx.executor().run_until_parked();
-    buffer.update(cx, |buffer, _| {
-        assert_eq!(
-            buffer
-                .snapshot()
-                .diagnostics_in_range::<_, usize>(0..1, false)
-                .map(|entry| entry.diagnostic.message.clone())
-                .collect::<Vec<_>>(),
-            Vec::<String>::new(),
-        );
-    });
     project.update(cx, |project, cx| {
-        assert_eq!(
-            project.diagnostic_summary(false, cx),
-            DiagnosticSummary {
-                error_count: 0,
-                warning_count: 0,
-            }
-        );
+        project.cancel_language_server_work(server_id, progress_token.into(), cx)
     });
+    let params = fake_server
+        .receive_notification::<lsp::notification::WorkDoneProgressCancel>()
+        .await;
+    assert_eq!(
+        params.token,
+        lsp::NumberOrString::String(progress_token.into())
+    );
 }
 
 #[gpui::test]
-async fn test_restarted_server_reporting_invalid_buffer_version(cx: &mut gpui::TestAppContext) {
+async fn test_debug_session_breakpoints_and_stepping(cx: &mut gpui::TestAppContext) {
+    use dap::{
+        requests::{Launch, Next, SetBreakpoints, StackTrace, Threads},
+        types::{SetBreakpointsResponse, StackFrame, StackTraceResponse, Thread, ThreadsResponse},
+    };
+    use dap_store::DebugSessionStatus;
+
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
-    fs.insert_tree("/dir", json!({ "a.rs": "" })).await;
-
+    fs.insert_tree(
+        "/dir",
+        json!({ "main.rs": "fn main() {\n    let a = 1;\n    let b = 2;\n    let c = 3;\n}\n" }),
+    )
+    .await;
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
-    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-
-    language_registry.add(rust_lang());
-    let mut fake_servers =
-        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
-
     let buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
         .await
         .unwrap();
+    let dap_store = project.read_with(cx, |project, _| project.dap_store().clone());
 
-    // Before restarting the server, report diagnostics with an unknown buffer version.
-    let fake_server = fake_servers.next().await.unwrap();
-    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
-        uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
-        version: Some(10000),
-        diagnostics: Vec::new(),
+    // Breakpoints follow the edits made to their buffer.
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.toggle_breakpoints(&buffer, [1, 3], cx)
+    });
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(0..0, "use std::io;\n")], None, cx)
+    });
+    dap_store.read_with(cx, |dap_store, cx| {
+        assert_eq!(
+            dap_store.breakpoint_rows(Path::new("/dir/main.rs"), cx),
+            [2, 4]
+        );
+    });
+
+    let (client_id, fake_adapter) = dap_store.update(cx, |dap_store, cx| {
+        dap_store.start_fake_session(
+            task::DebugAdapterConfig {
+                label: "Debug main".into(),
+                command: "fake-adapter".into(),
+                args: Vec::new(),
+                env: HashMap::default(),
+                cwd: None,
+                request: task::DebugRequestType::Launch,
+                request_args: json!({ "program": "main" }),
+            },
+            cx,
+        )
+    });
+    let sent_breakpoints = Arc::new(Mutex::new(Vec::new()));
+    let stepped_threads = Arc::new(Mutex::new(Vec::new()));
+    fake_adapter.handle_request::<Launch, _>(|_| Ok(serde_json::Value::Null));
+    fake_adapter.handle_request::<SetBreakpoints, _>({
+        let sent_breakpoints = sent_breakpoints.clone();
+        move |arguments| {
+            let lines = arguments
+                .breakpoints
+                .unwrap_or_default()
+                .iter()
+                .map(|breakpoint| breakpoint.line)
+                .collect::<Vec<_>>();
+            sent_breakpoints
+                .lock()
+                .push((arguments.source.path.unwrap(), lines));
+            Ok(SetBreakpointsResponse::default())
+        }
+    });
+    fake_adapter.handle_request::<Threads, _>(|_| {
+        Ok(ThreadsResponse {
+            threads: vec![Thread {
+                id: 1,
+                name: "main".into(),
+            }],
+        })
+    });
+    fake_adapter.handle_request::<StackTrace, _>(|arguments| {
+        assert_eq!(arguments.thread_id, 1);
+        Ok(StackTraceResponse {
+            stack_frames: vec![StackFrame {
+                id: 10,
+                name: "main::main".into(),
+                line: 3,
+                column: 1,
+                ..Default::default()
+            }],
+            total_frames: Some(1),
+        })
+    });
+    fake_adapter.handle_request::<Next, _>({
+        let stepped_threads = stepped_threads.clone();
+        move |arguments| {
+            stepped_threads.lock().push(arguments.thread_id);
+            Ok(serde_json::Value::Null)
+        }
     });
+
+    // The breakpoints are sent once the adapter is initialized, with 1-based lines.
+    fake_adapter.send_event("initialized", None);
     cx.executor().run_until_parked();
+    assert_eq!(
+        sent_breakpoints.lock().drain(..).collect::<Vec<_>>(),
+        [("/dir/main.rs".to_string(), vec![3, 5])]
+    );
 
-    project.update(cx, |project, cx| {
-        project.restart_language_servers_for_buffers([buffer.clone()], cx);
+    fake_adapter.send_event(
+        "stopped",
+        Some(json!({ "reason": "breakpoint", "threadId": 1 })),
+    );
+    cx.executor().run_until_parked();
+    dap_store.read_with(cx, |dap_store, _| {
+        let session = dap_store.session(client_id).unwrap();
+        assert_eq!(session.status(), DebugSessionStatus::Stopped);
+        assert_eq!(session.stop_reason(), Some("breakpoint"));
+        assert_eq!(
+            session
+                .active_stack_frame()
+                .map(|frame| frame.name.as_str()),
+            Some("main::main")
+        );
     });
-    let mut fake_server = fake_servers.next().await.unwrap();
-    let notification = fake_server
-        .receive_notification::<lsp::notification::DidOpenTextDocument>()
+
+    dap_store
+        .update(cx, |dap_store, cx| dap_store.step_over(client_id, cx))
         .await
-        .text_document;
-    assert_eq!(notification.version, 0);
+        .unwrap();
+    assert_eq!(*stepped_threads.lock(), [1]);
+    dap_store.read_with(cx, |dap_store, _| {
+        let session = dap_store.session(client_id).unwrap();
+        assert_eq!(session.status(), DebugSessionStatus::Running);
+        assert!(session.stack_frames().is_empty());
+    });
+
+    // Toggling a breakpoint sends the new breakpoints of the file to the running session.
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.toggle_breakpoints(&buffer, [2], cx)
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(
+        sent_breakpoints.lock().drain(..).collect::<Vec<_>>(),
+        [("/dir/main.rs".to_string(), vec![5])]
+    );
+
+    fake_adapter.send_event("terminated", None);
+    cx.executor().run_until_parked();
+    dap_store.read_with(cx, |dap_store, _| {
+        assert!(dap_store.session(client_id).is_none());
+        assert!(dap_store.active_session().is_none());
+    });
 }
 
 #[gpui::test]
-async fn test_toggling_enable_language_server(cx: &mut gpui::TestAppContext) {
+async fn test_debug_session_breakpoint_conditions_and_logpoints(cx: &mut gpui::TestAppContext) {
+    use dap::{
+        requests::{Launch, SetBreakpoints},
+        types::{Capabilities, SetBreakpointsResponse, SourceBreakpoint},
+    };
+    use dap_store::{BreakpointKind, BreakpointProperty};
+
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
-    fs.insert_tree("/dir", json!({ "a.rs": "", "b.js": "" }))
-        .await;
-
+    fs.insert_tree(
+        "/dir",
+        json!({ "main.rs": "fn main() {\n    let a = 1;\n    let b = 2;\n}\n" }),
+    )
+    .await;
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
-    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-
-    let mut fake_rust_servers = language_registry.register_fake_lsp_adapter(
-        "Rust",
-        FakeLspAdapter {
-            name: "rust-lsp",
-            ..Default::default()
-        },
-    );
-    let mut fake_js_servers = language_registry.register_fake_lsp_adapter(
-        "JavaScript",
-        FakeLspAdapter {
-            name: "js-lsp",
-            ..Default::default()
-        },
-    );
-    language_registry.add(rust_lang());
-    language_registry.add(js_lang());
-
-    let _rs_buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
-        .await
-        .unwrap();
-    let _js_buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/b.js", cx))
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/main.rs", cx)
+        })
         .await
         .unwrap();
+    let dap_store = project.read_with(cx, |project, _| project.dap_store().clone());
+
+    // Setting a property adds a breakpoint if there is none, and empty values clear properties.
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.toggle_breakpoints(&buffer, [1], cx);
+        dap_store.set_breakpoint_property(
+            &buffer,
+            1,
+            BreakpointProperty::Condition,
+            Some("a == 1".into()),
+            cx,
+        );
+        dap_store.set_breakpoint_property(
+            &buffer,
+            2,
+            BreakpointProperty::LogMessage,
+            Some("b is {b}".into()),
+            cx,
+        );
+        dap_store.set_breakpoint_property(
+            &buffer,
+            2,
+            BreakpointProperty::HitCondition,
+            Some("  ".into()),
+            cx,
+        );
+    });
+    dap_store.read_with(cx, |dap_store, cx| {
+        let path = Path::new("/dir/main.rs");
+        assert_eq!(dap_store.breakpoint_rows(path, cx), [1, 2]);
+        let breakpoint = dap_store.breakpoint_at(path, 1, cx).unwrap();
+        assert_eq!(breakpoint.kind(), BreakpointKind::Conditional);
+        assert_eq!(
+            breakpoint.property(BreakpointProperty::Condition),
+            Some("a == 1")
+        );
+        let breakpoint = dap_store.breakpoint_at(path, 2, cx).unwrap();
+        assert_eq!(breakpoint.kind(), BreakpointKind::Log);
+        assert_eq!(breakpoint.property(BreakpointProperty::HitCondition), None);
+    });
 
-    let mut fake_rust_server_1 = fake_rust_servers.next().await.unwrap();
-    assert_eq!(
-        fake_rust_server_1
-            .receive_notification::<lsp::notification::DidOpenTextDocument>()
-            .await
-            .text_document
-            .uri
-            .as_str(),
-        "file:///dir/a.rs"
-    );
-
-    let mut fake_js_server = fake_js_servers.next().await.unwrap();
+    let (client_id, fake_adapter) = dap_store.update(cx, |dap_store, cx| {
+        dap_store.start_fake_session(
+            task::DebugAdapterConfig {
+                label: "Debug main".into(),
+                command: "fake-adapter".into(),
+                args: Vec::new(),
+                env: HashMap::default(),
+                cwd: None,
+                request: task::DebugRequestType::Launch,
+                request_args: json!({ "program": "main" }),
+            },
+            cx,
+        )
+    });
+    let sent_breakpoints = Arc::new(Mutex::new(Vec::new()));
+    fake_adapter.handle_request::<Launch, _>(|_| Ok(serde_json::Value::Null));
+    fake_adapter.handle_request::<SetBreakpoints, _>({
+        let sent_breakpoints = sent_breakpoints.clone();
+        move |arguments| {
+            sent_breakpoints
+                .lock()
+                .push(arguments.breakpoints.unwrap_or_default());
+            Ok(SetBreakpointsResponse::default())
+        }
+    });
+
+    // Adapters that can't log points are only sent the breakpoints that stop, without the
+    // options they don't support.
+    fake_adapter.send_event("initialized", None);
+    cx.executor().run_until_parked();
     assert_eq!(
-        fake_js_server
-            .receive_notification::<lsp::notification::DidOpenTextDocument>()
-            .await
-            .text_document
-            .uri
-            .as_str(),
-        "file:///dir/b.js"
+        sent_breakpoints
+            .lock()
+            .drain(..)
+            .map(|breakpoints| breakpoints
+                .into_iter()
+                .map(|breakpoint| (
+                    breakpoint.line,
+                    breakpoint.condition,
+                    breakpoint.log_message
+                ))
+                .collect::<Vec<_>>())
+            .collect::<Vec<_>>(),
+        [vec![(2, None, None)]]
     );
 
-    // Disable Rust language server, ensuring only that server gets stopped.
-    cx.update(|cx| {
-        SettingsStore::update_global(cx, |settings, cx| {
-            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
-                settings.languages.insert(
-                    Arc::from("Rust"),
-                    LanguageSettingsContent {
-                        enable_language_server: Some(false),
-                        ..Default::default()
-                    },
-                );
+    dap_store.read_with(cx, |dap_store, _| {
+        dap_store
+            .session(client_id)
+            .unwrap()
+            .client()
+            .set_capabilities(Capabilities {
+                supports_conditional_breakpoints: Some(true),
+                supports_log_points: Some(true),
+                ..Default::default()
             });
-        })
     });
-    fake_rust_server_1
-        .receive_notification::<lsp::notification::Exit>()
-        .await;
-
-    // Enable Rust and disable JavaScript language servers, ensuring that the
-    // former gets started again and that the latter stops.
-    cx.update(|cx| {
-        SettingsStore::update_global(cx, |settings, cx| {
-            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
-                settings.languages.insert(
-                    Arc::from("Rust"),
-                    LanguageSettingsContent {
-                        enable_language_server: Some(true),
-                        ..Default::default()
-                    },
-                );
-                settings.languages.insert(
-                    Arc::from("JavaScript"),
-                    LanguageSettingsContent {
-                        enable_language_server: Some(false),
-                        ..Default::default()
-                    },
-                );
-            });
-        })
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.set_breakpoint_property(
+            &buffer,
+            1,
+            BreakpointProperty::HitCondition,
+            Some(">= 2".into()),
+            cx,
+        );
     });
-    let mut fake_rust_server_2 = fake_rust_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+    let sent_breakpoints = sent_breakpoints.lock().drain(..).collect::<Vec<_>>();
+    assert_eq!(sent_breakpoints.len(), 1);
+    let sent_breakpoints = sent_breakpoints[0]
+        .iter()
+        .map(|breakpoint: &SourceBreakpoint| {
+            (
+                breakpoint.line,
+                breakpoint.condition.clone(),
+                breakpoint.hit_condition.clone(),
+                breakpoint.log_message.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
     assert_eq!(
-        fake_rust_server_2
-            .receive_notification::<lsp::notification::DidOpenTextDocument>()
-            .await
-            .text_document
-            .uri
-            .as_str(),
-        "file:///dir/a.rs"
+        sent_breakpoints,
+        [
+            (2, Some("a == 1".to_string()), None, None),
+            (3, None, None, Some("b is {b}".to_string())),
+        ]
     );
-    fake_js_server
-        .receive_notification::<lsp::notification::Exit>()
-        .await;
 }
 
-#[gpui::test(iterations = 3)]
-async fn test_transforming_diagnostics(cx: &mut gpui::TestAppContext) {
-    init_test(cx);
+#[gpui::test]
+async fn test_debug_session_exception_breakpoint_filters(cx: &mut gpui::TestAppContext) {
+    use dap::{
+        requests::{Launch, SetExceptionBreakpoints},
+        types::{Capabilities, ExceptionBreakpointsFilter},
+    };
 
-    let text = "
-        fn a() { A }
-        fn b() { BB }
-        fn c() { CCC }
-    "
-    .unindent();
+    init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
-    fs.insert_tree("/dir", json!({ "a.rs": text })).await;
-
+    fs.insert_tree("/dir", json!({ "main.py": "raise Exception()\n" }))
+        .await;
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
-    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-
-    language_registry.add(rust_lang());
-    let mut fake_servers = language_registry.register_fake_lsp_adapter(
-        "Rust",
-        FakeLspAdapter {
-            disk_based_diagnostics_sources: vec!["disk".into()],
-            ..Default::default()
+    let dap_store = project.read_with(cx, |project, _| project.dap_store().clone());
+
+    let (client_id, fake_adapter) = dap_store.update(cx, |dap_store, cx| {
+        dap_store.start_fake_session(
+            task::DebugAdapterConfig {
+                label: "Debug main".into(),
+                command: "fake-adapter".into(),
+                args: Vec::new(),
+                env: HashMap::default(),
+                cwd: None,
+                request: task::DebugRequestType::Launch,
+                request_args: json!({ "program": "main.py" }),
+            },
+            cx,
+        )
+    });
+    let sent_filters = Arc::new(Mutex::new(Vec::new()));
+    fake_adapter.handle_request::<Launch, _>(|_| Ok(serde_json::Value::Null));
+    fake_adapter.handle_request::<SetExceptionBreakpoints, _>({
+        let sent_filters = sent_filters.clone();
+        move |arguments| {
+            sent_filters.lock().push(arguments.filters);
+            Ok(serde_json::Value::Null)
+        }
+    });
+    let filters = vec![
+        ExceptionBreakpointsFilter {
+            filter: "raised".into(),
+            label: "Raised Exceptions".into(),
+            description: None,
+            default: Some(false),
         },
+        ExceptionBreakpointsFilter {
+            filter: "uncaught".into(),
+            label: "Uncaught Exceptions".into(),
+            description: None,
+            default: Some(true),
+        },
+    ];
+    dap_store.read_with(cx, |dap_store, _| {
+        dap_store
+            .session(client_id)
+            .unwrap()
+            .client()
+            .set_capabilities(Capabilities {
+                exception_breakpoint_filters: Some(filters.clone()),
+                ..Default::default()
+            });
+    });
+
+    // The filters are enabled according to their defaults when the session is configured.
+    fake_adapter.send_event("initialized", None);
+    cx.executor().run_until_parked();
+    assert_eq!(
+        sent_filters.lock().drain(..).collect::<Vec<_>>(),
+        [vec!["uncaught".to_string()]]
     );
 
-    let buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
-        .await
-        .unwrap();
+    // Toggled filters are sent to the configured sessions.
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.toggle_exception_breakpoint_filter(&filters[0], cx);
+        assert!(dap_store.is_exception_breakpoint_filter_enabled(&filters[0]));
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(
+        sent_filters.lock().drain(..).collect::<Vec<_>>(),
+        [vec!["raised".to_string(), "uncaught".to_string()]]
+    );
 
-    let mut fake_server = fake_servers.next().await.unwrap();
-    let open_notification = fake_server
-        .receive_notification::<lsp::notification::DidOpenTextDocument>()
-        .await;
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.set_exception_breakpoint_filters(
+            HashMap::from_iter([("uncaught".to_string(), false)]),
+            cx,
+        );
+        assert_eq!(
+            dap_store
+                .toggled_exception_breakpoint_filters()
+                .get("uncaught"),
+            Some(&false)
+        );
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(
+        sent_filters.lock().drain(..).collect::<Vec<_>>(),
+        [Vec::<String>::new()]
+    );
+}
 
-    // Edit the buffer, moving the content down
-    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n\n")], None, cx));
-    let change_notification_1 = fake_server
-        .receive_notification::<lsp::notification::DidChangeTextDocument>()
+#[gpui::test]
+async fn test_debug_session_variables_and_watches(cx: &mut gpui::TestAppContext) {
+    use dap::{
+        requests::{Evaluate, Launch, Scopes, SetVariable, StackTrace, Threads, Variables},
+        types::{
+            Capabilities, EvaluateResponse, Scope, ScopesResponse, SetVariableResponse, StackFrame,
+            StackTraceResponse, Thread, ThreadsResponse, Variable, VariablesResponse,
+        },
+    };
+
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "main.rs": "fn main() {}\n" }))
         .await;
-    assert!(change_notification_1.text_document.version > open_notification.text_document.version);
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let dap_store = project.read_with(cx, |project, _| project.dap_store().clone());
 
-    // Report some diagnostics for the initial version of the buffer
-    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
-        uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
-        version: Some(open_notification.text_document.version),
-        diagnostics: vec![
-            lsp::Diagnostic {
-                range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
-                severity: Some(DiagnosticSeverity::ERROR),
-                message: "undefined variable 'A'".to_string(),
-                source: Some("disk".to_string()),
-                ..Default::default()
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.add_watch_expression("a + 1".into(), cx);
+        dap_store.add_watch_expression("missing".into(), cx);
+    });
+    let (client_id, fake_adapter) = dap_store.update(cx, |dap_store, cx| {
+        dap_store.start_fake_session(
+            task::DebugAdapterConfig {
+                label: "Debug main".into(),
+                command: "fake-adapter".into(),
+                args: Vec::new(),
+                env: HashMap::default(),
+                cwd: None,
+                request: task::DebugRequestType::Launch,
+                request_args: json!({ "program": "main" }),
             },
-            lsp::Diagnostic {
-                range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 11)),
-                severity: Some(DiagnosticSeverity::ERROR),
-                message: "undefined variable 'BB'".to_string(),
-                source: Some("disk".to_string()),
+            cx,
+        )
+    });
+    dap_store.read_with(cx, |dap_store, _| {
+        dap_store
+            .session(client_id)
+            .unwrap()
+            .client()
+            .set_capabilities(Capabilities {
+                supports_set_variable: Some(true),
                 ..Default::default()
-            },
-            lsp::Diagnostic {
-                range: lsp::Range::new(lsp::Position::new(2, 9), lsp::Position::new(2, 12)),
-                severity: Some(DiagnosticSeverity::ERROR),
-                source: Some("disk".to_string()),
-                message: "undefined variable 'CCC'".to_string(),
+            });
+    });
+
+    let a = Arc::new(Mutex::new(1));
+    let fetched_references = Arc::new(Mutex::new(Vec::new()));
+    fake_adapter.handle_request::<Launch, _>(|_| Ok(serde_json::Value::Null));
+    fake_adapter.handle_request::<Threads, _>(|_| {
+        Ok(ThreadsResponse {
+            threads: vec![Thread {
+                id: 1,
+                name: "main".into(),
+            }],
+        })
+    });
+    fake_adapter.handle_request::<StackTrace, _>(|_| {
+        Ok(StackTraceResponse {
+            stack_frames: vec![StackFrame {
+                id: 10,
+                name: "main::main".into(),
+                line: 1,
+                column: 1,
                 ..Default::default()
-            },
-        ],
+            }],
+            total_frames: Some(1),
+        })
+    });
+    fake_adapter.handle_request::<Scopes, _>(|arguments| {
+        assert_eq!(arguments.frame_id, 10);
+        Ok(ScopesResponse {
+            scopes: vec![
+                Scope {
+                    name: "Locals".into(),
+                    variables_reference: 100,
+                    expensive: false,
+                },
+                Scope {
+                    name: "Globals".into(),
+                    variables_reference: 200,
+                    expensive: true,
+                },
+            ],
+        })
+    });
+    fake_adapter.handle_request::<Variables, _>({
+        let a = a.clone();
+        let fetched_references = fetched_references.clone();
+        move |arguments| {
+            fetched_references
+                .lock()
+                .push(arguments.variables_reference);
+            Ok(VariablesResponse {
+                variables: vec![Variable {
+                    name: "a".into(),
+                    value: a.lock().to_string(),
+                    ..Default::default()
+                }],
+            })
+        }
+    });
+    fake_adapter.handle_request::<Evaluate, _>({
+        let a = a.clone();
+        move |arguments| {
+            assert_eq!(arguments.frame_id, Some(10));
+            match arguments.expression.as_str() {
+                "a + 1" => Ok(EvaluateResponse {
+                    result: (*a.lock() + 1).to_string(),
+                    ..Default::default()
+                }),
+                _ => Err(anyhow::anyhow!("cannot find value `missing` in this scope")),
+            }
+        }
+    });
+    fake_adapter.handle_request::<SetVariable, _>({
+        let a = a.clone();
+        move |arguments| {
+            assert_eq!(arguments.variables_reference, 100);
+            assert_eq!(arguments.name, "a");
+            *a.lock() = arguments.value.parse().unwrap();
+            Ok(SetVariableResponse {
+                value: arguments.value,
+                ..Default::default()
+            })
+        }
     });
 
-    // The diagnostics have moved down since they were created.
+    // Stopping fetches the scopes of the active stack frame, the variables of its inexpensive
+    // scopes, and the values of the watch expressions.
+    fake_adapter.send_event(
+        "stopped",
+        Some(json!({ "reason": "breakpoint", "threadId": 1 })),
+    );
     cx.executor().run_until_parked();
-    buffer.update(cx, |buffer, _| {
+    assert_eq!(*fetched_references.lock(), [100]);
+    dap_store.read_with(cx, |dap_store, _| {
+        let session = dap_store.session(client_id).unwrap();
         assert_eq!(
-            buffer
-                .snapshot()
-                .diagnostics_in_range::<_, Point>(Point::new(3, 0)..Point::new(5, 0), false)
+            session
+                .scopes()
+                .iter()
+                .map(|scope| scope.name.as_str())
                 .collect::<Vec<_>>(),
-            &[
-                DiagnosticEntry {
-                    range: Point::new(3, 9)..Point::new(3, 11),
-                    diagnostic: Diagnostic {
-                        source: Some("disk".into()),
-                        severity: DiagnosticSeverity::ERROR,
-                        message: "undefined variable 'BB'".to_string(),
-                        is_disk_based: true,
-                        group_id: 1,
-                        is_primary: true,
-                        ..Default::default()
-                    },
-                },
-                DiagnosticEntry {
-                    range: Point::new(4, 9)..Point::new(4, 12),
-                    diagnostic: Diagnostic {
-                        source: Some("disk".into()),
-                        severity: DiagnosticSeverity::ERROR,
-                        message: "undefined variable 'CCC'".to_string(),
-                        is_disk_based: true,
-                        group_id: 2,
-                        is_primary: true,
-                        ..Default::default()
-                    }
-                }
-            ]
+            ["Locals", "Globals"]
         );
+        assert_eq!(session.variables(100).unwrap()[0].value, "1");
+        assert!(session.variables(200).is_none());
         assert_eq!(
-            chunks_with_diagnostics(buffer, 0..buffer.len()),
-            [
-                ("\n\nfn a() { ".to_string(), None),
-                ("A".to_string(), Some(DiagnosticSeverity::ERROR)),
-                (" }\nfn b() { ".to_string(), None),
-                ("BB".to_string(), Some(DiagnosticSeverity::ERROR)),
-                (" }\nfn c() { ".to_string(), None),
-                ("CCC".to_string(), Some(DiagnosticSeverity::ERROR)),
-                (" }\n".to_string(), None),
-            ]
+            session
+                .watch_value("a + 1")
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .result,
+            "2"
         );
+        assert!(session.watch_value("missing").unwrap().is_err());
+    });
+
+    // Expensive scopes are fetched on demand.
+    dap_store
+        .update(cx, |dap_store, cx| {
+            dap_store.fetch_variables(client_id, 200, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(*fetched_references.lock(), [100, 200]);
+
+    // Setting a variable updates its value and re-evaluates the watch expressions.
+    dap_store
+        .update(cx, |dap_store, cx| {
+            dap_store.set_variable(client_id, 100, "a".into(), "41".into(), cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    dap_store.read_with(cx, |dap_store, _| {
+        let session = dap_store.session(client_id).unwrap();
+        assert_eq!(session.variables(100).unwrap()[0].value, "41");
         assert_eq!(
-            chunks_with_diagnostics(buffer, Point::new(3, 10)..Point::new(4, 11)),
+            session
+                .watch_value("a + 1")
+                .unwrap()
+                .as_ref()
+                .unwrap()
+                .result,
+            "42"
+        );
+    });
+
+    dap_store.update(cx, |dap_store, cx| {
+        dap_store.remove_watch_expression(1, cx);
+        assert_eq!(dap_store.watch_expressions(), ["a + 1"]);
+    });
+
+    // The values are cleared once the session resumes.
+    dap_store
+        .update(cx, |dap_store, cx| dap_store.continue_thread(client_id, cx))
+        .await
+        .ok();
+    dap_store.read_with(cx, |dap_store, _| {
+        let session = dap_store.session(client_id).unwrap();
+        assert!(session.scopes().is_empty());
+        assert!(session.variables(100).is_none());
+        assert!(session.watch_value("a + 1").is_none());
+    });
+}
+
+#[gpui::test]
+async fn test_debug_session_console(cx: &mut gpui::TestAppContext) {
+    use dap::{
+        requests::{Completions, Evaluate, Launch},
+        types::{Capabilities, CompletionItem, CompletionsResponse, EvaluateResponse},
+    };
+    use dap_store::{ConsoleEntry, ConsoleEntryKind};
+
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "main.rs": "fn main() {}\n" }))
+        .await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let dap_store = project.read_with(cx, |project, _| project.dap_store().clone());
+    let (client_id, fake_adapter) = dap_store.update(cx, |dap_store, cx| {
+        dap_store.start_fake_session(
+            task::DebugAdapterConfig {
+                label: "Debug main".into(),
+                command: "fake-adapter".into(),
+                args: Vec::new(),
+                env: HashMap::default(),
+                cwd: None,
+                request: task::DebugRequestType::Launch,
+                request_args: json!({ "program": "main" }),
+            },
+            cx,
+        )
+    });
+    fake_adapter.handle_request::<Launch, _>(|_| Ok(serde_json::Value::Null));
+    fake_adapter.handle_request::<Evaluate, _>(|arguments| {
+        assert_eq!(arguments.context.as_deref(), Some("repl"));
+        match arguments.expression.as_str() {
+            "1 + 1" => Ok(EvaluateResponse {
+                result: "2".into(),
+                ..Default::default()
+            }),
+            _ => Err(anyhow::anyhow!("syntax error")),
+        }
+    });
+    fake_adapter.handle_request::<Completions, _>(|arguments| {
+        assert_eq!((arguments.text.as_str(), arguments.column), ("pri", 4));
+        Ok(CompletionsResponse {
+            targets: vec![CompletionItem {
+                label: "print".into(),
+                ..Default::default()
+            }],
+        })
+    });
+
+    // Output that doesn't end with a newline is continued by the next output of its category.
+    fake_adapter.send_event(
+        "output",
+        Some(json!({ "category": "stdout", "output": "hel" })),
+    );
+    fake_adapter.send_event(
+        "output",
+        Some(json!({ "category": "stdout", "output": "lo\n" })),
+    );
+    fake_adapter.send_event(
+        "output",
+        Some(json!({ "category": "stderr", "output": "oops\n" })),
+    );
+    fake_adapter.send_event(
+        "output",
+        Some(json!({ "category": "telemetry", "output": "{}" })),
+    );
+    cx.executor().run_until_parked();
+
+    for expression in ["1 + 1", "1 +"] {
+        dap_store
+            .update(cx, |dap_store, cx| {
+                dap_store.evaluate_in_console(client_id, expression.into(), cx)
+            })
+            .await
+            .unwrap();
+    }
+    dap_store.read_with(cx, |dap_store, _| {
+        let entry = |kind, text: &str| ConsoleEntry {
+            kind,
+            text: text.to_string(),
+        };
+        assert_eq!(
+            dap_store.session(client_id).unwrap().console(),
             [
-                ("B".to_string(), Some(DiagnosticSeverity::ERROR)),
-                (" }\nfn c() { ".to_string(), None),
-                ("CC".to_string(), Some(DiagnosticSeverity::ERROR)),
+                entry(ConsoleEntryKind::Output, "hello\n"),
+                entry(ConsoleEntryKind::Stderr, "oops\n"),
+                entry(ConsoleEntryKind::Input, "1 + 1"),
+                entry(ConsoleEntryKind::Result, "2"),
+                entry(ConsoleEntryKind::Input, "1 +"),
+                entry(ConsoleEntryKind::Error, "evaluate failed: syntax error"),
             ]
         );
     });
 
-    // Ensure overlapping diagnostics are highlighted correctly.
-    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
-        uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
-        version: Some(open_notification.text_document.version),
-        diagnostics: vec![
-            lsp::Diagnostic {
-                range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
-                severity: Some(DiagnosticSeverity::ERROR),
-                message: "undefined variable 'A'".to_string(),
-                source: Some("disk".to_string()),
-                ..Default::default()
-            },
-            lsp::Diagnostic {
-                range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 12)),
-                severity: Some(DiagnosticSeverity::WARNING),
-                message: "unreachable statement".to_string(),
-                source: Some("disk".to_string()),
+    // Completions are only requested from adapters that support them.
+    let completions = dap_store
+        .read_with(cx, |dap_store, cx| {
+            dap_store.console_completions(client_id, "pri".into(), 4, cx)
+        })
+        .await
+        .unwrap();
+    assert!(completions.is_empty());
+    dap_store.read_with(cx, |dap_store, _| {
+        dap_store
+            .session(client_id)
+            .unwrap()
+            .client()
+            .set_capabilities(Capabilities {
+                supports_completions_request: Some(true),
                 ..Default::default()
-            },
-        ],
+            });
+    });
+    let completions = dap_store
+        .read_with(cx, |dap_store, cx| {
+            dap_store.console_completions(client_id, "pri".into(), 4, cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        completions
+            .iter()
+            .map(|completion| completion.label.as_str())
+            .collect::<Vec<_>>(),
+        ["print"]
+    );
+}
+
+#[gpui::test]
+async fn test_restarting_server_with_diagnostics_published(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "x" })).await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    // Publish diagnostics
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
+        uri: Url::from_file_path("/dir/a.rs").unwrap(),
+        version: None,
+        diagnostics: vec![lsp::Diagnostic {
+            range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(0, 0)),
+            severity: Some(lsp::DiagnosticSeverity::ERROR),
+            message: "the message".to_string(),
+            ..Default::default()
+        }],
     });
 
     cx.executor().run_until_parked();
@@ -1689,277 +2236,1695 @@ async fn test_transforming_diagnostics(cx: &mut gpui::TestAppContext) {
         assert_eq!(
             buffer
                 .snapshot()
-                .diagnostics_in_range::<_, Point>(Point::new(2, 0)..Point::new(3, 0), false)
+                .diagnostics_in_range::<_, usize>(0..1, false)
+                .map(|entry| entry.diagnostic.message.clone())
                 .collect::<Vec<_>>(),
-            &[
-                DiagnosticEntry {
-                    range: Point::new(2, 9)..Point::new(2, 12),
-                    diagnostic: Diagnostic {
-                        source: Some("disk".into()),
-                        severity: DiagnosticSeverity::WARNING,
-                        message: "unreachable statement".to_string(),
-                        is_disk_based: true,
-                        group_id: 4,
-                        is_primary: true,
-                        ..Default::default()
-                    }
-                },
-                DiagnosticEntry {
-                    range: Point::new(2, 9)..Point::new(2, 10),
-                    diagnostic: Diagnostic {
-                        source: Some("disk".into()),
-                        severity: DiagnosticSeverity::ERROR,
-                        message: "undefined variable 'A'".to_string(),
-                        is_disk_based: true,
-                        group_id: 3,
-                        is_primary: true,
-                        ..Default::default()
-                    },
-                }
-            ]
+            ["the message".to_string()]
         );
+    });
+    project.update(cx, |project, cx| {
         assert_eq!(
-            chunks_with_diagnostics(buffer, Point::new(2, 0)..Point::new(3, 0)),
-            [
-                ("fn a() { ".to_string(), None),
-                ("A".to_string(), Some(DiagnosticSeverity::ERROR)),
-                (" }".to_string(), Some(DiagnosticSeverity::WARNING)),
-                ("\n".to_string(), None),
-            ]
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 1,
+                warning_count: 0,
+            }
         );
+    });
+
+    project.update(cx, |project, cx| {
+        project.restart_language_servers_for_buffers([buffer.clone()], cx);
+    });
+
+    // The diagnostics are cleared.
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
         assert_eq!(
-            chunks_with_diagnostics(buffer, Point::new(2, 10)..Point::new(3, 0)),
-            [
-                (" }".to_string(), Some(DiagnosticSeverity::WARNING)),
-                ("\n".to_string(), None),
-            ]
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, usize>(0..1, false)
+                .map(|entry| entry.diagnostic.message.clone())
+                .collect::<Vec<_>>(),
+            Vec::<String>::new(),
         );
     });
-
-    // Keep editing the buffer and ensure disk-based diagnostics get translated according to the
-    // changes since the last save.
-    buffer.update(cx, |buffer, cx| {
-        buffer.edit([(Point::new(2, 0)..Point::new(2, 0), "    ")], None, cx);
-        buffer.edit(
-            [(Point::new(2, 8)..Point::new(2, 10), "(x: usize)")],
-            None,
-            cx,
+    project.update(cx, |project, cx| {
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 0,
+                warning_count: 0,
+            }
         );
-        buffer.edit([(Point::new(3, 10)..Point::new(3, 10), "xxx")], None, cx);
     });
-    let change_notification_2 = fake_server
-        .receive_notification::<lsp::notification::DidChangeTextDocument>()
-        .await;
-    assert!(
-        change_notification_2.text_document.version > change_notification_1.text_document.version
+}
+
+#[gpui::test]
+async fn test_restarted_server_reporting_invalid_buffer_version(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "" })).await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+
+    language_registry.add(rust_lang());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    // Before restarting the server, report diagnostics with an unknown buffer version.
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
+        uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+        version: Some(10000),
+        diagnostics: Vec::new(),
+    });
+    cx.executor().run_until_parked();
+
+    project.update(cx, |project, cx| {
+        project.restart_language_servers_for_buffers([buffer.clone()], cx);
+    });
+    let mut fake_server = fake_servers.next().await.unwrap();
+    let notification = fake_server
+        .receive_notification::<lsp::notification::DidOpenTextDocument>()
+        .await
+        .text_document;
+    assert_eq!(notification.version, 0);
+}
+
+#[gpui::test]
+async fn test_toggling_enable_language_server(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "", "b.js": "" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+
+    let mut fake_rust_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            name: "rust-lsp",
+            ..Default::default()
+        },
+    );
+    let mut fake_js_servers = language_registry.register_fake_lsp_adapter(
+        "JavaScript",
+        FakeLspAdapter {
+            name: "js-lsp",
+            ..Default::default()
+        },
+    );
+    language_registry.add(rust_lang());
+    language_registry.add(js_lang());
+
+    let _rs_buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    let _js_buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/b.js", cx))
+        .await
+        .unwrap();
+
+    let mut fake_rust_server_1 = fake_rust_servers.next().await.unwrap();
+    assert_eq!(
+        fake_rust_server_1
+            .receive_notification::<lsp::notification::DidOpenTextDocument>()
+            .await
+            .text_document
+            .uri
+            .as_str(),
+        "file:///dir/a.rs"
+    );
+
+    let mut fake_js_server = fake_js_servers.next().await.unwrap();
+    assert_eq!(
+        fake_js_server
+            .receive_notification::<lsp::notification::DidOpenTextDocument>()
+            .await
+            .text_document
+            .uri
+            .as_str(),
+        "file:///dir/b.js"
+    );
+
+    // Disable Rust language server, ensuring only that server gets stopped.
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.languages.insert(
+                    Arc::from("Rust"),
+                    LanguageSettingsContent {
+                        enable_language_server: Some(false),
+                        ..Default::default()
+                    },
+                );
+            });
+        })
+    });
+    fake_rust_server_1
+        .receive_notification::<lsp::notification::Exit>()
+        .await;
+
+    // Enable Rust and disable JavaScript language servers, ensuring that the
+    // former gets started again and that the latter stops.
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                settings.languages.insert(
+                    Arc::from("Rust"),
+                    LanguageSettingsContent {
+                        enable_language_server: Some(true),
+                        ..Default::default()
+                    },
+                );
+                settings.languages.insert(
+                    Arc::from("JavaScript"),
+                    LanguageSettingsContent {
+                        enable_language_server: Some(false),
+                        ..Default::default()
+                    },
+                );
+            });
+        })
+    });
+    let mut fake_rust_server_2 = fake_rust_servers.next().await.unwrap();
+    assert_eq!(
+        fake_rust_server_2
+            .receive_notification::<lsp::notification::DidOpenTextDocument>()
+            .await
+            .text_document
+            .uri
+            .as_str(),
+        "file:///dir/a.rs"
+    );
+    fake_js_server
+        .receive_notification::<lsp::notification::Exit>()
+        .await;
+}
+
+#[gpui::test]
+async fn test_language_servers_defined_in_settings(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    // Projects can't define language servers in their own settings.
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".zed": {
+                "settings.json": r#"{
+                    "lsp": {
+                        "project-lsp": {
+                            "binary": { "path": "./project-lsp" },
+                            "languages": ["Rust"]
+                        }
+                    }
+                }"#
+            },
+            "a.rs": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert!(language_registry.lsp_adapters(&rust_lang()).is_empty());
+    project.update(cx, |project, cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(project.language_servers_for_buffer(buffer, cx).count(), 0);
+    });
+
+    // Define a language server for Rust, ensuring that it gets started.
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.lsp.insert(
+                    "my-lsp".into(),
+                    LspSettings {
+                        binary: Some(project_settings::BinarySettings {
+                            path: Some("/usr/bin/my-lsp".into()),
+                            arguments: Some(vec!["--stdio".into()]),
+                            ..Default::default()
+                        }),
+                        languages: Some(vec!["Rust".into()]),
+                        ..Default::default()
+                    },
+                );
+            });
+        });
+    });
+    cx.executor().run_until_parked();
+    project.update(cx, |project, cx| {
+        let buffer = buffer.read(cx);
+        let server_names = project
+            .language_servers_for_buffer(buffer, cx)
+            .map(|(adapter, _)| adapter.name.0.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(server_names, ["my-lsp"]);
+    });
+
+    // Remove the definition, ensuring that the server gets stopped and unregistered.
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.lsp.remove("my-lsp");
+            });
+        });
+    });
+    cx.executor().run_until_parked();
+    project.update(cx, |project, cx| {
+        let buffer = buffer.read(cx);
+        assert_eq!(project.language_servers_for_buffer(buffer, cx).count(), 0);
+    });
+    assert!(language_registry.lsp_adapters(&rust_lang()).is_empty());
+}
+
+#[gpui::test(iterations = 3)]
+async fn test_transforming_diagnostics(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let text = "
+        fn a() { A }
+        fn b() { BB }
+        fn c() { CCC }
+    "
+    .unindent();
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": text })).await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+
+    language_registry.add(rust_lang());
+    let mut fake_servers = language_registry.register_fake_lsp_adapter(
+        "Rust",
+        FakeLspAdapter {
+            disk_based_diagnostics_sources: vec!["disk".into()],
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    let mut fake_server = fake_servers.next().await.unwrap();
+    let open_notification = fake_server
+        .receive_notification::<lsp::notification::DidOpenTextDocument>()
+        .await;
+
+    // Edit the buffer, moving the content down
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n\n")], None, cx));
+    let change_notification_1 = fake_server
+        .receive_notification::<lsp::notification::DidChangeTextDocument>()
+        .await;
+    assert!(change_notification_1.text_document.version > open_notification.text_document.version);
+
+    // Report some diagnostics for the initial version of the buffer
+    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
+        uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+        version: Some(open_notification.text_document.version),
+        diagnostics: vec![
+            lsp::Diagnostic {
+                range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+                severity: Some(DiagnosticSeverity::ERROR),
+                message: "undefined variable 'A'".to_string(),
+                source: Some("disk".to_string()),
+                ..Default::default()
+            },
+            lsp::Diagnostic {
+                range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 11)),
+                severity: Some(DiagnosticSeverity::ERROR),
+                message: "undefined variable 'BB'".to_string(),
+                source: Some("disk".to_string()),
+                ..Default::default()
+            },
+            lsp::Diagnostic {
+                range: lsp::Range::new(lsp::Position::new(2, 9), lsp::Position::new(2, 12)),
+                severity: Some(DiagnosticSeverity::ERROR),
+                source: Some("disk".to_string()),
+                message: "undefined variable 'CCC'".to_string(),
+                ..Default::default()
+            },
+        ],
+    });
+
+    // The diagnostics have moved down since they were created.
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(Point::new(3, 0)..Point::new(5, 0), false)
+                .collect::<Vec<_>>(),
+            &[
+                DiagnosticEntry {
+                    range: Point::new(3, 9)..Point::new(3, 11),
+                    diagnostic: Diagnostic {
+                        source: Some("disk".into()),
+                        severity: DiagnosticSeverity::ERROR,
+                        message: "undefined variable 'BB'".to_string(),
+                        is_disk_based: true,
+                        group_id: 1,
+                        is_primary: true,
+                        ..Default::default()
+                    },
+                },
+                DiagnosticEntry {
+                    range: Point::new(4, 9)..Point::new(4, 12),
+                    diagnostic: Diagnostic {
+                        source: Some("disk".into()),
+                        severity: DiagnosticSeverity::ERROR,
+                        message: "undefined variable 'CCC'".to_string(),
+                        is_disk_based: true,
+                        group_id: 2,
+                        is_primary: true,
+                        ..Default::default()
+                    }
+                }
+            ]
+        );
+        assert_eq!(
+            chunks_with_diagnostics(buffer, 0..buffer.len()),
+            [
+                ("\n\nfn a() { ".to_string(), None),
+                ("A".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (" }\nfn b() { ".to_string(), None),
+                ("BB".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (" }\nfn c() { ".to_string(), None),
+                ("CCC".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (" }\n".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            chunks_with_diagnostics(buffer, Point::new(3, 10)..Point::new(4, 11)),
+            [
+                ("B".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (" }\nfn c() { ".to_string(), None),
+                ("CC".to_string(), Some(DiagnosticSeverity::ERROR)),
+            ]
+        );
+    });
+
+    // Ensure overlapping diagnostics are highlighted correctly.
+    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
+        uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+        version: Some(open_notification.text_document.version),
+        diagnostics: vec![
+            lsp::Diagnostic {
+                range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+                severity: Some(DiagnosticSeverity::ERROR),
+                message: "undefined variable 'A'".to_string(),
+                source: Some("disk".to_string()),
+                ..Default::default()
+            },
+            lsp::Diagnostic {
+                range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 12)),
+                severity: Some(DiagnosticSeverity::WARNING),
+                message: "unreachable statement".to_string(),
+                source: Some("disk".to_string()),
+                ..Default::default()
+            },
+        ],
+    });
+
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(Point::new(2, 0)..Point::new(3, 0), false)
+                .collect::<Vec<_>>(),
+            &[
+                DiagnosticEntry {
+                    range: Point::new(2, 9)..Point::new(2, 12),
+                    diagnostic: Diagnostic {
+                        source: Some("disk".into()),
+                        severity: DiagnosticSeverity::WARNING,
+                        message: "unreachable statement".to_string(),
+                        is_disk_based: true,
+                        group_id: 4,
+                        is_primary: true,
+                        ..Default::default()
+                    }
+                },
+                DiagnosticEntry {
+                    range: Point::new(2, 9)..Point::new(2, 10),
+                    diagnostic: Diagnostic {
+                        source: Some("disk".into()),
+                        severity: DiagnosticSeverity::ERROR,
+                        message: "undefined variable 'A'".to_string(),
+                        is_disk_based: true,
+                        group_id: 3,
+                        is_primary: true,
+                        ..Default::default()
+                    },
+                }
+            ]
+        );
+        assert_eq!(
+            chunks_with_diagnostics(buffer, Point::new(2, 0)..Point::new(3, 0)),
+            [
+                ("fn a() { ".to_string(), None),
+                ("A".to_string(), Some(DiagnosticSeverity::ERROR)),
+                (" }".to_string(), Some(DiagnosticSeverity::WARNING)),
+                ("\n".to_string(), None),
+            ]
+        );
+        assert_eq!(
+            chunks_with_diagnostics(buffer, Point::new(2, 10)..Point::new(3, 0)),
+            [
+                (" }".to_string(), Some(DiagnosticSeverity::WARNING)),
+                ("\n".to_string(), None),
+            ]
+        );
+    });
+
+    // Keep editing the buffer and ensure disk-based diagnostics get translated according to the
+    // changes since the last save.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit([(Point::new(2, 0)..Point::new(2, 0), "    ")], None, cx);
+        buffer.edit(
+            [(Point::new(2, 8)..Point::new(2, 10), "(x: usize)")],
+            None,
+            cx,
+        );
+        buffer.edit([(Point::new(3, 10)..Point::new(3, 10), "xxx")], None, cx);
+    });
+    let change_notification_2 = fake_server
+        .receive_notification::<lsp::notification::DidChangeTextDocument>()
+        .await;
+    assert!(
+        change_notification_2.text_document.version > change_notification_1.text_document.version
+    );
+
+    // Handle out-of-order diagnostics
+    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
+        uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+        version: Some(change_notification_2.text_document.version),
+        diagnostics: vec![
+            lsp::Diagnostic {
+                range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 11)),
+                severity: Some(DiagnosticSeverity::ERROR),
+                message: "undefined variable 'BB'".to_string(),
+                source: Some("disk".to_string()),
+                ..Default::default()
+            },
+            lsp::Diagnostic {
+                range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+                severity: Some(DiagnosticSeverity::WARNING),
+                message: "undefined variable 'A'".to_string(),
+                source: Some("disk".to_string()),
+                ..Default::default()
+            },
+        ],
+    });
+
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, _| {
+        assert_eq!(
+            buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .collect::<Vec<_>>(),
+            &[
+                DiagnosticEntry {
+                    range: Point::new(2, 21)..Point::new(2, 22),
+                    diagnostic: Diagnostic {
+                        source: Some("disk".into()),
+                        severity: DiagnosticSeverity::WARNING,
+                        message: "undefined variable 'A'".to_string(),
+                        is_disk_based: true,
+                        group_id: 6,
+                        is_primary: true,
+                        ..Default::default()
+                    }
+                },
+                DiagnosticEntry {
+                    range: Point::new(3, 9)..Point::new(3, 14),
+                    diagnostic: Diagnostic {
+                        source: Some("disk".into()),
+                        severity: DiagnosticSeverity::ERROR,
+                        message: "undefined variable 'BB'".to_string(),
+                        is_disk_based: true,
+                        group_id: 5,
+                        is_primary: true,
+                        ..Default::default()
+                    },
+                }
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_empty_diagnostic_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let text = concat!(
+        "let one = ;\n", //
+        "let two = \n",
+        "let three = 3;\n",
+    );
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": text })).await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    project.update(cx, |project, cx| {
+        project
+            .update_buffer_diagnostics(
+                &buffer,
+                LanguageServerId(0),
+                None,
+                vec![
+                    DiagnosticEntry {
+                        range: Unclipped(PointUtf16::new(0, 10))..Unclipped(PointUtf16::new(0, 10)),
+                        diagnostic: Diagnostic {
+                            severity: DiagnosticSeverity::ERROR,
+                            message: "syntax error 1".to_string(),
+                            ..Default::default()
+                        },
+                    },
+                    DiagnosticEntry {
+                        range: Unclipped(PointUtf16::new(1, 10))..Unclipped(PointUtf16::new(1, 10)),
+                        diagnostic: Diagnostic {
+                            severity: DiagnosticSeverity::ERROR,
+                            message: "syntax error 2".to_string(),
+                            ..Default::default()
+                        },
+                    },
+                ],
+                cx,
+            )
+            .unwrap();
+    });
+
+    // An empty range is extended forward to include the following character.
+    // At the end of a line, an empty range is extended backward to include
+    // the preceding character.
+    buffer.update(cx, |buffer, _| {
+        let chunks = chunks_with_diagnostics(buffer, 0..buffer.len());
+        assert_eq!(
+            chunks
+                .iter()
+                .map(|(s, d)| (s.as_str(), *d))
+                .collect::<Vec<_>>(),
+            &[
+                ("let one = ", None),
+                (";", Some(DiagnosticSeverity::ERROR)),
+                ("\nlet two =", None),
+                (" ", Some(DiagnosticSeverity::ERROR)),
+                ("\nlet three = 3;\n", None)
+            ]
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.rs": "one two three" }))
+        .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    project.update(cx, |project, cx| {
+        project
+            .update_diagnostic_entries(
+                LanguageServerId(0),
+                Path::new("/dir/a.rs").to_owned(),
+                None,
+                vec![DiagnosticEntry {
+                    range: Unclipped(PointUtf16::new(0, 0))..Unclipped(PointUtf16::new(0, 3)),
+                    diagnostic: Diagnostic {
+                        severity: DiagnosticSeverity::ERROR,
+                        is_primary: true,
+                        message: "syntax error a1".to_string(),
+                        ..Default::default()
+                    },
+                }],
+                cx,
+            )
+            .unwrap();
+        project
+            .update_diagnostic_entries(
+                LanguageServerId(1),
+                Path::new("/dir/a.rs").to_owned(),
+                None,
+                vec![DiagnosticEntry {
+                    range: Unclipped(PointUtf16::new(0, 0))..Unclipped(PointUtf16::new(0, 3)),
+                    diagnostic: Diagnostic {
+                        severity: DiagnosticSeverity::ERROR,
+                        is_primary: true,
+                        message: "syntax error b1".to_string(),
+                        ..Default::default()
+                    },
+                }],
+                cx,
+            )
+            .unwrap();
+
+        assert_eq!(
+            project.diagnostic_summary(false, cx),
+            DiagnosticSummary {
+                error_count: 2,
+                warning_count: 0,
+            }
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let text = "
+        fn a() {
+            f1();
+        }
+        fn b() {
+            f2();
+        }
+        fn c() {
+            f3();
+        }
+    "
+    .unindent();
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": text.clone(),
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    let mut fake_server = fake_servers.next().await.unwrap();
+    let lsp_document_version = fake_server
+        .receive_notification::<lsp::notification::DidOpenTextDocument>()
+        .await
+        .text_document
+        .version;
+
+    // Simulate editing the buffer after the language server computes some edits.
+    buffer.update(cx, |buffer, cx| {
+        buffer.edit(
+            [(
+                Point::new(0, 0)..Point::new(0, 0),
+                "// above first function\n",
+            )],
+            None,
+            cx,
+        );
+        buffer.edit(
+            [(
+                Point::new(2, 0)..Point::new(2, 0),
+                "    // inside first function\n",
+            )],
+            None,
+            cx,
+        );
+        buffer.edit(
+            [(
+                Point::new(6, 4)..Point::new(6, 4),
+                "// inside second function ",
+            )],
+            None,
+            cx,
+        );
+
+        assert_eq!(
+            buffer.text(),
+            "
+                // above first function
+                fn a() {
+                    // inside first function
+                    f1();
+                }
+                fn b() {
+                    // inside second function f2();
+                }
+                fn c() {
+                    f3();
+                }
+            "
+            .unindent()
+        );
+    });
+
+    let edits = project
+        .update(cx, |project, cx| {
+            project.edits_from_lsp(
+                &buffer,
+                vec![
+                    // replace body of first function
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(3, 0)),
+                        new_text: "
+                            fn a() {
+                                f10();
+                            }
+                            "
+                        .unindent(),
+                    },
+                    // edit inside second function
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(4, 6), lsp::Position::new(4, 6)),
+                        new_text: "00".into(),
+                    },
+                    // edit inside third function via two distinct edits
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(7, 5), lsp::Position::new(7, 5)),
+                        new_text: "4000".into(),
+                    },
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(7, 5), lsp::Position::new(7, 6)),
+                        new_text: "".into(),
+                    },
+                ],
+                LanguageServerId(0),
+                Some(lsp_document_version),
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    buffer.update(cx, |buffer, cx| {
+        for (range, new_text) in edits {
+            buffer.edit([(range, new_text)], None, cx);
+        }
+        assert_eq!(
+            buffer.text(),
+            "
+                // above first function
+                fn a() {
+                    // inside first function
+                    f10();
+                }
+                fn b() {
+                    // inside second function f200();
+                }
+                fn c() {
+                    f4000();
+                }
+                "
+            .unindent()
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_edits_from_lsp2_with_edits_on_adjacent_lines(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let text = "
+        use a::b;
+        use a::c;
+
+        fn f() {
+            b();
+            c();
+        }
+    "
+    .unindent();
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": text.clone(),
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    // Simulate the language server sending us a small edit in the form of a very large diff.
+    // Rust-analyzer does this when performing a merge-imports code action.
+    let edits = project
+        .update(cx, |project, cx| {
+            project.edits_from_lsp(
+                &buffer,
+                [
+                    // Replace the first use statement without editing the semicolon.
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 8)),
+                        new_text: "a::{b, c}".into(),
+                    },
+                    // Reinsert the remainder of the file between the semicolon and the final
+                    // newline of the file.
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
+                        new_text: "\n\n".into(),
+                    },
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
+                        new_text: "
+                            fn f() {
+                                b();
+                                c();
+                            }"
+                        .unindent(),
+                    },
+                    // Delete everything after the first newline of the file.
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(7, 0)),
+                        new_text: "".into(),
+                    },
+                ],
+                LanguageServerId(0),
+                None,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    buffer.update(cx, |buffer, cx| {
+        let edits = edits
+            .into_iter()
+            .map(|(range, text)| {
+                (
+                    range.start.to_point(buffer)..range.end.to_point(buffer),
+                    text,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            edits,
+            [
+                (Point::new(0, 4)..Point::new(0, 8), "a::{b, c}".into()),
+                (Point::new(1, 0)..Point::new(2, 0), "".into())
+            ]
+        );
+
+        for (range, new_text) in edits {
+            buffer.edit([(range, new_text)], None, cx);
+        }
+        assert_eq!(
+            buffer.text(),
+            "
+                use a::{b, c};
+
+                fn f() {
+                    b();
+                    c();
+                }
+            "
+            .unindent()
+        );
+    });
+}
+
+#[gpui::test]
+async fn test_invalid_edits_from_lsp2(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let text = "
+        use a::b;
+        use a::c;
+
+        fn f() {
+            b();
+            c();
+        }
+    "
+    .unindent();
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": text.clone(),
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .await
+        .unwrap();
+
+    // Simulate the language server sending us edits in a non-ordered fashion,
+    // with ranges sometimes being inverted or pointing to invalid locations.
+    let edits = project
+        .update(cx, |project, cx| {
+            project.edits_from_lsp(
+                &buffer,
+                [
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
+                        new_text: "\n\n".into(),
+                    },
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 4)),
+                        new_text: "a::{b, c}".into(),
+                    },
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(99, 0)),
+                        new_text: "".into(),
+                    },
+                    lsp::TextEdit {
+                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
+                        new_text: "
+                            fn f() {
+                                b();
+                                c();
+                            }"
+                        .unindent(),
+                    },
+                ],
+                LanguageServerId(0),
+                None,
+                cx,
+            )
+        })
+        .await
+        .unwrap();
+
+    buffer.update(cx, |buffer, cx| {
+        let edits = edits
+            .into_iter()
+            .map(|(range, text)| {
+                (
+                    range.start.to_point(buffer)..range.end.to_point(buffer),
+                    text,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            edits,
+            [
+                (Point::new(0, 4)..Point::new(0, 8), "a::{b, c}".into()),
+                (Point::new(1, 0)..Point::new(2, 0), "".into())
+            ]
+        );
+
+        for (range, new_text) in edits {
+            buffer.edit([(range, new_text)], None, cx);
+        }
+        assert_eq!(
+            buffer.text(),
+            "
+                use a::{b, c};
+
+                fn f() {
+                    b();
+                    c();
+                }
+            "
+            .unindent()
+        );
+    });
+}
+
+fn chunks_with_diagnostics<T: ToOffset + ToPoint>(
+    buffer: &Buffer,
+    range: Range<T>,
+) -> Vec<(String, Option<DiagnosticSeverity>)> {
+    let mut chunks: Vec<(String, Option<DiagnosticSeverity>)> = Vec::new();
+    for chunk in buffer.snapshot().chunks(range, true) {
+        if chunks.last().map_or(false, |prev_chunk| {
+            prev_chunk.1 == chunk.diagnostic_severity
+        }) {
+            chunks.last_mut().unwrap().0.push_str(chunk.text);
+        } else {
+            chunks.push((chunk.text.to_string(), chunk.diagnostic_severity));
+        }
+    }
+    chunks
+}
+
+#[gpui::test(iterations = 10)]
+async fn test_definition(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.rs": "const fn a() { A }",
+            "b.rs": "const y: i32 = crate::a()",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir/b.rs".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+    let mut fake_servers =
+        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/b.rs", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_servers.next().await.unwrap();
+    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
+        let params = params.text_document_position_params;
+        assert_eq!(
+            params.text_document.uri.to_file_path().unwrap(),
+            Path::new("/dir/b.rs"),
+        );
+        assert_eq!(params.position, lsp::Position::new(0, 22));
+
+        Ok(Some(lsp::GotoDefinitionResponse::Scalar(
+            lsp::Location::new(
+                lsp::Url::from_file_path("/dir/a.rs").unwrap(),
+                lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+            ),
+        )))
+    });
+
+    let mut definitions = project
+        .update(cx, |project, cx| project.definition(&buffer, 22, cx))
+        .await
+        .unwrap();
+
+    // Assert no new language server started
+    cx.executor().run_until_parked();
+    assert!(fake_servers.try_next().is_err());
+
+    assert_eq!(definitions.len(), 1);
+    let definition = definitions.pop().unwrap();
+    cx.update(|cx| {
+        let target_buffer = definition.target.buffer.read(cx);
+        assert_eq!(
+            target_buffer
+                .file()
+                .unwrap()
+                .as_local()
+                .unwrap()
+                .abs_path(cx),
+            Path::new("/dir/a.rs"),
+        );
+        assert_eq!(definition.target.range.to_offset(target_buffer), 9..10);
+        assert_eq!(
+            list_worktrees(&project, cx),
+            [("/dir/a.rs".as_ref(), false), ("/dir/b.rs".as_ref(), true)],
+        );
+
+        drop(definition);
+    });
+    cx.update(|cx| {
+        assert_eq!(list_worktrees(&project, cx), [("/dir/b.rs".as_ref(), true)]);
+    });
+
+    fn list_worktrees<'a>(
+        project: &'a Model<Project>,
+        cx: &'a AppContext,
+    ) -> Vec<(&'a Path, bool)> {
+        project
+            .read(cx)
+            .worktrees()
+            .map(|worktree| {
+                let worktree = worktree.read(cx);
+                (
+                    worktree.as_local().unwrap().abs_path().as_ref(),
+                    worktree.is_visible(),
+                )
+            })
+            .collect::<Vec<_>>()
+    }
+}
+
+#[gpui::test]
+async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                completion_provider: Some(lsp::CompletionOptions {
+                    trigger_characters: Some(vec![":".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let text = "let a = b.fqn";
+    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+    let completions = project.update(cx, |project, cx| {
+        project.completions(&buffer, text.len(), cx)
+    });
+
+    fake_server
+        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(vec![
+                lsp::CompletionItem {
+                    label: "fullyQualifiedName?".into(),
+                    insert_text: Some("fullyQualifiedName".into()),
+                    ..Default::default()
+                },
+            ])))
+        })
+        .next()
+        .await;
+    let completions = completions.await.unwrap();
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].new_text, "fullyQualifiedName");
+    assert_eq!(
+        completions[0].old_range.to_offset(&snapshot),
+        text.len() - 3..text.len()
+    );
+
+    let text = "let a = \"atoms/cmp\"";
+    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+    let completions = project.update(cx, |project, cx| {
+        project.completions(&buffer, text.len() - 1, cx)
+    });
+
+    fake_server
+        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(vec![
+                lsp::CompletionItem {
+                    label: "component".into(),
+                    ..Default::default()
+                },
+            ])))
+        })
+        .next()
+        .await;
+    let completions = completions.await.unwrap();
+    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].new_text, "component");
+    assert_eq!(
+        completions[0].old_range.to_offset(&snapshot),
+        text.len() - 4..text.len() - 1
+    );
+}
+
+#[gpui::test]
+async fn test_completions_routed_to_multiple_language_servers(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "a.ts": "" })).await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let capabilities = lsp::ServerCapabilities {
+        completion_provider: Some(lsp::CompletionOptions::default()),
+        ..Default::default()
+    };
+    let mut fake_ts_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            name: "ts-lsp",
+            capabilities: capabilities.clone(),
+            ..Default::default()
+        },
+    );
+    let mut fake_tailwind_servers = language_registry.register_specific_fake_lsp_adapter(
+        "TypeScript",
+        false,
+        FakeLspAdapter {
+            name: "tailwind-lsp",
+            capabilities,
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| project.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
+    let fake_ts_server = fake_ts_servers.next().await.unwrap();
+    let fake_tailwind_server = fake_tailwind_servers.next().await.unwrap();
+    cx.executor().run_until_parked();
+
+    for (fake_server, labels) in [
+        (&fake_ts_server, ["alpha", "beta"]),
+        (&fake_tailwind_server, ["beta", "gamma"]),
+    ] {
+        fake_server.handle_request::<lsp::request::Completion, _, _>(move |_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(
+                labels
+                    .into_iter()
+                    .map(|label| lsp::CompletionItem {
+                        label: label.into(),
+                        ..Default::default()
+                    })
+                    .collect(),
+            )))
+        });
+    }
+
+    for (completion_merge, expected_labels) in [
+        (CompletionMerge::All, vec!["beta", "gamma", "alpha", "beta"]),
+        (CompletionMerge::First, vec!["beta", "gamma"]),
+        (CompletionMerge::Deduplicate, vec!["beta", "gamma", "alpha"]),
+    ] {
+        cx.update(|cx| {
+            SettingsStore::update_global(cx, |settings, cx| {
+                settings.update_user_settings::<AllLanguageSettings>(cx, |settings| {
+                    settings.languages.insert(
+                        Arc::from("TypeScript"),
+                        LanguageSettingsContent {
+                            language_server_routing: Some(LanguageServerRouting {
+                                completions: vec!["tailwind-lsp".into(), "...".into()],
+                                completion_merge,
+                                ..Default::default()
+                            }),
+                            ..Default::default()
+                        },
+                    );
+                });
+            })
+        });
+
+        let completions = project
+            .update(cx, |project, cx| project.completions(&buffer, 0, cx))
+            .await
+            .unwrap();
+        let labels = completions
+            .iter()
+            .map(|completion| completion.label.text.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(labels, expected_labels, "{completion_merge:?}");
+    }
+}
+
+#[gpui::test]
+async fn test_completions_with_carriage_returns(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                completion_provider: Some(lsp::CompletionOptions {
+                    trigger_characters: Some(vec![":".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let text = "let a = b.fqn";
+    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+    let completions = project.update(cx, |project, cx| {
+        project.completions(&buffer, text.len(), cx)
+    });
+
+    fake_server
+        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
+            Ok(Some(lsp::CompletionResponse::Array(vec![
+                lsp::CompletionItem {
+                    label: "fullyQualifiedName?".into(),
+                    insert_text: Some("fully\rQualified\r\nName".into()),
+                    ..Default::default()
+                },
+            ])))
+        })
+        .next()
+        .await;
+    let completions = completions.await.unwrap();
+    assert_eq!(completions.len(), 1);
+    assert_eq!(completions[0].new_text, "fully\nQualified\nName");
+}
+
+#[gpui::test]
+async fn test_code_lens(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "function a() {}\nfunction b() {}",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_lens_provider: Some(lsp::CodeLensOptions {
+                    resolve_provider: Some(true),
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
     );
 
-    // Handle out-of-order diagnostics
-    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
-        uri: lsp::Url::from_file_path("/dir/a.rs").unwrap(),
-        version: Some(change_notification_2.text_document.version),
-        diagnostics: vec![
-            lsp::Diagnostic {
-                range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 11)),
-                severity: Some(DiagnosticSeverity::ERROR),
-                message: "undefined variable 'BB'".to_string(),
-                source: Some("disk".to_string()),
-                ..Default::default()
-            },
-            lsp::Diagnostic {
-                range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
-                severity: Some(DiagnosticSeverity::WARNING),
-                message: "undefined variable 'A'".to_string(),
-                source: Some("disk".to_string()),
-                ..Default::default()
-            },
-        ],
-    });
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
 
-    cx.executor().run_until_parked();
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    // The language server returns one resolved lens and one that needs resolving.
+    let lenses = project.update(cx, |project, cx| project.code_lens(&buffer, cx));
+    fake_server
+        .handle_request::<lsp::request::CodeLensRequest, _, _>(|_, _| async move {
+            Ok(Some(vec![
+                lsp::CodeLens {
+                    range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
+                    command: Some(lsp::Command {
+                        title: "Run".into(),
+                        command: "_the/run".into(),
+                        arguments: None,
+                    }),
+                    data: None,
+                },
+                lsp::CodeLens {
+                    range: lsp::Range::new(lsp::Position::new(1, 9), lsp::Position::new(1, 10)),
+                    command: None,
+                    data: Some(json!({ "symbol": "b" })),
+                },
+            ]))
+        })
+        .next()
+        .await;
+    let lenses = lenses.await;
+    assert_eq!(lenses.len(), 2);
     buffer.update(cx, |buffer, _| {
         assert_eq!(
-            buffer
-                .snapshot()
-                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
-                .collect::<Vec<_>>(),
-            &[
-                DiagnosticEntry {
-                    range: Point::new(2, 21)..Point::new(2, 22),
-                    diagnostic: Diagnostic {
-                        source: Some("disk".into()),
-                        severity: DiagnosticSeverity::WARNING,
-                        message: "undefined variable 'A'".to_string(),
-                        is_disk_based: true,
-                        group_id: 6,
-                        is_primary: true,
-                        ..Default::default()
-                    }
-                },
-                DiagnosticEntry {
-                    range: Point::new(3, 9)..Point::new(3, 14),
-                    diagnostic: Diagnostic {
-                        source: Some("disk".into()),
-                        severity: DiagnosticSeverity::ERROR,
-                        message: "undefined variable 'BB'".to_string(),
-                        is_disk_based: true,
-                        group_id: 5,
-                        is_primary: true,
-                        ..Default::default()
-                    },
-                }
-            ]
+            lenses[1].range.to_point(buffer),
+            Point::new(1, 9)..Point::new(1, 10)
         );
     });
+
+    fake_server.handle_request::<lsp::request::CodeLensResolve, _, _>(|mut lens, _| async move {
+        assert_eq!(lens.data, Some(json!({ "symbol": "b" })));
+        lens.command = Some(lsp::Command {
+            title: "2 references".into(),
+            command: "_the/references".into(),
+            arguments: None,
+        });
+        Ok(lens)
+    });
+    let resolved_lens = project
+        .update(cx, |project, cx| {
+            project.resolve_code_lens(buffer.clone(), lenses[1].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        resolved_lens.lsp_lens.command.map(|command| command.title),
+        Some("2 references".to_string())
+    );
+
+    // Running a lens executes its command on the language server.
+    let mut execute_requests =
+        fake_server.handle_request::<lsp::request::ExecuteCommand, _, _>(|params, _| async move {
+            assert_eq!(params.command, "_the/run");
+            Ok(Some(json!(null)))
+        });
+    let run = project.update(cx, |project, cx| {
+        project.run_code_lens(buffer.clone(), lenses[0].clone(), cx)
+    });
+    execute_requests.next().await.unwrap();
+    run.await.unwrap();
 }
 
 #[gpui::test]
-async fn test_empty_diagnostic_ranges(cx: &mut gpui::TestAppContext) {
+async fn test_document_links(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let text = concat!(
-        "let one = ;\n", //
-        "let two = \n",
-        "let three = 3;\n",
-    );
-
     let fs = FakeFs::new(cx.executor());
-    fs.insert_tree("/dir", json!({ "a.rs": text })).await;
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "import './b';\n// https://zed.dev",
+            "b.ts": "",
+        }),
+    )
+    .await;
 
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                document_link_provider: Some(lsp::DocumentLinkOptions {
+                    resolve_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                }),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
     let buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
         .await
         .unwrap();
 
-    project.update(cx, |project, cx| {
-        project
-            .update_buffer_diagnostics(
-                &buffer,
-                LanguageServerId(0),
-                None,
-                vec![
-                    DiagnosticEntry {
-                        range: Unclipped(PointUtf16::new(0, 10))..Unclipped(PointUtf16::new(0, 10)),
-                        diagnostic: Diagnostic {
-                            severity: DiagnosticSeverity::ERROR,
-                            message: "syntax error 1".to_string(),
-                            ..Default::default()
-                        },
-                    },
-                    DiagnosticEntry {
-                        range: Unclipped(PointUtf16::new(1, 10))..Unclipped(PointUtf16::new(1, 10)),
-                        diagnostic: Diagnostic {
-                            severity: DiagnosticSeverity::ERROR,
-                            message: "syntax error 2".to_string(),
-                            ..Default::default()
-                        },
-                    },
-                ],
-                cx,
-            )
-            .unwrap();
-    });
+    let fake_server = fake_language_servers.next().await.unwrap();
 
-    // An empty range is extended forward to include the following character.
-    // At the end of a line, an empty range is extended backward to include
-    // the preceding character.
+    // The language server returns one link with a target and one that needs resolving.
+    let links = project.update(cx, |project, cx| project.document_links(&buffer, cx));
+    fake_server
+        .handle_request::<lsp::request::DocumentLinkRequest, _, _>(|_, _| async move {
+            Ok(Some(vec![
+                lsp::DocumentLink {
+                    range: lsp::Range::new(lsp::Position::new(1, 3), lsp::Position::new(1, 18)),
+                    target: Some("https://zed.dev".parse().unwrap()),
+                    tooltip: None,
+                    data: None,
+                },
+                lsp::DocumentLink {
+                    range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 11)),
+                    target: None,
+                    tooltip: None,
+                    data: Some(json!({ "module": "./b" })),
+                },
+            ]))
+        })
+        .next()
+        .await;
+    let links = links.await;
+    assert_eq!(links.len(), 2);
     buffer.update(cx, |buffer, _| {
-        let chunks = chunks_with_diagnostics(buffer, 0..buffer.len());
         assert_eq!(
-            chunks
-                .iter()
-                .map(|(s, d)| (s.as_str(), *d))
-                .collect::<Vec<_>>(),
-            &[
-                ("let one = ", None),
-                (";", Some(DiagnosticSeverity::ERROR)),
-                ("\nlet two =", None),
-                (" ", Some(DiagnosticSeverity::ERROR)),
-                ("\nlet three = 3;\n", None)
-            ]
+            links[1].range.to_point(buffer),
+            Point::new(0, 8)..Point::new(0, 11)
         );
     });
+
+    fake_server.handle_request::<lsp::request::DocumentLinkResolve, _, _>(
+        |mut link, _| async move {
+            assert_eq!(link.data, Some(json!({ "module": "./b" })));
+            link.target = Some(lsp::Url::from_file_path("/dir/b.ts").unwrap());
+            Ok(link)
+        },
+    );
+    let resolved_link = project
+        .update(cx, |project, cx| {
+            project.resolve_document_link(buffer.clone(), links[1].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(
+        resolved_link.lsp_link.target,
+        Some(lsp::Url::from_file_path("/dir/b.ts").unwrap())
+    );
 }
 
 #[gpui::test]
-async fn test_diagnostics_from_multiple_language_servers(cx: &mut gpui::TestAppContext) {
+async fn test_folding_ranges(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
-    fs.insert_tree("/dir", json!({ "a.rs": "one two three" }))
-        .await;
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "import a from './a';\nimport b from './b';\n\nfunction f() {\n    a();\n}",
+        }),
+    )
+    .await;
 
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
-    project.update(cx, |project, cx| {
-        project
-            .update_diagnostic_entries(
-                LanguageServerId(0),
-                Path::new("/dir/a.rs").to_owned(),
-                None,
-                vec![DiagnosticEntry {
-                    range: Unclipped(PointUtf16::new(0, 0))..Unclipped(PointUtf16::new(0, 3)),
-                    diagnostic: Diagnostic {
-                        severity: DiagnosticSeverity::ERROR,
-                        is_primary: true,
-                        message: "syntax error a1".to_string(),
-                        ..Default::default()
-                    },
-                }],
-                cx,
-            )
-            .unwrap();
-        project
-            .update_diagnostic_entries(
-                LanguageServerId(1),
-                Path::new("/dir/a.rs").to_owned(),
-                None,
-                vec![DiagnosticEntry {
-                    range: Unclipped(PointUtf16::new(0, 0))..Unclipped(PointUtf16::new(0, 3)),
-                    diagnostic: Diagnostic {
-                        severity: DiagnosticSeverity::ERROR,
-                        is_primary: true,
-                        message: "syntax error b1".to_string(),
-                        ..Default::default()
-                    },
-                }],
-                cx,
-            )
-            .unwrap();
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                folding_range_provider: Some(lsp::FoldingRangeProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_language_servers.next().await.unwrap();
 
+    // Ranges spanning a single line or past the end of the buffer are discarded.
+    let ranges = project.update(cx, |project, cx| project.folding_ranges(&buffer, cx));
+    fake_server
+        .handle_request::<lsp::request::FoldingRangeRequest, _, _>(|_, _| async move {
+            Ok(Some(vec![
+                lsp::FoldingRange {
+                    start_line: 3,
+                    end_line: 5,
+                    ..Default::default()
+                },
+                lsp::FoldingRange {
+                    start_line: 0,
+                    end_line: 1,
+                    kind: Some(lsp::FoldingRangeKind::Imports),
+                    ..Default::default()
+                },
+                lsp::FoldingRange {
+                    start_line: 4,
+                    end_line: 4,
+                    ..Default::default()
+                },
+                lsp::FoldingRange {
+                    start_line: 4,
+                    end_line: 10,
+                    ..Default::default()
+                },
+            ]))
+        })
+        .next()
+        .await;
+    let ranges = ranges.await.unwrap();
+    buffer.update(cx, |buffer, _| {
         assert_eq!(
-            project.diagnostic_summary(false, cx),
-            DiagnosticSummary {
-                error_count: 2,
-                warning_count: 0,
-            }
+            ranges
+                .iter()
+                .map(|range| (range.range.to_point(buffer), range.kind.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    Point::new(0, 20)..Point::new(1, 20),
+                    Some(lsp::FoldingRangeKind::Imports)
+                ),
+                (Point::new(3, 14)..Point::new(5, 1), None),
+            ]
         );
     });
 }
 
 #[gpui::test]
-async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
+async fn test_document_colors(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let text = "
-        fn a() {
-            f1();
-        }
-        fn b() {
-            f2();
-        }
-        fn c() {
-            f3();
-        }
-    "
-    .unindent();
-
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.rs": text.clone(),
+            "a.ts": "const red = \"#f00\";",
         }),
     )
     .await;
@@ -1967,463 +3932,432 @@ async fn test_edits_from_lsp2_with_past_version(cx: &mut gpui::TestAppContext) {
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(rust_lang());
-    let mut fake_servers =
-        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                color_provider: Some(lsp::ColorProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
 
     let buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
         .await
         .unwrap();
 
-    let mut fake_server = fake_servers.next().await.unwrap();
-    let lsp_document_version = fake_server
-        .receive_notification::<lsp::notification::DidOpenTextDocument>()
-        .await
-        .text_document
-        .version;
-
-    // Simulate editing the buffer after the language server computes some edits.
-    buffer.update(cx, |buffer, cx| {
-        buffer.edit(
-            [(
-                Point::new(0, 0)..Point::new(0, 0),
-                "// above first function\n",
-            )],
-            None,
-            cx,
-        );
-        buffer.edit(
-            [(
-                Point::new(2, 0)..Point::new(2, 0),
-                "    // inside first function\n",
-            )],
-            None,
-            cx,
-        );
-        buffer.edit(
-            [(
-                Point::new(6, 4)..Point::new(6, 4),
-                "// inside second function ",
-            )],
-            None,
-            cx,
-        );
+    let fake_server = fake_language_servers.next().await.unwrap();
 
+    let colors = project.update(cx, |project, cx| project.document_colors(&buffer, cx));
+    fake_server
+        .handle_request::<lsp::request::DocumentColor, _, _>(|_, _| async move {
+            Ok(vec![lsp::ColorInformation {
+                range: lsp::Range::new(lsp::Position::new(0, 13), lsp::Position::new(0, 17)),
+                color: lsp::Color {
+                    red: 1.,
+                    green: 0.,
+                    blue: 0.,
+                    alpha: 1.,
+                },
+            }])
+        })
+        .next()
+        .await;
+    let colors = colors.await;
+    assert_eq!(colors.len(), 1);
+    buffer.update(cx, |buffer, _| {
         assert_eq!(
-            buffer.text(),
-            "
-                // above first function
-                fn a() {
-                    // inside first function
-                    f1();
-                }
-                fn b() {
-                    // inside second function f2();
-                }
-                fn c() {
-                    f3();
-                }
-            "
-            .unindent()
+            colors[0].range.to_point(buffer),
+            Point::new(0, 13)..Point::new(0, 17)
         );
     });
 
-    let edits = project
+    // Picking a new color asks the language server how to write it over the literal.
+    fake_server.handle_request::<lsp::request::ColorPresentationRequest, _, _>(
+        |params, _| async move {
+            assert_eq!(params.color.green, 1.);
+            Ok(vec![
+                lsp::ColorPresentation {
+                    label: "#ff0".into(),
+                    text_edit: None,
+                    additional_text_edits: None,
+                },
+                lsp::ColorPresentation {
+                    label: "#ffff00".into(),
+                    text_edit: Some(lsp::TextEdit {
+                        range: lsp::Range::new(
+                            lsp::Position::new(0, 13),
+                            lsp::Position::new(0, 17),
+                        ),
+                        new_text: "#ffff00".into(),
+                    }),
+                    additional_text_edits: None,
+                },
+            ])
+        },
+    );
+    let presentations = project
         .update(cx, |project, cx| {
-            project.edits_from_lsp(
-                &buffer,
-                vec![
-                    // replace body of first function
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 0), lsp::Position::new(3, 0)),
-                        new_text: "
-                            fn a() {
-                                f10();
-                            }
-                            "
-                        .unindent(),
-                    },
-                    // edit inside second function
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(4, 6), lsp::Position::new(4, 6)),
-                        new_text: "00".into(),
-                    },
-                    // edit inside third function via two distinct edits
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(7, 5), lsp::Position::new(7, 5)),
-                        new_text: "4000".into(),
-                    },
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(7, 5), lsp::Position::new(7, 6)),
-                        new_text: "".into(),
-                    },
-                ],
-                LanguageServerId(0),
-                Some(lsp_document_version),
+            project.color_presentations(
+                buffer.clone(),
+                colors[0].clone(),
+                lsp::Color {
+                    red: 1.,
+                    green: 1.,
+                    blue: 0.,
+                    alpha: 1.,
+                },
                 cx,
             )
         })
         .await
         .unwrap();
-
-    buffer.update(cx, |buffer, cx| {
-        for (range, new_text) in edits {
-            buffer.edit([(range, new_text)], None, cx);
+    assert_eq!(presentations.len(), 2);
+    buffer.update(cx, |buffer, _| {
+        for presentation in &presentations {
+            let edits = presentation
+                .edits
+                .iter()
+                .map(|(range, text)| (range.to_point(buffer), text.as_str()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                edits,
+                [(
+                    Point::new(0, 13)..Point::new(0, 17),
+                    presentation.label.as_str()
+                )]
+            );
         }
-        assert_eq!(
-            buffer.text(),
-            "
-                // above first function
-                fn a() {
-                    // inside first function
-                    f10();
-                }
-                fn b() {
-                    // inside second function f200();
-                }
-                fn c() {
-                    f4000();
-                }
-                "
-            .unindent()
-        );
     });
 }
 
 #[gpui::test]
-async fn test_edits_from_lsp2_with_edits_on_adjacent_lines(cx: &mut gpui::TestAppContext) {
+async fn test_call_hierarchy(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let text = "
-        use a::b;
-        use a::c;
-
-        fn f() {
-            b();
-            c();
-        }
-    "
-    .unindent();
-
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.rs": text.clone(),
+            "a.ts": "function a() { b(); }\nfunction b() {}",
         }),
     )
     .await;
 
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
-    let buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
-        .await
-        .unwrap();
 
-    // Simulate the language server sending us a small edit in the form of a very large diff.
-    // Rust-analyzer does this when performing a merge-imports code action.
-    let edits = project
-        .update(cx, |project, cx| {
-            project.edits_from_lsp(
-                &buffer,
-                [
-                    // Replace the first use statement without editing the semicolon.
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 4), lsp::Position::new(0, 8)),
-                        new_text: "a::{b, c}".into(),
-                    },
-                    // Reinsert the remainder of the file between the semicolon and the final
-                    // newline of the file.
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
-                        new_text: "\n\n".into(),
-                    },
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
-                        new_text: "
-                            fn f() {
-                                b();
-                                c();
-                            }"
-                        .unindent(),
-                    },
-                    // Delete everything after the first newline of the file.
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(7, 0)),
-                        new_text: "".into(),
-                    },
-                ],
-                LanguageServerId(0),
-                None,
-                cx,
-            )
-        })
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                call_hierarchy_provider: Some(lsp::CallHierarchyServerCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
         .await
         .unwrap();
 
-    buffer.update(cx, |buffer, cx| {
-        let edits = edits
-            .into_iter()
-            .map(|(range, text)| {
-                (
-                    range.start.to_point(buffer)..range.end.to_point(buffer),
-                    text,
-                )
-            })
-            .collect::<Vec<_>>();
+    let fake_server = fake_language_servers.next().await.unwrap();
 
-        assert_eq!(
-            edits,
-            [
-                (Point::new(0, 4)..Point::new(0, 8), "a::{b, c}".into()),
-                (Point::new(1, 0)..Point::new(2, 0), "".into())
-            ]
-        );
+    let lsp_item = |name: &str, row: u32| lsp::CallHierarchyItem {
+        name: name.into(),
+        kind: lsp::SymbolKind::FUNCTION,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path("/dir/a.ts").unwrap(),
+        range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 15)),
+        selection_range: lsp::Range::new(lsp::Position::new(row, 9), lsp::Position::new(row, 10)),
+        data: None,
+    };
 
-        for (range, new_text) in edits {
-            buffer.edit([(range, new_text)], None, cx);
-        }
-        assert_eq!(
-            buffer.text(),
-            "
-                use a::{b, c};
+    let items = project.update(cx, |project, cx| {
+        project.prepare_call_hierarchy(&buffer, Point::new(1, 9), cx)
+    });
+    fake_server
+        .handle_request::<lsp::request::CallHierarchyPrepare, _, _>(move |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 9)
+            );
+            Ok(Some(vec![lsp_item("b", 1)]))
+        })
+        .next()
+        .await;
+    let items = items.await.unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].lsp_item.name, "b");
+
+    fake_server.handle_request::<lsp::request::CallHierarchyIncomingCalls, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "b");
+            Ok(Some(vec![lsp::CallHierarchyIncomingCall {
+                from: lsp_item("a", 0),
+                from_ranges: vec![lsp::Range::new(
+                    lsp::Position::new(0, 15),
+                    lsp::Position::new(0, 16),
+                )],
+            }]))
+        },
+    );
+    let calls = project
+        .update(cx, |project, cx| {
+            project.incoming_calls(&buffer, items[0].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].item.lsp_item.name, "a");
+    assert_eq!(calls[0].from_ranges[0].start, lsp::Position::new(0, 15));
 
-                fn f() {
-                    b();
-                    c();
-                }
-            "
-            .unindent()
-        );
-    });
+    fake_server.handle_request::<lsp::request::CallHierarchyOutgoingCalls, _, _>(
+        |_, _| async move { Ok(None) },
+    );
+    let calls = project
+        .update(cx, |project, cx| {
+            project.outgoing_calls(&buffer, items[0].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert!(calls.is_empty());
 }
 
 #[gpui::test]
-async fn test_invalid_edits_from_lsp2(cx: &mut gpui::TestAppContext) {
+async fn test_type_hierarchy(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
-    let text = "
-        use a::b;
-        use a::c;
-
-        fn f() {
-            b();
-            c();
-        }
-    "
-    .unindent();
-
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.rs": text.clone(),
+            "a.ts": "class A {}\nclass B extends A {}",
         }),
     )
     .await;
 
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers =
+        language_registry.register_fake_lsp_adapter("TypeScript", FakeLspAdapter::default());
+
     let buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/a.rs", cx))
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
         .await
         .unwrap();
 
-    // Simulate the language server sending us edits in a non-ordered fashion,
-    // with ranges sometimes being inverted or pointing to invalid locations.
-    let edits = project
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let lsp_item = |name: &str, row: u32| lsp::TypeHierarchyItem {
+        name: name.into(),
+        kind: lsp::SymbolKind::CLASS,
+        tags: None,
+        detail: None,
+        uri: lsp::Url::from_file_path("/dir/a.ts").unwrap(),
+        range: lsp::Range::new(lsp::Position::new(row, 0), lsp::Position::new(row, 10)),
+        selection_range: lsp::Range::new(lsp::Position::new(row, 6), lsp::Position::new(row, 7)),
+        data: None,
+    };
+
+    let items = project.update(cx, |project, cx| {
+        project.prepare_type_hierarchy(&buffer, Point::new(1, 6), cx)
+    });
+    fake_server
+        .handle_request::<lsp::request::TypeHierarchyPrepare, _, _>(move |params, _| async move {
+            assert_eq!(
+                params.text_document_position_params.position,
+                lsp::Position::new(1, 6)
+            );
+            Ok(Some(vec![lsp_item("B", 1)]))
+        })
+        .next()
+        .await;
+    let items = items.await.unwrap();
+    assert_eq!(items.len(), 1);
+    assert_eq!(items[0].lsp_item.name, "B");
+
+    fake_server.handle_request::<lsp::request::TypeHierarchySupertypes, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.item.name, "B");
+            Ok(Some(vec![lsp_item("A", 0)]))
+        },
+    );
+    let supertypes = project
         .update(cx, |project, cx| {
-            project.edits_from_lsp(
-                &buffer,
-                [
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
-                        new_text: "\n\n".into(),
-                    },
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 8), lsp::Position::new(0, 4)),
-                        new_text: "a::{b, c}".into(),
-                    },
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(1, 0), lsp::Position::new(99, 0)),
-                        new_text: "".into(),
-                    },
-                    lsp::TextEdit {
-                        range: lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 9)),
-                        new_text: "
-                            fn f() {
-                                b();
-                                c();
-                            }"
-                        .unindent(),
-                    },
-                ],
-                LanguageServerId(0),
-                None,
-                cx,
-            )
+            project.supertypes(&buffer, items[0].clone(), cx)
         })
         .await
         .unwrap();
+    assert_eq!(supertypes.len(), 1);
+    assert_eq!(supertypes[0].lsp_item.name, "A");
 
-    buffer.update(cx, |buffer, cx| {
-        let edits = edits
-            .into_iter()
-            .map(|(range, text)| {
-                (
-                    range.start.to_point(buffer)..range.end.to_point(buffer),
-                    text,
-                )
-            })
-            .collect::<Vec<_>>();
-
-        assert_eq!(
-            edits,
-            [
-                (Point::new(0, 4)..Point::new(0, 8), "a::{b, c}".into()),
-                (Point::new(1, 0)..Point::new(2, 0), "".into())
-            ]
-        );
-
-        for (range, new_text) in edits {
-            buffer.edit([(range, new_text)], None, cx);
-        }
-        assert_eq!(
-            buffer.text(),
-            "
-                use a::{b, c};
-
-                fn f() {
-                    b();
-                    c();
-                }
-            "
-            .unindent()
-        );
+    fake_server.handle_request::<lsp::request::TypeHierarchySubtypes, _, _>(|_, _| async move {
+        Ok(None)
     });
+    let subtypes = project
+        .update(cx, |project, cx| {
+            project.subtypes(&buffer, items[0].clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert!(subtypes.is_empty());
 }
 
-fn chunks_with_diagnostics<T: ToOffset + ToPoint>(
-    buffer: &Buffer,
-    range: Range<T>,
-) -> Vec<(String, Option<DiagnosticSeverity>)> {
-    let mut chunks: Vec<(String, Option<DiagnosticSeverity>)> = Vec::new();
-    for chunk in buffer.snapshot().chunks(range, true) {
-        if chunks.last().map_or(false, |prev_chunk| {
-            prev_chunk.1 == chunk.diagnostic_severity
-        }) {
-            chunks.last_mut().unwrap().0.push_str(chunk.text);
-        } else {
-            chunks.push((chunk.text.to_string(), chunk.diagnostic_severity));
-        }
-    }
-    chunks
-}
-
-#[gpui::test(iterations = 10)]
-async fn test_definition(cx: &mut gpui::TestAppContext) {
+#[gpui::test]
+async fn test_semantic_tokens(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.rs": "const fn a() { A }",
-            "b.rs": "const y: i32 = crate::a()",
+            "a.ts": "let a = 1;\nlet b = a;",
         }),
     )
     .await;
 
-    let project = Project::test(fs, ["/dir/b.rs".as_ref()], cx).await;
+    let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
     let language_registry = project.read_with(cx, |project, _| project.languages().clone());
-    language_registry.add(rust_lang());
-    let mut fake_servers =
-        language_registry.register_fake_lsp_adapter("Rust", FakeLspAdapter::default());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                semantic_tokens_provider: Some(
+                    lsp::SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        lsp::SemanticTokensOptions {
+                            legend: lsp::SemanticTokensLegend {
+                                token_types: vec![
+                                    lsp::SemanticTokenType::VARIABLE,
+                                    lsp::SemanticTokenType::FUNCTION,
+                                ],
+                                token_modifiers: vec![
+                                    lsp::SemanticTokenModifier::DECLARATION,
+                                    lsp::SemanticTokenModifier::new("mutable"),
+                                ],
+                            },
+                            full: Some(lsp::SemanticTokensFullOptions::Delta { delta: Some(true) }),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
 
     let buffer = project
-        .update(cx, |project, cx| project.open_local_buffer("/dir/b.rs", cx))
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
         .await
         .unwrap();
 
-    let fake_server = fake_servers.next().await.unwrap();
-    fake_server.handle_request::<lsp::request::GotoDefinition, _, _>(|params, _| async move {
-        let params = params.text_document_position_params;
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    let token = |delta_line, delta_start, token_type, token_modifiers_bitset| lsp::SemanticToken {
+        delta_line,
+        delta_start,
+        length: 1,
+        token_type,
+        token_modifiers_bitset,
+    };
+    fake_server.handle_request::<lsp::request::SemanticTokensFullRequest, _, _>(
+        move |_, _| async move {
+            Ok(Some(lsp::SemanticTokensResult::Tokens(
+                lsp::SemanticTokens {
+                    result_id: Some("1".into()),
+                    data: vec![token(0, 4, 0, 0b11), token(1, 8, 0, 0)],
+                },
+            )))
+        },
+    );
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
+        .await
+        .unwrap();
+    buffer.read_with(cx, |buffer, _| {
+        let tokens = tokens
+            .iter()
+            .map(|token| {
+                (
+                    token.range.to_point(buffer),
+                    token.token_type.as_ref(),
+                    token
+                        .modifiers
+                        .iter()
+                        .map(|modifier| modifier.as_ref())
+                        .collect::<Vec<_>>(),
+                )
+            })
+            .collect::<Vec<_>>();
         assert_eq!(
-            params.text_document.uri.to_file_path().unwrap(),
-            Path::new("/dir/b.rs"),
+            tokens,
+            [
+                (
+                    Point::new(0, 4)..Point::new(0, 5),
+                    "variable",
+                    vec!["declaration", "mutable"]
+                ),
+                (Point::new(1, 8)..Point::new(1, 9), "variable", vec![]),
+            ]
         );
-        assert_eq!(params.position, lsp::Position::new(0, 22));
-
-        Ok(Some(lsp::GotoDefinitionResponse::Scalar(
-            lsp::Location::new(
-                lsp::Url::from_file_path("/dir/a.rs").unwrap(),
-                lsp::Range::new(lsp::Position::new(0, 9), lsp::Position::new(0, 10)),
-            ),
-        )))
     });
 
-    let mut definitions = project
-        .update(cx, |project, cx| project.definition(&buffer, 22, cx))
+    // Later requests only ask for the tokens that changed since the last result.
+    fake_server.handle_request::<lsp::request::SemanticTokensFullDeltaRequest, _, _>(
+        move |params, _| async move {
+            assert_eq!(params.previous_result_id, "1");
+            Ok(Some(lsp::SemanticTokensFullDeltaResult::TokensDelta(
+                lsp::SemanticTokensDelta {
+                    result_id: Some("2".into()),
+                    edits: vec![lsp::SemanticTokensEdit {
+                        start: 5,
+                        delete_count: 5,
+                        data: Some(vec![token(1, 4, 1, 0)]),
+                    }],
+                },
+            )))
+        },
+    );
+    let tokens = project
+        .update(cx, |project, cx| project.semantic_tokens(&buffer, cx))
         .await
         .unwrap();
-
-    // Assert no new language server started
-    cx.executor().run_until_parked();
-    assert!(fake_servers.try_next().is_err());
-
-    assert_eq!(definitions.len(), 1);
-    let definition = definitions.pop().unwrap();
-    cx.update(|cx| {
-        let target_buffer = definition.target.buffer.read(cx);
-        assert_eq!(
-            target_buffer
-                .file()
-                .unwrap()
-                .as_local()
-                .unwrap()
-                .abs_path(cx),
-            Path::new("/dir/a.rs"),
-        );
-        assert_eq!(definition.target.range.to_offset(target_buffer), 9..10);
+    buffer.read_with(cx, |buffer, _| {
+        let tokens = tokens
+            .iter()
+            .map(|token| (token.range.to_point(buffer), token.token_type.as_ref()))
+            .collect::<Vec<_>>();
         assert_eq!(
-            list_worktrees(&project, cx),
-            [("/dir/a.rs".as_ref(), false), ("/dir/b.rs".as_ref(), true)],
+            tokens,
+            [
+                (Point::new(0, 4)..Point::new(0, 5), "variable"),
+                (Point::new(1, 4)..Point::new(1, 5), "function"),
+            ]
         );
-
-        drop(definition);
-    });
-    cx.update(|cx| {
-        assert_eq!(list_worktrees(&project, cx), [("/dir/b.rs".as_ref(), true)]);
     });
-
-    fn list_worktrees<'a>(
-        project: &'a Model<Project>,
-        cx: &'a AppContext,
-    ) -> Vec<(&'a Path, bool)> {
-        project
-            .read(cx)
-            .worktrees()
-            .map(|worktree| {
-                let worktree = worktree.read(cx);
-                (
-                    worktree.as_local().unwrap().abs_path().as_ref(),
-                    worktree.is_visible(),
-                )
-            })
-            .collect::<Vec<_>>()
-    }
 }
 
 #[gpui::test]
-async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
+async fn test_pull_diagnostics(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
     fs.insert_tree(
         "/dir",
         json!({
-            "a.ts": "",
+            "a.ts": "let a = b;",
         }),
     )
     .await;
@@ -2436,13 +4370,12 @@ async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
         "TypeScript",
         FakeLspAdapter {
             capabilities: lsp::ServerCapabilities {
-                completion_provider: Some(lsp::CompletionOptions {
-                    trigger_characters: Some(vec![":".to_string()]),
-                    ..Default::default()
-                }),
-                ..Default::default()
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions::default(),
+                )),
+                ..lsp::ServerCapabilities::default()
             },
-            ..Default::default()
+            ..FakeLspAdapter::default()
         },
     );
 
@@ -2452,73 +4385,89 @@ async fn test_completions_without_edit_ranges(cx: &mut gpui::TestAppContext) {
         .unwrap();
 
     let fake_server = fake_language_servers.next().await.unwrap();
+    let mut requests = fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>(
+        |params, _| async move {
+            let report = match params.previous_result_id {
+                None => {
+                    lsp::DocumentDiagnosticReport::Full(lsp::RelatedFullDocumentDiagnosticReport {
+                        related_documents: None,
+                        full_document_diagnostic_report: lsp::FullDocumentDiagnosticReport {
+                            result_id: Some("1".into()),
+                            items: vec![lsp::Diagnostic {
+                                range: lsp::Range::new(
+                                    lsp::Position::new(0, 8),
+                                    lsp::Position::new(0, 9),
+                                ),
+                                severity: Some(lsp::DiagnosticSeverity::ERROR),
+                                message: "undefined variable 'b'".to_string(),
+                                ..Default::default()
+                            }],
+                        },
+                    })
+                }
+                Some(previous_result_id) => {
+                    assert_eq!(previous_result_id, "1");
+                    lsp::DocumentDiagnosticReport::Unchanged(
+                        lsp::RelatedUnchangedDocumentDiagnosticReport {
+                            related_documents: None,
+                            unchanged_document_diagnostic_report:
+                                lsp::UnchangedDocumentDiagnosticReport {
+                                    result_id: "1".into(),
+                                },
+                        },
+                    )
+                }
+            };
+            Ok(lsp::DocumentDiagnosticReportResult::Report(report))
+        },
+    );
+    cx.executor().advance_clock(DIAGNOSTIC_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
 
-    let text = "let a = b.fqn";
-    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
-    let completions = project.update(cx, |project, cx| {
-        project.completions(&buffer, text.len(), cx)
-    });
+    let assert_diagnostics = |cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            let diagnostics = buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .map(|entry| (entry.range, entry.diagnostic.message))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                diagnostics,
+                [(
+                    Point::new(0, 8)..Point::new(0, 9),
+                    "undefined variable 'b'".to_string()
+                )]
+            );
+        });
+    };
+    assert_diagnostics(cx);
 
-    fake_server
-        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
-            Ok(Some(lsp::CompletionResponse::Array(vec![
-                lsp::CompletionItem {
-                    label: "fullyQualifiedName?".into(),
-                    insert_text: Some("fullyQualifiedName".into()),
-                    ..Default::default()
-                },
-            ])))
-        })
-        .next()
-        .await;
-    let completions = completions.await.unwrap();
-    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
-    assert_eq!(completions.len(), 1);
-    assert_eq!(completions[0].new_text, "fullyQualifiedName");
-    assert_eq!(
-        completions[0].old_range.to_offset(&snapshot),
-        text.len() - 3..text.len()
-    );
+    // Edits pull the diagnostics again, sending the result id of the last ones.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..1, "")], None, cx));
+    cx.executor().advance_clock(DIAGNOSTIC_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_diagnostics(cx);
 
-    let text = "let a = \"atoms/cmp\"";
-    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
-    let completions = project.update(cx, |project, cx| {
-        project.completions(&buffer, text.len() - 1, cx)
+    // Published diagnostics don't replace the pulled ones.
+    fake_server.notify::<lsp::notification::PublishDiagnostics>(lsp::PublishDiagnosticsParams {
+        uri: Url::from_file_path("/dir/a.ts").unwrap(),
+        version: None,
+        diagnostics: Vec::new(),
     });
-
-    fake_server
-        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
-            Ok(Some(lsp::CompletionResponse::Array(vec![
-                lsp::CompletionItem {
-                    label: "component".into(),
-                    ..Default::default()
-                },
-            ])))
-        })
-        .next()
-        .await;
-    let completions = completions.await.unwrap();
-    let snapshot = buffer.update(cx, |buffer, _| buffer.snapshot());
-    assert_eq!(completions.len(), 1);
-    assert_eq!(completions[0].new_text, "component");
-    assert_eq!(
-        completions[0].old_range.to_offset(&snapshot),
-        text.len() - 4..text.len() - 1
-    );
+    cx.executor().run_until_parked();
+    assert_diagnostics(cx);
 }
 
 #[gpui::test]
-async fn test_completions_with_carriage_returns(cx: &mut gpui::TestAppContext) {
+async fn test_pull_diagnostics_after_edits(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
-    fs.insert_tree(
-        "/dir",
-        json!({
-            "a.ts": "",
-        }),
-    )
-    .await;
+    fs.insert_tree("/dir", json!({ "a.ts": "let a = b;" }))
+        .await;
 
     let project = Project::test(fs, ["/dir".as_ref()], cx).await;
 
@@ -2528,13 +4477,12 @@ async fn test_completions_with_carriage_returns(cx: &mut gpui::TestAppContext) {
         "TypeScript",
         FakeLspAdapter {
             capabilities: lsp::ServerCapabilities {
-                completion_provider: Some(lsp::CompletionOptions {
-                    trigger_characters: Some(vec![":".to_string()]),
-                    ..Default::default()
-                }),
-                ..Default::default()
+                diagnostic_provider: Some(lsp::DiagnosticServerCapabilities::Options(
+                    lsp::DiagnosticOptions::default(),
+                )),
+                ..lsp::ServerCapabilities::default()
             },
-            ..Default::default()
+            ..FakeLspAdapter::default()
         },
     );
 
@@ -2543,29 +4491,84 @@ async fn test_completions_with_carriage_returns(cx: &mut gpui::TestAppContext) {
         .await
         .unwrap();
 
+    // The server reports the undefined variable on the row it's on in the text it was last sent,
+    // holding back its response when asked to.
     let fake_server = fake_language_servers.next().await.unwrap();
+    let row = Arc::new(Mutex::new(0));
+    let held_response = Arc::new(Mutex::new(None::<futures::channel::oneshot::Receiver<()>>));
+    let mut requests =
+        fake_server.handle_request::<lsp::request::DocumentDiagnosticRequest, _, _>({
+            let row = row.clone();
+            let held_response = held_response.clone();
+            move |_, _| {
+                let row = *row.lock();
+                let held_response = held_response.lock().take();
+                async move {
+                    if let Some(held_response) = held_response {
+                        held_response.await.ok();
+                    }
+                    Ok(lsp::DocumentDiagnosticReportResult::Report(
+                        lsp::DocumentDiagnosticReport::Full(
+                            lsp::RelatedFullDocumentDiagnosticReport {
+                                related_documents: None,
+                                full_document_diagnostic_report:
+                                    lsp::FullDocumentDiagnosticReport {
+                                        result_id: None,
+                                        items: vec![lsp::Diagnostic {
+                                            range: lsp::Range::new(
+                                                lsp::Position::new(row, 8),
+                                                lsp::Position::new(row, 9),
+                                            ),
+                                            severity: Some(lsp::DiagnosticSeverity::ERROR),
+                                            message: "undefined variable 'b'".to_string(),
+                                            ..Default::default()
+                                        }],
+                                    },
+                            },
+                        ),
+                    ))
+                }
+            }
+        });
+    cx.executor().advance_clock(DIAGNOSTIC_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
 
-    let text = "let a = b.fqn";
-    buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
-    let completions = project.update(cx, |project, cx| {
-        project.completions(&buffer, text.len(), cx)
-    });
+    let assert_diagnostic_row = |row: u32, cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            let ranges = buffer
+                .snapshot()
+                .diagnostics_in_range::<_, Point>(0..buffer.len(), false)
+                .map(|entry| entry.range)
+                .collect::<Vec<_>>();
+            assert_eq!(ranges, [Point::new(row, 8)..Point::new(row, 9)]);
+        });
+    };
+    assert_diagnostic_row(0, cx);
 
-    fake_server
-        .handle_request::<lsp::request::Completion, _, _>(|_, _| async move {
-            Ok(Some(lsp::CompletionResponse::Array(vec![
-                lsp::CompletionItem {
-                    label: "fullyQualifiedName?".into(),
-                    insert_text: Some("fully\rQualified\r\nName".into()),
-                    ..Default::default()
-                },
-            ])))
-        })
-        .next()
-        .await;
-    let completions = completions.await.unwrap();
-    assert_eq!(completions.len(), 1);
-    assert_eq!(completions[0].new_text, "fully\nQualified\nName");
+    // The diagnostics pulled after an edit are for the edited text.
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    *row.lock() = 1;
+    cx.executor().advance_clock(DIAGNOSTIC_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_diagnostic_row(1, cx);
+
+    // Edits made while a pull is in flight pull the diagnostics again.
+    let (respond, held) = futures::channel::oneshot::channel();
+    held_response.lock().replace(held);
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    *row.lock() = 2;
+    cx.executor().advance_clock(DIAGNOSTIC_PULL_DEBOUNCE);
+    cx.executor().run_until_parked();
+    buffer.update(cx, |buffer, cx| buffer.edit([(0..0, "\n")], None, cx));
+    *row.lock() = 3;
+    respond.send(()).unwrap();
+    requests.next().await.unwrap();
+    cx.executor().advance_clock(DIAGNOSTIC_PULL_DEBOUNCE);
+    requests.next().await.unwrap();
+    cx.executor().run_until_parked();
+    assert_diagnostic_row(3, cx);
 }
 
 #[gpui::test(iterations = 10)]
@@ -2701,36 +4704,266 @@ async fn test_apply_code_actions_with_commands(cx: &mut gpui::TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_apply_code_actions_with_resource_operations(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "a.ts": "a",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(typescript_lang());
+    let mut fake_language_servers = language_registry.register_fake_lsp_adapter(
+        "TypeScript",
+        FakeLspAdapter {
+            capabilities: lsp::ServerCapabilities {
+                code_action_provider: Some(lsp::CodeActionProviderCapability::Simple(true)),
+                ..lsp::ServerCapabilities::default()
+            },
+            ..FakeLspAdapter::default()
+        },
+    );
+
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/a.ts", cx))
+        .await
+        .unwrap();
+
+    let fake_server = fake_language_servers.next().await.unwrap();
+
+    // The code action moves the open file, edits it twice at its new path and creates
+    // another file.
+    let edit_b = |column, new_text: &str| {
+        lsp::DocumentChangeOperation::Edit(lsp::TextDocumentEdit {
+            text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                uri: lsp::Url::from_file_path("/dir/b.ts").unwrap(),
+                version: None,
+            },
+            edits: vec![lsp::Edit::Plain(lsp::TextEdit {
+                range: lsp::Range::new(
+                    lsp::Position::new(0, column),
+                    lsp::Position::new(0, column),
+                ),
+                new_text: new_text.into(),
+            })],
+        })
+    };
+    let operations = vec![
+        lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Rename(lsp::RenameFile {
+            old_uri: lsp::Url::from_file_path("/dir/a.ts").unwrap(),
+            new_uri: lsp::Url::from_file_path("/dir/b.ts").unwrap(),
+            options: None,
+            annotation_id: None,
+        })),
+        edit_b(0, "X"),
+        edit_b(2, "Y"),
+        lsp::DocumentChangeOperation::Op(lsp::ResourceOp::Create(lsp::CreateFile {
+            uri: lsp::Url::from_file_path("/dir/c.ts").unwrap(),
+            options: None,
+            annotation_id: None,
+        })),
+    ];
+    let actions = project.update(cx, |project, cx| project.code_actions(&buffer, 0..0, cx));
+    fake_server
+        .handle_request::<lsp::request::CodeActionRequest, _, _>(move |_, _| {
+            let operations = operations.clone();
+            async move {
+                Ok(Some(vec![lsp::CodeActionOrCommand::CodeAction(
+                    lsp::CodeAction {
+                        title: "Move to b.ts".into(),
+                        edit: Some(lsp::WorkspaceEdit {
+                            document_changes: Some(lsp::DocumentChanges::Operations(operations)),
+                            ..Default::default()
+                        }),
+                        ..lsp::CodeAction::default()
+                    },
+                )]))
+            }
+        })
+        .next()
+        .await;
+
+    let action = actions.await[0].clone();
+    let transaction = project
+        .update(cx, |project, cx| {
+            project.apply_code_action(buffer.clone(), action, true, cx)
+        })
+        .await
+        .unwrap();
+
+    // The open buffer follows the rename and gets both edits, in a single transaction.
+    assert_eq!(transaction.0.len(), 1);
+    assert!(transaction.0.contains_key(&buffer));
+    assert!(fs.is_file(Path::new("/dir/c.ts")).await);
+    buffer.update(cx, |buffer, cx| {
+        assert_eq!(buffer.file().unwrap().path().as_ref(), Path::new("b.ts"));
+        assert_eq!(buffer.text(), "XaY");
+        buffer.undo(cx);
+        assert_eq!(buffer.text(), "a");
+    });
+}
+
 #[gpui::test(iterations = 10)]
 async fn test_save_file(cx: &mut gpui::TestAppContext) {
     init_test(cx);
 
     let fs = FakeFs::new(cx.executor());
-    fs.insert_tree(
-        "/dir",
-        json!({
-            "file1": "the old contents",
-        }),
-    )
-    .await;
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "file1": "the old contents",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+        .await
+        .unwrap();
+    buffer.update(cx, |buffer, cx| {
+        assert_eq!(buffer.text(), "the old contents");
+        buffer.edit([(0..0, "a line of text.\n".repeat(10 * 1024))], None, cx);
+    });
+
+    project
+        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+        .await
+        .unwrap();
+
+    let new_text = fs.load(Path::new("/dir/file1")).await.unwrap();
+    assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
+}
+
+#[gpui::test]
+async fn test_local_history(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.local_history.max_entries = 2;
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".env": "SECRET=1",
+            "file1": "one",
+        }),
+    )
+    .await;
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let local_history =
+        project.read_with(cx, |project, _| project.local_history().cloned().unwrap());
+    let buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+        .await
+        .unwrap();
+    for text in ["two", "two", "three", "four"] {
+        buffer.update(cx, |buffer, cx| buffer.set_text(text, cx));
+        project
+            .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+            .await
+            .unwrap();
+    }
+
+    // Saving unchanged contents doesn't add a version, and only the most recent ones are kept.
+    let entries = local_history
+        .entries(Path::new("/dir/file1"))
+        .await
+        .unwrap();
+    let mut texts = Vec::new();
+    for entry in &entries {
+        texts.push(local_history.load(entry).await.unwrap());
+    }
+    assert_eq!(texts, ["four", "three"]);
+
+    // Private files aren't kept.
+    let private_buffer = project
+        .update(cx, |p, cx| p.open_local_buffer("/dir/.env", cx))
+        .await
+        .unwrap();
+    private_buffer.update(cx, |buffer, cx| buffer.set_text("SECRET=2", cx));
+    project
+        .update(cx, |project, cx| {
+            project.save_buffer(private_buffer.clone(), cx)
+        })
+        .await
+        .unwrap();
+    assert!(local_history
+        .entries(Path::new("/dir/.env"))
+        .await
+        .unwrap()
+        .is_empty());
+}
+
+#[gpui::test]
+async fn test_local_history_pruning(cx: &mut gpui::TestAppContext) {
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree("/dir", json!({ "file": "" })).await;
+    let local_history = LocalHistory::new(fs, PathBuf::from("/history"));
+    let path = Path::new("/dir/file");
 
-    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
-    let buffer = project
-        .update(cx, |p, cx| p.open_local_buffer("/dir/file1", cx))
+    for text in ["one", "two", "three"] {
+        local_history
+            .record(path, text.to_string(), 3)
+            .await
+            .unwrap();
+    }
+    assert_eq!(
+        local_history_texts(&local_history, path).await,
+        ["three", "two", "one"]
+    );
+
+    // Once at the limit, the oldest version makes room for the new one.
+    local_history
+        .record(path, "four".to_string(), 3)
         .await
         .unwrap();
-    buffer.update(cx, |buffer, cx| {
-        assert_eq!(buffer.text(), "the old contents");
-        buffer.edit([(0..0, "a line of text.\n".repeat(10 * 1024))], None, cx);
-    });
+    assert_eq!(
+        local_history_texts(&local_history, path).await,
+        ["four", "three", "two"]
+    );
 
-    project
-        .update(cx, |project, cx| project.save_buffer(buffer.clone(), cx))
+    // Lowering the limit deletes all the versions beyond it.
+    local_history
+        .record(path, "five".to_string(), 2)
         .await
         .unwrap();
+    assert_eq!(
+        local_history_texts(&local_history, path).await,
+        ["five", "four"]
+    );
 
-    let new_text = fs.load(Path::new("/dir/file1")).await.unwrap();
-    assert_eq!(new_text, buffer.update(cx, |buffer, _| buffer.text()));
+    // A limit of zero doesn't record anything.
+    local_history
+        .record(path, "six".to_string(), 0)
+        .await
+        .unwrap();
+    assert_eq!(
+        local_history_texts(&local_history, path).await,
+        ["five", "four"]
+    );
+}
+
+async fn local_history_texts(local_history: &LocalHistory, path: &Path) -> Vec<String> {
+    let mut texts = Vec::new();
+    for entry in local_history.entries(path).await.unwrap() {
+        texts.push(local_history.load(&entry).await.unwrap());
+    }
+    texts
 }
 
 #[gpui::test(iterations = 30)]
@@ -3895,6 +6128,200 @@ async fn test_search(cx: &mut gpui::TestAppContext) {
             ("dir/four.rs".to_string(), vec![25..28, 36..39])
         ])
     );
+
+    // Open buffers are reported by text and regex searches even when they don't match.
+    project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/one.rs", cx)
+        })
+        .await
+        .unwrap();
+    for query in [
+        SearchQuery::text("TWO", false, true, false, Vec::new(), Vec::new()).unwrap(),
+        SearchQuery::regex("TW[O]", false, true, false, Vec::new(), Vec::new()).unwrap(),
+    ] {
+        assert_eq!(
+            search(&project, query, cx).await.unwrap(),
+            HashMap::from_iter([
+                ("dir/one.rs".to_string(), vec![]),
+                ("dir/two.rs".to_string(), vec![6..9]),
+                ("dir/three.rs".to_string(), vec![37..40]),
+                ("dir/four.rs".to_string(), vec![25..28, 36..39])
+            ])
+        );
+    }
+}
+
+#[gpui::test]
+async fn test_search_with_search_index(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+    cx.update(|cx| {
+        SettingsStore::update_global(cx, |settings, cx| {
+            settings.update_user_settings::<ProjectSettings>(cx, |settings| {
+                settings.search_index.enabled = true;
+            });
+        });
+    });
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "const ONE: usize = 1;",
+            "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let worktree = project.read_with(cx, |project, _| project.worktrees().next().unwrap());
+    cx.executor().run_until_parked();
+
+    let query = SearchQuery::text("two", false, false, false, Vec::new(), Vec::new()).unwrap();
+    let may_match = |cx: &mut gpui::TestAppContext| {
+        project.read_with(cx, |project, cx| {
+            let filter =
+                search_index::SearchIndexFilter::new(&query, &project.search_indices).unwrap();
+            let worktree = worktree.read(cx);
+            let mut paths = worktree
+                .files(false, 0)
+                .filter(|entry| filter.may_match(worktree.id(), entry))
+                .map(|entry| entry.path.to_string_lossy().to_string())
+                .collect::<Vec<_>>();
+            paths.sort();
+            paths
+        })
+    };
+    assert_eq!(may_match(cx), ["three.rs", "two.rs"]);
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([
+            ("dir/two.rs".to_string(), vec![6..9]),
+            ("dir/three.rs".to_string(), vec![32..35, 37..40])
+        ])
+    );
+
+    // Changed files are reindexed.
+    fs.save(
+        "/dir/one.rs".as_ref(),
+        &"const ONE: usize = two::TWO - 1;".into(),
+        Default::default(),
+    )
+    .await
+    .unwrap();
+    cx.executor().run_until_parked();
+    assert_eq!(may_match(cx), ["one.rs", "three.rs", "two.rs"]);
+    assert_eq!(
+        search(&project, query, cx).await.unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![19..22, 24..27]),
+            ("dir/two.rs".to_string(), vec![6..9]),
+            ("dir/three.rs".to_string(), vec![32..35, 37..40])
+        ])
+    );
+}
+
+#[gpui::test]
+async fn test_multiline_regex_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "fn foo(\n    a: usize,\n) {\n}\n",
+            "two.rs": "fn foo() {}\nfn bar() {}\n",
+            "three.rs": "fn bar(\n) {}\n",
+            // Matches spanning many lines are kept whole.
+            "four.rs": format!("fn foo(\n{}) {{}}\n", "    a: usize,\n".repeat(150)),
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::regex(
+                r"(?s)fn foo\([^)]*\)\s*\{",
+                false,
+                true,
+                false,
+                Vec::new(),
+                Vec::new()
+            )
+            .unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![0..25]),
+            ("dir/two.rs".to_string(), vec![0..10]),
+            ("dir/four.rs".to_string(), vec![0..2111]),
+        ])
+    );
+}
+
+#[gpui::test]
+async fn test_structural_search(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            "one.rs": "fn one() { two(); }",
+            "two.rs": "fn two() { one(); three(); }",
+            "notes.txt": "call two(); later",
+        }),
+    )
+    .await;
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let language_registry = project.read_with(cx, |project, _| project.languages().clone());
+    language_registry.add(rust_lang());
+
+    let query = SearchQuery::structural(
+        "(call_expression function: (identifier) @fn)",
+        false,
+        Vec::new(),
+        Vec::new(),
+    )
+    .unwrap();
+    assert_eq!(
+        search(&project, query.clone(), cx).await.unwrap(),
+        HashMap::from_iter([
+            ("dir/one.rs".to_string(), vec![11..14]),
+            ("dir/two.rs".to_string(), vec![11..14, 18..23]),
+        ])
+    );
+
+    // Patterns that don't compile for a language yield no matches instead of an error.
+    assert_eq!(
+        search(
+            &project,
+            SearchQuery::structural("(no_such_node) @fn", false, Vec::new(), Vec::new()).unwrap(),
+            cx
+        )
+        .await
+        .unwrap(),
+        HashMap::default()
+    );
+
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/two.rs", cx)
+        })
+        .await
+        .unwrap();
+    let query = query.with_replacement("${fn}_v2($$)".to_string());
+    buffer.update(cx, |buffer, _| {
+        let snapshot = buffer.snapshot();
+        assert_eq!(
+            query.structural_replacement_for(&snapshot, 18..23),
+            Some("three_v2($)".to_string())
+        );
+        assert_eq!(query.structural_replacement_for(&snapshot, 17..23), None);
+    });
 }
 
 #[gpui::test]
@@ -4334,6 +6761,153 @@ async fn test_search_multiple_worktrees_with_inclusions(cx: &mut gpui::TestAppCo
     );
 }
 
+#[gpui::test]
+async fn test_staging_and_unstaging_hunks(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "a.txt": "one\nTWO\nthree\nextra\n",
+        }),
+    )
+    .await;
+    let dot_git = Path::new("/dir/.git");
+    fs.set_head_for_repo(
+        dot_git,
+        &[(Path::new("a.txt"), "one\ntwo\nthree\nfour\n".to_string())],
+    );
+    fs.set_index_for_repo(
+        dot_git,
+        &[(Path::new("a.txt"), "one\ntwo\nthree\n".to_string())],
+    );
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/a.txt", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let assert_index_text = |expected: &str, cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            assert_eq!(
+                buffer.diff_base().map(|text| text.to_string()).as_deref(),
+                Some(expected)
+            );
+        });
+        fs.with_git_state(dot_git, false, |state| {
+            assert_eq!(state.index_contents[Path::new("a.txt")], expected);
+        });
+    };
+    let row_range = |row: u32, cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            let anchor = buffer.anchor_before(Point::new(row, 0));
+            vec![anchor..anchor]
+        })
+    };
+
+    // Only the hunks intersecting the ranges are staged, even when they only add lines.
+    let ranges = row_range(3, cx);
+    project
+        .update(cx, |project, cx| project.stage_hunks(&buffer, ranges, cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_index_text("one\ntwo\nthree\nextra\n", cx);
+
+    // The staged changes are restored to their contents in HEAD.
+    let ranges = row_range(3, cx);
+    project
+        .update(cx, |project, cx| project.unstage_hunks(&buffer, ranges, cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_index_text("one\ntwo\nthree\nfour\n", cx);
+
+    let ranges = row_range(1, cx);
+    project
+        .update(cx, |project, cx| project.stage_hunks(&buffer, ranges, cx))
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+    assert_index_text("one\nTWO\nthree\nfour\n", cx);
+}
+
+#[gpui::test]
+async fn test_changing_diff_base(cx: &mut gpui::TestAppContext) {
+    init_test(cx);
+
+    let fs = FakeFs::new(cx.executor());
+    fs.insert_tree(
+        "/dir",
+        json!({
+            ".git": {},
+            "a.txt": "one\nTWO\nthree\nfour\n",
+        }),
+    )
+    .await;
+    let dot_git = Path::new("/dir/.git");
+    fs.with_git_state(dot_git, true, |state| {
+        let path = PathBuf::from("a.txt");
+        state
+            .index_contents
+            .insert(path.clone(), "one\nTWO\nthree\n".to_string());
+        state
+            .head_contents
+            .insert(path.clone(), "one\ntwo\nthree\n".to_string());
+        state.merge_bases.insert(
+            ("HEAD".to_string(), "main".to_string()),
+            "abc123".to_string(),
+        );
+        state.revision_contents.insert(
+            ("abc123".to_string(), path),
+            "zero\none\nTWO\nthree\nfour\n".to_string(),
+        );
+    });
+
+    let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+    let buffer = project
+        .update(cx, |project, cx| {
+            project.open_local_buffer("/dir/a.txt", cx)
+        })
+        .await
+        .unwrap();
+    cx.executor().run_until_parked();
+
+    let hunk_rows = |cx: &mut gpui::TestAppContext| {
+        buffer.read_with(cx, |buffer, _| {
+            buffer
+                .snapshot()
+                .git_diff_hunks_in_row_range(0..u32::MAX)
+                .map(|hunk| hunk.associated_range)
+                .collect::<Vec<_>>()
+        })
+    };
+    assert_eq!(hunk_rows(cx), vec![3..4]);
+
+    fs.with_git_state(dot_git, true, |state| {
+        state.diff_base = git::repository::DiffBase::Head;
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(hunk_rows(cx), vec![1..2, 3..4]);
+
+    fs.with_git_state(dot_git, true, |state| {
+        state.diff_base = git::repository::DiffBase::MergeBase("main".to_string());
+    });
+    cx.executor().run_until_parked();
+    assert_eq!(hunk_rows(cx), vec![0..0]);
+
+    // Without a common ancestor, there's nothing to compare with.
+    fs.with_git_state(dot_git, true, |state| state.merge_bases.clear());
+    cx.executor().run_until_parked();
+    buffer.read_with(cx, |buffer, _| assert!(buffer.diff_base().is_none()));
+}
+
 #[gpui::test]
 async fn test_search_in_gitignored_dirs(cx: &mut gpui::TestAppContext) {
     init_test(cx);
@@ -4440,6 +7014,36 @@ fn test_glob_literal_prefix() {
     assert_eq!(glob_literal_prefix("foo/bar/baz.js"), "foo/bar/baz.js");
 }
 
+#[test]
+fn test_task_template_proto_round_trip() {
+    let template = TaskTemplate {
+        label: "watch".to_string(),
+        command: "cargo".to_string(),
+        args: vec!["watch".to_string()],
+        env: HashMap::from_iter([("RUST_LOG".to_string(), "info".to_string())]),
+        cwd: Some("$ZED_WORKTREE_ROOT".to_string()),
+        use_new_terminal: true,
+        allow_concurrent_runs: true,
+        dedicated_terminal: true,
+        reveal: task::RevealStrategy::Never,
+        tags: vec!["rust-test".to_string()],
+        ..TaskTemplate::default()
+    };
+    assert_eq!(
+        deserialize_task_template(serialize_task_template(template.clone())),
+        template
+    );
+
+    let template = TaskTemplate {
+        dedicated_terminal: false,
+        ..template
+    };
+    assert_eq!(
+        deserialize_task_template(serialize_task_template(template.clone())),
+        template
+    );
+}
+
 #[gpui::test]
 async fn test_create_entry(cx: &mut gpui::TestAppContext) {
     init_test(cx);
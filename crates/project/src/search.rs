@@ -2,7 +2,7 @@ use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use anyhow::{Context, Result};
 use client::proto;
 use itertools::Itertools;
-use language::{char_kind, BufferSnapshot};
+use language::{char_kind, BufferSnapshot, StructuralMatch};
 use regex::{Captures, Regex, RegexBuilder};
 use smol::future::yield_now;
 use std::{
@@ -15,6 +15,7 @@ use std::{
 use util::paths::PathMatcher;
 
 static TEXT_REPLACEMENT_SPECIAL_CHARACTERS_REGEX: OnceLock<Regex> = OnceLock::new();
+static STRUCTURAL_REPLACEMENT_CAPTURE_REGEX: OnceLock<Regex> = OnceLock::new();
 
 #[derive(Clone, Debug)]
 pub struct SearchInputs {
@@ -39,21 +40,32 @@ pub enum SearchQuery {
     Text {
         search: Arc<AhoCorasick>,
         replacement: Option<String>,
+        preserve_case: bool,
         whole_word: bool,
         case_sensitive: bool,
         include_ignored: bool,
         inner: SearchInputs,
     },
 
+    /// Replacements can refer to the regex's capture groups as `$1`, `${name}` or `\1`.
     Regex {
         regex: Regex,
         replacement: Option<String>,
+        preserve_case: bool,
         multiline: bool,
         whole_word: bool,
         case_sensitive: bool,
         include_ignored: bool,
         inner: SearchInputs,
     },
+
+    /// A tree-sitter query, matched against the syntax trees of the searched buffers.
+    /// Replacements can refer to the query's captures as `$name` or `${name}`.
+    Structural {
+        replacement: Option<String>,
+        include_ignored: bool,
+        inner: SearchInputs,
+    },
 }
 
 impl SearchQuery {
@@ -77,6 +89,7 @@ impl SearchQuery {
         Ok(Self::Text {
             search: Arc::new(search),
             replacement: None,
+            preserve_case: false,
             whole_word,
             case_sensitive,
             include_ignored,
@@ -102,7 +115,7 @@ impl SearchQuery {
             query = word_query
         }
 
-        let multiline = query.contains('\n') || query.contains("\\n");
+        let multiline = can_match_newline(&query);
         let regex = RegexBuilder::new(&query)
             .case_insensitive(!case_sensitive)
             .multi_line(multiline)
@@ -115,6 +128,7 @@ impl SearchQuery {
         Ok(Self::Regex {
             regex,
             replacement: None,
+            preserve_case: false,
             multiline,
             whole_word,
             case_sensitive,
@@ -123,8 +137,36 @@ impl SearchQuery {
         })
     }
 
+    /// Creates a structural query from the source of a tree-sitter query. The query is compiled
+    /// separately for the language of every searched buffer, so it cannot be validated upfront.
+    pub fn structural(
+        query: impl ToString,
+        include_ignored: bool,
+        files_to_include: Vec<PathMatcher>,
+        files_to_exclude: Vec<PathMatcher>,
+    ) -> Result<Self> {
+        let query = query.to_string();
+        let inner = SearchInputs {
+            query: query.into(),
+            files_to_exclude,
+            files_to_include,
+        };
+        Ok(Self::Structural {
+            replacement: None,
+            include_ignored,
+            inner,
+        })
+    }
+
     pub fn from_proto(message: proto::SearchProject) -> Result<Self> {
-        if message.regex {
+        if message.structural {
+            Self::structural(
+                message.query,
+                message.include_ignored,
+                deserialize_path_matches(&message.files_to_include)?,
+                deserialize_path_matches(&message.files_to_exclude)?,
+            )
+        } else if message.regex {
             Self::regex(
                 message.query,
                 message.whole_word,
@@ -153,17 +195,38 @@ impl SearchQuery {
             | Self::Regex {
                 ref mut replacement,
                 ..
+            }
+            | Self::Structural {
+                ref mut replacement,
+                ..
             } => {
                 *replacement = Some(new_replacement);
                 self
             }
         }
     }
+    /// Makes the replacements follow the case of the text they replace, see [`preserve_case`].
+    /// Structural replacements are always inserted as they are.
+    pub fn with_preserve_case(mut self, new_preserve_case: bool) -> Self {
+        match self {
+            Self::Text {
+                ref mut preserve_case,
+                ..
+            }
+            | Self::Regex {
+                ref mut preserve_case,
+                ..
+            } => *preserve_case = new_preserve_case,
+            Self::Structural { .. } => {}
+        }
+        self
+    }
     pub fn to_proto(&self, project_id: u64) -> proto::SearchProject {
         proto::SearchProject {
             project_id,
             query: self.as_str().to_string(),
             regex: self.is_regex(),
+            structural: self.is_structural(),
             whole_word: self.whole_word(),
             case_sensitive: self.case_sensitive(),
             include_ignored: self.include_ignored(),
@@ -215,42 +278,102 @@ impl SearchQuery {
                     Ok(false)
                 }
             }
+            // Matches can only be found after parsing the file.
+            Self::Structural { .. } => Ok(true),
         }
     }
     /// Returns the replacement text for this `SearchQuery`.
     pub fn replacement(&self) -> Option<&str> {
         match self {
-            SearchQuery::Text { replacement, .. } | SearchQuery::Regex { replacement, .. } => {
-                replacement.as_deref()
-            }
+            SearchQuery::Text { replacement, .. }
+            | SearchQuery::Regex { replacement, .. }
+            | SearchQuery::Structural { replacement, .. } => replacement.as_deref(),
         }
     }
     /// Replaces search hits if replacement is set. `text` is assumed to be a string that matches this `SearchQuery` exactly, without any leftovers on either side.
     pub fn replacement_for<'a>(&self, text: &'a str) -> Option<Cow<'a, str>> {
         match self {
-            SearchQuery::Text { replacement, .. } => replacement.clone().map(Cow::from),
+            SearchQuery::Text {
+                replacement,
+                preserve_case: case_preserved,
+                ..
+            } => replacement.as_ref().map(|replacement| {
+                if *case_preserved {
+                    Cow::from(preserve_case(text, replacement))
+                } else {
+                    Cow::from(replacement.clone())
+                }
+            }),
             SearchQuery::Regex {
-                regex, replacement, ..
+                regex,
+                replacement,
+                preserve_case: case_preserved,
+                ..
             } => {
                 if let Some(replacement) = replacement {
                     let replacement = TEXT_REPLACEMENT_SPECIAL_CHARACTERS_REGEX
-                        .get_or_init(|| Regex::new(r"\\\\|\\n|\\t").unwrap())
+                        .get_or_init(|| Regex::new(r"\\\\|\\n|\\t|\\(\d+)").unwrap())
                         .replace_all(replacement, |c: &Captures| {
+                            if let Some(group) = c.get(1) {
+                                return format!("${{{}}}", group.as_str());
+                            }
                             match c.get(0).unwrap().as_str() {
                                 r"\\" => "\\",
                                 r"\n" => "\n",
                                 r"\t" => "\t",
                                 x => unreachable!("Unexpected escape sequence: {}", x),
                             }
+                            .to_string()
                         });
-                    Some(regex.replace(text, replacement))
+                    let replaced = regex.replace(text, replacement);
+                    if *case_preserved {
+                        Some(Cow::from(preserve_case(text, &replaced)))
+                    } else {
+                        Some(replaced)
+                    }
                 } else {
                     None
                 }
             }
+            // Captures are needed to expand structural replacements, see `structural_replacement_for`.
+            SearchQuery::Structural { .. } => None,
         }
     }
 
+    /// Returns the range of a capture group within `text`, which is assumed to match this regex
+    /// `SearchQuery` exactly, like in `replacement_for`. The group is given by its index or name.
+    pub fn capture_range(&self, text: &str, group: &str) -> Option<Range<usize>> {
+        let SearchQuery::Regex { regex, .. } = self else {
+            return None;
+        };
+        let captures = regex.captures(text)?;
+        let capture = match group.parse::<usize>() {
+            Ok(index) => captures.get(index),
+            Err(_) => captures.name(group),
+        };
+        Some(capture?.range())
+    }
+
+    /// Replaces a structural search hit, expanding the captures of the match found at `range` in `buffer`.
+    pub fn structural_replacement_for(
+        &self,
+        buffer: &BufferSnapshot,
+        range: Range<usize>,
+    ) -> Option<String> {
+        let SearchQuery::Structural {
+            replacement: Some(replacement),
+            ..
+        } = self
+        else {
+            return None;
+        };
+        let mat = buffer
+            .structural_matches(self.as_str(), range.clone())
+            .into_iter()
+            .find(|mat| mat.range == range)?;
+        Some(expand_structural_replacement(replacement, buffer, &mat))
+    }
+
     pub async fn search(
         &self,
         buffer: &BufferSnapshot,
@@ -262,6 +385,16 @@ impl SearchQuery {
             return Default::default();
         }
 
+        if let Self::Structural { .. } = self {
+            let range = subrange.unwrap_or(0..buffer.len());
+            let range_offset = range.start;
+            return buffer
+                .structural_matches(self.as_str(), range)
+                .into_iter()
+                .map(|mat| mat.range.start - range_offset..mat.range.end - range_offset)
+                .collect();
+        }
+
         let range_offset = subrange.as_ref().map(|r| r.start).unwrap_or(0);
         let rope = if let Some(range) = subrange {
             buffer.as_rope().slice(range)
@@ -335,6 +468,7 @@ impl SearchQuery {
                     }
                 }
             }
+            Self::Structural { .. } => unreachable!("structural queries are handled above"),
         }
 
         matches
@@ -352,6 +486,7 @@ impl SearchQuery {
         match self {
             Self::Text { whole_word, .. } => *whole_word,
             Self::Regex { whole_word, .. } => *whole_word,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -359,6 +494,7 @@ impl SearchQuery {
         match self {
             Self::Text { case_sensitive, .. } => *case_sensitive,
             Self::Regex { case_sensitive, .. } => *case_sensitive,
+            Self::Structural { .. } => true,
         }
     }
 
@@ -370,6 +506,17 @@ impl SearchQuery {
             Self::Regex {
                 include_ignored, ..
             } => *include_ignored,
+            Self::Structural {
+                include_ignored, ..
+            } => *include_ignored,
+        }
+    }
+
+    pub fn preserve_case(&self) -> bool {
+        match self {
+            Self::Text { preserve_case, .. } => *preserve_case,
+            Self::Regex { preserve_case, .. } => *preserve_case,
+            Self::Structural { .. } => false,
         }
     }
 
@@ -377,6 +524,10 @@ impl SearchQuery {
         matches!(self, Self::Regex { .. })
     }
 
+    pub fn is_structural(&self) -> bool {
+        matches!(self, Self::Structural { .. })
+    }
+
     pub fn files_to_include(&self) -> &[PathMatcher] {
         self.as_inner().files_to_include()
     }
@@ -413,11 +564,116 @@ impl SearchQuery {
     }
     pub fn as_inner(&self) -> &SearchInputs {
         match self {
-            Self::Regex { inner, .. } | Self::Text { inner, .. } => inner,
+            Self::Regex { inner, .. }
+            | Self::Text { inner, .. }
+            | Self::Structural { inner, .. } => inner,
+        }
+    }
+}
+
+/// Expands `$name` and `${name}` in `replacement` to the text of the corresponding capture,
+/// with `$$` standing for a literal `$`. Unknown captures expand to an empty string.
+fn expand_structural_replacement(
+    replacement: &str,
+    buffer: &BufferSnapshot,
+    mat: &StructuralMatch,
+) -> String {
+    STRUCTURAL_REPLACEMENT_CAPTURE_REGEX
+        .get_or_init(|| Regex::new(r"\$\$|\$\{(\w+)\}|\$(\w+)").unwrap())
+        .replace_all(replacement, |c: &Captures| {
+            let Some(name) = c.get(1).or_else(|| c.get(2)) else {
+                return "$".to_string();
+            };
+            mat.captures
+                .iter()
+                .find(|(capture_name, _)| capture_name.as_ref() == name.as_str())
+                .map(|(_, range)| buffer.text_for_range(range.clone()).collect::<String>())
+                .unwrap_or_default()
+        })
+        .into_owned()
+}
+
+/// Adapts the case of `replacement` to the one of the `matched` text, so that replacing
+/// `fooBar`, `FooBar`, `FOO_BAR` and `foo-bar` with `bazQux` gives `bazQux`, `BazQux`,
+/// `BAZ_QUX` and `baz-qux`.
+fn preserve_case(matched: &str, replacement: &str) -> String {
+    let has_lowercase = matched.chars().any(char::is_lowercase);
+    let has_uppercase = matched.chars().any(char::is_uppercase);
+    if !has_lowercase && !has_uppercase {
+        return replacement.to_string();
+    }
+
+    if let Some(separator) = matched.chars().find(|c| *c == '_' || *c == '-') {
+        return replacement_words(replacement)
+            .into_iter()
+            .map(|word| {
+                if !has_lowercase {
+                    word.to_uppercase()
+                } else if !has_uppercase {
+                    word.to_lowercase()
+                } else {
+                    capitalize(&word)
+                }
+            })
+            .join(&separator.to_string());
+    }
+
+    let letter_count = matched.chars().filter(|c| c.is_alphabetic()).count();
+    if !has_lowercase && letter_count > 1 {
+        replacement.to_uppercase()
+    } else if !has_uppercase {
+        replacement.to_lowercase()
+    } else {
+        let first_is_uppercase = matched
+            .chars()
+            .find(|c| c.is_alphabetic())
+            .map_or(false, char::is_uppercase);
+        let mut chars = replacement.chars();
+        match chars.next() {
+            Some(first) if first_is_uppercase => first.to_uppercase().chain(chars).collect(),
+            Some(first) => first.to_lowercase().chain(chars).collect(),
+            None => String::new(),
         }
     }
 }
 
+/// Splits a replacement into words, at separators and at the start of camel case humps.
+fn replacement_words(replacement: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut previous = None;
+    for c in replacement.chars() {
+        if c == '_' || c == '-' || c.is_whitespace() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+        } else {
+            let hump = c.is_uppercase()
+                && previous.map_or(false, |p: char| p.is_lowercase() || p.is_ascii_digit());
+            if hump && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        previous = Some(c);
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first
+            .to_uppercase()
+            .chain(chars.flat_map(char::to_lowercase))
+            .collect(),
+        None => String::new(),
+    }
+}
+
 fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<Vec<PathMatcher>> {
     glob_set
         .split(',')
@@ -430,6 +686,25 @@ fn deserialize_path_matches(glob_set: &str) -> anyhow::Result<Vec<PathMatcher>>
         .collect()
 }
 
+/// Whether a regex asks to match across lines, with an explicit newline or the `s` flag, in
+/// which case files are searched as a whole rather than line by line. Classes like `\s` or
+/// `[^a]` don't count, so that common single-line patterns keep matching line by line.
+fn can_match_newline(regex: &str) -> bool {
+    if regex.contains('\n') || regex.contains("\\n") {
+        return true;
+    }
+
+    // The `s` flag makes `.` match newlines, as in `(?s)` or `(?is:...)`.
+    regex.match_indices("(?").any(|(ix, _)| {
+        regex[ix + 2..]
+            .split([')', ':'])
+            .next()
+            .map_or(false, |flags| {
+                flags.split('-').next().unwrap_or("").contains('s')
+            })
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -477,4 +752,67 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_can_match_newline() {
+        for multiline in [
+            "foo\nbar",
+            r"foo\nbar",
+            r"(?s)impl.*for",
+            r"(?is:struct.+\})",
+        ] {
+            assert!(can_match_newline(multiline), "{multiline:?}");
+        }
+        for single_line in [
+            r"fn \w+\(",
+            r"\S+=\d+",
+            r"(?i)todo",
+            r"(?-s:a.b)",
+            r"foo\.bar",
+            r"fn foo\([^)]*\)\s*\{",
+            r"a[[:space:]]+b",
+            r"\W\D\p{L}\P{L}",
+        ] {
+            assert!(!can_match_newline(single_line), "{single_line:?}");
+        }
+    }
+
+    #[test]
+    fn test_replacement_capture_references() {
+        let query =
+            SearchQuery::regex("(\\w+)=(?P<value>\\d+)", false, true, false, vec![], vec![])
+                .unwrap()
+                .with_replacement(r"\2 <- $1 (${value}, \\1)".to_string());
+        assert_eq!(
+            query.replacement_for("count=42").unwrap(),
+            r"42 <- count (42, \1)"
+        );
+    }
+
+    #[test]
+    fn test_replacement_preserve_case() {
+        let query = SearchQuery::regex("foo[_-]?bar", false, false, false, vec![], vec![])
+            .unwrap()
+            .with_replacement("bazQux".to_string())
+            .with_preserve_case(true);
+        for (matched, expected) in [
+            ("fooBar", "bazQux"),
+            ("FooBar", "BazQux"),
+            ("foobar", "bazqux"),
+            ("FOOBAR", "BAZQUX"),
+            ("FOO_BAR", "BAZ_QUX"),
+            ("foo_bar", "baz_qux"),
+            ("Foo-Bar", "Baz-Qux"),
+        ] {
+            assert_eq!(query.replacement_for(matched).unwrap(), expected);
+        }
+
+        let query = SearchQuery::text("foo", false, false, false, vec![], vec![])
+            .unwrap()
+            .with_replacement("bar".to_string());
+        assert_eq!(query.replacement_for("FOO").unwrap(), "bar");
+        let query = query.with_preserve_case(true);
+        assert_eq!(query.replacement_for("FOO").unwrap(), "BAR");
+        assert_eq!(query.replacement_for("Foo").unwrap(), "Bar");
+    }
 }
@@ -84,6 +84,11 @@ impl SearchHistory {
         Some(&self.history[next_index])
     }
 
+    /// The queries in the history, from the oldest to the newest.
+    pub fn queries(&self) -> &[String] {
+        &self.history
+    }
+
     pub fn current(&self, cursor: &SearchHistoryCursor) -> Option<&str> {
         cursor
             .selection
@@ -0,0 +1,269 @@
+use crate::search::SearchQuery;
+use collections::HashMap;
+use fs::Fs;
+use futures::{channel::mpsc, StreamExt};
+use gpui::{AppContext, Task};
+use parking_lot::RwLock;
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+use worktree::{Entry, PathChange, ProjectEntryId, Snapshot, WorktreeId};
+
+/// The trigrams, that is the sequences of three bytes, contained in the files of a worktree.
+///
+/// Literal searches look up the trigrams of their query to skip reading the files that can't
+/// contain it. ASCII letters are lowercased before the trigrams are taken, so that the same index
+/// serves case-sensitive and case-insensitive searches.
+#[derive(Default)]
+pub struct SearchIndex {
+    files: HashMap<Arc<Path>, IndexedFile>,
+}
+
+struct IndexedFile {
+    /// The modification time of the file when its contents were indexed.
+    mtime: Option<SystemTime>,
+    /// The file's distinct trigrams, sorted.
+    trigrams: Box<[u32]>,
+}
+
+impl SearchIndex {
+    /// Records the trigrams of a file, as returned by [`trigrams`], replacing the ones it was
+    /// previously indexed with.
+    pub fn insert(&mut self, path: Arc<Path>, mtime: Option<SystemTime>, trigrams: Vec<u32>) {
+        self.files.insert(
+            path,
+            IndexedFile {
+                mtime,
+                trigrams: trigrams.into(),
+            },
+        );
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.files.remove(path);
+    }
+
+    /// The number of indexed files.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Whether the file at the given path may contain all of the given trigrams.
+    ///
+    /// Files that weren't indexed at the given modification time always may, as their contents
+    /// changed since, or they weren't indexed at all.
+    pub fn may_contain(&self, path: &Path, mtime: Option<SystemTime>, trigrams: &[u32]) -> bool {
+        match self.files.get(path) {
+            Some(file) if file.mtime.is_some() && file.mtime == mtime => trigrams
+                .iter()
+                .all(|trigram| file.trigrams.binary_search(trigram).is_ok()),
+            _ => true,
+        }
+    }
+}
+
+/// The distinct trigrams of the given bytes, sorted, with ASCII letters lowercased.
+pub fn trigrams(bytes: &[u8]) -> Vec<u32> {
+    let mut trigrams = bytes
+        .windows(3)
+        .map(|window| {
+            u32::from_le_bytes([
+                window[0].to_ascii_lowercase(),
+                window[1].to_ascii_lowercase(),
+                window[2].to_ascii_lowercase(),
+                0,
+            ])
+        })
+        .collect::<Vec<_>>();
+    trigrams.sort_unstable();
+    trigrams.dedup();
+    trigrams
+}
+
+/// Keeps the search index of a local worktree up to date with its files on disk, reading the
+/// files to index in the background.
+pub(crate) struct WorktreeSearchIndex {
+    index: Arc<RwLock<SearchIndex>>,
+    updates_tx: mpsc::UnboundedSender<IndexUpdate>,
+    _maintain_index: Task<()>,
+}
+
+enum IndexUpdate {
+    Index {
+        path: Arc<Path>,
+        abs_path: PathBuf,
+        mtime: Option<SystemTime>,
+    },
+    Remove(Arc<Path>),
+}
+
+impl WorktreeSearchIndex {
+    /// Starts indexing the files of the given worktree snapshot. Files larger than the given
+    /// size, in bytes, are left out of the index and are always read when searching.
+    pub fn new(
+        snapshot: &Snapshot,
+        fs: Arc<dyn Fs>,
+        max_file_size: usize,
+        cx: &AppContext,
+    ) -> Self {
+        let index = Arc::new(RwLock::new(SearchIndex::default()));
+        let (updates_tx, mut updates_rx) = mpsc::unbounded();
+        let _maintain_index = cx.background_executor().spawn({
+            let index = index.clone();
+            async move {
+                while let Some(update) = updates_rx.next().await {
+                    match update {
+                        IndexUpdate::Index {
+                            path,
+                            abs_path,
+                            mtime,
+                        } => match load_contents(fs.as_ref(), &abs_path, max_file_size).await {
+                            Some(contents) => {
+                                let trigrams = trigrams(&contents);
+                                index.write().insert(path, mtime, trigrams);
+                            }
+                            None => index.write().remove(&path),
+                        },
+                        IndexUpdate::Remove(path) => index.write().remove(&path),
+                    }
+                }
+            }
+        });
+
+        let this = Self {
+            index,
+            updates_tx,
+            _maintain_index,
+        };
+        for entry in snapshot.files(false, 0) {
+            this.index_entry(snapshot, entry);
+        }
+        this
+    }
+
+    /// Reindexes the files of the worktree that changed.
+    pub fn update(&self, snapshot: &Snapshot, changes: &[(Arc<Path>, ProjectEntryId, PathChange)]) {
+        for (path, _, change) in changes {
+            match snapshot.entry_for_path(path) {
+                Some(entry) if *change != PathChange::Removed => self.index_entry(snapshot, entry),
+                _ => {
+                    self.updates_tx
+                        .unbounded_send(IndexUpdate::Remove(path.clone()))
+                        .ok();
+                }
+            }
+        }
+    }
+
+    fn index_entry(&self, snapshot: &Snapshot, entry: &Entry) {
+        // Only the files that project search reads are indexed.
+        let update = if entry.is_file() && !entry.is_ignored && !entry.is_external {
+            IndexUpdate::Index {
+                path: entry.path.clone(),
+                abs_path: snapshot.abs_path().join(&entry.path),
+                mtime: entry.mtime,
+            }
+        } else {
+            IndexUpdate::Remove(entry.path.clone())
+        };
+        self.updates_tx.unbounded_send(update).ok();
+    }
+}
+
+/// Reads the contents of a file to index, unless it's larger than the given size.
+async fn load_contents(fs: &dyn Fs, abs_path: &Path, max_file_size: usize) -> Option<Vec<u8>> {
+    let file = fs.open_sync(abs_path).await.ok()?;
+    let mut contents = Vec::new();
+    file.take(max_file_size as u64 + 1)
+        .read_to_end(&mut contents)
+        .ok()?;
+    (contents.len() <= max_file_size).then_some(contents)
+}
+
+/// The search indices of a project's worktrees, used to rule out the files that can't contain a
+/// match of a literal query.
+#[derive(Clone)]
+pub(crate) struct SearchIndexFilter {
+    trigrams: Arc<[u32]>,
+    indices: HashMap<WorktreeId, Arc<RwLock<SearchIndex>>>,
+}
+
+impl SearchIndexFilter {
+    /// Returns `None` when the indices can't help with the query: for regular expressions and
+    /// structural queries, or for text shorter than a trigram.
+    pub fn new(
+        query: &SearchQuery,
+        indices: &HashMap<WorktreeId, WorktreeSearchIndex>,
+    ) -> Option<Self> {
+        if indices.is_empty() || !matches!(query, SearchQuery::Text { .. }) {
+            return None;
+        }
+        let trigrams = trigrams(query.as_str().as_bytes());
+        if trigrams.is_empty() {
+            return None;
+        }
+        Some(Self {
+            trigrams: trigrams.into(),
+            indices: indices
+                .iter()
+                .map(|(worktree_id, index)| (*worktree_id, index.index.clone()))
+                .collect(),
+        })
+    }
+
+    /// Whether the given file of a worktree may contain a match of the query.
+    pub fn may_match(&self, worktree_id: WorktreeId, entry: &Entry) -> bool {
+        self.indices.get(&worktree_id).map_or(true, |index| {
+            index
+                .read()
+                .may_contain(&entry.path, entry.mtime, &self.trigrams)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_search_index() {
+        let mut index = SearchIndex::default();
+        let mtime = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(1));
+        let path: Arc<Path> = Path::new("src/lib.rs").into();
+        index.insert(path.clone(), mtime, trigrams(b"const ONE: usize = 1;"));
+
+        assert!(index.may_contain(&path, mtime, &trigrams(b"ONE")));
+        assert!(index.may_contain(&path, mtime, &trigrams(b"one")));
+        assert!(index.may_contain(&path, mtime, &trigrams(b"usize = 1")));
+        assert!(!index.may_contain(&path, mtime, &trigrams(b"TWO")));
+        assert!(!index.may_contain(&path, mtime, &trigrams(b"usize = 2")));
+
+        // Files changed since they were indexed, or not indexed at all, may contain anything.
+        let later = Some(SystemTime::UNIX_EPOCH + Duration::from_secs(2));
+        assert!(index.may_contain(&path, later, &trigrams(b"TWO")));
+        assert!(index.may_contain(Path::new("src/main.rs"), mtime, &trigrams(b"TWO")));
+
+        index.insert(path.clone(), later, trigrams(b"const TWO: usize = 2;"));
+        assert!(index.may_contain(&path, later, &trigrams(b"TWO")));
+        assert!(!index.may_contain(&path, later, &trigrams(b"ONE")));
+
+        index.remove(&path);
+        assert!(index.is_empty());
+    }
+
+    #[test]
+    fn test_trigrams() {
+        assert!(trigrams(b"ab").is_empty());
+        assert_eq!(trigrams(b"abcabc").len(), 3);
+        assert_eq!(trigrams(b"ABC"), trigrams(b"abc"));
+        assert_ne!(trigrams("äbc".as_bytes()), trigrams("Äbc".as_bytes()));
+    }
+}
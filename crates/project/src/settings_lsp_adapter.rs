@@ -0,0 +1,156 @@
+use std::{
+    any::Any,
+    path::{Path, PathBuf},
+    pin::Pin,
+    sync::Arc,
+};
+
+use anyhow::{anyhow, Result};
+use async_trait::async_trait;
+use collections::HashMap;
+use futures::{Future, FutureExt};
+use gpui::AsyncAppContext;
+use language::{Language, LanguageServerName, LspAdapter, LspAdapterDelegate};
+use lsp::LanguageServerBinary;
+use serde_json::Value;
+use settings::{Settings, SettingsLocation};
+
+use crate::project_settings::{LspSettings, ProjectSettings};
+
+/// A language server defined in the `lsp` settings, with a `binary.path` and the `languages` to
+/// run it for, rather than by a language or an extension.
+pub struct SettingsLspAdapter {
+    name: LanguageServerName,
+}
+
+impl SettingsLspAdapter {
+    pub fn new(name: LanguageServerName) -> Self {
+        Self { name }
+    }
+
+    /// Whether the given settings define a language server that should run for the language.
+    pub fn is_defined_for(settings: &LspSettings, language_name: &str) -> bool {
+        let has_command = settings
+            .binary
+            .as_ref()
+            .map_or(false, |binary| binary.path.is_some());
+        has_command
+            && settings.languages.as_ref().map_or(false, |languages| {
+                languages.iter().any(|language| language == language_name)
+            })
+    }
+
+    fn settings(&self, delegate: &dyn LspAdapterDelegate, cx: &AsyncAppContext) -> LspSettings {
+        let worktree_id = delegate.worktree_id() as usize;
+        cx.update(|cx| {
+            ProjectSettings::get(
+                Some(SettingsLocation {
+                    worktree_id,
+                    path: Path::new(""),
+                }),
+                cx,
+            )
+            .lsp
+            .get(&self.name.0)
+            .cloned()
+        })
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+    }
+}
+
+#[async_trait(?Send)]
+impl LspAdapter for SettingsLspAdapter {
+    fn name(&self) -> LanguageServerName {
+        self.name.clone()
+    }
+
+    fn get_language_server_command<'a>(
+        self: Arc<Self>,
+        _: Arc<Language>,
+        _: Arc<Path>,
+        delegate: Arc<dyn LspAdapterDelegate>,
+        _: futures::lock::MutexGuard<'a, Option<LanguageServerBinary>>,
+        cx: &'a mut AsyncAppContext,
+    ) -> Pin<Box<dyn 'a + Future<Output = Result<LanguageServerBinary>>>> {
+        async move {
+            let binary = self.settings(delegate.as_ref(), cx).binary;
+            let binary = binary.unwrap_or_default();
+            let command = binary.path.ok_or_else(|| {
+                anyhow!("no `binary.path` set for language server {}", self.name.0)
+            })?;
+
+            // Bare command names are looked up in the shell's `PATH`, while relative paths are
+            // resolved against the worktree root.
+            let command = PathBuf::from(command);
+            let (path, mut env) = if command.is_absolute() {
+                (command, None)
+            } else if command.components().count() > 1 {
+                (delegate.worktree_root_path().join(command), None)
+            } else {
+                let env = delegate.shell_env().await;
+                let path = delegate.which(command.as_os_str()).await.unwrap_or(command);
+                (path, Some(env))
+            };
+            if let Some(overrides) = binary.env {
+                env.get_or_insert_with(HashMap::default).extend(overrides);
+            }
+
+            Ok(LanguageServerBinary {
+                path,
+                arguments: binary
+                    .arguments
+                    .unwrap_or_default()
+                    .iter()
+                    .map(|arg| arg.into())
+                    .collect(),
+                env,
+            })
+        }
+        .boxed_local()
+    }
+
+    async fn fetch_latest_server_version(
+        &self,
+        _: &dyn LspAdapterDelegate,
+    ) -> Result<Box<dyn 'static + Send + Any>> {
+        unreachable!("get_language_server_command is overridden")
+    }
+
+    async fn fetch_server_binary(
+        &self,
+        _: Box<dyn 'static + Send + Any>,
+        _: PathBuf,
+        _: &dyn LspAdapterDelegate,
+    ) -> Result<LanguageServerBinary> {
+        unreachable!("get_language_server_command is overridden")
+    }
+
+    async fn cached_server_binary(
+        &self,
+        _: PathBuf,
+        _: &dyn LspAdapterDelegate,
+    ) -> Option<LanguageServerBinary> {
+        unreachable!("get_language_server_command is overridden")
+    }
+
+    fn can_be_reinstalled(&self) -> bool {
+        false
+    }
+
+    async fn installation_test_binary(&self, _: PathBuf) -> Option<LanguageServerBinary> {
+        None
+    }
+
+    async fn workspace_configuration(
+        self: Arc<Self>,
+        delegate: &Arc<dyn LspAdapterDelegate>,
+        cx: &mut AsyncAppContext,
+    ) -> Result<Value> {
+        Ok(self
+            .settings(delegate.as_ref(), cx)
+            .settings
+            .unwrap_or_else(|| serde_json::json!({})))
+    }
+}
@@ -15,7 +15,9 @@ use std::{
 };
 use task::{SpawnInTerminal, TerminalWorkDir};
 use terminal::{
-    terminal_settings::{self, Shell, TerminalSettings, VenvSettingsContent},
+    output_log,
+    session::{RestoredTerminal, TerminalSession},
+    terminal_settings::{self, Shell, TerminalProfile, TerminalSettings, VenvSettingsContent},
     TaskState, TaskStatus, Terminal, TerminalBuilder,
 };
 use util::ResultExt;
@@ -69,6 +71,8 @@ impl Project {
         &mut self,
         working_directory: Option<TerminalWorkDir>,
         spawn_task: Option<SpawnInTerminal>,
+        profile: Option<TerminalProfile>,
+        restored: Option<RestoredTerminal>,
         window: AnyWindowHandle,
         cx: &mut ModelContext<Self>,
     ) -> anyhow::Result<Model<Terminal>> {
@@ -98,7 +102,29 @@ impl Project {
         let python_settings = settings.detect_venv.clone();
         let (completion_tx, completion_rx) = bounded(1);
 
-        let mut env = settings.env.clone();
+        let mut env = restored
+            .as_ref()
+            .map(|restored| restored.env.clone())
+            .unwrap_or_default();
+        env.extend(settings.env.clone());
+        if let Some(profile) = &profile {
+            env.extend(profile.env.clone());
+        }
+        let session = (is_terminal && settings.persistent_sessions.reattach).then(|| {
+            restored
+                .as_ref()
+                .and_then(|restored| restored.session_id.clone())
+                .map_or_else(TerminalSession::with_random_id, TerminalSession::new)
+        });
+        let task_log = spawn_task
+            .as_ref()
+            .filter(|_| settings.task_logs.enabled)
+            .map(|spawn_task| {
+                (
+                    output_log::task_log_path(&settings.task_logs, &spawn_task.label),
+                    settings.task_logs.format,
+                )
+            });
         // Alacritty uses parent project's working directory when no working directory is provided
         // https://github.com/alacritty/alacritty/blob/fd1a3cc79192d1d03839f0fd8c72e1f8d0fce42e/extra/man/alacritty.5.scd?plain=1#L47-L52
 
@@ -113,10 +139,19 @@ impl Project {
             Some(TerminalWorkDir::Ssh { ssh_command, path }) => {
                 log::debug!("Connecting to a remote server: {ssh_command:?}");
                 let tmp_dir = tempfile::tempdir()?;
+                let command = match (&spawn_task, &profile) {
+                    (Some(spawn_task), _) => Some((&spawn_task.command, &spawn_task.args)),
+                    (None, Some(profile)) => profile
+                        .command
+                        .as_ref()
+                        .map(|command| (command, &profile.args)),
+                    (None, None) => None,
+                };
                 let ssh_shell_result = prepare_ssh_shell(
                     &mut env,
                     tmp_dir.path(),
-                    spawn_task.as_ref(),
+                    command,
+                    spawn_task.is_none(),
                     ssh_command,
                     path.as_deref(),
                 );
@@ -161,6 +196,11 @@ impl Project {
                             args: spawn_task.args,
                         },
                     )
+                } else if let Some((program, args)) = profile
+                    .as_ref()
+                    .and_then(|profile| Some((profile.command.clone()?, profile.args.clone())))
+                {
+                    (None, Shell::WithArguments { program, args })
                 } else {
                     (None, settings.shell.clone())
                 }
@@ -178,11 +218,20 @@ impl Project {
             Some(settings.blinking),
             settings.alternate_scroll,
             settings.max_scroll_history_lines,
+            is_terminal && settings.shell_integration,
+            session,
+            restored,
             window,
             completion_tx,
         )
         .map(|builder| {
             let terminal_handle = cx.new_model(|cx| builder.subscribe(cx));
+            terminal_handle.update(cx, |terminal, _| {
+                terminal.set_profile(profile);
+                if let Some((path, format)) = task_log {
+                    terminal.start_logging(path, format).log_err();
+                }
+            });
 
             self.terminals
                 .local_handles
@@ -308,7 +357,8 @@ impl Project {
 fn prepare_ssh_shell(
     env: &mut HashMap<String, String>,
     tmp_dir: &Path,
-    spawn_task: Option<&SpawnInTerminal>,
+    command: Option<(&String, &Vec<String>)>,
+    interactive: bool,
     ssh_command: &str,
     path: Option<&str>,
 ) -> anyhow::Result<Shell> {
@@ -322,30 +372,45 @@ fn prepare_ssh_shell(
     let real_ssh = which::which("ssh")?;
     let ssh_path = tmp_dir.join("ssh");
     let mut ssh_file = File::create(&ssh_path)?;
+    let local_dev = env::var("ZED_RPC_URL").as_deref() == Ok("http://localhost:8080/rpc");
+    ssh_file.write_all(ssh_script(&real_ssh, command, interactive, path, local_dev)?.as_bytes())?;
+
+    // todo(windows)
+    #[cfg(not(target_os = "windows"))]
+    std::fs::set_permissions(ssh_path, smol::fs::unix::PermissionsExt::from_mode(0o755))?;
+
+    add_environment_path(env, tmp_dir)?;
 
-    let to_run = if let Some(spawn_task) = spawn_task {
-        Some(shlex::try_quote(&spawn_task.command)?)
+    let mut args = shlex::split(&ssh_command).unwrap_or_default();
+    let program = args.drain(0..1).next().unwrap_or("ssh".to_string());
+    Ok(Shell::WithArguments { program, args })
+}
+
+/// The script run in place of `ssh`, running the command (or a login shell) on the host in `path`.
+fn ssh_script(
+    real_ssh: &Path,
+    command: Option<(&String, &Vec<String>)>,
+    interactive: bool,
+    path: Option<&str>,
+    local_dev: bool,
+) -> anyhow::Result<String> {
+    let to_run = if let Some((command, args)) = command {
+        Some(shlex::try_quote(command)?)
             .into_iter()
-            .chain(
-                spawn_task
-                    .args
-                    .iter()
-                    .filter_map(|arg| shlex::try_quote(arg).ok()),
-            )
+            .chain(args.iter().filter_map(|arg| shlex::try_quote(arg).ok()))
             .join(" ")
     } else {
         "exec $SHELL -l".to_string()
     };
 
-    let (port_forward, local_dev_env) =
-        if env::var("ZED_RPC_URL").as_deref() == Ok("http://localhost:8080/rpc") {
-            (
-                "-R 8080:localhost:8080",
-                "export ZED_RPC_URL=http://localhost:8080/rpc;",
-            )
-        } else {
-            ("", "")
-        };
+    let (port_forward, local_dev_env) = if local_dev {
+        (
+            "-R 8080:localhost:8080",
+            "export ZED_RPC_URL=http://localhost:8080/rpc;",
+        )
+    } else {
+        ("", "")
+    };
 
     let commands = if let Some(path) = path {
         // I've found that `ssh -t dev sh -c 'cd; cd /tmp; pwd'` gives /tmp
@@ -359,24 +424,13 @@ fn prepare_ssh_shell(
     // To support things like `gh cs ssh`/`coder ssh`, we run whatever command
     // you have configured, but place our custom script on the path so that it will
     // be run instead.
-    write!(
-        &mut ssh_file,
+    Ok(format!(
         "#!/bin/sh\nexec {} \"$@\" {} {} {}",
         real_ssh.to_string_lossy(),
-        if spawn_task.is_none() { "-t" } else { "" },
+        if interactive { "-t" } else { "" },
         port_forward,
         shlex::try_quote(shell_invocation)?,
-    )?;
-
-    // todo(windows)
-    #[cfg(not(target_os = "windows"))]
-    std::fs::set_permissions(ssh_path, smol::fs::unix::PermissionsExt::from_mode(0o755))?;
-
-    add_environment_path(env, tmp_dir)?;
-
-    let mut args = shlex::split(&ssh_command).unwrap_or_default();
-    let program = args.drain(0..1).next().unwrap_or("ssh".to_string());
-    Ok(Shell::WithArguments { program, args })
+    ))
 }
 
 fn add_environment_path(env: &mut HashMap<String, String>, new_path: &Path) -> anyhow::Result<()> {
@@ -431,4 +485,54 @@ mod tests {
         }
         assert_eq!(env.get("OTHER").unwrap(), "aaa");
     }
+
+    #[test]
+    fn test_ssh_script() {
+        #[track_caller]
+        fn ssh_invocation(script: &str) -> (Vec<String>, Vec<String>) {
+            let mut lines = script.lines();
+            assert_eq!(lines.next(), Some("#!/bin/sh"));
+            let mut args = shlex::split(lines.next().unwrap()).unwrap();
+            let commands = shlex::split(&args.pop().unwrap()).unwrap();
+            (args, commands)
+        }
+
+        let real_ssh = std::path::Path::new("/usr/bin/ssh");
+        let script = super::ssh_script(real_ssh, None, true, Some("/srv/app"), false).unwrap();
+        assert_eq!(
+            ssh_invocation(&script),
+            (
+                vec!["exec", "/usr/bin/ssh", "$@", "-t"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                vec!["sh", "-c", "cd /srv/app;  exec $SHELL -l"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+            )
+        );
+
+        let command = "tail".to_string();
+        let args = vec!["-f".to_string(), "/var/log/app.log".to_string()];
+        let script =
+            super::ssh_script(real_ssh, Some((&command, &args)), false, None, true).unwrap();
+        assert_eq!(
+            ssh_invocation(&script),
+            (
+                vec!["exec", "/usr/bin/ssh", "$@", "-R", "8080:localhost:8080"]
+                    .into_iter()
+                    .map(String::from)
+                    .collect(),
+                vec![
+                    "sh",
+                    "-c",
+                    "cd; export ZED_RPC_URL=http://localhost:8080/rpc; tail -f /var/log/app.log",
+                ]
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            )
+        );
+    }
 }
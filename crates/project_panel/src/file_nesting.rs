@@ -0,0 +1,200 @@
+use std::ffi::OsStr;
+
+use collections::{BTreeMap, HashMap};
+use project::{Entry, ProjectEntryId};
+
+use crate::NEW_ENTRY_ID;
+
+/// Moves the files nested under another file of their directory right after it, leaving them
+/// out when that file's group is collapsed. Expects the entries in the order they're shown in,
+/// and records the parent of every nested file in `nested_entry_parents`.
+pub(crate) fn nest_files(
+    entries: Vec<Entry>,
+    patterns: &BTreeMap<String, Vec<String>>,
+    expanded_entry_ids: &[ProjectEntryId],
+    nested_entry_parents: &mut HashMap<ProjectEntryId, ProjectEntryId>,
+) -> Vec<Entry> {
+    let mut nested_entries = Vec::with_capacity(entries.len());
+    let mut entries = entries.into_iter().peekable();
+    while let Some(entry) = entries.next() {
+        if !entry.is_file() {
+            nested_entries.push(entry);
+            continue;
+        }
+
+        // The files of a directory come right after each other, following its subdirectories.
+        let mut files = vec![entry];
+        while let Some(file) =
+            entries.next_if(|next| next.is_file() && next.path.parent() == files[0].path.parent())
+        {
+            files.push(file);
+        }
+
+        let names = files
+            .iter()
+            .map(|file| {
+                if file.id == NEW_ENTRY_ID {
+                    ""
+                } else {
+                    file.path.file_name().and_then(OsStr::to_str).unwrap_or("")
+                }
+            })
+            .collect::<Vec<_>>();
+        let parents = nesting_parents(&names, patterns);
+        let mut children = vec![Vec::new(); files.len()];
+        for (ix, parent_ix) in parents.iter().enumerate() {
+            if let Some(parent_ix) = parent_ix {
+                children[*parent_ix].push(ix);
+            }
+        }
+
+        for (ix, file) in files.iter().enumerate() {
+            if parents[ix].is_some() {
+                continue;
+            }
+            nested_entries.push(file.clone());
+            let is_expanded = expanded_entry_ids.binary_search(&file.id).is_ok();
+            for &child_ix in &children[ix] {
+                nested_entry_parents.insert(files[child_ix].id, file.id);
+                if is_expanded {
+                    nested_entries.push(files[child_ix].clone());
+                }
+            }
+        }
+    }
+    nested_entries
+}
+
+/// Finds the file each of the given sibling files is nested under, if any. Nesting is a single
+/// level deep: the children of a nested file are nested under its own parent instead.
+fn nesting_parents(names: &[&str], patterns: &BTreeMap<String, Vec<String>>) -> Vec<Option<usize>> {
+    let indices_by_name = names
+        .iter()
+        .enumerate()
+        .map(|(ix, name)| (*name, ix))
+        .collect::<HashMap<_, _>>();
+    let mut parents = vec![None; names.len()];
+    for (parent_ix, parent_name) in names.iter().enumerate() {
+        for (parent_pattern, child_patterns) in patterns {
+            let Some(capture) = capture(parent_pattern, parent_name) else {
+                continue;
+            };
+            for child_pattern in child_patterns {
+                let child_pattern = child_pattern.replace("${capture}", capture);
+                let mut nest = |child_ix: usize| {
+                    if child_ix != parent_ix
+                        && !names[child_ix].is_empty()
+                        && parents[child_ix].is_none()
+                    {
+                        parents[child_ix] = Some(parent_ix);
+                    }
+                };
+                if child_pattern.contains('*') {
+                    for (child_ix, child_name) in names.iter().enumerate() {
+                        if wildcard_match(&child_pattern, child_name) {
+                            nest(child_ix);
+                        }
+                    }
+                } else if let Some(&child_ix) = indices_by_name.get(child_pattern.as_str()) {
+                    nest(child_ix);
+                }
+            }
+        }
+    }
+
+    (0..names.len())
+        .map(|ix| {
+            let mut parent_ix = parents[ix]?;
+            // Bounded, in case the patterns nest files under each other.
+            for _ in 0..names.len() {
+                match parents[parent_ix] {
+                    Some(grandparent_ix) if grandparent_ix != ix => parent_ix = grandparent_ix,
+                    Some(_) => return None,
+                    None => return Some(parent_ix),
+                }
+            }
+            None
+        })
+        .collect()
+}
+
+/// Matches a parent's name against a pattern with at most one `*`, returning what the `*`
+/// matched, or the empty string for patterns without one.
+fn capture<'a>(pattern: &str, name: &'a str) -> Option<&'a str> {
+    if name.is_empty() {
+        return None;
+    }
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => name
+            .strip_prefix(prefix)?
+            .strip_suffix(suffix)
+            .filter(|capture| !capture.is_empty()),
+        None => (pattern == name).then_some(""),
+    }
+}
+
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let Some(prefix) = parts.next() else {
+        return false;
+    };
+    let Some(mut rest) = name.strip_prefix(prefix) else {
+        return false;
+    };
+    let mut parts = parts.collect::<Vec<_>>();
+    let Some(suffix) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(ix) => rest = &rest[ix + part.len()..],
+            None => return false,
+        }
+    }
+    rest.ends_with(suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nesting_parents() {
+        let patterns = BTreeMap::from_iter([
+            (
+                "*.ts".to_string(),
+                vec!["${capture}.js".to_string(), "${capture}.d.ts".to_string()],
+            ),
+            ("*.js".to_string(), vec!["${capture}.js.map".to_string()]),
+            ("*.dart".to_string(), vec!["${capture}.*.dart".to_string()]),
+            ("Cargo.toml".to_string(), vec!["Cargo.lock".to_string()]),
+        ]);
+        let names = [
+            "Cargo.lock",
+            "Cargo.toml",
+            "foo.d.ts",
+            "foo.js",
+            "foo.js.map",
+            "foo.ts",
+            "bar.js",
+            "model.dart",
+            "model.g.dart",
+            "model.freezed.dart",
+        ];
+        assert_eq!(
+            nesting_parents(&names, &patterns),
+            [
+                Some(1),
+                None,
+                Some(5),
+                Some(5),
+                Some(5),
+                None,
+                None,
+                None,
+                Some(7),
+                Some(7),
+            ]
+        );
+    }
+}
@@ -0,0 +1,230 @@
+use collections::HashSet;
+use file_icons::FileIcons;
+use gpui::{relative, AnyElement, AppContext, Subscription, View, ViewContext};
+use settings::Settings;
+use ui::{prelude::*, Icon, ListHeader, ListItem, ListSubHeader, Tooltip};
+use util::ResultExt;
+use workspace::{
+    item::{ItemHandle, TabContentParams},
+    render_item_indicator, DraggedTab, Pane, SaveIntent, Workspace,
+};
+
+use crate::{ProjectPanel, ProjectPanelSettings};
+
+impl ProjectPanel {
+    /// Re-renders the panel when the items of the given pane change, while it lists them.
+    pub(crate) fn observe_pane(pane: &View<Pane>, cx: &mut ViewContext<Self>) -> Subscription {
+        cx.observe(pane, |_, _, cx| {
+            if ProjectPanelSettings::get_global(cx).open_editors {
+                cx.notify();
+            }
+        })
+    }
+
+    /// Drops the observations of the panes removed from the workspace.
+    pub(crate) fn forget_removed_panes(&mut self, cx: &mut ViewContext<Self>) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let pane_ids = workspace
+            .read(cx)
+            .panes()
+            .iter()
+            .map(|pane| pane.entity_id())
+            .collect::<HashSet<_>>();
+        self.pane_observations
+            .retain(|pane_id, _| pane_ids.contains(pane_id));
+        if ProjectPanelSettings::get_global(cx).open_editors {
+            cx.notify();
+        }
+    }
+
+    /// The workspace's panes with items open in them, listed in the Open Editors section.
+    pub(crate) fn open_editor_panes(&self, cx: &AppContext) -> Vec<View<Pane>> {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return Vec::new();
+        };
+        workspace
+            .read(cx)
+            .panes()
+            .iter()
+            .filter(|pane| pane.read(cx).items_len() > 0)
+            .cloned()
+            .collect()
+    }
+
+    /// Lists the items open in the workspace's panes, grouped by pane, above the file tree.
+    pub(crate) fn render_open_editors(&self, cx: &mut ViewContext<Self>) -> Option<AnyElement> {
+        if !ProjectPanelSettings::get_global(cx).open_editors {
+            return None;
+        }
+        let workspace = self.workspace.upgrade()?;
+        let panes = self.open_editor_panes(cx);
+
+        let is_collapsed = self.open_editors_collapsed;
+        let header = ListHeader::new("Open Editors")
+            .toggle(!is_collapsed)
+            .on_toggle(cx.listener(|this, _, cx| {
+                this.open_editors_collapsed = !this.open_editors_collapsed;
+                cx.notify();
+            }))
+            .end_hover_slot(
+                IconButton::new("close-all-editors", IconName::Close)
+                    .icon_size(IconSize::Small)
+                    .tooltip(|cx| Tooltip::text("Close All Editors", cx))
+                    .on_click(cx.listener({
+                        let panes = panes.clone();
+                        move |_, _, cx| {
+                            for pane in &panes {
+                                pane.update(cx, |pane, cx| {
+                                    if let Some(task) = pane.close_all_items(
+                                        &workspace::CloseAllItems { save_intent: None },
+                                        cx,
+                                    ) {
+                                        task.detach_and_log_err(cx);
+                                    }
+                                });
+                            }
+                        }
+                    })),
+            );
+
+        let mut section = v_flex()
+            .id("open-editors")
+            .flex_none()
+            .max_h(relative(0.4))
+            .overflow_y_scroll()
+            .border_b_1()
+            .border_color(cx.theme().colors().border_variant)
+            .child(header);
+        if !is_collapsed {
+            let show_groups = panes.len() > 1;
+            for (group_ix, pane) in panes.iter().enumerate() {
+                if show_groups {
+                    section = section.child(ListSubHeader::new(format!("Group {}", group_ix + 1)));
+                }
+                let pane_ref = pane.read(cx);
+                let active_item_id = pane_ref.active_item().map(|item| item.item_id());
+                let items = pane_ref
+                    .items()
+                    .map(|item| item.boxed_clone())
+                    .collect::<Vec<_>>();
+                for (ix, item) in items.into_iter().enumerate() {
+                    let is_active = Some(item.item_id()) == active_item_id;
+                    section = section.child(self.render_open_editor(
+                        &workspace,
+                        pane,
+                        ix,
+                        item,
+                        is_active,
+                        show_groups,
+                        cx,
+                    ));
+                }
+            }
+        }
+        Some(section.into_any_element())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn render_open_editor(
+        &self,
+        workspace: &View<Workspace>,
+        pane: &View<Pane>,
+        ix: usize,
+        item: Box<dyn ItemHandle>,
+        is_active: bool,
+        indent: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let settings = *ProjectPanelSettings::get_global(cx);
+        let item_id = item.item_id();
+        let icon = settings
+            .file_icons
+            .then(|| {
+                let path = item.project_path(cx)?.path;
+                FileIcons::get_icon(&path, cx)
+            })
+            .flatten();
+        let is_preview = pane.read(cx).is_active_preview_item(item_id);
+        let label = item.tab_content(
+            TabContentParams {
+                detail: Some(0),
+                selected: is_active,
+                preview: is_preview,
+            },
+            cx,
+        );
+        let indicator = render_item_indicator(item.boxed_clone(), cx);
+
+        div()
+            .id(("open-editor", item_id))
+            .on_drag(
+                DraggedTab {
+                    pane: pane.clone(),
+                    item: item.boxed_clone(),
+                    ix,
+                    detail: 0,
+                    is_active,
+                },
+                |tab, cx| cx.new_view(|_| tab.clone()),
+            )
+            .drag_over::<DraggedTab>(|style, _, cx| {
+                style.bg(cx.theme().colors().drop_target_background)
+            })
+            .on_drop(cx.listener({
+                let workspace = workspace.downgrade();
+                let pane = pane.clone();
+                move |_, dragged_tab: &DraggedTab, cx| {
+                    let from_pane = dragged_tab.pane.clone();
+                    let to_pane = pane.clone();
+                    let item_id = dragged_tab.item.item_id();
+                    workspace
+                        .update(cx, |workspace, cx| {
+                            workspace.move_item(from_pane, to_pane, item_id, ix, cx)
+                        })
+                        .log_err();
+                }
+            }))
+            .child(
+                ListItem::new(("open-editor-item", item_id))
+                    .indent_level(indent as usize)
+                    .indent_step_size(px(settings.indent_size))
+                    .selected(is_active)
+                    .start_slot(if let Some(icon) = icon {
+                        h_flex().child(Icon::from_path(icon.to_string()))
+                    } else {
+                        h_flex()
+                            .size(IconSize::default().rems())
+                            .invisible()
+                            .flex_none()
+                    })
+                    .child(div().ml_1().child(label))
+                    .end_slot(indicator)
+                    .end_hover_slot(
+                        IconButton::new(("close-open-editor", item_id), IconName::Close)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Close", cx))
+                            .on_click(cx.listener({
+                                let pane = pane.clone();
+                                move |_, _, cx| {
+                                    pane.update(cx, |pane, cx| {
+                                        pane.close_item_by_id(item_id, SaveIntent::Close, cx)
+                                            .detach_and_log_err(cx);
+                                    });
+                                }
+                            })),
+                    )
+                    .on_click(cx.listener({
+                        let pane = pane.clone();
+                        move |_, _, cx| {
+                            pane.update(cx, |pane, cx| {
+                                if let Some(ix) = pane.index_for_item(item.as_ref()) {
+                                    pane.activate_item(ix, true, true, cx);
+                                }
+                            });
+                        }
+                    })),
+            )
+    }
+}
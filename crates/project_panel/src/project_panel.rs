@@ -1,3 +1,5 @@
+mod file_nesting;
+mod open_editors;
 mod project_panel_settings;
 use client::{ErrorCode, ErrorExt};
 use settings::{Settings, SettingsStore};
@@ -6,25 +8,29 @@ use db::kvp::KEY_VALUE_STORE;
 use editor::{items::entry_git_aware_label_color, scroll::Autoscroll, Editor};
 use file_icons::FileIcons;
 
-use anyhow::{anyhow, Result};
+use anyhow::{anyhow, Context as _, Result};
 use collections::{hash_map, BTreeSet, HashMap};
 use git::repository::GitFileStatus;
 use gpui::{
     actions, anchored, deferred, div, impl_actions, px, uniform_list, Action, AnyElement,
-    AppContext, AssetSource, AsyncWindowContext, ClipboardItem, DismissEvent, Div, EventEmitter,
-    FocusHandle, FocusableView, InteractiveElement, KeyContext, ListSizingBehavior, Model,
-    MouseButton, MouseDownEvent, ParentElement, Pixels, Point, PromptLevel, Render, Stateful,
-    Styled, Subscription, Task, UniformListScrollHandle, View, ViewContext, VisualContext as _,
-    WeakView, WindowContext,
+    AppContext, AssetSource, AsyncWindowContext, ClipboardItem, DismissEvent, Div, EntityId,
+    EventEmitter, ExternalPaths, FocusHandle, FocusableView, InteractiveElement, KeyContext,
+    ListSizingBehavior, Model, MouseButton, MouseDownEvent, ParentElement, Pixels, Point,
+    PromptLevel, Render, Stateful, Styled, Subscription, Task, UniformListScrollHandle, View,
+    ViewContext, VisualContext as _, WeakView, WindowContext,
 };
 use menu::{Confirm, SelectFirst, SelectLast, SelectNext, SelectPrev};
-use project::{Entry, EntryKind, Fs, Project, ProjectEntryId, ProjectPath, Worktree, WorktreeId};
-use project_panel_settings::{ProjectPanelDockPosition, ProjectPanelSettings};
+use project::{
+    copy_recursive, CopyOptions, Entry, EntryKind, Fs, Project, ProjectEntryId, ProjectPath,
+    RemoveOptions, RenameOptions, Worktree, WorktreeId,
+};
+use project_panel_settings::{FileNestingSettings, ProjectPanelDockPosition, ProjectPanelSettings};
 use serde::{Deserialize, Serialize};
 use std::{
     cmp::Ordering,
     collections::HashSet,
     ffi::OsStr,
+    io,
     ops::Range,
     path::{Path, PathBuf},
     sync::Arc,
@@ -36,12 +42,12 @@ use util::{maybe, NumericPrefixWithSuffix, ResultExt, TryFutureExt};
 use workspace::{
     dock::{DockPosition, Panel, PanelEvent},
     notifications::{DetachAndPromptErr, NotifyTaskExt},
-    DraggedSelection, OpenInTerminal, SelectedEntry, Workspace,
+    DraggedSelection, DraggedTab, OpenInTerminal, SelectedEntry, Workspace,
 };
 use worktree::CreatedEntry;
 
 const PROJECT_PANEL_KEY: &str = "ProjectPanel";
-const NEW_ENTRY_ID: ProjectEntryId = ProjectEntryId::MAX;
+pub(crate) const NEW_ENTRY_ID: ProjectEntryId = ProjectEntryId::MAX;
 
 pub struct ProjectPanel {
     project: Model<Project>,
@@ -52,8 +58,13 @@ pub struct ProjectPanel {
     last_worktree_root_id: Option<ProjectEntryId>,
     expanded_dir_ids: HashMap<WorktreeId, Vec<ProjectEntryId>>,
     unfolded_dir_ids: HashSet<ProjectEntryId>,
+    /// The files nested under another file of their directory, mapped to that file.
+    nested_entry_parents: HashMap<ProjectEntryId, ProjectEntryId>,
+    nesting_parent_ids: HashSet<ProjectEntryId>,
     // Currently selected entry in a file tree
     selection: Option<SelectedEntry>,
+    // The folded directory whose segment of the selected entry's name was clicked
+    folded_ancestor: Option<FoldedAncestor>,
     marked_entries: BTreeSet<SelectedEntry>,
     context_menu: Option<(View<ContextMenu>, Point<Pixels>, Subscription)>,
     edit_state: Option<EditState>,
@@ -61,6 +72,8 @@ pub struct ProjectPanel {
     clipboard: Option<ClipboardEntry>,
     _dragged_entry_destination: Option<Arc<Path>>,
     workspace: WeakView<Workspace>,
+    open_editors_collapsed: bool,
+    pane_observations: HashMap<EntityId, Subscription>,
     width: Option<Pixels>,
     pending_serialization: Task<Option<()>>,
 }
@@ -74,6 +87,13 @@ struct EditState {
     processing_filename: Option<String>,
 }
 
+/// A directory folded into the name of its deepest single-child descendant's entry.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct FoldedAncestor {
+    entry_id: ProjectEntryId,
+    ancestor_id: ProjectEntryId,
+}
+
 #[derive(Clone, Debug)]
 enum ClipboardEntry {
     Copied(BTreeSet<SelectedEntry>),
@@ -96,6 +116,9 @@ pub struct EntryDetails {
     is_cut: bool,
     git_status: Option<GitFileStatus>,
     is_private: bool,
+    has_nested_entries: bool,
+    /// The directories folded into this entry's name, with their segment of that name.
+    folded_segments: Vec<(ProjectEntryId, String)>,
     worktree_id: WorktreeId,
     canonical_path: Option<PathBuf>,
 }
@@ -134,6 +157,7 @@ actions!(
         OpenPermanent,
         ToggleFocus,
         NewSearchInDirectory,
+        ExcludeDirectoryFromSearch,
         UnfoldDirectory,
         FoldDirectory,
         SelectParent,
@@ -142,6 +166,7 @@ actions!(
 
 pub fn init_settings(cx: &mut AppContext) {
     ProjectPanelSettings::register(cx);
+    FileNestingSettings::register(cx);
 }
 
 pub fn init(assets: impl AssetSource, cx: &mut AppContext) {
@@ -185,10 +210,34 @@ struct DraggedProjectEntryView {
 impl ProjectPanel {
     fn new(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) -> View<Self> {
         let project = workspace.project().clone();
+        let workspace_handle = cx.view().clone();
         let project_panel = cx.new_view(|cx: &mut ViewContext<Self>| {
             let focus_handle = cx.focus_handle();
             cx.on_focus(&focus_handle, Self::focus_in).detach();
 
+            cx.subscribe(&workspace_handle, |this, _, event, cx| match event {
+                workspace::Event::PaneAdded(pane) => {
+                    let observation = Self::observe_pane(pane, cx);
+                    this.pane_observations.insert(pane.entity_id(), observation);
+                    cx.notify();
+                }
+                workspace::Event::PaneRemoved => this.forget_removed_panes(cx),
+                workspace::Event::ItemAdded
+                | workspace::Event::ItemRemoved
+                | workspace::Event::ActiveItemChanged => {
+                    if ProjectPanelSettings::get_global(cx).open_editors {
+                        cx.notify();
+                    }
+                }
+                _ => {}
+            })
+            .detach();
+            let pane_observations = workspace
+                .panes()
+                .iter()
+                .map(|pane| (pane.entity_id(), Self::observe_pane(pane, cx)))
+                .collect();
+
             cx.subscribe(&project, |this, project, event, cx| match event {
                 project::Event::ActiveEntryChanged(Some(entry_id)) => {
                     if ProjectPanelSettings::get_global(cx).auto_reveal_entries {
@@ -244,12 +293,19 @@ impl ProjectPanel {
             .detach();
 
             let mut project_panel_settings = *ProjectPanelSettings::get_global(cx);
-            cx.observe_global::<SettingsStore>(move |_, cx| {
+            let mut file_nesting_settings = FileNestingSettings::get_global(cx).clone();
+            cx.observe_global::<SettingsStore>(move |this, cx| {
                 let new_settings = *ProjectPanelSettings::get_global(cx);
                 if project_panel_settings != new_settings {
                     project_panel_settings = new_settings;
                     cx.notify();
                 }
+                let new_file_nesting_settings = FileNestingSettings::get_global(cx);
+                if file_nesting_settings != *new_file_nesting_settings {
+                    file_nesting_settings = new_file_nesting_settings.clone();
+                    this.update_visible_entries(None, cx);
+                    cx.notify();
+                }
             })
             .detach();
 
@@ -262,7 +318,10 @@ impl ProjectPanel {
                 last_worktree_root_id: Default::default(),
                 expanded_dir_ids: Default::default(),
                 unfolded_dir_ids: Default::default(),
+                nested_entry_parents: Default::default(),
+                nesting_parent_ids: Default::default(),
                 selection: None,
+                folded_ancestor: None,
                 marked_entries: Default::default(),
                 edit_state: None,
                 context_menu: None,
@@ -270,6 +329,8 @@ impl ProjectPanel {
                 clipboard: None,
                 _dragged_entry_destination: None,
                 workspace: workspace.weak_handle(),
+                open_editors_collapsed: false,
+                pane_observations,
                 width: None,
                 pending_serialization: Task::ready(None),
             };
@@ -452,6 +513,10 @@ impl ProjectPanel {
                         menu.action("Copy Relative Path", Box::new(CopyRelativePath))
                             .when(is_dir, |menu| {
                                 menu.action("Search Inside", Box::new(NewSearchInDirectory))
+                                    .action(
+                                        "Exclude from Search",
+                                        Box::new(ExcludeDirectoryFromSearch),
+                                    )
                             })
                     },
                     |menu| {
@@ -463,6 +528,10 @@ impl ProjectPanel {
                             .when(is_dir, |menu| {
                                 menu.separator()
                                     .action("Find in Folder…", Box::new(NewSearchInDirectory))
+                                    .action(
+                                        "Exclude Folder from Search",
+                                        Box::new(ExcludeDirectoryFromSearch),
+                                    )
                             })
                             .when(is_unfoldable, |menu| {
                                 menu.action("Unfold Directory", Box::new(UnfoldDirectory))
@@ -594,12 +663,32 @@ impl ProjectPanel {
                         cx.notify();
                     }
                 }
+            } else if self.nesting_parent_ids.contains(&entry.id) {
+                let worktree_id = worktree.id();
+                let entry_id = entry.id;
+                let is_expanded = self
+                    .expanded_dir_ids
+                    .get(&worktree_id)
+                    .map_or(false, |ids| ids.binary_search(&entry_id).is_ok());
+                if is_expanded {
+                    self.select_next(&SelectNext, cx);
+                } else {
+                    self.toggle_nested_entries(entry_id, cx);
+                }
             }
         }
     }
 
     fn collapse_selected_entry(&mut self, _: &CollapseSelectedEntry, cx: &mut ViewContext<Self>) {
         if let Some((worktree, mut entry)) = self.selected_entry(cx) {
+            // Nested files collapse the group of the file they're nested under.
+            if let Some(parent_entry) = self
+                .nested_entry_parents
+                .get(&entry.id)
+                .and_then(|parent_id| worktree.entry_for_id(*parent_id))
+            {
+                entry = parent_entry;
+            }
             let worktree_id = worktree.id();
             let expanded_dir_ids =
                 if let Some(expanded_dir_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
@@ -661,6 +750,22 @@ impl ProjectPanel {
         }
     }
 
+    fn toggle_nested_entries(&mut self, entry_id: ProjectEntryId, cx: &mut ViewContext<Self>) {
+        if let Some(worktree_id) = self.project.read(cx).worktree_id_for_entry(entry_id, cx) {
+            if let Some(expanded_entry_ids) = self.expanded_dir_ids.get_mut(&worktree_id) {
+                match expanded_entry_ids.binary_search(&entry_id) {
+                    Ok(ix) => {
+                        expanded_entry_ids.remove(ix);
+                    }
+                    Err(ix) => expanded_entry_ids.insert(ix, entry_id),
+                }
+                self.update_visible_entries(Some((worktree_id, entry_id)), cx);
+                cx.focus(&self.focus_handle);
+                cx.notify();
+            }
+        }
+    }
+
     fn select_prev(&mut self, _: &SelectPrev, cx: &mut ViewContext<Self>) {
         if let Some(selection) = self.selection {
             let (mut worktree_ix, mut entry_ix, _) =
@@ -680,6 +785,7 @@ impl ProjectPanel {
                 entry_id: worktree_entries[entry_ix].id,
             };
             self.selection = Some(selection);
+            self.folded_ancestor = None;
             if cx.modifiers().shift {
                 self.marked_entries.insert(selection);
             }
@@ -868,6 +974,19 @@ impl ProjectPanel {
         cx.emit(Event::SplitEntry { entry_id });
     }
 
+    /// The entry to rename or to add new entries next to: the selected one, or the folded
+    /// directory whose segment of its name was clicked.
+    fn edit_target(&self) -> Option<SelectedEntry> {
+        let selection = self.selection?;
+        match self.folded_ancestor {
+            Some(folded) if folded.entry_id == selection.entry_id => Some(SelectedEntry {
+                worktree_id: selection.worktree_id,
+                entry_id: folded.ancestor_id,
+            }),
+            _ => Some(selection),
+        }
+    }
+
     fn new_file(&mut self, _: &NewFile, cx: &mut ViewContext<Self>) {
         self.add_entry(false, cx)
     }
@@ -880,7 +999,7 @@ impl ProjectPanel {
         if let Some(SelectedEntry {
             worktree_id,
             entry_id,
-        }) = self.selection
+        }) = self.edit_target()
         {
             let directory_id;
             if let Some((worktree, expanded_dir_ids)) = self
@@ -936,7 +1055,7 @@ impl ProjectPanel {
         if let Some(SelectedEntry {
             worktree_id,
             entry_id,
-        }) = self.selection
+        }) = self.edit_target()
         {
             if let Some(worktree) = self.project.read(cx).worktree_for_id(worktree_id, cx) {
                 if let Some(entry) = worktree.read(cx).entry_for_id(entry_id) {
@@ -1135,6 +1254,7 @@ impl ProjectPanel {
                         entry_id: entry.id,
                     };
                     self.selection = Some(selection);
+                    self.folded_ancestor = None;
                     if cx.modifiers().shift {
                         self.marked_entries.insert(selection);
                     }
@@ -1156,6 +1276,7 @@ impl ProjectPanel {
                         worktree_id: worktree.id(),
                         entry_id: parent_entry.id,
                     });
+                    self.folded_ancestor = None;
                     self.autoscroll(cx);
                     cx.notify();
                 }
@@ -1179,6 +1300,7 @@ impl ProjectPanel {
                     entry_id: root_entry.id,
                 };
                 self.selection = Some(selection);
+                self.folded_ancestor = None;
                 if cx.modifiers().shift {
                     self.marked_entries.insert(selection);
                 }
@@ -1201,6 +1323,7 @@ impl ProjectPanel {
                     worktree_id,
                     entry_id: last_entry.id,
                 });
+                self.folded_ancestor = None;
                 self.autoscroll(cx);
                 cx.notify();
             }
@@ -1357,30 +1480,51 @@ impl ProjectPanel {
         }
     }
 
+    /// The selected directory's path as written in project search filters, which is prefixed
+    /// with the worktree's name when there are several.
+    fn selected_directory_search_path(&self, cx: &AppContext) -> Option<Arc<Path>> {
+        let (worktree, entry) = self.selected_entry(cx)?;
+        if !entry.is_dir() {
+            return None;
+        }
+
+        let include_root = self.project.read(cx).visible_worktrees(cx).count() > 1;
+        if include_root {
+            let mut full_path = PathBuf::from(worktree.root_name());
+            full_path.push(&entry.path);
+            Some(Arc::from(full_path))
+        } else {
+            Some(entry.path.clone())
+        }
+    }
+
     pub fn new_search_in_directory(
         &mut self,
         _: &NewSearchInDirectory,
         cx: &mut ViewContext<Self>,
     ) {
-        if let Some((worktree, entry)) = self.selected_entry(cx) {
-            if entry.is_dir() {
-                let include_root = self.project.read(cx).visible_worktrees(cx).count() > 1;
-                let dir_path = if include_root {
-                    let mut full_path = PathBuf::from(worktree.root_name());
-                    full_path.push(&entry.path);
-                    Arc::from(full_path)
-                } else {
-                    entry.path.clone()
-                };
+        if let Some(dir_path) = self.selected_directory_search_path(cx) {
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    search::ProjectSearchView::new_search_in_directory(workspace, &dir_path, cx);
+                })
+                .ok();
+        }
+    }
 
-                self.workspace
-                    .update(cx, |workspace, cx| {
-                        search::ProjectSearchView::new_search_in_directory(
-                            workspace, &dir_path, cx,
-                        );
-                    })
-                    .ok();
-            }
+    fn exclude_directory_from_search(
+        &mut self,
+        _: &ExcludeDirectoryFromSearch,
+        cx: &mut ViewContext<Self>,
+    ) {
+        if let Some(dir_path) = self.selected_directory_search_path(cx) {
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    search::ProjectSearchView::exclude_directory_from_search(
+                        workspace, &dir_path, cx,
+                    );
+                })
+                .ok();
         }
     }
 
@@ -1535,6 +1679,8 @@ impl ProjectPanel {
         cx: &mut ViewContext<Self>,
     ) {
         let auto_collapse_dirs = ProjectPanelSettings::get_global(cx).auto_fold_dirs;
+        let file_nesting = &FileNestingSettings::get_global(cx).file_nesting;
+        let nesting_patterns = file_nesting.enabled.then_some(&file_nesting.patterns);
         let project = self.project.read(cx);
         self.last_worktree_root_id = project
             .visible_worktrees(cx)
@@ -1544,6 +1690,7 @@ impl ProjectPanel {
             .map(|entry| entry.id);
 
         self.visible_entries.clear();
+        self.nested_entry_parents.clear();
         for worktree in project.visible_worktrees(cx) {
             let snapshot = worktree.read(cx).snapshot();
             let worktree_id = snapshot.id();
@@ -1577,9 +1724,11 @@ impl ProjectPanel {
             let mut visible_worktree_entries = Vec::new();
             let mut entry_iter = snapshot.entries(true);
             while let Some(entry) = entry_iter.entry() {
+                // Directories that get a new entry are shown on their own, to place it under them.
                 if auto_collapse_dirs
                     && entry.kind.is_dir()
                     && !self.unfolded_dir_ids.contains(&entry.id)
+                    && Some(entry.id) != new_entry_parent_id
                 {
                     if let Some(root_path) = snapshot.root_entry() {
                         let mut child_entries = snapshot.child_entries(&entry.path);
@@ -1666,9 +1815,18 @@ impl ProjectPanel {
                     }
                 }
             });
+            if let Some(patterns) = nesting_patterns {
+                visible_worktree_entries = file_nesting::nest_files(
+                    visible_worktree_entries,
+                    patterns,
+                    expanded_dir_ids,
+                    &mut self.nested_entry_parents,
+                );
+            }
             self.visible_entries
                 .push((worktree_id, visible_worktree_entries));
         }
+        self.nesting_parent_ids = self.nested_entry_parents.values().copied().collect();
 
         if let Some((worktree_id, entry_id)) = new_selected_entry {
             self.selection = Some(SelectedEntry {
@@ -1713,6 +1871,11 @@ impl ProjectPanel {
                         }
                     }
                 }
+                if let Some(parent_id) = self.nested_entry_parents.get(&entry_id) {
+                    if let Err(ix) = expanded_dir_ids.binary_search(parent_id) {
+                        expanded_dir_ids.insert(ix, *parent_id);
+                    }
+                }
             }
         });
     }
@@ -1755,6 +1918,107 @@ impl ProjectPanel {
         }
     }
 
+    /// Copies files dropped from outside of Zed into the directory of the target entry, or moves
+    /// them there when the secondary modifier is held.
+    fn drop_external_paths(
+        &mut self,
+        paths: &ExternalPaths,
+        target_entry_id: ProjectEntryId,
+        is_file: bool,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let should_move = cx.modifiers().secondary();
+        self.add_external_paths(
+            paths.paths().to_vec(),
+            target_entry_id,
+            is_file,
+            should_move,
+            cx,
+        )
+        .detach_and_prompt_err("Failed to add the dropped files", cx, |_, _| None);
+    }
+
+    /// Copies or moves the given paths into the directory of the target entry, asking whether to
+    /// replace the files and directories they have the names of, or to keep both.
+    fn add_external_paths(
+        &mut self,
+        paths: Vec<PathBuf>,
+        target_entry_id: ProjectEntryId,
+        is_file: bool,
+        should_move: bool,
+        cx: &mut ViewContext<Self>,
+    ) -> Task<Result<()>> {
+        if self.project.read(cx).is_remote() {
+            return Task::ready(Err(anyhow!("Cannot drop files on a remote project")));
+        }
+        let Some(target_dir) = maybe!({
+            let worktree = self
+                .project
+                .read(cx)
+                .worktree_for_entry(target_entry_id, cx)?;
+            let worktree = worktree.read(cx);
+            let entry = worktree.entry_for_id(target_entry_id)?;
+            let dir = if is_file {
+                entry.path.parent()?
+            } else {
+                entry.path.as_ref()
+            };
+            worktree.absolutize(dir).log_err()
+        }) else {
+            return Task::ready(Ok(()));
+        };
+
+        let fs = self.fs.clone();
+        cx.spawn(|this, mut cx| async move {
+            for path in paths {
+                let Some(file_name) = path.file_name() else {
+                    continue;
+                };
+                let mut target = target_dir.join(file_name);
+                if target == path {
+                    // Dropping a file next to itself copies it, and leaves it be when moving it.
+                    if should_move {
+                        continue;
+                    }
+                    target = free_copy_path(fs.as_ref(), &target).await?;
+                } else if let Some(existing) = fs.metadata(&target).await? {
+                    let answer = this.update(&mut cx, |_, cx| {
+                        let prompt = format!(
+                            "{} already exists in {}. Do you want to replace it?",
+                            file_name.to_string_lossy(),
+                            target_dir.to_string_lossy()
+                        );
+                        cx.prompt(
+                            PromptLevel::Warning,
+                            &prompt,
+                            None,
+                            &["Replace", "Keep Both", "Cancel"],
+                        )
+                    })?;
+                    match answer.await? {
+                        0 => remove_path(fs.as_ref(), &target, existing.is_dir).await?,
+                        1 => target = free_copy_path(fs.as_ref(), &target).await?,
+                        _ => return Ok(()),
+                    }
+                }
+
+                if should_move {
+                    move_path(fs.as_ref(), &path, &target).await?;
+                } else {
+                    copy_recursive(fs.as_ref(), &path, &target, CopyOptions::default()).await?;
+                }
+            }
+            anyhow::Ok(())
+        })
+    }
+
+    /// Reveals the file of a tab dropped onto the panel.
+    fn reveal_dragged_tab(&mut self, dragged_tab: &DraggedTab, cx: &mut ViewContext<Self>) {
+        if let Some(entry_id) = dragged_tab.item.project_entry_ids(cx).first().copied() {
+            self.reveal_entry(self.project.clone(), entry_id, false, cx);
+        }
+    }
+
     fn for_each_visible_entry(
         &self,
         range: Range<usize>,
@@ -1811,10 +2075,13 @@ impl ProjectPanel {
                         }
                     };
 
-                    let (depth, difference) = ProjectPanel::calculate_depth_and_difference(
+                    let (mut depth, difference) = ProjectPanel::calculate_depth_and_difference(
                         entry,
                         visible_worktree_entries,
                     );
+                    if self.nested_entry_parents.contains_key(&entry.id) {
+                        depth += 1;
+                    }
 
                     let filename = match difference {
                         diff if diff > 1 => entry
@@ -1831,6 +2098,18 @@ impl ProjectPanel {
                             .map(|name| name.to_string_lossy().into_owned())
                             .unwrap_or_else(|| root_name.to_string_lossy().to_string()),
                     };
+                    let mut folded_segments = Vec::new();
+                    if difference > 1 {
+                        for path in entry.path.ancestors().take(difference) {
+                            if let Some((ancestor, name)) =
+                                snapshot.entry_for_path(path).zip(path.file_name())
+                            {
+                                folded_segments
+                                    .push((ancestor.id, name.to_string_lossy().into_owned()));
+                            }
+                        }
+                        folded_segments.reverse();
+                    }
                     let selection = SelectedEntry {
                         worktree_id: snapshot.id(),
                         entry_id: entry.id,
@@ -1853,18 +2132,29 @@ impl ProjectPanel {
                             .map_or(false, |e| e.is_cut() && e.items().contains(&selection)),
                         git_status: status,
                         is_private: entry.is_private,
+                        has_nested_entries: self.nesting_parent_ids.contains(&entry.id),
+                        folded_segments,
                         worktree_id: *worktree_id,
                         canonical_path: entry.canonical_path.clone(),
                     };
 
                     if let Some(edit_state) = &self.edit_state {
+                        let edited_segment = details
+                            .folded_segments
+                            .iter()
+                            .find(|(ancestor_id, _)| *ancestor_id == edit_state.entry_id);
                         let is_edited_entry = if edit_state.is_new_entry {
                             entry.id == NEW_ENTRY_ID
                         } else {
-                            entry.id == edit_state.entry_id
+                            entry.id == edit_state.entry_id || edited_segment.is_some()
                         };
 
                         if is_edited_entry {
+                            if let Some((_, name)) = edited_segment {
+                                if !edit_state.is_new_entry {
+                                    details.filename = name.clone();
+                                }
+                            }
                             if let Some(processing_filename) = &edit_state.processing_filename {
                                 details.is_processing = true;
                                 details.filename.clear();
@@ -1960,6 +2250,17 @@ impl ProjectPanel {
 
         let depth = details.depth;
         let worktree_id = details.worktree_id;
+        let has_nested_entries = details.has_nested_entries;
+        let is_expanded = details.is_expanded;
+        let folded_segments = if details.is_processing {
+            Vec::new()
+        } else {
+            details.folded_segments.clone()
+        };
+        let folded_ancestor_id = self
+            .folded_ancestor
+            .filter(|folded| is_active && folded.entry_id == entry_id)
+            .map(|folded| folded.ancestor_id);
         let selections = Arc::new(self.marked_entries.clone());
 
         let dragged_selection = DraggedSelection {
@@ -1982,11 +2283,27 @@ impl ProjectPanel {
             .on_drop(cx.listener(move |this, selections: &DraggedSelection, cx| {
                 this.drag_onto(selections, entry_id, kind.is_file(), cx);
             }))
+            .drag_over::<ExternalPaths>(|style, _, cx| {
+                style.bg(cx.theme().colors().drop_target_background)
+            })
+            .on_drop(cx.listener(move |this, paths: &ExternalPaths, cx| {
+                this.drop_external_paths(paths, entry_id, kind.is_file(), cx);
+            }))
+            .on_drop(cx.listener(|this, dragged_tab: &DraggedTab, cx| {
+                this.reveal_dragged_tab(dragged_tab, cx);
+            }))
             .child(
                 ListItem::new(entry_id.to_proto() as usize)
                     .indent_level(depth)
                     .indent_step_size(px(settings.indent_size))
                     .selected(is_marked || is_active)
+                    .when(has_nested_entries, |this| {
+                        this.toggle(is_expanded).on_toggle(
+                            cx.listener(move |this, _, cx| {
+                                this.toggle_nested_entries(entry_id, cx)
+                            }),
+                        )
+                    })
                     .when_some(canonical_path, |this, path| {
                         this.end_slot::<AnyElement>(
                             div()
@@ -2013,6 +2330,38 @@ impl ProjectPanel {
                     .child(
                         if let (Some(editor), true) = (Some(&self.filename_editor), show_editor) {
                             h_flex().h_6().w_full().child(editor.clone())
+                        } else if !folded_segments.is_empty() {
+                            let mut segments = h_flex().h_6();
+                            for (ix, (ancestor_id, name)) in folded_segments.into_iter().enumerate()
+                            {
+                                if ix > 0 {
+                                    segments = segments.child(
+                                        Label::new(std::path::MAIN_SEPARATOR_STR)
+                                            .single_line()
+                                            .color(filename_text_color),
+                                    );
+                                }
+                                segments = segments.child(
+                                    div()
+                                        .when(folded_ancestor_id == Some(ancestor_id), |this| {
+                                            this.border_b_1()
+                                                .border_color(Color::Selected.color(cx))
+                                        })
+                                        .on_any_mouse_down(cx.listener(move |this, _, cx| {
+                                            this.folded_ancestor = Some(FoldedAncestor {
+                                                entry_id,
+                                                ancestor_id,
+                                            });
+                                            cx.notify();
+                                        }))
+                                        .child(
+                                            Label::new(name)
+                                                .single_line()
+                                                .color(filename_text_color),
+                                        ),
+                                );
+                            }
+                            segments
                         } else {
                             h_flex().h_6().child(
                                 Label::new(file_name)
@@ -2163,6 +2512,65 @@ impl ProjectPanel {
     }
 }
 
+/// Moves a file or directory, copying it and removing the original when it's moved to another
+/// filesystem, which renaming can't do.
+async fn move_path(fs: &dyn Fs, source: &Path, target: &Path) -> Result<()> {
+    match fs.rename(source, target, RenameOptions::default()).await {
+        Err(error) if is_cross_device_error(&error) => {
+            let is_dir = fs.is_dir(source).await;
+            copy_recursive(fs, source, target, CopyOptions::default()).await?;
+            remove_path(fs, source, is_dir).await
+        }
+        result => result,
+    }
+}
+
+/// Whether renaming failed as the source and the target are on different filesystems.
+fn is_cross_device_error(error: &anyhow::Error) -> bool {
+    // `EXDEV` on Unix, `ERROR_NOT_SAME_DEVICE` on Windows.
+    const CROSS_DEVICE_ERROR: i32 = if cfg!(windows) { 17 } else { 18 };
+    error
+        .downcast_ref::<io::Error>()
+        .and_then(io::Error::raw_os_error)
+        == Some(CROSS_DEVICE_ERROR)
+}
+
+async fn remove_path(fs: &dyn Fs, path: &Path, is_dir: bool) -> Result<()> {
+    let options = RemoveOptions {
+        recursive: true,
+        ignore_if_not_exists: false,
+    };
+    if is_dir {
+        fs.remove_dir(path, options).await
+    } else {
+        fs.remove_file(path, options).await
+    }
+}
+
+/// The path next to the given one named like a copy of it, as pasting names copies, which no
+/// file or directory has yet.
+async fn free_copy_path(fs: &dyn Fs, path: &Path) -> Result<PathBuf> {
+    let file_stem = path.file_stem().context("invalid file name")?;
+    let extension = path.extension();
+    let mut ix = 0;
+    loop {
+        let mut file_name = file_stem.to_os_string();
+        file_name.push(" copy");
+        if ix > 0 {
+            file_name.push(format!(" {}", ix));
+        }
+        if let Some(extension) = extension {
+            file_name.push(".");
+            file_name.push(extension);
+        }
+        let copy_path = path.with_file_name(file_name);
+        if fs.metadata(&copy_path).await?.is_none() {
+            return Ok(copy_path);
+        }
+        ix += 1;
+    }
+}
+
 impl Render for ProjectPanel {
     fn render(&mut self, cx: &mut gpui::ViewContext<Self>) -> impl IntoElement {
         let has_worktree = self.visible_entries.len() != 0;
@@ -2189,6 +2597,7 @@ impl Render for ProjectPanel {
                 .on_action(cx.listener(Self::copy_path))
                 .on_action(cx.listener(Self::copy_relative_path))
                 .on_action(cx.listener(Self::new_search_in_directory))
+                .on_action(cx.listener(Self::exclude_directory_from_search))
                 .on_action(cx.listener(Self::unfold_directory))
                 .on_action(cx.listener(Self::fold_directory))
                 .when(!project.is_read_only(), |el| {
@@ -2206,6 +2615,16 @@ impl Render for ProjectPanel {
                     el.on_action(cx.listener(Self::reveal_in_finder))
                         .on_action(cx.listener(Self::open_in_terminal))
                 })
+                // Dropping files anywhere below the last project entry adds them to the root of
+                // the last worktree.
+                .on_drop(cx.listener(|this, paths: &ExternalPaths, cx| {
+                    if let Some(entry_id) = this.last_worktree_root_id {
+                        this.drop_external_paths(paths, entry_id, false, cx);
+                    }
+                }))
+                .on_drop(cx.listener(|this, dragged_tab: &DraggedTab, cx| {
+                    this.reveal_dragged_tab(dragged_tab, cx);
+                }))
                 .on_mouse_down(
                     MouseButton::Right,
                     cx.listener(move |this, event: &MouseDownEvent, cx| {
@@ -2218,26 +2637,35 @@ impl Render for ProjectPanel {
                 )
                 .track_focus(&self.focus_handle)
                 .child(
-                    uniform_list(
-                        cx.view().clone(),
-                        "entries",
-                        self.visible_entries
-                            .iter()
-                            .map(|(_, worktree_entries)| worktree_entries.len())
-                            .sum(),
-                        {
-                            |this, range, cx| {
-                                let mut items = Vec::new();
-                                this.for_each_visible_entry(range, cx, |id, details, cx| {
-                                    items.push(this.render_entry(id, details, cx));
-                                });
-                                items
-                            }
-                        },
-                    )
-                    .size_full()
-                    .with_sizing_behavior(ListSizingBehavior::Infer)
-                    .track_scroll(self.scroll_handle.clone()),
+                    v_flex()
+                        .size_full()
+                        .children(self.render_open_editors(cx))
+                        .child(
+                            uniform_list(
+                                cx.view().clone(),
+                                "entries",
+                                self.visible_entries
+                                    .iter()
+                                    .map(|(_, worktree_entries)| worktree_entries.len())
+                                    .sum(),
+                                {
+                                    |this, range, cx| {
+                                        let mut items = Vec::new();
+                                        this.for_each_visible_entry(
+                                            range,
+                                            cx,
+                                            |id, details, cx| {
+                                                items.push(this.render_entry(id, details, cx));
+                                            },
+                                        );
+                                        items
+                                    }
+                                },
+                            )
+                            .size_full()
+                            .with_sizing_behavior(ListSizingBehavior::Infer)
+                            .track_scroll(self.scroll_handle.clone()),
+                        ),
                 )
                 .children(self.context_menu.as_ref().map(|(menu, position, _)| {
                     deferred(
@@ -2397,7 +2825,7 @@ mod tests {
     use std::path::{Path, PathBuf};
     use workspace::{
         item::{Item, ProjectItem},
-        register_project_item, AppState,
+        register_project_item, AppState, CloseActiveItem, SplitDirection,
     };
 
     #[gpui::test]
@@ -2728,6 +3156,136 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_folded_directory_segments(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "src": {
+                    "main": {
+                        "java": {
+                            "com": {
+                                "example": {
+                                    "Main.java": "",
+                                }
+                            }
+                        }
+                    }
+                }
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        cx.update(|cx| {
+            let settings = *ProjectPanelSettings::get_global(cx);
+            ProjectPanelSettings::override_global(
+                ProjectPanelSettings {
+                    auto_fold_dirs: true,
+                    ..settings
+                },
+                cx,
+            );
+        });
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "    > src/main/java/com/example"]
+        );
+
+        // Adding a file to a folded directory shows that directory on its own.
+        let folded_entry_id =
+            find_project_entry(&panel, "root/src/main/java/com/example", cx).unwrap();
+        let java_id = find_project_entry(&panel, "root/src/main/java", cx).unwrap();
+        select_path(&panel, "root/src/main/java/com/example", cx);
+        panel.update(cx, |panel, cx| {
+            panel.folded_ancestor = Some(FoldedAncestor {
+                entry_id: folded_entry_id,
+                ancestor_id: java_id,
+            });
+            panel.new_file(&NewFile, cx)
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "    v src/main/java",
+                "        > com/example",
+                "          [EDITOR: '']  <== selected",
+            ]
+        );
+
+        let confirm = panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("Util.java", cx));
+            panel.confirm_edit(cx).unwrap()
+        });
+        confirm.await.unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "    v src/main/java",
+                "        > com/example",
+                "          Util.java  <== selected",
+            ]
+        );
+
+        // Renaming a folded directory only edits its own segment of the entry's name.
+        let com_id = find_project_entry(&panel, "root/src/main/java/com", cx).unwrap();
+        select_path(&panel, "root/src/main/java/com/example", cx);
+        panel.update(cx, |panel, cx| {
+            panel.folded_ancestor = Some(FoldedAncestor {
+                entry_id: folded_entry_id,
+                ancestor_id: com_id,
+            });
+            panel.rename(&Rename, cx);
+            assert_eq!(panel.filename_editor.read(cx).text(cx), "com");
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "    v src/main/java",
+                "        > [EDITOR: 'com']  <== selected",
+                "          Util.java",
+            ]
+        );
+
+        let confirm = panel.update(cx, |panel, cx| {
+            panel
+                .filename_editor
+                .update(cx, |editor, cx| editor.set_text("org", cx));
+            panel.confirm_edit(cx).unwrap()
+        });
+        confirm.await.unwrap();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "    v src/main/java",
+                "        > org/example  <== selected",
+                "          Util.java",
+            ]
+        );
+
+        // Moving the selection with the keyboard targets whole entries again.
+        panel.update(cx, |panel, cx| {
+            panel.select_next(&SelectNext, cx);
+            panel.select_prev(&SelectPrev, cx);
+            assert_eq!(panel.folded_ancestor, None);
+        });
+    }
+
     #[gpui::test(iterations = 30)]
     async fn test_editing_files(cx: &mut gpui::TestAppContext) {
         init_test(cx);
@@ -4704,6 +5262,303 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_file_nesting(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "src": {
+                    "a.d.ts": "",
+                    "a.js": "",
+                    "a.ts": "",
+                    "b.js": "",
+                },
+                "Cargo.lock": "",
+                "Cargo.toml": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        cx.update(|cx| {
+            let mut settings = FileNestingSettings::get_global(cx).clone();
+            settings.file_nesting.enabled = true;
+            FileNestingSettings::override_global(settings, cx);
+        });
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.executor().run_until_parked();
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &["v root", "    > src", "    > Cargo.toml"]
+        );
+
+        toggle_expand_dir(&panel, "root/src", cx);
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "    v src  <== selected",
+                "        > a.ts",
+                "          b.js",
+                "    > Cargo.toml",
+            ]
+        );
+
+        select_path(&panel, "root/src/a.ts", cx);
+        panel.update(cx, |panel, cx| {
+            panel.expand_selected_entry(&ExpandSelectedEntry, cx)
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "    v src",
+                "        v a.ts  <== selected",
+                "              a.d.ts",
+                "              a.js",
+                "          b.js",
+                "    > Cargo.toml",
+            ]
+        );
+
+        select_path(&panel, "root/src/a.js", cx);
+        panel.update(cx, |panel, cx| {
+            panel.collapse_selected_entry(&CollapseSelectedEntry, cx)
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "    v src",
+                "        > a.ts  <== selected",
+                "          b.js",
+                "    > Cargo.toml",
+            ]
+        );
+
+        let cargo_toml_id = find_project_entry(&panel, "root/Cargo.toml", cx).unwrap();
+        panel.update(cx, |panel, cx| {
+            panel.toggle_nested_entries(cargo_toml_id, cx)
+        });
+        assert_eq!(
+            visible_entries_as_strings(&panel, 0..10, cx),
+            &[
+                "v root",
+                "    v src",
+                "        > a.ts",
+                "          b.js",
+                "    v Cargo.toml  <== selected",
+                "          Cargo.lock",
+            ]
+        );
+    }
+
+    #[gpui::test]
+    async fn test_open_editors(cx: &mut gpui::TestAppContext) {
+        init_test_with_editor(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "a.rs": "",
+                "b.rs": "",
+                "c.rs": "",
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        cx.update(|cx| {
+            let mut settings = *ProjectPanelSettings::get_global(cx);
+            settings.open_editors = true;
+            ProjectPanelSettings::override_global(settings, cx);
+        });
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        let worktree_id = workspace
+            .update(cx, |workspace, cx| {
+                workspace.worktrees(cx).next().unwrap().read(cx).id()
+            })
+            .unwrap();
+        assert!(open_editors_as_strings(&panel, cx).is_empty());
+
+        for path in ["a.rs", "b.rs"] {
+            workspace
+                .update(cx, |workspace, cx| {
+                    workspace.open_path((worktree_id, path), None, true, cx)
+                })
+                .unwrap()
+                .await
+                .unwrap();
+        }
+        assert_eq!(open_editors_as_strings(&panel, cx), [["a.rs", "b.rs"]]);
+
+        let right_pane = workspace
+            .update(cx, |workspace, cx| {
+                workspace.split_pane(workspace.active_pane().clone(), SplitDirection::Right, cx)
+            })
+            .unwrap();
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.open_path(
+                    (worktree_id, "c.rs"),
+                    Some(right_pane.downgrade()),
+                    true,
+                    cx,
+                )
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(
+            open_editors_as_strings(&panel, cx),
+            [vec!["a.rs", "b.rs"], vec!["c.rs"]]
+        );
+        panel.update(cx, |panel, _| assert_eq!(panel.pane_observations.len(), 2));
+
+        right_pane
+            .update(cx, |pane, cx| {
+                pane.close_active_item(&CloseActiveItem { save_intent: None }, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        cx.executor().run_until_parked();
+        assert_eq!(open_editors_as_strings(&panel, cx), [["a.rs", "b.rs"]]);
+        panel.update(cx, |panel, _| assert_eq!(panel.pane_observations.len(), 1));
+
+        let left_pane = workspace
+            .update(cx, |workspace, _| workspace.active_pane().clone())
+            .unwrap();
+        left_pane
+            .update(cx, |pane, cx| {
+                pane.close_active_item(&CloseActiveItem { save_intent: None }, cx)
+            })
+            .unwrap()
+            .await
+            .unwrap();
+        assert_eq!(open_editors_as_strings(&panel, cx), [["a.rs"]]);
+    }
+
+    #[gpui::test]
+    async fn test_drop_external_paths(cx: &mut gpui::TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor().clone());
+        fs.insert_tree(
+            "/root",
+            json!({
+                "dir": {
+                    "a.txt": "existing a",
+                },
+                "b.txt": "b",
+            }),
+        )
+        .await;
+        fs.insert_tree(
+            "/outside",
+            json!({
+                "a.txt": "dropped a",
+                "c.txt": "c",
+                "sub": {
+                    "d.txt": "d",
+                },
+            }),
+        )
+        .await;
+
+        let project = Project::test(fs.clone(), ["/root".as_ref()], cx).await;
+        let workspace = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let cx = &mut VisualTestContext::from_window(*workspace, cx);
+        let panel = workspace
+            .update(cx, |workspace, cx| ProjectPanel::new(workspace, cx))
+            .unwrap();
+        cx.run_until_parked();
+        let dir_id = find_project_entry(&panel, "root/dir", cx).unwrap();
+        let b_id = find_project_entry(&panel, "root/b.txt", cx).unwrap();
+
+        // Copying into a directory.
+        panel
+            .update(cx, |panel, cx| {
+                let paths = vec![
+                    PathBuf::from("/outside/c.txt"),
+                    PathBuf::from("/outside/sub"),
+                ];
+                panel.add_external_paths(paths, dir_id, false, false, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(fs.load("/root/dir/c.txt".as_ref()).await.unwrap(), "c");
+        assert_eq!(fs.load("/root/dir/sub/d.txt".as_ref()).await.unwrap(), "d");
+        assert!(fs.is_file("/outside/c.txt".as_ref()).await);
+        assert!(fs.is_dir("/outside/sub".as_ref()).await);
+
+        // Moving onto a file, into its directory.
+        panel
+            .update(cx, |panel, cx| {
+                panel.add_external_paths(vec![PathBuf::from("/outside/sub")], b_id, true, true, cx)
+            })
+            .await
+            .unwrap();
+        assert_eq!(fs.load("/root/sub/d.txt".as_ref()).await.unwrap(), "d");
+        assert!(!fs.is_dir("/outside/sub".as_ref()).await);
+
+        // Cancelling when a file has the name of a dropped one.
+        let a_path = PathBuf::from("/outside/a.txt");
+        let task = panel.update(cx, |panel, cx| {
+            panel.add_external_paths(vec![a_path.clone()], dir_id, false, false, cx)
+        });
+        cx.run_until_parked();
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(2);
+        task.await.unwrap();
+        assert_eq!(
+            fs.load("/root/dir/a.txt".as_ref()).await.unwrap(),
+            "existing a"
+        );
+        assert!(!fs.is_file("/root/dir/a copy.txt".as_ref()).await);
+
+        // Keeping both.
+        let task = panel.update(cx, |panel, cx| {
+            panel.add_external_paths(vec![a_path.clone()], dir_id, false, false, cx)
+        });
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(1);
+        task.await.unwrap();
+        assert_eq!(
+            fs.load("/root/dir/a.txt".as_ref()).await.unwrap(),
+            "existing a"
+        );
+        assert_eq!(
+            fs.load("/root/dir/a copy.txt".as_ref()).await.unwrap(),
+            "dropped a"
+        );
+
+        // Replacing, while moving.
+        let task = panel.update(cx, |panel, cx| {
+            panel.add_external_paths(vec![a_path.clone()], dir_id, false, true, cx)
+        });
+        cx.run_until_parked();
+        cx.simulate_prompt_answer(0);
+        task.await.unwrap();
+        assert_eq!(
+            fs.load("/root/dir/a.txt".as_ref()).await.unwrap(),
+            "dropped a"
+        );
+        assert!(!fs.is_file(&a_path).await);
+    }
+
     fn toggle_expand_dir(
         panel: &View<ProjectPanel>,
         path: impl AsRef<Path>,
@@ -4782,7 +5637,7 @@ mod tests {
                 }
 
                 let indent = "    ".repeat(details.depth);
-                let icon = if details.kind.is_dir() {
+                let icon = if details.kind.is_dir() || details.has_nested_entries {
                     if details.is_expanded {
                         "v "
                     } else {
@@ -4816,6 +5671,26 @@ mod tests {
         result
     }
 
+    fn open_editors_as_strings(
+        panel: &View<ProjectPanel>,
+        cx: &mut VisualTestContext,
+    ) -> Vec<Vec<String>> {
+        panel.update(cx, |panel, cx| {
+            panel
+                .open_editor_panes(cx)
+                .iter()
+                .map(|pane| {
+                    pane.read(cx)
+                        .items()
+                        .filter_map(|item| {
+                            Some(item.project_path(cx)?.path.to_string_lossy().into_owned())
+                        })
+                        .collect()
+                })
+                .collect()
+        })
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings_store = SettingsStore::test(cx);
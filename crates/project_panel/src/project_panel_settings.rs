@@ -1,4 +1,5 @@
 use anyhow;
+use collections::BTreeMap;
 use gpui::Pixels;
 use schemars::JsonSchema;
 use serde_derive::{Deserialize, Serialize};
@@ -22,6 +23,7 @@ pub struct ProjectPanelSettings {
     pub indent_size: f32,
     pub auto_reveal_entries: bool,
     pub auto_fold_dirs: bool,
+    pub open_editors: bool,
 }
 
 #[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
@@ -62,9 +64,15 @@ pub struct ProjectPanelSettingsContent {
     pub auto_reveal_entries: Option<bool>,
     /// Whether to fold directories automatically
     /// when directory has only one directory inside.
+    /// Clicking a folded directory's segment of the entry's name
+    /// makes renames and new files target that directory.
     ///
     /// Default: false
     pub auto_fold_dirs: Option<bool>,
+    /// Whether to list the open editors, grouped by pane, above the project's files.
+    ///
+    /// Default: false
+    pub open_editors: Option<bool>,
 }
 
 impl Settings for ProjectPanelSettings {
@@ -79,3 +87,50 @@ impl Settings for ProjectPanelSettings {
         sources.json_merge()
     }
 }
+
+/// Rules for showing companion files, like generated ones, under the file they belong to.
+/// These are configured under `project_panel.file_nesting`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FileNestingSettings {
+    pub file_nesting: FileNesting,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct FileNesting {
+    pub enabled: bool,
+    pub patterns: BTreeMap<String, Vec<String>>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct FileNestingSettingsContent {
+    /// How to nest companion files under the file they belong to.
+    pub file_nesting: Option<FileNestingContent>,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema, Debug)]
+pub struct FileNestingContent {
+    /// Whether to nest files under their parent file.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The names of parent files, mapped to the names of the files nested under them. A `*`
+    /// in a parent's name is captured and substituted for `${capture}` in the names of its
+    /// children, which can also use `*` as a wildcard.
+    ///
+    /// Default: Cargo.lock under Cargo.toml, lockfiles under package.json, and
+    /// generated JavaScript and Dart files under their source.
+    pub patterns: Option<BTreeMap<String, Vec<String>>>,
+}
+
+impl Settings for FileNestingSettings {
+    const KEY: Option<&'static str> = Some("project_panel");
+
+    type FileContent = FileNestingSettingsContent;
+
+    fn load(
+        sources: SettingsSources<Self::FileContent>,
+        _: &mut gpui::AppContext,
+    ) -> anyhow::Result<Self> {
+        sources.json_merge()
+    }
+}
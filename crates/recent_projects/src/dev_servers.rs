@@ -1115,6 +1115,7 @@ pub async fn spawn_ssh_task(
                     env: Default::default(),
                     use_new_terminal: true,
                     allow_concurrent_runs: false,
+                    dedicated_terminal: false,
                     reveal: RevealStrategy::Always,
                 },
                 cx,
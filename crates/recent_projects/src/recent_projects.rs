@@ -1,13 +1,15 @@
 mod dev_servers;
+mod start_screen;
 
 use client::ProjectId;
+use collections::HashMap;
 use dev_servers::reconnect_to_dev_server;
 pub use dev_servers::DevServerProjects;
 use feature_flags::FeatureFlagAppExt;
 use fuzzy::{StringMatch, StringMatchCandidate};
 use gpui::{
-    Action, AnyElement, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView,
-    Subscription, Task, View, ViewContext, WeakView,
+    Action, AnyElement, AppContext, BackgroundExecutor, DismissEvent, EventEmitter, FocusHandle,
+    FocusableView, Subscription, Task, View, ViewContext, WeakView,
 };
 use ordered_float::OrderedFloat;
 use picker::{
@@ -16,18 +18,23 @@ use picker::{
 };
 use rpc::proto::DevServerStatus;
 use serde::Deserialize;
+use start_screen::StartScreen;
 use std::{
+    mem,
     path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
 };
+use time::OffsetDateTime;
+use time_format::TimestampFormat;
 use ui::{
     prelude::*, tooltip_container, ButtonLike, IconWithIndicator, Indicator, KeyBinding, ListItem,
     ListItemSpacing, Tooltip,
 };
 use util::{paths::PathExt, ResultExt};
 use workspace::{
-    AppState, ModalView, SerializedWorkspaceLocation, Workspace, WorkspaceId, WORKSPACE_DB,
+    AppState, EmptyPanePlaceholder, ModalView, RecentProjectDetails, SerializedWorkspaceLocation,
+    Workspace, WorkspaceId, WORKSPACE_DB,
 };
 
 #[derive(PartialEq, Clone, Deserialize, Default)]
@@ -43,9 +50,133 @@ fn default_create_new_window() -> bool {
 gpui::impl_actions!(projects, [OpenRecent]);
 gpui::actions!(projects, [OpenRemote]);
 
+/// Recent projects that aren't pinned become stale when they weren't opened for this many days.
+const STALE_PROJECT_DAYS: i64 = 30;
+/// How many of the player colors recent projects can be tagged with.
+const PROJECT_COLOR_COUNT: u32 = 8;
+
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(RecentProjects::register).detach();
     cx.observe_new_views(DevServerProjects::register).detach();
+    cx.set_global(EmptyPanePlaceholder(Arc::new(
+        |workspace: WeakView<Workspace>, cx: &mut WindowContext| {
+            cx.new_view(|cx| StartScreen::new(workspace, cx)).into()
+        },
+    )));
+}
+
+/// The recent projects, pinned ones first, along with the preferences set for them and the git
+/// branch checked out in their folder.
+#[derive(Default)]
+struct RecentProjectEntries {
+    workspaces: Vec<(WorkspaceId, SerializedWorkspaceLocation)>,
+    details: HashMap<WorkspaceId, RecentProjectDetails>,
+    branches: HashMap<WorkspaceId, SharedString>,
+}
+
+async fn load_recent_projects(background: BackgroundExecutor) -> RecentProjectEntries {
+    let mut workspaces = WORKSPACE_DB
+        .recent_workspaces_on_disk()
+        .await
+        .log_err()
+        .unwrap_or_default();
+    let details = WORKSPACE_DB
+        .recent_project_details()
+        .log_err()
+        .unwrap_or_default();
+    sort_pinned_first(&mut workspaces, &details);
+
+    let folders = workspaces
+        .iter()
+        .filter_map(|(workspace_id, location)| match location {
+            SerializedWorkspaceLocation::Local(paths, _) => {
+                Some((*workspace_id, paths.paths().first()?.clone()))
+            }
+            SerializedWorkspaceLocation::DevServer(_) => None,
+        })
+        .collect::<Vec<_>>();
+    let branches = background
+        .spawn(async move {
+            folders
+                .into_iter()
+                .filter_map(|(workspace_id, folder)| {
+                    Some((workspace_id, git_branch(&folder)?.into()))
+                })
+                .collect()
+        })
+        .await;
+
+    RecentProjectEntries {
+        workspaces,
+        details,
+        branches,
+    }
+}
+
+fn sort_pinned_first(
+    workspaces: &mut [(WorkspaceId, SerializedWorkspaceLocation)],
+    details: &HashMap<WorkspaceId, RecentProjectDetails>,
+) {
+    workspaces.sort_by_key(|(workspace_id, _)| {
+        !details
+            .get(workspace_id)
+            .map_or(false, |details| details.pinned)
+    });
+}
+
+/// The projects that aren't pinned and weren't opened in the last [`STALE_PROJECT_DAYS`].
+fn stale_projects(
+    workspaces: &[(WorkspaceId, SerializedWorkspaceLocation)],
+    details: &HashMap<WorkspaceId, RecentProjectDetails>,
+    now: i64,
+) -> Vec<WorkspaceId> {
+    let cutoff = now - STALE_PROJECT_DAYS * 24 * 60 * 60;
+    workspaces
+        .iter()
+        .map(|(workspace_id, _)| *workspace_id)
+        .filter(|workspace_id| {
+            details.get(workspace_id).map_or(false, |details| {
+                !details.pinned && details.last_opened.map_or(false, |time| time < cutoff)
+            })
+        })
+        .collect()
+}
+
+/// Reads the branch checked out in the git repository at the given folder, or the abbreviated
+/// commit when the head is detached, without opening the repository.
+fn git_branch(folder: &Path) -> Option<String> {
+    let dot_git = folder.join(".git");
+    let git_dir = if dot_git.is_file() {
+        // Worktrees and submodules point to their git directory from a `.git` file.
+        let contents = std::fs::read_to_string(&dot_git).ok()?;
+        folder.join(contents.strip_prefix("gitdir:")?.trim())
+    } else {
+        dot_git
+    };
+    let head = std::fs::read_to_string(git_dir.join("HEAD")).ok()?;
+    let head = head.trim();
+    match head.strip_prefix("ref: refs/heads/") {
+        Some(branch) => Some(branch.to_string()),
+        None => Some(head.get(..7)?.to_string()),
+    }
+}
+
+fn format_last_opened(last_opened: i64, cx: &AppContext) -> Option<String> {
+    let last_opened = OffsetDateTime::from_unix_timestamp(last_opened).ok()?;
+    Some(time_format::format_localized_timestamp(
+        last_opened,
+        OffsetDateTime::now_utc(),
+        cx.local_timezone(),
+        TimestampFormat::Relative,
+    ))
+}
+
+fn next_project_color(color: Option<u32>) -> Option<u32> {
+    match color {
+        None => Some(0),
+        Some(color) if color + 1 < PROJECT_COLOR_COUNT => Some(color + 1),
+        Some(_) => None,
+    }
 }
 
 pub struct RecentProjects {
@@ -70,14 +201,10 @@ impl RecentProjects {
         // We do not want to block the UI on a potentially lengthy call to DB, so we're gonna swap
         // out workspace locations once the future runs to completion.
         cx.spawn(|this, mut cx| async move {
-            let workspaces = WORKSPACE_DB
-                .recent_workspaces_on_disk()
-                .await
-                .log_err()
-                .unwrap_or_default();
+            let entries = load_recent_projects(cx.background_executor().clone()).await;
             this.update(&mut cx, move |this, cx| {
                 this.picker.update(cx, move |picker, cx| {
-                    picker.delegate.set_workspaces(workspaces);
+                    picker.delegate.set_entries(entries);
                     picker.update_matches(picker.query(cx), cx)
                 })
             })
@@ -144,6 +271,11 @@ impl Render for RecentProjects {
 pub struct RecentProjectsDelegate {
     workspace: WeakView<Workspace>,
     workspaces: Vec<(WorkspaceId, SerializedWorkspaceLocation)>,
+    details: HashMap<WorkspaceId, RecentProjectDetails>,
+    branches: HashMap<WorkspaceId, SharedString>,
+    /// The project being renamed, whose new display name is typed in the query.
+    renaming: Option<WorkspaceId>,
+    query: String,
     selected_match_index: usize,
     matches: Vec<StringMatch>,
     render_paths: bool,
@@ -158,6 +290,10 @@ impl RecentProjectsDelegate {
         Self {
             workspace,
             workspaces: Vec::new(),
+            details: HashMap::default(),
+            branches: HashMap::default(),
+            renaming: None,
+            query: String::new(),
             selected_match_index: 0,
             matches: Default::default(),
             create_new_window,
@@ -169,17 +305,27 @@ impl RecentProjectsDelegate {
 
     pub fn set_workspaces(&mut self, workspaces: Vec<(WorkspaceId, SerializedWorkspaceLocation)>) {
         self.workspaces = workspaces;
+        sort_pinned_first(&mut self.workspaces, &self.details);
         self.has_any_dev_server_projects = self
             .workspaces
             .iter()
             .any(|(_, location)| matches!(location, SerializedWorkspaceLocation::DevServer(_)));
     }
+
+    fn set_entries(&mut self, entries: RecentProjectEntries) {
+        self.details = entries.details;
+        self.branches = entries.branches;
+        self.set_workspaces(entries.workspaces);
+    }
 }
 impl EventEmitter<DismissEvent> for RecentProjectsDelegate {}
 impl PickerDelegate for RecentProjectsDelegate {
     type ListItem = ListItem;
 
     fn placeholder_text(&self, cx: &mut WindowContext) -> Arc<str> {
+        if self.renaming.is_some() {
+            return "Name the project, or leave empty to show its folders".into();
+        }
         let (create_window, reuse_window) = if self.create_new_window {
             (
                 cx.keystroke_text_for(&menu::Confirm),
@@ -197,7 +343,11 @@ impl PickerDelegate for RecentProjectsDelegate {
     }
 
     fn match_count(&self) -> usize {
-        self.matches.len()
+        if self.renaming.is_some() {
+            1
+        } else {
+            self.matches.len()
+        }
     }
 
     fn selected_index(&self) -> usize {
@@ -213,6 +363,11 @@ impl PickerDelegate for RecentProjectsDelegate {
         query: String,
         cx: &mut ViewContext<Picker<Self>>,
     ) -> gpui::Task<()> {
+        self.query = query.clone();
+        if self.renaming.is_some() {
+            self.selected_match_index = 0;
+            return Task::ready(());
+        }
         let query = query.trim_start();
         let smart_case = query.chars().any(|c| c.is_uppercase());
         let candidates = self
@@ -220,8 +375,8 @@ impl PickerDelegate for RecentProjectsDelegate {
             .iter()
             .enumerate()
             .filter(|(_, (id, _))| !self.is_current_workspace(*id, cx))
-            .map(|(id, (_, location))| {
-                let combined_string = match location {
+            .map(|(id, (workspace_id, location))| {
+                let mut combined_string = match location {
                     SerializedWorkspaceLocation::Local(paths, _) => paths
                         .paths()
                         .iter()
@@ -235,6 +390,15 @@ impl PickerDelegate for RecentProjectsDelegate {
                         )
                     }
                 };
+                // The display name goes last, so that the match positions within the paths
+                // don't shift.
+                if let Some(display_name) = self
+                    .details
+                    .get(workspace_id)
+                    .and_then(|details| details.display_name.as_ref())
+                {
+                    combined_string.push_str(display_name);
+                }
 
                 StringMatchCandidate::new(id, combined_string)
             })
@@ -264,6 +428,14 @@ impl PickerDelegate for RecentProjectsDelegate {
     }
 
     fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(workspace_id) = self.renaming.take() {
+            let display_name = Some(self.query.trim().to_string()).filter(|name| !name.is_empty());
+            self.update_details(workspace_id, cx, |details| {
+                details.display_name = display_name
+            });
+            cx.defer(|picker, cx| picker.set_query("", cx));
+            return;
+        }
         if let Some((selected_match, workspace)) = self
             .matches
             .get(self.selected_index())
@@ -281,85 +453,15 @@ impl PickerDelegate for RecentProjectsDelegate {
                     if workspace.database_id() == Some(*candidate_workspace_id) {
                         Task::ready(Ok(()))
                     } else {
-                        match candidate_workspace_location {
-                            SerializedWorkspaceLocation::Local(paths, _) => {
-                                let paths = paths.paths().as_ref().clone();
-                                if replace_current_window {
-                                    cx.spawn(move |workspace, mut cx| async move {
-                                        let continue_replacing = workspace
-                                            .update(&mut cx, |workspace, cx| {
-                                                workspace.prepare_to_close(true, cx)
-                                            })?
-                                            .await?;
-                                        if continue_replacing {
-                                            workspace
-                                                .update(&mut cx, |workspace, cx| {
-                                                    workspace
-                                                        .open_workspace_for_paths(true, paths, cx)
-                                                })?
-                                                .await
-                                        } else {
-                                            Ok(())
-                                        }
-                                    })
-                                } else {
-                                    workspace.open_workspace_for_paths(false, paths, cx)
-                                }
-                            }
-                            SerializedWorkspaceLocation::DevServer(dev_server_project) => {
-                                let store = dev_server_projects::Store::global(cx);
-                                let Some(project_id) = store.read(cx)
-                                    .dev_server_project(dev_server_project.id)
-                                    .and_then(|p| p.project_id)
-                                else {
-                                    let server = store.read(cx).dev_server_for_project(dev_server_project.id);
-                                    if server.is_some_and(|server| server.ssh_connection_string.is_some()) {
-                                        let reconnect =  reconnect_to_dev_server(cx.view().clone(), server.unwrap().clone(), cx);
-                                        let id = dev_server_project.id;
-                                        return cx.spawn(|workspace, mut cx| async move {
-                                            reconnect.await?;
-
-                                            cx.background_executor().timer(Duration::from_millis(1000)).await;
-
-                                            if let Some(project_id) = store.update(&mut cx, |store, _| {
-                                                store.dev_server_project(id)
-                                                    .and_then(|p| p.project_id)
-                                            })? {
-                                                    workspace.update(&mut cx, move |_, cx| {
-                                                    open_dev_server_project(replace_current_window, project_id, cx)
-                                                    })?.await?;
-                                                }
-                                            Ok(())
-                                        })
-                                    } else {
-                                        let dev_server_name = dev_server_project.dev_server_name.clone();
-                                        return cx.spawn(|workspace, mut cx| async move {
-                                            let response =
-                                                cx.prompt(gpui::PromptLevel::Warning,
-                                                    "Dev Server is offline",
-                                                    Some(format!("Cannot connect to {}. To debug open the remote project settings.", dev_server_name).as_str()),
-                                                    &["Ok", "Open Settings"]
-                                                ).await?;
-                                            if response == 1 {
-                                                workspace.update(&mut cx, |workspace, cx| {
-                                                    let handle = cx.view().downgrade();
-                                                    workspace.toggle_modal(cx, |cx| DevServerProjects::new(cx, handle))
-                                                })?;
-                                            } else {
-                                                workspace.update(&mut cx, |workspace, cx| {
-                                                    RecentProjects::open(workspace, true, cx);
-                                                })?;
-                                            }
-                                            Ok(())
-                                        })
-                                    }
-                                };
-                                open_dev_server_project(replace_current_window, project_id, cx)
-                        }
+                        open_recent_project(
+                            workspace,
+                            candidate_workspace_location,
+                            replace_current_window,
+                            cx,
+                        )
                     }
-                }
                 })
-            .detach_and_log_err(cx);
+                .detach_and_log_err(cx);
             cx.emit(DismissEvent);
         }
     }
@@ -380,11 +482,32 @@ impl PickerDelegate for RecentProjectsDelegate {
         selected: bool,
         cx: &mut ViewContext<Picker<Self>>,
     ) -> Option<Self::ListItem> {
+        if self.renaming.is_some() {
+            let name = self.query.trim();
+            let label = if name.is_empty() {
+                "Show the Project's Folders Instead of a Name".to_string()
+            } else {
+                format!("Rename the Project to \"{name}\"")
+            };
+            return Some(
+                ListItem::new(ix)
+                    .selected(selected)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .child(Label::new(label)),
+            );
+        }
+
         let Some(hit) = self.matches.get(ix) else {
             return None;
         };
 
-        let (_, location) = self.workspaces.get(hit.candidate_id)?;
+        let (workspace_id, location) = self.workspaces.get(hit.candidate_id)?;
+        let details = self.details.get(workspace_id).cloned().unwrap_or_default();
+        let branch = self.branches.get(workspace_id).cloned();
+        let last_opened = details
+            .last_opened
+            .and_then(|last_opened| format_last_opened(last_opened, cx));
 
         let is_remote = matches!(location, SerializedWorkspaceLocation::DevServer(_));
         let dev_server_status =
@@ -424,8 +547,17 @@ impl PickerDelegate for RecentProjectsDelegate {
             })
             .unzip();
 
+        let match_label = match &details.display_name {
+            Some(display_name) => HighlightedText {
+                text: display_name.clone(),
+                highlight_positions: Vec::new(),
+                char_count: display_name.chars().count(),
+                color: Color::Default,
+            },
+            None => HighlightedText::join(match_labels.into_iter().flatten(), ", "),
+        };
         let highlighted_match = HighlightedMatchWithPaths {
-            match_label: HighlightedText::join(match_labels.into_iter().flatten(), ", ").color(
+            match_label: match_label.color(
                 if matches!(dev_server_status, Some(DevServerStatus::Offline)) {
                     Color::Disabled
                 } else {
@@ -444,6 +576,9 @@ impl PickerDelegate for RecentProjectsDelegate {
                     h_flex()
                         .flex_grow()
                         .gap_3()
+                        .when_some(details.color, |this, color| {
+                            this.child(Indicator::dot().color(Color::Player(color)))
+                        })
                         .when(self.has_any_dev_server_projects, |this| {
                             this.child(if is_remote {
                                 // if disabled, Color::Disabled
@@ -475,10 +610,92 @@ impl PickerDelegate for RecentProjectsDelegate {
                                 highlighted.paths.clear();
                             }
                             highlighted.render(cx)
-                        }),
+                        })
+                        .child(
+                            h_flex()
+                                .ml_auto()
+                                .flex_none()
+                                .gap_2()
+                                .when(details.pinned, |this| {
+                                    this.child(
+                                        Icon::new(IconName::Pin)
+                                            .size(IconSize::XSmall)
+                                            .color(Color::Muted),
+                                    )
+                                })
+                                .when_some(branch, |this, branch| {
+                                    this.child(
+                                        h_flex()
+                                            .gap_1()
+                                            .child(
+                                                Icon::new(IconName::GitBranch)
+                                                    .size(IconSize::XSmall)
+                                                    .color(Color::Muted),
+                                            )
+                                            .child(
+                                                Label::new(branch)
+                                                    .size(LabelSize::Small)
+                                                    .color(Color::Muted),
+                                            ),
+                                    )
+                                })
+                                .when_some(last_opened, |this, last_opened| {
+                                    this.child(
+                                        Label::new(last_opened)
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                }),
+                        ),
                 )
                 .map(|el| {
-                    let delete_button = div()
+                    let buttons = h_flex()
+                        .gap_1()
+                        .child(
+                            IconButton::new("pin", IconName::Pin)
+                                .icon_size(IconSize::Small)
+                                .selected(details.pinned)
+                                .on_click(cx.listener(move |this, _event, cx| {
+                                    cx.stop_propagation();
+                                    cx.prevent_default();
+
+                                    this.delegate.toggle_pinned(ix, cx);
+                                    this.refresh(cx);
+                                }))
+                                .tooltip(move |cx| {
+                                    if details.pinned {
+                                        Tooltip::text("Unpin Project", cx)
+                                    } else {
+                                        Tooltip::text("Pin Project to the Top", cx)
+                                    }
+                                }),
+                        )
+                        .child(
+                            IconButton::new("rename", IconName::Pencil)
+                                .icon_size(IconSize::Small)
+                                .on_click(cx.listener(move |this, _event, cx| {
+                                    cx.stop_propagation();
+                                    cx.prevent_default();
+
+                                    this.delegate.start_renaming(ix, cx);
+                                }))
+                                .tooltip(|cx| Tooltip::text("Rename Project...", cx)),
+                        )
+                        .child(
+                            IconButton::new("color", IconName::Indicator)
+                                .icon_size(IconSize::Small)
+                                .when_some(details.color, |button, color| {
+                                    button.icon_color(Color::Player(color))
+                                })
+                                .on_click(cx.listener(move |this, _event, cx| {
+                                    cx.stop_propagation();
+                                    cx.prevent_default();
+
+                                    this.delegate.cycle_color(ix, cx);
+                                    this.refresh(cx);
+                                }))
+                                .tooltip(|cx| Tooltip::text("Change Project Color", cx)),
+                        )
                         .child(
                             IconButton::new("delete", IconName::Close)
                                 .icon_size(IconSize::Small)
@@ -493,9 +710,9 @@ impl PickerDelegate for RecentProjectsDelegate {
                         .into_any_element();
 
                     if self.selected_index() == ix {
-                        el.end_slot::<AnyElement>(delete_button)
+                        el.end_slot::<AnyElement>(buttons)
                     } else {
-                        el.end_hover_slot::<AnyElement>(delete_button)
+                        el.end_hover_slot::<AnyElement>(buttons)
                     }
                 })
                 .tooltip(move |cx| {
@@ -509,7 +726,10 @@ impl PickerDelegate for RecentProjectsDelegate {
     }
 
     fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
-        if !cx.has_flag::<feature_flags::Remoting>() {
+        let has_remoting = cx.has_flag::<feature_flags::Remoting>();
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let has_stale_projects = !stale_projects(&self.workspaces, &self.details, now).is_empty();
+        if !has_remoting && !has_stale_projects {
             return None;
         }
         Some(
@@ -520,28 +740,137 @@ impl PickerDelegate for RecentProjectsDelegate {
                 .border_color(cx.theme().colors().border)
                 .justify_end()
                 .gap_4()
-                .child(
-                    ButtonLike::new("remote")
-                        .when_some(KeyBinding::for_action(&OpenRemote, cx), |button, key| {
-                            button.child(key)
-                        })
-                        .child(Label::new("New remote project…").color(Color::Muted))
-                        .on_click(|_, cx| cx.dispatch_action(OpenRemote.boxed_clone())),
-                )
-                .child(
-                    ButtonLike::new("local")
-                        .when_some(
-                            KeyBinding::for_action(&workspace::Open, cx),
-                            |button, key| button.child(key),
-                        )
-                        .child(Label::new("Open local folder…").color(Color::Muted))
-                        .on_click(|_, cx| cx.dispatch_action(workspace::Open.boxed_clone())),
-                )
+                .when(has_stale_projects, |this| {
+                    this.child(
+                        ButtonLike::new("remove-stale")
+                            .child(Label::new("Remove stale projects").color(Color::Muted))
+                            .tooltip(|cx| {
+                                Tooltip::text(
+                                    format!(
+                                        "Forget projects that aren't pinned \
+                                        and weren't opened in {STALE_PROJECT_DAYS} days"
+                                    ),
+                                    cx,
+                                )
+                            })
+                            .on_click(
+                                cx.listener(|this, _, cx| this.delegate.remove_stale_projects(cx)),
+                            ),
+                    )
+                })
+                .when(has_remoting, |this| {
+                    this.child(
+                        ButtonLike::new("remote")
+                            .when_some(KeyBinding::for_action(&OpenRemote, cx), |button, key| {
+                                button.child(key)
+                            })
+                            .child(Label::new("New remote project…").color(Color::Muted))
+                            .on_click(|_, cx| cx.dispatch_action(OpenRemote.boxed_clone())),
+                    )
+                    .child(
+                        ButtonLike::new("local")
+                            .when_some(
+                                KeyBinding::for_action(&workspace::Open, cx),
+                                |button, key| button.child(key),
+                            )
+                            .child(Label::new("Open local folder…").color(Color::Muted))
+                            .on_click(|_, cx| cx.dispatch_action(workspace::Open.boxed_clone())),
+                    )
+                })
                 .into_any(),
         )
     }
 }
 
+/// Opens a recent project from the given workspace, either in its window or in a new one.
+fn open_recent_project(
+    workspace: &mut Workspace,
+    location: &SerializedWorkspaceLocation,
+    replace_current_window: bool,
+    cx: &mut ViewContext<Workspace>,
+) -> Task<anyhow::Result<()>> {
+    match location {
+        SerializedWorkspaceLocation::Local(paths, _) => {
+            let paths = paths.paths().as_ref().clone();
+            if replace_current_window {
+                cx.spawn(move |workspace, mut cx| async move {
+                    let continue_replacing = workspace
+                        .update(&mut cx, |workspace, cx| {
+                            workspace.prepare_to_close(true, cx)
+                        })?
+                        .await?;
+                    if continue_replacing {
+                        workspace
+                            .update(&mut cx, |workspace, cx| {
+                                workspace.open_workspace_for_paths(true, paths, cx)
+                            })?
+                            .await
+                    } else {
+                        Ok(())
+                    }
+                })
+            } else {
+                workspace.open_workspace_for_paths(false, paths, cx)
+            }
+        }
+        SerializedWorkspaceLocation::DevServer(dev_server_project) => {
+            let store = dev_server_projects::Store::global(cx);
+            let Some(project_id) = store
+                .read(cx)
+                .dev_server_project(dev_server_project.id)
+                .and_then(|p| p.project_id)
+            else {
+                let server = store.read(cx).dev_server_for_project(dev_server_project.id);
+                if server.is_some_and(|server| server.ssh_connection_string.is_some()) {
+                    let reconnect =
+                        reconnect_to_dev_server(cx.view().clone(), server.unwrap().clone(), cx);
+                    let id = dev_server_project.id;
+                    return cx.spawn(|workspace, mut cx| async move {
+                        reconnect.await?;
+
+                        cx.background_executor()
+                            .timer(Duration::from_millis(1000))
+                            .await;
+
+                        if let Some(project_id) = store.update(&mut cx, |store, _| {
+                            store.dev_server_project(id).and_then(|p| p.project_id)
+                        })? {
+                            workspace
+                                .update(&mut cx, move |_, cx| {
+                                    open_dev_server_project(replace_current_window, project_id, cx)
+                                })?
+                                .await?;
+                        }
+                        Ok(())
+                    });
+                } else {
+                    let dev_server_name = dev_server_project.dev_server_name.clone();
+                    return cx.spawn(|workspace, mut cx| async move {
+                        let response =
+                            cx.prompt(gpui::PromptLevel::Warning,
+                                "Dev Server is offline",
+                                Some(format!("Cannot connect to {}. To debug open the remote project settings.", dev_server_name).as_str()),
+                                &["Ok", "Open Settings"]
+                            ).await?;
+                        if response == 1 {
+                            workspace.update(&mut cx, |workspace, cx| {
+                                let handle = cx.view().downgrade();
+                                workspace.toggle_modal(cx, |cx| DevServerProjects::new(cx, handle))
+                            })?;
+                        } else {
+                            workspace.update(&mut cx, |workspace, cx| {
+                                RecentProjects::open(workspace, true, cx);
+                            })?;
+                        }
+                        Ok(())
+                    });
+                }
+            };
+            open_dev_server_project(replace_current_window, project_id, cx)
+        }
+    }
+}
+
 fn open_dev_server_project(
     replace_current_window: bool,
     project_id: ProjectId,
@@ -658,6 +987,87 @@ impl RecentProjectsDelegate {
         }
     }
 
+    fn workspace_id_at(&self, ix: usize) -> Option<WorkspaceId> {
+        let selected_match = self.matches.get(ix)?;
+        Some(self.workspaces[selected_match.candidate_id].0)
+    }
+
+    /// Changes the preferences of a project, keeping pinned projects first, and saves them.
+    fn update_details(
+        &mut self,
+        workspace_id: WorkspaceId,
+        cx: &mut ViewContext<Picker<Self>>,
+        update: impl FnOnce(&mut RecentProjectDetails),
+    ) {
+        let details = self.details.entry(workspace_id).or_default();
+        update(details);
+        let RecentProjectDetails {
+            pinned,
+            display_name,
+            color,
+            ..
+        } = details.clone();
+        let workspaces = mem::take(&mut self.workspaces);
+        self.set_workspaces(workspaces);
+        self.reset_selected_match_index = false;
+        cx.background_executor()
+            .spawn(WORKSPACE_DB.save_recent_project_preferences(
+                workspace_id,
+                pinned,
+                display_name,
+                color,
+            ))
+            .detach_and_log_err(cx);
+    }
+
+    fn toggle_pinned(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(workspace_id) = self.workspace_id_at(ix) {
+            self.update_details(workspace_id, cx, |details| details.pinned = !details.pinned);
+        }
+    }
+
+    fn cycle_color(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(workspace_id) = self.workspace_id_at(ix) {
+            self.update_details(workspace_id, cx, |details| {
+                details.color = next_project_color(details.color)
+            });
+        }
+    }
+
+    /// Switches the picker to typing a display name for the project, in place of the query.
+    fn start_renaming(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(workspace_id) = self.workspace_id_at(ix) else {
+            return;
+        };
+        self.renaming = Some(workspace_id);
+        let display_name = self
+            .details
+            .get(&workspace_id)
+            .and_then(|details| details.display_name.clone())
+            .unwrap_or_default();
+        cx.defer(move |picker, cx| picker.set_query(display_name, cx));
+        cx.notify();
+    }
+
+    fn remove_stale_projects(&self, cx: &mut ViewContext<Picker<Self>>) {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let stale_projects = stale_projects(&self.workspaces, &self.details, now);
+        cx.spawn(move |this, mut cx| async move {
+            for workspace_id in stale_projects {
+                WORKSPACE_DB
+                    .delete_workspace_by_id(workspace_id)
+                    .await
+                    .log_err();
+            }
+            let entries = load_recent_projects(cx.background_executor().clone()).await;
+            this.update(&mut cx, move |picker, cx| {
+                picker.delegate.set_entries(entries);
+                picker.update_matches(picker.query(cx), cx)
+            })
+        })
+        .detach();
+    }
+
     fn is_current_workspace(
         &self,
         workspace_id: WorkspaceId,
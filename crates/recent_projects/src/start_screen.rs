@@ -0,0 +1,198 @@
+use gpui::{Action, Task, ViewContext, WeakView};
+use ui::{prelude::*, Indicator, ListItem, ListItemSpacing};
+use util::{paths::PathExt, ResultExt};
+use workspace::{SerializedWorkspaceLocation, Workspace};
+
+use crate::{
+    format_last_opened, load_recent_projects, open_recent_project, OpenRecent, RecentProjectEntries,
+};
+
+/// How many recent projects the start screen lists, the rest being a click away in the modal.
+const MAX_START_SCREEN_PROJECTS: usize = 8;
+
+/// Shown in the empty panes of a window without a project, to open one of the recent projects
+/// in that window.
+pub(crate) struct StartScreen {
+    workspace: WeakView<Workspace>,
+    entries: RecentProjectEntries,
+    _load_entries: Task<()>,
+}
+
+impl StartScreen {
+    pub(crate) fn new(workspace: WeakView<Workspace>, cx: &mut ViewContext<Self>) -> Self {
+        let _load_entries = cx.spawn(|this, mut cx| async move {
+            let entries = load_recent_projects(cx.background_executor().clone()).await;
+            this.update(&mut cx, |this, cx| {
+                this.entries = entries;
+                cx.notify();
+            })
+            .log_err();
+        });
+        Self {
+            workspace,
+            entries: RecentProjectEntries::default(),
+            _load_entries,
+        }
+    }
+
+    fn open_project(&self, location: SerializedWorkspaceLocation, cx: &mut ViewContext<Self>) {
+        self.workspace
+            .update(cx, |workspace, cx| {
+                open_recent_project(workspace, &location, true, cx).detach_and_log_err(cx)
+            })
+            .log_err();
+    }
+
+    fn render_project(
+        &self,
+        ix: usize,
+        location: &SerializedWorkspaceLocation,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        let (workspace_id, _) = self.entries.workspaces[ix];
+        let details = self
+            .entries
+            .details
+            .get(&workspace_id)
+            .cloned()
+            .unwrap_or_default();
+        let branch = self.entries.branches.get(&workspace_id).cloned();
+        let last_opened = details
+            .last_opened
+            .and_then(|last_opened| format_last_opened(last_opened, cx));
+
+        let (folder_names, folders) = match location {
+            SerializedWorkspaceLocation::Local(paths, _) => (
+                paths
+                    .paths()
+                    .iter()
+                    .filter_map(|path| Some(path.file_name()?.to_string_lossy().into_owned()))
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                paths
+                    .paths()
+                    .iter()
+                    .map(|path| path.compact().to_string_lossy().into_owned())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            ),
+            SerializedWorkspaceLocation::DevServer(dev_server_project) => (
+                dev_server_project.path.clone(),
+                format!(
+                    "{}:{}",
+                    dev_server_project.dev_server_name, dev_server_project.path
+                ),
+            ),
+        };
+        let name = details.display_name.clone().unwrap_or(folder_names);
+
+        ListItem::new(ix)
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .start_slot(div().w_2().when_some(details.color, |this, color| {
+                this.child(Indicator::dot().color(Color::Player(color)))
+            }))
+            .child(
+                h_flex()
+                    .gap_3()
+                    .child(
+                        v_flex().child(Label::new(name)).child(
+                            Label::new(folders)
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                    )
+                    .child(
+                        h_flex()
+                            .ml_auto()
+                            .flex_none()
+                            .gap_2()
+                            .when(details.pinned, |this| {
+                                this.child(
+                                    Icon::new(IconName::Pin)
+                                        .size(IconSize::XSmall)
+                                        .color(Color::Muted),
+                                )
+                            })
+                            .when_some(branch, |this, branch| {
+                                this.child(
+                                    h_flex()
+                                        .gap_1()
+                                        .child(
+                                            Icon::new(IconName::GitBranch)
+                                                .size(IconSize::XSmall)
+                                                .color(Color::Muted),
+                                        )
+                                        .child(
+                                            Label::new(branch)
+                                                .size(LabelSize::Small)
+                                                .color(Color::Muted),
+                                        ),
+                                )
+                            })
+                            .when_some(last_opened, |this, last_opened| {
+                                this.child(
+                                    Label::new(last_opened)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                            }),
+                    ),
+            )
+            .on_click(cx.listener({
+                let location = location.clone();
+                move |this, _, cx| this.open_project(location.clone(), cx)
+            }))
+    }
+}
+
+impl Render for StartScreen {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let projects = self
+            .entries
+            .workspaces
+            .iter()
+            .take(MAX_START_SCREEN_PROJECTS)
+            .enumerate()
+            .map(|(ix, (_, location))| self.render_project(ix, location, cx).into_any_element())
+            .collect::<Vec<_>>();
+
+        v_flex()
+            .w(rems(34.))
+            .gap_2()
+            .when(projects.is_empty(), |this| {
+                this.child(Label::new("Open a file or project to get started.").color(Color::Muted))
+            })
+            .when(!projects.is_empty(), |this| {
+                this.child(
+                    Label::new("Recent Projects")
+                        .size(LabelSize::Small)
+                        .color(Color::Muted),
+                )
+                .child(v_flex().children(projects))
+            })
+            .child(
+                h_flex()
+                    .gap_2()
+                    .child(
+                        Button::new("open-folder", "Open Folder…")
+                            .on_click(|_, cx| cx.dispatch_action(workspace::Open.boxed_clone())),
+                    )
+                    .when(
+                        self.entries.workspaces.len() > MAX_START_SCREEN_PROJECTS,
+                        |this| {
+                            this.child(Button::new("all-recent", "All Recent Projects…").on_click(
+                                |_, cx| {
+                                    cx.dispatch_action(
+                                        OpenRecent {
+                                            create_new_window: false,
+                                        }
+                                        .boxed_clone(),
+                                    )
+                                },
+                            ))
+                        },
+                    ),
+            )
+    }
+}
@@ -148,6 +148,7 @@ messages!(
     (Call, Foreground),
     (CallCanceled, Foreground),
     (CancelCall, Foreground),
+    (CancelLanguageServerWork, Background),
     (ChannelMessageSent, Foreground),
     (ChannelMessageUpdate, Foreground),
     (CompleteWithLanguageModel, Background),
@@ -183,14 +184,25 @@ messages!(
     (GetChannelMessages, Background),
     (GetChannelMessagesById, Background),
     (GetChannelMessagesResponse, Background),
+    (GetCallHierarchyCallsResponse, Background),
     (GetCodeActions, Background),
     (GetCodeActionsResponse, Background),
+    (GetCodeLens, Background),
+    (GetCodeLensResponse, Background),
+    (GetColorPresentations, Background),
+    (GetColorPresentationsResponse, Background),
     (GetCompletions, Background),
     (GetCompletionsResponse, Background),
     (GetDefinition, Background),
     (GetDefinitionResponse, Background),
+    (GetDocumentColors, Background),
+    (GetDocumentColorsResponse, Background),
     (GetDocumentHighlights, Background),
     (GetDocumentHighlightsResponse, Background),
+    (GetDocumentLinks, Background),
+    (GetDocumentLinksResponse, Background),
+    (GetFoldingRanges, Background),
+    (GetFoldingRangesResponse, Background),
     (GetHover, Background),
     (GetHoverResponse, Background),
     (GetNotifications, Foreground),
@@ -201,12 +213,20 @@ messages!(
     (GetProjectSymbolsResponse, Background),
     (GetReferences, Background),
     (GetReferencesResponse, Background),
+    (GetRenameEdits, Background),
+    (GetRenameEditsResponse, Background),
+    (GetSemanticTokens, Background),
+    (GetSemanticTokensResponse, Background),
+    (GetSubtypes, Background),
+    (GetSupertypes, Background),
     (GetSupermavenApiKey, Background),
     (GetSupermavenApiKeyResponse, Background),
     (GetTypeDefinition, Background),
     (GetTypeDefinitionResponse, Background),
     (GetImplementation, Background),
     (GetImplementationResponse, Background),
+    (GetIncomingCalls, Background),
+    (GetOutgoingCalls, Background),
     (GetUsers, Foreground),
     (Hello, Foreground),
     (IncomingCall, Foreground),
@@ -240,8 +260,11 @@ messages!(
     (PerformRename, Background),
     (PerformRenameResponse, Background),
     (Ping, Foreground),
+    (PrepareCallHierarchy, Background),
+    (PrepareCallHierarchyResponse, Background),
     (PrepareRename, Background),
     (PrepareRenameResponse, Background),
+    (PrepareTypeHierarchy, Background),
     (ProjectEntryResponse, Foreground),
     (RefreshInlayHints, Foreground),
     (RejoinChannelBuffers, Foreground),
@@ -259,6 +282,10 @@ messages!(
     (RenameChannelResponse, Foreground),
     (RenameProjectEntry, Foreground),
     (RequestContact, Foreground),
+    (ResolveCodeLens, Background),
+    (ResolveCodeLensResponse, Background),
+    (ResolveDocumentLink, Background),
+    (ResolveDocumentLinkResponse, Background),
     (ResolveCompletionDocumentation, Background),
     (ResolveCompletionDocumentationResponse, Background),
     (ResolveInlayHint, Background),
@@ -285,6 +312,7 @@ messages!(
     (TaskTemplates, Background),
     (TaskTemplatesResponse, Background),
     (Test, Foreground),
+    (TypeHierarchyItemsResponse, Background),
     (Unfollow, Foreground),
     (UnshareProject, Foreground),
     (UpdateBuffer, Foreground),
@@ -343,6 +371,7 @@ request_messages!(
     ),
     (Call, Ack),
     (CancelCall, Ack),
+    (CancelLanguageServerWork, Ack),
     (CopyProjectEntry, ProjectEntryResponse),
     (CompleteWithLanguageModel, LanguageModelResponse),
     (ComputeEmbeddings, ComputeEmbeddingsResponse),
@@ -362,15 +391,26 @@ request_messages!(
     (GetChannelMessages, GetChannelMessagesResponse),
     (GetChannelMessagesById, GetChannelMessagesResponse),
     (GetCodeActions, GetCodeActionsResponse),
+    (GetCodeLens, GetCodeLensResponse),
+    (GetColorPresentations, GetColorPresentationsResponse),
     (GetCompletions, GetCompletionsResponse),
     (GetDefinition, GetDefinitionResponse),
+    (GetDocumentColors, GetDocumentColorsResponse),
     (GetImplementation, GetImplementationResponse),
+    (GetIncomingCalls, GetCallHierarchyCallsResponse),
     (GetDocumentHighlights, GetDocumentHighlightsResponse),
+    (GetDocumentLinks, GetDocumentLinksResponse),
+    (GetFoldingRanges, GetFoldingRangesResponse),
     (GetHover, GetHoverResponse),
     (GetNotifications, GetNotificationsResponse),
+    (GetOutgoingCalls, GetCallHierarchyCallsResponse),
     (GetPrivateUserInfo, GetPrivateUserInfoResponse),
     (GetProjectSymbols, GetProjectSymbolsResponse),
     (GetReferences, GetReferencesResponse),
+    (GetRenameEdits, GetRenameEditsResponse),
+    (GetSemanticTokens, GetSemanticTokensResponse),
+    (GetSubtypes, TypeHierarchyItemsResponse),
+    (GetSupertypes, TypeHierarchyItemsResponse),
     (GetSupermavenApiKey, GetSupermavenApiKeyResponse),
     (GetTypeDefinition, GetTypeDefinitionResponse),
     (GetUsers, UsersResponse),
@@ -394,7 +434,9 @@ request_messages!(
     (OpenNewBuffer, OpenBufferResponse),
     (PerformRename, PerformRenameResponse),
     (Ping, Ack),
+    (PrepareCallHierarchy, PrepareCallHierarchyResponse),
     (PrepareRename, PrepareRenameResponse),
+    (PrepareTypeHierarchy, TypeHierarchyItemsResponse),
     (RefreshInlayHints, Ack),
     (RejoinChannelBuffers, RejoinChannelBuffersResponse),
     (RejoinRoom, RejoinRoomResponse),
@@ -406,6 +448,8 @@ request_messages!(
     (RenameChannel, RenameChannelResponse),
     (RenameProjectEntry, ProjectEntryResponse),
     (RequestContact, Ack),
+    (ResolveCodeLens, ResolveCodeLensResponse),
+    (ResolveDocumentLink, ResolveDocumentLinkResponse),
     (
         ResolveCompletionDocumentation,
         ResolveCompletionDocumentationResponse
@@ -454,6 +498,7 @@ entity_messages!(
     BlameBuffer,
     BufferReloaded,
     BufferSaved,
+    CancelLanguageServerWork,
     CopyProjectEntry,
     CreateBufferForPeer,
     CreateProjectEntry,
@@ -461,13 +506,24 @@ entity_messages!(
     ExpandProjectEntry,
     FormatBuffers,
     GetCodeActions,
+    GetCodeLens,
+    GetColorPresentations,
     GetCompletions,
     GetDefinition,
+    GetDocumentColors,
     GetImplementation,
+    GetIncomingCalls,
     GetDocumentHighlights,
+    GetDocumentLinks,
+    GetFoldingRanges,
     GetHover,
+    GetOutgoingCalls,
     GetProjectSymbols,
     GetReferences,
+    GetRenameEdits,
+    GetSemanticTokens,
+    GetSubtypes,
+    GetSupertypes,
     GetTypeDefinition,
     InlayHints,
     JoinProject,
@@ -480,11 +536,15 @@ entity_messages!(
     OpenBufferByPath,
     OpenBufferForSymbol,
     PerformRename,
+    PrepareCallHierarchy,
     PrepareRename,
+    PrepareTypeHierarchy,
     RefreshInlayHints,
     ReloadBuffers,
     RemoveProjectCollaborator,
     RenameProjectEntry,
+    ResolveCodeLens,
+    ResolveDocumentLink,
     ResolveCompletionDocumentation,
     ResolveInlayHint,
     SaveBuffer,
@@ -2,8 +2,9 @@ mod registrar;
 
 use crate::{
     search_bar::render_nav_button, FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll,
-    ReplaceNext, SearchOptions, SelectAllMatches, SelectNextMatch, SelectPrevMatch,
-    ToggleCaseSensitive, ToggleRegex, ToggleReplace, ToggleSelection, ToggleWholeWord,
+    ReplaceNext, SearchOptions, SelectAllCaptures, SelectAllMatches, SelectNextMatch,
+    SelectPrevMatch, ToggleCaseSensitive, TogglePreserveCase, ToggleRegex, ToggleReplace,
+    ToggleSelection, ToggleWholeWord,
 };
 use any_vec::AnyVec;
 use collections::HashMap;
@@ -330,7 +331,15 @@ impl Render for BufferSearchBar {
                             &self.replacement_editor,
                             cx.theme().colors().text,
                             cx,
-                        )),
+                        ))
+                        .when(!hide_inline_icons, |div| {
+                            div.child(self.render_search_option_button(
+                                SearchOptions::PRESERVE_CASE,
+                                cx.listener(|this, _, cx| {
+                                    this.toggle_preserve_case(&TogglePreserveCase, cx)
+                                }),
+                            ))
+                        }),
                 )
                 .child(
                     h_flex()
@@ -477,6 +486,11 @@ impl BufferSearchBar {
                 this.toggle_replace(action, cx);
             }
         }));
+        registrar.register_handler(ForDeployed(|this, action: &TogglePreserveCase, cx| {
+            if this.supported_options().replacement {
+                this.toggle_preserve_case(action, cx);
+            }
+        }));
         registrar.register_handler(WithResults(|this, action: &SelectNextMatch, cx| {
             this.select_next_match(action, cx);
         }));
@@ -486,6 +500,9 @@ impl BufferSearchBar {
         registrar.register_handler(WithResults(|this, action: &SelectAllMatches, cx| {
             this.select_all_matches(action, cx);
         }));
+        registrar.register_handler(WithResults(|this, action: &SelectAllCaptures, cx| {
+            this.select_all_captures(action, cx);
+        }));
         registrar.register_handler(ForDeployed(|this, _: &editor::actions::Cancel, cx| {
             this.dismiss(&Dismiss, cx);
         }));
@@ -769,6 +786,24 @@ impl BufferSearchBar {
         }
     }
 
+    fn select_all_captures(&mut self, action: &SelectAllCaptures, cx: &mut ViewContext<Self>) {
+        if self.dismissed || self.active_match_index.is_none() {
+            return;
+        }
+        let Some(query) = self.active_search.clone().filter(|query| query.is_regex()) else {
+            return;
+        };
+        if let Some(searchable_item) = self.active_searchable_item.as_ref() {
+            if let Some(matches) = self
+                .searchable_items_with_matches
+                .get(&searchable_item.downgrade())
+            {
+                searchable_item.select_match_captures(matches, &query, &action.group, cx);
+                self.focus_editor(&FocusEditor, cx);
+            }
+        }
+    }
+
     pub fn select_match(&mut self, direction: Direction, count: usize, cx: &mut ViewContext<Self>) {
         if let Some(index) = self.active_match_index {
             if let Some(searchable_item) = self.active_searchable_item.as_ref() {
@@ -878,6 +913,10 @@ impl BufferSearchBar {
         self.toggle_search_option(SearchOptions::REGEX, cx)
     }
 
+    fn toggle_preserve_case(&mut self, _: &TogglePreserveCase, cx: &mut ViewContext<Self>) {
+        self.toggle_search_option(SearchOptions::PRESERVE_CASE, cx)
+    }
+
     fn clear_active_searchable_item_matches(&mut self, cx: &mut WindowContext) {
         if let Some(active_searchable_item) = self.active_searchable_item.as_ref() {
             self.active_match_index = None;
@@ -930,7 +969,11 @@ impl BufferSearchBar {
                         Vec::new(),
                         Vec::new(),
                     ) {
-                        Ok(query) => query.with_replacement(self.replacement(cx)),
+                        Ok(query) => query
+                            .with_replacement(self.replacement(cx))
+                            .with_preserve_case(
+                                self.search_options.contains(SearchOptions::PRESERVE_CASE),
+                            ),
                         Err(_) => {
                             self.query_contains_error = true;
                             self.clear_active_searchable_item_matches(cx);
@@ -947,7 +990,11 @@ impl BufferSearchBar {
                         Vec::new(),
                         Vec::new(),
                     ) {
-                        Ok(query) => query.with_replacement(self.replacement(cx)),
+                        Ok(query) => query
+                            .with_replacement(self.replacement(cx))
+                            .with_preserve_case(
+                                self.search_options.contains(SearchOptions::PRESERVE_CASE),
+                            ),
                         Err(_) => {
                             self.query_contains_error = true;
                             self.clear_active_searchable_item_matches(cx);
@@ -1095,7 +1142,10 @@ impl BufferSearchBar {
                             let query = query
                                 .as_ref()
                                 .clone()
-                                .with_replacement(self.replacement(cx));
+                                .with_replacement(self.replacement(cx))
+                                .with_preserve_case(
+                                    self.search_options.contains(SearchOptions::PRESERVE_CASE),
+                                );
                             searchable_item.replace(matches.at(active_index), &query, cx);
                             self.select_next_match(&SelectNextMatch, cx);
                         }
@@ -1120,7 +1170,10 @@ impl BufferSearchBar {
                         let query = query
                             .as_ref()
                             .clone()
-                            .with_replacement(self.replacement(cx));
+                            .with_replacement(self.replacement(cx))
+                            .with_preserve_case(
+                                self.search_options.contains(SearchOptions::PRESERVE_CASE),
+                            );
                         for m in matches {
                             searchable_item.replace(m, &query, cx);
                         }
@@ -1519,6 +1572,55 @@ mod tests {
         });
     }
 
+    #[gpui::test]
+    async fn test_search_select_all_captures(cx: &mut TestAppContext) {
+        let (editor, search_bar, cx) = init_test(cx);
+        search_bar
+            .update(cx, |search_bar, cx| {
+                search_bar.search("\\[(?P<digit>\\d)\\]", Some(SearchOptions::REGEX), cx)
+            })
+            .await
+            .unwrap();
+
+        let selected_texts = |editor: &View<Editor>, cx: &mut VisualTestContext| {
+            editor.update(cx, |editor, cx| {
+                let text = editor.text(cx);
+                editor
+                    .selections
+                    .all::<usize>(cx)
+                    .into_iter()
+                    .map(|selection| text[selection.range()].to_string())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        search_bar.update(cx, |search_bar, cx| {
+            search_bar.activate_current_match(cx);
+            search_bar.select_all_captures(&SelectAllCaptures::default(), cx);
+        });
+        assert_eq!(selected_texts(&editor, cx), ["1", "2", "3"]);
+
+        search_bar.update(cx, |search_bar, cx| {
+            search_bar.select_all_captures(
+                &SelectAllCaptures {
+                    group: "0".to_string(),
+                },
+                cx,
+            );
+        });
+        assert_eq!(selected_texts(&editor, cx), ["[1]", "[2]", "[3]"]);
+
+        search_bar.update(cx, |search_bar, cx| {
+            search_bar.select_all_captures(
+                &SelectAllCaptures {
+                    group: "digit".to_string(),
+                },
+                cx,
+            );
+        });
+        assert_eq!(selected_texts(&editor, cx), ["1", "2", "3"]);
+    }
+
     #[gpui::test]
     async fn test_search_select_all_matches(cx: &mut TestAppContext) {
         init_globals(cx);
@@ -2086,6 +2188,45 @@ mod tests {
         .await;
     }
 
+    #[gpui::test]
+    async fn test_replace_preserving_case(cx: &mut TestAppContext) {
+        init_globals(cx);
+        let buffer =
+            cx.new_model(|cx| Buffer::local("let fooBar = FooBar::new(FOO_BAR, foo_bar);", cx));
+        let cx = cx.add_empty_window();
+        let editor = cx.new_view(|cx| Editor::for_buffer(buffer.clone(), None, cx));
+        let search_bar = cx.new_view(|cx| {
+            let mut search_bar = BufferSearchBar::new(cx);
+            search_bar.set_active_pane_item(Some(&editor), cx);
+            search_bar.show(cx);
+            search_bar
+        });
+
+        run_replacement_test(ReplacementTestParams {
+            editor: &editor,
+            search_bar: &search_bar,
+            cx,
+            search_text: r"foo(_?)bar",
+            search_options: Some(SearchOptions::REGEX | SearchOptions::PRESERVE_CASE),
+            replacement_text: r"baz\1qux",
+            replace_all: true,
+            expected_text: "let bazqux = Bazqux::new(BAZ_QUX, baz_qux);".to_string(),
+        })
+        .await;
+
+        run_replacement_test(ReplacementTestParams {
+            editor: &editor,
+            search_bar: &search_bar,
+            cx,
+            search_text: "baz_?qux",
+            search_options: Some(SearchOptions::REGEX | SearchOptions::PRESERVE_CASE),
+            replacement_text: "newName",
+            replace_all: true,
+            expected_text: "let newname = NewName::new(NEW_NAME, new_name);".to_string(),
+        })
+        .await;
+    }
+
     #[gpui::test]
     async fn test_find_matches_in_selections_singleton_buffer_multiple_selections(
         cx: &mut TestAppContext,
@@ -0,0 +1,122 @@
+use anyhow::Context as _;
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    // Current schema shape using pseudo-rust syntax:
+    // project_search_history(
+    //   workspace_id: usize,
+    //   ix: usize,
+    //   query: String,
+    //   options: u32,
+    //   include: String,
+    //   exclude: String,
+    // )
+    //
+    // saved_searches(
+    //   workspace_id: usize,
+    //   name: String,
+    //   query: String,
+    //   options: u32,
+    //   include: String,
+    //   exclude: String,
+    // )
+    pub static ref SEARCH_DB: SearchDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE project_search_history (
+                workspace_id INTEGER NOT NULL,
+                ix INTEGER NOT NULL,
+                query TEXT NOT NULL,
+                options INTEGER NOT NULL,
+                include TEXT NOT NULL,
+                exclude TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, ix),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE saved_searches (
+                workspace_id INTEGER NOT NULL,
+                name TEXT NOT NULL,
+                query TEXT NOT NULL,
+                options INTEGER NOT NULL,
+                include TEXT NOT NULL,
+                exclude TEXT NOT NULL,
+                PRIMARY KEY(workspace_id, name),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl SearchDb {
+    query! {
+        pub fn get_project_search_history(workspace_id: WorkspaceId) -> Result<Vec<(String, u32, String, String)>> {
+            SELECT query, options, include, exclude
+            FROM project_search_history
+            WHERE workspace_id = ?
+            ORDER BY ix
+        }
+    }
+
+    /// Replaces the project search history stored for the given workspace.
+    pub async fn save_project_search_history(
+        &self,
+        workspace_id: WorkspaceId,
+        searches: Vec<(String, u32, String, String)>,
+    ) -> anyhow::Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_project_search_history", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM project_search_history WHERE workspace_id = ?
+                ))?(workspace_id)
+                .context("clearing old project search history")?;
+                for (ix, (query, options, include, exclude)) in searches.into_iter().enumerate() {
+                    conn.exec_bound(sql!(
+                        INSERT INTO project_search_history(workspace_id, ix, query, options, include, exclude)
+                        VALUES (?, ?, ?, ?, ?, ?)
+                    ))?((workspace_id, ix, query, options, include, exclude))
+                    .context("inserting project search history")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn get_saved_searches(workspace_id: WorkspaceId) -> Result<Vec<(String, String, u32, String, String)>> {
+            SELECT name, query, options, include, exclude
+            FROM saved_searches
+            WHERE workspace_id = ?
+            ORDER BY name
+        }
+    }
+
+    /// Replaces the saved searches stored for the given workspace.
+    pub async fn save_saved_searches(
+        &self,
+        workspace_id: WorkspaceId,
+        searches: Vec<(String, String, u32, String, String)>,
+    ) -> anyhow::Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_saved_searches", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM saved_searches WHERE workspace_id = ?
+                ))?(workspace_id)
+                .context("clearing old saved searches")?;
+                for (name, query, options, include, exclude) in searches {
+                    conn.exec_bound(sql!(
+                        INSERT OR REPLACE INTO saved_searches(workspace_id, name, query, options, include, exclude)
+                        VALUES (?, ?, ?, ?, ?, ?)
+                    ))?((workspace_id, name, query, options, include, exclude))
+                    .context("inserting saved search")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
@@ -1,7 +1,9 @@
 use crate::{
+    saved_searches::{self, SavedSearchesModal, SearchParams},
+    search_settings::{SearchScope, SearchScopeSettings},
     FocusSearch, NextHistoryQuery, PreviousHistoryQuery, ReplaceAll, ReplaceNext, SearchOptions,
-    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored, ToggleRegex,
-    ToggleReplace, ToggleWholeWord,
+    SelectNextMatch, SelectPrevMatch, ToggleCaseSensitive, ToggleIncludeIgnored,
+    TogglePreserveCase, ToggleRegex, ToggleReplace, ToggleStructural, ToggleWholeWord,
 };
 use anyhow::Context as _;
 use collections::{HashMap, HashSet};
@@ -9,7 +11,8 @@ use editor::{
     actions::SelectAll,
     items::active_match_index,
     scroll::{Autoscroll, Axis},
-    Anchor, Editor, EditorElement, EditorEvent, EditorStyle, MultiBuffer, MAX_TAB_TITLE_LEN,
+    Anchor, AnchorRangeExt, Editor, EditorElement, EditorEvent, EditorStyle, MultiBuffer,
+    RenamePreview, ToPoint, MAX_TAB_TITLE_LEN,
 };
 use gpui::{
     actions, div, Action, AnyElement, AnyView, AppContext, Context as _, Element, EntityId,
@@ -19,7 +22,9 @@ use gpui::{
     WeakView, WhiteSpace, WindowContext,
 };
 use menu::Confirm;
-use project::{search::SearchQuery, search_history::SearchHistoryCursor, Project, ProjectPath};
+use project::{
+    search::SearchQuery, search_history::SearchHistoryCursor, Project, ProjectPath, RenameEdits,
+};
 use settings::Settings;
 use smol::stream::StreamExt;
 use std::{
@@ -30,10 +35,10 @@ use std::{
 };
 use theme::ThemeSettings;
 use ui::{
-    h_flex, prelude::*, v_flex, Icon, IconButton, IconName, Label, LabelCommon, LabelSize,
-    Selectable, Tooltip,
+    h_flex, popover_menu, prelude::*, v_flex, ContextMenu, Icon, IconButton, IconName, Label,
+    LabelCommon, LabelSize, Selectable, Tooltip,
 };
-use util::paths::PathMatcher;
+use util::{paths::PathMatcher, ResultExt};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, ItemHandle, TabContentParams},
     searchable::{Direction, SearchableItem, SearchableItemHandle},
@@ -43,10 +48,20 @@ use workspace::{
 
 const MIN_INPUT_WIDTH_REMS: f32 = 15.;
 const MAX_INPUT_WIDTH_REMS: f32 = 30.;
+/// Rows of a match past this many are folded in the results.
+const MAX_DISPLAYED_MATCH_ROWS: u32 = 100;
 
 actions!(
     project_search,
-    [SearchInNew, ToggleFocus, NextField, ToggleFilters]
+    [
+        SearchInNew,
+        ToggleFocus,
+        NextField,
+        ToggleFilters,
+        PreviewReplaceAll,
+        SaveSearch,
+        OpenSavedSearch
+    ]
 );
 
 #[derive(Default)]
@@ -55,8 +70,11 @@ struct ActiveSettings(HashMap<WeakModel<Project>, ProjectSearchSettings>);
 impl Global for ActiveSettings {}
 
 pub fn init(cx: &mut AppContext) {
+    SearchScopeSettings::register(cx);
     cx.set_global(ActiveSettings::default());
-    cx.observe_new_views(|workspace: &mut Workspace, _cx| {
+    saved_searches::init(cx);
+    cx.observe_new_views(|workspace: &mut Workspace, cx| {
+        saved_searches::load(workspace, cx);
         register_workspace_action(workspace, move |search_bar, _: &FocusSearch, cx| {
             search_bar.focus_search(cx);
         });
@@ -72,6 +90,12 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action(workspace, move |search_bar, _: &ToggleRegex, cx| {
             search_bar.toggle_search_option(SearchOptions::REGEX, cx);
         });
+        register_workspace_action(workspace, move |search_bar, _: &ToggleStructural, cx| {
+            search_bar.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+        });
+        register_workspace_action(workspace, move |search_bar, _: &TogglePreserveCase, cx| {
+            search_bar.toggle_search_option(SearchOptions::PRESERVE_CASE, cx);
+        });
         register_workspace_action(workspace, move |search_bar, action: &ToggleReplace, cx| {
             search_bar.toggle_replace(action, cx)
         });
@@ -92,6 +116,15 @@ pub fn init(cx: &mut AppContext) {
         register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
             ProjectSearchView::search_in_new(workspace, action, cx)
         });
+        register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
+            ProjectSearchView::preview_replace_all(workspace, action, cx)
+        });
+        register_workspace_action_for_present_search(workspace, |workspace, action, cx| {
+            ProjectSearchView::save_search(workspace, action, cx)
+        });
+        workspace.register_action(|workspace, _: &OpenSavedSearch, cx| {
+            SavedSearchesModal::toggle(workspace, None, cx);
+        });
 
         // Both on present and dismissed search, we need to unconditionally handle those actions to focus from the editor.
         workspace.register_action(move |workspace, action: &DeploySearch, cx| {
@@ -144,6 +177,8 @@ pub struct ProjectSearchView {
     panels_with_errors: HashSet<InputPanel>,
     active_match_index: Option<usize>,
     search_id: usize,
+    /// The number of matches of the current search whose rows were checked for folding.
+    folded_match_count: usize,
     query_editor_was_focused: bool,
     included_files_editor: View<Editor>,
     excluded_files_editor: View<Editor>,
@@ -482,6 +517,11 @@ impl Item for ProjectSearchView {
             .update(cx, |editor, cx| editor.navigate(data, cx))
     }
 
+    fn handle_drop(&mut self, dropped: &dyn Any, cx: &mut ViewContext<Self>) -> bool {
+        self.results_editor
+            .update(cx, |editor, cx| editor.handle_drop(dropped, cx))
+    }
+
     fn to_item_events(event: &Self::Event, mut f: impl FnMut(ItemEvent)) {
         match event {
             ViewEvent::UpdateTab => {
@@ -561,7 +601,9 @@ impl ProjectSearchView {
 
         let query = self.model.read(cx).active_query.clone();
         if let Some(query) = query {
-            let query = query.with_replacement(self.replacement(cx));
+            let query = query
+                .with_replacement(self.replacement(cx))
+                .with_preserve_case(self.search_options.contains(SearchOptions::PRESERVE_CASE));
 
             // TODO: Do we need the clone here?
             let mat = self.model.read(cx).match_ranges[active_index].clone();
@@ -582,7 +624,10 @@ impl ProjectSearchView {
         let Some(query) = self.model.read(cx).active_query.as_ref() else {
             return;
         };
-        let query = query.clone().with_replacement(self.replacement(cx));
+        let query = query
+            .clone()
+            .with_replacement(self.replacement(cx))
+            .with_preserve_case(self.search_options.contains(SearchOptions::PRESERVE_CASE));
 
         let match_ranges = self
             .model
@@ -602,6 +647,84 @@ impl ProjectSearchView {
         });
     }
 
+    /// The edits that replacing every match with `query`'s replacement would make, grouped by
+    /// buffer.
+    fn replacement_edits(&self, query: &SearchQuery, cx: &AppContext) -> RenameEdits {
+        let model = self.model.read(cx);
+        let excerpts = model.excerpts.read(cx);
+        let snapshot = excerpts.snapshot(cx);
+        let mut edits_by_buffer = Vec::new();
+        for range in &model.match_ranges {
+            let Some(buffer) = range.start.buffer_id.and_then(|id| excerpts.buffer(id)) else {
+                continue;
+            };
+            let offset_range = range.to_offset(&snapshot);
+            let replacement = if query.is_structural() {
+                snapshot
+                    .excerpt_containing(offset_range.clone())
+                    .and_then(|excerpt| {
+                        query.structural_replacement_for(
+                            excerpt.buffer(),
+                            excerpt.map_range_to_buffer(offset_range),
+                        )
+                    })
+            } else {
+                let text = snapshot.text_for_range(offset_range).collect::<String>();
+                query
+                    .replacement_for(&text)
+                    .map(|replacement| replacement.into_owned())
+            };
+            let Some(replacement) = replacement else {
+                continue;
+            };
+
+            let edit = (range.start.text_anchor..range.end.text_anchor, replacement);
+            match edits_by_buffer.last_mut() {
+                Some((last_buffer, edits)) if *last_buffer == buffer => edits.push(edit),
+                _ => edits_by_buffer.push((buffer, vec![edit])),
+            }
+        }
+        RenameEdits(edits_by_buffer)
+    }
+
+    /// Opens a preview of replacing every match, where matches or whole files can be left out
+    /// before the rest are applied as a single project transaction.
+    fn preview_replace_all(
+        workspace: &mut Workspace,
+        _: &PreviewReplaceAll,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(search_view) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+        else {
+            return;
+        };
+        let (edits, title) = {
+            let search_view = search_view.read(cx);
+            let Some(query) = search_view.model.read(cx).active_query.clone() else {
+                return;
+            };
+            let replacement = search_view.replacement(cx);
+            let title = format!("Replace: {} → {}", query.as_str(), replacement);
+            let query = query.with_replacement(replacement).with_preserve_case(
+                search_view
+                    .search_options
+                    .contains(SearchOptions::PRESERVE_CASE),
+            );
+            (search_view.replacement_edits(&query, cx), title)
+        };
+        if edits.0.is_empty() {
+            return;
+        }
+
+        let project = workspace.project().clone();
+        let workspace_handle = cx.view().downgrade();
+        let preview =
+            cx.new_view(|cx| RenamePreview::new(edits, title, project, workspace_handle, cx));
+        workspace.add_item_to_active_pane(Box::new(preview), None, cx);
+    }
+
     fn new(
         model: Model<ProjectSearch>,
         cx: &mut ViewContext<Self>,
@@ -711,6 +834,7 @@ impl ProjectSearchView {
             focus_handle,
             replacement_editor,
             search_id: model.read(cx).search_id,
+            folded_match_count: 0,
             model,
             query_editor,
             results_editor,
@@ -749,6 +873,99 @@ impl ProjectSearchView {
         });
     }
 
+    /// Adds `dir_path` to the exclude filter of the search in the active pane, or of a new one if
+    /// there is none, and searches again.
+    pub fn exclude_directory_from_search(
+        workspace: &mut Workspace,
+        dir_path: &Path,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(filter_str) = dir_path.to_str() else {
+            return;
+        };
+
+        let search = Self::active_or_new_search(workspace, cx);
+        search.update(cx, |search, cx| {
+            search.excluded_files_editor.update(cx, |editor, cx| {
+                let text = editor.text(cx);
+                let text = text.trim_end_matches([',', ' ']);
+                if text.is_empty() {
+                    editor.set_text(filter_str, cx);
+                } else {
+                    editor.set_text(format!("{text}, {filter_str}"), cx);
+                }
+            });
+            search.filters_enabled = true;
+            if search.model.read(cx).active_query.is_some() {
+                search.search(cx);
+            }
+            search.focus_query_editor(cx)
+        });
+    }
+
+    /// Runs a saved search in the project search of the active pane, or in a new one if there is
+    /// none.
+    pub(crate) fn run_saved_search(
+        workspace: &mut Workspace,
+        params: &SearchParams,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let search = Self::active_or_new_search(workspace, cx);
+        search.update(cx, |search, cx| {
+            search.set_search_params(params, cx);
+            search.search(cx);
+        });
+    }
+
+    /// Activates the project search of the active pane, or adds a new one if there is none.
+    fn active_or_new_search(
+        workspace: &mut Workspace,
+        cx: &mut ViewContext<Workspace>,
+    ) -> View<Self> {
+        let existing = workspace
+            .active_pane()
+            .read(cx)
+            .items()
+            .find_map(|item| item.downcast::<ProjectSearchView>());
+        if let Some(existing) = existing {
+            workspace.activate_item(&existing, cx);
+            existing
+        } else {
+            let model = cx.new_model(|cx| ProjectSearch::new(workspace.project().clone(), cx));
+            let search = cx.new_view(|cx| ProjectSearchView::new(model, cx, None));
+            workspace.add_item_to_active_pane(Box::new(search.clone()), None, cx);
+            search
+        }
+    }
+
+    /// Asks for a name to save the search of the active pane under.
+    fn save_search(workspace: &mut Workspace, _: &SaveSearch, cx: &mut ViewContext<Workspace>) {
+        let Some(search_view) = workspace
+            .active_item(cx)
+            .and_then(|item| item.downcast::<ProjectSearchView>())
+        else {
+            return;
+        };
+        let params = search_view.read(cx).search_params(cx);
+        if !params.query.is_empty() {
+            SavedSearchesModal::toggle(workspace, Some(params), cx);
+        }
+    }
+
+    /// Replaces the filters with those of a scope from the `search_scopes` setting and searches
+    /// again.
+    fn apply_search_scope(&mut self, scope: &SearchScope, cx: &mut ViewContext<Self>) {
+        self.included_files_editor
+            .update(cx, |editor, cx| editor.set_text(scope.include.as_str(), cx));
+        self.excluded_files_editor
+            .update(cx, |editor, cx| editor.set_text(scope.exclude.as_str(), cx));
+        self.filters_enabled = true;
+        if self.model.read(cx).active_query.is_some() {
+            self.search(cx);
+        }
+        cx.notify();
+    }
+
     // Re-activate the most recently activated search in this pane or the most recent if it has been closed.
     // If no search exists in the workspace, create a new one.
     fn deploy_search(
@@ -856,6 +1073,44 @@ impl ProjectSearchView {
     fn search(&mut self, cx: &mut ViewContext<Self>) {
         if let Some(query) = self.build_search_query(cx) {
             self.model.update(cx, |model, cx| model.search(query, cx));
+            let project = self.model.read(cx).project.clone();
+            saved_searches::record_search(&project, self.search_params(cx), cx);
+        }
+    }
+
+    /// The query, options and filters the view searches with.
+    fn search_params(&self, cx: &AppContext) -> SearchParams {
+        SearchParams {
+            query: self.query_editor.read(cx).text(cx),
+            options: self.search_options,
+            include: self.included_files_editor.read(cx).text(cx),
+            exclude: self.excluded_files_editor.read(cx).text(cx),
+        }
+    }
+
+    /// Fills in the query, options and filters of a search from the history or a saved one.
+    fn set_search_params(&mut self, params: &SearchParams, cx: &mut ViewContext<Self>) {
+        self.set_query(&params.query, cx);
+        self.search_options = params.options;
+        self.included_files_editor.update(cx, |editor, cx| {
+            editor.set_text(params.include.as_str(), cx)
+        });
+        self.excluded_files_editor.update(cx, |editor, cx| {
+            editor.set_text(params.exclude.as_str(), cx)
+        });
+        if !params.include.is_empty() || !params.exclude.is_empty() {
+            self.filters_enabled = true;
+        }
+        cx.notify();
+    }
+
+    /// Fills in a query recalled from the history, along with the options and filters it was
+    /// last searched with.
+    fn set_history_query(&mut self, query: &str, cx: &mut ViewContext<Self>) {
+        let project = self.model.read(cx).project.clone();
+        match saved_searches::recorded_search(&project, query, cx) {
+            Some(params) => self.set_search_params(&params, cx),
+            None => self.set_query(query, cx),
         }
     }
 
@@ -898,7 +1153,17 @@ impl ProjectSearchView {
                 }
             };
 
-        let query = if self.search_options.contains(SearchOptions::REGEX) {
+        let query = if self.search_options.contains(SearchOptions::STRUCTURAL) {
+            // The query is only compiled against the grammars of the searched buffers,
+            // so it can't be marked as invalid here.
+            SearchQuery::structural(
+                text,
+                self.search_options.contains(SearchOptions::INCLUDE_IGNORED),
+                included_files,
+                excluded_files,
+            )
+            .log_err()
+        } else if self.search_options.contains(SearchOptions::REGEX) {
             match SearchQuery::regex(
                 text,
                 self.search_options.contains(SearchOptions::WHOLE_WORD),
@@ -1022,7 +1287,32 @@ impl ProjectSearchView {
             self.update_match_index(cx);
             let prev_search_id = mem::replace(&mut self.search_id, self.model.read(cx).search_id);
             let is_new_search = self.search_id != prev_search_id;
+            if is_new_search {
+                self.folded_match_count = 0;
+            }
+            let first_new_match = mem::replace(&mut self.folded_match_count, match_ranges.len());
             self.results_editor.update(cx, |editor, cx| {
+                // Matches spanning many rows are folded past their first ones, so that a
+                // pattern matching most of a file doesn't fill the results with it.
+                let snapshot = editor.buffer().read(cx).snapshot(cx);
+                let placeholder = editor.default_fold_placeholder(cx);
+                let folds = match_ranges[first_new_match..]
+                    .iter()
+                    .filter_map(|range| {
+                        let start = range.start.to_point(&snapshot).row;
+                        let end = range.end.to_point(&snapshot).row;
+                        (end - start > MAX_DISPLAYED_MATCH_ROWS).then(|| {
+                            let fold_start =
+                                language::Point::new(start + MAX_DISPLAYED_MATCH_ROWS, 0);
+                            (
+                                fold_start..language::Point::new(end, 0),
+                                placeholder.clone(),
+                            )
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                editor.fold_ranges(folds, false, cx);
+
                 if is_new_search {
                     let range_to_select = match_ranges
                         .first()
@@ -1247,7 +1537,7 @@ impl ProjectSearchBar {
                         String::new()
                     }
                 });
-                search_view.set_query(&new_query, cx);
+                search_view.set_history_query(&new_query, cx);
             });
         }
     }
@@ -1265,7 +1555,7 @@ impl ProjectSearchBar {
                         .current(&search_view.model.read(cx).search_history_cursor)
                         .map(str::to_string)
                     {
-                        search_view.set_query(&new_query, cx);
+                        search_view.set_history_query(&new_query, cx);
                         return;
                     }
                 }
@@ -1278,7 +1568,7 @@ impl ProjectSearchBar {
                             .map(str::to_string)
                     })
                 }) {
-                    search_view.set_query(&new_query, cx);
+                    search_view.set_history_query(&new_query, cx);
                 }
             });
         }
@@ -1373,6 +1663,12 @@ impl Render for ProjectSearchBar {
                         cx.listener(|this, _, cx| {
                             this.toggle_search_option(SearchOptions::REGEX, cx);
                         }),
+                    ))
+                    .child(SearchOptions::STRUCTURAL.as_button(
+                        self.is_option_enabled(SearchOptions::STRUCTURAL, cx),
+                        cx.listener(|this, _, cx| {
+                            this.toggle_search_option(SearchOptions::STRUCTURAL, cx);
+                        }),
                     )),
             );
 
@@ -1403,6 +1699,11 @@ impl Render for ProjectSearchBar {
                                 .unwrap_or_default(),
                         )
                         .tooltip(|cx| Tooltip::for_action("Toggle replace", &ToggleReplace, cx)),
+                )
+                .child(
+                    IconButton::new("project-search-save", IconName::Pin)
+                        .on_click(|_, cx| cx.dispatch_action(SaveSearch.boxed_clone()))
+                        .tooltip(|cx| Tooltip::for_action("Save search", &SaveSearch, cx)),
                 ),
         );
 
@@ -1485,7 +1786,13 @@ impl Render for ProjectSearchBar {
                 .border_1()
                 .border_color(cx.theme().colors().border)
                 .rounded_lg()
-                .child(self.render_text_input(&search.replacement_editor, cx));
+                .child(self.render_text_input(&search.replacement_editor, cx))
+                .child(SearchOptions::PRESERVE_CASE.as_button(
+                    search.search_options.contains(SearchOptions::PRESERVE_CASE),
+                    cx.listener(|this, _, cx| {
+                        this.toggle_search_option(SearchOptions::PRESERVE_CASE, cx);
+                    }),
+                ));
             let replace_actions = h_flex().when(search.replace_enabled, |this| {
                 this.child(
                     IconButton::new("project-search-replace-next", IconName::ReplaceNext)
@@ -1509,6 +1816,13 @@ impl Render for ProjectSearchBar {
                         }))
                         .tooltip(|cx| Tooltip::for_action("Replace all matches", &ReplaceAll, cx)),
                 )
+                .child(
+                    IconButton::new("project-search-preview-replace-all", IconName::Reveal)
+                        .on_click(|_, cx| cx.dispatch_action(PreviewReplaceAll.boxed_clone()))
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Preview replacements", &PreviewReplaceAll, cx)
+                        }),
+                )
             });
             h_flex()
                 .gap_2()
@@ -1516,6 +1830,7 @@ impl Render for ProjectSearchBar {
                 .child(replace_actions)
         });
 
+        let scopes = SearchScopeSettings::get_global(cx).search_scopes.clone();
         let filter_line = search.filters_enabled.then(|| {
             h_flex()
                 .w_full()
@@ -1556,6 +1871,31 @@ impl Render for ProjectSearchBar {
                         .rounded_lg()
                         .child(self.render_text_input(&search.excluded_files_editor, cx)),
                 )
+                .when(!scopes.is_empty(), |this| {
+                    let search_view = self.active_project_search.clone();
+                    this.child(
+                        popover_menu("project-search-scopes")
+                            .trigger(
+                                IconButton::new("project-search-scopes-button", IconName::FileTree)
+                                    .tooltip(|cx| Tooltip::text("Search scopes", cx)),
+                            )
+                            .menu(move |cx| {
+                                let search_view = search_view.clone()?;
+                                let scopes = scopes.clone();
+                                Some(ContextMenu::build(cx, move |mut menu, _| {
+                                    for (name, scope) in scopes {
+                                        let search_view = search_view.clone();
+                                        menu = menu.entry(name, None, move |cx| {
+                                            search_view.update(cx, |search_view, cx| {
+                                                search_view.apply_search_scope(&scope, cx)
+                                            })
+                                        });
+                                    }
+                                    menu
+                                }))
+                            }),
+                    )
+                })
         });
 
         v_flex()
@@ -1820,6 +2160,41 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_project_search_folds_long_matches(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": format!("start\n{}end\n", "line\n".repeat(150)),
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = cx.add_window(|cx| ProjectSearchView::new(search.clone(), cx, None));
+        search_view
+            .update(cx, |search_view, cx| {
+                search_view.toggle_search_option(SearchOptions::REGEX, cx)
+            })
+            .unwrap();
+
+        // The match is kept whole, but only its first rows are shown.
+        perform_search(search_view, "(?s)start.*end", cx);
+        search_view
+            .update(cx, |search_view, cx| {
+                assert_eq!(search_view.model.read(cx).match_ranges.len(), 1);
+                let display_text = search_view
+                    .results_editor
+                    .update(cx, |editor, cx| editor.display_text(cx));
+                assert_eq!(display_text.matches("line").count(), 99);
+                assert!(display_text.contains("⋯end"));
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_deploy_project_search_focus(cx: &mut TestAppContext) {
         init_test(cx);
@@ -2470,6 +2845,68 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_exclude_directory_from_search(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "a": {
+                    "one.rs": "const ONE: usize = 1;",
+                },
+                "b": {
+                    "two.rs": "const TWO: usize = 2;",
+                },
+                "c": {
+                    "three.rs": "const THREE: usize = 3;",
+                },
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let search = cx.new_model(|cx| ProjectSearch::new(project, cx));
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                let search_view = cx.new_view(|cx| ProjectSearchView::new(search, cx, None));
+                workspace.add_item_to_active_pane(Box::new(search_view.clone()), None, cx);
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |editor, cx| editor.set_text("const", cx));
+                    search_view.search(cx);
+                });
+                search_view
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::exclude_directory_from_search(workspace, Path::new("a"), cx);
+                ProjectSearchView::exclude_directory_from_search(workspace, Path::new("b"), cx);
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    assert!(search_view.filters_enabled);
+                    assert_eq!(search_view.excluded_files_editor.read(cx).text(cx), "a, b");
+                    assert_eq!(
+                        search_view
+                            .results_editor
+                            .update(cx, |editor, cx| editor.display_text(cx)),
+                        "\n\n\nconst THREE: usize = 3;\n",
+                        "Excluded directories should be left out of the search again"
+                    );
+                });
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_search_query_history(cx: &mut TestAppContext) {
         init_test(cx);
@@ -2788,6 +3225,104 @@ pub mod tests {
             .unwrap();
     }
 
+    #[gpui::test]
+    async fn test_search_query_history_restores_options_and_filters(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project, cx));
+        let workspace = window.root(cx).unwrap();
+        let search_bar = window.build_view(cx, |_| ProjectSearchBar::new());
+
+        window
+            .update(cx, {
+                let search_bar = search_bar.clone();
+                move |workspace, cx| {
+                    workspace.panes()[0].update(cx, move |pane, cx| {
+                        pane.toolbar()
+                            .update(cx, |toolbar, cx| toolbar.add_item(search_bar, cx))
+                    });
+                    ProjectSearchView::new_search(workspace, &workspace::NewSearch, cx)
+                }
+            })
+            .unwrap();
+        let search_view = cx.read(|cx| {
+            workspace
+                .read(cx)
+                .active_pane()
+                .read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<ProjectSearchView>())
+                .unwrap()
+        });
+
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.search_options = SearchOptions::CASE_SENSITIVE;
+                    search_view
+                        .included_files_editor
+                        .update(cx, |editor, cx| editor.set_text("one.rs", cx));
+                    search_view
+                        .query_editor
+                        .update(cx, |editor, cx| editor.set_text("ONE", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view.search_options = SearchOptions::NONE;
+                    search_view
+                        .included_files_editor
+                        .update(cx, |editor, cx| editor.set_text("", cx));
+                    search_view
+                        .query_editor
+                        .update(cx, |editor, cx| editor.set_text("TWO", cx));
+                    search_view.search(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        window
+            .update(cx, |_, cx| {
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.previous_history_query(&PreviousHistoryQuery, cx);
+                });
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "ONE");
+                    assert_eq!(search_view.search_options, SearchOptions::CASE_SENSITIVE);
+                    assert_eq!(
+                        search_view.included_files_editor.read(cx).text(cx),
+                        "one.rs"
+                    );
+                    assert!(search_view.filters_enabled);
+                });
+
+                search_bar.update(cx, |search_bar, cx| {
+                    search_bar.next_history_query(&NextHistoryQuery, cx);
+                });
+                search_view.update(cx, |search_view, cx| {
+                    assert_eq!(search_view.query_editor.read(cx).text(cx), "TWO");
+                    assert_eq!(search_view.search_options, SearchOptions::NONE);
+                    assert_eq!(search_view.included_files_editor.read(cx).text(cx), "");
+                });
+            })
+            .unwrap();
+    }
+
     #[gpui::test]
     async fn test_search_query_history_with_multiple_views(cx: &mut TestAppContext) {
         init_test(cx);
@@ -3225,6 +3760,82 @@ pub mod tests {
             .expect("unable to update search view");
     }
 
+    #[gpui::test]
+    async fn test_preview_replace_all(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.background_executor.clone());
+        fs.insert_tree(
+            "/dir",
+            json!({
+                "one.rs": "const ONE: usize = 1;",
+                "two.rs": "const TWO: usize = one::ONE + one::ONE;",
+                "three.rs": "const THREE: usize = one::ONE + two::TWO;",
+            }),
+        )
+        .await;
+        let project = Project::test(fs.clone(), ["/dir".as_ref()], cx).await;
+        let window = cx.add_window(|cx| Workspace::test_new(project.clone(), cx));
+        let search = cx.new_model(|cx| ProjectSearch::new(project.clone(), cx));
+        let search_view = window
+            .update(cx, |workspace, cx| {
+                let search_view = cx.new_view(|cx| ProjectSearchView::new(search, cx, None));
+                workspace.add_item_to_active_pane(Box::new(search_view.clone()), None, cx);
+                search_view
+            })
+            .unwrap();
+        window
+            .update(cx, |_, cx| {
+                search_view.update(cx, |search_view, cx| {
+                    search_view
+                        .query_editor
+                        .update(cx, |editor, cx| editor.set_text("ONE", cx));
+                    search_view
+                        .replacement_editor
+                        .update(cx, |editor, cx| editor.set_text("UNO", cx));
+                    search_view.search(cx);
+                })
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        let preview = window
+            .update(cx, |workspace, cx| {
+                ProjectSearchView::preview_replace_all(workspace, &PreviewReplaceAll, cx);
+                workspace.active_item_as::<RenamePreview>(cx).unwrap()
+            })
+            .unwrap();
+        // Nothing is replaced until the preview is applied, and only the matches that are still
+        // included then get replaced. Files are listed by path: one.rs, three.rs, two.rs.
+        window
+            .update(cx, |_, cx| {
+                let text = search_view.read(cx).results_editor.read(cx).text(cx);
+                assert!(!text.contains("UNO"), "{text:?}");
+                preview.update(cx, |preview, cx| {
+                    preview.set_file_accepted(1, false, cx);
+                    preview.set_edit_accepted(2, 1, false, cx);
+                    preview.apply(cx);
+                });
+            })
+            .unwrap();
+        cx.background_executor.run_until_parked();
+
+        for (path, expected_text) in [
+            ("/dir/one.rs", "const UNO: usize = 1;"),
+            ("/dir/two.rs", "const TWO: usize = one::UNO + one::ONE;"),
+            ("/dir/three.rs", "const THREE: usize = one::ONE + two::TWO;"),
+        ] {
+            let buffer = project
+                .update(cx, |project, cx| project.open_local_buffer(path, cx))
+                .await
+                .unwrap();
+            assert_eq!(
+                buffer.read_with(cx, |buffer, _| buffer.text()),
+                expected_text
+            );
+        }
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings = SettingsStore::test(cx);
@@ -0,0 +1,490 @@
+use std::sync::Arc;
+
+use collections::{HashMap, HashSet};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Global, Model, Render,
+    Task, UpdateGlobal, View, ViewContext, WeakModel, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use project::{search_history::SearchHistoryCursor, Project};
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{ModalView, Workspace, WorkspaceId};
+
+use crate::{persistence::SEARCH_DB, ProjectSearchView, SearchOptions};
+
+/// What a project search was run with, to recall it from the history or run it again once saved.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct SearchParams {
+    pub query: String,
+    pub options: SearchOptions,
+    pub include: String,
+    pub exclude: String,
+}
+
+impl SearchParams {
+    fn from_row((query, options, include, exclude): (String, u32, String, String)) -> Self {
+        Self {
+            query,
+            options: SearchOptions::from_bits_truncate(options as u8),
+            include,
+            exclude,
+        }
+    }
+
+    fn to_row(&self) -> (String, u32, String, String) {
+        (
+            self.query.clone(),
+            self.options.bits() as u32,
+            self.include.clone(),
+            self.exclude.clone(),
+        )
+    }
+
+    /// A short description of the options and filters, shown next to the query.
+    fn details(&self) -> String {
+        let mut details = self
+            .options
+            .iter()
+            .map(|option| option.label().to_string())
+            .collect::<Vec<_>>();
+        if !self.include.is_empty() {
+            details.push(format!("Include: {}", self.include));
+        }
+        if !self.exclude.is_empty() {
+            details.push(format!("Exclude: {}", self.exclude));
+        }
+        details.join(", ")
+    }
+}
+
+#[derive(Clone, Debug)]
+struct SavedSearch {
+    name: String,
+    params: SearchParams,
+}
+
+/// The searches of a project that are remembered in the database of its workspace.
+#[derive(Default)]
+struct ProjectSearches {
+    database_id: Option<WorkspaceId>,
+    /// The options and filters each query of the project's search history was last run with.
+    history: HashMap<String, SearchParams>,
+    saved: Vec<SavedSearch>,
+}
+
+#[derive(Default)]
+struct RememberedSearches(HashMap<WeakModel<Project>, ProjectSearches>);
+
+impl Global for RememberedSearches {}
+
+impl RememberedSearches {
+    fn project_mut(&mut self, project: &Model<Project>) -> &mut ProjectSearches {
+        self.0.entry(project.downgrade()).or_default()
+    }
+}
+
+pub(crate) fn init(cx: &mut AppContext) {
+    cx.set_global(RememberedSearches::default());
+}
+
+/// Loads the project search history and the saved searches of a workspace.
+pub(crate) fn load(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let Some(database_id) = workspace.database_id() else {
+        return;
+    };
+    let project = workspace.project().clone();
+    RememberedSearches::update_global(cx, |searches, _| {
+        searches.project_mut(&project).database_id = Some(database_id);
+    });
+    cx.spawn(|_, mut cx| async move {
+        let (history, saved) = cx
+            .background_executor()
+            .spawn(async move {
+                anyhow::Ok((
+                    SEARCH_DB.get_project_search_history(database_id)?,
+                    SEARCH_DB.get_saved_searches(database_id)?,
+                ))
+            })
+            .await?;
+        let history = history
+            .into_iter()
+            .map(SearchParams::from_row)
+            .collect::<Vec<_>>();
+        project.update(&mut cx, |project, _| {
+            let mut cursor = SearchHistoryCursor::default();
+            for params in &history {
+                project
+                    .search_history_mut()
+                    .add(&mut cursor, params.query.clone());
+            }
+        })?;
+        cx.update(|cx| {
+            RememberedSearches::update_global(cx, |searches, _| {
+                let searches = searches.project_mut(&project);
+                for params in history {
+                    searches.history.insert(params.query.clone(), params);
+                }
+                searches.saved = saved
+                    .into_iter()
+                    .map(|(name, query, options, include, exclude)| SavedSearch {
+                        name,
+                        params: SearchParams::from_row((query, options, include, exclude)),
+                    })
+                    .collect();
+            })
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// Remembers the options and filters of a search that was added to the project's search
+/// history, and stores the history.
+pub(crate) fn record_search(project: &Model<Project>, params: SearchParams, cx: &mut AppContext) {
+    let queries = project.read(cx).search_history().queries().to_vec();
+    let history = RememberedSearches::update_global(cx, |searches, _| {
+        let searches = searches.project_mut(project);
+        searches.history.insert(params.query.clone(), params);
+        let remembered_queries = queries.iter().collect::<HashSet<_>>();
+        searches
+            .history
+            .retain(|query, _| remembered_queries.contains(query));
+        let rows = queries
+            .iter()
+            .map(|query| match searches.history.get(query) {
+                Some(params) => params.to_row(),
+                None => (query.clone(), 0, String::new(), String::new()),
+            })
+            .collect::<Vec<_>>();
+        Some((searches.database_id?, rows))
+    });
+    if let Some((database_id, rows)) = history {
+        cx.background_executor()
+            .spawn(async move {
+                SEARCH_DB
+                    .save_project_search_history(database_id, rows)
+                    .await
+                    .log_err()
+            })
+            .detach();
+    }
+}
+
+/// The options and filters a query of the project's search history was last run with.
+pub(crate) fn recorded_search(
+    project: &Model<Project>,
+    query: &str,
+    cx: &AppContext,
+) -> Option<SearchParams> {
+    cx.try_global::<RememberedSearches>()?
+        .0
+        .get(&project.downgrade())?
+        .history
+        .get(query)
+        .cloned()
+}
+
+fn saved_searches(project: &Model<Project>, cx: &AppContext) -> Vec<SavedSearch> {
+    cx.try_global::<RememberedSearches>()
+        .and_then(|searches| searches.0.get(&project.downgrade()))
+        .map(|searches| searches.saved.clone())
+        .unwrap_or_default()
+}
+
+/// Changes the saved searches of the project and stores them.
+fn update_saved_searches(
+    project: &Model<Project>,
+    cx: &mut AppContext,
+    update: impl FnOnce(&mut Vec<SavedSearch>),
+) {
+    let saved = RememberedSearches::update_global(cx, |searches, _| {
+        let searches = searches.project_mut(project);
+        update(&mut searches.saved);
+        searches.saved.sort_by(|a, b| a.name.cmp(&b.name));
+        let rows = searches
+            .saved
+            .iter()
+            .map(|search| {
+                let (query, options, include, exclude) = search.params.to_row();
+                (search.name.clone(), query, options, include, exclude)
+            })
+            .collect::<Vec<_>>();
+        Some((searches.database_id?, rows))
+    });
+    if let Some((database_id, rows)) = saved {
+        cx.background_executor()
+            .spawn(async move {
+                SEARCH_DB
+                    .save_saved_searches(database_id, rows)
+                    .await
+                    .log_err()
+            })
+            .detach();
+    }
+}
+
+/// Lists the saved searches of the project to run one of them, or names a search to save it.
+pub(crate) struct SavedSearchesModal {
+    picker: View<Picker<SavedSearchesDelegate>>,
+}
+
+impl SavedSearchesModal {
+    /// Opens the list of saved searches, or the prompt to name the given search when saving it.
+    pub(crate) fn toggle(
+        workspace: &mut Workspace,
+        search_to_save: Option<SearchParams>,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let project = workspace.project().clone();
+        let workspace_handle = cx.view().downgrade();
+        workspace.toggle_modal(cx, move |cx| {
+            let delegate = SavedSearchesDelegate {
+                modal: cx.view().downgrade(),
+                workspace: workspace_handle,
+                searches: saved_searches(&project, cx),
+                project,
+                matches: Vec::new(),
+                selected_index: 0,
+                search_to_save,
+                query: String::new(),
+            };
+            Self {
+                picker: cx.new_view(|cx| Picker::uniform_list(delegate, cx)),
+            }
+        });
+    }
+}
+
+impl Render for SavedSearchesModal {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for SavedSearchesModal {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for SavedSearchesModal {}
+impl ModalView for SavedSearchesModal {}
+
+pub(crate) struct SavedSearchesDelegate {
+    modal: WeakView<SavedSearchesModal>,
+    workspace: WeakView<Workspace>,
+    project: Model<Project>,
+    searches: Vec<SavedSearch>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+    /// The search being saved, under the name typed in the query.
+    search_to_save: Option<SearchParams>,
+    query: String,
+}
+
+impl SavedSearchesDelegate {
+    fn remove_saved_search(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(name) = self
+            .matches
+            .get(ix)
+            .map(|mat| self.searches[mat.candidate_id].name.clone())
+        else {
+            return;
+        };
+        update_saved_searches(&self.project, cx, |searches| {
+            searches.retain(|search| search.name != name)
+        });
+        self.searches.retain(|search| search.name != name);
+        cx.notify();
+    }
+}
+
+impl PickerDelegate for SavedSearchesDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _: &mut WindowContext) -> Arc<str> {
+        if self.search_to_save.is_some() {
+            "Name the search...".into()
+        } else {
+            "Run a saved search...".into()
+        }
+    }
+
+    fn no_matches_text(&self, _: &mut WindowContext) -> SharedString {
+        "No saved searches".into()
+    }
+
+    fn match_count(&self) -> usize {
+        if self.search_to_save.is_some() {
+            1
+        } else {
+            self.matches.len()
+        }
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        self.query = query.clone();
+        if self.search_to_save.is_some() {
+            self.selected_index = 0;
+            return Task::ready(());
+        }
+        let candidates = self
+            .searches
+            .iter()
+            .enumerate()
+            .map(|(id, search)| {
+                StringMatchCandidate::new(id, format!("{} {}", search.name, search.params.query))
+            })
+            .collect::<Vec<_>>();
+        let background = cx.background_executor().clone();
+        cx.spawn(|picker, mut cx| async move {
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    100,
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+            picker
+                .update(&mut cx, |picker, cx| {
+                    let delegate = &mut picker.delegate;
+                    delegate.matches = matches;
+                    delegate.selected_index = delegate
+                        .selected_index
+                        .min(delegate.matches.len().saturating_sub(1));
+                    cx.notify();
+                })
+                .log_err();
+        })
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if let Some(params) = self.search_to_save.take() {
+            let name = self.query.trim().to_string();
+            if name.is_empty() {
+                self.search_to_save = Some(params);
+                return;
+            }
+            update_saved_searches(&self.project, cx, |searches| {
+                searches.retain(|search| search.name != name);
+                searches.push(SavedSearch { name, params });
+            });
+        } else if let Some(mat) = self.matches.get(self.selected_index) {
+            let params = self.searches[mat.candidate_id].params.clone();
+            self.workspace
+                .update(cx, |workspace, cx| {
+                    ProjectSearchView::run_saved_search(workspace, &params, cx)
+                })
+                .log_err();
+        }
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.modal
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        if let Some(params) = &self.search_to_save {
+            let name = self.query.trim();
+            let label = if name.is_empty() {
+                format!("Type a name for the search \"{}\"", params.query)
+            } else {
+                format!("Save the search \"{}\" as \"{name}\"", params.query)
+            };
+            return Some(
+                ListItem::new(ix)
+                    .inset(true)
+                    .spacing(ListItemSpacing::Sparse)
+                    .selected(selected)
+                    .child(Label::new(label)),
+            );
+        }
+
+        let mat = self.matches.get(ix)?;
+        let search = self.searches.get(mat.candidate_id)?;
+        let name_len = search.name.len();
+        let name_positions = mat
+            .positions
+            .iter()
+            .copied()
+            .filter(|position| *position < name_len)
+            .collect();
+        let query_positions = mat
+            .positions
+            .iter()
+            .filter_map(|position| position.checked_sub(name_len + 1))
+            .collect();
+        let details = search.params.details();
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    v_flex()
+                        .child(HighlightedLabel::new(search.name.clone(), name_positions))
+                        .child(
+                            h_flex()
+                                .gap_2()
+                                .child(
+                                    HighlightedLabel::new(
+                                        search.params.query.clone(),
+                                        query_positions,
+                                    )
+                                    .size(LabelSize::Small)
+                                    .color(Color::Muted),
+                                )
+                                .when(!details.is_empty(), |this| {
+                                    this.child(
+                                        Label::new(details)
+                                            .size(LabelSize::Small)
+                                            .color(Color::Muted),
+                                    )
+                                }),
+                        ),
+                )
+                .end_hover_slot(
+                    IconButton::new(("remove-saved-search", ix), IconName::Trash)
+                        .icon_size(IconSize::Small)
+                        .tooltip(|cx| Tooltip::text("Remove Saved Search", cx))
+                        .on_click(cx.listener(move |picker, _, cx| {
+                            cx.stop_propagation();
+                            picker.delegate.remove_saved_search(ix, cx);
+                            picker.refresh(cx);
+                        })),
+                ),
+        )
+    }
+}
@@ -1,14 +1,18 @@
 use bitflags::bitflags;
 pub use buffer_search::BufferSearchBar;
-use gpui::{actions, Action, AppContext, IntoElement};
+use gpui::{actions, impl_actions, Action, AppContext, IntoElement};
 use project::search::SearchQuery;
 pub use project_search::ProjectSearchView;
+use serde::Deserialize;
 use ui::{prelude::*, Tooltip};
 use ui::{ButtonStyle, IconButton};
 
 pub mod buffer_search;
+mod persistence;
 pub mod project_search;
+mod saved_searches;
 pub(crate) mod search_bar;
+pub mod search_settings;
 
 pub fn init(cx: &mut AppContext) {
     menu::init();
@@ -24,6 +28,8 @@ actions!(
         ToggleCaseSensitive,
         ToggleIncludeIgnored,
         ToggleRegex,
+        ToggleStructural,
+        TogglePreserveCase,
         ToggleReplace,
         ToggleSelection,
         SelectNextMatch,
@@ -36,6 +42,28 @@ actions!(
     ]
 );
 
+/// Selects a capture group of every match of a regex search, rather than the whole matches.
+#[derive(PartialEq, Clone, Deserialize)]
+pub struct SelectAllCaptures {
+    /// The index or the name of the capture group.
+    #[serde(default = "default_capture_group")]
+    pub group: String,
+}
+
+impl Default for SelectAllCaptures {
+    fn default() -> Self {
+        Self {
+            group: default_capture_group(),
+        }
+    }
+}
+
+fn default_capture_group() -> String {
+    "1".to_string()
+}
+
+impl_actions!(search, [SelectAllCaptures]);
+
 bitflags! {
     #[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
     pub struct SearchOptions: u8 {
@@ -44,6 +72,8 @@ bitflags! {
         const CASE_SENSITIVE = 0b010;
         const INCLUDE_IGNORED = 0b100;
         const REGEX = 0b1000;
+        const STRUCTURAL = 0b10000;
+        const PRESERVE_CASE = 0b100000;
     }
 }
 
@@ -54,6 +84,8 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => "match case",
             SearchOptions::INCLUDE_IGNORED => "include Ignored",
             SearchOptions::REGEX => "regular expression",
+            SearchOptions::STRUCTURAL => "structural search",
+            SearchOptions::PRESERVE_CASE => "preserve case",
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -64,6 +96,8 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => ui::IconName::CaseSensitive,
             SearchOptions::INCLUDE_IGNORED => ui::IconName::FileGit,
             SearchOptions::REGEX => ui::IconName::Regex,
+            SearchOptions::STRUCTURAL => ui::IconName::Code,
+            SearchOptions::PRESERVE_CASE => ui::IconName::PreserveCase,
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -74,6 +108,8 @@ impl SearchOptions {
             SearchOptions::CASE_SENSITIVE => Box::new(ToggleCaseSensitive),
             SearchOptions::INCLUDE_IGNORED => Box::new(ToggleIncludeIgnored),
             SearchOptions::REGEX => Box::new(ToggleRegex),
+            SearchOptions::STRUCTURAL => Box::new(ToggleStructural),
+            SearchOptions::PRESERVE_CASE => Box::new(TogglePreserveCase),
             _ => panic!("{:?} is not a named SearchOption", self),
         }
     }
@@ -88,6 +124,8 @@ impl SearchOptions {
         options.set(SearchOptions::CASE_SENSITIVE, query.case_sensitive());
         options.set(SearchOptions::INCLUDE_IGNORED, query.include_ignored());
         options.set(SearchOptions::REGEX, query.is_regex());
+        options.set(SearchOptions::STRUCTURAL, query.is_structural());
+        options.set(SearchOptions::PRESERVE_CASE, query.preserve_case());
         options
     }
 
@@ -0,0 +1,40 @@
+use anyhow::Result;
+use collections::BTreeMap;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::{Settings, SettingsSources};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema)]
+pub struct SearchScopeSettings {
+    /// Named sets of paths to include in or exclude from project search, selectable from
+    /// the search bar's filters.
+    ///
+    /// Default: {}
+    #[serde(default)]
+    pub search_scopes: BTreeMap<String, SearchScope>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct SearchScope {
+    /// Comma-separated globs of the paths to search, as typed in the include filter.
+    ///
+    /// Default: ""
+    #[serde(default)]
+    pub include: String,
+    /// Comma-separated globs of the paths to skip, as typed in the exclude filter.
+    ///
+    /// Default: ""
+    #[serde(default)]
+    pub exclude: String,
+}
+
+impl Settings for SearchScopeSettings {
+    const KEY: Option<&'static str> = None;
+
+    type FileContent = Self;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
@@ -9,6 +9,7 @@ use schemars::{
 };
 use serde::Deserialize;
 use serde_json::Value;
+use std::mem;
 use util::{asset_str, ResultExt};
 
 #[derive(Debug, Deserialize, Default, Clone, JsonSchema)]
@@ -53,6 +54,30 @@ impl KeymapFile {
         parse_json_with_comments::<Self>(content)
     }
 
+    /// Replaces every `<leader>` in the bindings' keystrokes with the given keystroke.
+    pub fn with_leader(mut self, leader: &str) -> Self {
+        for block in &mut self.0 {
+            block.bindings = mem::take(&mut block.bindings)
+                .into_iter()
+                .map(|(keystrokes, action)| {
+                    let keystrokes = keystrokes
+                        .split_whitespace()
+                        .map(|keystroke| {
+                            if keystroke == "<leader>" {
+                                leader
+                            } else {
+                                keystroke
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join(" ");
+                    (keystrokes, action)
+                })
+                .collect();
+        }
+        self
+    }
+
     pub fn add_to_cx(self, cx: &mut AppContext) -> Result<()> {
         for KeymapBlock { context, bindings } in self.0 {
             let bindings = bindings
@@ -163,4 +188,22 @@ mod tests {
         };
         KeymapFile::parse(json).unwrap();
     }
+
+    #[test]
+    fn can_substitute_leader() {
+        let json = indoc::indoc! {"[
+              {
+                \"bindings\": {
+                  \"<leader> w\": \"workspace::Save\",
+                  \"g <leader>\": \"menu::SelectPrev\",
+                },
+              },
+            ]
+                  "
+
+        };
+        let keymap = KeymapFile::parse(json).unwrap().with_leader("space");
+        let keystrokes = keymap.0[0].bindings.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(keystrokes, ["g space", "space w"]);
+    }
 }
@@ -144,6 +144,24 @@ pub struct SettingsLocation<'a> {
     pub path: &'a Path,
 }
 
+/// The settings file a value was read from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SettingsFile {
+    Default,
+    Extensions,
+    User,
+    /// The section of the user settings for the current release channel.
+    ReleaseChannel,
+    /// The `.zed/settings.json` file in the given directory of a worktree.
+    Local {
+        worktree_id: usize,
+        path: Arc<Path>,
+    },
+}
+
+/// The keys of the user settings sections that only apply to one release channel.
+const RELEASE_CHANNEL_KEYS: [&str; 4] = ["dev", "nightly", "stable", "preview"];
+
 pub struct SettingsJsonSchemaParams<'a> {
     pub staff_mode: bool,
     pub language_names: &'a [String],
@@ -492,6 +510,61 @@ impl SettingsStore {
             .map(|((_, path), content)| (path.clone(), serde_json::to_string(content).unwrap()))
     }
 
+    /// Returns the effective value of every setting at the given location, keyed by its
+    /// dot-separated path, along with the settings file that value was read from.
+    ///
+    /// The local settings of every directory containing the location apply, with the ones of
+    /// nested directories overriding the ones of their parents.
+    pub fn value_sources(
+        &self,
+        location: Option<SettingsLocation>,
+    ) -> BTreeMap<String, (serde_json::Value, SettingsFile)> {
+        let mut sources = BTreeMap::default();
+        record_value_sources(
+            &self.raw_default_settings,
+            "",
+            &SettingsFile::Default,
+            &mut sources,
+        );
+        record_value_sources(
+            &self.raw_extension_settings,
+            "",
+            &SettingsFile::Extensions,
+            &mut sources,
+        );
+        if let Some(user_settings) = self.raw_user_settings.as_object() {
+            for (key, value) in user_settings {
+                if !RELEASE_CHANNEL_KEYS.contains(&key.as_str()) {
+                    record_value_sources(value, key, &SettingsFile::User, &mut sources);
+                }
+            }
+        }
+        if let Some(release_settings) = self
+            .raw_user_settings
+            .get(release_channel::RELEASE_CHANNEL.dev_name())
+        {
+            record_value_sources(
+                release_settings,
+                "",
+                &SettingsFile::ReleaseChannel,
+                &mut sources,
+            );
+        }
+        if let Some(SettingsLocation { worktree_id, path }) = location {
+            // Parent directories are ordered before the directories nested in them.
+            for ((root_id, directory), local_settings) in &self.raw_local_settings {
+                if *root_id == worktree_id && path.starts_with(directory) {
+                    let file = SettingsFile::Local {
+                        worktree_id,
+                        path: directory.clone(),
+                    };
+                    record_value_sources(local_settings, "", &file, &mut sources);
+                }
+            }
+        }
+        sources
+    }
+
     pub fn json_schema(
         &self,
         schema_params: &SettingsJsonSchemaParams,
@@ -587,7 +660,7 @@ impl SettingsStore {
             }
         }
 
-        for release_stage in ["dev", "nightly", "stable", "preview"] {
+        for release_stage in RELEASE_CHANNEL_KEYS {
             let schema = combined_schema.schema.clone();
             combined_schema
                 .schema
@@ -702,6 +775,31 @@ impl SettingsStore {
     }
 }
 
+fn record_value_sources(
+    value: &serde_json::Value,
+    key_path: &str,
+    file: &SettingsFile,
+    sources: &mut BTreeMap<String, (serde_json::Value, SettingsFile)>,
+) {
+    match value {
+        serde_json::Value::Object(object) => {
+            for (key, value) in object {
+                let key_path = if key_path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{key_path}.{key}")
+                };
+                record_value_sources(value, &key_path, file, sources);
+            }
+        }
+        // Null values don't override the ones of less specific files.
+        serde_json::Value::Null => {}
+        value => {
+            sources.insert(key_path.to_string(), (value.clone(), file.clone()));
+        }
+    }
+}
+
 impl Debug for SettingsStore {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("SettingsStore")
@@ -1034,6 +1132,7 @@ pub fn parse_json_with_comments<T: DeserializeOwned>(content: &str) -> Result<T>
 mod tests {
     use super::*;
     use serde_derive::Deserialize;
+    use serde_json::json;
     use unindent::Unindent;
 
     #[gpui::test]
@@ -1208,6 +1307,70 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    fn test_settings_value_sources(cx: &mut AppContext) {
+        let mut store = SettingsStore::default();
+        store
+            .set_default_settings(
+                r#"{ "user": { "name": "John Doe", "age": 30, "staff": false }, "key1": "x" }"#,
+                cx,
+            )
+            .unwrap();
+        store
+            .set_user_settings(r#"{ "user": { "age": 31 } }"#, cx)
+            .unwrap();
+        store
+            .set_local_settings(
+                1,
+                Path::new("/root").into(),
+                Some(r#"{ "user": { "staff": true, "name": "Jane Doe" } }"#),
+                cx,
+            )
+            .unwrap();
+        store
+            .set_local_settings(
+                1,
+                Path::new("/root/package").into(),
+                Some(r#"{ "user": { "name": "Jim Doe" }, "key1": null }"#),
+                cx,
+            )
+            .unwrap();
+
+        let local = |path: &str| SettingsFile::Local {
+            worktree_id: 1,
+            path: Path::new(path).into(),
+        };
+        let sources = store.value_sources(Some(SettingsLocation {
+            worktree_id: 1,
+            path: Path::new("/root/package/src"),
+        }));
+        assert_eq!(
+            sources.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("key1".into(), (json!("x"), SettingsFile::Default)),
+                ("user.age".into(), (json!(31), SettingsFile::User)),
+                (
+                    "user.name".into(),
+                    (json!("Jim Doe"), local("/root/package"))
+                ),
+                ("user.staff".into(), (json!(true), local("/root"))),
+            ]
+        );
+
+        let sources = store.value_sources(Some(SettingsLocation {
+            worktree_id: 1,
+            path: Path::new("/root/other"),
+        }));
+        assert_eq!(
+            sources.get("user.name"),
+            Some(&(json!("Jane Doe"), local("/root")))
+        );
+        assert_eq!(
+            store.value_sources(None).get("user.name"),
+            Some(&(json!("John Doe"), SettingsFile::Default))
+        );
+    }
+
     #[gpui::test]
     fn test_setting_store_update(cx: &mut AppContext) {
         let mut store = SettingsStore::default();
@@ -1,20 +1,43 @@
 use anyhow::{anyhow, Context, Result};
+use regex::{Captures, Regex, RegexBuilder};
 use smallvec::SmallVec;
 use std::{collections::BTreeMap, ops::Range};
 
-#[derive(Clone, Debug, Default, PartialEq)]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Snippet {
     pub text: String,
     pub tabstops: Vec<TabStop>,
+    /// The values offered for each tabstop, in the same order as `tabstops`. Empty for the
+    /// tabstops that aren't choices.
+    pub choices: Vec<Vec<String>>,
+    /// The transforms applied to each range of each tabstop once it's left, in the same order
+    /// as `tabstops` and their ranges.
+    pub transforms: Vec<SmallVec<[Option<Transform>; 2]>>,
 }
 
 type TabStop = SmallVec<[Range<isize>; 2]>;
 
+#[derive(Clone, Default)]
+struct ParsedTabStop {
+    ranges: TabStop,
+    transforms: SmallVec<[Option<Transform>; 2]>,
+    choices: Vec<String>,
+}
+
 impl Snippet {
     pub fn parse(source: &str) -> Result<Self> {
+        Self::parse_with_variables(source, |_| None)
+    }
+
+    /// Parses a snippet, replacing its variables, like `$TM_FILENAME`, with the values returned
+    /// by `variable`. Variables without a value are replaced with their default, if any.
+    pub fn parse_with_variables(
+        source: &str,
+        variable: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self> {
         let mut text = String::with_capacity(source.len());
         let mut tabstops = BTreeMap::new();
-        parse_snippet(source, false, &mut text, &mut tabstops)
+        parse_snippet(source, false, &variable, &mut text, &mut tabstops)
             .context("failed to parse snippet")?;
 
         let len = text.len() as isize;
@@ -24,27 +47,156 @@ impl Snippet {
         if let Some(final_tabstop) = final_tabstop {
             tabstops.push(final_tabstop);
         } else {
-            let end_tabstop = [len..len].into_iter().collect();
-            if !tabstops.last().map_or(false, |t| *t == end_tabstop) {
-                tabstops.push(end_tabstop);
+            let end_tabstop: TabStop = [len..len].into_iter().collect();
+            if !tabstops.last().map_or(false, |t| t.ranges == end_tabstop) {
+                tabstops.push(ParsedTabStop {
+                    transforms: [None].into_iter().collect(),
+                    ranges: end_tabstop,
+                    choices: Vec::new(),
+                });
             }
         }
 
-        Ok(Snippet { text, tabstops })
+        let mut snippet = Snippet {
+            text,
+            ..Default::default()
+        };
+        for tabstop in tabstops {
+            snippet.tabstops.push(tabstop.ranges);
+            snippet.choices.push(tabstop.choices);
+            snippet.transforms.push(tabstop.transforms);
+        }
+        Ok(snippet)
+    }
+}
+
+/// A regular expression replacement applied to the text of a tabstop or a variable, like
+/// `${1/(.*)/${1:/upcase}/}`.
+#[derive(Clone, Debug)]
+pub struct Transform {
+    regex: Regex,
+    format: Vec<FormatItem>,
+    global: bool,
+}
+
+impl PartialEq for Transform {
+    fn eq(&self, other: &Self) -> bool {
+        self.regex.as_str() == other.regex.as_str()
+            && self.format == other.format
+            && self.global == other.global
+    }
+}
+
+impl Eq for Transform {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum FormatItem {
+    Text(String),
+    Group(usize),
+    Case(usize, Case),
+    /// Inserts `if_text` when the group matched, or the group itself when `if_text` is
+    /// `None`, and `else_text` otherwise.
+    Conditional {
+        group: usize,
+        if_text: Option<String>,
+        else_text: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Case {
+    Upcase,
+    Downcase,
+    Capitalize,
+    Camelcase,
+    Pascalcase,
+}
+
+impl Transform {
+    /// Replaces the first match of the regular expression in the text, or every match for
+    /// global transforms, with the format.
+    pub fn apply(&self, text: &str) -> String {
+        let mut result = String::with_capacity(text.len());
+        let mut last_match_end = 0;
+        for captures in self.regex.captures_iter(text) {
+            let Some(mat) = captures.get(0) else {
+                continue;
+            };
+            result.push_str(&text[last_match_end..mat.start()]);
+            self.format(&captures, &mut result);
+            last_match_end = mat.end();
+            if !self.global {
+                break;
+            }
+        }
+        result.push_str(&text[last_match_end..]);
+        result
+    }
+
+    fn format(&self, captures: &Captures, result: &mut String) {
+        let group = |index: usize| captures.get(index).map(|group| group.as_str());
+        for item in &self.format {
+            match item {
+                FormatItem::Text(text) => result.push_str(text),
+                FormatItem::Group(index) => result.push_str(group(*index).unwrap_or_default()),
+                FormatItem::Case(index, case) => {
+                    result.push_str(&case.apply(group(*index).unwrap_or_default()))
+                }
+                FormatItem::Conditional {
+                    group: index,
+                    if_text,
+                    else_text,
+                } => match group(*index).filter(|group| !group.is_empty()) {
+                    Some(group) => result.push_str(if_text.as_deref().unwrap_or(group)),
+                    None => result.push_str(else_text),
+                },
+            }
+        }
+    }
+}
+
+impl Case {
+    fn apply(self, text: &str) -> String {
+        match self {
+            Case::Upcase => text.to_uppercase(),
+            Case::Downcase => text.to_lowercase(),
+            Case::Capitalize => capitalize(text),
+            Case::Pascalcase => text
+                .split(|c: char| !c.is_alphanumeric())
+                .map(capitalize)
+                .collect(),
+            Case::Camelcase => {
+                let pascal = Case::Pascalcase.apply(text);
+                let mut chars = pascal.chars();
+                match chars.next() {
+                    Some(first) => first.to_lowercase().chain(chars).collect(),
+                    None => pascal,
+                }
+            }
+        }
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
     }
 }
 
 fn parse_snippet<'a>(
     mut source: &'a str,
     nested: bool,
+    variable: &dyn Fn(&str) -> Option<String>,
     text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
+    tabstops: &mut BTreeMap<usize, ParsedTabStop>,
 ) -> Result<&'a str> {
     loop {
         match source.chars().next() {
             None => return Ok(""),
             Some('$') => {
-                source = parse_tabstop(&source[1..], text, tabstops)?;
+                source = parse_tabstop(&source[1..], variable, text, tabstops)?;
             }
             Some('\\') => {
                 source = &source[1..];
@@ -73,18 +225,39 @@ fn parse_snippet<'a>(
 
 fn parse_tabstop<'a>(
     mut source: &'a str,
+    variable: &dyn Fn(&str) -> Option<String>,
     text: &mut String,
-    tabstops: &mut BTreeMap<usize, TabStop>,
+    tabstops: &mut BTreeMap<usize, ParsedTabStop>,
 ) -> Result<&'a str> {
+    if source.starts_with(is_variable_start) {
+        return parse_variable(source, variable, text, tabstops);
+    }
+    if let Some(rest) = source.strip_prefix('{') {
+        if rest.starts_with(is_variable_start) {
+            return parse_variable(source, variable, text, tabstops);
+        }
+    }
+
     let tabstop_start = text.len();
     let tabstop_index;
+    let mut transform = None;
+    let mut choices = Vec::new();
     if source.starts_with('{') {
         let (index, rest) = parse_int(&source[1..])?;
         tabstop_index = index;
         source = rest;
 
         if source.starts_with(':') {
-            source = parse_snippet(&source[1..], true, text, tabstops)?;
+            source = parse_snippet(&source[1..], true, variable, text, tabstops)?;
+        } else if source.starts_with('|') {
+            let (parsed_choices, rest) = parse_choices(&source[1..])?;
+            text.push_str(parsed_choices.first().map_or("", String::as_str));
+            choices = parsed_choices;
+            source = rest;
+        } else if source.starts_with('/') {
+            let (parsed_transform, rest) = parse_transform(&source[1..])?;
+            transform = Some(parsed_transform);
+            source = rest;
         }
 
         if source.starts_with('}') {
@@ -98,13 +271,243 @@ fn parse_tabstop<'a>(
         source = rest;
     }
 
-    tabstops
-        .entry(tabstop_index)
-        .or_default()
+    let tabstop = tabstops.entry(tabstop_index).or_default();
+    tabstop
+        .ranges
         .push(tabstop_start as isize..text.len() as isize);
+    tabstop.transforms.push(transform);
+    if !choices.is_empty() {
+        tabstop.choices = choices;
+    }
     Ok(source)
 }
 
+fn parse_variable<'a>(
+    mut source: &'a str,
+    variable: &dyn Fn(&str) -> Option<String>,
+    text: &mut String,
+    tabstops: &mut BTreeMap<usize, ParsedTabStop>,
+) -> Result<&'a str> {
+    let Some(rest) = source.strip_prefix('{') else {
+        let (name, rest) = parse_variable_name(source);
+        text.push_str(&variable(name).unwrap_or_default());
+        return Ok(rest);
+    };
+
+    let (name, rest) = parse_variable_name(rest);
+    source = rest;
+    let value = variable(name);
+    if source.starts_with(':') {
+        // The default may contain tabstops, which are dropped along with it when the
+        // variable has a value.
+        let start = text.len();
+        let previous_tabstops = value.as_ref().map(|_| tabstops.clone());
+        source = parse_snippet(&source[1..], true, variable, text, tabstops)?;
+        if let (Some(value), Some(previous_tabstops)) = (value, previous_tabstops) {
+            text.truncate(start);
+            *tabstops = previous_tabstops;
+            text.push_str(&value);
+        }
+    } else if source.starts_with('/') {
+        let (transform, rest) = parse_transform(&source[1..])?;
+        text.push_str(&transform.apply(&value.unwrap_or_default()));
+        source = rest;
+    } else {
+        text.push_str(&value.unwrap_or_default());
+    }
+
+    if source.starts_with('}') {
+        Ok(&source[1..])
+    } else {
+        Err(anyhow!("expected a closing brace"))
+    }
+}
+
+fn is_variable_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_'
+}
+
+fn parse_variable_name(source: &str) -> (&str, &str) {
+    let len = source
+        .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+        .unwrap_or(source.len());
+    source.split_at(len)
+}
+
+/// Parses the comma-separated values of a choice, like `a,b,c|`, returning the source
+/// following the closing `|`.
+fn parse_choices(mut source: &str) -> Result<(Vec<String>, &str)> {
+    let mut choices = Vec::new();
+    let mut choice = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected the end of a choice")),
+            Some('\\') => {
+                source = &source[1..];
+                if let Some(c) = source.chars().next() {
+                    choice.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            Some(',') => {
+                choices.push(std::mem::take(&mut choice));
+                source = &source[1..];
+            }
+            Some('|') => {
+                choices.push(choice);
+                return Ok((choices, &source[1..]));
+            }
+            Some(c) => {
+                choice.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
+/// Parses a transform, like `(.*)/$1/g`, returning the source following its options.
+fn parse_transform(source: &str) -> Result<(Transform, &str)> {
+    let (pattern, mut source) = take_until_slash(source)?;
+    let mut format = Vec::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected the end of a transform")),
+            Some('/') => {
+                source = &source[1..];
+                break;
+            }
+            Some('$') => {
+                let (item, rest) = parse_format_group(&source[1..])?;
+                format.push(item);
+                source = rest;
+            }
+            Some(_) => {
+                let (text, rest) = take_format_text(source, &['/', '$'])?;
+                format.push(FormatItem::Text(text));
+                source = rest;
+            }
+        }
+    }
+
+    let options_len = source
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(source.len());
+    let (options, rest) = source.split_at(options_len);
+    let regex = RegexBuilder::new(&pattern)
+        .case_insensitive(options.contains('i'))
+        .multi_line(options.contains('m'))
+        .dot_matches_new_line(options.contains('s'))
+        .build()?;
+    Ok((
+        Transform {
+            regex,
+            format,
+            global: options.contains('g'),
+        },
+        rest,
+    ))
+}
+
+/// Returns the text up to the next unescaped `/`, and the source following it.
+fn take_until_slash(mut source: &str) -> Result<(String, &str)> {
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected a slash")),
+            Some('/') => return Ok((text, &source[1..])),
+            Some('\\') if source[1..].starts_with('/') => {
+                text.push('/');
+                source = &source[2..];
+            }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
+/// Returns the text up to the next unescaped terminator, which is left in the source.
+fn take_format_text<'a>(mut source: &'a str, terminators: &[char]) -> Result<(String, &'a str)> {
+    let mut text = String::new();
+    loop {
+        match source.chars().next() {
+            None => return Err(anyhow!("expected the end of a transform")),
+            Some(c) if terminators.contains(&c) => return Ok((text, source)),
+            Some('\\') => {
+                source = &source[1..];
+                if let Some(c) = source.chars().next() {
+                    text.push(c);
+                    source = &source[c.len_utf8()..];
+                }
+            }
+            Some(c) => {
+                text.push(c);
+                source = &source[c.len_utf8()..];
+            }
+        }
+    }
+}
+
+fn parse_format_group(source: &str) -> Result<(FormatItem, &str)> {
+    let Some(source) = source.strip_prefix('{') else {
+        let (group, rest) = parse_int(source)?;
+        return Ok((FormatItem::Group(group), rest));
+    };
+
+    let (group, source) = parse_int(source)?;
+    if let Some(rest) = source.strip_prefix('}') {
+        return Ok((FormatItem::Group(group), rest));
+    }
+    let source = source
+        .strip_prefix(':')
+        .ok_or_else(|| anyhow!("expected a colon"))?;
+
+    let (item, source) = if let Some(rest) = source.strip_prefix('/') {
+        let (name, rest) = parse_variable_name(rest);
+        let case = match name {
+            "upcase" => Case::Upcase,
+            "downcase" => Case::Downcase,
+            "capitalize" => Case::Capitalize,
+            "camelcase" => Case::Camelcase,
+            "pascalcase" => Case::Pascalcase,
+            _ => return Err(anyhow!("unknown case {name:?}")),
+        };
+        (FormatItem::Case(group, case), rest)
+    } else if let Some(rest) = source.strip_prefix('+') {
+        let (if_text, rest) = take_format_text(rest, &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_text: Some(if_text),
+            else_text: String::new(),
+        };
+        (item, rest)
+    } else if let Some(rest) = source.strip_prefix('?') {
+        let (if_text, rest) = take_format_text(rest, &[':'])?;
+        let (else_text, rest) = take_format_text(&rest[1..], &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_text: Some(if_text),
+            else_text,
+        };
+        (item, rest)
+    } else {
+        let rest = source.strip_prefix('-').unwrap_or(source);
+        let (else_text, rest) = take_format_text(rest, &['}'])?;
+        let item = FormatItem::Conditional {
+            group,
+            if_text: None,
+            else_text,
+        };
+        (item, rest)
+    };
+
+    let source = source
+        .strip_prefix('}')
+        .ok_or_else(|| anyhow!("expected a closing brace"))?;
+    Ok((item, source))
+}
+
 fn parse_int(source: &str) -> Result<(usize, &str)> {
     let len = source
         .find(|c: char| !c.is_ascii_digit())
@@ -199,6 +602,72 @@ mod tests {
         assert_eq!(tabstops(&snippet), &[vec![3..3]]);
     }
 
+    #[test]
+    fn test_snippet_with_choices() {
+        let snippet = Snippet::parse("let x = ${1|one,two\\,three|};").unwrap();
+        assert_eq!(snippet.text, "let x = one;");
+        assert_eq!(tabstops(&snippet), &[vec![8..11], vec![12..12]]);
+        assert_eq!(
+            snippet.choices,
+            &[vec!["one".to_string(), "two,three".to_string()], vec![]]
+        );
+    }
+
+    #[test]
+    fn test_snippet_with_variables() {
+        let variable = |name: &str| match name {
+            "TM_FILENAME" => Some("main.rs".to_string()),
+            "CURRENT_YEAR" => Some("2024".to_string()),
+            _ => None,
+        };
+
+        let snippet =
+            Snippet::parse_with_variables("// $TM_FILENAME, ${CURRENT_YEAR}$1", variable).unwrap();
+        assert_eq!(snippet.text, "// main.rs, 2024");
+        assert_eq!(tabstops(&snippet), &[vec![16..16]]);
+
+        // Defaults, and the tabstops they contain, are only used for variables without a value.
+        let snippet = Snippet::parse_with_variables(
+            "${TM_FILENAME:${1:name}} ${CLIPBOARD:${2:text}}",
+            variable,
+        )
+        .unwrap();
+        assert_eq!(snippet.text, "main.rs text");
+        assert_eq!(tabstops(&snippet), &[vec![8..12], vec![12..12]]);
+
+        // Variables are transformed with regular expressions.
+        let snippet =
+            Snippet::parse_with_variables("mod ${TM_FILENAME/(.*)\\.rs$/${1:/upcase}/};", variable)
+                .unwrap();
+        assert_eq!(snippet.text, "mod MAIN;");
+    }
+
+    #[test]
+    fn test_snippet_with_tabstop_transforms() {
+        let snippet = Snippet::parse("${1:foo_bar} ${1/(.*)/${1:/pascalcase}/}").unwrap();
+        assert_eq!(snippet.text, "foo_bar ");
+        assert_eq!(tabstops(&snippet), &[vec![0..7, 8..8], vec![8..8]]);
+        let transform = snippet.transforms[0][1].as_ref().unwrap();
+        assert!(snippet.transforms[0][0].is_none());
+        assert_eq!(transform.apply("foo_bar"), "FooBar");
+        assert_eq!(transform.apply("some-name"), "SomeName");
+
+        let transform = Snippet::parse("${1/a(b)?/${1:?B:-}/gi}")
+            .unwrap()
+            .transforms[0][0]
+            .clone()
+            .unwrap();
+        assert_eq!(transform.apply("xAbxax"), "xBx-x");
+
+        let transform = Snippet::parse("${1/^(\\w)(\\w*)$/${1:/upcase}$2\\/${2:+ok}/}")
+            .unwrap()
+            .transforms[0][0]
+            .clone()
+            .unwrap();
+        assert_eq!(transform.apply("abc"), "Abc/ok");
+        assert_eq!(transform.apply("a"), "A/");
+    }
+
     fn tabstops(snippet: &Snippet) -> Vec<Vec<Range<isize>>> {
         snippet.tabstops.iter().map(|t| t.to_vec()).collect()
     }
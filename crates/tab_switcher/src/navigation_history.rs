@@ -0,0 +1,212 @@
+use gpui::{
+    rems, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Task, View,
+    ViewContext, WeakView,
+};
+use picker::{Picker, PickerDelegate};
+use std::sync::Arc;
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{
+    pane::{NavigationHistoryEntry, NavigationMode},
+    ModalView, Pane, ShowNavigationHistory, Workspace,
+};
+
+const PANEL_WIDTH_REMS: f32 = 34.;
+
+/// Lists the locations that going back or forward in the active pane leads to, and navigates to
+/// the confirmed one.
+pub struct NavigationHistory {
+    pub(crate) picker: View<Picker<NavigationHistoryDelegate>>,
+}
+
+impl ModalView for NavigationHistory {}
+
+impl NavigationHistory {
+    pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+        workspace.register_action(|workspace, _: &ShowNavigationHistory, cx| {
+            let pane = workspace.active_pane().downgrade();
+            let weak_workspace = cx.view().downgrade();
+            workspace.toggle_modal(cx, |cx| {
+                let delegate =
+                    NavigationHistoryDelegate::new(cx.view().downgrade(), weak_workspace, pane, cx);
+                Self {
+                    picker: cx.new_view(|cx| Picker::uniform_list(delegate, cx)),
+                }
+            });
+        });
+    }
+}
+
+impl EventEmitter<DismissEvent> for NavigationHistory {}
+
+impl FocusableView for NavigationHistory {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl Render for NavigationHistory {
+    fn render(&mut self, _: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex()
+            .key_context("NavigationHistory")
+            .w(rems(PANEL_WIDTH_REMS))
+            .child(self.picker.clone())
+    }
+}
+
+pub struct NavigationHistoryDelegate {
+    navigation_history: WeakView<NavigationHistory>,
+    workspace: WeakView<Workspace>,
+    pane: WeakView<Pane>,
+    /// The locations going back leads to, nearest first, followed by those going forward does.
+    entries: Vec<(NavigationMode, NavigationHistoryEntry)>,
+    /// The indices of the entries matching the query.
+    matches: Vec<usize>,
+    selected_index: usize,
+}
+
+impl NavigationHistoryDelegate {
+    fn new(
+        navigation_history: WeakView<NavigationHistory>,
+        workspace: WeakView<Workspace>,
+        pane: WeakView<Pane>,
+        cx: &mut ViewContext<NavigationHistory>,
+    ) -> Self {
+        let mut entries = Vec::new();
+        if let Some(pane) = pane.upgrade() {
+            let nav_history = pane.read(cx).nav_history();
+            for mode in [NavigationMode::GoingBack, NavigationMode::GoingForward] {
+                entries.extend(
+                    nav_history
+                        .entries(mode, cx)
+                        .into_iter()
+                        .map(|entry| (mode, entry)),
+                );
+            }
+        }
+        Self {
+            navigation_history,
+            workspace,
+            pane,
+            matches: (0..entries.len()).collect(),
+            entries,
+            selected_index: 0,
+        }
+    }
+}
+
+impl PickerDelegate for NavigationHistoryDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search navigation history...".into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        "No navigation history".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+        cx.notify();
+    }
+
+    fn separators_after_indices(&self) -> Vec<usize> {
+        // Separates the locations going back leads to from those going forward does.
+        let back_count = self
+            .matches
+            .iter()
+            .filter(|&&ix| self.entries[ix].0 == NavigationMode::GoingBack)
+            .count();
+        if back_count > 0 && back_count < self.matches.len() {
+            vec![back_count - 1]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let query = query.trim().to_lowercase();
+        self.matches = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, entry))| {
+                query.is_empty()
+                    || entry.title().to_lowercase().contains(&query)
+                    || entry.location.as_ref().map_or(false, |location| {
+                        location.preview.to_lowercase().contains(&query)
+                    })
+            })
+            .map(|(ix, _)| ix)
+            .collect();
+        self.selected_index = 0;
+        cx.notify();
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        let Some((mode, entry)) = self
+            .matches
+            .get(self.selected_index)
+            .and_then(|&ix| self.entries.get(ix))
+        else {
+            return;
+        };
+        let (mode, steps) = (*mode, entry.steps);
+        let pane = self.pane.clone();
+        self.dismissed(cx);
+        self.workspace
+            .update(cx, |workspace, cx| {
+                workspace
+                    .navigate_history_steps(pane, mode, steps, cx)
+                    .detach_and_log_err(cx)
+            })
+            .log_err();
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.navigation_history
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let (mode, entry) = &self.entries[*self.matches.get(ix)?];
+        let icon = if *mode == NavigationMode::GoingBack {
+            IconName::ArrowLeft
+        } else {
+            IconName::ArrowRight
+        };
+        Some(
+            ListItem::new(ix)
+                .spacing(ListItemSpacing::Sparse)
+                .inset(true)
+                .selected(selected)
+                .start_slot(Icon::new(icon).size(IconSize::Small).color(Color::Muted))
+                .child(v_flex().child(Label::new(entry.title())).when_some(
+                    entry.location.as_ref(),
+                    |el, location| {
+                        el.child(
+                            Label::new(location.preview.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        )
+                    },
+                )),
+        )
+    }
+}
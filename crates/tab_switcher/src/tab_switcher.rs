@@ -1,3 +1,4 @@
+mod navigation_history;
 #[cfg(test)]
 mod tab_switcher_tests;
 
@@ -38,6 +39,8 @@ impl ModalView for TabSwitcher {}
 
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(TabSwitcher::register).detach();
+    cx.observe_new_views(navigation_history::NavigationHistory::register)
+        .detach();
 }
 
 impl TabSwitcher {
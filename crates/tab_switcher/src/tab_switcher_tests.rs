@@ -1,7 +1,7 @@
 use super::*;
 use editor::Editor;
 use gpui::{TestAppContext, VisualTestContext};
-use menu::SelectPrev;
+use menu::{Confirm, SelectNext, SelectPrev};
 use project::{Project, ProjectPath};
 use serde_json::json;
 use std::path::Path;
@@ -228,6 +228,79 @@ async fn test_close_selected_item(cx: &mut gpui::TestAppContext) {
     assert_tab_switcher_is_closed(workspace, cx);
 }
 
+#[gpui::test]
+async fn test_navigation_history(cx: &mut gpui::TestAppContext) {
+    let app_state = init_test(cx);
+    app_state
+        .fs
+        .as_fake()
+        .insert_tree(
+            "/root",
+            json!({
+                "1.txt": "First file",
+                "2.txt": "Second file",
+                "3.txt": "Third file",
+            }),
+        )
+        .await;
+
+    let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+    let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+    open_buffer("1.txt", &workspace, cx).await;
+    open_buffer("2.txt", &workspace, cx).await;
+    open_buffer("3.txt", &workspace, cx).await;
+
+    // Lists the locations going back leads to, nearest first.
+    let navigation_history = open_navigation_history(&workspace, cx);
+    navigation_history.update(cx, |navigation_history, _| {
+        assert_eq!(navigation_history.delegate.match_count(), 2);
+        assert_eq!(navigation_history.delegate.selected_index(), 0);
+    });
+
+    // Confirming the farthest one goes back twice.
+    cx.dispatch_action(SelectNext);
+    cx.dispatch_action(Confirm);
+    cx.run_until_parked();
+    cx.read(|cx| {
+        let active_editor = workspace.read(cx).active_item_as::<Editor>(cx).unwrap();
+        assert_eq!(active_editor.read(cx).title(cx), "1.txt");
+        assert!(workspace
+            .read(cx)
+            .active_modal::<navigation_history::NavigationHistory>(cx)
+            .is_none());
+    });
+
+    // Both locations that were left are now ahead.
+    let navigation_history = open_navigation_history(&workspace, cx);
+    navigation_history.update(cx, |navigation_history, _| {
+        assert_eq!(navigation_history.delegate.match_count(), 2);
+    });
+    cx.dispatch_action(SelectNext);
+    cx.dispatch_action(Confirm);
+    cx.run_until_parked();
+    cx.read(|cx| {
+        let active_editor = workspace.read(cx).active_item_as::<Editor>(cx).unwrap();
+        assert_eq!(active_editor.read(cx).title(cx), "3.txt");
+    });
+}
+
+#[track_caller]
+fn open_navigation_history(
+    workspace: &View<Workspace>,
+    cx: &mut VisualTestContext,
+) -> View<Picker<navigation_history::NavigationHistoryDelegate>> {
+    cx.dispatch_action(workspace::ShowNavigationHistory);
+    workspace.update(cx, |workspace, cx| {
+        workspace
+            .active_modal::<navigation_history::NavigationHistory>(cx)
+            .expect("navigation history is not open")
+            .read(cx)
+            .picker
+            .clone()
+    })
+}
+
 fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
     cx.update(|cx| {
         let state = AppState::test(cx);
@@ -12,7 +12,7 @@ use std::path::PathBuf;
 use std::str::FromStr;
 use std::{borrow::Cow, path::Path};
 
-pub use task_template::{RevealStrategy, TaskTemplate, TaskTemplates};
+pub use task_template::{DebugRequestType, RevealStrategy, TaskTemplate, TaskTemplates, TaskType};
 pub use vscode_format::VsCodeTaskFile;
 
 /// Task identifier, unique within the application.
@@ -76,10 +76,75 @@ pub struct SpawnInTerminal {
     pub use_new_terminal: bool,
     /// Whether to allow multiple instances of the same task to be run, or rather wait for the existing ones to finish.
     pub allow_concurrent_runs: bool,
+    /// Whether all runs of the task reuse its terminal tab, restarting the task when it's still running.
+    pub dedicated_terminal: bool,
     /// What to do with the terminal pane and tab, after the command was started.
     pub reveal: RevealStrategy,
 }
 
+/// Contains all information needed by Zed to start a debug session for the given debug task.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DebugAdapterConfig {
+    /// Human readable name of the debug session.
+    pub label: String,
+    /// Executable command that spawns the debug adapter.
+    pub command: String,
+    /// Arguments to the debug adapter command, with all task variables substituted.
+    pub args: Vec<String>,
+    /// Env overrides for the debug adapter process.
+    pub env: HashMap<String, String>,
+    /// Current working directory to spawn the debug adapter into.
+    pub cwd: Option<PathBuf>,
+    /// Whether the debug adapter should launch the program to debug, or attach to a running one.
+    pub request: DebugRequestType,
+    /// The adapter-specific arguments of the `launch` or `attach` request, with all task variables substituted.
+    pub request_args: serde_json::Value,
+}
+
+/// The value of the `debug_args` of an attach debug task that is replaced with the id of a process
+/// picked when the task is spawned, like `"pid": "$ZED_PICKED_PROCESS_ID"`.
+pub const PICKED_PROCESS_ID: &str = "$ZED_PICKED_PROCESS_ID";
+
+impl DebugAdapterConfig {
+    /// Whether a process to attach to has to be picked before starting the debug session, as some
+    /// of the request arguments are [`PICKED_PROCESS_ID`].
+    pub fn picks_process(&self) -> bool {
+        fn contains_placeholder(value: &serde_json::Value) -> bool {
+            match value {
+                serde_json::Value::String(string) => string == PICKED_PROCESS_ID,
+                serde_json::Value::Array(values) => values.iter().any(contains_placeholder),
+                serde_json::Value::Object(object) => object.values().any(contains_placeholder),
+                _ => false,
+            }
+        }
+        self.request == DebugRequestType::Attach && contains_placeholder(&self.request_args)
+    }
+
+    /// Replaces the [`PICKED_PROCESS_ID`] request arguments with the id of the picked process.
+    pub fn with_process_id(mut self, process_id: u32) -> Self {
+        fn replace_placeholder(value: &mut serde_json::Value, process_id: u32) {
+            match value {
+                serde_json::Value::String(string) if string == PICKED_PROCESS_ID => {
+                    *value = process_id.into();
+                }
+                serde_json::Value::Array(values) => {
+                    for value in values {
+                        replace_placeholder(value, process_id);
+                    }
+                }
+                serde_json::Value::Object(object) => {
+                    for value in object.values_mut() {
+                        replace_placeholder(value, process_id);
+                    }
+                }
+                _ => {}
+            }
+        }
+        replace_placeholder(&mut self.request_args, process_id);
+        self
+    }
+}
+
 /// A final form of the [`TaskTemplate`], that got resolved with a particualar [`TaskContext`] and now is ready to spawn the actual task.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ResolvedTask {
@@ -98,6 +163,8 @@ pub struct ResolvedTask {
     /// Further actions that need to take place after the resolved task is spawned,
     /// with all task variables resolved.
     pub resolved: Option<SpawnInTerminal>,
+    /// For debug tasks, the debug session to start instead of spawning the command in a terminal.
+    pub debug_adapter_config: Option<DebugAdapterConfig>,
 }
 
 impl ResolvedTask {
@@ -8,8 +8,8 @@ use sha2::{Digest, Sha256};
 use util::{truncate_and_remove_front, ResultExt};
 
 use crate::{
-    ResolvedTask, SpawnInTerminal, TaskContext, TaskId, TerminalWorkDir, VariableName,
-    ZED_VARIABLE_NAME_PREFIX,
+    DebugAdapterConfig, ResolvedTask, SpawnInTerminal, TaskContext, TaskId, TerminalWorkDir,
+    VariableName, PICKED_PROCESS_ID, ZED_VARIABLE_NAME_PREFIX,
 };
 
 /// A template definition of a Zed task to run.
@@ -40,6 +40,10 @@ pub struct TaskTemplate {
     /// Whether to allow multiple instances of the same task to be run, or rather wait for the existing ones to finish.
     #[serde(default)]
     pub allow_concurrent_runs: bool,
+    /// Whether the task keeps a terminal tab of its own, which all its reruns reuse: a rerun of the task while it's still
+    /// running restarts it in that tab. Takes precedence over `use_new_terminal` and `allow_concurrent_runs`.
+    #[serde(default)]
+    pub dedicated_terminal: bool,
     /// What to do with the terminal pane and tab, after the command was started:
     /// * `always` — always show the terminal pane, add and focus the corresponding task's tab in it (default)
     /// * `never` — avoid changing current terminal pane focus, but still add/reuse the task's tab there
@@ -49,6 +53,42 @@ pub struct TaskTemplate {
     /// Represents the tags which this template attaches to. Adding this removes this task from other UI.
     #[serde(default)]
     pub tags: Vec<String>,
+
+    /// The kind of the task:
+    /// * `script` — runs the command in a terminal (default)
+    /// * `debug` — starts a debug session with the debug adapter spawned by the command
+    #[serde(default, rename = "type")]
+    pub task_type: TaskType,
+    /// For debug tasks, whether the debug adapter should `launch` the program to debug, or `attach` to a running one.
+    #[serde(default)]
+    pub request: DebugRequestType,
+    /// For debug tasks, the adapter-specific arguments of the `launch` or `attach` request, such as the program to debug.
+    /// Task variables are substituted in their string values, and `$ZED_PICKED_PROCESS_ID` values of attach requests are
+    /// replaced with the id of a process picked when the task is spawned.
+    #[serde(default)]
+    pub debug_args: serde_json::Value,
+}
+
+/// The kind of a task.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskType {
+    /// Runs the command in a terminal.
+    #[default]
+    Script,
+    /// Starts a debug session with the debug adapter spawned by the command.
+    Debug,
+}
+
+/// How a debug adapter starts debugging.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum DebugRequestType {
+    /// Launches the program to debug.
+    #[default]
+    Launch,
+    /// Attaches to a running program.
+    Attach,
 }
 
 /// What to do with the terminal pane and tab, after the command was started.
@@ -169,12 +209,33 @@ impl TaskTemplate {
             &variable_names,
             &mut substituted_variables,
         )?;
+        let debug_adapter_config = match self.task_type {
+            TaskType::Script => None,
+            TaskType::Debug => Some(DebugAdapterConfig {
+                label: human_readable_label.clone(),
+                command: command.clone(),
+                args: args_with_substitutions.clone(),
+                env: env.clone(),
+                cwd: cwd
+                    .as_ref()
+                    .and_then(|cwd| cwd.local_path())
+                    .map(|cwd| cwd.to_path_buf()),
+                request: self.request,
+                request_args: substitute_all_template_variables_in_json(
+                    &self.debug_args,
+                    &task_variables,
+                    &variable_names,
+                    &mut substituted_variables,
+                )?,
+            }),
+        };
         env.extend(task_variables.into_iter().map(|(k, v)| (k, v.to_owned())));
         Some(ResolvedTask {
             id: id.clone(),
             substituted_variables,
             original_task: self.clone(),
             resolved_label: full_label.clone(),
+            debug_adapter_config,
             resolved: Some(SpawnInTerminal {
                 id,
                 cwd,
@@ -193,6 +254,7 @@ impl TaskTemplate {
                 env,
                 use_new_terminal: self.use_new_terminal,
                 allow_concurrent_runs: self.allow_concurrent_runs,
+                dedicated_terminal: self.dedicated_terminal,
                 reveal: self.reveal,
             }),
         })
@@ -301,6 +363,55 @@ fn substitute_all_template_variables_in_map(
     Some(new_map)
 }
 
+fn substitute_all_template_variables_in_json(
+    value: &serde_json::Value,
+    task_variables: &HashMap<String, &str>,
+    variable_names: &HashMap<String, VariableName>,
+    substituted_variables: &mut HashSet<VariableName>,
+) -> Option<serde_json::Value> {
+    Some(match value {
+        // Replaced with the id of the process picked when the task is spawned instead.
+        serde_json::Value::String(string) if string == PICKED_PROCESS_ID => value.clone(),
+        serde_json::Value::String(string) => {
+            serde_json::Value::String(substitute_all_template_variables_in_str(
+                string,
+                task_variables,
+                variable_names,
+                substituted_variables,
+            )?)
+        }
+        serde_json::Value::Array(values) => serde_json::Value::Array(
+            values
+                .iter()
+                .map(|value| {
+                    substitute_all_template_variables_in_json(
+                        value,
+                        task_variables,
+                        variable_names,
+                        substituted_variables,
+                    )
+                })
+                .collect::<Option<_>>()?,
+        ),
+        serde_json::Value::Object(object) => {
+            let mut new_object = serde_json::Map::new();
+            for (key, value) in object {
+                new_object.insert(
+                    key.clone(),
+                    substitute_all_template_variables_in_json(
+                        value,
+                        task_variables,
+                        variable_names,
+                        substituted_variables,
+                    )?,
+                );
+            }
+            serde_json::Value::Object(new_object)
+        }
+        value => value.clone(),
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::{borrow::Cow, path::Path};
@@ -668,6 +779,140 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_resolving_debug_tasks() {
+        let task = TaskTemplate {
+            label: "Debug $ZED_STEM".into(),
+            command: "lldb-dap".into(),
+            task_type: TaskType::Debug,
+            request: DebugRequestType::Launch,
+            debug_args: serde_json::json!({
+                "program": "$ZED_DIRNAME/$ZED_STEM",
+                "args": ["--verbose"],
+                "stopOnEntry": true,
+            }),
+            ..TaskTemplate::default()
+        };
+        let cx = TaskContext {
+            cwd: Some(PathBuf::from("/dir")),
+            task_variables: TaskVariables::from_iter([
+                (VariableName::Stem, "main".to_string()),
+                (VariableName::Dirname, "/dir/target".to_string()),
+            ]),
+        };
+
+        let resolved_task = task.resolve_task(TEST_ID_BASE, &cx).unwrap();
+        assert_substituted_variables(
+            &resolved_task,
+            vec![VariableName::Stem, VariableName::Dirname],
+        );
+        let config = resolved_task.debug_adapter_config.unwrap();
+        assert_eq!(config.label, "Debug main");
+        assert_eq!(config.command, "lldb-dap");
+        assert_eq!(config.cwd, Some(PathBuf::from("/dir")));
+        assert_eq!(config.request, DebugRequestType::Launch);
+        assert_eq!(
+            config.request_args,
+            serde_json::json!({
+                "program": "/dir/target/main",
+                "args": ["--verbose"],
+                "stopOnEntry": true,
+            })
+        );
+
+        let script_task = TaskTemplate {
+            task_type: TaskType::Script,
+            ..task
+        };
+        assert!(script_task
+            .resolve_task(TEST_ID_BASE, &cx)
+            .unwrap()
+            .debug_adapter_config
+            .is_none());
+    }
+
+    #[test]
+    fn test_resolving_attach_debug_tasks() {
+        let task = TaskTemplate {
+            label: "Attach".into(),
+            command: "lldb-dap".into(),
+            task_type: TaskType::Debug,
+            request: DebugRequestType::Attach,
+            debug_args: serde_json::json!({
+                "pid": PICKED_PROCESS_ID,
+                "sourceMap": [["/build", "$ZED_WORKTREE_ROOT"]],
+            }),
+            ..TaskTemplate::default()
+        };
+        let cx = TaskContext {
+            cwd: None,
+            task_variables: TaskVariables::from_iter([(
+                VariableName::WorktreeRoot,
+                "/dir".to_string(),
+            )]),
+        };
+
+        let config = task
+            .resolve_task(TEST_ID_BASE, &cx)
+            .unwrap()
+            .debug_adapter_config
+            .unwrap();
+        assert!(config.picks_process());
+        let config = config.with_process_id(42);
+        assert!(!config.picks_process());
+        assert_eq!(
+            config.request_args,
+            serde_json::json!({
+                "pid": 42,
+                "sourceMap": [["/build", "/dir"]],
+            })
+        );
+
+        let launch_task = TaskTemplate {
+            request: DebugRequestType::Launch,
+            ..task
+        };
+        assert!(!launch_task
+            .resolve_task(TEST_ID_BASE, &cx)
+            .unwrap()
+            .debug_adapter_config
+            .unwrap()
+            .picks_process());
+    }
+
+    #[test]
+    fn test_resolving_dedicated_terminal_tasks() {
+        let task: TaskTemplate = serde_json::from_value(serde_json::json!({
+            "label": "watch",
+            "command": "cargo",
+            "args": ["watch"],
+            "dedicated_terminal": true,
+        }))
+        .unwrap();
+        assert!(task.dedicated_terminal);
+        let spawn_in_terminal = task
+            .resolve_task(TEST_ID_BASE, &TaskContext::default())
+            .unwrap()
+            .resolved
+            .unwrap();
+        assert!(spawn_in_terminal.dedicated_terminal);
+
+        let task: TaskTemplate = serde_json::from_value(serde_json::json!({
+            "label": "build",
+            "command": "cargo",
+        }))
+        .unwrap();
+        assert!(!task.dedicated_terminal);
+        assert!(
+            !task
+                .resolve_task(TEST_ID_BASE, &TaskContext::default())
+                .unwrap()
+                .resolved
+                .unwrap()
+                .dedicated_terminal
+        );
+    }
+
     #[track_caller]
     fn assert_substituted_variables(resolved_task: &ResolvedTask, mut expected: Vec<VariableName>) {
         let mut resolved_variables = resolved_task
@@ -0,0 +1,787 @@
+//! Inline images sent with the Kitty graphics protocol or as Sixel, which Alacritty ignores.
+//!
+//! The images are picked out of the shell's output before Alacritty parses it. In their place,
+//! the output gets a placeholder character, colored after the id of the image, followed by the
+//! cursor movements making room for the image. The image is then drawn from wherever the
+//! placeholder ends up, as the output scrolls and reflows.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+mod sixel;
+
+use alacritty_terminal::{
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    vte::ansi::{Color, Rgb},
+    Term,
+};
+use anyhow::{anyhow, bail, Context as _, Result};
+use collections::HashMap;
+use gpui::ImageData;
+use image::{Bgra, ImageBuffer};
+use std::{fmt::Write as _, io::Read as _, path::Path, sync::Arc};
+
+/// The character in the cell an image is drawn from, as in Kitty's Unicode placeholders.
+pub const IMAGE_PLACEHOLDER: char = '\u{10EEEE}';
+
+/// Images are scaled down to fit this many lines.
+const MAX_IMAGE_ROWS: u32 = 200;
+/// Images sent in sequences longer than this are dropped.
+const MAX_SEQUENCE_LEN: usize = 128 * 1024 * 1024;
+/// The number of images kept for Kitty's placements, which refer to them by id.
+const MAX_KITTY_IMAGES: usize = 64;
+
+const ESC: u8 = 0x1b;
+const CAN: u8 = 0x18;
+const SUB: u8 = 0x1a;
+
+/// An image shown in the terminal, taking up a rectangle of cells.
+#[derive(Clone)]
+pub struct TerminalImage {
+    pub data: Arc<ImageData>,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+/// An image in the terminal's content, whose top left corner is at the point. The point's line
+/// is relative to the top of the screen, and is negative for images scrolled partly off it.
+#[derive(Clone)]
+pub struct ImagePlacement {
+    pub point: AlacPoint,
+    pub image: TerminalImage,
+}
+
+pub(crate) enum GraphicsEvent {
+    /// An image was placed at the placeholder with the id.
+    Place { id: u32, image: TerminalImage },
+    /// The images at the placeholders with the ids were deleted.
+    Delete(Vec<u32>),
+    /// A reply for the program that sent the image.
+    Reply(String),
+}
+
+enum ScanState {
+    Ground,
+    Escape,
+    /// The parameters of a DCS sequence, which is a Sixel image when they're followed by `q`.
+    DcsParams(Vec<u8>),
+    ApcStart,
+    Sixel(Vec<u8>),
+    Kitty(Vec<u8>),
+    /// An image too large to keep, skipped up to its terminator.
+    Discard,
+}
+
+/// Replaces the images in the shell's output, which may split them across reads, with their
+/// placeholders.
+pub(crate) struct GraphicsScanner {
+    state: ScanState,
+    /// Whether the last byte of an image was an escape, which may start its terminator.
+    escaped: bool,
+    cell_width: u32,
+    cell_height: u32,
+    next_id: u32,
+    kitty: KittyImages,
+}
+
+impl GraphicsScanner {
+    pub fn new(cell_width: u16, cell_height: u16) -> Self {
+        let mut scanner = Self {
+            state: ScanState::Ground,
+            escaped: false,
+            cell_width: 0,
+            cell_height: 0,
+            next_id: 0,
+            kitty: KittyImages::default(),
+        };
+        scanner.set_cell_size(cell_width, cell_height);
+        scanner
+    }
+
+    pub fn set_cell_size(&mut self, cell_width: u16, cell_height: u16) {
+        self.cell_width = cell_width.max(1) as u32;
+        self.cell_height = cell_height.max(1) as u32;
+    }
+
+    pub fn scan(
+        &mut self,
+        bytes: &[u8],
+        output: &mut Vec<u8>,
+        mut on_event: impl FnMut(GraphicsEvent),
+    ) {
+        let mut ix = 0;
+        while ix < bytes.len() {
+            match &mut self.state {
+                ScanState::Ground => {
+                    let end = bytes[ix..]
+                        .iter()
+                        .position(|&byte| byte == ESC)
+                        .map_or(bytes.len(), |len| ix + len);
+                    output.extend_from_slice(&bytes[ix..end]);
+                    if end < bytes.len() {
+                        self.state = ScanState::Escape;
+                    }
+                    ix = end + 1;
+                }
+                ScanState::Escape => {
+                    let byte = bytes[ix];
+                    ix += 1;
+                    match byte {
+                        b'P' => self.state = ScanState::DcsParams(Vec::new()),
+                        b'_' => self.state = ScanState::ApcStart,
+                        ESC => output.push(ESC),
+                        _ => {
+                            output.extend_from_slice(&[ESC, byte]);
+                            self.state = ScanState::Ground;
+                        }
+                    }
+                }
+                ScanState::DcsParams(params) => {
+                    let byte = bytes[ix];
+                    if byte == b'q' {
+                        ix += 1;
+                        self.state = ScanState::Sixel(Vec::new());
+                        self.escaped = false;
+                    } else if (byte.is_ascii_digit() || byte == b';') && params.len() < 32 {
+                        ix += 1;
+                        params.push(byte);
+                    } else {
+                        // Another DCS sequence, left to Alacritty.
+                        output.extend_from_slice(&[ESC, b'P']);
+                        output.extend_from_slice(params);
+                        self.state = ScanState::Ground;
+                    }
+                }
+                ScanState::ApcStart => {
+                    if bytes[ix] == b'G' {
+                        ix += 1;
+                        self.state = ScanState::Kitty(Vec::new());
+                        self.escaped = false;
+                    } else {
+                        output.extend_from_slice(&[ESC, b'_']);
+                        self.state = ScanState::Ground;
+                    }
+                }
+                ScanState::Sixel(data) | ScanState::Kitty(data) => {
+                    if self.escaped {
+                        self.escaped = false;
+                        if bytes[ix] == b'\\' {
+                            ix += 1;
+                            self.finish(output, &mut on_event);
+                        } else {
+                            // Any other escape sequence cuts the image short.
+                            self.state = ScanState::Escape;
+                        }
+                        continue;
+                    }
+
+                    let end = bytes[ix..]
+                        .iter()
+                        .position(|&byte| matches!(byte, ESC | CAN | SUB))
+                        .map_or(bytes.len(), |len| ix + len);
+                    if data.len() + (end - ix) > MAX_SEQUENCE_LEN {
+                        self.state = ScanState::Discard;
+                        continue;
+                    }
+                    data.extend_from_slice(&bytes[ix..end]);
+                    match bytes.get(end) {
+                        Some(&ESC) => self.escaped = true,
+                        Some(_) => self.state = ScanState::Ground,
+                        None => {}
+                    }
+                    ix = end + 1;
+                }
+                ScanState::Discard => {
+                    let byte = bytes[ix];
+                    ix += 1;
+                    if (self.escaped && byte == b'\\') || matches!(byte, CAN | SUB) {
+                        self.state = ScanState::Ground;
+                    }
+                    self.escaped = byte == ESC;
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self, output: &mut Vec<u8>, on_event: &mut impl FnMut(GraphicsEvent)) {
+        match std::mem::replace(&mut self.state, ScanState::Ground) {
+            ScanState::Sixel(data) => {
+                if let Some((width, height, pixels)) = sixel::decode(&data) {
+                    if let Some(data) = image_data(width, height, pixels, 4) {
+                        let (columns, rows) = self.cell_span(width, height, 0, 0);
+                        self.place(data, columns, rows, CursorMovement::Below, output, on_event);
+                    }
+                }
+            }
+            ScanState::Kitty(data) => self.kitty_command(&data, output, on_event),
+            _ => {}
+        }
+    }
+
+    fn kitty_command(
+        &mut self,
+        data: &[u8],
+        output: &mut Vec<u8>,
+        on_event: &mut impl FnMut(GraphicsEvent),
+    ) {
+        let (control, payload) = match data.iter().position(|&byte| byte == b';') {
+            Some(ix) => (&data[..ix], &data[ix + 1..]),
+            None => (data, &data[data.len()..]),
+        };
+        let mut command = KittyCommand::parse(control);
+
+        // The chunks following the first one of an image only tell whether more are to come.
+        if let Some((mut first_command, mut first_payload)) = self.kitty.pending.take() {
+            first_payload.extend_from_slice(payload);
+            first_command.more = command.more;
+            if command.more {
+                self.kitty.pending = Some((first_command, first_payload));
+                return;
+            }
+            return self.run_kitty_command(first_command, &first_payload, output, on_event);
+        }
+        if command.more {
+            command.more = false;
+            self.kitty.pending = Some((command, payload.to_vec()));
+            return;
+        }
+        self.run_kitty_command(command, payload, output, on_event);
+    }
+
+    fn run_kitty_command(
+        &mut self,
+        command: KittyCommand,
+        payload: &[u8],
+        output: &mut Vec<u8>,
+        on_event: &mut impl FnMut(GraphicsEvent),
+    ) {
+        let result = match command.action {
+            b'q' => command.load(payload).map(drop),
+            b't' | b'T' => command.load(payload).map(|image| {
+                if command.image_id != 0 {
+                    self.kitty.insert(command.image_id, image.clone());
+                }
+                if command.action == b'T' {
+                    self.place_kitty_image(&command, image, output, on_event);
+                }
+            }),
+            b'p' => match self.kitty.images.get(&command.image_id).cloned() {
+                Some(image) => {
+                    self.place_kitty_image(&command, image, output, on_event);
+                    Ok(())
+                }
+                None => Err(anyhow!("ENOENT:no image with id {}", command.image_id)),
+            },
+            b'd' => {
+                let ids = self.kitty.delete(command.delete, command.image_id);
+                if !ids.is_empty() {
+                    on_event(GraphicsEvent::Delete(ids));
+                }
+                Ok(())
+            }
+            _ => Err(anyhow!("EINVAL:unsupported action")),
+        };
+
+        // Replies are only sent for the images named by the program, unless it asks for quiet.
+        if command.image_id == 0 || command.action == b'd' {
+            return;
+        }
+        let reply = match result {
+            Ok(()) if command.quiet == 0 => "OK".to_string(),
+            Err(error) if command.quiet < 2 => {
+                let error = error.to_string();
+                if error.contains(':') {
+                    error
+                } else {
+                    format!("EINVAL:{error}")
+                }
+            }
+            _ => return,
+        };
+        on_event(GraphicsEvent::Reply(format!(
+            "\x1b_Gi={};{reply}\x1b\\",
+            command.image_id
+        )));
+    }
+
+    fn place_kitty_image(
+        &mut self,
+        command: &KittyCommand,
+        image: Arc<ImageData>,
+        output: &mut Vec<u8>,
+        on_event: &mut impl FnMut(GraphicsEvent),
+    ) {
+        let size = image.size();
+        let (columns, rows) = self.cell_span(
+            size.width.into(),
+            size.height.into(),
+            command.columns,
+            command.rows,
+        );
+        let movement = if command.no_cursor_movement {
+            CursorMovement::None
+        } else {
+            CursorMovement::AfterLastRow
+        };
+        let id = self.place(image, columns, rows, movement, output, on_event);
+        self.kitty.placements.push((command.image_id, id));
+    }
+
+    /// Returns the columns and rows the image takes up: those asked for, with the other one
+    /// following the image's aspect ratio when only one is, or those of its size otherwise.
+    fn cell_span(&self, width: u32, height: u32, columns: u32, rows: u32) -> (usize, usize) {
+        let (width, height) = (width.max(1) as f32, height.max(1) as f32);
+        let (cell_width, cell_height) = (self.cell_width as f32, self.cell_height as f32);
+        let (columns, rows) = match (columns, rows) {
+            (0, 0) => ((width / cell_width).ceil(), (height / cell_height).ceil()),
+            (columns, 0) => {
+                let columns = columns as f32;
+                let rows = columns * cell_width * height / width / cell_height;
+                (columns, rows.ceil())
+            }
+            (0, rows) => {
+                let rows = rows as f32;
+                let columns = rows * cell_height * width / height / cell_width;
+                (columns.ceil(), rows)
+            }
+            (columns, rows) => (columns as f32, rows as f32),
+        };
+        let rows = rows.max(1.);
+        let scale = (MAX_IMAGE_ROWS as f32 / rows).min(1.);
+        (
+            (columns * scale).ceil().max(1.) as usize,
+            (rows * scale).ceil() as usize,
+        )
+    }
+
+    /// Writes the placeholder of the image at the cursor, and moves the cursor past the image.
+    fn place(
+        &mut self,
+        data: Arc<ImageData>,
+        columns: usize,
+        rows: usize,
+        movement: CursorMovement,
+        output: &mut Vec<u8>,
+        on_event: &mut impl FnMut(GraphicsEvent),
+    ) -> u32 {
+        // Ids are colors, and wrap around after the 16 million or so of them.
+        self.next_id = (self.next_id + 1) & 0xff_ffff;
+        let id = self.next_id;
+        on_event(GraphicsEvent::Place {
+            id,
+            image: TerminalImage {
+                data,
+                columns,
+                rows,
+            },
+        });
+
+        // The placeholder is written with the colors and position of the cursor saved around it.
+        let mut placeholder = String::new();
+        write!(
+            placeholder,
+            "\x1b7\x1b[38;2;{};{};{}m{IMAGE_PLACEHOLDER}\x1b8",
+            id >> 16,
+            (id >> 8) & 0xff,
+            id & 0xff
+        )
+        .ok();
+        match movement {
+            CursorMovement::None => {}
+            CursorMovement::Below => placeholder.push_str(&"\n".repeat(rows)),
+            CursorMovement::AfterLastRow => {
+                placeholder.push_str(&"\n".repeat(rows.saturating_sub(1)));
+                write!(placeholder, "\x1b[{columns}C").ok();
+            }
+        }
+        output.extend_from_slice(placeholder.as_bytes());
+        id
+    }
+}
+
+enum CursorMovement {
+    None,
+    /// To the line below the image, as for Sixel images.
+    Below,
+    /// To the column after the image on its last line, as for Kitty's images.
+    AfterLastRow,
+}
+
+/// The images Kitty's placements refer to, and the placements made of them.
+#[derive(Default)]
+struct KittyImages {
+    images: HashMap<u32, Arc<ImageData>>,
+    /// The order the images were sent in, to drop the oldest ones first.
+    image_ids: Vec<u32>,
+    placements: Vec<(u32, u32)>,
+    /// The first chunk of an image sent in several.
+    pending: Option<(KittyCommand, Vec<u8>)>,
+}
+
+impl KittyImages {
+    fn insert(&mut self, image_id: u32, image: Arc<ImageData>) {
+        self.image_ids.retain(|id| *id != image_id);
+        if self.image_ids.len() == MAX_KITTY_IMAGES {
+            let oldest_id = self.image_ids.remove(0);
+            self.images.remove(&oldest_id);
+        }
+        self.image_ids.push(image_id);
+        self.images.insert(image_id, image);
+    }
+
+    /// Deletes the placements the command of Kitty's `d` key refers to, returning their ids.
+    /// Its uppercase commands delete the images themselves as well.
+    fn delete(&mut self, delete: u8, image_id: u32) -> Vec<u32> {
+        let deletes_placement = |placement_image_id: u32| match delete.to_ascii_lowercase() {
+            b'a' => true,
+            b'i' => placement_image_id == image_id,
+            _ => false,
+        };
+        let mut deleted = Vec::new();
+        self.placements.retain(|(placement_image_id, id)| {
+            let deletes = deletes_placement(*placement_image_id);
+            if deletes {
+                deleted.push(*id);
+            }
+            !deletes
+        });
+        if delete.is_ascii_uppercase() {
+            match delete {
+                b'A' => {
+                    self.images.clear();
+                    self.image_ids.clear();
+                }
+                b'I' => {
+                    self.images.remove(&image_id);
+                    self.image_ids.retain(|id| *id != image_id);
+                }
+                _ => {}
+            }
+        }
+        deleted
+    }
+}
+
+/// The keys of a command of the Kitty graphics protocol this terminal supports.
+#[derive(Debug, PartialEq)]
+struct KittyCommand {
+    action: u8,
+    format: u32,
+    medium: u8,
+    compressed: bool,
+    width: u32,
+    height: u32,
+    image_id: u32,
+    more: bool,
+    quiet: u32,
+    columns: u32,
+    rows: u32,
+    no_cursor_movement: bool,
+    delete: u8,
+}
+
+impl KittyCommand {
+    fn parse(control: &[u8]) -> Self {
+        let mut command = Self {
+            action: b't',
+            format: 32,
+            medium: b'd',
+            compressed: false,
+            width: 0,
+            height: 0,
+            image_id: 0,
+            more: false,
+            quiet: 0,
+            columns: 0,
+            rows: 0,
+            no_cursor_movement: false,
+            delete: b'a',
+        };
+        for key_value in control.split(|&byte| byte == b',') {
+            let [key, b'=', value @ ..] = key_value else {
+                continue;
+            };
+            let number = || {
+                std::str::from_utf8(value)
+                    .ok()
+                    .and_then(|value| value.parse::<u32>().ok())
+                    .unwrap_or(0)
+            };
+            let character = value.first().copied().unwrap_or(0);
+            match *key {
+                b'a' => command.action = character,
+                b'f' => command.format = number(),
+                b't' => command.medium = character,
+                b'o' => command.compressed = character == b'z',
+                b's' => command.width = number(),
+                b'v' => command.height = number(),
+                b'i' => command.image_id = number(),
+                b'm' => command.more = number() == 1,
+                b'q' => command.quiet = number(),
+                b'c' => command.columns = number(),
+                b'r' => command.rows = number(),
+                b'C' => command.no_cursor_movement = number() == 1,
+                b'd' => command.delete = character,
+                _ => {}
+            }
+        }
+        command
+    }
+
+    /// Decodes the image the command transmits, from its base64 payload.
+    fn load(&self, payload: &[u8]) -> Result<Arc<ImageData>> {
+        let data = base64::decode(payload).context("EINVAL:invalid base64 payload")?;
+        let data = match self.medium {
+            b'd' => data,
+            b'f' | b't' => {
+                let path = String::from_utf8(data).context("EINVAL:invalid file path")?;
+                read_image_file(Path::new(&path), self.medium == b't')?
+            }
+            _ => bail!("EINVAL:unsupported transmission medium"),
+        };
+        let data = if self.compressed {
+            let mut decompressed = Vec::new();
+            flate2::read::ZlibDecoder::new(&data[..])
+                .take(MAX_SEQUENCE_LEN as u64)
+                .read_to_end(&mut decompressed)
+                .context("EINVAL:invalid zlib data")?;
+            decompressed
+        } else {
+            data
+        };
+
+        match self.format {
+            100 => {
+                let image = image::load_from_memory(&data).context("EBADPNG:invalid PNG")?;
+                Ok(Arc::new(ImageData::new(image.into_bgra8())))
+            }
+            24 | 32 => image_data(self.width, self.height, data, self.format as usize / 8)
+                .context("EINVAL:the image data doesn't match its size"),
+            _ => bail!("EINVAL:unsupported format"),
+        }
+    }
+}
+
+/// Reads an image file named by a program, refusing anything but a regular file no longer than
+/// a sequence could be. Temporary files are deleted once read, but only from the system's
+/// temporary directory and only if named as the protocol asks.
+fn read_image_file(path: &Path, temporary: bool) -> Result<Vec<u8>> {
+    let mut options = std::fs::OpenOptions::new();
+    options.read(true);
+    // Opening a FIFO would otherwise block until something writes to it.
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::custom_flags(&mut options, libc::O_NONBLOCK);
+    let file = options.open(path).context("EBADF:cannot open the file")?;
+    let metadata = file.metadata().context("EBADF:cannot read the file")?;
+    if !metadata.is_file() {
+        bail!("EINVAL:not a regular file");
+    }
+    if metadata.len() > MAX_SEQUENCE_LEN as u64 {
+        bail!("EFBIG:the file is too large");
+    }
+    let mut contents = Vec::new();
+    file.take(MAX_SEQUENCE_LEN as u64 + 1)
+        .read_to_end(&mut contents)
+        .context("EBADF:cannot read the file")?;
+    if contents.len() > MAX_SEQUENCE_LEN {
+        bail!("EFBIG:the file is too large");
+    }
+
+    if temporary && is_temporary_image_file(path) {
+        std::fs::remove_file(path).ok();
+    }
+    Ok(contents)
+}
+
+fn is_temporary_image_file(path: &Path) -> bool {
+    let named_as_temporary = path.file_name().map_or(false, |name| {
+        name.to_string_lossy().contains("tty-graphics-protocol")
+    });
+    let directory = path
+        .parent()
+        .and_then(|directory| directory.canonicalize().ok());
+    let temp_dir = std::env::temp_dir().canonicalize().ok();
+    match (directory, temp_dir) {
+        (Some(directory), Some(temp_dir)) => named_as_temporary && directory.starts_with(temp_dir),
+        _ => false,
+    }
+}
+
+/// Converts RGB or RGBA pixels to the image data GPUI paints.
+fn image_data(width: u32, height: u32, pixels: Vec<u8>, channels: usize) -> Option<Arc<ImageData>> {
+    if width == 0 || height == 0 || pixels.len() != width as usize * height as usize * channels {
+        return None;
+    }
+    let bgra = pixels
+        .chunks_exact(channels)
+        .flat_map(|pixel| {
+            [
+                pixel[2],
+                pixel[1],
+                pixel[0],
+                pixel.get(3).copied().unwrap_or(255),
+            ]
+        })
+        .collect();
+    let buffer = ImageBuffer::<Bgra<u8>, Vec<u8>>::from_raw(width, height, bgra)?;
+    Some(Arc::new(ImageData::new(buffer)))
+}
+
+/// Finds the images in and around the screen, from their placeholders. Those whose placeholder
+/// scrolled off the top of the screen are found as long as they're still partly on it.
+pub(crate) fn image_placements<T>(
+    term: &Term<T>,
+    images: &HashMap<u32, TerminalImage>,
+) -> Vec<ImagePlacement> {
+    if images.is_empty() {
+        return Vec::new();
+    }
+    let grid = term.grid();
+    let top = -(grid.display_offset() as i32);
+    let bottom = top + grid.screen_lines() as i32 - 1;
+    let start = (top - MAX_IMAGE_ROWS as i32).max(grid.topmost_line().0);
+
+    let mut placements = Vec::new();
+    for line in start..=bottom {
+        let row = &grid[Line(line)];
+        for column in 0..grid.columns() {
+            let cell = &row[Column(column)];
+            if cell.c != IMAGE_PLACEHOLDER {
+                continue;
+            }
+            let Color::Spec(Rgb { r, g, b }) = cell.fg else {
+                continue;
+            };
+            let id = (r as u32) << 16 | (g as u32) << 8 | b as u32;
+            let Some(image) = images.get(&id) else {
+                continue;
+            };
+            if line + image.rows as i32 > top {
+                placements.push(ImagePlacement {
+                    point: AlacPoint::new(Line(line - top), Column(column)),
+                    image: image.clone(),
+                });
+            }
+        }
+    }
+    placements
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scan(scanner: &mut GraphicsScanner, bytes: &[u8]) -> (Vec<u8>, Vec<GraphicsEvent>) {
+        let mut output = Vec::new();
+        let mut events = Vec::new();
+        scanner.scan(bytes, &mut output, |event| events.push(event));
+        (output, events)
+    }
+
+    #[test]
+    fn test_scan_replaces_images_with_placeholders() {
+        // A 2x2 RGBA image, sent in two chunks, around text and sequences left as they are.
+        let payload = base64::encode([255u8; 16]);
+        let (first_chunk, second_chunk) = payload.split_at(8);
+        let input = format!(
+            "ls\r\n\x1b[1m\x1bP1$qm\x1b\\\x1b_Ga=T,f=32,s=2,v=2,c=4,i=7,m=1;{first_chunk}\x1b\\\
+            \x1b_Gm=0;{second_chunk}\x1b\\done"
+        );
+
+        for chunk_size in [1, 3, input.len()] {
+            let mut scanner = GraphicsScanner::new(10, 20);
+            let mut output = Vec::new();
+            let mut events = Vec::new();
+            for chunk in input.as_bytes().chunks(chunk_size) {
+                let (chunk_output, chunk_events) = scan(&mut scanner, chunk);
+                output.extend(chunk_output);
+                events.extend(chunk_events);
+            }
+
+            assert_eq!(
+                String::from_utf8(output).unwrap(),
+                format!(
+                    "ls\r\n\x1b[1m\x1bP1$qm\x1b\\\
+                    \x1b7\x1b[38;2;0;0;1m{IMAGE_PLACEHOLDER}\x1b8\n\x1b[4Cdone"
+                ),
+                "chunks of {chunk_size} bytes"
+            );
+            match &events[..] {
+                [GraphicsEvent::Place { id: 1, image }, GraphicsEvent::Reply(reply)] => {
+                    assert_eq!((image.columns, image.rows), (4, 2));
+                    assert_eq!(reply, "\x1b_Gi=7;OK\x1b\\");
+                }
+                _ => panic!("unexpected events"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_kitty_placements_and_deletions() {
+        let mut scanner = GraphicsScanner::new(10, 20);
+        let payload = base64::encode([0u8; 3 * 30 * 50]);
+        let (output, events) = scan(
+            &mut scanner,
+            format!("\x1b_Ga=t,f=24,s=30,v=50,i=1,q=1;{payload}\x1b\\\x1b_Ga=p,i=1,C=1,q=1\x1b\\")
+                .as_bytes(),
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            format!("\x1b7\x1b[38;2;0;0;1m{IMAGE_PLACEHOLDER}\x1b8")
+        );
+        match &events[..] {
+            [GraphicsEvent::Place { image, .. }] => {
+                assert_eq!((image.columns, image.rows), (3, 3))
+            }
+            _ => panic!("unexpected events"),
+        }
+
+        let (_, events) = scan(&mut scanner, b"\x1b_Ga=p,i=2\x1b\\\x1b_Ga=d,d=I,i=1\x1b\\");
+        match &events[..] {
+            [GraphicsEvent::Reply(reply), GraphicsEvent::Delete(ids)] => {
+                assert_eq!(reply, "\x1b_Gi=2;ENOENT:no image with id 2\x1b\\");
+                assert_eq!(ids, &[1]);
+            }
+            _ => panic!("unexpected events"),
+        }
+        assert!(scanner.kitty.images.is_empty());
+    }
+
+    #[test]
+    fn test_kitty_file_media() {
+        let mut scanner = GraphicsScanner::new(10, 20);
+        let mut reply = |medium: &str, path: &Path| {
+            let path = base64::encode(path.to_str().unwrap());
+            let (_, events) = scan(
+                &mut scanner,
+                format!("\x1b_Ga=q,f=24,s=1,v=1,i=3,t={medium};{path}\x1b\\").as_bytes(),
+            );
+            match &events[..] {
+                [GraphicsEvent::Reply(reply)] => reply.clone(),
+                _ => panic!("unexpected events"),
+            }
+        };
+
+        // Temporary files are only deleted when named as such.
+        let temp_dir = std::env::temp_dir();
+        let temporary = temp_dir.join(format!("tty-graphics-protocol-{}", std::process::id()));
+        let other = temp_dir.join(format!("zed-terminal-image-{}", std::process::id()));
+        for path in [&temporary, &other] {
+            std::fs::write(path, [0u8; 3]).unwrap();
+            assert_eq!(reply("t", path), "\x1b_Gi=3;OK\x1b\\");
+        }
+        assert!(!temporary.exists());
+        assert!(other.exists());
+        std::fs::remove_file(&other).unwrap();
+
+        // Directories and devices are refused rather than read.
+        assert_eq!(
+            reply("f", &temp_dir),
+            "\x1b_Gi=3;EINVAL:not a regular file\x1b\\"
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            reply("f", Path::new("/dev/zero")),
+            "\x1b_Gi=3;EINVAL:not a regular file\x1b\\"
+        );
+    }
+}
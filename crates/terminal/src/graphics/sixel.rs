@@ -0,0 +1,239 @@
+//! A decoder of the pixels of Sixel images, as sent between the `q` of their DCS sequence and its
+//! terminator.
+
+/// Images larger than this many pixels on either side are not decoded.
+const MAX_SIDE: usize = 10_000;
+/// Images with more pixels than this are not decoded, however they are shaped.
+const MAX_PIXELS: usize = 4096 * 4096;
+
+/// The default colors of the VT340, in percents of red, green and blue.
+const DEFAULT_PALETTE: [[u32; 3]; 16] = [
+    [0, 0, 0],
+    [20, 20, 80],
+    [80, 13, 13],
+    [20, 80, 20],
+    [80, 20, 80],
+    [20, 80, 80],
+    [80, 80, 20],
+    [53, 53, 53],
+    [26, 26, 26],
+    [33, 33, 60],
+    [60, 26, 26],
+    [33, 60, 33],
+    [60, 33, 60],
+    [33, 60, 60],
+    [60, 60, 33],
+    [80, 80, 80],
+];
+
+/// Decodes the image into its width, height and RGBA pixels. Pixels left out by the image are
+/// transparent, so that the terminal's background shows through them.
+pub(super) fn decode(data: &[u8]) -> Option<(u32, u32, Vec<u8>)> {
+    let mut decoder = Decoder::new(None);
+    decoder.run(data);
+    let width = decoder.width.max(decoder.raster_width).min(MAX_SIDE);
+    let height = decoder.height.max(decoder.raster_height).min(MAX_SIDE);
+    if width == 0 || height == 0 || width * height > MAX_PIXELS {
+        return None;
+    }
+
+    let mut pixels = vec![0; width * height * 4];
+    let mut decoder = Decoder::new(Some((&mut pixels, width, height)));
+    decoder.run(data);
+    Some((width as u32, height as u32, pixels))
+}
+
+struct Decoder<'a> {
+    palette: [[u8; 4]; 256],
+    color: usize,
+    x: usize,
+    y: usize,
+    /// The extent of the sixels drawn so far.
+    width: usize,
+    height: usize,
+    /// The size the image declares with its raster attributes.
+    raster_width: usize,
+    raster_height: usize,
+    pixels: Option<(&'a mut [u8], usize, usize)>,
+}
+
+impl<'a> Decoder<'a> {
+    fn new(pixels: Option<(&'a mut [u8], usize, usize)>) -> Self {
+        let mut palette = [[0, 0, 0, 255]; 256];
+        for (color, rgb) in palette.iter_mut().zip(DEFAULT_PALETTE) {
+            *color = rgb_color(rgb);
+        }
+        Self {
+            palette,
+            color: 0,
+            x: 0,
+            y: 0,
+            width: 0,
+            height: 0,
+            raster_width: 0,
+            raster_height: 0,
+            pixels,
+        }
+    }
+
+    fn run(&mut self, data: &[u8]) {
+        let mut ix = 0;
+        while ix < data.len() {
+            let byte = data[ix];
+            ix += 1;
+            match byte {
+                b'"' => {
+                    let params = parse_params(data, &mut ix);
+                    if let [_, _, width, height, ..] = params[..] {
+                        self.raster_width = width as usize;
+                        self.raster_height = height as usize;
+                    }
+                }
+                b'#' => {
+                    let params = parse_params(data, &mut ix);
+                    let Some(&color) = params.first() else {
+                        continue;
+                    };
+                    self.color = color as usize % self.palette.len();
+                    if let [_, space, a, b, c] = params[..] {
+                        self.palette[self.color] = match space {
+                            1 => hls_color(a, b, c),
+                            _ => rgb_color([a, b, c]),
+                        };
+                    }
+                }
+                b'!' => {
+                    let repeat = parse_params(data, &mut ix).first().copied().unwrap_or(1);
+                    if let Some(&sixel @ b'?'..=b'~') = data.get(ix) {
+                        ix += 1;
+                        self.draw(sixel - b'?', (repeat as usize).clamp(1, MAX_SIDE));
+                    }
+                }
+                b'$' => self.x = 0,
+                b'-' => {
+                    self.x = 0;
+                    self.y += 6;
+                }
+                b'?'..=b'~' => self.draw(byte - b'?', 1),
+                _ => {}
+            }
+        }
+    }
+
+    fn draw(&mut self, bits: u8, repeat: usize) {
+        if bits != 0 {
+            let top_bit = 7 - bits.leading_zeros() as usize;
+            self.width = self.width.max(self.x + repeat);
+            self.height = self.height.max(self.y + top_bit + 1);
+            if let Some((pixels, width, height)) = self.pixels.as_mut() {
+                let color = self.palette[self.color];
+                for bit in 0..6 {
+                    let y = self.y + bit;
+                    if bits & (1 << bit) == 0 || y >= *height {
+                        continue;
+                    }
+                    for x in self.x..(self.x + repeat).min(*width) {
+                        let offset = (y * *width + x) * 4;
+                        pixels[offset..offset + 4].copy_from_slice(&color);
+                    }
+                }
+            }
+        }
+        self.x = (self.x + repeat).min(MAX_SIDE);
+    }
+}
+
+fn parse_params(data: &[u8], ix: &mut usize) -> Vec<u32> {
+    let mut params = Vec::new();
+    let mut param: Option<u32> = None;
+    while let Some(&byte) = data.get(*ix) {
+        match byte {
+            b'0'..=b'9' => {
+                let digit = (byte - b'0') as u32;
+                param = Some(param.unwrap_or(0).saturating_mul(10).saturating_add(digit));
+            }
+            b';' => params.push(param.take().unwrap_or(0)),
+            _ => break,
+        }
+        *ix += 1;
+    }
+    params.extend(param);
+    params
+}
+
+fn rgb_color(percents: [u32; 3]) -> [u8; 4] {
+    let [r, g, b] = percents.map(|percent| (percent.min(100) * 255 / 100) as u8);
+    [r, g, b, 255]
+}
+
+/// Converts a color of Sixel's HLS space, where blue rather than red is at 0°.
+fn hls_color(hue: u32, lightness: u32, saturation: u32) -> [u8; 4] {
+    let hue = (hue % 360 + 240) as f32 / 360.;
+    let lightness = lightness.min(100) as f32 / 100.;
+    let saturation = saturation.min(100) as f32 / 100.;
+    let q = if lightness < 0.5 {
+        lightness * (1. + saturation)
+    } else {
+        lightness + saturation - lightness * saturation
+    };
+    let p = 2. * lightness - q;
+    let channel = |t: f32| {
+        let t = t.rem_euclid(1.);
+        let value = if t < 1. / 6. {
+            p + (q - p) * 6. * t
+        } else if t < 0.5 {
+            q
+        } else if t < 2. / 3. {
+            p + (q - p) * (2. / 3. - t) * 6.
+        } else {
+            p
+        };
+        (value * 255.).round() as u8
+    };
+    [
+        channel(hue + 1. / 3.),
+        channel(hue),
+        channel(hue - 1. / 3.),
+        255,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_sixels() {
+        // A red 3x6 block, drawn with a repeat, next to a sixel of green whose top and bottom
+        // pixels are left out, and a second band with a single blue pixel.
+        let (width, height, pixels) =
+            decode(b"\"1;1;5;8#1;2;100;0;0!3~#2;2;0;100;0]-#3;2;0;0;100@").unwrap();
+        assert_eq!((width, height), (5, 8));
+
+        let pixel = |x: usize, y: usize| {
+            let offset = (y * width as usize + x) * 4;
+            <[u8; 4]>::try_from(&pixels[offset..offset + 4]).unwrap()
+        };
+        assert_eq!(pixel(0, 0), [255, 0, 0, 255]);
+        assert_eq!(pixel(2, 5), [255, 0, 0, 255]);
+        assert_eq!(pixel(3, 0), [0, 0, 0, 0]);
+        assert_eq!(pixel(3, 1), [0, 255, 0, 255]);
+        assert_eq!(pixel(3, 4), [0, 255, 0, 255]);
+        assert_eq!(pixel(3, 5), [0, 0, 0, 0]);
+        assert_eq!(pixel(0, 6), [0, 0, 255, 255]);
+        assert_eq!(pixel(1, 6), [0, 0, 0, 0]);
+        assert_eq!(pixel(4, 7), [0, 0, 0, 0]);
+        assert_eq!(hls_color(120, 50, 100), [255, 0, 0, 255]);
+    }
+
+    #[test]
+    fn test_decode_hostile_sixels() {
+        // Either side is within bounds, but not the pixels they'd take together.
+        assert_eq!(decode(b"\"1;1;10000;10000#1~"), None);
+        // Hues wrap around rather than overflow.
+        assert_eq!(
+            hls_color(u32::MAX, 50, 100),
+            hls_color(u32::MAX % 360, 50, 100)
+        );
+    }
+}
@@ -0,0 +1,157 @@
+use alacritty_terminal::{
+    index::Point as AlacPoint,
+    term::search::{Match, RegexSearch},
+    Term,
+};
+use anyhow::anyhow;
+use gpui::{AppContext, Global};
+use regex::{Captures, Regex};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use settings::Settings;
+use util::ResultExt;
+
+use crate::{regex_match_at, terminal_settings::TerminalSettings};
+
+/// The patterns making the errors of common tools links, on top of the paths the terminal finds
+/// on its own.
+const BUILTIN_LINK_PATTERNS: &[&str] = &[
+    // Python tracebacks: `File "app/main.py", line 12, in main`
+    r#"File "(?P<path>[^"]+)", line (?P<line>\d+)"#,
+    // Java and Kotlin stack traces: `at com.example.Main.run(Main.java:42)`
+    r"at [\w$.<>/]+\((?P<path>[\w$-]+\.(?:java|kt|scala|groovy)):(?P<line>\d+)\)",
+    // The failure summaries of pytest: `FAILED tests/test_app.py::test_main - AssertionError`
+    r"(?:FAILED|ERROR) (?P<path>[^\s:]+\.py)::",
+    // MSBuild and the compilers following it: `Program.cs(12,34): error CS1002`
+    r"(?P<path>[^\s()]+)\((?P<line>\d+)(?:,(?P<column>\d+))?\): (?:error|warning)",
+];
+
+/// A pattern making the terminal output it matches a link to a position in a file.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct TerminalLinkPattern {
+    /// The regex matching the link. Its `path` named group is the file to open, and its
+    /// optional `line` and `column` named groups the position to open it at.
+    pub regex: String,
+}
+
+/// The link patterns contributed by extensions.
+#[derive(Default)]
+struct ExtensionLinkPatterns(Vec<TerminalLinkPattern>);
+
+impl Global for ExtensionLinkPatterns {}
+
+/// Replaces the link patterns contributed by extensions.
+pub fn set_extension_link_patterns(patterns: Vec<TerminalLinkPattern>, cx: &mut AppContext) {
+    cx.set_global(ExtensionLinkPatterns(patterns));
+}
+
+struct LinkPattern {
+    search: RegexSearch,
+    captures: Regex,
+}
+
+/// Finds the links of the built-in, user and extension link patterns in the terminal's
+/// content, compiling the patterns again whenever they change.
+#[derive(Default)]
+pub(crate) struct LinkPatterns {
+    sources: Vec<String>,
+    patterns: Vec<LinkPattern>,
+}
+
+impl LinkPatterns {
+    /// Returns the range of the link at the point, along with the path it links to, suffixed
+    /// with the `:line:column` to open it at.
+    pub fn link_at<T>(
+        &mut self,
+        term: &Term<T>,
+        point: AlacPoint,
+        cx: &AppContext,
+    ) -> Option<(Match, String)> {
+        self.refresh(cx);
+        self.patterns.iter_mut().find_map(|pattern| {
+            let link_match = regex_match_at(term, point, &mut pattern.search)?;
+            let text = term.bounds_to_string(*link_match.start(), *link_match.end());
+            let path = link_path(&pattern.captures.captures(&text)?)?;
+            Some((link_match, path))
+        })
+    }
+
+    fn refresh(&mut self, cx: &AppContext) {
+        let user_patterns = &TerminalSettings::get_global(cx).link_patterns;
+        let extension_patterns = cx
+            .try_global::<ExtensionLinkPatterns>()
+            .map_or(&[][..], |patterns| &patterns.0);
+        let sources = BUILTIN_LINK_PATTERNS
+            .iter()
+            .map(|pattern| pattern.to_string())
+            .chain(user_patterns.iter().map(|pattern| pattern.regex.clone()))
+            .chain(
+                extension_patterns
+                    .iter()
+                    .map(|pattern| pattern.regex.clone()),
+            )
+            .collect::<Vec<_>>();
+        if sources == self.sources {
+            return;
+        }
+
+        self.patterns = sources
+            .iter()
+            .filter_map(|source| {
+                Some(LinkPattern {
+                    search: RegexSearch::new(source)
+                        .map_err(|error| anyhow!("invalid link pattern {source:?}: {error}"))
+                        .log_err()?,
+                    captures: Regex::new(source)
+                        .map_err(|error| anyhow!("invalid link pattern {source:?}: {error}"))
+                        .log_err()?,
+                })
+            })
+            .collect();
+        self.sources = sources;
+    }
+}
+
+fn link_path(captures: &Captures) -> Option<String> {
+    let mut path = captures.name("path")?.as_str().to_string();
+    if let Some(line) = captures.name("line") {
+        path.push(':');
+        path.push_str(line.as_str());
+        if let Some(column) = captures.name("column") {
+            path.push(':');
+            path.push_str(column.as_str());
+        }
+    }
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_link_patterns() {
+        let outputs = [
+            (
+                r#"  File "app/main.py", line 12, in main"#,
+                "app/main.py:12",
+            ),
+            ("\tat com.example.Main.run(Main.java:42)", "Main.java:42"),
+            (
+                "FAILED tests/test_app.py::test_main - AssertionError",
+                "tests/test_app.py",
+            ),
+            (
+                "Program.cs(12,34): error CS1002: ; expected",
+                "Program.cs:12:34",
+            ),
+        ];
+
+        for (output, expected_path) in outputs {
+            let path = BUILTIN_LINK_PATTERNS
+                .iter()
+                .find_map(|pattern| link_path(&Regex::new(pattern).unwrap().captures(output)?));
+            assert_eq!(path.as_deref(), Some(expected_path), "{output}");
+        }
+    }
+}
@@ -0,0 +1,299 @@
+//! Writing the terminal's output to files: logs of what the shell prints, as it prints it, and
+//! exports of the scrollback.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use alacritty_terminal::{
+    grid::Dimensions,
+    index::{Column, Line, Point as AlacPoint},
+    sync::FairMutex,
+    term::cell::{Cell, Flags},
+    vte::ansi::Color,
+    Term,
+};
+use std::{
+    fmt::Write as _,
+    fs::{self, File},
+    io::{self, Write as _},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+use util::paths::LOGS_DIR;
+
+use crate::terminal_settings::{TaskLogs, TerminalOutputFormat};
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+const CAN: u8 = 0x18;
+const SUB: u8 = 0x1a;
+
+/// The log the shell's output is written to as it's read from the pty, when there's one.
+pub(crate) type SharedOutputLog = Arc<FairMutex<Option<OutputLog>>>;
+
+pub(crate) struct OutputLog {
+    path: PathBuf,
+    file: File,
+    /// Strips the escape sequences out of the output of plain logs.
+    stripper: Option<EscapeStripper>,
+}
+
+impl OutputLog {
+    /// Opens the log, appending to the file when it exists.
+    pub fn open(path: PathBuf, format: TerminalOutputFormat) -> io::Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let file = File::options().create(true).append(true).open(&path)?;
+        Ok(Self {
+            path,
+            file,
+            stripper: match format {
+                TerminalOutputFormat::Plain => Some(EscapeStripper::default()),
+                TerminalOutputFormat::Raw => None,
+            },
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn write(&mut self, output: &[u8]) -> io::Result<()> {
+        match &mut self.stripper {
+            Some(stripper) => {
+                let mut text = Vec::with_capacity(output.len());
+                stripper.strip(output, &mut text);
+                self.file.write_all(&text)
+            }
+            None => self.file.write_all(output),
+        }
+    }
+}
+
+#[derive(Default)]
+enum StripState {
+    #[default]
+    Ground,
+    Escape,
+    /// An escape sequence with intermediate bytes, such as those designating character sets.
+    Intermediate,
+    Csi,
+    /// An OSC, DCS, APC, PM or SOS sequence, terminated by ST or BEL.
+    String,
+    /// An escape in a string sequence, which may start its terminator.
+    StringEscape,
+}
+
+/// Strips the escape sequences and control characters other than newlines and tabs out of the
+/// shell's output, which may split them across reads.
+#[derive(Default)]
+struct EscapeStripper {
+    state: StripState,
+}
+
+impl EscapeStripper {
+    fn strip(&mut self, bytes: &[u8], text: &mut Vec<u8>) {
+        for &byte in bytes {
+            if matches!(byte, CAN | SUB) {
+                self.state = StripState::Ground;
+                continue;
+            }
+            self.state = match self.state {
+                StripState::Ground => match byte {
+                    ESC => StripState::Escape,
+                    b'\n' | b'\t' => {
+                        text.push(byte);
+                        StripState::Ground
+                    }
+                    0x00..=0x1f | 0x7f => StripState::Ground,
+                    _ => {
+                        text.push(byte);
+                        StripState::Ground
+                    }
+                },
+                StripState::StringEscape if byte == b'\\' => StripState::Ground,
+                StripState::Escape | StripState::StringEscape => match byte {
+                    b'[' => StripState::Csi,
+                    b']' | b'P' | b'_' | b'^' | b'X' => StripState::String,
+                    0x20..=0x2f => StripState::Intermediate,
+                    ESC => StripState::Escape,
+                    _ => StripState::Ground,
+                },
+                StripState::Intermediate => match byte {
+                    0x20..=0x2f => StripState::Intermediate,
+                    _ => StripState::Ground,
+                },
+                StripState::Csi => match byte {
+                    0x40..=0x7e => StripState::Ground,
+                    _ => StripState::Csi,
+                },
+                StripState::String => match byte {
+                    BEL => StripState::Ground,
+                    ESC => StripState::StringEscape,
+                    _ => StripState::String,
+                },
+            };
+        }
+    }
+}
+
+/// Returns the terminal's scrollback and screen, with the colors and styles of the text as
+/// escape sequences in the raw format.
+pub(crate) fn export<T>(term: &Term<T>, format: TerminalOutputFormat) -> String {
+    let grid = term.grid();
+    let mut text = match format {
+        TerminalOutputFormat::Plain => term.bounds_to_string(
+            AlacPoint::new(grid.topmost_line(), Column(0)),
+            AlacPoint::new(grid.bottommost_line(), grid.last_column()),
+        ),
+        TerminalOutputFormat::Raw => styled_text(term),
+    };
+    text.truncate(text.trim_end().len());
+    text.push('\n');
+    text
+}
+
+fn styled_text<T>(term: &Term<T>) -> String {
+    let grid = term.grid();
+    let style_flags = Flags::BOLD
+        | Flags::DIM
+        | Flags::ITALIC
+        | Flags::ALL_UNDERLINES
+        | Flags::INVERSE
+        | Flags::HIDDEN
+        | Flags::STRIKEOUT;
+    let default_style = (Cell::default().fg, Cell::default().bg, Flags::empty());
+
+    let mut text = String::new();
+    for line in grid.topmost_line().0..=grid.bottommost_line().0 {
+        let row = &grid[Line(line)];
+        let end = (0..grid.columns())
+            .rev()
+            .find(|column| {
+                let cell = &row[Column(*column)];
+                cell.c != ' '
+                    || (cell.bg, cell.flags & style_flags) != (default_style.1, Flags::empty())
+            })
+            .map_or(0, |column| column + 1);
+
+        let mut style = default_style;
+        for column in 0..end {
+            let cell = &row[Column(column)];
+            if cell
+                .flags
+                .intersects(Flags::WIDE_CHAR_SPACER | Flags::LEADING_WIDE_CHAR_SPACER)
+            {
+                continue;
+            }
+            let cell_style = (cell.fg, cell.bg, cell.flags & style_flags);
+            if cell_style != style {
+                style = cell_style;
+                text.push_str(&sgr(style.0, style.1, style.2));
+            }
+            text.push(cell.c);
+            if let Some(zerowidth) = cell.zerowidth() {
+                text.extend(zerowidth);
+            }
+        }
+        if style != default_style {
+            text.push_str("\x1b[0m");
+        }
+        if !row[grid.last_column()].flags.contains(Flags::WRAPLINE) {
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// The SGR sequence setting the style, from the default one.
+fn sgr(fg: Color, bg: Color, flags: Flags) -> String {
+    let mut sequence = "\x1b[0".to_string();
+    for (flag, param) in [
+        (Flags::BOLD, 1),
+        (Flags::DIM, 2),
+        (Flags::ITALIC, 3),
+        (Flags::ALL_UNDERLINES, 4),
+        (Flags::INVERSE, 7),
+        (Flags::HIDDEN, 8),
+        (Flags::STRIKEOUT, 9),
+    ] {
+        if flags.intersects(flag) {
+            write!(sequence, ";{param}").ok();
+        }
+    }
+    for (color, base) in [(fg, 30), (bg, 40)] {
+        match color {
+            // The named colors other than the 16 of the palette are the default ones.
+            Color::Named(name) => match name as usize {
+                index @ 0..=7 => write!(sequence, ";{}", base + index),
+                index @ 8..=15 => write!(sequence, ";{}", base + 60 + index - 8),
+                _ => Ok(()),
+            },
+            Color::Indexed(index) => write!(sequence, ";{};5;{index}", base + 8),
+            Color::Spec(rgb) => write!(sequence, ";{};2;{};{};{}", base + 8, rgb.r, rgb.g, rgb.b),
+        }
+        .ok();
+    }
+    sequence.push('m');
+    sequence
+}
+
+/// The file a run of a task is logged to, named after the task and the time it started at.
+pub fn task_log_path(settings: &TaskLogs, task_label: &str) -> PathBuf {
+    let directory = settings
+        .directory
+        .clone()
+        .unwrap_or_else(|| LOGS_DIR.join("tasks"));
+    let name = task_label
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect::<String>();
+    let started_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs());
+    directory.join(format!("{name}-{started_at}.log"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_escape_sequences() {
+        let output = b"\x1b]0;~/zed\x07\x1b[1;32m$\x1b[0m cargo build\r\n\x1b(B\
+            \x1b]133;C\x1b\\   Compiling\x1b[K zed\x08\r\n\x1bP1$qm\x1b\\done\t!\n";
+        let expected = "$ cargo build\n   Compiling zed\ndone\t!\n";
+
+        for chunk_size in [1, 2, 5, output.len()] {
+            let mut stripper = EscapeStripper::default();
+            let mut text = Vec::new();
+            for chunk in output.chunks(chunk_size) {
+                stripper.strip(chunk, &mut text);
+            }
+            assert_eq!(
+                String::from_utf8(text).unwrap(),
+                expected,
+                "chunks of {chunk_size} bytes"
+            );
+        }
+    }
+
+    #[test]
+    fn test_sgr() {
+        assert_eq!(
+            sgr(
+                Color::Named(alacritty_terminal::vte::ansi::NamedColor::BrightRed),
+                Color::Indexed(236),
+                Flags::BOLD | Flags::UNDERLINE
+            ),
+            "\x1b[0;1;4;91;48;5;236m"
+        );
+    }
+}
@@ -3,7 +3,7 @@ use alacritty_terminal::tty::Pty;
 use std::num::NonZeroU32;
 #[cfg(unix)]
 use std::os::fd::AsRawFd;
-use std::path::PathBuf;
+use std::{fs, path::PathBuf};
 
 #[cfg(target_os = "windows")]
 use windows::Win32::{Foundation::HANDLE, System::Threading::GetProcessId};
@@ -76,6 +76,7 @@ pub struct PtyProcessInfo {
     system: System,
     refresh_kind: ProcessRefreshKind,
     pid_getter: ProcessIdGetter,
+    session_pid_file: Option<PathBuf>,
     pub current: Option<ProcessInfo>,
 }
 
@@ -92,12 +93,24 @@ impl PtyProcessInfo {
             system,
             refresh_kind: process_refresh_kind,
             pid_getter: ProcessIdGetter::new(pty),
+            session_pid_file: None,
             current: None,
         }
     }
 
+    /// Follows the shell whose pid is written to the file, rather than the process in the
+    /// foreground of the pty, which is `dtach` for the shells run in a session.
+    pub(crate) fn follow_session(&mut self, pid_file: PathBuf) {
+        self.session_pid_file = Some(pid_file);
+    }
+
+    fn session_pid(&self) -> Option<Pid> {
+        let pid = fs::read_to_string(self.session_pid_file.as_ref()?).ok()?;
+        Some(Pid::from_u32(pid.trim().parse().ok()?))
+    }
+
     fn refresh(&mut self) -> Option<&Process> {
-        let pid = self.pid_getter.pid()?;
+        let pid = self.session_pid().or_else(|| self.pid_getter.pid())?;
         if self
             .system
             .refresh_process_specifics(pid, self.refresh_kind)
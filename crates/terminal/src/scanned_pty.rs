@@ -0,0 +1,157 @@
+//! A pty picking what Alacritty ignores out of the shell's output before Alacritty parses it:
+//! the marks of shell integration, and inline images. It also writes the output to the
+//! terminal's log, when it has one.
+
+use alacritty_terminal::{
+    event::{OnResize, WindowSize},
+    tty::{ChildEvent, EventedPty, EventedReadWrite, Pty},
+};
+use polling::{Event, PollMode, Poller};
+use smol::channel::Sender;
+use std::{
+    fs::File,
+    io::{self, Read},
+    sync::Arc,
+    time::Instant,
+};
+
+use crate::{
+    graphics::{GraphicsEvent, GraphicsScanner},
+    output_log::SharedOutputLog,
+    shell_integration::{MarkScanner, ShellMark},
+};
+
+/// A pty reporting the shell marks in what's read from it, and replacing the images in it with
+/// their placeholders.
+pub(crate) struct ScannedPty {
+    pty: Pty,
+    reader: ScannedReader,
+}
+
+pub(crate) struct ScannedReader {
+    file: File,
+    marks: MarkScanner,
+    marks_tx: Sender<(ShellMark, Instant)>,
+    graphics: GraphicsScanner,
+    graphics_tx: Sender<GraphicsEvent>,
+    output_log: SharedOutputLog,
+    buffer: Vec<u8>,
+    /// The output left to read, as images make it longer than what was read from the pty.
+    output: Vec<u8>,
+    output_start: usize,
+}
+
+impl ScannedPty {
+    pub fn new(
+        pty: Pty,
+        window_size: WindowSize,
+        marks_tx: Sender<(ShellMark, Instant)>,
+        graphics_tx: Sender<GraphicsEvent>,
+        output_log: SharedOutputLog,
+    ) -> io::Result<Self> {
+        Ok(Self {
+            reader: ScannedReader {
+                file: pty.file().try_clone()?,
+                marks: MarkScanner::default(),
+                marks_tx,
+                graphics: GraphicsScanner::new(window_size.cell_width, window_size.cell_height),
+                graphics_tx,
+                output_log,
+                buffer: vec![0; 0x10000],
+                output: Vec::new(),
+                output_start: 0,
+            },
+            pty,
+        })
+    }
+}
+
+impl Read for ScannedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        // Reads whose bytes all belong to an image have no output, and are followed by the next
+        // one, until the pty has nothing more to read.
+        while self.output_start == self.output.len() {
+            let len = self.file.read(&mut self.buffer)?;
+            if len == 0 {
+                return Ok(0);
+            }
+            // Logs get the output as the shell printed it, images included. A log that can't
+            // be written to is closed.
+            let mut output_log = self.output_log.lock();
+            if output_log
+                .as_mut()
+                .is_some_and(|log| log.write(&self.buffer[..len]).is_err())
+            {
+                *output_log = None;
+            }
+            drop(output_log);
+
+            self.output.clear();
+            self.output_start = 0;
+            self.graphics
+                .scan(&self.buffer[..len], &mut self.output, |event| {
+                    self.graphics_tx.try_send(event).ok();
+                });
+            let now = Instant::now();
+            self.marks.scan(&self.output, |mark| {
+                self.marks_tx.try_send((mark, now)).ok();
+            });
+        }
+
+        let output = &self.output[self.output_start..];
+        let len = output.len().min(buf.len());
+        buf[..len].copy_from_slice(&output[..len]);
+        self.output_start += len;
+        Ok(len)
+    }
+}
+
+impl EventedReadWrite for ScannedPty {
+    type Reader = ScannedReader;
+    type Writer = File;
+
+    unsafe fn register(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.register(poll, interest, mode)
+    }
+
+    fn reregister(
+        &mut self,
+        poll: &Arc<Poller>,
+        interest: Event,
+        mode: PollMode,
+    ) -> io::Result<()> {
+        self.pty.reregister(poll, interest, mode)
+    }
+
+    fn deregister(&mut self, poll: &Arc<Poller>) -> io::Result<()> {
+        self.pty.deregister(poll)
+    }
+
+    fn reader(&mut self) -> &mut ScannedReader {
+        &mut self.reader
+    }
+
+    fn writer(&mut self) -> &mut File {
+        self.pty.writer()
+    }
+}
+
+impl EventedPty for ScannedPty {
+    fn next_child_event(&mut self) -> Option<ChildEvent> {
+        self.pty.next_child_event()
+    }
+}
+
+impl OnResize for ScannedPty {
+    fn on_resize(&mut self, window_size: WindowSize) {
+        self.reader
+            .graphics
+            .set_cell_size(window_size.cell_width, window_size.cell_height);
+        self.pty.on_resize(window_size)
+    }
+}
@@ -0,0 +1,150 @@
+//! Terminal sessions outliving Zed: shells run under `dtach`, which keeps them running when Zed
+//! quits, so that the terminals restored on the next start attach to them again.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use collections::HashMap;
+use std::path::PathBuf;
+use util::paths::SUPPORT_DIR;
+
+use crate::terminal_settings::Shell;
+
+/// What's restored of a terminal of the previous run of Zed.
+#[derive(Clone, Debug, Default)]
+pub struct RestoredTerminal {
+    /// The title the shell last gave the terminal.
+    pub title: String,
+    /// The environment the terminal was spawned with.
+    pub env: HashMap<String, String>,
+    /// The last lines of the terminal's scrollback, shown above the new prompt.
+    pub scrollback: String,
+    /// The session running the terminal's shell, which may still be running.
+    pub session_id: Option<String>,
+}
+
+/// A shell run under `dtach`, named by an id unique to its terminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TerminalSession {
+    id: String,
+}
+
+impl TerminalSession {
+    pub fn new(id: String) -> Self {
+        Self { id }
+    }
+
+    pub fn with_random_id() -> Self {
+        Self::new(format!("{:016x}", rand::random::<u64>()))
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn socket_path(&self) -> PathBuf {
+        SUPPORT_DIR.join("sessions").join(&self.id)
+    }
+
+    /// The file the shell of the session writes its pid to.
+    pub(crate) fn pid_path(&self) -> PathBuf {
+        self.socket_path().with_extension("pid")
+    }
+
+    /// Wraps the shell in `dtach`, which starts the session running it, or attaches to the
+    /// session when it's still running. Returns `None` when `dtach` isn't installed.
+    #[cfg(unix)]
+    pub(crate) fn attach(&self, shell: &Shell) -> Option<Shell> {
+        use util::ResultExt;
+
+        let dtach = std::env::var_os("PATH").and_then(|path| {
+            std::env::split_paths(&path)
+                .map(|dir| dir.join("dtach"))
+                .find(|dtach| dtach.is_file())
+        })?;
+        let (program, args) = match shell.clone() {
+            Shell::System => (
+                std::env::var("SHELL").unwrap_or_else(|_| "/bin/sh".to_string()),
+                Vec::new(),
+            ),
+            Shell::Program(program) => (program, Vec::new()),
+            Shell::WithArguments { program, args } => (program, args),
+        };
+        std::fs::create_dir_all(self.socket_path().parent()?).log_err()?;
+        Some(Shell::WithArguments {
+            program: dtach.to_string_lossy().into_owned(),
+            args: self.dtach_args(program, args),
+        })
+    }
+
+    /// The arguments `dtach` runs the program of the session with.
+    fn dtach_args(&self, program: String, args: Vec<String>) -> Vec<String> {
+        // The detach key is disabled, leaving Ctrl-\ to the shell, and the shell is told to
+        // redraw its prompt when attached to again.
+        let mut dtach_args = vec![
+            "-A".to_string(),
+            self.socket_path().to_string_lossy().into_owned(),
+            "-E".to_string(),
+            "-r".to_string(),
+            "winch".to_string(),
+            "-z".to_string(),
+            "/bin/sh".to_string(),
+            "-c".to_string(),
+            r#"echo $$ > "$0" && exec "$@""#.to_string(),
+            self.pid_path().to_string_lossy().into_owned(),
+            program,
+        ];
+        dtach_args.extend(args);
+        dtach_args
+    }
+
+    #[cfg(not(unix))]
+    pub(crate) fn attach(&self, _: &Shell) -> Option<Shell> {
+        None
+    }
+
+    /// Hangs up the shell of the session, ending it.
+    pub(crate) fn end(&self) {
+        let pid_path = self.pid_path();
+        #[cfg(unix)]
+        if let Some(pid) = std::fs::read_to_string(&pid_path)
+            .ok()
+            .and_then(|pid| pid.trim().parse::<libc::pid_t>().ok())
+        {
+            // SAFETY: `kill` only sends a signal to the pid the session's shell wrote; no memory
+            // is passed to it.
+            unsafe { libc::kill(pid, libc::SIGHUP) };
+        }
+        std::fs::remove_file(pid_path).ok();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dtach_args() {
+        let session = TerminalSession::new("0123456789abcdef".to_string());
+        let socket_path = SUPPORT_DIR.join("sessions").join("0123456789abcdef");
+        let args = session.dtach_args("/bin/zsh".to_string(), vec!["-l".to_string()]);
+        assert_eq!(
+            args,
+            vec![
+                "-A".to_string(),
+                socket_path.to_string_lossy().into_owned(),
+                "-E".to_string(),
+                "-r".to_string(),
+                "winch".to_string(),
+                "-z".to_string(),
+                "/bin/sh".to_string(),
+                "-c".to_string(),
+                r#"echo $$ > "$0" && exec "$@""#.to_string(),
+                socket_path
+                    .with_extension("pid")
+                    .to_string_lossy()
+                    .into_owned(),
+                "/bin/zsh".to_string(),
+                "-l".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,257 @@
+//! Shell integration: scripts making bash, zsh and fish mark their prompts and commands with
+//! OSC 133 sequences, and a scanner picking those marks out of the shell's output before
+//! Alacritty, which ignores them, parses it.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use anyhow::Context as _;
+use collections::HashMap;
+use std::{fs, path::Path};
+use util::{paths::SUPPORT_DIR, ResultExt};
+
+use crate::terminal_settings::Shell;
+
+const ZSH_ENV: &str = include_str!("shell_integration/zshenv.zsh");
+const BASH_INIT: &str = include_str!("shell_integration/zed.bash");
+const FISH_CONF: &str = include_str!("shell_integration/zed.fish");
+
+const MARK_PREFIX: &[u8] = b"\x1b]133;";
+/// Marks longer than this are not ours to read, and are skipped.
+const MAX_MARK_LEN: usize = 4096;
+
+/// A point of the shell's prompt and command cycle, as marked by the shell.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShellMark {
+    /// The shell started printing its prompt.
+    PromptStart,
+    /// The prompt is printed, and the user can type a command.
+    CommandStart,
+    /// The typed command started running.
+    CommandExecuted { command: Option<String> },
+    /// The command finished running.
+    CommandFinished { exit_code: Option<i32> },
+}
+
+/// Finds the shell marks in the shell's output, which may split them across reads.
+#[derive(Default)]
+pub(crate) struct MarkScanner {
+    /// How many bytes of [`MARK_PREFIX`] the output last ended with.
+    matched: usize,
+    /// The parameters of the mark being read, once its prefix is matched.
+    params: Option<Vec<u8>>,
+    /// Whether the last byte of the parameters was an escape, which may start the terminator.
+    escaped: bool,
+}
+
+impl MarkScanner {
+    pub fn scan(&mut self, bytes: &[u8], mut on_mark: impl FnMut(ShellMark)) {
+        for &byte in bytes {
+            let Some(params) = self.params.as_mut() else {
+                self.match_prefix(byte);
+                continue;
+            };
+
+            if self.escaped {
+                self.escaped = false;
+                if byte == b'\\' {
+                    if let Some(mark) = self.params.take().and_then(|params| parse_mark(&params)) {
+                        on_mark(mark);
+                    }
+                } else {
+                    // Any other escape sequence cuts the mark short.
+                    self.params = None;
+                    self.matched = 1;
+                    self.match_prefix(byte);
+                }
+                continue;
+            }
+
+            match byte {
+                b'\x07' => {
+                    if let Some(mark) = self.params.take().and_then(|params| parse_mark(&params)) {
+                        on_mark(mark);
+                    }
+                }
+                b'\x1b' => self.escaped = true,
+                _ if params.len() < MAX_MARK_LEN => params.push(byte),
+                _ => self.params = None,
+            }
+        }
+    }
+
+    fn match_prefix(&mut self, byte: u8) {
+        if byte == MARK_PREFIX[self.matched] {
+            self.matched += 1;
+            if self.matched == MARK_PREFIX.len() {
+                self.matched = 0;
+                self.params = Some(Vec::new());
+            }
+        } else {
+            self.matched = (byte == MARK_PREFIX[0]) as usize;
+        }
+    }
+}
+
+fn parse_mark(params: &[u8]) -> Option<ShellMark> {
+    let params = String::from_utf8_lossy(params);
+    let mut params = params.split(';');
+    Some(match params.next()? {
+        "A" => ShellMark::PromptStart,
+        "B" => ShellMark::CommandStart,
+        "C" => ShellMark::CommandExecuted {
+            command: params
+                .find_map(|param| param.strip_prefix("cmdline_url="))
+                .map(percent_decode),
+        },
+        "D" => ShellMark::CommandFinished {
+            exit_code: params.next().and_then(|code| code.parse().ok()),
+        },
+        _ => return None,
+    })
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut ix = 0;
+    while ix < bytes.len() {
+        let byte = match (bytes[ix], bytes.get(ix + 1..ix + 3)) {
+            (b'%', Some(hex)) => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                ix += 3;
+            }
+            None => {
+                decoded.push(bytes[ix]);
+                ix += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Points the shell at Zed's integration scripts, through its environment or arguments, and
+/// returns the shell to spawn. Shells other than bash, zsh and fish are spawned as they are.
+pub(crate) fn integrate(shell: Shell, env: &mut HashMap<String, String>) -> Shell {
+    let program = match &shell {
+        Shell::System => std::env::var("SHELL").ok(),
+        Shell::Program(program) => Some(program.clone()),
+        Shell::WithArguments { program, .. } => Some(program.clone()),
+    };
+    let Some(program) = program else {
+        return shell;
+    };
+    let dir = SUPPORT_DIR.join("shell_integration");
+    let inherited = |name: &str| env.get(name).cloned().or_else(|| std::env::var(name).ok());
+
+    match Path::new(&program)
+        .file_name()
+        .and_then(|name| name.to_str())
+    {
+        Some("zsh") => {
+            let zdotdir = dir.join("zsh");
+            if write_script(&zdotdir.join(".zshenv"), ZSH_ENV).is_none() {
+                return shell;
+            }
+            if let Some(user_zdotdir) = inherited("ZDOTDIR") {
+                env.insert("ZED_USER_ZDOTDIR".into(), user_zdotdir);
+            }
+            env.insert("ZDOTDIR".into(), zdotdir.to_string_lossy().into_owned());
+            shell
+        }
+        Some("fish") => {
+            let conf = dir.join("fish/vendor_conf.d/zed.fish");
+            if write_script(&conf, FISH_CONF).is_none() {
+                return shell;
+            }
+            let data_dirs = inherited("XDG_DATA_DIRS");
+            let mut integrated_dirs = dir.to_string_lossy().into_owned();
+            integrated_dirs.push(':');
+            integrated_dirs.push_str(
+                data_dirs
+                    .as_deref()
+                    .unwrap_or("/usr/local/share:/usr/share"),
+            );
+            if let Some(data_dirs) = data_dirs {
+                env.insert("ZED_USER_XDG_DATA_DIRS".into(), data_dirs);
+            }
+            env.insert("XDG_DATA_DIRS".into(), integrated_dirs);
+            shell
+        }
+        // Bash has no startup file to redirect through its environment, so the script replaces
+        // ~/.bashrc, and sources it itself. Shells given their own arguments are left alone.
+        Some("bash") if !matches!(shell, Shell::WithArguments { .. }) => {
+            let init_file = dir.join("zed.bash");
+            if write_script(&init_file, BASH_INIT).is_none() {
+                return shell;
+            }
+            Shell::WithArguments {
+                program,
+                args: vec![
+                    "--init-file".into(),
+                    init_file.to_string_lossy().into_owned(),
+                ],
+            }
+        }
+        _ => shell,
+    }
+}
+
+fn write_script(path: &Path, contents: &str) -> Option<()> {
+    if fs::read_to_string(path).is_ok_and(|existing| existing == contents) {
+        return Some(());
+    }
+    path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(path, contents))
+        .with_context(|| format!("writing shell integration script {path:?}"))
+        .log_err()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_marks() {
+        let output = b"\x1b]133;D;1\x07\x1b]133;A\x07~/zed $ \x1b]133;B\x1b\\ls\r\n\
+            \x1b]133;C;cmdline_url=ls%20-a%3Bpwd\x07Cargo.toml\r\n\x1b]133;D;0\x07";
+        let expected = [
+            ShellMark::CommandFinished { exit_code: Some(1) },
+            ShellMark::PromptStart,
+            ShellMark::CommandStart,
+            ShellMark::CommandExecuted {
+                command: Some("ls -a;pwd".into()),
+            },
+            ShellMark::CommandFinished { exit_code: Some(0) },
+        ];
+
+        let mut marks = Vec::new();
+        MarkScanner::default().scan(output, |mark| marks.push(mark));
+        assert_eq!(marks, expected);
+
+        // Marks split across reads are found all the same.
+        for chunk_size in 1..8 {
+            let mut scanner = MarkScanner::default();
+            let mut marks = Vec::new();
+            for chunk in output.chunks(chunk_size) {
+                scanner.scan(chunk, |mark| marks.push(mark));
+            }
+            assert_eq!(marks, expected, "chunks of {chunk_size} bytes");
+        }
+    }
+
+    #[test]
+    fn test_scan_ignores_other_sequences() {
+        let mut marks = Vec::new();
+        MarkScanner::default().scan(
+            b"\x1b]0;title\x07\x1b]1337;X\x07\x1b]133;A\x1b[0m\x1b]133;Z\x07\x1b\x1b]133;B\x07",
+            |mark| marks.push(mark),
+        );
+        assert_eq!(marks, [ShellMark::CommandStart]);
+    }
+}
@@ -0,0 +1,44 @@
+# Zed's shell integration for bash, loaded with --init-file in place of ~/.bashrc.
+if [[ -r ~/.bashrc ]]; then
+    source ~/.bashrc
+fi
+
+__zed_command_running=
+__zed_at_prompt=
+
+__zed_precmd() {
+    local exit_code=$?
+    if [[ -n "$__zed_command_running" ]]; then
+        builtin printf '\e]133;D;%s\a' "$exit_code"
+        __zed_command_running=
+    fi
+    builtin printf '\e]133;A\a'
+    if [[ "$PS1" != *'133;B'* ]]; then
+        PS1="$PS1"'\[\e]133;B\a\]'
+    fi
+}
+
+__zed_prompt_ready() {
+    __zed_at_prompt=1
+}
+
+# The DEBUG trap runs before every command, so only the first one after the prompt is the
+# command line the user typed.
+__zed_preexec() {
+    if [[ -z "$__zed_at_prompt" || -n "$COMP_LINE" ]]; then
+        return
+    fi
+    __zed_at_prompt=
+    if [[ "$BASH_COMMAND" == __zed_precmd* ]]; then
+        return
+    fi
+    local command
+    builtin read -r _ command <<< "$(HISTTIMEFORMAT= builtin history 1)"
+    command="${command//%/%25}"
+    command="${command//;/%3B}"
+    builtin printf '\e]133;C;cmdline_url=%s\a' "$command"
+    __zed_command_running=1
+}
+
+PROMPT_COMMAND="__zed_precmd;${PROMPT_COMMAND:+$PROMPT_COMMAND;}__zed_prompt_ready"
+trap '__zed_preexec' DEBUG
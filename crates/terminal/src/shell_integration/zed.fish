@@ -0,0 +1,31 @@
+# Zed's shell integration for fish, loaded from vendor_conf.d through XDG_DATA_DIRS. It restores
+# the user's XDG_DATA_DIRS first, so the programs run in the shell don't see Zed's directory.
+if set -q ZED_USER_XDG_DATA_DIRS
+    set -gx XDG_DATA_DIRS $ZED_USER_XDG_DATA_DIRS
+    set -e ZED_USER_XDG_DATA_DIRS
+else
+    set -e XDG_DATA_DIRS
+end
+
+status is-interactive; or exit
+
+function __zed_prompt --on-event fish_prompt
+    printf '\e]133;A\a'
+    # The user's prompt is only defined once their config is loaded, after this file.
+    if not functions -q __zed_user_fish_prompt
+        functions -c fish_prompt __zed_user_fish_prompt
+        function fish_prompt
+            __zed_user_fish_prompt
+            printf '\e]133;B\a'
+        end
+    end
+end
+
+function __zed_preexec --on-event fish_preexec
+    set -l command (string replace -a '%' '%25' -- $argv[1] | string replace -a ';' '%3B' | string join '%0A')
+    printf '\e]133;C;cmdline_url=%s\a' "$command"
+end
+
+function __zed_postexec --on-event fish_postexec
+    printf '\e]133;D;%s\a' $status
+end
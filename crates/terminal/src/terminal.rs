@@ -1,8 +1,15 @@
+pub mod link_patterns;
 pub mod mappings;
+pub mod output_log;
 
 pub use alacritty_terminal;
 
+pub mod graphics;
 mod pty_info;
+#[cfg(unix)]
+mod scanned_pty;
+pub mod session;
+mod shell_integration;
 pub mod terminal_settings;
 
 use alacritty_terminal::{
@@ -18,10 +25,10 @@ use alacritty_terminal::{
         Config, RenderableCursor, TermMode,
     },
     tty::{self, setup_env},
-    vte::ansi::{ClearMode, Handler, NamedPrivateMode, PrivateMode, Rgb},
+    vte::ansi::{ClearMode, Handler, NamedPrivateMode, PrivateMode, Processor, Rgb},
     Term,
 };
-use anyhow::{bail, Result};
+use anyhow::{bail, Context as _, Result};
 
 use futures::{
     channel::mpsc::{unbounded, UnboundedReceiver, UnboundedSender},
@@ -35,12 +42,19 @@ use mappings::mouse::{
 
 use collections::{HashMap, VecDeque};
 use futures::StreamExt;
+use graphics::{GraphicsEvent, ImagePlacement, TerminalImage};
+use link_patterns::LinkPatterns;
+use output_log::{OutputLog, SharedOutputLog};
 use pty_info::PtyProcessInfo;
 use serde::{Deserialize, Serialize};
+use session::{RestoredTerminal, TerminalSession};
 use settings::Settings;
+use shell_integration::ShellMark;
 use smol::channel::{Receiver, Sender};
 use task::TaskId;
-use terminal_settings::{AlternateScroll, Shell, TerminalBlink, TerminalSettings};
+use terminal_settings::{
+    AlternateScroll, Shell, TerminalBlink, TerminalOutputFormat, TerminalProfile, TerminalSettings,
+};
 use theme::{ActiveTheme, Theme};
 use util::truncate_and_trailoff;
 
@@ -50,21 +64,36 @@ use std::{
     ops::{Deref, Index, RangeInclusive},
     path::PathBuf,
     sync::Arc,
-    time::Duration,
+    time::{Duration, Instant},
 };
 use thiserror::Error;
 
 use gpui::{
     actions, black, px, AnyWindowHandle, AppContext, Bounds, ClipboardItem, EventEmitter, Hsla,
     Keystroke, ModelContext, Modifiers, MouseButton, MouseDownEvent, MouseMoveEvent, MouseUpEvent,
-    Pixels, Point, Rgba, ScrollWheelEvent, Size, Task, TouchPhase,
+    Pixels, Point, Rgba, ScrollWheelEvent, Size, Subscription, Task, TouchPhase,
 };
 
 use crate::mappings::{colors::to_alac_rgb, keys::to_esc_str};
 
 actions!(
     terminal,
-    [Clear, Copy, Paste, ShowCharacterPalette, SearchTest,]
+    [
+        Clear,
+        Copy,
+        CopyAllMatches,
+        ExportScrollback,
+        ExportRawScrollback,
+        LogToFile,
+        LogRawToFile,
+        StopLogging,
+        Paste,
+        ShowCharacterPalette,
+        SearchTest,
+        ScrollToPreviousPrompt,
+        ScrollToNextPrompt,
+        RerunLastCommand,
+    ]
 );
 
 ///Scrolling is unbearably sluggish by default. Alacritty supports a configurable
@@ -79,6 +108,10 @@ const DEBUG_TERMINAL_WIDTH: Pixels = px(500.);
 const DEBUG_TERMINAL_HEIGHT: Pixels = px(30.);
 const DEBUG_CELL_WIDTH: Pixels = px(5.);
 const DEBUG_LINE_HEIGHT: Pixels = px(5.);
+/// How many of the commands run at the shell's prompt are remembered.
+const MAX_SHELL_COMMANDS: usize = 1000;
+/// How many of the inline images the shell printed are kept, to draw them.
+const MAX_IMAGES: usize = 256;
 
 ///Upward flowing events, for changing the title and such
 #[derive(Clone, Debug)]
@@ -90,6 +123,7 @@ pub enum Event {
     Wakeup,
     BlinkChanged,
     SelectionsChanged,
+    Copied(String),
     NewNavigationTarget(Option<MaybeNavigationTarget>),
     Open(MaybeNavigationTarget),
 }
@@ -101,6 +135,9 @@ pub struct PathLikeTarget {
     pub maybe_path: String,
     /// Current working directory of the terminal
     pub terminal_dir: Option<PathBuf>,
+    /// Whether the path may be relative to any directory of the project, as the file names of
+    /// stack traces are, when it's not relative to the terminal's or the project's directory.
+    pub search_project: bool,
 }
 
 /// A string inside terminal, potentially useful as a URI that can be opened.
@@ -126,6 +163,7 @@ enum InternalEvent {
     // Adjusted mouse position, should open
     FindHyperlink(Point<Pixels>, bool),
     Copy,
+    CopyAllMatches,
 }
 
 ///A translation struct for Alacritty to communicate with us from their event loop
@@ -305,15 +343,33 @@ impl TerminalBuilder {
         blink_settings: Option<TerminalBlink>,
         alternate_scroll: AlternateScroll,
         max_scroll_history_lines: Option<usize>,
+        with_shell_integration: bool,
+        session: Option<TerminalSession>,
+        restored: Option<RestoredTerminal>,
         window: AnyWindowHandle,
         completion_tx: Sender<()>,
     ) -> Result<TerminalBuilder> {
+        let spawn_env = env.clone();
+
         // TODO: Properly set the current locale,
         env.entry("LC_ALL".to_string())
             .or_insert_with(|| "en_US.UTF-8".to_string());
 
         env.insert("ZED_TERM".to_string(), "true".to_string());
 
+        let shell = if with_shell_integration {
+            shell_integration::integrate(shell, &mut env)
+        } else {
+            shell
+        };
+
+        // Without dtach installed, the shell is spawned as it is, and ends with the terminal.
+        let (session, shell) =
+            match session.and_then(|session| Some((session.attach(&shell)?, session))) {
+                Some((attached_shell, session)) => (Some(session), attached_shell),
+                None => (None, shell),
+            };
+
         let pty_options = {
             let alac_shell = match shell.clone() {
                 Shell::System => None,
@@ -371,6 +427,16 @@ impl TerminalBuilder {
             term.unset_private_mode(PrivateMode::Named(NamedPrivateMode::AlternateScroll));
         }
 
+        //Show the output of the previous run of the terminal above the new prompt
+        let restored = restored.unwrap_or_default();
+        if !restored.scrollback.is_empty() {
+            let mut parser: Processor = Processor::new();
+            let scrollback = restored.scrollback.replace('\n', "\r\n");
+            for byte in scrollback.bytes().chain(*b"\r\n") {
+                parser.advance(&mut term, byte);
+            }
+        }
+
         let term = Arc::new(FairMutex::new(term));
 
         //Setup the pty...
@@ -389,7 +455,33 @@ impl TerminalBuilder {
             }
         };
 
-        let pty_info = PtyProcessInfo::new(&pty);
+        let mut pty_info = PtyProcessInfo::new(&pty);
+        if let Some(session) = &session {
+            pty_info.follow_session(session.pid_path());
+        }
+
+        // Alacritty ignores the marks of shell integration and inline images, so they're
+        // picked out of the shell's output before it gets to parse it.
+        #[cfg(unix)]
+        let (pty, shell_marks, graphics, output_log) = {
+            let (marks_tx, marks_rx) = smol::channel::unbounded();
+            let (graphics_tx, graphics_rx) = smol::channel::unbounded();
+            let output_log: SharedOutputLog = Arc::new(FairMutex::new(None));
+            (
+                scanned_pty::ScannedPty::new(
+                    pty,
+                    TerminalSize::default().into(),
+                    marks_tx,
+                    graphics_tx,
+                    output_log.clone(),
+                )?,
+                Some(marks_rx),
+                Some(graphics_rx),
+                Some(output_log),
+            )
+        };
+        #[cfg(not(unix))]
+        let (shell_marks, graphics, output_log) = (None, None, None);
 
         //And connect them together
         let event_loop = EventLoop::new(
@@ -418,7 +510,7 @@ impl TerminalBuilder {
             matches: Vec::new(),
             selection_head: None,
             pty_info,
-            breadcrumb_text: String::new(),
+            breadcrumb_text: restored.title,
             scroll_px: px(0.),
             last_mouse_position: None,
             next_link_id: 0,
@@ -427,6 +519,17 @@ impl TerminalBuilder {
             hovered_word: false,
             url_regex,
             word_regex,
+            link_patterns: LinkPatterns::default(),
+            shell_marks,
+            shell_commands: VecDeque::new(),
+            graphics,
+            images: HashMap::default(),
+            output_log,
+            profile: None,
+            env: spawn_env,
+            session,
+            keep_session: false,
+            _quit_subscription: None,
         };
 
         Ok(TerminalBuilder {
@@ -436,6 +539,15 @@ impl TerminalBuilder {
     }
 
     pub fn subscribe(mut self, cx: &mut ModelContext<Terminal>) -> Terminal {
+        if self.terminal.session.is_some() {
+            // Quitting detaches from the session rather than ending it, to attach to it again
+            // once the terminal is restored.
+            self.terminal._quit_subscription = Some(cx.on_app_quit(|terminal, _| {
+                terminal.keep_session = true;
+                async {}
+            }));
+        }
+
         //Event loop
         cx.spawn(|terminal, mut cx| async move {
             while let Some(event) = self.events_rx.next().await {
@@ -525,6 +637,10 @@ pub struct TerminalContent {
     pub cursor_char: char,
     pub size: TerminalSize,
     pub last_hovered_word: Option<HoveredWord>,
+    /// The number of lines scrolled off the top of the screen, into the scrollback.
+    pub history_size: usize,
+    /// The inline images on the screen.
+    pub images: Vec<ImagePlacement>,
 }
 
 #[derive(Clone)]
@@ -549,6 +665,8 @@ impl Default for TerminalContent {
             cursor_char: Default::default(),
             size: Default::default(),
             last_hovered_word: None,
+            history_size: 0,
+            images: Vec::new(),
         }
     }
 }
@@ -580,7 +698,47 @@ pub struct Terminal {
     hovered_word: bool,
     url_regex: RegexSearch,
     word_regex: RegexSearch,
+    link_patterns: LinkPatterns,
     task: Option<TaskState>,
+    shell_marks: Option<Receiver<(ShellMark, Instant)>>,
+    shell_commands: VecDeque<ShellCommand>,
+    graphics: Option<Receiver<GraphicsEvent>>,
+    /// The inline images, by the id of their placeholder.
+    images: HashMap<u32, TerminalImage>,
+    /// The log the shell's output is written to, shared with the reader of the pty. Only
+    /// terminals on Unix can log their output.
+    output_log: Option<SharedOutputLog>,
+    /// The profile the terminal was opened with, when it wasn't opened with the shell.
+    profile: Option<TerminalProfile>,
+    env: HashMap<String, String>,
+    session: Option<TerminalSession>,
+    /// Whether to leave the session running when the terminal is dropped, as Zed is quitting or
+    /// its window is closing.
+    keep_session: bool,
+    _quit_subscription: Option<Subscription>,
+}
+
+/// A command run at the prompt of a shell with shell integration.
+#[derive(Clone, Debug)]
+pub struct ShellCommand {
+    /// The line the command was typed on, counted from the top of the scrollback.
+    pub line: usize,
+    /// The command line, when the shell reported it.
+    pub command: Option<String>,
+    started_at: Option<Instant>,
+    /// How long the command ran for, once it finished.
+    pub duration: Option<Duration>,
+    pub exit_code: Option<i32>,
+}
+
+impl ShellCommand {
+    pub fn is_finished(&self) -> bool {
+        self.duration.is_some()
+    }
+
+    pub fn failed(&self) -> bool {
+        self.exit_code.is_some_and(|code| code != 0)
+    }
 }
 
 pub struct TaskState {
@@ -652,6 +810,8 @@ impl Terminal {
                 //NOOP, Handled in render
             }
             AlacTermEvent::Wakeup => {
+                self.process_shell_marks(cx);
+                self.process_graphics();
                 cx.emit(Event::Wakeup);
 
                 if self.pty_info.has_changed() {
@@ -668,6 +828,175 @@ impl Terminal {
         }
     }
 
+    /// Updates the commands run at the shell's prompt with the marks the shell printed. The
+    /// marks are read before Alacritty parses the output around them, so they're only applied
+    /// once it did, when it wakes us up.
+    fn process_shell_marks(&mut self, cx: &mut ModelContext<Self>) {
+        let Some(marks) = self.shell_marks.as_ref().filter(|marks| !marks.is_empty()) else {
+            return;
+        };
+        let cursor_line = {
+            let term = self.term.clone();
+            let terminal = term.lock_unfair();
+            let grid = terminal.grid();
+            (grid.history_size() as i32 + grid.cursor.point.line.0).max(0) as usize
+        };
+
+        let mut finished = false;
+        while let Ok((mark, time)) = marks.try_recv() {
+            let pending = self
+                .shell_commands
+                .back_mut()
+                .filter(|command| command.started_at.is_none());
+            match mark {
+                ShellMark::PromptStart | ShellMark::CommandStart => match pending {
+                    // The prompt was printed again, or finished printing.
+                    Some(command) => command.line = cursor_line,
+                    None => {
+                        if self.shell_commands.len() == MAX_SHELL_COMMANDS {
+                            self.shell_commands.pop_front();
+                        }
+                        self.shell_commands.push_back(ShellCommand {
+                            line: cursor_line,
+                            command: None,
+                            started_at: None,
+                            duration: None,
+                            exit_code: None,
+                        });
+                    }
+                },
+                ShellMark::CommandExecuted { command } => {
+                    if let Some(pending) = pending {
+                        pending.command = command.filter(|command| !command.trim().is_empty());
+                        pending.started_at = Some(time);
+                    }
+                }
+                ShellMark::CommandFinished { exit_code } => {
+                    if let Some(running) = self
+                        .shell_commands
+                        .back_mut()
+                        .filter(|command| !command.is_finished())
+                    {
+                        if let Some(started_at) = running.started_at {
+                            running.duration = Some(time.saturating_duration_since(started_at));
+                            running.exit_code = exit_code;
+                            finished = true;
+                        }
+                    }
+                }
+            }
+        }
+
+        if finished {
+            cx.emit(Event::TitleChanged);
+        }
+    }
+
+    /// Keeps the images the shell printed, to draw them at their placeholders once Alacritty
+    /// parsed those, and answers the programs that sent them.
+    fn process_graphics(&mut self) {
+        let Some(graphics) = self.graphics.as_ref() else {
+            return;
+        };
+        let mut replies = Vec::new();
+        while let Ok(event) = graphics.try_recv() {
+            match event {
+                GraphicsEvent::Place { id, image } => {
+                    if self.images.len() == MAX_IMAGES {
+                        if let Some(oldest_id) = self.images.keys().min().copied() {
+                            self.images.remove(&oldest_id);
+                        }
+                    }
+                    self.images.insert(id, image);
+                }
+                GraphicsEvent::Delete(ids) => {
+                    for id in ids {
+                        self.images.remove(&id);
+                    }
+                }
+                GraphicsEvent::Reply(reply) => replies.push(reply),
+            }
+        }
+        for reply in replies {
+            self.write_to_pty(reply);
+        }
+    }
+
+    /// The commands run at the shell's prompt, oldest first. Only shells with shell
+    /// integration report them.
+    pub fn shell_commands(&self) -> impl DoubleEndedIterator<Item = &ShellCommand> {
+        self.shell_commands.iter()
+    }
+
+    /// The last command that finished running at the shell's prompt.
+    pub fn last_finished_command(&self) -> Option<&ShellCommand> {
+        self.shell_commands
+            .iter()
+            .rev()
+            .find(|command| command.is_finished())
+    }
+
+    /// Whether the shell is showing its prompt, rather than running a command.
+    pub fn is_at_prompt(&self) -> bool {
+        self.shell_commands
+            .back()
+            .is_some_and(|command| command.started_at.is_none())
+    }
+
+    /// Scrolls the closest prompt above the top of the screen to it.
+    pub fn scroll_to_previous_prompt(&mut self) {
+        let top_line = self.top_line();
+        if let Some(command) = self
+            .shell_commands
+            .iter()
+            .rev()
+            .find(|command| command.line < top_line)
+        {
+            self.scroll_line_to_top(command.line);
+        }
+    }
+
+    /// Scrolls the closest prompt below the top of the screen to it.
+    pub fn scroll_to_next_prompt(&mut self) {
+        let top_line = self.top_line();
+        if let Some(command) = self
+            .shell_commands
+            .iter()
+            .find(|command| command.line > top_line)
+        {
+            self.scroll_line_to_top(command.line);
+        }
+    }
+
+    /// The line at the top of the screen, counted from the top of the scrollback.
+    fn top_line(&self) -> usize {
+        self.last_content
+            .history_size
+            .saturating_sub(self.last_content.display_offset)
+    }
+
+    fn scroll_line_to_top(&mut self, line: usize) {
+        let display_offset = self.last_content.history_size.saturating_sub(line);
+        let delta = display_offset as i32 - self.last_content.display_offset as i32;
+        self.events
+            .push_back(InternalEvent::Scroll(AlacScroll::Delta(delta)));
+    }
+
+    /// Runs the last command run at the shell's prompt again, if the shell is at its prompt.
+    pub fn rerun_last_command(&mut self) {
+        if !self.is_at_prompt() {
+            return;
+        }
+        let last_command = self
+            .shell_commands
+            .iter()
+            .rev()
+            .find_map(|command| command.command.clone());
+        if let Some(command) = last_command {
+            self.input(format!("{command}\r"));
+        }
+    }
+
     pub fn selection_started(&self) -> bool {
         self.selection_phase == SelectionPhase::Selecting
     }
@@ -676,6 +1005,66 @@ impl Terminal {
         self.pty_info.current.as_ref().map(|info| info.cwd.clone())
     }
 
+    /// The environment the terminal was spawned with, on top of Zed's own.
+    pub fn env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    pub fn profile(&self) -> Option<&TerminalProfile> {
+        self.profile.as_ref()
+    }
+
+    pub fn set_profile(&mut self, profile: Option<TerminalProfile>) {
+        self.profile = profile;
+    }
+
+    /// The session running the terminal's shell, when it's kept running across restarts.
+    pub fn session(&self) -> Option<&TerminalSession> {
+        self.session.as_ref()
+    }
+
+    /// Leaves the session running when the terminal is dropped, to attach to it again once the
+    /// terminal is restored.
+    pub fn keep_session(&mut self) {
+        self.keep_session = true;
+    }
+
+    /// Starts writing the shell's output to the file, in place of the log it was written to.
+    /// The output is appended to the file when it exists.
+    pub fn start_logging(&mut self, path: PathBuf, format: TerminalOutputFormat) -> Result<()> {
+        let Some(output_log) = &self.output_log else {
+            bail!("logging the terminal's output is not supported on this platform");
+        };
+        let log = OutputLog::open(path.clone(), format)
+            .with_context(|| format!("opening terminal log {path:?}"))?;
+        *output_log.lock() = Some(log);
+        Ok(())
+    }
+
+    /// Stops writing the shell's output to its log, returning the log's path.
+    pub fn stop_logging(&mut self) -> Option<PathBuf> {
+        let log = self.output_log.as_ref()?.lock().take()?;
+        Some(log.path().to_path_buf())
+    }
+
+    /// The file the shell's output is being written to.
+    pub fn log_path(&self) -> Option<PathBuf> {
+        let output_log = self.output_log.as_ref()?.lock();
+        Some(output_log.as_ref()?.path().to_path_buf())
+    }
+
+    /// Returns the text of the scrollback and screen, to save it to a file.
+    pub fn export_scrollback(&self, format: TerminalOutputFormat) -> String {
+        let term = self.term.lock();
+        output_log::export(&term, format)
+    }
+
+    /// Returns the text of up to `max_lines` lines above the cursor, scrollback included. The
+    /// cursor's line is left out, as it's the prompt a restored shell prints again.
+    pub fn scrollback_tail(&self, max_lines: usize) -> String {
+        scrollback_tail(&self.term.lock(), max_lines)
+    }
+
     ///Takes events from Alacritty and translates them to behavior on this view
     fn process_terminal_event(
         &mut self,
@@ -725,6 +1114,15 @@ impl Terminal {
                     AlacPoint::new(Line(0), term.grid_mut().cursor.point.column);
                 let new_cursor = term.grid().cursor.point;
 
+                // Only the prompt on the cursor's line is left
+                let pending = self
+                    .shell_commands
+                    .pop_back()
+                    .filter(|command| !command.is_finished());
+                self.shell_commands.clear();
+                self.shell_commands
+                    .extend(pending.map(|command| ShellCommand { line: 0, ..command }));
+
                 // Clear the lines below the new cursor
                 if (new_cursor.line.0 as usize) < term.screen_lines() - 1 {
                     term.grid_mut().reset_region((new_cursor.line + 1)..);
@@ -772,7 +1170,22 @@ impl Terminal {
 
             InternalEvent::Copy => {
                 if let Some(txt) = term.selection_to_string() {
-                    cx.write_to_clipboard(ClipboardItem::new(txt))
+                    cx.write_to_clipboard(ClipboardItem::new(txt.clone()));
+                    cx.emit(Event::Copied(txt));
+                }
+            }
+            InternalEvent::CopyAllMatches => {
+                if !self.matches.is_empty() {
+                    let txt = self
+                        .matches
+                        .iter()
+                        .map(|search_match| {
+                            term.bounds_to_string(*search_match.start(), *search_match.end())
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    cx.write_to_clipboard(ClipboardItem::new(txt.clone()));
+                    cx.emit(Event::Copied(txt));
                 }
             }
             InternalEvent::ScrollToAlacPoint(point) => {
@@ -818,7 +1231,10 @@ impl Terminal {
                     let url = link.unwrap().uri().to_owned();
                     let url_match = min_index..=max_index;
 
-                    Some((url, true, url_match))
+                    Some((url, true, false, url_match))
+                } else if let Some((link_match, path)) = self.link_patterns.link_at(term, point, cx)
+                {
+                    Some((path, false, true, link_match))
                 } else if let Some(word_match) = regex_match_at(term, point, &mut self.word_regex) {
                     let maybe_url_or_path =
                         term.bounds_to_string(*word_match.start(), *word_match.end());
@@ -849,13 +1265,13 @@ impl Terminal {
                         }
                         None => false,
                     };
-                    Some((sanitized_word, is_url, sanitized_match))
+                    Some((sanitized_word, is_url, false, sanitized_match))
                 } else {
                     None
                 };
 
                 match found_word {
-                    Some((maybe_url_or_path, is_url, url_match)) => {
+                    Some((maybe_url_or_path, is_url, search_project, url_match)) => {
                         if *open {
                             let target = if is_url {
                                 MaybeNavigationTarget::Url(maybe_url_or_path)
@@ -863,6 +1279,7 @@ impl Terminal {
                                 MaybeNavigationTarget::PathLike(PathLikeTarget {
                                     maybe_path: maybe_url_or_path,
                                     terminal_dir: self.get_cwd(),
+                                    search_project,
                                 })
                             };
                             cx.emit(Event::Open(target));
@@ -872,6 +1289,7 @@ impl Terminal {
                                 url_match,
                                 maybe_url_or_path,
                                 is_url,
+                                search_project,
                                 cx,
                             );
                         }
@@ -894,6 +1312,7 @@ impl Terminal {
         word_match: RangeInclusive<AlacPoint>,
         word: String,
         is_url: bool,
+        search_project: bool,
         cx: &mut ModelContext<Self>,
     ) {
         if let Some(prev_word) = prev_word {
@@ -918,6 +1337,7 @@ impl Terminal {
             MaybeNavigationTarget::PathLike(PathLikeTarget {
                 maybe_path: word,
                 terminal_dir: self.get_cwd(),
+                search_project,
             })
         };
         cx.emit(Event::NewNavigationTarget(Some(navigation_target)));
@@ -978,6 +1398,11 @@ impl Terminal {
         self.events.push_back(InternalEvent::Copy);
     }
 
+    /// Copies the text of every search match, one match per line.
+    pub fn copy_all_matches(&mut self) {
+        self.events.push_back(InternalEvent::CopyAllMatches);
+    }
+
     pub fn clear(&mut self) {
         self.events.push_back(InternalEvent::Clear)
     }
@@ -1052,10 +1477,14 @@ impl Terminal {
             self.process_terminal_event(&e, &mut terminal, cx)
         }
 
-        self.last_content = Self::make_content(&terminal, &self.last_content);
+        self.last_content = Self::make_content(&terminal, &self.last_content, &self.images);
     }
 
-    fn make_content(term: &Term<ZedListener>, last_content: &TerminalContent) -> TerminalContent {
+    fn make_content(
+        term: &Term<ZedListener>,
+        last_content: &TerminalContent,
+        images: &HashMap<u32, TerminalImage>,
+    ) -> TerminalContent {
         let content = term.renderable_content();
         TerminalContent {
             cells: content
@@ -1080,6 +1509,8 @@ impl Terminal {
             cursor_char: term.grid()[content.cursor.point].c,
             size: last_content.size,
             last_hovered_word: last_content.last_hovered_word.clone(),
+            history_size: term.grid().history_size(),
+            images: graphics::image_placements(term, images),
         }
     }
 
@@ -1371,6 +1802,13 @@ impl Terminal {
 
     pub fn title(&self, truncate: bool) -> String {
         const MAX_CHARS: usize = 25;
+        if let (None, Some(profile)) = (&self.task, &self.profile) {
+            return if truncate {
+                truncate_and_trailoff(&profile.name, MAX_CHARS)
+            } else {
+                profile.name.clone()
+            };
+        }
         match &self.task {
             Some(task_state) => {
                 if truncate {
@@ -1529,6 +1967,11 @@ unsafe fn append_text_to_term(term: &mut Term<ZedListener>, text_lines: &[&str])
 impl Drop for Terminal {
     fn drop(&mut self) {
         self.pty_tx.0.send(Msg::Shutdown).ok();
+        if let Some(session) = &self.session {
+            if !self.keep_session {
+                session.end();
+            }
+        }
     }
 }
 
@@ -1665,17 +2108,39 @@ pub fn rgba_color(r: u8, g: u8, b: u8) -> Hsla {
     .into()
 }
 
+fn scrollback_tail<T>(term: &Term<T>, max_lines: usize) -> String {
+    if max_lines == 0 {
+        return String::new();
+    }
+    let grid = term.grid();
+    let end_line = grid.cursor.point.line.0 - 1;
+    let start_line = cmp::max(grid.topmost_line().0, end_line + 1 - max_lines as i32);
+    if start_line > end_line {
+        return String::new();
+    }
+    term.bounds_to_string(
+        AlacPoint::new(Line(start_line), Column(0)),
+        AlacPoint::new(Line(end_line), grid.last_column()),
+    )
+    .trim_end()
+    .to_string()
+}
+
 #[cfg(test)]
 mod tests {
     use alacritty_terminal::{
         index::{Column, Line, Point as AlacPoint},
-        term::cell::Cell,
+        term::{cell::Cell, Config},
+        vte::ansi::Processor,
+        Term,
     };
+    use futures::channel::mpsc::unbounded;
     use gpui::{point, size, Pixels};
     use rand::{distributions::Alphanumeric, rngs::ThreadRng, thread_rng, Rng};
 
     use crate::{
-        content_index_for_mouse, rgb_for_index, IndexedCell, TerminalContent, TerminalSize,
+        content_index_for_mouse, rgb_for_index, scrollback_tail, IndexedCell, TerminalContent,
+        TerminalSize, ZedListener,
     };
 
     #[test]
@@ -1687,6 +2152,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_scrollback_tail() {
+        let mut term = Term::new(
+            Config::default(),
+            &TerminalSize::default(),
+            ZedListener(unbounded().0),
+        );
+        assert_eq!(scrollback_tail(&term, 10), "");
+
+        let mut parser: Processor = Processor::new();
+        for byte in "one\r\ntwo\r\nthree\r\n$ ".bytes() {
+            parser.advance(&mut term, byte);
+        }
+        assert_eq!(scrollback_tail(&term, 0), "");
+        assert_eq!(scrollback_tail(&term, 2), "two\nthree");
+        assert_eq!(scrollback_tail(&term, 10), "one\ntwo\nthree");
+    }
+
     #[test]
     fn test_mouse_to_cell_test() {
         let mut rng = thread_rng();
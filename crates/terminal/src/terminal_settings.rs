@@ -10,6 +10,8 @@ use serde_json::Value;
 use settings::{SettingsJsonSchemaParams, SettingsSources};
 use std::path::PathBuf;
 
+use crate::link_patterns::TerminalLinkPattern;
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum TerminalDockPosition {
@@ -23,6 +25,19 @@ pub struct Toolbar {
     pub title: bool,
 }
 
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct PersistentSessions {
+    pub restore_scrollback_lines: usize,
+    pub reattach: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct TaskLogs {
+    pub enabled: bool,
+    pub directory: Option<PathBuf>,
+    pub format: TerminalOutputFormat,
+}
+
 #[derive(Deserialize)]
 pub struct TerminalSettings {
     pub shell: Shell,
@@ -43,6 +58,11 @@ pub struct TerminalSettings {
     pub default_height: Pixels,
     pub detect_venv: VenvSettings,
     pub max_scroll_history_lines: Option<usize>,
+    pub shell_integration: bool,
+    pub link_patterns: Vec<TerminalLinkPattern>,
+    pub persistent_sessions: PersistentSessions,
+    pub task_logs: TaskLogs,
+    pub profiles: Vec<TerminalProfile>,
     pub toolbar: Toolbar,
 }
 
@@ -169,6 +189,26 @@ pub struct TerminalSettingsContent {
     ///
     /// Default: 10_000
     pub max_scroll_history_lines: Option<usize>,
+    /// Whether to load Zed's shell integration into bash, zsh and fish, so that the terminal
+    /// knows where each command's prompt is, how long it ran for and whether it failed.
+    /// Existing terminals will not pick up this change until they are recreated.
+    ///
+    /// Default: true
+    pub shell_integration: Option<bool>,
+    /// Patterns making the terminal output they match links to positions in files, on top of
+    /// the paths the terminal finds on its own and the built-in patterns for common errors.
+    ///
+    /// Default: []
+    pub link_patterns: Option<Vec<TerminalLinkPattern>>,
+    /// What's kept of terminals when reopening a workspace.
+    pub persistent_sessions: Option<PersistentSessionsContent>,
+    /// Whether to write the output of tasks to log files as they run.
+    pub task_logs: Option<TaskLogsContent>,
+    /// The kinds of terminals to open from the terminal panel's new terminal menu, besides
+    /// the one running the shell.
+    ///
+    /// Default: []
+    pub profiles: Option<Vec<TerminalProfile>>,
     /// Toolbar related settings
     pub toolbar: Option<ToolbarContent>,
 }
@@ -291,6 +331,74 @@ pub enum WorkingDirectory {
     Always { directory: String },
 }
 
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct PersistentSessionsContent {
+    /// How many lines of their output to show in terminals restored when reopening a
+    /// workspace. 0 restores none.
+    ///
+    /// Default: 1000
+    pub restore_scrollback_lines: Option<usize>,
+    /// Whether to run shells under `dtach`, so that they keep running when Zed quits, and the
+    /// restored terminals attach to them again. Has no effect when `dtach` isn't installed,
+    /// and on Windows.
+    ///
+    /// Default: false
+    pub reattach: Option<bool>,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct TaskLogsContent {
+    /// Whether to write the output of each task to a log file.
+    ///
+    /// Default: false
+    pub enabled: Option<bool>,
+    /// The directory to write the logs to. Defaults to the `tasks` directory in Zed's logs
+    /// directory.
+    ///
+    /// Default: null
+    pub directory: Option<PathBuf>,
+    /// Whether to strip the colors and other escape sequences from the logs.
+    ///
+    /// Default: plain
+    pub format: Option<TerminalOutputFormat>,
+}
+
+/// How the terminal's output is written to files.
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TerminalOutputFormat {
+    /// The text of the output, without its colors and other escape sequences.
+    #[default]
+    Plain,
+    /// The output as the shell printed it, escape sequences included.
+    Raw,
+}
+
+/// A kind of terminal to open from the terminal panel, running another program than the shell,
+/// or a shell on another host.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct TerminalProfile {
+    /// The name of the profile, shown in the new terminal menu and in the tabs of its terminals.
+    pub name: String,
+    /// The program to run in place of the shell. Runs on the host of `ssh` when it's set.
+    #[serde(default)]
+    pub command: Option<String>,
+    /// The arguments of the program.
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// The environment variables to set for the program, on top of the `env` setting. They're
+    /// set for the local `ssh` command when `ssh` is set.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// The icon of the tabs of the profile's terminals, such as `server`.
+    #[serde(default)]
+    pub icon: Option<String>,
+    /// The host to open the shell on over SSH, as the arguments of `ssh`, such as `user@host`
+    /// or `-p 2222 user@host`.
+    #[serde(default)]
+    pub ssh: Option<String>,
+}
+
 // Toolbar related settings
 #[derive(Clone, Debug, Default, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
 pub struct ToolbarContent {
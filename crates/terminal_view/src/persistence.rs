@@ -34,6 +34,15 @@ define_connection! {
             DROP TABLE terminals;
 
             ALTER TABLE terminals2 RENAME TO terminals;
+        ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN title TEXT;
+            ALTER TABLE terminals ADD COLUMN env TEXT;
+            ALTER TABLE terminals ADD COLUMN scrollback TEXT;
+            ALTER TABLE terminals ADD COLUMN session_id TEXT;
+        ),
+        sql!(
+            ALTER TABLE terminals ADD COLUMN profile TEXT;
         )];
 }
 
@@ -56,8 +65,10 @@ impl TerminalDb {
             workspace_id: WorkspaceId,
             working_directory: PathBuf
         ) -> Result<()> {
-            INSERT OR REPLACE INTO terminals(item_id, workspace_id, working_directory)
-            VALUES (?, ?, ?)
+            INSERT INTO terminals(item_id, workspace_id, working_directory)
+            VALUES (?1, ?2, ?3)
+            ON CONFLICT DO UPDATE SET
+                working_directory = ?3
         }
     }
 
@@ -68,4 +79,44 @@ impl TerminalDb {
             WHERE item_id = ? AND workspace_id = ?
         }
     }
+
+    query! {
+        pub async fn save_session(
+            item_id: ItemId,
+            workspace_id: WorkspaceId,
+            title: String,
+            env: String,
+            scrollback: String,
+            session_id: Option<String>,
+            profile: Option<String>
+        ) -> Result<()> {
+            INSERT INTO terminals(
+                item_id, workspace_id, title, env, scrollback, session_id, profile
+            )
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)
+            ON CONFLICT DO UPDATE SET
+                title = ?3,
+                env = ?4,
+                scrollback = ?5,
+                session_id = ?6,
+                profile = ?7
+        }
+    }
+
+    query! {
+        pub fn get_session(
+            item_id: ItemId,
+            workspace_id: WorkspaceId
+        ) -> Result<Option<(
+            Option<String>,
+            Option<String>,
+            Option<String>,
+            Option<String>,
+            Option<String>
+        )>> {
+            SELECT title, env, scrollback, session_id, profile
+            FROM terminals
+            WHERE item_id = ? AND workspace_id = ?
+        }
+    }
 }
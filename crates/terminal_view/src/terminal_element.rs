@@ -1,9 +1,9 @@
 use editor::{CursorLayout, HighlightedRange, HighlightedRangeLine};
 use gpui::{
-    div, fill, point, px, relative, AnyElement, Bounds, DispatchPhase, Element, ElementId,
-    FocusHandle, Font, FontStyle, FontWeight, GlobalElementId, HighlightStyle, Hitbox, Hsla,
-    InputHandler, InteractiveElement, Interactivity, IntoElement, LayoutId, Model, ModelContext,
-    ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels, Point, ShapedLine,
+    div, fill, point, px, relative, size, AnyElement, Bounds, ContentMask, Corners, DispatchPhase,
+    Element, ElementId, FocusHandle, Font, FontStyle, FontWeight, GlobalElementId, HighlightStyle,
+    Hitbox, Hsla, InputHandler, InteractiveElement, Interactivity, IntoElement, LayoutId, Model,
+    ModelContext, ModifiersChangedEvent, MouseButton, MouseMoveEvent, Pixels, Point, ShapedLine,
     StatefulInteractiveElement, StrikethroughStyle, Styled, TextRun, TextStyle, UnderlineStyle,
     WeakView, WhiteSpace, WindowContext, WindowTextSystem,
 };
@@ -20,6 +20,7 @@ use terminal::{
             CursorShape as AlacCursorShape, NamedColor,
         },
     },
+    graphics::{ImagePlacement, IMAGE_PLACEHOLDER},
     terminal_settings::TerminalSettings,
     HoveredWord, IndexedCell, Terminal, TerminalContent, TerminalSize,
 };
@@ -28,7 +29,7 @@ use ui::Tooltip;
 use workspace::Workspace;
 
 use std::mem;
-use std::{fmt::Debug, ops::RangeInclusive};
+use std::{fmt::Debug, ops::RangeInclusive, time::Duration};
 
 /// The information generated during layout that is necessary for painting.
 pub struct LayoutState {
@@ -44,6 +45,15 @@ pub struct LayoutState {
     hyperlink_tooltip: Option<AnyElement>,
     gutter: Pixels,
     last_hovered_word: Option<HoveredWord>,
+    shell_commands: Vec<ShellCommandLayout>,
+    images: Vec<ImagePlacement>,
+}
+
+/// The result of a finished shell command, shown on its prompt's line.
+struct ShellCommandLayout {
+    line: i32,
+    color: Hsla,
+    duration: ShapedLine,
 }
 
 /// Helper struct for converting data between Alacritty's cursor points, and displayed cursor points.
@@ -184,6 +194,55 @@ impl TerminalElement {
         .element
     }
 
+    /// Lays out the results of the finished shell commands whose prompts are on screen.
+    fn layout_shell_commands(
+        &self,
+        dimensions: TerminalSize,
+        text_style: &TextStyle,
+        theme: &Theme,
+        cx: &WindowContext,
+    ) -> Vec<ShellCommandLayout> {
+        let terminal = self.terminal.read(cx);
+        let content = &terminal.last_content;
+        let top_line = content.history_size.saturating_sub(content.display_offset);
+        terminal
+            .shell_commands()
+            .filter_map(|command| {
+                let line = command
+                    .line
+                    .checked_sub(top_line)
+                    .filter(|line| *line < dimensions.num_lines())?;
+                let label = format_duration(command.duration?);
+                let (color, label_color) = if command.failed() {
+                    (theme.status().error, theme.status().error)
+                } else {
+                    (theme.status().success, theme.colors().text_muted)
+                };
+                let len = label.len();
+                let duration = cx
+                    .text_system()
+                    .shape_line(
+                        label.into(),
+                        text_style.font_size.to_pixels(cx.rem_size()),
+                        &[TextRun {
+                            len,
+                            font: text_style.font(),
+                            color: label_color,
+                            background_color: None,
+                            underline: None,
+                            strikethrough: None,
+                        }],
+                    )
+                    .ok()?;
+                Some(ShellCommandLayout {
+                    line: line as i32,
+                    color,
+                    duration,
+                })
+            })
+            .collect()
+    }
+
     //Vec<Range<AlacPoint>> -> Clip out the parts of the ranges
 
     fn layout_grid(
@@ -268,7 +327,8 @@ impl TerminalElement {
 
                 //Layout current cell text
                 {
-                    if !is_blank(&cell) {
+                    // Images are drawn over their placeholders, once the text is.
+                    if !is_blank(&cell) && cell.c != IMAGE_PLACEHOLDER {
                         let cell_text = cell.c.to_string();
                         let cell_style =
                             TerminalElement::cell_style(&cell, fg, theme, text_style, hyperlink);
@@ -686,6 +746,9 @@ impl Element for TerminalElement {
                     element
                 });
 
+                let shell_commands =
+                    self.layout_shell_commands(dimensions, &text_style, &theme, cx);
+
                 let TerminalContent {
                     cells,
                     mode,
@@ -693,6 +756,7 @@ impl Element for TerminalElement {
                     cursor_char,
                     selection,
                     cursor,
+                    images,
                     ..
                 } = &self.terminal.read(cx).last_content;
 
@@ -781,6 +845,8 @@ impl Element for TerminalElement {
                     hyperlink_tooltip,
                     gutter,
                     last_hovered_word,
+                    shell_commands,
+                    images: images.clone(),
                 }
             })
     }
@@ -859,6 +925,27 @@ impl Element for TerminalElement {
                     cell.paint(origin, &layout, bounds, cx);
                 }
 
+                // Images scrolled partly off the top of the terminal are cut off at its bounds.
+                cx.with_content_mask(Some(ContentMask { bounds }), |cx| {
+                    for placement in &layout.images {
+                        paint_image(placement, origin, layout.dimensions, cx);
+                    }
+                });
+
+                let line_height = layout.dimensions.line_height;
+                for command in &layout.shell_commands {
+                    let y = origin.y + command.line as f32 * line_height;
+                    cx.paint_quad(fill(
+                        Bounds::new(
+                            point(bounds.origin.x, y),
+                            size(layout.gutter / 4., line_height),
+                        ),
+                        command.color,
+                    ));
+                    let x = bounds.right() - command.duration.width - layout.dimensions.cell_width;
+                    command.duration.paint(point(x, y), line_height, cx).ok();
+                }
+
                 if self.cursor_visible {
                     if let Some(mut cursor) = cursor {
                         cursor.paint(origin, cx);
@@ -951,6 +1038,42 @@ impl InputHandler for TerminalInputHandler {
     }
 }
 
+/// Paints the image in the cells it takes up, scaled down to fit them, and keeping its aspect
+/// ratio.
+fn paint_image(
+    placement: &ImagePlacement,
+    origin: Point<Pixels>,
+    dimensions: TerminalSize,
+    cx: &mut WindowContext,
+) {
+    let image = &placement.image;
+    let cells_origin = origin
+        + point(
+            placement.point.column.0 as f32 * dimensions.cell_width,
+            placement.point.line.0 as f32 * dimensions.line_height,
+        );
+    let cells_size = size(
+        image.columns as f32 * dimensions.cell_width,
+        image.rows as f32 * dimensions.line_height,
+    );
+    let image_size = image.data.size();
+    let (image_width, image_height) = (
+        u32::from(image_size.width) as f32,
+        u32::from(image_size.height) as f32,
+    );
+    if image_width == 0. || image_height == 0. {
+        return;
+    }
+    let scale = (f32::from(cells_size.width) / image_width)
+        .min(f32::from(cells_size.height) / image_height);
+    let image_bounds = Bounds::new(
+        cells_origin,
+        size(px(image_width * scale), px(image_height * scale)),
+    );
+    cx.paint_image(image_bounds, Corners::default(), image.data.clone(), false)
+        .ok();
+}
+
 fn is_blank(cell: &IndexedCell) -> bool {
     if cell.c != ' ' {
         return false;
@@ -974,6 +1097,17 @@ fn is_blank(cell: &IndexedCell) -> bool {
     return true;
 }
 
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 60 {
+        format!("{}m {}s", secs / 60, secs % 60)
+    } else if secs >= 1 {
+        format!("{:.1}s", duration.as_secs_f32())
+    } else {
+        format!("{}ms", duration.as_millis())
+    }
+}
+
 fn to_highlighted_range_lines(
     range: &RangeInclusive<AlacPoint>,
     layout: &LayoutState,
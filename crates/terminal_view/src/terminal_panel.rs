@@ -1,22 +1,23 @@
 use std::{ops::ControlFlow, path::PathBuf, sync::Arc};
 
-use crate::TerminalView;
-use collections::{HashMap, HashSet};
+use crate::{TerminalView, ToolbarControls};
+use collections::HashMap;
 use db::kvp::KEY_VALUE_STORE;
 use futures::future::join_all;
 use gpui::{
-    actions, Action, AppContext, AsyncWindowContext, DismissEvent, Entity, EventEmitter,
-    ExternalPaths, FocusHandle, FocusableView, IntoElement, Model, ParentElement, Pixels, Render,
-    Styled, Subscription, Task, View, ViewContext, VisualContext, WeakView, WindowContext,
+    actions, impl_actions, point, px, Action, AppContext, AsyncWindowContext, DismissEvent, Entity,
+    EntityId, EventEmitter, ExternalPaths, FocusHandle, FocusableView, InteractiveElement,
+    IntoElement, Model, ParentElement, Pixels, Render, Styled, Subscription, Task, View,
+    ViewContext, VisualContext, WeakView, WindowContext,
 };
 use itertools::Itertools;
-use project::{Fs, ProjectEntryId};
+use project::{Fs, Project, ProjectEntryId};
 use search::{buffer_search::DivRegistrar, BufferSearchBar};
 use serde::{Deserialize, Serialize};
 use settings::Settings;
 use task::{RevealStrategy, SpawnInTerminal, TaskId, TerminalWorkDir};
 use terminal::{
-    terminal_settings::{Shell, TerminalDockPosition, TerminalSettings},
+    terminal_settings::{Shell, TerminalDockPosition, TerminalProfile, TerminalSettings},
     Terminal,
 };
 use ui::{
@@ -29,7 +30,9 @@ use workspace::{
     item::Item,
     pane,
     ui::IconName,
-    DraggedTab, NewTerminal, Pane, ToggleZoom, Workspace,
+    ActivateNextPane, ActivatePaneInDirection, ActivatePreviousPane, DraggedTab, NewTerminal, Pane,
+    PaneGroup, PaneGroupLayout, SplitDirection, SplitDown, SplitLeft, SplitRight, SplitUp,
+    ToggleZoom, Workspace, HANDLE_HITBOX_SIZE,
 };
 
 use anyhow::Result;
@@ -38,10 +41,20 @@ const TERMINAL_PANEL_KEY: &str = "TerminalPanel";
 
 actions!(terminal_panel, [ToggleFocus]);
 
+/// Opens a terminal with one of the profiles of the `terminal.profiles` setting.
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct NewTerminalWithProfile {
+    /// The name of the profile.
+    pub name: String,
+}
+
+impl_actions!(terminal_panel, [NewTerminalWithProfile]);
+
 pub fn init(cx: &mut AppContext) {
     cx.observe_new_views(
         |workspace: &mut Workspace, _: &mut ViewContext<Workspace>| {
             workspace.register_action(TerminalPanel::new_terminal);
+            workspace.register_action(TerminalPanel::new_terminal_with_profile);
             workspace.register_action(TerminalPanel::open_terminal);
             workspace.register_action(|workspace, _: &ToggleFocus, cx| {
                 if workspace
@@ -58,7 +71,10 @@ pub fn init(cx: &mut AppContext) {
 }
 
 pub struct TerminalPanel {
-    pane: View<Pane>,
+    /// The terminal panes, split side by side or stacked.
+    center: PaneGroup,
+    active_pane: View<Pane>,
+    pane_subscriptions: HashMap<EntityId, Vec<Subscription>>,
     fs: Arc<dyn Fs>,
     workspace: WeakView<Workspace>,
     width: Option<Pixels>,
@@ -72,10 +88,36 @@ pub struct TerminalPanel {
 
 impl TerminalPanel {
     fn new(workspace: &Workspace, cx: &mut ViewContext<Self>) -> Self {
-        let pane = cx.new_view(|cx| {
+        let pane = Self::new_pane(workspace.weak_handle(), workspace.project().clone(), cx);
+        let project = workspace.project().read(cx);
+        let enabled = project.is_local() || project.supports_remote_terminal(cx);
+        let mut this = Self {
+            center: PaneGroup::new(pane.clone()),
+            active_pane: pane.clone(),
+            pane_subscriptions: HashMap::default(),
+            fs: workspace.app_state().fs.clone(),
+            workspace: workspace.weak_handle(),
+            pending_serialization: Task::ready(None),
+            width: None,
+            height: None,
+            pending_terminals_to_add: 0,
+            deferred_tasks: HashMap::default(),
+            _subscriptions: Vec::new(),
+            enabled,
+        };
+        this.subscribe_to_pane(&pane, cx);
+        this
+    }
+
+    fn new_pane(
+        workspace: WeakView<Workspace>,
+        project: Model<Project>,
+        cx: &mut ViewContext<Self>,
+    ) -> View<Pane> {
+        cx.new_view(|cx| {
             let mut pane = Pane::new(
-                workspace.weak_handle(),
-                workspace.project().clone(),
+                workspace.clone(),
+                project,
                 Default::default(),
                 None,
                 NewTerminal.boxed_clone(),
@@ -93,12 +135,20 @@ impl TerminalPanel {
                             .icon_size(IconSize::Small)
                             .on_click(cx.listener(|pane, _, cx| {
                                 let focus_handle = pane.focus_handle(cx);
+                                let profiles = TerminalSettings::get_global(cx).profiles.clone();
                                 let menu = ContextMenu::build(cx, |menu, _| {
-                                    menu.action(
+                                    let mut menu = menu.action(
                                         "New Terminal",
                                         workspace::NewTerminal.boxed_clone(),
-                                    )
-                                    .entry(
+                                    );
+                                    for profile in profiles {
+                                        menu = menu.action(
+                                            profile.name.clone(),
+                                            NewTerminalWithProfile { name: profile.name }
+                                                .boxed_clone(),
+                                        );
+                                    }
+                                    menu.entry(
                                         "Spawn task",
                                         Some(tasks_ui::Spawn::modal().boxed_clone()),
                                         move |cx| {
@@ -123,6 +173,28 @@ impl TerminalPanel {
                     .when_some(pane.new_item_menu.as_ref(), |el, new_item_menu| {
                         el.child(Pane::render_menu_overlay(new_item_menu))
                     })
+                    .child(
+                        IconButton::new("split", IconName::Split)
+                            .icon_size(IconSize::Small)
+                            .on_click(cx.listener(|pane, _, cx| {
+                                let menu = ContextMenu::build(cx, |menu, _| {
+                                    menu.action("Split Right", SplitRight.boxed_clone())
+                                        .action("Split Left", SplitLeft.boxed_clone())
+                                        .action("Split Up", SplitUp.boxed_clone())
+                                        .action("Split Down", SplitDown.boxed_clone())
+                                });
+                                cx.subscribe(&menu, |pane, _, _: &DismissEvent, cx| {
+                                    pane.focus(cx);
+                                    pane.split_item_menu = None;
+                                })
+                                .detach();
+                                pane.split_item_menu = Some(menu);
+                            }))
+                            .tooltip(|cx| Tooltip::text("Split Terminal", cx)),
+                    )
+                    .when_some(pane.split_item_menu.as_ref(), |el, split_item_menu| {
+                        el.child(Pane::render_menu_overlay(split_item_menu))
+                    })
                     .child({
                         let zoomed = pane.is_zoomed();
                         IconButton::new("toggle_zoom", IconName::Maximize)
@@ -143,7 +215,6 @@ impl TerminalPanel {
                     .into_any_element()
             });
 
-            let workspace = workspace.weak_handle();
             pane.set_custom_drop_handle(cx, move |pane, dropped_item, cx| {
                 if let Some(tab) = dropped_item.downcast_ref::<DraggedTab>() {
                     let item = if &tab.pane == cx.view() {
@@ -189,29 +260,22 @@ impl TerminalPanel {
                 ControlFlow::Break(())
             });
             let buffer_search_bar = cx.new_view(search::BufferSearchBar::new);
-            pane.toolbar()
-                .update(cx, |toolbar, cx| toolbar.add_item(buffer_search_bar, cx));
+            let task_controls = cx.new_view(|_| ToolbarControls::new());
+            pane.toolbar().update(cx, |toolbar, cx| {
+                toolbar.add_item(buffer_search_bar, cx);
+                toolbar.add_item(task_controls, cx);
+            });
             pane
-        });
+        })
+    }
+
+    fn subscribe_to_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
         let subscriptions = vec![
-            cx.observe(&pane, |_, _, cx| cx.notify()),
-            cx.subscribe(&pane, Self::handle_pane_event),
+            cx.observe(pane, |_, _, cx| cx.notify()),
+            cx.subscribe(pane, Self::handle_pane_event),
         ];
-        let project = workspace.project().read(cx);
-        let enabled = project.is_local() || project.supports_remote_terminal(cx);
-        let this = Self {
-            pane,
-            fs: workspace.app_state().fs.clone(),
-            workspace: workspace.weak_handle(),
-            pending_serialization: Task::ready(None),
-            width: None,
-            height: None,
-            pending_terminals_to_add: 0,
-            deferred_tasks: HashMap::default(),
-            _subscriptions: subscriptions,
-            enabled,
-        };
-        this
+        self.pane_subscriptions
+            .insert(pane.entity_id(), subscriptions);
     }
 
     pub async fn load(
@@ -229,36 +293,54 @@ impl TerminalPanel {
             .log_err()
             .flatten();
 
-        let (panel, pane, items) = workspace.update(&mut cx, |workspace, cx| {
+        let (panel, panes_to_load) = workspace.update(&mut cx, |workspace, cx| {
             let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
-            let items = if let Some((serialized_panel, database_id)) =
+            let mut panes_to_load = Vec::new();
+            if let Some((serialized_panel, database_id)) =
                 serialized_panel.as_ref().zip(workspace.database_id())
             {
                 panel.update(cx, |panel, cx| {
                     cx.notify();
                     panel.height = serialized_panel.height.map(|h| h.round());
                     panel.width = serialized_panel.width.map(|w| w.round());
-                    panel.pane.update(cx, |_, cx| {
-                        serialized_panel
-                            .items
-                            .iter()
-                            .map(|item_id| {
-                                TerminalView::deserialize(
-                                    workspace.project().clone(),
-                                    workspace.weak_handle(),
-                                    database_id,
-                                    *item_id,
-                                    cx,
-                                )
-                            })
-                            .collect::<Vec<_>>()
-                    })
-                })
-            } else {
-                Vec::new()
-            };
-            let pane = panel.read(cx).pane.clone();
-            (panel, pane, items)
+                    let layout = serialized_panel.center().map(&mut |serialized_pane| {
+                        // The panel's first pane is reused for the first serialized one.
+                        let pane = if panes_to_load.is_empty() {
+                            panel.active_pane.clone()
+                        } else {
+                            let pane = Self::new_pane(
+                                workspace.weak_handle(),
+                                workspace.project().clone(),
+                                cx,
+                            );
+                            panel.subscribe_to_pane(&pane, cx);
+                            pane
+                        };
+                        if serialized_pane.is_active {
+                            panel.active_pane = pane.clone();
+                        }
+                        let items = pane.update(cx, |_, cx| {
+                            serialized_pane
+                                .items
+                                .iter()
+                                .map(|item_id| {
+                                    TerminalView::deserialize(
+                                        workspace.project().clone(),
+                                        workspace.weak_handle(),
+                                        database_id,
+                                        *item_id,
+                                        cx,
+                                    )
+                                })
+                                .collect::<Vec<_>>()
+                        });
+                        panes_to_load.push((pane.clone(), items, serialized_pane.active_item_id));
+                        pane
+                    });
+                    panel.center = PaneGroup::from_layout(layout);
+                });
+            }
+            (panel, panes_to_load)
         })?;
 
         if let Some(workspace) = workspace.upgrade() {
@@ -276,25 +358,44 @@ impl TerminalPanel {
                 .ok();
         }
 
-        let pane = pane.downgrade();
-        let items = futures::future::join_all(items).await;
-        pane.update(&mut cx, |pane, cx| {
-            let active_item_id = serialized_panel
-                .as_ref()
-                .and_then(|panel| panel.active_item_id);
-            let mut active_ix = None;
-            for item in items {
-                if let Some(item) = item.log_err() {
-                    let item_id = item.entity_id().as_u64();
-                    pane.add_item(Box::new(item), false, false, None, cx);
-                    if Some(item_id) == active_item_id {
-                        active_ix = Some(pane.items_len() - 1);
+        for (pane, items, active_item_id) in panes_to_load {
+            let items = join_all(items).await;
+            pane.update(&mut cx, |pane, cx| {
+                let mut active_ix = None;
+                for item in items {
+                    if let Some(item) = item.log_err() {
+                        let item_id = item.entity_id().as_u64();
+                        pane.add_item(Box::new(item), false, false, None, cx);
+                        if Some(item_id) == active_item_id {
+                            active_ix = Some(pane.items_len() - 1);
+                        }
                     }
                 }
-            }
 
-            if let Some(active_ix) = active_ix {
-                pane.activate_item(active_ix, false, false, cx)
+                if let Some(active_ix) = active_ix {
+                    pane.activate_item(active_ix, false, false, cx)
+                }
+            })?;
+        }
+
+        // Splits whose terminals couldn't be restored are left out.
+        panel.update(&mut cx, |panel, cx| {
+            let panes = panel
+                .center
+                .panes()
+                .into_iter()
+                .cloned()
+                .collect::<Vec<_>>();
+            for pane in panes {
+                if pane.read(cx).items_len() == 0
+                    && panel.center.panes().len() > 1
+                    && panel.center.remove(&pane).log_err() == Some(true)
+                {
+                    panel.pane_subscriptions.remove(&pane.entity_id());
+                }
+            }
+            if !panel.center.panes().contains(&&panel.active_pane) {
+                panel.active_pane = panel.center.panes()[0].clone();
             }
         })?;
 
@@ -303,20 +404,30 @@ impl TerminalPanel {
 
     fn handle_pane_event(
         &mut self,
-        _pane: View<Pane>,
+        pane: View<Pane>,
         event: &pane::Event,
         cx: &mut ViewContext<Self>,
     ) {
         match event {
             pane::Event::ActivateItem { .. } => self.serialize(cx),
             pane::Event::RemoveItem { .. } => self.serialize(cx),
-            pane::Event::Remove => cx.emit(PanelEvent::Close),
+            pane::Event::Remove => {
+                if self.center.panes().len() > 1 {
+                    self.remove_pane(&pane, cx);
+                } else {
+                    cx.emit(PanelEvent::Close);
+                }
+            }
             pane::Event::ZoomIn => cx.emit(PanelEvent::ZoomIn),
             pane::Event::ZoomOut => cx.emit(PanelEvent::ZoomOut),
+            pane::Event::Split(direction) => self.split_pane(pane, *direction, cx),
+            pane::Event::Focus => {
+                self.active_pane = pane;
+                cx.notify();
+            }
 
             pane::Event::AddItem { item } => {
                 if let Some(workspace) = self.workspace.upgrade() {
-                    let pane = self.pane.clone();
                     workspace.update(cx, |workspace, cx| item.added_to_pane(workspace, pane, cx))
                 }
             }
@@ -325,6 +436,101 @@ impl TerminalPanel {
         }
     }
 
+    /// Splits the pane with a new terminal, starting in the working directory of the pane's
+    /// active terminal.
+    fn split_pane(
+        &mut self,
+        pane: View<Pane>,
+        direction: SplitDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let Some(workspace) = self.workspace.upgrade() else {
+            return;
+        };
+        let project = workspace.read(cx).project().clone();
+        let working_directory = if project.read(cx).is_local() {
+            pane.read(cx)
+                .active_item()
+                .and_then(|item| item.downcast::<TerminalView>())
+                .and_then(|terminal_view| terminal_view.read(cx).terminal().read(cx).get_cwd())
+                .map(TerminalWorkDir::Local)
+        } else {
+            None
+        };
+
+        let new_pane = Self::new_pane(self.workspace.clone(), project, cx);
+        self.subscribe_to_pane(&new_pane, cx);
+        self.center.split(&pane, &new_pane, direction).log_err();
+        self.active_pane = new_pane.clone();
+        cx.focus_view(&new_pane);
+        self.add_terminal(working_directory, None, None, RevealStrategy::Always, cx)
+            .detach_and_log_err(cx);
+        cx.notify();
+    }
+
+    fn remove_pane(&mut self, pane: &View<Pane>, cx: &mut ViewContext<Self>) {
+        if self.center.remove(pane).log_err() != Some(true) {
+            return;
+        }
+        self.pane_subscriptions.remove(&pane.entity_id());
+        if self.active_pane == *pane {
+            if let Some(next_pane) = self.center.panes().last().map(|pane| (*pane).clone()) {
+                cx.focus_view(&next_pane);
+                self.active_pane = next_pane;
+            }
+        }
+        self.serialize(cx);
+        cx.notify();
+    }
+
+    fn activate_pane_in_direction(
+        &mut self,
+        action: &ActivatePaneInDirection,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let target = self
+            .center
+            .bounding_box_for_pane(&self.active_pane)
+            .and_then(|bounds| {
+                let center = bounds.center();
+                let distance = px(HANDLE_HITBOX_SIZE);
+                let target = match action.0 {
+                    SplitDirection::Left => point(bounds.left() - distance, center.y),
+                    SplitDirection::Right => point(bounds.right() + distance, center.y),
+                    SplitDirection::Up => point(center.x, bounds.top() - distance),
+                    SplitDirection::Down => point(center.x, bounds.bottom() + distance),
+                };
+                self.center.pane_at_pixel_position(target).cloned()
+            });
+        match target {
+            Some(pane) => cx.focus_view(&pane),
+            // Leave the panel for the workspace's panes and docks.
+            None => cx.propagate(),
+        }
+    }
+
+    fn activate_adjacent_pane(&mut self, delta: isize, cx: &mut ViewContext<Self>) {
+        let panes = self.center.panes();
+        if panes.len() < 2 {
+            cx.propagate();
+            return;
+        }
+        if let Some(ix) = panes.iter().position(|pane| **pane == self.active_pane) {
+            let ix = (ix as isize + delta).rem_euclid(panes.len() as isize) as usize;
+            let pane = panes[ix].clone();
+            cx.focus_view(&pane);
+        }
+    }
+
+    fn terminal_views(&self, cx: &AppContext) -> Vec<View<TerminalView>> {
+        self.center
+            .panes()
+            .into_iter()
+            .flat_map(|pane| pane.read(cx).items())
+            .filter_map(|item| item.act_as::<TerminalView>(cx))
+            .collect()
+    }
+
     pub fn open_terminal(
         workspace: &mut Workspace,
         action: &workspace::OpenTerminal,
@@ -341,7 +547,7 @@ impl TerminalPanel {
 
         terminal_panel
             .update(cx, |panel, cx| {
-                panel.add_terminal(terminal_work_dir, None, RevealStrategy::Always, cx)
+                panel.add_terminal(terminal_work_dir, None, None, RevealStrategy::Always, cx)
             })
             .detach_and_log_err(cx);
     }
@@ -375,6 +581,24 @@ impl TerminalPanel {
         let allow_concurrent_runs = spawn_in_terminal.allow_concurrent_runs;
         let use_new_terminal = spawn_in_terminal.use_new_terminal;
 
+        // Dedicated terminals are reused by every run of their task, which restarts the task
+        // when it's still running.
+        if spawn_in_terminal.dedicated_terminal {
+            self.deferred_tasks.remove(&spawn_in_terminal.id);
+            match self
+                .terminals_for_task(&spawn_in_terminal.full_label, cx)
+                .pop()
+            {
+                Some(existing_terminal) => {
+                    self.replace_terminal(spawn_task, existing_terminal, cx);
+                }
+                None => self
+                    .spawn_in_new_terminal(spawn_task, cx)
+                    .detach_and_log_err(cx),
+            }
+            return;
+        }
+
         if allow_concurrent_runs && use_new_terminal {
             self.spawn_in_new_terminal(spawn_task, cx)
                 .detach_and_log_err(cx);
@@ -387,7 +611,7 @@ impl TerminalPanel {
                 .detach_and_log_err(cx);
             return;
         }
-        let (existing_item_index, existing_terminal) = terminals_for_task
+        let existing_terminal = terminals_for_task
             .last()
             .expect("covered no terminals case above")
             .clone();
@@ -396,34 +620,36 @@ impl TerminalPanel {
                 !use_new_terminal,
                 "Should have handled 'allow_concurrent_runs && use_new_terminal' case above"
             );
-            self.replace_terminal(spawn_task, existing_item_index, existing_terminal, cx);
+            self.replace_terminal(spawn_task, existing_terminal, cx);
         } else {
             self.deferred_tasks.insert(
                 spawn_in_terminal.id.clone(),
-                cx.spawn(|terminal_panel, mut cx| async move {
-                    wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
-                    terminal_panel
-                        .update(&mut cx, |terminal_panel, cx| {
-                            if use_new_terminal {
-                                terminal_panel
-                                    .spawn_in_new_terminal(spawn_task, cx)
-                                    .detach_and_log_err(cx);
-                            } else {
-                                terminal_panel.replace_terminal(
-                                    spawn_task,
-                                    existing_item_index,
-                                    existing_terminal,
-                                    cx,
-                                );
-                            }
-                        })
-                        .ok();
+                cx.spawn({
+                    let existing_terminal = existing_terminal.clone();
+                    |terminal_panel, mut cx| async move {
+                        wait_for_terminals_tasks(terminals_for_task, &mut cx).await;
+                        terminal_panel
+                            .update(&mut cx, |terminal_panel, cx| {
+                                if use_new_terminal {
+                                    terminal_panel
+                                        .spawn_in_new_terminal(spawn_task, cx)
+                                        .detach_and_log_err(cx);
+                                } else {
+                                    terminal_panel.replace_terminal(
+                                        spawn_task,
+                                        existing_terminal,
+                                        cx,
+                                    );
+                                }
+                            })
+                            .ok();
+                    }
                 }),
             );
 
             match reveal {
                 RevealStrategy::Always => {
-                    self.activate_terminal_view(existing_item_index, cx);
+                    self.activate_terminal_view(&existing_terminal, cx);
                     let task_workspace = self.workspace.clone();
                     cx.spawn(|_, mut cx| async move {
                         task_workspace
@@ -443,7 +669,7 @@ impl TerminalPanel {
         cx: &mut ViewContext<Self>,
     ) -> Task<Result<Model<Terminal>>> {
         let reveal = spawn_task.reveal;
-        self.add_terminal(spawn_task.cwd.clone(), Some(spawn_task), reveal, cx)
+        self.add_terminal(spawn_task.cwd.clone(), Some(spawn_task), None, reveal, cx)
     }
 
     /// Create a new Terminal in the current working directory or the user's home directory
@@ -458,42 +684,80 @@ impl TerminalPanel {
 
         terminal_panel
             .update(cx, |this, cx| {
-                this.add_terminal(None, None, RevealStrategy::Always, cx)
+                this.add_terminal(None, None, None, RevealStrategy::Always, cx)
             })
             .detach_and_log_err(cx);
     }
 
-    fn terminals_for_task(
-        &self,
-        label: &str,
-        cx: &mut AppContext,
-    ) -> Vec<(usize, View<TerminalView>)> {
-        self.pane
-            .read(cx)
-            .items()
-            .enumerate()
-            .filter_map(|(index, item)| Some((index, item.act_as::<TerminalView>(cx)?)))
-            .filter_map(|(index, terminal_view)| {
-                let task_state = terminal_view.read(cx).terminal().read(cx).task()?;
-                if &task_state.full_label == label {
-                    Some((index, terminal_view))
-                } else {
-                    None
-                }
+    /// Create a new Terminal with the named profile
+    fn new_terminal_with_profile(
+        workspace: &mut Workspace,
+        action: &NewTerminalWithProfile,
+        cx: &mut ViewContext<Workspace>,
+    ) {
+        let Some(terminal_panel) = workspace.panel::<Self>(cx) else {
+            return;
+        };
+        let Some(profile) = TerminalSettings::get_global(cx)
+            .profiles
+            .iter()
+            .find(|profile| profile.name == action.name)
+            .cloned()
+        else {
+            workspace.show_error(
+                &anyhow::anyhow!("no terminal profile named {:?}", action.name),
+                cx,
+            );
+            return;
+        };
+
+        // Profiles on other hosts open their shell in its home directory.
+        let working_directory = profile.ssh.as_ref().map(|ssh| TerminalWorkDir::Ssh {
+            ssh_command: format!("ssh {ssh}"),
+            path: None,
+        });
+        terminal_panel
+            .update(cx, |this, cx| {
+                this.add_terminal(
+                    working_directory,
+                    None,
+                    Some(profile),
+                    RevealStrategy::Always,
+                    cx,
+                )
+            })
+            .detach_and_log_err(cx);
+    }
+
+    fn terminals_for_task(&self, label: &str, cx: &mut AppContext) -> Vec<View<TerminalView>> {
+        self.terminal_views(cx)
+            .into_iter()
+            .filter(|terminal_view| {
+                terminal_view
+                    .read(cx)
+                    .terminal()
+                    .read(cx)
+                    .task()
+                    .is_some_and(|task_state| task_state.full_label == label)
             })
             .collect()
     }
 
-    fn activate_terminal_view(&self, item_index: usize, cx: &mut WindowContext) {
-        self.pane.update(cx, |pane, cx| {
-            pane.activate_item(item_index, true, true, cx)
-        })
+    fn activate_terminal_view(&self, terminal_view: &View<TerminalView>, cx: &mut WindowContext) {
+        for pane in self.center.panes() {
+            let index = pane.read(cx).index_for_item(terminal_view);
+            if let Some(index) = index {
+                pane.update(cx, |pane, cx| pane.activate_item(index, true, true, cx));
+                return;
+            }
+        }
     }
 
     fn add_terminal(
         &mut self,
         working_directory: Option<TerminalWorkDir>,
         spawn_task: Option<SpawnInTerminal>,
+        profile: Option<TerminalProfile>,
         reveal_strategy: RevealStrategy,
         cx: &mut ViewContext<Self>,
     ) -> Task<Result<Model<Terminal>>> {
@@ -514,7 +778,7 @@ impl TerminalPanel {
         self.pending_terminals_to_add += 1;
 
         cx.spawn(|terminal_panel, mut cx| async move {
-            let pane = terminal_panel.update(&mut cx, |this, _| this.pane.clone())?;
+            let pane = terminal_panel.update(&mut cx, |this, _| this.active_pane.clone())?;
             let result = workspace.update(&mut cx, |workspace, cx| {
                 let working_directory = if let Some(working_directory) = working_directory {
                     Some(working_directory)
@@ -526,7 +790,14 @@ impl TerminalPanel {
 
                 let window = cx.window_handle();
                 let terminal = workspace.project().update(cx, |project, cx| {
-                    project.create_terminal(working_directory, spawn_task, window, cx)
+                    project.create_terminal(
+                        working_directory,
+                        spawn_task,
+                        profile,
+                        None,
+                        window,
+                        cx,
+                    )
                 })?;
                 let terminal_view = Box::new(cx.new_view(|cx| {
                     TerminalView::new(
@@ -555,28 +826,27 @@ impl TerminalPanel {
     }
 
     fn serialize(&mut self, cx: &mut ViewContext<Self>) {
-        let mut items_to_serialize = HashSet::default();
-        let items = self
-            .pane
-            .read(cx)
-            .items()
-            .filter_map(|item| {
-                let terminal_view = item.act_as::<TerminalView>(cx)?;
-                if terminal_view.read(cx).terminal().read(cx).task().is_some() {
-                    None
-                } else {
-                    let id = item.item_id().as_u64();
-                    items_to_serialize.insert(id);
-                    Some(id)
-                }
-            })
-            .collect::<Vec<_>>();
-        let active_item_id = self
-            .pane
-            .read(cx)
-            .active_item()
-            .map(|item| item.item_id().as_u64())
-            .filter(|active_id| items_to_serialize.contains(active_id));
+        let active_pane = self.active_pane.clone();
+        let center = self.center.layout(&mut |pane| {
+            let is_active = *pane == active_pane;
+            let pane = pane.read(cx);
+            // Terminals running tasks aren't restored.
+            let items = pane
+                .items()
+                .filter_map(|item| item.act_as::<TerminalView>(cx))
+                .filter(|terminal_view| terminal_view.read(cx).terminal().read(cx).task().is_none())
+                .map(|terminal_view| terminal_view.entity_id().as_u64())
+                .collect::<Vec<_>>();
+            let active_item_id = pane
+                .active_item()
+                .map(|item| item.item_id().as_u64())
+                .filter(|active_id| items.contains(active_id));
+            SerializedTerminalPane {
+                items,
+                active_item_id,
+                is_active,
+            }
+        });
         let height = self.height;
         let width = self.width;
         self.pending_serialization = cx.background_executor().spawn(
@@ -585,8 +855,9 @@ impl TerminalPanel {
                     .write_kvp(
                         TERMINAL_PANEL_KEY.into(),
                         serde_json::to_string(&SerializedTerminalPanel {
-                            items,
-                            active_item_id,
+                            items: Vec::new(),
+                            active_item_id: None,
+                            center: Some(center),
                             height,
                             width,
                         })?,
@@ -601,7 +872,6 @@ impl TerminalPanel {
     fn replace_terminal(
         &self,
         spawn_task: SpawnInTerminal,
-        terminal_item_index: usize,
         terminal_to_replace: View<TerminalView>,
         cx: &mut ViewContext<'_, Self>,
     ) -> Option<()> {
@@ -614,7 +884,14 @@ impl TerminalPanel {
         let window = cx.window_handle();
         let new_terminal = project.update(cx, |project, cx| {
             project
-                .create_terminal(spawn_task.cwd.clone(), Some(spawn_task), window, cx)
+                .create_terminal(
+                    spawn_task.cwd.clone(),
+                    Some(spawn_task),
+                    None,
+                    None,
+                    window,
+                    cx,
+                )
                 .log_err()
         })?;
         terminal_to_replace.update(cx, |terminal_to_replace, cx| {
@@ -623,7 +900,7 @@ impl TerminalPanel {
 
         match reveal {
             RevealStrategy::Always => {
-                self.activate_terminal_view(terminal_item_index, cx);
+                self.activate_terminal_view(&terminal_to_replace, cx);
                 let task_workspace = self.workspace.clone();
                 cx.spawn(|_, mut cx| async move {
                     task_workspace
@@ -638,20 +915,25 @@ impl TerminalPanel {
         Some(())
     }
 
+    /// Returns the pane of the focused split, or of the last focused one.
     pub fn pane(&self) -> &View<Pane> {
-        &self.pane
+        &self.active_pane
     }
 
     fn has_no_terminals(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).items_len() == 0 && self.pending_terminals_to_add == 0
+        self.center
+            .panes()
+            .iter()
+            .all(|pane| pane.read(cx).items_len() == 0)
+            && self.pending_terminals_to_add == 0
     }
 }
 
 async fn wait_for_terminals_tasks(
-    terminals_for_task: Vec<(usize, View<TerminalView>)>,
+    terminals_for_task: Vec<View<TerminalView>>,
     cx: &mut AsyncWindowContext,
 ) {
-    let pending_tasks = terminals_for_task.iter().filter_map(|(_, terminal)| {
+    let pending_tasks = terminals_for_task.iter().filter_map(|terminal| {
         terminal
             .update(cx, |terminal_view, cx| {
                 terminal_view
@@ -686,7 +968,7 @@ impl Render for TerminalPanel {
         let mut registrar = DivRegistrar::new(
             |panel, cx| {
                 panel
-                    .pane
+                    .active_pane
                     .read(cx)
                     .toolbar()
                     .read(cx)
@@ -695,13 +977,28 @@ impl Render for TerminalPanel {
             cx,
         );
         BufferSearchBar::register(&mut registrar);
-        registrar.into_div().size_full().child(self.pane.clone())
+        registrar
+            .into_div()
+            .size_full()
+            .on_action(cx.listener(Self::activate_pane_in_direction))
+            .on_action(
+                cx.listener(|this, _: &ActivateNextPane, cx| this.activate_adjacent_pane(1, cx)),
+            )
+            .on_action(
+                cx.listener(|this, _: &ActivatePreviousPane, cx| {
+                    this.activate_adjacent_pane(-1, cx)
+                }),
+            )
+            .child(
+                self.center
+                    .render_in_panel(&self.active_pane, &self.workspace, cx),
+            )
     }
 }
 
 impl FocusableView for TerminalPanel {
     fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
-        self.pane.focus_handle(cx)
+        self.active_pane.focus_handle(cx)
     }
 }
 
@@ -749,22 +1046,29 @@ impl Panel for TerminalPanel {
     }
 
     fn is_zoomed(&self, cx: &WindowContext) -> bool {
-        self.pane.read(cx).is_zoomed()
+        self.active_pane.read(cx).is_zoomed()
     }
 
     fn set_zoomed(&mut self, zoomed: bool, cx: &mut ViewContext<Self>) {
-        self.pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        for pane in self.center.panes() {
+            pane.update(cx, |pane, cx| pane.set_zoomed(zoomed, cx));
+        }
     }
 
     fn set_active(&mut self, active: bool, cx: &mut ViewContext<Self>) {
         if active && self.has_no_terminals(cx) {
-            self.add_terminal(None, None, RevealStrategy::Never, cx)
+            self.add_terminal(None, None, None, RevealStrategy::Never, cx)
                 .detach_and_log_err(cx)
         }
     }
 
     fn icon_label(&self, cx: &WindowContext) -> Option<String> {
-        let count = self.pane.read(cx).items_len();
+        let count = self
+            .center
+            .panes()
+            .iter()
+            .map(|pane| pane.read(cx).items_len())
+            .sum::<usize>();
         if count == 0 {
             None
         } else {
@@ -795,8 +1099,127 @@ impl Panel for TerminalPanel {
 
 #[derive(Serialize, Deserialize)]
 struct SerializedTerminalPanel {
+    /// The terminals of panels serialized before they could be split.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     items: Vec<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     active_item_id: Option<u64>,
+    #[serde(default)]
+    center: Option<PaneGroupLayout<SerializedTerminalPane>>,
     width: Option<Pixels>,
     height: Option<Pixels>,
 }
+
+impl SerializedTerminalPanel {
+    fn center(&self) -> PaneGroupLayout<SerializedTerminalPane> {
+        self.center.clone().unwrap_or_else(|| {
+            PaneGroupLayout::Pane(SerializedTerminalPane {
+                items: self.items.clone(),
+                active_item_id: self.active_item_id,
+                is_active: true,
+            })
+        })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct SerializedTerminalPane {
+    items: Vec<u64>,
+    active_item_id: Option<u64>,
+    is_active: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::TestAppContext;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_split_terminal_panel(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        cx.executor().allow_parking();
+        let project = Project::test(app_state.fs.clone(), [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        let panel = workspace.update(cx, |workspace, cx| {
+            let panel = cx.new_view(|cx| TerminalPanel::new(workspace, cx));
+            workspace.add_panel(panel.clone(), cx);
+            panel
+        });
+        panel
+            .update(cx, |panel, cx| {
+                panel.add_terminal(None, None, None, RevealStrategy::Always, cx)
+            })
+            .await
+            .unwrap();
+        cx.run_until_parked();
+        let first_pane = panel.update(cx, |panel, _| panel.active_pane.clone());
+
+        // The new split gets a terminal of its own, and the focus.
+        first_pane.update(cx, |pane, cx| pane.split(SplitDirection::Right, cx));
+        cx.run_until_parked();
+        let second_pane = panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes().len(), 2);
+            assert_ne!(panel.active_pane, first_pane);
+            assert!(panel.active_pane.focus_handle(cx).contains_focused(cx));
+            assert_eq!(
+                panel.center.layout(&mut |pane| pane.read(cx).items_len()),
+                PaneGroupLayout::Axis {
+                    horizontal: true,
+                    members: vec![PaneGroupLayout::Pane(1), PaneGroupLayout::Pane(1)],
+                    flexes: vec![1., 1.],
+                }
+            );
+            panel.active_pane.clone()
+        });
+
+        // The splits are serialized along with their terminals.
+        let serialized_panel = KEY_VALUE_STORE
+            .read_kvp(TERMINAL_PANEL_KEY)
+            .unwrap()
+            .unwrap();
+        let serialized_panel =
+            serde_json::from_str::<SerializedTerminalPanel>(&serialized_panel).unwrap();
+        let panes = serialized_panel
+            .center()
+            .map(&mut |pane| (pane.items.len(), pane.is_active));
+        assert_eq!(
+            panes,
+            PaneGroupLayout::Axis {
+                horizontal: true,
+                members: vec![
+                    PaneGroupLayout::Pane((1, false)),
+                    PaneGroupLayout::Pane((1, true))
+                ],
+                flexes: vec![1., 1.],
+            }
+        );
+
+        panel.update(cx, |panel, cx| panel.activate_adjacent_pane(-1, cx));
+        first_pane.update(cx, |pane, cx| {
+            assert!(pane.focus_handle(cx).contains_focused(cx))
+        });
+
+        panel.update(cx, |panel, cx| panel.remove_pane(&second_pane, cx));
+        cx.run_until_parked();
+        panel.update(cx, |panel, cx| {
+            assert_eq!(panel.center.panes(), [&first_pane]);
+            assert_eq!(panel.active_pane, first_pane);
+            assert!(first_pane.focus_handle(cx).contains_focused(cx));
+        });
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            client::init_settings(cx);
+            language::init(cx);
+            Project::init_settings(cx);
+            workspace::init_settings(cx);
+            editor::init(cx);
+            crate::init(cx);
+            state
+        })
+    }
+}
@@ -1,14 +1,16 @@
 mod persistence;
 pub mod terminal_element;
 pub mod terminal_panel;
+mod toolbar_controls;
 
 use collections::HashSet;
 use editor::{scroll::Autoscroll, Editor};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
-    anchored, deferred, div, impl_actions, AnyElement, AppContext, DismissEvent, EventEmitter,
-    FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model, MouseButton,
-    MouseDownEvent, Pixels, Render, Styled, Subscription, Task, View, VisualContext, WeakView,
+    anchored, deferred, div, impl_actions, AnyElement, AppContext, ClipboardItem, DismissEvent,
+    EventEmitter, FocusHandle, FocusableView, KeyContext, KeyDownEvent, Keystroke, Model,
+    MouseButton, MouseDownEvent, Pixels, Render, Styled, Subscription, Task, View, VisualContext,
+    WeakView,
 };
 use language::Bias;
 use persistence::TERMINAL_DB;
@@ -20,19 +22,22 @@ use terminal::{
         index::Point,
         term::{search::RegexSearch, TermMode},
     },
-    terminal_settings::{TerminalBlink, TerminalSettings, WorkingDirectory},
-    Clear, Copy, Event, MaybeNavigationTarget, Paste, ShowCharacterPalette, TaskStatus, Terminal,
+    session::RestoredTerminal,
+    terminal_settings::{TerminalBlink, TerminalOutputFormat, TerminalSettings, WorkingDirectory},
+    Clear, Copy, CopyAllMatches, Event, ExportRawScrollback, ExportScrollback, LogRawToFile,
+    LogToFile, MaybeNavigationTarget, Paste, PathLikeTarget, RerunLastCommand, ScrollToNextPrompt,
+    ScrollToPreviousPrompt, ShellCommand, ShowCharacterPalette, StopLogging, TaskStatus, Terminal,
 };
 use terminal_element::TerminalElement;
 use ui::{h_flex, prelude::*, ContextMenu, Icon, IconName, Label, Tooltip};
 use util::{paths::PathLikeWithPosition, ResultExt};
 use workspace::{
     item::{BreadcrumbText, Item, ItemEvent, TabContentParams},
-    notifications::NotifyResultExt,
+    notifications::{NotifyResultExt, NotifyTaskExt},
     register_deserializable_item,
     searchable::{SearchEvent, SearchOptions, SearchableItem, SearchableItemHandle},
-    CloseActiveItem, NewCenterTerminal, OpenVisible, Pane, ToolbarItemLocation, Workspace,
-    WorkspaceId,
+    ClipboardHistory, CloseActiveItem, NewCenterTerminal, OpenVisible, Pane, ToolbarItemLocation,
+    Workspace, WorkspaceId,
 };
 
 use anyhow::Context;
@@ -42,12 +47,15 @@ use settings::Settings;
 use smol::Timer;
 
 use std::{
+    future::Future,
     ops::RangeInclusive,
     path::{Path, PathBuf},
     sync::Arc,
     time::Duration,
 };
 
+pub use toolbar_controls::ToolbarControls;
+
 const REGEX_SPECIAL_CHARS: &[char] = &[
     '\\', '.', '*', '+', '?', '|', '(', ')', '[', ']', '{', '}', '^', '$',
 ];
@@ -122,7 +130,7 @@ impl TerminalView {
         let terminal = workspace
             .project()
             .update(cx, |project, cx| {
-                project.create_terminal(working_directory, None, window, cx)
+                project.create_terminal(working_directory, None, None, None, window, cx)
             })
             .notify_err(workspace, cx);
 
@@ -155,6 +163,35 @@ impl TerminalView {
         let focus_out = cx.on_focus_out(&focus_handle, |terminal_view, cx| {
             terminal_view.focus_out(cx);
         });
+        let view = cx.view().downgrade();
+        let save_on_quit = cx.on_app_quit(move |cx| {
+            let save = view
+                .upgrade()
+                .and_then(|view| view.read(cx).save_session(view.entity_id().as_u64(), cx));
+            async move {
+                if let Some(save) = save {
+                    save.await.log_err();
+                }
+            }
+        });
+        // Closing the window detaches from the session like quitting does, so that the terminal
+        // is restored along with its workspace.
+        let save_on_close = workspace_handle.upgrade().map(|workspace| {
+            cx.subscribe(&workspace, |terminal_view, _, event, cx| {
+                if let workspace::Event::Closing = event {
+                    if let Some(save) =
+                        terminal_view.save_session(cx.view().entity_id().as_u64(), cx)
+                    {
+                        cx.background_executor()
+                            .spawn(async move { save.await.log_err() })
+                            .detach();
+                    }
+                    terminal_view
+                        .terminal
+                        .update(cx, |terminal, _| terminal.keep_session());
+                }
+            })
+        });
 
         Self {
             terminal,
@@ -169,11 +206,15 @@ impl TerminalView {
             can_navigate_to_selected_word: false,
             workspace_id,
             show_title: TerminalSettings::get_global(cx).toolbar.title,
-            _subscriptions: vec![
+            _subscriptions: [
                 focus_in,
                 focus_out,
+                save_on_quit,
                 cx.observe_global::<SettingsStore>(Self::settings_changed),
-            ],
+            ]
+            .into_iter()
+            .chain(save_on_close)
+            .collect(),
             _terminal_subscriptions: terminal_subscriptions,
         }
     }
@@ -182,6 +223,46 @@ impl TerminalView {
         &self.terminal
     }
 
+    /// Saves what's restored of the terminal when its workspace is reopened.
+    fn save_session(
+        &self,
+        item_id: workspace::ItemId,
+        cx: &AppContext,
+    ) -> Option<impl Future<Output = anyhow::Result<()>>> {
+        let terminal = self.terminal.read(cx);
+        if terminal.task().is_some() {
+            return None;
+        }
+        let workspace_id = self.workspace_id?;
+        let scrollback_lines = TerminalSettings::get_global(cx)
+            .persistent_sessions
+            .restore_scrollback_lines;
+        let cwd = terminal.get_cwd();
+        let title = terminal.breadcrumb_text.clone();
+        let env = serde_json::to_string(terminal.env()).log_err()?;
+        let scrollback = terminal.scrollback_tail(scrollback_lines);
+        let session_id = terminal.session().map(|session| session.id().to_string());
+        let profile = terminal.profile().map(|profile| profile.name.clone());
+        Some(async move {
+            if let Some(cwd) = cwd {
+                TERMINAL_DB
+                    .save_working_directory(item_id, workspace_id, cwd)
+                    .await?;
+            }
+            TERMINAL_DB
+                .save_session(
+                    item_id,
+                    workspace_id,
+                    title,
+                    env,
+                    scrollback,
+                    session_id,
+                    profile,
+                )
+                .await
+        })
+    }
+
     pub fn has_bell(&self) -> bool {
         self.has_bell
     }
@@ -196,8 +277,23 @@ impl TerminalView {
         position: gpui::Point<Pixels>,
         cx: &mut ViewContext<Self>,
     ) {
+        let has_matches = !self.terminal.read(cx).matches.is_empty();
+        let is_logging = self.terminal.read(cx).log_path().is_some();
         let context_menu = ContextMenu::build(cx, |menu, _| {
             menu.action("Clear", Box::new(Clear))
+                .when(has_matches, |menu| {
+                    menu.action("Copy All Matches", Box::new(CopyAllMatches))
+                })
+                .separator()
+                .action("Export Scrollback…", Box::new(ExportScrollback))
+                .map(|menu| {
+                    if is_logging {
+                        menu.action("Stop Logging", Box::new(StopLogging))
+                    } else {
+                        menu.action("Log to File…", Box::new(LogToFile))
+                    }
+                })
+                .separator()
                 .action("Close", Box::new(CloseActiveItem { save_intent: None }))
         });
 
@@ -251,6 +347,28 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn scroll_to_previous_prompt(
+        &mut self,
+        _: &ScrollToPreviousPrompt,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_previous_prompt());
+        cx.notify();
+    }
+
+    fn scroll_to_next_prompt(&mut self, _: &ScrollToNextPrompt, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.scroll_to_next_prompt());
+        cx.notify();
+    }
+
+    fn rerun_last_command(&mut self, _: &RerunLastCommand, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |term, _| term.rerun_last_command());
+        cx.notify();
+    }
+
     pub fn should_show_cursor(&self, focused: bool, cx: &mut gpui::ViewContext<Self>) -> bool {
         //Don't blink the cursor when not focused, blinking is disabled, or paused
         if !focused
@@ -324,6 +442,74 @@ impl TerminalView {
         cx.notify();
     }
 
+    fn copy_all_matches(&mut self, _: &CopyAllMatches, cx: &mut ViewContext<Self>) {
+        self.terminal.update(cx, |term, _| term.copy_all_matches());
+        cx.notify();
+    }
+
+    fn log_to_file(&mut self, _: &LogToFile, cx: &mut ViewContext<Self>) {
+        self.start_logging(TerminalOutputFormat::Plain, cx);
+    }
+
+    fn log_raw_to_file(&mut self, _: &LogRawToFile, cx: &mut ViewContext<Self>) {
+        self.start_logging(TerminalOutputFormat::Raw, cx);
+    }
+
+    fn start_logging(&mut self, format: TerminalOutputFormat, cx: &mut ViewContext<Self>) {
+        let path = self.prompt_for_output_path(cx);
+        cx.spawn(|this, mut cx| async move {
+            let Some(path) = path.await.ok().flatten() else {
+                return Ok(());
+            };
+            this.update(&mut cx, |this, cx| {
+                this.terminal
+                    .update(cx, |terminal, _| terminal.start_logging(path, format))
+            })?
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    fn stop_logging(&mut self, _: &StopLogging, cx: &mut ViewContext<Self>) {
+        self.terminal
+            .update(cx, |terminal, _| terminal.stop_logging());
+    }
+
+    fn export_scrollback(&mut self, _: &ExportScrollback, cx: &mut ViewContext<Self>) {
+        self.export(TerminalOutputFormat::Plain, cx);
+    }
+
+    fn export_raw_scrollback(&mut self, _: &ExportRawScrollback, cx: &mut ViewContext<Self>) {
+        self.export(TerminalOutputFormat::Raw, cx);
+    }
+
+    /// Saves the scrollback as it is when the action is run, to the file picked afterwards.
+    fn export(&mut self, format: TerminalOutputFormat, cx: &mut ViewContext<Self>) {
+        let text = self.terminal.read(cx).export_scrollback(format);
+        let path = self.prompt_for_output_path(cx);
+        cx.spawn(|_, _| async move {
+            let Some(path) = path.await.ok().flatten() else {
+                return Ok(());
+            };
+            smol::fs::write(&path, text)
+                .await
+                .with_context(|| format!("exporting terminal scrollback to {path:?}"))
+        })
+        .detach_and_notify_err(cx);
+    }
+
+    fn prompt_for_output_path(
+        &self,
+        cx: &mut ViewContext<Self>,
+    ) -> futures::channel::oneshot::Receiver<Option<PathBuf>> {
+        let directory = self
+            .terminal
+            .read(cx)
+            .get_cwd()
+            .or_else(home_dir)
+            .unwrap_or_default();
+        cx.prompt_for_new_path(&directory)
+    }
+
     ///Attempt to paste the clipboard into the terminal
     fn paste(&mut self, _: &Paste, cx: &mut ViewContext<Self>) {
         if let Some(item) = cx.read_from_clipboard() {
@@ -479,13 +665,8 @@ fn subscribe_for_terminal_events(
                         if let Ok(fs) = workspace.update(cx, |workspace, cx| {
                             workspace.project().read(cx).fs().clone()
                         }) {
-                            let valid_files_to_open_task = possible_open_targets(
-                                fs,
-                                &workspace,
-                                &path_like_target.terminal_dir,
-                                &path_like_target.maybe_path,
-                                cx,
-                            );
+                            let valid_files_to_open_task =
+                                possible_open_targets(fs, &workspace, path_like_target, cx);
                             smol::block_on(valid_files_to_open_task).len() > 0
                         } else {
                             false
@@ -516,13 +697,7 @@ fn subscribe_for_terminal_events(
                     cx.spawn(|terminal_view, mut cx| async move {
                         let valid_files_to_open = terminal_view
                             .update(&mut cx, |_, cx| {
-                                possible_open_targets(
-                                    fs,
-                                    &task_workspace,
-                                    &path_like_target.terminal_dir,
-                                    &path_like_target.maybe_path,
-                                    cx,
-                                )
+                                possible_open_targets(fs, &task_workspace, &path_like_target, cx)
                             })?
                             .await;
                         let paths_to_open = valid_files_to_open
@@ -591,6 +766,7 @@ fn subscribe_for_terminal_events(
             Event::BreadcrumbsChanged => cx.emit(ItemEvent::UpdateBreadcrumbs),
             Event::CloseTerminal => cx.emit(ItemEvent::CloseItem),
             Event::SelectionsChanged => cx.emit(SearchEvent::ActiveMatchChanged),
+            Event::Copied(text) => ClipboardHistory::record(ClipboardItem::new(text.clone()), cx),
         });
     vec![terminal_subscription, terminal_events_subscription]
 }
@@ -633,11 +809,10 @@ fn possible_open_paths_metadata(
 fn possible_open_targets(
     fs: Arc<dyn Fs>,
     workspace: &WeakView<Workspace>,
-    cwd: &Option<PathBuf>,
-    maybe_path: &String,
+    target: &PathLikeTarget,
     cx: &mut ViewContext<TerminalView>,
 ) -> Task<Vec<(PathLikeWithPosition<PathBuf>, Metadata)>> {
-    let path_like = PathLikeWithPosition::parse_str(maybe_path.as_str(), |path_str| {
+    let path_like = PathLikeWithPosition::parse_str(target.maybe_path.as_str(), |path_str| {
         Ok::<_, std::convert::Infallible>(Path::new(path_str).to_path_buf())
     })
     .expect("infallible");
@@ -659,16 +834,22 @@ fn possible_open_targets(
     } else {
         // First check cwd and then workspace
         let mut potential_cwd_and_workspace_paths = HashSet::default();
-        if let Some(cwd) = cwd {
+        if let Some(cwd) = &target.terminal_dir {
             potential_cwd_and_workspace_paths.insert(Path::join(cwd, &maybe_path));
         }
         if let Some(workspace) = workspace.upgrade() {
             workspace.update(cx, |workspace, cx| {
-                for potential_worktree_path in workspace
-                    .worktrees(cx)
-                    .map(|worktree| worktree.read(cx).abs_path().join(&maybe_path))
-                {
-                    potential_cwd_and_workspace_paths.insert(potential_worktree_path);
+                for worktree in workspace.worktrees(cx) {
+                    let worktree = worktree.read(cx);
+                    potential_cwd_and_workspace_paths.insert(worktree.abs_path().join(&maybe_path));
+                    if target.search_project {
+                        potential_cwd_and_workspace_paths.extend(
+                            worktree
+                                .files(false, 0)
+                                .filter(|entry| entry.path.ends_with(&maybe_path))
+                                .map(|entry| worktree.abs_path().join(&entry.path)),
+                        );
+                    }
                 }
             });
         }
@@ -692,12 +873,26 @@ fn regex_to_literal(regex: &str) -> String {
 }
 
 pub fn regex_search_for_query(query: &project::search::SearchQuery) -> Option<RegexSearch> {
-    let query = query.as_str();
-    if query == "." {
-        return None;
-    }
-    let searcher = RegexSearch::new(&query);
-    searcher.ok()
+    RegexSearch::new(&search_pattern_for_query(query)?).ok()
+}
+
+/// Builds the regex matching the query in the terminal, which doesn't guess the case
+/// sensitivity of the query from its case, as Alacritty does.
+fn search_pattern_for_query(query: &SearchQuery) -> Option<String> {
+    let pattern = match query {
+        SearchQuery::Text { .. } => regex_to_literal(query.as_str()),
+        SearchQuery::Regex { .. } if query.as_str() == "." => return None,
+        SearchQuery::Regex { .. } => query.as_str().to_string(),
+        // The terminal content has no syntax tree to match against.
+        SearchQuery::Structural { .. } => return None,
+    };
+    let pattern = if query.whole_word() {
+        format!(r"\b(?:{pattern})\b")
+    } else {
+        pattern
+    };
+    let case_flag = if query.case_sensitive() { "-i" } else { "i" };
+    Some(format!("(?{case_flag}){pattern}"))
 }
 
 impl TerminalView {
@@ -741,8 +936,17 @@ impl Render for TerminalView {
             .on_action(cx.listener(TerminalView::send_text))
             .on_action(cx.listener(TerminalView::send_keystroke))
             .on_action(cx.listener(TerminalView::copy))
+            .on_action(cx.listener(TerminalView::copy_all_matches))
+            .on_action(cx.listener(TerminalView::log_to_file))
+            .on_action(cx.listener(TerminalView::log_raw_to_file))
+            .on_action(cx.listener(TerminalView::stop_logging))
+            .on_action(cx.listener(TerminalView::export_scrollback))
+            .on_action(cx.listener(TerminalView::export_raw_scrollback))
             .on_action(cx.listener(TerminalView::paste))
             .on_action(cx.listener(TerminalView::clear))
+            .on_action(cx.listener(TerminalView::scroll_to_previous_prompt))
+            .on_action(cx.listener(TerminalView::scroll_to_next_prompt))
+            .on_action(cx.listener(TerminalView::rerun_last_command))
             .on_action(cx.listener(TerminalView::show_character_palette))
             .on_action(cx.listener(TerminalView::select_all))
             .on_key_down(cx.listener(Self::key_down))
@@ -788,6 +992,10 @@ impl Item for TerminalView {
     fn tab_content(&self, params: TabContentParams, cx: &WindowContext) -> AnyElement {
         let terminal = self.terminal().read(cx);
         let title = terminal.title(true);
+        let terminal_icon = terminal
+            .profile()
+            .and_then(|profile| profile.icon.as_deref()?.parse().ok())
+            .unwrap_or(IconName::Terminal);
 
         let (icon, icon_color, rerun_btn) = match terminal.task() {
             Some(terminal_task) => match &terminal_task.status {
@@ -815,7 +1023,13 @@ impl Item for TerminalView {
                     }
                 }
             },
-            None => (IconName::Terminal, Color::Muted, None),
+            None if terminal
+                .last_finished_command()
+                .is_some_and(ShellCommand::failed) =>
+            {
+                (terminal_icon, Color::Error, None)
+            }
+            None => (terminal_icon, Color::Muted, None),
         };
 
         h_flex()
@@ -915,8 +1129,38 @@ impl Item for TerminalView {
     ) -> Task<anyhow::Result<View<Self>>> {
         let window = cx.window_handle();
         cx.spawn(|pane, mut cx| async move {
+            let session = TERMINAL_DB
+                .get_session(item_id, workspace_id)
+                .log_err()
+                .flatten();
+            let profile_name = session
+                .as_ref()
+                .and_then(|(_, _, _, _, profile)| profile.clone());
+            let restored =
+                session.map(|(title, env, scrollback, session_id, _)| RestoredTerminal {
+                    title: title.unwrap_or_default(),
+                    env: env
+                        .and_then(|env| serde_json::from_str(&env).log_err())
+                        .unwrap_or_default(),
+                    scrollback: scrollback.unwrap_or_default(),
+                    session_id,
+                });
+            // The terminal is reopened with its profile, unless it was removed from the settings.
+            let profile = cx.update(|cx| {
+                TerminalSettings::get_global(cx)
+                    .profiles
+                    .iter()
+                    .find(|profile| Some(&profile.name) == profile_name.as_ref())
+                    .cloned()
+            })?;
             let cwd = cx
                 .update(|cx| {
+                    if let Some(ssh) = profile.as_ref().and_then(|profile| profile.ssh.as_ref()) {
+                        return Some(TerminalWorkDir::Ssh {
+                            ssh_command: format!("ssh {ssh}"),
+                            path: None,
+                        });
+                    }
                     let from_db = TERMINAL_DB
                         .get_working_directory(item_id, workspace_id)
                         .log_err()
@@ -939,7 +1183,7 @@ impl Item for TerminalView {
                 .flatten();
 
             let terminal = project.update(&mut cx, |project, cx| {
-                project.create_terminal(cwd, None, window, cx)
+                project.create_terminal(cwd, None, profile, restored, window, cx)
             })??;
             pane.update(&mut cx, |_, cx| {
                 cx.new_view(|cx| TerminalView::new(terminal, workspace, Some(workspace_id), cx))
@@ -968,8 +1212,8 @@ impl SearchableItem for TerminalView {
 
     fn supported_options() -> SearchOptions {
         SearchOptions {
-            case: false,
-            word: false,
+            case: true,
+            word: true,
             regex: true,
             replacement: false,
             selection: false,
@@ -1017,22 +1261,7 @@ impl SearchableItem for TerminalView {
         query: Arc<SearchQuery>,
         cx: &mut ViewContext<Self>,
     ) -> Task<Vec<Self::Match>> {
-        let searcher = match &*query {
-            SearchQuery::Text { .. } => regex_search_for_query(
-                &(SearchQuery::text(
-                    regex_to_literal(&query.as_str()),
-                    query.whole_word(),
-                    query.case_sensitive(),
-                    query.include_ignored(),
-                    query.files_to_include().to_vec(),
-                    query.files_to_exclude().to_vec(),
-                )
-                .unwrap()),
-            ),
-            SearchQuery::Regex { .. } => regex_search_for_query(&query),
-        };
-
-        if let Some(s) = searcher {
+        if let Some(s) = regex_search_for_query(&query) {
             self.terminal()
                 .update(cx, |term, cx| term.find_matches(s, cx))
         } else {
@@ -1340,4 +1569,28 @@ mod tests {
     fn empty_string_stays_empty() {
         assert_eq!(regex_to_literal(""), "".to_string());
     }
+
+    #[test]
+    fn search_pattern_respects_options() {
+        let text = |query, whole_word, case_sensitive| {
+            SearchQuery::text(query, whole_word, case_sensitive, false, vec![], vec![]).unwrap()
+        };
+        let regex = |query, whole_word, case_sensitive| {
+            SearchQuery::regex(query, whole_word, case_sensitive, false, vec![], vec![]).unwrap()
+        };
+
+        assert_eq!(
+            search_pattern_for_query(&text("Error(", false, false)).as_deref(),
+            Some(r"(?i)Error\(")
+        );
+        assert_eq!(
+            search_pattern_for_query(&text("error", true, true)).as_deref(),
+            Some(r"(?-i)\b(?:error)\b")
+        );
+        assert_eq!(
+            search_pattern_for_query(&regex(r"warn(ing)?: \d+", false, true)).as_deref(),
+            Some(r"(?-i)warn(ing)?: \d+")
+        );
+        assert_eq!(search_pattern_for_query(&regex(".", false, false)), None);
+    }
 }
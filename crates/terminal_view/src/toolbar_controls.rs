@@ -0,0 +1,83 @@
+use crate::TerminalView;
+use gpui::{EventEmitter, ParentElement, Render, Subscription, ViewContext, WeakView};
+use terminal::TaskStatus;
+use ui::prelude::*;
+use ui::{IconButton, IconName, Tooltip};
+use workspace::{item::ItemHandle, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
+
+/// The controls of the task running in the active terminal.
+pub struct ToolbarControls {
+    terminal_view: Option<WeakView<TerminalView>>,
+    _subscription: Option<Subscription>,
+}
+
+impl Render for ToolbarControls {
+    fn render(&mut self, cx: &mut ViewContext<Self>) -> impl IntoElement {
+        let task = self
+            .terminal_view
+            .as_ref()
+            .and_then(|terminal_view| terminal_view.upgrade())
+            .and_then(|terminal_view| {
+                let task = terminal_view.read(cx).terminal().read(cx).task()?;
+                Some((task.id.clone(), task.status == TaskStatus::Running))
+            });
+        let Some((task_id, is_running)) = task else {
+            return h_flex();
+        };
+
+        let tooltip = if is_running {
+            "Restart Task"
+        } else {
+            "Rerun Task"
+        };
+        h_flex().child(
+            IconButton::new("restart-task", IconName::Rerun)
+                .tooltip(move |cx| Tooltip::text(tooltip, cx))
+                .on_click(move |_, cx| {
+                    // Rerunning the task in its own terminal stops the run still going on.
+                    cx.dispatch_action(Box::new(tasks_ui::Rerun {
+                        task_id: Some(task_id.clone()),
+                        allow_concurrent_runs: Some(true),
+                        use_new_terminal: Some(false),
+                        ..Default::default()
+                    }));
+                }),
+        )
+    }
+}
+
+impl EventEmitter<ToolbarItemEvent> for ToolbarControls {}
+
+impl ToolbarItemView for ToolbarControls {
+    fn set_active_pane_item(
+        &mut self,
+        active_pane_item: Option<&dyn ItemHandle>,
+        cx: &mut ViewContext<Self>,
+    ) -> ToolbarItemLocation {
+        let terminal_view = active_pane_item
+            .and_then(|pane_item| pane_item.downcast::<TerminalView>())
+            .filter(|terminal_view| terminal_view.read(cx).terminal().read(cx).task().is_some());
+        match terminal_view {
+            Some(terminal_view) => {
+                // The status of the task is shown as it changes.
+                self._subscription = Some(cx.observe(&terminal_view, |_, _, cx| cx.notify()));
+                self.terminal_view = Some(terminal_view.downgrade());
+                ToolbarItemLocation::PrimaryRight
+            }
+            None => {
+                self._subscription = None;
+                self.terminal_view = None;
+                ToolbarItemLocation::Hidden
+            }
+        }
+    }
+}
+
+impl ToolbarControls {
+    pub fn new() -> Self {
+        ToolbarControls {
+            terminal_view: None,
+            _subscription: None,
+        }
+    }
+}
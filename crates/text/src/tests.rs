@@ -604,6 +604,73 @@ fn test_history() {
     assert_eq!(buffer.text(), "X12cde6");
 }
 
+#[test]
+fn test_undo_history_tree() {
+    let now = Instant::now();
+    let mut buffer = Buffer::new(0, BufferId::new(1).unwrap(), "abc".into());
+    buffer.set_group_interval(Duration::from_secs(0));
+
+    let transaction_1 = edit_in_transaction(&mut buffer, now, 3..3, "1");
+    let transaction_2 = edit_in_transaction(&mut buffer, now, 4..4, "2");
+    assert_eq!(buffer.text(), "abc12");
+
+    // Making an edit after undoing forks the history instead of discarding the undone edit.
+    buffer.undo();
+    let transaction_3 = edit_in_transaction(&mut buffer, now, 4..4, "3");
+    assert_eq!(buffer.text(), "abc13");
+    buffer.redo();
+    assert_eq!(buffer.text(), "abc13");
+
+    let nodes = buffer
+        .undo_history()
+        .into_iter()
+        .map(|node| {
+            (
+                node.transaction_id,
+                node.parent,
+                node.applied,
+                node.inserted_text,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        nodes,
+        [
+            (transaction_1, None, true, "1".to_string()),
+            (transaction_3, Some(transaction_1), true, "3".to_string()),
+            (transaction_2, Some(transaction_1), false, "2".to_string()),
+        ]
+    );
+
+    // Restoring a transaction on the abandoned branch switches branches.
+    buffer.restore_undo_history(Some(transaction_2)).unwrap();
+    assert_eq!(buffer.text(), "abc12");
+    buffer.undo();
+    assert_eq!(buffer.text(), "abc1");
+    buffer.redo();
+    assert_eq!(buffer.text(), "abc12");
+
+    buffer.restore_undo_history(Some(transaction_3)).unwrap();
+    assert_eq!(buffer.text(), "abc13");
+    buffer.restore_undo_history(None).unwrap();
+    assert_eq!(buffer.text(), "abc");
+    buffer.redo();
+    buffer.redo();
+    assert_eq!(buffer.text(), "abc13");
+}
+
+fn edit_in_transaction(
+    buffer: &mut Buffer,
+    now: Instant,
+    range: Range<usize>,
+    text: &str,
+) -> TransactionId {
+    let transaction_id = buffer.start_transaction_at(now).unwrap();
+    buffer.edit([(range, text)]);
+    buffer.end_transaction_at(now);
+    transaction_id
+}
+
 #[test]
 fn test_finalize_last_transaction() {
     let now = Instant::now();
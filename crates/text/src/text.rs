@@ -136,12 +136,37 @@ impl HistoryEntry {
     }
 }
 
+/// Transactions that were undone and then abandoned by making a new edit, which forks the
+/// undo history into a tree. Abandoned transactions stay undone, but can be restored with
+/// [`Buffer::restore_undo_history`].
+#[derive(Clone, Debug)]
+struct UndoBranch {
+    /// The transaction the branch forked from, or `None` for the initial state of the buffer.
+    parent: Option<TransactionId>,
+    /// The abandoned transactions, in the order they were applied.
+    entries: Vec<HistoryEntry>,
+}
+
+/// A transaction in the undo history of a buffer, see [`Buffer::undo_history`].
+#[derive(Clone, Debug)]
+pub struct UndoHistoryNode {
+    pub transaction_id: TransactionId,
+    /// The transaction this one was applied on top of, or `None` for the initial state of the buffer.
+    pub parent: Option<TransactionId>,
+    /// Whether the transaction is part of the current state of the buffer.
+    pub applied: bool,
+    pub last_edit_at: Instant,
+    /// The text inserted by the transaction, used to preview it.
+    pub inserted_text: String,
+}
+
 struct History {
     base_text: Rope,
     operations: TreeMap<clock::Lamport, Operation>,
     insertion_slices: HashMap<clock::Lamport, Vec<InsertionSlice>>,
     undo_stack: Vec<HistoryEntry>,
     redo_stack: Vec<HistoryEntry>,
+    branches: Vec<UndoBranch>,
     transaction_depth: usize,
     group_interval: Duration,
 }
@@ -160,6 +185,7 @@ impl History {
             insertion_slices: Default::default(),
             undo_stack: Vec::new(),
             redo_stack: Vec::new(),
+            branches: Vec::new(),
             transaction_depth: 0,
             // Don't group transactions in tests unless we opt in, because it's a footgun.
             #[cfg(any(test, feature = "test-support"))]
@@ -213,7 +239,13 @@ impl History {
                 self.undo_stack.pop();
                 None
             } else {
-                self.redo_stack.clear();
+                let parent = self
+                    .undo_stack
+                    .iter()
+                    .rev()
+                    .nth(1)
+                    .map(|e| e.transaction.id);
+                self.abandon_redo_stack(parent);
                 let entry = self.undo_stack.last_mut().unwrap();
                 entry.last_edit_at = now;
                 Some(entry)
@@ -268,6 +300,17 @@ impl History {
             if let Some(entry) = entries_to_merge.last_mut() {
                 last_entry.last_edit_at = entry.last_edit_at;
             }
+
+            // Branches that forked from a merged transaction now fork from the one it got merged into.
+            let merged_into = last_entry.transaction.id;
+            for branch in &mut self.branches {
+                if entries_to_merge
+                    .iter()
+                    .any(|entry| Some(entry.transaction.id) == branch.parent)
+                {
+                    branch.parent = Some(merged_into);
+                }
+            }
         }
 
         self.undo_stack.truncate(new_len);
@@ -289,7 +332,24 @@ impl History {
             last_edit_at: now,
             suppress_grouping: false,
         });
-        self.redo_stack.clear();
+        let parent = self
+            .undo_stack
+            .iter()
+            .rev()
+            .nth(1)
+            .map(|e| e.transaction.id);
+        self.abandon_redo_stack(parent);
+    }
+
+    /// Moves the undone transactions into a new branch forking from `parent`, instead of
+    /// discarding them.
+    fn abandon_redo_stack(&mut self, parent: Option<TransactionId>) {
+        if !self.redo_stack.is_empty() {
+            self.branches.push(UndoBranch {
+                parent,
+                entries: self.redo_stack.drain(..).rev().collect(),
+            });
+        }
     }
 
     fn push_undo(&mut self, op_id: clock::Lamport) {
@@ -344,6 +404,14 @@ impl History {
             .iter()
             .rposition(|entry| entry.transaction.id == transaction_id)
         {
+            let parent = entry_ix
+                .checked_sub(1)
+                .map(|ix| self.undo_stack[ix].transaction.id);
+            for branch in &mut self.branches {
+                if branch.parent == Some(transaction_id) {
+                    branch.parent = parent;
+                }
+            }
             Some(self.undo_stack.remove(entry_ix).transaction)
         } else if let Some(entry_ix) = self
             .redo_stack
@@ -387,6 +455,100 @@ impl History {
         }
     }
 
+    /// Rearranges the history so that the undo stack ends with the given transaction, or is
+    /// empty for `None`. If the transaction is on a branch, the branch becomes the new redo
+    /// stack and the previous redo stack is abandoned instead.
+    ///
+    /// Returns the transactions whose undo state needs to be toggled to get to the new state,
+    /// or `None` if the transaction is not part of the history.
+    fn restore(&mut self, transaction_id: Option<TransactionId>) -> Option<Vec<Transaction>> {
+        assert_eq!(self.transaction_depth, 0);
+
+        let mut toggled = Vec::new();
+        let contains = |entries: &[HistoryEntry]| {
+            entries
+                .iter()
+                .any(|entry| Some(entry.transaction.id) == transaction_id)
+        };
+        if transaction_id.is_some() && !contains(&self.undo_stack) && !contains(&self.redo_stack) {
+            let branch_parent = self
+                .branches
+                .iter()
+                .find(|branch| contains(&branch.entries))?
+                .parent;
+            // The parent may be on another branch, so bring it onto the undo stack first.
+            toggled.extend(self.restore(branch_parent)?);
+            let branch_ix = self
+                .branches
+                .iter()
+                .position(|branch| contains(&branch.entries))?;
+            let branch = self.branches.remove(branch_ix);
+            self.abandon_redo_stack(branch_parent);
+            self.redo_stack = branch.entries.into_iter().rev().collect();
+        }
+
+        if transaction_id.is_none() || contains(&self.undo_stack) {
+            while self.undo_stack.last().map(|entry| entry.transaction.id) != transaction_id {
+                let entry = self.undo_stack.pop()?;
+                toggled.push(entry.transaction.clone());
+                self.redo_stack.push(entry);
+            }
+        } else {
+            while self.undo_stack.last().map(|entry| entry.transaction.id) != transaction_id {
+                let entry = self.redo_stack.pop()?;
+                toggled.push(entry.transaction.clone());
+                self.undo_stack.push(entry);
+            }
+        }
+        Some(toggled)
+    }
+
+    fn undo_history(&self) -> Vec<UndoHistoryNode> {
+        let mut nodes = Vec::new();
+        let mut push_entries =
+            |entries: &mut dyn Iterator<Item = &HistoryEntry>,
+             mut parent: Option<TransactionId>,
+             mut applied: bool,
+             applied_until: Option<TransactionId>| {
+                for entry in entries {
+                    let inserted_text = entry
+                        .transaction
+                        .edit_ids
+                        .iter()
+                        .filter_map(|edit_id| match self.operations.get(edit_id) {
+                            Some(Operation::Edit(edit)) => Some(edit.new_text.iter()),
+                            _ => None,
+                        })
+                        .flatten()
+                        .map(|text| text.as_ref())
+                        .collect::<String>();
+                    nodes.push(UndoHistoryNode {
+                        transaction_id: entry.transaction.id,
+                        parent,
+                        applied,
+                        last_edit_at: entry.last_edit_at,
+                        inserted_text,
+                    });
+                    if applied_until == Some(entry.transaction.id) {
+                        applied = false;
+                    }
+                    parent = Some(entry.transaction.id);
+                }
+            };
+
+        let last_applied = self.undo_stack.last().map(|entry| entry.transaction.id);
+        push_entries(
+            &mut self.undo_stack.iter().chain(self.redo_stack.iter().rev()),
+            None,
+            last_applied.is_some(),
+            last_applied,
+        );
+        for branch in &self.branches {
+            push_entries(&mut branch.entries.iter(), branch.parent, false, None);
+        }
+        nodes
+    }
+
     fn remove_from_redo(&mut self, transaction_id: TransactionId) -> &[HistoryEntry] {
         assert_eq!(self.transaction_depth, 0);
 
@@ -1352,6 +1514,28 @@ impl Buffer {
         }
     }
 
+    /// Returns every transaction of the undo history, including the ones that were undone and
+    /// then abandoned by making a new edit.
+    pub fn undo_history(&self) -> Vec<UndoHistoryNode> {
+        self.history.undo_history()
+    }
+
+    /// Brings the buffer back to the state right after the given transaction was applied, or
+    /// to its initial state for `None`, even if that state is on an abandoned branch of the
+    /// undo history.
+    pub fn restore_undo_history(
+        &mut self,
+        transaction_id: Option<TransactionId>,
+    ) -> Option<Vec<Operation>> {
+        let transactions = self.history.restore(transaction_id)?;
+        Some(
+            transactions
+                .into_iter()
+                .map(|transaction| self.undo_or_redo(transaction).unwrap())
+                .collect(),
+        )
+    }
+
     pub fn redo_to_transaction(&mut self, transaction_id: TransactionId) -> Vec<Operation> {
         let transactions = self
             .history
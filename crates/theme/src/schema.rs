@@ -410,6 +410,11 @@ pub struct ThemeColorsContent {
     #[serde(rename = "editor.document_highlight.write_background")]
     pub editor_document_highlight_write_background: Option<String>,
 
+    /// Background of ranges that are edited together with the range under the cursor,
+    /// such as the matching closing tag of an HTML element.
+    #[serde(rename = "editor.linked_edit_background")]
+    pub editor_linked_edit_background: Option<String>,
+
     /// Terminal background color.
     #[serde(rename = "terminal.background")]
     pub terminal_background: Option<String>,
@@ -776,6 +781,10 @@ impl ThemeColorsContent {
                 .editor_document_highlight_write_background
                 .as_ref()
                 .and_then(|color| try_parse_color(color).ok()),
+            editor_linked_edit_background: self
+                .editor_linked_edit_background
+                .as_ref()
+                .and_then(|color| try_parse_color(color).ok()),
             terminal_background: self
                 .terminal_background
                 .as_ref()
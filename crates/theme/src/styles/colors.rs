@@ -170,6 +170,9 @@ pub struct ThemeColors {
     /// special attention. Usually a document highlight is visualized by changing
     /// the background color of its range.
     pub editor_document_highlight_write_background: Hsla,
+    /// Background of ranges that are edited together with the range under the cursor,
+    /// such as the matching closing tag of an HTML element.
+    pub editor_linked_edit_background: Hsla,
 
     // ===
     // Terminal
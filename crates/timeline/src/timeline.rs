@@ -0,0 +1,384 @@
+use anyhow::{Context as _, Result};
+use editor::{actions::ExpandAllHunkDiffs, Editor, MultiBuffer, DEFAULT_MULTIBUFFER_CONTEXT};
+use fuzzy::{match_strings, StringMatch, StringMatchCandidate};
+use gpui::{
+    actions, AppContext, AsyncWindowContext, DismissEvent, EventEmitter, FocusHandle,
+    FocusableView, Model, Render, Task, View, ViewContext, VisualContext, WeakView,
+};
+use language::{Buffer, Capability};
+use picker::{Picker, PickerDelegate};
+use project::local_history::{LocalHistory, LocalHistoryEntry};
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+use time::OffsetDateTime;
+use time_format::TimestampFormat;
+use ui::{prelude::*, HighlightedLabel, ListItem, ListItemSpacing, Tooltip};
+use util::ResultExt;
+use workspace::{notifications::DetachAndPromptErr, ModalView, Workspace};
+
+actions!(timeline, [Toggle]);
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(|workspace: &mut Workspace, _| {
+        workspace.register_action(Timeline::toggle);
+    })
+    .detach();
+}
+
+/// A modal listing the versions the file of the active editor was saved with, kept by the
+/// project's local history.
+pub struct Timeline {
+    picker: View<Picker<TimelineDelegate>>,
+}
+
+impl Timeline {
+    fn toggle(workspace: &mut Workspace, _: &Toggle, cx: &mut ViewContext<Workspace>) {
+        let Some(buffer) = workspace
+            .active_item_as::<Editor>(cx)
+            .and_then(|editor| editor.read(cx).buffer().read(cx).as_singleton())
+        else {
+            return;
+        };
+        let Some(abs_path) = buffer
+            .read(cx)
+            .file()
+            .and_then(|file| file.as_local())
+            .map(|file| file.abs_path(cx))
+        else {
+            return;
+        };
+        let Some(local_history) = workspace.project().read(cx).local_history().cloned() else {
+            return;
+        };
+
+        cx.spawn(|workspace, mut cx| async move {
+            let entries = local_history.entries(&abs_path).await?;
+            workspace.update(&mut cx, |workspace, cx| {
+                let workspace_handle = cx.view().downgrade();
+                workspace.toggle_modal(cx, move |cx| {
+                    let now = OffsetDateTime::now_utc();
+                    let timezone = cx.local_timezone();
+                    let labels = entries
+                        .iter()
+                        .map(|entry| {
+                            let saved_at = OffsetDateTime::from(entry.saved_at);
+                            let format = |format| {
+                                time_format::format_localized_timestamp(
+                                    saved_at, now, timezone, format,
+                                )
+                            };
+                            (
+                                format(TimestampFormat::EnhancedAbsolute),
+                                format(TimestampFormat::Relative),
+                            )
+                        })
+                        .collect();
+                    let delegate = TimelineDelegate {
+                        timeline: cx.view().downgrade(),
+                        workspace: workspace_handle,
+                        local_history,
+                        abs_path,
+                        buffer,
+                        entries: Arc::new(entries),
+                        labels: Arc::new(labels),
+                        matches: Vec::new(),
+                        selected_index: 0,
+                    };
+                    Self {
+                        picker: cx.new_view(|cx| Picker::uniform_list(delegate, cx)),
+                    }
+                });
+            })
+        })
+        .detach_and_prompt_err("Failed to load the timeline", cx, |_, _| None);
+    }
+}
+
+impl Render for Timeline {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl FocusableView for Timeline {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for Timeline {}
+impl ModalView for Timeline {}
+
+struct TimelineDelegate {
+    timeline: WeakView<Timeline>,
+    workspace: WeakView<Workspace>,
+    local_history: Arc<LocalHistory>,
+    abs_path: PathBuf,
+    buffer: Model<Buffer>,
+    entries: Arc<Vec<LocalHistoryEntry>>,
+    /// The absolute and relative times each entry was saved at.
+    labels: Arc<Vec<(String, String)>>,
+    matches: Vec<StringMatch>,
+    selected_index: usize,
+}
+
+impl TimelineDelegate {
+    /// Opens the changes made to the file since the given version, up to its current contents.
+    fn open_diff(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(mat) = self.matches.get(ix) else {
+            return;
+        };
+        let entry = self.entries[mat.candidate_id].clone();
+        let title = format!(
+            "{} @ {} ↔ current",
+            file_name(&self.abs_path),
+            self.labels[mat.candidate_id].0
+        );
+        let current_text = self.buffer.read(cx).text();
+        open_diff(
+            self.workspace.clone(),
+            self.local_history.clone(),
+            entry,
+            self.abs_path.clone(),
+            current_text,
+            title,
+            cx,
+        )
+        .detach_and_prompt_err("Failed to open the version", cx, |_, _| None);
+        self.dismissed(cx);
+    }
+
+    /// Replaces the contents of the file's buffer with the given version, as an edit that can be
+    /// undone. The buffer isn't saved.
+    fn restore(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        let Some(mat) = self.matches.get(ix) else {
+            return;
+        };
+        let entry = self.entries[mat.candidate_id].clone();
+        let local_history = self.local_history.clone();
+        let buffer = self.buffer.clone();
+        cx.spawn(|_, mut cx| async move {
+            let text = local_history.load(&entry).await?;
+            let diff = buffer
+                .update(&mut cx, |buffer, cx| buffer.diff(text, cx))?
+                .await;
+            buffer.update(&mut cx, |buffer, cx| {
+                buffer.finalize_last_transaction();
+                buffer.apply_diff(diff, cx);
+                buffer.finalize_last_transaction();
+            })
+        })
+        .detach_and_prompt_err("Failed to restore the version", cx, |_, _| None);
+        self.dismissed(cx);
+    }
+}
+
+impl PickerDelegate for TimelineDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        format!("Search the timeline of {}...", file_name(&self.abs_path)).into()
+    }
+
+    fn no_matches_text(&self, _cx: &mut WindowContext) -> SharedString {
+        if self.entries.is_empty() {
+            "No saved versions".into()
+        } else {
+            "No matches".into()
+        }
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, _: &mut ViewContext<Picker<Self>>) {
+        self.selected_index = ix;
+    }
+
+    fn update_matches(&mut self, query: String, cx: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let background = cx.background_executor().clone();
+        let labels = self.labels.clone();
+        cx.spawn(|this, mut cx| async move {
+            let candidates = labels
+                .iter()
+                .enumerate()
+                .map(|(ix, (absolute, _))| StringMatchCandidate::new(ix, absolute.clone()))
+                .collect::<Vec<_>>();
+            let matches = if query.is_empty() {
+                candidates
+                    .into_iter()
+                    .map(|candidate| StringMatch {
+                        candidate_id: candidate.id,
+                        string: candidate.string,
+                        positions: Vec::new(),
+                        score: 0.0,
+                    })
+                    .collect()
+            } else {
+                match_strings(
+                    &candidates,
+                    &query,
+                    false,
+                    labels.len(),
+                    &Default::default(),
+                    background,
+                )
+                .await
+            };
+
+            this.update(&mut cx, |this, cx| {
+                let delegate = &mut this.delegate;
+                delegate.matches = matches;
+                delegate.selected_index = delegate
+                    .selected_index
+                    .min(delegate.matches.len().saturating_sub(1));
+                cx.notify();
+            })
+            .log_err();
+        })
+    }
+
+    fn confirm(&mut self, secondary: bool, cx: &mut ViewContext<Picker<Self>>) {
+        if secondary {
+            self.restore(self.selected_index, cx);
+        } else {
+            self.open_diff(self.selected_index, cx);
+        }
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.timeline
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let mat = &self.matches[ix];
+        let (absolute, relative) = &self.labels[mat.candidate_id];
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .child(
+                    h_flex()
+                        .gap_2()
+                        .child(HighlightedLabel::new(
+                            absolute.clone(),
+                            mat.positions.clone(),
+                        ))
+                        .child(
+                            Label::new(relative.clone())
+                                .size(LabelSize::Small)
+                                .color(Color::Muted),
+                        ),
+                )
+                .end_hover_slot(
+                    IconButton::new(("restore-version", ix), IconName::HistoryRerun)
+                        .icon_size(IconSize::Small)
+                        .tooltip(|cx| {
+                            Tooltip::for_action("Restore This Version", &menu::SecondaryConfirm, cx)
+                        })
+                        .on_click(cx.listener(move |picker, _, cx| {
+                            cx.stop_propagation();
+                            cx.prevent_default();
+                            picker.delegate.restore(ix, cx);
+                        })),
+                ),
+        )
+    }
+}
+
+/// Opens the changes made to a file since one of its saved versions in a read-only diff.
+fn open_diff(
+    workspace: WeakView<Workspace>,
+    local_history: Arc<LocalHistory>,
+    entry: LocalHistoryEntry,
+    abs_path: PathBuf,
+    current_text: String,
+    title: String,
+    cx: &mut WindowContext,
+) -> Task<Result<()>> {
+    cx.spawn(|mut cx| async move {
+        let version_text = local_history
+            .load(&entry)
+            .await
+            .context("loading the saved version")?;
+        let buffer = read_only_buffer(&workspace, &abs_path, current_text, &mut cx).await?;
+        let recalc = buffer.update(&mut cx, |buffer, cx| {
+            buffer.set_diff_base(Some(version_text), cx);
+            buffer.git_diff_recalc(cx)
+        })?;
+        if let Some(recalc) = recalc {
+            recalc.await;
+        }
+
+        workspace.update(&mut cx, |workspace, cx| {
+            let snapshot = buffer.read(cx).snapshot();
+            let mut hunk_ranges = snapshot
+                .git_diff_hunks_in_row_range(0..snapshot.max_point().row + 1)
+                .map(|hunk| hunk.buffer_range)
+                .collect::<Vec<_>>();
+            if hunk_ranges.is_empty() {
+                hunk_ranges.push(snapshot.anchor_before(0)..snapshot.anchor_after(snapshot.len()));
+            }
+
+            let multibuffer = cx.new_model(|cx| {
+                let mut multibuffer = MultiBuffer::new(0, Capability::ReadOnly).with_title(title);
+                multibuffer.push_excerpts_with_context_lines(
+                    buffer,
+                    hunk_ranges,
+                    DEFAULT_MULTIBUFFER_CONTEXT,
+                    cx,
+                );
+                multibuffer
+            });
+            let project = workspace.project().clone();
+            let editor = cx.new_view(|cx| {
+                let mut editor = Editor::for_multibuffer(multibuffer, Some(project), true, cx);
+                editor.expand_all_hunk_diffs(&ExpandAllHunkDiffs, cx);
+                editor
+            });
+            workspace.add_item_to_active_pane(Box::new(editor), None, cx);
+        })
+    })
+}
+
+/// Creates a read-only buffer holding the given contents of a file.
+async fn read_only_buffer(
+    workspace: &WeakView<Workspace>,
+    path: &Path,
+    text: String,
+    cx: &mut AsyncWindowContext,
+) -> Result<Model<Buffer>> {
+    let languages = workspace.update(cx, |workspace, cx| {
+        workspace.project().read(cx).languages().clone()
+    })?;
+    let language = languages.language_for_file_path(path).await.ok();
+    cx.new_model(|cx| {
+        let mut buffer = Buffer::local(text, cx);
+        buffer.set_language_registry(languages);
+        buffer.set_language(language, cx);
+        buffer.set_capability(Capability::ReadOnly, cx);
+        buffer
+    })
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_else(|| path.to_string_lossy().to_string())
+}
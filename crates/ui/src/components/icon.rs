@@ -1,5 +1,5 @@
 use gpui::{svg, AnimationElement, Hsla, IntoElement, Rems, Transformation};
-use strum::EnumIter;
+use strum::{EnumIter, EnumString};
 
 use crate::{prelude::*, Indicator};
 
@@ -76,7 +76,8 @@ impl IconSize {
     }
 }
 
-#[derive(Debug, PartialEq, Copy, Clone, EnumIter)]
+#[derive(Debug, PartialEq, Copy, Clone, EnumIter, EnumString)]
+#[strum(serialize_all = "snake_case")]
 pub enum IconName {
     Ai,
     ArrowCircle,
@@ -96,6 +97,7 @@ pub enum IconName {
     BellOff,
     BellRing,
     Bolt,
+    Bookmark,
     CaseSensitive,
     Check,
     ChevronDown,
@@ -114,6 +116,16 @@ pub enum IconName {
     Copy,
     CountdownTimer,
     Dash,
+    Debug,
+    DebugBreakpoint,
+    DebugBreakpointConditional,
+    DebugContinue,
+    DebugLogBreakpoint,
+    DebugPause,
+    DebugStepInto,
+    DebugStepOut,
+    DebugStepOver,
+    DebugStop,
     Delete,
     Disconnected,
     Ellipsis,
@@ -135,6 +147,7 @@ pub enum IconName {
     Folder,
     FolderOpen,
     FolderX,
+    GitBranch,
     Github,
     Hash,
     HistoryRerun,
@@ -157,8 +170,10 @@ pub enum IconName {
     PageUp,
     Pencil,
     Person,
+    Pin,
     Play,
     Plus,
+    PreserveCase,
     Public,
     PullRequest,
     Quote,
@@ -223,6 +238,7 @@ impl IconName {
             IconName::BellOff => "icons/bell_off.svg",
             IconName::BellRing => "icons/bell_ring.svg",
             IconName::Bolt => "icons/bolt.svg",
+            IconName::Bookmark => "icons/bookmark.svg",
             IconName::CaseSensitive => "icons/case_insensitive.svg",
             IconName::Check => "icons/check.svg",
             IconName::ChevronDown => "icons/chevron_down.svg",
@@ -241,6 +257,16 @@ impl IconName {
             IconName::Copy => "icons/copy.svg",
             IconName::CountdownTimer => "icons/countdown_timer.svg",
             IconName::Dash => "icons/dash.svg",
+            IconName::Debug => "icons/debug.svg",
+            IconName::DebugBreakpoint => "icons/debug_breakpoint.svg",
+            IconName::DebugBreakpointConditional => "icons/debug_breakpoint_conditional.svg",
+            IconName::DebugContinue => "icons/debug_continue.svg",
+            IconName::DebugLogBreakpoint => "icons/debug_log_breakpoint.svg",
+            IconName::DebugPause => "icons/debug_pause.svg",
+            IconName::DebugStepInto => "icons/debug_step_into.svg",
+            IconName::DebugStepOut => "icons/debug_step_out.svg",
+            IconName::DebugStepOver => "icons/debug_step_over.svg",
+            IconName::DebugStop => "icons/debug_stop.svg",
             IconName::Delete => "icons/delete.svg",
             IconName::Disconnected => "icons/disconnected.svg",
             IconName::Ellipsis => "icons/ellipsis.svg",
@@ -262,6 +288,7 @@ impl IconName {
             IconName::Folder => "icons/file_icons/folder.svg",
             IconName::FolderOpen => "icons/file_icons/folder_open.svg",
             IconName::FolderX => "icons/stop_sharing.svg",
+            IconName::GitBranch => "icons/git_branch.svg",
             IconName::Github => "icons/github.svg",
             IconName::Hash => "icons/hash.svg",
             IconName::HistoryRerun => "icons/history_rerun.svg",
@@ -284,8 +311,10 @@ impl IconName {
             IconName::PageUp => "icons/page_up.svg",
             IconName::Pencil => "icons/pencil.svg",
             IconName::Person => "icons/person.svg",
+            IconName::Pin => "icons/pin.svg",
             IconName::Play => "icons/play.svg",
             IconName::Plus => "icons/plus.svg",
+            IconName::PreserveCase => "icons/preserve_case.svg",
             IconName::Public => "icons/public.svg",
             IconName::PullRequest => "icons/pull_request.svg",
             IconName::Quote => "icons/quote.svg",
@@ -0,0 +1,368 @@
+use collections::HashMap;
+use editor::{actions::OpenUndoHistory, Editor, EditorMode};
+use gpui::{
+    rems, AppContext, DismissEvent, EventEmitter, FocusHandle, FocusableView, Render, Task, View,
+    ViewContext, VisualContext, WeakView, WindowContext,
+};
+use language::{TransactionId, UndoHistoryNode};
+use picker::{Picker, PickerDelegate};
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use ui::{prelude::*, ListItem, ListItemSpacing};
+use util::ResultExt;
+use workspace::{DismissDecision, ModalView};
+
+const PREVIEW_LEN: usize = 48;
+
+pub fn init(cx: &mut AppContext) {
+    cx.observe_new_views(UndoHistoryView::register).detach();
+}
+
+pub fn toggle(editor: View<Editor>, _: &OpenUndoHistory, cx: &mut WindowContext) {
+    let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let nodes = buffer.read(cx).undo_history();
+    if let Some(workspace) = editor.read(cx).workspace() {
+        workspace.update(cx, |workspace, cx| {
+            workspace.toggle_modal(cx, |cx| UndoHistoryView::new(nodes, editor, cx));
+        })
+    }
+}
+
+pub struct UndoHistoryView {
+    picker: View<Picker<UndoHistoryViewDelegate>>,
+}
+
+impl FocusableView for UndoHistoryView {
+    fn focus_handle(&self, cx: &AppContext) -> FocusHandle {
+        self.picker.focus_handle(cx)
+    }
+}
+
+impl EventEmitter<DismissEvent> for UndoHistoryView {}
+impl ModalView for UndoHistoryView {
+    fn on_before_dismiss(&mut self, cx: &mut ViewContext<Self>) -> DismissDecision {
+        self.picker
+            .update(cx, |picker, cx| picker.delegate.restore_active_editor(cx));
+        DismissDecision::Dismiss(true)
+    }
+}
+
+impl Render for UndoHistoryView {
+    fn render(&mut self, _cx: &mut ViewContext<Self>) -> impl IntoElement {
+        v_flex().w(rems(34.)).child(self.picker.clone())
+    }
+}
+
+impl UndoHistoryView {
+    fn register(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+        if editor.mode() == EditorMode::Full {
+            let handle = cx.view().downgrade();
+            editor.register_action(move |action, cx| {
+                if let Some(editor) = handle.upgrade() {
+                    toggle(editor, action, cx);
+                }
+            });
+        }
+    }
+
+    fn new(
+        nodes: Vec<UndoHistoryNode>,
+        editor: View<Editor>,
+        cx: &mut ViewContext<Self>,
+    ) -> UndoHistoryView {
+        let delegate = UndoHistoryViewDelegate::new(cx.view().downgrade(), nodes, editor);
+        let picker =
+            cx.new_view(|cx| Picker::uniform_list(delegate, cx).max_height(Some(vh(0.75, cx))));
+        UndoHistoryView { picker }
+    }
+}
+
+/// A state of the buffer that can be restored, listed in tree order.
+#[derive(Debug)]
+struct UndoHistoryEntry {
+    /// The transaction producing this state, or `None` for the initial state of the buffer.
+    transaction_id: Option<TransactionId>,
+    depth: usize,
+    preview: SharedString,
+    last_edit_at: Option<Instant>,
+}
+
+struct UndoHistoryViewDelegate {
+    undo_history_view: WeakView<UndoHistoryView>,
+    active_editor: View<Editor>,
+    entries: Vec<UndoHistoryEntry>,
+    matches: Vec<usize>,
+    selected_match_index: usize,
+    original_state: Option<TransactionId>,
+    previewed_state: Option<TransactionId>,
+    confirmed: bool,
+}
+
+impl UndoHistoryViewDelegate {
+    fn new(
+        undo_history_view: WeakView<UndoHistoryView>,
+        nodes: Vec<UndoHistoryNode>,
+        editor: View<Editor>,
+    ) -> Self {
+        let original_state = current_state(&nodes);
+        let entries = entries_for_nodes(nodes);
+        Self {
+            undo_history_view,
+            active_editor: editor,
+            matches: (0..entries.len()).collect(),
+            entries,
+            selected_match_index: 0,
+            original_state,
+            previewed_state: original_state,
+            confirmed: false,
+        }
+    }
+
+    fn restore_active_editor(&mut self, cx: &mut WindowContext) {
+        if !self.confirmed {
+            self.preview(self.original_state, cx);
+        }
+    }
+
+    fn preview(&mut self, state: Option<TransactionId>, cx: &mut WindowContext) {
+        if self.previewed_state != state {
+            self.previewed_state = state;
+            self.active_editor.update(cx, |editor, cx| {
+                editor.restore_undo_history(state, cx);
+            });
+        }
+    }
+}
+
+impl PickerDelegate for UndoHistoryViewDelegate {
+    type ListItem = ListItem;
+
+    fn placeholder_text(&self, _cx: &mut WindowContext) -> Arc<str> {
+        "Search undo history...".into()
+    }
+
+    fn match_count(&self) -> usize {
+        self.matches.len()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.selected_match_index
+    }
+
+    fn set_selected_index(&mut self, ix: usize, cx: &mut ViewContext<Picker<Self>>) {
+        self.selected_match_index = ix;
+        if let Some(&entry_ix) = self.matches.get(ix) {
+            self.preview(self.entries[entry_ix].transaction_id, cx);
+        }
+    }
+
+    fn update_matches(&mut self, query: String, _: &mut ViewContext<Picker<Self>>) -> Task<()> {
+        let query = query.to_lowercase();
+        self.matches = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| query.is_empty() || entry.preview.to_lowercase().contains(&query))
+            .map(|(ix, _)| ix)
+            .collect();
+        self.selected_match_index = self
+            .matches
+            .iter()
+            .position(|&ix| self.entries[ix].transaction_id == self.previewed_state)
+            .unwrap_or(0);
+        Task::ready(())
+    }
+
+    fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
+        self.confirmed = true;
+        self.active_editor.update(cx, |editor, cx| editor.focus(cx));
+        self.dismissed(cx);
+    }
+
+    fn dismissed(&mut self, cx: &mut ViewContext<Picker<Self>>) {
+        self.undo_history_view
+            .update(cx, |_, cx| cx.emit(DismissEvent))
+            .log_err();
+        self.restore_active_editor(cx);
+    }
+
+    fn render_match(
+        &self,
+        ix: usize,
+        selected: bool,
+        _: &mut ViewContext<Picker<Self>>,
+    ) -> Option<Self::ListItem> {
+        let entry = &self.entries[*self.matches.get(ix)?];
+        let is_original = entry.transaction_id == self.original_state;
+        let age = entry
+            .last_edit_at
+            .map(|last_edit_at| format_age(last_edit_at.elapsed()));
+
+        Some(
+            ListItem::new(ix)
+                .inset(true)
+                .spacing(ListItemSpacing::Sparse)
+                .selected(selected)
+                .start_slot(
+                    Icon::new(if is_original {
+                        IconName::Check
+                    } else {
+                        IconName::HistoryRerun
+                    })
+                    .color(if is_original {
+                        Color::Accent
+                    } else {
+                        Color::Muted
+                    })
+                    .size(IconSize::Small),
+                )
+                .child(
+                    h_flex()
+                        .w_full()
+                        .pl(rems(entry.depth as f32 * 0.75))
+                        .justify_between()
+                        .gap_2()
+                        .child(Label::new(entry.preview.clone()).single_line())
+                        .children(
+                            age.map(|age| {
+                                Label::new(age).size(LabelSize::Small).color(Color::Muted)
+                            }),
+                        ),
+                ),
+        )
+    }
+}
+
+/// The transaction producing the current state of the buffer, which is the last applied one.
+fn current_state(nodes: &[UndoHistoryNode]) -> Option<TransactionId> {
+    nodes
+        .iter()
+        .rev()
+        .find(|node| node.applied)
+        .map(|node| node.transaction_id)
+}
+
+/// Flattens the undo tree depth-first, starting from the initial state and visiting the
+/// children of every state in the order they were made.
+fn entries_for_nodes(nodes: Vec<UndoHistoryNode>) -> Vec<UndoHistoryEntry> {
+    let mut children = HashMap::<Option<TransactionId>, Vec<UndoHistoryNode>>::default();
+    for node in nodes {
+        children.entry(node.parent).or_default().push(node);
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|node| std::cmp::Reverse(node.last_edit_at));
+    }
+
+    let mut entries = vec![UndoHistoryEntry {
+        transaction_id: None,
+        depth: 0,
+        preview: "Initial state".into(),
+        last_edit_at: None,
+    }];
+    // Siblings are sorted newest first, so popping them off the stack visits the oldest first.
+    let mut stack = children
+        .remove(&None)
+        .unwrap_or_default()
+        .into_iter()
+        .map(|node| (node, 1))
+        .collect::<Vec<_>>();
+    while let Some((node, depth)) = stack.pop() {
+        if let Some(siblings) = children.remove(&Some(node.transaction_id)) {
+            stack.extend(siblings.into_iter().map(|child| (child, depth + 1)));
+        }
+        entries.push(UndoHistoryEntry {
+            transaction_id: Some(node.transaction_id),
+            depth,
+            preview: preview_text(&node.inserted_text).into(),
+            last_edit_at: Some(node.last_edit_at),
+        });
+    }
+    entries
+}
+
+fn preview_text(inserted_text: &str) -> String {
+    if inserted_text.trim().is_empty() {
+        return if inserted_text.is_empty() {
+            "Deletion".to_string()
+        } else {
+            "Whitespace".to_string()
+        };
+    }
+
+    let mut preview = String::new();
+    for (ix, c) in inserted_text.trim().chars().enumerate() {
+        if ix == PREVIEW_LEN {
+            preview.push('…');
+            break;
+        }
+        match c {
+            '\n' => preview.push('⏎'),
+            '\t' => preview.push(' '),
+            c => preview.push(c),
+        }
+    }
+    preview
+}
+
+fn format_age(age: Duration) -> String {
+    let seconds = age.as_secs();
+    if seconds < 5 {
+        "just now".to_string()
+    } else if seconds < 60 {
+        format!("{seconds}s ago")
+    } else if seconds < 60 * 60 {
+        format!("{}m ago", seconds / 60)
+    } else if seconds < 60 * 60 * 24 {
+        format!("{}h ago", seconds / (60 * 60))
+    } else {
+        format!("{}d ago", seconds / (60 * 60 * 24))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use clock::Lamport;
+
+    #[test]
+    fn test_entries_for_nodes() {
+        let start = Instant::now();
+        let id = |value| Lamport {
+            replica_id: 0,
+            value,
+        };
+        let node = |value, parent, applied, inserted_text: &str| UndoHistoryNode {
+            transaction_id: id(value),
+            parent,
+            applied,
+            last_edit_at: start + Duration::from_secs(value as u64),
+            inserted_text: inserted_text.to_string(),
+        };
+
+        // Transaction 2 was undone and replaced by transaction 3, which was then undone too.
+        let nodes = vec![
+            node(1, None, true, "fn main() {\n}"),
+            node(3, Some(id(1)), false, "  "),
+            node(2, Some(id(1)), false, ""),
+        ];
+        assert_eq!(current_state(&nodes), Some(id(1)));
+
+        let entries = entries_for_nodes(nodes)
+            .into_iter()
+            .map(|entry| (entry.transaction_id, entry.depth, entry.preview))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            entries,
+            [
+                (None, 0, SharedString::from("Initial state")),
+                (Some(id(1)), 1, SharedString::from("fn main() {⏎}")),
+                (Some(id(2)), 2, SharedString::from("Deletion")),
+                (Some(id(3)), 2, SharedString::from("Whitespace")),
+            ]
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::{Context, Result};
+use collections::HashMap;
 use fuzzy::{StringMatch, StringMatchCandidate};
-use git::repository::Branch;
+use git::repository::{Branch, GitRepository};
 use gpui::{
     actions, rems, AnyElement, AppContext, DismissEvent, Element, EventEmitter, FocusHandle,
     FocusableView, InteractiveElement, IntoElement, ParentElement, Render, SharedString, Styled,
@@ -9,8 +10,9 @@ use gpui::{
 use picker::{Picker, PickerDelegate};
 use std::{ops::Not, sync::Arc};
 use ui::{
-    h_flex, v_flex, Button, ButtonCommon, Clickable, Color, HighlightedLabel, Label, LabelCommon,
-    LabelSize, ListItem, ListItemSpacing, Selectable,
+    h_flex, v_flex, Button, ButtonCommon, Clickable, Color, FluentBuilder, HighlightedLabel,
+    IconButton, IconName, IconSize, Label, LabelCommon, LabelSize, ListItem, ListItemSpacing,
+    Selectable, Tooltip,
 };
 use util::ResultExt;
 use workspace::notifications::NotificationId;
@@ -80,6 +82,13 @@ impl Render for BranchList {
 pub struct BranchListDelegate {
     matches: Vec<StringMatch>,
     all_branches: Vec<Branch>,
+    /// The branches the matches were computed from, indexed by their candidate ids.
+    candidates: Vec<Branch>,
+    /// The branch being renamed to the query, if any.
+    rename_target: Option<Box<str>>,
+    /// The number of commits the local branches are ahead and behind of their upstream, loaded
+    /// in the background once the branches are listed.
+    upstream_ahead_behind: HashMap<Box<str>, (usize, usize)>,
     workspace: View<Workspace>,
     selected_index: usize,
     last_query: String,
@@ -95,21 +104,84 @@ impl BranchListDelegate {
         cx: &AppContext,
     ) -> Result<Self> {
         let project = workspace.project().read(&cx);
-        let repo = project
-            .get_first_worktree_root_repo(cx)
-            .context("failed to get root repository for first worktree")?;
+        let (_, repo) = project
+            .active_git_repository(cx)
+            .context("failed to get the repository of the active file")?;
 
         let all_branches = repo.branches()?;
         Ok(Self {
             matches: vec![],
             workspace: handle,
             all_branches,
+            candidates: Vec::new(),
+            rename_target: None,
+            upstream_ahead_behind: HashMap::default(),
             selected_index: 0,
             last_query: Default::default(),
             branch_name_trailoff_after,
         })
     }
 
+    /// Returns the repository containing the active file, which may be a nested one or a
+    /// submodule, or the repository at the root of the first worktree.
+    fn repository(&self, cx: &AppContext) -> Result<Arc<dyn GitRepository>> {
+        let (_, repo) = self
+            .workspace
+            .read(cx)
+            .project()
+            .read(cx)
+            .active_git_repository(cx)
+            .context("failed to get the repository of the active file")?;
+        Ok(repo)
+    }
+
+    fn selected_branch(&self) -> Option<&Branch> {
+        let hit = self.matches.get(self.selected_index)?;
+        self.candidates.get(hit.candidate_id)
+    }
+
+    /// Runs a branch operation, then lists the branches again, showing a toast when it fails.
+    fn update_branches(
+        &mut self,
+        error_message: String,
+        operation: impl FnOnce(&dyn GitRepository) -> Result<()>,
+        cx: &mut ViewContext<Picker<Self>>,
+    ) {
+        let result = self.repository(cx).and_then(|repo| {
+            operation(repo.as_ref())?;
+            self.all_branches = repo.branches()?;
+            self.upstream_ahead_behind.clear();
+            Ok(())
+        });
+        if let Err(error) = result {
+            self.display_error_toast(format!("{error_message}: {error}"), cx);
+        }
+        cx.defer(|picker, cx| picker.refresh(cx));
+    }
+
+    fn start_renaming(&mut self, name: Box<str>, cx: &mut ViewContext<Picker<Self>>) {
+        let query = name.to_string();
+        self.rename_target = Some(name);
+        cx.defer(move |picker, cx| picker.set_query(query.as_str(), cx));
+    }
+
+    /// Creates a branch named after the query from the given revision, or from HEAD, and
+    /// checks it out.
+    fn create_branch(&mut self, start_point: Option<&str>, cx: &mut ViewContext<Picker<Self>>) {
+        let name = self.last_query.trim().to_string();
+        let result = self.repository(cx).and_then(|repo| {
+            repo.create_branch(&name, start_point)
+                .with_context(|| format!("Failed to create branch '{name}'"))?;
+            repo.change_branch(&name).with_context(|| {
+                format!("Failed to check branch '{name}', check for conflicts or unstashed files")
+            })
+        });
+        match result {
+            Ok(()) => cx.emit(DismissEvent),
+            Err(error) => self.display_error_toast(format!("{error:#}"), cx),
+        }
+    }
+
     fn display_error_toast(&self, message: String, cx: &mut WindowContext<'_>) {
         self.workspace.update(cx, |model, ctx| {
             struct GitCheckoutFailure;
@@ -143,8 +215,13 @@ impl PickerDelegate for BranchListDelegate {
         cx.spawn(move |picker, mut cx| async move {
             let candidates = picker.update(&mut cx, |view, _| {
                 const RECENT_BRANCHES_COUNT: usize = 10;
+                if view.delegate.rename_target.is_some() {
+                    return Vec::new();
+                }
                 let mut branches = view.delegate.all_branches.clone();
                 if query.is_empty() {
+                    // Remote branches are only listed when searching.
+                    branches.retain(|branch| !branch.is_remote);
                     if branches.len() > RECENT_BRANCHES_COUNT {
                         // Truncate list of recent branches
                         // Do a partial sort to show recent-ish branches first.
@@ -159,13 +236,15 @@ impl PickerDelegate for BranchListDelegate {
                         rhs.is_head.cmp(&lhs.is_head).then(lhs.name.cmp(&rhs.name))
                     });
                 }
-                branches
-                    .into_iter()
+                view.delegate.candidates = branches;
+                view.delegate
+                    .candidates
+                    .iter()
                     .enumerate()
-                    .map(|(ix, command)| StringMatchCandidate {
+                    .map(|(ix, branch)| StringMatchCandidate {
                         id: ix,
-                        char_bag: command.name.chars().collect(),
-                        string: command.name.into(),
+                        char_bag: branch.name.chars().collect(),
+                        string: branch.name.to_string(),
                     })
                     .collect::<Vec<StringMatchCandidate>>()
             });
@@ -207,29 +286,79 @@ impl PickerDelegate for BranchListDelegate {
                     delegate.last_query = query;
                 })
                 .log_err();
+
+            let branches_to_compare = picker.update(&mut cx, |picker, cx| {
+                let delegate = &picker.delegate;
+                let branches = delegate
+                    .matches
+                    .iter()
+                    .filter_map(|hit| {
+                        let branch = delegate.candidates.get(hit.candidate_id)?;
+                        let loaded = delegate.upstream_ahead_behind.contains_key(&branch.name);
+                        (branch.upstream.is_some() && !loaded).then(|| branch.name.clone())
+                    })
+                    .collect::<Vec<_>>();
+                anyhow::Ok((delegate.repository(cx)?, branches))
+            });
+            let Some((repo, branches)) = branches_to_compare.ok().and_then(Result::ok) else {
+                return;
+            };
+            if branches.is_empty() {
+                return;
+            }
+            let upstream_ahead_behind = cx
+                .background_executor()
+                .spawn(async move {
+                    branches
+                        .into_iter()
+                        .filter_map(|name| {
+                            let counts = repo.upstream_ahead_behind(&name).log_err()??;
+                            Some((name, counts))
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .await;
+            picker
+                .update(&mut cx, |picker, cx| {
+                    picker
+                        .delegate
+                        .upstream_ahead_behind
+                        .extend(upstream_ahead_behind);
+                    cx.notify();
+                })
+                .log_err();
         })
     }
 
     fn confirm(&mut self, _: bool, cx: &mut ViewContext<Picker<Self>>) {
-        let current_pick = self.selected_index();
-        let Some(current_pick) = self
-            .matches
-            .get(current_pick)
-            .map(|pick| pick.string.clone())
-        else {
+        if let Some(name) = self.rename_target.take() {
+            let new_name = self.last_query.trim().to_string();
+            if !new_name.is_empty() && *new_name != *name {
+                self.update_branches(
+                    format!("Failed to rename branch '{name}'"),
+                    |repo| repo.rename_branch(&name, &new_name),
+                    cx,
+                );
+            }
+            cx.defer(|picker, cx| picker.set_query("", cx));
+            return;
+        }
+
+        let Some(branch) = self.selected_branch().cloned() else {
             return;
         };
         cx.spawn(|picker, mut cx| async move {
             picker
                 .update(&mut cx, |this, cx| {
-                    let project = this.delegate.workspace.read(cx).project().read(cx);
-                    let repo = project
-                        .get_first_worktree_root_repo(cx)
-                        .context("failed to get root repository for first worktree")?;
-                    let status = repo
-                        .change_branch(&current_pick);
+                    let repo = this.delegate.repository(cx)?;
+                    let status = if branch.is_remote {
+                        repo.checkout_remote_branch(&branch.name).map(|_| ())
+                    } else {
+                        repo.change_branch(&branch.name)
+                    };
                     if status.is_err() {
-                        this.delegate.display_error_toast(format!("Failed to checkout branch '{current_pick}', check for conflicts or unstashed files"), cx);
+                        let name = &branch.name;
+                        this.delegate.display_error_toast(format!("Failed to checkout branch '{name}', check for conflicts or unstashed files"), cx);
                         status?;
                     }
                     cx.emit(DismissEvent);
@@ -249,9 +378,10 @@ impl PickerDelegate for BranchListDelegate {
         &self,
         ix: usize,
         selected: bool,
-        _cx: &mut ViewContext<Picker<Self>>,
+        cx: &mut ViewContext<Picker<Self>>,
     ) -> Option<Self::ListItem> {
         let hit = &self.matches[ix];
+        let branch = self.candidates.get(hit.candidate_id)?;
         let shortened_branch_name =
             util::truncate_and_trailoff(&hit.string, self.branch_name_trailoff_after);
         let highlights: Vec<_> = hit
@@ -260,16 +390,87 @@ impl PickerDelegate for BranchListDelegate {
             .filter(|index| index < &&self.branch_name_trailoff_after)
             .copied()
             .collect();
-        Some(
-            ListItem::new(SharedString::from(format!("vcs-menu-{ix}")))
-                .inset(true)
-                .spacing(ListItemSpacing::Sparse)
-                .selected(selected)
-                .start_slot(HighlightedLabel::new(shortened_branch_name, highlights)),
-        )
+        let upstream_ahead_behind = self.upstream_ahead_behind.get(&branch.name).copied();
+        let ahead_behind = upstream_ahead_behind.and_then(|(ahead, behind)| {
+            let mut counts = Vec::new();
+            if ahead > 0 {
+                counts.push(format!("↑{ahead}"));
+            }
+            if behind > 0 {
+                counts.push(format!("↓{behind}"));
+            }
+            counts.is_empty().not().then(|| {
+                Label::new(counts.join(" "))
+                    .size(LabelSize::Small)
+                    .color(Color::Muted)
+            })
+        });
+        let remote_label = branch.is_remote.then(|| {
+            Label::new("remote")
+                .size(LabelSize::Small)
+                .color(Color::Muted)
+        });
+
+        let mut item = ListItem::new(SharedString::from(format!("vcs-menu-{ix}")))
+            .inset(true)
+            .spacing(ListItemSpacing::Sparse)
+            .selected(selected)
+            .start_slot(
+                h_flex()
+                    .gap_2()
+                    .child(HighlightedLabel::new(shortened_branch_name, highlights))
+                    .children(remote_label)
+                    .children(ahead_behind),
+            );
+        if !branch.is_remote {
+            let name = branch.name.clone();
+            let buttons = h_flex()
+                .gap_1()
+                .child(
+                    IconButton::new(("rename-branch", ix), IconName::Pencil)
+                        .icon_size(IconSize::Small)
+                        .tooltip(|cx| Tooltip::text("Rename Branch", cx))
+                        .on_click(cx.listener({
+                            let name = name.clone();
+                            move |picker, _, cx| {
+                                cx.stop_propagation();
+                                cx.prevent_default();
+                                picker.delegate.start_renaming(name.clone(), cx);
+                            }
+                        })),
+                )
+                .when(!branch.is_head, |buttons| {
+                    buttons.child(
+                        IconButton::new(("delete-branch", ix), IconName::Trash)
+                            .icon_size(IconSize::Small)
+                            .tooltip(|cx| Tooltip::text("Delete Branch", cx))
+                            .on_click(cx.listener(move |picker, _, cx| {
+                                cx.stop_propagation();
+                                cx.prevent_default();
+                                let name = name.clone();
+                                picker.delegate.update_branches(
+                                    format!("Failed to delete branch '{name}'"),
+                                    |repo| repo.delete_branch(&name),
+                                    cx,
+                                );
+                            })),
+                    )
+                })
+                .into_any_element();
+            item = if selected {
+                item.end_slot::<AnyElement>(buttons)
+            } else {
+                item.end_hover_slot::<AnyElement>(buttons)
+            };
+        }
+        Some(item)
     }
     fn render_header(&self, _: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
-        let label = if self.last_query.is_empty() {
+        let label = if let Some(name) = &self.rename_target {
+            h_flex()
+                .ml_3()
+                .child(Label::new(format!("Type the new name of '{name}'")).size(LabelSize::Small))
+        } else if self.last_query.is_empty() {
             h_flex()
                 .ml_3()
                 .child(Label::new("Recent Branches").size(LabelSize::Small))
@@ -289,39 +490,190 @@ impl PickerDelegate for BranchListDelegate {
         Some(label.mt_1().into_any())
     }
     fn render_footer(&self, cx: &mut ViewContext<Picker<Self>>) -> Option<AnyElement> {
-        if self.last_query.is_empty() {
+        if self.last_query.is_empty() || self.rename_target.is_some() {
             return None;
         }
 
+        let start_point = self.selected_branch().map(|branch| branch.name.clone());
         Some(
-            h_flex().mr_3().pb_2().child(h_flex().w_full()).child(
-            Button::new("branch-picker-create-branch-button", "Create branch").on_click(
-                cx.listener(|_, _, cx| {
-                    cx.spawn(|picker, mut cx| async move {
-                                        picker.update(&mut cx, |this, cx| {
-                                            let project = this.delegate.workspace.read(cx).project().read(cx);
-                                            let current_pick = &this.delegate.last_query;
-                                            let repo = project
-                                                .get_first_worktree_root_repo(cx)
-                                                .context("failed to get root repository for first worktree")?;
-                                            let status = repo
-                                                .create_branch(&current_pick);
-                                            if status.is_err() {
-                                                this.delegate.display_error_toast(format!("Failed to create branch '{current_pick}', check for conflicts or unstashed files"), cx);
-                                                status?;
-                                            }
-                                            let status = repo.change_branch(&current_pick);
-                                            if status.is_err() {
-                                                this.delegate.display_error_toast(format!("Failed to check branch '{current_pick}', check for conflicts or unstashed files"), cx);
-                                                status?;
-                                            }
-                                            this.cancel(&Default::default(), cx);
-                                            Ok::<(), anyhow::Error>(())
-                                })
-
-                    }).detach_and_log_err(cx);
-                }),
-            ).style(ui::ButtonStyle::Filled)).into_any_element(),
+            h_flex()
+                .mr_3()
+                .pb_2()
+                .gap_2()
+                .child(h_flex().w_full())
+                .children(start_point.map(|start_point| {
+                    Button::new(
+                        "branch-picker-create-branch-from-button",
+                        format!(
+                            "Create from {}",
+                            util::truncate_and_trailoff(&start_point, 24)
+                        ),
+                    )
+                    .on_click(cx.listener(move |picker, _, cx| {
+                        picker.delegate.create_branch(Some(&start_point), cx)
+                    }))
+                }))
+                .child(
+                    Button::new("branch-picker-create-branch-button", "Create branch")
+                        .on_click(
+                            cx.listener(|picker, _, cx| picker.delegate.create_branch(None, cx)),
+                        )
+                        .style(ui::ButtonStyle::Filled),
+                )
+                .into_any_element(),
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gpui::{TestAppContext, VisualTestContext};
+    use project::Project;
+    use serde_json::json;
+    use std::path::Path;
+    use workspace::AppState;
+
+    #[gpui::test]
+    async fn test_manage_branches(cx: &mut TestAppContext) {
+        let app_state = init_test(cx);
+        let fs = app_state.fs.as_fake();
+        fs.insert_tree("/root", json!({ ".git": {}, "a.txt": "a" }))
+            .await;
+        fs.with_git_state(Path::new("/root/.git"), false, |state| {
+            state.branch_name = Some("main".into());
+            state.branches = vec![
+                branch("feature", false, Some("origin/feature")),
+                branch("main", false, None),
+                branch("origin/feature", true, None),
+                branch("origin/fix", true, None),
+            ];
+            state.upstream_ahead_behind.insert("feature".into(), (2, 1));
+        });
+        let project = Project::test(app_state.fs.clone(), ["/root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project, cx));
+        cx.run_until_parked();
+
+        // Remote branches are only listed when searching.
+        let picker = open_branch_list(&workspace, cx);
+        picker.update(cx, |picker, _| {
+            assert_eq!(match_names(picker), ["main", "feature"]);
+            assert_eq!(
+                picker.delegate.upstream_ahead_behind.get("feature"),
+                Some(&(2, 1))
+            );
+        });
+
+        // Rename a branch to the query.
+        picker.update(cx, |picker, cx| {
+            picker.delegate.start_renaming("feature".into(), cx)
+        });
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| {
+            assert_eq!(picker.query(cx), "feature");
+            picker.set_query("topic", cx);
+        });
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| picker.delegate.confirm(false, cx));
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            assert_eq!(picker.delegate.rename_target, None);
+            assert_eq!(match_names(picker), ["main", "topic"]);
+        });
+
+        // Delete it, which the checked out branch can't be.
+        picker.update(cx, |picker, cx| {
+            picker.delegate.update_branches(
+                "Failed to delete branch".into(),
+                |repo| repo.delete_branch("main"),
+                cx,
+            );
+            picker.delegate.update_branches(
+                "Failed to delete branch".into(),
+                |repo| repo.delete_branch("topic"),
+                cx,
+            );
+        });
+        cx.run_until_parked();
+        picker.update(cx, |picker, _| {
+            assert_eq!(match_names(picker), ["main"]);
+        });
+
+        // Confirming a remote branch checks out a local branch tracking it.
+        picker.update(cx, |picker, cx| picker.set_query("fix", cx));
+        cx.run_until_parked();
+        picker.update(cx, |picker, cx| {
+            assert_eq!(match_names(picker), ["origin/fix"]);
+            picker.delegate.confirm(false, cx);
+        });
+        cx.run_until_parked();
+        fs.with_git_state(Path::new("/root/.git"), false, |state| {
+            assert_eq!(state.branch_name.as_deref(), Some("fix"));
+            let names = state
+                .branches
+                .iter()
+                .map(|branch| (branch.name.as_ref(), branch.upstream.as_deref()))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                names,
+                [
+                    ("main", None),
+                    ("origin/feature", None),
+                    ("origin/fix", None),
+                    ("fix", Some("origin/fix")),
+                ]
+            );
+        });
+        workspace.update(cx, |workspace, cx| {
+            assert!(workspace.active_modal::<BranchList>(cx).is_none());
+        });
+    }
+
+    fn branch(name: &str, is_remote: bool, upstream: Option<&str>) -> Branch {
+        Branch {
+            is_head: false,
+            name: name.into(),
+            is_remote,
+            unix_timestamp: None,
+            upstream: upstream.map(Into::into),
+        }
+    }
+
+    fn open_branch_list(
+        workspace: &View<Workspace>,
+        cx: &mut VisualTestContext,
+    ) -> View<Picker<BranchListDelegate>> {
+        cx.dispatch_action(OpenRecent);
+        cx.run_until_parked();
+        workspace.update(cx, |workspace, cx| {
+            workspace
+                .active_modal::<BranchList>(cx)
+                .expect("branch list is not open")
+                .read(cx)
+                .picker
+                .clone()
+        })
+    }
+
+    fn match_names(picker: &Picker<BranchListDelegate>) -> Vec<&str> {
+        picker
+            .delegate
+            .matches
+            .iter()
+            .map(|hit| hit.string.as_str())
+            .collect()
+    }
+
+    fn init_test(cx: &mut TestAppContext) -> Arc<AppState> {
+        cx.update(|cx| {
+            let state = AppState::test(cx);
+            theme::init(theme::LoadThemes::JustBase, cx);
+            language::init(cx);
+            super::init(cx);
+            editor::init(cx);
+            workspace::init_settings(cx);
+            Project::init_settings(cx);
+            state
+        })
+    }
+}
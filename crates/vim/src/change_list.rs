@@ -1,13 +1,23 @@
-use editor::{display_map::ToDisplayPoint, movement, scroll::Autoscroll, Bias, Direction, Editor};
+use std::{path::Path, sync::Arc};
+
+use collections::HashMap;
+use editor::{
+    display_map::ToDisplayPoint, movement, scroll::Autoscroll, Bias, Direction, Editor, ToPoint,
+};
 use gpui::{actions, View};
+use language::Point;
 use ui::{ViewContext, WindowContext};
+use util::ResultExt;
 use workspace::Workspace;
 
-use crate::{state::Mode, Vim};
+use crate::{normal::mark::editor_path, persistence::VIM_DB, state::Mode, Vim};
 
 actions!(vim, [ChangeListOlder, ChangeListNewer]);
 
-pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+/// The number of changes kept for each file across restarts, as in vim.
+const CHANGE_LIST_LIMIT: usize = 100;
+
+pub(crate) fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
     workspace.register_action(|_, _: &ChangeListOlder, cx| {
         Vim::update(cx, |vim, cx| {
             move_to_change(vim, Direction::Prev, cx);
@@ -18,6 +28,34 @@ pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>
             move_to_change(vim, Direction::Next, cx);
         })
     });
+
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    cx.spawn(|_, mut cx| async move {
+        let changes = cx
+            .background_executor()
+            .spawn(async move { VIM_DB.get_changes(workspace_id) })
+            .await?;
+        cx.update(|cx| {
+            Vim::update(cx, |vim, _| {
+                let mut change_lists = HashMap::<Arc<Path>, Vec<Point>>::default();
+                for (path, row, column) in changes {
+                    change_lists
+                        .entry(path.into())
+                        .or_default()
+                        .push(Point::new(row, column));
+                }
+                for (path, points) in change_lists {
+                    vim.workspace_state
+                        .file_change_lists
+                        .entry(path)
+                        .or_insert(points);
+                }
+            })
+        })
+    })
+    .detach_and_log_err(cx);
 }
 
 fn move_to_change(vim: &mut Vim, direction: Direction, cx: &mut WindowContext) {
@@ -87,7 +125,81 @@ pub(crate) fn push_to_change_list(vim: &mut Vim, editor: View<Editor>, cx: &mut
             state.change_list.pop();
         }
         state.change_list.push(new_positions);
-    })
+    });
+    keep_change_list(vim, &editor, cx);
+}
+
+/// Records the changelist by the path of the editor's file, and stores it in the database
+/// when it has moved to other lines.
+fn keep_change_list(vim: &mut Vim, editor: &View<Editor>, cx: &mut WindowContext) {
+    let editor = editor.read(cx);
+    let Some(path) = editor_path(editor, cx) else {
+        return;
+    };
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let change_list = &vim.state().change_list;
+    let points = change_list[change_list.len().saturating_sub(CHANGE_LIST_LIMIT)..]
+        .iter()
+        .filter_map(|anchors| Some(anchors.first()?.to_point(&snapshot)))
+        .collect::<Vec<_>>();
+    let workspace_id = editor
+        .workspace()
+        .and_then(|workspace| workspace.read(cx).database_id());
+
+    let moved_lines = vim
+        .workspace_state
+        .file_change_lists
+        .get(&path)
+        .map_or(true, |old| {
+            !old.iter()
+                .map(|point| point.row)
+                .eq(points.iter().map(|point| point.row))
+        });
+    vim.workspace_state
+        .file_change_lists
+        .insert(path.clone(), points.clone());
+
+    if let Some(workspace_id) = workspace_id.filter(|_| moved_lines) {
+        let changes = points
+            .into_iter()
+            .map(|point| (point.row, point.column))
+            .collect();
+        cx.background_executor()
+            .spawn(async move {
+                VIM_DB
+                    .save_changes(workspace_id, path.to_path_buf(), changes)
+                    .await
+                    .log_err()
+            })
+            .detach();
+    }
+}
+
+/// Sets the changelist of the active editor's file in the editor, unless it has one already.
+pub(crate) fn restore_change_list(vim: &mut Vim, cx: &mut WindowContext) {
+    if !vim.state().change_list.is_empty() {
+        return;
+    }
+    let Some(change_list) = vim
+        .update_active_editor(cx, |vim, editor, cx| {
+            let path = editor_path(editor, cx)?;
+            let points = vim.workspace_state.file_change_lists.get(&path)?;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let change_list = points
+                .iter()
+                .map(|point| {
+                    let point = snapshot.clip_point(*point, Bias::Left);
+                    vec![snapshot.anchor_before(point)]
+                })
+                .collect::<Vec<_>>();
+            Some(change_list)
+        })
+        .flatten()
+    else {
+        return;
+    };
+
+    vim.update_state(|state| state.change_list = change_list);
 }
 
 #[cfg(test)]
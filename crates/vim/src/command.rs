@@ -1,14 +1,28 @@
+use std::path::{Path, PathBuf};
+
 use command_palette_hooks::CommandInterceptResult;
-use editor::actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive};
-use gpui::{impl_actions, Action, AppContext, ViewContext};
+use editor::{
+    actions::{SortLinesCaseInsensitive, SortLinesCaseSensitive},
+    Editor,
+};
+use fs::Fs as _;
+use gpui::{actions, impl_actions, Action, AppContext, ViewContext, VisualContext};
+use language::language_settings::SoftWrap;
 use serde_derive::Deserialize;
-use workspace::{SaveIntent, Workspace};
+use util::truncate_and_trailoff;
+use workspace::{
+    item::ItemHandle,
+    notifications::{simple_message_notification::MessageNotification, NotificationId},
+    SaveIntent, Workspace,
+};
 
 use crate::{
+    global::{is_global_command, is_normal_command, GlobalCommand, NormalCommand},
+    jump_list,
     motion::{EndOfDocument, Motion, StartOfDocument},
     normal::{
         move_cursor,
-        search::{range_regex, FindCommand, ReplaceCommand},
+        search::{is_substitute_command, FindCommand, ReplaceCommand},
         JoinLines,
     },
     state::Mode,
@@ -20,15 +34,274 @@ pub struct GoToLine {
     pub line: u32,
 }
 
-impl_actions!(vim, [GoToLine]);
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct EditFile {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct WriteFile {
+    pub path: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SwitchToBuffer {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SetOption {
+    pub option: String,
+}
+
+impl_actions!(
+    vim,
+    [GoToLine, EditFile, WriteFile, SwitchToBuffer, SetOption]
+);
+
+/// The full names of the commands offered when completing the command line.
+const COMMAND_NAMES: &[&str] = &[
+    "Explore",
+    "buffer",
+    "cc",
+    "clist",
+    "cnext",
+    "cprevious",
+    "delete",
+    "edit",
+    "exit",
+    "global",
+    "join",
+    "ll",
+    "lnext",
+    "lprevious",
+    "new",
+    "normal",
+    "only",
+    "quit",
+    "quitall",
+    "registers",
+    "set",
+    "sort",
+    "split",
+    "substitute",
+    "tabclose",
+    "tabedit",
+    "tabnew",
+    "tabnext",
+    "tabonly",
+    "tabprevious",
+    "update",
+    "vglobal",
+    "vnew",
+    "vsplit",
+    "wall",
+    "wq",
+    "wqall",
+    "write",
+    "xall",
+];
+
+const EDIT_NAMES: &[&str] = &["e", "ed", "edi", "edit"];
+const WRITE_NAMES: &[&str] = &["w", "wr", "wri", "writ", "write"];
+const BUFFER_NAMES: &[&str] = &["b", "bu", "buf", "buff", "buffe", "buffer"];
+const SET_NAMES: &[&str] = &["se", "set"];
+const OPTIONS: &[&str] = &["nonumber", "nowrap", "number", "wrap"];
+
+actions!(vim, [ShowRegisters]);
 
 pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     workspace.register_action(|_: &mut Workspace, action: &GoToLine, cx| {
         Vim::update(cx, |vim, cx| {
             vim.switch_mode(Mode::Normal, false, cx);
+            jump_list::record_jump(vim, None, cx);
             move_cursor(vim, Motion::StartOfDocument, Some(action.line as usize), cx);
         });
     });
+    workspace.register_action(|workspace: &mut Workspace, _: &ShowRegisters, cx| {
+        let listing = registers_listing(cx.global::<Vim>(), cx);
+        workspace.show_notification(NotificationId::unique::<ShowRegisters>(), cx, |cx| {
+            cx.new_view(|_| MessageNotification::new(listing))
+        });
+    });
+    workspace.register_action(|workspace: &mut Workspace, action: &EditFile, cx| {
+        let Some(abs_path) = resolve_path(workspace, &action.path, cx) else {
+            return;
+        };
+        workspace
+            .open_abs_path(abs_path, true, cx)
+            .detach_and_log_err(cx);
+    });
+    workspace.register_action(|workspace: &mut Workspace, action: &WriteFile, cx| {
+        let Some(abs_path) = resolve_path(workspace, &action.path, cx) else {
+            return;
+        };
+        let Some(editor) = workspace.active_item_as::<Editor>(cx) else {
+            return;
+        };
+        let Some(buffer) = editor.read(cx).buffer().read(cx).as_singleton() else {
+            return;
+        };
+        let buffer = buffer.read(cx);
+        let text = buffer.as_rope().clone();
+        let line_ending = buffer.line_ending();
+        let fs = workspace.app_state().fs.clone();
+        cx.background_executor()
+            .spawn(async move { fs.save(&abs_path, &text, line_ending).await })
+            .detach_and_log_err(cx);
+    });
+    workspace.register_action(|workspace: &mut Workspace, action: &SwitchToBuffer, cx| {
+        let item = workspace
+            .items(cx)
+            .find(|item| {
+                buffer_name(item.as_ref(), cx).is_some_and(|name| name.contains(&action.name))
+            })
+            .map(|item| item.boxed_clone());
+        if let Some(item) = item {
+            workspace.activate_item(item.as_ref(), cx);
+        }
+    });
+    workspace.register_action(|_: &mut Workspace, action: &SetOption, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| match action.option.as_str() {
+                "wrap" => editor.set_soft_wrap_mode(SoftWrap::EditorWidth, cx),
+                "nowrap" => editor.set_soft_wrap_mode(SoftWrap::None, cx),
+                "number" => editor.set_show_line_numbers(true, cx),
+                "nonumber" => editor.set_show_line_numbers(false, cx),
+                _ => {}
+            });
+        });
+    });
+}
+
+/// Resolves a path typed on the command line against the first folder of the project.
+fn resolve_path(workspace: &Workspace, path: &str, cx: &AppContext) -> Option<PathBuf> {
+    let path = Path::new(path);
+    if path.is_absolute() {
+        return Some(path.to_path_buf());
+    }
+    let worktree = workspace.visible_worktrees(cx).next()?;
+    let abs_path = worktree.read(cx).abs_path();
+    Some(abs_path.join(path))
+}
+
+/// The project-relative path of the item, used to name it with `:b`.
+fn buffer_name(item: &dyn ItemHandle, cx: &AppContext) -> Option<String> {
+    let project_path = item.project_path(cx)?;
+    Some(project_path.path.to_string_lossy().into_owned())
+}
+
+/// Splits a command with an argument, like `:e foo.rs`, if its name is one of the given ones.
+fn command_argument<'a>(query: &'a str, names: &[&str]) -> Option<&'a str> {
+    let (name, argument) = query.split_once(' ')?;
+    let argument = argument.trim();
+    (names.contains(&name) && !argument.is_empty()).then_some(argument)
+}
+
+/// Completes the command name, or the file, buffer or option given as its argument.
+pub fn command_completions(query: &str, cx: &AppContext) -> Vec<String> {
+    let command = query.trim_start_matches(':');
+    let prefix = &query[..query.len() - command.len()];
+
+    let Some((name, argument)) = command.split_once(' ') else {
+        return COMMAND_NAMES
+            .iter()
+            .filter(|name| name.starts_with(command))
+            .map(|name| format!("{prefix}{name}"))
+            .collect();
+    };
+    let argument = argument.trim_start();
+
+    let completions = if EDIT_NAMES.contains(&name) || WRITE_NAMES.contains(&name) {
+        file_completions(argument, cx)
+    } else if BUFFER_NAMES.contains(&name) {
+        buffer_completions(argument, cx)
+    } else if SET_NAMES.contains(&name) {
+        OPTIONS
+            .iter()
+            .filter(|option| option.starts_with(argument))
+            .map(|option| option.to_string())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    completions
+        .into_iter()
+        .map(|completion| format!("{prefix}{name} {completion}"))
+        .collect()
+}
+
+fn active_workspace<'a>(cx: &'a AppContext) -> Option<&'a Workspace> {
+    let editor = cx.global::<Vim>().active_editor.as_ref()?.upgrade()?;
+    let workspace = editor.read(cx).workspace()?;
+    Some(workspace.read(cx))
+}
+
+fn file_completions(argument: &str, cx: &AppContext) -> Vec<String> {
+    let Some(workspace) = active_workspace(cx) else {
+        return Vec::new();
+    };
+    let Some(worktree) = workspace.visible_worktrees(cx).next() else {
+        return Vec::new();
+    };
+    let (directory, file_name) = argument.rsplit_once('/').unwrap_or(("", argument));
+    let mut completions = worktree
+        .read(cx)
+        .child_entries(Path::new(directory))
+        .filter(|entry| {
+            entry
+                .path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with(file_name))
+        })
+        .map(|entry| {
+            let mut path = entry.path.to_string_lossy().into_owned();
+            if entry.is_dir() {
+                path.push('/');
+            }
+            path
+        })
+        .collect::<Vec<_>>();
+    completions.sort();
+    completions
+}
+
+fn buffer_completions(argument: &str, cx: &AppContext) -> Vec<String> {
+    let Some(workspace) = active_workspace(cx) else {
+        return Vec::new();
+    };
+    let mut completions = workspace
+        .items(cx)
+        .filter_map(|item| buffer_name(item.as_ref(), cx))
+        .filter(|name| name.contains(argument))
+        .collect::<Vec<_>>();
+    completions.sort();
+    completions.dedup();
+    completions
+}
+
+/// Lists the registers holding text, with the start of their text, like vim's `:registers`.
+fn registers_listing(vim: &Vim, cx: &AppContext) -> String {
+    const MAX_CHARS: usize = 60;
+    let registers = ['"']
+        .into_iter()
+        .chain('0'..='9')
+        .chain('a'..='z')
+        .chain(['+', '*'])
+        .filter_map(|register| {
+            let text = vim.read_register(register, cx)?;
+            if text.is_empty() {
+                return None;
+            }
+            let text = truncate_and_trailoff(&text.replace('\n', "^J"), MAX_CHARS);
+            Some(format!("\"{register}  {text}"))
+        })
+        .collect::<Vec<_>>();
+    if registers.is_empty() {
+        "No register holds any text".to_string()
+    } else {
+        registers.join("\n")
+    }
 }
 
 pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandInterceptResult> {
@@ -36,11 +309,8 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
     // In the future we should adjust it to handle parsing range syntax,
     // and then calling the appropriate commands with/without ranges.
     //
-    // We also need to support passing arguments to commands like :w
-    // (ideally with filename autocompletion).
-    //
-    // For now, you can only do a replace on the % range, and you can
-    // only use a specific line number range to "go to line"
+    // For now, only :s, :g and :normal take ranges, and only :e, :w, :b
+    // and :set take arguments.
     while query.starts_with(':') {
         query = &query[1..];
     }
@@ -255,6 +525,9 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
             ("lNext", editor::actions::GoToPrevDiagnostic.boxed_clone())
         }
 
+        "reg" | "regi" | "regis" | "regist" | "registe" | "registers" | "di" | "dis" | "disp"
+        | "displ" | "displa" | "display" => ("registers", ShowRegisters.boxed_clone()),
+
         // modify the buffer (should accept [range])
         "j" | "jo" | "joi" | "join" => ("join", JoinLines.boxed_clone()),
         "d" | "de" | "del" | "dele" | "delet" | "delete" | "dl" | "dell" | "delel" | "deletl"
@@ -330,17 +603,59 @@ pub fn command_interceptor(mut query: &str, cx: &AppContext) -> Option<CommandIn
                     }
                     .boxed_clone(),
                 )
-            } else if query.starts_with('%') {
+            } else if is_global_command(query) {
                 (
                     query,
-                    ReplaceCommand {
+                    GlobalCommand {
                         query: query.to_string(),
                     }
                     .boxed_clone(),
                 )
+            } else if is_normal_command(query) {
+                (
+                    query,
+                    NormalCommand {
+                        query: query.to_string(),
+                    }
+                    .boxed_clone(),
+                )
+            } else if let Some(path) = command_argument(query, EDIT_NAMES) {
+                (
+                    query,
+                    EditFile {
+                        path: path.to_string(),
+                    }
+                    .boxed_clone(),
+                )
+            } else if let Some(path) = command_argument(query, WRITE_NAMES) {
+                (
+                    query,
+                    WriteFile {
+                        path: path.to_string(),
+                    }
+                    .boxed_clone(),
+                )
+            } else if let Some(name) = command_argument(query, BUFFER_NAMES) {
+                (
+                    query,
+                    SwitchToBuffer {
+                        name: name.to_string(),
+                    }
+                    .boxed_clone(),
+                )
+            } else if let Some(option) =
+                command_argument(query, SET_NAMES).filter(|option| OPTIONS.contains(option))
+            {
+                (
+                    query,
+                    SetOption {
+                        option: option.to_string(),
+                    }
+                    .boxed_clone(),
+                )
             } else if let Ok(line) = query.parse::<u32>() {
                 (query, GoToLine { line }.boxed_clone())
-            } else if range_regex().is_match(query) {
+            } else if is_substitute_command(query, cx) {
                 (
                     query,
                     ReplaceCommand {
@@ -390,6 +705,7 @@ fn generate_positions(string: &str, query: &str) -> Vec<usize> {
 mod test {
     use std::path::Path;
 
+    use super::command_completions;
     use crate::test::{NeovimBackedTestContext, VimTestContext};
     use gpui::TestAppContext;
     use indoc::indoc;
@@ -503,6 +819,52 @@ mod test {
         assert_eq!(fs.load(&path).await.unwrap(), "@@\n");
     }
 
+    #[gpui::test]
+    async fn test_command_write_copy(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        let fs = cx.workspace(|workspace, cx| workspace.project().read(cx).fs().clone());
+
+        cx.simulate_keystrokes("i @ escape");
+        cx.simulate_keystrokes(": w space d i r / c o p y . r s enter");
+        cx.run_until_parked();
+
+        assert_eq!(
+            fs.load(Path::new("/root/dir/copy.rs")).await.unwrap(),
+            "@\n"
+        );
+        assert_eq!(fs.load(Path::new("/root/dir/file.rs")).await.unwrap(), "");
+    }
+
+    #[gpui::test]
+    async fn test_command_buffer(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.simulate_keystrokes(": n e w enter");
+        cx.simulate_keystrokes(": b space f i l e enter");
+        cx.workspace(|workspace, cx| {
+            let project_path = workspace.active_item(cx).unwrap().project_path(cx).unwrap();
+            assert_eq!(project_path.path.as_ref(), Path::new("dir/file.rs"));
+        });
+    }
+
+    #[gpui::test]
+    async fn test_command_completions(cx: &mut TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.update(|cx| {
+            assert_eq!(command_completions("wr", cx), vec!["write"]);
+            assert_eq!(command_completions(":tabc", cx), vec![":tabclose"]);
+            assert_eq!(command_completions("e d", cx), vec!["e dir/"]);
+            assert_eq!(command_completions("w dir/f", cx), vec!["w dir/file.rs"]);
+            assert_eq!(command_completions("b file", cx), vec!["b dir/file.rs"]);
+            assert_eq!(
+                command_completions("set no", cx),
+                vec!["set nonumber", "set nowrap"]
+            );
+            assert!(command_completions("j foo", cx).is_empty());
+        });
+    }
+
     #[gpui::test]
     async fn test_command_quit(cx: &mut TestAppContext) {
         let mut cx = VimTestContext::new(cx, true).await;
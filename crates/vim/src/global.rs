@@ -0,0 +1,529 @@
+//! The `:global` command, running an ex command on each line matching a pattern, and the
+//! `:normal` command, typing keys in normal mode on each line of a range.
+
+use editor::Editor;
+use gpui::{impl_actions, Keystroke, Modifiers, View, ViewContext, VisualContext, WindowContext};
+use language::Point;
+use multi_buffer::{MultiBufferRow, MultiBufferSnapshot, ToPoint};
+use regex::Regex;
+use serde_derive::Deserialize;
+use workspace::Workspace;
+
+use crate::{
+    motion::Motion,
+    normal::{
+        move_cursor,
+        search::{parse_replace_all, substitute},
+    },
+    state::Mode,
+    Vim,
+};
+
+/// Runs an ex command on each line matching a pattern, `:g/{pattern}/{command}`, or not matching
+/// it, `:v/{pattern}/{command}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct GlobalCommand {
+    pub query: String,
+}
+
+/// Types keys in normal mode on each line of a range, `:{range}normal {keys}`.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct NormalCommand {
+    pub query: String,
+}
+
+impl_actions!(vim, [GlobalCommand, NormalCommand]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_: &mut Workspace, action: &GlobalCommand, cx| {
+        global_command(&action.query, cx)
+    });
+    workspace.register_action(|_: &mut Workspace, action: &NormalCommand, cx| {
+        normal_command(&action.query, cx)
+    });
+}
+
+/// The lines an ex command applies to.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CommandRange {
+    /// `%`, all the lines.
+    All,
+    /// `'<,'>`, the selected lines.
+    Selection,
+    /// `{start},{end}` or `{line}`, counting lines from 1.
+    Lines(u32, u32),
+}
+
+/// The ex commands `:global` runs on the lines.
+#[derive(Debug, PartialEq)]
+enum ExCommand {
+    Delete,
+    Normal(String),
+    /// The substitution, as `:s` takes it.
+    Substitute(String),
+}
+
+#[derive(Debug, PartialEq)]
+struct Global {
+    range: Option<CommandRange>,
+    /// The pattern, in the regex syntax of Zed.
+    pattern: String,
+    /// Whether the command runs on the lines not matching the pattern.
+    invert: bool,
+    command: ExCommand,
+}
+
+/// Splits the range off the front of an ex command.
+pub(crate) fn split_range(query: &str) -> (Option<CommandRange>, &str) {
+    if let Some(rest) = query.strip_prefix('%') {
+        return (Some(CommandRange::All), rest);
+    }
+    if let Some(rest) = query.strip_prefix("'<,'>") {
+        return (Some(CommandRange::Selection), rest);
+    }
+    let Some((start, rest)) = split_number(query) else {
+        return (None, query);
+    };
+    if let Some((end, rest)) = rest.strip_prefix(',').and_then(split_number) {
+        return (Some(CommandRange::Lines(start, end)), rest);
+    }
+    (Some(CommandRange::Lines(start, start)), rest)
+}
+
+fn split_number(query: &str) -> Option<(u32, &str)> {
+    let len = query
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(query.len());
+    Some((query[..len].parse().ok()?, &query[len..]))
+}
+
+fn parse_global(query: &str) -> Option<Global> {
+    let (range, rest) = split_range(query);
+    let name_len = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    let (name, mut rest) = rest.split_at(name_len);
+    let mut invert = match name {
+        "g" | "gl" | "glo" | "glob" | "globa" | "global" => false,
+        "v" | "vg" | "vgl" | "vglo" | "vglob" | "vgloba" | "vglobal" => true,
+        _ => return None,
+    };
+    if let Some(bang_rest) = rest.strip_prefix('!') {
+        invert = !invert;
+        rest = bang_rest;
+    }
+
+    let delimiter = rest
+        .chars()
+        .next()
+        .filter(|c| !c.is_alphanumeric() && !c.is_whitespace() && !matches!(c, '"' | '|'))?;
+    // Like with `:s`, parentheses are flipped between their escaped and unescaped forms.
+    let mut pattern = String::new();
+    let mut command = "";
+    let mut escaped = false;
+    for (ix, c) in rest.char_indices().skip(1) {
+        if escaped {
+            escaped = false;
+            if c != delimiter && c != '(' && c != ')' {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        } else if c == '\\' {
+            escaped = true;
+        } else if c == delimiter {
+            command = &rest[ix + c.len_utf8()..];
+            break;
+        } else {
+            if c == '(' || c == ')' {
+                pattern.push('\\');
+            }
+            pattern.push(c);
+        }
+    }
+
+    Some(Global {
+        range,
+        pattern,
+        invert,
+        command: parse_ex_command(command.trim_start())?,
+    })
+}
+
+fn parse_ex_command(command: &str) -> Option<ExCommand> {
+    let name_len = command
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(command.len());
+    let (name, rest) = command.split_at(name_len);
+    match name {
+        "d" | "de" | "del" | "dele" | "delet" | "delete" if rest.trim().is_empty() => {
+            Some(ExCommand::Delete)
+        }
+        "norm" | "norma" | "normal" => {
+            let keys = rest.strip_prefix('!').unwrap_or(rest).strip_prefix(' ')?;
+            (!keys.is_empty()).then(|| ExCommand::Normal(keys.to_string()))
+        }
+        "s" | "su" | "sub" | "subs" | "subst" | "substi" | "substit" | "substitu" | "substitut"
+        | "substitute"
+            if rest.starts_with(|c: char| !c.is_alphanumeric() && !c.is_whitespace()) =>
+        {
+            Some(ExCommand::Substitute(format!("s{rest}")))
+        }
+        _ => None,
+    }
+}
+
+fn parse_normal(query: &str) -> Option<(Option<CommandRange>, String)> {
+    let (range, rest) = split_range(query);
+    match parse_ex_command(rest)? {
+        ExCommand::Normal(keys) => Some((range, keys)),
+        _ => None,
+    }
+}
+
+/// Whether the query is a `:global` or `:normal` command, for the command palette.
+pub(crate) fn is_global_command(query: &str) -> bool {
+    parse_global(query).is_some()
+}
+
+pub(crate) fn is_normal_command(query: &str) -> bool {
+    parse_normal(query).is_some()
+}
+
+/// The keystrokes typing the keys, which `:normal` takes as they're typed rather than in the
+/// syntax of key bindings.
+fn keystrokes(keys: &str) -> Vec<Keystroke> {
+    keys.chars()
+        .map(|c| {
+            let (key, shift) = match c {
+                ' ' => ("space".to_string(), false),
+                c if c.is_ascii_uppercase() => (c.to_ascii_lowercase().to_string(), true),
+                c => (c.to_string(), false),
+            };
+            Keystroke {
+                modifiers: Modifiers {
+                    shift,
+                    ..Default::default()
+                },
+                key,
+                ime_key: Some(c.to_string()),
+            }
+        })
+        .collect()
+}
+
+pub(crate) fn line_text(snapshot: &MultiBufferSnapshot, row: u32) -> String {
+    snapshot
+        .text_for_range(Point::new(row, 0)..Point::new(row, snapshot.line_len(MultiBufferRow(row))))
+        .collect()
+}
+
+/// The rows of the lines in the range, the line of the newest cursor when there's none.
+pub(crate) fn range_rows(
+    range: Option<CommandRange>,
+    editor: &mut Editor,
+    cx: &mut ViewContext<Editor>,
+) -> Vec<u32> {
+    let max_row = editor.buffer().read(cx).snapshot(cx).max_buffer_row().0;
+    match range {
+        None => vec![editor.selections.newest::<Point>(cx).head().row],
+        Some(CommandRange::All) => (0..=max_row).collect(),
+        Some(CommandRange::Lines(start, end)) => {
+            let (start, end) = (start.min(end), start.max(end));
+            (start.saturating_sub(1)..=end.saturating_sub(1).min(max_row)).collect()
+        }
+        Some(CommandRange::Selection) => {
+            let mut rows = editor
+                .selections
+                .all::<Point>(cx)
+                .iter()
+                .flat_map(|selection| {
+                    // Selections of whole lines end at the start of the next one.
+                    let end_row = if selection.end.column == 0 && selection.end > selection.start {
+                        selection.end.row - 1
+                    } else {
+                        selection.end.row
+                    };
+                    selection.start.row..=end_row
+                })
+                .collect::<Vec<_>>();
+            rows.sort_unstable();
+            rows.dedup();
+            rows
+        }
+    }
+}
+
+fn global_command(query: &str, cx: &mut WindowContext) {
+    let Some(global) = parse_global(query) else {
+        return;
+    };
+    let Ok(regex) = Regex::new(&global.pattern) else {
+        return;
+    };
+    Vim::update(cx, |vim, cx| {
+        let range = global.range.or(Some(CommandRange::All));
+        let Some((editor, rows)) = vim.update_active_editor(cx, |_, editor, cx| {
+            let rows = range_rows(range, editor, cx);
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let rows = rows
+                .into_iter()
+                .filter(|row| regex.is_match(&line_text(&snapshot, *row)) != global.invert)
+                .collect::<Vec<_>>();
+            (cx.view().clone(), rows)
+        }) else {
+            return;
+        };
+        vim.switch_mode(Mode::Normal, false, cx);
+        if rows.is_empty() {
+            return;
+        }
+        match global.command {
+            ExCommand::Delete => {
+                editor.update(cx, |editor, cx| delete_lines(editor, &rows, cx));
+                move_cursor(
+                    vim,
+                    Motion::FirstNonWhitespace {
+                        display_lines: false,
+                    },
+                    None,
+                    cx,
+                );
+            }
+            ExCommand::Substitute(substitution) => {
+                let previous = vim.workspace_state.last_substitution.as_ref();
+                let Some(mut replacement) = parse_replace_all(&substitution, previous) else {
+                    return;
+                };
+                // An empty pattern substitutes the pattern of `:global`, like vim's last search.
+                if replacement.search.is_empty() {
+                    replacement.search = global.pattern;
+                }
+                vim.workspace_state.last_substitution = Some(replacement.clone());
+                substitute(vim, editor, &rows, &replacement, cx);
+            }
+            ExCommand::Normal(keys) => type_keys_on_lines(editor, rows, &keys, cx),
+        }
+    });
+}
+
+fn normal_command(query: &str, cx: &mut WindowContext) {
+    let Some((range, keys)) = parse_normal(query) else {
+        return;
+    };
+    Vim::update(cx, |vim, cx| {
+        // In visual mode, the keys are typed on the selected lines.
+        let range = range.or_else(|| {
+            vim.state()
+                .mode
+                .is_visual()
+                .then_some(CommandRange::Selection)
+        });
+        let Some((editor, rows)) = vim.update_active_editor(cx, |_, editor, cx| {
+            (cx.view().clone(), range_rows(range, editor, cx))
+        }) else {
+            return;
+        };
+        vim.switch_mode(Mode::Normal, false, cx);
+        type_keys_on_lines(editor, rows, &keys, cx);
+    });
+}
+
+fn delete_lines(editor: &mut Editor, rows: &[u32], cx: &mut ViewContext<Editor>) {
+    let snapshot = editor.buffer().read(cx).snapshot(cx);
+    let max_row = snapshot.max_buffer_row().0;
+    let mut runs: Vec<(u32, u32)> = Vec::new();
+    for &row in rows {
+        match runs.last_mut() {
+            Some((_, end)) if *end + 1 == row => *end = row,
+            _ => runs.push((row, row)),
+        }
+    }
+    let ranges = runs
+        .iter()
+        .map(|&(start, end)| {
+            if end < max_row {
+                Point::new(start, 0)..Point::new(end + 1, 0)
+            } else if start > 0 {
+                Point::new(start - 1, snapshot.line_len(MultiBufferRow(start - 1)))
+                    ..snapshot.max_point()
+            } else {
+                Point::zero()..snapshot.max_point()
+            }
+        })
+        .collect::<Vec<_>>();
+    // The cursor ends on the line after the last one deleted.
+    let cursor = ranges.last().map(|range| snapshot.anchor_after(range.end));
+
+    editor.transact(cx, |editor, cx| {
+        editor.edit(ranges.into_iter().map(|range| (range, "")), cx);
+        if let Some(cursor) = cursor {
+            editor.change_selections(None, cx, |s| s.select_anchor_ranges([cursor..cursor]));
+        }
+    });
+}
+
+/// Types the keys in normal mode with the cursor at the start of each line, following the lines
+/// as the keys typed on the ones before edit the buffer.
+fn type_keys_on_lines(editor: View<Editor>, rows: Vec<u32>, keys: &str, cx: &mut WindowContext) {
+    let keystrokes = keystrokes(keys);
+    let lines = editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        rows.iter()
+            .map(|&row| snapshot.anchor_before(Point::new(row, 0)))
+            .collect::<Vec<_>>()
+    });
+    let escape = Keystroke::parse("escape").unwrap();
+
+    cx.spawn(|mut cx| async move {
+        for line in lines {
+            cx.update(|cx| {
+                cx.focus_view(&editor);
+                editor.update(cx, |editor, cx| {
+                    let snapshot = editor.buffer().read(cx).snapshot(cx);
+                    let start = Point::new(line.to_point(&snapshot).row, 0);
+                    editor.change_selections(None, cx, |s| s.select_ranges([start..start]));
+                });
+            })?;
+            for keystroke in &keystrokes {
+                cx.update(|cx| {
+                    cx.dispatch_keystroke(keystroke.clone());
+                })?;
+            }
+            // Like in vim, keys leaving a command unfinished or another mode on are followed by
+            // escape.
+            cx.update(|cx| {
+                let state = Vim::read(cx).state();
+                let unfinished = state.mode != Mode::Normal || !state.operator_stack.is_empty();
+                if unfinished {
+                    cx.dispatch_keystroke(escape.clone());
+                }
+            })?;
+        }
+        anyhow::Ok(())
+    })
+    .detach_and_log_err(cx);
+}
+
+#[cfg(test)]
+mod test {
+    use indoc::indoc;
+
+    use super::*;
+    use crate::test::VimTestContext;
+
+    #[test]
+    fn test_parse_global() {
+        assert_eq!(
+            parse_global("g/fo\\/o(/d"),
+            Some(Global {
+                range: None,
+                pattern: "fo/o\\(".to_string(),
+                invert: false,
+                command: ExCommand::Delete,
+            })
+        );
+        assert_eq!(
+            parse_global("1,3g!#x#norm A;"),
+            Some(Global {
+                range: Some(CommandRange::Lines(1, 3)),
+                pattern: "x".to_string(),
+                invert: true,
+                command: ExCommand::Normal("A;".to_string()),
+            })
+        );
+        assert_eq!(
+            parse_global("v/x/s/a/b/"),
+            Some(Global {
+                range: None,
+                pattern: "x".to_string(),
+                invert: true,
+                command: ExCommand::Substitute("s/a/b/".to_string()),
+            })
+        );
+        assert_eq!(parse_global("g/x/join"), None);
+        assert_eq!(parse_global("go"), None);
+
+        assert_eq!(
+            parse_normal("%norm! dd"),
+            Some((Some(CommandRange::All), "dd".to_string()))
+        );
+        assert_eq!(parse_normal("normal"), None);
+    }
+
+    #[gpui::test]
+    async fn test_global_command(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            one two
+            three"},
+            Mode::Normal,
+        );
+        cx.dispatch_action(GlobalCommand {
+            query: "g/one/d".to_string(),
+        });
+        cx.assert_state(
+            indoc! {"
+            two
+            ˇthree"},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            one two"},
+            Mode::Normal,
+        );
+        cx.dispatch_action(GlobalCommand {
+            query: "v/two/s/o/0/".to_string(),
+        });
+        cx.assert_state(
+            indoc! {"
+            ˇ0ne
+            two
+            one two"},
+            Mode::Normal,
+        );
+
+        cx.dispatch_action(GlobalCommand {
+            query: "g/two/normal A!".to_string(),
+        });
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            0ne
+            two!
+            one twoˇ!"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_normal_command(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            b
+            c"},
+            Mode::Normal,
+        );
+        cx.dispatch_action(NormalCommand {
+            query: "2,3norm Ix".to_string(),
+        });
+        cx.run_until_parked();
+        cx.assert_state(
+            indoc! {"
+            a
+            xb
+            ˇxc"},
+            Mode::Normal,
+        );
+    }
+}
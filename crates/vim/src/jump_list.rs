@@ -0,0 +1,366 @@
+use std::path::PathBuf;
+
+use editor::{scroll::Autoscroll, Anchor, Bias, Direction, Editor, ToPoint};
+use gpui::{actions, AppContext, Entity, EntityId, FocusableView, View};
+use language::Point;
+use ui::{ViewContext, WindowContext};
+use util::ResultExt;
+use workspace::{Workspace, WorkspaceId};
+
+use crate::{
+    normal::mark::editor_path,
+    persistence::VIM_DB,
+    state::{Jump, JumpList},
+    Vim,
+};
+
+actions!(vim, [JumpListOlder, JumpListNewer]);
+
+/// The number of jumps kept in each jumplist, as in vim.
+const JUMP_LIST_LIMIT: usize = 100;
+
+/// Registers the jumplist actions, and loads the jumplist stored for the workspace.
+pub(crate) fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_, _: &JumpListOlder, cx| {
+        Vim::update(cx, |vim, cx| {
+            move_in_jump_list(vim, Direction::Prev, cx);
+        })
+    });
+    workspace.register_action(|_, _: &JumpListNewer, cx| {
+        Vim::update(cx, |vim, cx| {
+            move_in_jump_list(vim, Direction::Next, cx);
+        })
+    });
+
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    cx.spawn(|_, mut cx| async move {
+        let jumps = cx
+            .background_executor()
+            .spawn(async move { VIM_DB.get_jumps(workspace_id) })
+            .await?;
+        cx.update(|cx| {
+            Vim::update(cx, |vim, _| {
+                let latest = &mut vim.workspace_state.latest_jump_list;
+                if latest.jumps.is_empty() {
+                    latest.jumps = jumps
+                        .into_iter()
+                        .map(|(path, row, column)| Jump {
+                            path: path.into(),
+                            point: Point::new(row, column),
+                            anchor: None,
+                        })
+                        .collect();
+                }
+            })
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+impl JumpList {
+    /// Adds a jump after the newest one, replacing any older jump to the same line.
+    fn push(&mut self, jump: Jump) {
+        self.jumps
+            .retain(|old| old.path != jump.path || old.point.row != jump.point.row);
+        self.jumps.push(jump);
+        if self.jumps.len() > JUMP_LIST_LIMIT {
+            self.jumps.remove(0);
+        }
+        self.position = None;
+    }
+}
+
+/// Records the active editor's cursor, or the given position in it, as a jump.
+pub(crate) fn record_jump(vim: &mut Vim, from: Option<Anchor>, cx: &mut WindowContext) {
+    let Some(editor) = vim
+        .active_editor
+        .clone()
+        .and_then(|editor| editor.upgrade())
+    else {
+        return;
+    };
+    let from = from.unwrap_or_else(|| editor.read(cx).selections.newest_anchor().head());
+    push_jump(vim, &editor, from, cx);
+}
+
+/// Records the jumps made outside of vim, like going to a definition or opening another file.
+pub(crate) fn pushed_to_nav_history(
+    vim: &mut Vim,
+    editor: View<Editor>,
+    from: Anchor,
+    is_deactivate: bool,
+    cx: &mut WindowContext,
+) {
+    if is_deactivate {
+        if vim.workspace_state.navigating_jump_list {
+            return;
+        }
+    } else {
+        let cursor = editor.read(cx).selections.newest_anchor().head();
+        if vim.workspace_state.vim_cursor == Some((editor.entity_id(), cursor))
+            || !editor.focus_handle(cx).is_focused(cx)
+        {
+            return;
+        }
+    }
+    push_jump(vim, &editor, from, cx);
+}
+
+fn push_jump(vim: &mut Vim, editor: &View<Editor>, from: Anchor, cx: &mut WindowContext) {
+    let Some(pane_id) = pane_id(editor, cx) else {
+        return;
+    };
+    let Some(jump) = jump_at(editor.read(cx), from, cx) else {
+        return;
+    };
+
+    let jump_list = jump_list(vim, pane_id);
+    refresh_jumps(jump_list, editor.read(cx), cx);
+    jump_list.push(jump);
+    vim.workspace_state.latest_jump_list = jump_list.clone();
+
+    if let Some(workspace_id) = editor
+        .read(cx)
+        .workspace()
+        .and_then(|workspace| workspace.read(cx).database_id())
+    {
+        save_jumps(vim, workspace_id, cx);
+    }
+}
+
+fn move_in_jump_list(vim: &mut Vim, direction: Direction, cx: &mut WindowContext) {
+    let count = vim.take_count(cx).unwrap_or(1);
+    let Some(editor) = vim
+        .active_editor
+        .clone()
+        .and_then(|editor| editor.upgrade())
+    else {
+        return;
+    };
+    let Some(pane_id) = pane_id(&editor, cx) else {
+        return;
+    };
+    let cursor = editor.read(cx).selections.newest_anchor().head();
+    let current = jump_at(editor.read(cx), cursor, cx);
+
+    let jump_list = jump_list(vim, pane_id);
+    refresh_jumps(jump_list, editor.read(cx), cx);
+    let target = match direction {
+        Direction::Prev => {
+            // Like vim, remember where `ctrl-o` was first typed so that `ctrl-i` can return there.
+            let position = match (jump_list.position, current) {
+                (Some(position), _) => position,
+                (None, Some(current)) => {
+                    jump_list.push(current);
+                    jump_list.jumps.len() - 1
+                }
+                (None, None) => jump_list.jumps.len(),
+            };
+            position.checked_sub(count)
+        }
+        Direction::Next => jump_list
+            .position
+            .map(|position| position + count)
+            .filter(|target| *target < jump_list.jumps.len()),
+    };
+    let Some(target) = target else {
+        return;
+    };
+    jump_list.position = Some(target);
+    let jump = jump_list.jumps[target].clone();
+    vim.workspace_state.latest_jump_list = jump_list.clone();
+
+    if editor_path(editor.read(cx), cx).as_ref() == Some(&jump.path) {
+        vim.update_active_editor(cx, |_, editor, cx| move_to_jump(editor, &jump, cx));
+        return;
+    }
+
+    let Some(workspace) = editor.read(cx).workspace() else {
+        return;
+    };
+    let task = workspace.update(cx, |workspace, cx| {
+        workspace.open_abs_path(PathBuf::from(jump.path.as_ref()), true, cx)
+    });
+    vim.workspace_state.navigating_jump_list = true;
+    cx.spawn(|mut cx| async move {
+        let item = task.await;
+        cx.update(|cx| {
+            Vim::update(cx, |vim, cx| {
+                vim.workspace_state.navigating_jump_list = false;
+                let Some(editor) = item.log_err()?.downcast::<Editor>() else {
+                    return None;
+                };
+                let cursor = editor.update(cx, |editor, cx| {
+                    move_to_jump(editor, &jump, cx);
+                    editor.selections.newest_anchor().head()
+                });
+                vim.workspace_state.vim_cursor = Some((editor.entity_id(), cursor));
+                Some(())
+            });
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
+/// The jumplist of the pane, which starts as a copy of the latest jumplist like a new window's
+/// does in vim.
+fn jump_list(vim: &mut Vim, pane_id: EntityId) -> &mut JumpList {
+    let state = &mut vim.workspace_state;
+    state.jump_lists.entry(pane_id).or_insert_with(|| JumpList {
+        jumps: state.latest_jump_list.jumps.clone(),
+        position: None,
+    })
+}
+
+/// The pane showing the editor, which is vim's window.
+fn pane_id(editor: &View<Editor>, cx: &AppContext) -> Option<EntityId> {
+    let workspace = editor.read(cx).workspace()?;
+    let pane = workspace.read(cx).pane_for(editor)?;
+    Some(pane.entity_id())
+}
+
+fn jump_at(editor: &Editor, anchor: Anchor, cx: &AppContext) -> Option<Jump> {
+    let path = editor_path(editor, cx)?;
+    let point = anchor.to_point(&editor.buffer().read(cx).snapshot(cx));
+    Some(Jump {
+        path,
+        point,
+        anchor: Some(anchor.text_anchor),
+    })
+}
+
+/// Updates the points of the jumps into the editor's file to follow the edits made since.
+fn refresh_jumps(jump_list: &mut JumpList, editor: &Editor, cx: &AppContext) {
+    let Some(path) = editor_path(editor, cx) else {
+        return;
+    };
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let buffer = buffer.read(cx);
+    for jump in &mut jump_list.jumps {
+        if let Some(anchor) = jump.anchor.filter(|anchor| buffer.can_resolve(anchor)) {
+            if jump.path == path {
+                jump.point = language::ToPoint::to_point(&anchor, buffer);
+            }
+        }
+    }
+}
+
+fn move_to_jump(editor: &mut Editor, jump: &Jump, cx: &mut ViewContext<Editor>) {
+    let Some(buffer) = editor.buffer().read(cx).as_singleton() else {
+        return;
+    };
+    let point = {
+        let buffer = buffer.read(cx);
+        match jump.anchor {
+            Some(anchor) if buffer.can_resolve(&anchor) => {
+                language::ToPoint::to_point(&anchor, buffer)
+            }
+            _ => buffer.clip_point(jump.point, Bias::Left),
+        }
+    };
+    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+        s.select_ranges([point..point])
+    });
+}
+
+fn save_jumps(vim: &Vim, workspace_id: WorkspaceId, cx: &mut WindowContext) {
+    let jumps = vim
+        .workspace_state
+        .latest_jump_list
+        .jumps
+        .iter()
+        .map(|jump| (jump.path.to_path_buf(), jump.point.row, jump.point.column))
+        .collect::<Vec<_>>();
+    cx.background_executor()
+        .spawn(async move { VIM_DB.save_jumps(workspace_id, jumps).await.log_err() })
+        .detach();
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_jump_list(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+        cx.simulate_keystrokes("shift-g");
+        cx.assert_state("one\ntwo\nthree\nfour\nˇfive", Mode::Normal);
+        // motions that aren't jumps aren't in the jumplist
+        cx.simulate_keystrokes("k k");
+        cx.simulate_keystrokes("g g");
+        cx.assert_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+
+        // the jump from the first line is replaced by the position `ctrl-o` was typed at
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state("one\ntwo\nˇthree\nfour\nfive", Mode::Normal);
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state("one\ntwo\nˇthree\nfour\nfive", Mode::Normal);
+        cx.simulate_keystrokes("ctrl-i");
+        cx.assert_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+        cx.simulate_keystrokes("ctrl-i");
+        cx.assert_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+
+        // a new jump goes after the newest one
+        cx.simulate_keystrokes("shift-g");
+        cx.simulate_keystrokes("2 ctrl-o");
+        cx.assert_state("one\ntwo\nˇthree\nfour\nfive", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_jump_list_search_and_marks(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇone two\nthree\nfour two", Mode::Normal);
+        cx.simulate_keystrokes("m a j");
+        cx.simulate_keystrokes("/ t w o enter");
+        cx.assert_state("one two\nthree\nfour ˇtwo", Mode::Normal);
+        cx.simulate_keystrokes("' a");
+        cx.assert_state("ˇone two\nthree\nfour two", Mode::Normal);
+
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state("one two\nthree\nfour ˇtwo", Mode::Normal);
+        cx.simulate_keystrokes("ctrl-o");
+        cx.assert_state("one two\nˇthree\nfour two", Mode::Normal);
+        cx.simulate_keystrokes("ctrl-i ctrl-i");
+        cx.assert_state("ˇone two\nthree\nfour two", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_jump_list_across_files(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("one\ntˇwo\nthree", Mode::Normal);
+        let fs = cx.workspace(|workspace, cx| workspace.project().read(cx).fs().clone());
+        fs.as_fake()
+            .insert_file("/root/dir/other.rs", b"other\n".to_vec())
+            .await;
+        let open = cx.workspace(|workspace, cx| {
+            workspace.open_abs_path(PathBuf::from("/root/dir/other.rs"), true, cx)
+        });
+        open.await.unwrap();
+        cx.run_until_parked();
+
+        cx.simulate_keystrokes("ctrl-o");
+        cx.run_until_parked();
+        cx.workspace(|workspace, cx| {
+            let project_path = workspace.active_item(cx).unwrap().project_path(cx).unwrap();
+            assert_eq!(project_path.path.as_ref(), Path::new("dir/file.rs"));
+        });
+        cx.assert_state("one\ntˇwo\nthree", Mode::Normal);
+
+        cx.simulate_keystrokes("ctrl-i");
+        cx.run_until_parked();
+        cx.workspace(|workspace, cx| {
+            let project_path = workspace.active_item(cx).unwrap().project_path(cx).unwrap();
+            assert_eq!(project_path.path.as_ref(), Path::new("dir/other.rs"));
+        });
+    }
+}
@@ -0,0 +1,211 @@
+use anyhow::{anyhow, Context, Result};
+use collections::BTreeMap;
+use gpui::AppContext;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde_derive::Serialize;
+use serde_json::{json, Value};
+use settings::{KeymapFile, Settings, SettingsSources};
+use util::ResultExt;
+
+const NORMAL_CONTEXT: &str = "Editor && vim_mode == normal && vim_operator == none && !VimWaiting";
+const VISUAL_CONTEXT: &str = "Editor && vim_mode == visual && vim_operator == none && !VimWaiting";
+const INSERT_CONTEXT: &str = "Editor && vim_mode == insert";
+const OPERATOR_PENDING_CONTEXT: &str =
+    "Editor && vim_operator != none && !VimWaiting && !VimObject";
+
+/// The `<leader>` key and the user's own mappings, configured under `vim` in settings.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct VimMappingSettings {
+    pub leader: String,
+    pub mappings: VimMappings,
+}
+
+#[derive(Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct VimMappingSettingsContent {
+    /// The key that `<leader>` stands for, in vim notation.
+    ///
+    /// Default: "\\"
+    pub leader: Option<String>,
+    /// Mappings for each mode, from keys in vim notation (e.g. `"<leader>w"`) to what they do.
+    ///
+    /// Default: {}
+    pub mappings: Option<VimMappings>,
+}
+
+/// Mappings, keyed by the mode they apply in.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize, JsonSchema)]
+pub struct VimMappings {
+    #[serde(default)]
+    pub normal: BTreeMap<String, VimMapping>,
+    #[serde(default)]
+    pub visual: BTreeMap<String, VimMapping>,
+    #[serde(default)]
+    pub insert: BTreeMap<String, VimMapping>,
+    #[serde(default)]
+    pub operator_pending: BTreeMap<String, VimMapping>,
+}
+
+/// What a mapping expands to.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize, JsonSchema)]
+#[serde(untagged)]
+pub enum VimMapping {
+    /// Keys in vim notation (e.g. `":w<cr>"`), typed as if by the user. Like vim's `:map`,
+    /// the keys may themselves trigger other mappings.
+    Keys(String),
+    /// A Zed action, written as it would be in `keymap.json`.
+    Action { action: Value },
+}
+
+impl Settings for VimMappingSettings {
+    const KEY: Option<&'static str> = Some("vim");
+
+    type FileContent = VimMappingSettingsContent;
+
+    fn load(sources: SettingsSources<Self::FileContent>, _: &mut AppContext) -> Result<Self> {
+        sources.json_merge()
+    }
+}
+
+impl VimMappingSettings {
+    /// The leader as a single keystroke, in the syntax used by `keymap.json`.
+    pub fn leader_keystroke(&self) -> Result<String> {
+        match parse_keys(&self.leader, None)?.as_slice() {
+            [keystroke] => Ok(keystroke.clone()),
+            _ => Err(anyhow!(
+                "vim leader must be a single key, got {:?}",
+                self.leader
+            )),
+        }
+    }
+
+    /// Binds every mapping in the context of its mode. Mappings that can't be parsed are
+    /// skipped and logged.
+    pub fn bind_keys(&self, cx: &mut AppContext) -> Result<()> {
+        let leader = self.leader_keystroke()?;
+        let mut blocks = Vec::new();
+        for (context, mappings) in [
+            (NORMAL_CONTEXT, &self.mappings.normal),
+            (VISUAL_CONTEXT, &self.mappings.visual),
+            (INSERT_CONTEXT, &self.mappings.insert),
+            (OPERATOR_PENDING_CONTEXT, &self.mappings.operator_pending),
+        ] {
+            let mut bindings = serde_json::Map::new();
+            for (keys, mapping) in mappings {
+                let Some(keystrokes) = parse_keys(keys, Some(&leader))
+                    .with_context(|| format!("invalid vim mapping {keys:?}"))
+                    .log_err()
+                else {
+                    continue;
+                };
+                let action = match mapping {
+                    VimMapping::Keys(expansion) => {
+                        let Some(expansion) = parse_keys(expansion, Some(&leader))
+                            .with_context(|| format!("invalid expansion for vim mapping {keys:?}"))
+                            .log_err()
+                        else {
+                            continue;
+                        };
+                        json!(["workspace::SendKeystrokes", expansion.join(" ")])
+                    }
+                    VimMapping::Action { action } => action.clone(),
+                };
+                bindings.insert(keystrokes.join(" "), action);
+            }
+            if !bindings.is_empty() {
+                blocks.push(json!({ "context": context, "bindings": bindings }));
+            }
+        }
+
+        serde_json::from_value::<KeymapFile>(Value::Array(blocks))?.add_to_cx(cx)
+    }
+}
+
+/// Converts keys in vim notation, such as `"<leader>gd"` or `"<C-w>v"`, into keystrokes.
+fn parse_keys(keys: &str, leader: Option<&str>) -> Result<Vec<String>> {
+    let mut keystrokes = Vec::new();
+    let mut rest = keys;
+    while let Some(c) = rest.chars().next() {
+        if c == '<' {
+            if let Some(end) = rest.find('>') {
+                if let Some(keystroke) = parse_special_key(&rest[1..end], leader) {
+                    keystrokes.push(keystroke);
+                    rest = &rest[end + 1..];
+                    continue;
+                }
+            }
+        }
+        keystrokes.push(match c {
+            ' ' => "space".to_string(),
+            c if c.is_ascii_uppercase() => format!("shift-{}", c.to_ascii_lowercase()),
+            c => c.to_string(),
+        });
+        rest = &rest[c.len_utf8()..];
+    }
+
+    if keystrokes.is_empty() {
+        Err(anyhow!("no keys given"))
+    } else {
+        Ok(keystrokes)
+    }
+}
+
+/// Parses the inside of a `<...>` key, returning `None` if vim would treat it literally.
+fn parse_special_key(name: &str, leader: Option<&str>) -> Option<String> {
+    if name.eq_ignore_ascii_case("leader") {
+        return leader.map(ToString::to_string);
+    }
+
+    let mut keystroke = String::new();
+    let mut key = name;
+    while let Some((modifier, rest)) = key.split_once('-') {
+        if modifier.len() != 1 || rest.is_empty() {
+            break;
+        }
+        keystroke.push_str(match modifier.to_ascii_lowercase().as_str() {
+            "c" => "ctrl-",
+            "s" => "shift-",
+            "a" | "m" => "alt-",
+            "d" => "cmd-",
+            _ => return None,
+        });
+        key = rest;
+    }
+
+    let key = key.to_ascii_lowercase();
+    keystroke.push_str(match key.as_str() {
+        "cr" | "enter" | "return" => "enter",
+        "esc" => "escape",
+        "bs" => "backspace",
+        "del" => "delete",
+        "lt" => "<",
+        "bar" => "|",
+        "bslash" => "\\",
+        "space" | "tab" | "up" | "down" | "left" | "right" | "home" | "end" | "pageup"
+        | "pagedown" | "f1" | "f2" | "f3" | "f4" | "f5" | "f6" | "f7" | "f8" | "f9" | "f10"
+        | "f11" | "f12" => key.as_str(),
+        _ if key.chars().count() == 1 && !keystroke.is_empty() => key.as_str(),
+        _ => return None,
+    });
+    Some(keystroke)
+}
+
+#[cfg(test)]
+mod test {
+    use super::parse_keys;
+
+    #[test]
+    fn test_parse_keys() {
+        assert_eq!(parse_keys(":w<CR>", None).unwrap(), [":", "w", "enter"]);
+        assert_eq!(
+            parse_keys("<leader>gD", Some("space")).unwrap(),
+            ["space", "g", "shift-d"]
+        );
+        assert_eq!(parse_keys("<C-w>v", None).unwrap(), ["ctrl-w", "v"]);
+        assert_eq!(parse_keys("<S-A-Tab>", None).unwrap(), ["shift-alt-tab"]);
+        assert_eq!(parse_keys("a<b", None).unwrap(), ["a", "<", "b"]);
+        assert_eq!(parse_keys("<lt>x>", None).unwrap(), ["<", "x", ">"]);
+        assert_eq!(parse_keys("\\", None).unwrap(), ["\\"]);
+        assert!(parse_keys("", None).is_err());
+    }
+}
@@ -14,6 +14,7 @@ use std::ops::Range;
 use workspace::Workspace;
 
 use crate::{
+    jump_list,
     normal::{mark, normal_motion},
     state::{Mode, Operator},
     surrounds::SurroundsType,
@@ -98,6 +99,8 @@ pub enum Motion {
     WindowTop,
     WindowMiddle,
     WindowBottom,
+    NextFold,
+    PreviousFold,
 
     // we don't have a good way to run a search syncronously, so
     // we handle search motions by running the search async and then
@@ -244,6 +247,8 @@ actions!(
         WindowTop,
         WindowMiddle,
         WindowBottom,
+        NextFold,
+        PreviousFold,
     ]
 );
 
@@ -310,6 +315,11 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     });
     workspace
         .register_action(|_: &mut Workspace, _: &Matching, cx: _| motion(Motion::Matching, cx));
+    workspace
+        .register_action(|_: &mut Workspace, _: &NextFold, cx: _| motion(Motion::NextFold, cx));
+    workspace.register_action(|_: &mut Workspace, _: &PreviousFold, cx: _| {
+        motion(Motion::PreviousFold, cx)
+    });
 
     workspace.register_action(
         |_: &mut Workspace, &NextWordStart { ignore_punctuation }: &NextWordStart, cx: _| {
@@ -398,6 +408,13 @@ pub(crate) fn search_motion(m: Motion, cx: &mut WindowContext) {
         prior_selections, ..
     } = &m
     {
+        if let Some(prior) = prior_selections.first() {
+            if Vim::read(cx).active_operator().is_none() {
+                Vim::update(cx, |vim, cx| {
+                    jump_list::record_jump(vim, Some(prior.end), cx)
+                });
+            }
+        }
         match Vim::read(cx).state().mode {
             Mode::Visual | Mode::VisualLine | Mode::VisualBlock => {
                 if !prior_selections.is_empty() {
@@ -431,6 +448,13 @@ pub(crate) fn motion(motion: Motion, cx: &mut WindowContext) {
     let count = Vim::update(cx, |vim, cx| vim.take_count(cx));
     let active_operator = Vim::read(cx).active_operator();
     let mut waiting_operator: Option<Operator> = None;
+    // Searches have moved the cursor already, so `search_motion` records their jumps.
+    if motion.is_jump()
+        && active_operator.is_none()
+        && !matches!(motion, Motion::ZedSearchResult { .. })
+    {
+        Vim::update(cx, |vim, cx| jump_list::record_jump(vim, None, cx));
+    }
     match Vim::read(cx).state().mode {
         Mode::Normal | Mode::Replace => {
             if active_operator == Some(Operator::AddSurrounds { target: None }) {
@@ -475,6 +499,8 @@ impl Motion {
             | WindowMiddle
             | WindowBottom
             | Jump { line: true, .. }
+            | NextFold
+            | PreviousFold
             | EndOfParagraph => true,
             EndOfLine { .. }
             | Matching
@@ -538,6 +564,8 @@ impl Motion {
             | WindowMiddle
             | WindowBottom
             | NextLineStart
+            | NextFold
+            | PreviousFold
             | ZedSearchResult { .. }
             | Jump { .. } => false,
         }
@@ -558,6 +586,8 @@ impl Motion {
             | WindowTop
             | WindowMiddle
             | WindowBottom
+            | NextFold
+            | PreviousFold
             | NextWordEnd { .. }
             | PreviousWordEnd { .. }
             | NextSubwordEnd { .. }
@@ -586,6 +616,51 @@ impl Motion {
         }
     }
 
+    /// Whether moving with the motion adds the position moved from to the jumplist.
+    pub fn is_jump(&self) -> bool {
+        use Motion::*;
+        match self {
+            StartOfDocument
+            | EndOfDocument
+            | StartOfParagraph
+            | EndOfParagraph
+            | Matching
+            | WindowTop
+            | WindowMiddle
+            | WindowBottom
+            | ZedSearchResult { .. }
+            | Jump { .. } => true,
+            Left
+            | Backspace
+            | Down { .. }
+            | Up { .. }
+            | Right
+            | Space
+            | NextWordStart { .. }
+            | NextWordEnd { .. }
+            | PreviousWordStart { .. }
+            | PreviousWordEnd { .. }
+            | NextSubwordStart { .. }
+            | NextSubwordEnd { .. }
+            | PreviousSubwordStart { .. }
+            | PreviousSubwordEnd { .. }
+            | FirstNonWhitespace { .. }
+            | CurrentLine
+            | StartOfLine { .. }
+            | EndOfLine { .. }
+            | FindForward { .. }
+            | FindBackward { .. }
+            | RepeatFind { .. }
+            | RepeatFindReversed { .. }
+            | NextLineStart
+            | StartOfLineDownward
+            | EndOfLineDownward
+            | GoToColumn
+            | NextFold
+            | PreviousFold => false,
+        }
+    }
+
     pub fn move_point(
         &self,
         map: &DisplaySnapshot,
@@ -770,6 +845,8 @@ impl Motion {
             WindowTop => window_top(map, point, &text_layout_details, times - 1),
             WindowMiddle => window_middle(map, point, &text_layout_details),
             WindowBottom => window_bottom(map, point, &text_layout_details, times - 1),
+            NextFold => (next_fold(map, point, times), SelectionGoal::None),
+            PreviousFold => (previous_fold(map, point, times), SelectionGoal::None),
             Jump { line, anchor } => mark::jump_motion(map, *anchor, *line),
             ZedSearchResult { new_selections, .. } => {
                 // There will be only one selection, as
@@ -1513,6 +1590,54 @@ fn end_of_document(
     map.clip_point(new_point.to_display_point(map), Bias::Left)
 }
 
+/// Whether a fold, open or closed, starts on the given row. A closed fold counts once.
+fn fold_starts_at(map: &DisplaySnapshot, row: u32) -> bool {
+    if map.is_line_folded(MultiBufferRow(row)) {
+        row == 0 || !map.is_line_folded(MultiBufferRow(row - 1))
+    } else {
+        map.foldable_range(MultiBufferRow(row)).is_some()
+    }
+}
+
+/// The last row of the fold starting on the given row, if there is one.
+fn fold_end(map: &DisplaySnapshot, row: u32) -> Option<u32> {
+    if map.is_line_folded(MultiBufferRow(row)) {
+        (!map.is_line_folded(MultiBufferRow(row + 1))).then_some(row)
+    } else {
+        map.foldable_range(MultiBufferRow(row))
+            .map(|(range, _)| range.end.row)
+    }
+}
+
+/// Moves to the start of the next fold, like vim's `zj`.
+fn next_fold(map: &DisplaySnapshot, point: DisplayPoint, times: usize) -> DisplayPoint {
+    let max_row = map.max_buffer_row().0;
+    let mut row = point.to_point(map).row;
+    for _ in 0..times {
+        let Some(next_row) = (row + 1..=max_row).find(|row| fold_starts_at(map, *row)) else {
+            break;
+        };
+        row = next_row;
+    }
+    map.clip_point(Point::new(row, 0).to_display_point(map), Bias::Left)
+}
+
+/// Moves to the end of the previous fold, like vim's `zk`.
+fn previous_fold(map: &DisplaySnapshot, point: DisplayPoint, times: usize) -> DisplayPoint {
+    let mut row = point.to_point(map).row;
+    for _ in 0..times {
+        let Some(end_row) = (0..row)
+            .filter_map(|start_row| fold_end(map, start_row))
+            .filter(|end_row| *end_row < row)
+            .max()
+        else {
+            break;
+        };
+        row = end_row;
+    }
+    map.clip_point(Point::new(row, 0).to_display_point(map), Bias::Left)
+}
+
 fn matching(map: &DisplaySnapshot, display_point: DisplayPoint) -> DisplayPoint {
     // https://github.com/vim/vim/blob/1d87e11a1ef201b26ed87585fba70182ad0c468a/runtime/doc/motion.txt#L1200
     let display_point = map.clip_at_line_end(display_point);
@@ -1,8 +1,10 @@
 mod case;
 mod change;
 mod delete;
+mod fold;
 mod increment;
 mod indent;
+pub(crate) mod macros;
 pub(crate) mod mark;
 mod paste;
 pub(crate) mod repeat;
@@ -37,6 +39,7 @@ use self::{
     case::{change_case, convert_to_lower_case, convert_to_upper_case},
     change::{change_motion, change_object},
     delete::{delete_motion, delete_object},
+    fold::{fold_motion, fold_object},
     indent::{indent_motion, indent_object, IndentDirection},
     yank::{yank_motion, yank_object},
 };
@@ -181,6 +184,8 @@ pub(crate) fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace
     search::register(workspace, cx);
     substitute::register(workspace, cx);
     increment::register(workspace, cx);
+    fold::register(workspace, cx);
+    macros::register(workspace, cx);
 }
 
 pub fn normal_motion(
@@ -198,6 +203,7 @@ pub fn normal_motion(
             Some(Operator::AddSurrounds { target: None }) => {}
             Some(Operator::Indent) => indent_motion(vim, motion, times, IndentDirection::In, cx),
             Some(Operator::Outdent) => indent_motion(vim, motion, times, IndentDirection::Out, cx),
+            Some(Operator::Fold) => fold_motion(vim, motion, times, cx),
             Some(operator) => {
                 // Can't do anything for text objects, Ignoring
                 error!("Unexpected normal mode motion operator: {:?}", operator)
@@ -220,6 +226,7 @@ pub fn normal_object(object: Object, cx: &mut WindowContext) {
                 Some(Operator::Outdent) => {
                     indent_object(vim, object, around, IndentDirection::Out, cx)
                 }
+                Some(Operator::Fold) => fold_object(vim, object, around, cx),
                 Some(Operator::AddSurrounds { target: None }) => {
                     waiting_operator = Some(Operator::AddSurrounds {
                         target: Some(SurroundsType::Object(object)),
@@ -0,0 +1,82 @@
+use crate::{motion::Motion, object::Object, state::Mode, Vim};
+use editor::{display_map::ToDisplayPoint, Editor};
+use gpui::{actions, ViewContext, WindowContext};
+use language::{Point, SelectionGoal};
+use multi_buffer::MultiBufferRow;
+use workspace::Workspace;
+
+actions!(vim, [ToggleFold, CreateFold]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|_: &mut Workspace, _: &ToggleFold, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| {
+                let row = editor.selections.newest::<Point>(cx).head().row;
+                let snapshot = editor.snapshot(cx);
+                if snapshot.is_line_folded(MultiBufferRow(row)) {
+                    editor.unfold_lines(&Default::default(), cx);
+                } else {
+                    editor.fold(&Default::default(), cx);
+                }
+            });
+        });
+    });
+
+    workspace.register_action(|_: &mut Workspace, _: &CreateFold, cx| {
+        Vim::update(cx, |vim, cx| {
+            vim.update_active_editor(cx, |_, editor, cx| fold_selected_lines(editor, cx));
+            vim.switch_mode(Mode::Normal, true, cx);
+        });
+    });
+}
+
+pub fn fold_motion(vim: &mut Vim, motion: Motion, times: Option<usize>, cx: &mut WindowContext) {
+    vim.update_active_editor(cx, |_, editor, cx| {
+        let text_layout_details = editor.text_layout_details(cx);
+        editor.change_selections(None, cx, |s| {
+            s.move_with(|map, selection| {
+                motion.expand_selection(map, selection, times, false, &text_layout_details);
+            });
+        });
+        fold_selected_lines(editor, cx);
+    });
+}
+
+pub fn fold_object(vim: &mut Vim, object: Object, around: bool, cx: &mut WindowContext) {
+    vim.update_active_editor(cx, |_, editor, cx| {
+        editor.change_selections(None, cx, |s| {
+            s.move_with(|map, selection| {
+                object.expand_selection(map, selection, around);
+            });
+        });
+        fold_selected_lines(editor, cx);
+    });
+}
+
+/// Folds the whole lines spanned by each selection, like vim's manual folds.
+fn fold_selected_lines(editor: &mut Editor, cx: &mut ViewContext<Editor>) {
+    editor.change_selections(None, cx, |s| {
+        s.move_with(|map, selection| {
+            let start = selection.start.to_point(map);
+            let end = selection.end.to_point(map);
+            let end_row = if end.column == 0 && end.row > start.row {
+                end.row - 1
+            } else {
+                end.row
+            };
+            let buffer = &map.buffer_snapshot;
+            selection.start = Point::new(start.row, 0).to_display_point(map);
+            selection.end =
+                Point::new(end_row, buffer.line_len(MultiBufferRow(end_row))).to_display_point(map);
+            selection.reversed = false;
+        });
+    });
+    editor.selections.line_mode = false;
+    editor.fold_selected_ranges(&Default::default(), cx);
+    editor.change_selections(None, cx, |s| {
+        s.move_with(|map, selection| {
+            let start = Point::new(selection.start.to_point(map).row, 0);
+            selection.collapse_to(start.to_display_point(map), SelectionGoal::None);
+        });
+    });
+}
@@ -0,0 +1,80 @@
+use std::sync::Arc;
+
+use gpui::{actions, ViewContext, WindowContext};
+use workspace::Workspace;
+
+use crate::{state::Operator, Vim};
+
+actions!(vim, [ToggleRecord]);
+
+pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
+    workspace.register_action(|workspace: &mut Workspace, _: &ToggleRecord, cx| {
+        if workspace.is_recording_macro() {
+            workspace.stop_recording_macro(cx);
+        } else {
+            Vim::update(cx, |vim, cx| {
+                vim.push_operator(Operator::RecordRegister, cx)
+            });
+        }
+    });
+}
+
+/// Starts recording a macro into the given register, using the workspace's keyboard macros.
+pub(crate) fn record(register: Arc<str>, cx: &mut WindowContext) {
+    Vim::update(cx, |vim, cx| vim.clear_operator(cx));
+    if let Some(workspace) = cx.window_handle().downcast::<Workspace>() {
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.start_recording_macro(register.to_string().into(), cx)
+            })
+            .ok();
+    }
+}
+
+/// Replays the macro recorded into the given register as many times as the count, `@` standing
+/// for the register replayed last.
+pub(crate) fn replay(register: Arc<str>, cx: &mut WindowContext) {
+    let replay = Vim::update(cx, |vim, cx| {
+        let count = vim.take_count(cx).unwrap_or(1);
+        vim.clear_operator(cx);
+        let register = if &*register == "@" {
+            vim.workspace_state.last_replayed_register.clone()?
+        } else {
+            register
+        };
+        vim.workspace_state.last_replayed_register = Some(register.clone());
+        Some((register, count))
+    });
+    let Some((register, count)) = replay else {
+        return;
+    };
+    if let Some(workspace) = cx.window_handle().downcast::<Workspace>() {
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.replay_macro(&register, count, cx)
+            })
+            .ok();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{state::Mode, test::VimTestContext};
+
+    #[gpui::test]
+    async fn test_record_and_replay_macro(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.set_state("ˇone\ntwo\nthree\nfour\nfive", Mode::Normal);
+
+        cx.simulate_keystrokes("q a shift-a ! escape j q");
+        cx.assert_state("one!\ntwˇo\nthree\nfour\nfive", Mode::Normal);
+
+        cx.simulate_keystrokes("2 @ a");
+        cx.run_until_parked();
+        cx.assert_state("one!\ntwo!\nthree!\nfouˇr\nfive", Mode::Normal);
+
+        cx.simulate_keystrokes("@ @");
+        cx.run_until_parked();
+        cx.assert_state("one!\ntwo!\nthree!\nfour!\nfivˇe", Mode::Normal);
+    }
+}
@@ -1,19 +1,63 @@
-use std::{ops::Range, sync::Arc};
+use std::{
+    ops::Range,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use editor::{
     display_map::{DisplaySnapshot, ToDisplayPoint},
     movement,
     scroll::Autoscroll,
-    Anchor, Bias, DisplayPoint,
+    Anchor, Bias, DisplayPoint, Editor, ToPoint,
 };
-use gpui::WindowContext;
-use language::SelectionGoal;
+use gpui::{AppContext, Entity, ViewContext, WindowContext};
+use language::{Point, SelectionGoal};
+use util::ResultExt;
+use workspace::{Workspace, WorkspaceId};
 
 use crate::{
+    jump_list,
     motion::{self, Motion},
+    persistence::VIM_DB,
+    state::FileMark,
     Vim,
 };
 
+/// Loads the marks stored for the workspace, so that they survive restarts.
+pub(crate) fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
+    let Some(workspace_id) = workspace.database_id() else {
+        return;
+    };
+    cx.spawn(|_, mut cx| async move {
+        let marks = cx
+            .background_executor()
+            .spawn(async move { VIM_DB.get_marks(workspace_id) })
+            .await?;
+        cx.update(|cx| {
+            Vim::update(cx, |vim, _| {
+                for (name, path, row, column) in marks {
+                    let path: Arc<Path> = path.into();
+                    let point = Point::new(row, column);
+                    if is_global_mark(&name) {
+                        vim.workspace_state
+                            .global_marks
+                            .entry(name)
+                            .or_insert(FileMark { path, point });
+                    } else {
+                        vim.workspace_state
+                            .file_marks
+                            .entry(path)
+                            .or_default()
+                            .entry(name)
+                            .or_insert(point);
+                    }
+                }
+            })
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
 pub fn create_mark(vim: &mut Vim, text: Arc<str>, tail: bool, cx: &mut WindowContext) {
     let Some(anchors) = vim.update_active_editor(cx, |_, editor, _| {
         editor
@@ -25,10 +69,182 @@ pub fn create_mark(vim: &mut Vim, text: Arc<str>, tail: bool, cx: &mut WindowCon
     }) else {
         return;
     };
+    if is_kept_mark(&text) {
+        keep_mark(vim, &text, &anchors, cx);
+    }
     vim.update_state(|state| state.marks.insert(text.to_string(), anchors));
     vim.clear_operator(cx);
 }
 
+/// Whether the mark is one of `A` to `Z`, which can be jumped to from any file.
+fn is_global_mark(name: &str) -> bool {
+    name.len() == 1 && name.chars().all(|c| c.is_ascii_uppercase())
+}
+
+/// Whether the mark is one set by the user, which is kept across restarts.
+fn is_kept_mark(name: &str) -> bool {
+    name.len() == 1 && name.chars().all(|c| c.is_ascii_alphabetic())
+}
+
+/// The path of the file shown in the editor, which marks, jumps and changes are kept by.
+pub(crate) fn editor_path(editor: &Editor, cx: &AppContext) -> Option<Arc<Path>> {
+    let buffer = editor.buffer().read(cx).as_singleton()?;
+    let file = buffer.read(cx).file()?.as_local()?;
+    Some(file.abs_path(cx).into())
+}
+
+/// Records the mark by the path of the active editor's file, and stores it in the database.
+fn keep_mark(vim: &mut Vim, name: &str, anchors: &[Anchor], cx: &mut WindowContext) {
+    let Some((path, point, workspace_id)) = vim
+        .update_active_editor(cx, |_, editor, cx| {
+            let path = editor_path(editor, cx)?;
+            let point = anchors
+                .first()?
+                .to_point(&editor.buffer().read(cx).snapshot(cx));
+            let workspace_id = editor.workspace()?.read(cx).database_id();
+            Some((path, point, workspace_id))
+        })
+        .flatten()
+    else {
+        return;
+    };
+
+    if is_global_mark(name) {
+        vim.workspace_state
+            .global_marks
+            .insert(name.to_string(), FileMark { path, point });
+    } else {
+        vim.workspace_state
+            .file_marks
+            .entry(path)
+            .or_default()
+            .insert(name.to_string(), point);
+    }
+
+    if let Some(workspace_id) = workspace_id {
+        save_marks(vim, workspace_id, cx);
+    }
+}
+
+fn save_marks(vim: &Vim, workspace_id: WorkspaceId, cx: &mut WindowContext) {
+    let file_marks = vim
+        .workspace_state
+        .file_marks
+        .iter()
+        .flat_map(|(path, marks)| {
+            marks
+                .iter()
+                .map(|(name, point)| (name.clone(), path.to_path_buf(), point.row, point.column))
+        });
+    let global_marks = vim.workspace_state.global_marks.iter().map(|(name, mark)| {
+        (
+            name.clone(),
+            mark.path.to_path_buf(),
+            mark.point.row,
+            mark.point.column,
+        )
+    });
+    let marks = file_marks.chain(global_marks).collect::<Vec<_>>();
+    cx.background_executor()
+        .spawn(async move { VIM_DB.save_marks(workspace_id, marks).await.log_err() })
+        .detach();
+}
+
+/// Sets the kept marks of the active editor's file in the editor, unless it has them already.
+pub(crate) fn restore_marks(vim: &mut Vim, cx: &mut WindowContext) {
+    let Some(marks) = vim
+        .update_active_editor(cx, |vim, editor, cx| {
+            let path = editor_path(editor, cx)?;
+            let snapshot = editor.buffer().read(cx).snapshot(cx);
+            let file_marks = vim
+                .workspace_state
+                .file_marks
+                .get(&path)
+                .into_iter()
+                .flatten()
+                .map(|(name, point)| (name.clone(), *point));
+            let global_marks = vim
+                .workspace_state
+                .global_marks
+                .iter()
+                .filter(|(_, mark)| mark.path == path)
+                .map(|(name, mark)| (name.clone(), mark.point));
+            let marks = file_marks
+                .chain(global_marks)
+                .map(|(name, point)| {
+                    let point = snapshot.clip_point(point, Bias::Left);
+                    (name, vec![snapshot.anchor_before(point)])
+                })
+                .collect::<Vec<_>>();
+            Some(marks)
+        })
+        .flatten()
+    else {
+        return;
+    };
+
+    vim.update_state(|state| {
+        for (name, anchors) in marks {
+            state.marks.entry(name).or_insert(anchors);
+        }
+    });
+}
+
+/// Opens the file of a global mark set in another file than the active editor's, and jumps to it.
+fn jump_to_other_file(
+    vim: &mut Vim,
+    name: &str,
+    mark: FileMark,
+    line: bool,
+    cx: &mut WindowContext,
+) {
+    vim.clear_operator(cx);
+    let Some(workspace) = vim
+        .update_active_editor(cx, |_, editor, _| editor.workspace())
+        .flatten()
+    else {
+        return;
+    };
+    let task = workspace.update(cx, |workspace, cx| {
+        workspace.open_abs_path(PathBuf::from(mark.path.as_ref()), true, cx)
+    });
+    let name = name.to_string();
+    cx.spawn(|mut cx| async move {
+        let item = task.await?;
+        let Some(editor) = item.downcast::<Editor>() else {
+            return Ok(());
+        };
+        cx.update(|cx| {
+            Vim::update(cx, |vim, cx| {
+                let anchor = vim
+                    .editor_states
+                    .get(&editor.entity_id())
+                    .and_then(|state| state.marks.get(&name))
+                    .and_then(|anchors| anchors.first())
+                    .copied();
+                editor.update(cx, |editor, cx| {
+                    let map = editor.snapshot(cx).display_snapshot;
+                    let mut point = match anchor {
+                        Some(anchor) => anchor.to_display_point(&map),
+                        None => map
+                            .buffer_snapshot
+                            .clip_point(mark.point, Bias::Left)
+                            .to_display_point(&map),
+                    };
+                    if line {
+                        point = motion::first_non_whitespace(&map, false, point);
+                    }
+                    let point = point.to_point(&map);
+                    editor.change_selections(Some(Autoscroll::fit()), cx, |s| {
+                        s.select_ranges([point..point])
+                    });
+                });
+            })
+        })
+    })
+    .detach_and_log_err(cx);
+}
+
 pub fn create_mark_after(vim: &mut Vim, text: Arc<str>, cx: &mut WindowContext) {
     let Some(anchors) = vim.update_active_editor(cx, |_, editor, cx| {
         let (map, selections) = editor.selections.all_display(cx);
@@ -88,7 +304,20 @@ pub fn jump(text: Arc<str>, line: bool, cx: &mut WindowContext) {
                     .collect::<Vec<Anchor>>()
             }),
             "." => vim.state().change_list.last().cloned(),
-            _ => vim.state().marks.get(&*text).cloned(),
+            _ => {
+                if let Some(mark) = vim.workspace_state.global_marks.get(&*text).cloned() {
+                    let in_active_editor = vim
+                        .update_active_editor(cx, |_, editor, cx| {
+                            editor_path(editor, cx).as_ref() == Some(&mark.path)
+                        })
+                        .unwrap_or(false);
+                    if !in_active_editor {
+                        jump_to_other_file(vim, &text, mark, line, cx);
+                        return None;
+                    }
+                }
+                vim.state().marks.get(&*text).cloned()
+            }
         }
     });
 
@@ -108,6 +337,7 @@ pub fn jump(text: Arc<str>, line: bool, cx: &mut WindowContext) {
         return;
     } else {
         Vim::update(cx, |vim, cx| {
+            jump_list::record_jump(vim, None, cx);
             vim.update_active_editor(cx, |_, editor, cx| {
                 let map = editor.snapshot(cx);
                 let mut ranges: Vec<Range<Anchor>> = Vec::new();
@@ -46,8 +46,12 @@ fn paste(_: &mut Workspace, action: &Paste, cx: &mut ViewContext<Workspace>) {
             editor.transact(cx, |editor, cx| {
                 editor.set_clip_at_line_ends(false, cx);
 
+                let register = vim.workspace_state.selected_register.take();
                 let (clipboard_text, clipboard_selections): (String, Option<_>) =
-                    if VimSettings::get_global(cx).use_system_clipboard == UseSystemClipboard::Never
+                    if let Some(register) = register {
+                        (vim.read_register(register, cx).unwrap_or_default(), None)
+                    } else if VimSettings::get_global(cx).use_system_clipboard
+                        == UseSystemClipboard::Never
                         || VimSettings::get_global(cx).use_system_clipboard
                             == UseSystemClipboard::OnYank
                             && !system_clipboard_is_newer(vim, cx)
@@ -316,6 +320,41 @@ mod test {
             the lazy dog"});
     }
 
+    #[gpui::test]
+    async fn test_registers(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        // uppercase registers append, and the black hole register keeps nothing
+        cx.set_state(
+            indoc! {"
+            ˇone
+            two
+            three"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("\" a y y j \" shift-a y y j \" _ d d");
+        cx.simulate_keystrokes("\" a p");
+        cx.assert_state(
+            indoc! {"
+            one
+            two
+            ˇone
+            two"},
+            Mode::Normal,
+        );
+
+        // the numbered registers hold the deletes
+        cx.simulate_keystrokes("d d \" 1 p");
+        cx.assert_state(
+            indoc! {"
+            one
+            two
+            two
+            ˇone"},
+            Mode::Normal,
+        );
+    }
+
     #[gpui::test]
     async fn test_yank_system_clipboard_never(cx: &mut gpui::TestAppContext) {
         let mut cx = VimTestContext::new(cx, true).await;
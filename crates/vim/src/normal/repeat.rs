@@ -15,6 +15,13 @@ fn should_replay(action: &Box<dyn Action>) -> bool {
     if editor::actions::ShowCharacterPalette.partial_eq(&**action) {
         return false;
     }
+    // the text accepted from completions is replayed as an insertion
+    if action.as_any().is::<editor::actions::ConfirmCompletion>()
+        || editor::actions::AcceptInlineCompletion.partial_eq(&**action)
+        || editor::actions::AcceptPartialInlineCompletion.partial_eq(&**action)
+    {
+        return false;
+    }
     true
 }
 
@@ -35,7 +42,7 @@ fn repeatable_insert(action: &ReplayableAction) -> Option<Box<dyn Action>> {
                 None
             }
         }
-        ReplayableAction::Insertion { .. } => None,
+        ReplayableAction::Insertion { .. } | ReplayableAction::Snippet { .. } => None,
     }
 }
 
@@ -193,6 +200,12 @@ pub(crate) fn repeat(cx: &mut WindowContext, from_insert_mode: bool) {
                 } => editor.update(&mut cx, |editor, cx| {
                     editor.replay_insert_event(&text, utf16_range_to_replace.clone(), cx)
                 }),
+                ReplayableAction::Snippet {
+                    snippet,
+                    utf16_range_to_replace,
+                } => editor.update(&mut cx, |editor, cx| {
+                    editor.replay_snippet_event(snippet, utf16_range_to_replace, cx)
+                }),
             }?
         }
         editor.update(&mut cx, |editor, _| {
@@ -209,7 +222,7 @@ mod test {
     use futures::StreamExt;
     use indoc::indoc;
 
-    use gpui::ViewInputHandler;
+    use gpui::{KeyBinding, ViewInputHandler};
 
     use crate::{
         state::Mode,
@@ -341,6 +354,120 @@ mod test {
         );
     }
 
+    #[gpui::test]
+    async fn test_repeat_snippet_completion(cx: &mut gpui::TestAppContext) {
+        VimTestContext::init(cx);
+        let cx = EditorLspTestContext::new_rust(
+            lsp::ServerCapabilities {
+                completion_provider: Some(lsp::CompletionOptions {
+                    trigger_characters: Some(vec![".".to_string()]),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            cx,
+        )
+        .await;
+        let mut cx = VimTestContext::new_with_lsp(cx, true);
+
+        cx.set_state(
+            indoc! {"
+            onˇe
+            two
+        "},
+            Mode::Normal,
+        );
+
+        let mut request =
+            cx.handle_request::<lsp::request::Completion, _, _>(move |_, params, _| async move {
+                let position = params.text_document_position.position;
+                Ok(Some(lsp::CompletionResponse::Array(vec![
+                    lsp::CompletionItem {
+                        label: "call".to_string(),
+                        insert_text_format: Some(lsp::InsertTextFormat::SNIPPET),
+                        text_edit: Some(lsp::CompletionTextEdit::Edit(lsp::TextEdit {
+                            range: lsp::Range::new(position, position),
+                            new_text: "call($1)$0".to_string(),
+                        })),
+                        ..Default::default()
+                    },
+                ])))
+            });
+        cx.simulate_keystrokes("a .");
+        request.next().await;
+        cx.condition(|editor, _| editor.context_menu_visible())
+            .await;
+        cx.simulate_keystrokes("enter x tab ! escape");
+        cx.assert_state(
+            indoc! {"
+                one.call(x)ˇ!
+                two
+            "},
+            Mode::Normal,
+        );
+
+        // the snippet's tabstops are visited again when repeating
+        cx.simulate_keystrokes("j .");
+        cx.assert_state(
+            indoc! {"
+                one.call(x)!
+                two.call(x)ˇ!
+            "},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_repeat_surrounds(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state("ˇhello world\n", Mode::Normal);
+        cx.simulate_keystrokes("y s i w )");
+        cx.assert_state("ˇ(hello) world\n", Mode::Normal);
+        cx.simulate_keystrokes("$ .");
+        cx.assert_state("(hello) ˇ(world)\n", Mode::Normal);
+
+        cx.set_state("(ˇa) (b)\n", Mode::Normal);
+        cx.simulate_keystrokes("c s ( ]");
+        cx.assert_state("ˇ[a] (b)\n", Mode::Normal);
+        cx.simulate_keystrokes("f b .");
+        cx.assert_state("[a] ˇ[b]\n", Mode::Normal);
+
+        cx.set_state("(ˇa) (b)\n", Mode::Normal);
+        cx.simulate_keystrokes("d s (");
+        cx.assert_state("ˇa (b)\n", Mode::Normal);
+        cx.simulate_keystrokes("f b .");
+        cx.assert_state("a ˇb\n", Mode::Normal);
+    }
+
+    #[gpui::test]
+    async fn test_repeat_editor_action(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+        cx.update(|cx| {
+            cx.bind_keys([KeyBinding::new(
+                "g x",
+                editor::actions::DeleteLine,
+                Some("vim_mode == normal"),
+            )])
+        });
+
+        cx.set_state("ˇone\ntwo\nthree\nfour\n", Mode::Normal);
+        cx.simulate_keystrokes("g x");
+        assert_eq!(cx.buffer_text(), "two\nthree\nfour\n");
+        cx.simulate_keystrokes(".");
+        assert_eq!(cx.buffer_text(), "three\nfour\n");
+
+        // moving around doesn't replace the action to repeat
+        cx.simulate_keystrokes("l h .");
+        assert_eq!(cx.buffer_text(), "four\n");
+
+        // neither does undoing
+        cx.simulate_keystrokes("u");
+        assert_eq!(cx.buffer_text(), "three\nfour\n");
+        cx.simulate_keystrokes(".");
+        assert_eq!(cx.buffer_text(), "four\n");
+    }
+
     #[gpui::test]
     async fn test_repeat_visual(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
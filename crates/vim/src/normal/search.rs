@@ -1,13 +1,16 @@
-use std::{ops::Range, sync::OnceLock, time::Duration};
+use std::ops::Range;
 
-use gpui::{actions, impl_actions, ViewContext};
+use editor::{scroll::Autoscroll, Anchor, Editor};
+use gpui::{actions, impl_actions, AppContext, PromptLevel, View, ViewContext, WindowContext};
 use language::Point;
-use regex::Regex;
+use multi_buffer::ToPoint;
+use regex::RegexBuilder;
 use search::{buffer_search, BufferSearchBar, SearchOptions};
 use serde_derive::Deserialize;
 use workspace::{searchable::Direction, Workspace};
 
 use crate::{
+    global::{line_text, range_rows, split_range, CommandRange},
     motion::{search_motion, Motion},
     normal::move_cursor,
     state::{Mode, SearchState},
@@ -45,13 +48,69 @@ pub struct ReplaceCommand {
     pub query: String,
 }
 
-#[derive(Debug, Default)]
-struct Replacement {
-    search: String,
-    replacement: String,
-    should_replace_all: bool,
-    is_case_sensitive: bool,
-    range: Option<Range<usize>>,
+/// A substitution of `:s`, with its pattern and replacement in the syntax of the regex crate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replacement {
+    pub search: String,
+    pub replacement: String,
+    pub range: Option<CommandRange>,
+    /// Whether every match in a line is replaced rather than the first, with the `g` flag.
+    pub replace_every_match: bool,
+    /// Whether each replacement is confirmed first, with the `c` flag.
+    pub confirm: bool,
+    pub is_case_sensitive: bool,
+}
+
+impl Default for Replacement {
+    fn default() -> Self {
+        Self {
+            search: String::new(),
+            replacement: String::new(),
+            range: None,
+            replace_every_match: false,
+            confirm: false,
+            is_case_sensitive: true,
+        }
+    }
+}
+
+impl Replacement {
+    /// The substitution repeated on the range, with its own flags when they start with `&`.
+    fn repeat(&self, range: Option<CommandRange>, flags: &str) -> Self {
+        let mut replacement = Self {
+            search: self.search.clone(),
+            replacement: self.replacement.clone(),
+            range,
+            ..Default::default()
+        };
+        let flags = match flags.strip_prefix('&') {
+            Some(flags) => {
+                replacement.copy_flags(self);
+                flags
+            }
+            None => flags,
+        };
+        replacement.apply_flags(flags);
+        replacement
+    }
+
+    fn copy_flags(&mut self, other: &Self) {
+        self.replace_every_match = other.replace_every_match;
+        self.confirm = other.confirm;
+        self.is_case_sensitive = other.is_case_sensitive;
+    }
+
+    fn apply_flags(&mut self, flags: &str) {
+        for c in flags.chars() {
+            match c {
+                'g' => self.replace_every_match = true,
+                'c' => self.confirm = true,
+                'i' => self.is_case_sensitive = false,
+                'I' => self.is_case_sensitive = true,
+                _ => {}
+            }
+        }
+    }
 }
 
 actions!(vim, [SearchSubmit, MoveToNextMatch, MoveToPrevMatch]);
@@ -60,11 +119,6 @@ impl_actions!(
     [FindCommand, ReplaceCommand, Search, MoveToPrev, MoveToNext]
 );
 
-static RANGE_REGEX: OnceLock<Regex> = OnceLock::new();
-pub(crate) fn range_regex() -> &'static Regex {
-    RANGE_REGEX.get_or_init(|| Regex::new(r"^(\d+),(\d+)s(.*)").unwrap())
-}
-
 pub(crate) fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     workspace.register_action(move_to_next);
     workspace.register_action(move_to_prev);
@@ -340,115 +394,242 @@ fn replace_command(
     action: &ReplaceCommand,
     cx: &mut ViewContext<Workspace>,
 ) {
-    let replacement = parse_replace_all(&action.query);
-    let pane = workspace.active_pane().clone();
-    let mut editor = Vim::read(cx)
-        .active_editor
-        .as_ref()
-        .and_then(|editor| editor.upgrade());
-    if let Some(range) = &replacement.range {
-        if let Some(editor) = editor.as_mut() {
-            editor.update(cx, |editor, cx| {
-                let snapshot = &editor.snapshot(cx).buffer_snapshot;
-                let range = snapshot
-                    .anchor_before(Point::new(range.start.saturating_sub(1) as u32, 0))
-                    ..snapshot.anchor_before(Point::new(range.end as u32, 0));
-                editor.set_search_within_ranges(&[range], cx)
-            })
+    let search_bar_query = workspace
+        .active_pane()
+        .read(cx)
+        .toolbar()
+        .read(cx)
+        .item_of_type::<BufferSearchBar>()
+        .map(|search_bar| search_bar.read(cx).query(cx));
+    Vim::update(cx, |vim, cx| {
+        let previous = vim.workspace_state.last_substitution.as_ref();
+        let Some(mut replacement) = parse_replace_all(&action.query, previous) else {
+            return;
+        };
+        // An empty pattern is the last one searched for.
+        if replacement.search.is_empty() {
+            replacement.search = search_bar_query
+                .filter(|query| !query.is_empty())
+                .or_else(|| previous.map(|previous| previous.search.clone()))
+                .unwrap_or_default();
         }
-    }
-    pane.update(cx, |pane, cx| {
-        let Some(search_bar) = pane.toolbar().read(cx).item_of_type::<BufferSearchBar>() else {
+        if replacement.search.is_empty() {
+            return;
+        }
+        vim.workspace_state.last_substitution = Some(replacement.clone());
+
+        // In visual mode, the substitution applies to the selected lines.
+        let range = replacement.range.or_else(|| {
+            vim.state()
+                .mode
+                .is_visual()
+                .then_some(CommandRange::Selection)
+        });
+        let Some((editor, rows)) = vim.update_active_editor(cx, |_, editor, cx| {
+            (cx.view().clone(), range_rows(range, editor, cx))
+        }) else {
             return;
         };
-        let search = search_bar.update(cx, |search_bar, cx| {
-            if !search_bar.show(cx) {
-                return None;
-            }
+        vim.switch_mode(Mode::Normal, false, cx);
+        substitute(vim, editor, &rows, &replacement, cx);
+    })
+}
 
-            let mut options = SearchOptions::REGEX;
-            if replacement.is_case_sensitive {
-                options.set(SearchOptions::CASE_SENSITIVE, true)
+/// Replaces the matches of the substitution in the lines, asking before each one with the `c`
+/// flag. The cursor ends on the last line substituted.
+pub(crate) fn substitute(
+    vim: &mut Vim,
+    editor: View<Editor>,
+    rows: &[u32],
+    replacement: &Replacement,
+    cx: &mut WindowContext,
+) {
+    let Ok(regex) = RegexBuilder::new(&replacement.search)
+        .case_insensitive(!replacement.is_case_sensitive)
+        .build()
+    else {
+        return;
+    };
+    let (matches, last_line) = editor.update(cx, |editor, cx| {
+        let snapshot = editor.buffer().read(cx).snapshot(cx);
+        let mut matches = Vec::new();
+        for &row in rows {
+            let line = line_text(&snapshot, row);
+            for captures in regex.captures_iter(&line) {
+                let range = captures.get(0).unwrap().range();
+                let mut text = String::new();
+                captures.expand(&replacement.replacement, &mut text);
+                let start = snapshot.anchor_after(Point::new(row, range.start as u32));
+                let end = if range.is_empty() {
+                    start
+                } else {
+                    snapshot.anchor_before(Point::new(row, range.end as u32))
+                };
+                matches.push((start..end, text));
+                if !replacement.replace_every_match {
+                    break;
+                }
             }
-            let search = if replacement.search == "" {
-                search_bar.query(cx)
-            } else {
-                replacement.search
-            };
-
-            search_bar.set_replacement(Some(&replacement.replacement), cx);
-            Some(search_bar.search(&search, Some(options), cx))
+        }
+        let last_line = matches.last().map(|(range, _)| {
+            snapshot.anchor_before(Point::new(range.start.to_point(&snapshot).row, 0))
         });
-        let Some(search) = search else { return };
-        let search_bar = search_bar.downgrade();
-        cx.spawn(|_, mut cx| async move {
-            search.await?;
-            search_bar.update(&mut cx, |search_bar, cx| {
-                if replacement.should_replace_all {
-                    search_bar.select_last_match(cx);
-                    search_bar.replace_all(&Default::default(), cx);
-                    if let Some(editor) = editor {
-                        cx.spawn(|_, mut cx| async move {
-                            cx.background_executor()
-                                .timer(Duration::from_millis(200))
-                                .await;
-                            editor
-                                .update(&mut cx, |editor, cx| editor.clear_search_within_ranges(cx))
-                                .ok();
-                        })
-                        .detach();
-                    }
-                    Vim::update(cx, |vim, cx| {
-                        move_cursor(
-                            vim,
-                            Motion::StartOfLine {
-                                display_lines: false,
-                            },
-                            None,
+        (matches, last_line)
+    });
+    let Some(last_line) = last_line else {
+        return;
+    };
+
+    if replacement.confirm {
+        confirm_replacements(editor, matches, cx);
+        return;
+    }
+    editor.update(cx, |editor, cx| {
+        editor.transact(cx, |editor, cx| editor.edit(matches, cx));
+    });
+    move_to_substituted_line(vim, &editor, last_line, cx);
+}
+
+struct SubstituteConfirmation;
+
+/// Replaces the matches one after the other, asking before each one whether to replace it, skip
+/// it, replace it and all the ones left, replace it and stop, or stop.
+fn confirm_replacements(
+    editor: View<Editor>,
+    matches: Vec<(Range<Anchor>, String)>,
+    cx: &mut WindowContext,
+) {
+    cx.spawn(|mut cx| async move {
+        let mut replace_all = false;
+        let mut last_line = None;
+        for (range, text) in matches {
+            let (replace, stop) = if replace_all {
+                (true, false)
+            } else {
+                let answer = cx.update(|cx| {
+                    editor.update(cx, |editor, cx| {
+                        editor.highlight_background::<SubstituteConfirmation>(
+                            &[range.clone()],
+                            |theme| theme.search_match_background,
                             cx,
-                        )
-                    })
+                        );
+                        editor.change_selections(Some(Autoscroll::center()), cx, |s| {
+                            s.select_anchor_ranges([range.start..range.start])
+                        });
+                    });
+                    cx.prompt(
+                        PromptLevel::Info,
+                        &format!("Replace with \"{text}\"?"),
+                        None,
+                        &["Yes", "No", "All", "Last", "Quit"],
+                    )
+                })?;
+                match answer.await {
+                    Ok(0) => (true, false),
+                    Ok(1) => (false, false),
+                    Ok(2) => {
+                        replace_all = true;
+                        (true, false)
+                    }
+                    Ok(3) => (true, true),
+                    _ => (false, true),
                 }
-            })?;
-            anyhow::Ok(())
+            };
+            if replace {
+                cx.update(|cx| {
+                    editor.update(cx, |editor, cx| {
+                        let snapshot = editor.buffer().read(cx).snapshot(cx);
+                        let row = range.start.to_point(&snapshot).row;
+                        last_line = Some(snapshot.anchor_before(Point::new(row, 0)));
+                        editor.transact(cx, |editor, cx| editor.edit([(range, text)], cx));
+                    })
+                })?;
+            }
+            if stop {
+                break;
+            }
+        }
+
+        cx.update(|cx| {
+            editor.update(cx, |editor, cx| {
+                editor.clear_background_highlights::<SubstituteConfirmation>(cx);
+            });
+            Vim::update(cx, |vim, cx| match last_line {
+                Some(last_line) => move_to_substituted_line(vim, &editor, last_line, cx),
+                None => vim.switch_mode(Mode::Normal, false, cx),
+            });
         })
-        .detach_and_log_err(cx);
     })
+    .detach_and_log_err(cx);
 }
 
-// convert a vim query into something more usable by zed.
+fn move_to_substituted_line(
+    vim: &mut Vim,
+    editor: &View<Editor>,
+    line: Anchor,
+    cx: &mut WindowContext,
+) {
+    editor.update(cx, |editor, cx| {
+        editor.change_selections(None, cx, |s| s.select_anchor_ranges([line..line]));
+    });
+    move_cursor(
+        vim,
+        Motion::FirstNonWhitespace {
+            display_lines: false,
+        },
+        None,
+        cx,
+    );
+}
+
+// convert a vim substitution into something more usable by zed.
 // we don't attempt to fully convert between the two regex syntaxes,
-// but we do flip \( and \) to ( and ) (and vice-versa) in the pattern,
-// and convert \0..\9 to $0..$9 in the replacement so that common idioms work.
-fn parse_replace_all(query: &str) -> Replacement {
-    let mut chars = query.chars();
-    let mut range = None;
-    let maybe_line_range_and_rest: Option<(Range<usize>, &str)> =
-        range_regex().captures(query).map(|captures| {
-            (
-                captures.get(1).unwrap().as_str().parse().unwrap()
-                    ..captures.get(2).unwrap().as_str().parse().unwrap(),
-                captures.get(3).unwrap().as_str(),
-            )
-        });
-    if maybe_line_range_and_rest.is_some() {
-        let (line_range, rest) = maybe_line_range_and_rest.unwrap();
-        range = Some(line_range);
-        chars = rest.chars();
-    } else if Some('%') != chars.next() || Some('s') != chars.next() {
-        return Replacement::default();
+// but we do flip \( and \) to ( and ) (and vice-versa) and convert \< and \> to \b in the
+// pattern, and convert \0..\9, &, ~, \r and \t in the replacement so that common idioms work.
+pub(crate) fn parse_replace_all(
+    query: &str,
+    previous: Option<&Replacement>,
+) -> Option<Replacement> {
+    let (range, rest) = split_range(query);
+
+    // `:&` repeats the last substitution, and `:&&` repeats it with its flags.
+    if let Some(flags) = rest.strip_prefix('&') {
+        return Some(previous?.repeat(range, flags));
+    }
+
+    let name_len = rest
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(rest.len());
+    let (name, rest) = rest.split_at(name_len);
+    if !matches!(
+        name,
+        "s" | "su"
+            | "sub"
+            | "subs"
+            | "subst"
+            | "substi"
+            | "substit"
+            | "substitu"
+            | "substitut"
+            | "substitute"
+    ) {
+        return None;
     }
 
-    let Some(delimiter) = chars.next() else {
-        return Replacement::default();
+    let mut chars = rest.chars();
+    let delimiter = match chars.next() {
+        // `:s` without a pattern repeats the last substitution too.
+        None | Some(' ') => return Some(previous?.repeat(range, rest.trim_start())),
+        Some(c) if c.is_alphanumeric() || c.is_whitespace() || matches!(c, '\\' | '"' | '|') => {
+            return None
+        }
+        Some(c) => c,
     };
 
     let mut search = String::new();
     let mut replacement = String::new();
     let mut flags = String::new();
 
-    let mut buffer = &mut search;
-
     let mut escaped = false;
     // 0 - parsing search
     // 1 - parsing replacement
@@ -456,55 +637,82 @@ fn parse_replace_all(query: &str) -> Replacement {
     let mut phase = 0;
 
     for c in chars {
-        if escaped {
+        if phase == 2 {
+            flags.push(c);
+        } else if escaped {
             escaped = false;
-            if phase == 1 && c.is_digit(10) {
-                buffer.push('$')
-            // unescape escaped parens
-            } else if phase == 0 && c == '(' || c == ')' {
-            } else if c != delimiter {
-                buffer.push('\\')
+            if phase == 0 {
+                match c {
+                    // unescape escaped parens
+                    '(' | ')' => search.push(c),
+                    '<' | '>' => search.push_str("\\b"),
+                    c if c == delimiter => search.push(c),
+                    c => {
+                        search.push('\\');
+                        search.push(c);
+                    }
+                }
+            } else {
+                match c {
+                    '0'..='9' => {
+                        replacement.push_str("${");
+                        replacement.push(c);
+                        replacement.push('}');
+                    }
+                    'r' | 'n' => replacement.push('\n'),
+                    't' => replacement.push('\t'),
+                    '$' => replacement.push_str("$$"),
+                    // `\&`, `\~`, `\\` and the delimiter are taken literally
+                    c => replacement.push(c),
+                }
             }
-            buffer.push(c)
         } else if c == '\\' {
             escaped = true;
         } else if c == delimiter {
-            if phase == 0 {
-                buffer = &mut replacement;
-                phase = 1;
-            } else if phase == 1 {
-                buffer = &mut flags;
-                phase = 2;
-            } else {
-                break;
+            phase += 1;
+        } else if phase == 0 {
+            // escape unescaped parens
+            if c == '(' || c == ')' {
+                search.push('\\');
             }
+            search.push(c);
         } else {
-            // escape unescaped parens
-            if phase == 0 && c == '(' || c == ')' {
-                buffer.push('\\')
+            match c {
+                '&' => replacement.push_str("${0}"),
+                '~' => replacement.push_str(previous.map_or("", |previous| &previous.replacement)),
+                '$' => replacement.push_str("$$"),
+                c => replacement.push(c),
             }
-            buffer.push(c)
         }
     }
 
     let mut replacement = Replacement {
         search,
         replacement,
-        should_replace_all: true,
-        is_case_sensitive: true,
         range,
+        ..Default::default()
     };
-
-    for c in flags.chars() {
-        match c {
-            'g' | 'I' => {}
-            'c' | 'n' => replacement.should_replace_all = false,
-            'i' => replacement.is_case_sensitive = false,
-            _ => {}
+    // The `&` flag keeps the flags of the last substitution.
+    let flags = match (flags.strip_prefix('&'), previous) {
+        (Some(flags), Some(previous)) => {
+            replacement.copy_flags(previous);
+            flags
         }
-    }
+        (Some(flags), None) => flags,
+        (None, _) => flags.as_str(),
+    };
+    replacement.apply_flags(flags);
+    Some(replacement)
+}
 
-    replacement
+/// Whether the query is a substitution, for the command palette.
+pub(crate) fn is_substitute_command(query: &str, cx: &AppContext) -> bool {
+    let previous = cx
+        .global::<Vim>()
+        .workspace_state
+        .last_substitution
+        .as_ref();
+    parse_replace_all(query, previous).is_some()
 }
 
 #[cfg(test)]
@@ -515,7 +723,9 @@ mod test {
     use indoc::indoc;
     use search::BufferSearchBar;
 
+    use super::{parse_replace_all, Replacement};
     use crate::{
+        global::CommandRange,
         state::Mode,
         test::{NeovimBackedTestContext, VimTestContext},
     };
@@ -758,4 +968,149 @@ mod test {
                  "
         });
     }
+
+    #[test]
+    fn test_parse_replace_all() {
+        let previous = Replacement {
+            search: "x".to_string(),
+            replacement: "y".to_string(),
+            range: None,
+            replace_every_match: true,
+            confirm: false,
+            is_case_sensitive: false,
+        };
+        assert_eq!(
+            parse_replace_all(r"'<,'>s#\(a\)(\<\#$#\1\r&\&~$#&I", Some(&previous)),
+            Some(Replacement {
+                search: r"(a)\(\b#$".to_string(),
+                replacement: "${1}\n${0}&y$$".to_string(),
+                range: Some(CommandRange::Selection),
+                replace_every_match: true,
+                confirm: false,
+                is_case_sensitive: true,
+            })
+        );
+        assert_eq!(
+            parse_replace_all("2,4&c", Some(&previous)),
+            Some(Replacement {
+                search: "x".to_string(),
+                replacement: "y".to_string(),
+                range: Some(CommandRange::Lines(2, 4)),
+                replace_every_match: false,
+                confirm: true,
+                is_case_sensitive: true,
+            })
+        );
+        assert_eq!(parse_replace_all("&&", None), None);
+        assert_eq!(parse_replace_all("sort", Some(&previous)), None);
+    }
+
+    #[gpui::test]
+    async fn test_substitute(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇaa
+            aa
+            aa"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": s / a / b enter");
+        cx.assert_state(
+            indoc! {"
+            ˇba
+            aa
+            aa"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": 2 , 3 s / a / ( & ) / g enter");
+        cx.assert_state(
+            indoc! {"
+            ba
+            (a)(a)
+            ˇ(a)(a)"},
+            Mode::Normal,
+        );
+        // `&` repeats the last substitution on the line, without its flags.
+        cx.simulate_keystrokes("g g &");
+        cx.assert_state(
+            indoc! {"
+            ˇb(a)
+            (a)(a)
+            (a)(a)"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": % s / b / ~ ! enter");
+        cx.assert_state(
+            indoc! {"
+            ˇ(b)!(a)
+            (a)(a)
+            (a)(a)"},
+            Mode::Normal,
+        );
+
+        cx.set_state(
+            indoc! {"
+            ˇa
+            a
+            a"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("shift-v j : s / a / c enter");
+        cx.assert_state(
+            indoc! {"
+            c
+            ˇc
+            a"},
+            Mode::Normal,
+        );
+    }
+
+    #[gpui::test]
+    async fn test_substitute_with_confirmation(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+            ˇa a
+            a a
+            a"},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes(": % s / a / b / g c enter");
+        cx.run_until_parked();
+        // "Yes"
+        assert!(cx.has_pending_prompt());
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        // "No"
+        cx.simulate_prompt_answer(1);
+        cx.run_until_parked();
+        // "All"
+        cx.simulate_prompt_answer(2);
+        cx.run_until_parked();
+        assert!(!cx.has_pending_prompt());
+        cx.assert_state(
+            indoc! {"
+            b a
+            b b
+            ˇb"},
+            Mode::Normal,
+        );
+
+        cx.simulate_keystrokes(": % s / b / c / g c enter");
+        cx.run_until_parked();
+        // "Last"
+        cx.simulate_prompt_answer(3);
+        cx.run_until_parked();
+        assert!(!cx.has_pending_prompt());
+        cx.assert_state(
+            indoc! {"
+            ˇc a
+            b b
+            b"},
+            Mode::Normal,
+        );
+    }
 }
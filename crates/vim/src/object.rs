@@ -13,7 +13,7 @@ use editor::{
 use itertools::Itertools;
 
 use gpui::{actions, impl_actions, ViewContext, WindowContext};
-use language::{char_kind, BufferSnapshot, CharKind, Point, Selection};
+use language::{char_kind, BufferSnapshot, CharKind, Point, Selection, TextObject};
 use multi_buffer::MultiBufferRow;
 use serde::Deserialize;
 use workspace::Workspace;
@@ -33,6 +33,9 @@ pub enum Object {
     AngleBrackets,
     Argument,
     Tag,
+    Method,
+    Class,
+    Comment,
 }
 
 #[derive(Clone, Deserialize, PartialEq)]
@@ -58,7 +61,10 @@ actions!(
         CurlyBrackets,
         AngleBrackets,
         Argument,
-        Tag
+        Tag,
+        Method,
+        Class,
+        Comment
     ]
 );
 
@@ -96,6 +102,9 @@ pub fn register(workspace: &mut Workspace, _: &mut ViewContext<Workspace>) {
     });
     workspace
         .register_action(|_: &mut Workspace, _: &Argument, cx: _| object(Object::Argument, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Method, cx: _| object(Object::Method, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Class, cx: _| object(Object::Class, cx));
+    workspace.register_action(|_: &mut Workspace, _: &Comment, cx: _| object(Object::Comment, cx));
 }
 
 fn object(object: Object, cx: &mut WindowContext) {
@@ -123,13 +132,22 @@ impl Object {
             | Object::AngleBrackets
             | Object::CurlyBrackets
             | Object::SquareBrackets
-            | Object::Argument => true,
+            | Object::Argument
+            | Object::Method
+            | Object::Class
+            | Object::Comment => true,
         }
     }
 
     pub fn always_expands_both_ways(self) -> bool {
         match self {
-            Object::Word { .. } | Object::Sentence | Object::Paragraph | Object::Argument => false,
+            Object::Word { .. }
+            | Object::Sentence
+            | Object::Paragraph
+            | Object::Argument
+            | Object::Method
+            | Object::Class
+            | Object::Comment => false,
             Object::Quotes
             | Object::BackQuotes
             | Object::DoubleQuotes
@@ -161,7 +179,10 @@ impl Object {
             | Object::AngleBrackets
             | Object::VerticalBars
             | Object::Tag
-            | Object::Argument => Mode::Visual,
+            | Object::Argument
+            | Object::Method
+            | Object::Class
+            | Object::Comment => Mode::Visual,
             Object::Paragraph => Mode::VisualLine,
         }
     }
@@ -208,7 +229,38 @@ impl Object {
             Object::AngleBrackets => {
                 surrounding_markers(map, relative_to, around, self.is_multiline(), '<', '>')
             }
-            Object::Argument => argument(map, relative_to, around),
+            Object::Argument => {
+                let target = if around {
+                    TextObject::AroundArgument
+                } else {
+                    TextObject::InsideArgument
+                };
+                text_object(map, relative_to, target).or_else(|| argument(map, relative_to, around))
+            }
+            Object::Method => {
+                let target = if around {
+                    TextObject::AroundFunction
+                } else {
+                    TextObject::InsideFunction
+                };
+                text_object(map, relative_to, target)
+            }
+            Object::Class => {
+                let target = if around {
+                    TextObject::AroundClass
+                } else {
+                    TextObject::InsideClass
+                };
+                text_object(map, relative_to, target)
+            }
+            Object::Comment => {
+                let target = if around {
+                    TextObject::AroundComment
+                } else {
+                    TextObject::InsideComment
+                };
+                text_object(map, relative_to, target)
+            }
         }
     }
 
@@ -411,6 +463,52 @@ fn around_next_word(
     Some(start..end)
 }
 
+/// Returns the range of the innermost `target` containing `relative_to`, as captured
+/// by the language's `textobjects` query.
+///
+/// When the cursor is within an "around" object, its inside is used even if the cursor
+/// isn't on it, so `dif` works from a function's signature.
+fn text_object(
+    map: &DisplaySnapshot,
+    relative_to: DisplayPoint,
+    target: TextObject,
+) -> Option<Range<DisplayPoint>> {
+    let snapshot = &map.buffer_snapshot;
+    let offset = relative_to.to_offset(map, Bias::Left);
+
+    let excerpt = snapshot.excerpt_containing(offset..offset)?;
+    let buffer = excerpt.buffer();
+    let offset = excerpt.map_offset_to_buffer(offset);
+
+    let matches = buffer
+        .text_object_ranges(offset..offset)
+        .collect::<Vec<_>>();
+    let containing = |object: TextObject| {
+        matches
+            .iter()
+            .filter(|(range, kind)| *kind == object && range.contains(&offset))
+            .map(|(range, _)| range.clone())
+            .min_by_key(|range| range.len())
+    };
+
+    let range = match target.around().and_then(containing) {
+        Some(around) => matches
+            .iter()
+            .filter(|(range, kind)| {
+                *kind == target && around.start <= range.start && range.end <= around.end
+            })
+            .map(|(range, _)| range.clone())
+            .max_by_key(|range| range.len())?,
+        None => containing(target)?,
+    };
+
+    if !excerpt.contains_buffer_range(range.clone()) {
+        return None;
+    }
+    let result = excerpt.map_range_from_buffer(range);
+    Some(result.start.to_display_point(map)..result.end.to_display_point(map))
+}
+
 fn argument(
     map: &DisplaySnapshot,
     relative_to: DisplayPoint,
@@ -1434,6 +1532,48 @@ mod test {
         cx.assert_state("let a = [«test::call(first_arg)ˇ»]", Mode::Visual);
     }
 
+    #[gpui::test]
+    async fn test_syntax_text_objects(cx: &mut gpui::TestAppContext) {
+        let mut cx = VimTestContext::new(cx, true).await;
+
+        cx.set_state(
+            indoc! {"
+                fn boop() {
+                    let a = ˇ1;
+                    let b = 2;
+                }
+            "},
+            Mode::Normal,
+        );
+        cx.simulate_keystrokes("c i f");
+        cx.assert_state(
+            indoc! {"
+                fn boop() {
+                    ˇ
+                }
+            "},
+            Mode::Insert,
+        );
+
+        // The body is selected even from the signature
+        cx.set_state("fn bˇoop() {\n    let a = 1;\n}\n", Mode::Normal);
+        cx.simulate_keystrokes("v i f");
+        cx.assert_state("fn boop() {\n    «let a = 1;ˇ»\n}\n", Mode::Visual);
+
+        cx.set_state("fn bˇoop() {\n    let a = 1;\n}\n", Mode::Normal);
+        cx.simulate_keystrokes("v a f");
+        cx.assert_state("«fn boop() {\n    let a = 1;\n}ˇ»\n", Mode::Visual);
+
+        cx.set_state("struct Foo {\n    a: ˇu8,\n    b: u8\n}\n", Mode::Normal);
+        cx.simulate_keystrokes("v i c");
+        cx.assert_state("struct Foo {\n    «a: u8,\n    b: u8ˇ»\n}\n", Mode::Visual);
+
+        // Nothing happens outside of a function
+        cx.set_state("ˇconst A: u8 = 1;\n", Mode::Normal);
+        cx.simulate_keystrokes("d i f");
+        cx.assert_state("ˇconst A: u8 = 1;\n", Mode::Normal);
+    }
+
     #[gpui::test]
     async fn test_delete_surrounding_character_objects(cx: &mut gpui::TestAppContext) {
         let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -0,0 +1,175 @@
+use std::path::PathBuf;
+
+use anyhow::Context as _;
+use db::{define_connection, query, sqlez_macros::sql};
+use workspace::{WorkspaceDb, WorkspaceId};
+
+define_connection! {
+    // Current schema shape using pseudo-rust syntax:
+    // vim_marks(
+    //   workspace_id: usize,
+    //   mark_name: String,
+    //   path: PathBuf,
+    //   row: u32,
+    //   column: u32,
+    // )
+    //
+    // vim_jumps(
+    //   workspace_id: usize,
+    //   ix: usize,
+    //   path: PathBuf,
+    //   row: u32,
+    //   column: u32,
+    // )
+    //
+    // vim_changes(
+    //   workspace_id: usize,
+    //   path: PathBuf,
+    //   ix: usize,
+    //   row: u32,
+    //   column: u32,
+    // )
+    pub static ref VIM_DB: VimDb<WorkspaceDb> =
+        &[sql!(
+            CREATE TABLE vim_marks (
+                workspace_id INTEGER NOT NULL,
+                mark_name TEXT NOT NULL,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, mark_name, path),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        ),
+        sql!(
+            CREATE TABLE vim_jumps (
+                workspace_id INTEGER NOT NULL,
+                ix INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, ix),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+
+            CREATE TABLE vim_changes (
+                workspace_id INTEGER NOT NULL,
+                path BLOB NOT NULL,
+                ix INTEGER NOT NULL,
+                row INTEGER NOT NULL,
+                column INTEGER NOT NULL,
+                PRIMARY KEY(workspace_id, path, ix),
+                FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+                ON DELETE CASCADE
+                ON UPDATE CASCADE
+            ) STRICT;
+        )];
+}
+
+impl VimDb {
+    query! {
+        pub fn get_marks(workspace_id: WorkspaceId) -> Result<Vec<(String, PathBuf, u32, u32)>> {
+            SELECT mark_name, path, row, column
+            FROM vim_marks
+            WHERE workspace_id = ?
+        }
+    }
+
+    /// Replaces all the marks stored for the given workspace.
+    pub async fn save_marks(
+        &self,
+        workspace_id: WorkspaceId,
+        marks: Vec<(String, PathBuf, u32, u32)>,
+    ) -> anyhow::Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_marks", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM vim_marks WHERE workspace_id = ?
+                ))?(workspace_id)
+                .context("clearing old marks")?;
+                for (mark_name, path, row, column) in marks {
+                    conn.exec_bound(sql!(
+                        INSERT OR REPLACE INTO vim_marks(workspace_id, mark_name, path, row, column)
+                        VALUES (?, ?, ?, ?, ?)
+                    ))?((workspace_id, mark_name, path, row, column))
+                    .context("inserting mark")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn get_jumps(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, u32)>> {
+            SELECT path, row, column
+            FROM vim_jumps
+            WHERE workspace_id = ?
+            ORDER BY ix
+        }
+    }
+
+    /// Replaces the jumplist stored for the given workspace.
+    pub async fn save_jumps(
+        &self,
+        workspace_id: WorkspaceId,
+        jumps: Vec<(PathBuf, u32, u32)>,
+    ) -> anyhow::Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_jumps", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM vim_jumps WHERE workspace_id = ?
+                ))?(workspace_id)
+                .context("clearing old jumps")?;
+                for (ix, (path, row, column)) in jumps.into_iter().enumerate() {
+                    conn.exec_bound(sql!(
+                        INSERT INTO vim_jumps(workspace_id, ix, path, row, column)
+                        VALUES (?, ?, ?, ?, ?)
+                    ))?((workspace_id, ix, path, row, column))
+                    .context("inserting jump")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+
+    query! {
+        pub fn get_changes(workspace_id: WorkspaceId) -> Result<Vec<(PathBuf, u32, u32)>> {
+            SELECT path, row, column
+            FROM vim_changes
+            WHERE workspace_id = ?
+            ORDER BY path, ix
+        }
+    }
+
+    /// Replaces the changelist stored for the given file in the workspace.
+    pub async fn save_changes(
+        &self,
+        workspace_id: WorkspaceId,
+        path: PathBuf,
+        changes: Vec<(u32, u32)>,
+    ) -> anyhow::Result<()> {
+        self.write(move |conn| {
+            conn.with_savepoint("save_changes", || {
+                conn.exec_bound(sql!(
+                    DELETE FROM vim_changes WHERE workspace_id = ? AND path = ?
+                ))?((workspace_id, path.clone()))
+                .context("clearing old changes")?;
+                for (ix, (row, column)) in changes.into_iter().enumerate() {
+                    conn.exec_bound(sql!(
+                        INSERT INTO vim_changes(workspace_id, path, ix, row, column)
+                        VALUES (?, ?, ?, ?, ?)
+                    ))?((workspace_id, path.clone(), ix, row, column))
+                    .context("inserting change")?;
+                }
+                Ok(())
+            })
+        })
+        .await
+    }
+}
@@ -1,12 +1,14 @@
-use std::{fmt::Display, ops::Range, sync::Arc};
+use std::{fmt::Display, ops::Range, path::Path, sync::Arc};
 
+use crate::normal::search::Replacement;
 use crate::surrounds::SurroundsType;
 use crate::{motion::Motion, object::Object};
 use collections::HashMap;
 use editor::Anchor;
-use gpui::{Action, KeyContext};
-use language::{CursorShape, Selection, TransactionId};
+use gpui::{Action, EntityId, KeyContext};
+use language::{CursorShape, Point, Selection, TransactionId};
 use serde::{Deserialize, Serialize};
+use snippet::Snippet;
 use workspace::searchable::Direction;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
@@ -63,6 +65,10 @@ pub enum Operator {
     Jump { line: bool },
     Indent,
     Outdent,
+    Fold,
+    RecordRegister,
+    ReplayRegister,
+    Register,
 }
 
 #[derive(Default, Clone)]
@@ -118,8 +124,56 @@ pub struct WorkspaceState {
     pub recorded_count: Option<usize>,
     pub recorded_actions: Vec<ReplayableAction>,
     pub recorded_selection: RecordedSelection,
+    /// The last action from outside of vim typed in normal mode, recorded for `.` once
+    /// it's seen to edit the buffer.
+    pub pending_editor_action: Option<ReplayableAction>,
 
     pub registers: HashMap<String, String>,
+    /// The register chosen with `"` for the next yank, delete or paste.
+    pub selected_register: Option<char>,
+    /// The register of the macro replayed last, replayed again by `@@`.
+    pub last_replayed_register: Option<Arc<str>>,
+    /// The last `:s`, repeated by `:&` and `&`.
+    pub last_substitution: Option<Replacement>,
+
+    /// The marks from `A` to `Z`, which can be jumped to from any file.
+    pub global_marks: HashMap<String, FileMark>,
+    /// The marks from `a` to `z` set in each file, restored in the editors opening it.
+    pub file_marks: HashMap<Arc<Path>, HashMap<String, Point>>,
+
+    /// The jumplist of each pane, which is vim's window.
+    pub jump_lists: HashMap<EntityId, JumpList>,
+    /// The jumplist changed last, which new panes start with and which is kept across restarts.
+    pub latest_jump_list: JumpList,
+    /// Whether `ctrl-o` or `ctrl-i` is opening another file, which isn't a jump of its own.
+    pub navigating_jump_list: bool,
+    /// The editor and cursor as vim last left them, to tell vim's motions from Zed's jumps.
+    pub vim_cursor: Option<(EntityId, Anchor)>,
+    /// The changelist of each file, restored in the editors opening it.
+    pub file_change_lists: HashMap<Arc<Path>, Vec<Point>>,
+}
+
+/// A position in a jumplist. The buffer anchor follows edits while the file is open, and the
+/// point is used once it has been closed.
+#[derive(Clone, Debug)]
+pub struct Jump {
+    pub path: Arc<Path>,
+    pub point: Point,
+    pub anchor: Option<language::Anchor>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct JumpList {
+    pub jumps: Vec<Jump>,
+    /// The index of the jump moved to by `ctrl-o` or `ctrl-i`, or `None` past the newest jump.
+    pub position: Option<usize>,
+}
+
+/// A mark kept by the path of its file, so that it outlives the editors showing it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileMark {
+    pub path: Arc<Path>,
+    pub point: Point,
 }
 
 #[derive(Debug)]
@@ -129,6 +183,10 @@ pub enum ReplayableAction {
         text: Arc<str>,
         utf16_range_to_replace: Option<Range<isize>>,
     },
+    Snippet {
+        snippet: Snippet,
+        utf16_range_to_replace: Option<Range<isize>>,
+    },
 }
 
 impl Clone for ReplayableAction {
@@ -142,6 +200,13 @@ impl Clone for ReplayableAction {
                 text: text.clone(),
                 utf16_range_to_replace: utf16_range_to_replace.clone(),
             },
+            Self::Snippet {
+                snippet,
+                utf16_range_to_replace,
+            } => Self::Snippet {
+                snippet: snippet.clone(),
+                utf16_range_to_replace: utf16_range_to_replace.clone(),
+            },
         }
     }
 }
@@ -270,6 +335,10 @@ impl Operator {
             Operator::Jump { line: false } => "`",
             Operator::Indent => ">",
             Operator::Outdent => "<",
+            Operator::Fold => "zf",
+            Operator::RecordRegister => "q",
+            Operator::ReplayRegister => "@",
+            Operator::Register => "\"",
         }
     }
 
@@ -279,6 +348,9 @@ impl Operator {
             Operator::FindForward { .. }
             | Operator::Mark
             | Operator::Jump { .. }
+            | Operator::RecordRegister
+            | Operator::ReplayRegister
+            | Operator::Register
             | Operator::FindBackward { .. }
             | Operator::Replace
             | Operator::AddSurrounds { target: Some(_) }
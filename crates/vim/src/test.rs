@@ -2,7 +2,10 @@ mod neovim_backed_test_context;
 mod neovim_connection;
 mod vim_test_context;
 
-use std::time::Duration;
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
 
 use command_palette::CommandPalette;
 use editor::{display_map::DisplayRow, DisplayPoint};
@@ -12,9 +15,14 @@ pub use neovim_backed_test_context::*;
 pub use vim_test_context::*;
 
 use indoc::indoc;
+use multi_buffer::MultiBufferRow;
 use search::BufferSearchBar;
 
-use crate::{insert::NormalBefore, motion, state::Mode, ModeIndicator};
+use crate::{
+    insert::NormalBefore, motion, state::Mode, ModeIndicator, VimMapping, VimMappingSettings,
+    VimMappings,
+};
+use settings::{Settings, SettingsStore};
 
 #[gpui::test]
 async fn test_initially_disabled(cx: &mut gpui::TestAppContext) {
@@ -651,6 +659,78 @@ async fn test_folds_panic(cx: &mut gpui::TestAppContext) {
         ˇ"});
 }
 
+#[gpui::test]
+async fn test_fold_commands(cx: &mut gpui::TestAppContext) {
+    let mut cx = VimTestContext::new(cx, true).await;
+    let folded_rows = |cx: &mut VimTestContext| {
+        cx.update_editor(|editor, cx| {
+            let snapshot = editor.snapshot(cx);
+            (0..=snapshot.max_buffer_row().0)
+                .filter(|row| snapshot.is_line_folded(MultiBufferRow(*row)))
+                .collect::<Vec<_>>()
+        })
+    };
+
+    cx.set_state(
+        indoc! {"
+            ˇfn boop() {
+                barp()
+            }
+
+            fn beep() {
+                bazp()
+            }
+        "},
+        Mode::Normal,
+    );
+
+    // za closes and reopens the fold under the cursor
+    cx.simulate_keystrokes("z a");
+    assert_eq!(folded_rows(&mut cx), [0, 1]);
+    cx.simulate_keystrokes("z a");
+    assert_eq!(folded_rows(&mut cx), Vec::<u32>::new());
+
+    // zj and zk move between folds
+    cx.simulate_keystrokes("z j");
+    cx.assert_state(
+        indoc! {"
+            fn boop() {
+                barp()
+            }
+
+            ˇfn beep() {
+                bazp()
+            }
+        "},
+        Mode::Normal,
+    );
+    cx.simulate_keystrokes("z k");
+    cx.assert_state(
+        indoc! {"
+            fn boop() {
+            ˇ    barp()
+            }
+
+            fn beep() {
+                bazp()
+            }
+        "},
+        Mode::Normal,
+    );
+
+    // zf folds the lines covered by a motion
+    cx.simulate_keystrokes("z f j");
+    assert_eq!(folded_rows(&mut cx), [1, 2]);
+    cx.simulate_keystrokes("z shift-r");
+    assert_eq!(folded_rows(&mut cx), Vec::<u32>::new());
+
+    // zM closes all the folds, zR opens them again
+    cx.simulate_keystrokes("z shift-m");
+    assert_eq!(folded_rows(&mut cx), [0, 1, 4, 5]);
+    cx.simulate_keystrokes("z shift-r");
+    assert_eq!(folded_rows(&mut cx), Vec::<u32>::new());
+}
+
 #[gpui::test]
 async fn test_clear_counts(cx: &mut gpui::TestAppContext) {
     let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -990,6 +1070,55 @@ async fn test_remap(cx: &mut gpui::TestAppContext) {
     cx.assert_state("12ˇ 34", Mode::Normal);
 }
 
+#[gpui::test]
+async fn test_mappings(cx: &mut gpui::TestAppContext) {
+    let mut cx = VimTestContext::new(cx, true).await;
+
+    cx.update_global(|store: &mut SettingsStore, cx| {
+        store.update_user_settings::<VimMappingSettings>(cx, |s| {
+            s.leader = Some("<space>".to_string());
+            s.mappings = Some(VimMappings {
+                normal: [("<leader>x".to_string(), VimMapping::Keys("dd".to_string()))].into(),
+                visual: [(
+                    "<leader>u".to_string(),
+                    VimMapping::Action {
+                        action: serde_json::json!("vim::ConvertToUpperCase"),
+                    },
+                )]
+                .into(),
+                insert: [("jk".to_string(), VimMapping::Keys("<Esc>".to_string()))].into(),
+                operator_pending: [("<leader>l".to_string(), VimMapping::Keys("$".to_string()))]
+                    .into(),
+            });
+        });
+    });
+    cx.update(|cx| {
+        VimMappingSettings::get_global(cx)
+            .clone()
+            .bind_keys(cx)
+            .unwrap()
+    });
+
+    // mappings can expand to vim keys
+    cx.set_state("one\ntˇwo\nthree", Mode::Normal);
+    cx.simulate_keystrokes("space x");
+    cx.assert_state("one\nˇthree", Mode::Normal);
+
+    // or to zed actions
+    cx.set_state("«oneˇ» two", Mode::Visual);
+    cx.simulate_keystrokes("space u");
+    cx.assert_state("ˇONE two", Mode::Normal);
+
+    // and only apply in their own mode
+    cx.set_state("ˇone", Mode::Normal);
+    cx.simulate_keystrokes("i space x j k");
+    cx.assert_state(" ˇxone", Mode::Normal);
+
+    cx.set_state("one ˇtwo three", Mode::Normal);
+    cx.simulate_keystrokes("d space l");
+    cx.assert_state("one ˇ", Mode::Normal);
+}
+
 #[gpui::test]
 async fn test_undo(cx: &mut gpui::TestAppContext) {
     let mut cx = NeovimBackedTestContext::new(cx).await;
@@ -1165,3 +1294,32 @@ async fn test_caret_mark(cx: &mut TestAppContext) {
     "
     });
 }
+
+#[gpui::test]
+async fn test_global_marks(cx: &mut TestAppContext) {
+    let mut cx = VimTestContext::new(cx, true).await;
+
+    cx.set_state("one\n  tˇwo\nthree", Mode::Normal);
+    cx.simulate_keystrokes("m A");
+
+    let fs = cx.workspace(|workspace, cx| workspace.project().read(cx).fs().clone());
+    fs.as_fake()
+        .insert_file("/root/dir/other.rs", b"other\n".to_vec())
+        .await;
+    let open = cx.workspace(|workspace, cx| {
+        workspace.open_abs_path(PathBuf::from("/root/dir/other.rs"), true, cx)
+    });
+    open.await.unwrap();
+    cx.run_until_parked();
+
+    cx.simulate_keystrokes("' A");
+    cx.run_until_parked();
+    cx.workspace(|workspace, cx| {
+        let project_path = workspace.active_item(cx).unwrap().project_path(cx).unwrap();
+        assert_eq!(project_path.path.as_ref(), Path::new("dir/file.rs"));
+    });
+    cx.assert_state("one\n  ˇtwo\nthree", Mode::Normal);
+
+    cx.simulate_keystrokes("j ` A");
+    cx.assert_state("one\n  tˇwo\nthree", Mode::Normal);
+}
@@ -5,6 +5,7 @@ use gpui::{ClipboardItem, ViewContext};
 use language::{CharKind, Point};
 use multi_buffer::MultiBufferRow;
 use settings::Settings;
+use workspace::ClipboardHistory;
 
 use crate::{state::Mode, UseSystemClipboard, Vim, VimSettings};
 
@@ -102,9 +103,28 @@ fn copy_selections_content_internal(
         }
     }
 
+    let register = vim.workspace_state.selected_register.take();
+    // The black hole register discards the text.
+    if register == Some('_') {
+        return;
+    }
+    let item = ClipboardItem::new(text.clone()).with_metadata(clipboard_selections);
+    // Like vim's numbered registers, which are read from the history, it doesn't get the text
+    // yanked or deleted into a named register.
+    if !register.is_some_and(|register| register.is_ascii_alphabetic()) {
+        if is_yank {
+            ClipboardHistory::record(item.clone(), cx);
+        } else {
+            ClipboardHistory::record_cut(item.clone(), cx);
+        }
+    }
+
     let setting = VimSettings::get_global(cx).use_system_clipboard;
-    if setting == UseSystemClipboard::Always || setting == UseSystemClipboard::OnYank && is_yank {
-        cx.write_to_clipboard(ClipboardItem::new(text.clone()).with_metadata(clipboard_selections));
+    if setting == UseSystemClipboard::Always
+        || setting == UseSystemClipboard::OnYank && is_yank
+        || matches!(register, Some('+' | '*'))
+    {
+        cx.write_to_clipboard(item);
         vim.workspace_state
             .registers
             .insert(".system.".to_string(), text.clone());
@@ -116,6 +136,16 @@ fn copy_selections_content_internal(
                 .unwrap_or_default(),
         );
     }
+    if let Some(register) = register.filter(char::is_ascii_alphabetic) {
+        let registers = &mut vim.workspace_state.registers;
+        let name = register.to_ascii_lowercase().to_string();
+        // Uppercase registers append to their lowercase ones.
+        if register.is_ascii_uppercase() {
+            registers.entry(name).or_default().push_str(&text);
+        } else {
+            registers.insert(name, text.clone());
+        }
+    }
     vim.workspace_state.registers.insert("\"".to_string(), text);
     if !is_yank || vim.state().mode == Mode::Visual {
         return;
@@ -6,11 +6,15 @@ mod test;
 mod change_list;
 mod command;
 mod editor_events;
+mod global;
 mod insert;
+mod jump_list;
+mod mapping;
 mod mode_indicator;
 mod motion;
 mod normal;
 mod object;
+mod persistence;
 mod replace;
 mod state;
 mod surrounds;
@@ -20,16 +24,19 @@ mod visual;
 use anyhow::Result;
 use change_list::push_to_change_list;
 use collections::HashMap;
-use command_palette_hooks::{CommandPaletteFilter, CommandPaletteInterceptor};
+use command_palette_hooks::{
+    CommandPaletteCompleter, CommandPaletteFilter, CommandPaletteInterceptor,
+};
 use editor::{
     movement::{self, FindRange},
     Anchor, Bias, Editor, EditorEvent, EditorMode, ToPoint,
 };
 use gpui::{
-    actions, impl_actions, Action, AppContext, EntityId, FocusableView, Global, KeystrokeEvent,
-    Subscription, UpdateGlobal, View, ViewContext, WeakView, WindowContext,
+    actions, impl_actions, Action, AppContext, Entity, EntityId, FocusableView, Global,
+    KeystrokeEvent, Subscription, UpdateGlobal, View, ViewContext, WeakView, WindowContext,
 };
 use language::{CursorShape, Point, SelectionGoal, TransactionId};
+pub use mapping::{VimMapping, VimMappingSettings, VimMappings};
 pub use mode_indicator::ModeIndicator;
 use motion::Motion;
 use normal::{
@@ -46,7 +53,7 @@ use std::{ops::Range, sync::Arc};
 use surrounds::{add_surrounds, change_surrounds, delete_surrounds};
 use ui::BorrowAppContext;
 use visual::{visual_block_motion, visual_replace};
-use workspace::{self, Workspace};
+use workspace::{self, ClipboardHistory, Workspace};
 
 use crate::state::ReplayableAction;
 
@@ -93,6 +100,7 @@ pub fn init(cx: &mut AppContext) {
     cx.set_global(Vim::default());
     VimModeSetting::register(cx);
     VimSettings::register(cx);
+    VimMappingSettings::register(cx);
 
     cx.observe_keystrokes(observe_keystrokes).detach();
     editor_events::init(cx);
@@ -162,6 +170,9 @@ fn register(workspace: &mut Workspace, cx: &mut ViewContext<Workspace>) {
     object::register(workspace, cx);
     visual::register(workspace, cx);
     change_list::register(workspace, cx);
+    jump_list::register(workspace, cx);
+    global::register(workspace, cx);
+    normal::mark::register(workspace, cx);
 }
 
 /// Called whenever an keystroke is typed so vim can observe all actions
@@ -182,6 +193,19 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
                     vim.workspace_state.recording = false;
                     vim.workspace_state.stop_recording_after_next_action = false;
                 }
+                vim.workspace_state.pending_editor_action = None;
+            } else if !action.name().starts_with("vim::")
+                && !editor::actions::Undo.partial_eq(&*action)
+                && !editor::actions::Redo.partial_eq(&*action)
+                && vim.state().mode == Mode::Normal
+                && vim.active_operator().is_none()
+            {
+                // Actions from outside of vim, like "g c c" or custom key bindings, are only
+                // known to edit the buffer once the editor reports it.
+                vim.workspace_state.pending_editor_action =
+                    Some(ReplayableAction::Action(action.boxed_clone()));
+            } else {
+                vim.workspace_state.pending_editor_action = None;
             }
         });
 
@@ -202,7 +226,10 @@ fn observe_keystrokes(keystroke_event: &KeystrokeEvent, cx: &mut WindowContext)
             | Operator::ChangeSurrounds { .. }
             | Operator::DeleteSurrounds
             | Operator::Mark
-            | Operator::Jump { .. },
+            | Operator::Jump { .. }
+            | Operator::RecordRegister
+            | Operator::ReplayRegister
+            | Operator::Register,
         ) => {}
         Some(_) => {
             vim.clear_operator(cx);
@@ -255,12 +282,34 @@ impl Vim {
             }
             EditorEvent::InputIgnored { text } => {
                 Vim::active_editor_input_ignored(text.clone(), cx);
-                Vim::record_insertion(text, None, cx)
+                Vim::record_insertion(
+                    ReplayableAction::Insertion {
+                        text: text.clone(),
+                        utf16_range_to_replace: None,
+                    },
+                    cx,
+                )
             }
             EditorEvent::InputHandled {
                 text,
                 utf16_range_to_replace: range_to_replace,
-            } => Vim::record_insertion(text, range_to_replace.clone(), cx),
+            } => Vim::record_insertion(
+                ReplayableAction::Insertion {
+                    text: text.clone(),
+                    utf16_range_to_replace: range_to_replace.clone(),
+                },
+                cx,
+            ),
+            EditorEvent::SnippetInputHandled {
+                snippet,
+                utf16_range_to_replace: range_to_replace,
+            } => Vim::record_insertion(
+                ReplayableAction::Snippet {
+                    snippet: snippet.clone(),
+                    utf16_range_to_replace: range_to_replace.clone(),
+                },
+                cx,
+            ),
             EditorEvent::TransactionBegun { transaction_id } => Vim::update(cx, |vim, cx| {
                 vim.transaction_begun(*transaction_id, cx);
             }),
@@ -268,8 +317,16 @@ impl Vim {
                 vim.transaction_undone(transaction_id, cx);
             }),
             EditorEvent::Edited => Vim::update(cx, |vim, cx| vim.transaction_ended(editor, cx)),
+            EditorEvent::PushedToNavHistory {
+                anchor,
+                is_deactivate,
+            } => Vim::update(cx, |vim, cx| {
+                jump_list::pushed_to_nav_history(vim, editor, *anchor, *is_deactivate, cx)
+            }),
             _ => {}
         }));
+        normal::mark::restore_marks(self, cx);
+        change_list::restore_change_list(self, cx);
 
         let editor = editor.read(cx);
         let editor_mode = editor.mode();
@@ -287,19 +344,10 @@ impl Vim {
         self.sync_vim_settings(cx);
     }
 
-    fn record_insertion(
-        text: &Arc<str>,
-        range_to_replace: Option<Range<isize>>,
-        cx: &mut WindowContext,
-    ) {
+    fn record_insertion(insertion: ReplayableAction, cx: &mut WindowContext) {
         Vim::update(cx, |vim, _| {
             if vim.workspace_state.recording {
-                vim.workspace_state
-                    .recorded_actions
-                    .push(ReplayableAction::Insertion {
-                        text: text.clone(),
-                        utf16_range_to_replace: range_to_replace,
-                    });
+                vim.workspace_state.recorded_actions.push(insertion);
                 if vim.workspace_state.stop_recording_after_next_action {
                     vim.workspace_state.recording = false;
                     vim.workspace_state.stop_recording_after_next_action = false;
@@ -314,7 +362,10 @@ impl Vim {
         update: impl FnOnce(&mut Vim, &mut Editor, &mut ViewContext<Editor>) -> S,
     ) -> Option<S> {
         let editor = self.active_editor.clone()?.upgrade()?;
-        Some(editor.update(cx, |editor, cx| update(self, editor, cx)))
+        let result = editor.update(cx, |editor, cx| update(self, editor, cx));
+        let cursor = editor.read(cx).selections.newest_anchor().head();
+        self.workspace_state.vim_cursor = Some((editor.entity_id(), cursor));
+        Some(result)
     }
 
     fn editor_selections(&mut self, cx: &mut WindowContext) -> Vec<Range<Anchor>> {
@@ -539,6 +590,7 @@ impl Vim {
                 | Operator::Replace
                 | Operator::Indent
                 | Operator::Outdent
+                | Operator::AddSurrounds { target: None }
         ) {
             self.start_recording(cx)
         };
@@ -574,6 +626,39 @@ impl Vim {
         self.sync_vim_settings(cx);
     }
 
+    /// Returns the contents of a register. The numbered registers are read from the clipboard
+    /// history: `"0` holds the most recent yank, and `"1` to `"9` the most recent deletes. The
+    /// unnamed register holds the most recent yank or delete.
+    fn read_register(&self, register: char, cx: &AppContext) -> Option<String> {
+        match register {
+            '0' => Some(
+                ClipboardHistory::entries(cx)
+                    .find(|entry| !entry.cut)?
+                    .item
+                    .text()
+                    .clone(),
+            ),
+            '1'..='9' => {
+                let ix = register.to_digit(10)? as usize - 1;
+                Some(
+                    ClipboardHistory::entries(cx)
+                        .filter(|entry| entry.cut)
+                        .nth(ix)?
+                        .item
+                        .text()
+                        .clone(),
+                )
+            }
+            '_' => None,
+            '+' | '*' => Some(cx.read_from_clipboard()?.text().clone()),
+            register => self
+                .workspace_state
+                .registers
+                .get(&register.to_ascii_lowercase().to_string())
+                .cloned(),
+        }
+    }
+
     fn active_operator(&self) -> Option<Operator> {
         self.state().operator_stack.last().cloned()
     }
@@ -627,6 +712,13 @@ impl Vim {
     }
 
     fn transaction_ended(&mut self, editor: View<Editor>, cx: &mut WindowContext) {
+        if let Some(action) = self.workspace_state.pending_editor_action.take() {
+            if !self.workspace_state.recording && !self.workspace_state.replaying {
+                self.workspace_state.recorded_actions = vec![action];
+                self.workspace_state.recorded_count = None;
+                self.workspace_state.recorded_selection = RecordedSelection::None;
+            }
+        }
         push_to_change_list(self, editor, cx)
     }
 
@@ -739,6 +831,12 @@ impl Vim {
                 normal::mark::create_mark(vim, text, false, cx)
             }),
             Some(Operator::Jump { line }) => normal::mark::jump(text, line, cx),
+            Some(Operator::RecordRegister) => normal::macros::record(text, cx),
+            Some(Operator::ReplayRegister) => normal::macros::replay(text, cx),
+            Some(Operator::Register) => Vim::update(cx, |vim, cx| {
+                vim.workspace_state.selected_register = text.chars().next();
+                vim.pop_operator(cx);
+            }),
             _ => match Vim::read(cx).state().mode {
                 Mode::Replace => multi_replace(text, cx),
                 _ => {}
@@ -754,6 +852,9 @@ impl Vim {
             CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
                 interceptor.clear();
             });
+            CommandPaletteCompleter::update_global(cx, |completer, _| {
+                completer.clear();
+            });
             CommandPaletteFilter::update_global(cx, |filter, _| {
                 filter.hide_namespace(Self::NAMESPACE);
             });
@@ -768,6 +869,9 @@ impl Vim {
         CommandPaletteInterceptor::update_global(cx, |interceptor, _| {
             interceptor.set(Box::new(command::command_interceptor));
         });
+        CommandPaletteCompleter::update_global(cx, |completer, _| {
+            completer.set(Box::new(command::command_completions));
+        });
 
         if let Some(active_window) = cx
             .active_window()
@@ -0,0 +1,87 @@
+use crate::WorkspaceSettings;
+use gpui::{AppContext, ClipboardItem, Global};
+use settings::Settings;
+use std::{collections::VecDeque, time::Instant};
+
+/// The items recently copied from the editors and terminals, most recent first.
+#[derive(Default)]
+pub struct ClipboardHistory {
+    entries: VecDeque<ClipboardHistoryEntry>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ClipboardHistoryEntry {
+    pub item: ClipboardItem,
+    pub copied_at: Instant,
+    /// Whether the item was cut or deleted, rather than copied.
+    pub cut: bool,
+}
+
+impl Global for ClipboardHistory {}
+
+impl ClipboardHistory {
+    /// Records an item copied to the clipboard, moving it to the front of the history if
+    /// the same text was copied before.
+    pub fn record(item: ClipboardItem, cx: &mut AppContext) {
+        let max_len = WorkspaceSettings::get_global(cx).clipboard_history_size;
+        cx.default_global::<Self>().push(item, false, max_len);
+    }
+
+    /// Records an item cut to the clipboard, or deleted by vim, like [`Self::record`].
+    pub fn record_cut(item: ClipboardItem, cx: &mut AppContext) {
+        let max_len = WorkspaceSettings::get_global(cx).clipboard_history_size;
+        cx.default_global::<Self>().push(item, true, max_len);
+    }
+
+    /// Returns the recorded items, most recent first.
+    pub fn entries(cx: &AppContext) -> impl Iterator<Item = &ClipboardHistoryEntry> {
+        cx.try_global::<Self>()
+            .into_iter()
+            .flat_map(|history| history.entries.iter())
+    }
+
+    /// Returns the item copied before the `ix` most recent ones.
+    pub fn entry(ix: usize, cx: &AppContext) -> Option<&ClipboardHistoryEntry> {
+        cx.try_global::<Self>()?.entries.get(ix)
+    }
+
+    fn push(&mut self, item: ClipboardItem, cut: bool, max_len: usize) {
+        if item.text().is_empty() {
+            return;
+        }
+        self.entries
+            .retain(|entry| entry.item.text() != item.text());
+        self.entries.push_front(ClipboardHistoryEntry {
+            item,
+            copied_at: Instant::now(),
+            cut,
+        });
+        self.entries.truncate(max_len);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_push_clipboard_history() {
+        let mut history = ClipboardHistory::default();
+        for text in ["one", "two", "", "three", "one"] {
+            history.push(ClipboardItem::new(text.to_string()), false, 3);
+        }
+        assert_eq!(texts(&history), ["one", "three", "two"]);
+
+        history.push(ClipboardItem::new("four".to_string()), true, 3);
+        assert!(history.entries[0].cut);
+        assert_eq!(texts(&history), ["four", "one", "three"]);
+    }
+
+    fn texts(history: &ClipboardHistory) -> Vec<&str> {
+        history
+            .entries
+            .iter()
+            .map(|entry| entry.item.text().as_str())
+            .collect()
+    }
+}
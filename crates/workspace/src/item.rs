@@ -129,6 +129,16 @@ pub struct BreadcrumbText {
     pub font: Option<Font>,
 }
 
+/// A location of an item recorded in a pane's navigation history, as listed in its navigation
+/// history menu.
+#[derive(Debug, Clone)]
+pub struct NavigationLocation {
+    /// The zero-based row of the location in its file.
+    pub row: u32,
+    /// The text of the location's line, trimmed.
+    pub preview: SharedString,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct TabContentParams {
     pub detail: Option<usize>,
@@ -261,6 +271,21 @@ pub trait Item: FocusableView + EventEmitter<Self::Event> {
     fn pixel_position_of_cursor(&self, _: &AppContext) -> Option<Point<Pixels>> {
         None
     }
+
+    /// Handles something dragged onto the item, such as a [`DraggedSelection`] of project entries
+    /// or [`ExternalPaths`], instead of the pane opening it. Returns whether it was handled.
+    ///
+    /// [`DraggedSelection`]: crate::DraggedSelection
+    /// [`ExternalPaths`]: gpui::ExternalPaths
+    fn handle_drop(&mut self, _dropped: &dyn Any, _cx: &mut ViewContext<Self>) -> bool {
+        false
+    }
+
+    /// Describes the location that the given data, pushed to the item's navigation history,
+    /// navigates to.
+    fn navigation_location(&self, _data: &dyn Any, _cx: &AppContext) -> Option<NavigationLocation> {
+        None
+    }
 }
 
 pub trait ItemHandle: 'static + Send {
@@ -330,6 +355,8 @@ pub trait ItemHandle: 'static + Send {
     fn serialized_item_kind(&self) -> Option<&'static str>;
     fn show_toolbar(&self, cx: &AppContext) -> bool;
     fn pixel_position_of_cursor(&self, cx: &AppContext) -> Option<Point<Pixels>>;
+    fn handle_drop(&self, dropped: &dyn Any, cx: &mut WindowContext) -> bool;
+    fn navigation_location(&self, data: &dyn Any, cx: &AppContext) -> Option<NavigationLocation>;
     fn downgrade_item(&self) -> Box<dyn WeakItemHandle>;
 }
 
@@ -703,6 +730,14 @@ impl<T: Item> ItemHandle for View<T> {
         self.read(cx).pixel_position_of_cursor(cx)
     }
 
+    fn handle_drop(&self, dropped: &dyn Any, cx: &mut WindowContext) -> bool {
+        self.update(cx, |this, cx| this.handle_drop(dropped, cx))
+    }
+
+    fn navigation_location(&self, data: &dyn Any, cx: &AppContext) -> Option<NavigationLocation> {
+        self.read(cx).navigation_location(data, cx)
+    }
+
     fn downgrade_item(&self) -> Box<dyn WeakItemHandle> {
         Box::new(self.downgrade())
     }
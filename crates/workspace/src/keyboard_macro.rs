@@ -0,0 +1,343 @@
+use crate::{persistence::DB, Workspace};
+use anyhow::{anyhow, Result};
+use collections::HashMap;
+use gpui::{
+    actions, impl_actions, Action, AppContext, Keystroke, KeystrokeEvent, SharedString, ViewContext,
+};
+use serde::Deserialize;
+use std::{cell::RefCell, rc::Rc, sync::Arc};
+use util::ResultExt;
+
+/// The slot used by the macros recorded without a name.
+pub const DEFAULT_MACRO_SLOT: &str = "default";
+
+/// Replaying stops after this many steps, as a macro may replay itself.
+const MAX_REPLAYED_STEPS: usize = 10_000;
+
+#[derive(Clone, Default, Deserialize, PartialEq)]
+pub struct StartRecordingMacro {
+    #[serde(default)]
+    pub slot: Option<String>,
+}
+
+#[derive(Clone, Deserialize, PartialEq)]
+pub struct ReplayMacro {
+    #[serde(default)]
+    pub slot: Option<String>,
+    #[serde(default = "default_replay_times")]
+    pub times: usize,
+}
+
+fn default_replay_times() -> usize {
+    1
+}
+
+impl_actions!(editor, [StartRecordingMacro, ReplayMacro]);
+actions!(editor, [StopRecordingMacro]);
+
+/// A single step of a keyboard macro.
+#[derive(Debug)]
+pub enum MacroStep {
+    /// An action dispatched by a keystroke.
+    Action {
+        action: Box<dyn Action>,
+        keystroke: Keystroke,
+    },
+    /// A keystroke that wasn't bound to an action, such as typed text. Macros restored from
+    /// the database only consist of those, as actions are replayed through their keystrokes.
+    Keystroke(Keystroke),
+}
+
+impl MacroStep {
+    pub fn keystroke(&self) -> &Keystroke {
+        match self {
+            MacroStep::Action { keystroke, .. } => keystroke,
+            MacroStep::Keystroke(keystroke) => keystroke,
+        }
+    }
+}
+
+impl Clone for MacroStep {
+    fn clone(&self) -> Self {
+        match self {
+            MacroStep::Action { action, keystroke } => MacroStep::Action {
+                action: action.boxed_clone(),
+                keystroke: keystroke.clone(),
+            },
+            MacroStep::Keystroke(keystroke) => MacroStep::Keystroke(keystroke.clone()),
+        }
+    }
+}
+
+struct MacroRecording {
+    slot: SharedString,
+    steps: Vec<MacroStep>,
+}
+
+#[derive(Default)]
+pub(crate) struct KeyboardMacros {
+    recording: Option<MacroRecording>,
+    slots: HashMap<SharedString, Arc<[MacroStep]>>,
+    /// The steps left to replay, the next one being last.
+    replay_queue: Rc<RefCell<Vec<MacroStep>>>,
+    replaying: bool,
+}
+
+impl KeyboardMacros {
+    /// Restores the macros persisted for the workspace, keeping those recorded since it opened.
+    pub(crate) fn restore(&mut self, macros: Vec<(String, String)>) {
+        for (slot, keystrokes) in macros {
+            if let Some(steps) = deserialize_macro_steps(&keystrokes).log_err() {
+                self.slots
+                    .entry(slot.into())
+                    .or_insert_with(|| steps.into());
+            }
+        }
+    }
+}
+
+pub(crate) fn init(cx: &mut AppContext) {
+    cx.observe_keystrokes(|event, cx| {
+        if let Some(workspace) = cx.window_handle().downcast::<Workspace>() {
+            workspace
+                .update(cx, |workspace, cx| workspace.record_macro_step(event, cx))
+                .ok();
+        }
+    })
+    .detach();
+}
+
+pub(crate) fn serialize_macro_steps(steps: &[MacroStep]) -> Result<String> {
+    let keystrokes = steps
+        .iter()
+        .map(|step| keystroke_source(step.keystroke()))
+        .collect::<Vec<_>>();
+    Ok(serde_json::to_string(&keystrokes)?)
+}
+
+/// Formats the keystroke in the syntax understood by [`Keystroke::parse`].
+fn keystroke_source(keystroke: &Keystroke) -> String {
+    let mut source = String::new();
+    for (enabled, modifier) in [
+        (keystroke.modifiers.control, "ctrl-"),
+        (keystroke.modifiers.alt, "alt-"),
+        (keystroke.modifiers.shift, "shift-"),
+        (keystroke.modifiers.platform, "cmd-"),
+        (keystroke.modifiers.function, "fn-"),
+    ] {
+        if enabled {
+            source.push_str(modifier);
+        }
+    }
+    source.push_str(&keystroke.key);
+    // Keep the text typed by keystrokes that don't type their key, such as `alt-a` on macOS.
+    if let Some(ime_key) = keystroke.ime_key.as_deref() {
+        let simulated_ime_key = Keystroke {
+            ime_key: None,
+            ..keystroke.clone()
+        }
+        .with_simulated_ime()
+        .ime_key;
+        if simulated_ime_key.as_deref() != Some(ime_key) && !ime_key.contains('-') {
+            source.push_str("->");
+            source.push_str(ime_key);
+        }
+    }
+    source
+}
+
+pub(crate) fn deserialize_macro_steps(keystrokes: &str) -> Result<Vec<MacroStep>> {
+    serde_json::from_str::<Vec<String>>(keystrokes)?
+        .iter()
+        .map(|keystroke| Ok(MacroStep::Keystroke(Keystroke::parse(keystroke)?)))
+        .collect()
+}
+
+impl Workspace {
+    pub fn is_recording_macro(&self) -> bool {
+        self.keyboard_macros.recording.is_some()
+    }
+
+    pub fn recording_macro_slot(&self) -> Option<&SharedString> {
+        Some(&self.keyboard_macros.recording.as_ref()?.slot)
+    }
+
+    pub fn macro_steps(&self, slot: &str) -> Option<Arc<[MacroStep]>> {
+        self.keyboard_macros.slots.get(slot).cloned()
+    }
+
+    /// Starts recording the actions and text typed into the given slot, replacing the macro
+    /// previously recorded there once the recording stops.
+    pub fn start_recording_macro(&mut self, slot: SharedString, cx: &mut ViewContext<Self>) {
+        // Wait for the current keystroke to be handled, so it isn't part of the macro.
+        cx.defer(move |this, cx| {
+            this.keyboard_macros.recording = Some(MacroRecording {
+                slot,
+                steps: Vec::new(),
+            });
+            cx.notify();
+        });
+    }
+
+    /// Stops recording the current macro, returning the slot it was saved to.
+    pub fn stop_recording_macro(&mut self, cx: &mut ViewContext<Self>) -> Option<SharedString> {
+        let recording = self.keyboard_macros.recording.take()?;
+        let steps = Arc::<[MacroStep]>::from(recording.steps);
+        if let Some(workspace_id) = self.database_id {
+            if let Some(keystrokes) = serialize_macro_steps(&steps).log_err() {
+                let slot = recording.slot.to_string();
+                cx.background_executor()
+                    .spawn(async move {
+                        DB.save_keyboard_macro(workspace_id, slot, keystrokes)
+                            .await
+                            .log_err()
+                    })
+                    .detach();
+            }
+        }
+        self.keyboard_macros
+            .slots
+            .insert(recording.slot.clone(), steps);
+        cx.notify();
+        Some(recording.slot)
+    }
+
+    /// Replays the macro of the given slot, returning false if nothing was recorded there.
+    pub fn replay_macro(&mut self, slot: &str, times: usize, cx: &mut ViewContext<Self>) -> bool {
+        let Some(steps) = self.keyboard_macros.slots.get(slot).cloned() else {
+            return false;
+        };
+        {
+            // Macros replayed by a macro run before the rest of its steps.
+            let mut queue = self.keyboard_macros.replay_queue.borrow_mut();
+            for _ in 0..times {
+                queue.extend(steps.iter().rev().cloned());
+            }
+        }
+        if self.keyboard_macros.replaying {
+            return true;
+        }
+
+        self.keyboard_macros.replaying = true;
+        let queue = self.keyboard_macros.replay_queue.clone();
+        let workspace = cx.view().downgrade();
+        cx.window_context()
+            .spawn(|mut cx| async move {
+                let mut result: Result<()> = Ok(());
+                for _ in 0..MAX_REPLAYED_STEPS {
+                    let Some(step) = queue.borrow_mut().pop() else {
+                        break;
+                    };
+                    result = cx.update(|cx| {
+                        let focused = cx.focused();
+                        match step {
+                            MacroStep::Action { action, .. } => cx.dispatch_action(action),
+                            MacroStep::Keystroke(keystroke) => {
+                                cx.dispatch_keystroke(keystroke);
+                            }
+                        }
+                        if cx.focused() != focused {
+                            // Like when sending keystrokes, the focus events must be scheduled
+                            // before the next step is replayed.
+                            cx.draw();
+                        }
+                    });
+                    if result.is_err() {
+                        break;
+                    }
+                }
+                if result.is_ok() && !queue.borrow().is_empty() {
+                    result = Err(anyhow!(
+                        "over {MAX_REPLAYED_STEPS} steps replayed by a keyboard macro"
+                    ));
+                }
+                queue.borrow_mut().clear();
+                workspace
+                    .update(&mut cx, |workspace, _| {
+                        workspace.keyboard_macros.replaying = false
+                    })
+                    .ok();
+                result
+            })
+            .detach_and_log_err(cx);
+        true
+    }
+
+    pub(crate) fn start_recording_macro_action(
+        &mut self,
+        action: &StartRecordingMacro,
+        cx: &mut ViewContext<Self>,
+    ) {
+        let slot = action.slot.as_deref().unwrap_or(DEFAULT_MACRO_SLOT);
+        self.start_recording_macro(SharedString::from(slot.to_string()), cx);
+    }
+
+    pub(crate) fn stop_recording_macro_action(
+        &mut self,
+        _: &StopRecordingMacro,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.stop_recording_macro(cx);
+    }
+
+    pub(crate) fn replay_macro_action(&mut self, action: &ReplayMacro, cx: &mut ViewContext<Self>) {
+        let slot = action.slot.as_deref().unwrap_or(DEFAULT_MACRO_SLOT);
+        self.replay_macro(slot, action.times.max(1), cx);
+    }
+
+    fn record_macro_step(&mut self, event: &KeystrokeEvent, cx: &mut ViewContext<Self>) {
+        if self.keyboard_macros.replaying || self.keyboard_macros.recording.is_none() {
+            return;
+        }
+        // Keystrokes handled by modals, such as the command palette used to stop the
+        // recording, aren't part of the macro.
+        if self.has_active_modal(cx) {
+            return;
+        }
+        let step = match &event.action {
+            Some(action) => {
+                if action.as_any().is::<StartRecordingMacro>()
+                    || action.as_any().is::<StopRecordingMacro>()
+                {
+                    return;
+                }
+                MacroStep::Action {
+                    action: action.boxed_clone(),
+                    keystroke: event.keystroke.clone(),
+                }
+            }
+            None if event.keystroke.ime_key.is_some() => {
+                MacroStep::Keystroke(event.keystroke.clone())
+            }
+            None => return,
+        };
+        if let Some(recording) = self.keyboard_macros.recording.as_mut() {
+            recording.steps.push(step);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serialize_macro_steps() {
+        let steps = vec![
+            MacroStep::Keystroke(Keystroke::parse("a").unwrap()),
+            MacroStep::Keystroke(Keystroke::parse("ctrl-shift-k").unwrap()),
+            MacroStep::Keystroke(Keystroke::parse("alt-a->å").unwrap()),
+        ];
+        let serialized = serialize_macro_steps(&steps).unwrap();
+        assert_eq!(serialized, r#"["a","ctrl-shift-k","alt-a->å"]"#);
+
+        let deserialized = deserialize_macro_steps(&serialized).unwrap();
+        assert_eq!(
+            deserialized
+                .iter()
+                .map(|step| keystroke_source(step.keystroke()))
+                .collect::<Vec<_>>(),
+            ["a", "ctrl-shift-k", "alt-a->å"]
+        );
+    }
+}
@@ -0,0 +1,354 @@
+use crate::{
+    dock::Dock, item::ItemHandle, persistence::DB, Member, OpenVisible, Pane, PaneAxis, PaneGroup,
+    Workspace,
+};
+use anyhow::{anyhow, Context as _, Result};
+use gpui::{px, AnyWeakView, AppContext, Axis, Model, Task, View, ViewContext, WindowContext};
+use project::Project;
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+use util::ResultExt;
+
+/// An arrangement of the workspace's panes, the files open in them, and its docks, saved under
+/// a name to be restored later.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SerializedLayout {
+    center: SerializedLayoutMember,
+    left_dock: SerializedLayoutDock,
+    right_dock: SerializedLayoutDock,
+    bottom_dock: SerializedLayoutDock,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum SerializedLayoutMember {
+    Axis {
+        axis: LayoutAxis,
+        flexes: Vec<f32>,
+        members: Vec<SerializedLayoutMember>,
+    },
+    Pane {
+        paths: Vec<PathBuf>,
+        active_path: Option<PathBuf>,
+        active: bool,
+    },
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum LayoutAxis {
+    Horizontal,
+    Vertical,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct SerializedLayoutDock {
+    visible: bool,
+    active_panel: Option<String>,
+    zoom: bool,
+    size: Option<f32>,
+}
+
+/// A pane created for a restored layout, with the files to open in it.
+struct RestoredPane {
+    pane: View<Pane>,
+    paths_to_open: Vec<PathBuf>,
+    active_path: Option<PathBuf>,
+}
+
+impl Workspace {
+    /// The names of the layouts saved for the workspace, sorted.
+    pub fn layout_names(&self) -> Vec<String> {
+        self.database_id
+            .and_then(|database_id| DB.layout_names(database_id).log_err())
+            .unwrap_or_default()
+    }
+
+    /// Saves the current arrangement of the panes and docks under the given name, replacing the
+    /// layout saved with it before.
+    pub fn save_layout(&mut self, name: String, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let Some(database_id) = self.database_id else {
+            return Task::ready(Err(anyhow!("Layouts can't be saved for this workspace")));
+        };
+        let layout = SerializedLayout {
+            center: serialize_member(&self.center.root, &self.active_pane, &self.project, cx),
+            left_dock: serialize_dock(&self.left_dock, cx),
+            right_dock: serialize_dock(&self.right_dock, cx),
+            bottom_dock: serialize_dock(&self.bottom_dock, cx),
+        };
+        cx.background_executor().spawn(async move {
+            let layout = serde_json::to_string(&layout)?;
+            DB.save_layout(database_id, name, layout).await
+        })
+    }
+
+    pub fn delete_layout(&mut self, name: String, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let Some(database_id) = self.database_id else {
+            return Task::ready(Ok(()));
+        };
+        cx.background_executor()
+            .spawn(async move { DB.delete_layout(database_id, name).await })
+    }
+
+    /// Rearranges the panes and docks as in the layout saved with the given name, reopening the
+    /// files it had open. The items open now that the layout doesn't place are kept in its
+    /// active pane rather than closed.
+    pub fn restore_layout(&mut self, name: String, cx: &mut ViewContext<Self>) -> Task<Result<()>> {
+        let layout = self
+            .database_id
+            .context("Layouts can't be restored for this workspace")
+            .and_then(|database_id| DB.layout(database_id, name.clone()))
+            .and_then(|layout| layout.with_context(|| format!("No layout named {name:?}")))
+            .and_then(|layout| Ok(serde_json::from_str::<SerializedLayout>(&layout)?));
+        let layout = match layout {
+            Ok(layout) => layout,
+            Err(error) => return Task::ready(Err(error)),
+        };
+
+        let mut items = Vec::new();
+        for pane in self.center.panes() {
+            for item in pane.read(cx).items() {
+                let abs_path = item_abs_path(item.as_ref(), &self.project, cx);
+                items.push((pane.clone(), item.boxed_clone(), abs_path));
+            }
+        }
+
+        let mut restored_panes = Vec::new();
+        let mut active_pane = None;
+        let root = self.restore_member(
+            layout.center,
+            &mut items,
+            &mut restored_panes,
+            &mut active_pane,
+            cx,
+        );
+        let center = PaneGroup::with_root(root);
+        let active_pane = active_pane.unwrap_or_else(|| center.first_pane());
+        for (pane, item, _) in items {
+            move_item(&pane, &active_pane, item, cx);
+        }
+
+        let old_root = std::mem::replace(&mut self.center, center).root;
+        self.remove_panes(old_root, cx);
+        self.active_pane = active_pane.clone();
+        self.last_active_center_pane = Some(active_pane.downgrade());
+
+        self.zoomed = None;
+        self.zoomed_position = None;
+        for (dock, layout) in [
+            (self.left_dock.clone(), layout.left_dock),
+            (self.right_dock.clone(), layout.right_dock),
+            (self.bottom_dock.clone(), layout.bottom_dock),
+        ] {
+            let zoomed_panel = dock.update(cx, |dock, cx| restore_dock(dock, layout, cx));
+            if let Some(zoomed_panel) = zoomed_panel {
+                self.zoomed = Some(zoomed_panel);
+                self.zoomed_position = Some(dock.read(cx).position());
+            }
+        }
+
+        let mut opened_paths = Vec::new();
+        let mut active_paths = Vec::new();
+        for restored_pane in restored_panes {
+            if !restored_pane.paths_to_open.is_empty() {
+                opened_paths.push(self.open_paths(
+                    restored_pane.paths_to_open,
+                    OpenVisible::None,
+                    Some(restored_pane.pane.downgrade()),
+                    cx,
+                ));
+            }
+            active_paths.push((restored_pane.pane, restored_pane.active_path));
+        }
+        cx.focus_view(&active_pane);
+        cx.notify();
+
+        cx.spawn(|this, mut cx| async move {
+            for opened_items in futures::future::join_all(opened_paths).await {
+                for opened_item in opened_items.into_iter().flatten() {
+                    opened_item.log_err();
+                }
+            }
+            this.update(&mut cx, |this, cx| {
+                for (pane, active_path) in active_paths {
+                    let Some(active_path) = active_path else {
+                        continue;
+                    };
+                    let project = this.project.clone();
+                    pane.update(cx, |pane, cx| {
+                        let ix = pane.items().position(|item| {
+                            item_abs_path(item.as_ref(), &project, cx).as_ref()
+                                == Some(&active_path)
+                        });
+                        if let Some(ix) = ix {
+                            pane.activate_item(ix, false, false, cx);
+                        }
+                    });
+                }
+                cx.focus_view(&active_pane);
+                this.serialize_workspace(cx);
+            })
+        })
+    }
+
+    /// Creates the panes of a layout, moving the given items with the files they should show
+    /// into them.
+    fn restore_member(
+        &mut self,
+        member: SerializedLayoutMember,
+        items: &mut Vec<(View<Pane>, Box<dyn ItemHandle>, Option<PathBuf>)>,
+        restored_panes: &mut Vec<RestoredPane>,
+        active_pane: &mut Option<View<Pane>>,
+        cx: &mut ViewContext<Self>,
+    ) -> Member {
+        match member {
+            SerializedLayoutMember::Axis {
+                axis,
+                flexes,
+                members,
+            } => {
+                let members = members
+                    .into_iter()
+                    .map(|member| {
+                        self.restore_member(member, items, restored_panes, active_pane, cx)
+                    })
+                    .collect::<Vec<_>>();
+                let flexes = (flexes.len() == members.len()).then_some(flexes);
+                let axis = match axis {
+                    LayoutAxis::Horizontal => Axis::Horizontal,
+                    LayoutAxis::Vertical => Axis::Vertical,
+                };
+                Member::Axis(PaneAxis::load(axis, members, flexes))
+            }
+            SerializedLayoutMember::Pane {
+                paths,
+                active_path,
+                active,
+            } => {
+                let pane = self.add_pane(cx);
+                let mut paths_to_open = Vec::new();
+                for path in paths {
+                    let ix = items
+                        .iter()
+                        .position(|(_, _, abs_path)| abs_path.as_ref() == Some(&path));
+                    if let Some(ix) = ix {
+                        let (old_pane, item, _) = items.remove(ix);
+                        move_item(&old_pane, &pane, item, cx);
+                    } else {
+                        paths_to_open.push(path);
+                    }
+                }
+                if active {
+                    *active_pane = Some(pane.clone());
+                }
+                restored_panes.push(RestoredPane {
+                    pane: pane.clone(),
+                    paths_to_open,
+                    active_path,
+                });
+                Member::Pane(pane)
+            }
+        }
+    }
+}
+
+fn serialize_member(
+    member: &Member,
+    active_pane: &View<Pane>,
+    project: &Model<Project>,
+    cx: &WindowContext,
+) -> SerializedLayoutMember {
+    match member {
+        Member::Axis(PaneAxis {
+            axis,
+            members,
+            flexes,
+            ..
+        }) => SerializedLayoutMember::Axis {
+            axis: match axis {
+                Axis::Horizontal => LayoutAxis::Horizontal,
+                Axis::Vertical => LayoutAxis::Vertical,
+            },
+            flexes: flexes.lock().clone(),
+            members: members
+                .iter()
+                .map(|member| serialize_member(member, active_pane, project, cx))
+                .collect(),
+        },
+        Member::Pane(pane) => {
+            let pane_ref = pane.read(cx);
+            SerializedLayoutMember::Pane {
+                paths: pane_ref
+                    .items()
+                    .filter_map(|item| item_abs_path(item.as_ref(), project, cx))
+                    .collect(),
+                active_path: pane_ref
+                    .active_item()
+                    .and_then(|item| item_abs_path(item.as_ref(), project, cx)),
+                active: pane == active_pane,
+            }
+        }
+    }
+}
+
+fn serialize_dock(dock: &View<Dock>, cx: &WindowContext) -> SerializedLayoutDock {
+    let dock = dock.read(cx);
+    let active_panel = dock.active_panel();
+    SerializedLayoutDock {
+        visible: dock.is_open(),
+        active_panel: active_panel.map(|panel| panel.persistent_name().to_string()),
+        zoom: dock.zoomed_panel(cx).is_some(),
+        size: active_panel.map(|panel| panel.size(cx).into()),
+    }
+}
+
+/// Arranges the dock as in a layout, returning its panel if it's zoomed.
+fn restore_dock(
+    dock: &mut Dock,
+    layout: SerializedLayoutDock,
+    cx: &mut ViewContext<Dock>,
+) -> Option<AnyWeakView> {
+    dock.zoom_out(cx);
+    let panel_ix = layout
+        .active_panel
+        .as_deref()
+        .and_then(|name| dock.panel_index_for_persistent_name(name, cx));
+    if let Some(panel_ix) = panel_ix {
+        dock.activate_panel(panel_ix, cx);
+        if let Some(size) = layout.size {
+            dock.resize_active_panel(Some(px(size)), cx);
+        }
+    }
+    dock.set_open(layout.visible, cx);
+
+    let panel = dock.active_panel()?.clone();
+    if layout.visible && layout.zoom {
+        panel.set_zoomed(true, cx);
+        Some(panel.to_any().downgrade())
+    } else {
+        None
+    }
+}
+
+fn move_item(
+    from: &View<Pane>,
+    to: &View<Pane>,
+    item: Box<dyn ItemHandle>,
+    cx: &mut ViewContext<Workspace>,
+) {
+    from.update(cx, |pane, cx| {
+        if let Some(ix) = pane.index_for_item(item.as_ref()) {
+            pane.remove_item(ix, false, false, cx);
+        }
+    });
+    to.update(cx, |pane, cx| pane.add_item(item, false, false, None, cx));
+}
+
+fn item_abs_path(
+    item: &dyn ItemHandle,
+    project: &Model<Project>,
+    cx: &AppContext,
+) -> Option<PathBuf> {
+    let project_path = item.project_path(cx)?;
+    project.read(cx).absolute_path(&project_path, cx)
+}
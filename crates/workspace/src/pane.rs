@@ -1,7 +1,7 @@
 use crate::{
     item::{
-        ClosePosition, Item, ItemHandle, ItemSettings, PreviewTabsSettings, TabContentParams,
-        WeakItemHandle,
+        ClosePosition, Item, ItemHandle, ItemSettings, NavigationLocation, PreviewTabsSettings,
+        TabContentParams, WeakItemHandle,
     },
     toolbar::Toolbar,
     workspace_settings::{AutosaveSetting, TabBarSettings, WorkspaceSettings},
@@ -13,10 +13,11 @@ use collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use futures::{stream::FuturesUnordered, StreamExt};
 use gpui::{
     actions, anchored, deferred, impl_actions, prelude::*, Action, AnchorCorner, AnyElement,
-    AppContext, AsyncWindowContext, ClickEvent, DismissEvent, Div, DragMoveEvent, EntityId,
-    EventEmitter, ExternalPaths, FocusHandle, FocusableView, KeyContext, Model, MouseButton,
-    MouseDownEvent, NavigationDirection, Pixels, Point, PromptLevel, Render, ScrollHandle,
-    Subscription, Task, View, ViewContext, VisualContext, WeakFocusHandle, WeakView, WindowContext,
+    AnyView, AppContext, AsyncWindowContext, ClickEvent, DismissEvent, Div, DragMoveEvent,
+    EntityId, EventEmitter, ExternalPaths, FocusHandle, FocusableView, Global, KeyContext, Model,
+    MouseButton, MouseDownEvent, NavigationDirection, Pixels, Point, PromptLevel, Render,
+    ScrollHandle, Subscription, Task, View, ViewContext, VisualContext, WeakFocusHandle, WeakView,
+    WindowContext,
 };
 use itertools::Itertools;
 use parking_lot::Mutex;
@@ -33,6 +34,7 @@ use std::{
         atomic::{AtomicUsize, Ordering},
         Arc,
     },
+    time::Duration,
 };
 use theme::ThemeSettings;
 
@@ -41,7 +43,7 @@ use ui::{
     IconSize, Indicator, Label, Tab, TabBar, TabPosition, Tooltip,
 };
 use ui::{v_flex, ContextMenu};
-use util::{debug_panic, maybe, truncate_and_remove_front, ResultExt};
+use util::{debug_panic, maybe, truncate_and_remove_front, truncate_and_trailoff, ResultExt};
 
 /// A selected entry in e.g. project panel.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -145,12 +147,14 @@ actions!(
         CloseItemsToTheRight,
         GoBack,
         GoForward,
+        PinTab,
         ReopenClosedItem,
         SplitLeft,
         SplitUp,
         SplitRight,
         SplitDown,
         TogglePreviewTab,
+        UnpinTab,
     ]
 );
 
@@ -221,11 +225,19 @@ pub struct Pane {
     was_focused: bool,
     active_item_index: usize,
     preview_item_id: Option<EntityId>,
+    /// The number of items at the start of `items` whose tabs are pinned.
+    pinned_tab_count: usize,
     last_focus_handle_by_item: HashMap<EntityId, WeakFocusHandle>,
     nav_history: NavHistory,
     toolbar: View<Toolbar>,
     pub new_item_menu: Option<View<ContextMenu>>,
-    split_item_menu: Option<View<ContextMenu>>,
+    pub split_item_menu: Option<View<ContextMenu>>,
+    tab_overflow_menu: Option<View<ContextMenu>>,
+    /// The menu listing the locations that going back or forward leads to.
+    nav_history_menu: Option<(NavigationMode, View<ContextMenu>)>,
+    /// Opens the navigation history menu once a navigation button is held down long enough.
+    nav_history_menu_task: Option<Task<()>>,
+    empty_pane_placeholder: Option<AnyView>,
     //     tab_context_menu: View<ContextMenu>,
     pub(crate) workspace: WeakView<Workspace>,
     project: Model<Project>,
@@ -269,7 +281,7 @@ struct NavHistoryState {
     next_timestamp: Arc<AtomicUsize>,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum NavigationMode {
     Normal,
     GoingBack,
@@ -292,6 +304,40 @@ pub struct NavigationEntry {
     pub is_preview: bool,
 }
 
+/// A location that going back or forward in a pane's navigation history leads to.
+#[derive(Debug, Clone)]
+pub struct NavigationHistoryEntry {
+    /// The number of times to go back or forward to reach the location.
+    pub steps: usize,
+    /// The path of the location's file, or the description of its item when it has none.
+    pub label: SharedString,
+    pub location: Option<NavigationLocation>,
+}
+
+impl NavigationHistoryEntry {
+    /// The entry's label, followed by the one-based row of its location.
+    pub fn title(&self) -> SharedString {
+        match &self.location {
+            Some(location) => format!("{}:{}", self.label, location.row + 1).into(),
+            None => self.label.clone(),
+        }
+    }
+}
+
+/// How long a navigation button is held down before the navigation history menu opens.
+const NAV_HISTORY_MENU_DELAY: Duration = Duration::from_millis(400);
+/// The number of characters of a location's line shown in the navigation history menu.
+const NAV_HISTORY_PREVIEW_LEN: usize = 80;
+
+/// Renders what empty panes show in a window without a project, in place of the hint to open
+/// one. Set as a global by crates with something to offer there.
+#[derive(Clone)]
+pub struct EmptyPanePlaceholder(
+    pub Arc<dyn Fn(WeakView<Workspace>, &mut WindowContext) -> AnyView>,
+);
+
+impl Global for EmptyPanePlaceholder {}
+
 #[derive(Clone)]
 pub struct DraggedTab {
     pub pane: View<Pane>,
@@ -332,6 +378,7 @@ impl Pane {
             zoomed: false,
             active_item_index: 0,
             preview_item_id: None,
+            pinned_tab_count: 0,
             last_focus_handle_by_item: Default::default(),
             nav_history: NavHistory(Arc::new(Mutex::new(NavHistoryState {
                 mode: NavigationMode::Normal,
@@ -345,6 +392,10 @@ impl Pane {
             toolbar: cx.new_view(|_| Toolbar::new()),
             new_item_menu: None,
             split_item_menu: None,
+            tab_overflow_menu: None,
+            nav_history_menu: None,
+            nav_history_menu_task: None,
+            empty_pane_placeholder: None,
             tab_bar_scroll_handle: ScrollHandle::new(),
             drag_split_direction: None,
             workspace,
@@ -662,6 +713,64 @@ impl Pane {
         }
     }
 
+    pub fn is_pinned(&self, item_id: EntityId) -> bool {
+        self.items[..self.pinned_tab_count]
+            .iter()
+            .any(|item| item.item_id() == item_id)
+    }
+
+    fn pinned_item_ids(&self) -> Vec<EntityId> {
+        self.items[..self.pinned_tab_count]
+            .iter()
+            .map(|item| item.item_id())
+            .collect()
+    }
+
+    /// Pins the tab of the item with the given ID, moving it after the other pinned tabs.
+    /// Pinned tabs stay at the start of the tab bar and aren't closed along with other tabs.
+    pub fn pin_item(&mut self, item_id: EntityId, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.items.iter().position(|item| item.item_id() == item_id) else {
+            return;
+        };
+        if ix < self.pinned_tab_count {
+            return;
+        }
+        if self.is_active_preview_item(item_id) {
+            self.set_preview_item_id(None, cx);
+        }
+        self.move_item_within_pane(ix, self.pinned_tab_count);
+        self.pinned_tab_count += 1;
+        cx.notify();
+    }
+
+    /// Unpins the tab of the item with the given ID, moving it before the other unpinned tabs.
+    pub fn unpin_item(&mut self, item_id: EntityId, cx: &mut ViewContext<Self>) {
+        let Some(ix) = self.items.iter().position(|item| item.item_id() == item_id) else {
+            return;
+        };
+        if ix >= self.pinned_tab_count {
+            return;
+        }
+        self.pinned_tab_count -= 1;
+        self.move_item_within_pane(ix, self.pinned_tab_count);
+        cx.notify();
+    }
+
+    fn move_item_within_pane(&mut self, from: usize, to: usize) {
+        if from == to {
+            return;
+        }
+        let item = self.items.remove(from);
+        self.items.insert(to, item);
+        if self.active_item_index == from {
+            self.active_item_index = to;
+        } else if from < self.active_item_index && self.active_item_index <= to {
+            self.active_item_index -= 1;
+        } else if to <= self.active_item_index && self.active_item_index < from {
+            self.active_item_index += 1;
+        }
+    }
+
     pub fn handle_item_edit(&mut self, item_id: EntityId, cx: &AppContext) {
         if let Some(preview_item_id) = self.preview_item_id {
             if preview_item_id == item_id {
@@ -799,6 +908,17 @@ impl Pane {
         if let Some(existing_item_index) = existing_item_index {
             // If the item already exists, move it to the desired destination and activate it
 
+            // Pinned tabs only move among the other pinned tabs, and only when asked to.
+            if existing_item_index < self.pinned_tab_count {
+                if destination_index.is_some() {
+                    insertion_index = insertion_index.min(self.pinned_tab_count - 1);
+                } else {
+                    insertion_index = existing_item_index;
+                }
+            } else {
+                insertion_index = insertion_index.max(self.pinned_tab_count);
+            }
+
             if existing_item_index != insertion_index {
                 let existing_item_is_active = existing_item_index == self.active_item_index;
 
@@ -827,6 +947,7 @@ impl Pane {
 
             self.activate_item(insertion_index, activate_pane, focus_item, cx);
         } else {
+            insertion_index = insertion_index.max(self.pinned_tab_count);
             self.items.insert(insertion_index, item.clone());
 
             if insertion_index <= self.active_item_index
@@ -1009,10 +1130,11 @@ impl Pane {
         }
 
         let active_item_id = self.items[self.active_item_index].item_id();
+        let pinned_item_ids = self.pinned_item_ids();
         Some(self.close_items(
             cx,
             action.save_intent.unwrap_or(SaveIntent::Close),
-            move |item_id| item_id != active_item_id,
+            move |item_id| item_id != active_item_id && !pinned_item_ids.contains(&item_id),
         ))
     }
 
@@ -1023,6 +1145,7 @@ impl Pane {
     ) -> Option<Task<Result<()>>> {
         let item_ids: Vec<_> = self
             .items()
+            .skip(self.pinned_tab_count)
             .filter(|item| !item.is_dirty(cx))
             .map(|item| item.item_id())
             .collect();
@@ -1051,6 +1174,7 @@ impl Pane {
         let item_ids: Vec<_> = self
             .items()
             .take_while(|item| item.item_id() != item_id)
+            .skip(self.pinned_tab_count)
             .map(|item| item.item_id())
             .collect();
         self.close_items(cx, SaveIntent::Close, move |item_id| {
@@ -1075,8 +1199,8 @@ impl Pane {
         item_id: EntityId,
         cx: &mut ViewContext<Self>,
     ) -> Task<Result<()>> {
-        let item_ids: Vec<_> = self
-            .items()
+        let item_ids: Vec<_> = self.items[self.pinned_tab_count..]
+            .iter()
             .rev()
             .take_while(|item| item.item_id() != item_id)
             .map(|item| item.item_id())
@@ -1095,11 +1219,12 @@ impl Pane {
             return None;
         }
 
-        Some(
-            self.close_items(cx, action.save_intent.unwrap_or(SaveIntent::Close), |_| {
-                true
-            }),
-        )
+        let pinned_item_ids = self.pinned_item_ids();
+        Some(self.close_items(
+            cx,
+            action.save_intent.unwrap_or(SaveIntent::Close),
+            move |item_id| !pinned_item_ids.contains(&item_id),
+        ))
     }
 
     pub(super) fn file_names_for_prompt(
@@ -1287,6 +1412,9 @@ impl Pane {
         }
 
         let item = self.items.remove(item_index);
+        if item_index < self.pinned_tab_count {
+            self.pinned_tab_count -= 1;
+        }
 
         cx.emit(Event::RemoveItem {
             item_id: item.item_id(),
@@ -1563,10 +1691,12 @@ impl Pane {
             .preview_item_id
             .map(|id| id == item.item_id())
             .unwrap_or(false);
+        let is_pinned = ix < self.pinned_tab_count;
 
+        // Pinned tabs are kept compact, without the path detail that tells similar tabs apart.
         let label = item.tab_content(
             TabContentParams {
-                detail: Some(detail),
+                detail: (!is_pinned).then_some(detail),
                 selected: is_active,
                 preview: is_preview,
             },
@@ -1638,7 +1768,7 @@ impl Pane {
             }))
             .on_drop(cx.listener(move |this, selection: &DraggedSelection, cx| {
                 this.drag_split_direction = None;
-                this.handle_project_entry_drop(&selection.active_selection.entry_id, cx)
+                this.handle_project_entry_drop(selection, cx)
             }))
             .on_drop(cx.listener(move |this, paths, cx| {
                 this.drag_split_direction = None;
@@ -1648,7 +1778,15 @@ impl Pane {
                 tab.tooltip(move |cx| Tooltip::text(text.clone(), cx))
             })
             .start_slot::<Indicator>(indicator)
-            .end_slot(
+            .end_slot(if is_pinned {
+                IconButton::new("unpin tab", IconName::Pin)
+                    .shape(IconButtonShape::Square)
+                    .icon_color(Color::Muted)
+                    .size(ButtonSize::None)
+                    .icon_size(IconSize::XSmall)
+                    .on_click(cx.listener(move |pane, _, cx| pane.unpin_item(item_id, cx)))
+                    .tooltip(|cx| Tooltip::text("Unpin Tab", cx))
+            } else {
                 IconButton::new("close tab", IconName::Close)
                     .shape(IconButtonShape::Square)
                     .icon_color(Color::Muted)
@@ -1657,8 +1795,8 @@ impl Pane {
                     .on_click(cx.listener(move |pane, _, cx| {
                         pane.close_item_by_id(item_id, SaveIntent::Close, cx)
                             .detach_and_log_err(cx);
-                    })),
-            )
+                    }))
+            })
             .child(label);
 
         let single_entry_to_resolve = {
@@ -1688,8 +1826,11 @@ impl Pane {
                             "Close Others",
                             Some(Box::new(CloseInactiveItems { save_intent: None })),
                             cx.handler_for(&pane, move |pane, cx| {
-                                pane.close_items(cx, SaveIntent::Close, |id| id != item_id)
-                                    .detach_and_log_err(cx);
+                                let pinned_item_ids = pane.pinned_item_ids();
+                                pane.close_items(cx, SaveIntent::Close, move |id| {
+                                    id != item_id && !pinned_item_ids.contains(&id)
+                                })
+                                .detach_and_log_err(cx);
                             }),
                         )
                         .separator()
@@ -1729,7 +1870,27 @@ impl Pane {
                                     task.detach_and_log_err(cx)
                                 }
                             }),
-                        );
+                        )
+                        .separator()
+                        .map(|menu| {
+                            if is_pinned {
+                                menu.entry(
+                                    "Unpin Tab",
+                                    Some(Box::new(UnpinTab)),
+                                    cx.handler_for(&pane, move |pane, cx| {
+                                        pane.unpin_item(item_id, cx)
+                                    }),
+                                )
+                            } else {
+                                menu.entry(
+                                    "Pin Tab",
+                                    Some(Box::new(PinTab)),
+                                    cx.handler_for(&pane, move |pane, cx| {
+                                        pane.pin_item(item_id, cx)
+                                    }),
+                                )
+                            }
+                        });
 
                     if let Some(entry) = single_entry_to_resolve {
                         let parent_abs_path = pane
@@ -1796,22 +1957,33 @@ impl Pane {
         let navigate_backward = IconButton::new("navigate_backward", IconName::ArrowLeft)
             .shape(IconButtonShape::Square)
             .icon_size(IconSize::Small)
-            .on_click({
-                let view = cx.view().clone();
-                move |_, cx| view.update(cx, Self::navigate_backward)
-            })
+            .on_click(cx.listener(|pane, _, cx| {
+                if !pane.nav_history_menu_opened_by_hold() {
+                    pane.navigate_backward(cx);
+                }
+            }))
             .disabled(!self.can_navigate_backward())
             .tooltip(|cx| Tooltip::for_action("Go Back", &GoBack, cx));
+        let navigate_backward =
+            self.render_nav_history_menu_trigger(navigate_backward, NavigationMode::GoingBack, cx);
 
         let navigate_forward = IconButton::new("navigate_forward", IconName::ArrowRight)
             .shape(IconButtonShape::Square)
             .icon_size(IconSize::Small)
-            .on_click({
-                let view = cx.view().clone();
-                move |_, cx| view.update(cx, Self::navigate_forward)
-            })
+            .on_click(cx.listener(|pane, _, cx| {
+                if !pane.nav_history_menu_opened_by_hold() {
+                    pane.navigate_forward(cx);
+                }
+            }))
             .disabled(!self.can_navigate_forward())
             .tooltip(|cx| Tooltip::for_action("Go Forward", &GoForward, cx));
+        let navigate_forward = self.render_nav_history_menu_trigger(
+            navigate_forward,
+            NavigationMode::GoingForward,
+            cx,
+        );
+
+        let overflowing_tabs = self.overflowing_tab_indices();
 
         TabBar::new("tab_bar")
             .track_scroll(self.tab_bar_scroll_handle.clone())
@@ -1819,6 +1991,10 @@ impl Pane {
                 self.display_nav_history_buttons.unwrap_or_default(),
                 |tab_bar| tab_bar.start_children(vec![navigate_backward, navigate_forward]),
             )
+            .when(
+                !overflowing_tabs.is_empty() || self.tab_overflow_menu.is_some(),
+                |tab_bar| tab_bar.end_child(self.render_tab_overflow_button(overflowing_tabs, cx)),
+            )
             .when(self.has_focus(cx), |tab_bar| {
                 tab_bar.end_child({
                     let render_tab_buttons = self.render_tab_bar_buttons.clone();
@@ -1853,7 +2029,7 @@ impl Pane {
                     }))
                     .on_drop(cx.listener(move |this, selection: &DraggedSelection, cx| {
                         this.drag_split_direction = None;
-                        this.handle_project_entry_drop(&selection.active_selection.entry_id, cx)
+                        this.handle_project_entry_drop(selection, cx)
                     }))
                     .on_drop(cx.listener(move |this, paths, cx| {
                         this.drag_split_direction = None;
@@ -1867,6 +2043,188 @@ impl Pane {
             )
     }
 
+    /// The indices of the tabs that don't fit in the tab bar, as of its last layout.
+    fn overflowing_tab_indices(&self) -> Vec<usize> {
+        let bar_bounds = self.tab_bar_scroll_handle.bounds();
+        let offset = self.tab_bar_scroll_handle.offset();
+        (0..self.items.len())
+            .filter(|&ix| {
+                self.tab_bar_scroll_handle
+                    .bounds_for_item(ix)
+                    .map_or(false, |tab_bounds| {
+                        tab_bounds.left() + offset.x < bar_bounds.left()
+                            || tab_bounds.right() + offset.x > bar_bounds.right()
+                    })
+            })
+            .collect()
+    }
+
+    fn render_tab_overflow_button(
+        &self,
+        overflowing_tabs: Vec<usize>,
+        cx: &mut ViewContext<Self>,
+    ) -> impl IntoElement {
+        h_flex()
+            .child(
+                IconButton::new("tab_overflow", IconName::ChevronDown)
+                    .icon_size(IconSize::Small)
+                    .selected(self.tab_overflow_menu.is_some())
+                    .on_click(cx.listener(move |pane, _, cx| {
+                        let items = overflowing_tabs
+                            .iter()
+                            .filter_map(|&ix| {
+                                let item = pane.items.get(ix)?;
+                                let label = item
+                                    .tab_description(0, cx)
+                                    .unwrap_or_else(|| "untitled".into());
+                                Some((item.item_id(), label))
+                            })
+                            .collect::<Vec<_>>();
+                        let pane_handle = cx.view().clone();
+                        let menu = ContextMenu::build(cx, move |mut menu, cx| {
+                            for (item_id, label) in items {
+                                menu = menu.entry(
+                                    label,
+                                    None,
+                                    cx.handler_for(&pane_handle, move |pane, cx| {
+                                        if let Some(ix) = pane
+                                            .items
+                                            .iter()
+                                            .position(|item| item.item_id() == item_id)
+                                        {
+                                            pane.activate_item(ix, true, true, cx);
+                                        }
+                                    }),
+                                );
+                            }
+                            menu
+                        });
+                        cx.subscribe(&menu, |pane, _, _: &DismissEvent, cx| {
+                            pane.tab_overflow_menu = None;
+                            cx.notify();
+                        })
+                        .detach();
+                        pane.tab_overflow_menu = Some(menu);
+                    }))
+                    .tooltip(|cx| Tooltip::text("Show Hidden Tabs", cx)),
+            )
+            .when_some(self.tab_overflow_menu.as_ref(), |el, tab_overflow_menu| {
+                el.child(Self::render_menu_overlay(tab_overflow_menu))
+            })
+    }
+
+    fn empty_pane_placeholder(&mut self, cx: &mut ViewContext<Self>) -> Option<AnyView> {
+        if self.empty_pane_placeholder.is_none() {
+            let render_placeholder = cx.try_global::<EmptyPanePlaceholder>()?.0.clone();
+            self.empty_pane_placeholder = Some(render_placeholder(self.workspace.clone(), cx));
+        }
+        self.empty_pane_placeholder.clone()
+    }
+
+    /// Wraps a navigation button so that holding it down, or right-clicking it, opens the menu
+    /// of the locations that navigating in its direction leads to.
+    fn render_nav_history_menu_trigger(
+        &self,
+        button: IconButton,
+        mode: NavigationMode,
+        cx: &mut ViewContext<Self>,
+    ) -> AnyElement {
+        let menu = self
+            .nav_history_menu
+            .as_ref()
+            .filter(|(menu_mode, _)| *menu_mode == mode)
+            .map(|(_, menu)| menu.clone());
+        div()
+            .on_mouse_down(
+                MouseButton::Left,
+                cx.listener(move |pane, _, cx| {
+                    pane.nav_history_menu_task = Some(cx.spawn(|pane, mut cx| async move {
+                        cx.background_executor().timer(NAV_HISTORY_MENU_DELAY).await;
+                        pane.update(&mut cx, |pane, cx| pane.deploy_nav_history_menu(mode, cx))
+                            .ok();
+                    }));
+                }),
+            )
+            .on_mouse_up_out(
+                MouseButton::Left,
+                cx.listener(|pane, _, _| {
+                    pane.nav_history_menu_task = None;
+                }),
+            )
+            .on_mouse_down(
+                MouseButton::Right,
+                cx.listener(move |pane, _, cx| pane.deploy_nav_history_menu(mode, cx)),
+            )
+            .child(button.selected(menu.is_some()))
+            .when_some(menu, |el, menu| {
+                el.child(div().absolute().bottom_0().left_0().size_0().child(
+                    deferred(anchored().anchor(AnchorCorner::TopLeft).child(menu)).with_priority(1),
+                ))
+            })
+            .into_any_element()
+    }
+
+    /// Whether the click on a navigation button ends holding it down long enough to open the
+    /// navigation history menu, in which case it doesn't navigate.
+    fn nav_history_menu_opened_by_hold(&mut self) -> bool {
+        self.nav_history_menu_task.take().is_some() && self.nav_history_menu.is_some()
+    }
+
+    fn deploy_nav_history_menu(&mut self, mode: NavigationMode, cx: &mut ViewContext<Self>) {
+        let entries = self.nav_history.entries(mode, cx);
+        if entries.is_empty() {
+            return;
+        }
+
+        let pane = cx.view().downgrade();
+        let workspace = self.workspace.clone();
+        let menu = ContextMenu::build(cx, move |mut menu, _| {
+            for entry in entries {
+                let title = entry.title();
+                let preview = entry.location.map(|location| {
+                    SharedString::from(truncate_and_trailoff(
+                        &location.preview,
+                        NAV_HISTORY_PREVIEW_LEN,
+                    ))
+                });
+                let pane = pane.clone();
+                let workspace = workspace.clone();
+                menu = menu.custom_entry(
+                    move |_| {
+                        v_flex()
+                            .child(Label::new(title.clone()))
+                            .when_some(preview.clone(), |el, preview| {
+                                el.child(
+                                    Label::new(preview)
+                                        .size(LabelSize::Small)
+                                        .color(Color::Muted),
+                                )
+                            })
+                            .into_any_element()
+                    },
+                    move |cx| {
+                        workspace
+                            .update(cx, |workspace, cx| {
+                                workspace
+                                    .navigate_history_steps(pane.clone(), mode, entry.steps, cx)
+                                    .detach_and_log_err(cx)
+                            })
+                            .ok();
+                    },
+                );
+            }
+            menu
+        });
+        cx.subscribe(&menu, |pane, _, _: &DismissEvent, cx| {
+            pane.nav_history_menu = None;
+            cx.notify();
+        })
+        .detach();
+        cx.focus_view(&menu);
+        self.nav_history_menu = Some((mode, menu));
+        cx.notify();
+    }
+
     pub fn render_menu_overlay(menu: &View<ContextMenu>) -> Div {
         div().absolute().bottom_0().right_0().size_0().child(
             deferred(
@@ -1963,30 +2321,61 @@ impl Pane {
             .log_err();
     }
 
+    /// Lets the active item handle something dropped onto it, unless it's being dropped to split
+    /// the pane.
+    fn handle_drop_on_active_item(
+        &mut self,
+        dropped: &dyn Any,
+        cx: &mut ViewContext<Self>,
+    ) -> bool {
+        if self.drag_split_direction.is_some() {
+            return false;
+        }
+        self.active_item()
+            .map_or(false, |item| item.handle_drop(dropped, cx))
+    }
+
+    /// Opens the files of the project entries dropped onto the pane, in a new pane when they're
+    /// dropped to split this one.
     fn handle_project_entry_drop(
         &mut self,
-        project_entry_id: &ProjectEntryId,
+        selection: &DraggedSelection,
         cx: &mut ViewContext<'_, Self>,
     ) {
         if let Some(custom_drop_handle) = self.custom_drop_handle.clone() {
+            let project_entry_id = &selection.active_selection.entry_id;
             if let ControlFlow::Break(()) = custom_drop_handle(self, project_entry_id, cx) {
                 return;
             }
         }
         let mut to_pane = cx.view().clone();
         let split_direction = self.drag_split_direction;
-        let project_entry_id = *project_entry_id;
+        let project_entry_ids = selection
+            .items()
+            .map(|selected| selected.entry_id)
+            .collect::<Vec<_>>();
         self.workspace
             .update(cx, |_, cx| {
                 cx.defer(move |workspace, cx| {
-                    if let Some(path) = workspace
-                        .project()
-                        .read(cx)
-                        .path_for_entry(project_entry_id, cx)
-                    {
-                        if let Some(split_direction) = split_direction {
-                            to_pane = workspace.split_pane(to_pane, split_direction, cx);
-                        }
+                    let project = workspace.project().read(cx);
+                    let paths = project_entry_ids
+                        .into_iter()
+                        .filter_map(|entry_id| {
+                            let worktree = project.worktree_for_entry(entry_id, cx)?.read(cx);
+                            let entry = worktree.entry_for_id(entry_id)?;
+                            entry.is_file().then(|| ProjectPath {
+                                worktree_id: worktree.id(),
+                                path: entry.path.clone(),
+                            })
+                        })
+                        .collect::<Vec<_>>();
+                    if paths.is_empty() {
+                        return;
+                    }
+                    if let Some(split_direction) = split_direction {
+                        to_pane = workspace.split_pane(to_pane, split_direction, cx);
+                    }
+                    for path in paths {
                         workspace
                             .open_path(path, Some(to_pane.downgrade()), true, cx)
                             .detach_and_log_err(cx);
@@ -2090,6 +2479,12 @@ impl Render for Pane {
 
         let should_display_tab_bar = self.should_display_tab_bar.clone();
         let display_tab_bar = should_display_tab_bar(cx);
+        let empty_pane_placeholder =
+            if self.active_item().is_none() && self.project.read(cx).worktrees().next().is_none() {
+                self.empty_pane_placeholder(cx)
+            } else {
+                None
+            };
 
         v_flex()
             .key_context(key_context)
@@ -2121,6 +2516,16 @@ impl Render for Pane {
             .on_action(cx.listener(|pane: &mut Pane, _: &ActivateNextItem, cx| {
                 pane.activate_next_item(true, cx);
             }))
+            .on_action(cx.listener(|pane: &mut Pane, _: &PinTab, cx| {
+                if let Some(active_item_id) = pane.active_item().map(|i| i.item_id()) {
+                    pane.pin_item(active_item_id, cx);
+                }
+            }))
+            .on_action(cx.listener(|pane: &mut Pane, _: &UnpinTab, cx| {
+                if let Some(active_item_id) = pane.active_item().map(|i| i.item_id()) {
+                    pane.unpin_item(active_item_id, cx);
+                }
+            }))
             .when(PreviewTabsSettings::get_global(cx).enabled, |this| {
                 this.on_action(cx.listener(|pane: &mut Pane, _: &TogglePreviewTab, cx| {
                     if let Some(active_item_id) = pane.active_item().map(|i| i.item_id()) {
@@ -2214,6 +2619,8 @@ impl Render for Pane {
                             let placeholder = div.h_flex().size_full().justify_center();
                             if has_worktrees {
                                 placeholder
+                            } else if let Some(empty_pane_placeholder) = empty_pane_placeholder {
+                                placeholder.child(empty_pane_placeholder)
                             } else {
                                 placeholder.child(
                                     Label::new("Open a file or project to get started.")
@@ -2238,12 +2645,15 @@ impl Render for Pane {
                                 this.handle_tab_drop(dragged_tab, this.active_item_index(), cx)
                             }))
                             .on_drop(cx.listener(move |this, selection: &DraggedSelection, cx| {
-                                this.handle_project_entry_drop(
-                                    &selection.active_selection.entry_id,
-                                    cx,
-                                )
+                                if this.handle_drop_on_active_item(selection, cx) {
+                                    return;
+                                }
+                                this.handle_project_entry_drop(selection, cx)
                             }))
-                            .on_drop(cx.listener(move |this, paths, cx| {
+                            .on_drop(cx.listener(move |this, paths: &ExternalPaths, cx| {
+                                if this.handle_drop_on_active_item(paths, cx) {
+                                    return;
+                                }
                                 this.handle_external_paths_drop(paths, cx)
                             }))
                             .map(|div| {
@@ -2444,6 +2854,42 @@ impl NavHistory {
     pub fn path_for_item(&self, item_id: EntityId) -> Option<(ProjectPath, Option<PathBuf>)> {
         self.0.lock().paths_by_item.get(&item_id).cloned()
     }
+
+    /// The locations that going back or forward leads to, nearest first. Entries whose item was
+    /// closed and can't be reopened are left out.
+    pub fn entries(&self, mode: NavigationMode, cx: &AppContext) -> Vec<NavigationHistoryEntry> {
+        let state = self.0.lock();
+        let stack = match mode {
+            NavigationMode::GoingBack => &state.backward_stack,
+            NavigationMode::GoingForward => &state.forward_stack,
+            _ => return Vec::new(),
+        };
+        stack
+            .iter()
+            .rev()
+            .enumerate()
+            .filter_map(|(ix, entry)| {
+                let item = entry.item.upgrade();
+                let path = match state.paths_by_item.get(&entry.item.id()) {
+                    Some((path, _)) => Some(path.clone()),
+                    None => item.as_ref().and_then(|item| item.project_path(cx)),
+                };
+                let label = match (path, &item) {
+                    (Some(path), _) => path.path.to_string_lossy().to_string().into(),
+                    (None, Some(item)) => item.tab_description(0, cx)?,
+                    (None, None) => return None,
+                };
+                let location = item
+                    .zip(entry.data.as_ref())
+                    .and_then(|(item, data)| item.navigation_location(&**data, cx));
+                Some(NavigationHistoryEntry {
+                    steps: ix + 1,
+                    label,
+                    location,
+                })
+            })
+            .collect()
+    }
 }
 
 impl NavHistoryState {
@@ -2970,6 +3416,64 @@ mod tests {
         assert_item_labels(&pane, [], cx);
     }
 
+    #[gpui::test]
+    async fn test_pinned_tabs(cx: &mut TestAppContext) {
+        init_test(cx);
+        let fs = FakeFs::new(cx.executor());
+
+        let project = Project::test(fs, None, cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let pane = workspace.update(cx, |workspace, _| workspace.active_pane().clone());
+
+        let [_, _, item_c, item_d, _] = set_labeled_items(&pane, ["A", "B", "C", "D*", "E"], cx);
+
+        // Pinned tabs move to the start of the pane, in the order they're pinned.
+        pane.update(cx, |pane, cx| {
+            pane.pin_item(item_d.item_id(), cx);
+            pane.pin_item(item_c.item_id(), cx);
+        });
+        assert_item_labels(&pane, ["D*", "C", "A", "B", "E"], cx);
+
+        // New items are added after the pinned tabs.
+        pane.update(cx, |pane, cx| pane.activate_item(0, false, false, cx));
+        add_labeled_item(&pane, "F", false, cx);
+        assert_item_labels(&pane, ["D", "C", "F*", "A", "B", "E"], cx);
+
+        // Pinned tabs aren't closed along with the others.
+        pane.update(cx, |pane, cx| {
+            pane.close_inactive_items(&CloseInactiveItems { save_intent: None }, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap();
+        assert_item_labels(&pane, ["D", "C", "F*"], cx);
+
+        pane.update(cx, |pane, cx| {
+            pane.close_all_items(&CloseAllItems { save_intent: None }, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap();
+        assert_item_labels(&pane, ["D*", "C"], cx);
+
+        // Unpinned tabs move after the remaining pinned tabs.
+        pane.update(cx, |pane, cx| {
+            pane.unpin_item(item_d.item_id(), cx);
+            assert!(pane.is_pinned(item_c.item_id()));
+            assert!(!pane.is_pinned(item_d.item_id()));
+        });
+        assert_item_labels(&pane, ["C", "D*"], cx);
+
+        pane.update(cx, |pane, cx| {
+            pane.activate_item(0, false, false, cx);
+            pane.close_items_to_the_right(&CloseItemsToTheRight, cx)
+        })
+        .unwrap()
+        .await
+        .unwrap();
+        assert_item_labels(&pane, ["C*"], cx);
+    }
+
     fn init_test(cx: &mut TestAppContext) {
         cx.update(|cx| {
             let settings_store = SettingsStore::test(cx);
@@ -3002,6 +3506,7 @@ mod tests {
     ) -> [Box<View<TestItem>>; COUNT] {
         pane.update(cx, |pane, cx| {
             pane.items.clear();
+            pane.pinned_tab_count = 0;
             let mut active_item_index = 0;
 
             let mut index = 0;
@@ -3,12 +3,12 @@ use anyhow::{anyhow, Result};
 use call::{ActiveCall, ParticipantLocation};
 use collections::HashMap;
 use gpui::{
-    point, size, AnyView, AnyWeakView, Axis, Bounds, IntoElement, Model, MouseButton, Pixels,
-    Point, StyleRefinement, View, ViewContext,
+    point, size, AnyElement, AnyView, AnyWeakView, Axis, Bounds, IntoElement, Model, MouseButton,
+    Pixels, Point, StyleRefinement, View, ViewContext, WeakView, WindowContext,
 };
 use parking_lot::Mutex;
 use project::Project;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use ui::prelude::*;
 
@@ -114,17 +114,69 @@ impl PaneGroup {
         )
     }
 
-    pub(crate) fn panes(&self) -> Vec<&View<Pane>> {
+    /// Renders a group of panes living outside of the workspace's center, such as in a panel,
+    /// where there's no one to follow.
+    pub fn render_in_panel(
+        &self,
+        active_pane: &View<Pane>,
+        workspace: &WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> impl IntoElement {
+        self.root.render_in_panel(0, active_pane, workspace, cx)
+    }
+
+    pub fn panes(&self) -> Vec<&View<Pane>> {
         let mut panes = Vec::new();
         self.root.collect_panes(&mut panes);
         panes
     }
 
+    /// Returns the arrangement of the panes, with the value the given function returns for each
+    /// of them.
+    pub fn layout<T>(&self, f: &mut impl FnMut(&View<Pane>) -> T) -> PaneGroupLayout<T> {
+        self.root.layout(f)
+    }
+
+    /// Rebuilds a pane group from the arrangement of its panes.
+    pub fn from_layout(layout: PaneGroupLayout<View<Pane>>) -> Self {
+        Self::with_root(Member::from_layout(layout))
+    }
+
     pub(crate) fn first_pane(&self) -> View<Pane> {
         self.root.first_pane()
     }
 }
 
+/// The arrangement of the panes of a [`PaneGroup`], with a value standing for each pane, used to
+/// persist the pane groups living outside of the workspace's center.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PaneGroupLayout<T> {
+    Pane(T),
+    Axis {
+        /// Whether the members are side by side, rather than stacked.
+        horizontal: bool,
+        members: Vec<PaneGroupLayout<T>>,
+        flexes: Vec<f32>,
+    },
+}
+
+impl<T> PaneGroupLayout<T> {
+    pub fn map<U>(self, f: &mut impl FnMut(T) -> U) -> PaneGroupLayout<U> {
+        match self {
+            PaneGroupLayout::Pane(pane) => PaneGroupLayout::Pane(f(pane)),
+            PaneGroupLayout::Axis {
+                horizontal,
+                members,
+                flexes,
+            } => PaneGroupLayout::Axis {
+                horizontal,
+                members: members.into_iter().map(|member| member.map(f)).collect(),
+                flexes,
+            },
+        }
+    }
+}
+
 #[derive(Clone)]
 pub(crate) enum Member {
     Axis(PaneAxis),
@@ -303,6 +355,44 @@ impl Member {
         }
     }
 
+    fn render_in_panel(
+        &self,
+        basis: usize,
+        active_pane: &View<Pane>,
+        workspace: &WeakView<Workspace>,
+        cx: &mut WindowContext,
+    ) -> AnyElement {
+        match self {
+            Member::Pane(pane) => div()
+                .relative()
+                .flex_1()
+                .size_full()
+                .child(
+                    AnyView::from(pane.clone())
+                        .cached(StyleRefinement::default().v_flex().size_full()),
+                )
+                .into_any(),
+            Member::Axis(axis) => {
+                let mut active_pane_ix = None;
+                pane_axis(
+                    axis.axis,
+                    basis,
+                    axis.flexes.clone(),
+                    axis.bounding_boxes.clone(),
+                    workspace.clone(),
+                )
+                .children(axis.members.iter().enumerate().map(|(ix, member)| {
+                    if member.contains(active_pane) {
+                        active_pane_ix = Some(ix);
+                    }
+                    member.render_in_panel((basis + ix) * 10, active_pane, workspace, cx)
+                }))
+                .with_active_pane(active_pane_ix)
+                .into_any_element()
+            }
+        }
+    }
+
     fn collect_panes<'a>(&'a self, panes: &mut Vec<&'a View<Pane>>) {
         match self {
             Member::Axis(axis) => {
@@ -313,6 +403,40 @@ impl Member {
             Member::Pane(pane) => panes.push(pane),
         }
     }
+
+    fn layout<T>(&self, f: &mut impl FnMut(&View<Pane>) -> T) -> PaneGroupLayout<T> {
+        match self {
+            Member::Axis(axis) => PaneGroupLayout::Axis {
+                horizontal: axis.axis == Axis::Horizontal,
+                members: axis.members.iter().map(|member| member.layout(f)).collect(),
+                flexes: axis.flexes.lock().clone(),
+            },
+            Member::Pane(pane) => PaneGroupLayout::Pane(f(pane)),
+        }
+    }
+
+    fn from_layout(layout: PaneGroupLayout<View<Pane>>) -> Self {
+        match layout {
+            PaneGroupLayout::Pane(pane) => Member::Pane(pane),
+            PaneGroupLayout::Axis {
+                horizontal,
+                mut members,
+                flexes,
+            } => {
+                if members.len() == 1 {
+                    return Member::from_layout(members.remove(0));
+                }
+                let axis = if horizontal {
+                    Axis::Horizontal
+                } else {
+                    Axis::Vertical
+                };
+                let flexes = (flexes.len() == members.len()).then_some(flexes);
+                let members = members.into_iter().map(Member::from_layout).collect();
+                Member::Axis(PaneAxis::load(axis, members, flexes))
+            }
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -4,6 +4,7 @@ use std::path::Path;
 
 use anyhow::{anyhow, bail, Context, Result};
 use client::DevServerProjectId;
+use collections::HashMap;
 use db::{define_connection, query, sqlez::connection::Connection, sqlez_macros::sql};
 use gpui::{point, size, Axis, Bounds, WindowBounds};
 
@@ -23,7 +24,8 @@ use model::{
 };
 
 use self::model::{
-    DockStructure, LocalPathsOrder, SerializedDevServerProject, SerializedWorkspaceLocation,
+    DockStructure, LocalPathsOrder, RecentProjectDetails, SerializedDevServerProject,
+    SerializedWorkspaceLocation,
 };
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -223,6 +225,19 @@ define_connection! {
     //     position: usize, // Position of the item in the parent pane. This is equivalent to panes' position column
     //     active: bool, // Indicates if this item is the active one in the pane
     //     preview: bool // Indicates if this item is a preview item
+    //     pinned: bool // Indicates if this item's tab is pinned
+    // )
+    //
+    // keyboard_macros(
+    //     workspace_id: usize, // References workspaces table
+    //     name: String, // The slot of the macro
+    //     keystrokes: String, // A JSON array of keystrokes
+    // )
+    //
+    // workspace_layouts(
+    //     workspace_id: usize, // References workspaces table
+    //     name: String, // The name the layout was saved with
+    //     layout: String, // A JSON object of the panes, their files and the docks
     // )
     pub static ref DB: WorkspaceDb<()> =
     &[sql!(
@@ -366,6 +381,43 @@ define_connection! {
     sql!(
         ALTER TABLE workspaces ADD COLUMN local_paths_order BLOB;
     ),
+    sql!(
+        CREATE TABLE keyboard_macros (
+            workspace_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            keystrokes TEXT NOT NULL,
+            PRIMARY KEY(workspace_id, name),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+        ) STRICT;
+    ),
+    sql!(
+        CREATE TABLE workspace_layouts (
+            workspace_id INTEGER NOT NULL,
+            name TEXT NOT NULL,
+            layout TEXT NOT NULL,
+            PRIMARY KEY(workspace_id, name),
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+        ) STRICT;
+    ),
+    // Add pinned field to items
+    sql!(
+        ALTER TABLE items ADD COLUMN pinned INTEGER; //bool
+    ),
+    sql!(
+        CREATE TABLE recent_project_preferences (
+            workspace_id INTEGER PRIMARY KEY,
+            pinned INTEGER NOT NULL,
+            display_name TEXT,
+            color INTEGER,
+            FOREIGN KEY(workspace_id) REFERENCES workspaces(workspace_id)
+            ON DELETE CASCADE
+            ON UPDATE CASCADE
+        ) STRICT;
+    ),
     ];
 }
 
@@ -858,7 +910,7 @@ impl WorkspaceDb {
 
     fn get_items(&self, pane_id: PaneId) -> Result<Vec<SerializedItem>> {
         self.select_bound(sql!(
-            SELECT kind, item_id, active, preview FROM items
+            SELECT kind, item_id, active, preview, pinned FROM items
             WHERE pane_id = ?
                 ORDER BY position
         ))?(pane_id)
@@ -871,7 +923,7 @@ impl WorkspaceDb {
         items: &[SerializedItem],
     ) -> Result<()> {
         let mut insert = conn.exec_bound(sql!(
-            INSERT INTO items(workspace_id, pane_id, position, kind, item_id, active, preview) VALUES (?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO items(workspace_id, pane_id, position, kind, item_id, active, preview, pinned) VALUES (?, ?, ?, ?, ?, ?, ?, ?)
         )).context("Preparing insertion")?;
         for (position, item) in items.iter().enumerate() {
             insert((workspace_id, pane_id, position, item))?;
@@ -908,6 +960,90 @@ impl WorkspaceDb {
             WHERE workspace_id = ?1
         }
     }
+
+    query! {
+        pub(crate) async fn keyboard_macros(workspace_id: WorkspaceId) -> Result<Vec<(String, String)>> {
+            SELECT name, keystrokes
+            FROM keyboard_macros
+            WHERE workspace_id = ?
+        }
+    }
+
+    query! {
+        pub(crate) async fn save_keyboard_macro(workspace_id: WorkspaceId, name: String, keystrokes: String) -> Result<()> {
+            INSERT OR REPLACE INTO keyboard_macros(workspace_id, name, keystrokes)
+            VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        pub(crate) fn layout_names(workspace_id: WorkspaceId) -> Result<Vec<String>> {
+            SELECT name
+            FROM workspace_layouts
+            WHERE workspace_id = ?
+            ORDER BY name
+        }
+    }
+
+    query! {
+        pub(crate) fn layout(workspace_id: WorkspaceId, name: String) -> Result<Option<String>> {
+            SELECT layout
+            FROM workspace_layouts
+            WHERE workspace_id = ? AND name = ?
+        }
+    }
+
+    query! {
+        pub(crate) async fn save_layout(workspace_id: WorkspaceId, name: String, layout: String) -> Result<()> {
+            INSERT OR REPLACE INTO workspace_layouts(workspace_id, name, layout)
+            VALUES (?, ?, ?)
+        }
+    }
+
+    query! {
+        pub(crate) async fn delete_layout(workspace_id: WorkspaceId, name: String) -> Result<()> {
+            DELETE FROM workspace_layouts
+            WHERE workspace_id = ? AND name = ?
+        }
+    }
+
+    /// When each workspace was last opened, and how the user set it up in the recent projects.
+    pub fn recent_project_details(&self) -> Result<HashMap<WorkspaceId, RecentProjectDetails>> {
+        Ok(self
+            .recent_project_detail_rows()?
+            .into_iter()
+            .map(|(workspace_id, last_opened, pinned, display_name, color)| {
+                let details = RecentProjectDetails {
+                    last_opened,
+                    pinned: pinned.unwrap_or(false),
+                    display_name,
+                    color,
+                };
+                (workspace_id, details)
+            })
+            .collect())
+    }
+
+    query! {
+        fn recent_project_detail_rows() -> Result<Vec<(WorkspaceId, Option<i64>, Option<bool>, Option<String>, Option<u32>)>> {
+            SELECT
+                workspaces.workspace_id,
+                CAST(strftime('%s', workspaces.timestamp) AS INTEGER),
+                recent_project_preferences.pinned,
+                recent_project_preferences.display_name,
+                recent_project_preferences.color
+            FROM workspaces
+            LEFT JOIN recent_project_preferences
+            ON recent_project_preferences.workspace_id = workspaces.workspace_id
+        }
+    }
+
+    query! {
+        pub async fn save_recent_project_preferences(workspace_id: WorkspaceId, pinned: bool, display_name: Option<String>, color: Option<u32>) -> Result<()> {
+            INSERT OR REPLACE INTO recent_project_preferences(workspace_id, pinned, display_name, color)
+            VALUES (?, ?, ?, ?)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -1071,15 +1207,15 @@ mod tests {
                     vec![
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 5, false, false),
-                                SerializedItem::new("Terminal", 6, true, false),
+                                SerializedItem::new("Terminal", 5, false, false, false),
+                                SerializedItem::new("Terminal", 6, true, false, false),
                             ],
                             false,
                         )),
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 7, true, false),
-                                SerializedItem::new("Terminal", 8, false, false),
+                                SerializedItem::new("Terminal", 7, true, false, false),
+                                SerializedItem::new("Terminal", 8, false, false, false),
                             ],
                             false,
                         )),
@@ -1087,8 +1223,8 @@ mod tests {
                 ),
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 9, false, false),
-                        SerializedItem::new("Terminal", 10, true, false),
+                        SerializedItem::new("Terminal", 9, false, false, false),
+                        SerializedItem::new("Terminal", 10, true, false, false),
                     ],
                     false,
                 )),
@@ -1207,6 +1343,43 @@ mod tests {
         );
     }
 
+    #[gpui::test]
+    async fn test_recent_project_preferences() {
+        env_logger::try_init().ok();
+
+        let db = WorkspaceDb(open_test_db("test_recent_project_preferences").await);
+
+        let workspace = SerializedWorkspace {
+            id: WorkspaceId(1),
+            location: LocalPaths::new(["/tmp"]).into(),
+            center_group: Default::default(),
+            window_bounds: Default::default(),
+            display: Default::default(),
+            docks: Default::default(),
+            centered_layout: false,
+        };
+        db.save_workspace(workspace).await;
+
+        let details = db.recent_project_details().unwrap();
+        let details = &details[&WorkspaceId(1)];
+        assert!(details.last_opened.is_some());
+        assert!(!details.pinned);
+        assert_eq!(details.display_name, None);
+
+        db.save_recent_project_preferences(WorkspaceId(1), true, Some("Temp".to_string()), Some(2))
+            .await
+            .unwrap();
+        let details = db.recent_project_details().unwrap();
+        let details = &details[&WorkspaceId(1)];
+        assert!(details.pinned);
+        assert_eq!(details.display_name.as_deref(), Some("Temp"));
+        assert_eq!(details.color, Some(2));
+
+        // The preferences are removed along with the workspace.
+        db.delete_workspace_by_id(WorkspaceId(1)).await.unwrap();
+        assert!(db.recent_project_details().unwrap().is_empty());
+    }
+
     use crate::persistence::model::SerializedWorkspace;
     use crate::persistence::model::{SerializedItem, SerializedPane, SerializedPaneGroup};
 
@@ -1244,15 +1417,15 @@ mod tests {
                     vec![
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 1, false, false),
-                                SerializedItem::new("Terminal", 2, true, false),
+                                SerializedItem::new("Terminal", 1, false, false, false),
+                                SerializedItem::new("Terminal", 2, true, false, false),
                             ],
                             false,
                         )),
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 4, false, false),
-                                SerializedItem::new("Terminal", 3, true, false),
+                                SerializedItem::new("Terminal", 4, false, false, false),
+                                SerializedItem::new("Terminal", 3, true, false, false),
                             ],
                             true,
                         )),
@@ -1260,8 +1433,8 @@ mod tests {
                 ),
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 5, true, false),
-                        SerializedItem::new("Terminal", 6, false, false),
+                        SerializedItem::new("Terminal", 5, true, false, false),
+                        SerializedItem::new("Terminal", 6, false, false, false),
                     ],
                     false,
                 )),
@@ -1291,15 +1464,15 @@ mod tests {
                     vec![
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 1, false, false),
-                                SerializedItem::new("Terminal", 2, true, false),
+                                SerializedItem::new("Terminal", 1, false, false, false),
+                                SerializedItem::new("Terminal", 2, true, false, false),
                             ],
                             false,
                         )),
                         SerializedPaneGroup::Pane(SerializedPane::new(
                             vec![
-                                SerializedItem::new("Terminal", 4, false, false),
-                                SerializedItem::new("Terminal", 3, true, false),
+                                SerializedItem::new("Terminal", 4, false, false, false),
+                                SerializedItem::new("Terminal", 3, true, false, false),
                             ],
                             true,
                         )),
@@ -1307,8 +1480,8 @@ mod tests {
                 ),
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 5, false, false),
-                        SerializedItem::new("Terminal", 6, true, false),
+                        SerializedItem::new("Terminal", 5, false, false, false),
+                        SerializedItem::new("Terminal", 6, true, false, false),
                     ],
                     false,
                 )),
@@ -1326,15 +1499,15 @@ mod tests {
             vec![
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 1, false, false),
-                        SerializedItem::new("Terminal", 2, true, false),
+                        SerializedItem::new("Terminal", 1, false, false, false),
+                        SerializedItem::new("Terminal", 2, true, false, false),
                     ],
                     false,
                 )),
                 SerializedPaneGroup::Pane(SerializedPane::new(
                     vec![
-                        SerializedItem::new("Terminal", 4, true, false),
-                        SerializedItem::new("Terminal", 3, false, false),
+                        SerializedItem::new("Terminal", 4, true, false, false),
+                        SerializedItem::new("Terminal", 3, false, false, false),
                     ],
                     true,
                 )),
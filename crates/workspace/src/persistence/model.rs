@@ -24,6 +24,17 @@ pub struct SerializedDevServerProject {
     pub path: String,
 }
 
+/// How the user set up a workspace in the recent projects, and when it was last opened.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct RecentProjectDetails {
+    /// The Unix timestamp of when the workspace was last opened.
+    pub last_opened: Option<i64>,
+    pub pinned: bool,
+    pub display_name: Option<String>,
+    /// The index of the player color to mark the project with.
+    pub color: Option<u32>,
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct LocalPaths(Arc<Vec<PathBuf>>);
 
@@ -349,13 +360,20 @@ impl SerializedPane {
         }
 
         let mut items = Vec::new();
-        for item_handle in futures::future::join_all(item_tasks).await {
+        for (item_handle, item) in futures::future::join_all(item_tasks)
+            .await
+            .into_iter()
+            .zip(&self.children)
+        {
             let item_handle = item_handle.log_err();
             items.push(item_handle.clone());
 
             if let Some(item_handle) = item_handle {
                 pane.update(cx, |pane, cx| {
                     pane.add_item(item_handle.clone(), true, true, None, cx);
+                    if item.pinned {
+                        pane.pin_item(item_handle.item_id(), cx);
+                    }
                 })?;
             }
         }
@@ -388,15 +406,23 @@ pub struct SerializedItem {
     pub item_id: ItemId,
     pub active: bool,
     pub preview: bool,
+    pub pinned: bool,
 }
 
 impl SerializedItem {
-    pub fn new(kind: impl AsRef<str>, item_id: ItemId, active: bool, preview: bool) -> Self {
+    pub fn new(
+        kind: impl AsRef<str>,
+        item_id: ItemId,
+        active: bool,
+        preview: bool,
+        pinned: bool,
+    ) -> Self {
         Self {
             kind: Arc::from(kind.as_ref()),
             item_id,
             active,
             preview,
+            pinned,
         }
     }
 }
@@ -409,13 +435,14 @@ impl Default for SerializedItem {
             item_id: 100000,
             active: false,
             preview: false,
+            pinned: false,
         }
     }
 }
 
 impl StaticColumnCount for SerializedItem {
     fn column_count() -> usize {
-        4
+        5
     }
 }
 impl Bind for &SerializedItem {
@@ -423,7 +450,8 @@ impl Bind for &SerializedItem {
         let next_index = statement.bind(&self.kind, start_index)?;
         let next_index = statement.bind(&self.item_id, next_index)?;
         let next_index = statement.bind(&self.active, next_index)?;
-        statement.bind(&self.preview, next_index)
+        let next_index = statement.bind(&self.preview, next_index)?;
+        statement.bind(&self.pinned, next_index)
     }
 }
 
@@ -433,12 +461,14 @@ impl Column for SerializedItem {
         let (item_id, next_index) = ItemId::column(statement, next_index)?;
         let (active, next_index) = bool::column(statement, next_index)?;
         let (preview, next_index) = bool::column(statement, next_index)?;
+        let (pinned, next_index) = bool::column(statement, next_index)?;
         Ok((
             SerializedItem {
                 kind,
                 item_id,
                 active,
                 preview,
+                pinned,
             },
             next_index,
         ))
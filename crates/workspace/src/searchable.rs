@@ -70,6 +70,17 @@ pub trait SearchableItem: Item + EventEmitter<SearchEvent> {
     fn query_suggestion(&mut self, cx: &mut ViewContext<Self>) -> String;
     fn activate_match(&mut self, index: usize, matches: &[Self::Match], cx: &mut ViewContext<Self>);
     fn select_matches(&mut self, matches: &[Self::Match], cx: &mut ViewContext<Self>);
+    /// Selects a capture group of the regex query in each match, skipping the matches
+    /// where the group didn't participate.
+    fn select_match_captures(
+        &mut self,
+        matches: &[Self::Match],
+        _query: &SearchQuery,
+        _group: &str,
+        cx: &mut ViewContext<Self>,
+    ) {
+        self.select_matches(matches, cx);
+    }
     fn replace(&mut self, _: &Self::Match, _: &SearchQuery, _: &mut ViewContext<Self>);
     fn match_index_for_direction(
         &mut self,
@@ -117,6 +128,13 @@ pub trait SearchableItemHandle: ItemHandle {
     fn query_suggestion(&self, cx: &mut WindowContext) -> String;
     fn activate_match(&self, index: usize, matches: &AnyVec<dyn Send>, cx: &mut WindowContext);
     fn select_matches(&self, matches: &AnyVec<dyn Send>, cx: &mut WindowContext);
+    fn select_match_captures(
+        &self,
+        matches: &AnyVec<dyn Send>,
+        query: &SearchQuery,
+        group: &str,
+        cx: &mut WindowContext,
+    );
     fn replace(
         &self,
         _: any_vec::element::ElementRef<'_, dyn Send>,
@@ -189,6 +207,19 @@ impl<T: SearchableItem> SearchableItemHandle for View<T> {
         self.update(cx, |this, cx| this.select_matches(matches.as_slice(), cx));
     }
 
+    fn select_match_captures(
+        &self,
+        matches: &AnyVec<dyn Send>,
+        query: &SearchQuery,
+        group: &str,
+        cx: &mut WindowContext,
+    ) {
+        let matches = matches.downcast_ref().unwrap();
+        self.update(cx, |this, cx| {
+            this.select_match_captures(matches.as_slice(), query, group, cx)
+        });
+    }
+
     fn match_index_for_direction(
         &self,
         matches: &AnyVec<dyn Send>,
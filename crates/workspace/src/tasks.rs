@@ -1,8 +1,9 @@
+use gpui::Action;
 use project::TaskSourceKind;
 use task::{ResolvedTask, TaskContext, TaskTemplate};
 use ui::ViewContext;
 
-use crate::Workspace;
+use crate::{notifications::NotificationId, Toast, TrustWorkspace, Workspace};
 
 pub fn schedule_task(
     workspace: &Workspace,
@@ -32,7 +33,25 @@ pub fn schedule_resolved_task(
     omit_history: bool,
     cx: &mut ViewContext<'_, Workspace>,
 ) {
+    if workspace.project().read(cx).is_restricted() {
+        struct RestrictedTask;
+
+        cx.defer(|workspace, cx| {
+            workspace.show_toast(
+                Toast::new(
+                    NotificationId::unique::<RestrictedTask>(),
+                    "Tasks don't run until the workspace is trusted",
+                )
+                .on_click("Trust Workspace", |cx| {
+                    cx.dispatch_action(TrustWorkspace.boxed_clone())
+                }),
+                cx,
+            );
+        });
+        return;
+    }
     if let Some(spawn_in_terminal) = resolved_task.resolved.take() {
+        let debug_adapter_config = resolved_task.debug_adapter_config.clone();
         if !omit_history {
             resolved_task.resolved = Some(spawn_in_terminal.clone());
             workspace.project().update(cx, |project, cx| {
@@ -41,6 +60,20 @@ pub fn schedule_resolved_task(
                 })
             });
         }
-        cx.emit(crate::Event::SpawnTask(spawn_in_terminal));
+        if let Some(debug_adapter_config) = debug_adapter_config {
+            if debug_adapter_config.picks_process() {
+                cx.emit(crate::Event::PickProcessToDebug(debug_adapter_config));
+                return;
+            }
+            workspace.project().update(cx, |project, cx| {
+                project.dap_store().update(cx, |dap_store, cx| {
+                    dap_store
+                        .start_session(debug_adapter_config, cx)
+                        .detach_and_log_err(cx);
+                })
+            });
+        } else {
+            cx.emit(crate::Event::SpawnTask(spawn_in_terminal));
+        }
     }
 }
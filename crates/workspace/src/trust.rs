@@ -0,0 +1,42 @@
+use anyhow::Result;
+use db::kvp::KEY_VALUE_STORE;
+use std::path::{Path, PathBuf};
+use util::ResultExt;
+
+const TRUSTED_PATHS_KEY: &str = "trusted_workspace_paths";
+
+/// The folders the user trusted, shared by all workspaces. Everything within them is trusted.
+pub fn trusted_paths() -> Vec<PathBuf> {
+    KEY_VALUE_STORE
+        .read_kvp(TRUSTED_PATHS_KEY)
+        .log_err()
+        .flatten()
+        .and_then(|paths| serde_json::from_str(&paths).log_err())
+        .unwrap_or_default()
+}
+
+pub fn is_trusted(path: &Path) -> bool {
+    trusted_paths()
+        .iter()
+        .any(|trusted_path| path.starts_with(trusted_path))
+}
+
+/// Trusts the given folders, along with everything within them.
+pub async fn trust_paths(paths: impl IntoIterator<Item = PathBuf>) -> Result<()> {
+    let mut trusted_paths = trusted_paths();
+    for path in paths {
+        if !trusted_paths
+            .iter()
+            .any(|trusted_path| path.starts_with(trusted_path))
+        {
+            trusted_paths.retain(|trusted_path| !trusted_path.starts_with(&path));
+            trusted_paths.push(path);
+        }
+    }
+    KEY_VALUE_STORE
+        .write_kvp(
+            TRUSTED_PATHS_KEY.to_string(),
+            serde_json::to_string(&trusted_paths)?,
+        )
+        .await
+}
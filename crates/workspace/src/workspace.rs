@@ -1,5 +1,8 @@
+mod clipboard_history;
 pub mod dock;
 pub mod item;
+mod keyboard_macro;
+mod layouts;
 mod modal_layer;
 pub mod notifications;
 pub mod pane;
@@ -10,6 +13,7 @@ pub mod shared_screen;
 mod status_bar;
 pub mod tasks;
 mod toolbar;
+pub mod trust;
 mod workspace_settings;
 
 use anyhow::{anyhow, Context as _, Result};
@@ -18,6 +22,7 @@ use client::{
     proto::{self, ErrorCode, PeerId},
     ChannelId, Client, ErrorExt, ProjectId, Status, TypedEnvelope, UserStore,
 };
+pub use clipboard_history::{ClipboardHistory, ClipboardHistoryEntry};
 use collections::{hash_map, HashMap, HashSet};
 use derive_more::{Deref, DerefMut};
 use dock::{Dock, DockPosition, Panel, PanelButtons, PanelHandle};
@@ -39,16 +44,25 @@ use item::{
     ProjectItem,
 };
 use itertools::Itertools;
+use keyboard_macro::KeyboardMacros;
+pub use keyboard_macro::{
+    MacroStep, ReplayMacro, StartRecordingMacro, StopRecordingMacro, DEFAULT_MACRO_SLOT,
+};
 use language::{LanguageRegistry, Rope};
 use lazy_static::lazy_static;
 pub use modal_layer::*;
 use node_runtime::NodeRuntime;
-use notifications::{simple_message_notification::MessageNotification, NotificationHandle};
+use notifications::{
+    simple_message_notification::MessageNotification, DetachAndPromptErr, NotificationHandle,
+};
 pub use pane::*;
 pub use pane_group::*;
 use persistence::{model::SerializedWorkspace, SerializedWindowBounds, DB};
 pub use persistence::{
-    model::{ItemId, LocalPaths, SerializedDevServerProject, SerializedWorkspaceLocation},
+    model::{
+        ItemId, LocalPaths, RecentProjectDetails, SerializedDevServerProject,
+        SerializedWorkspaceLocation,
+    },
     WorkspaceDb, DB as WORKSPACE_DB,
 };
 use postage::stream::Stream;
@@ -75,7 +89,7 @@ use std::{
     sync::{atomic::AtomicUsize, Arc, Weak},
     time::Duration,
 };
-use task::SpawnInTerminal;
+use task::{DebugAdapterConfig, SpawnInTerminal};
 use theme::{ActiveTheme, SystemAppearance, ThemeSettings};
 pub use toolbar::{Toolbar, ToolbarItemEvent, ToolbarItemLocation, ToolbarItemView};
 pub use ui;
@@ -137,6 +151,8 @@ actions!(
         ToggleBottomDock,
         ToggleCenteredLayout,
         CloseAllDocks,
+        TrustWorkspace,
+        ShowNavigationHistory,
     ]
 );
 
@@ -280,6 +296,7 @@ pub fn init_settings(cx: &mut AppContext) {
 pub fn init(app_state: Arc<AppState>, cx: &mut AppContext) {
     init_settings(cx);
     notifications::init(cx);
+    keyboard_macro::init(cx);
 
     cx.on_action(Workspace::close_global);
     cx.on_action(restart);
@@ -539,12 +556,16 @@ pub enum Event {
     ContactRequestedJoin(u64),
     WorkspaceCreated(WeakView<Workspace>),
     SpawnTask(SpawnInTerminal),
+    /// A debug task attaching to a process was spawned, and the process has to be picked.
+    PickProcessToDebug(DebugAdapterConfig),
     OpenBundledFile {
         text: Cow<'static, str>,
         title: &'static str,
         language: &'static str,
     },
     ZoomChanged,
+    /// The window of the workspace is about to close, its items having agreed to it.
+    Closing,
 }
 
 pub enum OpenVisible {
@@ -591,6 +612,7 @@ pub struct Workspace {
     database_id: Option<WorkspaceId>,
     app_state: Arc<AppState>,
     dispatching_keystrokes: Rc<RefCell<Vec<Keystroke>>>,
+    keyboard_macros: KeyboardMacros,
     _subscriptions: Vec<Subscription>,
     _apply_leader_updates: Task<Result<()>>,
     _observe_current_user: Task<Result<()>>,
@@ -600,6 +622,9 @@ pub struct Workspace {
     centered_layout: bool,
     bounds_save_task_queued: Option<Task<()>>,
     on_prompt_for_new_path: Option<PromptForNewPath>,
+    /// Whether folders added to the project are checked for trust, which is only the case for
+    /// local workspaces opened by the user.
+    checks_trust: bool,
 }
 
 impl EventEmitter<Event> for Workspace {}
@@ -638,9 +663,15 @@ impl Workspace {
                     this.collaborator_left(*peer_id, cx);
                 }
 
-                project::Event::WorktreeRemoved(_) | project::Event::WorktreeAdded => {
+                project::Event::WorktreeRemoved(_) => {
+                    this.update_window_title(cx);
+                    this.serialize_workspace(cx);
+                }
+
+                project::Event::WorktreeAdded => {
                     this.update_window_title(cx);
                     this.serialize_workspace(cx);
+                    this.check_trust(cx);
                 }
 
                 project::Event::DisconnectedFromHost => {
@@ -842,6 +873,13 @@ impl Workspace {
         cx.defer(|this, cx| {
             this.update_window_title(cx);
         });
+        if let Some(workspace_id) = workspace_id {
+            cx.spawn(|this, mut cx| async move {
+                let macros = DB.keyboard_macros(workspace_id).await?;
+                this.update(&mut cx, |this, _| this.keyboard_macros.restore(macros))
+            })
+            .detach_and_log_err(cx);
+        }
         Workspace {
             weak_self: weak_handle.clone(),
             zoomed: None,
@@ -863,6 +901,7 @@ impl Workspace {
             follower_states: Default::default(),
             last_leaders_by_pane: Default::default(),
             dispatching_keystrokes: Default::default(),
+            keyboard_macros: Default::default(),
             window_edited: false,
             active_call,
             database_id: workspace_id,
@@ -879,6 +918,7 @@ impl Workspace {
             centered_layout: false,
             bounds_save_task_queued: None,
             on_prompt_for_new_path: None,
+            checks_trust: false,
         }
     }
 
@@ -1010,6 +1050,13 @@ impl Workspace {
             };
 
             notify_if_database_failed(window, &mut cx);
+            // Restrict the project before opening any buffers, so that no language servers start.
+            window
+                .update(&mut cx, |workspace, cx| {
+                    workspace.checks_trust = true;
+                    workspace.check_trust(cx);
+                })
+                .log_err();
             let opened_items = window
                 .update(&mut cx, |_workspace, cx| {
                     open_items(serialized_workspace, project_paths, app_state, cx)
@@ -1234,6 +1281,29 @@ impl Workspace {
         self.navigate_history(pane, NavigationMode::GoingForward, cx)
     }
 
+    /// Goes back or forward in the pane's navigation history as many times as the given number
+    /// of steps, such as those of a [`NavigationHistoryEntry`].
+    ///
+    /// [`NavigationHistoryEntry`]: crate::pane::NavigationHistoryEntry
+    pub fn navigate_history_steps(
+        &mut self,
+        pane: WeakView<Pane>,
+        mode: NavigationMode,
+        steps: usize,
+        cx: &mut ViewContext<Workspace>,
+    ) -> Task<Result<()>> {
+        cx.spawn(|workspace, mut cx| async move {
+            for _ in 0..steps {
+                workspace
+                    .update(&mut cx, |workspace, cx| {
+                        workspace.navigate_history(pane.clone(), mode, cx)
+                    })?
+                    .await?;
+            }
+            Ok(())
+        })
+    }
+
     pub fn reopen_closed_item(&mut self, cx: &mut ViewContext<Workspace>) -> Task<Result<()>> {
         self.navigate_history(
             self.active_pane().downgrade(),
@@ -1378,8 +1448,9 @@ impl Workspace {
     pub fn close_window(&mut self, _: &CloseWindow, cx: &mut ViewContext<Self>) {
         let window = cx.window_handle();
         let prepare = self.prepare_to_close(false, cx);
-        cx.spawn(|_, mut cx| async move {
+        cx.spawn(|this, mut cx| async move {
             if prepare.await? {
+                this.update(&mut cx, |_, cx| cx.emit(Event::Closing))?;
                 window.update(&mut cx, |_, cx| {
                     cx.remove_window();
                 })?;
@@ -3592,6 +3663,7 @@ impl Workspace {
                                 item_id: item_handle.item_id().as_u64(),
                                 active: Some(item_handle.item_id()) == active_item_id,
                                 preview: pane.is_active_preview_item(item_handle.item_id()),
+                                pinned: pane.is_pinned(item_handle.item_id()),
                             })
                         })
                         .collect::<Vec<_>>(),
@@ -3825,6 +3897,9 @@ impl Workspace {
             .on_action(cx.listener(Self::close_all_items_and_panes))
             .on_action(cx.listener(Self::save_all))
             .on_action(cx.listener(Self::send_keystrokes))
+            .on_action(cx.listener(Self::start_recording_macro_action))
+            .on_action(cx.listener(Self::stop_recording_macro_action))
+            .on_action(cx.listener(Self::replay_macro_action))
             .on_action(cx.listener(Self::add_folder_to_project))
             .on_action(cx.listener(Self::follow_next_collaborator))
             .on_action(cx.listener(|workspace, _: &Unfollow, cx| {
@@ -3887,6 +3962,7 @@ impl Workspace {
                 }),
             )
             .on_action(cx.listener(Workspace::toggle_centered_layout))
+            .on_action(cx.listener(Workspace::trust_workspace))
     }
 
     #[cfg(any(test, feature = "test-support"))]
@@ -3966,6 +4042,71 @@ impl Workspace {
         cx.notify();
     }
 
+    /// The root folders of the project that haven't been trusted yet.
+    fn untrusted_worktree_paths(&self, cx: &AppContext) -> Vec<PathBuf> {
+        let project = self.project.read(cx);
+        if !project.is_local() {
+            return Vec::new();
+        }
+        project
+            .visible_worktrees(cx)
+            .map(|worktree| worktree.read(cx).abs_path().to_path_buf())
+            .filter(|path| !trust::is_trusted(path))
+            .collect()
+    }
+
+    /// Puts the project in restricted mode if any of its folders haven't been trusted yet,
+    /// asking the user whether to trust them.
+    fn check_trust(&mut self, cx: &mut ViewContext<Self>) {
+        if !self.checks_trust || !WorkspaceSettings::get_global(cx).workspace_trust {
+            return;
+        }
+        let untrusted_paths = self.untrusted_worktree_paths(cx);
+        if untrusted_paths.is_empty() {
+            return;
+        }
+        self.project
+            .update(cx, |project, cx| project.set_restricted(true, cx));
+
+        let detail = format!(
+            "{}\n\nUntil you trust them, no tasks, language servers or external formatters will \
+             run, to keep their code from being executed.",
+            untrusted_paths
+                .iter()
+                .map(|path| path.to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        let answer = cx.prompt(
+            PromptLevel::Warning,
+            "Do you trust the authors of the files in these folders?",
+            Some(&detail),
+            &["Trust", "Stay in Restricted Mode"],
+        );
+        cx.spawn(|this, mut cx| async move {
+            if answer.await.log_err() == Some(0) {
+                this.update(&mut cx, |this, cx| {
+                    this.trust_workspace(&TrustWorkspace, cx);
+                })?;
+            }
+            anyhow::Ok(())
+        })
+        .detach_and_log_err(cx);
+    }
+
+    /// Trusts the folders of the project, lifting its restricted mode.
+    pub fn trust_workspace(&mut self, _: &TrustWorkspace, cx: &mut ViewContext<Self>) {
+        let untrusted_paths = self.untrusted_worktree_paths(cx);
+        let project = self.project.clone();
+        cx.spawn(|_, mut cx| async move {
+            if !untrusted_paths.is_empty() {
+                trust::trust_paths(untrusted_paths).await?;
+            }
+            project.update(&mut cx, |project, cx| project.set_restricted(false, cx))
+        })
+        .detach_and_prompt_err("Failed to trust the workspace", cx, |_, _| None);
+    }
+
     fn adjust_padding(padding: Option<f32>) -> f32 {
         padding
             .unwrap_or(Self::DEFAULT_PADDING)
@@ -5285,6 +5426,107 @@ mod tests {
         item3.update(cx, |item, _| assert_eq!(item.tab_detail.get(), Some(3)));
     }
 
+    #[gpui::test]
+    async fn test_workspace_trust(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        fs.insert_tree("/untrusted-root", json!({ "a.txt": "" }))
+            .await;
+        let project = Project::test(fs, ["/untrusted-root".as_ref()], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+
+        // Workspaces not opened by the user aren't checked.
+        workspace.update(cx, |workspace, cx| workspace.check_trust(cx));
+        assert!(!cx.has_pending_prompt());
+        project.update(cx, |project, _| assert!(!project.is_restricted()));
+
+        workspace.update(cx, |workspace, cx| {
+            workspace.checks_trust = true;
+            workspace.check_trust(cx);
+        });
+        project.update(cx, |project, _| assert!(project.is_restricted()));
+
+        // Staying in restricted mode.
+        cx.simulate_prompt_answer(1);
+        cx.run_until_parked();
+        project.update(cx, |project, _| assert!(project.is_restricted()));
+        assert!(!trust::is_trusted(Path::new("/untrusted-root/a.txt")));
+
+        workspace.update(cx, |workspace, cx| workspace.check_trust(cx));
+        cx.simulate_prompt_answer(0);
+        cx.run_until_parked();
+        project.update(cx, |project, _| assert!(!project.is_restricted()));
+        assert!(trust::is_trusted(Path::new("/untrusted-root/a.txt")));
+
+        // Trusted folders aren't asked about again.
+        workspace.update(cx, |workspace, cx| workspace.check_trust(cx));
+        assert!(!cx.has_pending_prompt());
+        project.update(cx, |project, _| assert!(!project.is_restricted()));
+    }
+
+    #[gpui::test]
+    async fn test_save_and_restore_layout(cx: &mut TestAppContext) {
+        init_test(cx);
+
+        let fs = FakeFs::new(cx.executor());
+        let project = Project::test(fs, [], cx).await;
+        let (workspace, cx) = cx.add_window_view(|cx| Workspace::test_new(project.clone(), cx));
+        let workspace_id = DB.next_id().await.unwrap();
+        let item = cx.new_view(|cx| TestItem::new(cx));
+        workspace.update(cx, |workspace, cx| {
+            workspace.database_id = Some(workspace_id);
+            let panel = cx.new_view(|cx| TestPanel::new(DockPosition::Left, cx));
+            workspace.add_panel(panel, cx);
+            workspace.toggle_dock(DockPosition::Left, cx);
+            workspace.add_item_to_active_pane(Box::new(item.clone()), None, cx);
+            workspace.split_pane(workspace.active_pane().clone(), SplitDirection::Right, cx);
+        });
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.save_layout("review".into(), cx)
+            })
+            .await
+            .unwrap();
+        workspace.update(cx, |workspace, cx| {
+            assert_eq!(workspace.layout_names(), ["review"]);
+
+            workspace.toggle_dock(DockPosition::Left, cx);
+            workspace.split_pane(workspace.active_pane().clone(), SplitDirection::Down, cx);
+            assert_eq!(workspace.center.panes().len(), 3);
+        });
+
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.restore_layout("review".into(), cx)
+            })
+            .await
+            .unwrap();
+        workspace.update(cx, |workspace, cx| {
+            assert_eq!(workspace.center.panes().len(), 2);
+            assert_eq!(workspace.panes().len(), 2);
+            assert!(workspace.left_dock().read(cx).is_open());
+            // The item without a file is kept rather than closed.
+            let items = workspace
+                .center
+                .panes()
+                .iter()
+                .flat_map(|pane| pane.read(cx).items().map(|item| item.item_id()))
+                .collect::<Vec<_>>();
+            assert_eq!(items, [item.item_id()]);
+        });
+
+        workspace
+            .update(cx, |workspace, cx| {
+                workspace.delete_layout("review".into(), cx)
+            })
+            .await
+            .unwrap();
+        workspace.update(cx, |workspace, _| {
+            assert!(workspace.layout_names().is_empty())
+        });
+    }
+
     #[gpui::test]
     async fn test_tracking_active_path(cx: &mut TestAppContext) {
         init_test(cx);
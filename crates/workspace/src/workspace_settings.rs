@@ -14,6 +14,8 @@ pub struct WorkspaceSettings {
     pub restore_on_startup: RestoreOnStartupBehaviour,
     pub drop_target_size: f32,
     pub when_closing_with_no_tabs: CloseWindowWhenNoItems,
+    pub clipboard_history_size: usize,
+    pub workspace_trust: bool,
 }
 
 #[derive(Copy, Clone, Default, Serialize, Deserialize, JsonSchema)]
@@ -83,6 +85,16 @@ pub struct WorkspaceSettingsContent {
     ///
     /// Default: auto ("on" on macOS, "off" otherwise)
     pub when_closing_with_no_tabs: Option<CloseWindowWhenNoItems>,
+    /// How many of the items most recently copied from the editors and terminals
+    /// to keep for pasting from the clipboard history.
+    ///
+    /// Default: 50
+    pub clipboard_history_size: Option<usize>,
+    /// Whether to ask before trusting folders that weren't trusted before, keeping them in
+    /// restricted mode until then: without tasks, language servers or external formatters.
+    ///
+    /// Default: true
+    pub workspace_trust: Option<bool>,
 }
 
 #[derive(Deserialize)]
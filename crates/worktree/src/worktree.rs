@@ -298,8 +298,8 @@ struct BackgroundScannerState {
 pub struct LocalRepositoryEntry {
     pub(crate) git_dir_scan_id: usize,
     pub(crate) repo_ptr: Arc<dyn GitRepository>,
-    /// Path to the actual .git folder.
-    /// Note: if .git is a file, this points to the folder indicated by the .git file
+    /// Path to the repository's `.git`. For submodules and linked worktrees, this is a file
+    /// pointing to the actual git directory, which [`GitRepository::path`] returns.
     pub(crate) git_dir_path: Arc<Path>,
 }
 
@@ -954,7 +954,8 @@ impl LocalWorktree {
                                 if abs_path_metadata.is_dir || abs_path_metadata.is_symlink {
                                     None
                                 } else {
-                                    git_repo.load_index_text(&repo_path)
+                                    let diff_base = git_repo.resolve_diff_base().log_err()?;
+                                    git_repo.load_diff_base_text(&diff_base, &repo_path)
                                 }
                             }
                         }));
@@ -2051,7 +2052,8 @@ impl LocalSnapshot {
                     new_ignores.push((ancestor, None));
                 }
             }
-            if ancestor.join(&*DOT_GIT).is_dir() {
+            // Submodules and linked worktrees have a `.git` file instead of a directory.
+            if ancestor.join(&*DOT_GIT).exists() {
                 break;
             }
         }
@@ -2993,25 +2995,47 @@ impl BackgroundScanner {
             }
 
             let ancestor_dot_git = ancestor.join(&*DOT_GIT);
-            if ancestor_dot_git.is_dir() {
+            // Submodules and linked worktrees have a `.git` file instead of a directory.
+            if ancestor_dot_git.exists() {
                 if index != 0 {
                     // We canonicalize, since the FS events use the canonicalized path.
                     if let Some(ancestor_dot_git) =
                         self.fs.canonicalize(&ancestor_dot_git).await.log_err()
                     {
-                        let (ancestor_git_events, _) =
-                            self.fs.watch(&ancestor_dot_git, FS_WATCH_LATENCY).await;
-                        fs_events_rx = select(fs_events_rx, ancestor_git_events).boxed();
-
                         // We associate the external git repo with our root folder and
                         // also mark where in the git repo the root folder is located.
-                        self.state.lock().build_git_repository_for_path(
+                        let repository = self.state.lock().build_git_repository_for_path(
                             Path::new("").into(),
-                            ancestor_dot_git.into(),
+                            ancestor_dot_git.clone().into(),
                             Some(root_abs_path.strip_prefix(ancestor).unwrap().into()),
                             self.fs.as_ref(),
                         );
+
+                        // Watch the git directory, which a `.git` file points to.
+                        let git_dir = match repository {
+                            Some((_, repository)) => self
+                                .fs
+                                .canonicalize(&repository.path())
+                                .await
+                                .unwrap_or(ancestor_dot_git),
+                            None => ancestor_dot_git,
+                        };
+                        let (ancestor_git_events, _) =
+                            self.fs.watch(&git_dir, FS_WATCH_LATENCY).await;
+                        fs_events_rx = select(fs_events_rx, ancestor_git_events).boxed();
                     };
+                } else if ancestor_dot_git.is_file() {
+                    // The git directory of a linked worktree or a submodule lives outside of it,
+                    // in the `.git` of the repository it belongs to, so it needs a watch of its
+                    // own for changes to the index and HEAD.
+                    if let Some(repository) = self.fs.open_repo(&ancestor_dot_git) {
+                        if let Some(git_dir) =
+                            self.fs.canonicalize(&repository.path()).await.log_err()
+                        {
+                            let (git_events, _) = self.fs.watch(&git_dir, FS_WATCH_LATENCY).await;
+                            fs_events_rx = select(fs_events_rx, git_events).boxed();
+                        }
+                    }
                 }
 
                 // Reached root of git repository.
@@ -3165,14 +3189,36 @@ impl BackgroundScanner {
             let snapshot = &self.state.lock().snapshot;
             {
                 let mut is_git_related = false;
-                if let Some(dot_git_dir) = abs_path
-                    .ancestors()
-                    .find(|ancestor| ancestor.file_name() == Some(*DOT_GIT))
-                {
-                    let dot_git_path = dot_git_dir
-                        .strip_prefix(&root_canonical_path)
-                        .unwrap_or(dot_git_dir)
-                        .to_path_buf();
+                // Submodules keep their git directories in their superproject's `.git`, so
+                // changes are attributed to the innermost repository whose git directory
+                // contains them, rather than to the `.git` they are in.
+                let dot_git_path = snapshot
+                    .git_repositories
+                    .values()
+                    .filter_map(|repo| {
+                        let git_dir = repo.repo_ptr.path();
+                        let git_dir = match git_dir.strip_prefix(&root_path) {
+                            Ok(path) => root_canonical_path.join(path),
+                            Err(_) => git_dir,
+                        };
+                        abs_path
+                            .starts_with(&git_dir)
+                            .then(|| (git_dir.components().count(), &repo.git_dir_path))
+                    })
+                    .max_by_key(|(depth, _)| *depth)
+                    .map(|(_, dot_git_path)| dot_git_path.to_path_buf())
+                    .or_else(|| {
+                        let dot_git_dir = abs_path
+                            .ancestors()
+                            .find(|ancestor| ancestor.file_name() == Some(*DOT_GIT))?;
+                        Some(
+                            dot_git_dir
+                                .strip_prefix(&root_canonical_path)
+                                .unwrap_or(dot_git_dir)
+                                .to_path_buf(),
+                        )
+                    });
+                if let Some(dot_git_path) = dot_git_path {
                     if !dot_git_paths.contains(&dot_git_path) {
                         dot_git_paths.push(dot_git_path);
                     }
@@ -4036,10 +4082,28 @@ impl BackgroundScanner {
         let t0 = Instant::now();
         let mut changes = Vec::new();
         let snapshot = self.state.lock().snapshot.snapshot.clone();
+        // Files of repositories nested in this one's working directory have statuses of their
+        // own, while the directories of those repositories keep the status this one reports.
+        let nested_work_directories = snapshot
+            .repositories()
+            .map(|(work_directory, _)| work_directory)
+            .filter(|work_directory| {
+                **work_directory != job.work_directory.0
+                    && work_directory.starts_with(&job.work_directory.0)
+            })
+            .cloned()
+            .collect::<Vec<_>>();
         for file in snapshot.traverse_from_path(true, false, false, job.work_directory.0.as_ref()) {
             let Ok(repo_path) = file.path.strip_prefix(&job.work_directory.0) else {
                 break;
             };
+            if (repo_path == Path::new("") && job.location_in_repo.is_none())
+                || nested_work_directories.iter().any(|work_directory| {
+                    file.path != *work_directory && file.path.starts_with(work_directory)
+                })
+            {
+                continue;
+            }
             let git_status = if let Some(location) = &job.location_in_repo {
                 statuses.get(&location.join(repo_path))
             } else {
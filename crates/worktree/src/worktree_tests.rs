@@ -2305,6 +2305,164 @@ async fn test_repository_subfolder_git_status(cx: &mut TestAppContext) {
     });
 }
 
+#[gpui::test]
+async fn test_nested_repository_git_status(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let root = temp_tree(json!({
+        "project": {
+            "a.txt": "a",
+            "nested": {
+                "b.txt": "b",
+                "c.txt": "c",
+            },
+        },
+    }));
+
+    let project_path = root.path().join("project");
+    let repo = git_init(&project_path);
+    git_add("a.txt", &repo);
+    git_commit("Initial commit", &repo);
+    let nested_repo = git_init(&project_path.join("nested"));
+    git_add("b.txt", &nested_repo);
+    git_commit("Initial commit", &nested_repo);
+
+    let tree = Worktree::local(
+        project_path.clone(),
+        true,
+        Arc::new(RealFs::default()),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+
+    tree.flush_fs_events(cx).await;
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+    cx.executor().run_until_parked();
+
+    tree.read_with(cx, |tree, _cx| {
+        let snapshot = tree.snapshot();
+        let work_directories = snapshot
+            .repositories()
+            .map(|(work_directory, _)| work_directory.as_ref())
+            .collect::<Vec<_>>();
+        assert_eq!(work_directories, [Path::new(""), Path::new("nested")]);
+
+        assert_eq!(snapshot.status_for_file("a.txt"), None);
+        assert_eq!(snapshot.status_for_file("nested/b.txt"), None);
+        assert_eq!(
+            snapshot.status_for_file("nested/c.txt"),
+            Some(GitFileStatus::Added)
+        );
+    });
+
+    // Reloading the outer repository's statuses leaves the nested one's alone.
+    std::fs::write(project_path.join("a.txt"), "aa").unwrap();
+    std::fs::write(project_path.join("nested/b.txt"), "bb").unwrap();
+    git_add("a.txt", &repo);
+    tree.flush_fs_events(cx).await;
+    cx.executor().run_until_parked();
+
+    tree.read_with(cx, |tree, _cx| {
+        let snapshot = tree.snapshot();
+        assert_eq!(
+            snapshot.status_for_file("a.txt"),
+            Some(GitFileStatus::Modified)
+        );
+        assert_eq!(
+            snapshot.status_for_file("nested/b.txt"),
+            Some(GitFileStatus::Modified)
+        );
+        assert_eq!(
+            snapshot.status_for_file("nested/c.txt"),
+            Some(GitFileStatus::Added)
+        );
+    });
+
+    // Committing in the nested repository only updates its own files.
+    git_add("b.txt", &nested_repo);
+    git_add("c.txt", &nested_repo);
+    git_commit("Update the nested files", &nested_repo);
+    tree.flush_fs_events(cx).await;
+    cx.executor().run_until_parked();
+
+    tree.read_with(cx, |tree, _cx| {
+        let snapshot = tree.snapshot();
+        assert_eq!(
+            snapshot.status_for_file("a.txt"),
+            Some(GitFileStatus::Modified)
+        );
+        assert_eq!(snapshot.status_for_file("nested/b.txt"), None);
+        assert_eq!(snapshot.status_for_file("nested/c.txt"), None);
+    });
+}
+
+#[gpui::test]
+async fn test_linked_worktree_git_status(cx: &mut TestAppContext) {
+    init_test(cx);
+    cx.executor().allow_parking();
+
+    let root = temp_tree(json!({
+        "main": {
+            "a.txt": "a",
+        },
+    }));
+
+    let main_path = root.path().join("main");
+    let repo = git_init(&main_path);
+    git_add("a.txt", &repo);
+    git_commit("Initial commit", &repo);
+
+    // A linked worktree has a `.git` file pointing at a directory in the main one's `.git`.
+    let linked_path = root.path().join("linked");
+    repo.worktree("linked", &linked_path, None).unwrap();
+    assert!(linked_path.join(".git").is_file());
+    std::fs::write(linked_path.join("b.txt"), "b").unwrap();
+
+    let tree = Worktree::local(
+        linked_path.clone(),
+        true,
+        Arc::new(RealFs::default()),
+        Default::default(),
+        &mut cx.to_async(),
+    )
+    .await
+    .unwrap();
+
+    tree.flush_fs_events(cx).await;
+    cx.read(|cx| tree.read(cx).as_local().unwrap().scan_complete())
+        .await;
+    cx.executor().run_until_parked();
+
+    tree.read_with(cx, |tree, _cx| {
+        let snapshot = tree.snapshot();
+        let (work_directory, repo_entry) = snapshot.repositories().next().unwrap();
+        assert_eq!(work_directory.as_ref(), Path::new(""));
+        assert_eq!(repo_entry.branch().as_deref(), Some("linked"));
+
+        assert_eq!(snapshot.status_for_file("a.txt"), None);
+        assert_eq!(
+            snapshot.status_for_file("b.txt"),
+            Some(GitFileStatus::Added)
+        );
+    });
+
+    // Staging in the linked worktree changes its index, which lives outside of it.
+    let linked_repo = git2::Repository::open(&linked_path).unwrap();
+    git_add("b.txt", &linked_repo);
+    git_commit("Add b", &linked_repo);
+    tree.flush_fs_events(cx).await;
+    cx.executor().run_until_parked();
+
+    tree.read_with(cx, |tree, _cx| {
+        let snapshot = tree.snapshot();
+        assert_eq!(snapshot.status_for_file("b.txt"), None);
+    });
+}
+
 #[gpui::test]
 async fn test_propagate_git_statuses(cx: &mut TestAppContext) {
     init_test(cx);
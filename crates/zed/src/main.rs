@@ -144,6 +144,14 @@ fn init_ui(app_state: Arc<AppState>, cx: &mut AppContext) -> Result<()> {
     file_finder::init(cx);
     tab_switcher::init(cx);
     outline::init(cx);
+    clipboard_history::init(cx);
+    bookmarks::init(cx);
+    git_ui::init(cx);
+    call_hierarchy::init(cx);
+    debugger_ui::init(cx);
+    undo_history::init(cx);
+    timeline::init(cx);
+    layout_selector::init(cx);
     project_symbols::init(cx);
     project_panel::init(Assets, cx);
     tasks_ui::init(cx);
@@ -332,13 +340,13 @@ fn main() {
         }
 
         GitHostingProviderRegistry::set_global(git_hosting_provider_registry, cx);
-        git_hosting_providers::init(cx);
 
         OpenListener::set_global(cx, open_listener.clone());
 
         settings::init(cx);
         handle_settings_file_changes(user_settings_file_rx, cx);
         handle_keymap_file_changes(user_keymap_file_rx, cx);
+        git_hosting_providers::init(cx);
 
         client::init_settings(cx);
         let client = Client::production(cx);
@@ -18,15 +18,15 @@ pub use open_listener::*;
 use anyhow::Context as _;
 use assets::Assets;
 use futures::{channel::mpsc, select_biased, StreamExt};
-use project::TaskSourceKind;
+use project::{Project, TaskSourceKind, WorktreeId};
 use project_panel::ProjectPanel;
 use quick_action_bar::QuickActionBar;
-use release_channel::{AppCommitSha, ReleaseChannel};
+use release_channel::{AppCommitSha, ReleaseChannel, RELEASE_CHANNEL};
 use rope::Rope;
 use search::project_search::ProjectSearchBar;
 use settings::{
     initial_local_settings_content, initial_tasks_content, watch_config_file, KeymapFile, Settings,
-    SettingsStore, DEFAULT_KEYMAP_PATH,
+    SettingsFile, SettingsStore, DEFAULT_KEYMAP_PATH,
 };
 use std::{borrow::Cow, ops::Deref, path::Path, sync::Arc};
 use task::static_source::{StaticSource, TrackedFile};
@@ -36,11 +36,11 @@ use workspace::notifications::NotificationId;
 use terminal_view::terminal_panel::{self, TerminalPanel};
 use util::{
     asset_str,
-    paths::{self, LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH},
+    paths::{self, PathExt, LOCAL_SETTINGS_RELATIVE_PATH, LOCAL_TASKS_RELATIVE_PATH},
     ResultExt,
 };
 use uuid::Uuid;
-use vim::VimModeSetting;
+use vim::{VimMappingSettings, VimModeSetting};
 use welcome::BaseKeymap;
 use workspace::{
     create_and_open_local_file, notifications::simple_message_notification::MessageNotification,
@@ -61,6 +61,7 @@ actions!(
         Minimize,
         OpenDefaultKeymap,
         OpenDefaultSettings,
+        OpenEffectiveSettings,
         OpenKeymap,
         OpenLicenses,
         OpenLocalSettings,
@@ -141,14 +142,18 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
         let active_buffer_language =
             cx.new_view(|_| language_selector::ActiveBufferLanguage::new(workspace));
         let vim_mode_indicator = cx.new_view(|cx| vim::ModeIndicator::new(cx));
+        let linked_editing_indicator = cx.new_view(|_| editor::LinkedEditingIndicator::new());
         let cursor_position =
             cx.new_view(|_| go_to_line::cursor_position::CursorPosition::new(workspace));
+        let diff_base_indicator = cx.new_view(|cx| git_ui::DiffBaseIndicator::new(workspace, cx));
         workspace.status_bar().update(cx, |status_bar, cx| {
             status_bar.add_left_item(diagnostic_summary, cx);
             status_bar.add_left_item(activity_indicator, cx);
+            status_bar.add_right_item(diff_base_indicator, cx);
             status_bar.add_right_item(inline_completion_button, cx);
             status_bar.add_right_item(active_buffer_language, cx);
             status_bar.add_right_item(vim_mode_indicator, cx);
+            status_bar.add_right_item(linked_editing_indicator, cx);
             status_bar.add_right_item(cursor_position, cx);
         });
 
@@ -199,6 +204,14 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 workspace_handle.clone(),
                 cx.clone(),
             );
+            let bookmarks_panel =
+                bookmarks::BookmarksPanel::load(workspace_handle.clone(), cx.clone());
+            let call_hierarchy_panel =
+                call_hierarchy::CallHierarchyPanel::load(workspace_handle.clone(), cx.clone());
+            let git_panel = git_ui::GitPanel::load(workspace_handle.clone(), cx.clone());
+            let debug_panel = debugger_ui::DebugPanel::load(workspace_handle.clone(), cx.clone());
+            let debug_console =
+                debugger_ui::DebugConsole::load(workspace_handle.clone(), cx.clone());
 
             let (
                 project_panel,
@@ -207,6 +220,11 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 channels_panel,
                 chat_panel,
                 notification_panel,
+                bookmarks_panel,
+                call_hierarchy_panel,
+                git_panel,
+                debug_panel,
+                debug_console,
             ) = futures::try_join!(
                 project_panel,
                 terminal_panel,
@@ -214,6 +232,11 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 channels_panel,
                 chat_panel,
                 notification_panel,
+                bookmarks_panel,
+                call_hierarchy_panel,
+                git_panel,
+                debug_panel,
+                debug_console,
             )?;
 
             workspace_handle.update(&mut cx, |workspace, cx| {
@@ -223,6 +246,11 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 workspace.add_panel(channels_panel, cx);
                 workspace.add_panel(chat_panel, cx);
                 workspace.add_panel(notification_panel, cx);
+                workspace.add_panel(bookmarks_panel, cx);
+                workspace.add_panel(call_hierarchy_panel, cx);
+                workspace.add_panel(git_panel, cx);
+                workspace.add_panel(debug_panel, cx);
+                workspace.add_panel(debug_console, cx);
                 cx.focus_self();
             })
         })
@@ -343,6 +371,7 @@ pub fn initialize_workspace(app_state: Arc<AppState>, cx: &mut AppContext) {
                 },
             )
             .register_action(open_local_settings_file)
+            .register_action(open_effective_settings)
             .register_action(open_local_tasks_file)
             .register_action(
                 move |workspace: &mut Workspace,
@@ -454,6 +483,8 @@ fn initialize_pane(workspace: &mut Workspace, pane: &View<Pane>, cx: &mut ViewCo
             let syntax_tree_item =
                 cx.new_view(|_| language_tools::SyntaxTreeToolbarItemView::new());
             toolbar.add_item(syntax_tree_item, cx);
+            let terminal_task_controls = cx.new_view(|_| terminal_view::ToolbarControls::new());
+            toolbar.add_item(terminal_task_controls, cx);
         })
     });
 }
@@ -614,17 +645,24 @@ pub fn handle_keymap_file_changes(
 ) {
     BaseKeymap::register(cx);
     VimModeSetting::register(cx);
+    VimMappingSettings::register(cx);
 
     let (base_keymap_tx, mut base_keymap_rx) = mpsc::unbounded();
     let mut old_base_keymap = *BaseKeymap::get_global(cx);
     let mut old_vim_enabled = VimModeSetting::get_global(cx).0;
+    let mut old_vim_mappings = VimMappingSettings::get_global(cx).clone();
     cx.observe_global::<SettingsStore>(move |cx| {
         let new_base_keymap = *BaseKeymap::get_global(cx);
         let new_vim_enabled = VimModeSetting::get_global(cx).0;
+        let new_vim_mappings = VimMappingSettings::get_global(cx);
 
-        if new_base_keymap != old_base_keymap || new_vim_enabled != old_vim_enabled {
+        if new_base_keymap != old_base_keymap
+            || new_vim_enabled != old_vim_enabled
+            || *new_vim_mappings != old_vim_mappings
+        {
             old_base_keymap = new_base_keymap;
             old_vim_enabled = new_vim_enabled;
+            old_vim_mappings = new_vim_mappings.clone();
             base_keymap_tx.unbounded_send(()).unwrap();
         }
     })
@@ -656,7 +694,15 @@ pub fn handle_keymap_file_changes(
 fn reload_keymaps(cx: &mut AppContext, keymap_content: &KeymapFile) {
     cx.clear_key_bindings();
     load_default_keymap(cx);
-    keymap_content.clone().add_to_cx(cx).log_err();
+    let mut keymap_content = keymap_content.clone();
+    if VimModeSetting::get_global(cx).0 {
+        let vim_mappings = VimMappingSettings::get_global(cx).clone();
+        if let Some(leader) = vim_mappings.leader_keystroke().log_err() {
+            keymap_content = keymap_content.with_leader(&leader);
+        }
+        vim_mappings.bind_keys(cx).log_err();
+    }
+    keymap_content.add_to_cx(cx).log_err();
     cx.set_menus(app_menus());
     cx.set_dock_menu(vec![MenuItem::action("New Window", workspace::NewWindow)])
 }
@@ -690,6 +736,60 @@ fn open_local_settings_file(
     )
 }
 
+/// Lists the value of every setting that applies to the active item's file, each along with the
+/// settings file it was read from.
+fn open_effective_settings(
+    workspace: &mut Workspace,
+    _: &OpenEffectiveSettings,
+    cx: &mut ViewContext<Workspace>,
+) {
+    let project_path = workspace
+        .active_item(cx)
+        .and_then(|item| item.project_path(cx));
+    let sources = SettingsStore::global(cx).value_sources(project_path.as_ref().map(Into::into));
+
+    let project = workspace.project().read(cx);
+    let mut text = match &project_path {
+        Some(project_path) => format!(
+            "// The settings in effect for `{}`.\n",
+            project_path.path.to_string_lossy()
+        ),
+        None => "// The settings in effect outside of the project's folders.\n".to_string(),
+    };
+    text.push_str("{\n");
+    for (key_path, (value, file)) in sources {
+        text.push_str(&format!(
+            "  \"{key_path}\": {value}, // settings from: {}\n",
+            settings_file_label(&file, project, cx)
+        ));
+    }
+    text.push_str("}\n");
+
+    open_bundled_file(workspace, text.into(), "Effective Settings", "JSON", cx);
+}
+
+fn settings_file_label(file: &SettingsFile, project: &Project, cx: &AppContext) -> String {
+    match file {
+        SettingsFile::Default => "default settings".to_string(),
+        SettingsFile::Extensions => "extensions".to_string(),
+        SettingsFile::User => paths::SETTINGS.compact().to_string_lossy().into_owned(),
+        SettingsFile::ReleaseChannel => format!(
+            "{} (\"{}\" section)",
+            paths::SETTINGS.compact().to_string_lossy(),
+            RELEASE_CHANNEL.dev_name()
+        ),
+        SettingsFile::Local { worktree_id, path } => {
+            let mut label = project
+                .worktree_for_id(WorktreeId::from_usize(*worktree_id), cx)
+                .map(|worktree| Path::new(worktree.read(cx).root_name()).to_path_buf())
+                .unwrap_or_default();
+            label.push(path);
+            label.push(&*LOCAL_SETTINGS_RELATIVE_PATH);
+            label.to_string_lossy().into_owned()
+        }
+    }
+}
+
 fn open_local_tasks_file(
     workspace: &mut Workspace,
     _: &OpenLocalTasks,
@@ -0,0 +1,6 @@
+; Keep the names of an element's start and end tags in sync
+(element
+    (start_tag
+        (tag_name) @linked)
+    (end_tag
+        (tag_name) @linked))